@@ -30,4 +30,8 @@ fn main() {
     if let Err(e) = emitter.emit() {
         eprintln!("cargo:warning=vergen emit failed: {e}");
     }
+
+    // Node addon linker flags (e.g. `-undefined dynamic_lookup` on macOS).
+    #[cfg(feature = "napi")]
+    napi_build::setup();
 }
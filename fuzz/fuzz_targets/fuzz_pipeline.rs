@@ -0,0 +1,78 @@
+//! Fuzz target for the hook's pre-evaluation pipeline.
+//!
+//! Feeds each input through the same steps the hook runs before pattern
+//! matching: hook JSON parsing and command extraction, wrapper stripping and
+//! normalization, compound command splitting, argument context extraction,
+//! and allowlist glob matching. It tests for:
+//! - Panics anywhere in the pipeline (a panic means fail-open)
+//! - Token ranges that are out of bounds or split a UTF-8 character
+//! - Hangs (run with `-timeout=` to catch them)
+//!
+//! Seed it with `dcg fuzz-corpus export` (writes `fuzz/corpus/fuzz_pipeline`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use destructive_command_guard::allowlist::{path_matches_glob, validate_glob_pattern};
+use destructive_command_guard::arg_context::{arg_contexts, command_tool};
+use destructive_command_guard::hook::{HookInput, extract_command};
+use destructive_command_guard::normalize::{
+    NormalizeTokenKind, dequote_segment_command_words, strip_wrapper_prefixes,
+    tokenize_for_normalization,
+};
+use destructive_command_guard::packs::normalize_command;
+
+fuzz_target!(|data: &[u8]| {
+    // Skip extremely large inputs to avoid timeout (not a real bug)
+    if data.len() > 10_000 {
+        return;
+    }
+
+    // Hook envelopes go through JSON parsing first; anything else is treated
+    // as a raw command string.
+    let command = match serde_json::from_slice::<HookInput>(data) {
+        Ok(input) => match extract_command(&input) {
+            Some(command) => command,
+            None => return,
+        },
+        Err(_) => String::from_utf8_lossy(data).into_owned(),
+    };
+
+    let stripped = strip_wrapper_prefixes(&command);
+    let normalized = normalize_command(&stripped.normalized);
+    let _ = dequote_segment_command_words(&normalized);
+
+    // Split into simple commands the way argument extraction does.
+    let mut segments: Vec<Vec<&str>> = vec![Vec::new()];
+    for token in &tokenize_for_normalization(&command) {
+        assert!(
+            token.byte_range.start <= token.byte_range.end
+                && token.byte_range.end <= command.len(),
+            "token range {:?} out of bounds for {:?}",
+            token.byte_range,
+            command
+        );
+        let text = token
+            .text(&command)
+            .expect("token range must fall on char boundaries");
+        if token.kind == NormalizeTokenKind::Separator {
+            segments.push(Vec::new());
+        } else if let Some(segment) = segments.last_mut() {
+            segment.push(text);
+        }
+    }
+
+    let _ = arg_contexts(&command);
+    for segment in &segments {
+        let segment = segment.join(" ");
+        let _ = command_tool(&segment);
+
+        // Treat the first word as a glob and the rest as a path; glob
+        // expansion (braces, `**`, negation) must not panic or blow up.
+        if let Some((pattern, path)) = segment.split_once(' ') {
+            let _ = validate_glob_pattern(pattern);
+            let _ = path_matches_glob(pattern, path);
+        }
+    }
+});
@@ -0,0 +1,48 @@
+// Decision documents mirror the dcg CLI JSON output; see
+// docs/json-schema/test-output.json in the dcg repository.
+
+export interface EvaluateOptions {
+  /** Working directory the command would run in. */
+  cwd?: string;
+  /** `[agents.<name>]` profile to apply. */
+  profile?: string;
+}
+
+export interface MatchInfo {
+  rule_id?: string;
+  pack_id?: string;
+  pattern_name?: string;
+  reason: string;
+  severity?: 'critical' | 'high' | 'medium' | 'low';
+  matched_span?: [number, number];
+}
+
+/** Same document as `dcg test --format json`. */
+export interface TestOutput {
+  schema_version: number;
+  dcg_version: string;
+  robot_mode: boolean;
+  command: string;
+  provenance: 'agent' | 'human';
+  decision: 'allow' | 'deny';
+  rule_id?: string;
+  pack_id?: string;
+  pattern_name?: string;
+  reason?: string;
+  explanation?: string;
+  source?: 'config_override' | 'legacy_pattern' | 'pack' | 'heredoc_ast';
+  matched_span?: [number, number];
+  severity?: 'critical' | 'high' | 'medium' | 'low';
+  allowlist?: { layer: 'project' | 'user' | 'system'; reason: string };
+  agent?: { detected: string; trust_level: string; detection_method: string };
+  matches?: MatchInfo[];
+}
+
+/** Evaluate `command` against the packs, config, and allowlists dcg loads. */
+export function evaluate(command: string, options?: EvaluateOptions): TestOutput;
+
+/** Same document as `dcg explain --format json`. */
+export function explain(command: string): Record<string, unknown>;
+
+/** Reread config and allowlists on the next call. */
+export function reload(): void;
@@ -0,0 +1,17 @@
+'use strict';
+
+// Prebuilt addons are named dcg.<platform>-<arch>.node (see the `node-addon`
+// job in .github/workflows/dist.yml). A local build can be dropped in as
+// dcg.node: cargo rustc --release --lib --features napi --crate-type cdylib
+const { existsSync } = require('fs');
+const { join } = require('path');
+
+const candidates = [`dcg.${process.platform}-${process.arch}.node`, 'dcg.node'];
+const file = candidates.map((name) => join(__dirname, name)).find(existsSync);
+if (!file) {
+  throw new Error(
+    `destructive-command-guard: no prebuilt addon for ${process.platform}-${process.arch}`,
+  );
+}
+
+module.exports = require(file);
@@ -0,0 +1,940 @@
+//! Admin HTTP API for `dcg serve`.
+//!
+//! A small JSON API on a loopback address so dashboards and chat bots can
+//! query dcg without shelling out to the CLI for each request:
+//!
+//! | Method   | Path                       | Action                                   |
+//! |----------|----------------------------|------------------------------------------|
+//! | `GET`    | `/v1/health`               | Liveness (no token required)             |
+//! | `GET`    | `/v1/decisions`            | Recent decisions from the history DB     |
+//! | `GET`    | `/v1/packs`                | Packs with enabled state                 |
+//! | `GET`    | `/v1/packs/{id}`           | One pack and its rules                   |
+//! | `GET`    | `/v1/allow-once`           | Pending codes and active allow-once      |
+//! | `POST`   | `/v1/allow-once/{code}`    | Grant a pending code                     |
+//! | `DELETE` | `/v1/allow-once/{target}`  | Revoke a code or full-hash prefix        |
+//! | `POST`   | `/v1/reload`               | Re-read config and allowlists            |
+//! | `GET`    | `/v1/approvals/{id}/{decision}` | Confirm an approvals-bridge link    |
+//! | `POST`   | `/v1/approvals/{id}/{decision}` | Approve or reject a queued request  |
+//!
+//! Every other endpoint requires `Authorization: Bearer <token>` matching
+//! `DCG_ADMIN_TOKEN`; approval links carry a signature instead (see
+//! [`crate::approval_bridge`]) and answer with HTML for a browser. The server
+//! only binds loopback addresses and handles one connection at a time; it
+//! frames HTTP/1.1 itself instead of pulling in a web framework, the same way
+//! `dcg lsp` frames JSON-RPC.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::{Value, json};
+
+use crate::approval_bridge::{Decision, html_escape};
+use crate::approvals::{ApprovalQueueStore, ApprovalStatus};
+use crate::config::Config;
+use crate::history::{ExportOptions, HistoryDb, Outcome};
+use crate::packs::REGISTRY;
+use crate::pending_exceptions::{
+    AllowOnceEntry, AllowOnceGrant, AllowOnceStore, PendingExceptionStore,
+};
+
+/// Environment variable holding the bearer token.
+pub const ENV_ADMIN_TOKEN: &str = "DCG_ADMIN_TOKEN";
+
+/// Default listen address for `dcg serve`.
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8765";
+
+/// Decisions returned when `limit` is not given.
+const DEFAULT_DECISION_LIMIT: usize = 50;
+
+/// Upper bound on `limit` for `/v1/decisions`.
+const MAX_DECISION_LIMIT: usize = 1000;
+
+/// Requests with larger headers or bodies are rejected.
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A parsed HTTP request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub authorization: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    fn query_param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A JSON response, or an HTML page for approval links.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+    pub html: Option<String>,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self {
+            status: 200,
+            body,
+            html: None,
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "status": "error", "error": message.into() }),
+            html: None,
+        }
+    }
+
+    fn page(status: u16, title: &str, content: &str) -> Self {
+        let title = html_escape(title);
+        Self {
+            status,
+            body: Value::Null,
+            html: Some(format!(
+                "<!doctype html>\n<html><head><meta charset=\"utf-8\">\
+                 <meta name=\"robots\" content=\"noindex\"><title>dcg: {title}</title></head>\
+                 <body><h1>{title}</h1>{content}</body></html>\n"
+            )),
+        }
+    }
+}
+
+/// State shared by all requests: the loaded policy, the allow-once stores,
+/// and the approvals queue.
+pub struct AdminApi {
+    token: String,
+    config: Config,
+    pending_store: PendingExceptionStore,
+    allow_once_store: AllowOnceStore,
+    approval_store: ApprovalQueueStore,
+}
+
+impl AdminApi {
+    /// Build the API around `config`, using the default allow-once stores.
+    #[must_use]
+    pub fn new(config: Config, token: String) -> Self {
+        let cwd = std::env::current_dir().ok();
+        Self {
+            token,
+            config,
+            pending_store: PendingExceptionStore::new(PendingExceptionStore::default_path(
+                cwd.as_deref(),
+            )),
+            allow_once_store: AllowOnceStore::new(AllowOnceStore::default_path(cwd.as_deref())),
+            approval_store: ApprovalQueueStore::new(ApprovalQueueStore::default_path(
+                cwd.as_deref(),
+            )),
+        }
+    }
+
+    /// Route one request.
+    pub fn handle(&mut self, request: &Request) -> Response {
+        let segments: Vec<&str> = request
+            .path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if let ["v1", "health"] = segments.as_slice() {
+            return Response::ok(json!({
+                "status": "ok",
+                "version": env!("CARGO_PKG_VERSION"),
+            }));
+        }
+
+        if let ["v1", "approvals", id, decision] = segments.as_slice() {
+            return self.approval_link(request, id, decision);
+        }
+
+        if !self.authorized(request) {
+            return Response::error(401, "missing or invalid bearer token");
+        }
+
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["v1", "decisions"]) => self.decisions(request),
+            ("GET", ["v1", "packs"]) => Ok(self.packs()),
+            ("GET", ["v1", "packs", id]) => Ok(self.pack(id)),
+            ("GET", ["v1", "allow-once"]) => self.allow_once_list(),
+            ("POST", ["v1", "allow-once", code]) => self.allow_once_grant(code, request),
+            ("DELETE", ["v1", "allow-once", target]) => self.allow_once_revoke(target),
+            ("POST", ["v1", "reload"]) => Ok(self.reload()),
+            (_, ["v1", ..]) => Ok(Response::error(404, "no such endpoint")),
+            _ => Ok(Response::error(404, "endpoints live under /v1")),
+        };
+        result.unwrap_or_else(|err| Response::error(500, err.to_string()))
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        request
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), self.token.as_bytes()))
+    }
+
+    fn decisions(&self, request: &Request) -> Result<Response, Box<dyn std::error::Error>> {
+        if !self.config.history.enabled {
+            return Ok(Response::error(
+                503,
+                "history is disabled; set [history] enabled = true",
+            ));
+        }
+
+        let limit = match request.query_param("limit") {
+            None => DEFAULT_DECISION_LIMIT,
+            Some(value) => match value.parse::<usize>() {
+                Ok(limit) => limit.clamp(1, MAX_DECISION_LIMIT),
+                Err(_) => return Ok(Response::error(400, format!("invalid limit '{value}'"))),
+            },
+        };
+        let outcome_filter = match request.query_param("outcome") {
+            None => None,
+            Some(value) => match Outcome::parse(value) {
+                Some(outcome) => Some(outcome),
+                None => return Ok(Response::error(400, format!("invalid outcome '{value}'"))),
+            },
+        };
+
+        let db = HistoryDb::open(self.config.history.expanded_database_path())?;
+        let decisions = db.query_commands_for_export(&ExportOptions {
+            outcome_filter,
+            limit: Some(limit),
+            ..ExportOptions::default()
+        })?;
+        Ok(Response::ok(json!({
+            "status": "ok",
+            "count": decisions.len(),
+            "decisions": decisions,
+        })))
+    }
+
+    fn packs(&self) -> Response {
+        let enabled = self.config.enabled_pack_ids();
+        let packs: Vec<Value> = REGISTRY
+            .list_packs(&enabled)
+            .iter()
+            .map(|info| {
+                json!({
+                    "id": info.id,
+                    "name": info.name,
+                    "description": info.description,
+                    "enabled": info.enabled,
+                    "safe_pattern_count": info.safe_pattern_count,
+                    "destructive_pattern_count": info.destructive_pattern_count,
+                })
+            })
+            .collect();
+        let enabled_count = packs.iter().filter(|p| p["enabled"] == true).count();
+        Response::ok(json!({
+            "status": "ok",
+            "enabled_count": enabled_count,
+            "packs": packs,
+        }))
+    }
+
+    fn pack(&self, id: &str) -> Response {
+        let Some(pack) = REGISTRY.get(id) else {
+            return Response::error(404, format!("unknown pack '{id}'"));
+        };
+        let enabled = REGISTRY
+            .expand_enabled(&self.config.enabled_pack_ids())
+            .contains(id);
+        let rules: Vec<Value> = pack
+            .destructive_patterns
+            .iter()
+            .filter_map(|pattern| {
+                let name = pattern.name?;
+                Some(json!({
+                    "rule_id": format!("{id}:{name}"),
+                    "severity": pattern.severity.label(),
+                    "reason": pattern.reason,
+                }))
+            })
+            .collect();
+        Response::ok(json!({
+            "status": "ok",
+            "id": id,
+            "name": pack.name,
+            "description": pack.description,
+            "enabled": enabled,
+            "rules": rules,
+        }))
+    }
+
+    fn allow_once_list(&self) -> Result<Response, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let (pending, pending_maintenance) = self.pending_store.load_active(now)?;
+        let (allow_once, allow_once_maintenance) = self.allow_once_store.load_active(now)?;
+        Ok(Response::ok(crate::cli::build_allow_once_list_json(
+            &pending,
+            pending_maintenance,
+            &allow_once,
+            allow_once_maintenance,
+            false,
+        )))
+    }
+
+    /// Grant a pending code. Codes from config blocklist denials need
+    /// `dcg allow-once --force` and are refused here.
+    fn allow_once_grant(
+        &self,
+        code: &str,
+        request: &Request,
+    ) -> Result<Response, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let (mut matches, _) = self.pending_store.lookup_by_code(code, now)?;
+        if let Some(hash) = request.query_param("hash") {
+            matches.retain(|record| record.full_hash.starts_with(hash));
+        }
+        let selected = match matches.as_slice() {
+            [] => {
+                return Ok(Response::error(
+                    404,
+                    format!("no pending exception for code '{code}'"),
+                ));
+            }
+            [one] => one,
+            many => {
+                let hashes: Vec<&str> = many.iter().map(|r| r.full_hash.as_str()).collect();
+                return Ok(Response {
+                    status: 409,
+                    body: json!({
+                        "status": "error",
+                        "error": format!("code '{code}' is ambiguous; pass ?hash=<prefix>"),
+                        "matches": hashes,
+                    }),
+                    html: None,
+                });
+            }
+        };
+        if selected.source.as_deref() == Some("ConfigOverride") {
+            return Ok(Response::error(
+                403,
+                "this denial came from the config blocklist; use `dcg allow-once --force`",
+            ));
+        }
+
+        let body = if request.body.is_empty() {
+            Value::Null
+        } else {
+            match serde_json::from_slice::<Value>(&request.body) {
+                Ok(body) => body,
+                Err(err) => return Ok(Response::error(400, format!("invalid JSON body: {err}"))),
+            }
+        };
+        let single_use = body["single_use"].as_bool().unwrap_or(false);
+        let requested = match body.get("scope") {
+            None | Some(Value::Null) => None,
+            Some(scope) => match serde_json::from_value::<AllowOnceGrant>(scope.clone()) {
+                Ok(grant) => Some(grant),
+                Err(_) => {
+                    return Ok(Response::error(
+                        400,
+                        "scope must be one of \"exact\", \"rule\", \"session\"",
+                    ));
+                }
+            },
+        };
+        let grant = match crate::cli::resolve_allow_once_grant(&self.config, selected, requested) {
+            Ok(grant) => grant,
+            Err(err) => return Ok(Response::error(400, err)),
+        };
+
+        let cwd = std::path::PathBuf::from(&selected.cwd);
+        let (scope_kind, scope_path) = crate::cli::allow_once_scope(&cwd);
+        let entry = AllowOnceEntry::from_pending(
+            selected,
+            now,
+            scope_kind,
+            &scope_path.to_string_lossy(),
+            single_use,
+            false,
+            &self.config.logging.redaction,
+        )
+        .with_grant(grant);
+        self.allow_once_store.add_entry(&entry, now)?;
+        let _ = self
+            .pending_store
+            .remove_by_full_hash(&selected.full_hash, now);
+        self.log_action(
+            "grant",
+            &format!("code={code}, full_hash={}", selected.full_hash),
+        );
+
+        Ok(Response::ok(json!({
+            "status": "ok",
+            "code": code,
+            "full_hash": selected.full_hash,
+            "single_use": single_use,
+            "grant": grant.label(),
+            "scope_kind": format!("{scope_kind:?}").to_lowercase(),
+            "scope_path": entry.scope_path,
+            "command": entry.command_redacted,
+            "expires_at": entry.expires_at,
+        })))
+    }
+
+    fn allow_once_revoke(&self, target: &str) -> Result<Response, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let (pending, _) = self.pending_store.preview_active(now)?;
+        let (allow_once, _) = self.allow_once_store.preview_active(now)?;
+        let full_hash =
+            match crate::cli::resolve_allow_once_revoke_target(target, &pending, &allow_once) {
+                Ok(hash) => hash,
+                Err(err) => return Ok(Response::error(404, err.to_string())),
+            };
+
+        let (pending_removed, _) = self.pending_store.remove_by_full_hash(&full_hash, now)?;
+        let (allow_once_removed, _) = self
+            .allow_once_store
+            .remove_by_source_full_hash(&full_hash, now)?;
+        self.log_action(
+            "revoke",
+            &format!(
+                "target={target}, full_hash={full_hash}, pending_removed={pending_removed}, allow_once_removed={allow_once_removed}"
+            ),
+        );
+
+        Ok(Response::ok(json!({
+            "status": "ok",
+            "target": target,
+            "full_hash": full_hash,
+            "pending": { "removed": pending_removed },
+            "allow_once": { "removed": allow_once_removed },
+        })))
+    }
+
+    /// An Approve or Deny link from the approvals bridge. GET shows a
+    /// confirmation page (so link previews decide nothing); its form POSTs
+    /// back to the same URL, which applies the decision.
+    fn approval_link(&self, request: &Request, id: &str, decision: &str) -> Response {
+        let (Ok(id), Some(decision)) = (id.parse::<u64>(), Decision::from_segment(decision)) else {
+            return Response::page(404, "Not found", "<p>No such approval link.</p>");
+        };
+        let queue = self.approval_store.load();
+        let Some(queued) = queue.get(id) else {
+            return Response::page(
+                404,
+                "Not found",
+                &format!("<p>Request #{id} is no longer queued.</p>"),
+            );
+        };
+        let expected = crate::approval_bridge::sign(&self.token, decision, queued);
+        let signature = request.query_param("sig").unwrap_or_default();
+        if !constant_time_eq(signature.as_bytes(), expected.as_bytes()) {
+            return Response::page(403, "Invalid link", "<p>This link is not valid.</p>");
+        }
+        if queued.status != ApprovalStatus::Pending {
+            return Response::page(
+                409,
+                "Already decided",
+                &format!(
+                    "<p>Request #{id} was already {}.</p>",
+                    queued.status.label()
+                ),
+            );
+        }
+
+        let verb = match decision {
+            Decision::Approve => "Approve",
+            Decision::Reject => "Deny",
+        };
+        let details = format!(
+            "<pre>{}</pre><p>Reason: {}<br>Directory: {}</p>",
+            html_escape(&queued.block.command_redacted),
+            html_escape(&queued.block.reason),
+            html_escape(&queued.block.cwd),
+        );
+        match request.method.as_str() {
+            "GET" => Response::page(
+                200,
+                &format!("{verb} request #{id}?"),
+                &format!(
+                    "{details}<form method=\"post\"><button type=\"submit\">{verb}</button></form>"
+                ),
+            ),
+            "POST" => match self.decide_approval(id, decision) {
+                Ok(()) => {
+                    let outcome = match decision {
+                        Decision::Approve => {
+                            "<p>Approved. The agent's next retry of this command is allowed.</p>"
+                        }
+                        Decision::Reject => "<p>Denied. The command stays blocked.</p>",
+                    };
+                    Response::page(
+                        200,
+                        &format!("Request #{id}"),
+                        &format!("{details}{outcome}"),
+                    )
+                }
+                Err(err) => Response::page(
+                    409,
+                    &format!("Request #{id}"),
+                    &format!("<p>{}</p>", html_escape(&err.to_string())),
+                ),
+            },
+            _ => Response::page(404, "Not found", "<p>No such approval link.</p>"),
+        }
+    }
+
+    fn decide_approval(
+        &self,
+        id: u64,
+        decision: Decision,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let queue = self.approval_store.load();
+        let queued = queue
+            .get(id)
+            .filter(|queued| queued.status == ApprovalStatus::Pending)
+            .ok_or_else(|| format!("request #{id} is no longer pending"))?;
+        if decision == Decision::Approve {
+            let (entry, _) = crate::cli::approval_allow_once_entry(
+                &self.config,
+                &queued.block,
+                false,
+                false,
+                now,
+            )?;
+            self.allow_once_store.add_entry(&entry, now)?;
+            let _ = self
+                .pending_store
+                .remove_by_full_hash(&queued.block.full_hash, now);
+        }
+        self.approval_store.decide(id, decision.status(), now)?;
+        self.log_action(
+            decision.segment(),
+            &format!("request={id}, full_hash={}", queued.block.full_hash),
+        );
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Response {
+        self.config = Config::load();
+        Response::ok(json!({
+            "status": "ok",
+            "enabled_packs": REGISTRY
+                .expand_enabled(&self.config.enabled_pack_ids())
+                .len(),
+        }))
+    }
+
+    fn log_action(&self, action: &str, details: &str) {
+        if let Some(log_file) = self.config.general.log_file.as_deref() {
+            let _ = crate::pending_exceptions::log_allow_once_action(
+                log_file,
+                action,
+                &format!("{details}, via=admin-api"),
+            );
+        }
+    }
+}
+
+/// Bind `listen` and serve requests until the process is stopped.
+///
+/// # Errors
+///
+/// Returns an error if the token is empty, the address is not loopback, or
+/// the socket cannot be bound.
+pub fn run(config: Config, listen: &str, token: String) -> Result<(), Box<dyn std::error::Error>> {
+    if token.trim().is_empty() {
+        return Err(format!("{ENV_ADMIN_TOKEN} must be set to a non-empty token").into());
+    }
+    let addr: SocketAddr = listen
+        .parse()
+        .map_err(|err| format!("invalid listen address '{listen}': {err}"))?;
+    if !addr.ip().is_loopback() {
+        return Err(format!("refusing to listen on non-loopback address {addr}").into());
+    }
+
+    let listener = TcpListener::bind(addr)?;
+    eprintln!(
+        "dcg admin API listening on http://{}",
+        listener.local_addr()?
+    );
+    if let Some(bridge) = crate::approval_bridge::Bridge::from_config(
+        &config.approvals,
+        &token,
+        std::env::var(crate::approval_bridge::ENV_MATRIX_ACCESS_TOKEN).ok(),
+    )? {
+        let channels: Vec<&str> = bridge
+            .channels()
+            .iter()
+            .map(crate::approval_bridge::Channel::label)
+            .collect();
+        eprintln!(
+            "dcg admin API: posting approval requests to {}",
+            channels.join(", ")
+        );
+        let cwd = std::env::current_dir().ok();
+        bridge.spawn(ApprovalQueueStore::new(ApprovalQueueStore::default_path(
+            cwd.as_deref(),
+        )));
+    }
+    let mut api = AdminApi::new(config, token);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = handle_connection(stream, &mut api) {
+                    eprintln!("dcg admin API: {err}");
+                }
+            }
+            Err(err) => eprintln!("dcg admin API: accept failed: {err}"),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, api: &mut AdminApi) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let response = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => api.handle(&request),
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            Response::error(400, err.to_string())
+        }
+        Err(err) => return Err(err),
+    };
+    write_response(&mut stream, &response)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Read one header line, charging it against the remaining header budget.
+fn read_header_line(reader: &mut impl BufRead, budget: &mut usize) -> io::Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(*budget as u64).read_line(&mut line)?;
+    *budget -= line.len();
+    if !line.ends_with('\n') {
+        return Err(invalid("request headers too large or truncated"));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Read one HTTP/1.1 request.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut budget = MAX_HEADER_BYTES;
+    let request_line = read_header_line(reader, &mut budget)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+                (k.to_string(), v.to_string())
+            })
+            .collect(),
+        ..Request::default()
+    };
+
+    let mut content_length = 0usize;
+    loop {
+        let line = read_header_line(reader, &mut budget)?;
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(invalid("malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            request.authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .parse()
+                .map_err(|_| invalid("invalid Content-Length"))?;
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return Err(invalid("request body too large"));
+    }
+    request.body = vec![0; content_length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+fn write_response(writer: &mut impl Write, response: &Response) -> io::Result<()> {
+    let (body, content_type) = match &response.html {
+        Some(html) => (html.as_bytes().to_vec(), "text/html; charset=utf-8"),
+        None => (
+            serde_json::to_vec_pretty(&response.body).map_err(io::Error::other)?,
+            "application/json",
+        ),
+    };
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        body.len()
+    )?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/// Compare tokens without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pending_exceptions::PendingExceptionRecord;
+
+    const TOKEN: &str = "s3cret";
+
+    fn api(dir: &std::path::Path) -> AdminApi {
+        AdminApi {
+            token: TOKEN.to_string(),
+            config: Config::default(),
+            pending_store: PendingExceptionStore::new(dir.join("pending.jsonl")),
+            allow_once_store: AllowOnceStore::new(dir.join("allow_once.jsonl")),
+            approval_store: ApprovalQueueStore::new(dir.join("approvals.json")),
+        }
+    }
+
+    fn get(path: &str) -> Request {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        Request {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            query: query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            authorization: Some(format!("Bearer {TOKEN}")),
+            body: Vec::new(),
+        }
+    }
+
+    fn with_method(method: &str, path: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            ..get(path)
+        }
+    }
+
+    #[test]
+    fn token_is_required_except_for_health() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = api(dir.path());
+
+        let mut request = get("/v1/packs");
+        request.authorization = None;
+        assert_eq!(api.handle(&request).status, 401);
+        request.authorization = Some("Bearer wrong!".to_string());
+        assert_eq!(api.handle(&request).status, 401);
+
+        request.path = "/v1/health".to_string();
+        assert_eq!(api.handle(&request).status, 200);
+
+        assert_eq!(api.handle(&get("/v1/packs")).status, 200);
+        assert_eq!(api.handle(&get("/v1/nope")).status, 404);
+    }
+
+    #[test]
+    fn packs_and_rules_are_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = api(dir.path());
+
+        let packs = api.handle(&get("/v1/packs")).body;
+        let git = packs["packs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["id"] == "core.git")
+            .unwrap();
+        assert_eq!(git["enabled"], true);
+
+        let pack = api.handle(&get("/v1/packs/core.git"));
+        assert_eq!(pack.status, 200);
+        assert!(
+            pack.body["rules"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|rule| rule["rule_id"] == "core.git:reset-hard")
+        );
+        assert_eq!(api.handle(&get("/v1/packs/no.such")).status, 404);
+    }
+
+    #[test]
+    fn decisions_need_history_and_validate_params() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = api(dir.path());
+        assert_eq!(api.handle(&get("/v1/decisions")).status, 503);
+
+        api.config.history.enabled = true;
+        api.config.history.database_path =
+            Some(dir.path().join("history.db").to_string_lossy().to_string());
+        assert_eq!(api.handle(&get("/v1/decisions?limit=x")).status, 400);
+        assert_eq!(api.handle(&get("/v1/decisions?outcome=maybe")).status, 400);
+
+        let response = api.handle(&get("/v1/decisions?limit=5&outcome=deny"));
+        assert_eq!(response.status, 200, "{}", response.body);
+        assert_eq!(response.body["count"], 0);
+    }
+
+    #[test]
+    fn allow_once_grant_and_revoke() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = api(dir.path());
+        let now = Utc::now();
+        let (record, _): (PendingExceptionRecord, _) = api
+            .pending_store
+            .record_block(
+                "git reset --hard",
+                &dir.path().to_string_lossy(),
+                "destroys work",
+                &crate::logging::RedactionConfig::default(),
+                false,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let listed = api.handle(&get("/v1/allow-once")).body;
+        assert_eq!(listed["pending"]["count"], 1);
+
+        let mut grant = with_method("POST", &format!("/v1/allow-once/{}", record.short_code));
+        grant.body = br#"{"single_use": true}"#.to_vec();
+        let granted = api.handle(&grant);
+        assert_eq!(granted.status, 200, "{}", granted.body);
+        assert_eq!(granted.body["single_use"], true);
+        let (active, _) = api.allow_once_store.load_active(now).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(api.handle(&grant).status, 404);
+
+        let revoke = with_method("DELETE", &format!("/v1/allow-once/{}", record.short_code));
+        let revoked = api.handle(&revoke);
+        assert_eq!(revoked.status, 200, "{}", revoked.body);
+        assert_eq!(revoked.body["allow_once"]["removed"], 1);
+        assert_eq!(api.handle(&revoke).status, 404);
+    }
+
+    #[test]
+    fn approval_links_need_a_valid_signature_and_a_post() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = api(dir.path());
+        let now = Utc::now();
+        let block = PendingExceptionRecord::new(
+            now,
+            &dir.path().to_string_lossy(),
+            "git reset --hard",
+            "destroys work",
+            &crate::logging::RedactionConfig::default(),
+            false,
+            None,
+        );
+        let id = api.approval_store.record(&block, now).unwrap();
+        let queued = api.approval_store.load().get(id).unwrap().clone();
+        let link = |decision| {
+            let sig = crate::approval_bridge::sign(TOKEN, decision, &queued);
+            let mut request = get(&format!(
+                "/v1/approvals/{id}/{}?sig={sig}",
+                decision.segment()
+            ));
+            request.authorization = None;
+            request
+        };
+
+        let mut forged = link(Decision::Approve);
+        forged.query = vec![("sig".to_string(), "0".repeat(64))];
+        assert_eq!(api.handle(&forged).status, 403);
+        let mut swapped = link(Decision::Reject);
+        swapped.path = format!("/v1/approvals/{id}/approve");
+        assert_eq!(api.handle(&swapped).status, 403);
+
+        let preview = api.handle(&link(Decision::Approve));
+        assert_eq!(preview.status, 200);
+        assert!(preview.html.unwrap().contains("<form method=\"post\">"));
+        assert_eq!(api.approval_store.load().pending().count(), 1);
+
+        let mut approve = link(Decision::Approve);
+        approve.method = "POST".to_string();
+        let approved = api.handle(&approve);
+        assert_eq!(approved.status, 200, "{:?}", approved.html);
+        assert_eq!(
+            api.approval_store.load().get(id).unwrap().status,
+            ApprovalStatus::Approved
+        );
+        let (active, _) = api.allow_once_store.load_active(now).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].command_raw, "git reset --hard");
+
+        assert_eq!(api.handle(&approve).status, 409);
+        assert_eq!(
+            api.handle(&get("/v1/approvals/99/approve?sig=x")).status,
+            404
+        );
+    }
+
+    #[test]
+    fn parses_requests_from_the_wire() {
+        let raw = b"POST /v1/allow-once/12345?hash=ab HTTP/1.1\r\n\
+                    Host: localhost\r\n\
+                    Authorization: Bearer s3cret\r\n\
+                    Content-Length: 2\r\n\r\n{}";
+        let request = read_request(&mut &raw[..]).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/allow-once/12345");
+        assert_eq!(request.query_param("hash"), Some("ab"));
+        assert_eq!(request.authorization.as_deref(), Some("Bearer s3cret"));
+        assert_eq!(request.body, b"{}");
+
+        let err = read_request(&mut &b"GET\r\n\r\n"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut out = Vec::new();
+        write_response(&mut out, &Response::error(401, "no")).unwrap();
+        assert!(out.starts_with(b"HTTP/1.1 401 Unauthorized\r\n"));
+        let mut out = Vec::new();
+        write_response(&mut out, &Response::page(404, "Not found", "")).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Content-Type: text/html; charset=utf-8\r\n"));
+    }
+
+    #[test]
+    fn run_rejects_non_loopback_and_empty_token() {
+        assert!(run(Config::default(), "0.0.0.0:0", TOKEN.to_string()).is_err());
+        assert!(run(Config::default(), "127.0.0.1:0", " ".to_string()).is_err());
+    }
+}
@@ -0,0 +1,448 @@
+//! Generated allowlist layer for a project's own build and test commands.
+//!
+//! When the working directory belongs to a JavaScript, Rust or Python project,
+//! dcg derives command prefixes from the project's manifests and lockfiles:
+//! `npm run <script>` for every `package.json` script (with the package
+//! manager picked from the lockfile), the everyday `cargo` subcommands, and
+//! the usual Python test and lint runners (under `poetry run`, `uv run`, ...
+//! when that tool's lockfile is present). They form the lowest-precedence
+//! [`AllowlistLayer::Generated`] layer, rebuilt on every load and never
+//! written to disk, so routine build and test commands stay allowed even as
+//! packs grow stricter.
+//!
+//! Every generated entry carries the `string-argument` context (see
+//! [`crate::allowlist::context_permits`]): it only covers a single simple
+//! command, never one chained with `&&`, `;`, a pipe or a substitution.
+//! Disable the layer with `general.bootstrap_allowlist = false`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::allowlist::{
+    AllowEntry, AllowSelector, AllowlistFile, AllowlistLayer, LoadedAllowlistLayer, PathMatch,
+    STRING_ARGUMENT_CONTEXT,
+};
+
+/// Everyday cargo subcommands that only build, check, test or inspect.
+const CARGO_SUBCOMMANDS: &[&str] = &[
+    "build",
+    "check",
+    "test",
+    "clippy",
+    "fmt",
+    "run",
+    "doc",
+    "bench",
+    "nextest run",
+    "tree",
+    "metadata",
+    "fetch",
+];
+
+/// Python test and lint runners, run directly or through the project's tool.
+const PYTHON_TOOLS: &[&str] = &[
+    "pytest",
+    "python -m pytest",
+    "python3 -m pytest",
+    "tox",
+    "ruff check",
+    "ruff format",
+    "mypy",
+    "black",
+];
+
+/// Python project tools, keyed by the lockfile that selects them.
+const PYTHON_RUNNERS: &[(&str, &str)] = &[
+    ("poetry.lock", "poetry"),
+    ("uv.lock", "uv"),
+    ("pdm.lock", "pdm"),
+    ("Pipfile.lock", "pipenv"),
+];
+
+/// Files that mark a Python project.
+const PYTHON_MARKERS: &[&str] = &[
+    "pyproject.toml",
+    "setup.py",
+    "setup.cfg",
+    "requirements.txt",
+    "Pipfile",
+];
+
+/// JavaScript package managers, keyed by their lockfile (first match wins).
+const NODE_LOCKFILES: &[(&str, NodePackageManager)] = &[
+    ("pnpm-lock.yaml", NodePackageManager::Pnpm),
+    ("yarn.lock", NodePackageManager::Yarn),
+    ("bun.lock", NodePackageManager::Bun),
+    ("bun.lockb", NodePackageManager::Bun),
+    ("package-lock.json", NodePackageManager::Npm),
+    ("npm-shrinkwrap.json", NodePackageManager::Npm),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodePackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+    Bun,
+}
+
+impl NodePackageManager {
+    fn from_package_manager_field(value: &str) -> Option<Self> {
+        match value.split('@').next()? {
+            "npm" => Some(Self::Npm),
+            "yarn" => Some(Self::Yarn),
+            "pnpm" => Some(Self::Pnpm),
+            "bun" => Some(Self::Bun),
+            _ => None,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Yarn => "yarn",
+            Self::Pnpm => "pnpm",
+            Self::Bun => "bun",
+        }
+    }
+
+    /// Ways this package manager runs the script `name`.
+    fn script_commands(self, name: &str) -> Vec<String> {
+        let pm = self.name();
+        match self {
+            Self::Npm => {
+                let mut commands = vec![format!("npm run {name}")];
+                if matches!(name, "test" | "start" | "stop" | "restart") {
+                    commands.push(format!("npm {name}"));
+                }
+                commands
+            }
+            Self::Yarn | Self::Pnpm => vec![format!("{pm} run {name}"), format!("{pm} {name}")],
+            Self::Bun => vec![format!("bun run {name}")],
+        }
+    }
+
+    fn install_commands(self) -> &'static [&'static str] {
+        match self {
+            Self::Npm => &["npm install", "npm ci"],
+            Self::Yarn => &["yarn install"],
+            Self::Pnpm => &["pnpm install"],
+            Self::Bun => &["bun install"],
+        }
+    }
+}
+
+/// A generated command prefix and the project file it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedCommand {
+    pub prefix: String,
+    /// E.g. `package.json (pnpm)` or `Cargo.toml`.
+    pub source: String,
+}
+
+/// The commands generated for one project directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bootstrap {
+    /// Directory the manifests were read from.
+    pub root: PathBuf,
+    pub commands: Vec<GeneratedCommand>,
+}
+
+impl Bootstrap {
+    /// Allowlist entries for the generated prefixes.
+    #[must_use]
+    pub fn entries(&self) -> Vec<AllowEntry> {
+        self.commands
+            .iter()
+            .map(|command| AllowEntry {
+                selector: AllowSelector::CommandPrefix(command.prefix.clone()),
+                reason: format!("generated from {}", command.source),
+                added_by: None,
+                added_at: None,
+                expires_at: None,
+                ttl: None,
+                session: None,
+                context: Some(STRING_ARGUMENT_CONTEXT.to_string()),
+                conditions: HashMap::new(),
+                environments: Vec::new(),
+                active_hours: None,
+                active_dates: Vec::new(),
+                hosts: Vec::new(),
+                users: Vec::new(),
+                arg_conditions: BTreeMap::new(),
+                paths: None,
+                path_match: PathMatch::Cwd,
+                risk_acknowledged: false,
+            })
+            .collect()
+    }
+
+    /// The generated layer, attributed to [`root`](Self::root).
+    #[must_use]
+    pub fn layer(&self) -> LoadedAllowlistLayer {
+        LoadedAllowlistLayer {
+            layer: AllowlistLayer::Generated,
+            path: self.root.clone(),
+            file: AllowlistFile {
+                entries: self.entries(),
+                ..AllowlistFile::default()
+            },
+        }
+    }
+}
+
+/// Generate commands for the nearest project directory at or above `cwd`.
+///
+/// The search stops at the enclosing git repository (outside one, only `cwd`
+/// itself is read). `None` if no project files are found.
+#[must_use]
+pub fn detect(cwd: &Path) -> Option<Bootstrap> {
+    let repo_root = crate::config::find_repo_root(cwd, crate::config::REPO_ROOT_SEARCH_MAX_HOPS);
+    cwd.ancestors()
+        .take_while(|dir| {
+            repo_root
+                .as_deref()
+                .map_or(*dir == cwd, |root| dir.starts_with(root))
+        })
+        .map(generate)
+        .find(|bootstrap| !bootstrap.commands.is_empty())
+}
+
+/// Generate commands from the project files directly in `root`.
+#[must_use]
+pub fn generate(root: &Path) -> Bootstrap {
+    let mut commands = Vec::new();
+    node_commands(root, &mut commands);
+    rust_commands(root, &mut commands);
+    python_commands(root, &mut commands);
+    Bootstrap {
+        root: root.to_path_buf(),
+        commands,
+    }
+}
+
+fn push(commands: &mut Vec<GeneratedCommand>, prefix: String, source: &str) {
+    if !commands.iter().any(|existing| existing.prefix == prefix) {
+        commands.push(GeneratedCommand {
+            prefix,
+            source: source.to_string(),
+        });
+    }
+}
+
+fn node_commands(root: &Path, commands: &mut Vec<GeneratedCommand>) {
+    let Ok(content) = std::fs::read_to_string(root.join("package.json")) else {
+        return;
+    };
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return;
+    };
+
+    let pm = package
+        .get("packageManager")
+        .and_then(serde_json::Value::as_str)
+        .and_then(NodePackageManager::from_package_manager_field)
+        .or_else(|| {
+            NODE_LOCKFILES
+                .iter()
+                .find(|(lockfile, _)| root.join(lockfile).exists())
+                .map(|(_, pm)| *pm)
+        })
+        .unwrap_or(NodePackageManager::Npm);
+    let source = format!("package.json ({})", pm.name());
+
+    let scripts = package
+        .get("scripts")
+        .and_then(serde_json::Value::as_object)
+        .into_iter()
+        .flat_map(|scripts| scripts.keys())
+        .filter(|name| is_plain_script_name(name));
+    for name in scripts {
+        for command in pm.script_commands(name) {
+            push(commands, command, &source);
+        }
+    }
+    for command in pm.install_commands() {
+        push(commands, (*command).to_string(), &source);
+    }
+}
+
+/// Script names that are a single shell word needing no quoting.
+fn is_plain_script_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, ':' | '_' | '.' | '-' | '/'))
+}
+
+fn rust_commands(root: &Path, commands: &mut Vec<GeneratedCommand>) {
+    if !root.join("Cargo.toml").is_file() {
+        return;
+    }
+    for subcommand in CARGO_SUBCOMMANDS {
+        push(commands, format!("cargo {subcommand}"), "Cargo.toml");
+    }
+}
+
+fn python_commands(root: &Path, commands: &mut Vec<GeneratedCommand>) {
+    let runner = PYTHON_RUNNERS
+        .iter()
+        .find(|(lockfile, _)| root.join(lockfile).is_file());
+    let is_python = runner.is_some()
+        || PYTHON_MARKERS
+            .iter()
+            .any(|marker| root.join(marker).is_file());
+    if !is_python {
+        return;
+    }
+
+    match runner {
+        Some((lockfile, tool)) => {
+            for python_tool in PYTHON_TOOLS {
+                push(commands, format!("{tool} run {python_tool}"), lockfile);
+            }
+            let install = if *tool == "uv" { "sync" } else { "install" };
+            push(commands, format!("{tool} {install}"), lockfile);
+        }
+        None => {
+            let source = PYTHON_MARKERS
+                .iter()
+                .find(|marker| root.join(marker).is_file())
+                .copied()
+                .unwrap_or("pyproject.toml");
+            for python_tool in PYTHON_TOOLS {
+                push(commands, (*python_tool).to_string(), source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefixes(bootstrap: &Bootstrap) -> Vec<&str> {
+        bootstrap
+            .commands
+            .iter()
+            .map(|command| command.prefix.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn package_json_scripts_use_the_lockfile_package_manager() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "tsc", "test": "vitest", "weird name": "x"}}"#,
+        )
+        .unwrap();
+
+        let npm = generate(dir.path());
+        assert_eq!(
+            prefixes(&npm),
+            [
+                "npm run build",
+                "npm run test",
+                "npm test",
+                "npm install",
+                "npm ci"
+            ]
+        );
+        assert_eq!(npm.commands[0].source, "package.json (npm)");
+
+        std::fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        let pnpm = generate(dir.path());
+        assert!(prefixes(&pnpm).contains(&"pnpm build"));
+        assert!(prefixes(&pnpm).contains(&"pnpm run test"));
+        assert!(!prefixes(&pnpm).iter().any(|p| p.starts_with("npm ")));
+    }
+
+    #[test]
+    fn package_manager_field_wins_over_lockfiles() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"packageManager": "yarn@4.1.0", "scripts": {"lint": "eslint ."}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
+
+        let bootstrap = generate(dir.path());
+        assert_eq!(
+            prefixes(&bootstrap),
+            ["yarn run lint", "yarn lint", "yarn install"]
+        );
+    }
+
+    #[test]
+    fn cargo_and_python_projects() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("uv.lock"), "").unwrap();
+
+        let bootstrap = generate(dir.path());
+        let prefixes = prefixes(&bootstrap);
+        assert!(prefixes.contains(&"cargo test"));
+        assert!(prefixes.contains(&"cargo clippy"));
+        assert!(
+            !prefixes
+                .iter()
+                .any(|p| p.contains("clean") || p.contains("publish"))
+        );
+        assert!(prefixes.contains(&"uv run pytest"));
+        assert!(prefixes.contains(&"uv sync"));
+        assert!(!prefixes.contains(&"pytest"));
+    }
+
+    #[test]
+    fn detect_stops_at_the_repository_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        let nested = repo.join("crates").join("core");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join("requirements.txt"), "").unwrap();
+
+        assert_eq!(detect(&nested), None);
+
+        std::fs::write(repo.join("Cargo.toml"), "").unwrap();
+        let bootstrap = detect(&nested).unwrap();
+        assert_eq!(bootstrap.root, repo);
+        assert!(prefixes(&bootstrap).contains(&"cargo build"));
+    }
+
+    #[test]
+    fn generated_entries_only_cover_simple_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"clean": "rimraf dist"}}"#,
+        )
+        .unwrap();
+        let mut allowlists = crate::allowlist::LayeredAllowlist::default();
+        allowlists.add_generated_layer(generate(dir.path()).layer());
+
+        let hit = allowlists
+            .match_command_prefix("npm run clean")
+            .expect("script is allowlisted");
+        assert_eq!(hit.layer, AllowlistLayer::Generated);
+        assert!(
+            allowlists
+                .match_command_prefix("npm run clean --force")
+                .is_some()
+        );
+        assert!(
+            allowlists
+                .match_command_prefix("npm run clean && rm -rf ~")
+                .is_none()
+        );
+        assert!(
+            allowlists
+                .match_command_prefix("npm run clean; git reset --hard")
+                .is_none()
+        );
+    }
+}
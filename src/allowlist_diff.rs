@@ -0,0 +1,306 @@
+//! Semantic diff between two versions of an allowlist.
+//!
+//! `dcg allowlist list --diff <rev>` compares the project allowlist in the
+//! working copy with the copy committed at `<rev>`. Entries are paired by
+//! selector (kind and value, in file order when a selector repeats), so a
+//! reviewer sees which exceptions were added, removed, or had their reason,
+//! expiration, or gating changed instead of a line diff of the TOML.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::allowlist::{AllowEntry, AllowSelector, PathMatch};
+
+/// How an entry changed between the two versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl ChangeKind {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Modified => "modified",
+        }
+    }
+}
+
+/// One field whose value differs; `None` means unset on that side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// One added, removed, or modified entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    pub kind: ChangeKind,
+    pub selector: AllowSelector,
+    /// Every set field of an added or removed entry; only the differing
+    /// fields of a modified one.
+    pub fields: Vec<FieldChange>,
+}
+
+/// Diff `before` against `after`.
+///
+/// Added and modified entries come in `after` order, followed by removed
+/// entries in `before` order.
+#[must_use]
+pub fn diff_entries(before: &[AllowEntry], after: &[AllowEntry]) -> Vec<EntryChange> {
+    let mut unmatched: HashMap<(&'static str, String), Vec<&AllowEntry>> = HashMap::new();
+    for entry in before.iter().rev() {
+        unmatched
+            .entry(selector_key(&entry.selector))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut changes = Vec::new();
+    for entry in after {
+        let old = unmatched
+            .get_mut(&selector_key(&entry.selector))
+            .and_then(Vec::pop);
+        let new_fields = entry_fields(entry);
+        match old {
+            None => changes.push(EntryChange {
+                kind: ChangeKind::Added,
+                selector: entry.selector.clone(),
+                fields: one_sided(new_fields, false),
+            }),
+            Some(old) => {
+                let fields = changed_fields(&entry_fields(old), &new_fields);
+                if !fields.is_empty() {
+                    changes.push(EntryChange {
+                        kind: ChangeKind::Modified,
+                        selector: entry.selector.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+
+    for entry in before {
+        let remaining = unmatched.get_mut(&selector_key(&entry.selector));
+        if let Some(pos) = remaining
+            .as_ref()
+            .and_then(|left| left.iter().position(|left| std::ptr::eq(*left, entry)))
+        {
+            remaining.expect("position implies entries").remove(pos);
+            changes.push(EntryChange {
+                kind: ChangeKind::Removed,
+                selector: entry.selector.clone(),
+                fields: one_sided(entry_fields(entry), true),
+            });
+        }
+    }
+    changes
+}
+
+/// Fields that differ between two versions of one entry.
+#[must_use]
+pub fn entry_changes(before: &AllowEntry, after: &AllowEntry) -> Vec<FieldChange> {
+    changed_fields(&entry_fields(before), &entry_fields(after))
+}
+
+fn selector_key(selector: &AllowSelector) -> (&'static str, String) {
+    (selector.kind_label(), selector.value())
+}
+
+/// The entry's set fields (everything but the selector), as displayed.
+fn entry_fields(entry: &AllowEntry) -> BTreeMap<&'static str, String> {
+    let join = |values: &[String]| values.join(", ");
+    let mut fields = BTreeMap::new();
+    fields.insert("reason", entry.reason.clone());
+    let optional = [
+        ("added_by", entry.added_by.clone()),
+        ("added_at", entry.added_at.clone()),
+        ("expires_at", entry.expires_at.clone()),
+        ("ttl", entry.ttl.clone()),
+        ("session", entry.session.map(|s| s.to_string())),
+        ("context", entry.context.clone()),
+        ("active_hours", entry.active_hours.clone()),
+        ("paths", entry.paths.as_deref().map(join)),
+        (
+            "match",
+            (entry.path_match != PathMatch::Cwd).then(|| entry.path_match.label().to_string()),
+        ),
+        (
+            "risk_acknowledged",
+            entry.risk_acknowledged.then(|| "true".to_string()),
+        ),
+    ];
+    for (field, value) in optional {
+        if let Some(value) = value {
+            fields.insert(field, value);
+        }
+    }
+    for (field, values) in [
+        ("environments", &entry.environments),
+        ("active_dates", &entry.active_dates),
+        ("hosts", &entry.hosts),
+        ("users", &entry.users),
+    ] {
+        if !values.is_empty() {
+            fields.insert(field, join(values));
+        }
+    }
+    if !entry.conditions.is_empty() {
+        let mut conditions: Vec<String> = entry
+            .conditions
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        conditions.sort();
+        fields.insert("conditions", conditions.join(", "));
+    }
+    if !entry.arg_conditions.is_empty() {
+        let arg_conditions: Vec<String> = entry
+            .arg_conditions
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        fields.insert("arg_conditions", arg_conditions.join(", "));
+    }
+    fields
+}
+
+fn one_sided(fields: BTreeMap<&'static str, String>, removed: bool) -> Vec<FieldChange> {
+    fields
+        .into_iter()
+        .map(|(field, value)| FieldChange {
+            field,
+            before: removed.then(|| value.clone()),
+            after: (!removed).then_some(value),
+        })
+        .collect()
+}
+
+fn changed_fields(
+    before: &BTreeMap<&'static str, String>,
+    after: &BTreeMap<&'static str, String>,
+) -> Vec<FieldChange> {
+    let mut names: Vec<&'static str> = before.keys().chain(after.keys()).copied().collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| before.get(name) != after.get(name))
+        .map(|field| FieldChange {
+            field,
+            before: before.get(field).cloned(),
+            after: after.get(field).cloned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allowlist::{AllowlistLayer, parse_allowlist_toml};
+    use std::path::Path;
+
+    fn entries(toml: &str) -> Vec<AllowEntry> {
+        let file = parse_allowlist_toml(AllowlistLayer::Project, Path::new("dummy"), toml);
+        assert!(file.errors.is_empty(), "{:#?}", file.errors);
+        file.entries
+    }
+
+    #[test]
+    fn reports_added_removed_and_modified_entries() {
+        let before = entries(
+            r#"
+            [[allow]]
+            rule = "core.git:reset-hard"
+            reason = "ci cleanup"
+            expires_at = "2030-01-01"
+
+            [[allow]]
+            exact_command = "rm -rf ./build"
+            reason = "build dir"
+
+            [[allow]]
+            rule = "core.git:clean-force"
+            reason = "unchanged"
+        "#,
+        );
+        let after = entries(
+            r#"
+            [[allow]]
+            rule = "core.git:clean-force"
+            reason = "unchanged"
+
+            [[allow]]
+            rule = "core.git:reset-hard"
+            reason = "ci cleanup"
+            expires_at = "2031-01-01"
+            environments = ["ci"]
+
+            [[allow]]
+            command_prefix = "bd create"
+            reason = "issue text"
+            context = "string-argument"
+        "#,
+        );
+
+        let changes = diff_entries(&before, &after);
+        let summary: Vec<(ChangeKind, String)> = changes
+            .iter()
+            .map(|change| (change.kind, change.selector.value()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Modified, "core.git:reset-hard".to_string()),
+                (ChangeKind::Added, "bd create".to_string()),
+                (ChangeKind::Removed, "rm -rf ./build".to_string()),
+            ]
+        );
+        assert_eq!(
+            changes[0].fields,
+            vec![
+                FieldChange {
+                    field: "environments",
+                    before: None,
+                    after: Some("ci".to_string()),
+                },
+                FieldChange {
+                    field: "expires_at",
+                    before: Some("2030-01-01".to_string()),
+                    after: Some("2031-01-01".to_string()),
+                },
+            ]
+        );
+        assert!(
+            changes[2]
+                .fields
+                .iter()
+                .all(|field| field.after.is_none() && field.before.is_some())
+        );
+    }
+
+    #[test]
+    fn repeated_selectors_pair_in_file_order() {
+        let before = entries(
+            r#"
+            [[allow]]
+            rule = "core.git:reset-hard"
+            reason = "first"
+
+            [[allow]]
+            rule = "core.git:reset-hard"
+            reason = "second"
+        "#,
+        );
+        let changes = diff_entries(&before, &before[..1]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Removed);
+        assert_eq!(changes[0].fields[0].before.as_deref(), Some("second"));
+    }
+}
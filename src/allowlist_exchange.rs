@@ -0,0 +1,463 @@
+//! Allowlist export and import between machines and formats.
+//!
+//! `dcg allowlist export` writes the `[[allow]]` entries of one layer as
+//! TOML (the allowlist file format) or as JSON with the same keys, and
+//! `dcg allowlist import` merges such a file into a layer. Entries are
+//! matched by selector (kind and value): an imported entry that matches an
+//! existing one with a different reason, expiration, or gating is a
+//! conflict, resolved by a [`MergeStrategy`]. Audit metadata (`added_by`,
+//! `added_at`) alone never makes a conflict.
+//!
+//! Import also reads the output of `dcg allowlist list --format json`, whose
+//! entries carry a `{"type", "value"}` selector object instead of a selector
+//! key.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::allowlist::{AllowEntry, AllowSelector, parse_allow_entry};
+use crate::allowlist_diff::{FieldChange, entry_changes};
+
+/// Serialization of an exported allowlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ExchangeFormat {
+    /// `[[allow]]` tables, as in `allowlist.toml`
+    Toml,
+    /// `{"allow": [...]}` with the TOML keys
+    Json,
+}
+
+impl ExchangeFormat {
+    /// Format implied by a file extension (`.json`, else TOML).
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            Self::Json
+        } else {
+            Self::Toml
+        }
+    }
+}
+
+/// What to do when an imported entry conflicts with an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MergeStrategy {
+    /// Replace the existing entry with the imported one
+    Theirs,
+    /// Keep the existing entry
+    Ours,
+    /// Ask for each conflict
+    Interactive,
+}
+
+/// Fields that identify when an entry was added, not what it allows.
+const AUDIT_FIELDS: &[&str] = &["added_by", "added_at"];
+
+/// An imported entry whose selector is already in the target allowlist.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// Position of the existing entry in the target's `allow` array.
+    pub existing_index: usize,
+    pub selector: AllowSelector,
+    /// Differences from the existing entry to the imported one.
+    pub changes: Vec<FieldChange>,
+    pub imported: toml::Table,
+}
+
+/// How an import merges into the target allowlist.
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    /// Entries with a selector the target does not have yet.
+    pub added: Vec<toml::Table>,
+    /// Entries already present with the same settings.
+    pub unchanged: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The valid `[[allow]]` tables of an allowlist file, in file order, with
+/// messages for the entries that were skipped.
+///
+/// # Errors
+///
+/// Returns an error if `content` is not TOML or `allow` is not an array.
+pub fn allow_tables(content: &str) -> Result<(Vec<toml::Table>, Vec<String>), String> {
+    let root: toml::Table = toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+    let Some(allow) = root.get("allow") else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let items = allow
+        .as_array()
+        .ok_or("`allow` must be an array of tables (use [[allow]])")?;
+
+    let mut tables = Vec::new();
+    let mut skipped = Vec::new();
+    for (idx, item) in items.iter().enumerate() {
+        match item.as_table() {
+            Some(table) => match parse_allow_entry(table) {
+                Ok(_) => tables.push(table.clone()),
+                Err(e) => skipped.push(format!("entry {}: {e}", idx + 1)),
+            },
+            None => skipped.push(format!("entry {}: not a table", idx + 1)),
+        }
+    }
+    Ok((tables, skipped))
+}
+
+/// Render `tables` as an exported allowlist.
+///
+/// # Errors
+///
+/// Returns an error if the tables cannot be serialized.
+pub fn export(tables: &[toml::Table], format: ExchangeFormat) -> Result<String, String> {
+    match format {
+        ExchangeFormat::Toml => {
+            let mut root = toml::Table::new();
+            root.insert(
+                "allow".to_string(),
+                toml::Value::Array(tables.iter().cloned().map(toml::Value::Table).collect()),
+            );
+            toml::to_string(&root).map_err(|e| e.to_string())
+        }
+        ExchangeFormat::Json => {
+            let allow: Vec<serde_json::Value> = tables
+                .iter()
+                .map(|table| toml_to_json(&toml::Value::Table(table.clone())))
+                .collect();
+            let mut json = serde_json::to_string_pretty(&serde_json::json!({ "allow": allow }))
+                .map_err(|e| e.to_string())?;
+            json.push('\n');
+            Ok(json)
+        }
+    }
+}
+
+/// Parse an exported allowlist. Every entry must be valid and have its own
+/// selector, so a bad file imports nothing.
+///
+/// # Errors
+///
+/// Returns the problems found, one per line.
+pub fn parse_import(content: &str, format: ExchangeFormat) -> Result<Vec<toml::Table>, String> {
+    let items: Vec<Option<toml::Value>> = match format {
+        ExchangeFormat::Toml => {
+            let root: toml::Table =
+                toml::from_str(content).map_err(|e| format!("invalid TOML: {e}"))?;
+            match root.get("allow") {
+                None => Vec::new(),
+                Some(toml::Value::Array(items)) => items.iter().cloned().map(Some).collect(),
+                Some(_) => return Err("`allow` must be an array of tables".to_string()),
+            }
+        }
+        ExchangeFormat::Json => {
+            let root: serde_json::Value =
+                serde_json::from_str(content).map_err(|e| format!("invalid JSON: {e}"))?;
+            let items = match &root {
+                serde_json::Value::Array(items) => items,
+                serde_json::Value::Object(object) => match object.get("allow") {
+                    Some(serde_json::Value::Array(items)) => items,
+                    None => return Ok(Vec::new()),
+                    Some(_) => return Err("`allow` must be an array".to_string()),
+                },
+                _ => return Err("expected an `allow` array".to_string()),
+            };
+            items
+                .iter()
+                .map(|item| json_to_toml(&listed_entry_to_keys(item)))
+                .collect()
+        }
+    };
+
+    let mut tables = Vec::new();
+    let mut seen = HashSet::new();
+    let mut problems = Vec::new();
+    for (idx, item) in items.into_iter().enumerate() {
+        let Some(toml::Value::Table(table)) = item else {
+            problems.push(format!("entry {}: not a table", idx + 1));
+            continue;
+        };
+        match parse_allow_entry(&table) {
+            Ok(entry) => {
+                if !seen.insert(selector_key(&entry.selector)) {
+                    problems.push(format!(
+                        "entry {}: duplicate {} {}",
+                        idx + 1,
+                        entry.selector.kind_label(),
+                        entry.selector.value()
+                    ));
+                }
+                tables.push(table);
+            }
+            Err(e) => problems.push(format!("entry {}: {e}", idx + 1)),
+        }
+    }
+    if problems.is_empty() {
+        Ok(tables)
+    } else {
+        Err(problems.join("\n"))
+    }
+}
+
+/// Match `imported` against the target's `allow` array items.
+///
+/// Target items that are not valid entries never match.
+#[must_use]
+pub fn plan_import(existing: &[toml::Value], imported: Vec<toml::Table>) -> ImportPlan {
+    let existing: Vec<(usize, AllowEntry)> = existing
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| Some((idx, parse_allow_entry(item.as_table()?).ok()?)))
+        .collect();
+
+    let mut plan = ImportPlan::default();
+    for table in imported {
+        let Ok(entry) = parse_allow_entry(&table) else {
+            continue;
+        };
+        let key = selector_key(&entry.selector);
+        let Some((existing_index, current)) = existing
+            .iter()
+            .find(|(_, current)| selector_key(&current.selector) == key)
+        else {
+            plan.added.push(table);
+            continue;
+        };
+        let changes: Vec<FieldChange> = entry_changes(current, &entry)
+            .into_iter()
+            .filter(|change| !AUDIT_FIELDS.contains(&change.field))
+            .collect();
+        if changes.is_empty() {
+            plan.unchanged += 1;
+        } else {
+            plan.conflicts.push(Conflict {
+                existing_index: *existing_index,
+                selector: entry.selector,
+                changes,
+                imported: table,
+            });
+        }
+    }
+    plan
+}
+
+/// `table` as a `toml_edit` table for appending to an allowlist document.
+#[must_use]
+pub fn to_edit_table(table: &toml::Table) -> toml_edit::Table {
+    let mut root = toml::Table::new();
+    root.insert(
+        "allow".to_string(),
+        toml::Value::Array(vec![toml::Value::Table(table.clone())]),
+    );
+    toml::to_string(&root)
+        .ok()
+        .and_then(|text| text.parse::<toml_edit::DocumentMut>().ok())
+        .and_then(|mut doc| {
+            doc.remove("allow")?
+                .into_array_of_tables()
+                .ok()?
+                .into_iter()
+                .next()
+        })
+        .unwrap_or_default()
+}
+
+fn selector_key(selector: &AllowSelector) -> (&'static str, String) {
+    (selector.kind_label(), selector.value())
+}
+
+/// An entry of `dcg allowlist list --format json` rewritten with the file
+/// keys; other entries are returned unchanged.
+fn listed_entry_to_keys(item: &serde_json::Value) -> serde_json::Value {
+    let Some(object) = item.as_object() else {
+        return item.clone();
+    };
+    let Some(selector) = object
+        .get("selector")
+        .and_then(serde_json::Value::as_object)
+    else {
+        return item.clone();
+    };
+    let mut entry = object.clone();
+    entry.remove("selector");
+    entry.remove("layer");
+    entry.remove("path");
+    if let (Some(kind), Some(value)) = (
+        selector.get("type").and_then(serde_json::Value::as_str),
+        selector.get("value"),
+    ) {
+        entry.insert(kind.to_string(), value.clone());
+    }
+    serde_json::Value::Object(entry)
+}
+
+pub(crate) fn toml_to_json(value: &toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s.clone()),
+        toml::Value::Integer(i) => serde_json::Value::from(*i),
+        toml::Value::Float(f) => serde_json::Value::from(*f),
+        toml::Value::Boolean(b) => serde_json::Value::Bool(*b),
+        toml::Value::Datetime(dt) => serde_json::Value::String(dt.to_string()),
+        toml::Value::Array(items) => items.iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(key, value)| (key.clone(), toml_to_json(value)))
+            .collect(),
+    }
+}
+
+/// `value` as TOML; `null` (which TOML lacks) becomes `None` and is dropped
+/// from tables and arrays.
+fn json_to_toml(value: &serde_json::Value) -> Option<toml::Value> {
+    Some(match value {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(b) => toml::Value::Boolean(*b),
+        serde_json::Value::Number(n) => n.as_i64().map_or_else(
+            || toml::Value::Float(n.as_f64().unwrap_or_default()),
+            toml::Value::Integer,
+        ),
+        serde_json::Value::String(s) => toml::Value::String(s.clone()),
+        serde_json::Value::Array(items) => {
+            toml::Value::Array(items.iter().filter_map(json_to_toml).collect())
+        }
+        serde_json::Value::Object(object) => toml::Value::Table(
+            object
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), json_to_toml(value)?)))
+                .collect(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "ci cleanup"
+added_at = 2026-01-02T03:04:05Z
+environments = ["ci"]
+
+[[allow]]
+exact_command = "rm -rf ./build"
+reason = "build dir"
+
+[[allow]]
+reason = "no selector"
+"#;
+
+    fn existing() -> Vec<toml::Value> {
+        let root: toml::Table = toml::from_str(FILE).expect("toml");
+        root["allow"].as_array().expect("array").clone()
+    }
+
+    #[test]
+    fn export_round_trips_through_both_formats() {
+        let (tables, skipped) = allow_tables(FILE).expect("parse");
+        assert_eq!(tables.len(), 2);
+        assert_eq!(
+            skipped,
+            vec![
+                "entry 3: missing selector: one of rule, exact_command, command_prefix, pattern, url_prefix"
+                    .to_string()
+            ]
+        );
+
+        for format in [ExchangeFormat::Toml, ExchangeFormat::Json] {
+            let exported = export(&tables, format).expect("export");
+            let imported = parse_import(&exported, format).expect("import");
+            let plan = plan_import(&existing(), imported);
+            assert_eq!(plan.unchanged, 2, "{format:?}");
+            assert!(plan.added.is_empty() && plan.conflicts.is_empty());
+        }
+
+        let json = export(&tables, ExchangeFormat::Json).expect("export");
+        assert!(json.contains("\"added_at\": \"2026-01-02T03:04:05Z\""));
+    }
+
+    #[test]
+    fn plan_separates_new_unchanged_and_conflicting_entries() {
+        let imported = parse_import(
+            r#"
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "ci cleanup"
+added_by = "someone-else"
+environments = ["ci"]
+
+[[allow]]
+exact_command = "rm -rf ./build"
+reason = "build and dist dirs"
+
+[[allow]]
+rule = "core.git:clean-force"
+reason = "scratch checkouts"
+"#,
+            ExchangeFormat::Toml,
+        )
+        .expect("import");
+
+        let plan = plan_import(&existing(), imported);
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.added.len(), 1);
+        assert_eq!(plan.added[0]["rule"].as_str(), Some("core.git:clean-force"));
+        assert_eq!(plan.conflicts.len(), 1);
+        let conflict = &plan.conflicts[0];
+        assert_eq!(conflict.existing_index, 1);
+        assert_eq!(conflict.selector.value(), "rm -rf ./build");
+        assert_eq!(conflict.changes.len(), 1);
+        assert_eq!(conflict.changes[0].field, "reason");
+    }
+
+    #[test]
+    fn import_reads_list_output_and_rejects_bad_files() {
+        let listed = r#"[
+  {"layer": "user", "path": "/tmp/a.toml",
+   "selector": {"type": "rule", "value": "core.git:reset-hard"},
+   "reason": "ci cleanup", "added_by": null, "added_at": null, "expires_at": null}
+]"#;
+        let tables = parse_import(listed, ExchangeFormat::Json).expect("import");
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0]["rule"].as_str(), Some("core.git:reset-hard"));
+        assert!(!tables[0].contains_key("layer"));
+
+        let duplicate = r#"{"allow": [
+  {"rule": "core.git:reset-hard", "reason": "a"},
+  {"rule": "core.git:reset-hard", "reason": "b"},
+  {"rule": "not-a-rule", "reason": "c"}
+]}"#;
+        let err = parse_import(duplicate, ExchangeFormat::Json).expect_err("invalid");
+        assert!(err.contains("entry 2: duplicate rule core.git:reset-hard"));
+        assert!(err.contains("entry 3: invalid rule id"));
+    }
+
+    #[test]
+    fn edit_table_keeps_every_key() {
+        let (tables, _) = allow_tables(FILE).expect("parse");
+        let table = to_edit_table(&tables[0]);
+        assert_eq!(table["rule"].as_str(), Some("core.git:reset-hard"));
+        assert!(table.contains_key("added_at"));
+        assert!(table["environments"].is_array());
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(
+            ExchangeFormat::from_path(Path::new("a.JSON")),
+            ExchangeFormat::Json
+        );
+        assert_eq!(
+            ExchangeFormat::from_path(Path::new("allowlist.toml")),
+            ExchangeFormat::Toml
+        );
+        assert_eq!(
+            ExchangeFormat::from_path(Path::new("exceptions")),
+            ExchangeFormat::Toml
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Allowlist usage accounting.
+//!
+//! Every time an allowlist entry decides a hook evaluation, its counter and
+//! last-used timestamp are bumped in a small JSON sidecar (next to the pending
+//! exception store by default). Entries are keyed by a hash of the allowlist
+//! file path and the entry's selector, so editing an entry's reason or
+//! expiration keeps its history while the policy files themselves stay
+//! untouched.
+//!
+//! `dcg allowlist stats` reports the counters and `dcg allowlist prune`
+//! removes entries that have not fired within a given window.
+//!
+//! Recording is best-effort: a missing or corrupt sidecar never affects the
+//! hook decision (a corrupt one is quarantined and started over), and a hook that finds the sidecar locked by another process
+//! skips the update instead of waiting. A failed update is remembered next to
+//! the sidecar so `dcg doctor` can report accounting that keeps failing.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::allowlist::{AllowEntry, AllowSelector};
+use crate::config::resolve_config_path_value;
+use crate::storage::{self, StateLock};
+
+/// Environment override for the usage sidecar path.
+pub const ENV_ALLOWLIST_USAGE_PATH: &str = "DCG_ALLOWLIST_USAGE_PATH";
+
+const ALLOWLIST_USAGE_FILE: &str = "allowlist_usage.json";
+const FAILURE_SUFFIX: &str = ".failed";
+const SCHEMA_VERSION: u32 = 1;
+
+/// Usage counters for one allowlist entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EntryUsage {
+    pub count: u64,
+    pub first_used: String,
+    pub last_used: String,
+}
+
+/// Contents of the usage sidecar.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UsageState {
+    pub schema_version: u32,
+    /// When accounting started; never-used entries are only stale relative to this.
+    pub tracking_since: String,
+    #[serde(default)]
+    pub entries: BTreeMap<String, EntryUsage>,
+}
+
+impl UsageState {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            tracking_since: storage::format_timestamp(now),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Usage recorded for a key, if the entry has ever fired.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&EntryUsage> {
+        self.entries.get(key)
+    }
+
+    /// Whether an entry has gone unused since `cutoff`.
+    ///
+    /// Entries that fired are judged by their last use. Entries that never
+    /// fired are only stale once both accounting and the entry itself
+    /// (`added_at`, when present) predate the cutoff.
+    #[must_use]
+    pub fn is_stale(&self, key: &str, entry: &AllowEntry, cutoff: DateTime<Utc>) -> bool {
+        if let Some(usage) = self.get(key) {
+            return storage::parse_timestamp(&usage.last_used).is_some_and(|last| last < cutoff);
+        }
+        let tracked_before =
+            storage::parse_timestamp(&self.tracking_since).is_some_and(|t| t < cutoff);
+        let added_before = entry
+            .added_at
+            .as_deref()
+            .is_none_or(|added| storage::parse_timestamp(added).is_some_and(|t| t < cutoff));
+        tracked_before && added_before
+    }
+}
+
+/// Stable usage key for an entry in a given allowlist file.
+#[must_use]
+pub fn usage_key(path: &Path, selector: &AllowSelector) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update([0]);
+    hasher.update(selector.kind_label().as_bytes());
+    hasher.update([0]);
+    hasher.update(selector.value().as_bytes());
+    let digest = hasher.finalize();
+    let mut key = String::with_capacity(16);
+    for byte in &digest[..8] {
+        let _ = write!(key, "{byte:02x}");
+    }
+    key
+}
+
+/// JSON-backed usage store.
+#[derive(Debug, Clone)]
+pub struct AllowlistUsageStore {
+    path: PathBuf,
+}
+
+impl AllowlistUsageStore {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resolve the default path (env override, else the dcg state directory).
+    #[must_use]
+    pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_ALLOWLIST_USAGE_PATH) {
+            if let Some(path) = resolve_config_path_value(&value, cwd) {
+                return path;
+            }
+        }
+
+        crate::paths::state_file(ALLOWLIST_USAGE_FILE)
+    }
+
+    /// Load the current state. A missing or unreadable sidecar yields an
+    /// empty state whose tracking starts now.
+    #[must_use]
+    pub fn load(&self, now: DateTime<Utc>) -> UsageState {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| UsageState::new(now))
+    }
+
+    /// Record one use of the entry identified by `key`.
+    ///
+    /// Returns `Ok(false)` without waiting if another process holds the lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn record(&self, key: &str, now: DateTime<Utc>) -> io::Result<bool> {
+        self.update(now, false, |state| {
+            let stamp = storage::format_timestamp(now);
+            state
+                .entries
+                .entry(key.to_string())
+                .and_modify(|usage| {
+                    usage.count += 1;
+                    usage.last_used.clone_from(&stamp);
+                })
+                .or_insert_with(|| EntryUsage {
+                    count: 1,
+                    first_used: stamp.clone(),
+                    last_used: stamp.clone(),
+                });
+        })
+    }
+
+    /// Drop usage for entries that no longer exist (e.g. after pruning).
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn forget(&self, keys: &[String], now: DateTime<Utc>) -> io::Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        self.update(now, true, |state| {
+            for key in keys {
+                state.entries.remove(key);
+            }
+        })
+        .map(|_| ())
+    }
+
+    /// Where the last failed [`record`](Self::record) is remembered.
+    #[must_use]
+    pub fn failure_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(FAILURE_SUFFIX);
+        PathBuf::from(path)
+    }
+
+    /// The last failed update (`<timestamp> <error>`), if the most recent
+    /// recorded use could not be written.
+    #[must_use]
+    pub fn last_failure(&self) -> Option<String> {
+        std::fs::read_to_string(self.failure_path())
+            .ok()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Apply `apply` under the sidecar lock. With `wait` unset, returns
+    /// `Ok(false)` instead of blocking when the lock is held elsewhere.
+    fn update(
+        &self,
+        now: DateTime<Utc>,
+        wait: bool,
+        apply: impl FnOnce(&mut UsageState),
+    ) -> io::Result<bool> {
+        let _lock = if wait {
+            StateLock::acquire(&self.path)?
+        } else {
+            let Some(lock) = StateLock::try_acquire(&self.path)? else {
+                return Ok(false);
+            };
+            lock
+        };
+        let mut state: UsageState =
+            storage::read_json(&self.path)?.unwrap_or_else(|| UsageState::new(now));
+        apply(&mut state);
+
+        storage::write_json(&self.path, &state)?;
+        Ok(true)
+    }
+}
+
+/// Record a use in the default store. Failures never reach the caller (hook
+/// paths must not fail on accounting); they are remembered for `dcg doctor`.
+pub fn record_use(key: &str, cwd: Option<&Path>) {
+    let store = AllowlistUsageStore::new(AllowlistUsageStore::default_path(cwd));
+    record_outcome(&store, store.record(key, Utc::now()));
+}
+
+fn record_outcome(store: &AllowlistUsageStore, outcome: io::Result<bool>) {
+    match outcome {
+        Ok(true) => {
+            let _ = std::fs::remove_file(store.failure_path());
+        }
+        // Busy: another process is updating; nothing failed.
+        Ok(false) => {}
+        Err(err) => {
+            let _ = std::fs::write(
+                store.failure_path(),
+                format!("{} {err}", storage::format_timestamp(Utc::now())),
+            );
+        }
+    }
+}
+
+/// Cutoff for `--unused-for <duration>` (e.g. "90d", "12w").
+///
+/// # Errors
+///
+/// Returns an error if the duration is not a valid allowlist TTL.
+pub fn unused_cutoff(unused_for: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let window: TimeDelta = crate::allowlist::parse_duration(unused_for)?;
+    Ok(now - window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allowlist::{PathMatch, RuleId};
+
+    fn entry(added_at: Option<&str>) -> AllowEntry {
+        AllowEntry {
+            selector: AllowSelector::Rule(RuleId::parse("core.git:reset-hard").unwrap()),
+            reason: "test".to_string(),
+            added_by: None,
+            added_at: added_at.map(str::to_string),
+            expires_at: None,
+            ttl: None,
+            session: None,
+            context: None,
+            conditions: std::collections::HashMap::new(),
+            environments: Vec::new(),
+            active_hours: None,
+            active_dates: Vec::new(),
+            hosts: Vec::new(),
+            users: Vec::new(),
+            arg_conditions: BTreeMap::new(),
+            paths: None,
+            path_match: PathMatch::Cwd,
+            risk_acknowledged: false,
+        }
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        storage::parse_timestamp(value).unwrap()
+    }
+
+    #[test]
+    fn usage_key_depends_on_file_and_selector() {
+        let rule = AllowSelector::Rule(RuleId::parse("core.git:reset-hard").unwrap());
+        let prefix = AllowSelector::CommandPrefix("core.git:reset-hard".to_string());
+        let a = Path::new("/repo/.dcg/allowlist.toml");
+        let b = Path::new("/home/u/.config/dcg/allowlist.toml");
+
+        assert_eq!(usage_key(a, &rule), usage_key(a, &rule));
+        assert_eq!(usage_key(a, &rule).len(), 16);
+        assert_ne!(usage_key(a, &rule), usage_key(b, &rule));
+        assert_ne!(usage_key(a, &rule), usage_key(a, &prefix));
+    }
+
+    #[test]
+    fn record_counts_uses_and_forget_drops_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AllowlistUsageStore::new(dir.path().join("usage.json"));
+
+        store.record("k1", at("2030-01-01T00:00:00Z")).unwrap();
+        store.record("k1", at("2030-02-01T00:00:00Z")).unwrap();
+        store.record("k2", at("2030-03-01T00:00:00Z")).unwrap();
+
+        let state = store.load(at("2031-01-01T00:00:00Z"));
+        assert_eq!(state.tracking_since, "2030-01-01T00:00:00Z");
+        let usage = state.get("k1").unwrap();
+        assert_eq!(usage.count, 2);
+        assert_eq!(usage.first_used, "2030-01-01T00:00:00Z");
+        assert_eq!(usage.last_used, "2030-02-01T00:00:00Z");
+
+        store
+            .forget(&["k1".to_string()], at("2031-01-01T00:00:00Z"))
+            .unwrap();
+        let state = store.load(at("2031-01-01T00:00:00Z"));
+        assert!(state.get("k1").is_none());
+        assert_eq!(state.get("k2").unwrap().count, 1);
+    }
+
+    #[test]
+    fn record_skips_a_busy_sidecar_and_remembers_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AllowlistUsageStore::new(dir.path().join("usage.json"));
+
+        let holder = StateLock::acquire(store.path()).unwrap();
+        assert!(!store.record("k1", at("2030-01-01T00:00:00Z")).unwrap());
+        drop(holder);
+        assert!(store.record("k1", at("2030-01-02T00:00:00Z")).unwrap());
+        assert_eq!(
+            store
+                .load(at("2030-01-03T00:00:00Z"))
+                .get("k1")
+                .unwrap()
+                .count,
+            1
+        );
+
+        record_outcome(&store, Err(io::Error::other("disk full")));
+        assert!(store.last_failure().unwrap().ends_with(" disk full"));
+        record_outcome(&store, Ok(false));
+        assert!(store.last_failure().is_some());
+        record_outcome(&store, Ok(true));
+        assert!(store.last_failure().is_none());
+    }
+
+    #[test]
+    fn corrupt_sidecar_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        std::fs::write(&path, "not json").unwrap();
+        let store = AllowlistUsageStore::new(path);
+
+        store.record("k1", at("2030-01-01T00:00:00Z")).unwrap();
+        assert_eq!(
+            store
+                .load(at("2030-01-02T00:00:00Z"))
+                .get("k1")
+                .unwrap()
+                .count,
+            1
+        );
+    }
+
+    #[test]
+    fn staleness_uses_last_use_then_tracking_and_added_at() {
+        let mut state = UsageState::new(at("2030-01-01T00:00:00Z"));
+        state.entries.insert(
+            "used".to_string(),
+            EntryUsage {
+                count: 3,
+                first_used: "2030-01-05T00:00:00Z".to_string(),
+                last_used: "2030-02-01T00:00:00Z".to_string(),
+            },
+        );
+        let cutoff = unused_cutoff("90d", at("2030-06-01T00:00:00Z")).unwrap();
+
+        assert!(state.is_stale("used", &entry(None), cutoff));
+        assert!(!state.is_stale(
+            "used",
+            &entry(None),
+            unused_cutoff("90d", at("2030-03-01T00:00:00Z")).unwrap()
+        ));
+
+        // Never used: stale only when tracking and the entry predate the cutoff.
+        assert!(state.is_stale("never", &entry(None), cutoff));
+        assert!(state.is_stale("never", &entry(Some("2029-06-01")), cutoff));
+        assert!(!state.is_stale("never", &entry(Some("2030-05-01T00:00:00Z")), cutoff));
+
+        let fresh = UsageState::new(at("2030-05-20T00:00:00Z"));
+        assert!(!fresh.is_stale("never", &entry(None), cutoff));
+    }
+}
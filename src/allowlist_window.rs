@@ -0,0 +1,287 @@
+//! Time-window conditions on allowlist entries.
+//!
+//! `active_hours = "Mon-Fri 09:00-18:00 Europe/Kyiv"` limits an entry to a
+//! weekly window and `active_dates = ["2026-03-14", "2026-04-01..2026-04-03"]`
+//! to specific days, so a risky exception only applies during a maintenance
+//! window. Both are validated when the allowlist is loaded; an entry outside
+//! its window is skipped like an expired one.
+//!
+//! Hours are read in the named IANA time zone (UTC if none is given). A window
+//! whose end is earlier than its start runs past midnight and belongs to the
+//! day it starts on. Dates are inclusive and read in the `active_hours` time
+//! zone, or UTC.
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::allowlist::AllowEntry;
+
+const DAY_NAMES: [(&str, &str, Weekday); 7] = [
+    ("mon", "monday", Weekday::Mon),
+    ("tue", "tuesday", Weekday::Tue),
+    ("wed", "wednesday", Weekday::Wed),
+    ("thu", "thursday", Weekday::Thu),
+    ("fri", "friday", Weekday::Fri),
+    ("sat", "saturday", Weekday::Sat),
+    ("sun", "sunday", Weekday::Sun),
+];
+
+/// A parsed `active_hours` value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveHours {
+    /// Bit `n` set when the window opens on `Weekday::num_days_from_monday() == n`.
+    days: u8,
+    /// Minutes since midnight.
+    start: u32,
+    /// Minutes since midnight; `1440` for `24:00`.
+    end: u32,
+    tz: Tz,
+}
+
+impl ActiveHours {
+    /// Parse `"<days> <HH:MM>-<HH:MM> [<time zone>]"`, where days are
+    /// comma-separated names or ranges (`Mon-Fri`, `Sat,Sun`, `Fri-Mon`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming the part that could not be parsed.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut parts = value.split_whitespace();
+        let (Some(days), Some(hours)) = (parts.next(), parts.next()) else {
+            return Err(format!(
+                "active_hours must look like \"Mon-Fri 09:00-18:00 Europe/Kyiv\", got \"{value}\""
+            ));
+        };
+        let tz = match parts.next() {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| format!("active_hours: unknown time zone \"{name}\""))?,
+            None => Tz::UTC,
+        };
+        if parts.next().is_some() {
+            return Err(format!("active_hours: unexpected text in \"{value}\""));
+        }
+
+        let (start, end) = hours
+            .split_once('-')
+            .ok_or_else(|| format!("active_hours: expected HH:MM-HH:MM, got \"{hours}\""))?;
+        let start = parse_minutes(start, false)?;
+        let end = parse_minutes(end, true)?;
+        if start == end {
+            return Err(format!("active_hours: empty window \"{hours}\""));
+        }
+
+        Ok(Self {
+            days: parse_days(days)?,
+            start,
+            end,
+            tz,
+        })
+    }
+
+    /// The time zone hours (and `active_dates`) are read in.
+    #[must_use]
+    pub const fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// Whether `now` falls inside the window.
+    #[must_use]
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.tz);
+        let minute = local.hour() * 60 + local.minute();
+        let opens_on = |day: Weekday| self.days & (1 << day.num_days_from_monday()) != 0;
+        if self.start < self.end {
+            opens_on(local.weekday()) && (self.start..self.end).contains(&minute)
+        } else {
+            (opens_on(local.weekday()) && minute >= self.start)
+                || (opens_on(local.weekday().pred()) && minute < self.end)
+        }
+    }
+}
+
+/// A parsed `active_dates` item: one date or an inclusive `from..to` range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateSpan {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+impl DateSpan {
+    /// Parse `"YYYY-MM-DD"` or `"YYYY-MM-DD..YYYY-MM-DD"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if a date is invalid or the range runs backwards.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let date = |text: &str| {
+            NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("active_dates: invalid date \"{}\"", text.trim()))
+        };
+        let (from, to) = match value.split_once("..") {
+            Some((from, to)) => (date(from)?, date(to)?),
+            None => {
+                let day = date(value)?;
+                (day, day)
+            }
+        };
+        if to < from {
+            return Err(format!(
+                "active_dates: range \"{value}\" ends before it starts"
+            ));
+        }
+        Ok(Self { from, to })
+    }
+
+    #[must_use]
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        (self.from..=self.to).contains(&date)
+    }
+}
+
+/// Validate an entry's `active_hours` and `active_dates` at load time.
+///
+/// # Errors
+///
+/// Returns the first parse error.
+pub fn validate(active_hours: Option<&str>, active_dates: &[String]) -> Result<(), String> {
+    if let Some(hours) = active_hours {
+        ActiveHours::parse(hours)?;
+    }
+    for item in active_dates {
+        DateSpan::parse(item)?;
+    }
+    Ok(())
+}
+
+/// Check if `now` is inside an entry's time window.
+///
+/// Entries without `active_hours` or `active_dates` always are; an entry whose
+/// window fails to parse never is.
+#[must_use]
+pub fn window_met(entry: &AllowEntry, now: DateTime<Utc>) -> bool {
+    let hours = match entry.active_hours.as_deref().map(ActiveHours::parse) {
+        None => None,
+        Some(Ok(hours)) => Some(hours),
+        Some(Err(_)) => return false,
+    };
+    if hours.as_ref().is_some_and(|hours| !hours.contains(now)) {
+        return false;
+    }
+    if entry.active_dates.is_empty() {
+        return true;
+    }
+
+    let tz = hours.as_ref().map_or(Tz::UTC, ActiveHours::tz);
+    let today = now.with_timezone(&tz).date_naive();
+    entry
+        .active_dates
+        .iter()
+        .any(|item| DateSpan::parse(item).is_ok_and(|span| span.contains(today)))
+}
+
+/// Human-readable window for traces, e.g.
+/// `Mon-Fri 09:00-18:00 Europe/Kyiv; dates 2026-03-14`.
+#[must_use]
+pub fn describe(entry: &AllowEntry) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(hours) = entry.active_hours.as_deref() {
+        parts.push(hours.trim().to_string());
+    }
+    if !entry.active_dates.is_empty() {
+        parts.push(format!("dates {}", entry.active_dates.join(", ")));
+    }
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+fn parse_minutes(text: &str, allow_end_of_day: bool) -> Result<u32, String> {
+    if allow_end_of_day && text == "24:00" {
+        return Ok(24 * 60);
+    }
+    NaiveTime::parse_from_str(text, "%H:%M")
+        .map(|time| time.hour() * 60 + time.minute())
+        .map_err(|_| format!("active_hours: invalid time \"{text}\""))
+}
+
+fn parse_days(text: &str) -> Result<u8, String> {
+    let day = |name: &str| {
+        DAY_NAMES
+            .iter()
+            .find(|(short, full, _)| {
+                name.eq_ignore_ascii_case(short) || name.eq_ignore_ascii_case(full)
+            })
+            .map(|(_, _, day)| *day)
+            .ok_or_else(|| format!("active_hours: unknown day \"{name}\""))
+    };
+
+    let mut days = 0u8;
+    for item in text.split(',') {
+        let (first, last) = match item.split_once('-') {
+            Some((first, last)) => (day(first)?, day(last)?),
+            None => {
+                let single = day(item)?;
+                (single, single)
+            }
+        };
+        let mut current = first;
+        loop {
+            days |= 1 << current.num_days_from_monday();
+            if current == last {
+                break;
+            }
+            current = current.succ();
+        }
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn business_hours_in_a_time_zone() {
+        let hours = ActiveHours::parse("Mon-Fri 09:00-18:00 Europe/Kyiv").unwrap();
+        // 2030-01-07 is a Monday; Kyiv is UTC+2 in January.
+        assert!(hours.contains(at("2030-01-07T07:00:00Z")));
+        assert!(!hours.contains(at("2030-01-07T06:59:00Z")));
+        assert!(!hours.contains(at("2030-01-07T16:00:00Z")));
+        assert!(!hours.contains(at("2030-01-05T10:00:00Z")));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_its_start_day() {
+        let hours = ActiveHours::parse("sat,sunday 22:00-02:00").unwrap();
+        assert!(hours.contains(at("2030-01-05T23:00:00Z")));
+        assert!(hours.contains(at("2030-01-07T01:00:00Z")));
+        assert!(!hours.contains(at("2030-01-08T01:00:00Z")));
+        assert!(!hours.contains(at("2030-01-05T01:00:00Z")));
+    }
+
+    #[test]
+    fn rejects_malformed_hours_and_dates() {
+        for bad in [
+            "Mon-Fri",
+            "Mon-Fri 9-17",
+            "Mon-Fri 09:00-09:00",
+            "Someday 09:00-17:00",
+            "Mon 09:00-17:00 Mars/Olympus",
+            "Mon 09:00-17:00 UTC extra",
+        ] {
+            assert!(ActiveHours::parse(bad).is_err(), "{bad}");
+        }
+        assert!(DateSpan::parse("2030-02-30").is_err());
+        assert!(DateSpan::parse("2030-01-03..2030-01-01").is_err());
+        assert!(
+            DateSpan::parse("2030-01-01..2030-01-03")
+                .unwrap()
+                .contains(NaiveDate::from_ymd_opt(2030, 1, 3).unwrap())
+        );
+    }
+}
@@ -0,0 +1,717 @@
+//! Embedding API: evaluate commands from other Rust tools.
+//!
+//! [`Guard`] wraps the same evaluation pipeline the hook uses (config
+//! overrides, canaries, allow-once entries, allowlists, packs, heredoc
+//! scanning, severity aggregation, `[policy]` modes, confidence scoring)
+//! behind a small surface for custom agent runners and CI bots:
+//!
+//! ```
+//! use destructive_command_guard::api::{Action, Context, Guard};
+//!
+//! let guard = Guard::builder()
+//!     .enable_pack("containers.docker")
+//!     .build()
+//!     .expect("known pack IDs");
+//!
+//! let decision = guard.evaluate("git reset --hard HEAD~3", &Context::new());
+//! assert_eq!(decision.action, Action::Deny);
+//! assert_eq!(
+//!     decision.rule.unwrap().rule_id.as_deref(),
+//!     Some("core.git:reset-hard")
+//! );
+//! ```
+//!
+//! # Side effects
+//!
+//! [`Guard::evaluate`] never writes to stdout or stderr, never records
+//! history, and never sends webhooks; what to do with a [`Decision`] is up to
+//! the caller. It does honor allow-once entries granted with `dcg allow-once`
+//! for the [`Context`]'s working directory, like the hook.
+//!
+//! A builder starts from compiled defaults (core packs, no allowlists) and
+//! reads no files. [`GuardBuilder::load_config`] and
+//! [`GuardBuilder::load_allowlists`] opt into the files `dcg` itself reads;
+//! like `dcg`, those loaders report unreadable or malformed files on stderr.
+//!
+//! # Stability
+//!
+//! Items in this module follow semantic versioning: they are only changed
+//! in backwards-compatible ways within a major version. Structs and enums are
+//! `#[non_exhaustive]`, so new fields, actions, and builder options can be
+//! added in minor releases. Everything outside `api` (including the
+//! [`Config`] and [`LayeredAllowlist`] types accepted here) is the
+//! implementation of the `dcg` binary and may change in any release.
+//!
+//! Build with `default-features = false` to leave out the `cli` feature
+//! (argument parsing, terminal rendering, self-update, and the MCP/LSP/admin
+//! servers).
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::allowlist::{AllowlistLayer, LayeredAllowlist};
+use crate::config::{CompiledOverrides, Config, HeredocSettings};
+use crate::evaluator::{
+    EvalContext, EvaluationResult, PatternMatch, apply_severity_aggregation, evaluate_in_context,
+    resolve_decision_for_agent,
+};
+use crate::packs::{DecisionMode, EnabledKeywordIndex, REGISTRY, load_external_packs};
+use crate::perf::Deadline;
+
+pub use crate::packs::Severity;
+pub use crate::provenance::Provenance;
+
+/// Errors from [`GuardBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GuardError {
+    /// A pack ID or category passed to the builder does not exist.
+    UnknownPack(String),
+    /// A config passed to [`GuardBuilder::config_toml`] did not parse.
+    InvalidConfig(String),
+}
+
+impl std::fmt::Display for GuardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownPack(id) => write!(f, "unknown pack '{id}'"),
+            Self::InvalidConfig(e) => write!(f, "invalid config: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GuardError {}
+
+/// Builder for [`Guard`].
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct GuardBuilder {
+    config: Option<Result<Config, GuardError>>,
+    enable: Vec<String>,
+    disable: Vec<String>,
+    allowlists: Option<LayeredAllowlist>,
+    agent_profile: Option<String>,
+    timeout: Option<Duration>,
+    ignore_allow_once: bool,
+}
+
+impl GuardBuilder {
+    /// Start from compiled defaults: core packs, no allowlists, no deadline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `config` as the base policy.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(Ok(config));
+        self
+    }
+
+    /// Parse `toml` (the `config.toml` format) as the base policy.
+    ///
+    /// Parse errors surface from [`build`](Self::build).
+    pub fn config_toml(mut self, toml: &str) -> Self {
+        self.config =
+            Some(toml::from_str(toml).map_err(|e| GuardError::InvalidConfig(e.to_string())));
+        self
+    }
+
+    /// Load the same layered config as `dcg` (env, project, user, system).
+    pub fn load_config(mut self) -> Self {
+        self.config = Some(Ok(Config::load()));
+        self
+    }
+
+    /// Enable a pack or category (e.g. `database.postgresql`, `kubernetes`).
+    pub fn enable_pack(mut self, id: impl Into<String>) -> Self {
+        self.enable.push(id.into());
+        self
+    }
+
+    /// Disable a pack or category.
+    pub fn disable_pack(mut self, id: impl Into<String>) -> Self {
+        self.disable.push(id.into());
+        self
+    }
+
+    /// Use `allowlists` instead of none.
+    ///
+    /// Entry `environments` are gated against this guard's config
+    /// (`general.environment`), not whatever `allowlists` carried.
+    pub fn allowlists(mut self, allowlists: LayeredAllowlist) -> Self {
+        self.allowlists = Some(allowlists);
+        self
+    }
+
+    /// Load the same project/user/system allowlists as `dcg`.
+    pub fn load_allowlists(self) -> Self {
+        self.allowlists(crate::load_default_allowlists())
+    }
+
+    /// Apply the `[agents.<name>]` profile from the config: its
+    /// `disabled_packs` and `extra_packs`, `disabled_allowlist`, and
+    /// `severity_modes`. Unknown names fall back like agent detection does
+    /// (`unknown`, then `default`).
+    pub fn agent_profile(mut self, name: impl Into<String>) -> Self {
+        self.agent_profile = Some(name.into());
+        self
+    }
+
+    /// Stop deep analysis after `timeout` and allow (fail open), as the hook
+    /// does with `hook_timeout_ms`. Without a timeout, evaluation always
+    /// completes.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Ignore `dcg allow-once` entries, for judging a command rather than
+    /// deciding whether it may run now.
+    pub(crate) const fn ignore_allow_once(mut self) -> Self {
+        self.ignore_allow_once = true;
+        self
+    }
+
+    /// Compile the policy into a [`Guard`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GuardError::UnknownPack`] for pack IDs passed to
+    /// [`enable_pack`](Self::enable_pack) or [`disable_pack`](Self::disable_pack)
+    /// that match no built-in pack or category, and
+    /// [`GuardError::InvalidConfig`] when [`config_toml`](Self::config_toml)
+    /// failed to parse.
+    pub fn build(self) -> Result<Guard, GuardError> {
+        let mut config = self.config.transpose()?.unwrap_or_default();
+
+        let categories = REGISTRY.all_categories();
+        for id in self.enable.iter().chain(&self.disable) {
+            if REGISTRY.get(id).is_none() && !categories.contains(&id) {
+                return Err(GuardError::UnknownPack(id.clone()));
+            }
+        }
+        config.packs.enabled.extend(self.enable);
+        config.packs.disabled.extend(self.disable);
+
+        let mut enabled_packs = match &self.agent_profile {
+            Some(name) => config.enabled_pack_ids_for_profile(name),
+            None => config.enabled_pack_ids(),
+        };
+        let allowlist_disabled = self
+            .agent_profile
+            .as_deref()
+            .is_some_and(|name| config.agents.allowlist_disabled_for(name));
+        let mut keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
+
+        // External packs from `custom_paths` are enabled by being listed, as in the hook.
+        let external = load_external_packs(&config.packs.expand_custom_paths());
+        enabled_packs.extend(external.pack_ids().cloned());
+        keywords.extend(external.keywords().iter().copied());
+
+        let mut ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
+        for id in external.pack_ids() {
+            if !ordered_packs.contains(id) {
+                ordered_packs.push(id.clone());
+            }
+        }
+        let keyword_index = if external.pack_ids().next().is_some() {
+            None
+        } else {
+            REGISTRY.build_enabled_keyword_index(&ordered_packs)
+        };
+
+        let mut overrides = config.compile_overrides();
+        overrides.skip_allow_once = self.ignore_allow_once;
+
+        Ok(Guard {
+            overrides,
+            heredoc_settings: config.heredoc_settings(),
+            allowlists: if allowlist_disabled {
+                LayeredAllowlist::default()
+            } else {
+                self.allowlists
+                    .unwrap_or_default()
+                    .with_configured_environment(config.general.environment.clone())
+            },
+            timeout: self.timeout,
+            agent_profile: self.agent_profile,
+            keywords,
+            ordered_packs,
+            keyword_index,
+            config,
+        })
+    }
+}
+
+/// A compiled policy. Cheap to share across threads; build once and reuse.
+#[derive(Debug)]
+pub struct Guard {
+    config: Config,
+    overrides: CompiledOverrides,
+    heredoc_settings: HeredocSettings,
+    allowlists: LayeredAllowlist,
+    keywords: Vec<&'static str>,
+    ordered_packs: Vec<String>,
+    keyword_index: Option<EnabledKeywordIndex>,
+    timeout: Option<Duration>,
+    agent_profile: Option<String>,
+}
+
+impl Guard {
+    /// Start building a guard.
+    pub fn builder() -> GuardBuilder {
+        GuardBuilder::new()
+    }
+
+    /// IDs of the packs this guard evaluates, in evaluation order.
+    #[must_use]
+    pub fn packs(&self) -> &[String] {
+        &self.ordered_packs
+    }
+
+    /// Evaluate `command` as it would run in `context`.
+    #[must_use]
+    pub fn evaluate(&self, command: &str, context: &Context) -> Decision {
+        let result = self.evaluate_result(command, context);
+        let mode = resolve_decision_for_agent(
+            &self.config,
+            command,
+            &result,
+            context.provenance,
+            self.agent_profile.as_deref(),
+        )
+        .map(|decision| decision.mode);
+        Decision::from_result(result, mode)
+    }
+
+    /// The config this guard was built from.
+    #[cfg(feature = "napi")]
+    pub(crate) const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The raw evaluator result behind [`evaluate`](Self::evaluate), before
+    /// policy modes apply.
+    pub(crate) fn evaluate_result(&self, command: &str, context: &Context) -> EvaluationResult {
+        let deadline = self.timeout.map(Deadline::new);
+        let mut eval = context.eval_context(command, deadline.as_ref());
+        eval.agent.clone_from(&self.agent_profile);
+        let result = evaluate_in_context(
+            &eval,
+            &self.keywords,
+            &self.ordered_packs,
+            self.keyword_index.as_ref(),
+            &self.overrides,
+            &self.allowlists,
+            &self.heredoc_settings,
+        );
+        apply_severity_aggregation(result, self.config.policy().aggregation())
+    }
+}
+
+/// Guards built from the loaded config and allowlists, one per agent profile,
+/// shared by the language bindings.
+#[cfg(any(feature = "pyo3", feature = "napi"))]
+static SHARED_GUARDS: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::HashMap<Option<String>, std::sync::Arc<Guard>>>,
+> = std::sync::LazyLock::new(Default::default);
+
+/// The shared guard for `profile`, built on first use with
+/// [`GuardBuilder::load_config`] and [`GuardBuilder::load_allowlists`].
+#[cfg(any(feature = "pyo3", feature = "napi"))]
+pub(crate) fn shared_guard(profile: Option<&str>) -> Result<std::sync::Arc<Guard>, GuardError> {
+    let mut guards = SHARED_GUARDS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let key = profile.map(str::to_string);
+    if let Some(guard) = guards.get(&key) {
+        return Ok(std::sync::Arc::clone(guard));
+    }
+    let mut builder = Guard::builder().load_config().load_allowlists();
+    if let Some(name) = profile {
+        builder = builder.agent_profile(name);
+    }
+    let guard = std::sync::Arc::new(builder.build()?);
+    guards.insert(key, std::sync::Arc::clone(&guard));
+    Ok(guard)
+}
+
+/// Drop the shared guards so the next call rereads config and allowlists.
+#[cfg(any(feature = "pyo3", feature = "napi"))]
+pub(crate) fn reload_shared_guards() {
+    SHARED_GUARDS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clear();
+}
+
+/// Where and by whom a command is being run.
+///
+/// Unset fields fall back to the calling process: its working directory,
+/// its environment, and its `DCG_SESSION_ID`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+#[must_use]
+pub struct Context {
+    /// Directory the command would run in (allow-once scope, project-scoped
+    /// allowlists).
+    pub cwd: Option<PathBuf>,
+    /// Who proposed the command, for `[policy.provenance.*]`. Defaults to
+    /// [`Provenance::Agent`].
+    pub provenance: Provenance,
+    /// Environment the command would run with.
+    pub env: Option<BTreeMap<String, String>>,
+    /// Agent session, for session-scoped allow-once grants.
+    pub session_id: Option<String>,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self {
+            cwd: None,
+            provenance: Provenance::Agent,
+            env: None,
+            session_id: None,
+        }
+    }
+}
+
+impl Context {
+    /// An agent-proposed command with no working directory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the working directory.
+    pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Set who proposed the command.
+    pub const fn provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Set the environment the command would run with.
+    pub fn env<K, V>(mut self, env: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env = Some(
+            env.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Set the agent session.
+    pub fn session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// The evaluator context for `command` run here.
+    fn eval_context<'a>(
+        &self,
+        command: &'a str,
+        deadline: Option<&'a Deadline>,
+    ) -> EvalContext<'a> {
+        EvalContext {
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            provenance: self.provenance,
+            session_id: self.session_id.clone(),
+            deadline,
+            ..EvalContext::new(command)
+        }
+    }
+}
+
+/// What to do with a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Action {
+    /// Run it.
+    Allow,
+    /// Run it; a rule matched but policy only records it.
+    Log,
+    /// Run it, but tell the user why it is risky.
+    Warn,
+    /// Do not run it.
+    Deny,
+}
+
+/// The verdict for one command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Decision {
+    pub action: Action,
+    /// The deciding rule: the highest-severity match for `Deny`/`Warn`/`Log`,
+    /// or the allowlisted match for an allowlisted `Allow`.
+    pub rule: Option<RuleMatch>,
+    /// Further rules the command tripped, highest severity first.
+    pub other_rules: Vec<RuleMatch>,
+    /// Allowlist layer that allowed an otherwise matching command.
+    pub allowlisted_by: Option<AllowlistLayer>,
+    /// Evaluation hit the [`GuardBuilder::timeout`] and failed open.
+    pub timed_out: bool,
+}
+
+impl Decision {
+    /// Whether the command should be blocked.
+    #[must_use]
+    pub fn is_denied(&self) -> bool {
+        self.action == Action::Deny
+    }
+
+    fn from_result(result: EvaluationResult, mode: Option<DecisionMode>) -> Self {
+        let other_rules = result.other_matches.iter().map(RuleMatch::from).collect();
+        if let Some(allowlisted) = result.allowlist_override {
+            return Self {
+                action: Action::Allow,
+                rule: Some(RuleMatch::from(&allowlisted.matched)),
+                other_rules,
+                allowlisted_by: Some(allowlisted.layer),
+                timed_out: false,
+            };
+        }
+
+        let action = if result.is_denied() {
+            match mode.unwrap_or(DecisionMode::Deny) {
+                // Library callers have no hook prompt to ask through.
+                DecisionMode::Deny | DecisionMode::Ask => Action::Deny,
+                DecisionMode::Warn | DecisionMode::WarnOnce => Action::Warn,
+                DecisionMode::Log => Action::Log,
+            }
+        } else {
+            Action::Allow
+        };
+        Self {
+            action,
+            rule: result
+                .pattern_info
+                .as_ref()
+                .filter(|_| action != Action::Allow)
+                .map(RuleMatch::from),
+            other_rules,
+            allowlisted_by: None,
+            timed_out: result.skipped_due_to_budget,
+        }
+    }
+}
+
+/// A rule that matched a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RuleMatch {
+    /// `pack:pattern` (e.g. `core.git:reset-hard`); `None` for config blocks.
+    pub rule_id: Option<String>,
+    pub pack_id: Option<String>,
+    pub severity: Option<Severity>,
+    /// One-line reason.
+    pub reason: String,
+    /// Longer explanation with safer alternatives, when the rule has one.
+    pub explanation: Option<String>,
+    /// Byte range of the match within the command.
+    pub span: Option<Range<usize>>,
+}
+
+impl From<&PatternMatch> for RuleMatch {
+    fn from(matched: &PatternMatch) -> Self {
+        Self {
+            rule_id: matched
+                .pack_id
+                .as_deref()
+                .zip(matched.pattern_name.as_deref())
+                .map(|(pack, pattern)| format!("{pack}:{pattern}")),
+            pack_id: matched.pack_id.clone(),
+            severity: matched.severity,
+            reason: matched.reason.clone(),
+            explanation: matched.explanation.clone(),
+            span: matched
+                .matched_span
+                .as_ref()
+                .map(|span| span.start..span.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guard_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Guard>();
+    }
+
+    #[test]
+    fn default_guard_denies_core_rules_and_allows_safe_commands() {
+        let guard = Guard::builder().build().unwrap();
+
+        let decision = guard.evaluate("git reset --hard", &Context::new());
+        assert!(decision.is_denied());
+        let rule = decision.rule.unwrap();
+        assert_eq!(rule.rule_id.as_deref(), Some("core.git:reset-hard"));
+        assert_eq!(rule.span, Some(0..16));
+
+        let decision = guard.evaluate("git status", &Context::new());
+        assert_eq!(decision.action, Action::Allow);
+        assert_eq!(decision.rule, None);
+    }
+
+    #[test]
+    fn builder_enables_packs_and_rejects_unknown_ones() {
+        let guard = Guard::builder()
+            .enable_pack("containers.docker")
+            .build()
+            .unwrap();
+        assert!(guard.packs().iter().any(|id| id == "containers.docker"));
+        assert!(
+            guard
+                .evaluate("docker system prune -af", &Context::new())
+                .is_denied()
+        );
+
+        assert_eq!(
+            Guard::builder()
+                .enable_pack("no.such.pack")
+                .build()
+                .unwrap_err(),
+            GuardError::UnknownPack("no.such.pack".to_string())
+        );
+        assert!(matches!(
+            Guard::builder().config_toml("[packs").build(),
+            Err(GuardError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn policy_modes_and_provenance_shape_the_action() {
+        let guard = Guard::builder()
+            .config_toml(
+                r#"
+                [policy.provenance.human.rules]
+                "core.git:reset-hard" = "warn"
+                "#,
+            )
+            .build()
+            .unwrap();
+
+        let agent = guard.evaluate("git reset --hard", &Context::new());
+        assert_eq!(agent.action, Action::Deny);
+
+        let human = guard.evaluate(
+            "git reset --hard",
+            &Context::new().provenance(Provenance::Human),
+        );
+        assert_eq!(human.action, Action::Warn);
+        assert!(human.rule.is_some());
+    }
+
+    fn reset_hard_allowlist() -> LayeredAllowlist {
+        let toml = r#"
+            [[allow]]
+            rule = "core.git:reset-hard"
+            reason = "scratch repo"
+        "#;
+        let file = crate::allowlist::parse_allowlist_toml(
+            AllowlistLayer::Project,
+            std::path::Path::new("allowlist.toml"),
+            toml,
+        );
+        LayeredAllowlist {
+            layers: vec![crate::allowlist::LoadedAllowlistLayer {
+                layer: AllowlistLayer::Project,
+                path: PathBuf::from("allowlist.toml"),
+                file,
+            }],
+            configured_environment: None,
+        }
+    }
+
+    #[test]
+    fn allowlisted_matches_report_the_layer() {
+        let guard = Guard::builder()
+            .allowlists(reset_hard_allowlist())
+            .build()
+            .unwrap();
+
+        let decision = guard.evaluate("git reset --hard", &Context::new());
+        assert_eq!(decision.action, Action::Allow);
+        assert_eq!(decision.allowlisted_by, Some(AllowlistLayer::Project));
+        assert_eq!(
+            decision.rule.unwrap().rule_id.as_deref(),
+            Some("core.git:reset-hard")
+        );
+    }
+
+    #[test]
+    fn guards_gate_allowlists_on_their_own_environment() {
+        let mut allowlists = reset_hard_allowlist();
+        allowlists.layers[0].file.entries[0].environments = vec!["staging".to_string()];
+        let guard_for = |environment: &str| {
+            Guard::builder()
+                .config_toml(&format!("[general]\nenvironment = \"{environment}\"\n"))
+                .allowlists(allowlists.clone())
+                .build()
+                .unwrap()
+        };
+
+        let staging = guard_for("staging");
+        let prod = guard_for("prod");
+        assert_eq!(
+            staging.evaluate("git reset --hard", &Context::new()).action,
+            Action::Allow
+        );
+        assert!(
+            prod.evaluate("git reset --hard", &Context::new())
+                .is_denied()
+        );
+    }
+
+    #[test]
+    fn agent_profile_adjusts_packs_and_allowlists() {
+        let config = r#"
+            [agents.ci-bot]
+            extra_packs = ["containers.docker"]
+            disabled_allowlist = true
+        "#;
+        let guard = Guard::builder()
+            .config_toml(config)
+            .allowlists(reset_hard_allowlist())
+            .agent_profile("ci-bot")
+            .build()
+            .unwrap();
+        assert!(
+            guard
+                .evaluate("docker system prune -af", &Context::new())
+                .is_denied()
+        );
+        assert!(
+            guard
+                .evaluate("git reset --hard", &Context::new())
+                .is_denied()
+        );
+
+        // Names without a profile fall back to `[agents.default]`.
+        let guard = Guard::builder()
+            .config_toml(config)
+            .allowlists(reset_hard_allowlist())
+            .agent_profile("someone-else")
+            .build()
+            .unwrap();
+        assert!(!guard.packs().iter().any(|id| id == "containers.docker"));
+        assert_eq!(
+            guard.evaluate("git reset --hard", &Context::new()).action,
+            Action::Allow
+        );
+    }
+}
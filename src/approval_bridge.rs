@@ -0,0 +1,532 @@
+//! Slack and Matrix bridge for the approvals queue.
+//!
+//! While `dcg serve` runs, every request queued for `dcg approvals` is posted
+//! once to the channels configured under `[approvals]`, with Approve and Deny
+//! links to `{callback_url}/v1/approvals/{id}/{approve|reject}?sig=...`. The
+//! admin API checks `sig` in place of the bearer token: an HMAC-SHA256 keyed
+//! by `DCG_ADMIN_TOKEN` over the decision, request ID, directory, and command,
+//! so a link can only decide the request it was posted for. Opening a link
+//! shows a confirmation page; the decision is made by that page's form POST,
+//! so chat link previews never approve anything.
+//!
+//! The human deciding needs a chat client and nothing else: no shell on the
+//! agent host, no token.
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+
+use crate::approvals::{ApprovalQueueStore, ApprovalRequest, ApprovalStatus};
+use crate::config::ApprovalsConfig;
+
+/// Environment variable holding the Matrix access token.
+pub const ENV_MATRIX_ACCESS_TOKEN: &str = "DCG_MATRIX_ACCESS_TOKEN";
+
+/// How often `dcg serve` checks the queue for new requests.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// What a link does to its request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+impl Decision {
+    /// Path segment in decision links.
+    #[must_use]
+    pub const fn segment(self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Reject => "reject",
+        }
+    }
+
+    #[must_use]
+    pub fn from_segment(segment: &str) -> Option<Self> {
+        match segment {
+            "approve" => Some(Self::Approve),
+            "reject" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+
+    /// Status the request moves to.
+    #[must_use]
+    pub const fn status(self) -> ApprovalStatus {
+        match self {
+            Self::Approve => ApprovalStatus::Approved,
+            Self::Reject => ApprovalStatus::Rejected,
+        }
+    }
+}
+
+/// Signature for a decision link (lowercase hex).
+#[must_use]
+pub fn sign(secret: &str, decision: Decision, request: &ApprovalRequest) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    for part in [
+        decision.segment(),
+        &request.id.to_string(),
+        &request.block.cwd,
+        &request.block.command_raw,
+    ] {
+        mac.update(part.as_bytes());
+        mac.update(&[0]);
+    }
+    let mut hex = String::with_capacity(64);
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// Link that applies `decision` to `request`.
+#[must_use]
+pub fn decision_url(
+    callback_url: &str,
+    secret: &str,
+    decision: Decision,
+    request: &ApprovalRequest,
+) -> String {
+    format!(
+        "{}/v1/approvals/{}/{}?sig={}",
+        callback_url.trim_end_matches('/'),
+        request.id,
+        decision.segment(),
+        sign(secret, decision, request)
+    )
+}
+
+/// A chat destination for approval requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Channel {
+    Slack {
+        webhook_url: String,
+    },
+    Matrix {
+        homeserver: String,
+        room_id: String,
+        access_token: String,
+    },
+}
+
+impl Channel {
+    /// Short name for logs.
+    #[must_use]
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Slack { .. } => "slack",
+            Self::Matrix { .. } => "matrix",
+        }
+    }
+}
+
+/// Posts queued approval requests to the configured channels.
+#[derive(Debug, Clone)]
+pub struct Bridge {
+    callback_url: String,
+    secret: String,
+    channels: Vec<Channel>,
+}
+
+impl Bridge {
+    /// Build the bridge from `[approvals]`. Returns `None` when no channel is
+    /// configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a channel is configured without `callback_url`, or
+    /// a Matrix room without a homeserver or `DCG_MATRIX_ACCESS_TOKEN`.
+    pub fn from_config(
+        config: &ApprovalsConfig,
+        secret: &str,
+        matrix_token: Option<String>,
+    ) -> Result<Option<Self>, String> {
+        let mut channels = Vec::new();
+        if let Some(webhook_url) = config.slack_webhook_url.as_deref() {
+            channels.push(Channel::Slack {
+                webhook_url: webhook_url.to_string(),
+            });
+        }
+        if let Some(room_id) = config.matrix_room_id.as_deref() {
+            let homeserver = config
+                .matrix_homeserver
+                .as_deref()
+                .ok_or("[approvals] matrix_room_id needs matrix_homeserver")?;
+            let access_token = matrix_token
+                .filter(|token| !token.trim().is_empty())
+                .ok_or_else(|| {
+                    format!("[approvals] matrix_room_id needs {ENV_MATRIX_ACCESS_TOKEN}")
+                })?;
+            channels.push(Channel::Matrix {
+                homeserver: homeserver.trim_end_matches('/').to_string(),
+                room_id: room_id.to_string(),
+                access_token,
+            });
+        }
+        if channels.is_empty() {
+            return Ok(None);
+        }
+        let callback_url = config
+            .callback_url
+            .as_deref()
+            .ok_or("[approvals] needs callback_url to post Approve and Deny links")?;
+        Ok(Some(Self {
+            callback_url: callback_url.trim_end_matches('/').to_string(),
+            secret: secret.to_string(),
+            channels,
+        }))
+    }
+
+    #[must_use]
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    fn urls(&self, request: &ApprovalRequest) -> (String, String) {
+        (
+            decision_url(&self.callback_url, &self.secret, Decision::Approve, request),
+            decision_url(&self.callback_url, &self.secret, Decision::Reject, request),
+        )
+    }
+
+    /// Slack incoming-webhook payload: the request plus Approve and Deny
+    /// link buttons.
+    #[must_use]
+    pub fn slack_payload(&self, request: &ApprovalRequest) -> Value {
+        let (approve, reject) = self.urls(request);
+        let block = &request.block;
+        let text = format!(
+            "*dcg blocked a command and is waiting for approval* (request #{})\n\
+             ```{}```\n*Reason:* {}\n*Directory:* {}\n*Agent:* {}",
+            request.id,
+            slack_escape(&block.command_redacted),
+            slack_escape(&block.reason),
+            slack_escape(&block.cwd),
+            slack_escape(block.agent.as_deref().unwrap_or("unknown")),
+        );
+        json!({
+            "text": format!("dcg approval request #{}", request.id),
+            "blocks": [
+                { "type": "section", "text": { "type": "mrkdwn", "text": text } },
+                {
+                    "type": "actions",
+                    "elements": [
+                        {
+                            "type": "button",
+                            "text": { "type": "plain_text", "text": "Approve" },
+                            "style": "primary",
+                            "url": approve,
+                        },
+                        {
+                            "type": "button",
+                            "text": { "type": "plain_text", "text": "Deny" },
+                            "style": "danger",
+                            "url": reject,
+                        },
+                    ],
+                },
+            ],
+        })
+    }
+
+    /// Matrix `m.room.message` content with Approve and Deny links.
+    #[must_use]
+    pub fn matrix_payload(&self, request: &ApprovalRequest) -> Value {
+        let (approve, reject) = self.urls(request);
+        let block = &request.block;
+        let agent = block.agent.as_deref().unwrap_or("unknown");
+        let body = format!(
+            "dcg blocked a command and is waiting for approval (request #{})\n\
+             {}\nReason: {}\nDirectory: {}\nAgent: {agent}\nApprove: {approve}\nDeny: {reject}",
+            request.id, block.command_redacted, block.reason, block.cwd,
+        );
+        let formatted = format!(
+            "<p><strong>dcg blocked a command and is waiting for approval</strong> \
+             (request #{})</p><pre><code>{}</code></pre>\
+             <p>Reason: {}<br>Directory: {}<br>Agent: {}</p>\
+             <p><a href=\"{}\">Approve</a> · <a href=\"{}\">Deny</a></p>",
+            request.id,
+            html_escape(&block.command_redacted),
+            html_escape(&block.reason),
+            html_escape(&block.cwd),
+            html_escape(agent),
+            html_escape(&approve),
+            html_escape(&reject),
+        );
+        json!({
+            "msgtype": "m.text",
+            "body": body,
+            "format": "org.matrix.custom.html",
+            "formatted_body": formatted,
+        })
+    }
+
+    fn post(
+        &self,
+        client: &reqwest::blocking::Client,
+        channel: &Channel,
+        request: &ApprovalRequest,
+    ) -> Result<(), String> {
+        let response = match channel {
+            Channel::Slack { webhook_url } => client
+                .post(webhook_url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(self.slack_payload(request).to_string())
+                .send(),
+            Channel::Matrix {
+                homeserver,
+                room_id,
+                access_token,
+            } => {
+                let txn = format!(
+                    "dcg-approval-{}-{}",
+                    request.id,
+                    chrono::Utc::now().timestamp_millis()
+                );
+                client
+                    .put(format!(
+                        "{homeserver}/_matrix/client/v3/rooms/{}/send/m.room.message/{txn}",
+                        percent_encode(room_id)
+                    ))
+                    .bearer_auth(access_token)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(self.matrix_payload(request).to_string())
+                    .send()
+            }
+        }
+        .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("answered HTTP {status}"))
+        }
+    }
+
+    /// Post every pending request that has not been posted yet; returns how
+    /// many were posted. A request counts as posted once any channel accepts
+    /// it, so a flaky channel does not repeat it on the others.
+    pub fn notify_pending(&self, store: &ApprovalQueueStore) -> usize {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(POST_TIMEOUT)
+            .user_agent(concat!("dcg/", env!("CARGO_PKG_VERSION")))
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                eprintln!("dcg approvals bridge: {err}");
+                return 0;
+            }
+        };
+
+        let queue = store.load();
+        let mut posted = 0;
+        for request in queue
+            .pending()
+            .filter(|request| request.notified_at.is_none())
+        {
+            let mut delivered = false;
+            for channel in &self.channels {
+                match self.post(&client, channel, request) {
+                    Ok(()) => delivered = true,
+                    Err(err) => eprintln!(
+                        "dcg approvals bridge: posting request #{} to {} failed: {err}",
+                        request.id,
+                        channel.label()
+                    ),
+                }
+            }
+            if delivered {
+                posted += 1;
+                let _ = store.mark_notified(request.id, chrono::Utc::now());
+            }
+        }
+        posted
+    }
+
+    /// Poll `store` every [`POLL_INTERVAL`] on a background thread.
+    pub fn spawn(self, store: ApprovalQueueStore) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            loop {
+                self.notify_pending(&store);
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        })
+    }
+}
+
+/// Escape text for an HTML page or Matrix `formatted_body`.
+#[must_use]
+pub fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Slack mrkdwn only needs `&`, `<`, and `>` escaped.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::RedactionConfig;
+    use crate::pending_exceptions::PendingExceptionRecord;
+
+    fn request(id: u64, command: &str) -> ApprovalRequest {
+        ApprovalRequest {
+            id,
+            status: ApprovalStatus::Pending,
+            attempts: 1,
+            first_seen: "2030-01-01T00:00:00Z".to_string(),
+            last_seen: "2030-01-01T00:00:00Z".to_string(),
+            decided_at: None,
+            notified_at: None,
+            block: PendingExceptionRecord::new(
+                chrono::Utc::now(),
+                "/srv/app",
+                command,
+                "core.git:reset-hard - destroys <work>",
+                &RedactionConfig::default(),
+                false,
+                None,
+            ),
+        }
+    }
+
+    fn config() -> ApprovalsConfig {
+        ApprovalsConfig {
+            callback_url: Some("https://dcg.example.com/".to_string()),
+            slack_webhook_url: Some("https://hooks.slack.com/services/x".to_string()),
+            matrix_homeserver: Some("https://matrix.example.org".to_string()),
+            matrix_room_id: Some("!ops:example.org".to_string()),
+        }
+    }
+
+    #[test]
+    fn signatures_bind_decision_request_and_command() {
+        let reset = request(3, "git reset --hard");
+        let approve = sign("s3cret", Decision::Approve, &reset);
+        assert_eq!(approve.len(), 64);
+        assert_eq!(approve, sign("s3cret", Decision::Approve, &reset));
+        assert_ne!(approve, sign("s3cret", Decision::Reject, &reset));
+        assert_ne!(approve, sign("other", Decision::Approve, &reset));
+        assert_ne!(
+            approve,
+            sign("s3cret", Decision::Approve, &request(4, "git reset --hard"))
+        );
+        assert_ne!(
+            approve,
+            sign("s3cret", Decision::Approve, &request(3, "rm -rf /srv"))
+        );
+
+        assert_eq!(
+            decision_url(
+                "https://dcg.example.com/",
+                "s3cret",
+                Decision::Approve,
+                &reset
+            ),
+            format!("https://dcg.example.com/v1/approvals/3/approve?sig={approve}")
+        );
+    }
+
+    #[test]
+    fn bridge_needs_callback_url_and_matrix_settings() {
+        assert!(
+            Bridge::from_config(&ApprovalsConfig::default(), "s", None)
+                .unwrap()
+                .is_none()
+        );
+
+        let bridge = Bridge::from_config(&config(), "s", Some("tok".to_string()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(bridge.channels().len(), 2);
+        assert_eq!(bridge.callback_url, "https://dcg.example.com");
+
+        assert!(Bridge::from_config(&config(), "s", None).is_err());
+        let no_callback = ApprovalsConfig {
+            callback_url: None,
+            ..config()
+        };
+        assert!(Bridge::from_config(&no_callback, "s", Some("tok".to_string())).is_err());
+        let no_homeserver = ApprovalsConfig {
+            matrix_homeserver: None,
+            ..config()
+        };
+        assert!(Bridge::from_config(&no_homeserver, "s", Some("tok".to_string())).is_err());
+    }
+
+    #[test]
+    fn payloads_carry_escaped_request_and_links() {
+        let bridge = Bridge::from_config(&config(), "s", Some("tok".to_string()))
+            .unwrap()
+            .unwrap();
+        let reset = request(7, "git reset --hard");
+
+        let slack = bridge.slack_payload(&reset);
+        let text = slack["blocks"][0]["text"]["text"].as_str().unwrap();
+        assert!(text.contains("request #7"));
+        assert!(text.contains("destroys &lt;work&gt;"));
+        let buttons = slack["blocks"][1]["elements"].as_array().unwrap();
+        assert_eq!(
+            buttons[0]["url"],
+            decision_url("https://dcg.example.com", "s", Decision::Approve, &reset)
+        );
+        assert!(
+            buttons[1]["url"]
+                .as_str()
+                .unwrap()
+                .contains("/7/reject?sig=")
+        );
+
+        let matrix = bridge.matrix_payload(&reset);
+        assert!(
+            matrix["body"]
+                .as_str()
+                .unwrap()
+                .contains("/v1/approvals/7/approve?sig=")
+        );
+        let html = matrix["formatted_body"].as_str().unwrap();
+        assert!(html.contains("destroys &lt;work&gt;"));
+        assert!(html.contains("<a href=\"https://dcg.example.com/v1/approvals/7/approve?sig="));
+
+        assert_eq!(percent_encode("!ops:example.org"), "%21ops%3Aexample.org");
+    }
+}
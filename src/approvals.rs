@@ -0,0 +1,341 @@
+//! Queue of blocked commands awaiting deferred human approval.
+//!
+//! In an unattended run (CI, or `DCG_NON_INTERACTIVE` set) nobody is around
+//! to read a denial and run `dcg allow-once`. With `[interactive]
+//! approval_queue = true`, the hook also records each such denial here. A
+//! human later runs `dcg approvals list`, then `dcg approvals approve <ID>`,
+//! which mints the same allow-once grant `dcg allow-once` would; the agent's
+//! next retry of the command then succeeds. Retries of a command that is
+//! still waiting are folded into its request.
+//!
+//! Requests are dropped [`REQUEST_EXPIRY`] after the last attempt. Recording
+//! is best-effort: a missing, locked, or corrupt queue never affects the hook
+//! decision.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::resolve_config_path_value;
+use crate::pending_exceptions::PendingExceptionRecord;
+use crate::storage::{self, StateLock};
+
+/// Environment override for the approvals queue path.
+pub const ENV_APPROVALS_PATH: &str = "DCG_APPROVALS_PATH";
+
+/// Set (to any value) to mark the session as unattended outside CI.
+pub const ENV_NON_INTERACTIVE: &str = "DCG_NON_INTERACTIVE";
+
+const APPROVALS_FILE: &str = "approvals.json";
+const SCHEMA_VERSION: u32 = 1;
+
+/// Oldest requests are dropped beyond this many.
+pub const MAX_QUEUED_APPROVALS: usize = 1000;
+
+/// A request with no attempt for this long is dropped.
+pub const REQUEST_EXPIRY: chrono::TimeDelta = chrono::TimeDelta::days(7);
+
+/// Where a request stands.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ApprovalStatus {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// One blocked command waiting for (or decided by) a human.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApprovalRequest {
+    /// Stable ID for `dcg approvals approve` / `reject`.
+    pub id: u64,
+    pub status: ApprovalStatus,
+    /// How many times the agent ran into this denial.
+    pub attempts: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided_at: Option<String>,
+    /// When `dcg serve` posted the request to the `[approvals]` chat channels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified_at: Option<String>,
+    /// The latest denial, as `dcg allow-once` would apply it.
+    pub block: PendingExceptionRecord,
+}
+
+/// Contents of the approvals queue file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApprovalQueue {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub requests: Vec<ApprovalRequest>,
+}
+
+impl ApprovalQueue {
+    /// The request with `id`, if still queued.
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<&ApprovalRequest> {
+        self.requests.iter().find(|request| request.id == id)
+    }
+
+    /// Requests still waiting for a decision, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &ApprovalRequest> {
+        self.requests
+            .iter()
+            .filter(|request| request.status == ApprovalStatus::Pending)
+    }
+}
+
+/// JSON-backed approvals queue store.
+#[derive(Debug, Clone)]
+pub struct ApprovalQueueStore {
+    path: PathBuf,
+}
+
+impl ApprovalQueueStore {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resolve the default path (env override or the dcg state directory).
+    #[must_use]
+    pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_APPROVALS_PATH) {
+            if let Some(path) = resolve_config_path_value(&value, cwd) {
+                return path;
+            }
+        }
+
+        crate::paths::state_file(APPROVALS_FILE)
+    }
+
+    /// Load the queue. A missing or unreadable file yields an empty queue.
+    #[must_use]
+    pub fn load(&self) -> ApprovalQueue {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Queue `block` for approval and return its request ID. A pending
+    /// request for the same command and directory absorbs the retry.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the queue.
+    pub fn record(&self, block: &PendingExceptionRecord, now: DateTime<Utc>) -> io::Result<u64> {
+        let mut id = 0;
+        self.update(now, |queue| {
+            let stamp = storage::format_timestamp(now);
+            if let Some(request) = queue.requests.iter_mut().find(|request| {
+                request.status == ApprovalStatus::Pending
+                    && request.block.command_raw == block.command_raw
+                    && request.block.cwd == block.cwd
+            }) {
+                request.attempts += 1;
+                request.last_seen = stamp;
+                request.block = block.clone();
+                id = request.id;
+                return;
+            }
+            queue.next_id += 1;
+            id = queue.next_id;
+            queue.requests.push(ApprovalRequest {
+                id,
+                status: ApprovalStatus::Pending,
+                attempts: 1,
+                first_seen: stamp.clone(),
+                last_seen: stamp,
+                decided_at: None,
+                notified_at: None,
+                block: block.clone(),
+            });
+            if queue.requests.len() > MAX_QUEUED_APPROVALS {
+                let excess = queue.requests.len() - MAX_QUEUED_APPROVALS;
+                queue.requests.drain(..excess);
+            }
+        })?;
+        Ok(id)
+    }
+
+    /// Mark pending request `id` as `status`. Returns the updated request, or
+    /// `None` when no pending request has that ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the queue.
+    pub fn decide(
+        &self,
+        id: u64,
+        status: ApprovalStatus,
+        now: DateTime<Utc>,
+    ) -> io::Result<Option<ApprovalRequest>> {
+        let mut decided = None;
+        self.update(now, |queue| {
+            if let Some(request) = queue
+                .requests
+                .iter_mut()
+                .find(|request| request.id == id && request.status == ApprovalStatus::Pending)
+            {
+                request.status = status;
+                request.decided_at = Some(storage::format_timestamp(now));
+                decided = Some(request.clone());
+            }
+        })?;
+        Ok(decided)
+    }
+
+    /// Record that request `id` was posted to the chat channels.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the queue.
+    pub fn mark_notified(&self, id: u64, now: DateTime<Utc>) -> io::Result<()> {
+        self.update(now, |queue| {
+            if let Some(request) = queue.requests.iter_mut().find(|request| request.id == id) {
+                request.notified_at = Some(storage::format_timestamp(now));
+            }
+        })
+    }
+
+    fn update(&self, now: DateTime<Utc>, apply: impl FnOnce(&mut ApprovalQueue)) -> io::Result<()> {
+        let _lock = StateLock::acquire(&self.path)?;
+        let mut queue: ApprovalQueue = storage::read_json(&self.path)?.unwrap_or_default();
+        queue.schema_version = SCHEMA_VERSION;
+        queue.requests.retain(|request| {
+            storage::parse_timestamp(&request.last_seen)
+                .is_some_and(|seen| now - seen < REQUEST_EXPIRY)
+        });
+        apply(&mut queue);
+
+        storage::write_json(&self.path, &queue)
+    }
+}
+
+/// Whether no human is around to answer a denial: a CI system is detected or
+/// `DCG_NON_INTERACTIVE` is set.
+#[must_use]
+pub fn is_unattended() -> bool {
+    std::env::var_os(ENV_NON_INTERACTIVE).is_some() || crate::interactive::is_ci_environment()
+}
+
+/// Queue `block` in the default store and return its request ID, or `None`
+/// when the queue cannot be written (hook paths must not fail on
+/// bookkeeping).
+#[must_use]
+pub fn queue_block(block: &PendingExceptionRecord, cwd: Option<&Path>) -> Option<u64> {
+    let store = ApprovalQueueStore::new(ApprovalQueueStore::default_path(cwd));
+    store.record(block, Utc::now()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::RedactionConfig;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        storage::parse_timestamp(value).unwrap()
+    }
+
+    fn block(command: &str, cwd: &str, now: DateTime<Utc>) -> PendingExceptionRecord {
+        PendingExceptionRecord::new(
+            now,
+            cwd,
+            command,
+            "core.git:reset-hard - destroys work",
+            &RedactionConfig::default(),
+            false,
+            None,
+        )
+    }
+
+    #[test]
+    fn record_folds_retries_into_pending_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ApprovalQueueStore::new(dir.path().join("approvals.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        let later = at("2030-01-01T01:00:00Z");
+
+        let first = store
+            .record(&block("git reset --hard", "/w", now), now)
+            .unwrap();
+        let retry = store
+            .record(&block("git reset --hard", "/w", later), later)
+            .unwrap();
+        let elsewhere = store
+            .record(&block("git reset --hard", "/x", now), now)
+            .unwrap();
+        assert_eq!(first, 1);
+        assert_eq!(retry, 1);
+        assert_eq!(elsewhere, 2);
+
+        let queue = store.load();
+        assert_eq!(queue.schema_version, SCHEMA_VERSION);
+        assert_eq!(queue.requests[0].attempts, 2);
+        assert_eq!(queue.requests[0].last_seen, "2030-01-01T01:00:00Z");
+        assert_eq!(queue.pending().count(), 2);
+
+        let approved = store
+            .decide(1, ApprovalStatus::Approved, later)
+            .unwrap()
+            .unwrap();
+        assert_eq!(approved.status, ApprovalStatus::Approved);
+        assert!(
+            store
+                .decide(1, ApprovalStatus::Rejected, later)
+                .unwrap()
+                .is_none(),
+            "decided requests stay decided"
+        );
+        assert!(
+            store
+                .decide(9, ApprovalStatus::Approved, later)
+                .unwrap()
+                .is_none()
+        );
+
+        let again = store
+            .record(&block("git reset --hard", "/w", later), later)
+            .unwrap();
+        assert_eq!(again, 3, "a decided request does not absorb new denials");
+    }
+
+    #[test]
+    fn stale_requests_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ApprovalQueueStore::new(dir.path().join("approvals.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        store.record(&block("rm -rf /srv", "/w", now), now).unwrap();
+
+        let next_week = at("2030-01-08T00:00:00Z");
+        store
+            .record(&block("git clean -fdx", "/w", next_week), next_week)
+            .unwrap();
+        let queue = store.load();
+        assert_eq!(queue.requests.len(), 1);
+        assert_eq!(queue.requests[0].id, 2, "IDs are never reused");
+        assert!(queue.get(1).is_none());
+    }
+}
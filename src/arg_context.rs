@@ -0,0 +1,322 @@
+//! Namespace, context, project, profile and region of cloud CLI commands.
+//!
+//! `kubectl delete deployment api` is routine in a dev namespace and an
+//! outage with `--context prod`. This module pulls the options that say
+//! *where* a `kubectl`, `aws`, `gcloud` or `az` command acts into an
+//! [`ArgContext`], so `[overrides]` rules (and allowlist entries) can
+//! condition on them instead of on the raw command text.
+//!
+//! Only options written on the command line are seen. Defaults from
+//! kubeconfig, `AWS_PROFILE` or `gcloud config` are not consulted, so a
+//! condition on a key the command does not spell out is not met.
+
+use crate::normalize::{NormalizeTokenKind, tokenize_for_normalization};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Keys an [`ArgContext`] can hold.
+pub const ARG_KEYS: &[&str] = &["namespace", "context", "project", "profile", "region"];
+
+/// Options read per tool: (tool, key, option names).
+const OPTIONS: &[(&str, &str, &[&str])] = &[
+    ("kubectl", "namespace", &["-n", "--namespace"]),
+    ("kubectl", "context", &["--context"]),
+    ("aws", "profile", &["--profile"]),
+    ("aws", "region", &["--region"]),
+    ("gcloud", "project", &["--project"]),
+    ("gcloud", "region", &["--region"]),
+    ("az", "project", &["--subscription"]),
+    ("az", "region", &["--location", "-l"]),
+];
+
+/// Flags that make `kubectl` act on every namespace; recorded as `*`.
+const ALL_NAMESPACES: &[&str] = &["-A", "--all-namespaces"];
+
+/// Where one `kubectl`, `aws`, `gcloud` or `az` invocation acts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ArgContext {
+    /// The tool (`kubectl`, `aws`, `gcloud` or `az`).
+    pub tool: String,
+    /// Extracted values by key (see [`ARG_KEYS`]).
+    pub values: BTreeMap<&'static str, String>,
+}
+
+impl ArgContext {
+    /// The value extracted for `key`, if the command set it.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// `namespace=dev, context=kind-local` for display.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        self.values
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Keys that can be extracted from commands run with `tool` (e.g.
+/// `kubectl` yields `namespace` and `context`). Empty for other tools.
+#[must_use]
+pub fn supported_keys(tool: &str) -> Vec<&'static str> {
+    OPTIONS
+        .iter()
+        .filter(|(name, _, _)| *name == tool)
+        .map(|(_, key, _)| *key)
+        .collect()
+}
+
+/// One [`ArgContext`] per simple command in `command` that runs a supported
+/// tool, in order. Values that depend on shell expansion (`$NS`) are left
+/// out.
+#[must_use]
+pub fn arg_contexts(command: &str) -> Vec<ArgContext> {
+    let mut contexts = Vec::new();
+    let mut words: Vec<&str> = Vec::new();
+    for token in &tokenize_for_normalization(command) {
+        if token.kind == NormalizeTokenKind::Separator {
+            contexts.extend(extract(&words));
+            words.clear();
+        } else {
+            words.extend(token.text(command));
+        }
+    }
+    contexts.extend(extract(&words));
+    contexts
+}
+
+/// The program `command` runs (`kubectl` for `sudo /usr/bin/kubectl -n dev`),
+/// skipping leading environment assignments and `sudo`.
+#[must_use]
+pub fn command_tool(command: &str) -> Option<&str> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    program(&words).map(|(tool, _)| tool)
+}
+
+/// The program of one simple command and the words after it.
+pub(crate) fn program<'a>(words: &[&'a str]) -> Option<(&'a str, Vec<&'a str>)> {
+    let mut words = words
+        .iter()
+        .copied()
+        .skip_while(|word| word.contains('=') && !word.starts_with('-'));
+    let mut program = words.next()?;
+    if program == "sudo" {
+        program = words.next()?;
+    }
+    let tool = Path::new(program).file_name()?.to_str()?;
+    Some((tool, words.collect()))
+}
+
+/// The context of one simple command, if it runs a supported tool.
+fn extract(words: &[&str]) -> Option<ArgContext> {
+    let (tool, words) = program(words)?;
+    let options: Vec<_> = OPTIONS
+        .iter()
+        .filter(|(name, _, _)| *name == tool)
+        .collect();
+    if options.is_empty() {
+        return None;
+    }
+
+    let mut context = ArgContext {
+        tool: tool.to_string(),
+        values: BTreeMap::new(),
+    };
+    let mut pending: Option<&'static str> = None;
+    for word in words {
+        if word == "--" {
+            break;
+        }
+        if let Some(key) = pending.take() {
+            if let Some(value) = unquote(word) {
+                context.values.insert(key, value);
+            }
+            continue;
+        }
+        if tool == "kubectl" && ALL_NAMESPACES.contains(&word) {
+            context.values.insert("namespace", "*".to_string());
+            continue;
+        }
+        for (_, key, names) in &options {
+            if names.contains(&word) {
+                pending = Some(key);
+            } else if let Some(value) = names
+                .iter()
+                .find_map(|name| word.strip_prefix(name)?.strip_prefix('='))
+            {
+                if let Some(value) = unquote(value) {
+                    context.values.insert(key, value);
+                }
+            }
+        }
+    }
+    Some(context)
+}
+
+/// Remove shell quoting; `None` if the word depends on expansion.
+pub(crate) fn unquote(word: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = word.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => out.push(c),
+            (_, '$' | '`') => return None,
+            (None, '\'' | '"') => quote = Some(c),
+            (_, '\\') => out.extend(chars.next()),
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// Regex conditions on extracted arguments, e.g. `{ namespace = "dev|sandbox" }`.
+///
+/// Each value must match the whole extracted value. A key the command does
+/// not set never matches.
+#[derive(Debug, Clone, Default)]
+pub struct ArgConditions {
+    conditions: Vec<(String, Regex)>,
+}
+
+impl ArgConditions {
+    /// Compile `conditions`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the key if it is not one of [`ARG_KEYS`] or
+    /// its value is not a valid regex.
+    pub fn compile(conditions: &BTreeMap<String, String>) -> Result<Self, String> {
+        let conditions = conditions
+            .iter()
+            .map(|(key, value)| {
+                if !ARG_KEYS.contains(&key.as_str()) {
+                    return Err(format!(
+                        "unknown argument key '{key}' (expected one of: {})",
+                        ARG_KEYS.join(", ")
+                    ));
+                }
+                Regex::new(&format!("^(?:{value})$"))
+                    .map(|regex| (key.clone(), regex))
+                    .map_err(|err| format!("invalid regex for '{key}': {err}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { conditions })
+    }
+
+    /// `true` if there are no conditions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Whether `context` satisfies every condition.
+    #[must_use]
+    pub fn matches(&self, context: &ArgContext) -> bool {
+        self.conditions
+            .iter()
+            .all(|(key, regex)| context.get(key).is_some_and(|value| regex.is_match(value)))
+    }
+
+    /// Whether every supported command in `command` satisfies the
+    /// conditions (and there is at least one). For allow rules.
+    #[must_use]
+    pub fn all_match(&self, command: &str) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let contexts = arg_contexts(command);
+        !contexts.is_empty() && contexts.iter().all(|context| self.matches(context))
+    }
+
+    /// Whether any supported command in `command` satisfies the conditions.
+    /// For block rules.
+    #[must_use]
+    pub fn any_match(&self, command: &str) -> bool {
+        self.is_empty()
+            || arg_contexts(command)
+                .iter()
+                .any(|context| self.matches(context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(command: &str) -> Vec<String> {
+        arg_contexts(command)
+            .iter()
+            .map(|context| format!("{}: {}", context.tool, context.describe()))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_per_tool_options() {
+        assert_eq!(
+            values("kubectl --context=prod-east delete deploy api -n payments"),
+            vec!["kubectl: context=prod-east, namespace=payments"]
+        );
+        assert_eq!(
+            values("kubectl delete pods --all -A"),
+            vec!["kubectl: namespace=*"]
+        );
+        assert_eq!(
+            values("AWS_PAGER= aws --profile 'prod' s3 rb s3://logs --region=eu-west-1"),
+            vec!["aws: profile=prod, region=eu-west-1"]
+        );
+        assert_eq!(
+            values("gcloud sql instances delete db --project web-prod"),
+            vec!["gcloud: project=web-prod"]
+        );
+        assert_eq!(
+            values("az group delete -n rg --subscription prod-sub"),
+            vec!["az: project=prod-sub"]
+        );
+    }
+
+    #[test]
+    fn extracts_each_command_and_skips_expansions() {
+        assert_eq!(
+            values("kubectl -n dev get pods && sudo kubectl -n $NS delete pods --all; ls -n"),
+            vec!["kubectl: namespace=dev", "kubectl: "]
+        );
+        assert!(arg_contexts("helm uninstall api -n prod").is_empty());
+        assert_eq!(supported_keys("kubectl"), vec!["namespace", "context"]);
+        assert!(supported_keys("git").is_empty());
+        assert_eq!(
+            command_tool("FOO=1 sudo /usr/bin/kubectl -n dev"),
+            Some("kubectl")
+        );
+    }
+
+    #[test]
+    fn conditions_match_whole_values() {
+        let conditions = ArgConditions::compile(&BTreeMap::from([(
+            "namespace".to_string(),
+            "dev|sandbox".to_string(),
+        )]))
+        .unwrap();
+        assert!(conditions.all_match("kubectl delete pods --all -n sandbox"));
+        assert!(!conditions.all_match("kubectl delete pods --all -n dev-prod"));
+        assert!(!conditions.all_match("kubectl delete pods --all"));
+        assert!(!conditions.all_match("kubectl -n dev get ns && kubectl -n prod delete ns x"));
+        assert!(conditions.any_match("kubectl -n dev get ns && kubectl -n prod delete ns x"));
+
+        let unknown = BTreeMap::from([("cluster".to_string(), "x".to_string())]);
+        assert!(
+            ArgConditions::compile(&unknown)
+                .unwrap_err()
+                .contains("cluster")
+        );
+        let invalid = BTreeMap::from([("region".to_string(), "(".to_string())]);
+        assert!(ArgConditions::compile(&invalid).is_err());
+    }
+}
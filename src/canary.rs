@@ -0,0 +1,432 @@
+//! Canary paths and resource names (honeytokens).
+//!
+//! `[canary]` lists paths and resource names that nothing should ever touch,
+//! such as `/srv/DO_NOT_DELETE` or a `prod-canary` bucket. Any command that
+//! mentions one is denied at critical severity before allow overrides,
+//! allow-once codes, or allowlists are consulted, and the hook fires the
+//! configured webhook so someone hears about it right away.
+//!
+//! Matching is on the literal command text, quoted data included: a canary is
+//! a tripwire, and an agent that even echoes one has wandered somewhere it
+//! should not be.
+
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Synthetic pack id reported for canary hits (rule ids `canary:path`, `canary:name`).
+pub const CANARY_PACK_ID: &str = "canary";
+
+/// How long the hook waits for the webhook to answer.
+#[cfg(feature = "cli")]
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Explanation attached to every canary denial.
+pub const CANARY_EXPLANATION: &str = "This path or resource is a canary configured in [canary]. \
+     Nothing legitimate should reference it, so the command was stopped and any \
+     configured webhook was notified. Ask the user before going any further.";
+
+/// What kind of canary was referenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanaryKind {
+    /// A filesystem path; subpaths match too.
+    Path,
+    /// A resource name (bucket, database, host, ...); matched case-insensitively.
+    Name,
+}
+
+impl CanaryKind {
+    /// Pattern name used in the rule id (`canary:<label>`).
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Path => "path",
+            Self::Name => "name",
+        }
+    }
+}
+
+/// A canary referenced by a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanaryHit {
+    pub kind: CanaryKind,
+    /// The canary as configured.
+    pub canary: String,
+    /// Byte range of the reference in the command.
+    pub span: Range<usize>,
+}
+
+impl CanaryHit {
+    /// Denial reason shown to the agent.
+    #[must_use]
+    pub fn reason(&self) -> String {
+        format!(
+            "Command references canary {} '{}'",
+            self.kind.label(),
+            self.canary
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Tripwire {
+    kind: CanaryKind,
+    canary: String,
+    /// Text searched for (lowercased for names, home-expanded for `~/` paths).
+    needle: String,
+}
+
+/// Compiled `[canary]` paths and names.
+#[derive(Debug, Clone, Default)]
+pub struct CanaryTripwires {
+    tripwires: Vec<Tripwire>,
+}
+
+impl CanaryTripwires {
+    /// Build tripwires from configured paths and names. Blank entries are ignored.
+    #[must_use]
+    pub fn new(paths: &[String], names: &[String]) -> Self {
+        let home = dirs::home_dir();
+        let mut tripwires = Vec::new();
+
+        for path in paths {
+            let trimmed = path.trim();
+            let trimmed = if trimmed.len() > 1 {
+                trimmed.trim_end_matches('/')
+            } else {
+                trimmed
+            };
+            if trimmed.is_empty() {
+                continue;
+            }
+            tripwires.push(Tripwire {
+                kind: CanaryKind::Path,
+                canary: trimmed.to_string(),
+                needle: trimmed.to_string(),
+            });
+            // `~/x` in config should also catch the expanded `/home/me/x`.
+            if let (Some(rest), Some(home)) = (trimmed.strip_prefix("~/"), home.as_ref()) {
+                tripwires.push(Tripwire {
+                    kind: CanaryKind::Path,
+                    canary: trimmed.to_string(),
+                    needle: home.join(rest).to_string_lossy().into_owned(),
+                });
+            }
+        }
+
+        for name in names {
+            let trimmed = name.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            tripwires.push(Tripwire {
+                kind: CanaryKind::Name,
+                canary: trimmed.to_string(),
+                needle: trimmed.to_ascii_lowercase(),
+            });
+        }
+
+        Self { tripwires }
+    }
+
+    /// `true` if no canaries are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tripwires.is_empty()
+    }
+
+    /// The first canary `command` references, if any.
+    #[must_use]
+    pub fn check(&self, command: &str) -> Option<CanaryHit> {
+        if self.tripwires.is_empty() {
+            return None;
+        }
+        let lowered = command.to_ascii_lowercase();
+
+        self.tripwires.iter().find_map(|tripwire| {
+            let (haystack, is_boundary): (&str, fn(u8) -> bool) = match tripwire.kind {
+                CanaryKind::Path => (command, is_path_boundary),
+                CanaryKind::Name => (&lowered, is_name_boundary),
+            };
+            find_bounded(haystack, &tripwire.needle, is_boundary).map(|span| CanaryHit {
+                kind: tripwire.kind,
+                canary: tripwire.canary.clone(),
+                span,
+            })
+        })
+    }
+}
+
+/// Characters that may sit next to a path without making it a different path.
+/// `/` is allowed on both sides so `//srv/x` and `/srv/x/file` still match.
+const fn is_path_boundary(byte: u8) -> bool {
+    !(byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'_' | b'-' | b'~'))
+}
+
+/// Characters that end a resource name (`prod-canary.s3` matches, `prod-canary-2` does not).
+const fn is_name_boundary(byte: u8) -> bool {
+    !(byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-'))
+}
+
+fn find_bounded(haystack: &str, needle: &str, is_boundary: fn(u8) -> bool) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    let bytes = haystack.as_bytes();
+
+    memchr::memmem::find_iter(bytes, needle.as_bytes()).find_map(|start| {
+        let end = start + needle.len();
+        let before_ok = start == 0 || is_boundary(bytes[start - 1]);
+        let after_ok = end == bytes.len() || is_boundary(bytes[end]);
+        (before_ok && after_ok).then_some(start..end)
+    })
+}
+
+/// JSON body posted to `[canary] webhook_url`.
+#[derive(Debug, Serialize)]
+pub struct CanaryEvent<'a> {
+    /// Always `"dcg.canary"`.
+    pub event: &'static str,
+    pub timestamp: String,
+    pub rule_id: String,
+    pub canary: &'a str,
+    /// The command, with inline credentials masked.
+    pub command: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<&'a str>,
+}
+
+impl<'a> CanaryEvent<'a> {
+    /// Event for a denial whose rule is `canary:<pattern_name>`; `canary` is the
+    /// referenced text.
+    #[must_use]
+    pub fn new(
+        pattern_name: &str,
+        canary: &'a str,
+        command: &'a str,
+        cwd: Option<&'a str>,
+    ) -> Self {
+        Self {
+            event: "dcg.canary",
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            rule_id: format!("{CANARY_PACK_ID}:{pattern_name}"),
+            canary,
+            command: crate::logging::redact_secrets(command),
+            cwd,
+        }
+    }
+}
+
+/// POST `event` to `url` as JSON.
+///
+/// The hook waits up to a few seconds for the answer; canary hits are rare and
+/// a lost notification is worse than a slow denial.
+///
+/// # Errors
+///
+/// Returns an error if the request fails, times out, or is answered with a
+/// non-success status.
+#[cfg(feature = "cli")]
+pub fn notify_webhook(url: &str, event: &CanaryEvent<'_>) -> Result<(), String> {
+    let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+    let url = url.to_string();
+    // The blocking client runs its own runtime, which must not be nested
+    // inside the MCP or admin servers' runtimes.
+    std::thread::spawn(move || {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .user_agent(concat!("dcg/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload)
+            .send()
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("webhook answered HTTP {status}"))
+        }
+    })
+    .join()
+    .unwrap_or_else(|_| Err("webhook request panicked".to_string()))
+}
+
+/// POST `event` to `url` as JSON.
+///
+/// # Errors
+///
+/// Always: sending webhooks requires the `cli` feature.
+#[cfg(not(feature = "cli"))]
+pub fn notify_webhook(_url: &str, _event: &CanaryEvent<'_>) -> Result<(), String> {
+    Err("canary webhooks require the `cli` feature".to_string())
+}
+
+/// Where the last failed webhook delivery is recorded for `dcg doctor`.
+#[must_use]
+pub fn webhook_failure_path() -> PathBuf {
+    crate::paths::state_dir().join("canary-webhook-failure")
+}
+
+/// Remember a failed delivery at `path`, or forget it after a successful one.
+pub fn record_webhook_outcome(path: &Path, outcome: &Result<(), String>) {
+    match outcome {
+        Ok(()) => {
+            let _ = fs::remove_file(path);
+        }
+        Err(error) => {
+            let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            let _ = crate::storage::write_atomic(path, format!("{now} {error}").as_bytes());
+        }
+    }
+}
+
+/// The last failed delivery recorded at `path` (`<timestamp> <error>`), if the
+/// most recent attempt failed.
+#[must_use]
+pub fn last_webhook_failure(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tripwires() -> CanaryTripwires {
+        CanaryTripwires::new(
+            &["/srv/DO_NOT_DELETE/".to_string(), "  ".to_string()],
+            &["prod-canary".to_string()],
+        )
+    }
+
+    #[test]
+    fn paths_match_themselves_and_subpaths_only() {
+        let canaries = tripwires();
+
+        let hit = canaries.check("rm -rf /srv/DO_NOT_DELETE").unwrap();
+        assert_eq!(hit.kind, CanaryKind::Path);
+        assert_eq!(hit.canary, "/srv/DO_NOT_DELETE");
+        assert_eq!(hit.span, 7..25);
+
+        assert!(canaries.check("cat '/srv/DO_NOT_DELETE/readme'").is_some());
+        assert!(canaries.check("ls //srv/DO_NOT_DELETE").is_some());
+        assert!(canaries.check("rm /srv/DO_NOT_DELETE.bak").is_none());
+        assert!(canaries.check("rm /srv/DO_NOT_DELETE_OLD").is_none());
+        assert!(canaries.check("rm /mnt/srv/DO_NOT_DELETE").is_none());
+    }
+
+    #[test]
+    fn names_match_case_insensitively_on_word_boundaries() {
+        let canaries = tripwires();
+
+        let hit = canaries.check("aws s3 rm s3://PROD-CANARY/key").unwrap();
+        assert_eq!(hit.kind, CanaryKind::Name);
+        assert_eq!(hit.canary, "prod-canary");
+        assert_eq!(hit.reason(), "Command references canary name 'prod-canary'");
+
+        assert!(
+            canaries
+                .check("curl https://prod-canary.s3.amazonaws.com")
+                .is_some()
+        );
+        assert!(canaries.check("aws s3 rm s3://prod-canary-2/key").is_none());
+        assert!(canaries.check("aws s3 rm s3://my_prod-canary").is_none());
+    }
+
+    #[test]
+    fn empty_config_never_matches() {
+        let canaries = CanaryTripwires::new(&[], &[" ".to_string()]);
+        assert!(canaries.is_empty());
+        assert!(canaries.check("rm -rf /").is_none());
+    }
+
+    #[test]
+    fn event_carries_rule_id_and_canary() {
+        let command = "rm -rf /srv/DO_NOT_DELETE";
+        let hit = tripwires().check(command).unwrap();
+        let event = CanaryEvent::new(
+            hit.kind.label(),
+            &command[hit.span.clone()],
+            command,
+            Some("/w"),
+        );
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "dcg.canary");
+        assert_eq!(json["rule_id"], "canary:path");
+        assert_eq!(json["canary"], "/srv/DO_NOT_DELETE");
+        assert_eq!(json["cwd"], "/w");
+    }
+
+    #[test]
+    fn failed_deliveries_are_remembered_until_one_succeeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("canary-webhook-failure");
+        assert_eq!(last_webhook_failure(&path), None);
+
+        record_webhook_outcome(&path, &Err("connection refused".to_string()));
+        assert!(
+            last_webhook_failure(&path)
+                .unwrap()
+                .ends_with(" connection refused")
+        );
+
+        record_webhook_outcome(&path, &Ok(()));
+        assert_eq!(last_webhook_failure(&path), None);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn webhook_posts_event_json_and_reports_http_errors() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in ["204 No Content", "500 Internal Server Error"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+            bodies
+        });
+
+        let event = CanaryEvent::new("path", "/srv/x", "rm -rf /srv/x", None);
+        assert_eq!(notify_webhook(&url, &event), Ok(()));
+        let err = notify_webhook(&url, &event).unwrap_err();
+        assert!(err.contains("500"), "{err}");
+
+        let bodies = server.join().unwrap();
+        assert!(bodies[0].contains("\"rule_id\":\"canary:path\""));
+    }
+}
@@ -12,7 +12,8 @@ use crate::agent::{DetectionMethod, detect_agent_with_details};
 use crate::config::Config;
 use crate::evaluator::{
     DEFAULT_WINDOW_WIDTH, EvaluationDecision, EvaluationResult, MatchSource,
-    evaluate_command_with_pack_order, evaluate_command_with_pack_order_deadline_at_path,
+    apply_severity_aggregation, evaluate_command_with_pack_order,
+    evaluate_command_with_pack_order_deadline_at_path, record_near_misses,
 };
 use crate::exit_codes::EXIT_DENIED;
 use crate::highlight::{HighlightSpan, format_highlighted_command, should_use_color};
@@ -23,19 +24,19 @@ use crate::interactive::{
     AllowlistScope, InteractiveConfig, InteractiveResult, check_interactive_available,
     print_not_available_message, run_interactive_prompt,
 };
-use crate::load_default_allowlists;
 use crate::packs::{
-    DecisionMode, ExternalPackStore, REGISTRY, Severity as PackSeverity, get_external_packs,
+    DecisionMode, ExternalPackStore, Pack, REGISTRY, Severity as PackSeverity, get_external_packs,
     load_external_packs,
 };
 use crate::pending_exceptions::{
-    AllowOnceEntry, AllowOnceScopeKind, AllowOnceStore, PendingExceptionRecord,
+    AllowOnceEntry, AllowOnceGrant, AllowOnceScopeKind, AllowOnceStore, PendingExceptionRecord,
     PendingExceptionStore,
 };
 use crate::suggest::{
     AllowlistSuggestion, CommandEntryInfo, ConfidenceTier, RiskLevel, filter_by_confidence,
-    filter_by_risk, generate_enhanced_suggestions,
+    filter_by_risk, generate_enhanced_suggestions_with_history, risk_guard,
 };
+use crate::suggest_history::{SuggestionHistoryStore, SuggestionStatus};
 use std::io::IsTerminal;
 
 /// Unified output format for all dcg commands.
@@ -161,6 +162,25 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub robot: bool,
 
+    /// Write the full evaluation pipeline (input, normalizations, pack order,
+    /// per-pattern timing, allowlist checks, decision) to this JSON file
+    ///
+    /// Honored by hook mode and `dcg test`.
+    #[arg(long, global = true, value_name = "PATH", env = "DCG_TRACE_FILE")]
+    pub trace_file: Option<std::path::PathBuf>,
+
+    /// Render boxes and tables at this many columns instead of the detected
+    /// terminal width (also set by DCG_TERM_WIDTH)
+    ///
+    /// Boxes whose content does not fit fall back to the borderless layout.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(i64::from(crate::output::MIN_TERM_WIDTH)..)
+    )]
+    pub width: Option<u16>,
+
     /// Subcommand to run (omit to run in hook mode)
     #[command(subcommand)]
     pub command: Option<Command>,
@@ -272,6 +292,18 @@ pub enum Command {
         shell: CompletionShell,
     },
 
+    /// Print a JSON Schema for one of dcg's machine-readable formats
+    ///
+    /// Schemas cover the hook output, the `dcg test --format json` envelope,
+    /// scan and stats reports, error responses, and the config and allowlist
+    /// TOML files (for editor completion via a TOML language server).
+    #[command(name = "schema")]
+    Schema {
+        /// Which schema to print
+        #[arg(value_enum)]
+        kind: crate::schema::SchemaKind,
+    },
+
     /// List all available packs and their status
     #[command(name = "packs")]
     ListPacks {
@@ -317,6 +349,11 @@ pub enum Command {
         #[arg(long)]
         explain: bool,
 
+        /// Who authored the command, for `[policy.provenance.*]` overrides
+        /// (default: `DCG_PROVENANCE`, else agent; the hook is always agent)
+        #[arg(long, value_enum)]
+        provenance: Option<crate::provenance::Provenance>,
+
         /// Output format (json for structured output, pretty for human-readable)
         #[arg(
             long,
@@ -364,9 +401,110 @@ pub enum Command {
         force: bool,
     },
 
+    /// Find commands an existing project runs that dcg would block
+    ///
+    /// With `--analyze`, scans Makefiles, justfiles, package.json scripts, CI
+    /// configs and shell scripts under `--path`, plus your shell history,
+    /// and proposes a starter allowlist for the rules the project relies on.
+    #[command(name = "onboard")]
+    Onboard(OnboardCommand),
+
+    /// Report which rules a command corpus exercises
+    ///
+    /// Reads commands from shell history, hook or decision logs, or corpus
+    /// fixture directories and reports, per pack, which rules fire, which
+    /// never fire, and which commands no pack looks at, to show which packs
+    /// are worth enabling and where coverage has gaps.
+    #[command(name = "coverage")]
+    Coverage(CoverageCommand),
+
+    /// Revert the last policy-file change made by dcg
+    ///
+    /// Commands that edit allowlists or config files (`allowlist add/remove/prune`,
+    /// `allow`, `unallow`, `init -o`, `pack new`, `suggest-allowlist`) journal a
+    /// snapshot under `~/.local/state/dcg/journal/` (override with
+    /// `DCG_JOURNAL_DIR`). Each `dcg undo` reverts one change, newest first.
+    #[command(name = "undo")]
+    Undo {
+        /// List journaled changes instead of undoing
+        #[arg(long)]
+        list: bool,
+
+        /// Show what would be reverted without changing anything
+        #[arg(long, conflicts_with = "list")]
+        dry_run: bool,
+
+        /// Revert even if the file was edited after dcg changed it
+        #[arg(long, conflicts_with = "list")]
+        force: bool,
+    },
+
+    /// List every file dcg reads or writes, with existence and permissions
+    ///
+    /// Covers config files, allowlists, state (allow-once codes, usage
+    /// counters, the undo journal), caches and the decision log. State lives
+    /// in `$XDG_STATE_HOME/dcg` and caches in `$XDG_CACHE_HOME/dcg`
+    /// (override with `DCG_STATE_DIR` / `DCG_CACHE_DIR`).
+    #[command(name = "paths")]
+    Paths {
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Upgrade config and allowlist files to the current schema version
+    ///
+    /// Files without `config_version` are version 1. Each file is rewritten
+    /// in place (comments and formatting preserved) after copying the
+    /// original to `<file>.v<N>.bak`; the change is also recorded for
+    /// `dcg undo`. Without paths, migrates every config layer and allowlist
+    /// dcg loads.
+    #[command(name = "migrate")]
+    Migrate {
+        /// Files to migrate (kind inferred from the name: `*allowlist*` files
+        /// are allowlists, everything else is a config file)
+        #[arg(value_name = "FILE")]
+        files: Vec<std::path::PathBuf>,
+
+        /// Show the planned edits without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Export the effective policy, or diff two exports to find drift
+    ///
+    /// `dcg policy export` prints enabled packs, `[policy]` modes,
+    /// overrides, canary and protected-branch settings, and allowlist
+    /// entries as canonical JSON. `dcg policy diff a.json b.json` lists what
+    /// differs between two exports and exits 1 if anything does.
+    #[command(name = "policy")]
+    Policy {
+        #[command(subcommand)]
+        action: PolicyAction,
+    },
+
+    /// Manage the opt-in anonymous usage ping
+    ///
+    /// Off by default. When enabled, dcg sends its version, OS, number of
+    /// enabled packs, and per-pack denial counts about once a week. Commands
+    /// are never sent. `DO_NOT_TRACK=1` overrides the stored setting.
+    #[cfg(feature = "telemetry")]
+    #[command(name = "telemetry")]
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryAction,
+    },
+
     /// Show current configuration
     #[command(name = "config")]
-    ShowConfig,
+    ShowConfig {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
 
     /// Scan files for destructive commands (CI/pre-commit integration)
     ///
@@ -376,6 +514,14 @@ pub enum Command {
     #[command(name = "scan")]
     Scan(ScanCommand),
 
+    /// Scan Makefile, justfile, package.json and deno.json scripts
+    ///
+    /// Evaluates every recipe, script and task command and reports findings
+    /// with the script name and file location. Directories are searched
+    /// recursively, skipping dependency and build directories.
+    #[command(name = "scan-scripts")]
+    ScanScripts(ScanScriptsCommand),
+
     /// Simulate policy evaluation on command logs (replay/dry-run)
     ///
     /// Parses a file containing commands (one per line) and evaluates each
@@ -395,10 +541,23 @@ pub enum Command {
     ///
     /// Shows the full decision pipeline: keyword gating, pack evaluation,
     /// pattern matching, and allowlist checks.
+    ///
+    /// With `--rule`, describes a rule by ID instead (as found in audit logs):
+    /// its pattern, severity, rationale, example blocked commands, safe
+    /// alternatives, and the allowlist entry that permits it.
     #[command(name = "explain")]
     Explain {
         /// Command to explain
-        command: String,
+        #[arg(required_unless_present = "rule", conflicts_with = "rule")]
+        command: Option<String>,
+
+        /// Rule ID to describe (e.g. core.git:reset-hard)
+        #[arg(long, value_name = "RULE_ID")]
+        rule: Option<String>,
+
+        /// Regression corpus to take `--rule` examples from (skipped if missing)
+        #[arg(long, value_name = "DIR", default_value = "tests/corpus")]
+        corpus: std::path::PathBuf,
 
         /// Output format
         #[arg(
@@ -415,6 +574,55 @@ pub enum Command {
         with_packs: Option<Vec<String>>,
     },
 
+    /// Run the dry-run variant of a destructive command and show its output
+    ///
+    /// Rewrites commands with native dry-run support (`git clean`, `git rm`,
+    /// `kubectl delete`, `helm uninstall`, `terraform destroy`, `rsync`, ...)
+    /// into their preview form and executes it in the current directory, so
+    /// you can see what the real command would touch before allowing it.
+    /// The preview itself is checked against dcg's rules and never runs if
+    /// it would be blocked.
+    #[command(name = "preview")]
+    Preview {
+        /// Command to preview
+        command: String,
+
+        /// Seconds to let the preview run before killing it
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Move files to a restorable trash instead of deleting them
+    ///
+    /// `dcg trash put <paths>` is the safe replacement for `rm -rf`: targets
+    /// are moved to `$XDG_STATE_HOME/dcg/trash/<timestamp>/` (or the desktop
+    /// trash with `[trash] backend = "system"`) and can be brought back with
+    /// `dcg trash restore`.
+    #[command(name = "trash")]
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+
+    /// Show why a command was allowed (full decision trace)
+    ///
+    /// Prints every normalization step, which packs were enabled or skipped
+    /// and why, near-miss patterns that matched but were guarded, and any
+    /// allowlist entries that fired.
+    #[command(name = "why-allowed")]
+    WhyAllowed {
+        /// Command to trace
+        command: String,
+
+        /// Additional packs to enable for this evaluation
+        #[arg(long, value_delimiter = ',')]
+        with_packs: Option<Vec<String>>,
+    },
+
     /// Run regression corpus tests and output detailed JSON logs
     ///
     /// Loads test cases from TOML corpus files and evaluates each command,
@@ -444,6 +652,45 @@ pub enum Command {
     #[command(name = "suggest-allowlist")]
     SuggestAllowlist(SuggestAllowlistCommand),
 
+    /// Review warnings queued by interactive learning
+    ///
+    /// With `[interactive] learning = true`, the hook queues commands it
+    /// allowed with a Medium-severity warning. Review walks the queue rule by
+    /// rule and turns each group into a deny rule or an allowlist entry.
+    #[command(name = "review")]
+    Review(ReviewCommand),
+
+    /// Review warn-mode events and convert recurring ones into rules
+    ///
+    /// The hook queues every command it lets through under a `warn` rule
+    /// (`[policy] warn_queue`). List them, acknowledge the ones that are
+    /// fine, or convert them in bulk into `[overrides] block` rules or
+    /// allowlist entries.
+    #[command(name = "warnings")]
+    Warnings {
+        #[command(subcommand)]
+        action: WarningsAction,
+    },
+
+    /// Approve or reject commands blocked while no human was around
+    ///
+    /// With `[interactive] approval_queue = true`, denials in unattended runs
+    /// (CI, or `DCG_NON_INTERACTIVE` set) are queued here. Approving a request
+    /// mints an allow-once grant, so the agent's next retry of the command
+    /// succeeds.
+    #[command(name = "approvals")]
+    Approvals {
+        #[command(subcommand)]
+        action: ApprovalsAction,
+    },
+
+    /// Seed corpus for the cargo-fuzz targets in `fuzz/`
+    #[command(name = "fuzz-corpus")]
+    FuzzCorpus {
+        #[command(subcommand)]
+        action: FuzzCorpusAction,
+    },
+
     /// Developer tools for pack development and testing
     #[command(name = "dev")]
     Dev {
@@ -451,6 +698,28 @@ pub enum Command {
         action: DevAction,
     },
 
+    /// Generate per-rule documentation
+    #[command(name = "docs")]
+    Docs {
+        #[command(subcommand)]
+        action: DocsAction,
+    },
+
+    /// Render the golden files for the terminal renderers
+    ///
+    /// Writes every denial/test/table fixture under every theme and border
+    /// style to one file per theme variant, or compares them with `--check`.
+    #[command(name = "render-fixtures", hide = true)]
+    RenderFixtures {
+        /// Directory holding the golden files
+        #[arg(long, value_name = "DIR", default_value = "tests/golden/render")]
+        dir: std::path::PathBuf,
+
+        /// Compare with the existing files instead of rewriting them
+        #[arg(long)]
+        check: bool,
+    },
+
     /// Start MCP server for direct agent integration
     ///
     /// Runs dcg as an MCP (Model Context Protocol) server over stdio,
@@ -471,10 +740,59 @@ pub enum Command {
     /// ```
     #[command(name = "mcp-server")]
     McpServer,
+
+    /// Run a language server for dcg policy files
+    ///
+    /// Speaks LSP over stdio, giving editors diagnostics (invalid selectors,
+    /// expired entries, bad globs, unknown pack and rule IDs) and rule/pack ID
+    /// completions for `allowlist.toml`, `config.toml`, and `.dcg.toml`.
+    ///
+    /// Example Neovim configuration:
+    /// ```lua
+    /// vim.lsp.start({ name = "dcg", cmd = { "dcg", "lsp" } })
+    /// ```
+    #[command(name = "lsp")]
+    Lsp,
+
+    /// Run the admin HTTP API (daemon mode)
+    ///
+    /// Serves a JSON API on a loopback address for dashboards and chat bots:
+    /// recent decisions, packs and rules, allow-once grants, and policy
+    /// reload. Requests must carry `Authorization: Bearer $DCG_ADMIN_TOKEN`.
+    ///
+    /// Example:
+    /// ```text
+    /// DCG_ADMIN_TOKEN=$(openssl rand -hex 32) dcg serve --listen 127.0.0.1:8765
+    /// curl -H "Authorization: Bearer $DCG_ADMIN_TOKEN" localhost:8765/v1/packs
+    /// ```
+    #[command(name = "serve")]
+    Serve(ServeCommand),
+}
+
+/// `dcg serve` arguments.
+#[derive(Args, Debug)]
+pub struct ServeCommand {
+    /// Loopback address to listen on
+    #[arg(long, default_value = crate::admin_api::DEFAULT_LISTEN)]
+    pub listen: String,
+}
+
+/// `dcg config` subcommands.
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Show current configuration (same as bare `dcg config`)
+    #[command(name = "show")]
+    Show {
+        /// Show the fully merged config plus the provenance of every layer,
+        /// including files pulled in via `include = [...]`
+        #[arg(long)]
+        effective: bool,
+    },
 }
 
 /// `dcg hook` command arguments.
 #[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct HookCommand {
     /// Enable batch mode: read JSONL from stdin, output JSONL results
     ///
@@ -506,6 +824,45 @@ pub struct HookCommand {
     /// Continue processing on parse errors (skip invalid lines)
     #[arg(long)]
     pub continue_on_error: bool,
+
+    /// Optional action subcommand (git hook integration)
+    #[command(subcommand)]
+    pub action: Option<HookAction>,
+}
+
+/// `dcg hook` subcommands.
+#[derive(Subcommand, Debug)]
+pub enum HookAction {
+    /// Install a git hook that scans staged files with `dcg scan --staged`
+    ///
+    /// The hook blocks the commit when staged shell scripts, Dockerfiles, CI
+    /// configs, etc. contain destructive commands at or above the threshold.
+    #[command(name = "install")]
+    Install {
+        /// Git hook to install
+        #[arg(value_enum)]
+        kind: GitHookKind,
+
+        /// Block the commit when findings meet this threshold
+        /// (default: `.dcg/hooks.toml`, then `error`)
+        #[arg(long, value_enum)]
+        fail_on: Option<crate::scan::ScanFailOn>,
+    },
+
+    /// Remove a git hook installed by `dcg hook install`
+    #[command(name = "uninstall")]
+    Uninstall {
+        /// Git hook to remove
+        #[arg(value_enum)]
+        kind: GitHookKind,
+    },
+}
+
+/// Git hooks dcg can install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GitHookKind {
+    /// `.git/hooks/pre-commit`
+    PreCommit,
 }
 
 /// Output format for batch hook mode.
@@ -659,6 +1016,8 @@ pub struct TestOutput {
     pub robot_mode: bool,
     /// The command that was tested
     pub command: String,
+    /// Who authored the command: "agent" or "human"
+    pub provenance: crate::provenance::Provenance,
     /// The decision: "allow" or "deny"
     pub decision: String,
     /// Rule ID if blocked (e.g., "core.git:reset-hard")
@@ -682,15 +1041,55 @@ pub struct TestOutput {
     /// Matched span (start, end) in the command
     #[serde(skip_serializing_if = "Option::is_none")]
     pub matched_span: Option<(usize, usize)>,
+    /// 1-based line of the match in a multi-line command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
     /// Severity level: "critical", "high", "medium", "low"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub severity: Option<String>,
+    /// Match confidence (0.0-1.0), when confidence scoring ran
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Confidence below which a deny falls back to warn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence_to_deny: Option<f64>,
     /// Allowlist override info if allowed via allowlist
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allowlist: Option<AllowlistOverrideInfo>,
     /// Detected agent information
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<AgentInfo>,
+    /// Every pattern that matched if blocked, highest severity first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<TestMatchInfo>,
+    /// Namespace, context, project, profile and region of cloud CLI commands
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<crate::arg_context::ArgContext>,
+}
+
+/// One matched pattern in test output
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TestMatchInfo {
+    /// Rule ID (e.g., "core.git:reset-hard")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    /// Pack ID that matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack_id: Option<String>,
+    /// Pattern name within the pack
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_name: Option<String>,
+    /// Reason for the match
+    pub reason: String,
+    /// Severity level: "critical", "high", "medium", "low"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    /// Matched span (start, end) in the command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_span: Option<(usize, usize)>,
+    /// 1-based line of the match in a multi-line command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
 }
 
 /// Allowlist override information in test output
@@ -722,6 +1121,9 @@ pub struct PacksOutput {
     pub enabled_count: usize,
     /// Total pack count
     pub total_count: usize,
+    /// Version pin problems (see `packs.enabled = ["core.git@1"]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// Pack information in the packs list
@@ -735,8 +1137,17 @@ pub struct PackInfo {
     pub category: String,
     /// Description
     pub description: String,
+    /// Pack version
+    pub version: u32,
+    /// Version pinned in `packs.enabled` (`core.git@1`), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_version: Option<String>,
     /// Whether the pack is enabled
     pub enabled: bool,
+    /// Config entry and layer that decided `enabled` (absent when the pack is
+    /// simply not enabled anywhere)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decided_by: Option<crate::config::PackDecision>,
     /// Number of safe patterns
     pub safe_pattern_count: usize,
     /// Number of destructive patterns
@@ -783,6 +1194,118 @@ pub struct SuggestAllowlistCommand {
     /// Undo recently added auto-suggested patterns (removes patterns added in the last N minutes)
     #[arg(long)]
     pub undo: Option<u32>,
+
+    /// Dismiss a suggestion by its `suggestion_id` so it is not proposed again (repeatable)
+    #[arg(long, value_name = "ID")]
+    pub dismiss: Vec<String>,
+}
+
+/// `dcg scan-scripts` command arguments.
+#[derive(Args, Debug)]
+pub struct ScanScriptsCommand {
+    /// Files or directories to scan
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub paths: Vec<std::path::PathBuf>,
+
+    /// Output format
+    #[arg(
+        long,
+        short = 'f',
+        value_enum,
+        default_value = "pretty",
+        env = "DCG_FORMAT"
+    )]
+    pub format: crate::scan::ScanFormat,
+
+    /// Exit non-zero when findings meet this threshold
+    #[arg(long, value_enum, default_value = "error")]
+    pub fail_on: crate::scan::ScanFailOn,
+
+    /// Exclude files matching glob pattern (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+}
+
+/// `dcg onboard` command arguments.
+#[derive(Args, Debug)]
+pub struct OnboardCommand {
+    /// Run the analysis (without it, print the onboarding steps)
+    #[arg(long)]
+    pub analyze: bool,
+
+    /// Project directory to analyze
+    #[arg(long, default_value = ".")]
+    pub path: std::path::PathBuf,
+
+    /// Skip shell history
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Newest commands read from each history file
+    #[arg(long, default_value = "5000")]
+    pub history_limit: usize,
+
+    /// History matches needed to propose a rule not used by project files
+    #[arg(long, default_value = "3")]
+    pub min_history: usize,
+
+    /// Write the proposed starter allowlist to this file
+    #[arg(long, short = 'o')]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Output format (text, json)
+    #[arg(
+        long,
+        short = 'f',
+        value_enum,
+        default_value = "text",
+        env = "DCG_FORMAT"
+    )]
+    pub format: SuggestFormat,
+}
+
+/// `dcg coverage` command arguments.
+#[derive(Args, Debug)]
+pub struct CoverageCommand {
+    /// Corpus files or fixture directories ("-" for stdin). Files named like
+    /// `*history` are read as shell history; other files one command per line
+    /// (plain, hook JSON, or `DCG_LOG_V1` decision log)
+    #[arg(required = true, value_name = "PATH")]
+    pub paths: Vec<std::path::PathBuf>,
+
+    /// Only measure the packs enabled in the current config
+    #[arg(long)]
+    pub enabled_only: bool,
+
+    /// Untouched commands to list, most frequent first
+    #[arg(long, default_value = "20")]
+    pub top: usize,
+
+    /// Output format
+    #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+    pub format: DoctorFormat,
+}
+
+/// `dcg review` command arguments.
+#[derive(Args, Debug)]
+pub struct ReviewCommand {
+    /// Print the queue without prompting
+    #[arg(long)]
+    pub list: bool,
+
+    /// Output format (text, json); json implies --list
+    #[arg(
+        long,
+        short = 'f',
+        value_enum,
+        default_value = "text",
+        env = "DCG_FORMAT"
+    )]
+    pub format: SuggestFormat,
+
+    /// Empty the queue without creating any rules
+    #[arg(long, conflicts_with = "list")]
+    pub clear: bool,
 }
 
 /// Output format for suggest-allowlist command.
@@ -953,40 +1476,309 @@ pub enum HistoryAction {
     },
 }
 
-/// Developer tool subcommands
+/// Documentation subcommands
 #[derive(Subcommand, Debug)]
-pub enum DevAction {
-    /// Test a regex pattern against sample commands
+pub enum DocsAction {
+    /// Write a Markdown page per rule plus an index
     ///
-    /// Validates regex syntax and tests matching against provided commands.
-    /// Useful for developing and debugging pack patterns.
-    #[command(name = "test-pattern")]
-    TestPattern {
-        /// Regex pattern to test
-        pattern: String,
-
-        /// Test commands to match against (interactive if not provided)
-        #[arg(long, short = 'c', num_args = 1..)]
-        commands: Option<Vec<String>>,
-
-        /// Pattern type for context
-        #[arg(long, value_enum, default_value = "destructive")]
-        pattern_type: PatternType,
+    /// Walks every built-in pack and the custom packs from `custom_paths`.
+    /// Each page has the pattern, severity, explanation, examples from the
+    /// regression corpus, safe alternatives, and an allowlist snippet, at the
+    /// path the rule hyperlinks and SARIF `helpUri` point to.
+    #[command(name = "generate")]
+    Generate {
+        /// Directory to write the pages to
+        #[arg(long, short = 'o', value_name = "DIR", default_value = "docs/rules")]
+        output: std::path::PathBuf,
+
+        /// Regression corpus to take blocked examples from (skipped if missing)
+        #[arg(long, value_name = "DIR", default_value = "tests/corpus")]
+        corpus: std::path::PathBuf,
+
+        /// Compare with the existing pages instead of writing them
+        #[arg(long)]
+        check: bool,
     },
+}
 
-    /// Validate pack structure and patterns
-    ///
-    /// Checks a pack source file for structural issues, pattern validity,
-    /// regex complexity, and test coverage.
-    #[command(name = "validate-pack")]
-    ValidatePack {
-        /// Pack ID to validate (e.g., "core.git", "database.postgresql")
-        pack_id: String,
-        // NOTE: Removed `verbose: bool` - use global `-v`/`--verbose` instead.
-        // The global flag (u8 count) conflicts with local bool flags.
+/// `dcg policy` subcommands
+#[derive(Subcommand, Debug)]
+pub enum PolicyAction {
+    /// Print the effective policy as a canonical JSON snapshot
+    #[command(name = "export")]
+    Export {
+        /// Write the snapshot to this file instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
     },
 
-    /// Debug pattern matching for a command
+    /// Show what differs between two snapshots (exit 1 if anything does)
+    #[command(name = "diff")]
+    Diff {
+        /// Reference snapshot
+        #[arg(value_name = "BEFORE")]
+        before: std::path::PathBuf,
+
+        /// Snapshot compared against the reference
+        #[arg(value_name = "AFTER")]
+        after: std::path::PathBuf,
+
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+}
+
+/// `dcg telemetry` subcommands
+#[cfg(feature = "telemetry")]
+#[derive(Subcommand, Debug)]
+pub enum TelemetryAction {
+    /// Show whether the ping is enabled and exactly what it would send
+    #[command(name = "status")]
+    Status {
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Opt in to the weekly anonymous usage ping
+    #[command(name = "enable")]
+    Enable,
+
+    /// Opt out and discard any counts collected so far
+    #[command(name = "disable")]
+    Disable,
+}
+
+/// `dcg trash` subcommands
+#[derive(Subcommand, Debug)]
+pub enum TrashAction {
+    /// Move files or directories to the trash
+    #[command(name = "put")]
+    Put {
+        /// Paths to trash
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<std::path::PathBuf>,
+    },
+
+    /// List trash entries, oldest first
+    #[command(name = "list")]
+    List {
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Move an entry's files back to where they were trashed from
+    #[command(name = "restore")]
+    Restore {
+        /// Entry ID from `dcg trash list` (default: the newest entry)
+        id: Option<String>,
+
+        /// Replace files that now exist at the original paths
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Permanently delete trash entries
+    #[command(name = "empty")]
+    Empty {
+        /// Only delete entries trashed more than this many days ago
+        #[arg(long, value_name = "DAYS")]
+        older_than_days: Option<u64>,
+
+        /// Confirm deletion
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// `dcg warnings` subcommands
+#[derive(Subcommand, Debug)]
+pub enum WarningsAction {
+    /// List queued warnings grouped by rule, most frequent first
+    #[command(name = "list")]
+    List {
+        #[command(flatten)]
+        selection: WarningSelectionArgs,
+
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Remove warnings from the queue without creating rules
+    #[command(name = "ack")]
+    Ack {
+        #[command(flatten)]
+        selection: WarningSelectionArgs,
+
+        /// Acknowledge every queued warning
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Turn each selected rule's commands into a block rule or allowlist entry
+    #[command(name = "convert")]
+    Convert {
+        /// What to create from each rule's commands
+        #[arg(long, value_enum)]
+        to: WarningDisposition,
+
+        #[command(flatten)]
+        selection: WarningSelectionArgs,
+
+        /// Convert every queued warning
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ApprovalsAction {
+    /// List requests waiting for approval, oldest first
+    #[command(name = "list")]
+    List {
+        /// Include requests that were already approved or rejected
+        #[arg(long)]
+        all: bool,
+
+        /// Show raw commands (default shows redacted)
+        #[arg(long)]
+        show_raw: bool,
+
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+
+    /// Approve requests; each mints an allow-once grant for its command
+    #[command(name = "approve")]
+    Approve {
+        /// Request IDs from `dcg approvals list`
+        #[arg(value_name = "ID", required = true)]
+        ids: Vec<u64>,
+
+        /// Allow a single use only (consumed after the first retry)
+        #[arg(long)]
+        single_use: bool,
+
+        /// Override explicit config blocklist
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Reject requests; the commands stay blocked
+    #[command(name = "reject")]
+    Reject {
+        /// Request IDs from `dcg approvals list`
+        #[arg(value_name = "ID", required = true)]
+        ids: Vec<u64>,
+    },
+
+    /// Walk the pending requests and approve or reject each one
+    #[command(name = "review")]
+    Review {
+        /// Show raw commands (default shows redacted)
+        #[arg(long)]
+        show_raw: bool,
+    },
+}
+
+/// Which queued warnings a `dcg warnings` subcommand applies to.
+#[derive(Args, Debug)]
+pub struct WarningSelectionArgs {
+    /// Warning IDs from `dcg warnings list`
+    #[arg(value_name = "ID")]
+    pub ids: Vec<u64>,
+
+    /// Only warnings from this rule (pack:pattern); repeatable
+    #[arg(long = "rule", value_name = "RULE_ID")]
+    pub rules: Vec<String>,
+
+    /// Only rules that warned at least this many times
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub min_count: u64,
+}
+
+impl WarningSelectionArgs {
+    fn selection(&self) -> crate::warnings::WarningSelection {
+        crate::warnings::WarningSelection {
+            ids: self.ids.clone(),
+            rules: self.rules.clone(),
+            min_count: self.min_count,
+        }
+    }
+}
+
+/// What `dcg warnings convert` creates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WarningDisposition {
+    /// An `[overrides] block` rule in `.dcg.toml` (or the user config)
+    Deny,
+    /// An allowlist pattern entry
+    Allow,
+}
+
+/// `dcg fuzz-corpus` subcommands
+#[derive(Subcommand, Debug)]
+pub enum FuzzCorpusAction {
+    /// Write regression-corpus commands and adversarial shell strings as
+    /// fuzz seeds (raw and wrapped in hook JSON)
+    #[command(name = "export")]
+    Export {
+        /// Directory to write seed files into
+        #[arg(long, default_value = crate::fuzz_corpus::DEFAULT_OUT_DIR)]
+        out: std::path::PathBuf,
+
+        /// Regression corpus directory (`[[case]]` TOML files)
+        #[arg(long, default_value = crate::fuzz_corpus::DEFAULT_CORPUS_DIR)]
+        corpus: std::path::PathBuf,
+
+        /// Only export the built-in adversarial strings
+        #[arg(long)]
+        no_corpus: bool,
+
+        /// Output format (pretty or json)
+        #[arg(long, short, value_enum, default_value_t = DoctorFormat::Pretty, env = "DCG_FORMAT")]
+        format: DoctorFormat,
+    },
+}
+
+/// Developer tool subcommands
+#[derive(Subcommand, Debug)]
+pub enum DevAction {
+    /// Test a regex pattern against sample commands
+    ///
+    /// Validates regex syntax and tests matching against provided commands.
+    /// Useful for developing and debugging pack patterns.
+    #[command(name = "test-pattern")]
+    TestPattern {
+        /// Regex pattern to test
+        pattern: String,
+
+        /// Test commands to match against (interactive if not provided)
+        #[arg(long, short = 'c', num_args = 1..)]
+        commands: Option<Vec<String>>,
+
+        /// Pattern type for context
+        #[arg(long, value_enum, default_value = "destructive")]
+        pattern_type: PatternType,
+    },
+
+    /// Validate pack structure and patterns
+    ///
+    /// Checks a pack source file for structural issues, pattern validity,
+    /// regex complexity, and test coverage.
+    #[command(name = "validate-pack")]
+    ValidatePack {
+        /// Pack ID to validate (e.g., "core.git", "database.postgresql")
+        pack_id: String,
+        // NOTE: Removed `verbose: bool` - use global `-v`/`--verbose` instead.
+        // The global flag (u8 count) conflicts with local bool flags.
+    },
+
+    /// Debug pattern matching for a command
     ///
     /// Shows detailed trace of how each pack evaluates the command,
     /// including keyword matching, safe/destructive pattern evaluation.
@@ -1192,6 +1984,18 @@ pub struct ScanCommand {
     #[arg(long = "max-findings", value_name = "N")]
     max_findings: Option<usize>,
 
+    /// Worker threads for scanning (0 = one per CPU, 1 = sequential)
+    ///
+    /// Output order and the `--max-findings` cutoff are unaffected.
+    #[arg(long, short = 'j', value_name = "N", default_value = "0")]
+    jobs: usize,
+
+    /// Stop scanning after this many seconds and report partial results
+    ///
+    /// A timed-out scan exits with code 124; Ctrl-C exits with 130.
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
     /// Exclude files matching glob pattern (repeatable)
     #[arg(long, value_name = "GLOB")]
     exclude: Vec<String>,
@@ -1374,6 +2178,11 @@ pub enum AllowlistAction {
         #[arg(long, conflicts_with = "project")]
         user: bool,
 
+        /// Show entries added, removed, or modified in the project allowlist
+        /// since a git revision (e.g. `main`, `HEAD~3`)
+        #[arg(long, value_name = "REV", conflicts_with = "user")]
+        diff: Option<String>,
+
         /// Output format
         #[arg(long, value_enum, default_value = "pretty", env = "DCG_FORMAT")]
         format: AllowlistOutputFormat,
@@ -1409,6 +2218,114 @@ pub enum AllowlistAction {
         #[arg(long)]
         strict: bool,
     },
+
+    /// Rewrite deprecated rule IDs to the current names of renamed patterns
+    #[command(name = "migrate-ids")]
+    MigrateIds {
+        /// Rewrite project allowlist (default if in git repo)
+        #[arg(long, conflicts_with = "user")]
+        project: bool,
+
+        /// Rewrite user allowlist
+        #[arg(long, conflicts_with = "project")]
+        user: bool,
+
+        /// Show what would change without changing the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show how often each allowlist entry has fired
+    #[command(name = "stats")]
+    Stats {
+        /// Show project allowlist only
+        #[arg(long, conflicts_with = "user")]
+        project: bool,
+
+        /// Show user allowlist only
+        #[arg(long, conflicts_with = "project")]
+        user: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "pretty", env = "DCG_FORMAT")]
+        format: AllowlistOutputFormat,
+    },
+
+    /// Remove entries that have not fired within a time window
+    #[command(name = "prune")]
+    Prune {
+        /// Remove entries unused for this long (e.g., "90d", "12w")
+        #[arg(long, value_name = "DURATION")]
+        unused_for: String,
+
+        /// Prune project allowlist (default if in git repo)
+        #[arg(long, conflicts_with = "user")]
+        project: bool,
+
+        /// Prune user allowlist
+        #[arg(long, conflicts_with = "project")]
+        user: bool,
+
+        /// Show what would be removed without changing the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Write a layer's entries as TOML or JSON for `allowlist import`
+    #[command(name = "export")]
+    Export {
+        /// Output format
+        #[arg(long, short = 'f', value_enum, default_value = "toml")]
+        format: crate::allowlist_exchange::ExchangeFormat,
+
+        /// Layer to export (default: project if in a git repo, else user)
+        #[arg(long, value_enum)]
+        layer: Option<AllowlistLayer>,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Merge entries from an exported TOML or JSON file into a layer
+    ///
+    /// Entries are matched by selector. An entry that already exists with a
+    /// different reason, expiration, or gating is a conflict; without
+    /// `--merge-strategy` conflicts are listed and nothing is written.
+    #[command(name = "import")]
+    Import {
+        /// File from `dcg allowlist export` (or `allowlist list --format json`)
+        file: std::path::PathBuf,
+
+        /// Layer to import into (default: project if in a git repo, else user)
+        #[arg(long, value_enum)]
+        layer: Option<AllowlistLayer>,
+
+        /// How to resolve conflicting entries
+        #[arg(long, value_enum)]
+        merge_strategy: Option<crate::allowlist_exchange::MergeStrategy>,
+
+        /// File format (default: from the extension, `.json` or TOML)
+        #[arg(long, short = 'f', value_enum)]
+        format: Option<crate::allowlist_exchange::ExchangeFormat>,
+
+        /// Show what would change without writing the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show the entries generated from this project's manifests and lockfiles
+    ///
+    /// `npm run <script>`, `cargo test`, `pytest` and similar prefixes are
+    /// derived from `package.json`, `Cargo.toml`, `pyproject.toml` and their
+    /// lockfiles on every load; nothing is written to disk. Disable with
+    /// `general.bootstrap_allowlist = false`.
+    #[command(name = "bootstrap")]
+    Bootstrap {
+        /// Output format
+        #[arg(long, value_enum, default_value = "pretty", env = "DCG_FORMAT")]
+        format: AllowlistOutputFormat,
+    },
 }
 
 /// Subcommands for managing allow-once entries.
@@ -1495,6 +2412,34 @@ pub struct AllowOnceCommand {
     /// Select by full hash when multiple match the code (apply-only)
     #[arg(long, value_name = "HASH", conflicts_with = "pick")]
     pub hash: Option<String>,
+
+    /// Allow only this exact command for 24h (apply-only)
+    #[arg(long, conflicts_with_all = ["rule", "session"])]
+    pub exact: bool,
+
+    /// Allow anything the blocking rule denies for 24h (apply-only)
+    #[arg(long, conflicts_with = "session")]
+    pub rule: bool,
+
+    /// Allow this exact command until the blocked agent session ends (apply-only)
+    #[arg(long)]
+    pub session: bool,
+}
+
+impl AllowOnceCommand {
+    /// Grant requested by `--exact`, `--rule` or `--session`, if any.
+    #[must_use]
+    pub const fn requested_grant(&self) -> Option<AllowOnceGrant> {
+        if self.rule {
+            Some(AllowOnceGrant::Rule)
+        } else if self.session {
+            Some(AllowOnceGrant::Session)
+        } else if self.exact {
+            Some(AllowOnceGrant::Exact)
+        } else {
+            None
+        }
+    }
 }
 
 /// Output format for allowlist list command
@@ -1523,6 +2468,25 @@ pub enum DoctorFormat {
 /// Pack subcommand actions
 #[derive(Subcommand, Debug)]
 pub enum PackAction {
+    /// List packs with their effective status and the config layer that
+    /// decided it (same as `dcg packs`)
+    #[command(name = "list")]
+    List {
+        /// Show only enabled packs
+        #[arg(long)]
+        enabled: bool,
+
+        /// Output format (json for structured output, pretty for human-readable)
+        #[arg(
+            long,
+            short = 'f',
+            value_enum,
+            default_value = "pretty",
+            env = "DCG_FORMAT"
+        )]
+        format: PacksFormat,
+    },
+
     /// Show information about a specific pack (built-in or external)
     #[command(name = "info")]
     Info {
@@ -1561,6 +2525,69 @@ pub enum PackAction {
         #[arg(long, short = 'f', value_enum, default_value_t = PackValidateFormat::Pretty, env = "DCG_FORMAT")]
         format: PackValidateFormat,
     },
+
+    /// Audit built-in pack patterns against their corpus examples
+    ///
+    /// Reports corpus examples a rule no longer blocks and rules without any
+    /// example. With `--semantic`, each example is also embedded where the
+    /// shell treats it as data (an `echo` string, a commit message, a `grep`
+    /// pattern) or glued to a longer program name; the probes are parsed with
+    /// tree-sitter-bash and rules whose regex matches inside a quoted word or
+    /// mid-token are reported. Exits 1 if a probe is still blocked.
+    #[command(name = "audit")]
+    Audit {
+        /// Packs to audit (default: all built-in packs)
+        #[arg(value_name = "PACK_ID")]
+        packs: Vec<String>,
+
+        /// Probe examples against real shell token boundaries
+        #[arg(long)]
+        semantic: bool,
+
+        /// Regression corpus to take examples from
+        #[arg(long, value_name = "DIR", default_value = "tests/corpus")]
+        corpus: std::path::PathBuf,
+
+        /// Output format
+        #[arg(long, short = 'f', value_enum, default_value_t = PackValidateFormat::Pretty, env = "DCG_FORMAT")]
+        format: PackValidateFormat,
+    },
+
+    /// Scaffold a new pack from a template
+    ///
+    /// Prompts for keywords and patterns (with example commands, severities,
+    /// explanations, and suggestions), then writes:
+    /// - External pack (default): `<output-dir>/<name>.yaml` plus a fixture
+    ///   corpus, and adds the file to `[packs] custom_paths` in `.dcg.toml`
+    /// - Built-in pack (`--builtin`, run from a dcg checkout):
+    ///   `src/packs/<category>/<name>.rs` with unit tests, fixtures in
+    ///   `tests/fixtures/`, and a `pub mod` line in the category `mod.rs`
+    #[command(name = "new")]
+    New {
+        /// Pack ID in namespace.name form (prompted if omitted)
+        pack_id: Option<String>,
+
+        /// Scaffold a built-in Rust pack module instead of an external YAML pack
+        #[arg(long)]
+        builtin: bool,
+
+        /// Directory for the external pack file and fixtures
+        #[arg(
+            long,
+            short = 'o',
+            default_value = ".dcg/packs",
+            conflicts_with = "builtin"
+        )]
+        output_dir: std::path::PathBuf,
+
+        /// Skip prompts and write a placeholder rule to edit by hand
+        #[arg(long)]
+        no_input: bool,
+
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 /// Output format for pack validate command
@@ -1655,7 +2682,12 @@ fn maybe_show_update_notice(cli: &Cli, config: &Config, verbosity: Verbosity) {
     }
 
     if let Some(
-        Command::Update(_) | Command::Hook(_) | Command::Completions { .. } | Command::McpServer,
+        Command::Update(_)
+        | Command::Hook(_)
+        | Command::Completions { .. }
+        | Command::McpServer
+        | Command::Lsp
+        | Command::Serve(_),
     ) = cli.command
     {
         // Skip update notices for update/hook/completion/server flows.
@@ -1708,7 +2740,15 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Some(Command::Completions { shell }) => {
             write_completions(shell)?;
         }
-        Some(Command::ListPacks { enabled, format }) => {
+        Some(Command::Schema { kind }) => {
+            print!("{}", kind.document());
+        }
+        Some(
+            Command::ListPacks { enabled, format }
+            | Command::Pack {
+                action: PackAction::List { enabled, format },
+            },
+        ) => {
             // Robot mode forces JSON output
             let robot_mode = cli.robot || std::env::var("DCG_ROBOT").is_ok();
             let effective_format = if robot_mode {
@@ -1730,13 +2770,14 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             );
         }
         Some(Command::Pack { action }) => {
-            handle_pack_command(&config, action)?;
+            handle_pack_command(&config, action, verbosity.level > 0)?;
         }
         Some(Command::TestCommand {
             command,
             config: config_path,
             with_packs,
             explain,
+            provenance,
             format,
             no_color,
             heredoc_scan,
@@ -1767,9 +2808,14 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 };
                 handle_explain(&effective_config, &command, explain_format, with_packs);
             } else {
+                let provenance = provenance.unwrap_or_else(|| {
+                    crate::provenance::Provenance::resolve(crate::provenance::Provenance::Agent)
+                });
                 let was_blocked = test_command(
                     &effective_config,
                     &command,
+                    cli.trace_file.as_deref(),
+                    provenance,
                     with_packs,
                     effective_format,
                     verbosity,
@@ -1789,14 +2835,51 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Some(Command::Init { output, force }) => {
             init_config(output, force)?;
         }
-        Some(Command::ShowConfig) => {
-            if !verbosity.quiet {
-                show_config(&config);
-            }
+        Some(Command::Onboard(cmd)) => {
+            handle_onboard_command(&config, &cmd)?;
+        }
+        Some(Command::Coverage(cmd)) => {
+            handle_coverage_command(&config, &cmd)?;
+        }
+        Some(Command::Paths { format }) => {
+            handle_paths_command(&config, format)?;
+        }
+        Some(Command::Migrate {
+            files,
+            dry_run,
+            format,
+        }) => {
+            handle_migrate_command(&config, &files, dry_run, format)?;
+        }
+        Some(Command::Policy { action }) => {
+            handle_policy_command(&config, action)?;
+        }
+        #[cfg(feature = "telemetry")]
+        Some(Command::Telemetry { action }) => {
+            handle_telemetry_command(&config, action)?;
+        }
+        Some(Command::ShowConfig { action }) => {
+            if !verbosity.quiet {
+                match action {
+                    Some(ConfigAction::Show { effective: true }) => {
+                        show_effective_config(&config)?;
+                    }
+                    Some(ConfigAction::Show { effective: false }) | None => {
+                        show_config(&config);
+                    }
+                }
+            }
         }
         Some(Command::Allowlist { action }) => {
             handle_allowlist_command(action)?;
         }
+        Some(Command::Undo {
+            list,
+            dry_run,
+            force,
+        }) => {
+            handle_undo_command(list, dry_run, force)?;
+        }
         Some(Command::Allow {
             rule_id,
             reason,
@@ -1855,11 +2938,16 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Some(Command::Scan(scan)) => {
             handle_scan_command(&config, scan, verbosity)?;
         }
+        Some(Command::ScanScripts(cmd)) => {
+            handle_scan_scripts_command(&config, &cmd, verbosity.quiet)?;
+        }
         Some(Command::Simulate(sim)) => {
             handle_simulate_command(sim, &config, verbosity)?;
         }
         Some(Command::Explain {
             command,
+            rule,
+            corpus,
             format,
             with_packs,
         }) => {
@@ -1871,10 +2959,28 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 format
             };
 
-            if !verbosity.quiet {
+            if let Some(rule_id) = rule {
+                handle_explain_rule(&config, &rule_id, &corpus, effective_format)?;
+            } else if !verbosity.quiet {
+                let command = command.unwrap_or_default();
                 handle_explain(&config, &command, effective_format, with_packs);
             }
         }
+        Some(Command::Preview {
+            command,
+            timeout,
+            format,
+        }) => {
+            handle_preview_command(&config, &command, timeout, format)?;
+        }
+        Some(Command::WhyAllowed {
+            command,
+            with_packs,
+        }) => {
+            if !verbosity.quiet {
+                handle_why_allowed(&config, &command, with_packs);
+            }
+        }
         Some(Command::Corpus(corpus)) => {
             handle_corpus_command(&config, &corpus)?;
         }
@@ -1884,15 +2990,50 @@ pub fn run_command(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Some(Command::History { action }) => {
             handle_history_command(&config, action)?;
         }
+        Some(Command::Trash { action }) => {
+            handle_trash_command(&config, action)?;
+        }
         Some(Command::SuggestAllowlist(cmd)) => {
             handle_suggest_allowlist_command(&config, &cmd)?;
         }
+        Some(Command::Review(cmd)) => {
+            handle_review_command(&config, &cmd)?;
+        }
+        Some(Command::Warnings { action }) => {
+            handle_warnings_command(&config, action)?;
+        }
+        Some(Command::Approvals { action }) => {
+            handle_approvals_command(&config, action)?;
+        }
+        Some(Command::FuzzCorpus { action }) => {
+            handle_fuzz_corpus_command(action)?;
+        }
         Some(Command::Dev { action }) => {
             handle_dev_command(&config, action, verbosity)?;
         }
+        Some(Command::Docs {
+            action:
+                DocsAction::Generate {
+                    output,
+                    corpus,
+                    check,
+                },
+        }) => {
+            handle_docs_generate(&config, &output, &corpus, check)?;
+        }
+        Some(Command::RenderFixtures { dir, check }) => {
+            handle_render_fixtures(&dir, check)?;
+        }
         Some(Command::McpServer) => {
             crate::mcp::run_mcp_server()?;
         }
+        Some(Command::Lsp) => {
+            crate::lsp::run_lsp_server()?;
+        }
+        Some(Command::Serve(cmd)) => {
+            let token = std::env::var(crate::admin_api::ENV_ADMIN_TOKEN).unwrap_or_default();
+            crate::admin_api::run(config, &cmd.listen, token)?;
+        }
         None => {
             // No subcommand - run in hook mode (default behavior)
             // This is handled by main.rs
@@ -1908,12 +3049,51 @@ fn write_completions(shell: CompletionShell) -> Result<(), Box<dyn std::error::E
 
     let mut cmd = Cli::command();
     let bin_name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    generate(shell.as_shell(), &mut cmd, &bin_name, &mut script);
+    let script =
+        crate::completion::add_dynamic_completion(shell.as_shell(), String::from_utf8(script)?);
     let mut stdout = io::stdout();
-    generate(shell.as_shell(), &mut cmd, &bin_name, &mut stdout);
+    stdout.write_all(script.as_bytes())?;
     stdout.flush()?;
     Ok(())
 }
 
+/// `dcg __complete`: print the pack or rule IDs completing the last of
+/// `words` (the words after `dcg` up to the cursor) and return the exit code.
+///
+/// Returns 1 without output when the last word is not a pack or rule ID
+/// argument, so the shell falls back to its static completion.
+#[must_use]
+pub fn complete_words(config: &Config, words: &[String]) -> i32 {
+    use crate::completion::{candidates, value_kind};
+
+    let Some(kind) = value_kind(Cli::command(), words) else {
+        return 1;
+    };
+    let current = words.last().map_or("", String::as_str);
+    for candidate in candidates(kind, current, &all_packs(config)) {
+        println!("{candidate}");
+    }
+    0
+}
+
+/// Built-in packs sorted by ID, followed by the external packs from
+/// `custom_paths`.
+fn all_packs(config: &Config) -> Vec<&'static Pack> {
+    let mut pack_ids = REGISTRY.all_pack_ids();
+    pack_ids.sort_unstable();
+    let mut packs: Vec<&Pack> = pack_ids
+        .into_iter()
+        .filter_map(|id| REGISTRY.get(id))
+        .collect();
+    let external = load_external_packs(&config.packs.expand_custom_paths());
+    let mut external_packs: Vec<(&String, &Pack)> = external.iter_packs().collect();
+    external_packs.sort_by_key(|(id, _)| *id);
+    packs.extend(external_packs.into_iter().map(|(_, pack)| pack));
+    packs
+}
+
 // ============================================================================
 // Hook Command (dcg hook --batch)
 // ============================================================================
@@ -1923,6 +3103,17 @@ fn write_completions(shell: CompletionShell) -> Result<(), Box<dyn std::error::E
 fn run_hook_command(config: &Config, cmd: &HookCommand) -> Result<(), Box<dyn std::error::Error>> {
     use std::io::{self, BufRead, Write};
 
+    match cmd.action {
+        Some(HookAction::Install {
+            kind: GitHookKind::PreCommit,
+            fail_on,
+        }) => return install_scan_pre_commit_hook(fail_on),
+        Some(HookAction::Uninstall {
+            kind: GitHookKind::PreCommit,
+        }) => return uninstall_scan_pre_commit_hook(),
+        None => {}
+    }
+
     // If not batch mode and not parallel, fall through to normal hook mode
     if !cmd.batch && !cmd.parallel {
         // Delegate to main.rs hook mode by returning an error
@@ -1940,8 +3131,8 @@ fn run_hook_command(config: &Config, cmd: &HookCommand) -> Result<(), Box<dyn st
     };
 
     // Load configuration for evaluation
-    let compiled_overrides = config.overrides.compile();
-    let allowlists = crate::load_default_allowlists();
+    let compiled_overrides = config.compile_overrides();
+    let allowlists = config.load_allowlists();
     let heredoc_settings = config.heredoc_settings();
     let enabled_packs = config.enabled_pack_ids();
     let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
@@ -2127,6 +3318,10 @@ fn evaluate_batch_line(
         None, // No deadline for batch mode
     );
 
+    if let Some(key) = eval_result.allowlist_entry.as_deref() {
+        crate::allowlist_usage::record_use(key, None);
+    }
+
     match eval_result.decision {
         EvaluationDecision::Allow => BatchHookOutput {
             index,
@@ -2175,6 +3370,9 @@ fn list_packs(
 
     let enabled_packs = config.enabled_pack_ids();
     let infos = REGISTRY.list_packs(&enabled_packs);
+    let packs_config = config.effective_packs();
+    let pins = packs_config.pins();
+    let warnings = config.pack_pin_warnings();
 
     // Build pack list (filtered if enabled_only)
     let mut pack_list: Vec<PackInfo> = infos
@@ -2187,7 +3385,10 @@ fn list_packs(
                 name: info.name.to_string(),
                 category,
                 description: info.description.to_string(),
+                version: info.version,
+                pinned_version: pins.get(info.id.as_str()).map(ToString::to_string),
                 enabled: info.enabled,
+                decided_by: packs_config.decided_by(&info.id, info.enabled),
                 safe_pattern_count: info.safe_pattern_count,
                 destructive_pattern_count: info.destructive_pattern_count,
             }
@@ -2210,7 +3411,13 @@ fn list_packs(
                 name: pack.name.to_string(),
                 category,
                 description: pack.description.to_string(),
+                version: external_store.version(id).unwrap_or(1),
+                pinned_version: pins.get(id.as_str()).map(ToString::to_string),
                 enabled: is_enabled,
+                decided_by: Some(crate::config::PackDecision {
+                    entry: id.clone(),
+                    layer: "custom_paths".to_string(),
+                }),
                 safe_pattern_count: pack.safe_patterns.len(),
                 destructive_pattern_count: pack.destructive_patterns.len(),
             });
@@ -2225,6 +3432,7 @@ fn list_packs(
             packs: pack_list,
             enabled_count,
             total_count,
+            warnings,
         };
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
         return;
@@ -2258,17 +3466,20 @@ fn list_packs(
                 }
 
                 let status = if info.enabled { "✓" } else { "○" };
+                let note = pack_decision_note(&packs_config, &info.id, info.enabled);
                 if verbose {
                     println!(
-                        "    {} {} - {} ({} safe, {} destructive)",
+                        "    {} {} v{} - {} ({} safe, {} destructive){}",
                         status,
                         info.id,
+                        info.version,
                         info.description,
                         info.safe_pattern_count,
-                        info.destructive_pattern_count
+                        info.destructive_pattern_count,
+                        note
                     );
                 } else {
-                    println!("    {} {} - {}", status, info.id, info.name);
+                    println!("    {} {} - {}{}", status, info.id, info.name, note);
                 }
             }
             println!();
@@ -2276,10 +3487,32 @@ fn list_packs(
 
         println!("Legend: ✓ = enabled, ○ = disabled");
         println!();
+        for warning in &warnings {
+            println!("Warning: {warning}");
+        }
         println!("Enable packs in ~/.config/dcg/config.toml");
     }
 }
 
+/// Describe which config layer decided a pack's status, e.g.
+/// ` (enabled by project: database)` (empty when nothing enabled the pack).
+fn pack_decision_note(packs: &crate::config::PacksConfig, pack_id: &str, enabled: bool) -> String {
+    let Some(decision) = packs.decided_by(pack_id, enabled) else {
+        return String::new();
+    };
+    let verb = if enabled { "enabled" } else { "disabled" };
+    let pin = packs
+        .pins()
+        .get(pack_id)
+        .map(|pin| format!(", pinned @{pin}"))
+        .unwrap_or_default();
+    if decision.entry == pack_id {
+        format!(" ({verb} by {}{pin})", decision.layer)
+    } else {
+        format!(" ({verb} by {}: {}{pin})", decision.layer, decision.entry)
+    }
+}
+
 /// Rich terminal packs output using DcgConsole and markup.
 #[cfg(feature = "rich-output")]
 fn list_packs_rich(config: &Config, enabled_only: bool, verbose: bool) {
@@ -2288,6 +3521,7 @@ fn list_packs_rich(config: &Config, enabled_only: bool, verbose: bool) {
     let con = console();
     let enabled_packs = config.enabled_pack_ids();
     let infos = REGISTRY.list_packs(&enabled_packs);
+    let packs_config = config.effective_packs();
 
     // Header
     con.rule(Some("[bold cyan] Available Packs [/]"));
@@ -2314,17 +3548,19 @@ fn list_packs_rich(config: &Config, enabled_only: bool, verbose: bool) {
                 ("○", "dim")
             };
 
+            let note = pack_decision_note(&packs_config, &info.id, info.enabled);
             if verbose {
                 con.print(&format!(
-                    "  [{color}]{status}[/] [bold]{id}[/] - {desc} [dim]({safe} safe, {destr} destructive)[/]",
+                    "  [{color}]{status}[/] [bold]{id}[/] v{version} - {desc} [dim]({safe} safe, {destr} destructive){note}[/]",
                     id = info.id,
+                    version = info.version,
                     desc = info.description,
                     safe = info.safe_pattern_count,
                     destr = info.destructive_pattern_count
                 ));
             } else {
                 con.print(&format!(
-                    "  [{color}]{status}[/] [bold]{id}[/] - {name}",
+                    "  [{color}]{status}[/] [bold]{id}[/] - {name}[dim]{note}[/]",
                     id = info.id,
                     name = info.name
                 ));
@@ -2334,6 +3570,9 @@ fn list_packs_rich(config: &Config, enabled_only: bool, verbose: bool) {
     }
 
     con.print("[dim]Legend: [green]●[/] = enabled, ○ = disabled[/]");
+    for warning in config.pack_pin_warnings() {
+        con.print(&format!("[yellow]Warning:[/] {warning}"));
+    }
     con.print("[dim]Enable packs in ~/.config/dcg/config.toml[/]");
 }
 
@@ -2467,7 +3706,10 @@ fn pack_info(
                 println!("    Explanation: {explanation}");
             }
             for suggestion in pattern.suggestions {
-                println!("    Suggestion: {} - {}", suggestion.command, suggestion.description);
+                println!(
+                    "    Suggestion: {} - {}",
+                    suggestion.command, suggestion.description
+                );
             }
         }
     }
@@ -2483,8 +3725,11 @@ fn pack_info(
 fn handle_pack_command(
     _config: &Config,
     action: PackAction,
+    verbose: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match action {
+        // Dispatched with `dcg packs` in `run_command`.
+        PackAction::List { .. } => unreachable!("pack list is handled by run_command"),
         PackAction::Info {
             pack_id,
             no_patterns,
@@ -2499,138 +3744,760 @@ fn handle_pack_command(
         } => {
             pack_validate(&file_path, strict, format)?;
         }
+        PackAction::Audit {
+            packs,
+            semantic,
+            corpus,
+            format,
+        } => {
+            pack_audit(&packs, semantic, &corpus, format, verbose)?;
+        }
+        PackAction::New {
+            pack_id,
+            builtin,
+            output_dir,
+            no_input,
+            force,
+        } => {
+            pack_new(pack_id.as_deref(), builtin, &output_dir, no_input, force)?;
+        }
     }
     Ok(())
 }
 
-/// Validate an external pack YAML file
-#[allow(clippy::too_many_lines)]
-fn pack_validate(
-    file_path: &str,
-    strict: bool,
+/// Audit built-in packs against the corpus at `corpus`.
+fn pack_audit(
+    pack_ids: &[String],
+    semantic: bool,
+    corpus: &std::path::Path,
     format: PackValidateFormat,
+    verbose: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use crate::packs::external::{
-        CURRENT_SCHEMA_VERSION, ExternalPack, RegexEngineType, analyze_pack_engines,
-        check_builtin_collision, summarize_pack_engines,
-    };
-    use std::path::Path;
+    use crate::packs::audit::audit_pack;
+    use colored::Colorize;
 
-    let path = Path::new(file_path);
+    if !corpus.is_dir() {
+        return Err(format!("corpus directory not found: {}", corpus.display()).into());
+    }
+    let examples = crate::rule_docs::load_examples(corpus)?;
 
-    let mut result = PackValidationOutput {
-        valid: true,
-        file: file_path.to_string(),
-        pack_id: None,
-        pack_name: None,
-        pack_version: None,
-        errors: Vec::new(),
-        warnings: Vec::new(),
-        suggestions: Vec::new(),
-        patterns: None,
-        engine_summary: None,
+    let mut ids = if pack_ids.is_empty() {
+        REGISTRY
+            .all_pack_ids()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    } else {
+        for id in pack_ids {
+            if REGISTRY.get(id).is_none() && REGISTRY.packs_in_category(id).is_empty() {
+                return Err(format!("unknown built-in pack: {id}").into());
+            }
+        }
+        REGISTRY.expand_enabled_ordered(&pack_ids.iter().cloned().collect())
     };
+    ids.sort_unstable();
 
-    // Step 1: Check if file exists
-    if !path.exists() {
-        result.valid = false;
-        result.errors.push(PackValidationIssue {
-            code: "E001".to_string(),
-            message: format!("File not found: {file_path}"),
-            suggestion: None,
+    let audits: Vec<_> = ids
+        .iter()
+        .filter_map(|id| REGISTRY.get(id))
+        .map(|pack| audit_pack(pack, &examples, semantic))
+        .collect();
+    let failed = audits
+        .iter()
+        .any(crate::packs::audit::PackAudit::has_problems);
+
+    if format == PackValidateFormat::Json {
+        let output = serde_json::json!({
+            "semantic": semantic,
+            "corpus": corpus,
+            "ok": !failed,
+            "packs": audits,
         });
-        return output_pack_validation(&result, format, strict);
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        let (mut rules, mut unexampled, mut stale, mut blocked, mut shielded) = (0, 0, 0, 0, 0);
+        for audit in &audits {
+            rules += audit.rules;
+            unexampled += audit.unexampled.len();
+            let shown = audit.stale_examples.len()
+                + audit
+                    .findings
+                    .iter()
+                    .filter(|finding| finding.blocked || verbose)
+                    .count();
+            shielded += audit.findings.iter().filter(|f| !f.blocked).count();
+            if shown == 0 {
+                continue;
+            }
+            println!("{}", audit.pack_id.bold());
+            for entry in &audit.stale_examples {
+                stale += 1;
+                println!(
+                    "  {} {} no longer blocks `{}`",
+                    "✗".red(),
+                    entry.rule_id,
+                    entry.example
+                );
+            }
+            for finding in &audit.findings {
+                let line = format!(
+                    "{} matches `{}` {} in `{}` ({})",
+                    finding.rule_id,
+                    finding.matched,
+                    finding.placement,
+                    finding.probe,
+                    finding.probe_kind.label()
+                );
+                if finding.blocked {
+                    blocked += 1;
+                    println!("  {} {line}", "✗".red());
+                } else if verbose {
+                    println!("  {} {}", "·".dimmed(), line.dimmed());
+                }
+            }
+        }
+        if stale + blocked > 0 || (verbose && shielded > 0) {
+            println!();
+        }
+        println!(
+            "Audited {rules} rules in {} packs ({unexampled} without corpus examples).",
+            audits.len()
+        );
+        if stale > 0 {
+            println!(
+                "{}",
+                format!("{stale} corpus example(s) no longer blocked.").red()
+            );
+        }
+        if semantic {
+            if blocked > 0 {
+                println!(
+                    "{}",
+                    format!("{blocked} probe(s) where quoted or mid-word text is still blocked.")
+                        .red()
+                );
+            }
+            if shielded > 0 {
+                println!(
+                    "{shielded} probe(s) matched by the bare regex but allowed after context sanitization{}.",
+                    if verbose { "" } else { " (-v to list)" }
+                );
+            }
+            if blocked == 0 && stale == 0 {
+                println!("{}", "No quoting false positives found.".green());
+            }
+        }
     }
 
-    // Step 2: Read file content
-    let content = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(e) => {
-            result.valid = false;
-            result.errors.push(PackValidationIssue {
-                code: "E002".to_string(),
-                message: format!("Failed to read file: {e}"),
-                suggestion: None,
-            });
-            return output_pack_validation(&result, format, strict);
-        }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Scaffold a new built-in or external pack.
+fn pack_new(
+    pack_id: Option<&str>,
+    builtin: bool,
+    output_dir: &std::path::Path,
+    no_input: bool,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::packs::scaffold::PackScaffold;
+    use colored::Colorize;
+
+    let interactive =
+        !no_input && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+
+    let scaffold = if interactive {
+        prompt_pack_scaffold(pack_id)?
+    } else {
+        let Some(pack_id) = pack_id else {
+            return Err("Pack ID is required when prompts are disabled (e.g., dcg pack new mycompany.deploy --no-input)".into());
+        };
+        PackScaffold::placeholder(pack_id)
     };
 
-    // Step 3: Parse YAML
-    let pack: ExternalPack = match serde_yaml::from_str(&content) {
-        Ok(p) => p,
-        Err(e) => {
-            result.valid = false;
-            result.errors.push(PackValidationIssue {
-                code: "E003".to_string(),
-                message: format!("YAML parse error: {e}"),
-                suggestion: Some("Check YAML syntax (indentation, colons, quotes)".to_string()),
-            });
-            return output_pack_validation(&result, format, strict);
+    if let Err(errors) = scaffold.validate() {
+        eprintln!("{} Pack scaffold is invalid:", "✗".red());
+        for error in &errors {
+            eprintln!("  - {error}");
         }
-    };
+        return Err(format!("{} validation error(s)", errors.len()).into());
+    }
 
-    // Store basic pack info for output
-    result.pack_id = Some(pack.id.clone());
-    result.pack_name = Some(pack.name.clone());
-    result.pack_version = Some(pack.version.clone());
+    let written = if builtin {
+        write_builtin_pack_scaffold(&scaffold, force)?
+    } else {
+        write_external_pack_scaffold(&scaffold, output_dir, force)?
+    };
 
-    // Step 4: Validate schema version
-    if pack.schema_version > CURRENT_SCHEMA_VERSION {
-        result.valid = false;
-        result.errors.push(PackValidationIssue {
-            code: "E004".to_string(),
-            message: format!(
-                "Schema version {} is not supported (max: {})",
-                pack.schema_version, CURRENT_SCHEMA_VERSION
-            ),
-            suggestion: Some(format!(
-                "Use schema_version: {CURRENT_SCHEMA_VERSION} or lower"
-            )),
-        });
+    println!("{} Created pack {}:", "✓".green(), scaffold.id.bold());
+    for path in &written.files {
+        println!("  - {}", path.display());
     }
-
-    // Step 5: Validate ID format
-    let id_regex = regex::Regex::new(r"^[a-z][a-z0-9_]*\.[a-z][a-z0-9_]*$").unwrap();
-    if !id_regex.is_match(&pack.id) {
-        result.valid = false;
-        result.errors.push(PackValidationIssue {
-            code: "E005".to_string(),
-            message: format!(
-                "Invalid pack ID '{}': must match pattern namespace.name (e.g., 'mycompany.deploy')",
-                pack.id
-            ),
-            suggestion: Some("Use lowercase letters, numbers, underscores. Format: namespace.name".to_string()),
-        });
+    for note in &written.notes {
+        println!("  {} {note}", "•".cyan());
+    }
+    println!();
+    println!("{}", "Next steps:".bold());
+    for (index, step) in written.next_steps.iter().enumerate() {
+        println!("  {}. {step}", index + 1);
     }
+    Ok(())
+}
 
-    // Step 6: Validate version format (semver)
-    let version_regex = regex::Regex::new(r"^\d+\.\d+\.\d+$").unwrap();
-    if !version_regex.is_match(&pack.version) {
-        result.valid = false;
-        result.errors.push(PackValidationIssue {
-            code: "E006".to_string(),
-            message: format!(
-                "Invalid version '{}': must be semantic version (e.g., '1.0.0')",
-                pack.version
-            ),
-            suggestion: Some("Use MAJOR.MINOR.PATCH format (e.g., 1.0.0, 2.1.3)".to_string()),
-        });
+/// Files and follow-up steps produced by a pack scaffold.
+struct ScaffoldOutput {
+    files: Vec<std::path::PathBuf>,
+    notes: Vec<String>,
+    next_steps: Vec<String>,
+}
+
+/// Refuse to overwrite any of `paths` unless `force` is set.
+///
+/// Checked before anything is written so a scaffold is never left half-done.
+fn check_scaffold_targets(
+    paths: &[&std::path::PathBuf],
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match paths.iter().find(|path| path.exists()) {
+        Some(path) if !force => Err(format!(
+            "{} already exists. Use --force to overwrite.",
+            path.display()
+        )
+        .into()),
+        _ => Ok(()),
     }
+}
 
-    // Step 7: Check for empty pack
-    if pack.destructive_patterns.is_empty() && pack.safe_patterns.is_empty() {
-        result.valid = false;
-        result.errors.push(PackValidationIssue {
-            code: "E007".to_string(),
-            message: "Pack has no patterns defined".to_string(),
-            suggestion: Some("Add at least one destructive_pattern or safe_pattern".to_string()),
-        });
+/// Write `contents` to `path`, creating parent directories.
+fn write_scaffold_file(
+    path: &std::path::Path,
+    contents: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
     }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
 
-    // Step 8: Check for duplicate pattern names
-    let mut seen_names = std::collections::HashSet::new();
-    for pattern in &pack.destructive_patterns {
+/// Write an external YAML pack and register it in the project config.
+fn write_external_pack_scaffold(
+    scaffold: &crate::packs::scaffold::PackScaffold,
+    output_dir: &std::path::Path,
+    force: bool,
+) -> Result<ScaffoldOutput, Box<dyn std::error::Error>> {
+    let pack_path = output_dir.join(format!("{}.yaml", scaffold.module_name()));
+    let fixtures_dir = output_dir.join("tests");
+    let stem = scaffold.fixture_stem();
+    let destructive_path = fixtures_dir.join(format!("{stem}_destructive.toml"));
+    let safe_path = fixtures_dir.join(format!("{stem}_safe.toml"));
+
+    check_scaffold_targets(&[&pack_path, &destructive_path, &safe_path], force)?;
+    write_scaffold_file(&pack_path, &scaffold.render_yaml())?;
+    write_scaffold_file(&destructive_path, &scaffold.render_destructive_fixtures())?;
+    write_scaffold_file(&safe_path, &scaffold.render_safe_fixtures())?;
+
+    let mut notes = Vec::new();
+    let repo_root =
+        find_repo_root_from_cwd().unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let config_path = repo_root.join(".dcg.toml");
+    let absolute_pack = if pack_path.is_absolute() {
+        pack_path.clone()
+    } else {
+        std::env::current_dir()?.join(&pack_path)
+    };
+    let entry = absolute_pack.strip_prefix(&repo_root).map_or_else(
+        |_| absolute_pack.to_string_lossy().into_owned(),
+        |relative| relative.to_string_lossy().into_owned(),
+    );
+    let before = std::fs::read_to_string(&config_path).ok();
+    if register_custom_pack_path(&config_path, &entry)? {
+        record_policy_change("pack new", &config_path, before.as_deref());
+        notes.push(format!(
+            "Added {entry} to [packs] custom_paths in {}",
+            config_path.display()
+        ));
+    } else {
+        notes.push(format!(
+            "{} already loads {entry} via [packs] custom_paths",
+            config_path.display()
+        ));
+    }
+
+    Ok(ScaffoldOutput {
+        files: vec![pack_path.clone(), destructive_path, safe_path],
+        notes,
+        next_steps: vec![
+            format!("Edit {} and refine the patterns", pack_path.display()),
+            format!("dcg pack validate {}", pack_path.display()),
+            format!(
+                "dcg test \"{}\"",
+                scaffold.destructive_patterns[0]
+                    .example
+                    .replace('"', "\\\"")
+            ),
+        ],
+    })
+}
+
+/// Add `entry` to `[packs] custom_paths` in the config at `config_path`.
+///
+/// Returns `false` if an existing entry (literal or glob) already covers it.
+fn register_custom_pack_path(
+    config_path: &std::path::Path,
+    entry: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut doc = if config_path.exists() {
+        std::fs::read_to_string(config_path)?.parse::<toml_edit::DocumentMut>()?
+    } else {
+        toml_edit::DocumentMut::new()
+    };
+
+    let packs = doc
+        .entry("packs")
+        .or_insert_with(|| toml_edit::Item::Table(toml_edit::Table::new()))
+        .as_table_mut()
+        .ok_or("[packs] in .dcg.toml is not a table")?;
+    let paths = packs
+        .entry("custom_paths")
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or("packs.custom_paths in .dcg.toml is not an array")?;
+
+    let covered = paths.iter().filter_map(|v| v.as_str()).any(|existing| {
+        existing == entry || glob::Pattern::new(existing).is_ok_and(|p| p.matches(entry))
+    });
+    if covered {
+        return Ok(false);
+    }
+    paths.push(entry);
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(config_path, doc.to_string())?;
+    Ok(true)
+}
+
+/// Append a `[overrides] block` rule to a config file.
+///
+/// Returns `false` if a block rule with the same pattern already exists.
+fn register_block_override(
+    config_path: &std::path::Path,
+    pattern: &str,
+    reason: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut doc = if config_path.exists() {
+        std::fs::read_to_string(config_path)?.parse::<toml_edit::DocumentMut>()?
+    } else {
+        toml_edit::DocumentMut::new()
+    };
+
+    let overrides = doc
+        .entry("overrides")
+        .or_insert_with(|| toml_edit::Item::Table(toml_edit::Table::new()))
+        .as_table_mut()
+        .ok_or("[overrides] is not a table")?;
+    let block = overrides
+        .entry("block")
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or("overrides.block is not an array")?;
+
+    let exists = block.iter().any(|v| {
+        v.as_inline_table()
+            .and_then(|t| t.get("pattern"))
+            .and_then(toml_edit::Value::as_str)
+            == Some(pattern)
+    });
+    if exists {
+        return Ok(false);
+    }
+    let mut entry = toml_edit::InlineTable::new();
+    entry.insert("pattern", pattern.into());
+    entry.insert("reason", reason.into());
+    block.push(entry);
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(config_path, doc.to_string())?;
+    Ok(true)
+}
+
+/// Write a built-in Rust pack module and declare it in its category.
+fn write_builtin_pack_scaffold(
+    scaffold: &crate::packs::scaffold::PackScaffold,
+    force: bool,
+) -> Result<ScaffoldOutput, Box<dyn std::error::Error>> {
+    use crate::packs::scaffold::{insert_mod_declaration, render_category_mod};
+    use std::path::Path;
+
+    let packs_dir = Path::new("src/packs");
+    let packs_mod = packs_dir.join("mod.rs");
+    if !packs_mod.is_file() {
+        return Err(
+            "--builtin must be run from the root of a dcg source checkout (src/packs/mod.rs not found)"
+                .into(),
+        );
+    }
+
+    let category = scaffold.category();
+    let module = scaffold.module_name();
+    let category_dir = packs_dir.join(category);
+    let module_path = category_dir.join(format!("{module}.rs"));
+    let category_mod = category_dir.join("mod.rs");
+    let fixtures_dir = Path::new("tests/fixtures");
+    let stem = scaffold.fixture_stem();
+    let destructive_path = fixtures_dir.join(format!("{stem}_destructive.toml"));
+    let safe_path = fixtures_dir.join(format!("{stem}_safe.toml"));
+
+    check_scaffold_targets(&[&module_path, &destructive_path, &safe_path], force)?;
+    write_scaffold_file(&module_path, &scaffold.render_module())?;
+    write_scaffold_file(&destructive_path, &scaffold.render_destructive_fixtures())?;
+    write_scaffold_file(&safe_path, &scaffold.render_safe_fixtures())?;
+
+    let mut files = vec![module_path, destructive_path, safe_path];
+    let mut notes = Vec::new();
+    if category_mod.exists() {
+        let source = std::fs::read_to_string(&category_mod)?;
+        if let Some(updated) = insert_mod_declaration(&source, module) {
+            std::fs::write(&category_mod, updated)?;
+            notes.push(format!(
+                "Declared `pub mod {module};` in {}",
+                category_mod.display()
+            ));
+        }
+    } else {
+        std::fs::write(&category_mod, render_category_mod(category, module))?;
+        files.push(category_mod);
+        let source = std::fs::read_to_string(&packs_mod)?;
+        if let Some(updated) = insert_mod_declaration(&source, category) {
+            std::fs::write(&packs_mod, updated)?;
+            notes.push(format!(
+                "Declared `pub mod {category};` in {}",
+                packs_mod.display()
+            ));
+        }
+    }
+
+    Ok(ScaffoldOutput {
+        files,
+        notes,
+        next_steps: vec![
+            format!(
+                "Register the pack in PACK_ENTRIES (src/packs/mod.rs) and bump the array length:\n       {}",
+                scaffold.render_pack_entry()
+            ),
+            "Add the pack to the README pack list and regenerate docs/packs/".to_string(),
+            format!("cargo fmt && cargo test --lib packs::{category}::{module}"),
+        ],
+    })
+}
+
+/// Interactively collect a pack scaffold.
+#[allow(clippy::too_many_lines)]
+fn prompt_pack_scaffold(
+    pack_id: Option<&str>,
+) -> Result<crate::packs::scaffold::PackScaffold, Box<dyn std::error::Error>> {
+    use crate::packs::scaffold::{
+        PackScaffold, ScaffoldDestructivePattern, ScaffoldSafePattern, ScaffoldSuggestion,
+        default_display_name,
+    };
+    use inquire::Confirm;
+    use inquire::validator::Validation;
+
+    let required = |input: &str| {
+        if input.trim().is_empty() {
+            Ok(Validation::Invalid("This field is required".into()))
+        } else {
+            Ok(Validation::Valid)
+        }
+    };
+    let valid_regex = |input: &str| {
+        Ok(
+            match crate::packs::regex_engine::CompiledRegex::new(input) {
+                Ok(_) => Validation::Valid,
+                Err(e) => Validation::Invalid(format!("Invalid regex: {e}").into()),
+            },
+        )
+    };
+
+    let id = match pack_id {
+        Some(id) => id.to_string(),
+        None => {
+            let id_regex = regex::Regex::new(crate::packs::external::ID_PATTERN)?;
+            Text::new("Pack ID (namespace.name):")
+                .with_help_message("e.g. mycompany.deploy or database.cockroach")
+                .with_validator(move |input: &str| {
+                    Ok(if id_regex.is_match(input) {
+                        Validation::Valid
+                    } else {
+                        Validation::Invalid(
+                            "Use lowercase letters, digits, underscores: namespace.name".into(),
+                        )
+                    })
+                })
+                .prompt()?
+        }
+    };
+    let mut scaffold = PackScaffold::placeholder(&id);
+
+    scaffold.name = Text::new("Pack name:")
+        .with_default(&default_display_name(&id))
+        .prompt()?;
+    scaffold.description = Text::new("Description:")
+        .with_default(&scaffold.description)
+        .with_validator(required)
+        .prompt()?;
+    let keywords = Text::new("Keywords (comma-separated):")
+        .with_default(&scaffold.keywords.join(", "))
+        .with_help_message("Commands without any keyword skip this pack entirely")
+        .with_validator(required)
+        .prompt()?;
+    scaffold.keywords = keywords
+        .split(',')
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+
+    scaffold.destructive_patterns.clear();
+    loop {
+        println!();
+        println!(
+            "Destructive pattern #{}",
+            scaffold.destructive_patterns.len() + 1
+        );
+        let name = Text::new("  Pattern name (kebab-case):")
+            .with_validator(required)
+            .prompt()?;
+        let (regex, example) = prompt_regex_with_example(valid_regex, required)?;
+        let severity = match Select::new("  Severity:", vec!["critical", "high", "medium", "low"])
+            .with_starting_cursor(1)
+            .prompt()?
+        {
+            "critical" => PackSeverity::Critical,
+            "medium" => PackSeverity::Medium,
+            "low" => PackSeverity::Low,
+            _ => PackSeverity::High,
+        };
+        let reason = Text::new("  Reason (shown on denial):")
+            .with_validator(required)
+            .prompt()?;
+        let explanation = Text::new("  Explanation (optional):").prompt()?;
+
+        let mut suggestions = Vec::new();
+        loop {
+            let command = Text::new("  Safer alternative command (blank to finish):").prompt()?;
+            if command.trim().is_empty() {
+                break;
+            }
+            let description = Text::new("    Why is it safer?").prompt()?;
+            suggestions.push(ScaffoldSuggestion {
+                command: command.trim().to_string(),
+                description: description.trim().to_string(),
+            });
+        }
+
+        scaffold
+            .destructive_patterns
+            .push(ScaffoldDestructivePattern {
+                name: name.trim().to_string(),
+                regex,
+                example,
+                severity,
+                reason: reason.trim().to_string(),
+                explanation: Some(explanation.trim().to_string()).filter(|e| !e.is_empty()),
+                suggestions,
+            });
+
+        if !Confirm::new("Add another destructive pattern?")
+            .with_default(false)
+            .prompt()?
+        {
+            break;
+        }
+    }
+
+    scaffold.safe_patterns.clear();
+    while Confirm::new("Add a safe pattern?")
+        .with_default(scaffold.safe_patterns.is_empty())
+        .prompt()?
+    {
+        let name = Text::new("  Pattern name (kebab-case):")
+            .with_validator(required)
+            .prompt()?;
+        let (regex, example) = prompt_regex_with_example(valid_regex, required)?;
+        scaffold.safe_patterns.push(ScaffoldSafePattern {
+            name: name.trim().to_string(),
+            regex,
+            example,
+        });
+    }
+
+    Ok(scaffold)
+}
+
+/// Prompt for a regex and an example command, re-asking until they match.
+fn prompt_regex_with_example<R, Q>(
+    valid_regex: R,
+    required: Q,
+) -> Result<(String, String), Box<dyn std::error::Error>>
+where
+    R: inquire::validator::StringValidator + Clone + 'static,
+    Q: inquire::validator::StringValidator + Clone + 'static,
+{
+    use colored::Colorize;
+
+    loop {
+        let regex = Text::new("  Regex:")
+            .with_validator(valid_regex.clone())
+            .prompt()?;
+        let example = Text::new("  Example command it should match:")
+            .with_validator(required.clone())
+            .prompt()?;
+        let compiled = crate::packs::regex_engine::CompiledRegex::new(&regex)?;
+        if compiled.is_match(&example) {
+            return Ok((regex, example));
+        }
+        eprintln!(
+            "  {} The example does not match the regex; try again.",
+            "✗".red()
+        );
+    }
+}
+
+/// Validate an external pack YAML file
+#[allow(clippy::too_many_lines)]
+fn pack_validate(
+    file_path: &str,
+    strict: bool,
+    format: PackValidateFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::packs::external::{
+        CURRENT_SCHEMA_VERSION, ExternalPack, RegexEngineType, analyze_pack_engines,
+        check_builtin_collision, summarize_pack_engines,
+    };
+    use std::path::Path;
+
+    let path = Path::new(file_path);
+
+    let mut result = PackValidationOutput {
+        valid: true,
+        file: file_path.to_string(),
+        pack_id: None,
+        pack_name: None,
+        pack_version: None,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        suggestions: Vec::new(),
+        patterns: None,
+        engine_summary: None,
+    };
+
+    // Step 1: Check if file exists
+    if !path.exists() {
+        result.valid = false;
+        result.errors.push(PackValidationIssue {
+            code: "E001".to_string(),
+            message: format!("File not found: {file_path}"),
+            suggestion: None,
+        });
+        return output_pack_validation(&result, format, strict);
+    }
+
+    // Step 2: Read file content
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            result.valid = false;
+            result.errors.push(PackValidationIssue {
+                code: "E002".to_string(),
+                message: format!("Failed to read file: {e}"),
+                suggestion: None,
+            });
+            return output_pack_validation(&result, format, strict);
+        }
+    };
+
+    // Step 3: Parse YAML
+    let pack: ExternalPack = match serde_yaml::from_str(&content) {
+        Ok(p) => p,
+        Err(e) => {
+            result.valid = false;
+            result.errors.push(PackValidationIssue {
+                code: "E003".to_string(),
+                message: format!("YAML parse error: {e}"),
+                suggestion: Some("Check YAML syntax (indentation, colons, quotes)".to_string()),
+            });
+            return output_pack_validation(&result, format, strict);
+        }
+    };
+
+    // Store basic pack info for output
+    result.pack_id = Some(pack.id.clone());
+    result.pack_name = Some(pack.name.clone());
+    result.pack_version = Some(pack.version.clone());
+
+    // Step 4: Validate schema version
+    if pack.schema_version > CURRENT_SCHEMA_VERSION {
+        result.valid = false;
+        result.errors.push(PackValidationIssue {
+            code: "E004".to_string(),
+            message: format!(
+                "Schema version {} is not supported (max: {})",
+                pack.schema_version, CURRENT_SCHEMA_VERSION
+            ),
+            suggestion: Some(format!(
+                "Use schema_version: {CURRENT_SCHEMA_VERSION} or lower"
+            )),
+        });
+    }
+
+    // Step 5: Validate ID format
+    let id_regex = regex::Regex::new(r"^[a-z][a-z0-9_]*\.[a-z][a-z0-9_]*$").unwrap();
+    if !id_regex.is_match(&pack.id) {
+        result.valid = false;
+        result.errors.push(PackValidationIssue {
+            code: "E005".to_string(),
+            message: format!(
+                "Invalid pack ID '{}': must match pattern namespace.name (e.g., 'mycompany.deploy')",
+                pack.id
+            ),
+            suggestion: Some("Use lowercase letters, numbers, underscores. Format: namespace.name".to_string()),
+        });
+    }
+
+    // Step 6: Validate version format (semver)
+    let version_regex = regex::Regex::new(r"^\d+\.\d+\.\d+$").unwrap();
+    if !version_regex.is_match(&pack.version) {
+        result.valid = false;
+        result.errors.push(PackValidationIssue {
+            code: "E006".to_string(),
+            message: format!(
+                "Invalid version '{}': must be semantic version (e.g., '1.0.0')",
+                pack.version
+            ),
+            suggestion: Some("Use MAJOR.MINOR.PATCH format (e.g., 1.0.0, 2.1.3)".to_string()),
+        });
+    }
+
+    // Step 7: Check for empty pack
+    if pack.destructive_patterns.is_empty() && pack.safe_patterns.is_empty() {
+        result.valid = false;
+        result.errors.push(PackValidationIssue {
+            code: "E007".to_string(),
+            message: "Pack has no patterns defined".to_string(),
+            suggestion: Some("Add at least one destructive_pattern or safe_pattern".to_string()),
+        });
+    }
+
+    // Step 8: Check for duplicate pattern names
+    let mut seen_names = std::collections::HashSet::new();
+    for pattern in &pack.destructive_patterns {
         if !seen_names.insert(&pattern.name) {
             result.valid = false;
             result.errors.push(PackValidationIssue {
@@ -3090,48 +4957,131 @@ fn prompt_allowlist_reason(default_reason: &str) -> String {
         .unwrap_or_else(|_| default_reason.to_string())
 }
 
-fn resolve_mode_for_cli(
-    config: &Config,
+/// Build the `dcg test --format json` document for an evaluation result.
+pub(crate) fn build_test_output(
     command: &str,
+    provenance: crate::provenance::Provenance,
     result: &EvaluationResult,
-) -> Option<DecisionMode> {
-    let info = result.pattern_info.as_ref()?;
-    let pack = info.pack_id.as_deref();
-    let pattern = info.pattern_name.as_deref();
-
-    let mut mode = match info.source {
-        MatchSource::Pack | MatchSource::HeredocAst => {
-            config.policy().resolve_mode(pack, pattern, info.severity)
-        }
-        MatchSource::ConfigOverride | MatchSource::LegacyPattern => DecisionMode::Deny,
-    };
-
-    if matches!(info.source, MatchSource::Pack | MatchSource::HeredocAst) {
-        let sanitized = crate::context::sanitize_for_pattern_matching(command);
-        let normalized_command = crate::normalize::normalize_command(command);
-        let normalized_sanitized = crate::normalize::normalize_command(sanitized.as_ref());
-
-        let mut confidence_command = command;
-        let mut confidence_sanitized: Option<&str> = None;
-
-        if normalized_command.len() == normalized_sanitized.len() {
-            confidence_command = normalized_command.as_ref();
-            if sanitized.as_ref() != command {
-                confidence_sanitized = Some(normalized_sanitized.as_ref());
-            }
+    agent: Option<AgentInfo>,
+    robot_mode: bool,
+) -> TestOutput {
+    match result.decision {
+        EvaluationDecision::Allow => {
+            let allowlist = result
+                .allowlist_override
+                .as_ref()
+                .map(|info| AllowlistOverrideInfo {
+                    layer: info.layer.label().to_string(),
+                    reason: info.reason.clone(),
+                });
+            TestOutput {
+                schema_version: TEST_OUTPUT_SCHEMA_VERSION,
+                dcg_version: env!("CARGO_PKG_VERSION").to_string(),
+                robot_mode,
+                command: command.to_string(),
+                provenance,
+                decision: "allow".to_string(),
+                rule_id: None,
+                pack_id: None,
+                pattern_name: None,
+                reason: None,
+                explanation: None,
+                source: None,
+                matched_span: None,
+                line: None,
+                severity: None,
+                confidence: None,
+                min_confidence_to_deny: None,
+                allowlist,
+                agent: agent.clone(),
+                matches: Vec::new(),
+                arguments: crate::arg_context::arg_contexts(command),
+            }
+        }
+        EvaluationDecision::Deny => {
+            let (
+                pack_id,
+                pattern_name,
+                reason,
+                explanation,
+                source_str,
+                matched_span,
+                rule_id,
+                severity,
+            ) = result.pattern_info.as_ref().map_or(
+                (None, None, None, None, None, None, None, None),
+                |info| {
+                    let source_str = match info.source {
+                        MatchSource::ConfigOverride => "config_override",
+                        MatchSource::LegacyPattern => "legacy_pattern",
+                        MatchSource::Pack => "pack",
+                        MatchSource::HeredocAst => "heredoc_ast",
+                    };
+                    let rule_id = info
+                        .pack_id
+                        .as_ref()
+                        .and_then(|p| info.pattern_name.as_ref().map(|n| format!("{p}:{n}")));
+                    let severity_str = info.severity.map(|s| match s {
+                        PackSeverity::Critical => "critical",
+                        PackSeverity::High => "high",
+                        PackSeverity::Medium => "medium",
+                        PackSeverity::Low => "low",
+                    });
+                    (
+                        info.pack_id.clone(),
+                        info.pattern_name.clone(),
+                        Some(info.reason.clone()),
+                        info.explanation.clone(),
+                        Some(source_str.to_string()),
+                        info.matched_span.as_ref().map(|s| (s.start, s.end)),
+                        rule_id,
+                        severity_str.map(std::string::ToString::to_string),
+                    )
+                },
+            );
+            TestOutput {
+                schema_version: TEST_OUTPUT_SCHEMA_VERSION,
+                dcg_version: env!("CARGO_PKG_VERSION").to_string(),
+                robot_mode,
+                command: command.to_string(),
+                provenance,
+                decision: "deny".to_string(),
+                rule_id,
+                pack_id,
+                pattern_name,
+                reason,
+                explanation,
+                source: source_str,
+                matched_span,
+                line: result
+                    .pattern_info
+                    .as_ref()
+                    .and_then(|info| info.line_number),
+                severity,
+                confidence: None,
+                min_confidence_to_deny: None,
+                allowlist: None,
+                agent: agent.clone(),
+                matches: result
+                    .all_matches()
+                    .map(|info| TestMatchInfo {
+                        rule_id: info
+                            .pack_id
+                            .as_ref()
+                            .zip(info.pattern_name.as_ref())
+                            .map(|(pack, pattern)| format!("{pack}:{pattern}")),
+                        pack_id: info.pack_id.clone(),
+                        pattern_name: info.pattern_name.clone(),
+                        reason: info.reason.clone(),
+                        severity: info.severity.map(|s| s.label().to_string()),
+                        matched_span: info.matched_span.as_ref().map(|s| (s.start, s.end)),
+                        line: info.line_number,
+                    })
+                    .collect(),
+                arguments: crate::arg_context::arg_contexts(command),
+            }
         }
-
-        let confidence_result = crate::apply_confidence_scoring(
-            confidence_command,
-            confidence_sanitized,
-            result,
-            mode,
-            &config.confidence,
-        );
-        mode = confidence_result.mode;
     }
-
-    Some(mode)
 }
 
 /// Test a command against the configured packs using the shared evaluator.
@@ -3147,6 +5097,8 @@ fn resolve_mode_for_cli(
 fn test_command(
     config: &Config,
     command: &str,
+    trace_file: Option<&std::path::Path>,
+    provenance: crate::provenance::Provenance,
     extra_packs: Option<Vec<String>>,
     format: TestFormat,
     verbosity: Verbosity,
@@ -3198,11 +5150,11 @@ fn test_command(
     let heredoc_settings = effective_config.heredoc_settings();
 
     // Compile overrides once (not per-command)
-    let compiled_overrides = effective_config.overrides.compile();
+    let compiled_overrides = effective_config.compile_overrides();
 
     // Load allowlists (project/user/system) for parity with hook mode.
     // This is a small file read and only affects decisions when a rule matches.
-    let allowlists = load_default_allowlists();
+    let allowlists = config.load_allowlists();
 
     // Load external packs from custom_paths (glob + tilde expansion).
     let external_paths = effective_config.packs.expand_custom_paths();
@@ -3256,102 +5208,53 @@ fn test_command(
         None, // project_path
         None, // deadline
     );
+    let result = apply_severity_aggregation(result, effective_config.policy().aggregation());
+    let result = if verbosity.is_verbose() {
+        record_near_misses(result, command, &ordered_packs, effective_config.policy())
+    } else {
+        result
+    };
 
     // NOTE: External packs from custom_paths are now checked in evaluate_command()
     // alongside built-in packs, so no separate fallback check is needed here.
 
     let elapsed = start.elapsed();
 
+    if let Some(path) = trace_file {
+        let input = crate::pipeline_trace::TraceInput {
+            source: "test",
+            raw_input: command,
+            command,
+            enabled_keywords: &enabled_keywords,
+            ordered_packs: &ordered_packs,
+            allowlists: &allowlists,
+        };
+        let mode = crate::evaluator::resolve_decision_mode(
+            &effective_config,
+            command,
+            &result,
+            provenance,
+        );
+        let trace = crate::pipeline_trace::build(&input, &result, mode, elapsed);
+        if let Err(e) = crate::pipeline_trace::write(path, &trace) {
+            eprintln!(
+                "Warning: failed to write trace file {}: {e}",
+                path.display()
+            );
+        }
+    }
+
     // Handle JSON output
     if format == TestFormat::Json {
-        let output = match result.decision {
-            EvaluationDecision::Allow => {
-                let allowlist =
-                    result
-                        .allowlist_override
-                        .as_ref()
-                        .map(|info| AllowlistOverrideInfo {
-                            layer: info.layer.label().to_string(),
-                            reason: info.reason.clone(),
-                        });
-                TestOutput {
-                    schema_version: TEST_OUTPUT_SCHEMA_VERSION,
-                    dcg_version: env!("CARGO_PKG_VERSION").to_string(),
-                    robot_mode,
-                    command: command.to_string(),
-                    decision: "allow".to_string(),
-                    rule_id: None,
-                    pack_id: None,
-                    pattern_name: None,
-                    reason: None,
-                    explanation: None,
-                    source: None,
-                    matched_span: None,
-                    severity: None,
-                    allowlist,
-                    agent: Some(agent_info.clone()),
-                }
-            }
-            EvaluationDecision::Deny => {
-                let (
-                    pack_id,
-                    pattern_name,
-                    reason,
-                    explanation,
-                    source_str,
-                    matched_span,
-                    rule_id,
-                    severity,
-                ) = result.pattern_info.as_ref().map_or(
-                    (None, None, None, None, None, None, None, None),
-                    |info| {
-                        let source_str = match info.source {
-                            MatchSource::ConfigOverride => "config_override",
-                            MatchSource::LegacyPattern => "legacy_pattern",
-                            MatchSource::Pack => "pack",
-                            MatchSource::HeredocAst => "heredoc_ast",
-                        };
-                        let rule_id = info
-                            .pack_id
-                            .as_ref()
-                            .and_then(|p| info.pattern_name.as_ref().map(|n| format!("{p}:{n}")));
-                        let severity_str = info.severity.map(|s| match s {
-                            PackSeverity::Critical => "critical",
-                            PackSeverity::High => "high",
-                            PackSeverity::Medium => "medium",
-                            PackSeverity::Low => "low",
-                        });
-                        (
-                            info.pack_id.clone(),
-                            info.pattern_name.clone(),
-                            Some(info.reason.clone()),
-                            info.explanation.clone(),
-                            Some(source_str.to_string()),
-                            info.matched_span.as_ref().map(|s| (s.start, s.end)),
-                            rule_id,
-                            severity_str.map(std::string::ToString::to_string),
-                        )
-                    },
-                );
-                TestOutput {
-                    schema_version: TEST_OUTPUT_SCHEMA_VERSION,
-                    dcg_version: env!("CARGO_PKG_VERSION").to_string(),
-                    robot_mode,
-                    command: command.to_string(),
-                    decision: "deny".to_string(),
-                    rule_id,
-                    pack_id,
-                    pattern_name,
-                    reason,
-                    explanation,
-                    source: source_str,
-                    matched_span,
-                    severity,
-                    allowlist: None,
-                    agent: Some(agent_info.clone()),
-                }
-            }
-        };
+        let mut output =
+            build_test_output(command, provenance, &result, Some(agent_info), robot_mode);
+        let gate =
+            crate::evaluator::resolve_decision(&effective_config, command, &result, provenance)
+                .and_then(|decision| decision.confidence);
+        if let Some(gate) = gate {
+            output.confidence = Some(crate::hook::gate_value(gate.confidence));
+            output.min_confidence_to_deny = Some(crate::hook::gate_value(gate.threshold));
+        }
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
         return result.decision == EvaluationDecision::Deny;
     }
@@ -3393,9 +5296,15 @@ fn test_command(
     } else {
         println!("Command: {command}");
     }
+    for context in crate::arg_context::arg_contexts(command) {
+        if !context.values.is_empty() {
+            println!("Arguments: {} {}", context.tool, context.describe());
+        }
+    }
     println!();
 
-    let resolved_mode = resolve_mode_for_cli(&effective_config, command, &result);
+    let resolved =
+        crate::evaluator::resolve_decision(&effective_config, command, &result, provenance);
 
     match result.decision {
         EvaluationDecision::Allow => {
@@ -3423,6 +5332,21 @@ fn test_command(
                 if let Some(ref explanation) = info.explanation {
                     println!("Explanation: {explanation}");
                 }
+                let impact = std::env::current_dir().ok().and_then(|cwd| {
+                    crate::impact::estimate_rm(
+                        command,
+                        &cwd,
+                        effective_config.output.impact_budget(),
+                    )
+                });
+                let impact = impact.map(|estimate| estimate.summary()).or_else(|| {
+                    let timeout = effective_config.output.storage_impact_timeout()?;
+                    crate::storage_impact::estimate_storage(command, timeout)
+                        .map(|impact| impact.summary())
+                });
+                if let Some(summary) = impact {
+                    println!("Impact: {summary}");
+                }
                 let source = match info.source {
                     MatchSource::ConfigOverride => "config override",
                     MatchSource::LegacyPattern => "legacy pattern",
@@ -3430,22 +5354,48 @@ fn test_command(
                     MatchSource::HeredocAst => "heredoc/inline script (AST)",
                 };
                 println!("Source: {source}");
+                if !result.other_matches.is_empty() {
+                    println!("Also matched:");
+                    for other in &result.other_matches {
+                        let id = other
+                            .pack_id
+                            .as_ref()
+                            .zip(other.pattern_name.as_ref())
+                            .map_or_else(
+                                || other.reason.clone(),
+                                |(pack, pattern)| format!("{pack}:{pattern}"),
+                            );
+                        match other.severity {
+                            Some(severity) => println!("  - {id} ({})", severity.label()),
+                            None => println!("  - {id}"),
+                        }
+                    }
+                }
+                if let Some(trace) = &result.aggregation {
+                    println!("{}", crate::hook::format_aggregation(trace));
+                }
+                if let Some(gate) = resolved.and_then(|decision| decision.confidence) {
+                    println!("Confidence: {}", crate::hook::format_confidence_gate(&gate));
+                }
 
                 let rule_id = info
                     .pack_id
                     .as_ref()
                     .zip(info.pattern_name.as_ref())
                     .map(|(pack, pattern)| format!("{pack}:{pattern}"));
-                let mode = resolved_mode.unwrap_or(DecisionMode::Deny);
+                let mode = resolved.map_or(DecisionMode::Deny, |decision| decision.mode);
 
                 match mode {
                     DecisionMode::Warn => {
                         result_line = "Result: WARN (policy allows)".to_string();
                     }
+                    DecisionMode::WarnOnce => {
+                        result_line = "Result: WARN ONCE (policy allows)".to_string();
+                    }
                     DecisionMode::Log => {
                         result_line = "Result: LOG (policy allows)".to_string();
                     }
-                    DecisionMode::Deny => {
+                    DecisionMode::Deny | DecisionMode::Ask => {
                         // For critical/high severity, use security-aware prompt
                         // For medium/low severity, use simpler inquire-based prompt
                         if should_use_secure_prompt(info.severity) {
@@ -3569,6 +5519,12 @@ fn test_command(
                 println!("Severity: {}", severity.label());
             }
         }
+        if !result.near_misses.is_empty() {
+            println!("Near misses:");
+            for miss in &result.near_misses {
+                println!("  - {}: {}", miss.rule_id(), miss.describe());
+            }
+        }
     }
 
     if verbosity.is_debug() {
@@ -3627,7 +5583,9 @@ fn init_config(output: Option<String>, force: bool) -> Result<(), Box<dyn std::e
                 std::fs::create_dir_all(parent)?;
             }
 
+            let before = std::fs::read_to_string(path).ok();
             std::fs::write(path, sample)?;
+            record_policy_change("init", path, before.as_deref());
             println!("Configuration written to: {}", path.display());
         }
         None => {
@@ -3723,13 +5681,53 @@ fn show_config(config: &Config) {
     }
 }
 
+/// Show the merged configuration with per-layer provenance.
+fn show_effective_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    use std::fmt::Write;
+
+    println!("Config layers (merge order, lowest → highest priority):");
+    if config.sources.is_empty() {
+        println!("  (none - compiled defaults only)");
+    }
+    for source in &config.sources {
+        let indent = if source.included_from.is_some() {
+            "    "
+        } else {
+            "  "
+        };
+        let mut line = format!("{indent}- {}: {}", source.kind.label(), source.location);
+        if let Some(from) = &source.included_from {
+            let _ = write!(line, " (included from {from})");
+        }
+        if !source.applied {
+            let note = source.note.as_deref().unwrap_or("not applied");
+            let _ = write!(line, " [skipped: {note}]");
+        } else if let Some(note) = &source.note {
+            let _ = write!(line, " [{note}]");
+        }
+        println!("{line}");
+    }
+    println!("  - environment: DCG_* variables (applied last)");
+    println!();
+    println!("Effective configuration:");
+    println!();
+    print!("{}", toml::to_string_pretty(config)?);
+    Ok(())
+}
+
 const DCG_SCAN_PRE_COMMIT_SENTINEL: &str = "# dcg:scan-pre-commit";
 
-fn build_scan_pre_commit_hook_script() -> String {
+fn build_scan_pre_commit_hook_script(fail_on: Option<crate::scan::ScanFailOn>) -> String {
+    let scan_command = fail_on
+        .and_then(|level| clap::ValueEnum::to_possible_value(&level))
+        .map_or_else(
+            || "dcg scan --staged".to_string(),
+            |level| format!("dcg scan --staged --fail-on {}", level.get_name()),
+        );
     format!(
         r#"#!/usr/bin/env sh
 {DCG_SCAN_PRE_COMMIT_SENTINEL}
-# Generated by: dcg scan install-pre-commit
+# Generated by: dcg hook install pre-commit
 #
 # This hook runs `dcg scan --staged` to block commits that introduce destructive
 # commands in executable contexts (CI workflows, scripts, etc.).
@@ -3740,11 +5738,11 @@ set -u
 
 if ! command -v dcg >/dev/null 2>&1; then
   echo "dcg pre-commit hook: 'dcg' not found in PATH; skipping scan." >&2
-  echo "Fix: install dcg or remove this hook via: dcg scan uninstall-pre-commit" >&2
+  echo "Fix: install dcg or remove this hook via: dcg hook uninstall pre-commit" >&2
   exit 0
 fi
 
-dcg scan --staged
+{scan_command}
 status=$?
 if [ "$status" -ne 0 ]; then
   echo >&2
@@ -3847,15 +5845,18 @@ fn hook_looks_like_dcg_scan_pre_commit(hook_bytes: &[u8]) -> bool {
     String::from_utf8_lossy(hook_bytes).contains(DCG_SCAN_PRE_COMMIT_SENTINEL)
 }
 
-fn install_scan_pre_commit_hook() -> Result<(), Box<dyn std::error::Error>> {
+fn install_scan_pre_commit_hook(
+    fail_on: Option<crate::scan::ScanFailOn>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let cwd = std::env::current_dir()?;
-    let hook_path = install_scan_pre_commit_hook_at(&cwd)?;
+    let hook_path = install_scan_pre_commit_hook_at(&cwd, fail_on)?;
     eprintln!("Installed pre-commit hook: {}", hook_path.display());
     Ok(())
 }
 
 fn install_scan_pre_commit_hook_at(
     cwd: &std::path::Path,
+    fail_on: Option<crate::scan::ScanFailOn>,
 ) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
     let hook_path = git_resolve_path(cwd, "hooks/pre-commit")?;
 
@@ -3869,7 +5870,7 @@ Manual integration options:\n\
   1) Add a line to your existing hook to run: dcg scan --staged\n\
   2) Configure your hook manager to run: dcg scan --staged\n\n\
 To replace your hook with a dcg-managed hook, delete it manually and re-run:\n\
-  dcg scan install-pre-commit",
+  dcg hook install pre-commit",
                 hook_path.display()
             )
             .into());
@@ -3878,7 +5879,7 @@ To replace your hook with a dcg-managed hook, delete it manually and re-run:\n\
         std::fs::create_dir_all(parent)?;
     }
 
-    std::fs::write(&hook_path, build_scan_pre_commit_hook_script())?;
+    std::fs::write(&hook_path, build_scan_pre_commit_hook_script(fail_on))?;
 
     #[cfg(unix)]
     {
@@ -4119,6 +6120,8 @@ fn handle_scan_command(
         fail_on,
         max_file_size,
         max_findings,
+        jobs,
+        timeout,
         exclude,
         include,
         redact,
@@ -4133,7 +6136,7 @@ fn handle_scan_command(
 
     match action {
         Some(ScanAction::InstallPreCommit) => {
-            install_scan_pre_commit_hook()?;
+            install_scan_pre_commit_hook(None)?;
         }
         Some(ScanAction::UninstallPreCommit) => {
             uninstall_scan_pre_commit_hook()?;
@@ -4172,6 +6175,8 @@ fn handle_scan_command(
                 &settings.include,
                 settings.redact,
                 settings.truncate,
+                jobs,
+                timeout,
                 effective_verbose,
                 quiet,
                 debug,
@@ -4200,6 +6205,8 @@ fn handle_scan(
     include: &[String],
     redact: crate::scan::ScanRedactMode,
     truncate: usize,
+    jobs: usize,
+    timeout: Option<u64>,
     verbose: bool,
     quiet: bool,
     debug: bool,
@@ -4207,7 +6214,9 @@ fn handle_scan(
     top: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use crate::output::progress::MaybeProgress;
-    use crate::scan::{ScanEvalContext, ScanOptions, scan_paths_with_progress, should_fail};
+    use crate::scan::{
+        ScanEvalContext, ScanInterrupt, ScanOptions, scan_paths_interruptible, should_fail,
+    };
 
     // Validate file selection mode - at least one must be specified
     let file_sources = [staged, paths.is_some(), git_diff.is_some()]
@@ -4233,6 +6242,7 @@ fn handle_scan(
         max_findings,
         redact,
         truncate,
+        jobs,
     };
 
     // Build evaluation context from config
@@ -4255,7 +6265,8 @@ fn handle_scan(
         }
         if debug {
             eprintln!(
-                "Scan settings: format={format:?}, fail_on={fail_on:?}, max_file_size={max_file_size}, max_findings={max_findings}"
+                "Scan settings: format={format:?}, fail_on={fail_on:?}, max_file_size={max_file_size}, max_findings={max_findings}, jobs={}",
+                crate::scan::resolve_scan_jobs(jobs)
             );
         }
         if trace {
@@ -4285,7 +6296,23 @@ fn handle_scan(
         }
     };
 
-    let report = scan_paths_with_progress(
+    // Ctrl-C stops the scan before the next file so partial results can be
+    // reported; a second Ctrl-C exits immediately.
+    let mut interrupt = ScanInterrupt::new();
+    if let Some(secs) = timeout {
+        interrupt = interrupt.with_timeout(std::time::Duration::from_secs(secs));
+    }
+    {
+        let interrupt = interrupt.clone();
+        let _ = ctrlc::set_handler(move || {
+            if interrupt.is_cancelled() {
+                std::process::exit(crate::exit_codes::EXIT_INTERRUPTED);
+            }
+            interrupt.cancel();
+        });
+    }
+
+    let report = scan_paths_interruptible(
         &scan_paths_list,
         &options,
         config,
@@ -4298,6 +6325,7 @@ fn handle_scan(
         } else {
             Some(&mut progress_callback)
         },
+        &interrupt,
     )?;
 
     // Finish progress bar if it was created
@@ -4326,6 +6354,19 @@ fn handle_scan(
         }
     }
 
+    // An incomplete scan can't vouch for the files it skipped, so the
+    // interruption code wins over the fail-on policy.
+    if let Some(reason) = report.summary.interrupted {
+        if !quiet {
+            eprintln!(
+                "Scan {} after {} file(s); results are partial.",
+                reason.describe(),
+                report.summary.files_scanned + report.summary.files_skipped
+            );
+        }
+        std::process::exit(reason.exit_code());
+    }
+
     // Exit with appropriate code based on fail-on policy
     if should_fail(&report, fail_on) {
         std::process::exit(1);
@@ -4334,6 +6375,97 @@ fn handle_scan(
     Ok(())
 }
 
+fn handle_scan_scripts_command(
+    config: &Config,
+    cmd: &ScanScriptsCommand,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::scan::{ScanEvalContext, ScanFormat, ScanOptions, scan_scripts, should_fail};
+
+    let options = ScanOptions {
+        format: cmd.format,
+        fail_on: cmd.fail_on,
+        max_file_size_bytes: 1_048_576,
+        max_findings: usize::MAX,
+        redact: crate::scan::ScanRedactMode::None,
+        truncate: 0,
+        jobs: 0,
+    };
+    let ctx = ScanEvalContext::from_config(config);
+    let repo_root = find_repo_root_from_cwd();
+    let report = scan_scripts(
+        &cmd.paths,
+        &options,
+        config,
+        &ctx,
+        &cmd.exclude,
+        repo_root.as_deref(),
+    )?;
+
+    if !quiet {
+        match cmd.format {
+            ScanFormat::Pretty => print_scan_scripts_pretty(&report),
+            ScanFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            ScanFormat::Markdown => print_scan_markdown(&report, 0, 0),
+            ScanFormat::Sarif => {
+                let sarif = crate::sarif::SarifReport::from_scan_report(&report);
+                println!("{}", serde_json::to_string_pretty(&sarif)?);
+            }
+        }
+    }
+
+    if should_fail(&report, cmd.fail_on) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Findings grouped by file, each labelled with the script that runs it.
+fn print_scan_scripts_pretty(report: &crate::scan::ScanReport) {
+    use colored::Colorize;
+
+    let files = report.summary.files_scanned;
+    if report.findings.is_empty() {
+        println!(
+            "{} ({files} script file(s) scanned)",
+            "No findings.".green()
+        );
+        return;
+    }
+
+    let mut current_file: Option<&str> = None;
+    for finding in &report.findings {
+        if current_file != Some(finding.file.as_str()) {
+            if current_file.is_some() {
+                println!();
+            }
+            println!("{}", finding.file.bold());
+            current_file = Some(finding.file.as_str());
+        }
+        let decision = match finding.decision {
+            crate::scan::ScanDecision::Deny => "deny".red(),
+            crate::scan::ScanDecision::Warn => "warn".yellow(),
+            crate::scan::ScanDecision::Allow => "allow".green(),
+        };
+        let script = finding.script.as_deref().unwrap_or("-");
+        let rule = finding.rule_id.as_deref().unwrap_or("(no rule)");
+        println!(
+            "  {} (line {}) [{decision}] {rule}",
+            script.cyan(),
+            finding.line
+        );
+        println!("    $ {}", finding.extracted_command);
+        if let Some(reason) = &finding.reason {
+            println!("    {}", reason.dimmed());
+        }
+    }
+    println!();
+    println!(
+        "{} finding(s) in {files} script file(s)",
+        report.findings.len().to_string().yellow().bold()
+    );
+}
+
 /// Get list of files staged for commit (git index).
 fn get_staged_files() -> Result<Vec<std::path::PathBuf>, Box<dyn std::error::Error>> {
     let cwd = std::env::current_dir()?;
@@ -4553,6 +6685,13 @@ fn print_scan_pretty(report: &crate::scan::ScanReport, verbose: bool, top: usize
         );
     }
 
+    if let Some(reason) = report.summary.interrupted {
+        println!(
+            "{}",
+            format!("Note: scan {}, results are partial", reason.describe()).yellow()
+        );
+    }
+
     if verbose {
         // Additional verbose info could go here
     }
@@ -4660,6 +6799,13 @@ fn print_scan_pretty(report: &crate::scan::ScanReport, verbose: bool, top: usize
         con.print("[yellow]Note: max findings limit reached, scan stopped early[/]");
     }
 
+    if let Some(reason) = report.summary.interrupted {
+        con.print(&format!(
+            "[yellow]Note: scan {}, results are partial[/]",
+            reason.describe()
+        ));
+    }
+
     if verbose {
         // Additional verbose info could go here
     }
@@ -4817,6 +6963,13 @@ fn print_scan_markdown_summary(report: &crate::scan::ScanReport) {
     if report.summary.max_findings_reached {
         println!("\n:warning: *Max findings limit reached, scan stopped early.*");
     }
+
+    if let Some(reason) = report.summary.interrupted {
+        println!(
+            "\n:warning: *Scan {}, results are partial.*",
+            reason.describe()
+        );
+    }
 }
 
 /// Truncate a string for markdown display, respecting char boundaries.
@@ -4838,36 +6991,18 @@ fn truncate_for_markdown(s: &str, max_len: usize) -> String {
     format!("{}...", &s[..end])
 }
 
-/// Handle the `dcg explain` subcommand.
-///
-/// Shows a detailed decision trace for why a command would be allowed or denied.
-/// Currently wraps the evaluator result; full tracing integration is future work.
-#[allow(clippy::needless_pass_by_value)] // Value consumed from CLI args
-fn handle_explain(
-    config: &Config,
-    command: &str,
-    format: ExplainFormat,
-    extra_packs: Option<Vec<String>>,
-) {
+/// Evaluate `command` under `config` (no allowlists) and record the trace
+/// behind `dcg explain`.
+pub(crate) fn explain_trace(config: &Config, command: &str) -> crate::trace::ExplainTrace {
     use crate::trace::{MatchInfo, TraceCollector, TraceDetails};
 
-    // Build effective config with extra packs if specified
-    let effective_config = extra_packs.map_or_else(
-        || config.clone(),
-        |packs| {
-            let mut modified = config.clone();
-            modified.packs.enabled.extend(packs);
-            modified
-        },
-    );
-
     // Get enabled packs and collect keywords
-    let enabled_packs = effective_config.enabled_pack_ids();
+    let enabled_packs = config.enabled_pack_ids();
     let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
     let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
     let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
-    let heredoc_settings = effective_config.heredoc_settings();
-    let compiled_overrides = effective_config.overrides.compile();
+    let heredoc_settings = config.heredoc_settings();
+    let compiled_overrides = config.compile_overrides();
     let allowlists = crate::LayeredAllowlist::default();
 
     // Start tracing
@@ -4916,8 +7051,99 @@ fn handle_explain(
         });
     }
 
-    // Finish and get trace
-    let trace = collector.finish(result.decision);
+    collector.finish(result.decision)
+}
+
+/// Handle `dcg docs generate`.
+fn handle_docs_generate(
+    config: &Config,
+    output: &std::path::Path,
+    corpus: &std::path::Path,
+    check: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::rule_docs::{RuleExamples, generate, load_examples};
+
+    let examples = if corpus.is_dir() {
+        load_examples(corpus)?
+    } else {
+        RuleExamples::new()
+    };
+
+    let pages = generate(&all_packs(config), &examples);
+    let mut stale = 0;
+    for page in &pages {
+        let path = output.join(&page.path);
+        if std::fs::read_to_string(&path).is_ok_and(|existing| existing == page.contents) {
+            continue;
+        }
+        if check {
+            eprintln!("out of date: {}", path.display());
+            stale += 1;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &page.contents)?;
+    }
+    if stale > 0 {
+        return Err(format!("{stale} rule page(s) out of date in {}", output.display()).into());
+    }
+    println!(
+        "{} {} rule pages in {}",
+        if check { "Checked" } else { "Generated" },
+        pages.len() - 1,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Handle the hidden `dcg render-fixtures` subcommand.
+fn handle_render_fixtures(
+    dir: &std::path::Path,
+    check: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::output::fixtures::{SnapshotStatus, check_snapshot, render_fixtures};
+
+    let mut stale = 0;
+    for fixture in render_fixtures() {
+        match check_snapshot(dir, &fixture, !check) {
+            Ok(SnapshotStatus::Matched) => println!("unchanged: {}.txt", fixture.name),
+            Ok(SnapshotStatus::Written) => println!("wrote: {}.txt", fixture.name),
+            Err(e) => {
+                eprintln!("{e}");
+                stale += 1;
+            }
+        }
+    }
+    if stale > 0 {
+        return Err(format!("{stale} render fixture(s) out of date in {}", dir.display()).into());
+    }
+    Ok(())
+}
+
+/// Handle the `dcg explain` subcommand.
+///
+/// Shows a detailed decision trace for why a command would be allowed or denied.
+/// Currently wraps the evaluator result; full tracing integration is future work.
+#[allow(clippy::needless_pass_by_value)] // Value consumed from CLI args
+fn handle_explain(
+    config: &Config,
+    command: &str,
+    format: ExplainFormat,
+    extra_packs: Option<Vec<String>>,
+) {
+    // Build effective config with extra packs if specified
+    let effective_config = extra_packs.map_or_else(
+        || config.clone(),
+        |packs| {
+            let mut modified = config.clone();
+            modified.packs.enabled.extend(packs);
+            modified
+        },
+    );
+
+    let trace = explain_trace(&effective_config, command);
 
     // Format and print based on selected format
     match format {
@@ -4945,14 +7171,93 @@ fn handle_explain(
     }
 }
 
-/// Rich output for explain command with tree visualization.
-#[cfg(feature = "rich-output")]
-fn explain_rich(trace: &crate::trace::ExplainTrace) {
-    use crate::evaluator::EvaluationDecision;
-    use crate::output::console::console;
-    use crate::trace::TraceDetails;
+/// Handle `dcg explain --rule`.
+fn handle_explain_rule(
+    config: &Config,
+    rule_id: &str,
+    corpus: &std::path::Path,
+    format: ExplainFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::rule_docs::{load_examples, platform_label, rule_info};
+    use colored::Colorize;
 
-    let con = console();
+    let examples = if corpus.is_dir() {
+        Some(load_examples(corpus)?)
+    } else {
+        None
+    };
+    let info = rule_info(rule_id, &all_packs(config), examples.as_ref())?;
+
+    match format {
+        ExplainFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&info)?);
+        }
+        ExplainFormat::Compact => {
+            println!(
+                "{} [{}] {} ({})",
+                info.rule_id, info.severity, info.reason, info.pattern
+            );
+        }
+        ExplainFormat::Pretty => {
+            println!("{}", info.rule_id.bold());
+            if let Some(old) = &info.renamed_from {
+                println!("  {}", format!("(formerly {old})").dimmed());
+            }
+            println!(
+                "  Pack:      {} ({})\n  Severity:  {} (default: {})\n  Pattern:   {}\n  Regex:     {}",
+                info.pack_name,
+                info.pack_id,
+                info.severity,
+                info.default_decision,
+                info.pattern.cyan(),
+                info.regex.dimmed()
+            );
+            println!("\n{}", info.reason);
+            if let Some(explanation) = info.explanation {
+                println!(
+                    "\n{}\n{}",
+                    "Why it is blocked:".bold(),
+                    explanation.trim_end()
+                );
+            }
+            if !info.examples.is_empty() {
+                println!("\n{}", "Blocked examples:".bold());
+                for example in &info.examples {
+                    println!("  {}", example.red());
+                }
+            }
+            if !info.safe_alternatives.is_empty() {
+                println!("\n{}", "Safe alternatives:".bold());
+                for suggestion in info.safe_alternatives {
+                    let platform = match suggestion.platform {
+                        crate::packs::Platform::All => String::new(),
+                        platform => format!(" ({})", platform_label(platform)),
+                    };
+                    println!(
+                        "  {}{platform}: {}",
+                        suggestion.command.green(),
+                        suggestion.description
+                    );
+                }
+            }
+            println!(
+                "\n{}\n{}",
+                "To allow it, add to .dcg/allowlist.toml:".bold(),
+                info.allowlist_stanza.trim_end()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Rich output for explain command with tree visualization.
+#[cfg(feature = "rich-output")]
+fn explain_rich(trace: &crate::trace::ExplainTrace) {
+    use crate::evaluator::EvaluationDecision;
+    use crate::output::console::console;
+    use crate::trace::TraceDetails;
+
+    let con = console();
 
     // Header
     con.rule(Some("[bold] DCG EXPLAIN [/]"));
@@ -5217,6 +7522,506 @@ fn explain_rich(trace: &crate::trace::ExplainTrace) {
     }
 }
 
+/// Print the decision trace for `dcg why-allowed`.
+/// Handle `dcg trash` subcommands.
+fn handle_trash_command(
+    config: &Config,
+    action: TrashAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::trash::TrashBackend;
+    use colored::Colorize;
+
+    let root = config.trash.dir();
+    match action {
+        TrashAction::Put { paths } => {
+            let cwd = std::env::current_dir()?;
+            if config.trash.backend() == TrashBackend::System {
+                let trashed = crate::trash::put_system(&cwd, &paths)?;
+                for path in trashed {
+                    println!("Moved {} to the system trash", path.display());
+                }
+                return Ok(());
+            }
+            let entry = crate::trash::put(&root, &cwd, &paths)?;
+            for item in &entry.items {
+                println!("Trashed {}", item.original.display());
+            }
+            println!(
+                "{}",
+                format!("Restore with: dcg trash restore {}", entry.id).dimmed()
+            );
+        }
+        TrashAction::List { format } => {
+            let entries = crate::trash::list(&root)?;
+            if format == DoctorFormat::Json {
+                let json = serde_json::json!({ "dir": root, "entries": entries });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
+            if entries.is_empty() {
+                println!("Trash is empty ({})", root.display());
+                return Ok(());
+            }
+            for entry in &entries {
+                println!(
+                    "{}  {}",
+                    entry.id.bold(),
+                    entry
+                        .trashed_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                        .dimmed()
+                );
+                for item in &entry.items {
+                    let suffix = if item.is_dir { "/" } else { "" };
+                    println!("  {}{suffix}", item.original.display());
+                }
+            }
+        }
+        TrashAction::Restore { id, force } => {
+            let entry = crate::trash::restore(&root, id.as_deref(), force)?;
+            for item in &entry.items {
+                println!("Restored {}", item.original.display());
+            }
+        }
+        TrashAction::Empty {
+            older_than_days,
+            yes,
+        } => {
+            if !yes {
+                return Err("refusing to permanently delete trash without --yes".into());
+            }
+            let cutoff = match older_than_days {
+                Some(days) => {
+                    let age = i64::try_from(days)
+                        .ok()
+                        .and_then(chrono::Duration::try_days)
+                        .ok_or("--older-than-days is too large")?;
+                    Some(chrono::Utc::now() - age)
+                }
+                None => None,
+            };
+            let removed = crate::trash::empty(&root, cutoff)?;
+            println!(
+                "Permanently deleted {} trash {}",
+                removed.len(),
+                if removed.len() == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `dcg preview` for a recursive `rm`: walk the targets and report what
+/// would be deleted. Nothing is executed.
+fn handle_rm_preview(
+    command: &str,
+    targets: &[String],
+    timeout: u64,
+    format: DoctorFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
+
+    let cwd = std::env::current_dir()?;
+    let estimate =
+        crate::impact::estimate_paths(targets, &cwd, std::time::Duration::from_secs(timeout));
+
+    if format == DoctorFormat::Json {
+        let json = serde_json::json!({
+            "command": command,
+            "tool": "rm",
+            "cwd": cwd,
+            "impact": estimate,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        "Impact estimate:".bold(),
+        "(rm has no dry-run mode; nothing was run)".dimmed()
+    );
+    println!("  {:<11}{command}", "Command:".dimmed());
+    println!("  {:<11}{}", "Directory:".dimmed(), cwd.display());
+    if estimate.files > 0 || estimate.dirs > 0 {
+        println!("  {}", estimate.summary().yellow());
+    } else {
+        println!("  {}", "nothing to delete".green());
+    }
+    if !estimate.missing.is_empty() {
+        println!(
+            "  {} {}",
+            "Not found:".dimmed(),
+            estimate.missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Handle `dcg preview`: run the dry-run variant of `command` and show what
+/// it reports.
+fn handle_preview_command(
+    config: &Config,
+    command: &str,
+    timeout: u64,
+    format: DoctorFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // `rm` has no dry-run mode; estimate what it would delete instead.
+    if let Some(targets) = crate::impact::rm_targets(command) {
+        return handle_rm_preview(command, &targets, timeout, format);
+    }
+
+    let plan = crate::preview::plan_preview(command)?;
+    let preview_line = plan.command_line();
+
+    // The preview must be safe in its own right; never run what dcg would block.
+    let trace = explain_trace(config, &preview_line);
+    if trace.decision == EvaluationDecision::Deny {
+        let reason = trace
+            .match_info
+            .map_or_else(|| "blocked by policy".to_string(), |info| info.reason);
+        return Err(format!("refusing to run preview `{preview_line}`: {reason}").into());
+    }
+    let original = explain_trace(config, command);
+
+    let cwd = std::env::current_dir()?;
+    let output = crate::preview::run_preview(&plan, &cwd, std::time::Duration::from_secs(timeout))
+        .map_err(|e| format!("failed to run `{}`: {e}", plan.program))?;
+
+    if format == DoctorFormat::Json {
+        let json = serde_json::json!({
+            "command": command,
+            "tool": plan.tool,
+            "preview": preview_line,
+            "cwd": cwd,
+            "would_block": original.decision == EvaluationDecision::Deny,
+            "rule_id": original.match_info.and_then(|info| info.rule_id),
+            "exit_code": output.exit_code,
+            "timed_out": output.timed_out,
+            "truncated": output.truncated,
+            "duration_ms": u64::try_from(output.duration.as_millis()).unwrap_or(u64::MAX),
+            "stdout": output.stdout,
+            "stderr": output.stderr,
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        return Ok(());
+    }
+
+    let cwd_display = cwd.display().to_string();
+    let panel = crate::output::PreviewPanel {
+        command,
+        plan: &plan,
+        cwd: &cwd_display,
+        output: &output,
+    };
+    println!(
+        "{}",
+        panel.render(&crate::output::auto_theme_with_config(config))
+    );
+    if original.decision == EvaluationDecision::Deny {
+        let rule = original
+            .match_info
+            .and_then(|info| info.rule_id)
+            .unwrap_or_else(|| "policy".to_string());
+        println!("\nThe original command is blocked by {rule}; nothing was changed.");
+    }
+    Ok(())
+}
+
+fn handle_why_allowed(config: &Config, command: &str, extra_packs: Option<Vec<String>>) {
+    let allowlists = config.load_allowlists();
+    let tree = build_why_allowed_tree(
+        config,
+        command,
+        extra_packs.as_deref().unwrap_or_default(),
+        &allowlists,
+    );
+    for line in tree.with_theme(&crate::output::auto_theme()).render_plain() {
+        println!("{line}");
+    }
+}
+
+/// Whether `pack_id` is named by `list`, either directly or through its category.
+fn pack_listed(list: &[String], pack_id: &str) -> bool {
+    list.iter().any(|entry| {
+        pack_id == entry
+            || pack_id
+                .strip_prefix(entry.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// One allowlist hit in the `why-allowed` tree, with the entry's time window
+/// when it has one.
+fn allowlist_hit_node(
+    label: String,
+    hit: &crate::allowlist::AllowlistHit<'_>,
+) -> crate::output::TreeNode {
+    let node = crate::output::TreeNode::new(format!(
+        "{label} ({} layer): {}",
+        hit.layer.label(),
+        hit.entry.reason
+    ));
+    match crate::allowlist_window::describe(hit.entry) {
+        Some(window) => node.child(crate::output::TreeNode::new(format!(
+            "active window: {window}"
+        ))),
+        None => node,
+    }
+}
+
+/// Build the `why-allowed` tree: normalization steps, pack gating, near misses,
+/// and allowlist hits for a single command.
+#[allow(clippy::too_many_lines)]
+fn build_why_allowed_tree(
+    config: &Config,
+    command: &str,
+    extra_packs: &[String],
+    allowlists: &crate::LayeredAllowlist,
+) -> crate::output::DcgTree {
+    use crate::normalize::{fold_confusables, normalize_command, normalize_flags};
+    use crate::output::{ExplainTreeBuilder, TreeNode};
+
+    let mut effective_config = config.clone();
+    effective_config
+        .packs
+        .enabled
+        .extend(extra_packs.iter().cloned());
+
+    let enabled_packs = effective_config.enabled_pack_ids();
+    let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
+    let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
+    let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
+    let heredoc_settings = effective_config.heredoc_settings();
+    let compiled_overrides = effective_config.compile_overrides();
+
+    let result = evaluate_command_with_pack_order(
+        command,
+        &enabled_keywords,
+        &ordered_packs,
+        keyword_index.as_ref(),
+        &compiled_overrides,
+        allowlists,
+        &heredoc_settings,
+    );
+    let result = record_near_misses(result, command, &ordered_packs, effective_config.policy());
+
+    // Reproduce the evaluator's views of the command, in pipeline order.
+    let folded = fold_confusables(command);
+    let input = folded.as_ref().map_or(command, |fold| fold.text.as_str());
+    let sanitized = crate::context::sanitize_for_pattern_matching(input);
+    let normalized = normalize_command(&sanitized);
+    let masked = crate::heredoc::mask_non_executing_heredocs(&normalized);
+    let command_for_packs = masked.as_ref();
+    // The same command with quoted data and heredoc bodies left in place.
+    let raw_normalized = normalize_command(input);
+
+    let step = |label: &str, before: &str, after: &str| {
+        if before == after {
+            TreeNode::new(format!("{label}: unchanged"))
+        } else {
+            TreeNode::new(format!("{label}: {}", after.trim_end()))
+        }
+    };
+    let mut pipeline = TreeNode::new("Normalization")
+        .child(step("Unicode fold", command, input))
+        .child(step("Sanitize data arguments", input, &sanitized))
+        .child(step("Normalize command words", &sanitized, &normalized))
+        .child(step(
+            "Mask non-executing heredocs",
+            &normalized,
+            command_for_packs,
+        ));
+
+    let enabled_reason = |pack_id: &str| {
+        if pack_listed(extra_packs, pack_id) {
+            "--with-packs"
+        } else if pack_listed(&config.packs.enabled, pack_id) {
+            "packs.enabled"
+        } else if pack_id == "core" || pack_id.starts_with("core.") {
+            "always on"
+        } else {
+            "enabled"
+        }
+    };
+
+    let mut pack_nodes = Vec::new();
+    let mut rule_hits = Vec::new();
+    let mut skipped = 0_usize;
+    for pack_id in &ordered_packs {
+        let Some(pack) = REGISTRY.get(pack_id) else {
+            continue;
+        };
+        if !pack.might_match(command_for_packs) && !pack.might_match(&raw_normalized) {
+            skipped += 1;
+            continue;
+        }
+
+        let flag_view = normalize_flags(command_for_packs, REGISTRY.flag_specs(pack_id));
+        if let Some(view) = flag_view.as_ref() {
+            pipeline = pipeline.child(TreeNode::new(format!(
+                "Canonical flags ({pack_id}): {}",
+                view.canonical
+            )));
+        }
+
+        let safe = pack
+            .safe_patterns
+            .iter()
+            .find(|safe| safe.regex.is_match(command_for_packs));
+        let mut matched = Vec::new();
+        for pattern in &pack.destructive_patterns {
+            let rule = pattern
+                .name
+                .map_or_else(|| pack_id.clone(), |name| format!("{pack_id}:{name}"));
+            let hits_command = pattern.regex.is_match(command_for_packs)
+                || flag_view
+                    .as_ref()
+                    .is_some_and(|view| pattern.regex.is_match(&view.canonical));
+            if hits_command && safe.is_none() {
+                if let Some(hit) = pattern.name.and_then(|name| {
+                    allowlists.match_rule_for_command(pack_id, name, Some(&normalized), None)
+                }) {
+                    rule_hits.push(allowlist_hit_node(format!("rule {rule}"), &hit));
+                    matched.push(format!("{rule} (allowlisted)"));
+                } else {
+                    matched.push(format!("{rule} ({})", pattern.severity.label()));
+                }
+            }
+        }
+
+        let status = if let Some(safe) = safe {
+            format!("exempted by safe pattern `{}`", safe.name)
+        } else if matched.is_empty() {
+            "keyword present, no destructive pattern matched".to_string()
+        } else {
+            format!("matched {}", matched.join(", "))
+        };
+        pack_nodes.push(TreeNode::new(format!(
+            "{pack_id} [{}]: {status}",
+            enabled_reason(pack_id)
+        )));
+    }
+
+    let mut packs =
+        TreeNode::new(format!("Packs ({} enabled)", ordered_packs.len())).children(pack_nodes);
+    if skipped > 0 {
+        packs = packs.child(TreeNode::new(format!(
+            "{skipped} {} skipped: no keyword in command",
+            if skipped == 1 { "pack" } else { "packs" }
+        )));
+    }
+
+    // Packs that would have looked at this command had they been enabled.
+    let dormant: Vec<TreeNode> = REGISTRY
+        .all_pack_ids()
+        .into_iter()
+        .filter(|pack_id| !ordered_packs.iter().any(|enabled| enabled == pack_id))
+        .filter_map(|pack_id| {
+            let pack = REGISTRY.get(pack_id)?;
+            if pack.keywords.is_empty() || !pack.might_match(&raw_normalized) {
+                return None;
+            }
+            let why = if pack_listed(&effective_config.packs.disabled, pack_id) {
+                "disabled by packs.disabled"
+            } else {
+                "not enabled"
+            };
+            let verdict = pack.check(command_for_packs).map_or_else(
+                || "would not block".to_string(),
+                |hit| {
+                    format!(
+                        "would block via {pack_id}:{}",
+                        hit.name.unwrap_or("unnamed")
+                    )
+                },
+            );
+            Some(TreeNode::new(format!("{pack_id} ({why}): {verdict}")))
+        })
+        .collect();
+    if !dormant.is_empty() {
+        packs = packs.child(TreeNode::new("Disabled / not enabled").children(dormant));
+    }
+
+    let override_allow = compiled_overrides.check_allow(input);
+    let mut allowlist = TreeNode::new("Allowlist");
+    if override_allow {
+        allowlist = allowlist.child(TreeNode::new("config allow override matched"));
+    }
+    let exact = allowlists.match_exact_command_at_path(&normalized, None);
+    if let Some(hit) = exact.as_ref() {
+        allowlist = allowlist.child(allowlist_hit_node("exact command".to_string(), hit));
+    }
+    let prefix = allowlists.match_command_prefix_at_path(&normalized, None);
+    if let Some(hit) = prefix.as_ref() {
+        allowlist = allowlist.child(allowlist_hit_node("command prefix".to_string(), hit));
+    }
+    allowlist = allowlist.children(rule_hits);
+    if !allowlist.has_children() {
+        allowlist = allowlist.child(TreeNode::new("no allowlist entries fired"));
+    }
+    let environment = allowlists.active_environment();
+    allowlist = allowlist.child(TreeNode::new(format!(
+        "environment: {} ({})",
+        environment.name,
+        environment.source.label()
+    )));
+    allowlist = allowlist.child(TreeNode::new(format!(
+        "host: {}, user: {}",
+        crate::identity::host_name().unwrap_or("unknown"),
+        crate::identity::effective_user().unwrap_or("unknown")
+    )));
+
+    let decision = match result.pattern_info.as_ref() {
+        Some(info) if result.is_denied() => {
+            let rule = match (info.pack_id.as_deref(), info.pattern_name.as_deref()) {
+                (Some(pack), Some(name)) => format!("{pack}:{name}"),
+                _ => info.reason.clone(),
+            };
+            format!("BLOCKED by {rule}")
+        }
+        _ if result.skipped_due_to_budget => {
+            "ALLOWED (evaluation budget exceeded, fail open)".to_string()
+        }
+        _ if override_allow => "ALLOWED (config allow override)".to_string(),
+        _ if exact.is_some() || prefix.is_some() || result.allowlist_override.is_some() => {
+            "ALLOWED (allowlisted)".to_string()
+        }
+        _ if crate::packs::pack_aware_quick_reject(input, &enabled_keywords) => {
+            "ALLOWED (no enabled pack keyword in command)".to_string()
+        }
+        _ => "ALLOWED (no enabled pack matched)".to_string(),
+    };
+
+    let command_node = TreeNode::new("Command")
+        .child(TreeNode::new(format!("Input: {command}")))
+        .child(TreeNode::new(format!("Decision: {decision}")));
+    let near_miss_node = if result.near_misses.is_empty() {
+        TreeNode::new("Near misses").child(TreeNode::new("none"))
+    } else {
+        TreeNode::new("Near misses").children(
+            result
+                .near_misses
+                .iter()
+                .map(|miss| TreeNode::new(format!("{}: {}", miss.rule_id(), miss.describe()))),
+        )
+    };
+
+    ExplainTreeBuilder::new()
+        .command(command_node)
+        .match_info(near_miss_node)
+        .allowlist(allowlist)
+        .packs(packs)
+        .pipeline(pipeline)
+        .build()
+}
+
 // =============================================================================
 // =============================================================================
 // =============================================================================
@@ -5511,7 +8316,7 @@ fn run_single_corpus_test(
     let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
     let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
     let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
-    let compiled_overrides = effective_config.overrides.compile();
+    let compiled_overrides = effective_config.compile_overrides();
     let allowlists = crate::LayeredAllowlist::default();
     let heredoc_settings = effective_config.heredoc_settings();
 
@@ -5661,26 +8466,12 @@ fn handle_corpus_command(
     Ok(())
 }
 
-/// Handle the `dcg stats` command.
-#[allow(clippy::option_if_let_else)]
-fn handle_stats_command(
+/// Resolve the decision log path: `--file`, then `general.log_file`, then the default location.
+fn resolve_stats_log_path(
     config: &Config,
-    cmd: &StatsCommand,
-    quiet: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use crate::stats;
-
-    if quiet {
-        return Ok(());
-    }
-
-    // Handle --rules mode (query history database for rule-level metrics)
-    if cmd.rules {
-        return handle_stats_rules(config, cmd);
-    }
-
-    // Determine log file path
-    let log_path = if let Some(ref path) = cmd.file {
+    file: Option<&std::path::PathBuf>,
+) -> std::path::PathBuf {
+    if let Some(path) = file {
         path.clone()
     } else if let Some(ref log_file) = config.general.log_file {
         // Expand ~ in path
@@ -5698,7 +8489,28 @@ fn handle_stats_command(
             .unwrap_or_else(|| std::path::PathBuf::from("~/.local/share"))
             .join("dcg")
             .join("blocked.log")
-    };
+    }
+}
+
+/// Handle the `dcg stats` command.
+#[allow(clippy::option_if_let_else)]
+fn handle_stats_command(
+    config: &Config,
+    cmd: &StatsCommand,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::stats;
+
+    if quiet {
+        return Ok(());
+    }
+
+    // Handle --rules mode (query history database for rule-level metrics)
+    if cmd.rules {
+        return handle_stats_rules(config, cmd);
+    }
+
+    let log_path = resolve_stats_log_path(config, cmd.file.as_ref());
 
     // Check if log file exists
     if !log_path.exists() {
@@ -6203,3854 +9015,6473 @@ fn parse_duration_string(s: &str) -> Result<chrono::Duration, String> {
     }
 }
 
-/// Handle the `dcg suggest-allowlist` command.
+/// Handle the `dcg review` command.
 ///
-/// Analyzes denied commands from history and suggests allowlist patterns.
-fn handle_suggest_allowlist_command(
+/// Walks the interactive-learning queue one rule at a time.
+#[allow(clippy::too_many_lines)]
+fn handle_review_command(
     config: &Config,
-    cmd: &SuggestAllowlistCommand,
+    cmd: &ReviewCommand,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Handle --undo mode first
-    if let Some(minutes) = cmd.undo {
-        return handle_suggest_allowlist_undo(minutes);
-    }
+    use crate::review::ReviewQueueStore;
+    use colored::Colorize;
+    use std::io::{self, BufRead, Write};
 
-    // Parse the "since" duration
-    let duration = parse_duration_string(&cmd.since)?;
-    let since_time = Utc::now() - duration;
+    let cwd = std::env::current_dir().ok();
+    let store = ReviewQueueStore::new(ReviewQueueStore::default_path(cwd.as_deref()));
 
-    // Open history database
-    let db_path = config.history.expanded_database_path();
-    let db = match HistoryDb::open(db_path) {
-        Ok(db) => db,
-        Err(err) => {
-            if matches!(cmd.format, SuggestFormat::Json) {
-                // Output empty array for JSON format
-                println!("[]");
-                return Ok(());
-            }
-            if matches!(err, crate::history::HistoryError::Disabled) {
-                println!("History is disabled. Enable it in config to use suggest-allowlist.");
-                return Ok(());
-            }
-            println!("Error opening history database: {err}");
-            println!();
-            println!("Run 'dcg history stats' to check database status.");
-            return Ok(());
-        }
-    };
+    if cmd.clear {
+        store.clear()?;
+        println!("Review queue cleared ({}).", store.path().display());
+        return Ok(());
+    }
 
-    // Query denied commands from history
-    let options = ExportOptions {
-        outcome_filter: Some(Outcome::Deny),
-        since: Some(since_time),
-        until: None,
-        limit: None,
-    };
+    let groups = store.load().groups();
 
-    let entries = db.query_commands_for_export(&options)?;
+    if matches!(cmd.format, SuggestFormat::Json) {
+        let output: Vec<serde_json::Value> = groups
+            .iter()
+            .map(|group| {
+                serde_json::json!({
+                    "rule_id": group.rule_id,
+                    "reason": group.reason,
+                    "count": group.count,
+                    "pattern": group.pattern.regex,
+                    "commands": group.commands,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
 
-    if entries.is_empty() {
-        if matches!(cmd.format, SuggestFormat::Json) {
-            // Output empty array for JSON format
-            println!("[]");
-            return Ok(());
+    if groups.is_empty() {
+        println!("Review queue is empty.");
+        if !config.interactive.learning {
+            println!();
+            println!("Medium-severity warnings are queued once learning is enabled:");
+            println!("  [interactive]");
+            println!("  learning = true");
         }
-        println!("No denied commands found in the last {}.", cmd.since);
-        println!();
-        println!("Suggestions:");
-        println!("  - Check if history is enabled: dcg history stats");
-        println!("  - Try a longer time period: --since 90d");
         return Ok(());
     }
 
-    // Also query bypassed commands to include bypass information
-    let bypass_options = ExportOptions {
-        outcome_filter: Some(Outcome::Bypass),
-        since: Some(since_time),
-        until: None,
-        limit: None,
-    };
-    let bypass_entries = db
-        .query_commands_for_export(&bypass_options)
-        .unwrap_or_default();
-
-    // Build a set of commands that were bypassed
-    let bypassed_commands: std::collections::HashSet<String> =
-        bypass_entries.iter().map(|e| e.command.clone()).collect();
+    let interactive = !cmd.list && io::stdin().is_terminal() && io::stdout().is_terminal();
 
-    // Convert to CommandEntryInfo with path and bypass information
-    let entry_infos: Vec<CommandEntryInfo> = entries
-        .iter()
-        .map(|e| CommandEntryInfo {
-            command: e.command.clone(),
-            working_dir: e.working_dir.clone(),
-            was_bypassed: bypassed_commands.contains(&e.command),
-        })
-        .collect();
+    if interactive {
+        println!("{} rule(s) have queued warnings.", groups.len());
+        println!();
+        println!("For each rule, you can:");
+        println!("  [D]eny  - Add the pattern as a [overrides] block rule");
+        println!("  [A]llow - Add the pattern to the allowlist as safe");
+        println!("  [S]kip  - Keep it queued");
+        println!("  [Q]uit  - Stop reviewing");
+        println!();
+    }
 
-    // Generate enhanced suggestions with confidence and risk analysis
-    let mut suggestions = generate_enhanced_suggestions(&entry_infos, cmd.min_frequency);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
 
-    if suggestions.is_empty() {
-        if matches!(cmd.format, SuggestFormat::Json) {
-            // Output empty array for JSON format
-            println!("[]");
-            return Ok(());
-        }
+    for (i, group) in groups.iter().enumerate() {
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!(" [{}/{}] {}", i + 1, groups.len(), group.rule_id.cyan());
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!(" Reason: {}", group.reason);
         println!(
-            "No commands found that were blocked {} or more times.",
-            cmd.min_frequency
+            " Warned: {} times ({} unique commands)",
+            group.count,
+            group.commands.len()
         );
+        println!(" Pattern: {}", group.pattern.regex);
+        if !group.pattern.matches_all {
+            println!(
+                "   {}",
+                "⚠ Pattern does not match every queued command".yellow()
+            );
+        }
+        println!();
+        println!(" Commands:");
+        for command in group.commands.iter().take(5) {
+            println!("   • {command}");
+        }
+        if group.commands.len() > 5 {
+            println!("   ... and {} more", group.commands.len() - 5);
+        }
         println!();
-        println!("Try lowering --min-frequency or increasing --since period.");
-        return Ok(());
-    }
-
-    // Apply confidence filtering
-    suggestions = match cmd.confidence {
-        ConfidenceTierFilter::High => filter_by_confidence(suggestions, ConfidenceTier::High),
-        ConfidenceTierFilter::Medium => filter_by_confidence(suggestions, ConfidenceTier::Medium),
-        ConfidenceTierFilter::Low => filter_by_confidence(suggestions, ConfidenceTier::Low),
-        ConfidenceTierFilter::All => suggestions,
-    };
 
-    // Apply risk filtering
-    suggestions = match cmd.risk {
-        RiskLevelFilter::Low => filter_by_risk(suggestions, RiskLevel::Low),
-        RiskLevelFilter::Medium => filter_by_risk(suggestions, RiskLevel::Medium),
-        RiskLevelFilter::High => filter_by_risk(suggestions, RiskLevel::High),
-        RiskLevelFilter::All => suggestions,
-    };
+        if !interactive {
+            continue;
+        }
 
-    // Take up to the limit
-    suggestions.truncate(cmd.limit);
+        print!(" [D]eny  [A]llow  [S]kip  [Q]uit: ");
+        stdout.flush()?;
+        let mut input = String::new();
+        stdin.lock().read_line(&mut input)?;
 
-    if suggestions.is_empty() {
-        if matches!(cmd.format, SuggestFormat::Json) {
-            // Output empty array for JSON format
-            println!("[]");
-            return Ok(());
+        match input.trim().to_lowercase().as_str() {
+            "d" | "deny" => {
+                let Some(config_path) = find_repo_root_from_cwd()
+                    .map(|root| root.join(".dcg.toml"))
+                    .or_else(Config::user_config_path)
+                else {
+                    eprintln!(" {} Could not locate a config file to update", "✗".red());
+                    continue;
+                };
+                let reason = format!(
+                    "Promoted in dcg review from {}: {}",
+                    group.rule_id, group.reason
+                );
+                let before = std::fs::read_to_string(&config_path).ok();
+                match register_block_override(&config_path, &group.pattern.regex, &reason) {
+                    Ok(added) => {
+                        if added {
+                            record_policy_change("review", &config_path, before.as_deref());
+                            println!(" {} Block rule added", "✓".green());
+                        } else {
+                            println!(" {} Block rule already present", "ℹ".cyan());
+                        }
+                        println!("   File: {}", config_path.display());
+                        store.resolve(&group.rule_id)?;
+                    }
+                    Err(e) => eprintln!(" {} Could not update config: {e}", "✗".red()),
+                }
+                println!();
+            }
+            "a" | "allow" => {
+                let reason = format!("Marked safe in dcg review ({})", group.rule_id);
+                match allowlist_add_reviewed_pattern(&group.pattern.regex, &reason, "review") {
+                    Ok(path) => {
+                        println!(" {} Pattern added to allowlist", "✓".green());
+                        println!("   File: {}", path.display());
+                        store.resolve(&group.rule_id)?;
+                    }
+                    Err(e) if e.to_string().contains("already exists") => {
+                        println!(" {} Pattern already in allowlist", "ℹ".cyan());
+                        store.resolve(&group.rule_id)?;
+                    }
+                    Err(e) => eprintln!(" {} Could not write to allowlist: {e}", "✗".red()),
+                }
+                println!();
+            }
+            "q" | "quit" => {
+                println!();
+                println!("Stopped. Remaining rules stay queued.");
+                break;
+            }
+            _ => {}
         }
-        println!("No suggestions available.");
-        return Ok(());
     }
 
-    // Output based on format
-    match cmd.format {
-        SuggestFormat::Json => {
-            output_suggestions_json(&suggestions)?;
-        }
-        SuggestFormat::Text => {
-            if cmd.non_interactive {
-                // Non-interactive mode: no writes to database
-                output_suggestions_text(&suggestions);
-            } else {
-                // Interactive mode: pass db for audit logging and config for conflict detection
-                output_suggestions_interactive(&suggestions, entries.len(), Some(&db), config)?;
-            }
-        }
+    if !interactive {
+        println!("Run `dcg review` in a terminal to turn these into rules.");
     }
 
     Ok(())
 }
 
-/// Output suggestions as JSON.
-fn output_suggestions_json(
-    suggestions: &[AllowlistSuggestion],
-) -> Result<(), Box<dyn std::error::Error>> {
-    #[derive(serde::Serialize)]
-    struct JsonSuggestion {
-        pattern: String,
-        frequency: usize,
-        unique_variants: usize,
-        confidence: String,
-        risk: String,
-        reason: String,
-        score: f32,
-        example_commands: Vec<String>,
-        #[serde(skip_serializing_if = "Vec::is_empty")]
-        path_patterns: Vec<String>,
-        suggest_path_specific: bool,
-        bypass_count: usize,
+/// Handle the `dcg fuzz-corpus` command.
+fn handle_fuzz_corpus_command(action: FuzzCorpusAction) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        FuzzCorpusAction::Export {
+            out,
+            corpus,
+            no_corpus,
+            format,
+        } => {
+            let corpus = (!no_corpus).then_some(corpus.as_path());
+            if let Some(dir) = corpus {
+                if !dir.is_dir() {
+                    return Err(format!(
+                        "regression corpus not found at {} (run from the repository root, pass --corpus, or use --no-corpus)",
+                        dir.display()
+                    )
+                    .into());
+                }
+            }
+            let commands = crate::fuzz_corpus::seed_commands(corpus)?;
+            let summary = crate::fuzz_corpus::export(&commands, &out)?;
+            if format == DoctorFormat::Json {
+                let mut value = serde_json::to_value(&summary)?;
+                value["out"] = serde_json::Value::String(out.display().to_string());
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                println!(
+                    "Exported {} seeds ({} already present) for {} commands to {}",
+                    summary.written,
+                    summary.existing,
+                    summary.commands,
+                    out.display()
+                );
+                println!("Run: cargo +nightly fuzz run fuzz_pipeline (from the repository root)");
+            }
+        }
     }
-
-    let output: Vec<JsonSuggestion> = suggestions
-        .iter()
-        .map(|s| JsonSuggestion {
-            pattern: s.cluster.proposed_pattern.clone(),
-            frequency: s.cluster.frequency,
-            unique_variants: s.cluster.unique_count,
-            confidence: s.confidence.as_str().to_string(),
-            risk: s.risk.as_str().to_string(),
-            reason: s.reason.as_str().to_string(),
-            score: s.score,
-            example_commands: s.cluster.commands.clone(),
-            path_patterns: s.path_patterns.iter().map(|p| p.pattern.clone()).collect(),
-            suggest_path_specific: s.suggest_path_specific,
-            bypass_count: s.bypass_count,
-        })
-        .collect();
-
-    let json = serde_json::to_string_pretty(&output)?;
-    println!("{json}");
     Ok(())
 }
 
-/// Output suggestions as formatted text (non-interactive).
-fn output_suggestions_text(suggestions: &[AllowlistSuggestion]) {
-    println!("Allowlist Suggestions");
-    println!("=====================");
-    println!();
+/// Handle the `dcg warnings` command.
+///
+/// Lists, acknowledges, or converts warn-mode events queued by the hook.
+#[allow(clippy::too_many_lines)]
+fn handle_warnings_command(
+    config: &Config,
+    action: WarningsAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::warnings::{WarningGroup, WarningQueueStore};
+    use colored::Colorize;
 
-    for (i, suggestion) in suggestions.iter().enumerate() {
-        println!("[{}/{}] Suggestion", i + 1, suggestions.len());
-        println!("────────────────────────────────────────");
-        println!("Pattern: {}", suggestion.cluster.proposed_pattern);
-        println!(
-            "Blocked: {} times ({} unique variants)",
-            suggestion.cluster.frequency, suggestion.cluster.unique_count
-        );
-        println!(
-            "Confidence: {} | Risk: {} | Score: {:.2}",
-            suggestion.confidence, suggestion.risk, suggestion.score
-        );
-        println!("Reason: {}", suggestion.reason.description());
-        if suggestion.bypass_count > 0 {
-            println!("Bypassed: {} times", suggestion.bypass_count);
+    let cwd = std::env::current_dir().ok();
+    let store = WarningQueueStore::new(WarningQueueStore::default_path(cwd.as_deref()));
+    let queue = store.load();
+
+    let require_selection = |selection: &WarningSelectionArgs, all: bool| {
+        if all || !selection.selection().is_empty() {
+            Ok(())
+        } else {
+            Err("select warnings by ID, --rule, or --min-count, or pass --all")
         }
-        if !suggestion.path_patterns.is_empty() {
-            println!("Common paths:");
-            for pp in suggestion.path_patterns.iter().take(3) {
-                println!(
-                    "  • {} ({} occurrences{})",
-                    pp.pattern,
-                    pp.occurrence_count,
-                    if pp.is_project_dir {
-                        ", project dir"
-                    } else {
-                        ""
-                    }
+    };
+
+    match action {
+        WarningsAction::List { selection, format } => {
+            let groups = queue.groups(&selection.selection());
+            if format == DoctorFormat::Json {
+                let groups: Vec<serde_json::Value> = groups
+                    .iter()
+                    .map(|group| {
+                        serde_json::json!({
+                            "rule_id": group.rule_id,
+                            "reason": group.reason,
+                            "count": group.count,
+                            "pattern": group.pattern().regex,
+                            "events": group.events,
+                        })
+                    })
+                    .collect();
+                let json = serde_json::json!({ "path": store.path(), "groups": groups });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
+            if groups.is_empty() {
+                println!("No queued warnings ({}).", store.path().display());
+                if !config.policy.warn_queue() {
+                    println!();
+                    println!("Warnings are not being queued: [policy] warn_queue = false");
+                }
+                return Ok(());
+            }
+            for group in &groups {
+                println!(
+                    "{}  {} warning(s)  {}",
+                    group.rule_id.cyan().bold(),
+                    group.count,
+                    group.reason.dimmed()
                 );
+                for event in &group.events {
+                    println!(
+                        "  {:>4}  {}  {}",
+                        format!("#{}", event.id).bold(),
+                        event.command,
+                        format!("×{}, last {}", event.count, event.last_seen).dimmed()
+                    );
+                }
+                println!();
             }
+            println!(
+                "{}",
+                "Acknowledge with `dcg warnings ack <ID>...`, or turn a rule's commands into \
+                 rules with `dcg warnings convert --to deny|allow --rule <RULE_ID>`."
+                    .dimmed()
+            );
         }
-        println!();
-        println!("Example commands:");
-        for cmd in suggestion.cluster.commands.iter().take(5) {
-            println!("  • {cmd}");
-        }
-        if suggestion.cluster.commands.len() > 5 {
-            println!("  ... and {} more", suggestion.cluster.commands.len() - 5);
+        WarningsAction::Ack { selection, all } => {
+            require_selection(&selection, all)?;
+            let ids: Vec<u64> = queue
+                .groups(&selection.selection())
+                .iter()
+                .flat_map(WarningGroup::ids)
+                .collect();
+            let removed = store.remove(&ids)?;
+            println!("Acknowledged {removed} warning(s).");
+        }
+        WarningsAction::Convert { to, selection, all } => {
+            require_selection(&selection, all)?;
+            let groups = queue.groups(&selection.selection());
+            if groups.is_empty() {
+                println!("No queued warnings match.");
+                return Ok(());
+            }
+
+            let mut converted = 0;
+            for group in &groups {
+                let pattern = group.pattern();
+                if pattern.regex.is_empty() || !pattern.matches_all {
+                    println!(
+                        "{} {}: no pattern matches every queued command; left queued",
+                        "⚠".yellow(),
+                        group.rule_id
+                    );
+                    continue;
+                }
+                let outcome = match to {
+                    WarningDisposition::Deny => convert_warnings_to_block(group, &pattern.regex),
+                    WarningDisposition::Allow => {
+                        let reason = format!("Converted from warnings ({})", group.rule_id);
+                        allowlist_add_reviewed_pattern(&pattern.regex, &reason, "warnings convert")
+                    }
+                };
+                match outcome {
+                    Ok(path) => {
+                        store.remove(&group.ids())?;
+                        converted += 1;
+                        println!(
+                            "{} {} → {}",
+                            "✓".green(),
+                            group.rule_id.cyan(),
+                            pattern.regex
+                        );
+                        println!("  File: {}", path.display());
+                    }
+                    Err(e) if e.to_string().contains("already exists") => {
+                        store.remove(&group.ids())?;
+                        converted += 1;
+                        println!(
+                            "{} {}: pattern already in allowlist",
+                            "ℹ".cyan(),
+                            group.rule_id
+                        );
+                    }
+                    Err(e) => eprintln!("{} {}: {e}", "✗".red(), group.rule_id),
+                }
+            }
+            println!("Converted {converted} of {} rule(s).", groups.len());
         }
-        println!();
     }
+    Ok(())
 }
 
-/// Output suggestions interactively (prompting user for each).
-#[allow(clippy::too_many_lines)]
-fn output_suggestions_interactive(
-    suggestions: &[AllowlistSuggestion],
-    total_denied: usize,
-    db: Option<&HistoryDb>,
+/// Add a block rule for a group of queued warnings to `.dcg.toml` (or the
+/// user config outside a repository); returns the file written.
+fn convert_warnings_to_block(
+    group: &crate::warnings::WarningGroup,
+    pattern: &str,
+) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let config_path = find_repo_root_from_cwd()
+        .map(|root| root.join(".dcg.toml"))
+        .or_else(Config::user_config_path)
+        .ok_or("could not locate a config file to update")?;
+    let reason = format!(
+        "Converted from warnings for {}: {}",
+        group.rule_id, group.reason
+    );
+    let before = std::fs::read_to_string(&config_path).ok();
+    if register_block_override(&config_path, pattern, &reason)? {
+        record_policy_change("warnings convert", &config_path, before.as_deref());
+    }
+    Ok(config_path)
+}
+
+fn handle_approvals_command(
     config: &Config,
+    action: ApprovalsAction,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::approvals::{ApprovalQueueStore, ApprovalRequest, ApprovalStatus};
     use colored::Colorize;
     use std::io::{self, BufRead, Write};
 
-    println!("Analyzing {total_denied} denied commands...");
-    println!("Found {} potential allowlist patterns.", suggestions.len());
-    println!();
-    println!("For each suggestion, you can:");
-    println!("  [A]ccept - Record pattern (to add to allowlist)");
-    println!("  [S]kip   - Move to next suggestion");
-    println!("  [Q]uit   - Exit without more changes");
-    println!();
-
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-    let working_dir = std::env::current_dir()
-        .ok()
-        .map(|p| p.to_string_lossy().to_string());
-
-    for (i, suggestion) in suggestions.iter().enumerate() {
-        let cluster = &suggestion.cluster;
-        // Check for potential conflicts before displaying
-        let conflict_check = check_pattern_conflicts(&cluster.proposed_pattern, config);
+    let cwd = std::env::current_dir().ok();
+    let store = ApprovalQueueStore::new(ApprovalQueueStore::default_path(cwd.as_deref()));
 
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!(" [{}/{}] Suggestion", i + 1, suggestions.len());
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!(" Pattern: {}", cluster.proposed_pattern);
+    let display_command = |request: &ApprovalRequest, show_raw: bool| {
+        if show_raw {
+            request.block.command_raw.clone()
+        } else {
+            request.block.command_redacted.clone()
+        }
+    };
+    let print_request = |request: &ApprovalRequest, show_raw: bool| {
+        let status = match request.status {
+            ApprovalStatus::Pending => request.status.label().yellow(),
+            ApprovalStatus::Approved => request.status.label().green(),
+            ApprovalStatus::Rejected => request.status.label().red(),
+        };
         println!(
-            " Blocked: {} times ({} unique variants)",
-            cluster.frequency, cluster.unique_count
+            "{:>5}  {}  {}",
+            format!("#{}", request.id).bold(),
+            status,
+            display_command(request, show_raw)
         );
-
-        // Display confidence, risk, and score
-        let confidence_color = match suggestion.confidence {
-            ConfidenceTier::High => "high".green(),
-            ConfidenceTier::Medium => "medium".yellow(),
-            ConfidenceTier::Low => "low".red(),
-        };
-        let risk_color = match suggestion.risk {
-            RiskLevel::Low => "low".green(),
-            RiskLevel::Medium => "medium".yellow(),
-            RiskLevel::High => "high".red(),
-        };
+        println!("       {}", request.block.reason.dimmed());
         println!(
-            " Confidence: {} | Risk: {} | Score: {:.2}",
-            confidence_color, risk_color, suggestion.score
+            "       {}",
+            format!(
+                "cwd {}, ×{}, last {}",
+                request.block.cwd, request.attempts, request.last_seen
+            )
+            .dimmed()
         );
-        println!(" Reason: {}", suggestion.reason.description());
+    };
 
-        // Show bypass information if available
-        if suggestion.bypass_count > 0 {
+    match action {
+        ApprovalsAction::List {
+            all,
+            show_raw,
+            format,
+        } => {
+            let queue = store.load();
+            let requests: Vec<&ApprovalRequest> = if all {
+                queue.requests.iter().collect()
+            } else {
+                queue.pending().collect()
+            };
+            if format == DoctorFormat::Json {
+                let requests: Vec<serde_json::Value> = requests
+                    .iter()
+                    .map(|request| {
+                        serde_json::json!({
+                            "id": request.id,
+                            "status": request.status,
+                            "command": display_command(request, show_raw),
+                            "cwd": request.block.cwd,
+                            "reason": request.block.reason,
+                            "rule_id": request.block.rule_id,
+                            "session_id": request.block.session_id,
+                            "agent": request.block.agent,
+                            "attempts": request.attempts,
+                            "first_seen": request.first_seen,
+                            "last_seen": request.last_seen,
+                            "decided_at": request.decided_at,
+                            "notified_at": request.notified_at,
+                        })
+                    })
+                    .collect();
+                let json = serde_json::json!({ "path": store.path(), "requests": requests });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
+            if requests.is_empty() {
+                println!(
+                    "No requests awaiting approval ({}).",
+                    store.path().display()
+                );
+                if !config.interactive.approval_queue {
+                    println!();
+                    println!("Denials are not being queued: [interactive] approval_queue = false");
+                }
+                return Ok(());
+            }
+            for request in requests {
+                print_request(request, show_raw);
+            }
+            println!();
             println!(
-                " {} Bypassed {} time(s) - user manually allowed this command",
-                "✓".green(),
-                suggestion.bypass_count
+                "{}",
+                "Approve with `dcg approvals approve <ID>...`, or reject with \
+                 `dcg approvals reject <ID>...`."
+                    .dimmed()
             );
         }
-
-        // Show path patterns if suggesting path-specific allowlisting
-        if !suggestion.path_patterns.is_empty() {
-            println!();
-            println!(" Common paths:");
-            for pp in suggestion.path_patterns.iter().take(3) {
-                let project_indicator = if pp.is_project_dir {
-                    " (project dir)".dimmed()
+        ApprovalsAction::Approve {
+            ids,
+            single_use,
+            force,
+        } => {
+            let mut failed = false;
+            for id in ids {
+                match approve_queued_request(config, &store, id, single_use, force) {
+                    Ok(path) => {
+                        println!("{} Request #{id} approved", "✓".green());
+                        println!("  File: {}", path.display());
+                    }
+                    Err(e) => {
+                        eprintln!("{} Request #{id}: {e}", "✗".red());
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        ApprovalsAction::Reject { ids } => {
+            let mut failed = false;
+            for id in ids {
+                if store
+                    .decide(id, ApprovalStatus::Rejected, Utc::now())?
+                    .is_some()
+                {
+                    println!("{} Request #{id} rejected", "✓".green());
                 } else {
-                    "".normal()
-                };
-                println!(
-                    "   • {} ({} occurrences){}",
-                    pp.pattern, pp.occurrence_count, project_indicator
-                );
+                    eprintln!("{} No pending request #{id}", "✗".red());
+                    failed = true;
+                }
             }
-            if suggestion.suggest_path_specific {
-                println!(
-                    "   {}",
-                    "→ Consider path-specific allowlisting for this pattern".cyan()
-                );
+            if failed {
+                std::process::exit(1);
             }
         }
-
-        // Display warnings if there are conflicts or the pattern is overly broad
-        if conflict_check.conflicts_with_blocks || conflict_check.is_overly_broad {
-            println!();
-            println!(" {}", "⚠ Warnings:".yellow());
-            if let Some(ref warning) = conflict_check.block_conflict_warning {
-                println!("   • {}", warning.yellow());
+        ApprovalsAction::Review { show_raw } => {
+            if !(io::stdin().is_terminal() && io::stdout().is_terminal()) {
+                return Err("dcg approvals review needs a terminal; use \
+                            `dcg approvals approve|reject <ID>` instead."
+                    .into());
             }
-            if conflict_check.is_overly_broad {
-                println!(
-                    "   • {}",
-                    "Pattern is overly broad (uses wildcards without anchors)".yellow()
-                );
-                if let Some(ref suggestion_text) = conflict_check.refinement_suggestion {
-                    println!("     {}", suggestion_text.dimmed());
+            let queue = store.load();
+            let pending: Vec<&ApprovalRequest> = queue.pending().collect();
+            if pending.is_empty() {
+                println!("No requests awaiting approval.");
+                return Ok(());
+            }
+
+            let stdin = io::stdin();
+            let mut stdout = io::stdout();
+            for request in pending {
+                print_request(request, show_raw);
+                println!();
+                print!(" [A]pprove  [R]eject  [S]kip  [Q]uit: ");
+                stdout.flush()?;
+                let mut input = String::new();
+                stdin.lock().read_line(&mut input)?;
+
+                match input.trim().to_lowercase().as_str() {
+                    "a" | "approve" => {
+                        match approve_queued_request(config, &store, request.id, false, false) {
+                            Ok(path) => {
+                                println!(" {} Approved", "✓".green());
+                                println!("   File: {}", path.display());
+                            }
+                            Err(e) => eprintln!(" {} {e}", "✗".red()),
+                        }
+                    }
+                    "r" | "reject" => {
+                        store.decide(request.id, ApprovalStatus::Rejected, Utc::now())?;
+                        println!(" {} Rejected", "✓".green());
+                    }
+                    "q" | "quit" => break,
+                    _ => {}
                 }
+                println!();
             }
         }
+    }
+    Ok(())
+}
 
-        println!();
-        println!(" Example commands:");
-        for cmd in cluster.commands.iter().take(5) {
-            println!("   • {cmd}");
-        }
-        if cluster.commands.len() > 5 {
-            println!("   ... and {} more", cluster.commands.len() - 5);
-        }
-        println!();
+/// Approve pending request `id`: add the allow-once grant `dcg allow-once`
+/// would create for its denial, then mark it approved. Returns the allow-once
+/// file written.
+fn approve_queued_request(
+    config: &Config,
+    store: &crate::approvals::ApprovalQueueStore,
+    id: u64,
+    single_use: bool,
+    force: bool,
+) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    use crate::approvals::ApprovalStatus;
 
-        // Prompt for action
-        print!(" [A]ccept  [S]kip  [Q]uit: ");
-        stdout.flush()?;
+    let now = Utc::now();
+    let queue = store.load();
+    let block = &queue
+        .get(id)
+        .filter(|request| request.status == ApprovalStatus::Pending)
+        .ok_or_else(|| format!("no pending request #{id}"))?
+        .block;
+    let (entry, block_cwd) = approval_allow_once_entry(config, block, single_use, force, now)?;
+
+    let allow_once_path = AllowOnceStore::default_path(Some(&block_cwd));
+    AllowOnceStore::new(allow_once_path.clone()).add_entry(&entry, now)?;
+    store.decide(id, ApprovalStatus::Approved, now)?;
+
+    // The pending exception is no longer needed (best-effort, as for
+    // `dcg allow-once`).
+    let pending_store =
+        PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&block_cwd)));
+    let _ = pending_store.remove_by_full_hash(&block.full_hash, now);
+
+    Ok(allow_once_path)
+}
 
-        let mut input = String::new();
-        stdin.lock().read_line(&mut input)?;
+/// The allow-once entry that approving a queued denial grants, and the
+/// directory the command was blocked in.
+pub(crate) fn approval_allow_once_entry(
+    config: &Config,
+    block: &PendingExceptionRecord,
+    single_use: bool,
+    force: bool,
+    now: chrono::DateTime<Utc>,
+) -> Result<(AllowOnceEntry, std::path::PathBuf), Box<dyn std::error::Error>> {
+    let is_config_block = block.source.as_deref() == Some("ConfigOverride");
+    if is_config_block && !force {
+        return Err(
+            "this denial came from your config blocklist; re-run with --force to override.".into(),
+        );
+    }
 
-        match input.trim().to_lowercase().as_str() {
-            "a" | "accept" => {
-                // Log audit entry for accepted suggestion
-                if let Some(db) = db {
-                    let audit_entry = SuggestionAuditEntry {
-                        timestamp: Utc::now(),
-                        action: SuggestionAction::Accepted,
-                        pattern: cluster.proposed_pattern.clone(),
-                        final_pattern: None,
-                        risk_level: suggestion.risk.as_str().to_string(),
-                        risk_score: suggestion.risk.score(),
-                        confidence_tier: suggestion.confidence.as_str().to_string(),
-                        confidence_points: match suggestion.confidence {
-                            ConfidenceTier::High => 3,
-                            ConfidenceTier::Medium => 2,
-                            ConfidenceTier::Low => 1,
-                        },
-                        cluster_frequency: cluster.frequency,
-                        unique_variants: cluster.unique_count,
-                        sample_commands: serde_json::to_string(&cluster.commands)
-                            .unwrap_or_default(),
-                        rule_id: None,
-                        session_id: None,
-                        working_dir: working_dir.clone(),
-                    };
-                    if let Err(e) = db.log_suggestion_audit(&audit_entry) {
-                        eprintln!(" Warning: Could not log audit entry: {e}");
-                    }
-                }
+    let block_cwd = if block.cwd == "<unknown>" || block.cwd.is_empty() {
+        std::env::current_dir().unwrap_or_default()
+    } else {
+        std::path::PathBuf::from(&block.cwd)
+    };
+    let (scope_kind, scope_path) = allow_once_scope(&block_cwd);
+    let grant = resolve_allow_once_grant(config, block, None)?;
+    let entry = AllowOnceEntry::from_pending(
+        block,
+        now,
+        scope_kind,
+        &scope_path.to_string_lossy(),
+        single_use,
+        force && is_config_block,
+        &config.logging.redaction,
+    )
+    .with_grant(grant);
+    Ok((entry, block_cwd))
+}
 
-                // Generate a descriptive reason from the suggestion
-                let reason = format!(
-                    "Auto-suggested ({} confidence, {} risk): {}",
-                    suggestion.confidence.as_str(),
-                    suggestion.risk.as_str(),
-                    suggestion.reason.description()
-                );
+/// Handle the `dcg suggest-allowlist` command.
+///
+/// Analyzes denied commands from history and suggests allowlist patterns.
+fn handle_suggest_allowlist_command(
+    config: &Config,
+    cmd: &SuggestAllowlistCommand,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Handle --undo mode first
+    if let Some(minutes) = cmd.undo {
+        return handle_suggest_allowlist_undo(minutes);
+    }
 
-                // Write the pattern to the allowlist
-                match allowlist_add_pattern(
-                    &cluster.proposed_pattern,
-                    &reason,
-                    suggestion.confidence.as_str(),
-                    suggestion.risk.as_str(),
-                    cluster.frequency,
-                    cluster.unique_count,
-                ) {
-                    Ok(path) => {
-                        use colored::Colorize;
-                        println!(" {} Pattern added to allowlist", "✓".green());
-                        println!("   File: {}", path.display());
-                        println!();
-                    }
-                    Err(e) => {
-                        use colored::Colorize;
-                        // Check if it's a duplicate error (not a real failure)
-                        if e.to_string().contains("already exists") {
-                            println!(" {} Pattern already in allowlist", "ℹ".cyan());
-                        } else {
-                            eprintln!(" {} Could not write to allowlist: {e}", "✗".red());
-                            println!("   You can manually add it with:");
-                            println!(
-                                "   dcg allowlist add-pattern --pattern '{}' --reason '{}'",
-                                cluster.proposed_pattern, reason
-                            );
-                        }
-                        println!();
-                    }
-                }
-            }
-            "q" | "quit" => {
-                println!();
-                println!("Exiting. No changes made to allowlist.");
-                break;
-            }
-            _ => {
-                // Skip by default - log as rejected for tracking
-                if let Some(db) = db {
-                    let audit_entry = SuggestionAuditEntry {
-                        timestamp: Utc::now(),
-                        action: SuggestionAction::Rejected,
-                        pattern: cluster.proposed_pattern.clone(),
-                        final_pattern: None,
-                        risk_level: suggestion.risk.as_str().to_string(),
-                        risk_score: suggestion.risk.score(),
-                        confidence_tier: suggestion.confidence.as_str().to_string(),
-                        confidence_points: match suggestion.confidence {
-                            ConfidenceTier::High => 3,
-                            ConfidenceTier::Medium => 2,
-                            ConfidenceTier::Low => 1,
-                        },
-                        cluster_frequency: cluster.frequency,
-                        unique_variants: cluster.unique_count,
-                        sample_commands: serde_json::to_string(&cluster.commands)
-                            .unwrap_or_default(),
-                        rule_id: None,
-                        session_id: None,
-                        working_dir: working_dir.clone(),
-                    };
-                    // Best effort - don't warn on skip audit failures
-                    let _ = db.log_suggestion_audit(&audit_entry);
-                }
-                println!(" → Skipped");
-                println!();
-            }
-        }
+    let cwd = std::env::current_dir().ok();
+    let suggestion_store =
+        SuggestionHistoryStore::new(SuggestionHistoryStore::default_path(cwd.as_deref()));
+    if !cmd.dismiss.is_empty() {
+        return handle_suggest_allowlist_dismiss(&suggestion_store, &cmd.dismiss);
     }
 
-    Ok(())
-}
+    // Parse the "since" duration
+    let duration = parse_duration_string(&cmd.since)?;
+    let since_time = Utc::now() - duration;
 
-/// Handle the `dcg history` command.
-fn handle_history_command(
-    config: &Config,
-    action: HistoryAction,
-) -> Result<(), Box<dyn std::error::Error>> {
+    // Open history database
     let db_path = config.history.expanded_database_path();
     let db = match HistoryDb::open(db_path) {
         Ok(db) => db,
         Err(err) => {
+            if matches!(cmd.format, SuggestFormat::Json) {
+                // Output empty array for JSON format
+                println!("[]");
+                return Ok(());
+            }
+            if matches!(err, crate::history::HistoryError::Disabled) {
+                println!("History is disabled. Enable it in config to use suggest-allowlist.");
+                return Ok(());
+            }
             println!("Error opening history database: {err}");
+            println!();
+            println!("Run 'dcg history stats' to check database status.");
             return Ok(());
         }
     };
 
-    match action {
-        HistoryAction::Stats { days, trends, json } => {
-            history_stats(&db, days, trends, json)?;
-        }
-        HistoryAction::Prune {
-            older_than_days,
-            dry_run,
-            yes,
-        } => {
-            history_prune(&db, older_than_days, dry_run, yes)?;
-        }
-        HistoryAction::Export {
-            output,
-            format,
-            outcome,
-            since,
-            until,
-            limit,
-            compress,
-        } => {
-            history_export(&db, output, format, outcome, since, until, limit, compress)?;
-        }
-        HistoryAction::Analyze {
-            days,
-            json,
-            recommendations_only,
-            false_positives,
-            gaps,
-        } => {
-            history_analyze(&db, days, json, recommendations_only, false_positives, gaps)?;
-        }
-        HistoryAction::Check { json, strict } => {
-            history_check(&db, json, strict)?;
+    // Query denied commands from history
+    let options = ExportOptions {
+        outcome_filter: Some(Outcome::Deny),
+        since: Some(since_time),
+        until: None,
+        limit: None,
+    };
+
+    let mut entries = db.query_commands_for_export(&options)?;
+
+    // Commands logged with privacy.log_mode = "hash" cannot be generalized.
+    let hashed = entries.len();
+    entries.retain(|e| !crate::privacy::is_hashed(&e.command));
+    let hashed = hashed - entries.len();
+
+    if entries.is_empty() {
+        if matches!(cmd.format, SuggestFormat::Json) {
+            // Output empty array for JSON format
+            println!("[]");
+            return Ok(());
         }
-        HistoryAction::Backup { output, compress } => {
-            history_backup(&db, &output, compress)?;
+        if hashed > 0 {
+            println!(
+                "All {hashed} denied commands in the last {} were logged as hashes \
+                 (privacy.log_mode = \"hash\"); switch to \"redacted\" to get suggestions.",
+                cmd.since
+            );
+            return Ok(());
         }
+        println!("No denied commands found in the last {}.", cmd.since);
+        println!();
+        println!("Suggestions:");
+        println!("  - Check if history is enabled: dcg history stats");
+        println!("  - Try a longer time period: --since 90d");
+        return Ok(());
     }
 
-    Ok(())
-}
-
-fn history_stats(
-    db: &HistoryDb,
-    days: u64,
-    trends: bool,
-    json: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let stats = if trends {
-        db.compute_stats_with_trends(days)?
-    } else {
-        db.compute_stats(days)?
+    // Also query bypassed commands to include bypass information
+    let bypass_options = ExportOptions {
+        outcome_filter: Some(Outcome::Bypass),
+        since: Some(since_time),
+        until: None,
+        limit: None,
     };
+    let bypass_entries = db
+        .query_commands_for_export(&bypass_options)
+        .unwrap_or_default();
 
-    if json {
-        let output = serde_json::to_string_pretty(&stats)?;
-        println!("{output}");
-    } else {
-        let output = format_history_stats_pretty(&stats);
-        print!("{output}");
-    }
+    // Build a set of commands that were bypassed
+    let bypassed_commands: std::collections::HashSet<String> =
+        bypass_entries.iter().map(|e| e.command.clone()).collect();
 
-    Ok(())
-}
+    // Convert to CommandEntryInfo with path and bypass information
+    let entry_infos: Vec<CommandEntryInfo> = entries
+        .iter()
+        .map(|e| CommandEntryInfo {
+            command: e.command.clone(),
+            working_dir: e.working_dir.clone(),
+            was_bypassed: bypassed_commands.contains(&e.command),
+        })
+        .collect();
 
-fn history_prune(
-    db: &HistoryDb,
-    older_than_days: u64,
-    dry_run: bool,
-    yes: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if older_than_days == 0 {
-        return Err("older-than-days must be at least 1".into());
-    }
+    // Generate enhanced suggestions with confidence and risk analysis, merged
+    // with clusters from earlier runs (accepted/dismissed ones are dropped)
+    let now = Utc::now();
+    let risk_guard = risk_guard(config);
+    let mut suggestions = suggestion_store
+        .update(|history| {
+            generate_enhanced_suggestions_with_history(
+                &entry_infos,
+                cmd.min_frequency,
+                &risk_guard,
+                history,
+                now,
+            )
+        })
+        .unwrap_or_else(|_| {
+            let mut history = suggestion_store.load();
+            generate_enhanced_suggestions_with_history(
+                &entry_infos,
+                cmd.min_frequency,
+                &risk_guard,
+                &mut history,
+                now,
+            )
+        });
 
-    if !dry_run && !yes {
-        println!("Refusing to prune without --yes or --dry-run.");
+    if suggestions.is_empty() {
+        if matches!(cmd.format, SuggestFormat::Json) {
+            // Output empty array for JSON format
+            println!("[]");
+            return Ok(());
+        }
+        println!(
+            "No commands found that were blocked {} or more times.",
+            cmd.min_frequency
+        );
+        println!();
+        println!("Try lowering --min-frequency or increasing --since period.");
         return Ok(());
     }
 
-    let pruned = db.prune_older_than_days(older_than_days, dry_run)?;
-    if dry_run {
-        println!("Would prune {pruned} entries older than {older_than_days} days");
-    } else {
-        println!("Pruned {pruned} entries older than {older_than_days} days");
-    }
-
-    Ok(())
-}
+    // Apply confidence filtering
+    suggestions = match cmd.confidence {
+        ConfidenceTierFilter::High => filter_by_confidence(suggestions, ConfidenceTier::High),
+        ConfidenceTierFilter::Medium => filter_by_confidence(suggestions, ConfidenceTier::Medium),
+        ConfidenceTierFilter::Low => filter_by_confidence(suggestions, ConfidenceTier::Low),
+        ConfidenceTierFilter::All => suggestions,
+    };
 
-#[allow(clippy::too_many_arguments)]
-#[allow(clippy::needless_pass_by_value)]
-fn history_export(
-    db: &HistoryDb,
-    output_path: Option<String>,
-    format: ExportFormat,
-    outcome: Option<String>,
-    since: Option<String>,
-    until: Option<String>,
-    limit: Option<usize>,
-    compress: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use chrono::DateTime;
-    use flate2::Compression;
-    use flate2::write::GzEncoder;
-    use std::fs::File;
-    use std::io::{self, BufWriter, Write};
+    // Apply risk filtering
+    suggestions = match cmd.risk {
+        RiskLevelFilter::Low => filter_by_risk(suggestions, RiskLevel::Low),
+        RiskLevelFilter::Medium => filter_by_risk(suggestions, RiskLevel::Medium),
+        RiskLevelFilter::High => filter_by_risk(suggestions, RiskLevel::High),
+        RiskLevelFilter::All => suggestions,
+    };
 
-    // Parse outcome filter
-    let outcome_filter = outcome
-        .as_deref()
-        .map(|o| Outcome::parse(o).ok_or_else(|| format!("Invalid outcome: {o}")))
-        .transpose()?;
+    // Take up to the limit
+    suggestions.truncate(cmd.limit);
 
-    // Parse date/time filters
-    let since_dt = since
-        .as_deref()
-        .map(|s| {
-            DateTime::parse_from_rfc3339(s)
-                .map(|dt| dt.with_timezone(&chrono::Utc))
-                .map_err(|_| format!("Invalid since datetime: {s} (use ISO 8601 format)"))
-        })
-        .transpose()?;
+    if suggestions.is_empty() {
+        if matches!(cmd.format, SuggestFormat::Json) {
+            // Output empty array for JSON format
+            println!("[]");
+            return Ok(());
+        }
+        println!("No suggestions available.");
+        return Ok(());
+    }
 
-    let until_dt = until
-        .as_deref()
-        .map(|s| {
-            DateTime::parse_from_rfc3339(s)
-                .map(|dt| dt.with_timezone(&chrono::Utc))
-                .map_err(|_| format!("Invalid until datetime: {s} (use ISO 8601 format)"))
-        })
-        .transpose()?;
+    // Output based on format
+    match cmd.format {
+        SuggestFormat::Json => {
+            output_suggestions_json(&suggestions)?;
+        }
+        SuggestFormat::Text => {
+            if cmd.non_interactive {
+                // Non-interactive mode: no writes to database
+                output_suggestions_text(&suggestions);
+            } else {
+                // Interactive mode: pass db for audit logging, the suggestion store
+                // for accept/skip decisions, and config for conflict detection
+                output_suggestions_interactive(
+                    &suggestions,
+                    entries.len(),
+                    Some(&db),
+                    Some(&suggestion_store),
+                    config,
+                )?;
+            }
+        }
+    }
 
-    let options = ExportOptions {
-        outcome_filter,
-        since: since_dt,
-        until: until_dt,
-        limit,
-    };
+    Ok(())
+}
 
-    // Create output writer
-    let count: usize;
-    if let Some(path) = output_path {
-        let file = File::create(&path)?;
-        if compress {
-            let encoder = GzEncoder::new(file, Compression::default());
-            let mut writer = BufWriter::new(encoder);
-            count = export_to_writer(db, &mut writer, format, &options)?;
-            writer.flush()?;
+/// Handle `dcg suggest-allowlist --dismiss <ID>`.
+fn handle_suggest_allowlist_dismiss(
+    store: &SuggestionHistoryStore,
+    ids: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut unknown = Vec::new();
+    for id in ids {
+        if store.set_status(id, SuggestionStatus::Dismissed)? {
+            println!("Dismissed suggestion {id}");
         } else {
-            let mut writer = BufWriter::new(file);
-            count = export_to_writer(db, &mut writer, format, &options)?;
-            writer.flush()?;
+            unknown.push(id.as_str());
         }
-        eprintln!("Exported {count} records to {path}");
+    }
+    if unknown.is_empty() {
+        Ok(())
     } else {
-        let stdout = io::stdout();
-        let mut writer = stdout.lock();
-        count = export_to_writer(db, &mut writer, format, &options)?;
-        writer.flush()?;
-        eprintln!("Exported {count} records");
+        Err(format!("Unknown suggestion id(s): {}", unknown.join(", ")).into())
+    }
+}
+
+/// `dcg onboard`: print the adoption steps, or analyze the project with
+/// `--analyze`.
+fn handle_onboard_command(
+    config: &Config,
+    cmd: &OnboardCommand,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::onboard::{
+        OnboardInputs, default_history_files, evaluate_history, history_commands, scan_project,
+        starter_allowlist, summarize,
+    };
+    use colored::Colorize;
+
+    if !cmd.analyze {
+        println!("{}", "Adopting dcg in an existing project".bold());
+        println!();
+        println!("  1. dcg onboard --analyze -o starter-allowlist.toml");
+        println!("     Finds commands the project runs that would be blocked.");
+        println!("  2. Review starter-allowlist.toml, then:");
+        println!("     dcg allowlist import starter-allowlist.toml --layer project");
+        println!("  3. dcg install");
+        println!("     Registers the hook with your agent.");
+        return Ok(());
+    }
+
+    let ctx = crate::scan::ScanEvalContext::from_config(config);
+    let scan = scan_project(&cmd.path, config, &ctx)?;
+
+    let mut history = Vec::new();
+    let mut history_files = Vec::new();
+    if !cmd.no_history {
+        for (shell, path) in default_history_files() {
+            let Ok(bytes) = std::fs::read(&path) else {
+                continue;
+            };
+            let content = String::from_utf8_lossy(&bytes);
+            history.extend(history_commands(shell, &path, &content, cmd.history_limit));
+            history_files.push(path.display().to_string());
+        }
+    }
+    let history_findings = evaluate_history(&history, config, &ctx);
+
+    let root = cmd.path.display().to_string();
+    let report = summarize(&OnboardInputs {
+        root: &root,
+        project: &scan.findings,
+        history: &history_findings,
+        files_scanned: scan.summary.files_scanned,
+        history_files,
+        history_commands: history.len(),
+        min_history: cmd.min_history,
+    });
+    let proposal = starter_allowlist(&report);
+
+    if let Some(output) = &cmd.output {
+        std::fs::write(output, &proposal)
+            .map_err(|e| format!("Failed to write {}: {e}", output.display()))?;
+    }
+
+    if matches!(cmd.format, SuggestFormat::Json) {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("{}", "Onboarding report".bold());
+    println!(
+        "Scanned {} file(s) under {root} and {} history command(s).",
+        report.files_scanned, report.history_commands
+    );
+    println!();
+
+    if report.rules.is_empty() && report.unattributed == 0 {
+        println!(
+            "{}",
+            "Nothing the project runs would be blocked by the current policy.".green()
+        );
+        return Ok(());
+    }
+
+    for usage in &report.rules {
+        let marker = if usage.proposed {
+            "proposed".green()
+        } else if usage.decision == crate::scan::ScanDecision::Warn {
+            "warn only".yellow()
+        } else {
+            "not proposed".dimmed()
+        };
+        let critical = if usage.critical {
+            format!(" {}", "critical".red().bold())
+        } else {
+            String::new()
+        };
+        println!(
+            "{} [{marker}]{critical}: {} in project, {} in history",
+            usage.rule_id.bold(),
+            usage.project_hits,
+            usage.history_hits
+        );
+        if let Some(reason) = &usage.reason {
+            println!("    {}", reason.dimmed());
+        }
+        for location in &usage.locations {
+            println!("    at {location}");
+        }
+        for example in &usage.examples {
+            println!("    $ {example}");
+        }
+    }
+    if report.unattributed > 0 {
+        println!();
+        println!(
+            "{} blocked command(s) have no rule ID and cannot be allowlisted by rule; \
+             see `dcg explain \"<command>\"`.",
+            report.unattributed
+        );
     }
 
+    println!();
+    match &cmd.output {
+        Some(output) => println!(
+            "Starter allowlist written to {}. Review it, then run:\n  dcg allowlist import {} --layer project",
+            output.display(),
+            output.display()
+        ),
+        None => {
+            println!("{}", "Proposed starter allowlist:".bold());
+            println!();
+            print!("{proposal}");
+        }
+    }
     Ok(())
 }
 
-fn export_to_writer<W: std::io::Write>(
-    db: &HistoryDb,
-    writer: &mut W,
-    format: ExportFormat,
-    options: &ExportOptions,
-) -> Result<usize, Box<dyn std::error::Error>> {
-    let count = match format {
-        ExportFormat::Json => db.export_json(writer, options)?,
-        ExportFormat::Jsonl => db.export_jsonl(writer, options)?,
-        ExportFormat::Csv => db.export_csv(writer, options)?,
+/// Commands in one `dcg coverage` input: a fixture directory, a shell
+/// history file, or a line-per-command log.
+fn coverage_corpus_commands(
+    path: &std::path::Path,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    use crate::onboard::{HistoryShell, parse_history};
+    use crate::simulate::{SimulateLimits, SimulateParser};
+
+    if path.is_dir() {
+        let cases = crate::packs::test_helpers::load_corpus_dir(path)?;
+        return Ok(cases.into_iter().map(|(_, _, case)| case.command).collect());
+    }
+    let is_history = path.file_name().is_some_and(|name| {
+        name.to_string_lossy()
+            .to_ascii_lowercase()
+            .ends_with("history")
+    });
+    if is_history {
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let content = String::from_utf8_lossy(&bytes);
+        return Ok(parse_history(HistoryShell::from_path(path), &content)
+            .into_iter()
+            .map(|(_, command)| command)
+            .collect());
+    }
+
+    let reader: Box<dyn std::io::Read> = if path.as_os_str() == "-" {
+        Box::new(std::io::stdin())
+    } else {
+        Box::new(
+            std::fs::File::open(path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?,
+        )
     };
-    Ok(count)
+    let (parsed, _) = SimulateParser::new(reader, SimulateLimits::default()).collect_commands()?;
+    Ok(parsed.into_iter().map(|parsed| parsed.command).collect())
 }
 
-#[allow(clippy::fn_params_excessive_bools, clippy::too_many_lines)]
-fn history_analyze(
-    db: &HistoryDb,
-    days: u64,
-    json: bool,
-    recommendations_only: bool,
-    false_positives: bool,
-    gaps: bool,
+/// Handle `dcg coverage`.
+fn handle_coverage_command(
+    config: &Config,
+    cmd: &CoverageCommand,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use colored::Colorize;
 
-    // Get enabled packs from config
-    let config = Config::load();
-    let enabled_pack_ids = config.enabled_pack_ids();
-    let enabled_packs: Vec<&str> = enabled_pack_ids.iter().map(String::as_str).collect();
+    let mut commands = Vec::new();
+    for path in &cmd.paths {
+        commands.extend(coverage_corpus_commands(path)?);
+    }
 
-    let analysis = db.analyze_pack_effectiveness(days, &enabled_packs)?;
+    let enabled = REGISTRY.expand_enabled(&config.enabled_pack_ids());
+    let packs: Vec<&Pack> = all_packs(config)
+        .into_iter()
+        .filter(|pack| !cmd.enabled_only || enabled.contains(&pack.id))
+        .collect();
+    let report = crate::coverage::analyze(
+        commands,
+        &packs,
+        &enabled,
+        &config.heredoc_settings(),
+        cmd.top,
+    );
 
-    if json {
-        let output = serde_json::to_string_pretty(&analysis)?;
-        println!("{output}");
+    if matches!(cmd.format, DoctorFormat::Json) {
+        println!("{}", serde_json::to_string_pretty(&report)?);
         return Ok(());
     }
 
-    // Pretty print output
     println!(
-        "\n{}",
-        "═══ Pack Effectiveness Analysis ═══".bright_cyan().bold()
+        "{} {} command(s), {} unique, against {} pack(s)",
+        "Rule coverage:".bold(),
+        report.total_commands,
+        report.unique_commands,
+        packs.len()
     );
     println!(
-        "Period: {} days | Commands analyzed: {}\n",
-        analysis.period_days,
-        analysis.total_commands.to_string().yellow()
+        "  Matched by a rule:   {} ({}%)",
+        report.matched_commands, report.matched_pct
+    );
+    println!(
+        "  Touched by no pack:  {} ({}%)",
+        report.untouched_commands, report.untouched_pct
     );
 
-    // Show recommendations (always unless specific view requested)
-    if !false_positives && !gaps || recommendations_only {
-        if analysis.recommendations.is_empty() {
-            println!("{}", "No recommendations at this time.".dimmed());
-        } else {
-            println!("{}", "📋 Recommendations:".bright_white().bold());
-            for rec in &analysis.recommendations {
-                let priority_indicator = match rec.priority {
-                    8..=10 => "🔴".to_string(),
-                    5..=7 => "🟡".to_string(),
-                    _ => "🟢".to_string(),
-                };
-                println!("  {} {}", priority_indicator, rec.description);
-                if let Some(action) = &rec.suggested_action {
-                    println!("     └─ {}", action.dimmed());
-                }
-            }
-            println!();
-        }
+    let (active, idle): (Vec<_>, Vec<_>) = report
+        .packs
+        .iter()
+        .partition(|pack| pack.touched > 0 || pack.matched > 0);
+    if !active.is_empty() {
+        println!();
+        println!("{}", "Packs".bold());
     }
-
-    // Show false positives (potentially aggressive patterns)
-    if false_positives || (!recommendations_only && !gaps) {
-        if analysis.potentially_aggressive.is_empty() {
-            println!(
-                "{}",
-                "✓ No patterns with high bypass rates detected.".green()
-            );
+    for pack in &active {
+        let state = if pack.enabled {
+            "enabled".green()
         } else {
+            "disabled".dimmed()
+        };
+        println!(
+            "  {} [{state}]: matched {} ({}%), touched {}, {}/{} rules fired",
+            pack.pack_id.bold(),
+            pack.matched,
+            pack.matched_pct,
+            pack.touched,
+            pack.fired.len(),
+            pack.rules_total
+        );
+        for rule in &pack.fired {
+            println!("      {:>6}  {}", rule.hits, rule.rule_id);
+        }
+        if !pack.never_fired.is_empty() && !pack.fired.is_empty() {
             println!(
-                "{}",
-                "⚠️  Potentially Aggressive Patterns (high bypass rate):"
-                    .yellow()
-                    .bold()
+                "      {}",
+                format!("never fired: {}", pack.never_fired.join(", ")).dimmed()
             );
-            for p in &analysis.potentially_aggressive {
-                println!(
-                    "  • {} ({}): {:.1}% bypass rate ({}/{} triggers)",
-                    p.pattern.bright_white(),
-                    p.pack_id.as_deref().unwrap_or("unknown").dimmed(),
-                    p.bypass_rate,
-                    p.bypassed_count,
-                    p.total_triggers
-                );
-            }
-            println!();
         }
     }
 
-    // Show coverage gaps
-    if gaps || (!recommendations_only && !false_positives) {
-        if analysis.potential_gaps.is_empty() {
-            println!("{}", "✓ No potential coverage gaps detected.".green());
-        } else {
-            println!(
-                "{}",
-                "⚠️  Potential Coverage Gaps (dangerous commands that were allowed):"
-                    .yellow()
-                    .bold()
-            );
-            for gap in analysis.potential_gaps.iter().take(10) {
-                let cmd_display = if gap.command.len() > 60 {
-                    format!("{}...", &gap.command[..57])
-                } else {
-                    gap.command.clone()
-                };
-                println!(
-                    "  • {} ({})",
-                    cmd_display.bright_white(),
-                    gap.reason.dimmed()
-                );
-            }
-            if analysis.potential_gaps.len() > 10 {
-                println!("  ... and {} more", analysis.potential_gaps.len() - 10);
-            }
-            println!();
-        }
+    let idle_enabled: Vec<&str> = idle
+        .iter()
+        .filter(|pack| pack.enabled)
+        .map(|pack| pack.pack_id.as_str())
+        .collect();
+    if !idle_enabled.is_empty() {
+        println!();
+        println!(
+            "{} {}",
+            "Enabled packs the corpus never touches:".bold(),
+            idle_enabled.join(", ")
+        );
     }
 
-    // Show high-value patterns summary
-    if !recommendations_only && !false_positives && !gaps {
-        if !analysis.high_value_patterns.is_empty() {
-            let total_blocked: u64 = analysis
-                .high_value_patterns
-                .iter()
-                .map(|p| p.denied_count)
-                .sum();
-            println!(
-                "{}",
-                format!(
-                    "✓ {} high-value patterns blocked {} commands with minimal false positives.",
-                    analysis.high_value_patterns.len(),
-                    total_blocked
-                )
-                .green()
-            );
-        }
-
-        // Show inactive packs
-        if !analysis.inactive_packs.is_empty() {
-            println!(
-                "\n{} Inactive packs (enabled but never triggered): {}",
-                "ℹ️ ".dimmed(),
-                analysis.inactive_packs.join(", ").dimmed()
-            );
+    if !report.untouched.is_empty() {
+        println!();
+        println!("{}", "Most frequent commands no pack looks at".bold());
+        for entry in &report.untouched {
+            println!("  {:>6}  {}", entry.count, entry.command);
         }
     }
-
     Ok(())
 }
 
-fn history_check(
-    db: &HistoryDb,
-    json: bool,
-    strict: bool,
+/// Output suggestions as JSON.
+fn output_suggestions_json(
+    suggestions: &[AllowlistSuggestion],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use colored::Colorize;
-
-    let result = db.check_health()?;
-
-    if json {
-        let output = serde_json::to_string_pretty(&result)?;
-        println!("{output}");
-    } else {
-        println!(
-            "\n{}",
-            "═══ History Database Health Check ═══".bright_cyan().bold()
-        );
+    #[derive(serde::Serialize)]
+    struct JsonSuggestion {
+        suggestion_id: String,
+        pattern: String,
+        frequency: usize,
+        unique_variants: usize,
+        confidence: String,
+        risk: String,
+        reason: String,
+        score: f32,
+        example_commands: Vec<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        path_patterns: Vec<String>,
+        suggest_path_specific: bool,
+        bypass_count: usize,
+    }
 
-        // Integrity status
-        let integrity_status = if result.integrity_ok {
-            "✓ PASSED".green()
-        } else {
-            "✗ FAILED".red()
-        };
-        println!(
-            "Integrity check: {} ({})",
-            integrity_status, result.integrity_check
-        );
+    let output: Vec<JsonSuggestion> = suggestions
+        .iter()
+        .map(|s| JsonSuggestion {
+            suggestion_id: s.suggestion_id.clone(),
+            pattern: s.cluster.proposed_pattern.clone(),
+            frequency: s.cluster.frequency,
+            unique_variants: s.cluster.unique_count,
+            confidence: s.confidence.as_str().to_string(),
+            risk: s.risk.as_str().to_string(),
+            reason: s.reason.as_str().to_string(),
+            score: s.score,
+            example_commands: s.cluster.commands.clone(),
+            path_patterns: s.path_patterns.iter().map(|p| p.pattern.clone()).collect(),
+            suggest_path_specific: s.suggest_path_specific,
+            bypass_count: s.bypass_count,
+        })
+        .collect();
 
-        // Foreign key check
-        if result.foreign_key_violations == 0 {
-            println!("Foreign keys: {} violations", "0".green());
-        } else {
-            println!(
-                "Foreign keys: {} violations",
-                result.foreign_key_violations.to_string().red()
-            );
-        }
+    let json = serde_json::to_string_pretty(&output)?;
+    println!("{json}");
+    Ok(())
+}
 
-        // FTS sync status
-        let fts_status = if result.fts_in_sync {
-            "✓ in sync".green()
-        } else {
-            "✗ out of sync".red()
-        };
-        println!(
-            "FTS index: {} ({} commands, {} FTS entries)",
-            fts_status, result.commands_count, result.fts_count
-        );
+/// Output suggestions as formatted text (non-interactive).
+fn output_suggestions_text(suggestions: &[AllowlistSuggestion]) {
+    println!("Allowlist Suggestions");
+    println!("=====================");
+    println!();
 
-        // Storage info
-        println!("\n{}", "Storage:".bright_white());
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        println!("[{}/{}] Suggestion", i + 1, suggestions.len());
+        println!("────────────────────────────────────────");
+        println!("Pattern: {}", suggestion.cluster.proposed_pattern);
+        println!("ID: {}", suggestion.suggestion_id);
         println!(
-            "  Database: {} ({} pages)",
-            format_size(result.file_size_bytes),
-            result.page_count
+            "Blocked: {} times ({} unique variants)",
+            suggestion.cluster.frequency, suggestion.cluster.unique_count
         );
-        println!("  WAL file: {}", format_size(result.wal_size_bytes));
         println!(
-            "  Free pages: {} ({} bytes)",
-            result.freelist_count,
-            result.freelist_count * u64::from(result.page_size)
+            "Confidence: {} | Risk: {} | Score: {:.2}",
+            suggestion.confidence, suggestion.risk, suggestion.score
         );
-
-        // Schema info
-        println!("\n{}", "Configuration:".bright_white());
-        println!("  Schema version: {}", result.schema_version);
-        println!("  Journal mode: {}", result.journal_mode);
-        println!("  Page size: {} bytes", result.page_size);
-    }
-
-    if strict && !result.integrity_ok {
-        std::process::exit(1);
+        println!("Reason: {}", suggestion.reason.description());
+        if suggestion.bypass_count > 0 {
+            println!("Bypassed: {} times", suggestion.bypass_count);
+        }
+        if !suggestion.path_patterns.is_empty() {
+            println!("Common paths:");
+            for pp in suggestion.path_patterns.iter().take(3) {
+                println!(
+                    "  • {} ({} occurrences{})",
+                    pp.pattern,
+                    pp.occurrence_count,
+                    if pp.is_project_dir {
+                        ", project dir"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        println!();
+        println!("Example commands:");
+        for cmd in suggestion.cluster.commands.iter().take(5) {
+            println!("  • {cmd}");
+        }
+        if suggestion.cluster.commands.len() > 5 {
+            println!("  ... and {} more", suggestion.cluster.commands.len() - 5);
+        }
+        println!();
     }
-
-    Ok(())
 }
 
-fn history_backup(
-    db: &HistoryDb,
-    output: &str,
-    compress: bool,
+/// Output suggestions interactively (prompting user for each).
+#[allow(clippy::too_many_lines)]
+fn output_suggestions_interactive(
+    suggestions: &[AllowlistSuggestion],
+    total_denied: usize,
+    db: Option<&HistoryDb>,
+    store: Option<&SuggestionHistoryStore>,
+    config: &Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use colored::Colorize;
-    use std::path::Path;
-
-    let output_path = Path::new(output);
-
-    // Add .gz extension if compressing and not already present
-    let has_gz_ext = output_path
-        .extension()
-        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
-    let final_path = if compress && !has_gz_ext {
-        output_path.with_extension(format!(
-            "{}.gz",
-            output_path
-                .extension()
-                .map(|e| e.to_string_lossy())
-                .unwrap_or_default()
-        ))
-    } else {
-        output_path.to_path_buf()
-    };
+    use std::io::{self, BufRead, Write};
 
-    println!("Creating backup...");
-    let result = db.backup(&final_path, compress)?;
+    println!("Analyzing {total_denied} denied commands...");
+    println!("Found {} potential allowlist patterns.", suggestions.len());
+    println!();
+    println!("For each suggestion, you can:");
+    println!("  [A]ccept - Record pattern (to add to allowlist)");
+    println!("  [S]kip   - Dismiss (won't be suggested again)");
+    println!("  [Q]uit   - Exit without more changes");
+    println!();
 
-    println!("\n{}", "═══ Backup Complete ═══".bright_cyan().bold());
-    println!("Output: {}", result.backup_path.bright_white());
-    println!(
-        "Size: {} {}",
-        format_size(result.backup_size_bytes),
-        if result.compressed {
-            "(compressed)"
-        } else {
-            ""
-        }
-    );
-    println!("Duration: {} ms", result.duration_ms);
-    if result.verified {
-        println!("Verification: {}", "✓ PASSED".green());
-    } else {
-        println!("Verification: {}", "skipped (compressed backup)".dimmed());
-    }
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let working_dir = std::env::current_dir()
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
 
-    Ok(())
-}
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        let cluster = &suggestion.cluster;
+        // Check for potential conflicts before displaying
+        let conflict_check = check_pattern_conflicts(&cluster.proposed_pattern, config);
 
-/// Format a byte size in human-readable format.
-#[allow(clippy::cast_precision_loss)]
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{bytes} bytes")
-    }
-}
-
-fn format_history_stats_pretty(stats: &HistoryStats) -> String {
-    use std::fmt::Write;
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!(" [{}/{}] Suggestion", i + 1, suggestions.len());
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!(" Pattern: {}", cluster.proposed_pattern);
+        println!(
+            " Blocked: {} times ({} unique variants)",
+            cluster.frequency, cluster.unique_count
+        );
 
-    let mut output = String::new();
-    let _ = writeln!(output, "History stats (last {} days)", stats.period_days);
-    let _ = writeln!(output, "Total commands: {}", stats.total_commands);
-    let _ = writeln!(
-        output,
-        "Outcomes: allow {} | deny {} | warn {} | bypass {}",
-        stats.outcomes.allowed,
-        stats.outcomes.denied,
-        stats.outcomes.warned,
-        stats.outcomes.bypassed
-    );
-    let _ = writeln!(output, "Block rate: {:.2}%", stats.block_rate * 100.0);
-    let _ = writeln!(
-        output,
-        "Performance (us): p50 {} | p95 {} | p99 {} | max {}",
-        stats.performance.p50_us,
-        stats.performance.p95_us,
-        stats.performance.p99_us,
-        stats.performance.max_us
-    );
+        // Display confidence, risk, and score
+        let confidence_color = match suggestion.confidence {
+            ConfidenceTier::High => "high".green(),
+            ConfidenceTier::Medium => "medium".yellow(),
+            ConfidenceTier::Low => "low".red(),
+        };
+        let risk_color = match suggestion.risk {
+            RiskLevel::Low => "low".green(),
+            RiskLevel::Medium => "medium".yellow(),
+            RiskLevel::High => "high".red(),
+        };
+        println!(
+            " Confidence: {} | Risk: {} | Score: {:.2}",
+            confidence_color, risk_color, suggestion.score
+        );
+        println!(" Reason: {}", suggestion.reason.description());
 
-    if !stats.top_patterns.is_empty() {
-        let _ = writeln!(output, "Top patterns:");
-        for pattern in &stats.top_patterns {
-            let _ = writeln!(
-                output,
-                "  - {} ({}{})",
-                pattern.name,
-                pattern.count,
-                pattern
-                    .pack_id
-                    .as_ref()
-                    .map_or_else(String::new, |pack| format!(", {pack}"))
+        // Show bypass information if available
+        if suggestion.bypass_count > 0 {
+            println!(
+                " {} Bypassed {} time(s) - user manually allowed this command",
+                "✓".green(),
+                suggestion.bypass_count
             );
         }
-    }
-
-    if !stats.top_projects.is_empty() {
-        let _ = writeln!(output, "Top projects:");
-        for project in &stats.top_projects {
-            let _ = writeln!(output, "  - {} ({})", project.path, project.command_count);
-        }
-    }
-
-    if !stats.agents.is_empty() {
-        let _ = writeln!(output, "Top agents:");
-        for agent in &stats.agents {
-            let _ = writeln!(output, "  - {} ({})", agent.name, agent.count);
-        }
-    }
 
-    if let Some(trends) = &stats.trends {
-        let _ = writeln!(
-            output,
-            "Trends: commands {:+.1}% | block rate {:+.2}pp",
-            trends.commands_change, trends.block_rate_change
-        );
-        if !trends.top_pattern_change.is_empty() {
-            let _ = writeln!(output, "Pattern shifts:");
-            for (name, delta) in &trends.top_pattern_change {
-                let _ = writeln!(output, "  - {name}: {delta:+}");
+        // Show path patterns if suggesting path-specific allowlisting
+        if !suggestion.path_patterns.is_empty() {
+            println!();
+            println!(" Common paths:");
+            for pp in suggestion.path_patterns.iter().take(3) {
+                let project_indicator = if pp.is_project_dir {
+                    " (project dir)".dimmed()
+                } else {
+                    "".normal()
+                };
+                println!(
+                    "   • {} ({} occurrences){}",
+                    pp.pattern, pp.occurrence_count, project_indicator
+                );
+            }
+            if suggestion.suggest_path_specific {
+                println!(
+                    "   {}",
+                    "→ Consider path-specific allowlisting for this pattern".cyan()
+                );
             }
         }
-    }
 
-    output
-}
-
-/// Compare two corpus outputs and return differences.
-fn diff_corpus_outputs(baseline: &CorpusOutput, current: &CorpusOutput) -> Vec<String> {
-    let mut diffs = Vec::new();
-
-    // Build lookup maps by ID
-    let baseline_map: std::collections::HashMap<_, _> =
-        baseline.cases.iter().map(|c| (c.id.as_str(), c)).collect();
-    let current_map: std::collections::HashMap<_, _> =
-        current.cases.iter().map(|c| (c.id.as_str(), c)).collect();
-
-    // Check for missing cases
-    for id in baseline_map.keys() {
-        if !current_map.contains_key(id) {
-            diffs.push(format!("REMOVED: {id}"));
+        // Display warnings if there are conflicts or the pattern is overly broad
+        if conflict_check.conflicts_with_blocks || conflict_check.is_overly_broad {
+            println!();
+            println!(" {}", "⚠ Warnings:".yellow());
+            if let Some(ref warning) = conflict_check.block_conflict_warning {
+                println!("   • {}", warning.yellow());
+            }
+            if conflict_check.is_overly_broad {
+                println!(
+                    "   • {}",
+                    "Pattern is overly broad (uses wildcards without anchors)".yellow()
+                );
+                if let Some(ref suggestion_text) = conflict_check.refinement_suggestion {
+                    println!("     {}", suggestion_text.dimmed());
+                }
+            }
         }
-    }
 
-    // Check for new cases
-    for id in current_map.keys() {
-        if !baseline_map.contains_key(id) {
-            diffs.push(format!("ADDED: {id}"));
+        println!();
+        println!(" Example commands:");
+        for cmd in cluster.commands.iter().take(5) {
+            println!("   • {cmd}");
         }
-    }
-
-    // Check for changed results
-    for (id, current_case) in &current_map {
-        if let Some(baseline_case) = baseline_map.get(id) {
-            if current_case.actual != baseline_case.actual {
-                diffs.push(format!(
-                    "CHANGED: {id} - decision: {} -> {}",
-                    baseline_case.actual, current_case.actual
-                ));
-            }
-            if current_case.actual_rule_id != baseline_case.actual_rule_id {
-                diffs.push(format!(
-                    "CHANGED: {id} - rule: {:?} -> {:?}",
-                    baseline_case.actual_rule_id, current_case.actual_rule_id
-                ));
-            }
+        if cluster.commands.len() > 5 {
+            println!("   ... and {} more", cluster.commands.len() - 5);
         }
-    }
+        println!();
 
-    // Sort diffs for deterministic output (HashMap iteration is non-deterministic)
-    diffs.sort();
+        // Prompt for action
+        print!(" [A]ccept  [S]kip  [Q]uit: ");
+        stdout.flush()?;
 
-    diffs
-}
+        let mut input = String::new();
+        stdin.lock().read_line(&mut input)?;
 
-/// Format corpus output for human-readable display.
-#[allow(clippy::too_many_lines)]
-fn format_corpus_pretty(output: &CorpusOutput) -> String {
-    use colored::Colorize;
-    use std::fmt::Write;
+        match input.trim().to_lowercase().as_str() {
+            "a" | "accept" => {
+                if let Some(store) = store {
+                    let _ = store.set_status(&suggestion.suggestion_id, SuggestionStatus::Accepted);
+                }
 
-    let mut result = String::new();
-    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+                // Log audit entry for accepted suggestion
+                if let Some(db) = db {
+                    let audit_entry = SuggestionAuditEntry {
+                        timestamp: Utc::now(),
+                        action: SuggestionAction::Accepted,
+                        pattern: cluster.proposed_pattern.clone(),
+                        final_pattern: None,
+                        risk_level: suggestion.risk.as_str().to_string(),
+                        risk_score: suggestion.risk.score(),
+                        confidence_tier: suggestion.confidence.as_str().to_string(),
+                        confidence_points: match suggestion.confidence {
+                            ConfidenceTier::High => 3,
+                            ConfidenceTier::Medium => 2,
+                            ConfidenceTier::Low => 1,
+                        },
+                        cluster_frequency: cluster.frequency,
+                        unique_variants: cluster.unique_count,
+                        sample_commands: serde_json::to_string(&cluster.commands)
+                            .unwrap_or_default(),
+                        rule_id: None,
+                        session_id: None,
+                        working_dir: working_dir.clone(),
+                    };
+                    if let Err(e) = db.log_suggestion_audit(&audit_entry) {
+                        eprintln!(" Warning: Could not log audit entry: {e}");
+                    }
+                }
 
-    // Header
-    let _ = writeln!(
-        result,
-        "{}\n",
-        if colorize {
-            "dcg corpus".green().bold().to_string()
-        } else {
-            "dcg corpus".to_string()
-        }
-    );
+                // Generate a descriptive reason from the suggestion
+                let reason = format!(
+                    "Auto-suggested ({} confidence, {} risk): {}",
+                    suggestion.confidence.as_str(),
+                    suggestion.risk.as_str(),
+                    suggestion.reason.description()
+                );
 
-    let _ = writeln!(result, "Corpus: {}", output.corpus_dir);
-    let _ = writeln!(result, "Version: {}", output.binary_version);
-    let _ = writeln!(result, "Generated: {}\n", output.generated_at);
-
-    // Summary
-    let _ = writeln!(
-        result,
-        "{}",
-        if colorize {
-            "=== Summary ===".blue().bold().to_string()
-        } else {
-            "=== Summary ===".to_string()
-        }
-    );
-
-    let _ = writeln!(
-        result,
-        "Total: {} ({} passed, {} failed)\n",
-        output.total_cases, output.total_passed, output.total_failed
-    );
-
-    // By category (sorted for deterministic output)
-    result.push_str("By Category:\n");
-    let mut categories: Vec<_> = output.summary.category.iter().collect();
-    categories.sort_by_key(|(k, _)| *k);
-    for (cat, stats) in categories {
-        let status = if stats.failed == 0 { "OK" } else { "FAIL" };
-        let status_str = if colorize {
-            if stats.failed == 0 {
-                status.green().to_string()
-            } else {
-                status.red().to_string()
+                // Write the pattern to the allowlist
+                match allowlist_add_pattern(
+                    &cluster.proposed_pattern,
+                    &reason,
+                    suggestion.confidence.as_str(),
+                    suggestion.risk.as_str(),
+                    cluster.frequency,
+                    cluster.unique_count,
+                ) {
+                    Ok(path) => {
+                        use colored::Colorize;
+                        println!(" {} Pattern added to allowlist", "✓".green());
+                        println!("   File: {}", path.display());
+                        println!();
+                    }
+                    Err(e) => {
+                        use colored::Colorize;
+                        // Check if it's a duplicate error (not a real failure)
+                        if e.to_string().contains("already exists") {
+                            println!(" {} Pattern already in allowlist", "ℹ".cyan());
+                        } else {
+                            eprintln!(" {} Could not write to allowlist: {e}", "✗".red());
+                            println!("   You can manually add it with:");
+                            println!(
+                                "   dcg allowlist add-pattern --pattern '{}' --reason '{}'",
+                                cluster.proposed_pattern, reason
+                            );
+                        }
+                        println!();
+                    }
+                }
             }
-        } else {
-            status.to_string()
-        };
-        let _ = writeln!(
-            result,
-            "  {}: {}/{} [{}]",
-            cat, stats.passed, stats.total, status_str
-        );
-    }
-    result.push('\n');
-
-    // By decision (sorted for deterministic output)
-    result.push_str("By Decision:\n");
-    let mut decisions: Vec<_> = output.summary.decision.iter().collect();
-    decisions.sort_by_key(|(k, _)| *k);
-    for (decision, count) in decisions {
-        let _ = writeln!(result, "  {decision}: {count}");
-    }
-    result.push('\n');
-
-    // By pack (top 10)
-    result.push_str("By Pack (top 10):\n");
-    let mut packs: Vec<_> = output.summary.pack.iter().collect();
-    packs.sort_by(|a, b| b.1.cmp(a.1));
-    for (pack, count) in packs.iter().take(10) {
-        let _ = writeln!(result, "  {pack}: {count}");
-    }
-    result.push('\n');
-
-    // Failed tests
-    let failures: Vec<_> = output.cases.iter().filter(|c| !c.passed).collect();
-    if !failures.is_empty() {
-        let _ = writeln!(
-            result,
-            "{}",
-            if colorize {
-                "=== Failures ===".red().bold().to_string()
-            } else {
-                "=== Failures ===".to_string()
+            "q" | "quit" => {
+                println!();
+                println!("Exiting. No changes made to allowlist.");
+                break;
             }
-        );
-
-        for case in failures {
-            let _ = writeln!(
-                result,
-                "  {} - {}",
-                if colorize {
-                    "FAIL".red().to_string()
-                } else {
-                    "FAIL".to_string()
-                },
-                case.description
-            );
-            let _ = writeln!(result, "    ID: {}", case.id);
-            let _ = writeln!(result, "    Command: {}", case.command);
-            let _ = writeln!(
-                result,
-                "    Expected: {}, Actual: {}",
-                case.expected, case.actual
-            );
-            if let Some(ref rule) = case.actual_rule_id {
-                let _ = writeln!(result, "    Rule: {rule}");
+            _ => {
+                // Skip by default - dismiss, and log as rejected for tracking
+                if let Some(store) = store {
+                    let _ =
+                        store.set_status(&suggestion.suggestion_id, SuggestionStatus::Dismissed);
+                }
+                if let Some(db) = db {
+                    let audit_entry = SuggestionAuditEntry {
+                        timestamp: Utc::now(),
+                        action: SuggestionAction::Rejected,
+                        pattern: cluster.proposed_pattern.clone(),
+                        final_pattern: None,
+                        risk_level: suggestion.risk.as_str().to_string(),
+                        risk_score: suggestion.risk.score(),
+                        confidence_tier: suggestion.confidence.as_str().to_string(),
+                        confidence_points: match suggestion.confidence {
+                            ConfidenceTier::High => 3,
+                            ConfidenceTier::Medium => 2,
+                            ConfidenceTier::Low => 1,
+                        },
+                        cluster_frequency: cluster.frequency,
+                        unique_variants: cluster.unique_count,
+                        sample_commands: serde_json::to_string(&cluster.commands)
+                            .unwrap_or_default(),
+                        rule_id: None,
+                        session_id: None,
+                        working_dir: working_dir.clone(),
+                    };
+                    // Best effort - don't warn on skip audit failures
+                    let _ = db.log_suggestion_audit(&audit_entry);
+                }
+                println!(" → Skipped");
+                println!();
             }
-            result.push('\n');
         }
     }
 
-    result
+    Ok(())
 }
 
-/// Check installation, configuration, and hook registration
-fn doctor(fix: bool, format: DoctorFormat) {
-    match format {
-        DoctorFormat::Pretty => {
-            #[cfg(feature = "rich-output")]
-            {
-                doctor_rich(fix);
-            }
-            #[cfg(not(feature = "rich-output"))]
-            {
-                doctor_pretty(fix);
-            }
+/// Handle the `dcg history` command.
+fn handle_history_command(
+    config: &Config,
+    action: HistoryAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = config.history.expanded_database_path();
+    let db = match HistoryDb::open(db_path) {
+        Ok(db) => db,
+        Err(err) => {
+            println!("Error opening history database: {err}");
+            return Ok(());
+        }
+    };
+
+    match action {
+        HistoryAction::Stats { days, trends, json } => {
+            history_stats(&db, days, trends, json)?;
+        }
+        HistoryAction::Prune {
+            older_than_days,
+            dry_run,
+            yes,
+        } => {
+            history_prune(&db, older_than_days, dry_run, yes)?;
+        }
+        HistoryAction::Export {
+            output,
+            format,
+            outcome,
+            since,
+            until,
+            limit,
+            compress,
+        } => {
+            history_export(&db, output, format, outcome, since, until, limit, compress)?;
+        }
+        HistoryAction::Analyze {
+            days,
+            json,
+            recommendations_only,
+            false_positives,
+            gaps,
+        } => {
+            history_analyze(&db, days, json, recommendations_only, false_positives, gaps)?;
+        }
+        HistoryAction::Check { json, strict } => {
+            history_check(&db, json, strict)?;
+        }
+        HistoryAction::Backup { output, compress } => {
+            history_backup(&db, &output, compress)?;
         }
-        DoctorFormat::Json => doctor_json(fix),
     }
+
+    Ok(())
 }
 
-/// Human-readable doctor output (colored crate, non-rich fallback).
-#[cfg(not(feature = "rich-output"))]
-#[allow(clippy::too_many_lines, clippy::unnecessary_unwrap)]
-fn doctor_pretty(fix: bool) {
-    use colored::Colorize;
+fn history_stats(
+    db: &HistoryDb,
+    days: u64,
+    trends: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = if trends {
+        db.compute_stats_with_trends(days)?
+    } else {
+        db.compute_stats(days)?
+    };
 
-    println!("{}", "dcg doctor".green().bold());
-    println!();
+    if json {
+        let output = serde_json::to_string_pretty(&stats)?;
+        println!("{output}");
+    } else {
+        let output = format_history_stats_pretty(&stats);
+        print!("{output}");
+    }
 
-    let mut issues = 0;
-    let mut fixed = 0;
+    Ok(())
+}
 
-    // Check 1: Binary in PATH
-    print!("Checking binary in PATH... ");
-    if which_dcg().is_some() {
-        println!("{}", "OK".green());
-    } else {
-        println!("{}", "NOT FOUND".red());
-        issues += 1;
-        println!("  dcg binary not found in PATH");
-        println!("  Run the install script or add to PATH manually");
+fn history_prune(
+    db: &HistoryDb,
+    older_than_days: u64,
+    dry_run: bool,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if older_than_days == 0 {
+        return Err("older-than-days must be at least 1".into());
     }
 
-    // Check 2: Claude Code settings file exists
-    print!("Checking Claude Code settings... ");
-    let settings_path = claude_settings_path();
-    if settings_path.exists() {
-        println!("{}", "OK".green());
+    if !dry_run && !yes {
+        println!("Refusing to prune without --yes or --dry-run.");
+        return Ok(());
+    }
+
+    let pruned = db.prune_older_than_days(older_than_days, dry_run)?;
+    if dry_run {
+        println!("Would prune {pruned} entries older than {older_than_days} days");
     } else {
-        println!("{}", "NOT FOUND".yellow());
-        println!("  ~/.claude/settings.json not found");
-        println!("  This is normal if Claude Code hasn't been configured yet");
+        println!("Pruned {pruned} entries older than {older_than_days} days");
     }
 
-    // Check 3: Hook wiring (expanded diagnostics)
-    print!("Checking hook wiring... ");
-    let hook_diag = diagnose_hook_wiring();
+    Ok(())
+}
 
-    if !hook_diag.settings_exists {
-        println!("{}", "SKIPPED".yellow());
-        println!("  No settings file to check");
-    } else if let Some(ref err) = hook_diag.settings_error {
-        println!("{}", "ERROR".red());
-        issues += 1;
-        println!("  {err}");
-        println!("  → Fix the settings.json file or reinstall Claude Code");
-    } else if hook_diag.dcg_hook_count == 0 {
-        println!("{}", "NOT REGISTERED".red());
-        issues += 1;
-        if fix {
-            println!("  Attempting to register hook...");
-            if install_hook(false).is_ok() {
-                println!("  {}", "Fixed!".green());
-                fixed += 1;
-            } else {
-                println!("  {}", "Failed to fix".red());
-            }
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::needless_pass_by_value)]
+fn history_export(
+    db: &HistoryDb,
+    output_path: Option<String>,
+    format: ExportFormat,
+    outcome: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    compress: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use chrono::DateTime;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+
+    // Parse outcome filter
+    let outcome_filter = outcome
+        .as_deref()
+        .map(|o| Outcome::parse(o).ok_or_else(|| format!("Invalid outcome: {o}")))
+        .transpose()?;
+
+    // Parse date/time filters
+    let since_dt = since
+        .as_deref()
+        .map(|s| {
+            DateTime::parse_from_rfc3339(s)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .map_err(|_| format!("Invalid since datetime: {s} (use ISO 8601 format)"))
+        })
+        .transpose()?;
+
+    let until_dt = until
+        .as_deref()
+        .map(|s| {
+            DateTime::parse_from_rfc3339(s)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .map_err(|_| format!("Invalid until datetime: {s} (use ISO 8601 format)"))
+        })
+        .transpose()?;
+
+    let options = ExportOptions {
+        outcome_filter,
+        since: since_dt,
+        until: until_dt,
+        limit,
+    };
+
+    // Create output writer
+    let count: usize;
+    if let Some(path) = output_path {
+        let file = File::create(&path)?;
+        if compress {
+            let encoder = GzEncoder::new(file, Compression::default());
+            let mut writer = BufWriter::new(encoder);
+            count = export_to_writer(db, &mut writer, format, &options)?;
+            writer.flush()?;
         } else {
-            println!("  → Run 'dcg install' to register the hook");
-        }
-    } else if hook_diag.dcg_hook_count > 1 {
-        println!("{}", "WARNING".yellow());
-        println!(
-            "  Found {} dcg hook entries (expected 1)",
-            hook_diag.dcg_hook_count
-        );
-        println!("  → Run 'dcg uninstall && dcg install' to fix duplicates");
-    } else if !hook_diag.wrong_matcher_hooks.is_empty() {
-        println!("{}", "MISCONFIGURED".red());
-        issues += 1;
-        println!(
-            "  Hook registered with wrong matcher: {:?}",
-            hook_diag.wrong_matcher_hooks
-        );
-        println!("  → dcg must be a Bash hook, not other tool types");
-        println!("  → Run 'dcg uninstall && dcg install' to fix");
-    } else if !hook_diag.missing_executable_hooks.is_empty() {
-        println!("{}", "BROKEN".red());
-        issues += 1;
-        for path in &hook_diag.missing_executable_hooks {
-            println!("  Hook points to missing executable: {path}");
+            let mut writer = BufWriter::new(file);
+            count = export_to_writer(db, &mut writer, format, &options)?;
+            writer.flush()?;
         }
-        println!("  → Run 'dcg uninstall && dcg install' to fix");
+        eprintln!("Exported {count} records to {path}");
     } else {
-        println!("{}", "OK".green());
+        let stdout = io::stdout();
+        let mut writer = stdout.lock();
+        count = export_to_writer(db, &mut writer, format, &options)?;
+        writer.flush()?;
+        eprintln!("Exported {count} records");
     }
 
-    // Check 4: Config validation (expanded diagnostics)
-    print!("Checking configuration... ");
-    let config_diag = validate_config_diagnostics();
+    Ok(())
+}
 
-    match &config_diag.config_path {
-        None => {
-            println!("{}", "USING DEFAULTS".yellow());
-            println!("  No config file found, using built-in defaults");
-            if fix {
-                let config_path = config_path();
-                if config_path.exists() {
-                    // File exists but wasn't loaded - could be empty, unreadable, or invalid
-                    println!(
-                        "  {} exists but wasn't loaded (check permissions/format)",
-                        config_path.display()
-                    );
-                    issues += 1;
-                } else {
-                    println!("  Creating default config...");
-                    if let Some(parent) = config_path.parent() {
-                        let _ = std::fs::create_dir_all(parent);
-                    }
-                    match std::fs::write(&config_path, Config::generate_sample_config()) {
-                        Ok(()) => {
-                            println!("  {} Created: {}", "Fixed!".green(), config_path.display());
-                            fixed += 1;
-                        }
-                        Err(e) => {
-                            println!("  {} Failed to create config: {e}", "Error".red());
-                        }
-                    }
-                }
-            } else {
-                println!("  → Run 'dcg init -o ~/.config/dcg/config.toml' to create one");
-            }
-        }
-        Some(path) if config_diag.parse_error.is_some() => {
-            println!("{}", "INVALID".red());
-            issues += 1;
-            println!("  Config: {}", path.display());
-            if let Some(ref err) = config_diag.parse_error {
-                println!("  {err}");
-            }
-            println!("  → Fix the TOML syntax error in your config file");
-        }
-        Some(path) => {
-            if config_diag.has_errors() || config_diag.has_warnings() {
-                println!("{}", "WARNING".yellow());
-                println!("  Config: {}", path.display());
-                if !config_diag.unknown_packs.is_empty() {
-                    println!("  Unknown pack IDs: {:?}", config_diag.unknown_packs);
-                    println!("  → Run 'dcg packs list' to see available packs");
-                }
-                if !config_diag.invalid_override_patterns.is_empty() {
-                    println!("  Invalid override patterns:");
-                    for (pattern, error) in &config_diag.invalid_override_patterns {
-                        println!("    - \"{pattern}\": {error}");
-                    }
-                    println!("  → Fix the regex patterns in [overrides] section");
-                }
-            } else {
-                println!("{} ({})", "OK".green(), path.display());
-            }
-        }
-    }
+fn export_to_writer<W: std::io::Write>(
+    db: &HistoryDb,
+    writer: &mut W,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let count = match format {
+        ExportFormat::Json => db.export_json(writer, options)?,
+        ExportFormat::Jsonl => db.export_jsonl(writer, options)?,
+        ExportFormat::Csv => db.export_csv(writer, options)?,
+    };
+    Ok(count)
+}
 
-    // Check 5: Pattern packs
-    print!("Checking pattern packs... ");
+#[allow(clippy::fn_params_excessive_bools, clippy::too_many_lines)]
+fn history_analyze(
+    db: &HistoryDb,
+    days: u64,
+    json: bool,
+    recommendations_only: bool,
+    false_positives: bool,
+    gaps: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
+
+    // Get enabled packs from config
     let config = Config::load();
-    let enabled = config.enabled_pack_ids();
-    println!("{} ({} enabled)", "OK".green(), enabled.len());
+    let enabled_pack_ids = config.enabled_pack_ids();
+    let enabled_packs: Vec<&str> = enabled_pack_ids.iter().map(String::as_str).collect();
 
-    // Check 6: Smoke test
-    print!("Running smoke test... ");
-    if run_smoke_test() {
-        println!("{}", "OK".green());
-    } else {
-        println!("{}", "FAILED".red());
-        issues += 1;
-        println!("  Evaluator smoke test failed");
-        println!("  → This may indicate a bug; please report it");
-    }
+    let analysis = db.analyze_pack_effectiveness(days, &enabled_packs)?;
 
-    // Check 7: Observe mode status
-    print!("Checking observe mode... ");
-    if let Some(observe_until) = config.policy().observe_until.as_ref() {
-        let now = chrono::Utc::now();
-        if let Some(until) = observe_until.parsed_utc() {
-            if &now < until {
-                // Observe window is active
-                let remaining = *until - now;
-                let days = remaining.num_days();
-                println!("{}", "ACTIVE".yellow());
-                println!(
-                    "  Observe mode enabled until: {}",
-                    until.format("%Y-%m-%d %H:%M UTC")
-                );
-                if days > 0 {
-                    println!("  {days} days remaining");
-                } else {
-                    let hours = remaining.num_hours();
-                    println!("  {hours} hours remaining");
+    if json {
+        let output = serde_json::to_string_pretty(&analysis)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    // Pretty print output
+    println!(
+        "\n{}",
+        "═══ Pack Effectiveness Analysis ═══".bright_cyan().bold()
+    );
+    println!(
+        "Period: {} days | Commands analyzed: {}\n",
+        analysis.period_days,
+        analysis.total_commands.to_string().yellow()
+    );
+
+    // Show recommendations (always unless specific view requested)
+    if !false_positives && !gaps || recommendations_only {
+        if analysis.recommendations.is_empty() {
+            println!("{}", "No recommendations at this time.".dimmed());
+        } else {
+            println!("{}", "📋 Recommendations:".bright_white().bold());
+            for rec in &analysis.recommendations {
+                let priority_indicator = match rec.priority {
+                    8..=10 => "🔴".to_string(),
+                    5..=7 => "🟡".to_string(),
+                    _ => "🟢".to_string(),
+                };
+                println!("  {} {}", priority_indicator, rec.description);
+                if let Some(action) = &rec.suggested_action {
+                    println!("     └─ {}", action.dimmed());
                 }
-                println!("  Non-critical rules are using WARN instead of DENY");
-                println!("  → This is expected during rollout");
-            } else {
-                // Observe window has expired
-                println!("{}", "EXPIRED".yellow().bold());
-                issues += 1;
-                println!(
-                    "  Observe mode expired: {}",
-                    until.format("%Y-%m-%d %H:%M UTC")
-                );
-                println!(
-                    "  {} DCG is now enforcing normal severity defaults",
-                    "→".bold()
-                );
-                println!("  To acknowledge and remove the expired setting:");
-                println!("    1. Edit your config file");
-                println!("    2. Remove or update the 'observe_until' line in [policy]");
-                println!();
-                println!("  Or to extend the observe window:");
-                println!(
-                    "    observe_until = \"{}\"",
-                    (now + chrono::Duration::days(30)).format("%Y-%m-%dT%H:%M:%SZ")
-                );
             }
+            println!();
+        }
+    }
+
+    // Show false positives (potentially aggressive patterns)
+    if false_positives || (!recommendations_only && !gaps) {
+        if analysis.potentially_aggressive.is_empty() {
+            println!(
+                "{}",
+                "✓ No patterns with high bypass rates detected.".green()
+            );
         } else {
-            // observe_until set but couldn't parse timestamp
-            println!("{}", "INVALID".red());
-            issues += 1;
             println!(
-                "  observe_until value could not be parsed: {}",
-                &**observe_until
+                "{}",
+                "⚠️  Potentially Aggressive Patterns (high bypass rate):"
+                    .yellow()
+                    .bold()
             );
-            println!("  → Use ISO 8601 format: YYYY-MM-DDTHH:MM:SSZ");
+            for p in &analysis.potentially_aggressive {
+                println!(
+                    "  • {} ({}): {:.1}% bypass rate ({}/{} triggers)",
+                    p.pattern.bright_white(),
+                    p.pack_id.as_deref().unwrap_or("unknown").dimmed(),
+                    p.bypass_rate,
+                    p.bypassed_count,
+                    p.total_triggers
+                );
+            }
+            println!();
         }
-    } else if let Some(mode) = config.policy().default_mode {
-        // No observe_until but default_mode is set (permanent warn/log mode)
-        if matches!(
-            mode,
-            crate::config::PolicyMode::Warn | crate::config::PolicyMode::Log
-        ) {
-            println!("{}", "PERMANENT".yellow());
-            println!("  policy.default_mode = {mode:?} (no expiration set)");
-            println!("  Non-critical rules will always use {mode:?} mode");
-            println!("  → Consider adding observe_until for time-limited rollout");
+    }
+
+    // Show coverage gaps
+    if gaps || (!recommendations_only && !false_positives) {
+        if analysis.potential_gaps.is_empty() {
+            println!("{}", "✓ No potential coverage gaps detected.".green());
         } else {
-            println!("{}", "OK".green());
-            println!("  Enforcing normal policy (default_mode = {mode:?})");
+            println!(
+                "{}",
+                "⚠️  Potential Coverage Gaps (dangerous commands that were allowed):"
+                    .yellow()
+                    .bold()
+            );
+            for gap in analysis.potential_gaps.iter().take(10) {
+                let cmd_display = if gap.command.len() > 60 {
+                    format!("{}...", &gap.command[..57])
+                } else {
+                    gap.command.clone()
+                };
+                println!(
+                    "  • {} ({})",
+                    cmd_display.bright_white(),
+                    gap.reason.dimmed()
+                );
+            }
+            if analysis.potential_gaps.len() > 10 {
+                println!("  ... and {} more", analysis.potential_gaps.len() - 10);
+            }
+            println!();
         }
-    } else {
-        println!("{}", "OK".green());
     }
 
-    // Check 8: Allowlist discovery + validation
-    print!("Checking allowlist entries... ");
-    let allowlist_diag = diagnose_allowlists();
-    if allowlist_diag.total_errors > 0 {
-        println!("{}", "INVALID".red());
-        issues += allowlist_diag.total_errors;
-        for msg in &allowlist_diag.error_messages {
-            println!("  {msg}");
+    // Show high-value patterns summary
+    if !recommendations_only && !false_positives && !gaps {
+        if !analysis.high_value_patterns.is_empty() {
+            let total_blocked: u64 = analysis
+                .high_value_patterns
+                .iter()
+                .map(|p| p.denied_count)
+                .sum();
+            println!(
+                "{}",
+                format!(
+                    "✓ {} high-value patterns blocked {} commands with minimal false positives.",
+                    analysis.high_value_patterns.len(),
+                    total_blocked
+                )
+                .green()
+            );
         }
-        println!("  → Run 'dcg allowlist validate' for details");
-    } else if allowlist_diag.total_warnings > 0 {
-        println!("{}", "WARNING".yellow());
-        for msg in &allowlist_diag.warning_messages {
-            println!("  {msg}");
+
+        // Show inactive packs
+        if !analysis.inactive_packs.is_empty() {
+            println!(
+                "\n{} Inactive packs (enabled but never triggered): {}",
+                "ℹ️ ".dimmed(),
+                analysis.inactive_packs.join(", ").dimmed()
+            );
         }
-        println!("  → Run 'dcg allowlist validate' for details");
-    } else if allowlist_diag.layers_found == 0 {
-        println!("{}", "NONE".yellow().dimmed());
-        println!("  No allowlist files found (project or user)");
-        println!("  → Use 'dcg allow <rule-id> -r \"reason\"' to create one");
-    } else {
-        println!(
-            "{} ({} layer{})",
-            "OK".green(),
-            allowlist_diag.layers_found,
-            if allowlist_diag.layers_found == 1 {
-                ""
-            } else {
-                "s"
-            }
-        );
     }
 
-    println!();
-    if issues == 0 {
-        println!("{}", "All checks passed!".green().bold());
-    } else if fix && fixed == issues {
-        println!("{}", "All issues fixed!".green().bold());
-    } else {
-        println!(
-            "{} issue(s) found{}",
-            issues.to_string().red().bold(),
-            if fix {
-                format!(", {fixed} fixed")
-            } else {
-                String::new()
-            }
-        );
-    }
+    Ok(())
 }
 
-const DOCTOR_SCHEMA_VERSION: u32 = 1;
+fn history_check(
+    db: &HistoryDb,
+    json: bool,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
 
-fn doctor_json(fix: bool) {
-    let report = collect_doctor_report(fix);
-    let json = serde_json::to_string_pretty(&report).expect("serialize doctor report");
-    println!("{json}");
-}
+    let result = db.check_health()?;
 
-/// Rich terminal doctor output using DcgConsole and markup.
-#[cfg(feature = "rich-output")]
-fn doctor_rich(fix: bool) {
-    use crate::output::console::console;
+    if json {
+        let output = serde_json::to_string_pretty(&result)?;
+        println!("{output}");
+    } else {
+        println!(
+            "\n{}",
+            "═══ History Database Health Check ═══".bright_cyan().bold()
+        );
 
-    let report = collect_doctor_report(fix);
-    let con = console();
+        // Integrity status
+        let integrity_status = if result.integrity_ok {
+            "✓ PASSED".green()
+        } else {
+            "✗ FAILED".red()
+        };
+        println!(
+            "Integrity check: {} ({})",
+            integrity_status, result.integrity_check
+        );
 
-    // Header
-    con.rule(Some("[bold green] dcg doctor [/]"));
-    con.print("");
+        // Foreign key check
+        if result.foreign_key_violations == 0 {
+            println!("Foreign keys: {} violations", "0".green());
+        } else {
+            println!(
+                "Foreign keys: {} violations",
+                result.foreign_key_violations.to_string().red()
+            );
+        }
 
-    // Render each check
-    for check in &report.checks {
-        let (icon, color) = match check.status {
-            DoctorCheckStatus::Ok => ("✓", "green"),
-            DoctorCheckStatus::Warning => ("⚠", "yellow"),
-            DoctorCheckStatus::Error => ("✗", "red"),
-            DoctorCheckStatus::Skipped => ("○", "dim"),
+        // FTS sync status
+        let fts_status = if result.fts_in_sync {
+            "✓ in sync".green()
+        } else {
+            "✗ out of sync".red()
         };
+        println!(
+            "FTS index: {} ({} commands, {} FTS entries)",
+            fts_status, result.commands_count, result.fts_count
+        );
 
-        // Status line with icon
-        con.print(&format!(
-            "[{color}]{icon}[/] [bold]{name}[/]: [{color}]{msg}[/]",
-            name = check.name,
-            msg = check.message
-        ));
-
-        // Remediation hint (indented)
-        if let Some(ref rem) = check.remediation {
-            con.print(&format!("  [dim]→ {rem}[/]"));
-        }
+        // Storage info
+        println!("\n{}", "Storage:".bright_white());
+        println!(
+            "  Database: {} ({} pages)",
+            format_size(result.file_size_bytes),
+            result.page_count
+        );
+        println!("  WAL file: {}", format_size(result.wal_size_bytes));
+        println!(
+            "  Free pages: {} ({} bytes)",
+            result.freelist_count,
+            result.freelist_count * u64::from(result.page_size)
+        );
 
-        // Fixed indicator
-        if check.fixed {
-            con.print("  [green bold]Fixed![/]");
-        }
+        // Schema info
+        println!("\n{}", "Configuration:".bright_white());
+        println!("  Schema version: {}", result.schema_version);
+        println!("  Journal mode: {}", result.journal_mode);
+        println!("  Page size: {} bytes", result.page_size);
     }
 
-    // Summary
-    con.print("");
-    if report.ok {
-        con.print("[green bold]All checks passed![/]");
-    } else if report.fixed > 0 && report.fixed == report.issues {
-        con.print("[green bold]All issues fixed![/]");
-    } else {
-        con.print(&format!(
-            "[red bold]{issues}[/] issue(s) found{fixed}",
-            issues = report.issues,
-            fixed = if report.fixed > 0 {
-                format!(", [green]{} fixed[/]", report.fixed)
-            } else {
-                String::new()
-            }
-        ));
+    if strict && !result.integrity_ok {
+        std::process::exit(1);
     }
+
+    Ok(())
 }
 
-#[allow(clippy::too_many_lines, clippy::option_if_let_else)]
-fn collect_doctor_report(fix: bool) -> DoctorReport {
-    let mut checks = Vec::new();
-    let mut issues = 0usize;
-    let mut fixed = 0usize;
+fn history_backup(
+    db: &HistoryDb,
+    output: &str,
+    compress: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
+    use std::path::Path;
 
-    // Check 1: Binary in PATH
-    let (status, message, remediation) = if which_dcg().is_some() {
-        (DoctorCheckStatus::Ok, "dcg found in PATH".to_string(), None)
-    } else {
-        issues += 1;
-        (
-            DoctorCheckStatus::Error,
-            "dcg binary not found in PATH".to_string(),
-            Some("Run the install script or add dcg to PATH".to_string()),
-        )
-    };
-    checks.push(DoctorCheck {
-        id: "binary_path",
-        name: "Binary in PATH",
-        status,
-        message,
-        remediation,
-        fixed: false,
-    });
+    let output_path = Path::new(output);
 
-    // Check 2: Claude settings file exists
-    let settings_path = claude_settings_path();
-    let (status, message) = if settings_path.exists() {
-        (
-            DoctorCheckStatus::Ok,
-            format!("settings.json found at {}", settings_path.display()),
-        )
+    // Add .gz extension if compressing and not already present
+    let has_gz_ext = output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+    let final_path = if compress && !has_gz_ext {
+        output_path.with_extension(format!(
+            "{}.gz",
+            output_path
+                .extension()
+                .map(|e| e.to_string_lossy())
+                .unwrap_or_default()
+        ))
     } else {
-        (
-            DoctorCheckStatus::Warning,
-            "settings.json not found (Claude Code not configured)".to_string(),
-        )
+        output_path.to_path_buf()
     };
-    checks.push(DoctorCheck {
-        id: "claude_settings",
-        name: "Claude Code settings file",
-        status,
-        message,
-        remediation: None,
-        fixed: false,
-    });
 
-    // Check 3: Hook wiring
-    let hook_diag = diagnose_hook_wiring();
-    let mut hook_fixed = false;
-    let (status, message, remediation) = if !hook_diag.settings_exists {
-        (
-            DoctorCheckStatus::Skipped,
-            "No settings file to check".to_string(),
-            None,
-        )
-    } else if let Some(ref err) = hook_diag.settings_error {
-        issues += 1;
-        (
-            DoctorCheckStatus::Error,
-            format!("Settings error: {err}"),
-            Some("Fix settings.json or reinstall Claude Code".to_string()),
-        )
-    } else if hook_diag.dcg_hook_count == 0 {
-        issues += 1;
-        if fix {
-            match install_hook_silent(false) {
-                Ok(true) => {
-                    fixed += 1;
-                    hook_fixed = true;
-                    (
-                        DoctorCheckStatus::Ok,
-                        "Hook registered successfully".to_string(),
-                        None,
-                    )
-                }
-                Ok(false) => (
-                    DoctorCheckStatus::Error,
-                    "Hook not registered (no changes made)".to_string(),
-                    Some("Run 'dcg install' to register the hook".to_string()),
-                ),
-                Err(e) => (
-                    DoctorCheckStatus::Error,
-                    format!("Failed to register hook: {e}"),
-                    Some("Run 'dcg install' to register the hook".to_string()),
-                ),
-            }
+    println!("Creating backup...");
+    let result = db.backup(&final_path, compress)?;
+
+    println!("\n{}", "═══ Backup Complete ═══".bright_cyan().bold());
+    println!("Output: {}", result.backup_path.bright_white());
+    println!(
+        "Size: {} {}",
+        format_size(result.backup_size_bytes),
+        if result.compressed {
+            "(compressed)"
         } else {
-            (
-                DoctorCheckStatus::Error,
-                "dcg hook not registered".to_string(),
-                Some("Run 'dcg install' to register the hook".to_string()),
-            )
+            ""
         }
-    } else if hook_diag.dcg_hook_count > 1 {
-        (
-            DoctorCheckStatus::Warning,
-            format!(
-                "Found {} dcg hook entries (expected 1)",
-                hook_diag.dcg_hook_count
-            ),
-            Some("Run 'dcg uninstall && dcg install' to fix duplicates".to_string()),
-        )
-    } else if !hook_diag.wrong_matcher_hooks.is_empty() {
-        issues += 1;
-        (
-            DoctorCheckStatus::Error,
-            format!(
-                "Hook registered with wrong matcher: {:?}",
-                hook_diag.wrong_matcher_hooks
-            ),
-            Some("dcg must be a Bash hook; reinstall to fix".to_string()),
-        )
-    } else if !hook_diag.missing_executable_hooks.is_empty() {
-        issues += 1;
-        (
-            DoctorCheckStatus::Error,
-            format!(
-                "Hook points to missing executable: {:?}",
-                hook_diag.missing_executable_hooks
-            ),
-            Some("Run 'dcg uninstall && dcg install' to fix".to_string()),
-        )
+    );
+    println!("Duration: {} ms", result.duration_ms);
+    if result.verified {
+        println!("Verification: {}", "✓ PASSED".green());
     } else {
-        (
-            DoctorCheckStatus::Ok,
-            "dcg hook registered".to_string(),
-            None,
-        )
-    };
-    checks.push(DoctorCheck {
-        id: "hook_wiring",
-        name: "Hook wiring",
-        status,
-        message,
-        remediation,
-        fixed: hook_fixed,
-    });
+        println!("Verification: {}", "skipped (compressed backup)".dimmed());
+    }
 
-    // Check 4: Config validation
-    let config_diag = validate_config_diagnostics();
-    let mut config_fixed = false;
-    let (status, message, remediation) = match &config_diag.config_path {
-        None => {
-            if fix {
-                let cfg_path = config_path();
-                if cfg_path.exists() {
-                    issues += 1;
-                    (
-                        DoctorCheckStatus::Error,
-                        format!("Config exists at {} but was not loaded", cfg_path.display()),
-                        Some("Check permissions and config syntax".to_string()),
-                    )
-                } else {
-                    match write_default_config() {
-                        Ok(path) => {
-                            fixed += 1;
-                            config_fixed = true;
-                            (
-                                DoctorCheckStatus::Ok,
-                                format!("Created default config at {}", path.display()),
-                                None,
-                            )
-                        }
-                        Err(e) => {
-                            issues += 1;
-                            (
-                                DoctorCheckStatus::Error,
-                                format!("Failed to create config: {e}"),
-                                Some("Create config with 'dcg init'".to_string()),
-                            )
-                        }
-                    }
-                }
-            } else {
-                (
-                    DoctorCheckStatus::Warning,
-                    "No config file found; using defaults".to_string(),
-                    Some("Run 'dcg init -o ~/.config/dcg/config.toml'".to_string()),
-                )
+    Ok(())
+}
+
+/// Format a byte size in human-readable format.
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+
+    if bytes >= GB {
+        format!("{:.2} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.2} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.2} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{bytes} bytes")
+    }
+}
+
+fn format_history_stats_pretty(stats: &HistoryStats) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    let _ = writeln!(output, "History stats (last {} days)", stats.period_days);
+    let _ = writeln!(output, "Total commands: {}", stats.total_commands);
+    let _ = writeln!(
+        output,
+        "Outcomes: allow {} | deny {} | warn {} | bypass {}",
+        stats.outcomes.allowed,
+        stats.outcomes.denied,
+        stats.outcomes.warned,
+        stats.outcomes.bypassed
+    );
+    let _ = writeln!(output, "Block rate: {:.2}%", stats.block_rate * 100.0);
+    let _ = writeln!(
+        output,
+        "Performance (us): p50 {} | p95 {} | p99 {} | max {}",
+        stats.performance.p50_us,
+        stats.performance.p95_us,
+        stats.performance.p99_us,
+        stats.performance.max_us
+    );
+
+    if !stats.top_patterns.is_empty() {
+        let _ = writeln!(output, "Top patterns:");
+        for pattern in &stats.top_patterns {
+            let _ = writeln!(
+                output,
+                "  - {} ({}{})",
+                pattern.name,
+                pattern.count,
+                pattern
+                    .pack_id
+                    .as_ref()
+                    .map_or_else(String::new, |pack| format!(", {pack}"))
+            );
+        }
+    }
+
+    if !stats.top_projects.is_empty() {
+        let _ = writeln!(output, "Top projects:");
+        for project in &stats.top_projects {
+            let _ = writeln!(output, "  - {} ({})", project.path, project.command_count);
+        }
+    }
+
+    if !stats.agents.is_empty() {
+        let _ = writeln!(output, "Top agents:");
+        for agent in &stats.agents {
+            let _ = writeln!(output, "  - {} ({})", agent.name, agent.count);
+        }
+    }
+
+    if let Some(trends) = &stats.trends {
+        let _ = writeln!(
+            output,
+            "Trends: commands {:+.1}% | block rate {:+.2}pp",
+            trends.commands_change, trends.block_rate_change
+        );
+        if !trends.top_pattern_change.is_empty() {
+            let _ = writeln!(output, "Pattern shifts:");
+            for (name, delta) in &trends.top_pattern_change {
+                let _ = writeln!(output, "  - {name}: {delta:+}");
             }
         }
-        Some(path) if config_diag.parse_error.is_some() => {
-            issues += 1;
-            (
-                DoctorCheckStatus::Error,
-                format!(
-                    "Invalid config at {}: {}",
-                    path.display(),
-                    config_diag.parse_error.as_deref().unwrap_or("parse error")
-                ),
-                Some("Fix the TOML syntax error in your config file".to_string()),
-            )
+    }
+
+    output
+}
+
+/// Compare two corpus outputs and return differences.
+fn diff_corpus_outputs(baseline: &CorpusOutput, current: &CorpusOutput) -> Vec<String> {
+    let mut diffs = Vec::new();
+
+    // Build lookup maps by ID
+    let baseline_map: std::collections::HashMap<_, _> =
+        baseline.cases.iter().map(|c| (c.id.as_str(), c)).collect();
+    let current_map: std::collections::HashMap<_, _> =
+        current.cases.iter().map(|c| (c.id.as_str(), c)).collect();
+
+    // Check for missing cases
+    for id in baseline_map.keys() {
+        if !current_map.contains_key(id) {
+            diffs.push(format!("REMOVED: {id}"));
         }
-        Some(path) => {
-            if config_diag.has_errors() || config_diag.has_warnings() {
-                let mut details = Vec::new();
-                if !config_diag.unknown_packs.is_empty() {
-                    details.push(format!("Unknown pack IDs: {:?}", config_diag.unknown_packs));
-                }
-                if !config_diag.invalid_override_patterns.is_empty() {
-                    details.push(format!(
-                        "Invalid override patterns: {}",
-                        config_diag.invalid_override_patterns.len()
-                    ));
-                }
-                (
-                    DoctorCheckStatus::Warning,
-                    format!(
-                        "Config warnings at {}: {}",
-                        path.display(),
-                        details.join("; ")
-                    ),
-                    Some("Run 'dcg packs list' and fix invalid overrides".to_string()),
-                )
-            } else {
-                (
-                    DoctorCheckStatus::Ok,
-                    format!("Config valid at {}", path.display()),
-                    None,
-                )
+    }
+
+    // Check for new cases
+    for id in current_map.keys() {
+        if !baseline_map.contains_key(id) {
+            diffs.push(format!("ADDED: {id}"));
+        }
+    }
+
+    // Check for changed results
+    for (id, current_case) in &current_map {
+        if let Some(baseline_case) = baseline_map.get(id) {
+            if current_case.actual != baseline_case.actual {
+                diffs.push(format!(
+                    "CHANGED: {id} - decision: {} -> {}",
+                    baseline_case.actual, current_case.actual
+                ));
+            }
+            if current_case.actual_rule_id != baseline_case.actual_rule_id {
+                diffs.push(format!(
+                    "CHANGED: {id} - rule: {:?} -> {:?}",
+                    baseline_case.actual_rule_id, current_case.actual_rule_id
+                ));
             }
         }
+    }
+
+    // Sort diffs for deterministic output (HashMap iteration is non-deterministic)
+    diffs.sort();
+
+    diffs
+}
+
+/// Format corpus output for human-readable display.
+#[allow(clippy::too_many_lines)]
+fn format_corpus_pretty(output: &CorpusOutput) -> String {
+    use colored::Colorize;
+    use std::fmt::Write;
+
+    let mut result = String::new();
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+
+    // Header
+    let _ = writeln!(
+        result,
+        "{}\n",
+        if colorize {
+            "dcg corpus".green().bold().to_string()
+        } else {
+            "dcg corpus".to_string()
+        }
+    );
+
+    let _ = writeln!(result, "Corpus: {}", output.corpus_dir);
+    let _ = writeln!(result, "Version: {}", output.binary_version);
+    let _ = writeln!(result, "Generated: {}\n", output.generated_at);
+
+    // Summary
+    let _ = writeln!(
+        result,
+        "{}",
+        if colorize {
+            "=== Summary ===".blue().bold().to_string()
+        } else {
+            "=== Summary ===".to_string()
+        }
+    );
+
+    let _ = writeln!(
+        result,
+        "Total: {} ({} passed, {} failed)\n",
+        output.total_cases, output.total_passed, output.total_failed
+    );
+
+    // By category (sorted for deterministic output)
+    result.push_str("By Category:\n");
+    let mut categories: Vec<_> = output.summary.category.iter().collect();
+    categories.sort_by_key(|(k, _)| *k);
+    for (cat, stats) in categories {
+        let status = if stats.failed == 0 { "OK" } else { "FAIL" };
+        let status_str = if colorize {
+            if stats.failed == 0 {
+                status.green().to_string()
+            } else {
+                status.red().to_string()
+            }
+        } else {
+            status.to_string()
+        };
+        let _ = writeln!(
+            result,
+            "  {}: {}/{} [{}]",
+            cat, stats.passed, stats.total, status_str
+        );
+    }
+    result.push('\n');
+
+    // By decision (sorted for deterministic output)
+    result.push_str("By Decision:\n");
+    let mut decisions: Vec<_> = output.summary.decision.iter().collect();
+    decisions.sort_by_key(|(k, _)| *k);
+    for (decision, count) in decisions {
+        let _ = writeln!(result, "  {decision}: {count}");
+    }
+    result.push('\n');
+
+    // By pack (top 10)
+    result.push_str("By Pack (top 10):\n");
+    let mut packs: Vec<_> = output.summary.pack.iter().collect();
+    packs.sort_by(|a, b| b.1.cmp(a.1));
+    for (pack, count) in packs.iter().take(10) {
+        let _ = writeln!(result, "  {pack}: {count}");
+    }
+    result.push('\n');
+
+    // Failed tests
+    let failures: Vec<_> = output.cases.iter().filter(|c| !c.passed).collect();
+    if !failures.is_empty() {
+        let _ = writeln!(
+            result,
+            "{}",
+            if colorize {
+                "=== Failures ===".red().bold().to_string()
+            } else {
+                "=== Failures ===".to_string()
+            }
+        );
+
+        for case in failures {
+            let _ = writeln!(
+                result,
+                "  {} - {}",
+                if colorize {
+                    "FAIL".red().to_string()
+                } else {
+                    "FAIL".to_string()
+                },
+                case.description
+            );
+            let _ = writeln!(result, "    ID: {}", case.id);
+            let _ = writeln!(result, "    Command: {}", case.command);
+            let _ = writeln!(
+                result,
+                "    Expected: {}, Actual: {}",
+                case.expected, case.actual
+            );
+            if let Some(ref rule) = case.actual_rule_id {
+                let _ = writeln!(result, "    Rule: {rule}");
+            }
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+/// Check installation, configuration, and hook registration
+fn doctor(fix: bool, format: DoctorFormat) {
+    match format {
+        DoctorFormat::Pretty => {
+            #[cfg(feature = "rich-output")]
+            {
+                doctor_rich(fix);
+            }
+            #[cfg(not(feature = "rich-output"))]
+            {
+                doctor_pretty(fix);
+            }
+        }
+        DoctorFormat::Json => doctor_json(fix),
+    }
+}
+
+/// Human-readable doctor output (colored crate, non-rich fallback).
+#[cfg(not(feature = "rich-output"))]
+#[allow(clippy::too_many_lines, clippy::unnecessary_unwrap)]
+fn doctor_pretty(fix: bool) {
+    use colored::Colorize;
+
+    println!("{}", "dcg doctor".green().bold());
+    println!();
+
+    let mut issues = 0;
+    let mut fixed = 0;
+
+    // Check 1: Binary in PATH
+    print!("Checking binary in PATH... ");
+    if which_dcg().is_some() {
+        println!("{}", "OK".green());
+    } else {
+        println!("{}", "NOT FOUND".red());
+        issues += 1;
+        println!("  dcg binary not found in PATH");
+        println!("  Run the install script or add to PATH manually");
+    }
+
+    // Check 2: Claude Code settings file exists
+    print!("Checking Claude Code settings... ");
+    let settings_path = claude_settings_path();
+    if settings_path.exists() {
+        println!("{}", "OK".green());
+    } else {
+        println!("{}", "NOT FOUND".yellow());
+        println!("  ~/.claude/settings.json not found");
+        println!("  This is normal if Claude Code hasn't been configured yet");
+    }
+
+    // Check 3: Hook wiring (expanded diagnostics)
+    print!("Checking hook wiring... ");
+    let hook_diag = diagnose_hook_wiring();
+
+    if !hook_diag.settings_exists {
+        println!("{}", "SKIPPED".yellow());
+        println!("  No settings file to check");
+    } else if let Some(ref err) = hook_diag.settings_error {
+        println!("{}", "ERROR".red());
+        issues += 1;
+        println!("  {err}");
+        println!("  → Fix the settings.json file or reinstall Claude Code");
+    } else if hook_diag.dcg_hook_count == 0 {
+        println!("{}", "NOT REGISTERED".red());
+        issues += 1;
+        if fix {
+            println!("  Attempting to register hook...");
+            if install_hook(false).is_ok() {
+                println!("  {}", "Fixed!".green());
+                fixed += 1;
+            } else {
+                println!("  {}", "Failed to fix".red());
+            }
+        } else {
+            println!("  → Run 'dcg install' to register the hook");
+        }
+    } else if hook_diag.dcg_hook_count > 1 {
+        println!("{}", "WARNING".yellow());
+        println!(
+            "  Found {} dcg hook entries (expected 1)",
+            hook_diag.dcg_hook_count
+        );
+        println!("  → Run 'dcg uninstall && dcg install' to fix duplicates");
+    } else if !hook_diag.wrong_matcher_hooks.is_empty() {
+        println!("{}", "MISCONFIGURED".red());
+        issues += 1;
+        println!(
+            "  Hook registered with wrong matcher: {:?}",
+            hook_diag.wrong_matcher_hooks
+        );
+        println!("  → dcg must be a Bash hook, not other tool types");
+        println!("  → Run 'dcg uninstall && dcg install' to fix");
+    } else if !hook_diag.missing_executable_hooks.is_empty() {
+        println!("{}", "BROKEN".red());
+        issues += 1;
+        for path in &hook_diag.missing_executable_hooks {
+            println!("  Hook points to missing executable: {path}");
+        }
+        println!("  → Run 'dcg uninstall && dcg install' to fix");
+    } else {
+        println!("{}", "OK".green());
+    }
+
+    // Check 4: Config validation (expanded diagnostics)
+    print!("Checking configuration... ");
+    let config_diag = validate_config_diagnostics();
+
+    match &config_diag.config_path {
+        None => {
+            println!("{}", "USING DEFAULTS".yellow());
+            println!("  No config file found, using built-in defaults");
+            if fix {
+                let config_path = config_path();
+                if config_path.exists() {
+                    // File exists but wasn't loaded - could be empty, unreadable, or invalid
+                    println!(
+                        "  {} exists but wasn't loaded (check permissions/format)",
+                        config_path.display()
+                    );
+                    issues += 1;
+                } else {
+                    println!("  Creating default config...");
+                    if let Some(parent) = config_path.parent() {
+                        let _ = std::fs::create_dir_all(parent);
+                    }
+                    match std::fs::write(&config_path, Config::generate_sample_config()) {
+                        Ok(()) => {
+                            record_policy_change("doctor --fix", &config_path, None);
+                            println!("  {} Created: {}", "Fixed!".green(), config_path.display());
+                            fixed += 1;
+                        }
+                        Err(e) => {
+                            println!("  {} Failed to create config: {e}", "Error".red());
+                        }
+                    }
+                }
+            } else {
+                println!("  → Run 'dcg init -o ~/.config/dcg/config.toml' to create one");
+            }
+        }
+        Some(path) if config_diag.parse_error.is_some() => {
+            println!("{}", "INVALID".red());
+            issues += 1;
+            println!("  Config: {}", path.display());
+            if let Some(ref err) = config_diag.parse_error {
+                println!("  {err}");
+            }
+            println!("  → Fix the TOML syntax error in your config file");
+        }
+        Some(path) => {
+            if config_diag.has_errors() || config_diag.has_warnings() {
+                println!("{}", "WARNING".yellow());
+                println!("  Config: {}", path.display());
+                if !config_diag.unknown_packs.is_empty() {
+                    println!("  Unknown pack IDs: {:?}", config_diag.unknown_packs);
+                    println!("  → Run 'dcg packs list' to see available packs");
+                }
+                if !config_diag.invalid_override_patterns.is_empty() {
+                    println!("  Invalid override patterns:");
+                    for (pattern, error) in &config_diag.invalid_override_patterns {
+                        println!("    - \"{pattern}\": {error}");
+                    }
+                    println!("  → Fix the regex patterns in [overrides] section");
+                }
+            } else {
+                println!("{} ({})", "OK".green(), path.display());
+            }
+        }
+    }
+
+    // Check 5: Pattern packs
+    print!("Checking pattern packs... ");
+    let config = Config::load();
+    let enabled = config.enabled_pack_ids();
+    let pin_warnings = config.pack_pin_warnings();
+    if pin_warnings.is_empty() {
+        println!("{} ({} enabled)", "OK".green(), enabled.len());
+    } else {
+        println!("{} ({} enabled)", "WARNING".yellow(), enabled.len());
+        for warning in &pin_warnings {
+            println!("  {warning}");
+        }
+        println!("  → Update the pins in packs.enabled after reviewing the pack changes");
+    }
+
+    // Check 5b: Config layers (includes that were skipped or served stale)
+    print!("Checking config includes... ");
+    let layer_warnings = config.load_warnings();
+    if layer_warnings.is_empty() {
+        println!("{}", "OK".green());
+    } else {
+        println!("{}", "WARNING".yellow());
+        for warning in &layer_warnings {
+            println!("  {warning}");
+        }
+        println!("  → Run 'dcg config show --effective' to see every layer");
+    }
+
+    // Check 5c: Canary webhook delivery
+    if config.canary.webhook_url.is_some() {
+        print!("Checking canary webhook... ");
+        if let Some(failure) =
+            crate::canary::last_webhook_failure(&crate::canary::webhook_failure_path())
+        {
+            println!("{}", "WARNING".yellow());
+            println!("  Last delivery failed: {failure}");
+            println!("  → Check canary.webhook_url and that the endpoint is reachable");
+        } else {
+            println!("{}", "OK".green());
+        }
+    }
+
+    // Check 6: Smoke test
+    print!("Running smoke test... ");
+    if run_smoke_test() {
+        println!("{}", "OK".green());
+    } else {
+        println!("{}", "FAILED".red());
+        issues += 1;
+        println!("  Evaluator smoke test failed");
+        println!("  → This may indicate a bug; please report it");
+    }
+
+    // Check 7: Observe mode status
+    print!("Checking observe mode... ");
+    if let Some(observe_until) = config.policy().observe_until.as_ref() {
+        let now = chrono::Utc::now();
+        if let Some(until) = observe_until.parsed_utc() {
+            if &now < until {
+                // Observe window is active
+                let remaining = *until - now;
+                let days = remaining.num_days();
+                println!("{}", "ACTIVE".yellow());
+                println!(
+                    "  Observe mode enabled until: {}",
+                    until.format("%Y-%m-%d %H:%M UTC")
+                );
+                if days > 0 {
+                    println!("  {days} days remaining");
+                } else {
+                    let hours = remaining.num_hours();
+                    println!("  {hours} hours remaining");
+                }
+                println!("  Non-critical rules are using WARN instead of DENY");
+                println!("  → This is expected during rollout");
+            } else {
+                // Observe window has expired
+                println!("{}", "EXPIRED".yellow().bold());
+                issues += 1;
+                println!(
+                    "  Observe mode expired: {}",
+                    until.format("%Y-%m-%d %H:%M UTC")
+                );
+                println!(
+                    "  {} DCG is now enforcing normal severity defaults",
+                    "→".bold()
+                );
+                println!("  To acknowledge and remove the expired setting:");
+                println!("    1. Edit your config file");
+                println!("    2. Remove or update the 'observe_until' line in [policy]");
+                println!();
+                println!("  Or to extend the observe window:");
+                println!(
+                    "    observe_until = \"{}\"",
+                    (now + chrono::Duration::days(30)).format("%Y-%m-%dT%H:%M:%SZ")
+                );
+            }
+        } else {
+            // observe_until set but couldn't parse timestamp
+            println!("{}", "INVALID".red());
+            issues += 1;
+            println!(
+                "  observe_until value could not be parsed: {}",
+                &**observe_until
+            );
+            println!("  → Use ISO 8601 format: YYYY-MM-DDTHH:MM:SSZ");
+        }
+    } else if let Some(mode) = config.policy().default_mode {
+        // No observe_until but default_mode is set (permanent warn/log mode)
+        if matches!(
+            mode,
+            crate::config::PolicyMode::Warn
+                | crate::config::PolicyMode::WarnOnce
+                | crate::config::PolicyMode::Log
+        ) {
+            println!("{}", "PERMANENT".yellow());
+            println!("  policy.default_mode = {mode:?} (no expiration set)");
+            println!("  Non-critical rules will always use {mode:?} mode");
+            println!("  → Consider adding observe_until for time-limited rollout");
+        } else {
+            println!("{}", "OK".green());
+            println!("  Enforcing normal policy (default_mode = {mode:?})");
+        }
+    } else {
+        println!("{}", "OK".green());
+    }
+
+    // Check 8: Allowlist discovery + validation
+    print!("Checking allowlist entries... ");
+    let allowlist_diag = diagnose_allowlists();
+    if allowlist_diag.total_errors > 0 {
+        println!("{}", "INVALID".red());
+        issues += allowlist_diag.total_errors;
+        for msg in &allowlist_diag.error_messages {
+            println!("  {msg}");
+        }
+        println!("  → Run 'dcg allowlist validate' for details");
+    } else if allowlist_diag.total_warnings > 0 {
+        println!("{}", "WARNING".yellow());
+        for msg in &allowlist_diag.warning_messages {
+            println!("  {msg}");
+        }
+        println!("  → Run 'dcg allowlist validate' for details");
+    } else if allowlist_diag.layers_found == 0 {
+        println!("{}", "NONE".yellow().dimmed());
+        println!("  No allowlist files found (project or user)");
+        println!("  → Use 'dcg allow <rule-id> -r \"reason\"' to create one");
+    } else {
+        println!(
+            "{} ({} layer{})",
+            "OK".green(),
+            allowlist_diag.layers_found,
+            if allowlist_diag.layers_found == 1 {
+                ""
+            } else {
+                "s"
+            }
+        );
+    }
+
+    // Check 8b: Allowlist usage accounting
+    print!("Checking allowlist usage accounting... ");
+    let usage_store = crate::allowlist_usage::AllowlistUsageStore::new(
+        crate::allowlist_usage::AllowlistUsageStore::default_path(None),
+    );
+    if let Some(failure) = usage_store.last_failure() {
+        println!("{}", "WARNING".yellow());
+        println!("  Last update failed: {failure}");
+        println!(
+            "  → Check that {} is writable",
+            usage_store.path().display()
+        );
+    } else {
+        println!("{}", "OK".green());
+    }
+
+    // Check 8c: State, cache and log paths
+    print!("Checking dcg paths... ");
+    let path_problems = dcg_path_problems(&config);
+    if path_problems.is_empty() {
+        println!("{}", "OK".green());
+    } else {
+        println!("{}", "WARNING".yellow());
+        for problem in &path_problems {
+            println!("  {problem}");
+        }
+        println!("  → Run 'dcg paths' for details");
+    }
+
+    // Check 9: Active environment for allowlist `environments` gating
+    print!("Checking allowlist environment... ");
+    println!(
+        "{} {}",
+        "OK".green(),
+        allowlist_environment_summary(&allowlist_diag)
+    );
+
+    println!();
+    if issues == 0 {
+        println!("{}", "All checks passed!".green().bold());
+    } else if fix && fixed == issues {
+        println!("{}", "All issues fixed!".green().bold());
+    } else {
+        println!(
+            "{} issue(s) found{}",
+            issues.to_string().red().bold(),
+            if fix {
+                format!(", {fixed} fixed")
+            } else {
+                String::new()
+            }
+        );
+    }
+}
+
+const DOCTOR_SCHEMA_VERSION: u32 = 1;
+
+fn doctor_json(fix: bool) {
+    let report = collect_doctor_report(fix);
+    let json = serde_json::to_string_pretty(&report).expect("serialize doctor report");
+    println!("{json}");
+}
+
+/// Rich terminal doctor output using DcgConsole and markup.
+#[cfg(feature = "rich-output")]
+fn doctor_rich(fix: bool) {
+    use crate::output::console::console;
+
+    let report = collect_doctor_report(fix);
+    let con = console();
+
+    // Header
+    con.rule(Some("[bold green] dcg doctor [/]"));
+    con.print("");
+
+    // Render each check
+    for check in &report.checks {
+        let (icon, color) = match check.status {
+            DoctorCheckStatus::Ok => ("✓", "green"),
+            DoctorCheckStatus::Warning => ("⚠", "yellow"),
+            DoctorCheckStatus::Error => ("✗", "red"),
+            DoctorCheckStatus::Skipped => ("○", "dim"),
+        };
+
+        // Status line with icon
+        con.print(&format!(
+            "[{color}]{icon}[/] [bold]{name}[/]: [{color}]{msg}[/]",
+            name = check.name,
+            msg = check.message
+        ));
+
+        // Remediation hint (indented)
+        if let Some(ref rem) = check.remediation {
+            con.print(&format!("  [dim]→ {rem}[/]"));
+        }
+
+        // Fixed indicator
+        if check.fixed {
+            con.print("  [green bold]Fixed![/]");
+        }
+    }
+
+    // Summary
+    con.print("");
+    if report.ok {
+        con.print("[green bold]All checks passed![/]");
+    } else if report.fixed > 0 && report.fixed == report.issues {
+        con.print("[green bold]All issues fixed![/]");
+    } else {
+        con.print(&format!(
+            "[red bold]{issues}[/] issue(s) found{fixed}",
+            issues = report.issues,
+            fixed = if report.fixed > 0 {
+                format!(", [green]{} fixed[/]", report.fixed)
+            } else {
+                String::new()
+            }
+        ));
+    }
+}
+
+#[allow(clippy::too_many_lines, clippy::option_if_let_else)]
+fn collect_doctor_report(fix: bool) -> DoctorReport {
+    let mut checks = Vec::new();
+    let mut issues = 0usize;
+    let mut fixed = 0usize;
+
+    // Check 1: Binary in PATH
+    let (status, message, remediation) = if which_dcg().is_some() {
+        (DoctorCheckStatus::Ok, "dcg found in PATH".to_string(), None)
+    } else {
+        issues += 1;
+        (
+            DoctorCheckStatus::Error,
+            "dcg binary not found in PATH".to_string(),
+            Some("Run the install script or add dcg to PATH".to_string()),
+        )
+    };
+    checks.push(DoctorCheck {
+        id: "binary_path",
+        name: "Binary in PATH",
+        status,
+        message,
+        remediation,
+        fixed: false,
+    });
+
+    // Check 2: Claude settings file exists
+    let settings_path = claude_settings_path();
+    let (status, message) = if settings_path.exists() {
+        (
+            DoctorCheckStatus::Ok,
+            format!("settings.json found at {}", settings_path.display()),
+        )
+    } else {
+        (
+            DoctorCheckStatus::Warning,
+            "settings.json not found (Claude Code not configured)".to_string(),
+        )
+    };
+    checks.push(DoctorCheck {
+        id: "claude_settings",
+        name: "Claude Code settings file",
+        status,
+        message,
+        remediation: None,
+        fixed: false,
+    });
+
+    // Check 3: Hook wiring
+    let hook_diag = diagnose_hook_wiring();
+    let mut hook_fixed = false;
+    let (status, message, remediation) = if !hook_diag.settings_exists {
+        (
+            DoctorCheckStatus::Skipped,
+            "No settings file to check".to_string(),
+            None,
+        )
+    } else if let Some(ref err) = hook_diag.settings_error {
+        issues += 1;
+        (
+            DoctorCheckStatus::Error,
+            format!("Settings error: {err}"),
+            Some("Fix settings.json or reinstall Claude Code".to_string()),
+        )
+    } else if hook_diag.dcg_hook_count == 0 {
+        issues += 1;
+        if fix {
+            match install_hook_silent(false) {
+                Ok(true) => {
+                    fixed += 1;
+                    hook_fixed = true;
+                    (
+                        DoctorCheckStatus::Ok,
+                        "Hook registered successfully".to_string(),
+                        None,
+                    )
+                }
+                Ok(false) => (
+                    DoctorCheckStatus::Error,
+                    "Hook not registered (no changes made)".to_string(),
+                    Some("Run 'dcg install' to register the hook".to_string()),
+                ),
+                Err(e) => (
+                    DoctorCheckStatus::Error,
+                    format!("Failed to register hook: {e}"),
+                    Some("Run 'dcg install' to register the hook".to_string()),
+                ),
+            }
+        } else {
+            (
+                DoctorCheckStatus::Error,
+                "dcg hook not registered".to_string(),
+                Some("Run 'dcg install' to register the hook".to_string()),
+            )
+        }
+    } else if hook_diag.dcg_hook_count > 1 {
+        (
+            DoctorCheckStatus::Warning,
+            format!(
+                "Found {} dcg hook entries (expected 1)",
+                hook_diag.dcg_hook_count
+            ),
+            Some("Run 'dcg uninstall && dcg install' to fix duplicates".to_string()),
+        )
+    } else if !hook_diag.wrong_matcher_hooks.is_empty() {
+        issues += 1;
+        (
+            DoctorCheckStatus::Error,
+            format!(
+                "Hook registered with wrong matcher: {:?}",
+                hook_diag.wrong_matcher_hooks
+            ),
+            Some("dcg must be a Bash hook; reinstall to fix".to_string()),
+        )
+    } else if !hook_diag.missing_executable_hooks.is_empty() {
+        issues += 1;
+        (
+            DoctorCheckStatus::Error,
+            format!(
+                "Hook points to missing executable: {:?}",
+                hook_diag.missing_executable_hooks
+            ),
+            Some("Run 'dcg uninstall && dcg install' to fix".to_string()),
+        )
+    } else {
+        (
+            DoctorCheckStatus::Ok,
+            "dcg hook registered".to_string(),
+            None,
+        )
+    };
+    checks.push(DoctorCheck {
+        id: "hook_wiring",
+        name: "Hook wiring",
+        status,
+        message,
+        remediation,
+        fixed: hook_fixed,
+    });
+
+    // Check 4: Config validation
+    let config_diag = validate_config_diagnostics();
+    let mut config_fixed = false;
+    let (status, message, remediation) = match &config_diag.config_path {
+        None => {
+            if fix {
+                let cfg_path = config_path();
+                if cfg_path.exists() {
+                    issues += 1;
+                    (
+                        DoctorCheckStatus::Error,
+                        format!("Config exists at {} but was not loaded", cfg_path.display()),
+                        Some("Check permissions and config syntax".to_string()),
+                    )
+                } else {
+                    match write_default_config() {
+                        Ok(path) => {
+                            fixed += 1;
+                            config_fixed = true;
+                            (
+                                DoctorCheckStatus::Ok,
+                                format!("Created default config at {}", path.display()),
+                                None,
+                            )
+                        }
+                        Err(e) => {
+                            issues += 1;
+                            (
+                                DoctorCheckStatus::Error,
+                                format!("Failed to create config: {e}"),
+                                Some("Create config with 'dcg init'".to_string()),
+                            )
+                        }
+                    }
+                }
+            } else {
+                (
+                    DoctorCheckStatus::Warning,
+                    "No config file found; using defaults".to_string(),
+                    Some("Run 'dcg init -o ~/.config/dcg/config.toml'".to_string()),
+                )
+            }
+        }
+        Some(path) if config_diag.parse_error.is_some() => {
+            issues += 1;
+            (
+                DoctorCheckStatus::Error,
+                format!(
+                    "Invalid config at {}: {}",
+                    path.display(),
+                    config_diag.parse_error.as_deref().unwrap_or("parse error")
+                ),
+                Some("Fix the TOML syntax error in your config file".to_string()),
+            )
+        }
+        Some(path) => {
+            if config_diag.has_errors() || config_diag.has_warnings() {
+                let mut details = Vec::new();
+                if !config_diag.unknown_packs.is_empty() {
+                    details.push(format!("Unknown pack IDs: {:?}", config_diag.unknown_packs));
+                }
+                if !config_diag.invalid_override_patterns.is_empty() {
+                    details.push(format!(
+                        "Invalid override patterns: {}",
+                        config_diag.invalid_override_patterns.len()
+                    ));
+                }
+                (
+                    DoctorCheckStatus::Warning,
+                    format!(
+                        "Config warnings at {}: {}",
+                        path.display(),
+                        details.join("; ")
+                    ),
+                    Some("Run 'dcg packs list' and fix invalid overrides".to_string()),
+                )
+            } else {
+                (
+                    DoctorCheckStatus::Ok,
+                    format!("Config valid at {}", path.display()),
+                    None,
+                )
+            }
+        }
+    };
+    checks.push(DoctorCheck {
+        id: "config",
+        name: "Configuration",
+        status,
+        message,
+        remediation,
+        fixed: config_fixed,
+    });
+
+    // Check 5: Pattern packs
+    let config = Config::load();
+    let enabled = config.enabled_pack_ids();
+    let pin_warnings = config.pack_pin_warnings();
+    checks.push(if pin_warnings.is_empty() {
+        DoctorCheck {
+            id: "packs",
+            name: "Pattern packs",
+            status: DoctorCheckStatus::Ok,
+            message: format!("{} packs enabled", enabled.len()),
+            remediation: None,
+            fixed: false,
+        }
+    } else {
+        DoctorCheck {
+            id: "packs",
+            name: "Pattern packs",
+            status: DoctorCheckStatus::Warning,
+            message: pin_warnings.join("; "),
+            remediation: Some(
+                "Update the pins in packs.enabled after reviewing the pack changes".to_string(),
+            ),
+            fixed: false,
+        }
+    });
+
+    // Check 5b: Config layers (includes that were skipped or served stale)
+    let layer_warnings = config.load_warnings();
+    checks.push(if layer_warnings.is_empty() {
+        DoctorCheck {
+            id: "config_includes",
+            name: "Config includes",
+            status: DoctorCheckStatus::Ok,
+            message: "All config layers applied".to_string(),
+            remediation: None,
+            fixed: false,
+        }
+    } else {
+        DoctorCheck {
+            id: "config_includes",
+            name: "Config includes",
+            status: DoctorCheckStatus::Warning,
+            message: layer_warnings.join("; "),
+            remediation: Some("Run 'dcg config show --effective' to see every layer".to_string()),
+            fixed: false,
+        }
+    });
+
+    // Check 5c: Canary webhook delivery
+    if config.canary.webhook_url.is_some() {
+        let failure = crate::canary::last_webhook_failure(&crate::canary::webhook_failure_path());
+        checks.push(match failure {
+            Some(failure) => DoctorCheck {
+                id: "canary_webhook",
+                name: "Canary webhook",
+                status: DoctorCheckStatus::Warning,
+                message: format!("Last delivery failed: {failure}"),
+                remediation: Some(
+                    "Check canary.webhook_url and that the endpoint is reachable".to_string(),
+                ),
+                fixed: false,
+            },
+            None => DoctorCheck {
+                id: "canary_webhook",
+                name: "Canary webhook",
+                status: DoctorCheckStatus::Ok,
+                message: "No failed deliveries".to_string(),
+                remediation: None,
+                fixed: false,
+            },
+        });
+    }
+
+    // Check 5d: Denial message template
+    if config.messages.denial_template.is_some() {
+        let unknown = config.messages.unknown_template_variables();
+        checks.push(if unknown.is_empty() {
+            DoctorCheck {
+                id: "denial_template",
+                name: "Denial template",
+                status: DoctorCheckStatus::Ok,
+                message: "messages.denial_template uses only known variables".to_string(),
+                remediation: None,
+                fixed: false,
+            }
+        } else {
+            DoctorCheck {
+                id: "denial_template",
+                name: "Denial template",
+                status: DoctorCheckStatus::Warning,
+                message: format!(
+                    "Unknown variable(s) in messages.denial_template: {}",
+                    unknown
+                        .iter()
+                        .map(|name| format!("{{{name}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                remediation: Some(format!(
+                    "Use only {}",
+                    crate::config::MessagesConfig::TEMPLATE_VARIABLES
+                        .iter()
+                        .map(|name| format!("{{{name}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                fixed: false,
+            }
+        });
+    }
+
+    // Check 6: Smoke test
+    if run_smoke_test() {
+        checks.push(DoctorCheck {
+            id: "smoke_test",
+            name: "Evaluator smoke test",
+            status: DoctorCheckStatus::Ok,
+            message: "Evaluator smoke test passed".to_string(),
+            remediation: None,
+            fixed: false,
+        });
+    } else {
+        issues += 1;
+        checks.push(DoctorCheck {
+            id: "smoke_test",
+            name: "Evaluator smoke test",
+            status: DoctorCheckStatus::Error,
+            message: "Evaluator smoke test failed".to_string(),
+            remediation: Some("Report a bug with the failing command".to_string()),
+            fixed: false,
+        });
+    }
+
+    // Check 7: Observe mode status
+    let (status, message, remediation) =
+        if let Some(observe_until) = config.policy().observe_until.as_ref() {
+            let now = chrono::Utc::now();
+            if let Some(until) = observe_until.parsed_utc() {
+                if now < *until {
+                    (
+                        DoctorCheckStatus::Warning,
+                        format!(
+                            "Observe mode active until {}",
+                            until.format("%Y-%m-%d %H:%M UTC")
+                        ),
+                        None,
+                    )
+                } else {
+                    issues += 1;
+                    (
+                        DoctorCheckStatus::Error,
+                        format!(
+                            "Observe mode expired at {}",
+                            until.format("%Y-%m-%d %H:%M UTC")
+                        ),
+                        Some("Remove or update observe_until in [policy]".to_string()),
+                    )
+                }
+            } else {
+                issues += 1;
+                let raw: &str = observe_until;
+                (
+                    DoctorCheckStatus::Error,
+                    format!("observe_until value could not be parsed: {raw}"),
+                    Some("Use ISO 8601 format: YYYY-MM-DDTHH:MM:SSZ".to_string()),
+                )
+            }
+        } else if let Some(mode) = config.policy().default_mode {
+            if matches!(
+                mode,
+                crate::config::PolicyMode::Warn
+                    | crate::config::PolicyMode::WarnOnce
+                    | crate::config::PolicyMode::Log
+            ) {
+                (
+                    DoctorCheckStatus::Warning,
+                    format!("policy.default_mode = {mode:?} (no expiration)"),
+                    Some("Consider adding observe_until for time-limited rollout".to_string()),
+                )
+            } else {
+                (
+                    DoctorCheckStatus::Ok,
+                    format!("Enforcing normal policy (default_mode = {mode:?})"),
+                    None,
+                )
+            }
+        } else {
+            (
+                DoctorCheckStatus::Ok,
+                "Observe mode disabled".to_string(),
+                None,
+            )
+        };
+    checks.push(DoctorCheck {
+        id: "observe_mode",
+        name: "Observe mode",
+        status,
+        message,
+        remediation,
+        fixed: false,
+    });
+
+    // Check 8: Allowlist discovery + validation
+    let allowlist_diag = diagnose_allowlists();
+    let (status, message, remediation) = if allowlist_diag.total_errors > 0 {
+        issues += allowlist_diag.total_errors;
+        (
+            DoctorCheckStatus::Error,
+            format!(
+                "Allowlist errors: {}",
+                allowlist_diag.error_messages.join("; ")
+            ),
+            Some("Run 'dcg allowlist validate' for details".to_string()),
+        )
+    } else if allowlist_diag.total_warnings > 0 {
+        (
+            DoctorCheckStatus::Warning,
+            format!(
+                "Allowlist warnings: {}",
+                allowlist_diag.warning_messages.join("; ")
+            ),
+            Some("Run 'dcg allowlist validate' for details".to_string()),
+        )
+    } else if allowlist_diag.layers_found == 0 {
+        (
+            DoctorCheckStatus::Warning,
+            "No allowlist files found (project or user)".to_string(),
+            Some("Use 'dcg allow <rule-id> -r \"reason\"' to create one".to_string()),
+        )
+    } else {
+        (
+            DoctorCheckStatus::Ok,
+            format!("Allowlist layers found: {}", allowlist_diag.layers_found),
+            None,
+        )
+    };
+    checks.push(DoctorCheck {
+        id: "allowlists",
+        name: "Allowlists",
+        status,
+        message,
+        remediation,
+        fixed: false,
+    });
+
+    // Check 8b: Allowlist usage accounting
+    let usage_store = crate::allowlist_usage::AllowlistUsageStore::new(
+        crate::allowlist_usage::AllowlistUsageStore::default_path(None),
+    );
+    checks.push(match usage_store.last_failure() {
+        Some(failure) => DoctorCheck {
+            id: "allowlist_usage",
+            name: "Allowlist usage accounting",
+            status: DoctorCheckStatus::Warning,
+            message: format!("Last update failed: {failure}"),
+            remediation: Some(format!(
+                "Check that {} is writable",
+                usage_store.path().display()
+            )),
+            fixed: false,
+        },
+        None => DoctorCheck {
+            id: "allowlist_usage",
+            name: "Allowlist usage accounting",
+            status: DoctorCheckStatus::Ok,
+            message: "Usage counters are being recorded".to_string(),
+            remediation: None,
+            fixed: false,
+        },
+    });
+
+    // Check 8c: State, cache and log paths
+    let path_problems = dcg_path_problems(&Config::load());
+    checks.push(DoctorCheck {
+        id: "paths",
+        name: "dcg paths",
+        status: if path_problems.is_empty() {
+            DoctorCheckStatus::Ok
+        } else {
+            DoctorCheckStatus::Warning
+        },
+        message: if path_problems.is_empty() {
+            "All config, state and cache paths are usable".to_string()
+        } else {
+            path_problems.join("; ")
+        },
+        remediation: (!path_problems.is_empty()).then(|| "Run 'dcg paths' for details".to_string()),
+        fixed: false,
+    });
+
+    // Check 9: Active environment for allowlist `environments` gating
+    checks.push(DoctorCheck {
+        id: "allowlist_environment",
+        name: "Allowlist environment",
+        status: DoctorCheckStatus::Ok,
+        message: format!(
+            "Active environment: {}",
+            allowlist_environment_summary(&allowlist_diag)
+        ),
+        remediation: None,
+        fixed: false,
+    });
+
+    DoctorReport {
+        schema_version: DOCTOR_SCHEMA_VERSION,
+        checks,
+        issues,
+        fixed,
+        ok: issues == 0 || (fix && fixed == issues),
+    }
+}
+
+fn is_dcg_command(cmd: &str) -> bool {
+    cmd == "dcg" || cmd.ends_with("/dcg")
+}
+
+fn is_dcg_hook_entry(entry: &serde_json::Value) -> bool {
+    entry
+        .get("matcher")
+        .and_then(|m| m.as_str())
+        .is_some_and(|m| m == "Bash")
+        && entry
+            .get("hooks")
+            .and_then(|h| h.as_array())
+            .is_some_and(|hooks| {
+                hooks.iter().any(|hook| {
+                    hook.get("command")
+                        .and_then(|c| c.as_str())
+                        .is_some_and(is_dcg_command)
+                })
+            })
+}
+
+/// Install the dcg hook entry into Claude Code settings without printing.
+fn install_hook_silent(force: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    let settings_path = claude_settings_path();
+
+    let mut settings: serde_json::Value = if settings_path.exists() {
+        let content = std::fs::read_to_string(&settings_path)?;
+        serde_json::from_str(&content)?
+    } else {
+        if let Some(parent) = settings_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        serde_json::json!({})
+    };
+
+    let changed = install_dcg_hook_into_settings(&mut settings, force)?;
+    if changed {
+        let content = serde_json::to_string_pretty(&settings)?;
+        std::fs::write(&settings_path, content)?;
+    }
+    Ok(changed)
+}
+
+/// Create the default config file at the standard path.
+fn write_default_config() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let config_path = config_path();
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&config_path, Config::generate_sample_config())?;
+    Ok(config_path)
+}
+
+/// Install the dcg hook entry into an in-memory Claude settings JSON value.
+///
+/// Returns `Ok(true)` when a new hook entry was added, `Ok(false)` when an
+/// existing hook was detected and `force == false`.
+///
+/// # Errors
+///
+/// Returns an error if the settings JSON is not in the expected format:
+/// - root must be an object
+/// - `hooks` must be an object (if present)
+/// - `hooks.PreToolUse` must be an array (if present)
+fn install_dcg_hook_into_settings(
+    settings: &mut serde_json::Value,
+    force: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    // Build the hook configuration.
+    let hook_config = serde_json::json!({
+        "matcher": "Bash",
+        "hooks": [{
+            "type": "command",
+            "command": "dcg"
+        }]
+    });
+
+    let settings_obj = settings
+        .as_object_mut()
+        .ok_or("Invalid settings format (expected JSON object)")?;
+
+    let hooks_value = settings_obj
+        .entry("hooks")
+        .or_insert_with(|| serde_json::json!({}));
+
+    let hooks_obj = hooks_value
+        .as_object_mut()
+        .ok_or("Invalid hooks format (expected JSON object)")?;
+
+    let pre_tool_use_value = hooks_obj
+        .entry("PreToolUse")
+        .or_insert_with(|| serde_json::json!([]));
+
+    let pre_tool_use = pre_tool_use_value
+        .as_array_mut()
+        .ok_or("Invalid PreToolUse hooks format (expected JSON array)")?;
+
+    let already_installed = pre_tool_use.iter().any(is_dcg_hook_entry);
+    if already_installed && !force {
+        return Ok(false);
+    }
+
+    if force {
+        pre_tool_use.retain(|h| !is_dcg_hook_entry(h));
+    }
+
+    pre_tool_use.push(hook_config);
+    Ok(true)
+}
+
+/// Remove the dcg hook entry from an in-memory Claude settings JSON value.
+///
+/// Returns `Ok(true)` when at least one entry was removed, `Ok(false)` when no
+/// dcg hook entry existed.
+///
+/// # Errors
+///
+/// Returns an error if `hooks.PreToolUse` exists but is not an array.
+fn uninstall_dcg_hook_from_settings(
+    settings: &mut serde_json::Value,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some(hooks) = settings.get_mut("hooks") else {
+        return Ok(false);
+    };
+    let Some(pre_tool_use) = hooks.get_mut("PreToolUse") else {
+        return Ok(false);
+    };
+
+    let Some(arr) = pre_tool_use.as_array_mut() else {
+        return Err("Invalid PreToolUse hooks format (expected JSON array)".into());
+    };
+
+    let before = arr.len();
+    arr.retain(|h| !is_dcg_hook_entry(h));
+    Ok(arr.len() < before)
+}
+
+/// Install the dcg hook entry into Claude Code settings.
+///
+/// This is a wrapper around `install_dcg_hook_into_settings` that handles the
+/// file I/O and error reporting.
+///
+/// # Errors
+///
+/// Returns an error if the settings file cannot be read, parsed, or written.
+fn install_hook(force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
+
+    let settings_path = claude_settings_path();
+
+    // Read existing settings or create new
+    let mut settings: serde_json::Value = if settings_path.exists() {
+        let content = std::fs::read_to_string(&settings_path)?;
+        serde_json::from_str(&content)?
+    } else {
+        // Create parent directory if needed
+        if let Some(parent) = settings_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        serde_json::json!({})
+    };
+
+    let changed = install_dcg_hook_into_settings(&mut settings, force)?;
+    if !changed {
+        println!("{}", "Hook already installed!".yellow());
+        println!("Use --force to reinstall");
+        return Ok(());
+    }
+
+    // Write back
+    let content = serde_json::to_string_pretty(&settings)?;
+    std::fs::write(&settings_path, content)?;
+
+    println!("{}", "Hook installed successfully!".green().bold());
+    println!("Settings updated: {}", settings_path.display());
+    println!();
+    println!(
+        "{}",
+        "Restart Claude Code for the changes to take effect.".yellow()
+    );
+
+    Ok(())
+}
+
+/// Remove the dcg hook entry from Claude Code settings.
+///
+/// This is a wrapper around `uninstall_dcg_hook_from_settings` that handles the
+/// file I/O and error reporting.
+///
+/// # Errors
+///
+/// Returns an error if the settings file cannot be read, parsed, or written.
+fn uninstall_hook(purge: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
+
+    let settings_path = claude_settings_path();
+
+    if !settings_path.exists() {
+        println!("{}", "No Claude Code settings found.".yellow());
+        return Ok(());
+    }
+
+    // Read existing settings
+    let content = std::fs::read_to_string(&settings_path)?;
+    let mut settings: serde_json::Value = serde_json::from_str(&content)?;
+
+    // Remove dcg hooks (fail if settings structure is unexpected).
+    let removed = uninstall_dcg_hook_from_settings(&mut settings)?;
+
+    if removed {
+        // Write back
+        let content = serde_json::to_string_pretty(&settings)?;
+        std::fs::write(&settings_path, content)?;
+        println!("{}", "Hook removed successfully!".green().bold());
+    } else {
+        println!("{}", "No dcg hook found in settings.".yellow());
+    }
+
+    // Purge config files if requested
+    if purge {
+        let config_dir = config_dir();
+        if config_dir.exists() {
+            std::fs::remove_dir_all(&config_dir)?;
+            println!("Removed configuration directory: {}", config_dir.display());
+        }
+    }
+
+    println!();
+    println!(
+        "{}",
+        "Restart Claude Code for the changes to take effect.".yellow()
+    );
+
+    Ok(())
+}
+
+/// Update dcg by re-running the platform installer.
+fn self_update(update: UpdateCommand) -> Result<(), Box<dyn std::error::Error>> {
+    // Handle --list-versions flag: show available backup versions
+    if update.list_versions {
+        return handle_list_versions();
+    }
+
+    // Handle --rollback flag: restore a previous version
+    if let Some(ref version) = update.rollback {
+        return handle_rollback(version.as_deref());
+    }
+
+    // Handle --check flag: just check for updates without installing
+    if update.check {
+        return handle_version_check(update.refresh, update.format);
+    }
+
+    if cfg!(windows) {
+        return self_update_windows(update);
+    }
+
+    self_update_unix(update)
+}
+
+/// Handle --list-versions flag: display available backup versions.
+fn handle_list_versions() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::update::{format_backup_list, list_backups};
+
+    let backups = list_backups().map_err(|e| format!("Failed to list backups: {e}"))?;
+
+    let use_color = std::io::IsTerminal::is_terminal(&std::io::stdout());
+    print!("{}", format_backup_list(&backups, use_color));
+
+    Ok(())
+}
+
+/// Handle --rollback flag: restore a previous version.
+fn handle_rollback(target_version: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::update::rollback;
+
+    eprintln!("Rolling back dcg...");
+
+    match rollback(target_version) {
+        Ok(message) => {
+            println!("{message}");
+            println!("\nRestart dcg to use the restored version.");
+            Ok(())
+        }
+        Err(e) => Err(format!("Rollback failed: {e}").into()),
+    }
+}
+
+/// Perform update using native Rust `self_update` crate.
+///
+/// Note: Native update is not yet implemented. Use installer flags instead:
+/// `dcg update --system` or `dcg update --from-source`
+/// Check for updates and display the result.
+fn handle_version_check(
+    force_refresh: bool,
+    format: UpdateFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::update::{check_for_update, format_check_result, format_check_result_json};
+
+    if !matches!(format, UpdateFormat::Json) {
+        eprintln!("Checking for updates...");
+    }
+
+    match check_for_update(force_refresh) {
+        Ok(result) => match format {
+            UpdateFormat::Pretty => {
+                // Detect if stdout is a TTY for color output
+                let use_color = std::io::IsTerminal::is_terminal(&std::io::stdout());
+                print!("{}", format_check_result(&result, use_color));
+            }
+            UpdateFormat::Json => {
+                let json = format_check_result_json(&result)
+                    .map_err(|e| format!("Failed to format JSON: {e}"))?;
+                println!("{json}");
+            }
+        },
+        Err(e) => return Err(format!("Failed to check for updates: {e}").into()),
+    }
+    Ok(())
+}
+
+fn self_update_unix(update: UpdateCommand) -> Result<(), Box<dyn std::error::Error>> {
+    let script_url = "https://raw.githubusercontent.com/Dicklesworthstone/destructive_command_guard/master/install.sh";
+    let mut args: Vec<String> = Vec::new();
+
+    if let Some(version) = update.version {
+        args.push("--version".to_string());
+        args.push(version);
+    }
+    if update.system {
+        args.push("--system".to_string());
+    }
+    if update.easy_mode {
+        args.push("--easy-mode".to_string());
+    }
+    if let Some(dest) = update.dest {
+        args.push("--dest".to_string());
+        args.push(dest.to_string_lossy().into_owned());
+    }
+    if update.from_source {
+        args.push("--from-source".to_string());
+    }
+    if update.verify {
+        args.push("--verify".to_string());
+    }
+    if update.quiet {
+        args.push("--quiet".to_string());
+    }
+    if update.no_gum {
+        args.push("--no-gum".to_string());
+    }
+    if update.force {
+        args.push("--force".to_string());
+    }
+
+    let mut escaped_args = String::new();
+    for (idx, arg) in args.iter().enumerate() {
+        if idx > 0 {
+            escaped_args.push(' ');
+        }
+        escaped_args.push_str(&shell_escape_posix(arg));
+    }
+
+    let command = if escaped_args.is_empty() {
+        format!("curl -fsSL {} | bash -s --", shell_escape_posix(script_url))
+    } else {
+        format!(
+            "curl -fsSL {} | bash -s -- {}",
+            shell_escape_posix(script_url),
+            escaped_args
+        )
+    };
+
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("Installer failed with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn self_update_windows(update: UpdateCommand) -> Result<(), Box<dyn std::error::Error>> {
+    if update.system || update.from_source || update.quiet || update.no_gum || update.force {
+        return Err(
+            "Windows updater supports only --version, --dest, --easy-mode, and --verify.".into(),
+        );
+    }
+
+    let script_url = "https://raw.githubusercontent.com/Dicklesworthstone/destructive_command_guard/master/install.ps1";
+    let mut args: Vec<String> = Vec::new();
+
+    if let Some(version) = update.version {
+        args.push(format!("-Version {}", shell_escape_powershell(&version)));
+    }
+    if let Some(dest) = update.dest {
+        args.push(format!(
+            "-Dest {}",
+            shell_escape_powershell(&dest.to_string_lossy())
+        ));
+    }
+    if update.easy_mode {
+        args.push("-EasyMode".to_string());
+    }
+    if update.verify {
+        args.push("-Verify".to_string());
+    }
+
+    let args_str = if args.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", args.join(" "))
+    };
+
+    let command = format!(
+        "$ErrorActionPreference='Stop'; \
+$url={url}; \
+$tmp=Join-Path $env:TEMP 'dcg-install.ps1'; \
+Invoke-WebRequest -Uri $url -OutFile $tmp; \
+& $tmp{args}; \
+$code=$LASTEXITCODE; \
+Remove-Item $tmp -ErrorAction SilentlyContinue; \
+exit $code;",
+        url = shell_escape_powershell(script_url),
+        args = args_str
+    );
+
+    let status = std::process::Command::new("powershell")
+        .arg("-NoProfile")
+        .arg("-ExecutionPolicy")
+        .arg("Bypass")
+        .arg("-Command")
+        .arg(command)
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("Installer failed with status {status}").into());
+    }
+
+    Ok(())
+}
+
+fn shell_escape_posix(value: &str) -> String {
+    if value.is_empty() {
+        return "''".to_string();
+    }
+    let mut escaped = String::from("'");
+    for ch in value.chars() {
+        if ch == '\'' {
+            escaped.push_str("'\\''");
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped.push('\'');
+    escaped
+}
+
+fn shell_escape_powershell(value: &str) -> String {
+    let mut escaped = String::from("'");
+    for ch in value.chars() {
+        if ch == '\'' {
+            escaped.push_str("''");
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped.push('\'');
+    escaped
+}
+
+/// Get the path to Claude Code settings
+fn claude_settings_path() -> std::path::PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".claude")
+        .join("settings.json")
+}
+
+/// Get the path to dcg config directory.
+///
+/// Prefers `$XDG_CONFIG_HOME/dcg/`, then XDG-style `~/.config/dcg/` if it exists,
+/// otherwise falls back to the platform-native location. This ensures users can
+/// use `~/.config/dcg/` on all platforms, including macOS where
+/// `dirs::config_dir()` returns `~/Library/Application Support`.
+fn config_dir() -> std::path::PathBuf {
+    // Check XDG_CONFIG_HOME first (if set)
+    if let Ok(xdg_home) = std::env::var("XDG_CONFIG_HOME") {
+        if let Some(xdg_home) = crate::config::resolve_config_path_value(&xdg_home, None) {
+            return xdg_home.join("dcg");
+        }
+    }
+
+    // Check XDG-style path next (~/.config/dcg/)
+    if let Some(home) = dirs::home_dir() {
+        let xdg_dir = home.join(".config").join("dcg");
+        if xdg_dir.exists() {
+            return xdg_dir;
+        }
+    }
+
+    // Fall back to platform-native or default to ~/.config/dcg
+    dirs::config_dir()
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"))
+        .join("dcg")
+}
+
+/// Get the path to dcg config file
+fn config_path() -> std::path::PathBuf {
+    // Prefer an existing config file in the same order as config loading.
+    if let Ok(xdg_home) = std::env::var("XDG_CONFIG_HOME") {
+        if let Some(xdg_home) = crate::config::resolve_config_path_value(&xdg_home, None) {
+            let path = xdg_home.join("dcg").join("config.toml");
+            if path.exists() {
+                return path;
+            }
+        }
+    }
+
+    if let Some(home) = dirs::home_dir() {
+        let path = home.join(".config").join("dcg").join("config.toml");
+        if path.exists() {
+            return path;
+        }
+    }
+
+    if let Some(config_dir) = dirs::config_dir() {
+        let path = config_dir.join("dcg").join("config.toml");
+        if path.exists() {
+            return path;
+        }
+    }
+
+    config_dir().join("config.toml")
+}
+
+/// Check if dcg is in PATH
+fn which_dcg() -> Option<std::path::PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths).find_map(|dir| {
+            let path = dir.join("dcg");
+            if path.is_file() { Some(path) } else { None }
+        })
+    })
+}
+
+/// Check if the hook is registered in Claude Code settings
+#[allow(dead_code)]
+fn check_hook_registered() -> Result<bool, Box<dyn std::error::Error>> {
+    let settings_path = claude_settings_path();
+    if !settings_path.exists() {
+        return Ok(false);
+    }
+
+    let content = std::fs::read_to_string(&settings_path)?;
+    let settings: serde_json::Value = serde_json::from_str(&content)?;
+
+    let registered = settings
+        .get("hooks")
+        .and_then(|h| h.get("PreToolUse"))
+        .and_then(|arr| arr.as_array())
+        .is_some_and(|a| a.iter().any(is_dcg_hook_entry));
+
+    Ok(registered)
+}
+
+// ============================================================================
+// Doctor: expanded diagnostics (git_safety_guard-1gt.7.1)
+// NOTE: These diagnostic types are scaffolding for future `dcg doctor` enhancements.
+// ============================================================================
+
+/// Detailed hook wiring diagnostics.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+struct HookDiagnostics {
+    /// Settings file exists
+    settings_exists: bool,
+    /// Settings JSON is valid
+    settings_valid: bool,
+    /// Error message if settings invalid
+    settings_error: Option<String>,
+    /// Number of dcg hook entries found
+    dcg_hook_count: usize,
+    /// Dcg hooks found with wrong matcher (not "Bash")
+    wrong_matcher_hooks: Vec<String>,
+    /// Dcg hooks pointing to absolute path that doesn't exist
+    missing_executable_hooks: Vec<String>,
+    /// Other non-dcg hooks in `PreToolUse`
+    other_hooks_count: usize,
+}
+
+#[allow(dead_code)]
+impl HookDiagnostics {
+    fn is_healthy(&self) -> bool {
+        self.settings_valid
+            && self.dcg_hook_count == 1
+            && self.wrong_matcher_hooks.is_empty()
+            && self.missing_executable_hooks.is_empty()
+    }
+
+    fn has_issues(&self) -> bool {
+        !self.settings_valid
+            || self.dcg_hook_count == 0
+            || self.dcg_hook_count > 1
+            || !self.wrong_matcher_hooks.is_empty()
+            || !self.missing_executable_hooks.is_empty()
+    }
+}
+
+/// Diagnose hook wiring in detail.
+#[allow(dead_code)]
+fn diagnose_hook_wiring() -> HookDiagnostics {
+    let mut diag = HookDiagnostics::default();
+    let settings_path = claude_settings_path();
+
+    if !settings_path.exists() {
+        return diag;
+    }
+    diag.settings_exists = true;
+
+    // Read and parse settings
+    let content = match std::fs::read_to_string(&settings_path) {
+        Ok(c) => c,
+        Err(e) => {
+            diag.settings_error = Some(format!("Failed to read settings: {e}"));
+            return diag;
+        }
+    };
+
+    let settings: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(s) => s,
+        Err(e) => {
+            diag.settings_error = Some(format!("Invalid JSON: {e}"));
+            return diag;
+        }
+    };
+    diag.settings_valid = true;
+
+    // Check hooks structure
+    let Some(hooks) = settings.get("hooks") else {
+        return diag;
+    };
+    let Some(pre_tool_use) = hooks.get("PreToolUse") else {
+        return diag;
+    };
+    let Some(entries) = pre_tool_use.as_array() else {
+        diag.settings_error = Some("hooks.PreToolUse is not an array".to_string());
+        diag.settings_valid = false;
+        return diag;
     };
-    checks.push(DoctorCheck {
-        id: "config",
-        name: "Configuration",
-        status,
-        message,
-        remediation,
-        fixed: config_fixed,
-    });
 
-    // Check 5: Pattern packs
-    let config = Config::load();
-    let enabled = config.enabled_pack_ids();
-    checks.push(DoctorCheck {
-        id: "packs",
-        name: "Pattern packs",
-        status: DoctorCheckStatus::Ok,
-        message: format!("{} packs enabled", enabled.len()),
-        remediation: None,
-        fixed: false,
-    });
+    // Analyze each entry
+    for entry in entries {
+        let matcher = entry.get("matcher").and_then(|m| m.as_str());
+        let hooks_arr = entry.get("hooks").and_then(|h| h.as_array());
 
-    // Check 6: Smoke test
-    if run_smoke_test() {
-        checks.push(DoctorCheck {
-            id: "smoke_test",
-            name: "Evaluator smoke test",
-            status: DoctorCheckStatus::Ok,
-            message: "Evaluator smoke test passed".to_string(),
-            remediation: None,
-            fixed: false,
-        });
-    } else {
-        issues += 1;
-        checks.push(DoctorCheck {
-            id: "smoke_test",
-            name: "Evaluator smoke test",
-            status: DoctorCheckStatus::Error,
-            message: "Evaluator smoke test failed".to_string(),
-            remediation: Some("Report a bug with the failing command".to_string()),
-            fixed: false,
-        });
-    }
+        let Some(hooks_arr) = hooks_arr else {
+            continue;
+        };
 
-    // Check 7: Observe mode status
-    let (status, message, remediation) =
-        if let Some(observe_until) = config.policy().observe_until.as_ref() {
-            let now = chrono::Utc::now();
-            if let Some(until) = observe_until.parsed_utc() {
-                if now < *until {
-                    (
-                        DoctorCheckStatus::Warning,
-                        format!(
-                            "Observe mode active until {}",
-                            until.format("%Y-%m-%d %H:%M UTC")
-                        ),
-                        None,
-                    )
+        for hook in hooks_arr {
+            let cmd = hook.get("command").and_then(|c| c.as_str());
+            if let Some(cmd) = cmd {
+                if is_dcg_command(cmd) {
+                    diag.dcg_hook_count += 1;
+
+                    // Check matcher
+                    if matcher != Some("Bash") {
+                        diag.wrong_matcher_hooks
+                            .push(matcher.unwrap_or("(none)").to_string());
+                    }
+
+                    // Check if absolute path exists
+                    if cmd.starts_with('/') && !std::path::Path::new(cmd).exists() {
+                        diag.missing_executable_hooks.push(cmd.to_string());
+                    }
                 } else {
-                    issues += 1;
-                    (
-                        DoctorCheckStatus::Error,
-                        format!(
-                            "Observe mode expired at {}",
-                            until.format("%Y-%m-%d %H:%M UTC")
-                        ),
-                        Some("Remove or update observe_until in [policy]".to_string()),
-                    )
+                    diag.other_hooks_count += 1;
                 }
-            } else {
-                issues += 1;
-                let raw: &str = observe_until;
-                (
-                    DoctorCheckStatus::Error,
-                    format!("observe_until value could not be parsed: {raw}"),
-                    Some("Use ISO 8601 format: YYYY-MM-DDTHH:MM:SSZ".to_string()),
-                )
-            }
-        } else if let Some(mode) = config.policy().default_mode {
-            if matches!(
-                mode,
-                crate::config::PolicyMode::Warn | crate::config::PolicyMode::Log
-            ) {
-                (
-                    DoctorCheckStatus::Warning,
-                    format!("policy.default_mode = {mode:?} (no expiration)"),
-                    Some("Consider adding observe_until for time-limited rollout".to_string()),
-                )
-            } else {
-                (
-                    DoctorCheckStatus::Ok,
-                    format!("Enforcing normal policy (default_mode = {mode:?})"),
-                    None,
-                )
             }
-        } else {
-            (
-                DoctorCheckStatus::Ok,
-                "Observe mode disabled".to_string(),
-                None,
-            )
-        };
-    checks.push(DoctorCheck {
-        id: "observe_mode",
-        name: "Observe mode",
-        status,
-        message,
-        remediation,
-        fixed: false,
-    });
-
-    // Check 8: Allowlist discovery + validation
-    let allowlist_diag = diagnose_allowlists();
-    let (status, message, remediation) = if allowlist_diag.total_errors > 0 {
-        issues += allowlist_diag.total_errors;
-        (
-            DoctorCheckStatus::Error,
-            format!(
-                "Allowlist errors: {}",
-                allowlist_diag.error_messages.join("; ")
-            ),
-            Some("Run 'dcg allowlist validate' for details".to_string()),
-        )
-    } else if allowlist_diag.total_warnings > 0 {
-        (
-            DoctorCheckStatus::Warning,
-            format!(
-                "Allowlist warnings: {}",
-                allowlist_diag.warning_messages.join("; ")
-            ),
-            Some("Run 'dcg allowlist validate' for details".to_string()),
-        )
-    } else if allowlist_diag.layers_found == 0 {
-        (
-            DoctorCheckStatus::Warning,
-            "No allowlist files found (project or user)".to_string(),
-            Some("Use 'dcg allow <rule-id> -r \"reason\"' to create one".to_string()),
-        )
-    } else {
-        (
-            DoctorCheckStatus::Ok,
-            format!("Allowlist layers found: {}", allowlist_diag.layers_found),
-            None,
-        )
-    };
-    checks.push(DoctorCheck {
-        id: "allowlists",
-        name: "Allowlists",
-        status,
-        message,
-        remediation,
-        fixed: false,
-    });
-
-    DoctorReport {
-        schema_version: DOCTOR_SCHEMA_VERSION,
-        checks,
-        issues,
-        fixed,
-        ok: issues == 0 || (fix && fixed == issues),
+        }
     }
+
+    diag
 }
 
-fn is_dcg_command(cmd: &str) -> bool {
-    cmd == "dcg" || cmd.ends_with("/dcg")
+/// Config validation diagnostics.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+struct ConfigDiagnostics {
+    /// Config file path (if found)
+    config_path: Option<std::path::PathBuf>,
+    /// TOML parse error (if any)
+    parse_error: Option<String>,
+    /// Unknown pack IDs in enabled list
+    unknown_packs: Vec<String>,
+    /// Override patterns that failed to compile
+    invalid_override_patterns: Vec<(String, String)>, // (pattern, error)
 }
 
-fn is_dcg_hook_entry(entry: &serde_json::Value) -> bool {
-    entry
-        .get("matcher")
-        .and_then(|m| m.as_str())
-        .is_some_and(|m| m == "Bash")
-        && entry
-            .get("hooks")
-            .and_then(|h| h.as_array())
-            .is_some_and(|hooks| {
-                hooks.iter().any(|hook| {
-                    hook.get("command")
-                        .and_then(|c| c.as_str())
-                        .is_some_and(is_dcg_command)
-                })
-            })
+#[allow(dead_code)]
+impl ConfigDiagnostics {
+    fn has_errors(&self) -> bool {
+        self.parse_error.is_some() || !self.unknown_packs.is_empty()
+    }
+
+    fn has_warnings(&self) -> bool {
+        !self.invalid_override_patterns.is_empty()
+    }
 }
 
-/// Install the dcg hook entry into Claude Code settings without printing.
-fn install_hook_silent(force: bool) -> Result<bool, Box<dyn std::error::Error>> {
-    let settings_path = claude_settings_path();
+/// Validate configuration in detail.
+#[allow(dead_code)]
+fn validate_config_diagnostics() -> ConfigDiagnostics {
+    let mut diag = ConfigDiagnostics::default();
+
+    let cwd = std::env::current_dir().ok();
+
+    // Explicit config path override (highest precedence for doctor diagnostics)
+    if let Ok(value) = std::env::var(crate::config::ENV_CONFIG_PATH) {
+        let Some(path) = crate::config::resolve_config_path_value(&value, cwd.as_deref()) else {
+            diag.parse_error = Some("DCG_CONFIG is set but empty".to_string());
+            return diag;
+        };
+        if !path.exists() {
+            diag.parse_error = Some(format!(
+                "DCG_CONFIG points to a missing file: {}",
+                path.display()
+            ));
+            diag.config_path = Some(path);
+            return diag;
+        }
+        diag.config_path = Some(path);
+    }
+
+    // Find default/user/project config path (when DCG_CONFIG isn't set)
+    if diag.config_path.is_none() {
+        let cfg_path = config_path();
+        if cfg_path.exists() {
+            diag.config_path = Some(cfg_path);
+        } else if let Some(repo_root) = find_repo_root_from_cwd() {
+            let project_config = repo_root.join(".dcg.toml");
+            if project_config.exists() {
+                diag.config_path = Some(project_config);
+            }
+        }
+    }
+
+    // If no config, nothing to validate
+    let Some(ref path) = diag.config_path else {
+        return diag;
+    };
+
+    // Read and parse
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            diag.parse_error = Some(format!("Failed to read: {e}"));
+            return diag;
+        }
+    };
 
-    let mut settings: serde_json::Value = if settings_path.exists() {
-        let content = std::fs::read_to_string(&settings_path)?;
-        serde_json::from_str(&content)?
-    } else {
-        if let Some(parent) = settings_path.parent() {
-            std::fs::create_dir_all(parent)?;
+    let config: Config = match toml::from_str(&content) {
+        Ok(c) => c,
+        Err(e) => {
+            diag.parse_error = Some(format!("Invalid TOML: {e}"));
+            return diag;
         }
-        serde_json::json!({})
     };
 
-    let changed = install_dcg_hook_into_settings(&mut settings, force)?;
-    if changed {
-        let content = serde_json::to_string_pretty(&settings)?;
-        std::fs::write(&settings_path, content)?;
+    // Validate pack IDs
+    for pack_id in &config.packs.enabled {
+        if !is_valid_pack_id(pack_id) {
+            diag.unknown_packs.push(pack_id.clone());
+        }
+    }
+    for pack_id in &config.packs.disabled {
+        if !is_valid_pack_id(pack_id) {
+            diag.unknown_packs.push(pack_id.clone());
+        }
     }
-    Ok(changed)
-}
 
-/// Create the default config file at the standard path.
-fn write_default_config() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
-    let config_path = config_path();
-    if let Some(parent) = config_path.parent() {
-        std::fs::create_dir_all(parent)?;
+    // Validate override patterns
+    let compiled = config.overrides.compile();
+    for ip in &compiled.invalid_patterns {
+        diag.invalid_override_patterns
+            .push((ip.pattern.clone(), ip.error.clone()));
     }
-    std::fs::write(&config_path, Config::generate_sample_config())?;
-    Ok(config_path)
-}
 
-/// Install the dcg hook entry into an in-memory Claude settings JSON value.
-///
-/// Returns `Ok(true)` when a new hook entry was added, `Ok(false)` when an
-/// existing hook was detected and `force == false`.
-///
-/// # Errors
-///
-/// Returns an error if the settings JSON is not in the expected format:
-/// - root must be an object
-/// - `hooks` must be an object (if present)
-/// - `hooks.PreToolUse` must be an array (if present)
-fn install_dcg_hook_into_settings(
-    settings: &mut serde_json::Value,
-    force: bool,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    // Build the hook configuration.
-    let hook_config = serde_json::json!({
-        "matcher": "Bash",
-        "hooks": [{
-            "type": "command",
-            "command": "dcg"
-        }]
-    });
+    diag
+}
 
-    let settings_obj = settings
-        .as_object_mut()
-        .ok_or("Invalid settings format (expected JSON object)")?;
+/// Check if a pack ID is valid (exists in registry or is a known category).
+#[allow(dead_code)]
+fn is_valid_pack_id(id: &str) -> bool {
+    // Direct pack lookup
+    if REGISTRY.get(id).is_some() {
+        return true;
+    }
 
-    let hooks_value = settings_obj
-        .entry("hooks")
-        .or_insert_with(|| serde_json::json!({}));
+    // Check if it's a category prefix (e.g., "containers" enables all containers.*)
+    let known_categories = [
+        "core",
+        "containers",
+        "kubernetes",
+        "database",
+        "cloud",
+        "infrastructure",
+        "system",
+        "strict_git",
+        "package_managers",
+    ];
 
-    let hooks_obj = hooks_value
-        .as_object_mut()
-        .ok_or("Invalid hooks format (expected JSON object)")?;
+    if known_categories.contains(&id) {
+        return true;
+    }
 
-    let pre_tool_use_value = hooks_obj
-        .entry("PreToolUse")
-        .or_insert_with(|| serde_json::json!([]));
+    // At this point:
+    // - id is NOT in REGISTRY (checked above)
+    // - id is NOT a bare category name (checked above)
+    // Therefore, if id contains a dot (e.g., "containers.fake"), it's invalid
+    // because we only accept full pack IDs that exist in REGISTRY.
+    false
+}
 
-    let pre_tool_use = pre_tool_use_value
-        .as_array_mut()
-        .ok_or("Invalid PreToolUse hooks format (expected JSON array)")?;
+/// Run a quick smoke test to verify the evaluator works.
+///
+/// Tests both an allow case and a deny case to ensure basic functionality.
+#[allow(dead_code)]
+fn run_smoke_test() -> bool {
+    let config = Config::load();
+    let enabled_packs = config.enabled_pack_ids();
+    let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
+    let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
+    let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
+    let compiled_overrides = config.overrides.compile();
+    let allowlists = crate::LayeredAllowlist::default();
+    let heredoc_settings = config.heredoc_settings();
 
-    let already_installed = pre_tool_use.iter().any(is_dcg_hook_entry);
-    if already_installed && !force {
-        return Ok(false);
+    // Test 1: "git status" should be allowed
+    let allow_result = crate::evaluate_command_with_pack_order(
+        "git status",
+        &enabled_keywords,
+        &ordered_packs,
+        keyword_index.as_ref(),
+        &compiled_overrides,
+        &allowlists,
+        &heredoc_settings,
+    );
+    if !allow_result.is_allowed() {
+        return false;
     }
 
-    if force {
-        pre_tool_use.retain(|h| !is_dcg_hook_entry(h));
+    // Test 2: "git reset --hard" should be denied
+    let deny_result = crate::evaluate_command_with_pack_order(
+        "git reset --hard",
+        &enabled_keywords,
+        &ordered_packs,
+        keyword_index.as_ref(),
+        &compiled_overrides,
+        &allowlists,
+        &heredoc_settings,
+    );
+    if deny_result.is_allowed() {
+        return false;
     }
 
-    pre_tool_use.push(hook_config);
-    Ok(true)
+    true
 }
 
-/// Remove the dcg hook entry from an in-memory Claude settings JSON value.
-///
-/// Returns `Ok(true)` when at least one entry was removed, `Ok(false)` when no
-/// dcg hook entry existed.
-///
-/// # Errors
-///
-/// Returns an error if `hooks.PreToolUse` exists but is not an array.
-fn uninstall_dcg_hook_from_settings(
-    settings: &mut serde_json::Value,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    let Some(hooks) = settings.get_mut("hooks") else {
-        return Ok(false);
-    };
-    let Some(pre_tool_use) = hooks.get_mut("PreToolUse") else {
-        return Ok(false);
-    };
-
-    let Some(arr) = pre_tool_use.as_array_mut() else {
-        return Err("Invalid PreToolUse hooks format (expected JSON array)".into());
-    };
+// ============================================================================
 
-    let before = arr.len();
-    arr.retain(|h| !is_dcg_hook_entry(h));
-    Ok(arr.len() < before)
+/// Allowlist validation diagnostics for doctor command.
+#[derive(Debug, Default)]
+struct AllowlistDiagnostics {
+    /// Number of allowlist layers found (project/user)
+    layers_found: usize,
+    /// Total error count
+    total_errors: usize,
+    /// Total warning count
+    total_warnings: usize,
+    /// Error messages to display
+    error_messages: Vec<String>,
+    /// Warning messages to display
+    warning_messages: Vec<String>,
+    /// Entries whose `environments` list excludes the active environment
+    environment_gated: usize,
+    /// The active environment and where it came from, e.g. `staging (DCG_ENV)`
+    environment: String,
 }
 
-/// Install the dcg hook entry into Claude Code settings.
-///
-/// This is a wrapper around `install_dcg_hook_into_settings` that handles the
-/// file I/O and error reporting.
-///
-/// # Errors
-///
-/// Returns an error if the settings file cannot be read, parsed, or written.
-fn install_hook(force: bool) -> Result<(), Box<dyn std::error::Error>> {
-    use colored::Colorize;
+/// Diagnose allowlist health across project and user layers.
+fn diagnose_allowlists() -> AllowlistDiagnostics {
+    use crate::allowlist::{AllowSelector, AllowlistLayer};
 
-    let settings_path = claude_settings_path();
+    let mut diag = AllowlistDiagnostics::default();
 
-    // Read existing settings or create new
-    let mut settings: serde_json::Value = if settings_path.exists() {
-        let content = std::fs::read_to_string(&settings_path)?;
-        serde_json::from_str(&content)?
-    } else {
-        // Create parent directory if needed
-        if let Some(parent) = settings_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        serde_json::json!({})
-    };
+    // Load all allowlists
+    let allowlist = Config::load().load_allowlists();
+    let environment = allowlist.active_environment();
+    diag.environment = format!("{} ({})", environment.name, environment.source.label());
 
-    let changed = install_dcg_hook_into_settings(&mut settings, force)?;
-    if !changed {
-        println!("{}", "Hook already installed!".yellow());
-        println!("Use --force to reinstall");
-        return Ok(());
-    }
+    // Check each layer
+    for loaded in &allowlist.layers {
+        // Skip system layer in doctor (less common)
+        if loaded.layer == AllowlistLayer::System {
+            continue;
+        }
 
-    // Write back
-    let content = serde_json::to_string_pretty(&settings)?;
-    std::fs::write(&settings_path, content)?;
+        // Count as found if path exists
+        let path = match loaded.layer {
+            AllowlistLayer::Project => {
+                if let Some(repo_root) = find_repo_root_from_cwd() {
+                    repo_root.join(".dcg").join("allowlist.toml")
+                } else {
+                    continue;
+                }
+            }
+            AllowlistLayer::User => loaded.path.clone(),
+            AllowlistLayer::System | AllowlistLayer::Generated => continue,
+        };
 
-    println!("{}", "Hook installed successfully!".green().bold());
-    println!("Settings updated: {}", settings_path.display());
-    println!();
-    println!(
-        "{}",
-        "Restart Claude Code for the changes to take effect.".yellow()
-    );
+        if !path.exists() {
+            continue;
+        }
 
-    Ok(())
-}
+        diag.layers_found += 1;
+        let layer_label = loaded.layer.label();
 
-/// Remove the dcg hook entry from Claude Code settings.
-///
-/// This is a wrapper around `uninstall_dcg_hook_from_settings` that handles the
-/// file I/O and error reporting.
-///
-/// # Errors
-///
-/// Returns an error if the settings file cannot be read, parsed, or written.
-fn uninstall_hook(purge: bool) -> Result<(), Box<dyn std::error::Error>> {
-    use colored::Colorize;
+        // Report parse errors
+        for err in &loaded.file.errors {
+            diag.total_errors += 1;
+            diag.error_messages
+                .push(format!("{layer_label}: [{}] {}", err.code(), err.message));
+        }
 
-    let settings_path = claude_settings_path();
+        for renamed in &loaded.file.renamed_rules {
+            diag.total_warnings += 1;
+            diag.warning_messages.push(format!(
+                "{layer_label}: entry {} uses deprecated rule ID {} (now {})",
+                renamed.entry_index + 1,
+                renamed.old,
+                renamed.new
+            ));
+        }
 
-    if !settings_path.exists() {
-        println!("{}", "No Claude Code settings found.".yellow());
-        return Ok(());
-    }
+        // Check entries
+        for (idx, entry) in loaded.file.entries.iter().enumerate() {
+            let entry_num = idx + 1;
 
-    // Read existing settings
-    let content = std::fs::read_to_string(&settings_path)?;
-    let mut settings: serde_json::Value = serde_json::from_str(&content)?;
+            if !crate::allowlist::environment_matches(entry, &environment.name) {
+                diag.environment_gated += 1;
+            }
 
-    // Remove dcg hooks (fail if settings structure is unexpected).
-    let removed = uninstall_dcg_hook_from_settings(&mut settings)?;
+            // Check for expired entries
+            if let Some(expires_at) = &entry.expires_at {
+                if is_expired(expires_at) {
+                    diag.total_warnings += 1;
+                    diag.warning_messages.push(format!(
+                        "{layer_label}: entry {entry_num} expired ({expires_at})"
+                    ));
+                }
+            }
 
-    if removed {
-        // Write back
-        let content = serde_json::to_string_pretty(&settings)?;
-        std::fs::write(&settings_path, content)?;
-        println!("{}", "Hook removed successfully!".green().bold());
-    } else {
-        println!("{}", "No dcg hook found in settings.".yellow());
-    }
+            // Check for risky regex patterns without acknowledgement
+            if matches!(entry.selector, AllowSelector::RegexPattern(_)) && !entry.risk_acknowledged
+            {
+                diag.total_warnings += 1;
+                diag.warning_messages.push(format!(
+                    "{layer_label}: entry {entry_num} uses regex without risk_acknowledged"
+                ));
+            }
 
-    // Purge config files if requested
-    if purge {
-        let config_dir = config_dir();
-        if config_dir.exists() {
-            std::fs::remove_dir_all(&config_dir)?;
-            println!("Removed configuration directory: {}", config_dir.display());
+            // Check for context values that can never match
+            if let Some(context) = entry.context.as_deref() {
+                if context != crate::allowlist::STRING_ARGUMENT_CONTEXT {
+                    diag.total_warnings += 1;
+                    diag.warning_messages.push(format!(
+                        "{layer_label}: entry {entry_num} has unknown context '{context}' and never matches"
+                    ));
+                }
+            }
+
+            // Check for overly broad wildcards
+            if let AllowSelector::Rule(rule_id) = &entry.selector {
+                if rule_id.pack_id == "*" {
+                    diag.total_errors += 1;
+                    diag.error_messages.push(format!(
+                        "{layer_label}: entry {entry_num} uses dangerous global wildcard (*:*)"
+                    ));
+                } else if rule_id.pattern_name == "*" {
+                    diag.total_warnings += 1;
+                    diag.warning_messages.push(format!(
+                        "{layer_label}: entry {entry_num} uses pack wildcard ({}:*)",
+                        rule_id.pack_id
+                    ));
+                }
+            }
         }
     }
 
-    println!();
-    println!(
-        "{}",
-        "Restart Claude Code for the changes to take effect.".yellow()
-    );
-
-    Ok(())
+    diag
 }
 
-/// Update dcg by re-running the platform installer.
-fn self_update(update: UpdateCommand) -> Result<(), Box<dyn std::error::Error>> {
-    // Handle --list-versions flag: show available backup versions
-    if update.list_versions {
-        return handle_list_versions();
+/// Describe the active allowlist environment, e.g. `staging (DCG_ENV)`.
+fn allowlist_environment_summary(diag: &AllowlistDiagnostics) -> String {
+    let summary = &diag.environment;
+    match diag.environment_gated {
+        0 => summary.clone(),
+        1 => format!("{summary}; 1 allowlist entry applies only in other environments"),
+        n => format!("{summary}; {n} allowlist entries apply only in other environments"),
     }
+}
+// Allowlist CLI implementation
+// ============================================================================
 
-    // Handle --rollback flag: restore a previous version
-    if let Some(ref version) = update.rollback {
-        return handle_rollback(version.as_deref());
-    }
+use crate::allowlist::{AllowEntry, AllowSelector, AllowlistLayer, RuleId};
 
-    // Handle --check flag: just check for updates without installing
-    if update.check {
-        return handle_version_check(update.refresh, update.format);
+/// Resolve which allowlist layer to use based on CLI flags.
+///
+/// Default: project if in a git repo, otherwise user.
+fn resolve_layer(project: bool, user: bool) -> AllowlistLayer {
+    if user {
+        AllowlistLayer::User
+    } else if project {
+        AllowlistLayer::Project
+    } else {
+        // Default: project if we can detect a git repo, otherwise user
+        if find_repo_root_from_cwd().is_some() {
+            AllowlistLayer::Project
+        } else {
+            AllowlistLayer::User
+        }
     }
+}
 
-    if cfg!(windows) {
-        return self_update_windows(update);
+/// Every file and directory dcg reads or writes, for `dcg paths` and `dcg doctor`.
+fn collect_dcg_paths(config: &Config) -> Vec<crate::paths::DcgPath> {
+    use crate::paths::{DcgPath, PathRole};
+
+    let cwd = std::env::current_dir().ok();
+    let mut paths = Vec::new();
+
+    if let Ok(explicit) = std::env::var("DCG_CONFIG") {
+        paths.push(DcgPath::file(
+            "config_explicit",
+            PathRole::Config,
+            "Config from DCG_CONFIG",
+            std::path::PathBuf::from(explicit),
+            false,
+        ));
     }
+    paths.push(DcgPath::file(
+        "config_user",
+        PathRole::Config,
+        "User config",
+        config_path(),
+        false,
+    ));
+    if let Some(repo_root) = find_repo_root_from_cwd() {
+        let project = cwd
+            .as_deref()
+            .and_then(crate::config::project_config_path)
+            .unwrap_or_else(|| repo_root.join(".dcg.toml"));
+        paths.push(DcgPath::file(
+            "config_project",
+            PathRole::Config,
+            "Project config",
+            project,
+            false,
+        ));
+        paths.push(DcgPath::file(
+            "allowlist_project",
+            PathRole::Allowlist,
+            "Project allowlist",
+            allowlist_path_for_layer(AllowlistLayer::Project),
+            true,
+        ));
+    }
+    paths.push(DcgPath::file(
+        "config_system",
+        PathRole::Config,
+        "System config",
+        std::path::PathBuf::from("/etc/dcg/config.toml"),
+        false,
+    ));
+    paths.push(DcgPath::file(
+        "allowlist_user",
+        PathRole::Allowlist,
+        "User allowlist",
+        allowlist_path_for_layer(AllowlistLayer::User),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "allowlist_system",
+        PathRole::Allowlist,
+        "System allowlist",
+        std::env::var("DCG_ALLOWLIST_SYSTEM_PATH").map_or_else(
+            |_| allowlist_path_for_layer(AllowlistLayer::System),
+            std::path::PathBuf::from,
+        ),
+        false,
+    ));
 
-    self_update_unix(update)
-}
+    let cwd = cwd.as_deref();
+    paths.push(DcgPath::dir(
+        "state_dir",
+        PathRole::State,
+        "State directory",
+        crate::paths::state_dir(),
+    ));
+    paths.push(DcgPath::file(
+        "pending_exceptions",
+        PathRole::State,
+        "Pending allow-once codes",
+        PendingExceptionStore::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "allow_once",
+        PathRole::State,
+        "Active allow-once grants",
+        AllowOnceStore::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "allowlist_usage",
+        PathRole::State,
+        "Allowlist usage counters",
+        crate::allowlist_usage::AllowlistUsageStore::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "review_queue",
+        PathRole::State,
+        "Review queue",
+        crate::review::ReviewQueueStore::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "suggestion_history",
+        PathRole::State,
+        "Suggestion history",
+        SuggestionHistoryStore::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "warn_once",
+        PathRole::State,
+        "warn_once session memory",
+        crate::warn_once::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "approvals",
+        PathRole::State,
+        "approvals queue",
+        crate::approvals::ApprovalQueueStore::default_path(cwd),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "history_db",
+        PathRole::State,
+        "Command history database",
+        config
+            .history
+            .expanded_database_path()
+            .unwrap_or_else(HistoryDb::default_path),
+        config.history.enabled,
+    ));
+    paths.push(DcgPath::dir(
+        "journal",
+        PathRole::State,
+        "Undo journal",
+        crate::journal::Journal::default_dir(),
+    ));
+    paths.push(DcgPath::dir(
+        "trash",
+        PathRole::State,
+        "Trash (dcg trash)",
+        config.trash.dir(),
+    ));
+    paths.push(DcgPath::file(
+        "privacy_salt",
+        PathRole::State,
+        "Privacy hashing salt",
+        crate::privacy::default_salt_path(),
+        true,
+    ));
+    paths.push(DcgPath::file(
+        "canary_webhook_failure",
+        PathRole::State,
+        "Last canary webhook failure",
+        crate::canary::webhook_failure_path(),
+        true,
+    ));
+    #[cfg(feature = "telemetry")]
+    paths.push(DcgPath::file(
+        "telemetry",
+        PathRole::State,
+        "Telemetry setting and counts",
+        crate::telemetry::default_path(),
+        true,
+    ));
 
-/// Handle --list-versions flag: display available backup versions.
-fn handle_list_versions() -> Result<(), Box<dyn std::error::Error>> {
-    use crate::update::{format_backup_list, list_backups};
+    paths.push(DcgPath::dir(
+        "cache_dir",
+        PathRole::Cache,
+        "Cache directory",
+        crate::paths::cache_dir(),
+    ));
+    paths.push(DcgPath::file(
+        "version_check",
+        PathRole::Cache,
+        "Update check",
+        crate::update::cache_path(),
+        true,
+    ));
+    paths.push(DcgPath::dir(
+        "remote_includes",
+        PathRole::Cache,
+        "Remote config includes",
+        crate::remote_include::IncludeCache::default_dir(),
+    ));
 
-    let backups = list_backups().map_err(|e| format!("Failed to list backups: {e}"))?;
+    paths.push(DcgPath::file(
+        "decision_log",
+        PathRole::Log,
+        "Decision log",
+        resolve_stats_log_path(config, None),
+        config.general.log_file.is_some(),
+    ));
 
-    let use_color = std::io::IsTerminal::is_terminal(&std::io::stdout());
-    print!("{}", format_backup_list(&backups, use_color));
+    paths.sort_by_key(|entry| entry.role);
+    paths
+}
 
-    Ok(())
+/// One line per path dcg cannot read or write, for `dcg doctor`.
+fn dcg_path_problems(config: &Config) -> Vec<String> {
+    collect_dcg_paths(config)
+        .into_iter()
+        .filter_map(|entry| {
+            let status = entry.status();
+            entry.has_problem(&status).then(|| {
+                format!(
+                    "{} ({}): {}",
+                    entry.description,
+                    entry.path.display(),
+                    status.label()
+                )
+            })
+        })
+        .collect()
 }
 
-/// Handle --rollback flag: restore a previous version.
-fn handle_rollback(target_version: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    use crate::update::rollback;
+/// Handle `dcg paths`.
+fn handle_paths_command(
+    config: &Config,
+    format: DoctorFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
 
-    eprintln!("Rolling back dcg...");
+    let paths = collect_dcg_paths(config);
+    if format == DoctorFormat::Json {
+        let entries: Vec<serde_json::Value> = paths
+            .iter()
+            .map(|entry| {
+                let status = entry.status();
+                serde_json::json!({
+                    "id": entry.id,
+                    "role": entry.role,
+                    "description": entry.description,
+                    "path": entry.path,
+                    "writes": entry.writes,
+                    "is_dir": entry.is_dir,
+                    "status": status,
+                    "problem": entry.has_problem(&status),
+                })
+            })
+            .collect();
+        let output = serde_json::json!({
+            "state_dir": crate::paths::state_dir(),
+            "cache_dir": crate::paths::cache_dir(),
+            "paths": entries,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
 
-    match rollback(target_version) {
-        Ok(message) => {
-            println!("{message}");
-            println!("\nRestart dcg to use the restored version.");
-            Ok(())
-        }
-        Err(e) => Err(format!("Rollback failed: {e}").into()),
+    let mut heading = None;
+    for entry in &paths {
+        if heading != Some(entry.role) {
+            if heading.is_some() {
+                println!();
+            }
+            println!("{}", entry.role.heading().bold());
+            heading = Some(entry.role);
+        }
+        let status = entry.status();
+        let label = if entry.has_problem(&status) {
+            status.label().red().to_string()
+        } else if status.exists {
+            status.label().green().to_string()
+        } else {
+            status.label().dimmed().to_string()
+        };
+        let mode = status
+            .mode
+            .as_deref()
+            .map_or_else(String::new, |mode| format!(" {mode}"));
+        println!(
+            "  {:<28} {} [{label}{mode}]",
+            entry.description,
+            entry.path.display()
+        );
     }
+    Ok(())
 }
 
-/// Perform update using native Rust `self_update` crate.
-///
-/// Note: Native update is not yet implemented. Use installer flags instead:
-/// `dcg update --system` or `dcg update --from-source`
-/// Check for updates and display the result.
-fn handle_version_check(
-    force_refresh: bool,
-    format: UpdateFormat,
+/// Handle `dcg policy`.
+fn handle_policy_command(
+    config: &Config,
+    action: PolicyAction,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use crate::update::{check_for_update, format_check_result, format_check_result_json};
-
-    if !matches!(format, UpdateFormat::Json) {
-        eprintln!("Checking for updates...");
-    }
+    use crate::policy_snapshot::{self, PolicySnapshot};
+    use colored::Colorize;
 
-    match check_for_update(force_refresh) {
-        Ok(result) => match format {
-            UpdateFormat::Pretty => {
-                // Detect if stdout is a TTY for color output
-                let use_color = std::io::IsTerminal::is_terminal(&std::io::stdout());
-                print!("{}", format_check_result(&result, use_color));
+    match action {
+        PolicyAction::Export { output } => {
+            let snapshot =
+                PolicySnapshot::capture(config, &crate::allowlist::load_default_allowlists());
+            let json = snapshot.to_json_string();
+            match output {
+                Some(path) => {
+                    crate::storage::write_atomic(&path, json.as_bytes())?;
+                    eprintln!("Wrote policy snapshot to {}", path.display());
+                }
+                None => print!("{json}"),
+            }
+        }
+        PolicyAction::Diff {
+            before,
+            after,
+            format,
+        } => {
+            let load = |path: &std::path::Path| -> Result<PolicySnapshot, String> {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| format!("{}: {e}", path.display()))?;
+                PolicySnapshot::parse(&content).map_err(|e| format!("{}: {e}", path.display()))
+            };
+            let (old, new) = (load(&before)?, load(&after)?);
+            let changes = policy_snapshot::diff(&old, &new);
+
+            if format == DoctorFormat::Json {
+                let output = serde_json::json!({
+                    "before": { "file": before, "source": old.source },
+                    "after": { "file": after, "source": new.source },
+                    "identical": changes.is_empty(),
+                    "changes": changes,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                let describe = |path: &std::path::Path, snapshot: &PolicySnapshot| {
+                    let host = snapshot.source.host.as_deref().unwrap_or("unknown host");
+                    format!(
+                        "{} ({host}, {})",
+                        path.display(),
+                        snapshot.source.generated_at
+                    )
+                };
+                println!("--- {}", describe(&before, &old));
+                println!("+++ {}", describe(&after, &new));
+                let show = |value: &Option<serde_json::Value>| {
+                    value.as_ref().map(ToString::to_string).unwrap_or_default()
+                };
+                for change in &changes {
+                    let line = match change.marker() {
+                        '+' => format!("+ {} = {}", change.path, show(&change.after)).green(),
+                        '-' => format!("- {} = {}", change.path, show(&change.before)).red(),
+                        _ => format!(
+                            "~ {}: {} -> {}",
+                            change.path,
+                            show(&change.before),
+                            show(&change.after)
+                        )
+                        .yellow(),
+                    };
+                    println!("{line}");
+                }
+                if changes.is_empty() {
+                    println!("{}", "Policies are identical.".green());
+                } else {
+                    let plural = if changes.len() == 1 { "" } else { "s" };
+                    println!("{} difference{plural}", changes.len());
+                }
             }
-            UpdateFormat::Json => {
-                let json = format_check_result_json(&result)
-                    .map_err(|e| format!("Failed to format JSON: {e}"))?;
-                println!("{json}");
+            if !changes.is_empty() {
+                std::process::exit(1);
             }
-        },
-        Err(e) => return Err(format!("Failed to check for updates: {e}").into()),
+        }
     }
     Ok(())
 }
 
-fn self_update_unix(update: UpdateCommand) -> Result<(), Box<dyn std::error::Error>> {
-    let script_url = "https://raw.githubusercontent.com/Dicklesworthstone/destructive_command_guard/master/install.sh";
-    let mut args: Vec<String> = Vec::new();
+/// Handle `dcg telemetry`.
+#[cfg(feature = "telemetry")]
+fn handle_telemetry_command(
+    config: &Config,
+    action: TelemetryAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::telemetry::{self, TelemetryState};
+    use colored::Colorize;
 
-    if let Some(version) = update.version {
-        args.push("--version".to_string());
-        args.push(version);
-    }
-    if update.system {
-        args.push("--system".to_string());
-    }
-    if update.easy_mode {
-        args.push("--easy-mode".to_string());
-    }
-    if let Some(dest) = update.dest {
-        args.push("--dest".to_string());
-        args.push(dest.to_string_lossy().into_owned());
-    }
-    if update.from_source {
-        args.push("--from-source".to_string());
-    }
-    if update.verify {
-        args.push("--verify".to_string());
-    }
-    if update.quiet {
-        args.push("--quiet".to_string());
-    }
-    if update.no_gum {
-        args.push("--no-gum".to_string());
-    }
-    if update.force {
-        args.push("--force".to_string());
-    }
+    let path = telemetry::default_path();
+    let mut state = TelemetryState::load(&path);
+    match action {
+        TelemetryAction::Status { format } => {
+            let pack_count = REGISTRY.expand_enabled(&config.enabled_pack_ids()).len();
+            let ping = state.ping(pack_count);
+            let overridden_by = telemetry::disabled_by_env();
+            let next_ping = state.next_ping().map(|time| time.to_rfc3339());
+            if format == DoctorFormat::Json {
+                let output = serde_json::json!({
+                    "enabled": state.enabled && overridden_by.is_none(),
+                    "stored_setting": state.enabled,
+                    "disabled_by_env": overridden_by,
+                    "endpoint": telemetry::endpoint(),
+                    "state_file": path,
+                    "enabled_at": state.enabled_at,
+                    "last_ping": state.last_ping,
+                    "next_ping": next_ping,
+                    "next_payload": ping,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+                return Ok(());
+            }
 
-    let mut escaped_args = String::new();
-    for (idx, arg) in args.iter().enumerate() {
-        if idx > 0 {
-            escaped_args.push(' ');
+            let status = match (state.enabled, overridden_by) {
+                (true, None) => "enabled".green(),
+                (true, Some(var)) => format!("disabled by {var}").yellow(),
+                (false, _) => "disabled".normal(),
+            };
+            println!("Telemetry: {status}");
+            println!("Endpoint:  {}", telemetry::endpoint());
+            println!("State:     {}", path.display());
+            if let Some(last) = &state.last_ping {
+                println!("Last ping: {last}");
+            }
+            if let (Some(next), None) = (&next_ping, overridden_by) {
+                println!("Next ping: {next}");
+            }
+            println!();
+            println!("Next payload:");
+            println!("{}", serde_json::to_string_pretty(&ping)?);
+            if !state.enabled {
+                println!();
+                println!("Nothing is sent until you run `dcg telemetry enable`.");
+            }
+        }
+        TelemetryAction::Enable => {
+            state.enable(chrono::Utc::now());
+            state.save(&path)?;
+            println!("{}", "Telemetry enabled.".green());
+            println!(
+                "dcg will send its version, OS, pack count, and per-pack denial counts about once a week."
+            );
+            println!(
+                "Commands are never sent. Run `dcg telemetry status` to see the next payload."
+            );
+            if let Some(var) = telemetry::disabled_by_env() {
+                println!(
+                    "{}",
+                    format!("Note: {var} is set, so nothing is sent from this environment.")
+                        .yellow()
+                );
+            }
+        }
+        TelemetryAction::Disable => {
+            state.disable();
+            state.save(&path)?;
+            println!("Telemetry disabled; collected counts were discarded.");
         }
-        escaped_args.push_str(&shell_escape_posix(arg));
     }
+    Ok(())
+}
 
-    let command = if escaped_args.is_empty() {
-        format!("curl -fsSL {} | bash -s --", shell_escape_posix(script_url))
+/// Handle `dcg migrate`.
+fn handle_migrate_command(
+    config: &Config,
+    files: &[std::path::PathBuf],
+    dry_run: bool,
+    format: DoctorFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::migrate::{self, FileKind};
+    use colored::Colorize;
+
+    let targets: Vec<(FileKind, std::path::PathBuf)> = if files.is_empty() {
+        migrate::discover(config)
     } else {
-        format!(
-            "curl -fsSL {} | bash -s -- {}",
-            shell_escape_posix(script_url),
-            escaped_args
-        )
+        files
+            .iter()
+            .map(|path| (FileKind::infer(path), path.clone()))
+            .collect()
     };
 
-    let status = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .status()?;
-
-    if !status.success() {
-        return Err(format!("Installer failed with status {status}").into());
+    let mut plans = Vec::with_capacity(targets.len());
+    for (kind, path) in &targets {
+        plans.push(migrate::plan_file(path, *kind)?);
     }
 
-    Ok(())
-}
-
-fn self_update_windows(update: UpdateCommand) -> Result<(), Box<dyn std::error::Error>> {
-    if update.system || update.from_source || update.quiet || update.no_gum || update.force {
-        return Err(
-            "Windows updater supports only --version, --dest, --easy-mode, and --verify.".into(),
-        );
+    let mut backups = Vec::with_capacity(plans.len());
+    for plan in &plans {
+        let backup = if dry_run {
+            None
+        } else {
+            let before = std::fs::read_to_string(&plan.path).ok();
+            let backup = migrate::apply(plan)?;
+            if backup.is_some() {
+                record_policy_change("migrate", &plan.path, before.as_deref());
+            }
+            backup
+        };
+        backups.push(backup);
     }
 
-    let script_url = "https://raw.githubusercontent.com/Dicklesworthstone/destructive_command_guard/master/install.ps1";
-    let mut args: Vec<String> = Vec::new();
-
-    if let Some(version) = update.version {
-        args.push(format!("-Version {}", shell_escape_powershell(&version)));
-    }
-    if let Some(dest) = update.dest {
-        args.push(format!(
-            "-Dest {}",
-            shell_escape_powershell(&dest.to_string_lossy())
-        ));
+    if format == DoctorFormat::Json {
+        let files: Vec<serde_json::Value> = plans
+            .iter()
+            .zip(&backups)
+            .map(|(plan, backup)| {
+                let mut value = serde_json::to_value(plan).unwrap_or_default();
+                if let Some(obj) = value.as_object_mut() {
+                    obj.insert("current".into(), plan.is_current().into());
+                    obj.insert("backup".into(), serde_json::json!(backup));
+                }
+                value
+            })
+            .collect();
+        let output = serde_json::json!({
+            "current_version": migrate::CURRENT_CONFIG_VERSION,
+            "dry_run": dry_run,
+            "files": files,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
     }
-    if update.easy_mode {
-        args.push("-EasyMode".to_string());
+
+    if plans.is_empty() {
+        println!("{}", "No config or allowlist files found.".dimmed());
+        return Ok(());
     }
-    if update.verify {
-        args.push("-Verify".to_string());
+    for (plan, backup) in plans.iter().zip(&backups) {
+        let label = format!("{} ({})", plan.path.display(), plan.kind.label());
+        if plan.is_current() {
+            println!(
+                "{} {label}: already at version {}",
+                "✓".green(),
+                plan.from_version
+            );
+            continue;
+        }
+        println!(
+            "{} {label}: version {} -> {}",
+            if dry_run { "•".cyan() } else { "✓".green() },
+            plan.from_version,
+            plan.to_version
+        );
+        for edit in &plan.edits {
+            println!("    {} {}", edit.key.bold(), edit.change);
+        }
+        for note in &plan.notes {
+            println!("    {} {note}", "note:".yellow());
+        }
+        if let Some(backup) = backup {
+            println!("    {} {}", "backup:".dimmed(), backup.display());
+        }
     }
-
-    let args_str = if args.is_empty() {
-        String::new()
-    } else {
-        format!(" {}", args.join(" "))
-    };
-
-    let command = format!(
-        "$ErrorActionPreference='Stop'; \
-$url={url}; \
-$tmp=Join-Path $env:TEMP 'dcg-install.ps1'; \
-Invoke-WebRequest -Uri $url -OutFile $tmp; \
-& $tmp{args}; \
-$code=$LASTEXITCODE; \
-Remove-Item $tmp -ErrorAction SilentlyContinue; \
-exit $code;",
-        url = shell_escape_powershell(script_url),
-        args = args_str
-    );
-
-    let status = std::process::Command::new("powershell")
-        .arg("-NoProfile")
-        .arg("-ExecutionPolicy")
-        .arg("Bypass")
-        .arg("-Command")
-        .arg(command)
-        .status()?;
-
-    if !status.success() {
-        return Err(format!("Installer failed with status {status}").into());
+    if dry_run && plans.iter().any(|plan| !plan.is_current()) {
+        println!();
+        println!("{}", "Dry run: no files were changed.".dimmed());
     }
-
     Ok(())
 }
 
-fn shell_escape_posix(value: &str) -> String {
-    if value.is_empty() {
-        return "''".to_string();
-    }
-    let mut escaped = String::from("'");
-    for ch in value.chars() {
-        if ch == '\'' {
-            escaped.push_str("'\\''");
-        } else {
-            escaped.push(ch);
-        }
-    }
-    escaped.push('\'');
-    escaped
+/// Find the repo root from the current working directory.
+fn find_repo_root_from_cwd() -> Option<std::path::PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    crate::config::find_repo_root(&cwd, crate::config::REPO_ROOT_SEARCH_MAX_HOPS)
 }
 
-fn shell_escape_powershell(value: &str) -> String {
-    let mut escaped = String::from("'");
-    for ch in value.chars() {
-        if ch == '\'' {
-            escaped.push_str("''");
-        } else {
-            escaped.push(ch);
+/// Get the path to the allowlist file for a given layer.
+fn allowlist_path_for_layer(layer: AllowlistLayer) -> std::path::PathBuf {
+    match layer {
+        AllowlistLayer::Project => {
+            let repo_root = find_repo_root_from_cwd()
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+            repo_root.join(".dcg").join("allowlist.toml")
+        }
+        AllowlistLayer::User => {
+            let shared = config_dir().join("allowlist.toml");
+            match crate::identity::Identity::resolve() {
+                Some(identity) => identity.allowlist_path(&shared),
+                None => shared,
+            }
+        }
+        AllowlistLayer::System => std::path::PathBuf::from("/etc/dcg/allowlist.toml"),
+        // Not a file: the project directory the layer is generated from.
+        AllowlistLayer::Generated => {
+            let cwd = std::env::current_dir().unwrap_or_default();
+            crate::allowlist_bootstrap::detect(&cwd).map_or(cwd, |bootstrap| bootstrap.root)
         }
     }
-    escaped.push('\'');
-    escaped
-}
-
-/// Get the path to Claude Code settings
-fn claude_settings_path() -> std::path::PathBuf {
-    dirs::home_dir()
-        .unwrap_or_default()
-        .join(".claude")
-        .join("settings.json")
 }
 
-/// Get the path to dcg config directory.
-///
-/// Prefers `$XDG_CONFIG_HOME/dcg/`, then XDG-style `~/.config/dcg/` if it exists,
-/// otherwise falls back to the platform-native location. This ensures users can
-/// use `~/.config/dcg/` on all platforms, including macOS where
-/// `dirs::config_dir()` returns `~/Library/Application Support`.
-fn config_dir() -> std::path::PathBuf {
-    // Check XDG_CONFIG_HOME first (if set)
-    if let Ok(xdg_home) = std::env::var("XDG_CONFIG_HOME") {
-        if let Some(xdg_home) = crate::config::resolve_config_path_value(&xdg_home, None) {
-            return xdg_home.join("dcg");
+/// Handle allowlist subcommand dispatch.
+fn handle_allowlist_command(action: AllowlistAction) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        AllowlistAction::Add {
+            rule_id,
+            reason,
+            project,
+            user,
+            expires,
+            conditions,
+        } => {
+            let layer = resolve_layer(project, user);
+            allowlist_add_rule(&rule_id, &reason, layer, expires.as_deref(), &conditions)?;
+        }
+        AllowlistAction::AddCommand {
+            command,
+            reason,
+            project,
+            user,
+            expires,
+        } => {
+            let layer = resolve_layer(project, user);
+            allowlist_add_command(&command, &reason, layer, expires.as_deref())?;
+        }
+        AllowlistAction::List {
+            project,
+            user,
+            diff,
+            format,
+        } => {
+            if let Some(rev) = diff {
+                allowlist_diff(&rev, format)?;
+            } else {
+                allowlist_list(project, user, format)?;
+            }
+        }
+        AllowlistAction::Remove {
+            rule_id,
+            project,
+            user,
+        } => {
+            let layer = resolve_layer(project, user);
+            allowlist_remove(&rule_id, layer)?;
         }
-    }
-
-    // Check XDG-style path next (~/.config/dcg/)
-    if let Some(home) = dirs::home_dir() {
-        let xdg_dir = home.join(".config").join("dcg");
-        if xdg_dir.exists() {
-            return xdg_dir;
+        AllowlistAction::Validate {
+            project,
+            user,
+            strict,
+        } => {
+            allowlist_validate(project, user, strict)?;
         }
-    }
-
-    // Fall back to platform-native or default to ~/.config/dcg
-    dirs::config_dir()
-        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"))
-        .join("dcg")
-}
-
-/// Get the path to dcg config file
-fn config_path() -> std::path::PathBuf {
-    // Prefer an existing config file in the same order as config loading.
-    if let Ok(xdg_home) = std::env::var("XDG_CONFIG_HOME") {
-        if let Some(xdg_home) = crate::config::resolve_config_path_value(&xdg_home, None) {
-            let path = xdg_home.join("dcg").join("config.toml");
-            if path.exists() {
-                return path;
-            }
+        AllowlistAction::MigrateIds {
+            project,
+            user,
+            dry_run,
+        } => {
+            let layer = resolve_layer(project, user);
+            allowlist_migrate_ids(layer, dry_run)?;
         }
-    }
-
-    if let Some(home) = dirs::home_dir() {
-        let path = home.join(".config").join("dcg").join("config.toml");
-        if path.exists() {
-            return path;
+        AllowlistAction::Stats {
+            project,
+            user,
+            format,
+        } => {
+            allowlist_stats(project, user, format)?;
         }
-    }
-
-    if let Some(config_dir) = dirs::config_dir() {
-        let path = config_dir.join("dcg").join("config.toml");
-        if path.exists() {
-            return path;
+        AllowlistAction::Prune {
+            unused_for,
+            project,
+            user,
+            dry_run,
+        } => {
+            let layer = resolve_layer(project, user);
+            allowlist_prune(&unused_for, layer, dry_run)?;
+        }
+        AllowlistAction::Export {
+            format,
+            layer,
+            output,
+        } => {
+            let layer = layer.unwrap_or_else(|| resolve_layer(false, false));
+            allowlist_export(format, layer, output.as_deref())?;
+        }
+        AllowlistAction::Import {
+            file,
+            layer,
+            merge_strategy,
+            format,
+            dry_run,
+        } => {
+            let layer = layer.unwrap_or_else(|| resolve_layer(false, false));
+            let format = format
+                .unwrap_or_else(|| crate::allowlist_exchange::ExchangeFormat::from_path(&file));
+            allowlist_import(&file, format, layer, merge_strategy, dry_run)?;
         }
+        AllowlistAction::Bootstrap { format } => allowlist_bootstrap(format)?,
     }
-
-    config_dir().join("config.toml")
+    Ok(())
 }
 
-/// Check if dcg is in PATH
-fn which_dcg() -> Option<std::path::PathBuf> {
-    std::env::var_os("PATH").and_then(|paths| {
-        std::env::split_paths(&paths).find_map(|dir| {
-            let path = dir.join("dcg");
-            if path.is_file() { Some(path) } else { None }
-        })
-    })
-}
+#[allow(clippy::too_many_lines)]
+fn handle_allow_once_command(
+    config: &Config,
+    cmd: &AllowOnceCommand,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{self, Write};
 
-/// Check if the hook is registered in Claude Code settings
-#[allow(dead_code)]
-fn check_hook_registered() -> Result<bool, Box<dyn std::error::Error>> {
-    let settings_path = claude_settings_path();
-    if !settings_path.exists() {
-        return Ok(false);
+    if let Some(action) = &cmd.action {
+        match action {
+            AllowOnceAction::List => return handle_allow_once_list(config, cmd),
+            AllowOnceAction::Clear(args) => return handle_allow_once_clear(config, cmd, args),
+            AllowOnceAction::Revoke(args) => return handle_allow_once_revoke(config, cmd, args),
+        }
     }
 
-    let content = std::fs::read_to_string(&settings_path)?;
-    let settings: serde_json::Value = serde_json::from_str(&content)?;
-
-    let registered = settings
-        .get("hooks")
-        .and_then(|h| h.get("PreToolUse"))
-        .and_then(|arr| arr.as_array())
-        .is_some_and(|a| a.iter().any(is_dcg_hook_entry));
+    let Some(code) = cmd.code.as_deref() else {
+        return Err("Missing allow-once code. Usage: dcg allow-once <CODE>".into());
+    };
 
-    Ok(registered)
-}
+    let now = Utc::now();
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let pending_path = PendingExceptionStore::default_path(Some(&cwd));
+    let pending_store = PendingExceptionStore::new(pending_path);
 
-// ============================================================================
-// Doctor: expanded diagnostics (git_safety_guard-1gt.7.1)
-// NOTE: These diagnostic types are scaffolding for future `dcg doctor` enhancements.
-// ============================================================================
+    let (matches, _maintenance) = pending_store.lookup_by_code(code, now)?;
+    if matches.is_empty() {
+        return Err(
+            format!("No pending exception found for code '{code}'. It may be expired.").into(),
+        );
+    }
 
-/// Detailed hook wiring diagnostics.
-#[allow(dead_code)]
-#[derive(Debug, Default)]
-struct HookDiagnostics {
-    /// Settings file exists
-    settings_exists: bool,
-    /// Settings JSON is valid
-    settings_valid: bool,
-    /// Error message if settings invalid
-    settings_error: Option<String>,
-    /// Number of dcg hook entries found
-    dcg_hook_count: usize,
-    /// Dcg hooks found with wrong matcher (not "Bash")
-    wrong_matcher_hooks: Vec<String>,
-    /// Dcg hooks pointing to absolute path that doesn't exist
-    missing_executable_hooks: Vec<String>,
-    /// Other non-dcg hooks in `PreToolUse`
-    other_hooks_count: usize,
-}
+    let selected = select_pending_entry(&matches, cmd)?;
 
-#[allow(dead_code)]
-impl HookDiagnostics {
-    fn is_healthy(&self) -> bool {
-        self.settings_valid
-            && self.dcg_hook_count == 1
-            && self.wrong_matcher_hooks.is_empty()
-            && self.missing_executable_hooks.is_empty()
+    let is_config_block = selected.source.as_deref() == Some("ConfigOverride");
+    if is_config_block && !cmd.force {
+        return Err(
+            "This denial came from your config blocklist; re-run with --force to override.".into(),
+        );
     }
-
-    fn has_issues(&self) -> bool {
-        !self.settings_valid
-            || self.dcg_hook_count == 0
-            || self.dcg_hook_count > 1
-            || !self.wrong_matcher_hooks.is_empty()
-            || !self.missing_executable_hooks.is_empty()
+    if cmd.json && !cmd.yes && !cmd.dry_run {
+        return Err("JSON output requires --yes or --dry-run to avoid prompts.".into());
     }
-}
 
-/// Diagnose hook wiring in detail.
-#[allow(dead_code)]
-fn diagnose_hook_wiring() -> HookDiagnostics {
-    let mut diag = HookDiagnostics::default();
-    let settings_path = claude_settings_path();
+    let selected_cwd = if selected.cwd == "<unknown>" || selected.cwd.is_empty() {
+        cwd
+    } else {
+        std::path::PathBuf::from(&selected.cwd)
+    };
+    let (scope_kind, scope_path) = allow_once_scope(&selected_cwd);
+    let scope_path_str = scope_path.to_string_lossy().to_string();
+    let grant = resolve_allow_once_grant(config, selected, cmd.requested_grant())?;
 
-    if !settings_path.exists() {
-        return diag;
-    }
-    diag.settings_exists = true;
+    let entry = AllowOnceEntry::from_pending(
+        selected,
+        now,
+        scope_kind,
+        &scope_path_str,
+        cmd.single_use,
+        cmd.force && is_config_block,
+        &config.logging.redaction,
+    )
+    .with_grant(grant);
 
-    // Read and parse settings
-    let content = match std::fs::read_to_string(&settings_path) {
-        Ok(c) => c,
-        Err(e) => {
-            diag.settings_error = Some(format!("Failed to read settings: {e}"));
-            return diag;
+    if cmd.json {
+        let output = serde_json::json!({
+            "status": "ok",
+            "code": code,
+            "dry_run": cmd.dry_run,
+            "single_use": cmd.single_use,
+            "force": entry.force_allow_config,
+            "grant": grant.label(),
+            "rule_id": entry.rule_id,
+            "scope_kind": format!("{scope_kind:?}").to_lowercase(),
+            "scope_path": scope_path_str,
+            "command": if cmd.show_raw { selected.command_raw.clone() } else { selected.command_redacted.clone() },
+            "cwd": selected.cwd.clone(),
+            "expires_at": entry.expires_at,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        if cmd.dry_run {
+            return Ok(());
+        }
+    } else {
+        let display_command = if cmd.show_raw {
+            selected.command_raw.as_str()
+        } else {
+            selected.command_redacted.as_str()
+        };
+        println!("Allow-once confirmation:");
+        println!("  Command: {display_command}");
+        println!("  CWD: {}", selected.cwd);
+        println!("  Expires: {}", entry.expires_at);
+        println!("  Scope: {scope_kind:?} ({scope_path_str})");
+        match (grant, entry.rule_id.as_deref()) {
+            (AllowOnceGrant::Rule, Some(rule_id)) => {
+                println!("  Grant: rule {rule_id} (any command it blocks)");
+            }
+            (AllowOnceGrant::Session, _) => println!("  Grant: this command, this session only"),
+            _ => println!("  Grant: this exact command"),
+        }
+        if cmd.single_use {
+            println!("  Mode: single-use");
+        } else {
+            println!("  Mode: reusable until expiry");
+        }
+
+        let needs_prompt = !(cmd.yes || cmd.dry_run);
+        if needs_prompt {
+            if cmd.force && is_config_block {
+                print!("Type 'FORCE' to confirm override: ");
+                io::stdout().flush()?;
+                let mut response = String::new();
+                io::stdin().read_line(&mut response)?;
+                if response.trim() != "FORCE" {
+                    return Err("Aborted.".into());
+                }
+            } else {
+                print!("Proceed? [y/N]: ");
+                io::stdout().flush()?;
+                let mut response = String::new();
+                io::stdin().read_line(&mut response)?;
+                let response = response.trim().to_lowercase();
+                if response != "y" && response != "yes" {
+                    return Err("Aborted.".into());
+                }
+            }
         }
-    };
 
-    let settings: serde_json::Value = match serde_json::from_str(&content) {
-        Ok(s) => s,
-        Err(e) => {
-            diag.settings_error = Some(format!("Invalid JSON: {e}"));
-            return diag;
+        if cmd.dry_run {
+            println!("Dry-run: no allow-once entry written.");
+            return Ok(());
         }
-    };
-    diag.settings_valid = true;
-
-    // Check hooks structure
-    let Some(hooks) = settings.get("hooks") else {
-        return diag;
-    };
-    let Some(pre_tool_use) = hooks.get("PreToolUse") else {
-        return diag;
-    };
-    let Some(entries) = pre_tool_use.as_array() else {
-        diag.settings_error = Some("hooks.PreToolUse is not an array".to_string());
-        diag.settings_valid = false;
-        return diag;
-    };
+    }
 
-    // Analyze each entry
-    for entry in entries {
-        let matcher = entry.get("matcher").and_then(|m| m.as_str());
-        let hooks_arr = entry.get("hooks").and_then(|h| h.as_array());
+    let allow_once_path = AllowOnceStore::default_path(Some(&selected_cwd));
+    let allow_once_store = AllowOnceStore::new(allow_once_path.clone());
+    let _maintenance = allow_once_store.add_entry(&entry, now)?;
 
-        let Some(hooks_arr) = hooks_arr else {
-            continue;
-        };
+    // Remove the pending exception so it doesn't show up in lists anymore.
+    // This is best-effort (if it fails, the allowed command still works).
+    if let Err(e) = pending_store.remove_by_full_hash(&selected.full_hash, now) {
+        eprintln!("Warning: Failed to remove pending exception: {e}");
+    }
 
-        for hook in hooks_arr {
-            let cmd = hook.get("command").and_then(|c| c.as_str());
-            if let Some(cmd) = cmd {
-                if is_dcg_command(cmd) {
-                    diag.dcg_hook_count += 1;
+    if !cmd.json {
+        println!("✓ Allow-once entry created");
+        println!("  File: {}", allow_once_path.display());
+    }
 
-                    // Check matcher
-                    if matcher != Some("Bash") {
-                        diag.wrong_matcher_hooks
-                            .push(matcher.unwrap_or("(none)").to_string());
-                    }
+    Ok(())
+}
 
-                    // Check if absolute path exists
-                    if cmd.starts_with('/') && !std::path::Path::new(cmd).exists() {
-                        diag.missing_executable_hooks.push(cmd.to_string());
-                    }
-                } else {
-                    diag.other_hooks_count += 1;
-                }
-            }
+/// Grant for an allow-once entry: the requested one, else the blocked agent's
+/// `[agents]` profile default.
+///
+/// An explicitly requested rule or session grant fails when the denial did not
+/// record a rule id or session id; a configured default quietly falls back to
+/// an exact-command grant instead.
+pub(crate) fn resolve_allow_once_grant(
+    config: &Config,
+    record: &PendingExceptionRecord,
+    requested: Option<AllowOnceGrant>,
+) -> Result<AllowOnceGrant, String> {
+    let available = |grant: AllowOnceGrant| match grant {
+        AllowOnceGrant::Exact => true,
+        AllowOnceGrant::Rule => record.rule_id.is_some(),
+        AllowOnceGrant::Session => record.session_id.is_some(),
+    };
+    match requested {
+        Some(AllowOnceGrant::Rule) if !available(AllowOnceGrant::Rule) => Err(
+            "This denial did not come from a pack rule; use --exact instead of --rule.".to_string(),
+        ),
+        Some(AllowOnceGrant::Session) if !available(AllowOnceGrant::Session) => Err(
+            "The blocked agent did not report a session id; use --exact instead of --session."
+                .to_string(),
+        ),
+        Some(grant) => Ok(grant),
+        None => {
+            let agent = record.agent.as_deref().unwrap_or("unknown");
+            let grant = config.agents.allow_once_scope_for(agent);
+            Ok(if available(grant) {
+                grant
+            } else {
+                AllowOnceGrant::Exact
+            })
         }
     }
-
-    diag
 }
 
-/// Config validation diagnostics.
-#[allow(dead_code)]
-#[derive(Debug, Default)]
-struct ConfigDiagnostics {
-    /// Config file path (if found)
-    config_path: Option<std::path::PathBuf>,
-    /// TOML parse error (if any)
-    parse_error: Option<String>,
-    /// Unknown pack IDs in enabled list
-    unknown_packs: Vec<String>,
-    /// Override patterns that failed to compile
-    invalid_override_patterns: Vec<(String, String)>, // (pattern, error)
+/// Scope for an allow-once entry created from a block in `cwd`: the enclosing
+/// repository when there is one, else the directory itself.
+pub(crate) fn allow_once_scope(cwd: &std::path::Path) -> (AllowOnceScopeKind, std::path::PathBuf) {
+    crate::config::find_repo_root(cwd, crate::config::REPO_ROOT_SEARCH_MAX_HOPS).map_or_else(
+        || (AllowOnceScopeKind::Cwd, cwd.to_path_buf()),
+        |root| (AllowOnceScopeKind::Project, root),
+    )
 }
 
-#[allow(dead_code)]
-impl ConfigDiagnostics {
-    fn has_errors(&self) -> bool {
-        self.parse_error.is_some() || !self.unknown_packs.is_empty()
-    }
+fn handle_allow_once_list(
+    _config: &Config,
+    cmd: &AllowOnceCommand,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let now = Utc::now();
+    let cwd = std::env::current_dir().unwrap_or_default();
 
-    fn has_warnings(&self) -> bool {
-        !self.invalid_override_patterns.is_empty()
-    }
-}
+    let pending_store = PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&cwd)));
+    let allow_once_store = AllowOnceStore::new(AllowOnceStore::default_path(Some(&cwd)));
 
-/// Validate configuration in detail.
-#[allow(dead_code)]
-fn validate_config_diagnostics() -> ConfigDiagnostics {
-    let mut diag = ConfigDiagnostics::default();
+    let (pending, pending_maintenance) = pending_store.load_active(now)?;
+    let (allow_once, allow_once_maintenance) = allow_once_store.load_active(now)?;
 
-    let cwd = std::env::current_dir().ok();
+    if cmd.json {
+        let output = build_allow_once_list_json(
+            &pending,
+            pending_maintenance,
+            &allow_once,
+            allow_once_maintenance,
+            cmd.show_raw,
+        );
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
 
-    // Explicit config path override (highest precedence for doctor diagnostics)
-    if let Ok(value) = std::env::var(crate::config::ENV_CONFIG_PATH) {
-        let Some(path) = crate::config::resolve_config_path_value(&value, cwd.as_deref()) else {
-            diag.parse_error = Some("DCG_CONFIG is set but empty".to_string());
-            return diag;
-        };
-        if !path.exists() {
-            diag.parse_error = Some(format!(
-                "DCG_CONFIG points to a missing file: {}",
-                path.display()
-            ));
-            diag.config_path = Some(path);
-            return diag;
+    println!("Allow-once pending codes: {}", pending.len());
+    if pending.is_empty() {
+        println!("  (none)");
+    } else {
+        for record in &pending {
+            let cmd_display = if cmd.show_raw {
+                record.command_raw.as_str()
+            } else {
+                record.command_redacted.as_str()
+            };
+            println!(
+                "  - {} [{}] {}",
+                record.short_code,
+                &record.full_hash[..8.min(record.full_hash.len())],
+                cmd_display
+            );
         }
-        diag.config_path = Some(path);
     }
 
-    // Find default/user/project config path (when DCG_CONFIG isn't set)
-    if diag.config_path.is_none() {
-        let cfg_path = config_path();
-        if cfg_path.exists() {
-            diag.config_path = Some(cfg_path);
-        } else if let Some(repo_root) = find_repo_root_from_cwd() {
-            let project_config = repo_root.join(".dcg.toml");
-            if project_config.exists() {
-                diag.config_path = Some(project_config);
-            }
+    println!();
+    println!("Allow-once active entries: {}", allow_once.len());
+    if allow_once.is_empty() {
+        println!("  (none)");
+    } else {
+        for entry in &allow_once {
+            let cmd_display = if cmd.show_raw {
+                entry.command_raw.as_str()
+            } else {
+                entry.command_redacted.as_str()
+            };
+            let grant = match (entry.grant, entry.rule_id.as_deref()) {
+                (AllowOnceGrant::Rule, Some(rule_id)) => format!("rule {rule_id}"),
+                (grant, _) => grant.label().to_string(),
+            };
+            println!(
+                "  - {} [{}] ({grant}) {}",
+                entry.source_short_code,
+                &entry.source_full_hash[..8.min(entry.source_full_hash.len())],
+                cmd_display
+            );
         }
     }
 
-    // If no config, nothing to validate
-    let Some(ref path) = diag.config_path else {
-        return diag;
-    };
+    if !pending_maintenance.is_empty() || !allow_once_maintenance.is_empty() {
+        println!();
+        println!(
+            "Maintenance: pending(pruned_expired={}, pruned_consumed={}, parse_errors={}), allow_once(pruned_expired={}, pruned_consumed={}, parse_errors={})",
+            pending_maintenance.pruned_expired,
+            pending_maintenance.pruned_consumed,
+            pending_maintenance.parse_errors,
+            allow_once_maintenance.pruned_expired,
+            allow_once_maintenance.pruned_consumed,
+            allow_once_maintenance.parse_errors
+        );
+    }
 
-    // Read and parse
-    let content = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(e) => {
-            diag.parse_error = Some(format!("Failed to read: {e}"));
-            return diag;
-        }
-    };
+    Ok(())
+}
+
+pub(crate) fn build_allow_once_list_json(
+    pending: &[PendingExceptionRecord],
+    pending_maintenance: crate::pending_exceptions::PendingMaintenance,
+    allow_once: &[AllowOnceEntry],
+    allow_once_maintenance: crate::pending_exceptions::PendingMaintenance,
+    show_raw: bool,
+) -> serde_json::Value {
+    let pending_json: Vec<serde_json::Value> = pending
+        .iter()
+        .map(|record| {
+            serde_json::json!({
+                "short_code": &record.short_code,
+                "full_hash": &record.full_hash,
+                "created_at": &record.created_at,
+                "expires_at": &record.expires_at,
+                "cwd": &record.cwd,
+                "reason": &record.reason,
+                "single_use": record.single_use,
+                "source": record.source.as_deref(),
+                "command": if show_raw { &record.command_raw } else { &record.command_redacted },
+            })
+        })
+        .collect();
+
+    let allow_once_json: Vec<serde_json::Value> = allow_once
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "source_short_code": &entry.source_short_code,
+                "source_full_hash": &entry.source_full_hash,
+                "created_at": &entry.created_at,
+                "expires_at": &entry.expires_at,
+                "scope_kind": format!("{:?}", entry.scope_kind).to_lowercase(),
+                "scope_path": &entry.scope_path,
+                "reason": &entry.reason,
+                "single_use": entry.single_use,
+                "force_allow_config": entry.force_allow_config,
+                "grant": entry.grant.label(),
+                "rule_id": entry.rule_id.as_deref(),
+                "command": if show_raw { &entry.command_raw } else { &entry.command_redacted },
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "status": "ok",
+        "pending": {
+            "count": pending_json.len(),
+            "maintenance": pending_maintenance,
+            "entries": pending_json,
+        },
+        "allow_once": {
+            "count": allow_once_json.len(),
+            "maintenance": allow_once_maintenance,
+            "entries": allow_once_json,
+        },
+    })
+}
 
-    let config: Config = match toml::from_str(&content) {
-        Ok(c) => c,
-        Err(e) => {
-            diag.parse_error = Some(format!("Invalid TOML: {e}"));
-            return diag;
-        }
-    };
+fn handle_allow_once_clear(
+    config: &Config,
+    cmd: &AllowOnceCommand,
+    args: &AllowOnceClearArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{self, Write};
 
-    // Validate pack IDs
-    for pack_id in &config.packs.enabled {
-        if !is_valid_pack_id(pack_id) {
-            diag.unknown_packs.push(pack_id.clone());
-        }
-    }
-    for pack_id in &config.packs.disabled {
-        if !is_valid_pack_id(pack_id) {
-            diag.unknown_packs.push(pack_id.clone());
-        }
+    if cmd.json && !cmd.yes {
+        return Err("JSON output requires --yes to avoid interactive prompts.".into());
     }
 
-    // Validate override patterns
-    let compiled = config.overrides.compile();
-    for ip in &compiled.invalid_patterns {
-        diag.invalid_override_patterns
-            .push((ip.pattern.clone(), ip.error.clone()));
-    }
+    let now = Utc::now();
+    let cwd = std::env::current_dir().unwrap_or_default();
 
-    diag
-}
+    let pending_store = PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&cwd)));
+    let allow_once_store = AllowOnceStore::new(AllowOnceStore::default_path(Some(&cwd)));
 
-/// Check if a pack ID is valid (exists in registry or is a known category).
-#[allow(dead_code)]
-fn is_valid_pack_id(id: &str) -> bool {
-    // Direct pack lookup
-    if REGISTRY.get(id).is_some() {
-        return true;
-    }
+    let wipe_pending = args.all || args.pending;
+    let wipe_allow_once = args.all || args.allow_once;
 
-    // Check if it's a category prefix (e.g., "containers" enables all containers.*)
-    let known_categories = [
-        "core",
-        "containers",
-        "kubernetes",
-        "database",
-        "cloud",
-        "infrastructure",
-        "system",
-        "strict_git",
-        "package_managers",
-    ];
+    let (pending_preview, pending_preview_maintenance) = pending_store.preview_active(now)?;
+    let (allow_once_preview, allow_once_preview_maintenance) =
+        allow_once_store.preview_active(now)?;
 
-    if known_categories.contains(&id) {
-        return true;
-    }
+    let pending_wipe_count = if wipe_pending {
+        pending_preview.len()
+    } else {
+        0
+    };
+    let allow_once_wipe_count = if wipe_allow_once {
+        allow_once_preview.len()
+    } else {
+        0
+    };
 
-    // At this point:
-    // - id is NOT in REGISTRY (checked above)
-    // - id is NOT a bare category name (checked above)
-    // Therefore, if id contains a dot (e.g., "containers.fake"), it's invalid
-    // because we only accept full pack IDs that exist in REGISTRY.
-    false
-}
+    if !cmd.json && !cmd.yes && (wipe_pending || wipe_allow_once) {
+        println!("Allow-once clear confirmation:");
+        println!("  pending_wipe_active={pending_wipe_count}");
+        println!("  allow_once_wipe_active={allow_once_wipe_count}");
+        print!("Proceed? [y/N]: ");
+        io::stdout().flush()?;
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+        let response = response.trim().to_lowercase();
+        if response != "y" && response != "yes" {
+            return Err("Aborted.".into());
+        }
+    }
 
-/// Run a quick smoke test to verify the evaluator works.
-///
-/// Tests both an allow case and a deny case to ensure basic functionality.
-#[allow(dead_code)]
-fn run_smoke_test() -> bool {
-    let config = Config::load();
-    let enabled_packs = config.enabled_pack_ids();
-    let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
-    let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
-    let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
-    let compiled_overrides = config.overrides.compile();
-    let allowlists = crate::LayeredAllowlist::default();
-    let heredoc_settings = config.heredoc_settings();
+    let (pending_wiped, pending_maintenance) = if wipe_pending {
+        pending_store.clear_all(now)?
+    } else {
+        let (_active, maintenance) = pending_store.load_active(now)?;
+        (0, maintenance)
+    };
+    let (allow_once_wiped, allow_once_maintenance) = if wipe_allow_once {
+        allow_once_store.clear_all(now)?
+    } else {
+        let (_active, maintenance) = allow_once_store.load_active(now)?;
+        (0, maintenance)
+    };
 
-    // Test 1: "git status" should be allowed
-    let allow_result = crate::evaluate_command_with_pack_order(
-        "git status",
-        &enabled_keywords,
-        &ordered_packs,
-        keyword_index.as_ref(),
-        &compiled_overrides,
-        &allowlists,
-        &heredoc_settings,
-    );
-    if !allow_result.is_allowed() {
-        return false;
+    if let Some(log_file) = config.general.log_file.as_deref() {
+        let _ = crate::pending_exceptions::log_allow_once_action(
+            log_file,
+            "clear",
+            &format!(
+                "pending_wiped={pending_wiped}, allow_once_wiped={allow_once_wiped}, flags=all:{} pending:{} allow_once:{}",
+                args.all, args.pending, args.allow_once
+            ),
+        );
     }
 
-    // Test 2: "git reset --hard" should be denied
-    let deny_result = crate::evaluate_command_with_pack_order(
-        "git reset --hard",
-        &enabled_keywords,
-        &ordered_packs,
-        keyword_index.as_ref(),
-        &compiled_overrides,
-        &allowlists,
-        &heredoc_settings,
-    );
-    if deny_result.is_allowed() {
-        return false;
+    if cmd.json {
+        let output = serde_json::json!({
+            "status": "ok",
+            "pending": {
+                "wiped": pending_wiped,
+                "preview_maintenance": pending_preview_maintenance,
+                "maintenance": pending_maintenance,
+            },
+            "allow_once": {
+                "wiped": allow_once_wiped,
+                "preview_maintenance": allow_once_preview_maintenance,
+                "maintenance": allow_once_maintenance,
+            },
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
     }
 
-    true
-}
-
-// ============================================================================
-
-/// Allowlist validation diagnostics for doctor command.
-#[derive(Debug, Default)]
-struct AllowlistDiagnostics {
-    /// Number of allowlist layers found (project/user)
-    layers_found: usize,
-    /// Total error count
-    total_errors: usize,
-    /// Total warning count
-    total_warnings: usize,
-    /// Error messages to display
-    error_messages: Vec<String>,
-    /// Warning messages to display
-    warning_messages: Vec<String>,
+    println!("✓ Cleared allow-once stores");
+    println!("  Pending wiped: {pending_wiped}");
+    println!("  Allow-once wiped: {allow_once_wiped}");
+    Ok(())
 }
 
-/// Diagnose allowlist health across project and user layers.
-fn diagnose_allowlists() -> AllowlistDiagnostics {
-    use crate::allowlist::{AllowSelector, AllowlistLayer};
-
-    let mut diag = AllowlistDiagnostics::default();
-
-    // Load all allowlists
-    let allowlist = crate::allowlist::load_default_allowlists();
+fn handle_allow_once_revoke(
+    config: &Config,
+    cmd: &AllowOnceCommand,
+    args: &AllowOnceRevokeArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{self, Write};
 
-    // Check each layer
-    for loaded in &allowlist.layers {
-        // Skip system layer in doctor (less common)
-        if loaded.layer == AllowlistLayer::System {
-            continue;
-        }
+    if cmd.json && !cmd.yes {
+        return Err("JSON output requires --yes to avoid interactive prompts.".into());
+    }
 
-        // Count as found if path exists
-        let path = match loaded.layer {
-            AllowlistLayer::Project => {
-                if let Some(repo_root) = find_repo_root_from_cwd() {
-                    repo_root.join(".dcg").join("allowlist.toml")
-                } else {
-                    continue;
-                }
-            }
-            AllowlistLayer::User => config_dir().join("allowlist.toml"),
-            AllowlistLayer::System => continue,
-        };
+    let now = Utc::now();
+    let cwd = std::env::current_dir().unwrap_or_default();
 
-        if !path.exists() {
-            continue;
-        }
+    let pending_store = PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&cwd)));
+    let allow_once_store = AllowOnceStore::new(AllowOnceStore::default_path(Some(&cwd)));
 
-        diag.layers_found += 1;
-        let layer_label = loaded.layer.label();
+    let (pending_preview, _) = pending_store.preview_active(now)?;
+    let (allow_once_preview, _) = allow_once_store.preview_active(now)?;
+    let full_hash =
+        resolve_allow_once_revoke_target(&args.target, &pending_preview, &allow_once_preview)?;
 
-        // Report parse errors
-        for err in &loaded.file.errors {
-            diag.total_errors += 1;
-            diag.error_messages
-                .push(format!("{layer_label}: {}", err.message));
+    if !cmd.json && !cmd.yes {
+        println!("Allow-once revoke confirmation:");
+        println!("  target: {}", args.target);
+        println!("  resolved_full_hash: {full_hash}");
+        print!("Proceed? [y/N]: ");
+        io::stdout().flush()?;
+        let mut response = String::new();
+        io::stdin().read_line(&mut response)?;
+        let response = response.trim().to_lowercase();
+        if response != "y" && response != "yes" {
+            return Err("Aborted.".into());
         }
+    }
 
-        // Check entries
-        for (idx, entry) in loaded.file.entries.iter().enumerate() {
-            let entry_num = idx + 1;
-
-            // Check for expired entries
-            if let Some(expires_at) = &entry.expires_at {
-                if is_expired(expires_at) {
-                    diag.total_warnings += 1;
-                    diag.warning_messages.push(format!(
-                        "{layer_label}: entry {entry_num} expired ({expires_at})"
-                    ));
-                }
-            }
+    let (pending_removed, pending_maintenance) =
+        pending_store.remove_by_full_hash(&full_hash, now)?;
+    let (allow_once_removed, allow_once_maintenance) =
+        allow_once_store.remove_by_source_full_hash(&full_hash, now)?;
 
-            // Check for risky regex patterns without acknowledgement
-            if matches!(entry.selector, AllowSelector::RegexPattern(_)) && !entry.risk_acknowledged
-            {
-                diag.total_warnings += 1;
-                diag.warning_messages.push(format!(
-                    "{layer_label}: entry {entry_num} uses regex without risk_acknowledged"
-                ));
-            }
+    if let Some(log_file) = config.general.log_file.as_deref() {
+        let _ = crate::pending_exceptions::log_allow_once_action(
+            log_file,
+            "revoke",
+            &format!(
+                "target={}, full_hash={}, pending_removed={}, allow_once_removed={}",
+                args.target, full_hash, pending_removed, allow_once_removed
+            ),
+        );
+    }
 
-            // Check for overly broad wildcards
-            if let AllowSelector::Rule(rule_id) = &entry.selector {
-                if rule_id.pack_id == "*" {
-                    diag.total_errors += 1;
-                    diag.error_messages.push(format!(
-                        "{layer_label}: entry {entry_num} uses dangerous global wildcard (*:*)"
-                    ));
-                } else if rule_id.pattern_name == "*" {
-                    diag.total_warnings += 1;
-                    diag.warning_messages.push(format!(
-                        "{layer_label}: entry {entry_num} uses pack wildcard ({}:*)",
-                        rule_id.pack_id
-                    ));
-                }
-            }
-        }
+    if cmd.json {
+        let output = serde_json::json!({
+            "status": "ok",
+            "target": &args.target,
+            "full_hash": full_hash,
+            "pending": { "removed": pending_removed, "maintenance": pending_maintenance },
+            "allow_once": { "removed": allow_once_removed, "maintenance": allow_once_maintenance },
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
     }
 
-    diag
+    println!("✓ Revoked allow-once exception");
+    println!("  Pending removed: {pending_removed}");
+    println!("  Allow-once removed: {allow_once_removed}");
+    Ok(())
 }
-// Allowlist CLI implementation
-// ============================================================================
 
-use crate::allowlist::{AllowEntry, AllowSelector, AllowlistLayer, RuleId};
+pub(crate) fn resolve_allow_once_revoke_target(
+    target: &str,
+    pending: &[PendingExceptionRecord],
+    allow_once: &[AllowOnceEntry],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut matches: Vec<String> = Vec::new();
 
-/// Resolve which allowlist layer to use based on CLI flags.
-///
-/// Default: project if in a git repo, otherwise user.
-fn resolve_layer(project: bool, user: bool) -> AllowlistLayer {
-    if user {
-        AllowlistLayer::User
-    } else if project {
-        AllowlistLayer::Project
+    // Short codes are 5-digit numeric strings; anything else is a hash prefix
+    let is_short_code = target.len() <= 5 && target.chars().all(|c| c.is_ascii_digit());
+
+    if is_short_code {
+        matches.extend(
+            pending
+                .iter()
+                .filter(|record| record.short_code == target)
+                .map(|record| record.full_hash.clone()),
+        );
+        matches.extend(
+            allow_once
+                .iter()
+                .filter(|entry| entry.source_short_code == target)
+                .map(|entry| entry.source_full_hash.clone()),
+        );
     } else {
-        // Default: project if we can detect a git repo, otherwise user
-        if find_repo_root_from_cwd().is_some() {
-            AllowlistLayer::Project
-        } else {
-            AllowlistLayer::User
-        }
+        matches.extend(
+            pending
+                .iter()
+                .filter(|record| record.full_hash.starts_with(target))
+                .map(|record| record.full_hash.clone()),
+        );
+        matches.extend(
+            allow_once
+                .iter()
+                .filter(|entry| entry.source_full_hash.starts_with(target))
+                .map(|entry| entry.source_full_hash.clone()),
+        );
     }
-}
 
-/// Find the repo root from the current working directory.
-fn find_repo_root_from_cwd() -> Option<std::path::PathBuf> {
-    let cwd = std::env::current_dir().ok()?;
-    crate::config::find_repo_root(&cwd, crate::config::REPO_ROOT_SEARCH_MAX_HOPS)
+    matches.sort();
+    matches.dedup();
+
+    match matches.as_slice() {
+        [] => Err(format!("No allow-once exception found matching '{target}'.").into()),
+        [one] => Ok(one.clone()),
+        many => Err(format!(
+            "Ambiguous allow-once revoke target '{target}'. Matches: {}",
+            many.join(", ")
+        )
+        .into()),
+    }
 }
 
-/// Get the path to the allowlist file for a given layer.
-fn allowlist_path_for_layer(layer: AllowlistLayer) -> std::path::PathBuf {
-    match layer {
-        AllowlistLayer::Project => {
-            let repo_root = find_repo_root_from_cwd()
-                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
-            repo_root.join(".dcg").join("allowlist.toml")
+fn select_pending_entry<'a>(
+    matches: &'a [PendingExceptionRecord],
+    cmd: &AllowOnceCommand,
+) -> Result<&'a PendingExceptionRecord, Box<dyn std::error::Error>> {
+    if matches.len() == 1 {
+        return Ok(&matches[0]);
+    }
+
+    if let Some(hash) = cmd.hash.as_deref() {
+        let record = matches
+            .iter()
+            .find(|record| record.full_hash == hash)
+            .ok_or_else(|| format!("No pending entry with hash '{hash}'"))?;
+        return Ok(record);
+    }
+
+    if let Some(pick) = cmd.pick {
+        if pick == 0 || pick > matches.len() {
+            return Err(format!("Pick must be between 1 and {}", matches.len()).into());
         }
-        AllowlistLayer::User => config_dir().join("allowlist.toml"),
-        AllowlistLayer::System => std::path::PathBuf::from("/etc/dcg/allowlist.toml"),
+        return Ok(&matches[pick - 1]);
     }
+
+    print_pending_choices(matches, cmd.show_raw);
+    Err("Multiple pending entries share this code; use --pick or --hash.".into())
 }
 
-/// Handle allowlist subcommand dispatch.
-fn handle_allowlist_command(action: AllowlistAction) -> Result<(), Box<dyn std::error::Error>> {
-    match action {
-        AllowlistAction::Add {
-            rule_id,
-            reason,
-            project,
-            user,
-            expires,
-            conditions,
-        } => {
-            let layer = resolve_layer(project, user);
-            allowlist_add_rule(&rule_id, &reason, layer, expires.as_deref(), &conditions)?;
-        }
-        AllowlistAction::AddCommand {
-            command,
-            reason,
-            project,
-            user,
-            expires,
-        } => {
-            let layer = resolve_layer(project, user);
-            allowlist_add_command(&command, &reason, layer, expires.as_deref())?;
-        }
-        AllowlistAction::List {
-            project,
-            user,
-            format,
-        } => {
-            allowlist_list(project, user, format)?;
-        }
-        AllowlistAction::Remove {
-            rule_id,
-            project,
-            user,
-        } => {
-            let layer = resolve_layer(project, user);
-            allowlist_remove(&rule_id, layer)?;
-        }
-        AllowlistAction::Validate {
-            project,
-            user,
-            strict,
-        } => {
-            allowlist_validate(project, user, strict)?;
-        }
+fn print_pending_choices(matches: &[PendingExceptionRecord], show_raw: bool) {
+    println!("Multiple pending entries match this code:");
+    for (idx, record) in matches.iter().enumerate() {
+        let display_command = if show_raw {
+            record.command_raw.as_str()
+        } else {
+            record.command_redacted.as_str()
+        };
+        println!(
+            "  {}. [{}] {} (cwd: {}, created: {})",
+            idx + 1,
+            &record.full_hash[..8.min(record.full_hash.len())],
+            display_command,
+            record.cwd,
+            record.created_at
+        );
     }
-    Ok(())
 }
 
-#[allow(clippy::too_many_lines)]
-fn handle_allow_once_command(
-    config: &Config,
-    cmd: &AllowOnceCommand,
+/// Add a rule to the allowlist.
+fn allowlist_add_rule(
+    rule_id: &str,
+    reason: &str,
+    layer: AllowlistLayer,
+    expires: Option<&str>,
+    conditions: &[String],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use std::io::{self, Write};
+    use colored::Colorize;
 
-    if let Some(action) = &cmd.action {
-        match action {
-            AllowOnceAction::List => return handle_allow_once_list(config, cmd),
-            AllowOnceAction::Clear(args) => return handle_allow_once_clear(config, cmd, args),
-            AllowOnceAction::Revoke(args) => return handle_allow_once_revoke(config, cmd, args),
-        }
+    // Validate rule ID format
+    let mut parsed_rule = RuleId::parse(rule_id)
+        .ok_or_else(|| format!("Invalid rule ID: {rule_id} (expected pack_id:pattern_name)"))?;
+    if let Some(current) = parsed_rule.canonical() {
+        println!(
+            "{} Rule {rule_id} was renamed; adding {current} instead",
+            "Note:".yellow()
+        );
+        parsed_rule = current;
     }
 
-    let Some(code) = cmd.code.as_deref() else {
-        return Err("Missing allow-once code. Usage: dcg allow-once <CODE>".into());
-    };
-
-    let now = Utc::now();
-    let cwd = std::env::current_dir().unwrap_or_default();
-    let pending_path = PendingExceptionStore::default_path(Some(&cwd));
-    let pending_store = PendingExceptionStore::new(pending_path);
+    // Validate expiration date format if provided
+    if let Some(exp) = expires {
+        crate::allowlist::validate_expiration_date(exp)?;
+    }
 
-    let (matches, _maintenance) = pending_store.lookup_by_code(code, now)?;
-    if matches.is_empty() {
-        return Err(
-            format!("No pending exception found for code '{code}'. It may be expired.").into(),
-        );
+    // Validate condition formats
+    for cond in conditions {
+        crate::allowlist::validate_condition(cond)?;
     }
 
-    let selected = select_pending_entry(&matches, cmd)?;
+    let path = allowlist_path_for_layer(layer);
+    let mut doc = load_or_create_allowlist_doc(&path)?;
 
-    let is_config_block = selected.source.as_deref() == Some("ConfigOverride");
-    if is_config_block && !cmd.force {
-        return Err(
-            "This denial came from your config blocklist; re-run with --force to override.".into(),
+    // Check for duplicate
+    if has_rule_entry(&doc, &parsed_rule) {
+        println!(
+            "{} Rule {} already exists in {} allowlist",
+            "Warning:".yellow(),
+            rule_id,
+            layer.label()
         );
-    }
-    if cmd.json && !cmd.yes && !cmd.dry_run {
-        return Err("JSON output requires --yes or --dry-run to avoid prompts.".into());
+        return Ok(());
     }
 
-    let selected_cwd = if selected.cwd == "<unknown>" || selected.cwd.is_empty() {
-        cwd
-    } else {
-        std::path::PathBuf::from(&selected.cwd)
-    };
-    let repo_root =
-        crate::config::find_repo_root(&selected_cwd, crate::config::REPO_ROOT_SEARCH_MAX_HOPS);
-    let (scope_kind, scope_path) = repo_root.map_or_else(
-        || (AllowOnceScopeKind::Cwd, selected_cwd.clone()),
-        |root| (AllowOnceScopeKind::Project, root),
-    );
-    let scope_path_str = scope_path.to_string_lossy().to_string();
+    // Build entry
+    let entry = build_rule_entry(&parsed_rule, reason, expires, conditions);
+    append_entry(&mut doc, entry);
 
-    let entry = AllowOnceEntry::from_pending(
-        selected,
-        now,
-        scope_kind,
-        &scope_path_str,
-        cmd.single_use,
-        cmd.force && is_config_block,
-        &config.logging.redaction,
+    // Write back
+    let before = std::fs::read_to_string(&path).ok();
+    write_allowlist(&path, &doc)?;
+    record_policy_change("allowlist add", &path, before.as_deref());
+
+    println!(
+        "{} Added {} to {} allowlist",
+        "✓".green(),
+        parsed_rule.to_string().cyan(),
+        layer.label()
     );
+    println!("  File: {}", path.display());
 
-    if cmd.json {
-        let output = serde_json::json!({
-            "status": "ok",
-            "code": code,
-            "dry_run": cmd.dry_run,
-            "single_use": cmd.single_use,
-            "force": entry.force_allow_config,
-            "scope_kind": format!("{scope_kind:?}").to_lowercase(),
-            "scope_path": scope_path_str,
-            "command": if cmd.show_raw { selected.command_raw.clone() } else { selected.command_redacted.clone() },
-            "cwd": selected.cwd.clone(),
-            "expires_at": entry.expires_at,
-        });
-        println!("{}", serde_json::to_string_pretty(&output)?);
-        if cmd.dry_run {
-            return Ok(());
-        }
-    } else {
-        let display_command = if cmd.show_raw {
-            selected.command_raw.as_str()
-        } else {
-            selected.command_redacted.as_str()
-        };
-        println!("Allow-once confirmation:");
-        println!("  Command: {display_command}");
-        println!("  CWD: {}", selected.cwd);
-        println!("  Expires: {}", entry.expires_at);
-        println!("  Scope: {scope_kind:?} ({scope_path_str})");
-        if cmd.single_use {
-            println!("  Mode: single-use");
-        } else {
-            println!("  Mode: reusable until expiry");
-        }
+    Ok(())
+}
 
-        let needs_prompt = !(cmd.yes || cmd.dry_run);
-        if needs_prompt {
-            if cmd.force && is_config_block {
-                print!("Type 'FORCE' to confirm override: ");
-                io::stdout().flush()?;
-                let mut response = String::new();
-                io::stdin().read_line(&mut response)?;
-                if response.trim() != "FORCE" {
-                    return Err("Aborted.".into());
-                }
-            } else {
-                print!("Proceed? [y/N]: ");
-                io::stdout().flush()?;
-                let mut response = String::new();
-                io::stdin().read_line(&mut response)?;
-                let response = response.trim().to_lowercase();
-                if response != "y" && response != "yes" {
-                    return Err("Aborted.".into());
-                }
-            }
-        }
+/// Add an exact command to the allowlist.
+fn allowlist_add_command(
+    command: &str,
+    reason: &str,
+    layer: AllowlistLayer,
+    expires: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
 
-        if cmd.dry_run {
-            println!("Dry-run: no allow-once entry written.");
-            return Ok(());
-        }
+    // Validate expiration date format if provided
+    if let Some(exp) = expires {
+        crate::allowlist::validate_expiration_date(exp)?;
     }
 
-    let allow_once_path = AllowOnceStore::default_path(Some(&selected_cwd));
-    let allow_once_store = AllowOnceStore::new(allow_once_path.clone());
-    let _maintenance = allow_once_store.add_entry(&entry, now)?;
+    let path = allowlist_path_for_layer(layer);
+    let mut doc = load_or_create_allowlist_doc(&path)?;
 
-    // Remove the pending exception so it doesn't show up in lists anymore.
-    // This is best-effort (if it fails, the allowed command still works).
-    if let Err(e) = pending_store.remove_by_full_hash(&selected.full_hash, now) {
-        eprintln!("Warning: Failed to remove pending exception: {e}");
+    // Check for duplicate
+    if has_command_entry(&doc, command) {
+        println!(
+            "{} Command already exists in {} allowlist",
+            "Warning:".yellow(),
+            layer.label()
+        );
+        return Ok(());
     }
 
-    if !cmd.json {
-        println!("✓ Allow-once entry created");
-        println!("  File: {}", allow_once_path.display());
-    }
+    // Build entry
+    let entry = build_command_entry(command, reason, expires);
+    append_entry(&mut doc, entry);
+
+    // Write back
+    let before = std::fs::read_to_string(&path).ok();
+    write_allowlist(&path, &doc)?;
+    record_policy_change("allowlist add-command", &path, before.as_deref());
+
+    println!(
+        "{} Added exact command to {} allowlist",
+        "✓".green(),
+        layer.label()
+    );
+    println!("  File: {}", path.display());
 
     Ok(())
 }
 
-fn handle_allow_once_list(
-    _config: &Config,
-    cmd: &AllowOnceCommand,
+/// List allowlist entries.
+fn allowlist_list(
+    project_only: bool,
+    user_only: bool,
+    format: AllowlistOutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let now = Utc::now();
-    let cwd = std::env::current_dir().unwrap_or_default();
+    use colored::Colorize;
 
-    let pending_store = PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&cwd)));
-    let allow_once_store = AllowOnceStore::new(AllowOnceStore::default_path(Some(&cwd)));
+    let layers: Vec<AllowlistLayer> = if project_only {
+        vec![AllowlistLayer::Project]
+    } else if user_only {
+        vec![AllowlistLayer::User]
+    } else {
+        vec![AllowlistLayer::Project, AllowlistLayer::User]
+    };
 
-    let (pending, pending_maintenance) = pending_store.load_active(now)?;
-    let (allow_once, allow_once_maintenance) = allow_once_store.load_active(now)?;
+    let mut all_entries: Vec<(AllowlistLayer, std::path::PathBuf, AllowEntry)> = Vec::new();
 
-    if cmd.json {
-        let output = build_allow_once_list_json(
-            &pending,
-            pending_maintenance,
-            &allow_once,
-            allow_once_maintenance,
-            cmd.show_raw,
-        );
-        println!("{}", serde_json::to_string_pretty(&output)?);
-        return Ok(());
-    }
+    // Load all allowlists once (more efficient than loading per-layer)
+    let allowlist = crate::allowlist::load_default_allowlists();
 
-    println!("Allow-once pending codes: {}", pending.len());
-    if pending.is_empty() {
-        println!("  (none)");
-    } else {
-        for record in &pending {
-            let cmd_display = if cmd.show_raw {
-                record.command_raw.as_str()
-            } else {
-                record.command_redacted.as_str()
-            };
-            println!(
-                "  - {} [{}] {}",
-                record.short_code,
-                &record.full_hash[..8.min(record.full_hash.len())],
-                cmd_display
-            );
+    for layer in layers {
+        let path = allowlist_path_for_layer(layer);
+        if !path.exists() {
+            continue;
+        }
+
+        for loaded in &allowlist.layers {
+            if loaded.layer == layer {
+                for entry in &loaded.file.entries {
+                    all_entries.push((layer, path.clone(), entry.clone()));
+                }
+            }
         }
     }
 
-    println!();
-    println!("Allow-once active entries: {}", allow_once.len());
-    if allow_once.is_empty() {
-        println!("  (none)");
-    } else {
-        for entry in &allow_once {
-            let cmd_display = if cmd.show_raw {
-                entry.command_raw.as_str()
-            } else {
-                entry.command_redacted.as_str()
-            };
-            println!(
-                "  - {} [{}] {}",
-                entry.source_short_code,
-                &entry.source_full_hash[..8.min(entry.source_full_hash.len())],
-                cmd_display
-            );
+    match format {
+        AllowlistOutputFormat::Pretty => {
+            if all_entries.is_empty() {
+                println!("{}", "No allowlist entries found.".yellow());
+                return Ok(());
+            }
+
+            println!("{}", "Allowlist entries:".bold());
+            println!();
+
+            for (layer, path, entry) in &all_entries {
+                let selector_str = match &entry.selector {
+                    AllowSelector::Rule(rule_id) => {
+                        serde_json::json!({"type": "rule", "value": rule_id.to_string()})
+                    }
+                    AllowSelector::ExactCommand(cmd) => {
+                        serde_json::json!({"type": "exact_command", "value": cmd})
+                    }
+                    AllowSelector::CommandPrefix(prefix) => {
+                        serde_json::json!({"type": "command_prefix", "value": prefix})
+                    }
+                    AllowSelector::RegexPattern(re) => {
+                        serde_json::json!({"type": "pattern", "value": re})
+                    }
+                    AllowSelector::UrlPrefix(prefix) => {
+                        serde_json::json!({"type": "url_prefix", "value": prefix})
+                    }
+                };
+
+                println!("  {} [{}]", selector_str, layer.label());
+                println!("    Reason: {}", entry.reason);
+                if let Some(added_by) = &entry.added_by {
+                    println!("    Added by: {added_by}");
+                }
+                if let Some(added_at) = &entry.added_at {
+                    println!("    Added at: {added_at}");
+                }
+                if let Some(expires_at) = &entry.expires_at {
+                    let expired = is_expired(expires_at);
+                    let status = if expired {
+                        "EXPIRED".red().to_string()
+                    } else {
+                        expires_at.clone()
+                    };
+                    println!("    Expires: {status}");
+                }
+                println!("    File: {}", path.display());
+                println!();
+            }
         }
-    }
+        AllowlistOutputFormat::Json => {
+            let json_entries: Vec<serde_json::Value> = all_entries
+                .iter()
+                .map(|(layer, path, entry)| {
+                    let selector = match &entry.selector {
+                        AllowSelector::Rule(rule_id) => {
+                            serde_json::json!({"type": "rule", "value": rule_id.to_string()})
+                        }
+                        AllowSelector::ExactCommand(cmd) => {
+                            serde_json::json!({"type": "exact_command", "value": cmd})
+                        }
+                        AllowSelector::CommandPrefix(prefix) => {
+                            serde_json::json!({"type": "command_prefix", "value": prefix})
+                        }
+                        AllowSelector::RegexPattern(re) => {
+                            serde_json::json!({"type": "pattern", "value": re})
+                        }
+                        AllowSelector::UrlPrefix(prefix) => {
+                            serde_json::json!({"type": "url_prefix", "value": prefix})
+                        }
+                    };
+                    serde_json::json!({
+                        "layer": layer.label(),
+                        "path": path.display().to_string(),
+                        "selector": selector,
+                        "reason": entry.reason,
+                        "added_by": entry.added_by,
+                        "added_at": entry.added_at,
+                        "expires_at": entry.expires_at,
+                    })
+                })
+                .collect();
 
-    if !pending_maintenance.is_empty() || !allow_once_maintenance.is_empty() {
-        println!();
-        println!(
-            "Maintenance: pending(pruned_expired={}, pruned_consumed={}, parse_errors={}), allow_once(pruned_expired={}, pruned_consumed={}, parse_errors={})",
-            pending_maintenance.pruned_expired,
-            pending_maintenance.pruned_consumed,
-            pending_maintenance.parse_errors,
-            allow_once_maintenance.pruned_expired,
-            allow_once_maintenance.pruned_consumed,
-            allow_once_maintenance.parse_errors
-        );
+            println!("{}", serde_json::to_string_pretty(&json_entries)?);
+        }
     }
 
     Ok(())
 }
 
-fn build_allow_once_list_json(
-    pending: &[PendingExceptionRecord],
-    pending_maintenance: crate::pending_exceptions::PendingMaintenance,
-    allow_once: &[AllowOnceEntry],
-    allow_once_maintenance: crate::pending_exceptions::PendingMaintenance,
-    show_raw: bool,
-) -> serde_json::Value {
-    let pending_json: Vec<serde_json::Value> = pending
-        .iter()
-        .map(|record| {
-            serde_json::json!({
-                "short_code": &record.short_code,
-                "full_hash": &record.full_hash,
-                "created_at": &record.created_at,
-                "expires_at": &record.expires_at,
-                "cwd": &record.cwd,
-                "reason": &record.reason,
-                "single_use": record.single_use,
-                "source": record.source.as_deref(),
-                "command": if show_raw { &record.command_raw } else { &record.command_redacted },
-            })
-        })
-        .collect();
-
-    let allow_once_json: Vec<serde_json::Value> = allow_once
-        .iter()
-        .map(|entry| {
-            serde_json::json!({
-                "source_short_code": &entry.source_short_code,
-                "source_full_hash": &entry.source_full_hash,
-                "created_at": &entry.created_at,
-                "expires_at": &entry.expires_at,
-                "scope_kind": format!("{:?}", entry.scope_kind).to_lowercase(),
-                "scope_path": &entry.scope_path,
-                "reason": &entry.reason,
-                "single_use": entry.single_use,
-                "force_allow_config": entry.force_allow_config,
-                "command": if show_raw { &entry.command_raw } else { &entry.command_redacted },
-            })
-        })
-        .collect();
-
-    serde_json::json!({
-        "status": "ok",
-        "pending": {
-            "count": pending_json.len(),
-            "maintenance": pending_maintenance,
-            "entries": pending_json,
-        },
-        "allow_once": {
-            "count": allow_once_json.len(),
-            "maintenance": allow_once_maintenance,
-            "entries": allow_once_json,
-        },
-    })
-}
-
-fn handle_allow_once_clear(
-    config: &Config,
-    cmd: &AllowOnceCommand,
-    args: &AllowOnceClearArgs,
+/// Show how the project allowlist changed since `rev`.
+fn allowlist_diff(
+    rev: &str,
+    format: AllowlistOutputFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use std::io::{self, Write};
+    use crate::allowlist_diff::ChangeKind;
+    use colored::Colorize;
 
-    if cmd.json && !cmd.yes {
-        return Err("JSON output requires --yes to avoid interactive prompts.".into());
+    let path = allowlist_path_for_layer(AllowlistLayer::Project);
+    let dir = path
+        .parent()
+        .ok_or("project allowlist path has no parent directory")?;
+    let repo_dir = dir
+        .ancestors()
+        .find(|candidate| candidate.is_dir())
+        .ok_or("project allowlist directory does not exist")?;
+    let root = git_show_toplevel(repo_dir)?;
+    let canonical_dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let relative = canonical_dir
+        .join("allowlist.toml")
+        .strip_prefix(root.canonicalize().unwrap_or_else(|_| root.clone()))
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        .map_err(|_| format!("{} is not inside the git repository", path.display()))?;
+
+    let verify = std::process::Command::new("git")
+        .current_dir(&root)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{rev}^{{commit}}"))
+        .output()?;
+    if !verify.status.success() {
+        return Err(format!("Unknown git revision: {rev}").into());
     }
 
-    let now = Utc::now();
-    let cwd = std::env::current_dir().unwrap_or_default();
+    // The revision exists, so a failed `git show` means the file is absent there.
+    let show = std::process::Command::new("git")
+        .current_dir(&root)
+        .arg("show")
+        .arg(format!("{rev}:{relative}"))
+        .output()?;
+    let old_content = if show.status.success() {
+        String::from_utf8_lossy(&show.stdout).into_owned()
+    } else {
+        String::new()
+    };
+    let new_content = std::fs::read_to_string(&path).unwrap_or_default();
 
-    let pending_store = PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&cwd)));
-    let allow_once_store = AllowOnceStore::new(AllowOnceStore::default_path(Some(&cwd)));
+    let old_file =
+        crate::allowlist::parse_allowlist_toml(AllowlistLayer::Project, &path, &old_content);
+    let new_file =
+        crate::allowlist::parse_allowlist_toml(AllowlistLayer::Project, &path, &new_content);
+    let changes = crate::allowlist_diff::diff_entries(&old_file.entries, &new_file.entries);
 
-    let wipe_pending = args.all || args.pending;
-    let wipe_allow_once = args.all || args.allow_once;
+    match format {
+        AllowlistOutputFormat::Pretty => {
+            if changes.is_empty() {
+                println!("{}", format!("No allowlist changes since {rev}.").yellow());
+                return Ok(());
+            }
 
-    let (pending_preview, pending_preview_maintenance) = pending_store.preview_active(now)?;
-    let (allow_once_preview, allow_once_preview_maintenance) =
-        allow_once_store.preview_active(now)?;
+            println!(
+                "{} {} ({})",
+                "Allowlist changes since".bold(),
+                rev.bold(),
+                path.display()
+            );
+            println!();
 
-    let pending_wipe_count = if wipe_pending {
-        pending_preview.len()
-    } else {
-        0
-    };
-    let allow_once_wipe_count = if wipe_allow_once {
-        allow_once_preview.len()
-    } else {
-        0
-    };
+            for change in &changes {
+                let (marker, heading) = match change.kind {
+                    ChangeKind::Added => ("+".green(), change.kind.label().green()),
+                    ChangeKind::Removed => ("-".red(), change.kind.label().red()),
+                    ChangeKind::Modified => ("~".yellow(), change.kind.label().yellow()),
+                };
+                println!(
+                    "  {marker} {} {} [{heading}]",
+                    change.selector.kind_label(),
+                    change.selector.value().cyan()
+                );
+                for field in &change.fields {
+                    match (&field.before, &field.after) {
+                        (Some(before), Some(after)) => {
+                            println!("      {}: {before} -> {after}", field.field);
+                        }
+                        (None, Some(value)) | (Some(value), None)
+                            if change.kind != ChangeKind::Modified =>
+                        {
+                            println!("      {}: {value}", field.field);
+                        }
+                        (None, Some(after)) => {
+                            println!("      {}: (unset) -> {after}", field.field);
+                        }
+                        (Some(before), None) => {
+                            println!("      {}: {before} -> (unset)", field.field);
+                        }
+                        (None, None) => {}
+                    }
+                }
+                println!();
+            }
 
-    if !cmd.json && !cmd.yes && (wipe_pending || wipe_allow_once) {
-        println!("Allow-once clear confirmation:");
-        println!("  pending_wipe_active={pending_wipe_count}");
-        println!("  allow_once_wipe_active={allow_once_wipe_count}");
-        print!("Proceed? [y/N]: ");
-        io::stdout().flush()?;
-        let mut response = String::new();
-        io::stdin().read_line(&mut response)?;
-        let response = response.trim().to_lowercase();
-        if response != "y" && response != "yes" {
-            return Err("Aborted.".into());
+            for error in old_file.errors.iter().chain(&new_file.errors) {
+                println!(
+                    "{} skipped invalid entry: [{}] {}",
+                    "Warning:".yellow(),
+                    error.code(),
+                    error.message
+                );
+            }
         }
-    }
-
-    let (pending_wiped, pending_maintenance) = if wipe_pending {
-        pending_store.clear_all(now)?
-    } else {
-        let (_active, maintenance) = pending_store.load_active(now)?;
-        (0, maintenance)
-    };
-    let (allow_once_wiped, allow_once_maintenance) = if wipe_allow_once {
-        allow_once_store.clear_all(now)?
-    } else {
-        let (_active, maintenance) = allow_once_store.load_active(now)?;
-        (0, maintenance)
-    };
-
-    if let Some(log_file) = config.general.log_file.as_deref() {
-        let _ = crate::pending_exceptions::log_allow_once_action(
-            log_file,
-            "clear",
-            &format!(
-                "pending_wiped={pending_wiped}, allow_once_wiped={allow_once_wiped}, flags=all:{} pending:{} allow_once:{}",
-                args.all, args.pending, args.allow_once
-            ),
-        );
-    }
+        AllowlistOutputFormat::Json => {
+            let json_changes: Vec<serde_json::Value> = changes
+                .iter()
+                .map(|change| {
+                    let fields: Vec<serde_json::Value> = change
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            serde_json::json!({
+                                "field": field.field,
+                                "before": field.before,
+                                "after": field.after,
+                            })
+                        })
+                        .collect();
+                    serde_json::json!({
+                        "change": change.kind.label(),
+                        "selector": {
+                            "type": change.selector.kind_label(),
+                            "value": change.selector.value(),
+                        },
+                        "fields": fields,
+                    })
+                })
+                .collect();
 
-    if cmd.json {
-        let output = serde_json::json!({
-            "status": "ok",
-            "pending": {
-                "wiped": pending_wiped,
-                "preview_maintenance": pending_preview_maintenance,
-                "maintenance": pending_maintenance,
-            },
-            "allow_once": {
-                "wiped": allow_once_wiped,
-                "preview_maintenance": allow_once_preview_maintenance,
-                "maintenance": allow_once_maintenance,
-            },
-        });
-        println!("{}", serde_json::to_string_pretty(&output)?);
-        return Ok(());
+            let output = serde_json::json!({
+                "rev": rev,
+                "path": path.display().to_string(),
+                "changes": json_changes,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
     }
 
-    println!("✓ Cleared allow-once stores");
-    println!("  Pending wiped: {pending_wiped}");
-    println!("  Allow-once wiped: {allow_once_wiped}");
     Ok(())
 }
 
-fn handle_allow_once_revoke(
-    config: &Config,
-    cmd: &AllowOnceCommand,
-    args: &AllowOnceRevokeArgs,
+/// Remove a rule from the allowlist.
+fn allowlist_remove(
+    rule_id: &str,
+    layer: AllowlistLayer,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use std::io::{self, Write};
-
-    if cmd.json && !cmd.yes {
-        return Err("JSON output requires --yes to avoid interactive prompts.".into());
-    }
-
-    let now = Utc::now();
-    let cwd = std::env::current_dir().unwrap_or_default();
-
-    let pending_store = PendingExceptionStore::new(PendingExceptionStore::default_path(Some(&cwd)));
-    let allow_once_store = AllowOnceStore::new(AllowOnceStore::default_path(Some(&cwd)));
+    use colored::Colorize;
 
-    let (pending_preview, _) = pending_store.preview_active(now)?;
-    let (allow_once_preview, _) = allow_once_store.preview_active(now)?;
-    let full_hash =
-        resolve_allow_once_revoke_target(&args.target, &pending_preview, &allow_once_preview)?;
+    let parsed_rule = RuleId::parse(rule_id)
+        .ok_or_else(|| format!("Invalid rule ID: {rule_id} (expected pack_id:pattern_name)"))?;
 
-    if !cmd.json && !cmd.yes {
-        println!("Allow-once revoke confirmation:");
-        println!("  target: {}", args.target);
-        println!("  resolved_full_hash: {full_hash}");
-        print!("Proceed? [y/N]: ");
-        io::stdout().flush()?;
-        let mut response = String::new();
-        io::stdin().read_line(&mut response)?;
-        let response = response.trim().to_lowercase();
-        if response != "y" && response != "yes" {
-            return Err("Aborted.".into());
-        }
+    let path = allowlist_path_for_layer(layer);
+    if !path.exists() {
+        println!(
+            "{} No {} allowlist file found at {}",
+            "Warning:".yellow(),
+            layer.label(),
+            path.display()
+        );
+        return Ok(());
     }
 
-    let (pending_removed, pending_maintenance) =
-        pending_store.remove_by_full_hash(&full_hash, now)?;
-    let (allow_once_removed, allow_once_maintenance) =
-        allow_once_store.remove_by_source_full_hash(&full_hash, now)?;
+    let mut doc = load_or_create_allowlist_doc(&path)?;
 
-    if let Some(log_file) = config.general.log_file.as_deref() {
-        let _ = crate::pending_exceptions::log_allow_once_action(
-            log_file,
-            "revoke",
-            &format!(
-                "target={}, full_hash={}, pending_removed={}, allow_once_removed={}",
-                args.target, full_hash, pending_removed, allow_once_removed
-            ),
+    let removed = remove_rule_entry(&mut doc, &parsed_rule);
+    if !removed {
+        println!(
+            "{} Rule {} not found in {} allowlist",
+            "Warning:".yellow(),
+            rule_id,
+            layer.label()
         );
-    }
-
-    if cmd.json {
-        let output = serde_json::json!({
-            "status": "ok",
-            "target": &args.target,
-            "full_hash": full_hash,
-            "pending": { "removed": pending_removed, "maintenance": pending_maintenance },
-            "allow_once": { "removed": allow_once_removed, "maintenance": allow_once_maintenance },
-        });
-        println!("{}", serde_json::to_string_pretty(&output)?);
         return Ok(());
     }
 
-    println!("✓ Revoked allow-once exception");
-    println!("  Pending removed: {pending_removed}");
-    println!("  Allow-once removed: {allow_once_removed}");
+    let before = std::fs::read_to_string(&path).ok();
+    write_allowlist(&path, &doc)?;
+    record_policy_change("allowlist remove", &path, before.as_deref());
+
+    println!(
+        "{} Removed {} from {} allowlist",
+        "✓".green(),
+        rule_id.cyan(),
+        layer.label()
+    );
+
     Ok(())
 }
 
-fn resolve_allow_once_revoke_target(
-    target: &str,
-    pending: &[PendingExceptionRecord],
-    allow_once: &[AllowOnceEntry],
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut matches: Vec<String> = Vec::new();
+/// Path the hook loads a layer's allowlist from (usage keys are tied to it).
+fn loaded_allowlist_path(
+    allowlists: &crate::allowlist::LayeredAllowlist,
+    layer: AllowlistLayer,
+) -> std::path::PathBuf {
+    allowlists
+        .layers
+        .iter()
+        .find(|loaded| loaded.layer == layer)
+        .map_or_else(
+            || allowlist_path_for_layer(layer),
+            |loaded| loaded.path.clone(),
+        )
+}
 
-    // Short codes are 5-digit numeric strings; anything else is a hash prefix
-    let is_short_code = target.len() <= 5 && target.chars().all(|c| c.is_ascii_digit());
+/// Show how often each allowlist entry has fired.
+/// Show the generated project layer.
+fn allowlist_bootstrap(format: AllowlistOutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    use colored::Colorize;
 
-    if is_short_code {
-        matches.extend(
-            pending
-                .iter()
-                .filter(|record| record.short_code == target)
-                .map(|record| record.full_hash.clone()),
-        );
-        matches.extend(
-            allow_once
-                .iter()
-                .filter(|entry| entry.source_short_code == target)
-                .map(|entry| entry.source_full_hash.clone()),
-        );
-    } else {
-        matches.extend(
-            pending
-                .iter()
-                .filter(|record| record.full_hash.starts_with(target))
-                .map(|record| record.full_hash.clone()),
-        );
-        matches.extend(
-            allow_once
+    let enabled = Config::load().general.bootstrap_allowlist;
+    let cwd = std::env::current_dir()?;
+    let bootstrap = crate::allowlist_bootstrap::detect(&cwd);
+
+    match format {
+        AllowlistOutputFormat::Json => {
+            let commands: Vec<serde_json::Value> = bootstrap
                 .iter()
-                .filter(|entry| entry.source_full_hash.starts_with(target))
-                .map(|entry| entry.source_full_hash.clone()),
-        );
+                .flat_map(|bootstrap| &bootstrap.commands)
+                .map(|command| {
+                    serde_json::json!({
+                        "command_prefix": command.prefix,
+                        "source": command.source,
+                    })
+                })
+                .collect();
+            let output = serde_json::json!({
+                "enabled": enabled,
+                "root": bootstrap.as_ref().map(|bootstrap| bootstrap.root.display().to_string()),
+                "context": crate::allowlist::STRING_ARGUMENT_CONTEXT,
+                "commands": commands,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        AllowlistOutputFormat::Pretty => {
+            let Some(bootstrap) = bootstrap else {
+                println!(
+                    "{}",
+                    "No package.json, Cargo.toml or Python project files found.".yellow()
+                );
+                return Ok(());
+            };
+            println!(
+                "{} {}",
+                "Generated allowlist for".bold(),
+                bootstrap.root.display()
+            );
+            if !enabled {
+                println!(
+                    "{}",
+                    "Disabled by general.bootstrap_allowlist = false; these entries are not applied."
+                        .yellow()
+                );
+            }
+            println!();
+            for command in &bootstrap.commands {
+                println!("  {:<32} {}", command.prefix, command.source.dimmed());
+            }
+            println!();
+            println!(
+                "Each prefix covers a single command only, never one chained with &&, ; or |."
+            );
+        }
     }
+    Ok(())
+}
 
-    matches.sort();
-    matches.dedup();
+fn allowlist_stats(
+    project_only: bool,
+    user_only: bool,
+    format: AllowlistOutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::allowlist_usage::{AllowlistUsageStore, usage_key};
+    use colored::Colorize;
 
-    match matches.as_slice() {
-        [] => Err(format!("No allow-once exception found matching '{target}'.").into()),
-        [one] => Ok(one.clone()),
-        many => Err(format!(
-            "Ambiguous allow-once revoke target '{target}'. Matches: {}",
-            many.join(", ")
-        )
-        .into()),
-    }
-}
+    let layers: Vec<AllowlistLayer> = if project_only {
+        vec![AllowlistLayer::Project]
+    } else if user_only {
+        vec![AllowlistLayer::User]
+    } else {
+        vec![AllowlistLayer::Project, AllowlistLayer::User]
+    };
 
-fn select_pending_entry<'a>(
-    matches: &'a [PendingExceptionRecord],
-    cmd: &AllowOnceCommand,
-) -> Result<&'a PendingExceptionRecord, Box<dyn std::error::Error>> {
-    if matches.len() == 1 {
-        return Ok(&matches[0]);
-    }
+    let store = AllowlistUsageStore::new(AllowlistUsageStore::default_path(None));
+    let state = store.load(Utc::now());
+    let allowlist = crate::allowlist::load_default_allowlists();
 
-    if let Some(hash) = cmd.hash.as_deref() {
-        let record = matches
-            .iter()
-            .find(|record| record.full_hash == hash)
-            .ok_or_else(|| format!("No pending entry with hash '{hash}'"))?;
-        return Ok(record);
+    let mut rows = Vec::new();
+    for loaded in &allowlist.layers {
+        if !layers.contains(&loaded.layer) {
+            continue;
+        }
+        for entry in &loaded.file.entries {
+            let key = usage_key(&loaded.path, &entry.selector);
+            let usage = state.get(&key);
+            rows.push(serde_json::json!({
+                "layer": loaded.layer.label(),
+                "path": loaded.path.display().to_string(),
+                "selector": {"type": entry.selector.kind_label(), "value": entry.selector.value()},
+                "reason": entry.reason,
+                "key": key,
+                "count": usage.map_or(0, |u| u.count),
+                "first_used": usage.map(|u| u.first_used.clone()),
+                "last_used": usage.map(|u| u.last_used.clone()),
+            }));
+        }
     }
 
-    if let Some(pick) = cmd.pick {
-        if pick == 0 || pick > matches.len() {
-            return Err(format!("Pick must be between 1 and {}", matches.len()).into());
+    match format {
+        AllowlistOutputFormat::Json => {
+            let output = serde_json::json!({
+                "tracking_since": state.tracking_since,
+                "usage_file": store.path().display().to_string(),
+                "entries": rows,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        AllowlistOutputFormat::Pretty => {
+            if rows.is_empty() {
+                println!("{}", "No allowlist entries found.".yellow());
+                return Ok(());
+            }
+
+            println!(
+                "{} (tracking since {}):",
+                "Allowlist usage".bold(),
+                state.tracking_since
+            );
+            println!();
+            for row in &rows {
+                let count = row["count"].as_u64().unwrap_or(0);
+                println!(
+                    "  {} {} [{}]",
+                    row["selector"]["type"].as_str().unwrap_or_default(),
+                    row["selector"]["value"].as_str().unwrap_or_default().cyan(),
+                    row["layer"].as_str().unwrap_or_default()
+                );
+                match row["last_used"].as_str() {
+                    Some(last_used) => println!("    Uses: {count}  Last used: {last_used}"),
+                    None => println!("    Uses: {}", "never".yellow()),
+                }
+            }
+            println!();
+            println!("Usage file: {}", store.path().display());
         }
-        return Ok(&matches[pick - 1]);
     }
 
-    print_pending_choices(matches, cmd.show_raw);
-    Err("Multiple pending entries share this code; use --pick or --hash.".into())
+    Ok(())
 }
 
-fn print_pending_choices(matches: &[PendingExceptionRecord], show_raw: bool) {
-    println!("Multiple pending entries match this code:");
-    for (idx, record) in matches.iter().enumerate() {
-        let display_command = if show_raw {
-            record.command_raw.as_str()
-        } else {
-            record.command_redacted.as_str()
-        };
-        println!(
-            "  {}. [{}] {} (cwd: {}, created: {})",
-            idx + 1,
-            &record.full_hash[..8.min(record.full_hash.len())],
-            display_command,
-            record.cwd,
-            record.created_at
-        );
-    }
+/// `[[allow]]` entries in `content` (by array index) that have not fired since `cutoff`.
+fn stale_allowlist_entries(
+    content: &str,
+    path: &std::path::Path,
+    state: &crate::allowlist_usage::UsageState,
+    cutoff: chrono::DateTime<Utc>,
+) -> Vec<(usize, String, AllowEntry)> {
+    let Ok(value) = toml::from_str::<toml::Value>(content) else {
+        return Vec::new();
+    };
+    let Some(entries) = value.get("allow").and_then(toml::Value::as_array) else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| {
+            let entry = crate::allowlist::parse_allow_entry(item.as_table()?).ok()?;
+            let key = crate::allowlist_usage::usage_key(path, &entry.selector);
+            state
+                .is_stale(&key, &entry, cutoff)
+                .then_some((idx, key, entry))
+        })
+        .collect()
 }
 
-/// Add a rule to the allowlist.
-fn allowlist_add_rule(
-    rule_id: &str,
-    reason: &str,
-    layer: AllowlistLayer,
-    expires: Option<&str>,
-    conditions: &[String],
+/// Remove allowlist entries that have not fired within `unused_for`.
+fn allowlist_prune(
+    unused_for: &str,
+    layer: AllowlistLayer,
+    dry_run: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::allowlist_usage::{AllowlistUsageStore, unused_cutoff};
     use colored::Colorize;
 
-    // Validate rule ID format
-    let parsed_rule = RuleId::parse(rule_id)
-        .ok_or_else(|| format!("Invalid rule ID: {rule_id} (expected pack_id:pattern_name)"))?;
-
-    // Validate expiration date format if provided
-    if let Some(exp) = expires {
-        crate::allowlist::validate_expiration_date(exp)?;
-    }
+    let now = Utc::now();
+    let cutoff =
+        unused_cutoff(unused_for, now).map_err(|e| format!("Invalid --unused-for: {e}"))?;
 
-    // Validate condition formats
-    for cond in conditions {
-        crate::allowlist::validate_condition(cond)?;
+    let allowlist = crate::allowlist::load_default_allowlists();
+    let path = loaded_allowlist_path(&allowlist, layer);
+    if !path.exists() {
+        println!(
+            "{} No {} allowlist file found at {}",
+            "Warning:".yellow(),
+            layer.label(),
+            path.display()
+        );
+        return Ok(());
     }
 
-    let path = allowlist_path_for_layer(layer);
-    let mut doc = load_or_create_allowlist_doc(&path)?;
+    let store = AllowlistUsageStore::new(AllowlistUsageStore::default_path(None));
+    let state = store.load(now);
+    let content = std::fs::read_to_string(&path)?;
+    let stale = stale_allowlist_entries(&content, &path, &state, cutoff);
 
-    // Check for duplicate
-    if has_rule_entry(&doc, &parsed_rule) {
+    if stale.is_empty() {
         println!(
-            "{} Rule {} already exists in {} allowlist",
-            "Warning:".yellow(),
-            rule_id,
+            "{} No {} allowlist entries unused for {unused_for}",
+            "✓".green(),
             layer.label()
         );
         return Ok(());
     }
 
-    // Build entry
-    let entry = build_rule_entry(&parsed_rule, reason, expires, conditions);
-    append_entry(&mut doc, entry);
+    let verb = if dry_run { "Would remove" } else { "Removing" };
+    println!(
+        "{verb} {} {} allowlist entr{} unused for {unused_for}:",
+        stale.len(),
+        layer.label(),
+        if stale.len() == 1 { "y" } else { "ies" }
+    );
+    for (_, key, entry) in &stale {
+        let last_used = state.get(key).map_or_else(
+            || "never used".to_string(),
+            |u| format!("last used {}", u.last_used),
+        );
+        println!(
+            "  {} {} ({last_used})",
+            entry.selector.kind_label(),
+            entry.selector.value().cyan()
+        );
+    }
 
-    // Write back
+    if dry_run {
+        return Ok(());
+    }
+
+    let mut doc: toml_edit::DocumentMut = content.parse()?;
+    let Some(arr) = doc
+        .get_mut("allow")
+        .and_then(toml_edit::Item::as_array_of_tables_mut)
+    else {
+        return Err("allowlist `allow` must be an array of tables ([[allow]]) to prune".into());
+    };
+    for (idx, _, _) in stale.iter().rev() {
+        arr.remove(*idx);
+    }
+    let before = std::fs::read_to_string(&path).ok();
     write_allowlist(&path, &doc)?;
+    record_policy_change("allowlist prune", &path, before.as_deref());
 
-    println!(
-        "{} Added {} to {} allowlist",
-        "✓".green(),
-        rule_id.cyan(),
-        layer.label()
-    );
-    println!("  File: {}", path.display());
+    let keys: Vec<String> = stale.into_iter().map(|(_, key, _)| key).collect();
+    let _ = store.forget(&keys, now);
 
+    println!("{} Updated {}", "✓".green(), path.display());
     Ok(())
 }
 
-/// Add an exact command to the allowlist.
-fn allowlist_add_command(
-    command: &str,
-    reason: &str,
+/// Point `rule` selectors that name renamed patterns at their current IDs.
+///
+/// Returns `(old, new)` for each rewritten entry, in file order.
+fn rename_deprecated_rule_ids(doc: &mut toml_edit::DocumentMut) -> Vec<(RuleId, RuleId)> {
+    let mut renamed = Vec::new();
+    let Some(arr) = doc
+        .get_mut("allow")
+        .and_then(toml_edit::Item::as_array_of_tables_mut)
+    else {
+        return renamed;
+    };
+    for tbl in arr.iter_mut() {
+        let Some(old) = tbl
+            .get("rule")
+            .and_then(toml_edit::Item::as_str)
+            .and_then(RuleId::parse)
+        else {
+            continue;
+        };
+        let Some(new) = old.canonical() else {
+            continue;
+        };
+        if let Some(value) = tbl.get_mut("rule").and_then(toml_edit::Item::as_value_mut) {
+            let decor = value.decor().clone();
+            *value = new.to_string().into();
+            *value.decor_mut() = decor;
+        }
+        renamed.push((old, new));
+    }
+    renamed
+}
+
+/// Handle `dcg allowlist migrate-ids`.
+fn allowlist_migrate_ids(
     layer: AllowlistLayer,
-    expires: Option<&str>,
+    dry_run: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use colored::Colorize;
 
-    // Validate expiration date format if provided
-    if let Some(exp) = expires {
-        crate::allowlist::validate_expiration_date(exp)?;
+    let allowlist = crate::allowlist::load_default_allowlists();
+    let path = loaded_allowlist_path(&allowlist, layer);
+    if !path.exists() {
+        println!(
+            "{} No {} allowlist file found at {}",
+            "Warning:".yellow(),
+            layer.label(),
+            path.display()
+        );
+        return Ok(());
     }
 
-    let path = allowlist_path_for_layer(layer);
-    let mut doc = load_or_create_allowlist_doc(&path)?;
-
-    // Check for duplicate
-    if has_command_entry(&doc, command) {
+    let content = std::fs::read_to_string(&path)?;
+    let mut doc: toml_edit::DocumentMut = content.parse()?;
+    let renamed = rename_deprecated_rule_ids(&mut doc);
+    if renamed.is_empty() {
         println!(
-            "{} Command already exists in {} allowlist",
-            "Warning:".yellow(),
+            "{} No deprecated rule IDs in {} allowlist",
+            "✓".green(),
             layer.label()
         );
         return Ok(());
     }
 
-    // Build entry
-    let entry = build_command_entry(command, reason, expires);
-    append_entry(&mut doc, entry);
-
-    // Write back
-    write_allowlist(&path, &doc)?;
-
+    let verb = if dry_run {
+        "Would rewrite"
+    } else {
+        "Rewriting"
+    };
     println!(
-        "{} Added exact command to {} allowlist",
-        "✓".green(),
+        "{verb} {} deprecated rule ID{} in {} allowlist:",
+        renamed.len(),
+        if renamed.len() == 1 { "" } else { "s" },
         layer.label()
     );
-    println!("  File: {}", path.display());
+    for (old, new) in &renamed {
+        println!(
+            "  {} -> {}",
+            old.to_string().dimmed(),
+            new.to_string().cyan()
+        );
+    }
+    if dry_run {
+        return Ok(());
+    }
 
+    write_allowlist(&path, &doc)?;
+    record_policy_change("allowlist migrate-ids", &path, Some(&content));
+    println!("{} Updated {}", "✓".green(), path.display());
     Ok(())
 }
 
-/// List allowlist entries.
-fn allowlist_list(
-    project_only: bool,
-    user_only: bool,
-    format: AllowlistOutputFormat,
+/// Write a layer's valid entries for `allowlist import`.
+fn allowlist_export(
+    format: crate::allowlist_exchange::ExchangeFormat,
+    layer: AllowlistLayer,
+    output: Option<&std::path::Path>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::allowlist_exchange::{allow_tables, export};
     use colored::Colorize;
 
-    let layers: Vec<AllowlistLayer> = if project_only {
-        vec![AllowlistLayer::Project]
-    } else if user_only {
-        vec![AllowlistLayer::User]
-    } else {
-        vec![AllowlistLayer::Project, AllowlistLayer::User]
-    };
-
-    let mut all_entries: Vec<(AllowlistLayer, std::path::PathBuf, AllowEntry)> = Vec::new();
-
-    // Load all allowlists once (more efficient than loading per-layer)
-    let allowlist = crate::allowlist::load_default_allowlists();
-
-    for layer in layers {
-        let path = allowlist_path_for_layer(layer);
-        if !path.exists() {
-            continue;
-        }
-
-        for loaded in &allowlist.layers {
-            if loaded.layer == layer {
-                for entry in &loaded.file.entries {
-                    all_entries.push((layer, path.clone(), entry.clone()));
-                }
-            }
-        }
+    let path = allowlist_path_for_layer(layer);
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    let (tables, skipped) =
+        allow_tables(&content).map_err(|e| format!("{}: {e}", path.display()))?;
+    for message in &skipped {
+        eprintln!("{} skipped invalid {message}", "Warning:".yellow());
     }
 
-    match format {
-        AllowlistOutputFormat::Pretty => {
-            if all_entries.is_empty() {
-                println!("{}", "No allowlist entries found.".yellow());
-                return Ok(());
-            }
-
-            println!("{}", "Allowlist entries:".bold());
-            println!();
-
-            for (layer, path, entry) in &all_entries {
-                let selector_str = match &entry.selector {
-                    AllowSelector::Rule(rule_id) => {
-                        serde_json::json!({"type": "rule", "value": rule_id.to_string()})
-                    }
-                    AllowSelector::ExactCommand(cmd) => {
-                        serde_json::json!({"type": "exact_command", "value": cmd})
-                    }
-                    AllowSelector::CommandPrefix(prefix) => {
-                        serde_json::json!({"type": "command_prefix", "value": prefix})
-                    }
-                    AllowSelector::RegexPattern(re) => {
-                        serde_json::json!({"type": "pattern", "value": re})
-                    }
-                };
-
-                println!("  {} [{}]", selector_str, layer.label());
-                println!("    Reason: {}", entry.reason);
-                if let Some(added_by) = &entry.added_by {
-                    println!("    Added by: {added_by}");
-                }
-                if let Some(added_at) = &entry.added_at {
-                    println!("    Added at: {added_at}");
-                }
-                if let Some(expires_at) = &entry.expires_at {
-                    let expired = is_expired(expires_at);
-                    let status = if expired {
-                        "EXPIRED".red().to_string()
-                    } else {
-                        expires_at.clone()
-                    };
-                    println!("    Expires: {status}");
-                }
-                println!("    File: {}", path.display());
-                println!();
-            }
-        }
-        AllowlistOutputFormat::Json => {
-            let json_entries: Vec<serde_json::Value> = all_entries
-                .iter()
-                .map(|(layer, path, entry)| {
-                    let selector = match &entry.selector {
-                        AllowSelector::Rule(rule_id) => {
-                            serde_json::json!({"type": "rule", "value": rule_id.to_string()})
-                        }
-                        AllowSelector::ExactCommand(cmd) => {
-                            serde_json::json!({"type": "exact_command", "value": cmd})
-                        }
-                        AllowSelector::CommandPrefix(prefix) => {
-                            serde_json::json!({"type": "command_prefix", "value": prefix})
-                        }
-                        AllowSelector::RegexPattern(re) => {
-                            serde_json::json!({"type": "pattern", "value": re})
-                        }
-                    };
-                    serde_json::json!({
-                        "layer": layer.label(),
-                        "path": path.display().to_string(),
-                        "selector": selector,
-                        "reason": entry.reason,
-                        "added_by": entry.added_by,
-                        "added_at": entry.added_at,
-                        "expires_at": entry.expires_at,
-                    })
-                })
-                .collect();
-
-            println!("{}", serde_json::to_string_pretty(&json_entries)?);
+    let exported = export(&tables, format)?;
+    match output {
+        Some(output) => {
+            std::fs::write(output, exported)?;
+            eprintln!(
+                "{} Exported {} {} allowlist entr{} to {}",
+                "✓".green(),
+                tables.len(),
+                layer.label(),
+                if tables.len() == 1 { "y" } else { "ies" },
+                output.display()
+            );
         }
+        None => print!("{exported}"),
     }
-
     Ok(())
 }
 
-/// Remove a rule from the allowlist.
-fn allowlist_remove(
-    rule_id: &str,
+/// Merge an exported allowlist into `layer`.
+#[allow(clippy::too_many_lines)]
+fn allowlist_import(
+    file: &std::path::Path,
+    format: crate::allowlist_exchange::ExchangeFormat,
     layer: AllowlistLayer,
+    strategy: Option<crate::allowlist_exchange::MergeStrategy>,
+    dry_run: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::allowlist_exchange::{MergeStrategy, parse_import, plan_import, to_edit_table};
     use colored::Colorize;
+    use std::io::{self, Write};
 
-    let parsed_rule = RuleId::parse(rule_id)
-        .ok_or_else(|| format!("Invalid rule ID: {rule_id} (expected pack_id:pattern_name)"))?;
+    let content = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+    let imported = parse_import(&content, format).map_err(|e| {
+        format!(
+            "Nothing imported; {} has invalid entries:\n{e}",
+            file.display()
+        )
+    })?;
 
     let path = allowlist_path_for_layer(layer);
-    if !path.exists() {
+    let mut doc = load_or_create_allowlist_doc(&path)?;
+    if doc
+        .get("allow")
+        .is_some_and(|allow| !allow.is_array_of_tables())
+    {
+        return Err("allowlist `allow` must be an array of tables ([[allow]]) to import".into());
+    }
+    let existing: Vec<toml::Value> = toml::from_str::<toml::Table>(&doc.to_string())?
+        .remove("allow")
+        .and_then(|allow| match allow {
+            toml::Value::Array(items) => Some(items),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let plan = plan_import(&existing, imported);
+
+    let describe = |conflict: &crate::allowlist_exchange::Conflict| {
         println!(
-            "{} No {} allowlist file found at {}",
-            "Warning:".yellow(),
+            "  {} {} {}",
+            "~".yellow(),
+            conflict.selector.kind_label(),
+            conflict.selector.value().cyan()
+        );
+        for change in &conflict.changes {
+            println!(
+                "      {}: {} -> {}",
+                change.field,
+                change.before.as_deref().unwrap_or("(unset)"),
+                change.after.as_deref().unwrap_or("(unset)")
+            );
+        }
+    };
+
+    if !plan.conflicts.is_empty() && strategy.is_none() {
+        println!(
+            "{} {} imported {} with the {} allowlist ({}):",
+            "Conflict:".red(),
+            plan.conflicts.len(),
+            if plan.conflicts.len() == 1 {
+                "entry conflicts"
+            } else {
+                "entries conflict"
+            },
             layer.label(),
             path.display()
         );
-        return Ok(());
-    }
-
-    let mut doc = load_or_create_allowlist_doc(&path)?;
-
-    let removed = remove_rule_entry(&mut doc, &parsed_rule);
-    if !removed {
-        println!(
-            "{} Rule {} not found in {} allowlist",
-            "Warning:".yellow(),
-            rule_id,
-            layer.label()
+        for conflict in &plan.conflicts {
+            describe(conflict);
+        }
+        return Err(
+            "Re-run with --merge-strategy theirs|ours|interactive to resolve conflicts".into(),
         );
-        return Ok(());
     }
 
-    write_allowlist(&path, &doc)?;
+    let mut replaced = 0;
+    let mut kept = 0;
+    for conflict in &plan.conflicts {
+        let take_theirs = match strategy {
+            Some(MergeStrategy::Theirs) => true,
+            Some(MergeStrategy::Ours) | None => false,
+            Some(MergeStrategy::Interactive) => {
+                describe(conflict);
+                print!("  Keep [o]urs or take [t]heirs? [o/t]: ");
+                io::stdout().flush()?;
+                let mut response = String::new();
+                io::stdin().read_line(&mut response)?;
+                matches!(response.trim().to_lowercase().as_str(), "t" | "theirs")
+            }
+        };
+        if !take_theirs {
+            kept += 1;
+            continue;
+        }
+        replaced += 1;
+        if let Some(table) = doc
+            .get_mut("allow")
+            .and_then(toml_edit::Item::as_array_of_tables_mut)
+            .and_then(|arr| arr.get_mut(conflict.existing_index))
+        {
+            *table = to_edit_table(&conflict.imported);
+        }
+    }
+    for table in &plan.added {
+        append_entry(&mut doc, to_edit_table(table));
+    }
 
+    let verb = if dry_run { "Would import" } else { "Imported" };
     println!(
-        "{} Removed {} from {} allowlist",
-        "✓".green(),
-        rule_id.cyan(),
-        layer.label()
+        "{verb} into {} allowlist: {} added, {replaced} replaced, {kept} kept, {} unchanged",
+        layer.label(),
+        plan.added.len(),
+        plan.unchanged
     );
+    for table in &plan.added {
+        if let Ok(entry) = crate::allowlist::parse_allow_entry(table) {
+            println!(
+                "  {} {} {}",
+                "+".green(),
+                entry.selector.kind_label(),
+                entry.selector.value().cyan()
+            );
+        }
+    }
+    if dry_run || (plan.added.is_empty() && replaced == 0) {
+        return Ok(());
+    }
 
+    let before = std::fs::read_to_string(&path).ok();
+    write_allowlist(&path, &doc)?;
+    record_policy_change("allowlist import", &path, before.as_deref());
+    println!("{} Updated {}", "✓".green(), path.display());
     Ok(())
 }
 
@@ -10091,10 +15522,21 @@ fn allowlist_validate(
 
             // Report parse errors
             for err in &loaded.file.errors {
-                println!("  {} {}", "ERROR:".red(), err.message);
+                println!("  {} [{}] {}", "ERROR:".red(), err.code(), err.message);
                 errors += 1;
             }
 
+            for renamed in &loaded.file.renamed_rules {
+                println!(
+                    "  {} Entry {} uses deprecated rule ID {} (now {}); run `dcg allowlist migrate-ids`",
+                    "WARNING:".yellow(),
+                    renamed.entry_index + 1,
+                    renamed.old,
+                    renamed.new
+                );
+                warnings += 1;
+            }
+
             // Check entries
             for (idx, entry) in loaded.file.entries.iter().enumerate() {
                 // Check for expired entries
@@ -10122,6 +15564,19 @@ fn allowlist_validate(
                     warnings += 1;
                 }
 
+                // Check for context values that can never match
+                if let Some(context) = entry.context.as_deref() {
+                    if context != crate::allowlist::STRING_ARGUMENT_CONTEXT {
+                        println!(
+                            "  {} Entry {} has unknown context '{}' and never matches",
+                            "WARNING:".yellow(),
+                            idx + 1,
+                            context
+                        );
+                        warnings += 1;
+                    }
+                }
+
                 // Check for overly broad wildcards
                 if let AllowSelector::Rule(rule_id) = &entry.selector {
                     if rule_id.pack_id == "*" {
@@ -10233,6 +15688,97 @@ fn write_allowlist(
     Ok(())
 }
 
+/// Handle `dcg undo`.
+fn handle_undo_command(
+    list: bool,
+    dry_run: bool,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::journal::{Journal, UndoEffect};
+    use colored::Colorize;
+
+    let journal = Journal::open_default();
+
+    if list {
+        let entries = journal.entries();
+        if entries.is_empty() {
+            println!("{}", "No journaled changes.".yellow());
+            return Ok(());
+        }
+        println!("{} (newest first):", "Journaled changes".bold());
+        for entry in entries.iter().rev() {
+            let kind = if entry.before.is_some() {
+                "modified"
+            } else {
+                "created"
+            };
+            let by = entry
+                .user
+                .as_deref()
+                .map(|user| format!(" (by {user})"))
+                .unwrap_or_default();
+            println!(
+                "  {}  dcg {}  {} {}{by}",
+                entry.created_at,
+                entry.action.cyan(),
+                kind,
+                entry.path.display()
+            );
+        }
+        return Ok(());
+    }
+
+    if dry_run {
+        let Some(entry) = journal.latest() else {
+            println!("{}", "Nothing to undo.".yellow());
+            return Ok(());
+        };
+        let verb = if entry.before.is_some() {
+            "restore"
+        } else {
+            "remove"
+        };
+        println!(
+            "Would {verb} {} (changed by `dcg {}` at {})",
+            entry.path.display(),
+            entry.action,
+            entry.created_at
+        );
+        return Ok(());
+    }
+
+    match journal.undo_latest(force) {
+        Ok((entry, effect)) => {
+            let verb = match effect {
+                UndoEffect::Restored => "Restored",
+                UndoEffect::Removed => "Removed",
+            };
+            println!(
+                "{} {verb} {} (undid `dcg {}` from {})",
+                "✓".green(),
+                entry.path.display(),
+                entry.action,
+                entry.created_at
+            );
+            Ok(())
+        }
+        Err(crate::journal::UndoError::Empty) => {
+            println!("{}", "Nothing to undo.".yellow());
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Record a policy-file write in the undo journal (see `dcg undo`).
+///
+/// Journaling is best-effort: a failure is reported but never fails the command.
+fn record_policy_change(action: &str, path: &std::path::Path, before: Option<&str>) {
+    if let Err(e) = crate::journal::Journal::open_default().record_change(action, path, before) {
+        eprintln!("Warning: could not record change in undo journal: {e}");
+    }
+}
+
 /// Check if a rule entry already exists in the document.
 fn has_rule_entry(doc: &toml_edit::DocumentMut, rule_id: &RuleId) -> bool {
     let Some(allow) = doc.get("allow") else {
@@ -10413,7 +15959,52 @@ fn allowlist_add_pattern(
     append_entry(&mut doc, entry);
 
     // Write atomically (temp file + rename to prevent corruption)
+    let before = std::fs::read_to_string(&path).ok();
+    write_allowlist(&path, &doc)?;
+    record_policy_change("suggest-allowlist", &path, before.as_deref());
+
+    Ok(path)
+}
+
+/// Add a regex pattern marked safe in `dcg review` to the allowlist.
+///
+/// Returns Ok(path) on success, or Err on failure.
+fn allowlist_add_reviewed_pattern(
+    pattern: &str,
+    reason: &str,
+    action: &str,
+) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let layer = if find_repo_root_from_cwd().is_some() {
+        AllowlistLayer::Project
+    } else {
+        AllowlistLayer::User
+    };
+
+    let path = allowlist_path_for_layer(layer);
+    let mut doc = load_or_create_allowlist_doc(&path)?;
+
+    if has_pattern_entry(&doc, pattern) {
+        return Err(format!(
+            "Pattern '{}' already exists in {} allowlist",
+            pattern,
+            layer.label()
+        )
+        .into());
+    }
+
+    let mut entry = toml_edit::Table::new();
+    entry.insert("pattern", toml_edit::value(pattern));
+    entry.insert("reason", toml_edit::value(reason));
+    if let Some(user) = get_current_user() {
+        entry.insert("added_by", toml_edit::value(user));
+    }
+    entry.insert("added_at", toml_edit::value(current_timestamp()));
+    entry.insert("risk_acknowledged", toml_edit::value(true));
+    append_entry(&mut doc, entry);
+
+    let before = std::fs::read_to_string(&path).ok();
     write_allowlist(&path, &doc)?;
+    record_policy_change(action, &path, before.as_deref());
 
     Ok(path)
 }
@@ -10531,7 +16122,7 @@ fn handle_suggest_allowlist_undo(minutes: u32) -> Result<(), Box<dyn std::error:
         ),
         (
             AllowlistLayer::User,
-            dirs::config_dir().map(|d| d.join("dcg").join("allowlist.toml")),
+            Some(allowlist_path_for_layer(AllowlistLayer::User)),
         ),
     ];
 
@@ -10552,7 +16143,9 @@ fn handle_suggest_allowlist_undo(minutes: u32) -> Result<(), Box<dyn std::error:
 
         let removed = remove_auto_suggested_entries(&mut doc, cutoff);
         if removed > 0 {
+            let before = std::fs::read_to_string(&path).ok();
             write_allowlist(&path, &doc)?;
+            record_policy_change("suggest-allowlist --undo", &path, before.as_deref());
             println!(
                 "{} Removed {} auto-suggested pattern(s) from {} allowlist ({})",
                 "✓".green(),
@@ -10671,11 +16264,9 @@ fn remove_rule_entry(doc: &mut toml_edit::DocumentMut, rule_id: &RuleId) -> bool
     arr.len() < initial_len
 }
 
-/// Get the current user (from environment or whoami).
+/// Get the current user: the resolved identity on shared hosts, else the account.
 fn get_current_user() -> Option<String> {
-    std::env::var("USER")
-        .or_else(|_| std::env::var("USERNAME"))
-        .ok()
+    crate::identity::audit_user()
 }
 
 /// Get current timestamp in RFC 3339 format.
@@ -11440,6 +17031,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_pack_audit() {
+        let cli = Cli::parse_from(["dcg", "pack", "audit", "core.git", "--semantic"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Pack {
+                action: PackAction::Audit {
+                    ref packs,
+                    semantic: true,
+                    ..
+                }
+            }) if packs == &["core.git"]
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_pack_new() {
+        let cli = Cli::parse_from(["dcg", "pack", "new", "acme.deploy", "--no-input"]);
+        if let Some(Command::Pack {
+            action:
+                PackAction::New {
+                    pack_id,
+                    builtin,
+                    output_dir,
+                    no_input,
+                    force,
+                },
+        }) = cli.command
+        {
+            assert_eq!(pack_id.as_deref(), Some("acme.deploy"));
+            assert!(!builtin);
+            assert_eq!(output_dir, std::path::PathBuf::from(".dcg/packs"));
+            assert!(no_input);
+            assert!(!force);
+        } else {
+            unreachable!("Expected Pack New command");
+        }
+
+        let cli = Cli::parse_from(["dcg", "pack", "new", "--builtin"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Pack {
+                action: PackAction::New {
+                    pack_id: None,
+                    builtin: true,
+                    ..
+                },
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_fuzz_corpus_export() {
+        let cli = Cli::parse_from(["dcg", "fuzz-corpus", "export", "--no-corpus"]);
+        if let Some(Command::FuzzCorpus {
+            action: FuzzCorpusAction::Export { out, no_corpus, .. },
+        }) = cli.command
+        {
+            assert_eq!(out, std::path::PathBuf::from("fuzz/corpus/fuzz_pipeline"));
+            assert!(no_corpus);
+        } else {
+            unreachable!("Expected FuzzCorpus Export command");
+        }
+    }
+
+    #[test]
+    fn test_register_block_override_appends_once() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let config_path = temp.path().join(".dcg.toml");
+        std::fs::write(
+            &config_path,
+            "# project config\n[overrides]\nblock = [{ pattern = \"shutdown\", reason = \"no\" }]\n",
+        )
+        .expect("write config");
+
+        let pattern = r"^git\s+stash\s+drop$";
+        assert!(register_block_override(&config_path, pattern, "reviewed").unwrap());
+        assert!(!register_block_override(&config_path, pattern, "reviewed").unwrap());
+        assert!(!register_block_override(&config_path, "shutdown", "dup").unwrap());
+
+        let content = std::fs::read_to_string(&config_path).unwrap();
+        assert!(content.starts_with("# project config\n"));
+        let config: Config = toml::from_str(&content).unwrap();
+        let compiled = config.overrides.compile();
+        assert_eq!(compiled.block.len(), 2);
+        assert!(compiled.check_block("git stash drop").is_some());
+    }
+
+    #[test]
+    fn test_register_custom_pack_path_skips_covered_entries() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let config_path = temp.path().join(".dcg.toml");
+        std::fs::write(
+            &config_path,
+            "# project config\n[packs]\nenabled = [\"database\"]\ncustom_paths = [\"tools/*.yaml\"]\n",
+        )
+        .expect("write config");
+
+        assert!(!register_custom_pack_path(&config_path, "tools/deploy.yaml").unwrap());
+        assert!(register_custom_pack_path(&config_path, ".dcg/packs/deploy.yaml").unwrap());
+        assert!(!register_custom_pack_path(&config_path, ".dcg/packs/deploy.yaml").unwrap());
+
+        let content = std::fs::read_to_string(&config_path).unwrap();
+        assert!(content.starts_with("# project config\n"));
+        let config: Config = toml::from_str(&content).unwrap();
+        assert_eq!(
+            config.packs.custom_paths,
+            vec!["tools/*.yaml", ".dcg/packs/deploy.yaml"]
+        );
+        assert_eq!(config.packs.enabled, vec!["database"]);
+    }
+
     #[test]
     fn test_cli_parse_test() {
         let cli = Cli::parse_from(["dcg", "test", "git reset --hard"]);
@@ -11630,29 +17333,265 @@ mod tests {
     }
 
     #[test]
-    fn test_cli_parse_allowlist_list() {
-        let cli = Cli::parse_from(["dcg", "allowlist", "list", "--format", "json"]);
+    fn test_cli_parse_allowlist_list() {
+        let cli = Cli::parse_from(["dcg", "allowlist", "list", "--format", "json"]);
+        if let Some(Command::Allowlist {
+            action: AllowlistAction::List { format, .. },
+        }) = cli.command
+        {
+            assert_eq!(format, AllowlistOutputFormat::Json);
+        } else {
+            unreachable!("Expected Allowlist List command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_allowlist_list_diff() {
+        let cli = Cli::parse_from(["dcg", "allowlist", "list", "--diff", "main"]);
+        if let Some(Command::Allowlist {
+            action: AllowlistAction::List { diff, .. },
+        }) = cli.command
+        {
+            assert_eq!(diff.as_deref(), Some("main"));
+        } else {
+            unreachable!("Expected Allowlist List command");
+        }
+
+        assert!(
+            Cli::try_parse_from(["dcg", "allowlist", "list", "--user", "--diff", "main"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_global_width() {
+        let cli = Cli::parse_from(["dcg", "test", "--width", "48", "git status"]);
+        assert_eq!(cli.width, Some(48));
+
+        assert!(Cli::try_parse_from(["dcg", "--width", "5", "test", "git status"]).is_err());
+        assert!(Cli::try_parse_from(["dcg", "--width", "wide", "test", "git status"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_allowlist_validate() {
+        let cli = Cli::parse_from(["dcg", "allowlist", "validate", "--strict"]);
+        if let Some(Command::Allowlist {
+            action: AllowlistAction::Validate { strict, .. },
+        }) = cli.command
+        {
+            assert!(strict);
+        } else {
+            unreachable!("Expected Allowlist Validate command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_allowlist_bootstrap() {
+        let cli = Cli::parse_from(["dcg", "allowlist", "bootstrap", "--format", "json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Allowlist {
+                action: AllowlistAction::Bootstrap {
+                    format: AllowlistOutputFormat::Json
+                },
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["dcg", "allowlist", "export", "--layer", "generated"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_parse_approvals() {
+        let cli = Cli::parse_from(["dcg", "approvals", "approve", "3", "7", "--single-use"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Approvals {
+                action: ApprovalsAction::Approve {
+                    ref ids,
+                    single_use: true,
+                    force: false,
+                },
+            }) if ids == &[3, 7]
+        ));
+        let cli = Cli::parse_from(["dcg", "approvals", "list", "--all"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Approvals {
+                action: ApprovalsAction::List { all: true, .. },
+            })
+        ));
+        assert!(Cli::try_parse_from(["dcg", "approvals", "reject"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_allowlist_prune() {
+        let cli = Cli::parse_from([
+            "dcg",
+            "allowlist",
+            "prune",
+            "--unused-for",
+            "90d",
+            "--user",
+            "--dry-run",
+        ]);
+        if let Some(Command::Allowlist {
+            action:
+                AllowlistAction::Prune {
+                    unused_for,
+                    user,
+                    dry_run,
+                    ..
+                },
+        }) = cli.command
+        {
+            assert_eq!(unused_for, "90d");
+            assert!(user);
+            assert!(dry_run);
+        } else {
+            unreachable!("Expected Allowlist Prune command");
+        }
+
+        assert!(Cli::try_parse_from(["dcg", "allowlist", "prune"]).is_err());
+        assert!(Cli::try_parse_from(["dcg", "allowlist", "stats", "--format", "json"]).is_ok());
+    }
+
+    #[test]
+    fn test_cli_parse_scan_scripts() {
+        let cli = Cli::try_parse_from(["dcg", "scan-scripts"]).expect("parse scan-scripts");
+        let Some(Command::ScanScripts(cmd)) = cli.command else {
+            panic!("expected scan-scripts command");
+        };
+        assert_eq!(cmd.paths, [std::path::PathBuf::from(".")]);
+        assert_eq!(cmd.fail_on, crate::scan::ScanFailOn::Error);
+
+        let cli = Cli::try_parse_from([
+            "dcg",
+            "scan-scripts",
+            "web",
+            "api/Makefile",
+            "--fail-on",
+            "none",
+            "-f",
+            "json",
+        ])
+        .expect("parse scan-scripts with paths");
+        let Some(Command::ScanScripts(cmd)) = cli.command else {
+            panic!("expected scan-scripts command");
+        };
+        assert_eq!(cmd.paths.len(), 2);
+        assert_eq!(cmd.format, crate::scan::ScanFormat::Json);
+    }
+
+    #[test]
+    fn test_cli_parse_onboard() {
+        let cli = Cli::try_parse_from([
+            "dcg",
+            "onboard",
+            "--analyze",
+            "--path",
+            "app",
+            "--no-history",
+            "-o",
+            "starter.toml",
+        ])
+        .expect("parse onboard");
+        let Some(Command::Onboard(cmd)) = cli.command else {
+            panic!("expected onboard command");
+        };
+        assert!(cmd.analyze);
+        assert!(cmd.no_history);
+        assert_eq!(cmd.path, std::path::PathBuf::from("app"));
+        assert_eq!(cmd.min_history, 3);
+        assert_eq!(cmd.output, Some(std::path::PathBuf::from("starter.toml")));
+    }
+
+    #[test]
+    fn test_cli_parse_coverage() {
+        let cli = Cli::try_parse_from([
+            "dcg",
+            "coverage",
+            "history.txt",
+            "-",
+            "--enabled-only",
+            "--top",
+            "5",
+            "--format",
+            "json",
+        ])
+        .expect("parse coverage");
+        let Some(Command::Coverage(cmd)) = cli.command else {
+            panic!("expected coverage command");
+        };
+        assert_eq!(
+            cmd.paths,
+            [
+                std::path::PathBuf::from("history.txt"),
+                std::path::PathBuf::from("-")
+            ]
+        );
+        assert!(cmd.enabled_only);
+        assert_eq!(cmd.top, 5);
+        assert_eq!(cmd.format, DoctorFormat::Json);
+
+        assert!(Cli::try_parse_from(["dcg", "coverage"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_allowlist_import_export() {
+        use crate::allowlist_exchange::{ExchangeFormat, MergeStrategy};
+
+        let cli = Cli::parse_from([
+            "dcg",
+            "allowlist",
+            "import",
+            "team.json",
+            "--layer",
+            "user",
+            "--merge-strategy",
+            "theirs",
+        ]);
         if let Some(Command::Allowlist {
-            action: AllowlistAction::List { format, .. },
+            action:
+                AllowlistAction::Import {
+                    file,
+                    layer,
+                    merge_strategy,
+                    format,
+                    dry_run,
+                },
         }) = cli.command
         {
-            assert_eq!(format, AllowlistOutputFormat::Json);
+            assert_eq!(file, std::path::PathBuf::from("team.json"));
+            assert_eq!(layer, Some(AllowlistLayer::User));
+            assert_eq!(merge_strategy, Some(MergeStrategy::Theirs));
+            assert_eq!(format, None);
+            assert!(!dry_run);
         } else {
-            unreachable!("Expected Allowlist List command");
+            unreachable!("Expected Allowlist Import command");
         }
-    }
 
-    #[test]
-    fn test_cli_parse_allowlist_validate() {
-        let cli = Cli::parse_from(["dcg", "allowlist", "validate", "--strict"]);
+        let cli = Cli::parse_from(["dcg", "allowlist", "export", "--format", "json"]);
         if let Some(Command::Allowlist {
-            action: AllowlistAction::Validate { strict, .. },
+            action: AllowlistAction::Export { format, layer, .. },
         }) = cli.command
         {
-            assert!(strict);
+            assert_eq!(format, ExchangeFormat::Json);
+            assert_eq!(layer, None);
         } else {
-            unreachable!("Expected Allowlist Validate command");
+            unreachable!("Expected Allowlist Export command");
         }
+
+        assert!(
+            Cli::try_parse_from([
+                "dcg",
+                "allowlist",
+                "import",
+                "a.toml",
+                "--merge-strategy",
+                "mine"
+            ])
+            .is_err()
+        );
     }
 
     #[test]
@@ -11702,6 +17641,214 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_allow_once_grant_flags() {
+        let cli = Cli::parse_from(["dcg", "allow-once", "ab12", "--rule"]);
+        if let Some(Command::AllowOnce(cmd)) = cli.command {
+            assert_eq!(cmd.requested_grant(), Some(AllowOnceGrant::Rule));
+        } else {
+            unreachable!("Expected AllowOnce command");
+        }
+
+        let cli = Cli::parse_from(["dcg", "allow-once", "ab12"]);
+        if let Some(Command::AllowOnce(cmd)) = cli.command {
+            assert_eq!(cmd.requested_grant(), None);
+        } else {
+            unreachable!("Expected AllowOnce command");
+        }
+
+        assert!(Cli::try_parse_from(["dcg", "allow-once", "ab12", "--rule", "--session"]).is_err());
+        assert!(Cli::try_parse_from(["dcg", "allow-once", "ab12", "--exact", "--rule"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_paths() {
+        let cli = Cli::parse_from(["dcg", "paths", "--format", "json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Paths {
+                format: DoctorFormat::Json
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rename_deprecated_rule_ids_keeps_formatting() {
+        let mut doc: toml_edit::DocumentMut = r#"# team allowlist
+[[allow]]
+rule = "core.filesystem:rm-rf-root" # legacy ID
+reason = "image builds"
+
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "scratch"
+"#
+        .parse()
+        .unwrap();
+        let renamed = rename_deprecated_rule_ids(&mut doc);
+        assert_eq!(
+            renamed,
+            vec![(
+                RuleId::parse("core.filesystem:rm-rf-root").unwrap(),
+                RuleId::parse("core.filesystem:rm-rf-root-home").unwrap(),
+            )]
+        );
+        let out = doc.to_string();
+        assert!(out.starts_with("# team allowlist\n"));
+        assert!(out.contains("rule = \"core.filesystem:rm-rf-root-home\" # legacy ID"));
+        assert!(out.contains("rule = \"core.git:reset-hard\""));
+        assert!(rename_deprecated_rule_ids(&mut doc).is_empty());
+    }
+
+    #[test]
+    fn test_cli_parse_migrate() {
+        let cli = Cli::parse_from(["dcg", "migrate", "--dry-run", ".dcg.toml"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Migrate {
+                ref files,
+                dry_run: true,
+                ..
+            }) if files == &[std::path::PathBuf::from(".dcg.toml")]
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_policy() {
+        let cli = Cli::parse_from(["dcg", "policy", "export", "-o", "host.json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Policy {
+                action: PolicyAction::Export { output: Some(ref path) }
+            }) if path == std::path::Path::new("host.json")
+        ));
+        let cli = Cli::parse_from([
+            "dcg", "policy", "diff", "a.json", "b.json", "--format", "json",
+        ]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Policy {
+                action: PolicyAction::Diff {
+                    format: DoctorFormat::Json,
+                    ..
+                }
+            })
+        ));
+    }
+
+    #[cfg(feature = "telemetry")]
+    #[test]
+    fn test_cli_parse_telemetry() {
+        let cli = Cli::parse_from(["dcg", "telemetry", "enable"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Telemetry {
+                action: TelemetryAction::Enable
+            })
+        ));
+        let cli = Cli::parse_from(["dcg", "telemetry", "status", "--format", "json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Telemetry {
+                action: TelemetryAction::Status {
+                    format: DoctorFormat::Json
+                }
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_parse_trash() {
+        let cli = Cli::parse_from(["dcg", "trash", "put", "build", "dist"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Trash {
+                action: TrashAction::Put { ref paths }
+            }) if paths.len() == 2
+        ));
+        let cli = Cli::parse_from(["dcg", "trash", "restore", "--force"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Trash {
+                action: TrashAction::Restore {
+                    id: None,
+                    force: true
+                }
+            })
+        ));
+        assert!(Cli::try_parse_from(["dcg", "trash", "put"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_preview() {
+        let cli = Cli::parse_from(["dcg", "preview", "git clean -fd", "--timeout", "5"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Preview {
+                ref command,
+                timeout: 5,
+                format: DoctorFormat::Pretty,
+            }) if command == "git clean -fd"
+        ));
+    }
+
+    #[test]
+    fn test_collect_dcg_paths_groups_by_role() {
+        let paths = collect_dcg_paths(&Config::default());
+        assert!(paths.windows(2).all(|pair| pair[0].role <= pair[1].role));
+        for id in [
+            "config_user",
+            "allow_once",
+            "pending_exceptions",
+            "version_check",
+        ] {
+            assert!(paths.iter().any(|entry| entry.id == id), "missing {id}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_allow_once_grant_uses_agent_profile_default() {
+        let now = Utc::now();
+        let redaction = crate::logging::RedactionConfig::default();
+        let mut record = PendingExceptionRecord::new(
+            now,
+            "/repo",
+            "git reset --hard",
+            "destroys work",
+            &redaction,
+            false,
+            None,
+        );
+        record.agent = Some("claude-code".to_string());
+
+        let mut config = Config::default();
+        config.agents.profiles.insert(
+            "claude-code".to_string(),
+            crate::config::AgentProfile {
+                allow_once_scope: Some(AllowOnceGrant::Rule),
+                ..Default::default()
+            },
+        );
+
+        // Without a recorded rule the profile default degrades to an exact grant,
+        // but an explicit --rule is an error.
+        assert_eq!(
+            resolve_allow_once_grant(&config, &record, None),
+            Ok(AllowOnceGrant::Exact)
+        );
+        assert!(resolve_allow_once_grant(&config, &record, Some(AllowOnceGrant::Rule)).is_err());
+
+        record.rule_id = Some("core.git:reset-hard".to_string());
+        assert_eq!(
+            resolve_allow_once_grant(&config, &record, None),
+            Ok(AllowOnceGrant::Rule)
+        );
+        assert_eq!(
+            resolve_allow_once_grant(&config, &record, Some(AllowOnceGrant::Exact)),
+            Ok(AllowOnceGrant::Exact)
+        );
+    }
+
     #[test]
     fn test_cli_parse_allow_once_list() {
         let cli = Cli::parse_from(["dcg", "allow-once", "list"]);
@@ -11855,6 +18002,71 @@ mod tests {
         assert!(!removed, "should return false for non-existent entry");
     }
 
+    #[test]
+    fn allowlist_prune_selects_entries_unused_since_cutoff() {
+        use crate::allowlist_usage::{EntryUsage, UsageState, usage_key};
+        use std::path::Path;
+
+        let path = Path::new("/repo/.dcg/allowlist.toml");
+        let content = r#"
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "still used"
+
+[[allow]]
+exact_command = "rm -rf ./build"
+reason = "used long ago"
+
+[[allow]]
+rule = "core.git:clean-force"
+reason = "never used"
+
+[[allow]]
+rule = "core.git:push-force"
+reason = "added recently"
+added_at = "2030-05-30T00:00:00Z"
+"#;
+        let stamp = |last_used: &str| EntryUsage {
+            count: 1,
+            first_used: "2030-01-01T00:00:00Z".to_string(),
+            last_used: last_used.to_string(),
+        };
+        let key = |selector: AllowSelector| usage_key(path, &selector);
+        let mut state = UsageState {
+            schema_version: 1,
+            tracking_since: "2030-01-01T00:00:00Z".to_string(),
+            entries: std::collections::BTreeMap::new(),
+        };
+        state.entries.insert(
+            key(AllowSelector::Rule(
+                RuleId::parse("core.git:reset-hard").unwrap(),
+            )),
+            stamp("2030-05-01T00:00:00Z"),
+        );
+        state.entries.insert(
+            key(AllowSelector::ExactCommand("rm -rf ./build".to_string())),
+            stamp("2030-01-02T00:00:00Z"),
+        );
+
+        let cutoff = chrono::DateTime::parse_from_rfc3339("2030-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let stale = stale_allowlist_entries(content, path, &state, cutoff);
+        let indices: Vec<usize> = stale.iter().map(|(idx, _, _)| *idx).collect();
+        assert_eq!(indices, vec![1, 2]);
+
+        let mut doc: toml_edit::DocumentMut = content.parse().unwrap();
+        let arr = doc["allow"].as_array_of_tables_mut().unwrap();
+        for (idx, _, _) in stale.iter().rev() {
+            arr.remove(*idx);
+        }
+        let pruned = doc.to_string();
+        assert!(pruned.contains("still used"));
+        assert!(pruned.contains("added recently"));
+        assert!(!pruned.contains("used long ago"));
+        assert!(!pruned.contains("never used"));
+    }
+
     #[test]
     fn allowlist_expired_entries_are_skipped_in_matching() {
         use crate::allowlist::{AllowlistLayer, is_expired, parse_allowlist_toml};
@@ -12258,6 +18470,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_parse_scan_jobs_and_timeout() {
+        let cli = Cli::try_parse_from(["dcg", "scan", "--staged", "-j", "4", "--timeout", "30"])
+            .expect("parse");
+        if let Some(Command::Scan(scan)) = cli.command {
+            assert_eq!(scan.jobs, 4);
+            assert_eq!(scan.timeout, Some(30));
+        } else {
+            unreachable!("Expected Scan command");
+        }
+    }
+
     #[test]
     fn test_cli_parse_scan_exclude_include() {
         let cli = Cli::try_parse_from([
@@ -12449,7 +18673,7 @@ exclude = ["target/**"]
         let tmp = tempfile::tempdir().expect("tempdir");
         init_temp_git_repo(tmp.path());
 
-        let hook_path = install_scan_pre_commit_hook_at(tmp.path()).expect("install");
+        let hook_path = install_scan_pre_commit_hook_at(tmp.path(), None).expect("install");
         assert!(hook_path.exists(), "hook should exist after install");
 
         let contents_1 = std::fs::read_to_string(&hook_path).expect("read hook");
@@ -12462,7 +18686,7 @@ exclude = ["target/**"]
             "hook should run dcg scan --staged"
         );
 
-        let hook_path_2 = install_scan_pre_commit_hook_at(tmp.path()).expect("install again");
+        let hook_path_2 = install_scan_pre_commit_hook_at(tmp.path(), None).expect("install again");
         assert_eq!(hook_path, hook_path_2);
 
         let contents_2 = std::fs::read_to_string(&hook_path).expect("read hook");
@@ -12475,6 +18699,60 @@ exclude = ["target/**"]
         assert!(removed_again.is_none(), "should be a no-op when missing");
     }
 
+    #[test]
+    fn hook_install_pre_commit_applies_fail_on_threshold() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        init_temp_git_repo(tmp.path());
+
+        let hook_path =
+            install_scan_pre_commit_hook_at(tmp.path(), Some(crate::scan::ScanFailOn::Warning))
+                .expect("install");
+        let contents = std::fs::read_to_string(&hook_path).expect("read hook");
+        assert!(contents.contains("dcg scan --staged --fail-on warning"));
+
+        // Re-installing over a dcg-managed hook updates the threshold.
+        install_scan_pre_commit_hook_at(tmp.path(), None).expect("reinstall");
+        let contents = std::fs::read_to_string(&hook_path).expect("read hook");
+        assert!(!contents.contains("--fail-on"));
+    }
+
+    #[test]
+    fn test_cli_parse_hook_install_pre_commit() {
+        let cli = Cli::try_parse_from([
+            "dcg",
+            "hook",
+            "install",
+            "pre-commit",
+            "--fail-on",
+            "warning",
+        ])
+        .expect("parse");
+        let Some(Command::Hook(hook)) = cli.command else {
+            unreachable!("Expected Hook command");
+        };
+        assert!(matches!(
+            hook.action,
+            Some(HookAction::Install {
+                kind: GitHookKind::PreCommit,
+                fail_on: Some(crate::scan::ScanFailOn::Warning),
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["dcg", "hook", "uninstall", "pre-commit"]).expect("parse");
+        let Some(Command::Hook(hook)) = cli.command else {
+            unreachable!("Expected Hook command");
+        };
+        assert!(matches!(
+            hook.action,
+            Some(HookAction::Uninstall {
+                kind: GitHookKind::PreCommit
+            })
+        ));
+
+        // Batch flags and actions don't mix.
+        assert!(Cli::try_parse_from(["dcg", "hook", "--batch", "install", "pre-commit"]).is_err());
+    }
+
     #[test]
     fn scan_pre_commit_install_refuses_to_overwrite_unknown_hook() {
         let tmp = tempfile::tempdir().expect("tempdir");
@@ -12484,7 +18762,7 @@ exclude = ["target/**"]
         let existing = "#!/usr/bin/env bash\necho hi\n";
         std::fs::write(&hook_path, existing).expect("write existing hook");
 
-        let err = install_scan_pre_commit_hook_at(tmp.path()).expect_err("should refuse");
+        let err = install_scan_pre_commit_hook_at(tmp.path(), None).expect_err("should refuse");
         assert!(err.to_string().contains("Refusing to overwrite"));
 
         let after = std::fs::read_to_string(&hook_path).expect("read hook after");
@@ -12531,11 +18809,14 @@ exclude = ["target/**"]
         let cli = Cli::try_parse_from(["dcg", "explain", "git reset --hard"]).expect("parse");
         if let Some(Command::Explain {
             command,
+            rule,
             format,
             with_packs,
+            ..
         }) = cli.command
         {
-            assert_eq!(command, "git reset --hard");
+            assert_eq!(command.as_deref(), Some("git reset --hard"));
+            assert!(rule.is_none());
             assert_eq!(format, ExplainFormat::Pretty);
             assert!(with_packs.is_none());
         } else {
@@ -12543,6 +18824,37 @@ exclude = ["target/**"]
         }
     }
 
+    #[test]
+    fn test_cli_parse_explain_rule() {
+        let cli = Cli::try_parse_from(["dcg", "explain", "--rule", "core.git:reset-hard"])
+            .expect("parse");
+        if let Some(Command::Explain {
+            command,
+            rule,
+            corpus,
+            ..
+        }) = cli.command
+        {
+            assert!(command.is_none());
+            assert_eq!(rule.as_deref(), Some("core.git:reset-hard"));
+            assert_eq!(corpus, std::path::PathBuf::from("tests/corpus"));
+        } else {
+            unreachable!("Expected Explain command");
+        }
+
+        assert!(Cli::try_parse_from(["dcg", "explain"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "dcg",
+                "explain",
+                "--rule",
+                "core.git:reset-hard",
+                "git status"
+            ])
+            .is_err()
+        );
+    }
+
     #[test]
     fn test_cli_parse_explain_with_format() {
         let cli =
@@ -12552,13 +18864,138 @@ exclude = ["target/**"]
             command, format, ..
         }) = cli.command
         {
-            assert_eq!(command, "docker system prune");
+            assert_eq!(command.as_deref(), Some("docker system prune"));
             assert_eq!(format, ExplainFormat::Json);
         } else {
             unreachable!("Expected Explain command");
         }
     }
 
+    #[test]
+    fn test_cli_parse_why_allowed() {
+        let cli = Cli::try_parse_from([
+            "dcg",
+            "why-allowed",
+            "--with-packs",
+            "containers.docker,kubernetes",
+            "docker system prune",
+        ])
+        .expect("parse");
+        if let Some(Command::WhyAllowed {
+            command,
+            with_packs,
+        }) = cli.command
+        {
+            assert_eq!(command, "docker system prune");
+            assert_eq!(
+                with_packs,
+                Some(vec![
+                    "containers.docker".to_string(),
+                    "kubernetes".to_string()
+                ])
+            );
+        } else {
+            unreachable!("Expected WhyAllowed command");
+        }
+    }
+
+    #[test]
+    fn test_cli_parse_schema() {
+        let cli = Cli::try_parse_from(["dcg", "schema", "allowlist"]).expect("parse");
+        assert!(matches!(
+            cli.command,
+            Some(Command::Schema {
+                kind: crate::schema::SchemaKind::Allowlist
+            })
+        ));
+        assert!(Cli::try_parse_from(["dcg", "schema", "nope"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_parse_lsp() {
+        let cli = Cli::try_parse_from(["dcg", "lsp"]).expect("parse");
+        assert!(matches!(cli.command, Some(Command::Lsp)));
+    }
+
+    #[test]
+    fn test_serve_parses_listen_address() {
+        let cli = Cli::parse_from(["dcg", "serve"]);
+        match cli.command {
+            Some(Command::Serve(cmd)) => assert_eq!(cmd.listen, "127.0.0.1:8765"),
+            other => panic!("expected serve, got {other:?}"),
+        }
+        let cli = Cli::parse_from(["dcg", "serve", "--listen", "127.0.0.1:9000"]);
+        assert!(matches!(cli.command, Some(Command::Serve(cmd)) if cmd.listen == "127.0.0.1:9000"));
+    }
+
+    #[test]
+    fn test_cli_parse_undo() {
+        let cli = Cli::parse_from(["dcg", "undo", "--dry-run"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Undo {
+                list: false,
+                dry_run: true,
+                force: false
+            })
+        ));
+        assert!(Cli::try_parse_from(["dcg", "undo", "--list", "--force"]).is_err());
+    }
+
+    fn why_allowed_lines(command: &str, extra_packs: &[String]) -> Vec<String> {
+        build_why_allowed_tree(
+            &Config::default(),
+            command,
+            extra_packs,
+            &crate::LayeredAllowlist::default(),
+        )
+        .guides(crate::output::DcgTreeGuides::Ascii)
+        .render_plain()
+    }
+
+    #[test]
+    fn test_why_allowed_reports_data_only_near_miss() {
+        let lines = why_allowed_lines(r#"git commit -m "rm -rf /""#, &[]);
+        let text = lines.join("\n");
+        assert!(
+            text.contains("Decision: ALLOWED"),
+            "expected allow decision:\n{text}"
+        );
+        assert!(
+            text.contains("Sanitize data arguments: git commit -m"),
+            "expected sanitize step:\n{text}"
+        );
+        assert!(
+            text.contains("matches only text treated as data"),
+            "expected data-only near miss:\n{text}"
+        );
+        assert!(text.contains("no allowlist entries fired"), "{text}");
+    }
+
+    #[test]
+    fn test_why_allowed_lists_packs_that_are_not_enabled() {
+        let lines = why_allowed_lines("docker system prune -af", &[]);
+        let text = lines.join("\n");
+        assert!(
+            text.contains("containers.docker (not enabled): would block via containers.docker:"),
+            "expected dormant docker pack:\n{text}"
+        );
+
+        let lines = why_allowed_lines(
+            "docker system prune -af",
+            &["containers.docker".to_string()],
+        );
+        let text = lines.join("\n");
+        assert!(
+            text.contains("Decision: BLOCKED by containers.docker:"),
+            "expected block once enabled:\n{text}"
+        );
+        assert!(
+            text.contains("containers.docker [--with-packs]: matched containers.docker:"),
+            "expected enabled reason:\n{text}"
+        );
+    }
+
     #[test]
     fn test_cli_parse_test_with_explain_flag() {
         let cli =
@@ -12611,6 +19048,24 @@ exclude = ["target/**"]
         }
     }
 
+    #[test]
+    fn test_cli_parse_test_with_provenance() {
+        let cli = Cli::try_parse_from(["dcg", "test", "--provenance", "human", "git push -f"])
+            .expect("parse");
+        if let Some(Command::TestCommand { provenance, .. }) = cli.command {
+            assert_eq!(provenance, Some(crate::provenance::Provenance::Human));
+        } else {
+            unreachable!("Expected TestCommand");
+        }
+
+        let cli = Cli::try_parse_from(["dcg", "test", "git push -f"]).expect("parse");
+        if let Some(Command::TestCommand { provenance, .. }) = cli.command {
+            assert_eq!(provenance, None);
+        } else {
+            unreachable!("Expected TestCommand");
+        }
+    }
+
     // ========================================================================
     // Scan git integration tests
     // ========================================================================
@@ -12884,6 +19339,27 @@ exclude = ["target/**"]
         }
     }
 
+    #[test]
+    fn cli_parse_docs_generate() {
+        let cli = Cli::try_parse_from(["dcg", "docs", "generate", "-o", "out", "--check"])
+            .expect("parse");
+        if let Some(Command::Docs {
+            action:
+                DocsAction::Generate {
+                    output,
+                    corpus,
+                    check,
+                },
+        }) = cli.command
+        {
+            assert_eq!(output, std::path::PathBuf::from("out"));
+            assert_eq!(corpus, std::path::PathBuf::from("tests/corpus"));
+            assert!(check);
+        } else {
+            unreachable!("Expected Docs command");
+        }
+    }
+
     // ==========================================================================
     // Doctor diagnostics tests (git_safety_guard-1gt.7.1)
     // ==========================================================================
@@ -13203,6 +19679,9 @@ exclude = ["target/**"]
             force: false,
             pick: Some(2),
             hash: None,
+            exact: false,
+            rule: false,
+            session: false,
         };
         let records = [a.clone(), b.clone()];
         let selected = select_pending_entry(&records, &cmd_pick).unwrap();
@@ -13219,6 +19698,9 @@ exclude = ["target/**"]
             force: false,
             pick: None,
             hash: Some(b.full_hash.clone()),
+            exact: false,
+            rule: false,
+            session: false,
         };
         let records = [a, b.clone()];
         let selected = select_pending_entry(&records, &cmd_hash).unwrap();
@@ -13262,6 +19744,9 @@ exclude = ["target/**"]
             force: false,
             pick: Some(3),
             hash: None,
+            exact: false,
+            rule: false,
+            session: false,
         };
 
         let records = [a, b];
@@ -0,0 +1,333 @@
+//! Dynamic shell completion of pack and rule IDs.
+//!
+//! `dcg completions <shell>` prints clap's static script with a small hook
+//! wrapped around it. The hook passes the words up to the cursor to the
+//! hidden `dcg __complete -- <words>...`, which succeeds and prints matching
+//! IDs from the loaded packs when the last word fills one of the
+//! [`DYNAMIC_ARGS`], and exits non-zero otherwise so the static completion
+//! takes over.
+
+use clap::{Arg, Command};
+use clap_complete::Shell;
+
+use crate::packs::Pack;
+
+/// What a dynamically completed argument holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// Pack IDs, e.g. `core.git`.
+    Packs,
+    /// Rule IDs, e.g. `core.git:reset-hard`.
+    Rules,
+}
+
+/// Arguments completed from the loaded packs, as (subcommand path, clap arg
+/// ID, kind).
+pub const DYNAMIC_ARGS: &[(&str, &str, ValueKind)] = &[
+    ("allow", "rule_id", ValueKind::Rules),
+    ("unallow", "rule_id", ValueKind::Rules),
+    ("allowlist add", "rule_id", ValueKind::Rules),
+    ("allowlist remove", "rule_id", ValueKind::Rules),
+    ("pack info", "pack_id", ValueKind::Packs),
+    ("test", "with_packs", ValueKind::Packs),
+    ("explain", "with_packs", ValueKind::Packs),
+    ("why-allowed", "with_packs", ValueKind::Packs),
+    ("dev validate-pack", "pack_id", ValueKind::Packs),
+    ("dev benchmark", "pack_id", ValueKind::Packs),
+    ("dev generate-fixtures", "pack_id", ValueKind::Packs),
+];
+
+/// The kind of ID the last of `words` (the word under the cursor) should be
+/// completed with, or `None` when it is not at one of the [`DYNAMIC_ARGS`].
+///
+/// `words` excludes the program name. Subcommands are followed through `cmd`
+/// and option values are skipped, so `allowlist add -r why <TAB>` still
+/// lands on the rule ID.
+#[must_use]
+pub fn value_kind(mut cmd: Command, words: &[String]) -> Option<ValueKind> {
+    let (current, before) = words.split_last()?;
+    cmd.build();
+
+    let mut cmd = &cmd;
+    let mut path: Vec<&str> = Vec::new();
+    let mut positionals = 0;
+    let mut pending: Option<&Arg> = None;
+    for word in before {
+        if pending.take().is_some() {
+            continue;
+        }
+        if word.starts_with('-') && word.len() > 1 {
+            pending = option_taking_value(cmd, word);
+            continue;
+        }
+        if let Some(sub) = cmd.find_subcommand(word) {
+            cmd = sub;
+            path.push(sub.get_name());
+            positionals = 0;
+            continue;
+        }
+        positionals += 1;
+    }
+
+    let arg = match pending {
+        Some(arg) => arg,
+        None if current.starts_with('-') => return None,
+        None => cmd.get_positionals().nth(positionals)?,
+    };
+    let path = path.join(" ");
+    DYNAMIC_ARGS
+        .iter()
+        .find(|(args_path, id, _)| *args_path == path && arg.get_id() == *id)
+        .map(|(_, _, kind)| *kind)
+}
+
+/// The option `word` names, if it is given without an inline `=value` and
+/// takes one.
+fn option_taking_value<'a>(cmd: &'a Command, word: &str) -> Option<&'a Arg> {
+    let arg = if let Some(long) = word.strip_prefix("--") {
+        if long.contains('=') {
+            return None;
+        }
+        cmd.get_arguments()
+            .find(|arg| arg.get_long() == Some(long))?
+    } else {
+        // In a cluster such as `-vr`, only the last flag can wait for a value.
+        let last = word.chars().next_back()?;
+        cmd.get_arguments()
+            .find(|arg| arg.get_short() == Some(last))?
+    };
+    arg.get_action().takes_values().then_some(arg)
+}
+
+/// IDs of `kind` from `packs` that complete `current`.
+///
+/// Pack lists such as `--with-packs core.git,data` complete the part after
+/// the last comma and keep the rest.
+#[must_use]
+pub fn candidates(kind: ValueKind, current: &str, packs: &[&Pack]) -> Vec<String> {
+    match kind {
+        ValueKind::Packs => {
+            let (head, tail) = current
+                .rfind(',')
+                .map_or(("", current), |i| current.split_at(i + 1));
+            packs
+                .iter()
+                .filter(|pack| pack.id.starts_with(tail))
+                .map(|pack| format!("{head}{}", pack.id))
+                .collect()
+        }
+        ValueKind::Rules => packs
+            .iter()
+            .flat_map(|pack| {
+                pack.destructive_patterns
+                    .iter()
+                    .filter_map(|pattern| pattern.name)
+                    .map(|name| format!("{}:{name}", pack.id))
+            })
+            .filter(|rule_id| rule_id.starts_with(current))
+            .collect(),
+    }
+}
+
+/// `script` (clap's completion for `shell`) with the `dcg __complete` hook
+/// added. Shells without a hook get the static script unchanged.
+#[must_use]
+pub fn add_dynamic_completion(shell: Shell, script: String) -> String {
+    match shell {
+        Shell::Bash => script + BASH_HOOK,
+        Shell::Zsh => {
+            let script = script.replacen("\n_dcg() {\n", "\n_dcg_static() {\n", 1);
+            match script.rfind("\nif [ \"$funcstack[1]\" = \"_dcg\" ]") {
+                Some(trailer) => {
+                    let (body, trailer) = script.split_at(trailer + 1);
+                    format!("{body}{ZSH_HOOK}{trailer}")
+                }
+                None => script + ZSH_HOOK,
+            }
+        }
+        Shell::Fish => script + FISH_HOOK,
+        Shell::PowerShell => {
+            script.replacen(
+                "Register-ArgumentCompleter -Native -CommandName 'dcg' -ScriptBlock {",
+                "$__dcgStaticCompleter = {",
+                1,
+            ) + POWERSHELL_HOOK
+        }
+        _ => script,
+    }
+}
+
+// Bash splits words at `:`, so the words are re-read from the line itself
+// and the part of a rule ID before the last colon is trimmed from replies.
+const BASH_HOOK: &str = r#"
+_dcg_dynamic() {
+    local line="${COMP_LINE:0:COMP_POINT}"
+    local -a words
+    read -ra words <<< "${line}"
+    [[ "${line}" =~ [[:space:]]$ ]] && words+=("")
+    local cur="${words[${#words[@]}-1]}"
+    local candidates
+    if candidates="$("${words[0]}" __complete -- "${words[@]:1}" 2>/dev/null)"; then
+        local IFS=$'\n'
+        COMPREPLY=($(compgen -W "${candidates}" -- "${cur}"))
+        if [[ "${cur}" == *:* ]]; then
+            local prefix="${cur%"${cur##*:}"}"
+            COMPREPLY=("${COMPREPLY[@]#"${prefix}"}")
+        fi
+        return 0
+    fi
+    _dcg "$@"
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _dcg_dynamic -o nosort -o bashdefault -o default dcg
+else
+    complete -F _dcg_dynamic -o bashdefault -o default dcg
+fi
+"#;
+
+const ZSH_HOOK: &str = r#"_dcg() {
+    local output
+    if output="$("${words[1]}" __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)"; then
+        local -a candidates
+        candidates=(${(f)output})
+        compadd -- "${candidates[@]}"
+        return
+    fi
+    _dcg_static "$@"
+}
+
+"#;
+
+const FISH_HOOK: &str = r#"
+function __fish_dcg_dynamic
+    dcg __complete -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null
+end
+complete -c dcg -f -n "__fish_dcg_dynamic >/dev/null" -a "(__fish_dcg_dynamic)"
+"#;
+
+const POWERSHELL_HOOK: &str = r"
+Register-ArgumentCompleter -Native -CommandName 'dcg' -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $words = @($commandAst.CommandElements |
+        Select-Object -Skip 1 |
+        Where-Object { $_.Extent.StartOffset -lt $cursorPosition } |
+        ForEach-Object { $_.ToString() })
+    if ($wordToComplete -eq '') {
+        $words += ''
+    }
+    $candidates = & dcg __complete -- @words 2>$null
+    if ($LASTEXITCODE -eq 0) {
+        $candidates | ForEach-Object {
+            [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_)
+        }
+        return
+    }
+    & $__dcgStaticCompleter $wordToComplete $commandAst $cursorPosition
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use crate::packs::REGISTRY;
+    use clap::CommandFactory;
+
+    fn kind(line: &str) -> Option<ValueKind> {
+        let mut words: Vec<String> = line.split_whitespace().map(String::from).collect();
+        if line.ends_with(' ') {
+            words.push(String::new());
+        }
+        value_kind(Cli::command(), &words)
+    }
+
+    #[test]
+    fn dynamic_args_exist() {
+        let mut root = Cli::command();
+        root.build();
+        for (path, id, _) in DYNAMIC_ARGS {
+            let cmd = path.split(' ').fold(&root, |cmd, name| {
+                cmd.find_subcommand(name)
+                    .unwrap_or_else(|| panic!("no subcommand {path}"))
+            });
+            assert!(
+                cmd.get_arguments().any(|arg| arg.get_id() == *id),
+                "{path} has no argument {id}"
+            );
+        }
+    }
+
+    #[test]
+    fn finds_dynamic_slots() {
+        assert_eq!(kind("allowlist add "), Some(ValueKind::Rules));
+        assert_eq!(kind("allowlist add core.git:re"), Some(ValueKind::Rules));
+        assert_eq!(
+            kind("allowlist add -r why --project "),
+            Some(ValueKind::Rules)
+        );
+        assert_eq!(kind("-v allow core"), Some(ValueKind::Rules));
+        assert_eq!(kind("pack info data"), Some(ValueKind::Packs));
+        assert_eq!(kind("explain --with-packs "), Some(ValueKind::Packs));
+        assert_eq!(
+            kind("test git --with-packs core.git,da"),
+            Some(ValueKind::Packs)
+        );
+    }
+
+    #[test]
+    fn other_slots_fall_back_to_static_completion() {
+        assert_eq!(kind(""), None);
+        assert_eq!(kind("allowlist "), None);
+        assert_eq!(kind("allowlist add core.git:reset-hard "), None);
+        assert_eq!(kind("allowlist add -"), None);
+        assert_eq!(kind("allowlist add core.git:reset-hard --reason "), None);
+        assert_eq!(kind("explain "), None);
+        assert_eq!(kind("pack new "), None);
+    }
+
+    #[test]
+    fn candidates_match_prefix() {
+        let git = REGISTRY.get("core.git").expect("core.git");
+        let fs = REGISTRY.get("core.filesystem").expect("core.filesystem");
+        let packs = [git, fs];
+
+        let rules = candidates(ValueKind::Rules, "core.git:reset", &packs);
+        assert!(rules.contains(&"core.git:reset-hard".to_string()));
+        assert!(rules.iter().all(|id| id.starts_with("core.git:reset")));
+
+        assert_eq!(
+            candidates(ValueKind::Packs, "core.f", &packs),
+            vec!["core.filesystem".to_string()]
+        );
+        assert_eq!(
+            candidates(ValueKind::Packs, "core.git,core.f", &packs),
+            vec!["core.git,core.filesystem".to_string()]
+        );
+    }
+
+    #[test]
+    fn hooks_wrap_the_static_scripts() {
+        let script = |shell| {
+            let mut out = Vec::new();
+            clap_complete::generate(shell, &mut Cli::command(), "dcg", &mut out);
+            add_dynamic_completion(shell, String::from_utf8(out).expect("utf-8"))
+        };
+
+        let bash = script(Shell::Bash);
+        assert!(bash.starts_with("_dcg() {"));
+        assert!(bash.trim_end().ends_with("fi"));
+        assert!(bash.contains("complete -F _dcg_dynamic"));
+
+        let zsh = script(Shell::Zsh);
+        assert!(zsh.contains("\n_dcg_static() {"));
+        assert!(zsh.find("\n_dcg() {") < zsh.find("if [ \"$funcstack[1]\" = \"_dcg\" ]"));
+
+        assert!(script(Shell::Fish).contains("-a \"(__fish_dcg_dynamic)\""));
+
+        let powershell = script(Shell::PowerShell);
+        assert!(powershell.contains("$__dcgStaticCompleter = {"));
+        assert_eq!(powershell.matches("Register-ArgumentCompleter").count(), 1);
+    }
+}
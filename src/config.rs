@@ -8,10 +8,12 @@
 //! 4. System config (/etc/dcg/config.toml)
 //! 5. Compiled defaults (lowest priority)
 
+use crate::arg_context::ArgConditions;
 use crate::interactive::{InteractiveConfig, VerificationMethod};
+use crate::remote_include::{IncludeCache, IncludePin};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -26,6 +28,18 @@ const CONFIG_FILE_NAME: &str = "config.toml";
 /// Project-level config file name.
 const PROJECT_CONFIG_NAME: &str = ".dcg.toml";
 
+/// Alternate project-level config path (relative to the repo root).
+///
+/// Used when `.dcg.toml` is absent, so repos that already keep their allowlist
+/// under `.dcg/` can keep policy alongside it.
+const PROJECT_POLICY_PATH: &str = ".dcg/policy.toml";
+
+/// Maximum nesting depth for `include = [...]` chains.
+///
+/// Cycles are detected explicitly; this bound only guards against absurdly deep
+/// (but acyclic) include trees.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
 /// Env var for selecting an explicit config file path.
 ///
 /// This is intentionally separate from per-setting env overrides (packs, verbose,
@@ -77,6 +91,36 @@ pub struct Config {
     /// Git branch-aware strictness configuration.
     pub git_awareness: GitAwarenessConfig,
 
+    /// Messaging pack settings (production-like target names).
+    pub messaging: MessagingConfig,
+
+    /// Production-looking resource names for every pack.
+    pub naming: NamingConfig,
+
+    /// Critical systemd units for the services pack.
+    pub services: ServicesConfig,
+
+    /// Canary paths and resource names that trip a critical denial.
+    pub canary: CanaryConfig,
+
+    /// Always-on checks against disabling dcg itself (system config only).
+    pub self_protection: SelfProtectionConfig,
+
+    /// Which per-invocation environment overrides are honoured (system config only).
+    pub security: SecurityConfig,
+
+    /// Chat channels `dcg serve` posts queued approval requests to.
+    pub approvals: ApprovalsConfig,
+
+    /// Where `dcg trash put` moves files.
+    pub trash: TrashConfig,
+
+    /// What the log file, history, and webhooks record for each command.
+    pub privacy: PrivacyConfig,
+
+    /// Organization-specific text for denial messages.
+    pub messages: MessagesConfig,
+
     /// Agent-specific profiles configuration.
     #[serde(default)]
     pub agents: AgentsConfig,
@@ -84,6 +128,82 @@ pub struct Config {
     /// Project-specific configurations (keyed by absolute path).
     #[serde(default)]
     pub projects: std::collections::HashMap<String, ProjectConfig>,
+
+    /// Config files that contributed to this configuration, in merge order.
+    ///
+    /// Populated by [`Config::load`] for `dcg config show --effective`; not part
+    /// of the on-disk schema.
+    #[serde(skip)]
+    pub sources: Vec<ConfigSource>,
+}
+
+/// Where a config layer came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSourceKind {
+    /// `/etc/dcg/config.toml`.
+    System,
+    /// User config (`~/.config/dcg/config.toml` or platform equivalent).
+    User,
+    /// Project config (`.dcg.toml` or `.dcg/policy.toml` in the repo root).
+    Project,
+    /// Explicit config file from `DCG_CONFIG`.
+    Explicit,
+    /// File pulled in via another layer's `include = [...]` list.
+    Include,
+}
+
+impl ConfigSourceKind {
+    /// Get a human-readable label for this source kind.
+    #[must_use]
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::User => "user",
+            Self::Project => "project",
+            Self::Explicit => "DCG_CONFIG",
+            Self::Include => "include",
+        }
+    }
+}
+
+/// Provenance record for one config file considered during [`Config::load`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigSource {
+    /// What kind of layer this is.
+    pub kind: ConfigSourceKind,
+    /// File path (or URL, for remote includes) as resolved.
+    pub location: String,
+    /// The file whose `include` list referenced this one (includes only).
+    pub included_from: Option<String>,
+    /// Whether the layer was merged into the effective config.
+    pub applied: bool,
+    /// Why the layer was skipped (cycle, missing file, pin mismatch, ...), or
+    /// a caveat for an applied one (a stale cached remote include).
+    pub note: Option<String>,
+}
+
+impl ConfigSource {
+    /// A warning for `dcg doctor` and verbose hook output, if this layer was
+    /// skipped or applied with a caveat.
+    #[must_use]
+    pub fn warning(&self) -> Option<String> {
+        let note = self.note.as_deref()?;
+        let from = self
+            .included_from
+            .as_deref()
+            .map(|from| format!(" (included from {from})"))
+            .unwrap_or_default();
+        Some(if self.applied {
+            format!("{} {}{from}: {note}", self.kind.label(), self.location)
+        } else {
+            format!(
+                "{} {}{from} was skipped: {note}",
+                self.kind.label(),
+                self.location
+            )
+        })
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -104,6 +224,10 @@ pub struct Config {
 
 #[derive(Debug, Clone, Default, Deserialize)]
 struct ConfigLayer {
+    /// Other config files to merge *below* this one (relative paths resolve
+    /// against this file's directory).
+    #[serde(default)]
+    include: Vec<IncludeSpec>,
     general: Option<GeneralConfigLayer>,
     output: Option<OutputConfigLayer>,
     theme: Option<ThemeConfigLayer>,
@@ -116,6 +240,16 @@ struct ConfigLayer {
     history: Option<HistoryConfigLayer>,
     interactive: Option<InteractiveConfigLayer>,
     git_awareness: Option<GitAwarenessConfigLayer>,
+    messaging: Option<MessagingConfigLayer>,
+    naming: Option<NamingConfigLayer>,
+    services: Option<ServicesConfigLayer>,
+    canary: Option<CanaryConfig>,
+    self_protection: Option<SelfProtectionConfigLayer>,
+    security: Option<SecurityConfigLayer>,
+    approvals: Option<ApprovalsConfig>,
+    trash: Option<TrashConfig>,
+    privacy: Option<PrivacyConfigLayer>,
+    messages: Option<MessagesConfig>,
     agents: Option<AgentsConfig>,
     projects: Option<std::collections::HashMap<String, ProjectConfig>>,
 }
@@ -130,6 +264,8 @@ struct GeneralConfigLayer {
     max_hook_input_bytes: Option<usize>,
     max_command_bytes: Option<usize>,
     max_findings_per_command: Option<usize>,
+    environment: Option<String>,
+    bootstrap_allowlist: Option<bool>,
 }
 
 #[derive(Debug, Clone, Copy, Default, Deserialize)]
@@ -137,6 +273,11 @@ struct OutputConfigLayer {
     highlight_enabled: Option<bool>,
     explanations_enabled: Option<bool>,
     high_contrast: Option<bool>,
+    hyperlinks: Option<bool>,
+    impact_budget_ms: Option<u64>,
+    storage_impact: Option<bool>,
+    storage_impact_timeout_ms: Option<u64>,
+    collapse_repeat_denials: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -174,6 +315,8 @@ struct InteractiveConfigLayer {
     allow_non_tty_fallback: Option<bool>,
     disable_in_ci: Option<bool>,
     require_env: Option<String>,
+    learning: Option<bool>,
+    approval_queue: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -209,6 +352,33 @@ struct GitAwarenessConfigLayer {
     warn_if_not_git: Option<bool>,
 }
 
+/// Messaging configuration layer for config file parsing.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct MessagingConfigLayer {
+    production_name_patterns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct NamingConfigLayer {
+    production_patterns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ServicesConfigLayer {
+    critical_units: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SelfProtectionConfigLayer {
+    enabled: Option<bool>,
+    binary_paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SecurityConfigLayer {
+    allow_env_overrides: Option<bool>,
+}
+
 fn expand_tilde_path(value: &str) -> (PathBuf, bool) {
     if value == "~" {
         if let Some(home) = dirs::home_dir() {
@@ -265,6 +435,69 @@ pub(crate) fn find_repo_root(start_dir: &Path, max_hops: usize) -> Option<PathBu
     None
 }
 
+/// Locate the project config file for the repo containing `start_dir`.
+///
+/// Prefers `.dcg.toml` and falls back to `.dcg/policy.toml`.
+pub(crate) fn project_config_path(start_dir: &Path) -> Option<PathBuf> {
+    let repo_root = find_repo_root(start_dir, REPO_ROOT_SEARCH_MAX_HOPS)?;
+    [PROJECT_CONFIG_NAME, PROJECT_POLICY_PATH]
+        .into_iter()
+        .map(|name| repo_root.join(name))
+        .find(|path| path.exists())
+}
+
+/// A parsed config layer together with its provenance.
+///
+/// `layer` is `None` for includes that were recorded but not merged.
+struct LoadedLayer {
+    layer: Option<ConfigLayer>,
+    source: ConfigSource,
+}
+
+/// One entry of an `include` list: a path or URL, or a URL with a pin.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum IncludeSpec {
+    Location(String),
+    Remote {
+        url: String,
+        #[serde(default)]
+        sha256: Option<String>,
+        #[serde(default)]
+        etag: Option<String>,
+    },
+}
+
+impl IncludeSpec {
+    fn location(&self) -> &str {
+        match self {
+            Self::Location(location) => location,
+            Self::Remote { url, .. } => url,
+        }
+    }
+
+    fn pin(&self) -> IncludePin<'_> {
+        match self {
+            Self::Remote {
+                sha256: Some(hash), ..
+            } => IncludePin::Sha256(hash),
+            Self::Remote {
+                etag: Some(etag), ..
+            } => IncludePin::Etag(etag),
+            _ => IncludePin::None,
+        }
+    }
+}
+
+fn is_remote_include(value: &str) -> bool {
+    let lower = value.trim().to_ascii_lowercase();
+    lower.starts_with("https://") || lower.starts_with("http://")
+}
+
+fn canonical_or_self(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
 /// Heredoc and inline-script scanning configuration.
 ///
 /// This configuration controls Tier 1/2/3 heredoc scanning behavior. Because the
@@ -757,7 +990,7 @@ fn language_filter_matches(filter: &str, language: crate::heredoc::ScriptLanguag
 /// # Returns
 ///
 /// A 64-character hex string representing the 256-bit hash.
-fn content_hash(content: &str) -> String {
+pub(crate) fn content_hash(content: &str) -> String {
     use sha2::Digest as _;
     let digest = sha2::Sha256::digest(content.as_bytes());
     let mut out = String::with_capacity(digest.len() * 2);
@@ -791,7 +1024,8 @@ pub struct GeneralConfig {
     pub max_hook_input_bytes: Option<usize>,
 
     /// Maximum bytes for command string after extraction from JSON.
-    /// Commands exceeding this limit are allowed (fail-open) with a warning.
+    /// Only this many leading bytes of a longer command are evaluated, and
+    /// an input anomaly is reported.
     /// Default: 65536 (64 KiB).
     pub max_command_bytes: Option<usize>,
 
@@ -806,6 +1040,22 @@ pub struct GeneralConfig {
     /// Default: true. Disable with `DCG_NO_UPDATE_CHECK` (any non-empty value)
     /// or `check_updates` = false.
     pub check_updates: bool,
+
+    /// Environment name that allowlist `environments = [...]` lists are
+    /// matched against (e.g. "staging"). `DCG_ENV` takes precedence; when
+    /// neither is set, dcg uses "ci" under CI and "local" otherwise.
+    pub environment: Option<String>,
+
+    /// Allow the project's own build and test commands (`npm run <script>`,
+    /// `cargo test`, `pytest`, ...) through a layer generated from its
+    /// manifests and lockfiles (see [`crate::allowlist_bootstrap`]).
+    /// Default: true.
+    pub bootstrap_allowlist: bool,
+
+    /// Print the decision trace to stderr for this invocation (`DCG_TRACE`).
+    /// Not read from config files.
+    #[serde(skip)]
+    pub trace: bool,
 }
 
 /// Default limits for input size (used when not configured).
@@ -824,6 +1074,9 @@ impl Default for GeneralConfig {
             max_command_bytes: None,
             max_findings_per_command: None,
             check_updates: true,
+            environment: None,
+            bootstrap_allowlist: true,
+            trace: false,
         }
     }
 }
@@ -870,6 +1123,32 @@ pub struct OutputConfig {
     /// Uses ASCII borders and a black/white palette for accessibility.
     /// Default: false
     pub high_contrast: Option<bool>,
+
+    /// Render rule IDs and the issue link as clickable OSC 8 hyperlinks on
+    /// terminals known to support them.
+    /// Default: true
+    pub hyperlinks: Option<bool>,
+
+    /// Milliseconds to spend estimating what a blocked `rm -r` would delete
+    /// (file count, size, notable directories) for the denial box.
+    /// 0 disables the estimate.
+    /// Default: 150
+    pub impact_budget_ms: Option<u64>,
+
+    /// Ask the cloud CLI (with credentials already in the environment) how
+    /// many objects a blocked bucket deletion would remove and whether
+    /// versioning or retention is enabled. Makes network requests.
+    /// Default: false
+    pub storage_impact: Option<bool>,
+
+    /// Milliseconds each `storage_impact` lookup may take.
+    /// Default: 3000
+    pub storage_impact_timeout_ms: Option<u64>,
+
+    /// Replace the denial box with a one-line "previously blocked (xN)"
+    /// when a session retries a command that was already blocked.
+    /// Default: true
+    pub collapse_repeat_denials: Option<bool>,
 }
 
 impl OutputConfig {
@@ -890,6 +1169,39 @@ impl OutputConfig {
     pub fn high_contrast_enabled(&self) -> bool {
         self.high_contrast.unwrap_or(false)
     }
+
+    /// Check if terminal hyperlinks are allowed (default: true).
+    #[must_use]
+    pub fn hyperlinks_enabled(&self) -> bool {
+        self.hyperlinks.unwrap_or(true)
+    }
+
+    /// Time budget for `rm -r` impact estimates (default: 150 ms).
+    #[must_use]
+    pub fn impact_budget(&self) -> std::time::Duration {
+        self.impact_budget_ms.map_or(
+            crate::impact::DEFAULT_BUDGET,
+            std::time::Duration::from_millis,
+        )
+    }
+
+    /// Timeout for bucket deletion lookups, or `None` when they are off
+    /// (the default).
+    #[must_use]
+    pub fn storage_impact_timeout(&self) -> Option<std::time::Duration> {
+        self.storage_impact.unwrap_or(false).then(|| {
+            self.storage_impact_timeout_ms.map_or(
+                crate::storage_impact::DEFAULT_TIMEOUT,
+                std::time::Duration::from_millis,
+            )
+        })
+    }
+
+    /// Check if repeated denials in a session are collapsed (default: true).
+    #[must_use]
+    pub fn collapse_repeat_denials(&self) -> bool {
+        self.collapse_repeat_denials.unwrap_or(true)
+    }
 }
 
 /// Theme configuration for rich terminal output.
@@ -911,11 +1223,19 @@ pub struct ThemeConfig {
 #[serde(default)]
 pub struct PacksConfig {
     /// List of enabled packs (e.g., `["database.postgresql", "kubernetes"]`).
+    ///
+    /// An entry may pin the pack version it was reviewed against
+    /// (`"core.git@1"`); see [`Config::pack_pin_warnings`].
     pub enabled: Vec<String>,
 
     /// List of explicitly disabled packs (for disabling sub-packs of enabled categories).
     pub disabled: Vec<String>,
 
+    /// Replace, rather than extend, the `enabled`/`disabled` lists of lower
+    /// config layers (e.g. a project that pins its exact pack set).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+
     /// Paths to custom external pack YAML files.
     ///
     /// Supports glob patterns and tilde expansion:
@@ -928,13 +1248,127 @@ pub struct PacksConfig {
     /// loading valid packs.
     #[serde(default)]
     pub custom_paths: Vec<String>,
+
+    /// Layer that contributed each `enabled` entry, keyed by pack ID.
+    #[serde(skip)]
+    pub(crate) enabled_by: BTreeMap<String, String>,
+
+    /// Layer that contributed each `disabled` entry, keyed by pack ID.
+    #[serde(skip)]
+    pub(crate) disabled_by: BTreeMap<String, String>,
+}
+
+/// Split a `packs.enabled` entry into its pack ID and optional version pin
+/// (`"core.git@1"` -> `("core.git", Some("1"))`).
+#[must_use]
+pub fn split_pack_pin(entry: &str) -> (&str, Option<&str>) {
+    match entry.split_once('@') {
+        Some((id, pin)) => (id.trim(), Some(pin.trim())),
+        None => (entry.trim(), None),
+    }
+}
+
+/// The config entry that decided a pack's effective status.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackDecision {
+    /// The `packs.enabled`/`packs.disabled` entry (pack or category ID), or
+    /// the pack itself for packs that are on by default.
+    pub entry: String,
+    /// Layer the entry came from (`system`, `user`, `project`, `DCG_CONFIG`,
+    /// `include`, `projects`, `DCG_PACKS`, `DCG_DISABLE`, `DCG_DISABLE_PACKS`,
+    /// or `default`).
+    pub layer: String,
 }
 
 impl PacksConfig {
+    /// Record `source` as the layer behind every current `enabled` entry.
+    fn set_enabled_origin(&mut self, source: &str) {
+        self.enabled_by = self
+            .enabled
+            .iter()
+            .map(|entry| (split_pack_pin(entry).0.to_string(), source.to_string()))
+            .collect();
+    }
+
+    /// Record `source` as the layer behind every current `disabled` entry.
+    fn set_disabled_origin(&mut self, source: &str) {
+        self.disabled_by = self
+            .disabled
+            .iter()
+            .map(|entry| (entry.trim().to_string(), source.to_string()))
+            .collect();
+    }
+
+    /// Version pins from `enabled` entries, keyed by pack ID.
+    #[must_use]
+    pub fn pins(&self) -> BTreeMap<&str, &str> {
+        self.enabled
+            .iter()
+            .filter_map(|entry| match split_pack_pin(entry) {
+                (id, Some(pin)) => Some((id, pin)),
+                (_, None) => None,
+            })
+            .collect()
+    }
+
+    /// Explain why `pack_id` ended up `enabled` (its effective status).
+    ///
+    /// Picks the most specific covering entry: the pack ID itself before its
+    /// category. Returns `None` for packs that are off because nothing
+    /// enabled them.
+    #[must_use]
+    pub fn decided_by(&self, pack_id: &str, enabled: bool) -> Option<PackDecision> {
+        let category = pack_id.split('.').next().unwrap_or(pack_id);
+        let (entries, origins) = if enabled {
+            (self.enabled_pack_ids_raw(), &self.enabled_by)
+        } else {
+            (
+                self.disabled.iter().map(|d| d.trim().to_string()).collect(),
+                &self.disabled_by,
+            )
+        };
+
+        for candidate in [pack_id, category] {
+            if entries.contains(candidate) {
+                return Some(PackDecision {
+                    entry: candidate.to_string(),
+                    layer: origins
+                        .get(candidate)
+                        .cloned()
+                        .unwrap_or_else(|| "config".to_string()),
+                });
+            }
+        }
+
+        (enabled && (category == "core" || pack_id == crate::packs::unicode::obfuscation::PACK_ID))
+            .then(|| PackDecision {
+                entry: pack_id.to_string(),
+                layer: "default".to_string(),
+            })
+    }
+
+    /// Pack IDs listed in `enabled`, with version pins stripped.
+    fn enabled_pack_ids_raw(&self) -> HashSet<String> {
+        self.enabled
+            .iter()
+            .map(|entry| split_pack_pin(entry).0.to_string())
+            .collect()
+    }
+
     /// Get enabled pack IDs as a deduplicated set.
     #[must_use]
     pub fn enabled_pack_ids(&self) -> HashSet<String> {
-        let mut enabled: HashSet<String> = self.enabled.iter().cloned().collect();
+        let mut enabled = self.enabled_pack_ids_raw();
+
+        // A disabled sub-pack carves itself out of an enabled category.
+        for disabled in &self.disabled {
+            if let Some((category, _)) = disabled.split_once('.') {
+                let sub_packs = crate::packs::REGISTRY.packs_in_category(category);
+                if !sub_packs.is_empty() && enabled.remove(category) {
+                    enabled.extend(sub_packs.into_iter().map(String::from));
+                }
+            }
+        }
 
         // Remove explicitly disabled packs.
         for disabled in &self.disabled {
@@ -946,6 +1380,16 @@ impl PacksConfig {
         // Core is always enabled.
         enabled.insert("core".to_string());
 
+        // Unicode obfuscation checks are on unless explicitly disabled.
+        let obfuscation = crate::packs::unicode::obfuscation::PACK_ID;
+        if !self
+            .disabled
+            .iter()
+            .any(|disabled| disabled == "unicode" || disabled == obfuscation)
+        {
+            enabled.insert(obfuscation.to_string());
+        }
+
         enabled
     }
 
@@ -999,10 +1443,11 @@ impl PacksConfig {
 /// Decision mode policy configuration.
 ///
 /// Controls how matched patterns are handled: deny (block), warn (allow with warning),
-/// or log (silent allow with optional logging).
+/// log (silent allow with optional logging), or ask (prompt the user).
 ///
 /// Defaults respect severity: Critical/High → deny, Medium → warn, Low → log.
-/// This config allows overriding the default behavior per pack or per specific rule.
+/// This config allows overriding the default behavior per severity, per pack,
+/// or per specific rule.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct PolicyConfig {
@@ -1038,6 +1483,131 @@ pub struct PolicyConfig {
     /// Takes precedence over pack-level and global overrides.
     #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
     pub rules: std::collections::HashMap<String, PolicyMode>,
+
+    /// Per-severity mode overrides.
+    /// Key is a severity label ("low", "medium", "high"); e.g. `medium = "ask"`.
+    /// Critical rules ignore this map, like pack-level overrides.
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub severity: std::collections::HashMap<String, PolicyMode>,
+
+    /// How severities combine when a command matches several rules.
+    /// If not set, the highest-severity match decides (`max`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregation: Option<SeverityAggregation>,
+
+    /// Where warn-mode notices go. If not set, they are printed to stderr only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warn_channel: Option<WarnChannel>,
+
+    /// Record warn-mode events for `dcg warnings` (default: true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warn_queue: Option<bool>,
+
+    /// Confidence (0.0-1.0) a match needs to be denied. Denials that score
+    /// lower fall back to warn. Setting this turns on confidence scoring and
+    /// replaces `[confidence] warn_threshold`; critical rules stay protected
+    /// unless `[confidence] protect_critical = false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_confidence_to_deny: Option<f32>,
+
+    /// Mode overrides that apply only to commands of one provenance.
+    /// Key is a provenance label ("agent", "human").
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub provenance: std::collections::HashMap<String, ProvenancePolicy>,
+}
+
+/// Mode overrides for one command provenance (`[policy.provenance.<name>]`).
+///
+/// Checked before the rest of `[policy]`. As there, a critical rule can only
+/// be loosened by an entry in `rules`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvenancePolicy {
+    /// Default mode for this provenance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_mode: Option<PolicyMode>,
+
+    /// Per-pack mode overrides for this provenance.
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub packs: std::collections::HashMap<String, PolicyMode>,
+
+    /// Per-rule mode overrides for this provenance.
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub rules: std::collections::HashMap<String, PolicyMode>,
+}
+
+impl ProvenancePolicy {
+    /// The mode this block sets for a match, if any.
+    fn mode_for(
+        &self,
+        pack_id: Option<&str>,
+        pattern_name: Option<&str>,
+        severity: Option<crate::packs::Severity>,
+    ) -> Option<PolicyMode> {
+        if let (Some(pack), Some(pattern)) = (pack_id, pattern_name) {
+            if let Some(mode) = self.rules.get(&format!("{pack}:{pattern}")) {
+                return Some(*mode);
+            }
+        }
+        if matches!(severity, Some(crate::packs::Severity::Critical)) {
+            return None;
+        }
+        pack_id
+            .and_then(|pack| self.packs.get(pack).copied())
+            .or(self.default_mode)
+    }
+}
+
+/// Severity aggregation policy for commands that match several rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityAggregation {
+    /// The highest-severity match decides.
+    #[default]
+    Max,
+    /// Start from the highest severity and raise it one level for each
+    /// additional medium-or-higher match (capped at critical).
+    Sum,
+    /// The match that appears first in the command decides, whatever its severity.
+    First,
+}
+
+impl SeverityAggregation {
+    /// Config/log label for this policy.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Max => "max",
+            Self::Sum => "sum",
+            Self::First => "first",
+        }
+    }
+}
+
+/// How a warn-mode decision is surfaced to the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarnChannel {
+    /// Human-readable notice on stderr; the hook writes nothing to stdout.
+    #[default]
+    Stderr,
+    /// The stderr notice plus an explicit `allow` hook response whose
+    /// `permissionDecisionReason` carries the warning.
+    Advisory,
+    /// A single-line JSON warning event on stderr instead of the notice.
+    Event,
+}
+
+impl WarnChannel {
+    /// Config label for this channel.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Stderr => "stderr",
+            Self::Advisory => "advisory",
+            Self::Event => "event",
+        }
+    }
 }
 
 /// Policy mode for overriding default decision behavior.
@@ -1048,8 +1618,14 @@ pub enum PolicyMode {
     Deny,
     /// Warn but allow (print warning to stderr, no JSON deny).
     Warn,
+    /// Warn once per rule, command and session; identical repeats pass silently.
+    #[serde(rename = "warn_once")]
+    WarnOnce,
     /// Log only (silent allow, record for history).
     Log,
+    /// Prompt the user through the hook's `"ask"` decision; deny where nobody
+    /// can be asked.
+    Ask,
 }
 
 impl PolicyMode {
@@ -1059,19 +1635,40 @@ impl PolicyMode {
         match self {
             Self::Deny => crate::packs::DecisionMode::Deny,
             Self::Warn => crate::packs::DecisionMode::Warn,
+            Self::WarnOnce => crate::packs::DecisionMode::WarnOnce,
             Self::Log => crate::packs::DecisionMode::Log,
+            Self::Ask => crate::packs::DecisionMode::Ask,
         }
     }
 }
 
 impl PolicyConfig {
+    /// The configured severity aggregation policy (`max` when unset).
+    #[must_use]
+    pub fn aggregation(&self) -> SeverityAggregation {
+        self.aggregation.unwrap_or_default()
+    }
+
+    /// The configured warn channel (`stderr` when unset).
+    #[must_use]
+    pub fn warn_channel(&self) -> WarnChannel {
+        self.warn_channel.unwrap_or_default()
+    }
+
+    /// Whether warn-mode events are queued for `dcg warnings` (on when unset).
+    #[must_use]
+    pub fn warn_queue(&self) -> bool {
+        self.warn_queue.unwrap_or(true)
+    }
+
     /// Resolve the effective decision mode for a given rule.
     ///
     /// Priority (highest to lowest):
     /// 1. Rule-specific override (via `rules["pack_id:pattern_name"]`)
     /// 2. Pack-specific override (via `packs["pack_id"]`)
-    /// 3. Global default (`default_mode`)
-    /// 4. Severity-based default (from pattern's severity)
+    /// 3. Severity-specific override (via `severity["medium"]`)
+    /// 4. Global default (`default_mode`)
+    /// 5. Severity-based default (from pattern's severity)
     #[must_use]
     pub fn resolve_mode(
         &self,
@@ -1082,6 +1679,52 @@ impl PolicyConfig {
         self.resolve_mode_at(Utc::now(), pack_id, pattern_name, severity)
     }
 
+    /// Resolve the decision mode for a command of the given provenance.
+    ///
+    /// `[policy.provenance.<provenance>]` is consulted first (rule, then pack,
+    /// then its `default_mode`); anything it leaves unset falls through to
+    /// [`Self::resolve_mode`].
+    #[must_use]
+    pub fn resolve_mode_for(
+        &self,
+        provenance: crate::provenance::Provenance,
+        pack_id: Option<&str>,
+        pattern_name: Option<&str>,
+        severity: Option<crate::packs::Severity>,
+    ) -> crate::packs::DecisionMode {
+        self.resolve_mode_for_profile(provenance, None, pack_id, pattern_name, severity)
+    }
+
+    /// [`Self::resolve_mode_for`] for a command from an agent with `profile`.
+    ///
+    /// The profile's `severity_modes` take precedence over `[policy.severity]`;
+    /// rule and pack overrides still win over both.
+    #[must_use]
+    pub fn resolve_mode_for_profile(
+        &self,
+        provenance: crate::provenance::Provenance,
+        profile: Option<&AgentProfile>,
+        pack_id: Option<&str>,
+        pattern_name: Option<&str>,
+        severity: Option<crate::packs::Severity>,
+    ) -> crate::packs::DecisionMode {
+        self.provenance
+            .get(provenance.label())
+            .and_then(|overrides| overrides.mode_for(pack_id, pattern_name, severity))
+            .map_or_else(
+                || {
+                    self.resolve_mode_with(
+                        Utc::now(),
+                        profile.map(|profile| &profile.severity_modes),
+                        pack_id,
+                        pattern_name,
+                        severity,
+                    )
+                },
+                PolicyMode::to_decision_mode,
+            )
+    }
+
     #[must_use]
     pub fn resolve_mode_at(
         &self,
@@ -1089,6 +1732,17 @@ impl PolicyConfig {
         pack_id: Option<&str>,
         pattern_name: Option<&str>,
         severity: Option<crate::packs::Severity>,
+    ) -> crate::packs::DecisionMode {
+        self.resolve_mode_with(now, None, pack_id, pattern_name, severity)
+    }
+
+    fn resolve_mode_with(
+        &self,
+        now: DateTime<Utc>,
+        profile_severity_modes: Option<&std::collections::HashMap<String, PolicyMode>>,
+        pack_id: Option<&str>,
+        pattern_name: Option<&str>,
+        severity: Option<crate::packs::Severity>,
     ) -> crate::packs::DecisionMode {
         // 1. Rule-specific override
         if let (Some(pack), Some(pattern)) = (pack_id, pattern_name) {
@@ -1111,7 +1765,18 @@ impl PolicyConfig {
             }
         }
 
-        // 3. Global default (optionally gated by observe_until)
+        // 3. Severity-specific override (agent profile first)
+        if let Some(severity) = severity {
+            let label = severity.label();
+            let mode = profile_severity_modes
+                .and_then(|modes| modes.get(label))
+                .or_else(|| self.severity.get(label));
+            if let Some(mode) = mode {
+                return mode.to_decision_mode();
+            }
+        }
+
+        // 4. Global default (optionally gated by observe_until)
         let effective_default_mode = self
             .observe_until
             .as_ref()
@@ -1128,7 +1793,7 @@ impl PolicyConfig {
             return mode.to_decision_mode();
         }
 
-        // 4. Severity-based default
+        // 5. Severity-based default
         severity.map_or(crate::packs::DecisionMode::Deny, |s| s.default_mode())
     }
 }
@@ -1472,6 +2137,10 @@ pub enum AllowOverride {
         pattern: String,
         /// Optional condition (e.g., "CI=true").
         when: Option<String>,
+        /// Regexes the command's extracted arguments must match, e.g.
+        /// `{ namespace = "dev|sandbox" }` (see [`crate::arg_context`]).
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        args: BTreeMap<String, String>,
     },
 }
 
@@ -1513,13 +2182,17 @@ pub struct BlockOverride {
     pub pattern: String,
     /// Human-readable reason for blocking.
     pub reason: String,
+    /// Regexes the command's extracted arguments must match for the block
+    /// to apply, e.g. `{ context = "prod.*" }` (see [`crate::arg_context`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, String>,
 }
 
 /// Redaction mode for command history.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum HistoryRedactionMode {
-    /// Store commands without redaction.
+    /// Store commands without redaction (inline credentials are still masked).
     None,
     /// Redact sensitive values using pattern-based filters.
     #[default]
@@ -1714,30 +2387,521 @@ impl std::fmt::Display for StrictnessLevel {
     }
 }
 
-/// Git branch-aware strictness configuration.
+/// Messaging pack configuration.
 ///
-/// This allows different strictness levels based on the current git branch,
-/// providing more protection on important branches and more freedom on
-/// experimental branches.
+/// Destructive queue, topic, stream, and vhost commands from `messaging.*`
+/// packs are escalated to Critical severity when one of their arguments looks
+/// like a production resource name. Names are matched case-insensitively
+/// against glob patterns; ARNs and paths are split on `/`, `:`, and `=` first.
 ///
 /// # Example Configuration (TOML)
 ///
 /// ```toml
-/// [git_awareness]
-/// enabled = true
-///
-/// # Protected branches get extra scrutiny
-/// protected_branches = ["main", "master", "production", "release/*"]
-/// protected_strictness = "all"  # Block everything including Low severity
-///
-/// # Feature branches get more freedom
-/// relaxed_branches = ["feature/*", "experiment/*", "sandbox/*"]
-/// relaxed_strictness = "critical"  # Only block Critical severity
-///
-/// # Packs to disable on relaxed branches
-/// relaxed_disabled_packs = []
-///
-/// # Default strictness when not matching any pattern
+/// [messaging]
+/// production_name_patterns = ["prod", "*-prod", "prod-*", "production*", "live"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessagingConfig {
+    /// Glob patterns for production-like target names.
+    /// Set to an empty list to disable escalation.
+    pub production_name_patterns: Vec<String>,
+}
+
+impl Default for MessagingConfig {
+    fn default() -> Self {
+        Self {
+            production_name_patterns: [
+                "prod",
+                "prd",
+                "live",
+                "production*",
+                "prod[-_.]*",
+                "*[-_.]prod",
+                "*[-_.]prod[-_.]*",
+                "*[-_.]prd",
+                "*[-_.]production*",
+                "*[-_.]live",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+impl MessagingConfig {
+    /// Returns `true` if `name` matches any production name pattern.
+    #[must_use]
+    pub fn is_production_name(&self, name: &str) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        self.production_name_patterns.iter().any(|pattern| {
+            glob::Pattern::new(pattern).is_ok_and(|compiled| compiled.matches_with(name, options))
+        })
+    }
+}
+
+/// Production-looking resource names, for every pack.
+///
+/// When an argument of a denied command (a bucket, namespace, database,
+/// queue, ...) matches one of the patterns, the match is raised one severity
+/// level and its explanation notes that the target looks like production.
+/// Patterns are case-insensitive globs, like `[messaging]`; paths and ARNs
+/// are split on `/`, `:`, `=` and `,` first. Empty by default.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [naming]
+/// production_patterns = ["prod-*", "*-prd", "live-*"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingConfig {
+    /// Glob patterns for production-like resource names.
+    pub production_patterns: Vec<String>,
+}
+
+impl NamingConfig {
+    /// The first pattern that `name` matches, if any.
+    #[must_use]
+    pub fn production_match(&self, name: &str) -> Option<&str> {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        self.production_patterns
+            .iter()
+            .find(|pattern| {
+                glob::Pattern::new(pattern)
+                    .is_ok_and(|compiled| compiled.matches_with(name, options))
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Critical systemd units.
+///
+/// `systemctl stop`, `disable`, or `mask` on one of these units is denied at
+/// Critical severity (`system.services:systemctl-stop-critical`); other units
+/// fall under the High `systemctl-stop` rule. Names are case-sensitive globs
+/// and a `.service` suffix on the command line is ignored. A later config
+/// layer replaces the list.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [services]
+/// critical_units = ["sshd", "systemd-networkd", "docker", "postgresql*"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServicesConfig {
+    /// Glob patterns for critical unit names.
+    pub critical_units: Vec<String>,
+}
+
+impl Default for ServicesConfig {
+    fn default() -> Self {
+        Self {
+            critical_units: [
+                "ssh",
+                "sshd",
+                "systemd-networkd",
+                "NetworkManager",
+                "network",
+                "networking",
+                "systemd-resolved",
+                "firewalld",
+                "ufw",
+                "docker",
+                "containerd",
+                "dbus",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+impl ServicesConfig {
+    /// Returns `true` if `unit` matches any critical unit pattern.
+    #[must_use]
+    pub fn is_critical_unit(&self, unit: &str) -> bool {
+        self.critical_units
+            .iter()
+            .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|compiled| compiled.matches(unit)))
+    }
+}
+
+/// Canary paths and resource names (honeytokens).
+///
+/// Any command that references one is denied at Critical severity, ahead of
+/// allow overrides and allowlists, and the hook POSTs a JSON event to
+/// `webhook_url`. Lists from every config layer are combined.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [canary]
+/// paths = ["/srv/DO_NOT_DELETE"]
+/// names = ["prod-canary"]
+/// webhook_url = "https://hooks.example.com/dcg-canary"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanaryConfig {
+    /// Canary paths; the path and anything under it match.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+
+    /// Canary resource names (buckets, databases, hosts), matched
+    /// case-insensitively as whole words.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+
+    /// URL that receives a JSON POST when the hook trips a canary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+impl CanaryConfig {
+    /// Compile the configured canaries for matching.
+    #[must_use]
+    pub fn compile(&self) -> crate::canary::CanaryTripwires {
+        crate::canary::CanaryTripwires::new(&self.paths, &self.names)
+    }
+}
+
+/// Always-on self-protection.
+///
+/// Commands that uninstall the hook, overwrite the dcg binary, make its config
+/// world-writable, or export bypass `DCG_*` variables are denied at Critical
+/// severity ahead of allow overrides and allowlists (rule ids
+/// `self_protection:<rule>`). This section is only read from the system config
+/// (`/etc/dcg/config.toml`); in any other layer it is ignored with a warning.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [self_protection]
+/// enabled = true
+/// binary_paths = ["/opt/tools/bin/dcg-hook"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelfProtectionConfig {
+    /// Run the self-protection checks (default: true).
+    pub enabled: bool,
+
+    /// Extra install locations of the dcg binary, protected like `*/dcg`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub binary_paths: Vec<String>,
+}
+
+impl Default for SelfProtectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            binary_paths: Vec::new(),
+        }
+    }
+}
+
+impl SelfProtectionConfig {
+    /// Compile the checks for matching.
+    #[must_use]
+    pub fn compile(&self) -> crate::self_protection::SelfProtection {
+        crate::self_protection::SelfProtection::new(self.enabled, &self.binary_paths)
+    }
+}
+
+/// Per-invocation environment overrides.
+///
+/// `DCG_TRACE`, `DCG_DISABLE_PACKS`, and `DCG_PROFILE` change what a single
+/// hook invocation does without touching any config file. Setting
+/// `allow_env_overrides = false` makes dcg ignore them, so they cannot be used
+/// to loosen the guard. Like `[self_protection]`, this section is only read
+/// from the system config (`/etc/dcg/config.toml`).
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [security]
+/// allow_env_overrides = false
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Honour `DCG_TRACE`, `DCG_DISABLE_PACKS`, and `DCG_PROFILE` (default: true).
+    pub allow_env_overrides: bool,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            allow_env_overrides: true,
+        }
+    }
+}
+
+/// Chat channels for the approvals queue.
+///
+/// While `dcg serve` runs, each request queued for `dcg approvals` is posted
+/// to Slack (an incoming webhook) and/or a Matrix room, with Approve and Deny
+/// links back to `callback_url`. The Matrix access token is read from
+/// `DCG_MATRIX_ACCESS_TOKEN`.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [approvals]
+/// callback_url = "https://dcg.example.com"
+/// slack_webhook_url = "https://hooks.slack.com/services/T000/B000/XXXX"
+/// matrix_homeserver = "https://matrix.example.org"
+/// matrix_room_id = "!ops:example.org"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalsConfig {
+    /// Public base URL that reaches `dcg serve` (usually through a reverse
+    /// proxy); Approve and Deny links point here.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+
+    /// Slack incoming webhook URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slack_webhook_url: Option<String>,
+
+    /// Matrix homeserver base URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix_homeserver: Option<String>,
+
+    /// Matrix room ID (`!room:server`) to post in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix_room_id: Option<String>,
+}
+
+/// Where `dcg trash put` sends files.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [trash]
+/// backend = "dcg"          # or "system" for the desktop trash / recycle bin
+/// dir = "~/.dcg/trash"     # default: $XDG_STATE_HOME/dcg/trash
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Trash backend. Default: `dcg`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<crate::trash::TrashBackend>,
+
+    /// Directory for the `dcg` backend.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+impl TrashConfig {
+    /// The configured backend (default: `dcg`).
+    #[must_use]
+    pub fn backend(&self) -> crate::trash::TrashBackend {
+        self.backend.unwrap_or_default()
+    }
+
+    /// Directory for the `dcg` backend, with `~` expanded.
+    #[must_use]
+    pub fn dir(&self) -> PathBuf {
+        self.dir
+            .as_deref()
+            .and_then(|dir| resolve_config_path_value(dir, None))
+            .unwrap_or_else(crate::trash::default_dir)
+    }
+}
+
+/// Organization-specific guidance shown when a command is denied.
+///
+/// `denial_template` replaces the default "ask the user for explicit
+/// permission" line, so blocked users land on internal runbooks and exception
+/// processes instead.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [messages]
+/// denial_template = "Blocked by {rule_id}. File an exception at {ticket_url} or ask {security_contact}."
+/// ticket_url = "https://wiki.example.com/dcg-exceptions"
+/// security_contact = "#security-help"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessagesConfig {
+    /// Guidance text with `{rule_id}`, `{pack}`, `{reason}`, `{command}`,
+    /// `{ticket_url}` and `{security_contact}` placeholders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub denial_template: Option<String>,
+
+    /// Value of `{ticket_url}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticket_url: Option<String>,
+
+    /// Value of `{security_contact}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_contact: Option<String>,
+}
+
+/// What a denial template can refer to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DenialTemplateVars<'a> {
+    pub rule_id: Option<&'a str>,
+    pub pack: Option<&'a str>,
+    pub reason: &'a str,
+    pub command: &'a str,
+}
+
+impl MessagesConfig {
+    /// Variables a denial template may use.
+    pub const TEMPLATE_VARIABLES: &'static [&'static str] = &[
+        "rule_id",
+        "pack",
+        "reason",
+        "command",
+        "ticket_url",
+        "security_contact",
+    ];
+
+    /// The configured guidance for a denial, or `None` to keep the default.
+    ///
+    /// Unset values render as empty strings; unknown `{names}` are kept
+    /// verbatim so a typo shows up in the message rather than vanishing.
+    #[must_use]
+    pub fn render_denial(&self, vars: &DenialTemplateVars<'_>) -> Option<String> {
+        let template = self.denial_template.as_deref()?.trim();
+        if template.is_empty() {
+            return None;
+        }
+        let rendered = substitute_placeholders(template, |name| match name {
+            "rule_id" => Some(vars.rule_id.unwrap_or_default()),
+            "pack" => Some(vars.pack.unwrap_or_default()),
+            "reason" => Some(vars.reason),
+            "command" => Some(vars.command),
+            "ticket_url" => Some(self.ticket_url.as_deref().unwrap_or_default()),
+            "security_contact" => Some(self.security_contact.as_deref().unwrap_or_default()),
+            _ => None,
+        });
+        Some(rendered)
+    }
+
+    /// Placeholders in `denial_template` that are not template variables.
+    #[must_use]
+    pub fn unknown_template_variables(&self) -> Vec<String> {
+        let mut unknown: Vec<String> = Vec::new();
+        if let Some(template) = self.denial_template.as_deref() {
+            substitute_placeholders(template, |name| {
+                if !Self::TEMPLATE_VARIABLES.contains(&name) && !unknown.iter().any(|u| u == name) {
+                    unknown.push(name.to_string());
+                }
+                None
+            });
+        }
+        unknown
+    }
+}
+
+/// Replace each `{name}` in `template` with `lookup(name)`, keeping it
+/// verbatim when `lookup` returns `None`. A `{` without a closing `}` before
+/// the next `{` is literal text.
+fn substitute_placeholders<'v>(
+    template: &str,
+    mut lookup: impl FnMut(&str) -> Option<&'v str>,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find(['{', '}']) {
+            Some(close) if after[close..].starts_with('}') => {
+                let name = &after[..close];
+                match lookup(name) {
+                    Some(value) => out.push_str(value),
+                    None => out.push_str(&rest[open..=open + close + 1]),
+                }
+                rest = &after[close + 1..];
+            }
+            _ => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// What persisted logs record for each evaluated command.
+///
+/// Applies to the log file, the history database (and so `dcg stats` and
+/// `dcg suggest-allowlist`), and canary webhook payloads.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [privacy]
+/// log_mode = "redacted"   # "full" | "hash" | "redacted"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// `full` keeps the command, `hash` keeps only a salted hash, `redacted`
+    /// replaces paths and literals with placeholders.
+    pub log_mode: crate::privacy::PrivacyLogMode,
+}
+
+impl PrivacyConfig {
+    /// The form of `command` to write to logs, history, and webhooks.
+    #[must_use]
+    pub fn log_command<'a>(&self, command: &'a str) -> std::borrow::Cow<'a, str> {
+        self.log_mode.apply(command)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct PrivacyConfigLayer {
+    log_mode: Option<crate::privacy::PrivacyLogMode>,
+}
+
+/// Git branch-aware strictness configuration.
+///
+/// This allows different strictness levels based on the current git branch,
+/// providing more protection on important branches and more freedom on
+/// experimental branches.
+///
+/// # Example Configuration (TOML)
+///
+/// ```toml
+/// [git_awareness]
+/// enabled = true
+///
+/// # Protected branches get extra scrutiny
+/// protected_branches = ["main", "master", "production", "release/*"]
+/// protected_strictness = "all"  # Block everything including Low severity
+///
+/// # Feature branches get more freedom
+/// relaxed_branches = ["feature/*", "experiment/*", "sandbox/*"]
+/// relaxed_strictness = "critical"  # Only block Critical severity
+///
+/// # Packs to disable on relaxed branches
+/// relaxed_disabled_packs = []
+///
+/// # Default strictness when not matching any pattern
 /// default_strictness = "high"  # Block Critical and High (normal behavior)
 ///
 /// # Show branch context in output
@@ -1959,6 +3123,16 @@ pub struct AgentProfile {
 
     /// If true, skip all allowlist checks for this agent (more restrictive).
     pub disabled_allowlist: bool,
+
+    /// Default grant for `dcg allow-once` when no `--exact`/`--rule`/`--session`
+    /// flag is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_once_scope: Option<crate::pending_exceptions::AllowOnceGrant>,
+
+    /// Per-severity mode overrides for this agent, ahead of
+    /// `[policy.severity]` (e.g. `medium = "ask"`).
+    #[serde(skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub severity_modes: std::collections::HashMap<String, PolicyMode>,
 }
 
 /// Agent-specific profiles configuration.
@@ -1977,6 +3151,11 @@ pub struct AgentsConfig {
     /// Use "unknown" for undetected/custom agents.
     #[serde(flatten)]
     pub profiles: std::collections::HashMap<String, AgentProfile>,
+
+    /// Profile forced for this invocation (`DCG_PROFILE`) instead of the
+    /// detected agent's. Not read from config files.
+    #[serde(skip)]
+    pub forced_profile: Option<String>,
 }
 
 impl AgentsConfig {
@@ -2004,12 +3183,37 @@ impl AgentsConfig {
         self.profile_for(agent_key).trust_level
     }
 
+    /// Whether any profile sets `severity_modes`, i.e. whether the agent
+    /// needs detecting to resolve a decision mode.
+    #[must_use]
+    pub fn has_severity_modes(&self) -> bool {
+        !self.default.severity_modes.is_empty()
+            || self
+                .profiles
+                .values()
+                .any(|profile| !profile.severity_modes.is_empty())
+    }
+
     /// Check if allowlists are disabled for an agent.
     #[must_use]
     pub fn allowlist_disabled_for(&self, agent_key: &str) -> bool {
         self.profile_for(agent_key).disabled_allowlist
     }
 
+    /// Get the default allow-once grant for an agent.
+    ///
+    /// Falls back to the default profile's setting, then to an exact-command grant.
+    #[must_use]
+    pub fn allow_once_scope_for(
+        &self,
+        agent_key: &str,
+    ) -> crate::pending_exceptions::AllowOnceGrant {
+        self.profile_for(agent_key)
+            .allow_once_scope
+            .or(self.default.allow_once_scope)
+            .unwrap_or_default()
+    }
+
     /// Get the profile for an agent using its config key.
     ///
     /// This is a convenience method that accepts an [`Agent`](crate::agent::Agent)
@@ -2040,6 +3244,8 @@ pub struct CompiledAllowOverride {
     /// For simple overrides, this always returns true.
     /// For conditional overrides, this checks the environment.
     condition: ConditionCheck,
+    /// Conditions on the command's extracted arguments (empty = none).
+    args: crate::arg_context::ArgConditions,
 }
 
 /// Condition check type - either always true or checks an env var.
@@ -2073,7 +3279,7 @@ impl CompiledAllowOverride {
     #[inline]
     #[must_use]
     pub fn matches(&self, command: &str) -> bool {
-        self.condition.is_met() && self.regex.is_match(command)
+        self.condition.is_met() && self.regex.is_match(command) && self.args.all_match(command)
     }
 }
 
@@ -2086,6 +3292,8 @@ pub struct CompiledBlockOverride {
     pub pattern: String,
     /// Human-readable reason for blocking.
     pub reason: String,
+    /// Conditions on the command's extracted arguments (empty = none).
+    args: crate::arg_context::ArgConditions,
 }
 
 impl CompiledBlockOverride {
@@ -2095,7 +3303,7 @@ impl CompiledBlockOverride {
     #[inline]
     #[must_use]
     pub fn matches(&self, command: &str) -> Option<&str> {
-        if self.regex.is_match(command) {
+        if self.regex.is_match(command) && self.args.any_match(command) {
             Some(&self.reason)
         } else {
             None
@@ -2115,6 +3323,18 @@ pub struct CompiledOverrides {
     pub block: Vec<CompiledBlockOverride>,
     /// Patterns that failed to compile (for diagnostics).
     pub invalid_patterns: Vec<InvalidPattern>,
+    /// `[canary]` tripwires, checked before everything else.
+    pub canaries: crate::canary::CanaryTripwires,
+    /// `[self_protection]` checks, run alongside the canaries.
+    pub self_protection: crate::self_protection::SelfProtection,
+    /// Ignore `dcg allow-once` entries (risk assessment, not enforcement).
+    pub skip_allow_once: bool,
+    /// `[messaging]` production names, used to escalate messaging matches.
+    pub messaging: MessagingConfig,
+    /// `[naming]` production names, used to escalate matches from any pack.
+    pub naming: NamingConfig,
+    /// `[services]` critical units, used to classify `systemctl stop` matches.
+    pub services: ServicesConfig,
 }
 
 /// Record of a pattern that failed to compile.
@@ -2168,8 +3388,12 @@ impl OverridesConfig {
 
         // Compile allow overrides
         for allow in &self.allow {
-            match CompiledRegex::new(allow.pattern()) {
-                Ok(regex) => {
+            let args = match allow {
+                AllowOverride::Conditional { args, .. } => ArgConditions::compile(args),
+                AllowOverride::Simple(_) => Ok(ArgConditions::default()),
+            };
+            match CompiledRegex::new_user(allow.pattern()).and_then(|regex| Ok((regex, args?))) {
+                Ok((regex, args)) => {
                     let condition = match allow {
                         AllowOverride::Simple(_)
                         | AllowOverride::Conditional { when: None, .. } => ConditionCheck::Always,
@@ -2193,6 +3417,7 @@ impl OverridesConfig {
                         regex,
                         pattern: allow.pattern().to_string(),
                         condition,
+                        args,
                     });
                 }
                 Err(e) => {
@@ -2207,12 +3432,15 @@ impl OverridesConfig {
 
         // Compile block overrides
         for block in &self.block {
-            match CompiledRegex::new(&block.pattern) {
-                Ok(regex) => {
+            let compiled_block = CompiledRegex::new_user(&block.pattern)
+                .and_then(|regex| Ok((regex, ArgConditions::compile(&block.args)?)));
+            match compiled_block {
+                Ok((regex, args)) => {
                     compiled.block.push(CompiledBlockOverride {
                         regex,
                         pattern: block.pattern.clone(),
                         reason: block.reason.clone(),
+                        args,
                     });
                 }
                 Err(e) => {
@@ -2231,12 +3459,13 @@ impl OverridesConfig {
                 if pattern.trim().is_empty() {
                     continue;
                 }
-                match CompiledRegex::new(pattern) {
+                match CompiledRegex::new_user(pattern) {
                     Ok(regex) => {
                         compiled.allow.push(CompiledAllowOverride {
                             regex,
                             pattern: pattern.clone(),
                             condition: ConditionCheck::Always,
+                            args: ArgConditions::default(),
                         });
                     }
                     Err(e) => {
@@ -2268,12 +3497,13 @@ impl OverridesConfig {
                     continue;
                 }
 
-                match CompiledRegex::new(&rule.pattern) {
+                match CompiledRegex::new_user(&rule.pattern) {
                     Ok(regex) => {
                         compiled.allow.push(CompiledAllowOverride {
                             regex,
                             pattern: rule.pattern.clone(),
                             condition: ConditionCheck::Always,
+                            args: ArgConditions::default(),
                         });
                     }
                     Err(e) => {
@@ -2386,11 +3616,15 @@ impl Config {
     /// Priority (highest to lowest):
     /// 1. Environment variables (settings overrides)
     /// 2. Explicit config file (`DCG_CONFIG=/path/to/config.toml`)
-    /// 3. Project config (`.dcg.toml` in repo root)
+    /// 3. Project config (`.dcg.toml` or `.dcg/policy.toml` in repo root)
     /// 4. User config (`$XDG_CONFIG_HOME/dcg/config.toml`, `~/.config/dcg/config.toml`,
     ///    or platform-native config dir)
     /// 5. System config (`/etc/dcg/config.toml`)
     /// 6. Compiled defaults
+    ///
+    /// Any file layer may list `include = ["../shared/dcg-policy.toml"]`. Included
+    /// files are merged immediately *below* the including file, so the including
+    /// file always wins. Every file considered is recorded in [`Config::sources`].
     #[must_use]
     pub fn load() -> Self {
         // Start with truly empty defaults - packs must be explicitly enabled.
@@ -2399,14 +3633,14 @@ impl Config {
         let cwd = env::current_dir().ok();
 
         // Optional explicit config path override (highest-priority file config).
-        let explicit_layer = env::var(ENV_CONFIG_PATH)
+        let explicit_layers = env::var(ENV_CONFIG_PATH)
             .ok()
             .and_then(|value| resolve_config_path_value(&value, cwd.as_deref()))
-            .and_then(|path| Self::load_layer_from_file(&path));
+            .and_then(|path| Self::load_layers_with_includes(&path, ConfigSourceKind::Explicit));
 
         // Load system config (lowest priority of file configs)
-        if let Some(system_config) = Self::load_system_config_layer() {
-            config.merge_layer(system_config);
+        if let Some(system_layers) = Self::load_system_config_layers() {
+            config.merge_loaded_layers(system_layers);
         }
 
         // Load user config
@@ -2414,20 +3648,20 @@ impl Config {
         // If an explicit config file is present and valid, we treat it as the
         // user-level config and skip loading the default user config path to
         // reduce layering confusion.
-        if explicit_layer.is_none() {
-            if let Some(user_config) = Self::load_user_config_layer() {
-                config.merge_layer(user_config);
+        if explicit_layers.is_none() {
+            if let Some(user_layers) = Self::load_user_config_layers() {
+                config.merge_loaded_layers(user_layers);
             }
         }
 
         // Load project config (if in a git repo)
-        if let Some(project_config) = Self::load_project_config_layer_from(cwd.as_deref()) {
-            config.merge_layer(project_config);
+        if let Some(project_layers) = Self::load_project_config_layers_from(cwd.as_deref()) {
+            config.merge_loaded_layers(project_layers);
         }
 
         // Apply explicit config last among file configs (if present and valid).
-        if let Some(explicit_layer) = explicit_layer {
-            config.merge_layer(explicit_layer);
+        if let Some(explicit_layers) = explicit_layers {
+            config.merge_loaded_layers(explicit_layers);
         }
 
         // Apply environment variable overrides (highest priority)
@@ -2468,6 +3702,194 @@ impl Config {
         }
     }
 
+    /// Load a config file plus everything it (transitively) includes.
+    ///
+    /// Returns `None` when the root file is missing or invalid, so callers can
+    /// fall back to other candidate paths. Otherwise the returned layers are in
+    /// merge order: includes first (depth-first, in list order), then the file
+    /// itself.
+    fn load_layers_with_includes(path: &Path, kind: ConfigSourceKind) -> Option<Vec<LoadedLayer>> {
+        let cache = IncludeCache::new(IncludeCache::default_dir());
+        Self::load_layers_with_include_cache(path, kind, &cache)
+    }
+
+    /// [`Self::load_layers_with_includes`], caching remote includes in `cache`.
+    fn load_layers_with_include_cache(
+        path: &Path,
+        kind: ConfigSourceKind,
+        cache: &IncludeCache,
+    ) -> Option<Vec<LoadedLayer>> {
+        let layer = Self::load_layer_from_file(path)?;
+        let mut stack = vec![canonical_or_self(path)];
+        let mut out = Vec::new();
+        Self::expand_includes(layer, path, kind, None, cache, &mut stack, &mut out);
+        Some(out)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn expand_includes(
+        mut layer: ConfigLayer,
+        path: &Path,
+        kind: ConfigSourceKind,
+        included_from: Option<&Path>,
+        cache: &IncludeCache,
+        stack: &mut Vec<PathBuf>,
+        out: &mut Vec<LoadedLayer>,
+    ) {
+        let includes = std::mem::take(&mut layer.include);
+        let from_remote = is_remote_include(&path.to_string_lossy());
+        let base_dir = path.parent();
+
+        for spec in includes {
+            let raw = spec.location();
+            let skipped = |location: String, note: &str| LoadedLayer {
+                layer: None,
+                source: ConfigSource {
+                    kind: ConfigSourceKind::Include,
+                    location,
+                    included_from: Some(path.display().to_string()),
+                    applied: false,
+                    note: Some(note.to_string()),
+                },
+            };
+
+            let remote = is_remote_include(raw);
+            if from_remote && !remote {
+                out.push(skipped(
+                    raw.to_string(),
+                    "remote includes can only include other URLs",
+                ));
+                continue;
+            }
+
+            let include_path = if remote {
+                PathBuf::from(raw.trim())
+            } else {
+                let Some(include_path) = resolve_config_path_value(raw, base_dir) else {
+                    continue;
+                };
+                include_path
+            };
+            let canonical = if remote {
+                include_path.clone()
+            } else {
+                canonical_or_self(&include_path)
+            };
+            let location = include_path.display().to_string();
+
+            if stack.contains(&canonical) {
+                out.push(skipped(location, "include cycle"));
+                continue;
+            }
+            if stack.len() > MAX_INCLUDE_DEPTH {
+                out.push(skipped(location, "include depth limit exceeded"));
+                continue;
+            }
+
+            let mut note = None;
+            let included = if remote {
+                match cache.load(&location, spec.pin(), Utc::now()) {
+                    Ok(fetched) => match toml::from_str::<ConfigLayer>(&fetched.content) {
+                        Ok(included) => {
+                            note = fetched.warning;
+                            included
+                        }
+                        Err(e) => {
+                            out.push(skipped(location, &format!("invalid TOML: {e}")));
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        out.push(skipped(location, &e));
+                        continue;
+                    }
+                }
+            } else {
+                let Some(included) = Self::load_layer_from_file(&include_path) else {
+                    out.push(skipped(location, "missing or invalid"));
+                    continue;
+                };
+                included
+            };
+
+            stack.push(canonical);
+            let first = out.len();
+            Self::expand_includes(
+                included,
+                &include_path,
+                ConfigSourceKind::Include,
+                Some(path),
+                cache,
+                stack,
+                out,
+            );
+            stack.pop();
+            if let Some(own) = out[first..].last_mut() {
+                own.source.note = note;
+            }
+        }
+
+        out.push(LoadedLayer {
+            layer: Some(layer),
+            source: ConfigSource {
+                kind,
+                location: path.display().to_string(),
+                included_from: included_from.map(|p| p.display().to_string()),
+                applied: true,
+                note: None,
+            },
+        });
+    }
+
+    /// Merge loaded layers in order and record their provenance.
+    ///
+    /// `[self_protection]` and `[security]` are only taken from the system
+    /// config itself; other layers are writable by the agent they guard.
+    fn merge_loaded_layers(&mut self, layers: Vec<LoadedLayer>) {
+        for mut loaded in layers {
+            if let Some(mut layer) = loaded.layer {
+                let system = loaded.source.kind == ConfigSourceKind::System;
+                let mut ignored = Vec::new();
+                if let Some(self_protection) = layer.self_protection.take() {
+                    if system {
+                        self.merge_self_protection_layer(self_protection);
+                    } else {
+                        ignored.push("[self_protection]");
+                    }
+                }
+                if let Some(security) = layer.security.take() {
+                    if system {
+                        self.merge_security_layer(&security);
+                    } else {
+                        ignored.push("[security]");
+                    }
+                }
+                if !ignored.is_empty() {
+                    let verb = if ignored.len() == 1 { "is" } else { "are" };
+                    loaded.source.note = Some(format!(
+                        "{} {verb} only read from /etc/dcg/config.toml; ignored",
+                        ignored.join(" and ")
+                    ));
+                }
+                self.merge_layer_from(layer, loaded.source.kind.label());
+            }
+            self.sources.push(loaded.source);
+        }
+    }
+
+    /// Warnings about config layers that were skipped or applied with a
+    /// caveat (include cycles, unreachable remote includes, ...).
+    ///
+    /// [`Config::load`] never prints these; `dcg doctor` reports them, and the
+    /// hook does with `general.verbose`.
+    #[must_use]
+    pub fn load_warnings(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .filter_map(ConfigSource::warning)
+            .collect()
+    }
+
     /// Load configuration from a specific file.
     #[must_use]
     pub fn load_from_file(path: &Path) -> Option<Self> {
@@ -2476,9 +3898,9 @@ impl Config {
     }
 
     /// Load system-wide configuration.
-    fn load_system_config_layer() -> Option<ConfigLayer> {
+    fn load_system_config_layers() -> Option<Vec<LoadedLayer>> {
         let path = PathBuf::from("/etc/dcg").join(CONFIG_FILE_NAME);
-        Self::load_layer_from_file(&path)
+        Self::load_layers_with_includes(&path, ConfigSourceKind::System)
     }
 
     /// Load user configuration.
@@ -2486,14 +3908,16 @@ impl Config {
     /// Checks XDG_CONFIG_HOME, XDG-style (`~/.config/dcg/`), and platform-native paths.
     /// This ensures users can use `~/.config/dcg/config.toml` on all platforms,
     /// including macOS where `dirs::config_dir()` returns `~/Library/Application Support`.
-    fn load_user_config_layer() -> Option<ConfigLayer> {
+    fn load_user_config_layers() -> Option<Vec<LoadedLayer>> {
         // First try XDG_CONFIG_HOME (if set)
         if let Ok(xdg_home) = env::var("XDG_CONFIG_HOME") {
             if let Some(xdg_home) = resolve_config_path_value(&xdg_home, None) {
                 let xdg_path = xdg_home.join("dcg").join(CONFIG_FILE_NAME);
                 if xdg_path.exists() {
-                    if let Some(layer) = Self::load_layer_from_file(&xdg_path) {
-                        return Some(layer);
+                    if let Some(layers) =
+                        Self::load_layers_with_includes(&xdg_path, ConfigSourceKind::User)
+                    {
+                        return Some(layers);
                     }
                 }
             }
@@ -2504,8 +3928,10 @@ impl Config {
         if let Some(home) = dirs::home_dir() {
             let xdg_path = home.join(".config").join("dcg").join(CONFIG_FILE_NAME);
             if xdg_path.exists() {
-                if let Some(layer) = Self::load_layer_from_file(&xdg_path) {
-                    return Some(layer);
+                if let Some(layers) =
+                    Self::load_layers_with_includes(&xdg_path, ConfigSourceKind::User)
+                {
+                    return Some(layers);
                 }
             }
         }
@@ -2513,22 +3939,31 @@ impl Config {
         // Fall back to platform-native path (e.g., ~/Library/Application Support/dcg/ on macOS)
         let config_dir = dirs::config_dir()?;
         let path = config_dir.join("dcg").join(CONFIG_FILE_NAME);
-        Self::load_layer_from_file(&path)
+        Self::load_layers_with_includes(&path, ConfigSourceKind::User)
     }
 
-    /// Load project-level configuration (`.dcg.toml` in repo root).
-    fn load_project_config_layer_from(start_dir: Option<&Path>) -> Option<ConfigLayer> {
-        let start_dir = start_dir?;
-        let repo_root = find_repo_root(start_dir, REPO_ROOT_SEARCH_MAX_HOPS)?;
-        let config_path = repo_root.join(PROJECT_CONFIG_NAME);
-        if !config_path.exists() {
-            return None;
-        }
-        Self::load_layer_from_file(&config_path)
+    /// Load project-level configuration (`.dcg.toml`, else `.dcg/policy.toml`, in repo root).
+    fn load_project_config_layers_from(start_dir: Option<&Path>) -> Option<Vec<LoadedLayer>> {
+        let config_path = project_config_path(start_dir?)?;
+        Self::load_layers_with_includes(&config_path, ConfigSourceKind::Project)
     }
 
     /// Merge another config layer into this one (other takes priority when set).
+    #[cfg(test)]
     fn merge_layer(&mut self, other: ConfigLayer) {
+        self.merge_layer_from(other, "config");
+    }
+
+    /// The defaults with one config file's TOML merged on top.
+    #[cfg(test)]
+    pub(crate) fn with_layer_toml(input: &str) -> Result<Self, toml::de::Error> {
+        let mut config = Self::default();
+        config.merge_layer(toml::from_str(input)?);
+        Ok(config)
+    }
+
+    /// Merge a layer, attributing its pack entries to `source`.
+    fn merge_layer_from(&mut self, other: ConfigLayer, source: &str) {
         if let Some(general) = other.general {
             self.merge_general_layer(general);
         }
@@ -2542,7 +3977,7 @@ impl Config {
         }
 
         if let Some(packs) = other.packs {
-            self.merge_packs_layer(packs);
+            self.merge_packs_layer(packs, source);
         }
 
         if let Some(policy) = other.policy {
@@ -2577,6 +4012,68 @@ impl Config {
             self.merge_git_awareness_layer(git_awareness);
         }
 
+        if let Some(messaging) = other.messaging {
+            self.merge_messaging_layer(messaging);
+        }
+
+        if let Some(patterns) = other.naming.and_then(|naming| naming.production_patterns) {
+            self.naming.production_patterns = patterns;
+        }
+
+        if let Some(units) = other.services.and_then(|services| services.critical_units) {
+            self.services.critical_units = units;
+        }
+
+        if let Some(canary) = other.canary {
+            self.canary.paths.extend(canary.paths);
+            self.canary.names.extend(canary.names);
+            if canary.webhook_url.is_some() {
+                self.canary.webhook_url = canary.webhook_url;
+            }
+        }
+
+        if let Some(approvals) = other.approvals {
+            if approvals.callback_url.is_some() {
+                self.approvals.callback_url = approvals.callback_url;
+            }
+            if approvals.slack_webhook_url.is_some() {
+                self.approvals.slack_webhook_url = approvals.slack_webhook_url;
+            }
+            if approvals.matrix_homeserver.is_some() {
+                self.approvals.matrix_homeserver = approvals.matrix_homeserver;
+            }
+            if approvals.matrix_room_id.is_some() {
+                self.approvals.matrix_room_id = approvals.matrix_room_id;
+            }
+        }
+
+        if let Some(trash) = other.trash {
+            if trash.backend.is_some() {
+                self.trash.backend = trash.backend;
+            }
+            if trash.dir.is_some() {
+                self.trash.dir = trash.dir;
+            }
+        }
+
+        if let Some(privacy) = other.privacy {
+            if let Some(log_mode) = privacy.log_mode {
+                self.privacy.log_mode = log_mode;
+            }
+        }
+
+        if let Some(messages) = other.messages {
+            if messages.denial_template.is_some() {
+                self.messages.denial_template = messages.denial_template;
+            }
+            if messages.ticket_url.is_some() {
+                self.messages.ticket_url = messages.ticket_url;
+            }
+            if messages.security_contact.is_some() {
+                self.messages.security_contact = messages.security_contact;
+            }
+        }
+
         if let Some(agents) = other.agents {
             self.merge_agents_layer(agents);
         }
@@ -2587,6 +4084,21 @@ impl Config {
         }
     }
 
+    fn merge_self_protection_layer(&mut self, self_protection: SelfProtectionConfigLayer) {
+        if let Some(enabled) = self_protection.enabled {
+            self.self_protection.enabled = enabled;
+        }
+        if let Some(binary_paths) = self_protection.binary_paths {
+            self.self_protection.binary_paths = binary_paths;
+        }
+    }
+
+    const fn merge_security_layer(&mut self, security: &SecurityConfigLayer) {
+        if let Some(allow_env_overrides) = security.allow_env_overrides {
+            self.security.allow_env_overrides = allow_env_overrides;
+        }
+    }
+
     fn merge_general_layer(&mut self, general: GeneralConfigLayer) {
         if let Some(color) = general.color {
             self.general.color = color;
@@ -2612,6 +4124,12 @@ impl Config {
         if let Some(check_updates) = general.check_updates {
             self.general.check_updates = check_updates;
         }
+        if let Some(environment) = general.environment {
+            self.general.environment = Some(environment);
+        }
+        if let Some(bootstrap_allowlist) = general.bootstrap_allowlist {
+            self.general.bootstrap_allowlist = bootstrap_allowlist;
+        }
     }
 
     const fn merge_output_layer(&mut self, output: OutputConfigLayer) {
@@ -2624,6 +4142,21 @@ impl Config {
         if let Some(high_contrast) = output.high_contrast {
             self.output.high_contrast = Some(high_contrast);
         }
+        if let Some(hyperlinks) = output.hyperlinks {
+            self.output.hyperlinks = Some(hyperlinks);
+        }
+        if let Some(impact_budget_ms) = output.impact_budget_ms {
+            self.output.impact_budget_ms = Some(impact_budget_ms);
+        }
+        if let Some(storage_impact) = output.storage_impact {
+            self.output.storage_impact = Some(storage_impact);
+        }
+        if let Some(timeout_ms) = output.storage_impact_timeout_ms {
+            self.output.storage_impact_timeout_ms = Some(timeout_ms);
+        }
+        if let Some(collapse) = output.collapse_repeat_denials {
+            self.output.collapse_repeat_denials = Some(collapse);
+        }
     }
 
     fn merge_theme_layer(&mut self, theme: ThemeConfigLayer) {
@@ -2638,9 +4171,20 @@ impl Config {
         }
     }
 
-    fn merge_packs_layer(&mut self, packs: PacksConfig) {
+    fn merge_packs_layer(&mut self, mut packs: PacksConfig, source: &str) {
+        packs.set_enabled_origin(source);
+        packs.set_disabled_origin(source);
+        if packs.pinned {
+            self.packs.enabled.clear();
+            self.packs.disabled.clear();
+            self.packs.enabled_by.clear();
+            self.packs.disabled_by.clear();
+            self.packs.pinned = true;
+        }
         self.packs.enabled.extend(packs.enabled);
         self.packs.disabled.extend(packs.disabled);
+        self.packs.enabled_by.extend(packs.enabled_by);
+        self.packs.disabled_by.extend(packs.disabled_by);
         self.packs.custom_paths.extend(packs.custom_paths);
     }
 
@@ -2651,8 +4195,29 @@ impl Config {
         if policy.observe_until.is_some() {
             self.policy.observe_until = policy.observe_until;
         }
+        if policy.aggregation.is_some() {
+            self.policy.aggregation = policy.aggregation;
+        }
+        if policy.warn_channel.is_some() {
+            self.policy.warn_channel = policy.warn_channel;
+        }
+        if policy.warn_queue.is_some() {
+            self.policy.warn_queue = policy.warn_queue;
+        }
+        if policy.min_confidence_to_deny.is_some() {
+            self.policy.min_confidence_to_deny = policy.min_confidence_to_deny;
+        }
+        for (provenance, overrides) in policy.provenance {
+            let merged = self.policy.provenance.entry(provenance).or_default();
+            if overrides.default_mode.is_some() {
+                merged.default_mode = overrides.default_mode;
+            }
+            merged.packs.extend(overrides.packs);
+            merged.rules.extend(overrides.rules);
+        }
         self.policy.packs.extend(policy.packs);
         self.policy.rules.extend(policy.rules);
+        self.policy.severity.extend(policy.severity);
     }
 
     fn merge_overrides_layer(&mut self, overrides: OverridesConfig) {
@@ -2786,6 +4351,12 @@ impl Config {
         if let Some(require_env) = interactive.require_env {
             self.interactive.require_env = Some(require_env);
         }
+        if let Some(learning) = interactive.learning {
+            self.interactive.learning = learning;
+        }
+        if let Some(approval_queue) = interactive.approval_queue {
+            self.interactive.approval_queue = approval_queue;
+        }
     }
 
     fn merge_git_awareness_layer(&mut self, git_awareness: GitAwarenessConfigLayer) {
@@ -2812,6 +4383,12 @@ impl Config {
         }
     }
 
+    fn merge_messaging_layer(&mut self, messaging: MessagingConfigLayer) {
+        if let Some(patterns) = messaging.production_name_patterns {
+            self.messaging.production_name_patterns = patterns;
+        }
+    }
+
     fn merge_agents_layer(&mut self, agents: AgentsConfig) {
         // Merge default profile
         self.agents.default = agents.default;
@@ -2831,11 +4408,15 @@ impl Config {
         // DCG_PACKS="core,database.postgresql,kubernetes"
         if let Some(packs) = get_env(&format!("{ENV_PREFIX}_PACKS")) {
             self.packs.enabled = packs.split(',').map(|s| s.trim().to_string()).collect();
+            self.packs
+                .set_enabled_origin(&format!("{ENV_PREFIX}_PACKS"));
         }
 
         // DCG_DISABLE="kubernetes.helm"
         if let Some(disable) = get_env(&format!("{ENV_PREFIX}_DISABLE")) {
             self.packs.disabled = disable.split(',').map(|s| s.trim().to_string()).collect();
+            self.packs
+                .set_disabled_origin(&format!("{ENV_PREFIX}_DISABLE"));
         }
 
         // DCG_CUSTOM_PATHS="/path/to/pack.yaml,~/.config/dcg/packs/*.yaml"
@@ -2886,6 +4467,12 @@ impl Config {
             self.output.high_contrast = Some(parsed);
         }
 
+        // DCG_HYPERLINKS=0
+        if let Some(hyperlinks) = get_env("DCG_HYPERLINKS") {
+            let parsed = parse_env_bool(&hyperlinks).unwrap_or(true);
+            self.output.hyperlinks = Some(parsed);
+        }
+
         // -----------------------------------------------------------------
         // Heredoc scanning (env overrides)
         // -----------------------------------------------------------------
@@ -2922,7 +4509,7 @@ impl Config {
         // Policy config (env overrides)
         // -----------------------------------------------------------------
 
-        // DCG_POLICY_DEFAULT_MODE=deny|warn|log
+        // DCG_POLICY_DEFAULT_MODE=deny|warn|warn_once|log|ask
         if let Some(mode) = get_env(&format!("{ENV_PREFIX}_POLICY_DEFAULT_MODE")) {
             if let Some(parsed) = parse_policy_mode(&mode) {
                 self.policy.default_mode = Some(parsed);
@@ -2934,6 +4521,27 @@ impl Config {
             self.policy.observe_until = ObserveUntil::parse(&observe_until);
         }
 
+        // DCG_POLICY_AGGREGATION=max|sum|first
+        if let Some(aggregation) = get_env(&format!("{ENV_PREFIX}_POLICY_AGGREGATION")) {
+            if let Some(parsed) = parse_severity_aggregation(&aggregation) {
+                self.policy.aggregation = Some(parsed);
+            }
+        }
+
+        // DCG_POLICY_WARN_CHANNEL=stderr|advisory|event
+        if let Some(channel) = get_env(&format!("{ENV_PREFIX}_POLICY_WARN_CHANNEL")) {
+            if let Some(parsed) = parse_warn_channel(&channel) {
+                self.policy.warn_channel = Some(parsed);
+            }
+        }
+
+        // DCG_POLICY_MIN_CONFIDENCE_TO_DENY=0.0..1.0
+        if let Some(threshold) = get_env(&format!("{ENV_PREFIX}_POLICY_MIN_CONFIDENCE_TO_DENY")) {
+            if let Some(parsed) = parse_confidence_threshold(&threshold) {
+                self.policy.min_confidence_to_deny = Some(parsed);
+            }
+        }
+
         // -----------------------------------------------------------------
         // History config (env overrides)
         // -----------------------------------------------------------------
@@ -2952,6 +4560,13 @@ impl Config {
             }
         }
 
+        // DCG_PRIVACY_LOG_MODE=full|hash|redacted
+        if let Some(mode) = get_env(&format!("{ENV_PREFIX}_PRIVACY_LOG_MODE")) {
+            if let Some(parsed) = crate::privacy::PrivacyLogMode::parse(&mode) {
+                self.privacy.log_mode = parsed;
+            }
+        }
+
         // -----------------------------------------------------------------
         // Git awareness config (env overrides)
         // -----------------------------------------------------------------
@@ -3014,6 +4629,60 @@ impl Config {
                 self.git_awareness.warn_if_not_git = parsed;
             }
         }
+
+        if self.security.allow_env_overrides {
+            self.apply_invocation_overrides_from(&mut get_env);
+        }
+    }
+
+    /// Per-invocation overrides for debugging a single hook run. The system
+    /// config can switch these off (`security.allow_env_overrides = false`).
+    fn apply_invocation_overrides_from<F>(&mut self, mut get_env: F)
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        // DCG_TRACE=1
+        if let Some(trace) = get_env(&format!("{ENV_PREFIX}_TRACE")) {
+            self.general.trace = parse_env_bool(&trace).unwrap_or(true);
+        }
+
+        // DCG_DISABLE_PACKS="cicd.*,storage.s3" (added to packs.disabled)
+        let disable_packs_var = format!("{ENV_PREFIX}_DISABLE_PACKS");
+        if let Some(disable) = get_env(&disable_packs_var) {
+            for entry in disable.split(',').map(str::trim) {
+                let entry = entry.strip_suffix(".*").unwrap_or(entry);
+                if entry.is_empty() || self.packs.disabled.iter().any(|d| d == entry) {
+                    continue;
+                }
+                self.packs.disabled.push(entry.to_string());
+                self.packs
+                    .disabled_by
+                    .insert(entry.to_string(), disable_packs_var.clone());
+            }
+        }
+
+        // DCG_PROFILE=ci
+        if let Some(profile) = get_env(&format!("{ENV_PREFIX}_PROFILE")) {
+            let profile = profile.trim();
+            if !profile.is_empty() {
+                self.agents.forced_profile = Some(profile.to_string());
+            }
+        }
+    }
+
+    /// Compile `[overrides]` patterns together with `[canary]` tripwires.
+    ///
+    /// Prefer this over `overrides.compile()` wherever commands are evaluated,
+    /// so canaries are enforced.
+    #[must_use]
+    pub fn compile_overrides(&self) -> CompiledOverrides {
+        let mut compiled = self.overrides.compile();
+        compiled.canaries = self.canary.compile();
+        compiled.self_protection = self.self_protection.compile();
+        compiled.messaging = self.messaging.clone();
+        compiled.naming = self.naming.clone();
+        compiled.services = self.services.clone();
+        compiled
     }
 
     /// Get a reference to the policy config.
@@ -3022,6 +4691,20 @@ impl Config {
         &self.policy
     }
 
+    /// Confidence scoring settings with `policy.min_confidence_to_deny`
+    /// applied: when set, scoring is on and that threshold is used.
+    #[must_use]
+    pub fn confidence_settings(&self) -> ConfidenceConfig {
+        match self.policy.min_confidence_to_deny {
+            Some(threshold) => ConfidenceConfig {
+                enabled: true,
+                warn_threshold: threshold.clamp(0.0, 1.0),
+                protect_critical: self.confidence.protect_critical,
+            },
+            None => self.confidence.clone(),
+        }
+    }
+
     /// Check if the bypass flag is set (escape hatch).
     #[must_use]
     pub fn is_bypassed() -> bool {
@@ -3037,7 +4720,11 @@ impl Config {
         for (project_pattern, project_config) in &self.projects {
             if path_str.starts_with(project_pattern) {
                 if let Some(packs) = &project_config.packs {
-                    return packs.clone();
+                    let mut packs = packs.clone();
+                    let source = format!("projects.\"{project_pattern}\"");
+                    packs.set_enabled_origin(&source);
+                    packs.set_disabled_origin(&source);
+                    return packs;
                 }
             }
         }
@@ -3046,6 +4733,20 @@ impl Config {
         self.packs.clone()
     }
 
+    /// Get the pack configuration that applies to the current directory.
+    #[must_use]
+    pub fn effective_packs(&self) -> PacksConfig {
+        if self.projects.is_empty() {
+            return self.packs.clone();
+        }
+
+        if let Ok(cwd) = std::env::current_dir() {
+            return self.effective_packs_for_project(&cwd);
+        }
+
+        self.packs.clone()
+    }
+
     /// Get enabled pack IDs as a deduplicated set.
     #[must_use]
     pub fn enabled_pack_ids(&self) -> HashSet<String> {
@@ -3053,11 +4754,41 @@ impl Config {
             return self.packs.enabled_pack_ids();
         }
 
-        if let Ok(cwd) = std::env::current_dir() {
-            return self.effective_packs_for_project(&cwd).enabled_pack_ids();
-        }
+        self.effective_packs().enabled_pack_ids()
+    }
 
-        self.packs.enabled_pack_ids()
+    /// Warnings about `packs.enabled` version pins (`"core.git@1"`) that do
+    /// not match the installed packs.
+    ///
+    /// A mismatched pin never disables the pack; `dcg doctor` and `dcg packs`
+    /// report these, and the hook does with `general.verbose`.
+    #[must_use]
+    pub fn pack_pin_warnings(&self) -> Vec<String> {
+        let packs = self.effective_packs();
+        crate::packs::load_external_packs(&self.packs.expand_custom_paths());
+        packs
+            .pins()
+            .into_iter()
+            .filter_map(|(id, pin)| {
+                let Ok(pinned) = pin.trim_start_matches('v').parse::<u32>() else {
+                    return Some(format!(
+                        "packs.enabled: invalid version pin '{id}@{pin}' (expected '{id}@<major>')"
+                    ));
+                };
+                match crate::packs::installed_pack_version(id) {
+                    None if !crate::packs::REGISTRY.packs_in_category(id).is_empty() => Some(format!(
+                        "packs.enabled: '{id}@{pin}' pins a category; pin individual packs instead"
+                    )),
+                    None => Some(format!(
+                        "packs.enabled: pinned pack '{id}@{pin}' is not installed"
+                    )),
+                    Some(installed) if installed != pinned => Some(format!(
+                        "packs.enabled: '{id}' is pinned to version {pinned} but version {installed} is installed"
+                    )),
+                    Some(_) => None,
+                }
+            })
+            .collect()
     }
 
     /// Get enabled pack IDs adjusted for an agent's profile.
@@ -3066,8 +4797,16 @@ impl Config {
     /// on top of the base configuration.
     #[must_use]
     pub fn enabled_pack_ids_for_agent(&self, agent: &crate::agent::Agent) -> HashSet<String> {
+        self.enabled_pack_ids_for_profile(agent.config_key())
+    }
+
+    /// Get enabled pack IDs adjusted for the `[agents.<agent_key>]` profile.
+    ///
+    /// Uses the same fallback as [`AgentsConfig::profile_for`].
+    #[must_use]
+    pub fn enabled_pack_ids_for_profile(&self, agent_key: &str) -> HashSet<String> {
         let mut packs = self.enabled_pack_ids();
-        let profile = self.agents.profile_for_agent(agent);
+        let profile = self.agents.profile_for(agent_key);
 
         // Remove disabled packs (and their sub-packs)
         for disabled in &profile.disabled_packs {
@@ -3111,6 +4850,24 @@ impl Config {
         self.heredoc.settings()
     }
 
+    /// Load the default allowlists, gating entry `environments` against
+    /// this config's `general.environment`, plus the layer generated from the
+    /// current project's manifests unless `general.bootstrap_allowlist` is off.
+    #[must_use]
+    pub fn load_allowlists(&self) -> crate::LayeredAllowlist {
+        let mut allowlists = crate::load_default_allowlists()
+            .with_configured_environment(self.general.environment.clone());
+        if self.general.bootstrap_allowlist {
+            if let Some(bootstrap) = env::current_dir()
+                .ok()
+                .and_then(|cwd| crate::allowlist_bootstrap::detect(&cwd))
+            {
+                allowlists.add_generated_layer(bootstrap.layer());
+            }
+        }
+        allowlists
+    }
+
     /// Get the path to the user config file (creates dir if needed).
     #[must_use]
     pub fn user_config_path() -> Option<PathBuf> {
@@ -3172,8 +4929,7 @@ impl Config {
                     "database.postgresql".to_string(),
                     "containers.docker".to_string(),
                 ],
-                disabled: vec![],
-                custom_paths: vec![],
+                ..PacksConfig::default()
             },
             policy: PolicyConfig::default(),
             overrides: OverridesConfig::default(),
@@ -3182,9 +4938,20 @@ impl Config {
             logging: crate::logging::LoggingConfig::default(),
             history: HistoryConfig::default(),
             git_awareness: GitAwarenessConfig::default(),
+            messaging: MessagingConfig::default(),
+            naming: NamingConfig::default(),
+            services: ServicesConfig::default(),
+            canary: CanaryConfig::default(),
+            self_protection: SelfProtectionConfig::default(),
+            security: SecurityConfig::default(),
+            approvals: ApprovalsConfig::default(),
+            trash: TrashConfig::default(),
+            privacy: PrivacyConfig::default(),
+            messages: MessagesConfig::default(),
             agents: AgentsConfig::default(),
             projects: std::collections::HashMap::new(),
             interactive: crate::interactive::InteractiveConfig::default(),
+            sources: Vec::new(),
         }
     }
 
@@ -3195,6 +4962,9 @@ impl Config {
         r#"# dcg configuration
 # https://github.com/Dicklesworthstone/destructive_command_guard
 
+# Schema version of this file; `dcg migrate` upgrades older files.
+config_version = 2
+
 [general]
 # Color output: "auto" | "always" | "never"
 color = "auto"
@@ -3211,6 +4981,14 @@ verbose = false
 # Hook evaluation budget override (milliseconds)
 # hook_timeout_ms = 200
 
+# Environment for allowlist entries with `environments = [...]`.
+# DCG_ENV overrides this; default is "ci" under CI, otherwise "local".
+# environment = "staging"
+
+# Allow the project's own build and test commands (npm run <script>,
+# cargo test, pytest, ...) generated from its manifests and lockfiles.
+# bootstrap_allowlist = true
+
 #─────────────────────────────────────────────────────────────
 # OUTPUT CONFIGURATION
 #─────────────────────────────────────────────────────────────
@@ -3227,6 +5005,26 @@ verbose = false
 # High-contrast mode (ASCII borders + black/white palette).
 # high_contrast = false
 
+# Clickable OSC 8 links for rule IDs and the issue link, on terminals that
+# support them (detected via TERM_PROGRAM / VTE_VERSION).
+# hyperlinks = true
+
+# Milliseconds spent estimating what a blocked `rm -r` would delete
+# ("would delete 12,400 files ..., 3.2 GB, includes .git"). 0 disables.
+# impact_budget_ms = 150
+
+# For blocked bucket deletions (`aws s3 rb --force`, `gsutil rm -r`, ...),
+# ask the provider CLI for the object count and versioning/retention
+# settings, using credentials already in the environment. Makes network
+# requests, each cut off after storage_impact_timeout_ms.
+# storage_impact = false
+# storage_impact_timeout_ms = 3000
+
+# When an agent retries a command that was already blocked in the same
+# session, print a one-line "previously blocked (xN)" instead of the full
+# box. The hook still answers with the full JSON deny.
+# collapse_repeat_denials = true
+
 #─────────────────────────────────────────────────────────────
 # THEME CONFIGURATION
 #─────────────────────────────────────────────────────────────
@@ -3286,6 +5084,13 @@ disabled = [
     # "kubernetes.kustomize",  # Example: disable kustomize if you don't use it
 ]
 
+# Lists from system, user, project and DCG_CONFIG layers are combined, and a
+# disabled entry beats an enabled one. Set `pinned = true` (usually in a
+# project's .dcg.toml) to replace lower layers' lists with this layer's exact
+# set. Entries may pin a pack version, e.g. "core.git@1"; `dcg doctor` and
+# `dcg packs` warn when the installed pack no longer matches the pin.
+# pinned = false
+
 # Load custom packs from YAML files.
 # Supports glob patterns and ~ for home directory.
 # See docs/custom-packs.md for pack authoring guide.
@@ -3303,7 +5108,12 @@ custom_paths = [
 # Optional global override for how matched rules are handled:
 # - "deny": block (default)
 # - "warn": allow but print a warning to stderr (no hook JSON deny)
+# - "warn_once": like "warn" for the first occurrence of a command per rule
+#   and agent session; identical repeats in that session pass silently
 # - "log": allow silently (no stderr/stdout; optional log_file history)
+# - "ask": answer the hook with permissionDecision "ask" so the agent prompts
+#   you to approve (Claude Code); denies where nobody can be asked (CI,
+#   DCG_NON_INTERACTIVE, agents without an ask decision)
 #
 # If unset, dcg uses severity defaults:
 # - critical/high => deny
@@ -3316,6 +5126,28 @@ custom_paths = [
 # When set and before the timestamp, `default_mode` applies (defaulting to "warn" when unset).
 # When set and after the timestamp, `default_mode` is ignored and severity defaults apply.
 # observe_until = "2026-02-01T00:00:00Z"
+#
+# How severities combine when one command matches several rules:
+# - "max": the highest-severity match decides (default)
+# - "sum": raise the highest severity one level per additional medium+ match
+# - "first": the match that appears first in the command decides
+# aggregation = "max"
+#
+# How "warn" decisions reach the agent:
+# - "stderr": print a notice to stderr only (default)
+# - "advisory": also answer the hook with an explicit allow whose reason
+#   carries the warning (note: an allow response skips the agent's own
+#   permission prompt for that command)
+# - "event": print a single-line JSON warning event to stderr instead
+# warn_channel = "stderr"
+#
+# Queue warn-mode events for review with `dcg warnings` (default: true).
+# warn_queue = true
+#
+# Confidence (0.0-1.0) a match needs to be denied; lower-confidence matches
+# (e.g. a destructive token inside a quoted string) fall back to "warn".
+# Setting this enables confidence scoring and overrides [confidence] warn_threshold.
+# min_confidence_to_deny = 0.5
 
 [policy.packs]
 # Override mode for an entire pack (pack_id => mode).
@@ -3327,10 +5159,27 @@ custom_paths = [
 # Override mode for a specific rule (rule_id => mode).
 # Examples:
 # "core.git:push-force-long" = "warn"
+# "core.git:stash-drop" = "warn_once" # still tuning: warn once per session
 # "core.git:reset-hard" = "deny"     # keep critical rules as hard blocks
 #
 # Safety: Critical rules are only loosened via explicit per-rule overrides.
 
+[policy.severity]
+# Override mode for every rule of a severity (low/medium/high => mode),
+# after rule and pack overrides. Critical rules ignore this table.
+# Example: prompt for medium-risk commands, keep high/critical as blocks:
+# medium = "ask"
+#
+# Agent profiles can set their own mapping, which wins over this table:
+# [agents.claude-code.severity_modes]
+# medium = "ask"
+
+# Overrides for one command provenance: "agent" (hook, MCP server) or "human"
+# (`dcg test --provenance human`, or `DCG_PROVENANCE=human dcg test` from a
+# shell integration). Same keys as [policy]; checked before the tables above.
+# [policy.provenance.human]
+# rules = { "core.git:push-force-long" = "warn" }
+
 #─────────────────────────────────────────────────────────────
 # CUSTOM OVERRIDES
 #─────────────────────────────────────────────────────────────
@@ -3358,6 +5207,76 @@ block = [
     # { pattern = "curl.*\\| ?sh", reason = "Piping curl to shell is dangerous" },
 ]
 
+#─────────────────────────────────────────────────────────────
+# CANARY TRIPWIRES
+#─────────────────────────────────────────────────────────────
+
+# Paths and resource names nothing should ever touch. Any command that
+# mentions one is denied (critical) before overrides or allowlists apply.
+# [canary]
+# paths = ["/srv/DO_NOT_DELETE"]
+# names = ["prod-canary"]
+# webhook_url = "https://hooks.example.com/dcg-canary"
+
+#─────────────────────────────────────────────────────────────
+# SELF-PROTECTION
+#─────────────────────────────────────────────────────────────
+
+# Commands that uninstall the hook, overwrite the dcg binary, make its config
+# world-writable, or export bypass DCG_* variables are always denied.
+# Only read from /etc/dcg/config.toml; ignored in user and project configs.
+# [self_protection]
+# enabled = true
+# binary_paths = ["/opt/tools/bin/dcg-hook"]
+
+# DCG_TRACE, DCG_DISABLE_PACKS and DCG_PROFILE adjust a single hook run.
+# Set allow_env_overrides = false to ignore them.
+# Only read from /etc/dcg/config.toml; ignored in user and project configs.
+# [security]
+# allow_env_overrides = true
+
+#─────────────────────────────────────────────────────────────
+# PRODUCTION NAMES
+#─────────────────────────────────────────────────────────────
+
+# Resource names that look like production (globs, case-insensitive).
+# A denied command whose bucket, namespace, database or queue matches is
+# raised one severity level and its explanation says so.
+# [naming]
+# production_patterns = ["prod-*", "*-prd", "live-*"]
+
+#─────────────────────────────────────────────────────────────
+# CRITICAL SERVICES
+#─────────────────────────────────────────────────────────────
+
+# systemd units whose stop/disable/mask is denied as critical (globs).
+# Setting the list replaces the default one.
+# [services]
+# critical_units = ["ssh", "sshd", "systemd-networkd", "NetworkManager", "docker"]
+
+#─────────────────────────────────────────────────────────────
+# TRASH
+#─────────────────────────────────────────────────────────────
+
+# Where `dcg trash put` moves files (the safe replacement for `rm -rf`).
+# "dcg" keeps them in `dir`, restorable with `dcg trash restore`;
+# "system" uses the desktop trash / recycle bin.
+# [trash]
+# backend = "dcg"
+# dir = "~/.dcg/trash"
+
+#─────────────────────────────────────────────────────────────
+# DENIAL MESSAGES
+#─────────────────────────────────────────────────────────────
+
+# Replace the closing "ask the user for explicit permission" guidance of
+# every denial with your own text. Variables: {rule_id}, {pack}, {reason},
+# {command}, {ticket_url}, {security_contact}.
+# [messages]
+# denial_template = "Blocked by {rule_id}. Request an exception at {ticket_url} or ask {security_contact}."
+# ticket_url = "https://wiki.example.com/dcg-exceptions"
+# security_contact = "security@example.com"
+
 #─────────────────────────────────────────────────────────────
 # HEREDOC / INLINE SCRIPT SCANNING
 #─────────────────────────────────────────────────────────────
@@ -3390,6 +5309,7 @@ fallback_on_timeout = true
 enabled = false
 
 # Redaction mode for stored commands: "pattern" | "full" | "none"
+# Inline credentials (access keys, passwords, bearer tokens) are always masked.
 redaction_mode = "pattern"
 
 # Retention window and database size limits.
@@ -3399,6 +5319,41 @@ max_size_mb = 500
 # Optional database path override.
 # database_path = "~/.config/dcg/history.db"
 
+#─────────────────────────────────────────────────────────────
+# PRIVACY
+#─────────────────────────────────────────────────────────────
+
+# What the log file, history, and canary webhooks record for each command:
+#   "full"     - the command as typed
+#   "hash"     - only a salted hash (sha256:...); salt from DCG_PRIVACY_SALT
+#                or a per-install file in the dcg state directory
+#   "redacted" - paths and literals replaced by <path>, <str>, <num>, <url>
+# dcg suggest-allowlist works from "full" and "redacted" entries.
+# [privacy]
+# log_mode = "full"
+
+#─────────────────────────────────────────────────────────────
+# INTERACTIVE LEARNING
+#─────────────────────────────────────────────────────────────
+
+# Queue commands allowed with a Medium-severity warning, then run
+# `dcg review` to turn them into block rules or allowlist entries.
+# [interactive]
+# learning = true
+
+# In unattended runs (CI, or DCG_NON_INTERACTIVE set), queue each denial
+# for `dcg approvals`; approving one lets the agent's next retry through.
+# approval_queue = true
+
+# While `dcg serve` runs, post queued requests to Slack and/or Matrix with
+# Approve and Deny links. callback_url must reach `dcg serve` (e.g. through
+# a reverse proxy); the Matrix token comes from DCG_MATRIX_ACCESS_TOKEN.
+# [approvals]
+# callback_url = "https://dcg.example.com"
+# slack_webhook_url = "https://hooks.slack.com/services/T000/B000/XXXX"
+# matrix_homeserver = "https://matrix.example.org"
+# matrix_room_id = "!ops:example.org"
+
 #─────────────────────────────────────────────────────────────
 # PROJECT-SPECIFIC OVERRIDES
 #─────────────────────────────────────────────────────────────
@@ -3430,11 +5385,39 @@ fn parse_policy_mode(value: &str) -> Option<PolicyMode> {
     match value.trim().to_ascii_lowercase().as_str() {
         "deny" | "block" => Some(PolicyMode::Deny),
         "warn" | "warning" => Some(PolicyMode::Warn),
+        "warn_once" | "warn-once" | "warnonce" => Some(PolicyMode::WarnOnce),
         "log" | "log-only" | "logonly" => Some(PolicyMode::Log),
+        "ask" | "prompt" => Some(PolicyMode::Ask),
+        _ => None,
+    }
+}
+
+fn parse_severity_aggregation(value: &str) -> Option<SeverityAggregation> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "max" | "highest" => Some(SeverityAggregation::Max),
+        "sum" | "escalate" => Some(SeverityAggregation::Sum),
+        "first" | "first-match" => Some(SeverityAggregation::First),
+        _ => None,
+    }
+}
+
+fn parse_warn_channel(value: &str) -> Option<WarnChannel> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "stderr" => Some(WarnChannel::Stderr),
+        "advisory" | "allow-reason" => Some(WarnChannel::Advisory),
+        "event" | "json" => Some(WarnChannel::Event),
         _ => None,
     }
 }
 
+fn parse_confidence_threshold(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|threshold| (0.0..=1.0).contains(threshold))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObserveUntil {
     raw: String,
@@ -3543,12 +5526,12 @@ mod tests {
             packs: PacksConfig {
                 enabled: vec!["kubernetes".to_string(), "kubernetes.helm".to_string()],
                 disabled: vec!["kubernetes.helm".to_string()],
-                custom_paths: vec![],
+                ..PacksConfig::default()
             },
             ..Default::default()
         };
-        let enabled = config.enabled_pack_ids();
-        assert!(enabled.contains("kubernetes"));
+        let enabled = crate::packs::REGISTRY.expand_enabled(&config.enabled_pack_ids());
+        assert!(enabled.contains("kubernetes.kubectl"));
         assert!(!enabled.contains("kubernetes.helm"));
     }
 
@@ -3565,8 +5548,7 @@ mod tests {
             ProjectConfig {
                 packs: Some(PacksConfig {
                     enabled: vec!["database.postgresql".to_string()],
-                    disabled: Vec::new(),
-                    custom_paths: vec![],
+                    ..PacksConfig::default()
                 }),
                 overrides: None,
             },
@@ -3578,6 +5560,95 @@ mod tests {
         assert!(!enabled.contains("kubernetes"));
     }
 
+    #[test]
+    fn test_pinned_pack_layer_replaces_lower_layers_and_records_origin() {
+        let mut config = Config::default();
+        let user: ConfigLayer = toml::from_str(
+            "[packs]\nenabled = [\"kubernetes\", \"cicd\"]\ndisabled = [\"database\"]\n",
+        )
+        .unwrap();
+        config.merge_layer_from(user, "user");
+
+        let project: ConfigLayer = toml::from_str(
+            "[packs]\npinned = true\nenabled = [\"core.git@1\", \"cicd\", \"storage.s3@2\"]\ndisabled = [\"cicd.jenkins\"]\n",
+        )
+        .unwrap();
+        config.merge_layer_from(project, "project");
+
+        let enabled = crate::packs::REGISTRY.expand_enabled(&config.enabled_pack_ids());
+        assert!(!enabled.contains("kubernetes.kubectl"));
+        assert!(enabled.contains("storage.s3"));
+        assert!(enabled.contains("cicd.github_actions"));
+        assert!(!enabled.contains("cicd.jenkins"));
+
+        let packs = config.effective_packs();
+        assert_eq!(packs.pins().get("storage.s3"), Some(&"2"));
+        assert_eq!(
+            packs.decided_by("cicd.jenkins", false),
+            Some(PackDecision {
+                entry: "cicd.jenkins".to_string(),
+                layer: "project".to_string(),
+            })
+        );
+        assert_eq!(
+            packs.decided_by("cicd.github_actions", true).unwrap().entry,
+            "cicd"
+        );
+        assert_eq!(
+            packs.decided_by("core.filesystem", true).unwrap().layer,
+            "default"
+        );
+        assert_eq!(packs.decided_by("kubernetes.kubectl", false), None);
+
+        let warnings = config.pack_pin_warnings();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("'storage.s3' is pinned to version 2 but version 1"));
+    }
+
+    #[test]
+    fn test_unpinned_pack_layers_extend_and_env_overrides_record_origin() {
+        let mut config = Config::default();
+        config.merge_layer_from(
+            toml::from_str("[packs]\nenabled = [\"kubernetes\"]\n").unwrap(),
+            "system",
+        );
+        config.merge_layer_from(
+            toml::from_str("[packs]\nenabled = [\"database.redis@x\"]\n").unwrap(),
+            "user",
+        );
+        assert_eq!(
+            config
+                .packs
+                .decided_by("kubernetes.helm", true)
+                .unwrap()
+                .layer,
+            "system"
+        );
+        assert_eq!(
+            config
+                .packs
+                .decided_by("database.redis", true)
+                .unwrap()
+                .layer,
+            "user"
+        );
+        assert!(config.pack_pin_warnings()[0].contains("invalid version pin"));
+
+        config.apply_env_overrides_from(|key| {
+            (key == "DCG_DISABLE").then(|| "kubernetes.helm".to_string())
+        });
+        let decision = config.packs.decided_by("kubernetes.helm", false).unwrap();
+        assert_eq!(decision.layer, "DCG_DISABLE");
+        assert_eq!(
+            config
+                .packs
+                .decided_by("kubernetes.kubectl", true)
+                .unwrap()
+                .layer,
+            "system"
+        );
+    }
+
     #[test]
     fn test_allow_override_simple() {
         let override_ = AllowOverride::Simple("test pattern".to_string());
@@ -3590,6 +5661,7 @@ mod tests {
         let override_ = AllowOverride::Conditional {
             pattern: "test pattern".to_string(),
             when: None,
+            args: BTreeMap::new(),
         };
         assert!(override_.condition_met());
     }
@@ -3743,6 +5815,11 @@ database_path = "/tmp/dcg-history.db"
             highlight_enabled: Some(false),
             explanations_enabled: Some(false),
             high_contrast: Some(false),
+            hyperlinks: None,
+            impact_budget_ms: None,
+            storage_impact: None,
+            storage_impact_timeout_ms: None,
+            collapse_repeat_denials: None,
         };
         assert!(
             !config.highlight_enabled(),
@@ -3761,6 +5838,11 @@ database_path = "/tmp/dcg-history.db"
             highlight_enabled: Some(true),
             explanations_enabled: Some(true),
             high_contrast: Some(false),
+            hyperlinks: None,
+            impact_budget_ms: None,
+            storage_impact: None,
+            storage_impact_timeout_ms: None,
+            collapse_repeat_denials: None,
         };
         assert!(config.highlight_enabled());
         assert!(config.explanations_enabled());
@@ -3773,6 +5855,11 @@ database_path = "/tmp/dcg-history.db"
             highlight_enabled: Some(true),
             explanations_enabled: Some(false),
             high_contrast: Some(false),
+            hyperlinks: None,
+            impact_budget_ms: None,
+            storage_impact: None,
+            storage_impact_timeout_ms: None,
+            collapse_repeat_denials: None,
         };
         assert!(
             config1.highlight_enabled(),
@@ -3787,6 +5874,11 @@ database_path = "/tmp/dcg-history.db"
             highlight_enabled: Some(false),
             explanations_enabled: Some(true),
             high_contrast: Some(false),
+            hyperlinks: None,
+            impact_budget_ms: None,
+            storage_impact: None,
+            storage_impact_timeout_ms: None,
+            collapse_repeat_denials: None,
         };
         assert!(
             !config2.highlight_enabled(),
@@ -3816,9 +5908,24 @@ use_color = false
     fn test_env_high_contrast_override() {
         let mut config = Config::default();
         let env_map: std::collections::HashMap<&str, &str> =
-            std::collections::HashMap::from([("DCG_HIGH_CONTRAST", "1")]);
+            std::collections::HashMap::from([("DCG_HIGH_CONTRAST", "1")]);
+        config.apply_env_overrides_from(|key| env_map.get(key).map(|v| (*v).to_string()));
+        assert!(config.output.high_contrast_enabled());
+    }
+
+    #[test]
+    fn test_hyperlinks_kill_switch() {
+        assert!(Config::default().output.hyperlinks_enabled());
+
+        let config: Config =
+            toml::from_str("[output]\nhyperlinks = false\n").expect("config parses");
+        assert!(!config.output.hyperlinks_enabled());
+
+        let mut config = Config::default();
+        let env_map: std::collections::HashMap<&str, &str> =
+            std::collections::HashMap::from([("DCG_HYPERLINKS", "0")]);
         config.apply_env_overrides_from(|key| env_map.get(key).map(|v| (*v).to_string()));
-        assert!(config.output.high_contrast_enabled());
+        assert!(!config.output.hyperlinks_enabled());
     }
 
     #[test]
@@ -4269,6 +6376,269 @@ color = "never"
         assert_eq!(config.general.color, "never");
     }
 
+    #[test]
+    fn test_include_layers_merge_below_including_file() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let shared_dir = temp.path().join("shared");
+        let repo_dir = temp.path().join("repo");
+        std::fs::create_dir_all(&shared_dir).expect("shared dir");
+        std::fs::create_dir_all(&repo_dir).expect("repo dir");
+
+        std::fs::write(
+            shared_dir.join("org-policy.toml"),
+            "[general]\nverbose = true\ncolor = \"never\"\n\n[packs]\nenabled = [\"database.postgresql\"]\n",
+        )
+        .expect("write shared");
+        let project = repo_dir.join(".dcg.toml");
+        std::fs::write(
+            &project,
+            "include = [\"../shared/org-policy.toml\"]\n\n[general]\ncolor = \"always\"\n\n[packs]\nenabled = [\"kubernetes\"]\n",
+        )
+        .expect("write project");
+
+        let layers = Config::load_layers_with_includes(&project, ConfigSourceKind::Project)
+            .expect("project layers");
+        let mut config = Config::default();
+        config.merge_loaded_layers(layers);
+
+        // Including file wins for scalars; lists are additive.
+        assert!(config.general.verbose);
+        assert_eq!(config.general.color, "always");
+        assert!(
+            config
+                .packs
+                .enabled
+                .contains(&"database.postgresql".to_string())
+        );
+        assert!(config.packs.enabled.contains(&"kubernetes".to_string()));
+
+        assert_eq!(config.sources.len(), 2);
+        assert_eq!(config.sources[0].kind, ConfigSourceKind::Include);
+        assert_eq!(
+            config.sources[0].included_from.as_deref(),
+            Some(project.display().to_string().as_str())
+        );
+        assert_eq!(config.sources[1].kind, ConfigSourceKind::Project);
+        assert!(config.sources.iter().all(|s| s.applied));
+    }
+
+    #[test]
+    fn test_self_protection_is_only_read_from_system_layer() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[self_protection]\nenabled = false\nbinary_paths = [\"/opt/bin/guard\"]\n",
+        )
+        .expect("write config");
+
+        let mut config = Config::default();
+        for kind in [ConfigSourceKind::Project, ConfigSourceKind::User] {
+            let layers = Config::load_layers_with_includes(&path, kind).expect("layers");
+            config.merge_loaded_layers(layers);
+        }
+        assert!(config.self_protection.enabled);
+        assert!(config.self_protection.binary_paths.is_empty());
+        assert_eq!(config.load_warnings().len(), 2);
+
+        let layers =
+            Config::load_layers_with_includes(&path, ConfigSourceKind::System).expect("layers");
+        let mut config = Config::default();
+        config.merge_loaded_layers(layers);
+        assert!(!config.self_protection.enabled);
+        assert_eq!(config.self_protection.binary_paths, ["/opt/bin/guard"]);
+        assert!(config.load_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_invocation_env_overrides() {
+        let env_map: std::collections::HashMap<&str, &str> = std::collections::HashMap::from([
+            ("DCG_TRACE", "1"),
+            ("DCG_DISABLE_PACKS", "cicd.*, storage.s3"),
+            ("DCG_PROFILE", "ci"),
+        ]);
+        let mut config = Config::default();
+        config.packs.enabled = vec!["cicd".to_string(), "storage".to_string()];
+        config.apply_env_overrides_from(|key| env_map.get(key).map(|v| (*v).to_string()));
+
+        assert!(config.general.trace);
+        assert_eq!(config.agents.forced_profile.as_deref(), Some("ci"));
+        assert_eq!(config.packs.disabled, ["cicd", "storage.s3"]);
+        let enabled = config.enabled_pack_ids();
+        assert!(!enabled.iter().any(|id| id.starts_with("cicd")));
+        assert!(!enabled.contains("storage.s3"));
+        assert!(enabled.contains("storage.gcs"));
+        let decision = config.packs.decided_by("storage.s3", false).unwrap();
+        assert_eq!(decision.layer, "DCG_DISABLE_PACKS");
+    }
+
+    #[test]
+    fn test_security_can_only_disable_env_overrides_from_system_layer() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("config.toml");
+        std::fs::write(&path, "[security]\nallow_env_overrides = false\n").expect("write config");
+        let env = |key: &str| match key {
+            "DCG_TRACE" => Some("1".to_string()),
+            "DCG_DISABLE_PACKS" => Some("core.git".to_string()),
+            "DCG_PROFILE" => Some("trusted".to_string()),
+            _ => None,
+        };
+
+        let mut config = Config::default();
+        let layers =
+            Config::load_layers_with_includes(&path, ConfigSourceKind::Project).expect("layers");
+        config.merge_loaded_layers(layers);
+        assert!(config.security.allow_env_overrides);
+        assert!(config.load_warnings()[0].contains("[security] is only read from"));
+        config.apply_env_overrides_from(env);
+        assert!(config.general.trace);
+
+        let mut config = Config::default();
+        let layers =
+            Config::load_layers_with_includes(&path, ConfigSourceKind::System).expect("layers");
+        config.merge_loaded_layers(layers);
+        assert!(!config.security.allow_env_overrides);
+        config.apply_env_overrides_from(env);
+        assert!(!config.general.trace);
+        assert!(config.packs.disabled.is_empty());
+        assert_eq!(config.agents.forced_profile, None);
+    }
+
+    #[test]
+    fn test_include_cycle_is_detected_and_skipped() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let a = temp.path().join("a.toml");
+        let b = temp.path().join("b.toml");
+        std::fs::write(&a, "include = [\"b.toml\"]\n[general]\ncolor = \"never\"\n")
+            .expect("write a");
+        std::fs::write(&b, "include = [\"a.toml\"]\n[general]\nverbose = true\n").expect("write b");
+
+        let layers = Config::load_layers_with_includes(&a, ConfigSourceKind::User).expect("layers");
+        let mut config = Config::default();
+        config.merge_loaded_layers(layers);
+
+        assert!(config.general.verbose);
+        assert_eq!(config.general.color, "never");
+        let cycle = config
+            .sources
+            .iter()
+            .find(|s| !s.applied)
+            .expect("cycle recorded");
+        assert_eq!(cycle.note.as_deref(), Some("include cycle"));
+    }
+
+    #[test]
+    fn test_remote_and_missing_includes_are_recorded_not_applied() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let root = temp.path().join("config.toml");
+        std::fs::write(
+            &root,
+            "include = [\"http://example.com/org-policy.toml\", \"missing.toml\"]\n",
+        )
+        .expect("write root");
+
+        let layers =
+            Config::load_layers_with_includes(&root, ConfigSourceKind::User).expect("layers");
+        let mut config = Config::default();
+        config.merge_loaded_layers(layers);
+
+        let skipped: Vec<_> = config.sources.iter().filter(|s| !s.applied).collect();
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(
+            skipped[0].note.as_deref(),
+            Some("remote includes must use https")
+        );
+        assert_eq!(skipped[1].note.as_deref(), Some("missing or invalid"));
+
+        let warnings = config.load_warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].contains("missing.toml"));
+        assert!(warnings[1].contains("was skipped: missing or invalid"));
+    }
+
+    #[test]
+    fn test_remote_includes_are_fetched_pinned_and_cached() {
+        use std::io::{BufRead, Write};
+
+        const ORG_POLICY: &str =
+            "include = [\"local.toml\"]\n[packs]\nenabled = [\"kubernetes\"]\n";
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let base = format!("http://{}", listener.local_addr().expect("addr"));
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(1) {
+                let mut stream = stream.expect("stream");
+                for line in std::io::BufReader::new(&stream).lines() {
+                    if line.expect("line").is_empty() {
+                        break;
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{ORG_POLICY}",
+                    ORG_POLICY.len()
+                );
+                stream.write_all(response.as_bytes()).expect("write");
+            }
+        });
+
+        let temp = tempfile::tempdir().expect("tempdir");
+        let cache = IncludeCache::new(temp.path().join("cache"));
+        let root = temp.path().join("config.toml");
+        let hash = content_hash(ORG_POLICY);
+        std::fs::write(
+            &root,
+            format!(
+                "include = [\n  {{ url = \"{base}/org.toml\", sha256 = \"{hash}\" }},\n  \
+                 {{ url = \"{base}/other.toml\", sha256 = \"{hash}\" }},\n]\n"
+            ),
+        )
+        .expect("write root");
+
+        // The server answers once: org.toml is fetched, other.toml is not.
+        let layers = Config::load_layers_with_include_cache(&root, ConfigSourceKind::User, &cache)
+            .expect("layers");
+        let mut config = Config::default();
+        config.merge_loaded_layers(layers);
+        assert!(config.packs.enabled.contains(&"kubernetes".to_string()));
+
+        let org = &config.sources[1];
+        assert_eq!(org.location, format!("{base}/org.toml"));
+        assert!(org.applied);
+        // A remote file cannot reach back into the local filesystem.
+        assert_eq!(
+            config.sources[0].note.as_deref(),
+            Some("remote includes can only include other URLs")
+        );
+        assert!(!config.sources[2].applied);
+
+        // The pinned copy is now served from the cache without the server.
+        let layers = Config::load_layers_with_include_cache(&root, ConfigSourceKind::User, &cache)
+            .expect("layers");
+        let mut cached = Config::default();
+        cached.merge_loaded_layers(layers);
+        assert!(cached.sources[1].applied);
+        assert_eq!(cached.sources[1].note, None);
+    }
+
+    #[test]
+    fn test_project_config_path_falls_back_to_dcg_policy() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir_all(temp.path().join(".git")).expect(".git");
+        std::fs::create_dir_all(temp.path().join(".dcg")).expect(".dcg");
+        assert!(project_config_path(temp.path()).is_none());
+
+        std::fs::write(temp.path().join(".dcg/policy.toml"), "").expect("policy");
+        assert_eq!(
+            project_config_path(temp.path()),
+            Some(temp.path().join(".dcg/policy.toml"))
+        );
+
+        std::fs::write(temp.path().join(".dcg.toml"), "").expect("dcg.toml");
+        assert_eq!(
+            project_config_path(temp.path()),
+            Some(temp.path().join(".dcg.toml"))
+        );
+    }
+
     #[test]
     fn test_config_merge_layer_logging_is_reversible() {
         let mut config = Config::default();
@@ -4446,6 +6816,7 @@ allow = false
             block: vec![BlockOverride {
                 pattern: "dangerous-command".to_string(),
                 reason: "This is dangerous!".to_string(),
+                args: BTreeMap::new(),
             }],
             ..Default::default()
         };
@@ -4467,6 +6838,7 @@ allow = false
             block: vec![BlockOverride {
                 pattern: "[also invalid".to_string(),
                 reason: "Won't compile".to_string(),
+                args: BTreeMap::new(),
             }],
             ..Default::default()
         };
@@ -4501,6 +6873,7 @@ allow = false
             allow: vec![AllowOverride::Conditional {
                 pattern: "test-pattern".to_string(),
                 when: None,
+                args: BTreeMap::new(),
             }],
             block: vec![],
             ..Default::default()
@@ -4512,6 +6885,35 @@ allow = false
         assert!(compiled.check_allow("test-pattern"));
     }
 
+    #[test]
+    fn test_compile_overrides_with_arg_conditions() {
+        let overrides: OverridesConfig = toml::from_str(
+            r#"
+            allow = [{ pattern = "kubectl .*delete", args = { namespace = "dev|sandbox" } }]
+            block = [
+                { pattern = "kubectl .*scale", reason = "prod cluster", args = { context = "prod.*" } },
+                { pattern = "aws", reason = "bad key", args = { account = "x" } },
+            ]
+            "#,
+        )
+        .unwrap();
+        let compiled = overrides.compile();
+
+        assert_eq!(compiled.invalid_patterns.len(), 1);
+        assert!(compiled.invalid_patterns[0].error.contains("account"));
+        assert!(compiled.check_allow("kubectl delete pods --all -n dev"));
+        assert!(!compiled.check_allow("kubectl delete pods --all -n prod"));
+        assert!(!compiled.check_allow("kubectl delete pods --all"));
+        assert_eq!(
+            compiled.check_block("kubectl --context prod-eu scale deploy api --replicas 0"),
+            Some("prod cluster")
+        );
+        assert_eq!(
+            compiled.check_block("kubectl --context kind scale deploy api --replicas 0"),
+            None
+        );
+    }
+
     #[test]
     fn test_compile_regex_pattern() {
         let overrides = OverridesConfig {
@@ -4569,6 +6971,7 @@ allow = false
             block: vec![BlockOverride {
                 pattern: r"(\w+)\s+\1".to_string(),
                 reason: "duplicate word".to_string(),
+                args: BTreeMap::new(),
             }],
             ..Default::default()
         };
@@ -4593,6 +6996,7 @@ allow = false
             block: vec![BlockOverride {
                 pattern: "test-command".to_string(),
                 reason: "Blocked!".to_string(),
+                args: BTreeMap::new(),
             }],
             ..Default::default()
         };
@@ -4628,10 +7032,12 @@ allow = false
                 BlockOverride {
                     pattern: "block-1".to_string(),
                     reason: "Reason 1".to_string(),
+                    args: BTreeMap::new(),
                 },
                 BlockOverride {
                     pattern: "block-2".to_string(),
                     reason: "Reason 2".to_string(),
+                    args: BTreeMap::new(),
                 },
             ],
             ..Default::default()
@@ -4973,6 +7379,223 @@ allow = false
         );
     }
 
+    #[test]
+    fn test_policy_aggregation_parses_and_defaults_to_max() {
+        let config: Config = toml::from_str("[policy]\naggregation = \"sum\"\n").unwrap();
+        assert_eq!(config.policy.aggregation(), SeverityAggregation::Sum);
+
+        let config: Config = toml::from_str("[policy]\n").unwrap();
+        assert_eq!(config.policy.aggregation(), SeverityAggregation::Max);
+
+        assert_eq!(
+            parse_severity_aggregation("First-Match"),
+            Some(SeverityAggregation::First)
+        );
+        assert_eq!(parse_severity_aggregation("average"), None);
+    }
+
+    #[test]
+    fn test_policy_provenance_overrides_apply_only_to_that_provenance() {
+        use crate::packs::{DecisionMode, Severity};
+        use crate::provenance::Provenance;
+
+        let mut config = Config::default();
+        let layer: ConfigLayer = toml::from_str(
+            r#"
+[policy.provenance.human]
+rules = { "core.git:push-force-long" = "warn" }
+packs = { "core.filesystem" = "log" }
+
+[policy.provenance.agent]
+default_mode = "deny"
+"#,
+        )
+        .unwrap();
+        config.merge_layer(layer);
+        let policy = config.policy();
+
+        let push = (Some("core.git"), Some("push-force-long"));
+        assert_eq!(
+            policy.resolve_mode_for(Provenance::Human, push.0, push.1, Some(Severity::Critical)),
+            DecisionMode::Warn
+        );
+        assert_eq!(
+            policy.resolve_mode_for(Provenance::Agent, push.0, push.1, Some(Severity::Critical)),
+            DecisionMode::Deny
+        );
+
+        // Pack-level provenance overrides never loosen critical rules.
+        let rm = (Some("core.filesystem"), Some("rm-rf-root"));
+        assert_eq!(
+            policy.resolve_mode_for(Provenance::Human, rm.0, rm.1, Some(Severity::Critical)),
+            DecisionMode::Deny
+        );
+        assert_eq!(
+            policy.resolve_mode_for(Provenance::Human, rm.0, rm.1, Some(Severity::High)),
+            DecisionMode::Log
+        );
+
+        // Agent default_mode beats the severity default (medium => warn).
+        let drop = (Some("core.git"), Some("stash-drop"));
+        assert_eq!(
+            policy.resolve_mode_for(Provenance::Agent, drop.0, drop.1, Some(Severity::Medium)),
+            DecisionMode::Deny
+        );
+        assert_eq!(
+            policy.resolve_mode_for(Provenance::Human, drop.0, drop.1, Some(Severity::Medium)),
+            DecisionMode::Warn
+        );
+    }
+
+    #[test]
+    fn test_policy_warn_channel_parses_merges_and_defaults_to_stderr() {
+        let config: Config = toml::from_str("[policy]\n").unwrap();
+        assert_eq!(config.policy.warn_channel(), WarnChannel::Stderr);
+
+        let mut base = Config::default();
+        let layer: ConfigLayer = toml::from_str("[policy]\nwarn_channel = \"advisory\"\n").unwrap();
+        base.merge_layer(layer);
+        assert_eq!(base.policy.warn_channel(), WarnChannel::Advisory);
+
+        base.apply_env_overrides_from(|key| {
+            (key == "DCG_POLICY_WARN_CHANNEL").then(|| "JSON".to_string())
+        });
+        assert_eq!(base.policy.warn_channel(), WarnChannel::Event);
+        assert_eq!(parse_warn_channel("stdout"), None);
+    }
+
+    #[test]
+    fn test_policy_warn_queue_defaults_on_and_merges() {
+        let mut base = Config::default();
+        assert!(base.policy.warn_queue());
+        let layer: ConfigLayer = toml::from_str("[policy]\nwarn_queue = false\n").unwrap();
+        base.merge_layer(layer);
+        assert!(!base.policy.warn_queue());
+        base.merge_layer(toml::from_str("[policy]\n").unwrap());
+        assert!(!base.policy.warn_queue());
+    }
+
+    #[test]
+    fn test_policy_min_confidence_to_deny_enables_scoring_with_its_threshold() {
+        let config: Config = toml::from_str("[policy]\n").unwrap();
+        assert!(config.policy.min_confidence_to_deny.is_none());
+        assert!(!config.confidence_settings().enabled);
+
+        let mut base = Config::default();
+        let layer: ConfigLayer =
+            toml::from_str("[policy]\nmin_confidence_to_deny = 0.3\n").unwrap();
+        base.merge_layer(layer);
+        let settings = base.confidence_settings();
+        assert!(settings.enabled);
+        assert!((settings.warn_threshold - 0.3).abs() < f32::EPSILON);
+        assert!(settings.protect_critical);
+
+        base.apply_env_overrides_from(|key| {
+            (key == "DCG_POLICY_MIN_CONFIDENCE_TO_DENY").then(|| "0.7".to_string())
+        });
+        assert_eq!(base.policy.min_confidence_to_deny, Some(0.7));
+        assert_eq!(parse_confidence_threshold("1.5"), None);
+        assert_eq!(parse_confidence_threshold("high"), None);
+    }
+
+    #[test]
+    fn test_privacy_log_mode_merges_env_and_shapes_logged_command() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.privacy.log_command("rm -rf /srv/app"),
+            "rm -rf /srv/app"
+        );
+
+        let layer: ConfigLayer = toml::from_str("[privacy]\nlog_mode = \"redacted\"\n").unwrap();
+        config.merge_layer(layer);
+        assert_eq!(
+            config.privacy.log_command("rm -rf /srv/app"),
+            "rm -rf <path>"
+        );
+
+        config.apply_env_overrides_from(|key| {
+            (key == "DCG_PRIVACY_LOG_MODE").then(|| "Hash".to_string())
+        });
+        assert_eq!(
+            config.privacy.log_mode,
+            crate::privacy::PrivacyLogMode::Hash
+        );
+
+        config.apply_env_overrides_from(|key| {
+            (key == "DCG_PRIVACY_LOG_MODE").then(|| "plaintext".to_string())
+        });
+        assert_eq!(
+            config.privacy.log_mode,
+            crate::privacy::PrivacyLogMode::Hash
+        );
+    }
+
+    #[test]
+    fn test_messages_denial_template_merges_and_renders() {
+        let mut config = Config::default();
+        let vars = DenialTemplateVars {
+            rule_id: Some("core.git:reset-hard"),
+            pack: Some("core.git"),
+            reason: "destroys uncommitted changes",
+            command: "git reset --hard",
+        };
+        assert_eq!(config.messages.render_denial(&vars), None);
+
+        let user: ConfigLayer = toml::from_str(
+            "[messages]\ndenial_template = \"{rule_id} blocked; see {ticket_url} or {security_contact}. {oops}\"\nticket_url = \"https://wiki.example/dcg\"\n",
+        )
+        .unwrap();
+        let project: ConfigLayer =
+            toml::from_str("[messages]\nsecurity_contact = \"#sec-help\"\n").unwrap();
+        config.merge_layer(user);
+        config.merge_layer(project);
+
+        assert_eq!(
+            config.messages.render_denial(&vars).as_deref(),
+            Some("core.git:reset-hard blocked; see https://wiki.example/dcg or #sec-help. {oops}")
+        );
+        assert_eq!(config.messages.unknown_template_variables(), ["oops"]);
+    }
+
+    #[test]
+    fn test_placeholder_substitution_keeps_unmatched_braces() {
+        let lookup = |name: &str| (name == "a").then_some("1");
+        assert_eq!(substitute_placeholders("{a}{b}{", lookup), "1{b}{");
+        assert_eq!(substitute_placeholders("{{a}} x{ {a}", lookup), "{1} x{ 1");
+        assert_eq!(substitute_placeholders("no vars", lookup), "no vars");
+    }
+
+    #[test]
+    fn test_canary_layers_accumulate_and_compile_into_overrides() {
+        let mut config = Config::default();
+        assert!(config.compile_overrides().canaries.is_empty());
+
+        let user: ConfigLayer = toml::from_str(
+            "[canary]\npaths = [\"/srv/DO_NOT_DELETE\"]\nwebhook_url = \"https://a.example\"\n",
+        )
+        .unwrap();
+        let project: ConfigLayer = toml::from_str("[canary]\nnames = [\"prod-canary\"]\n").unwrap();
+        config.merge_layer(user);
+        config.merge_layer(project);
+
+        assert_eq!(config.canary.paths, vec!["/srv/DO_NOT_DELETE"]);
+        assert_eq!(config.canary.names, vec!["prod-canary"]);
+        assert_eq!(
+            config.canary.webhook_url.as_deref(),
+            Some("https://a.example")
+        );
+
+        let compiled = config.compile_overrides();
+        assert!(
+            compiled
+                .canaries
+                .check("rm -rf /srv/DO_NOT_DELETE")
+                .is_some()
+        );
+        assert!(compiled.canaries.check("psql -d PROD-CANARY").is_some());
+        assert!(config.overrides.compile().canaries.is_empty());
+    }
+
     #[test]
     fn test_policy_resolve_mode_rule_override_takes_precedence() {
         let policy = PolicyConfig {
@@ -4983,6 +7606,12 @@ allow = false
                 "core.git:reset-hard".to_string(),
                 PolicyMode::Log,
             )]),
+            severity: std::collections::HashMap::new(),
+            aggregation: None,
+            warn_channel: None,
+            warn_queue: None,
+            min_confidence_to_deny: None,
+            provenance: std::collections::HashMap::new(),
         };
 
         // Rule-specific override should win
@@ -5011,6 +7640,77 @@ allow = false
         assert_eq!(mode, crate::packs::DecisionMode::Warn);
     }
 
+    #[test]
+    fn test_policy_warn_once_mode() {
+        let config: Config =
+            toml::from_str("[policy.rules]\n\"core.git:stash-drop\" = \"warn_once\"\n").unwrap();
+        let mode = config.policy.resolve_mode(
+            Some("core.git"),
+            Some("stash-drop"),
+            Some(crate::packs::Severity::Medium),
+        );
+        assert_eq!(mode, crate::packs::DecisionMode::WarnOnce);
+        assert_eq!(mode.label(), "warn_once");
+        assert!(!mode.blocks());
+        assert_eq!(parse_policy_mode("warn-once"), Some(PolicyMode::WarnOnce));
+    }
+
+    #[test]
+    fn test_policy_severity_modes_with_agent_profile() {
+        use crate::packs::{DecisionMode, Severity};
+        use crate::provenance::Provenance;
+
+        let config: Config = toml::from_str(
+            r#"
+[policy.severity]
+high = "ask"
+critical = "ask"
+
+[policy.rules]
+"core.git:stash-drop" = "warn"
+
+[agents.claude-code.severity_modes]
+medium = "ask"
+"#,
+        )
+        .unwrap();
+        let policy = &config.policy;
+        let claude = config.agents.profile_for("claude-code");
+        let resolve = |profile, pattern, severity| {
+            policy.resolve_mode_for_profile(
+                Provenance::Agent,
+                profile,
+                Some("core.git"),
+                Some(pattern),
+                Some(severity),
+            )
+        };
+
+        assert!(config.agents.has_severity_modes());
+        assert_eq!(
+            resolve(Some(claude), "clean-force", Severity::Medium),
+            DecisionMode::Ask
+        );
+        assert_eq!(
+            resolve(None, "clean-force", Severity::Medium),
+            DecisionMode::Warn
+        );
+        assert_eq!(
+            resolve(None, "push-force-long", Severity::High),
+            DecisionMode::Ask
+        );
+        // Critical rules are not loosened by severity tables; rule overrides win.
+        assert_eq!(
+            resolve(Some(claude), "reset-hard", Severity::Critical),
+            DecisionMode::Deny
+        );
+        assert_eq!(
+            resolve(Some(claude), "stash-drop", Severity::Medium),
+            DecisionMode::Warn
+        );
+        assert_eq!(parse_policy_mode("prompt"), Some(PolicyMode::Ask));
+    }
+
     #[test]
     fn test_policy_resolve_mode_global_default_when_no_pack() {
         let policy = PolicyConfig {
@@ -5177,6 +7877,15 @@ allow = false
                     "core.git:reset-hard".to_string(),
                     PolicyMode::Log,
                 )]),
+                severity: std::collections::HashMap::from([(
+                    "medium".to_string(),
+                    PolicyMode::Ask,
+                )]),
+                aggregation: None,
+                warn_channel: None,
+                warn_queue: None,
+                min_confidence_to_deny: None,
+                provenance: std::collections::HashMap::new(),
             }),
             ..Default::default()
         };
@@ -5201,6 +7910,7 @@ allow = false
             base.policy.rules.get("core.git:reset-hard"),
             Some(&PolicyMode::Log)
         );
+        assert_eq!(base.policy.severity.get("medium"), Some(&PolicyMode::Ask));
     }
 
     #[test]
@@ -5813,6 +8523,38 @@ allow = false
         assert_eq!(profile.trust_level, TrustLevel::Low);
     }
 
+    #[test]
+    fn test_agents_config_allow_once_scope_falls_back_to_default_profile() {
+        use crate::pending_exceptions::AllowOnceGrant;
+
+        let config: AgentsConfig = toml::from_str(
+            r#"
+[default]
+allow_once_scope = "session"
+
+[claude-code]
+allow_once_scope = "rule"
+
+[aider]
+trust_level = "low"
+"#,
+        )
+        .expect("agents config parses");
+
+        assert_eq!(
+            config.allow_once_scope_for("claude-code"),
+            AllowOnceGrant::Rule
+        );
+        assert_eq!(
+            config.allow_once_scope_for("aider"),
+            AllowOnceGrant::Session
+        );
+        assert_eq!(
+            AgentsConfig::default().allow_once_scope_for("aider"),
+            AllowOnceGrant::Exact
+        );
+    }
+
     #[test]
     fn test_agents_config_from_toml() {
         let input = r#"
@@ -0,0 +1,327 @@
+//! Rule coverage of a command corpus for `dcg coverage`.
+//!
+//! Given commands a team actually runs (shell history, hook or decision
+//! logs, corpus fixtures), this reports which rules fire and how often,
+//! which rules never fire, and which commands no pack even looks at (none of
+//! its keywords appear), per pack and overall. That shows which packs are
+//! worth enabling and where coverage has gaps.
+//!
+//! Coverage is about rules, not policy: every pack passed in is evaluated on
+//! its own, with no allowlists, config overrides, or allow-once entries, so a
+//! rule counts as firing even where the current policy would let the command
+//! through.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::allowlist::LayeredAllowlist;
+use crate::config::{CompiledOverrides, HeredocSettings};
+use crate::evaluator::evaluate_command_with_pack_order;
+use crate::packs::Pack;
+
+/// Report schema version for `--format json`.
+pub const COVERAGE_SCHEMA_VERSION: u32 = 1;
+
+/// How often one rule fired.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleHits {
+    pub rule_id: String,
+    pub hits: usize,
+}
+
+/// Coverage of one pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackCoverage {
+    pub pack_id: String,
+    /// Enabled in the current config.
+    pub enabled: bool,
+    /// Commands containing one of the pack's keywords.
+    pub touched: usize,
+    /// Commands one of the pack's rules fired on.
+    pub matched: usize,
+    /// `matched` as a percentage of all commands.
+    pub matched_pct: f64,
+    /// Named rules in the pack.
+    pub rules_total: usize,
+    /// Rules that fired, most hits first.
+    pub fired: Vec<RuleHits>,
+    /// Rules that never fired.
+    pub never_fired: Vec<String>,
+}
+
+/// A command no pack looked at, with how often it occurs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UntouchedCommand {
+    pub command: String,
+    pub count: usize,
+}
+
+/// Result of `dcg coverage`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    pub schema_version: u32,
+    /// Commands read, repeats included.
+    pub total_commands: usize,
+    pub unique_commands: usize,
+    /// Commands at least one rule fired on.
+    pub matched_commands: usize,
+    pub matched_pct: f64,
+    /// Commands no pack's keywords appear in.
+    pub untouched_commands: usize,
+    pub untouched_pct: f64,
+    /// Packs with the most matched commands first.
+    pub packs: Vec<PackCoverage>,
+    /// The most frequent untouched commands.
+    pub untouched: Vec<UntouchedCommand>,
+}
+
+#[derive(Default)]
+struct PackTally {
+    touched: usize,
+    matched: usize,
+    hits: BTreeMap<String, usize>,
+}
+
+/// Measure how `packs` cover `commands`. `enabled` holds the pack IDs the
+/// current config enables; at most `untouched_limit` untouched commands are
+/// listed.
+#[must_use]
+pub fn analyze<I>(
+    commands: I,
+    packs: &[&Pack],
+    enabled: &HashSet<String>,
+    heredoc_settings: &HeredocSettings,
+    untouched_limit: usize,
+) -> CoverageReport
+where
+    I: IntoIterator<Item = String>,
+{
+    // Repeats are evaluated once and counted by occurrence.
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut order = Vec::new();
+    for command in commands {
+        let command = command.trim().to_string();
+        if command.is_empty() {
+            continue;
+        }
+        let count = counts.entry(command.clone()).or_insert(0);
+        if *count == 0 {
+            order.push(command);
+        }
+        *count += 1;
+    }
+
+    let overrides = CompiledOverrides {
+        skip_allow_once: true,
+        ..CompiledOverrides::default()
+    };
+    let allowlists = LayeredAllowlist::default();
+    let mut tallies: Vec<PackTally> = packs.iter().map(|_| PackTally::default()).collect();
+    let mut total_commands = 0;
+    let mut matched_commands = 0;
+    let mut untouched = Vec::new();
+
+    for command in &order {
+        let count = counts[command];
+        total_commands += count;
+        let mut touched = false;
+        let mut matched = false;
+        for (pack, tally) in packs.iter().zip(&mut tallies) {
+            // Packs without keywords check every command; they touch none.
+            let keyword_hit = !pack.keywords.is_empty() && pack.might_match(command);
+            if keyword_hit {
+                touched = true;
+                tally.touched += count;
+            }
+            if !keyword_hit && !pack.keywords.is_empty() {
+                continue;
+            }
+            let ordered = [pack.id.clone()];
+            let result = evaluate_command_with_pack_order(
+                command,
+                pack.keywords,
+                &ordered,
+                None,
+                &overrides,
+                &allowlists,
+                heredoc_settings,
+            );
+            let mut fired = false;
+            for matched_rule in result.all_matches() {
+                let (Some(pack_id), Some(name)) =
+                    (&matched_rule.pack_id, &matched_rule.pattern_name)
+                else {
+                    continue;
+                };
+                if *pack_id != pack.id {
+                    continue;
+                }
+                *tally.hits.entry(format!("{pack_id}:{name}")).or_insert(0) += count;
+                fired = true;
+            }
+            if fired {
+                tally.matched += count;
+                matched = true;
+            }
+        }
+        if matched {
+            matched_commands += count;
+        }
+        if !touched && !matched {
+            untouched.push(UntouchedCommand {
+                command: command.clone(),
+                count,
+            });
+        }
+    }
+
+    let untouched_commands = untouched.iter().map(|entry| entry.count).sum();
+    untouched.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.command.cmp(&b.command))
+    });
+    untouched.truncate(untouched_limit);
+
+    let mut pack_reports: Vec<PackCoverage> = packs
+        .iter()
+        .zip(tallies)
+        .map(|(pack, tally)| {
+            let names: Vec<String> = pack
+                .destructive_patterns
+                .iter()
+                .filter_map(|pattern| pattern.name)
+                .map(|name| format!("{}:{name}", pack.id))
+                .collect();
+            let never_fired = names
+                .iter()
+                .filter(|rule_id| !tally.hits.contains_key(*rule_id))
+                .cloned()
+                .collect();
+            let mut fired: Vec<RuleHits> = tally
+                .hits
+                .into_iter()
+                .map(|(rule_id, hits)| RuleHits { rule_id, hits })
+                .collect();
+            fired.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.rule_id.cmp(&b.rule_id)));
+            PackCoverage {
+                pack_id: pack.id.clone(),
+                enabled: enabled.contains(&pack.id),
+                touched: tally.touched,
+                matched: tally.matched,
+                matched_pct: percent(tally.matched, total_commands),
+                rules_total: names.len(),
+                fired,
+                never_fired,
+            }
+        })
+        .collect();
+    pack_reports.sort_by(|a, b| {
+        b.matched
+            .cmp(&a.matched)
+            .then_with(|| b.touched.cmp(&a.touched))
+            .then_with(|| a.pack_id.cmp(&b.pack_id))
+    });
+
+    CoverageReport {
+        schema_version: COVERAGE_SCHEMA_VERSION,
+        total_commands,
+        unique_commands: order.len(),
+        matched_commands,
+        matched_pct: percent(matched_commands, total_commands),
+        untouched_commands,
+        untouched_pct: percent(untouched_commands, total_commands),
+        packs: pack_reports,
+        untouched,
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / total as f64).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::REGISTRY;
+
+    fn report(commands: &[&str]) -> CoverageReport {
+        let packs = [
+            REGISTRY.get("core.git").expect("core.git"),
+            REGISTRY
+                .get("containers.docker")
+                .expect("containers.docker"),
+        ];
+        let enabled = HashSet::from(["core.git".to_string()]);
+        analyze(
+            commands.iter().map(ToString::to_string),
+            &packs,
+            &enabled,
+            &HeredocSettings::default(),
+            10,
+        )
+    }
+
+    #[test]
+    fn counts_fired_rules_per_pack() {
+        let report = report(&[
+            "git reset --hard",
+            "git reset --hard",
+            "git status",
+            "docker system prune -af",
+            "ls -la",
+            "ls -la",
+            "make build",
+            "",
+        ]);
+        assert_eq!(report.total_commands, 7);
+        assert_eq!(report.unique_commands, 5);
+        assert_eq!(report.matched_commands, 3);
+        assert!((report.matched_pct - 42.9).abs() < f64::EPSILON);
+
+        let git = &report.packs[0];
+        assert_eq!(git.pack_id, "core.git");
+        assert!(git.enabled);
+        assert_eq!(git.touched, 3);
+        assert_eq!(git.matched, 2);
+        assert_eq!(
+            git.fired,
+            [RuleHits {
+                rule_id: "core.git:reset-hard".to_string(),
+                hits: 2
+            }]
+        );
+        assert!(!git.never_fired.contains(&"core.git:reset-hard".to_string()));
+        assert_eq!(git.never_fired.len(), git.rules_total - 1);
+
+        let docker = &report.packs[1];
+        assert_eq!(docker.pack_id, "containers.docker");
+        assert!(!docker.enabled);
+        assert_eq!(docker.matched, 1);
+    }
+
+    #[test]
+    fn lists_commands_no_pack_looks_at() {
+        let report = report(&["ls -la", "make build", "ls -la", "git status"]);
+        assert_eq!(report.untouched_commands, 3);
+        assert!((report.untouched_pct - 75.0).abs() < f64::EPSILON);
+        assert_eq!(
+            report.untouched,
+            [
+                UntouchedCommand {
+                    command: "ls -la".to_string(),
+                    count: 2
+                },
+                UntouchedCommand {
+                    command: "make build".to_string(),
+                    count: 1
+                },
+            ]
+        );
+    }
+}
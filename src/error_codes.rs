@@ -8,6 +8,10 @@
 //! - DCG-3xxx: Runtime errors
 //! - DCG-4xxx: External integration errors
 //!
+//! Module error types (`PackParseError`, `AllowlistError`, `HookReadError`) expose
+//! a `code()`, and IO/TOML/JSON/regex errors convert into [`DcgError`]. Causes and
+//! remedies for each code live in `docs/error-codes.md`.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -21,6 +25,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
+/// Where the cause and remedy for every code is documented.
+pub const ERROR_CODES_DOC_URL: &str = "https://github.com/Dicklesworthstone/destructive_command_guard/blob/master/docs/error-codes.md";
+
 /// Error categories for DCG operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -180,6 +187,15 @@ pub enum ErrorCode {
     #[serde(rename = "DCG-3010")]
     DatabaseError,
 
+    /// DCG-3011: Command failed (no more specific code applies)
+    #[serde(rename = "DCG-3011")]
+    CommandFailed,
+
+    /// DCG-3012: Hook command contained NUL bytes, oversized tokens, or
+    /// deeply nested quoting
+    #[serde(rename = "DCG-3012")]
+    InputAnomaly,
+
     // ===========================================
     // DCG-4xxx: External Integration Errors
     // ===========================================
@@ -225,6 +241,75 @@ pub enum ErrorCode {
 }
 
 impl ErrorCode {
+    /// Every error code, in numeric order.
+    pub const ALL: [Self; 42] = [
+        Self::PatternCompileFailed,
+        Self::PatternMatchTimeout,
+        Self::InvalidPatternSyntax,
+        Self::PatternEvaluationError,
+        Self::QuickRejectError,
+        Self::SafePatternMismatch,
+        Self::DestructivePatternMatch,
+        Self::PackPatternNotFound,
+        Self::HeredocExtractionFailed,
+        Self::AstMatchingError,
+        Self::ConfigFileNotFound,
+        Self::ConfigParseError,
+        Self::InvalidConfigValue,
+        Self::AllowlistLoadError,
+        Self::InvalidAllowlistEntry,
+        Self::PackConfigError,
+        Self::PackNotFound,
+        Self::InvalidRuleIdFormat,
+        Self::DuplicateRuleId,
+        Self::SettingsFileError,
+        Self::JsonParseError,
+        Self::IoError,
+        Self::TimeoutExceeded,
+        Self::MemoryLimitExceeded,
+        Self::InvalidInput,
+        Self::HookProtocolError,
+        Self::StdinReadError,
+        Self::StdoutWriteError,
+        Self::FileScanError,
+        Self::DatabaseError,
+        Self::CommandFailed,
+        Self::InputAnomaly,
+        Self::ExternalPackLoadFailed,
+        Self::ExternalPackParseError,
+        Self::NetworkRequestFailed,
+        Self::VersionCheckFailed,
+        Self::McpProtocolError,
+        Self::HookIntegrationError,
+        Self::GitOperationFailed,
+        Self::ClaudeCodeHookError,
+        Self::ExternalCommandFailed,
+        Self::ApiRateLimitExceeded,
+    ];
+
+    /// Look up a code from `DCG-1001`, `dcg1001`, or `1001`.
+    #[must_use]
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim();
+        let digits = code
+            .get(..3)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("dcg"))
+            .map_or(code, |_| code[3..].trim_start_matches('-'));
+        let numeric: u16 = digits.parse().ok()?;
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.numeric_code() == numeric)
+    }
+
+    /// Link to this code's entry (cause and remedy) in `docs/error-codes.md`.
+    #[must_use]
+    pub fn docs_url(&self) -> String {
+        format!(
+            "{ERROR_CODES_DOC_URL}#{}",
+            self.as_str().to_ascii_lowercase()
+        )
+    }
+
     /// Get the numeric code as a string (e.g., "DCG-1001").
     #[must_use]
     pub const fn as_str(&self) -> &'static str {
@@ -262,6 +347,8 @@ impl ErrorCode {
             Self::StdoutWriteError => "DCG-3008",
             Self::FileScanError => "DCG-3009",
             Self::DatabaseError => "DCG-3010",
+            Self::CommandFailed => "DCG-3011",
+            Self::InputAnomaly => "DCG-3012",
             // External integration errors
             Self::ExternalPackLoadFailed => "DCG-4001",
             Self::ExternalPackParseError => "DCG-4002",
@@ -311,7 +398,9 @@ impl ErrorCode {
             | Self::StdinReadError
             | Self::StdoutWriteError
             | Self::FileScanError
-            | Self::DatabaseError => ErrorCategory::Runtime,
+            | Self::DatabaseError
+            | Self::CommandFailed
+            | Self::InputAnomaly => ErrorCategory::Runtime,
 
             Self::ExternalPackLoadFailed
             | Self::ExternalPackParseError
@@ -363,6 +452,8 @@ impl ErrorCode {
             Self::StdoutWriteError => "Failed to write to stdout",
             Self::FileScanError => "File scan error",
             Self::DatabaseError => "Database error",
+            Self::CommandFailed => "Command failed",
+            Self::InputAnomaly => "Anomalous hook command input",
             // External integration errors
             Self::ExternalPackLoadFailed => "Failed to load external pack",
             Self::ExternalPackParseError => "Failed to parse external pack",
@@ -411,6 +502,8 @@ impl ErrorCode {
             Self::StdoutWriteError => 3008,
             Self::FileScanError => 3009,
             Self::DatabaseError => 3010,
+            Self::CommandFailed => 3011,
+            Self::InputAnomaly => 3012,
             Self::ExternalPackLoadFailed => 4001,
             Self::ExternalPackParseError => 4002,
             Self::NetworkRequestFailed => 4003,
@@ -434,7 +527,11 @@ impl fmt::Display for ErrorCode {
 /// A structured DCG error with code, category, message, and context.
 ///
 /// This is the standard error format for JSON output from DCG commands.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Module error types convert into it (`From` impls below), and
+/// [`DcgError::from_error`] classifies an arbitrary `dyn Error` so every CLI
+/// failure can be reported with a code.
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
+#[error("[{code}] {message}")]
 pub struct DcgError {
     /// The error code (e.g., "DCG-1001")
     pub code: String,
@@ -629,13 +726,157 @@ impl DcgError {
     }
 }
 
-impl fmt::Display for DcgError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{}] {}", self.code, self.message)
+impl DcgError {
+    /// The [`ErrorCode`] this error was created with.
+    #[must_use]
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        ErrorCode::parse(&self.code)
+    }
+
+    /// Classify any error: the first error in the `source()` chain with a
+    /// known type decides the code, falling back to
+    /// [`ErrorCode::CommandFailed`]. The message is the top-level error's.
+    #[must_use]
+    pub fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(dcg) = error.downcast_ref::<Self>() {
+            return dcg.clone();
+        }
+        let mut current = Some(error);
+        while let Some(err) = current {
+            if let Some(code) = classify(err) {
+                return Self::new(code, error.to_string());
+            }
+            current = err.source();
+        }
+        Self::new(ErrorCode::CommandFailed, error.to_string())
+    }
+}
+
+/// Error code for a known error type, if `error` is one.
+fn classify(error: &(dyn std::error::Error + 'static)) -> Option<ErrorCode> {
+    use crate::allowlist::AllowlistError;
+    use crate::packs::external::PackParseError;
+
+    if let Some(e) = error.downcast_ref::<DcgError>() {
+        return e.error_code();
+    }
+    if let Some(e) = error.downcast_ref::<PackParseError>() {
+        return Some(e.code());
     }
+    if let Some(e) = error.downcast_ref::<AllowlistError>() {
+        return Some(e.code());
+    }
+    #[cfg(feature = "cli")]
+    if let Some(e) = error.downcast_ref::<crate::hook::HookReadError>() {
+        return Some(e.code());
+    }
+    if let Some(e) = error.downcast_ref::<crate::api::GuardError>() {
+        return Some(match e {
+            crate::api::GuardError::UnknownPack(_) => ErrorCode::PackNotFound,
+            crate::api::GuardError::InvalidConfig(_) => ErrorCode::InvalidConfigValue,
+        });
+    }
+    if error.is::<crate::history::HistoryError>() || error.is::<rusqlite::Error>() {
+        return Some(ErrorCode::DatabaseError);
+    }
+    #[cfg(feature = "cli")]
+    if let Some(e) = error.downcast_ref::<crate::update::VersionCheckError>() {
+        return Some(match e {
+            crate::update::VersionCheckError::NetworkError(_) => ErrorCode::NetworkRequestFailed,
+            _ => ErrorCode::VersionCheckFailed,
+        });
+    }
+    if error.is::<crate::preview::PreviewError>() || error.is::<crate::simulate::ParseError>() {
+        return Some(ErrorCode::InvalidInput);
+    }
+    if error.is::<std::io::Error>() {
+        return Some(ErrorCode::IoError);
+    }
+    if error.is::<serde_json::Error>() {
+        return Some(ErrorCode::JsonParseError);
+    }
+    if error.is::<toml::de::Error>() || error.is::<toml_edit::TomlError>() {
+        return Some(ErrorCode::ConfigParseError);
+    }
+    if error.is::<serde_yaml::Error>() {
+        return Some(ErrorCode::ExternalPackParseError);
+    }
+    if error.is::<regex::Error>() || error.is::<fancy_regex::Error>() {
+        return Some(ErrorCode::InvalidPatternSyntax);
+    }
+    None
 }
 
-impl std::error::Error for DcgError {}
+impl From<std::io::Error> for DcgError {
+    fn from(error: std::io::Error) -> Self {
+        Self::new(ErrorCode::IoError, format!("IO error: {error}"))
+    }
+}
+
+impl From<serde_json::Error> for DcgError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::json_parse_error(&error.to_string())
+    }
+}
+
+impl From<toml::de::Error> for DcgError {
+    fn from(error: toml::de::Error) -> Self {
+        Self::new(
+            ErrorCode::ConfigParseError,
+            format!("TOML parse error: {error}"),
+        )
+    }
+}
+
+impl From<regex::Error> for DcgError {
+    fn from(error: regex::Error) -> Self {
+        Self::new(
+            ErrorCode::InvalidPatternSyntax,
+            format!("Invalid pattern: {error}"),
+        )
+    }
+}
+
+impl From<fancy_regex::Error> for DcgError {
+    fn from(error: fancy_regex::Error) -> Self {
+        Self::new(
+            ErrorCode::InvalidPatternSyntax,
+            format!("Invalid pattern: {error}"),
+        )
+    }
+}
+
+impl From<crate::packs::external::PackParseError> for DcgError {
+    fn from(error: crate::packs::external::PackParseError) -> Self {
+        Self::new(error.code(), error.to_string())
+    }
+}
+
+impl From<crate::allowlist::AllowlistError> for DcgError {
+    fn from(error: crate::allowlist::AllowlistError) -> Self {
+        Self::new(error.code(), error.to_string())
+            .add_context("layer", error.layer.label())
+            .add_context("path", error.path.display().to_string())
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<crate::hook::HookReadError> for DcgError {
+    fn from(error: crate::hook::HookReadError) -> Self {
+        Self::new(error.code(), error.to_string())
+    }
+}
+
+impl From<&crate::packs::regex_engine::SlowPattern> for DcgError {
+    fn from(slow: &crate::packs::regex_engine::SlowPattern) -> Self {
+        Self::new(ErrorCode::PatternMatchTimeout, slow.to_string())
+            .add_context("pattern", slow.pattern.clone())
+            .add_context(
+                "backtrack_limit",
+                crate::packs::regex_engine::BACKTRACK_LIMIT,
+            )
+    }
+}
 
 /// Wrapper for error responses in JSON format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -744,6 +985,84 @@ mod tests {
         assert_eq!(ErrorCode::ExternalPackLoadFailed.numeric_code(), 4001);
     }
 
+    #[test]
+    fn test_all_codes_unique_and_ordered() {
+        assert!(
+            ErrorCode::ALL
+                .windows(2)
+                .all(|w| w[0].numeric_code() < w[1].numeric_code())
+        );
+        for code in ErrorCode::ALL {
+            assert_eq!(code.as_str(), format!("DCG-{}", code.numeric_code()));
+        }
+    }
+
+    #[test]
+    fn test_error_code_parse() {
+        for input in ["DCG-2005", "dcg-2005", "DCG2005", "2005", " dcg2005 "] {
+            assert_eq!(
+                ErrorCode::parse(input),
+                Some(ErrorCode::InvalidAllowlistEntry),
+                "{input}"
+            );
+        }
+        assert_eq!(ErrorCode::parse("DCG-9999"), None);
+        assert_eq!(ErrorCode::parse("nope"), None);
+        assert!(
+            ErrorCode::IoError
+                .docs_url()
+                .ends_with("docs/error-codes.md#dcg-3002")
+        );
+    }
+
+    #[test]
+    fn test_every_code_is_documented() {
+        let doc = include_str!("../docs/error-codes.md");
+        for code in ErrorCode::ALL {
+            assert!(
+                doc.contains(&format!("\n### {}\n", code.as_str())),
+                "{code} missing from docs/error-codes.md"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        assert_eq!(DcgError::from(io).code, "DCG-3002");
+
+        let toml_err = toml::from_str::<toml::Value>("= 1").unwrap_err();
+        assert_eq!(DcgError::from(toml_err).code, "DCG-2002");
+
+        let json_err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert_eq!(DcgError::from(json_err).code, "DCG-3001");
+
+        let unclosed = String::from("(");
+        let regex_err = regex::Regex::new(&unclosed).unwrap_err();
+        assert_eq!(DcgError::from(regex_err).code, "DCG-1003");
+
+        let pack_err = crate::packs::external::PackParseError::EmptyPack;
+        assert_eq!(DcgError::from(pack_err).code, "DCG-4002");
+    }
+
+    #[test]
+    fn test_from_error_classifies_boxed_errors() {
+        let boxed: Box<dyn std::error::Error> = Box::new(std::io::Error::other("disk on fire"));
+        let error = DcgError::from_error(boxed.as_ref());
+        assert_eq!(error.error_code(), Some(ErrorCode::IoError));
+        assert_eq!(error.message, "disk on fire");
+
+        // Errors built from strings get the fallback code.
+        let boxed: Box<dyn std::error::Error> = "unknown pack 'x'".into();
+        let error = DcgError::from_error(boxed.as_ref());
+        assert_eq!(error.error_code(), Some(ErrorCode::CommandFailed));
+        assert_eq!(error.to_string(), "[DCG-3011] unknown pack 'x'");
+
+        // A DcgError passes through unchanged.
+        let boxed: Box<dyn std::error::Error> = Box::new(DcgError::config_not_found("x.toml"));
+        assert_eq!(DcgError::from_error(boxed.as_ref()).code, "DCG-2001");
+    }
+
     #[test]
     fn test_all_codes_have_descriptions() {
         // Ensure all error codes have non-empty descriptions
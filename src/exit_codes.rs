@@ -13,6 +13,8 @@
 //! | 3 | `EXIT_CONFIG_ERROR` | Configuration error |
 //! | 4 | `EXIT_PARSE_ERROR` | Parse/input error |
 //! | 5 | `EXIT_IO_ERROR` | IO error |
+//! | 124 | `EXIT_TIMEOUT` | Operation timed out (e.g. `dcg scan --timeout`) |
+//! | 130 | `EXIT_INTERRUPTED` | Interrupted by Ctrl-C |
 //!
 //! # Usage
 //!
@@ -79,6 +81,23 @@ pub const EXIT_PARSE_ERROR: i32 = 4;
 /// - Database access fails
 pub const EXIT_IO_ERROR: i32 = 5;
 
+/// Operation timed out before completing.
+///
+/// Used when:
+/// - `dcg scan --timeout` expires (partial results are still printed)
+///
+/// Matches the exit code of coreutils `timeout(1)`.
+pub const EXIT_TIMEOUT: i32 = 124;
+
+/// Operation was interrupted by Ctrl-C (SIGINT).
+///
+/// Used when:
+/// - A scan is cancelled (partial results are still printed)
+/// - Hook mode flushes history on shutdown
+///
+/// Follows the shell convention of 128 + SIGINT.
+pub const EXIT_INTERRUPTED: i32 = 130;
+
 /// Convert an exit code constant to [`std::process::ExitCode`].
 ///
 /// This is useful for returning from `main()` with the correct exit code.
@@ -146,6 +165,8 @@ mod tests {
             EXIT_CONFIG_ERROR,
             EXIT_PARSE_ERROR,
             EXIT_IO_ERROR,
+            EXIT_TIMEOUT,
+            EXIT_INTERRUPTED,
         ];
 
         // Check all codes are unique
@@ -167,6 +188,8 @@ mod tests {
             EXIT_CONFIG_ERROR,
             EXIT_PARSE_ERROR,
             EXIT_IO_ERROR,
+            EXIT_TIMEOUT,
+            EXIT_INTERRUPTED,
         ];
 
         for code in codes {
@@ -0,0 +1,172 @@
+//! Seed corpus for the `cargo fuzz` targets (`dcg fuzz-corpus export`).
+//!
+//! A panic in the hook means fail-open or a broken agent loop, so the fuzz
+//! targets in `fuzz/` push arbitrary input through hook parsing,
+//! normalization, compound splitting, and glob matching. Starting them from
+//! realistic shell strings gets them past the JSON and tokenizer front doors
+//! much faster than random bytes do.
+//!
+//! Seeds come from the regression corpus (`tests/corpus/**.toml`) plus a
+//! built-in list of adversarial strings. Each command is written twice: as
+//! raw bytes and wrapped in a Claude Code hook envelope, so `fuzz_pipeline`
+//! exercises both of its input paths.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::packs::test_helpers::load_corpus_dir;
+
+/// Default output directory, relative to the repository root.
+pub const DEFAULT_OUT_DIR: &str = "fuzz/corpus/fuzz_pipeline";
+
+/// Default regression corpus directory, relative to the repository root.
+pub const DEFAULT_CORPUS_DIR: &str = "tests/corpus";
+
+/// Shell strings that have tripped up tokenizers and normalizers before:
+/// unbalanced quotes, nested substitutions, wrapper chains, odd separators,
+/// heredocs, globs, and non-ASCII input.
+pub const ADVERSARIAL_SEEDS: &[&str] = &[
+    "",
+    " ",
+    "\\",
+    "'",
+    "\"",
+    "`",
+    "$(",
+    "${",
+    "rm -rf /",
+    "git reset --hard && git clean -fdx",
+    "echo 'unterminated && rm -rf /",
+    "echo \"$(echo \"$(echo \\\"x\\\")\")\"",
+    "a;b|c||d&&e&f\ng",
+    "sudo -u root env -i FOO=1 nice -n 5 timeout 10 nohup command git push -f",
+    "/usr/bin/env -S 'git reset --hard'",
+    "bash -c \"bash -c 'bash -c \\\"rm -rf /\\\"'\"",
+    "cat <<'EOF'\nrm -rf /\nEOF",
+    "python3 <<EOF\nimport shutil; shutil.rmtree('/')\nEOF\necho done",
+    "cat <<-\tEOF\n\tEOF",
+    "git\\ push --force",
+    "g\"i\"t reset --ha''rd",
+    "rm -r\\\n-f /",
+    "kubectl -n prod --context=prod delete ns default",
+    "aws --profile prod --region us-east-1 s3 rb s3://bucket --force",
+    "rm -rf /srv/{staging,dev{1,2}}/**",
+    "ls !(*.rs)",
+    "[[ -f x ]] && rm -- -rf",
+    "echo $((1<<62)) ${x:-${y:-${z}}}",
+    "(((((((((((((((((((((((((((((((((echo)))))))))))))))))))))))))))))))))",
+    "${${${${${${${${${${${${${${${${${${${${x}}}}}}}}}}}}}}}}}}}}",
+    "rm\u{a0}-rf /",
+    "ｒｍ -rf /",
+    "rm -rf /\u{202e}fdsa",
+    "git\u{200b} reset --hard",
+    "echo é😀\u{0}",
+];
+
+/// What [`export`] wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ExportSummary {
+    /// Distinct commands considered.
+    pub commands: usize,
+    /// Seed files created.
+    pub written: usize,
+    /// Seed files that already existed (same content hash).
+    pub existing: usize,
+}
+
+/// Commands from the regression corpus at `corpus_dir` (if it exists) and
+/// [`ADVERSARIAL_SEEDS`], deduplicated and sorted.
+///
+/// # Errors
+///
+/// Returns an error if a corpus file cannot be read or parsed.
+pub fn seed_commands(corpus_dir: Option<&Path>) -> Result<Vec<String>, String> {
+    let mut commands: BTreeSet<String> =
+        ADVERSARIAL_SEEDS.iter().map(|s| (*s).to_string()).collect();
+    if let Some(dir) = corpus_dir {
+        for (_, _, case) in load_corpus_dir(dir)? {
+            commands.insert(case.command);
+        }
+    }
+    Ok(commands.into_iter().collect())
+}
+
+/// Hook input a Claude Code `PreToolUse` Bash call would send for `command`.
+#[must_use]
+pub fn hook_envelope(command: &str) -> String {
+    serde_json::json!({
+        "tool_name": "Bash",
+        "tool_input": { "command": command },
+    })
+    .to_string()
+}
+
+/// Write each command as a raw seed and as a hook envelope seed into
+/// `out_dir`, named by content hash like `cargo fuzz` does. Existing files
+/// are left alone, so exporting twice is a no-op.
+///
+/// # Errors
+///
+/// Returns an error if `out_dir` cannot be created or a seed cannot be written.
+pub fn export(commands: &[String], out_dir: &Path) -> io::Result<ExportSummary> {
+    fs::create_dir_all(out_dir)?;
+    let mut summary = ExportSummary {
+        commands: commands.len(),
+        ..ExportSummary::default()
+    };
+    for command in commands {
+        for seed in [command.clone(), hook_envelope(command)] {
+            let mut name = String::with_capacity(64);
+            for byte in Sha256::digest(seed.as_bytes()) {
+                let _ = write!(name, "{byte:02x}");
+            }
+            let path = out_dir.join(name);
+            if path.exists() {
+                summary.existing += 1;
+            } else {
+                fs::write(path, seed)?;
+                summary.written += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_writes_raw_and_envelope_seeds_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let commands = vec!["git reset --hard".to_string(), "ls".to_string()];
+
+        let summary = export(&commands, dir.path()).unwrap();
+        assert_eq!(summary.written, 4);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 4);
+
+        let again = export(&commands, dir.path()).unwrap();
+        assert_eq!(again.written, 0);
+        assert_eq!(again.existing, 4);
+
+        let envelope = hook_envelope("git reset --hard");
+        let input: crate::hook::HookInput = serde_json::from_str(&envelope).unwrap();
+        assert_eq!(
+            crate::hook::extract_command(&input).as_deref(),
+            Some("git reset --hard")
+        );
+    }
+
+    #[test]
+    fn seed_commands_include_regression_corpus() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_CORPUS_DIR);
+        let commands = seed_commands(Some(&corpus)).unwrap();
+        assert!(commands.len() > ADVERSARIAL_SEEDS.len());
+        assert!(commands.iter().any(|c| c == "git reset --hard"));
+        assert!(commands.windows(2).all(|w| w[0] < w[1]));
+    }
+}
@@ -50,7 +50,8 @@ pub use schema::{
     HistoryAnalyzer, HistoryDb, HistoryError, HistoryStats, Outcome, OutcomeStats,
     PackEffectivenessAnalysis, PackRecommendation, PathCluster, PatternEffectiveness, PatternStat,
     PerformanceStats, PotentialGap, ProjectStat, RecommendationType, RuleMetrics, RuleTrend,
-    StatsTrends, SuggestionAction, SuggestionAuditEntry, SuggestionCandidate,
+    SAFER_ALTERNATIVE_WINDOW_SECS, SaferAlternative, StatsTrends, SuggestionAction,
+    SuggestionAuditEntry, SuggestionCandidate,
 };
 
 /// Environment variable to override the history database path.
@@ -491,7 +492,7 @@ fn check_and_prune(db: &HistoryDb, retention_days: u32) {
 
 fn redact_for_history(command: &str, mode: HistoryRedactionMode) -> String {
     match mode {
-        HistoryRedactionMode::None => command.to_string(),
+        HistoryRedactionMode::None => crate::logging::redact_secrets(command).into_owned(),
         HistoryRedactionMode::Full => "[REDACTED]".to_string(),
         HistoryRedactionMode::Pattern => {
             let config = RedactionConfig {
@@ -18,7 +18,7 @@ use std::fmt::Write as FmtWrite;
 use std::path::{Path, PathBuf};
 
 /// Current schema version for migrations.
-pub const CURRENT_SCHEMA_VERSION: u32 = 5;
+pub const CURRENT_SCHEMA_VERSION: u32 = 6;
 
 /// Default database filename.
 pub const DEFAULT_DB_FILENAME: &str = "history.db";
@@ -173,6 +173,9 @@ pub struct CommandEntry {
     /// Bypass code used (if command was bypassed).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bypass_code: Option<String>,
+    /// Who authored the command ("agent" or "human").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<String>,
 }
 
 impl Default for CommandEntry {
@@ -193,6 +196,7 @@ impl Default for CommandEntry {
             hostname: None,
             allowlist_layer: None,
             bypass_code: None,
+            provenance: None,
         }
     }
 }
@@ -372,6 +376,24 @@ pub struct SuggestionCandidate {
     pub last_seen: DateTime<Utc>,
 }
 
+/// A command that was run instead of a denied one (see
+/// [`HistoryAnalyzer::get_safer_alternatives`]).
+#[derive(Debug, Clone, Serialize)]
+pub struct SaferAlternative {
+    /// The allowed command that followed the denial.
+    pub command: String,
+    /// Number of denials it followed.
+    pub count: u64,
+    /// Most recent time it followed a denial.
+    pub last_seen: DateTime<Utc>,
+}
+
+/// How soon after a denial the replacement command must run to count.
+pub const SAFER_ALTERNATIVE_WINDOW_SECS: i64 = 600;
+
+/// Denials examined per [`HistoryAnalyzer::get_safer_alternatives`] query.
+const SAFER_ALTERNATIVE_SCAN_LIMIT: i64 = 200;
+
 /// Helper for history analysis queries used by suggestion heuristics.
 pub struct HistoryAnalyzer<'a> {
     conn: &'a Connection,
@@ -466,6 +488,98 @@ impl<'a> HistoryAnalyzer<'a> {
         Ok(clusters)
     }
 
+    /// Return commands that replaced denials of `rule_id` under `project_dir`,
+    /// most frequent first.
+    ///
+    /// A denial counts when its allow-once code went unused and the next
+    /// command from the same agent in the same directory ran the same program,
+    /// was allowed without an allowlist, and came within
+    /// [`SAFER_ALTERNATIVE_WINDOW_SECS`]. Re-running the denied command (an
+    /// allow-once redemption) does not count, nor do hashed log entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn get_safer_alternatives(
+        &self,
+        rule_id: &str,
+        project_dir: &str,
+    ) -> Result<Vec<SaferAlternative>, HistoryError> {
+        let prefix = format!(
+            "{}/%",
+            project_dir
+                .trim_end_matches('/')
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let mut stmt = self.conn.prepare(
+            r"SELECT d.command, d.timestamp, n.command, n.timestamp, n.outcome, n.allowlist_layer
+              FROM (
+                  SELECT id, command, timestamp, working_dir, agent_type
+                  FROM commands
+                  WHERE outcome = 'deny' AND rule_id = ?1
+                    AND (working_dir = ?2 OR working_dir LIKE ?3 ESCAPE '\')
+                  ORDER BY id DESC
+                  LIMIT ?4
+              ) d
+              JOIN commands n ON n.id = (
+                  SELECT MIN(c.id) FROM commands c
+                  WHERE c.id > d.id AND c.working_dir = d.working_dir
+                    AND c.agent_type = d.agent_type
+              )",
+        )?;
+        let rows = stmt.query_map(
+            params![rule_id, project_dir, prefix, SAFER_ALTERNATIVE_SCAN_LIMIT],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                ))
+            },
+        )?;
+
+        let parse = |ts: &str| {
+            DateTime::parse_from_rfc3339(ts)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        };
+        let mut alternatives: Vec<SaferAlternative> = Vec::new();
+        for row in rows {
+            let (denied, denied_at, next, next_at, outcome, allowlist_layer) = row?;
+            let (Some(denied_at), Some(next_at)) = (parse(&denied_at), parse(&next_at)) else {
+                continue;
+            };
+            let replaced = outcome == Outcome::Allow.as_str()
+                && allowlist_layer.is_none()
+                && next != denied
+                && !crate::privacy::is_hashed(&next)
+                && (next_at - denied_at).num_seconds() <= SAFER_ALTERNATIVE_WINDOW_SECS
+                && program_name(&next).is_some()
+                && program_name(&next) == program_name(&denied);
+            if !replaced {
+                continue;
+            }
+            match alternatives.iter_mut().find(|alt| alt.command == next) {
+                Some(alt) => {
+                    alt.count += 1;
+                    alt.last_seen = alt.last_seen.max(next_at);
+                }
+                None => alternatives.push(SaferAlternative {
+                    command: next,
+                    count: 1,
+                    last_seen: next_at,
+                }),
+            }
+        }
+        alternatives.sort_by(|a, b| b.count.cmp(&a.count).then(b.last_seen.cmp(&a.last_seen)));
+        Ok(alternatives)
+    }
+
     /// Return commands that were manually bypassed (allow-once).
     ///
     /// This approximates "manual allows" using the `bypass` outcome.
@@ -516,6 +630,15 @@ struct StatsSnapshot {
     performance: PerformanceStats,
 }
 
+/// The program a command runs: the first word that is not an environment
+/// assignment, without its directory.
+fn program_name(command: &str) -> Option<&str> {
+    let word = command
+        .split_whitespace()
+        .find(|word| !word.contains('='))?;
+    word.rsplit('/').next()
+}
+
 fn format_timestamp(dt: DateTime<Utc>) -> String {
     dt.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
 }
@@ -974,9 +1097,9 @@ impl HistoryDb {
                 timestamp, agent_type, working_dir, command, command_hash,
                 outcome, pack_id, pattern_name, rule_id, eval_duration_us,
                 session_id, exit_code, parent_command_id, hostname,
-                allowlist_layer, bypass_code
+                allowlist_layer, bypass_code, provenance
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17
             )",
             params![
                 timestamp,
@@ -995,6 +1118,7 @@ impl HistoryDb {
                 entry.hostname,
                 entry.allowlist_layer,
                 entry.bypass_code,
+                entry.provenance,
             ],
         )?;
 
@@ -1076,7 +1200,8 @@ impl HistoryDb {
                 parent_command_id INTEGER REFERENCES commands(id),
                 hostname TEXT,
                 allowlist_layer TEXT,
-                bypass_code TEXT
+                bypass_code TEXT,
+                provenance TEXT
             )",
             [],
         )?;
@@ -1224,6 +1349,9 @@ impl HistoryDb {
         if from_version < 5 {
             self.migrate_v4_to_v5()?;
         }
+        if from_version < 6 {
+            self.migrate_v5_to_v6()?;
+        }
 
         // Ensure we're at the expected version
         let current = self.get_schema_version()?;
@@ -1388,6 +1516,28 @@ impl HistoryDb {
         Ok(())
     }
 
+    fn migrate_v5_to_v6(&self) -> Result<(), HistoryError> {
+        // Add provenance column (agent vs human authored commands)
+        let columns: Vec<String> = self
+            .conn
+            .prepare("PRAGMA table_info(commands)")?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<_, _>>()?;
+
+        if !columns.iter().any(|col| col == "provenance") {
+            self.conn
+                .execute("ALTER TABLE commands ADD COLUMN provenance TEXT", [])?;
+        }
+
+        // Record migration
+        self.conn.execute(
+            "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+            params![6_u32, "Add provenance column"],
+        )?;
+
+        Ok(())
+    }
+
     // ========================================================================
     // Batch Operations
     // ========================================================================
@@ -1416,9 +1566,9 @@ impl HistoryDb {
                     timestamp, agent_type, working_dir, command, command_hash,
                     outcome, pack_id, pattern_name, eval_duration_us,
                     session_id, exit_code, parent_command_id, hostname,
-                    allowlist_layer, bypass_code, rule_id
+                    allowlist_layer, bypass_code, rule_id, provenance
                 ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16
+                    ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17
                 )",
                 params![
                     timestamp,
@@ -1437,6 +1587,7 @@ impl HistoryDb {
                     entry.allowlist_layer,
                     entry.bypass_code,
                     entry.get_rule_id(),
+                    entry.provenance,
                 ],
             )?;
         }
@@ -1870,7 +2021,8 @@ impl HistoryDb {
         let mut sql = String::from(
             "SELECT timestamp, agent_type, working_dir, command, outcome,
                     pack_id, pattern_name, rule_id, eval_duration_us, session_id,
-                    exit_code, parent_command_id, hostname, allowlist_layer, bypass_code
+                    exit_code, parent_command_id, hostname, allowlist_layer, bypass_code,
+                    provenance
              FROM commands WHERE 1=1",
         );
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -1925,6 +2077,7 @@ impl HistoryDb {
                 hostname: row.get(12)?,
                 allowlist_layer: row.get(13)?,
                 bypass_code: row.get(14)?,
+                provenance: row.get(15)?,
             })
         })?;
 
@@ -3807,6 +3960,31 @@ mod tests {
         assert!(description_count > 0);
     }
 
+    #[test]
+    fn test_migration_adds_provenance_column() {
+        let db = HistoryDb::open_in_memory().unwrap();
+        db.conn
+            .execute_batch(
+                "ALTER TABLE commands DROP COLUMN provenance;
+                 UPDATE schema_version SET version = 5;",
+            )
+            .unwrap();
+        assert_eq!(db.get_schema_version().unwrap(), 5);
+
+        db.run_migrations(5).unwrap();
+        assert_eq!(db.get_schema_version().unwrap(), CURRENT_SCHEMA_VERSION);
+
+        db.log_command(&CommandEntry {
+            provenance: Some("human".to_string()),
+            ..test_entry()
+        })
+        .unwrap();
+        let exported = db
+            .query_commands_for_export(&ExportOptions::default())
+            .unwrap();
+        assert_eq!(exported[0].provenance.as_deref(), Some("human"));
+    }
+
     #[test]
     fn test_command_hash_deterministic() {
         let entry1 = CommandEntry {
@@ -3949,6 +4127,7 @@ mod tests {
             hostname: Some("dev-machine".to_string()),
             allowlist_layer: None,
             bypass_code: Some("ab12".to_string()),
+            provenance: Some("agent".to_string()),
         };
 
         let id = db.log_command(&entry).unwrap();
@@ -4292,6 +4471,83 @@ mod tests {
         assert_eq!(results[0].bypass_count, 2);
     }
 
+    #[test]
+    fn test_history_analyzer_safer_alternatives() {
+        let db = HistoryDb::open_in_memory().unwrap();
+        let start = Utc::now() - Duration::days(1);
+        let at = |secs: i64| start + Duration::seconds(secs);
+        let deny = |dir: &str, command: &str, timestamp| {
+            let entry = CommandEntry {
+                timestamp,
+                agent_type: "claude_code".to_string(),
+                working_dir: dir.to_string(),
+                command: command.to_string(),
+                outcome: Outcome::Deny,
+                pack_id: Some("core.git".to_string()),
+                pattern_name: Some("reset-hard".to_string()),
+                ..Default::default()
+            };
+            db.log_command(&entry).unwrap();
+        };
+
+        // Replaced twice in the project (once from a subdirectory).
+        deny("/project/a", "git reset --hard", at(0));
+        insert_command(&db, "git stash", Outcome::Allow, "/project/a", at(5));
+        deny("/project/a/src", "git reset --hard HEAD", at(100));
+        insert_command(&db, "git stash", Outcome::Allow, "/project/a/src", at(110));
+        // Replaced once with something else.
+        deny("/project/a", "git reset --hard", at(200));
+        insert_command(
+            &db,
+            "git reset --soft HEAD",
+            Outcome::Allow,
+            "/project/a",
+            at(210),
+        );
+        // Not replacements: redeemed allow-once, other program, too late.
+        deny("/project/a", "git reset --hard", at(300));
+        insert_command(
+            &db,
+            "git reset --hard",
+            Outcome::Allow,
+            "/project/a",
+            at(305),
+        );
+        deny("/project/a", "git reset --hard", at(400));
+        insert_command(&db, "ls", Outcome::Allow, "/project/a", at(405));
+        deny("/project/a", "git reset --hard", at(500));
+        insert_command(&db, "git status", Outcome::Allow, "/project/a", at(5000));
+        // Other projects do not count (including name prefixes).
+        deny("/project/ab", "git reset --hard", at(6000));
+        insert_command(
+            &db,
+            "git checkout .",
+            Outcome::Allow,
+            "/project/ab",
+            at(6005),
+        );
+
+        let analyzer = HistoryAnalyzer::new(&db);
+        let results = analyzer
+            .get_safer_alternatives("core.git:reset-hard", "/project/a")
+            .unwrap();
+        let commands: Vec<(&str, u64)> = results
+            .iter()
+            .map(|alt| (alt.command.as_str(), alt.count))
+            .collect();
+        assert_eq!(
+            commands,
+            vec![("git stash", 2), ("git reset --soft HEAD", 1)]
+        );
+
+        assert!(
+            analyzer
+                .get_safer_alternatives("core.git:clean-force", "/project/a")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     // ========================================================================
     // Pack Effectiveness Analysis Tests
     // ========================================================================
@@ -3,14 +3,17 @@
 //! This module handles the JSON input/output for the Claude Code `PreToolUse` hook.
 //! It parses incoming hook requests and formats denial responses.
 
-use crate::evaluator::MatchSpan;
+use crate::config::{DenialTemplateVars, MessagesConfig, WarnChannel};
+use crate::evaluator::{ConfidenceGate, MatchSpan, PatternMatch, SeverityAggregationTrace};
 use crate::highlight::HighlightSpan;
 use crate::output::auto_theme;
 #[cfg(feature = "rich-output")]
 use crate::output::console::console;
 use crate::output::denial::DenialBox;
+use crate::output::hyperlink::{ISSUE_URL, hyperlink};
 use crate::output::theme::Severity as ThemeSeverity;
 use crate::packs::PatternSuggestion;
+use crate::suggestions::Suggestion;
 use colored::Colorize;
 #[cfg(feature = "rich-output")]
 #[allow(unused_imports)]
@@ -18,6 +21,7 @@ use rich_rust::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::io::{self, IsTerminal, Read, Write};
+use std::sync::OnceLock;
 use std::time::Duration;
 
 /// Input structure from Claude Code's `PreToolUse` hook.
@@ -38,6 +42,16 @@ pub struct HookInput {
     /// May be a JSON string (e.g. "{\"command\":\"...\"}") or an object.
     #[serde(alias = "toolArgs")]
     pub tool_args: Option<serde_json::Value>,
+
+    /// Agent session identifier (Claude Code sends `session_id`), used by
+    /// session-scoped allow-once entries.
+    #[serde(default, alias = "sessionId")]
+    pub session_id: Option<String>,
+
+    /// Path of the agent's session transcript (Claude Code sends
+    /// `transcript_path`); identifies the session when no id is given.
+    #[serde(default, alias = "transcriptPath")]
+    pub transcript_path: Option<String>,
 }
 
 /// Tool-specific input containing the command to execute.
@@ -62,7 +76,7 @@ pub struct HookSpecificOutput<'a> {
     #[serde(rename = "hookEventName")]
     pub hook_event_name: &'static str,
 
-    /// The permission decision: "allow" or "deny".
+    /// The permission decision: "allow", "deny", or "ask".
     #[serde(rename = "permissionDecision")]
     pub permission_decision: &'static str,
 
@@ -78,6 +92,10 @@ pub struct HookSpecificOutput<'a> {
     #[serde(rename = "allowOnceFullHash", skip_serializing_if = "Option::is_none")]
     pub allow_once_full_hash: Option<String>,
 
+    /// Request ID in the approvals queue (if the denial was queued).
+    #[serde(rename = "approvalId", skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<u64>,
+
     // --- New fields for AI agent ergonomics (git_safety_guard-e4fl.1) ---
     /// Stable rule identifier (e.g., "core.git:reset-hard").
     /// Format: "{packId}:{patternName}"
@@ -97,29 +115,41 @@ pub struct HookSpecificOutput<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confidence: Option<f64>,
 
+    /// Minimum confidence to deny that `confidence` was compared against.
+    #[serde(
+        rename = "minConfidenceToDeny",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_confidence_to_deny: Option<f64>,
+
     /// Remediation suggestions for the blocked command.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remediation: Option<Remediation>,
+
+    /// Every pattern that matched, highest severity first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<MatchEntry>,
 }
 
-/// Copilot-compatible denial output for pre-tool-use hooks.
+/// Copilot-compatible output for pre-tool-use hooks.
 ///
 /// Copilot hooks can consume either:
 /// - `continue=false` with `stopReason`
 /// - `permissionDecision=deny` with `permissionDecisionReason`
 ///
-/// We emit both for compatibility across documented variants.
+/// We emit both for compatibility across documented variants. Warn advisories
+/// use `continue=true` with `permissionDecision=allow` and no `stopReason`.
 #[derive(Debug, Serialize)]
 pub struct CopilotHookOutput<'a> {
     /// Whether execution should continue.
     #[serde(rename = "continue")]
     pub continue_execution: bool,
 
-    /// Human-readable stop reason.
-    #[serde(rename = "stopReason")]
+    /// Human-readable stop reason (omitted when empty).
+    #[serde(rename = "stopReason", skip_serializing_if = "cow_is_empty")]
     pub stop_reason: Cow<'a, str>,
 
-    /// Permission decision (`deny`).
+    /// Permission decision (`deny`, or `allow` for warn advisories).
     #[serde(rename = "permissionDecision")]
     pub permission_decision: &'static str,
 
@@ -135,6 +165,10 @@ pub struct CopilotHookOutput<'a> {
     #[serde(rename = "allowOnceFullHash", skip_serializing_if = "Option::is_none")]
     pub allow_once_full_hash: Option<String>,
 
+    /// Request ID in the approvals queue (if the denial was queued).
+    #[serde(rename = "approvalId", skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<u64>,
+
     /// Stable rule identifier (e.g., "core.git:reset-hard").
     #[serde(rename = "ruleId", skip_serializing_if = "Option::is_none")]
     pub rule_id: Option<String>,
@@ -151,9 +185,62 @@ pub struct CopilotHookOutput<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confidence: Option<f64>,
 
+    /// Minimum confidence to deny that `confidence` was compared against.
+    #[serde(
+        rename = "minConfidenceToDeny",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_confidence_to_deny: Option<f64>,
+
     /// Remediation suggestions for the blocked command.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remediation: Option<Remediation>,
+
+    /// Every pattern that matched, highest severity first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<MatchEntry>,
+}
+
+#[allow(clippy::ptr_arg)]
+fn cow_is_empty(value: &Cow<'_, str>) -> bool {
+    value.is_empty()
+}
+
+/// One pattern that matched the command, as listed in the `matches` array.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchEntry {
+    /// Stable rule identifier (e.g., "core.git:reset-hard").
+    #[serde(rename = "ruleId", skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+
+    /// Pack identifier that matched (e.g., "core.git").
+    #[serde(rename = "packId", skip_serializing_if = "Option::is_none")]
+    pub pack_id: Option<String>,
+
+    /// Severity level of the matched pattern.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<crate::packs::Severity>,
+
+    /// Human-readable reason for the match.
+    pub reason: String,
+
+    /// Matched byte span (start, end) in the command.
+    #[serde(rename = "matchedSpan", skip_serializing_if = "Option::is_none")]
+    pub matched_span: Option<(usize, usize)>,
+}
+
+impl MatchEntry {
+    /// Build an entry from an evaluator match.
+    #[must_use]
+    pub fn from_pattern_match(info: &PatternMatch) -> Self {
+        Self {
+            rule_id: build_rule_id(info.pack_id.as_deref(), info.pattern_name.as_deref()),
+            pack_id: info.pack_id.clone(),
+            severity: info.severity,
+            reason: info.reason.clone(),
+            matched_span: info.matched_span.map(|span| (span.start, span.end)),
+        }
+    }
 }
 
 /// Hook protocol variant for response formatting.
@@ -170,6 +257,8 @@ pub enum HookProtocol {
 pub struct AllowOnceInfo {
     pub code: String,
     pub full_hash: String,
+    /// Set when the denial was also queued for `dcg approvals`.
+    pub approval_id: Option<u64>,
 }
 
 /// Remediation suggestions for blocked commands.
@@ -216,14 +305,31 @@ pub enum HookResult {
 }
 
 /// Error type for reading and parsing hook input.
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum HookReadError {
     /// Failed to read from stdin.
-    Io(io::Error),
+    #[error("failed to read hook input: {0}")]
+    Io(#[source] io::Error),
     /// Input exceeded the configured size limit.
+    #[error("hook input ({0} bytes) exceeds the size limit")]
     InputTooLarge(usize),
     /// Failed to parse JSON input.
-    Json(serde_json::Error),
+    #[error("hook input is not valid JSON: {0}")]
+    Json(#[source] serde_json::Error),
+}
+
+impl HookReadError {
+    /// Stable error code for this error (see `docs/error-codes.md`).
+    #[must_use]
+    pub const fn code(&self) -> crate::error_codes::ErrorCode {
+        use crate::error_codes::ErrorCode;
+
+        match self {
+            Self::Io(_) => ErrorCode::StdinReadError,
+            Self::InputTooLarge(_) => ErrorCode::InvalidInput,
+            Self::Json(_) => ErrorCode::HookProtocolError,
+        }
+    }
 }
 
 /// Read and parse hook input from stdin.
@@ -234,6 +340,15 @@ pub enum HookReadError {
 /// if the input is not valid hook JSON, or [`HookReadError::InputTooLarge`] if
 /// the input exceeds `max_bytes`.
 pub fn read_hook_input(max_bytes: usize) -> Result<HookInput, HookReadError> {
+    read_hook_input_with_raw(max_bytes).map(|(input, _)| input)
+}
+
+/// Like [`read_hook_input`], also returning the raw stdin text (for `--trace-file`).
+///
+/// # Errors
+///
+/// Same as [`read_hook_input`].
+pub fn read_hook_input_with_raw(max_bytes: usize) -> Result<(HookInput, String), HookReadError> {
     let mut input = String::with_capacity(256);
     {
         let stdin = io::stdin();
@@ -248,7 +363,8 @@ pub fn read_hook_input(max_bytes: usize) -> Result<HookInput, HookReadError> {
         return Err(HookReadError::InputTooLarge(input.len()));
     }
 
-    serde_json::from_str(&input).map_err(HookReadError::Json)
+    let parsed = serde_json::from_str(&input).map_err(HookReadError::Json)?;
+    Ok((parsed, input))
 }
 
 /// Detect which hook protocol should be used for output formatting.
@@ -404,6 +520,38 @@ fn format_explanation_block(explanation: &str) -> String {
     output
 }
 
+/// Closing line of a denial when `[messages] denial_template` is unset.
+pub const DEFAULT_DENIAL_GUIDANCE: &str = "If this operation is truly needed, ask the user for \
+     explicit permission and have them run the command manually.";
+
+/// Denial text settings (set from `[messages]`).
+static MESSAGES: OnceLock<MessagesConfig> = OnceLock::new();
+
+/// Initialize denial text settings from configuration.
+pub fn init_messages(messages: MessagesConfig) {
+    let _ = MESSAGES.set(messages);
+}
+
+/// The closing guidance of a denial: the rendered `denial_template`, or
+/// [`DEFAULT_DENIAL_GUIDANCE`].
+fn denial_guidance(
+    messages: Option<&MessagesConfig>,
+    command: &str,
+    reason: &str,
+    rule_id: Option<&str>,
+    pack: Option<&str>,
+) -> Cow<'static, str> {
+    let vars = DenialTemplateVars {
+        rule_id,
+        pack,
+        reason,
+        command,
+    };
+    messages
+        .and_then(|messages| messages.render_denial(&vars))
+        .map_or(Cow::Borrowed(DEFAULT_DENIAL_GUIDANCE), Cow::Owned)
+}
+
 /// Format the denial message for the JSON output (plain text).
 #[must_use]
 pub fn format_denial_message(
@@ -412,6 +560,7 @@ pub fn format_denial_message(
     explanation: Option<&str>,
     pack: Option<&str>,
     pattern: Option<&str>,
+    other_matches: &[PatternMatch],
 ) -> String {
     let explain_hint = format_explain_hint(command);
     let rule_id = build_rule_id(pack, pattern);
@@ -425,6 +574,27 @@ pub fn format_denial_message(
         },
         |rule| format!("Rule: {rule}\n\n"),
     );
+    let also_matched: String = other_matches
+        .iter()
+        .map(|other| {
+            let id = build_rule_id(other.pack_id.as_deref(), other.pattern_name.as_deref())
+                .unwrap_or_else(|| other.reason.clone());
+            match other.severity {
+                Some(severity) => format!("Also matched: {id} ({})\n", severity.label()),
+                None => format!("Also matched: {id}\n"),
+            }
+        })
+        .collect();
+    let also_matched = if also_matched.is_empty() {
+        also_matched
+    } else {
+        also_matched + "\n"
+    };
+    let trash_hint = crate::trash::suggestion_for(command).map_or_else(String::new, |put| {
+        format!("Safe alternative: `{put}` moves the targets to a restorable trash.\n\n")
+    });
+
+    let guidance = denial_guidance(MESSAGES.get(), command, reason, rule_id.as_deref(), pack);
 
     format!(
         "BLOCKED by dcg\n\n\
@@ -432,9 +602,10 @@ pub fn format_denial_message(
          Reason: {reason}\n\n\
          {explanation_block}\n\n\
          {rule_line}\
+         {also_matched}\
          Command: {command}\n\n\
-         If this operation is truly needed, ask the user for explicit \
-         permission and have them run the command manually."
+         {trash_hint}\
+         {guidance}"
     )
 }
 
@@ -454,14 +625,16 @@ const MAX_SUGGESTIONS: usize = 4;
 #[allow(clippy::too_many_lines)]
 pub fn print_colorful_warning(
     command: &str,
-    _reason: &str,
+    reason: &str,
     pack: Option<&str>,
     pattern: Option<&str>,
     explanation: Option<&str>,
     allow_once_code: Option<&str>,
     matched_span: Option<&MatchSpan>,
     pattern_suggestions: &[PatternSuggestion],
+    command_suggestions: &[Suggestion],
     severity: Option<crate::packs::Severity>,
+    other_matches: &[PatternMatch],
 ) {
     #[cfg(feature = "rich-output")]
     let console_instance = console();
@@ -493,12 +666,27 @@ pub fn print_colorful_warning(
     } else {
         Vec::new()
     };
-    let mut alternatives: Vec<String> = filtered_suggestions
+    // Suggestions built from this command, then commands this project used
+    // instead of the rule, come first.
+    let tailored = command_suggestions
         .iter()
+        .filter(|_| suggestions_enabled)
+        .filter_map(|s| Some(format!("{}: {}", s.text, s.command.as_deref()?)));
+    let mut alternatives: Vec<String> = tailored
+        .chain(
+            filtered_suggestions
+                .iter()
+                .map(|s| format!("{}: {}", s.description, s.command)),
+        )
         .take(MAX_SUGGESTIONS)
-        .map(|s| format!("{}: {}", s.description, s.command))
         .collect();
 
+    // A restorable delete beats any static suggestion for `rm -r`.
+    if let Some(put) = crate::trash::suggestion_for(command).filter(|_| suggestions_enabled) {
+        alternatives.insert(0, format!("Move to the trash instead (restorable): {put}"));
+        alternatives.truncate(MAX_SUGGESTIONS);
+    }
+
     // Add contextual suggestion if available and no pattern suggestions
     if suggestions_enabled && alternatives.is_empty() {
         if let Some(sugg) = get_contextual_suggestion(command) {
@@ -506,8 +694,20 @@ pub fn print_colorful_warning(
         }
     }
 
+    let other_matches = other_matches
+        .iter()
+        .map(|other| {
+            let id = build_rule_id(other.pack_id.as_deref(), other.pattern_name.as_deref())
+                .unwrap_or_else(|| other.reason.clone());
+            let severity = other
+                .severity
+                .map_or(ThemeSeverity::High, to_output_severity);
+            (id, severity)
+        })
+        .collect();
     let mut denial = DenialBox::new(command, span, pattern_display, theme_severity)
-        .with_alternatives(alternatives);
+        .with_alternatives(alternatives)
+        .with_other_matches(other_matches);
 
     if let Some(text) = explanation_text {
         denial = denial.with_explanation(text);
@@ -517,6 +717,18 @@ pub fn print_colorful_warning(
         denial = denial.with_allow_once_code(code);
     }
 
+    let impact = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| crate::impact::estimate_rm(command, &cwd, crate::impact::budget()))
+        .map(|estimate| estimate.summary())
+        .or_else(|| {
+            let timeout = crate::storage_impact::timeout()?;
+            crate::storage_impact::estimate_storage(command, timeout).map(|impact| impact.summary())
+        });
+    if let Some(summary) = impact {
+        denial = denial.with_impact(summary);
+    }
+
     // Render the denial box
     // Note: DcgConsole auto-detects stderr usage
     eprintln!("{}", denial.render(&theme));
@@ -539,6 +751,18 @@ pub fn print_colorful_warning(
     let reset = if theme.colors_enabled { "\x1b[0m" } else { "" };
     let cyan = if theme.colors_enabled { "\x1b[36m" } else { "" };
 
+    if let Some(guidance) = MESSAGES.get().and_then(|messages| {
+        messages.render_denial(&DenialTemplateVars {
+            rule_id: rule_id.as_deref(),
+            pack,
+            reason,
+            command,
+        })
+    }) {
+        eprintln!("{guidance}");
+        eprintln!();
+    }
+
     eprintln!("{footer_style}Learn more:{reset}");
     eprintln!("  $ {cyan}{explain_cmd}{reset}");
 
@@ -547,10 +771,13 @@ pub fn print_colorful_warning(
     }
 
     eprintln!();
+    let issue_url = if theme.colors_enabled {
+        hyperlink(ISSUE_URL, ISSUE_URL)
+    } else {
+        ISSUE_URL.to_string()
+    };
     eprintln!("{footer_style}False positive? File an issue:{reset}");
-    eprintln!(
-        "{footer_style}https://github.com/Dicklesworthstone/destructive_command_guard/issues/new?template=false_positive.yml{reset}"
-    );
+    eprintln!("{footer_style}{issue_url}{reset}");
     eprintln!();
 }
 
@@ -626,6 +853,9 @@ fn get_contextual_suggestion(command: &str) -> Option<&'static str> {
 }
 
 /// Output a denial response to stdout (JSON for hook protocol).
+///
+/// `repeat_count` is how many times the session has now been denied this
+/// command; above 1, the stderr box collapses to a single line.
 #[cold]
 #[inline(never)]
 #[allow(clippy::too_many_arguments)]
@@ -639,30 +869,62 @@ pub fn output_denial_for_protocol(
     allow_once: Option<&AllowOnceInfo>,
     matched_span: Option<&MatchSpan>,
     severity: Option<crate::packs::Severity>,
-    confidence: Option<f64>,
+    confidence: Option<ConfidenceGate>,
     pattern_suggestions: &[PatternSuggestion],
+    command_suggestions: &[Suggestion],
+    other_matches: &[PatternMatch],
+    repeat_count: u32,
 ) {
-    // Print colorful warning to stderr (visible to user)
+    // Print colorful warning to stderr (visible to user); a retry of an
+    // already-blocked command gets one line instead.
     let allow_once_code = allow_once.map(|info| info.code.as_str());
-    print_colorful_warning(
-        command,
-        reason,
-        pack,
-        pattern,
-        explanation,
-        allow_once_code,
-        matched_span,
-        pattern_suggestions,
-        severity,
-    );
+    if repeat_count > 1 {
+        print_repeat_denial_notice(command, reason, pack, pattern, repeat_count);
+    } else {
+        print_colorful_warning(
+            command,
+            reason,
+            pack,
+            pattern,
+            explanation,
+            allow_once_code,
+            matched_span,
+            pattern_suggestions,
+            command_suggestions,
+            severity,
+            other_matches,
+        );
+    }
 
     // Build JSON response for hook protocol (stdout)
-    let message = format_denial_message(command, reason, explanation, pack, pattern);
+    let mut message =
+        format_denial_message(command, reason, explanation, pack, pattern, other_matches);
+    if let Some(id) = allow_once.and_then(|info| info.approval_id) {
+        use std::fmt::Write as _;
+        let _ = write!(
+            message,
+            "\n\nQueued for human approval as request {id}. Once a human runs \
+             `dcg approvals approve {id}`, retry this exact command."
+        );
+    }
     let rule_id = build_rule_id(pack, pattern);
+    let matches: Vec<MatchEntry> = std::iter::once(MatchEntry {
+        rule_id: rule_id.clone(),
+        pack_id: pack.map(String::from),
+        severity,
+        reason: reason.to_string(),
+        matched_span: matched_span.map(|span| (span.start, span.end)),
+    })
+    .chain(other_matches.iter().map(MatchEntry::from_pattern_match))
+    .collect();
     let remediation = allow_once.map(|info| {
         let explanation_text = format_explanation_text(explanation, rule_id.as_deref(), pack);
         Remediation {
-            safe_alternative: get_contextual_suggestion(command).map(String::from),
+            safe_alternative: command_suggestions
+                .iter()
+                .find_map(|s| s.command.clone())
+                .or_else(|| crate::trash::suggestion_for(command))
+                .or_else(|| get_contextual_suggestion(command).map(String::from)),
             explanation: explanation_text,
             allow_once_command: format!("dcg allow-once {}", info.code),
         }
@@ -680,11 +942,14 @@ pub fn output_denial_for_protocol(
                     permission_decision_reason: Cow::Owned(message),
                     allow_once_code: allow_once.map(|info| info.code.clone()),
                     allow_once_full_hash: allow_once.map(|info| info.full_hash.clone()),
+                    approval_id: allow_once.and_then(|info| info.approval_id),
                     rule_id,
                     pack_id: pack.map(String::from),
                     severity,
-                    confidence,
+                    confidence: confidence.map(|gate| gate_value(gate.confidence)),
+                    min_confidence_to_deny: confidence.map(|gate| gate_value(gate.threshold)),
                     remediation,
+                    matches,
                 },
             };
 
@@ -699,11 +964,14 @@ pub fn output_denial_for_protocol(
                 permission_decision_reason: Cow::Owned(message),
                 allow_once_code: allow_once.map(|info| info.code.clone()),
                 allow_once_full_hash: allow_once.map(|info| info.full_hash.clone()),
+                approval_id: allow_once.and_then(|info| info.approval_id),
                 rule_id,
                 pack_id: pack.map(String::from),
                 severity,
-                confidence,
+                confidence: confidence.map(|gate| gate_value(gate.confidence)),
+                min_confidence_to_deny: confidence.map(|gate| gate_value(gate.threshold)),
                 remediation,
+                matches,
             };
 
             let _ = serde_json::to_writer(&mut handle, &output);
@@ -712,6 +980,23 @@ pub fn output_denial_for_protocol(
     }
 }
 
+/// One-line stderr notice for a command the session has already seen
+/// blocked, in place of the full denial box.
+fn print_repeat_denial_notice(
+    command: &str,
+    reason: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    count: u32,
+) {
+    let label = build_rule_id(pack, pattern).unwrap_or_else(|| reason.to_string());
+    let _ = writeln!(
+        io::stderr(),
+        "{} {label}: {command}",
+        format!("dcg: previously blocked (x{count})").red().bold()
+    );
+}
+
 /// Output a denial response to stdout (JSON for hook protocol).
 #[cold]
 #[inline(never)]
@@ -725,8 +1010,9 @@ pub fn output_denial(
     allow_once: Option<&AllowOnceInfo>,
     matched_span: Option<&MatchSpan>,
     severity: Option<crate::packs::Severity>,
-    confidence: Option<f64>,
+    confidence: Option<ConfidenceGate>,
     pattern_suggestions: &[PatternSuggestion],
+    other_matches: &[PatternMatch],
 ) {
     output_denial_for_protocol(
         HookProtocol::ClaudeCompatible,
@@ -740,6 +1026,9 @@ pub fn output_denial(
         severity,
         confidence,
         pattern_suggestions,
+        &[],
+        other_matches,
+        1,
     );
 }
 
@@ -790,6 +1079,384 @@ pub fn output_warning(
     );
 }
 
+/// Surface a warn-mode decision through the configured [`WarnChannel`].
+///
+/// The command is allowed on every channel; they differ only in whether the
+/// agent gets something it can read (an allow advisory on stdout or a JSON
+/// event on stderr) or just the human-readable stderr notice.
+#[cold]
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+pub fn output_warning_for_channel(
+    channel: WarnChannel,
+    protocol: HookProtocol,
+    command: &str,
+    reason: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    explanation: Option<&str>,
+    severity: Option<crate::packs::Severity>,
+    confidence: Option<ConfidenceGate>,
+) {
+    match channel {
+        WarnChannel::Stderr => {
+            output_warning(command, reason, pack, pattern, explanation);
+            output_confidence_note(confidence);
+        }
+        WarnChannel::Advisory => {
+            output_warning(command, reason, pack, pattern, explanation);
+            output_confidence_note(confidence);
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            let _ = write_warning_advisory(
+                &mut handle,
+                protocol,
+                command,
+                reason,
+                pack,
+                pattern,
+                explanation,
+                severity,
+                confidence,
+            );
+        }
+        WarnChannel::Event => {
+            let stderr = io::stderr();
+            let mut handle = stderr.lock();
+            let _ = write_warning_event(
+                &mut handle,
+                command,
+                reason,
+                pack,
+                pattern,
+                severity,
+                confidence,
+            );
+        }
+    }
+}
+
+/// Explain on stderr that a warning is a low-confidence fallback from deny.
+fn output_confidence_note(confidence: Option<ConfidenceGate>) {
+    if let Some(gate) = confidence.filter(|gate| gate.downgraded) {
+        let _ = writeln!(
+            io::stderr(),
+            "  {} {}",
+            "Confidence:".bright_black(),
+            format_confidence_gate(&gate)
+        );
+    }
+}
+
+/// One-line summary of a confidence gate, e.g.
+/// `0.10 (min_confidence_to_deny 0.50, downgraded to warn)`.
+#[must_use]
+pub fn format_confidence_gate(gate: &ConfidenceGate) -> String {
+    let outcome = if gate.downgraded {
+        ", downgraded to warn"
+    } else {
+        ""
+    };
+    format!(
+        "{:.2} (min_confidence_to_deny {:.2}{outcome})",
+        gate.confidence, gate.threshold
+    )
+}
+
+/// A confidence value for JSON output, rounded to three decimals so `f32`
+/// noise (`0.10000000149`) does not leak through.
+#[must_use]
+pub(crate) fn gate_value(value: f32) -> f64 {
+    (f64::from(value) * 1000.0).round() / 1000.0
+}
+
+/// Format the `permissionDecisionReason` for a warn advisory.
+#[must_use]
+pub fn format_warning_message(
+    command: &str,
+    reason: &str,
+    explanation: Option<&str>,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+) -> String {
+    let rule_id = build_rule_id(pack, pattern);
+    let explanation_text = format_explanation_text(explanation, rule_id.as_deref(), pack);
+    let explanation_block = format_explanation_block(&explanation_text);
+    let rule_line = rule_id.as_deref().map_or_else(
+        || {
+            pack.map(|pack_name| format!("Pack: {pack_name}\n\n"))
+                .unwrap_or_default()
+        },
+        |rule| format!("Rule: {rule}\n\n"),
+    );
+
+    format!(
+        "WARNING from dcg (allowed by policy)\n\n\
+         Reason: {reason}\n\n\
+         {explanation_block}\n\n\
+         {rule_line}\
+         Command: {command}\n\n\
+         The command was allowed; this warning is informational."
+    )
+}
+
+/// Write an `allow` hook response whose reason carries the warning.
+///
+/// # Errors
+///
+/// Returns any error from serializing or writing to `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn write_warning_advisory<W: Write>(
+    out: &mut W,
+    protocol: HookProtocol,
+    command: &str,
+    reason: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    explanation: Option<&str>,
+    severity: Option<crate::packs::Severity>,
+    confidence: Option<ConfidenceGate>,
+) -> io::Result<()> {
+    let message = format_warning_message(command, reason, explanation, pack, pattern);
+    let rule_id = build_rule_id(pack, pattern);
+
+    match protocol {
+        HookProtocol::ClaudeCompatible => {
+            let output = HookOutput {
+                hook_specific_output: HookSpecificOutput {
+                    hook_event_name: "PreToolUse",
+                    permission_decision: "allow",
+                    permission_decision_reason: Cow::Owned(message),
+                    allow_once_code: None,
+                    allow_once_full_hash: None,
+                    approval_id: None,
+                    rule_id,
+                    pack_id: pack.map(String::from),
+                    severity,
+                    confidence: confidence.map(|gate| gate_value(gate.confidence)),
+                    min_confidence_to_deny: confidence.map(|gate| gate_value(gate.threshold)),
+                    remediation: None,
+                    matches: Vec::new(),
+                },
+            };
+            serde_json::to_writer(&mut *out, &output)?;
+        }
+        HookProtocol::Copilot => {
+            let output = CopilotHookOutput {
+                continue_execution: true,
+                stop_reason: Cow::Borrowed(""),
+                permission_decision: "allow",
+                permission_decision_reason: Cow::Owned(message),
+                allow_once_code: None,
+                allow_once_full_hash: None,
+                approval_id: None,
+                rule_id,
+                pack_id: pack.map(String::from),
+                severity,
+                confidence: confidence.map(|gate| gate_value(gate.confidence)),
+                min_confidence_to_deny: confidence.map(|gate| gate_value(gate.threshold)),
+                remediation: None,
+                matches: Vec::new(),
+            };
+            serde_json::to_writer(&mut *out, &output)?;
+        }
+    }
+    writeln!(out)
+}
+
+/// Structured warning event written to stderr by the `event` warn channel.
+#[derive(Debug, Serialize)]
+pub struct WarningEvent<'a> {
+    /// Always `"dcg.warning"`.
+    pub event: &'static str,
+    /// Always `"allow"`: warn mode never blocks.
+    pub decision: &'static str,
+    #[serde(rename = "ruleId", skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(rename = "packId", skip_serializing_if = "Option::is_none")]
+    pub pack_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<crate::packs::Severity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    #[serde(
+        rename = "minConfidenceToDeny",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_confidence_to_deny: Option<f64>,
+    pub reason: &'a str,
+    pub command: &'a str,
+}
+
+/// Write a [`WarningEvent`] as one JSON line.
+///
+/// # Errors
+///
+/// Returns any error from serializing or writing to `out`.
+pub fn write_warning_event<W: Write>(
+    out: &mut W,
+    command: &str,
+    reason: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    severity: Option<crate::packs::Severity>,
+    confidence: Option<ConfidenceGate>,
+) -> io::Result<()> {
+    let event = WarningEvent {
+        event: "dcg.warning",
+        decision: "allow",
+        rule_id: build_rule_id(pack, pattern),
+        pack_id: pack,
+        severity,
+        confidence: confidence.map(|gate| gate_value(gate.confidence)),
+        min_confidence_to_deny: confidence.map(|gate| gate_value(gate.threshold)),
+        reason,
+        command,
+    };
+    serde_json::to_writer(&mut *out, &event)?;
+    writeln!(out)
+}
+
+/// Format the `permissionDecisionReason` for an ask decision.
+#[must_use]
+pub fn format_ask_message(
+    command: &str,
+    reason: &str,
+    explanation: Option<&str>,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+) -> String {
+    let rule_id = build_rule_id(pack, pattern);
+    let explanation_text = format_explanation_text(explanation, rule_id.as_deref(), pack);
+    let explanation_block = format_explanation_block(&explanation_text);
+    let rule_line = rule_id.as_deref().map_or_else(
+        || {
+            pack.map(|pack_name| format!("Pack: {pack_name}\n\n"))
+                .unwrap_or_default()
+        },
+        |rule| format!("Rule: {rule}\n\n"),
+    );
+
+    format!(
+        "APPROVAL NEEDED by dcg\n\n\
+         Reason: {reason}\n\n\
+         {explanation_block}\n\n\
+         {rule_line}\
+         Command: {command}\n\n\
+         Policy asks a human to approve this command before it runs."
+    )
+}
+
+/// Write an `ask` hook response so the agent prompts the user.
+///
+/// Only the Claude-compatible protocol has an ask decision; callers deny on
+/// other protocols.
+///
+/// # Errors
+///
+/// Returns any error from serializing or writing to `out`.
+#[allow(clippy::too_many_arguments)]
+pub fn write_ask_response<W: Write>(
+    out: &mut W,
+    command: &str,
+    reason: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    explanation: Option<&str>,
+    severity: Option<crate::packs::Severity>,
+    confidence: Option<ConfidenceGate>,
+) -> io::Result<()> {
+    let output = HookOutput {
+        hook_specific_output: HookSpecificOutput {
+            hook_event_name: "PreToolUse",
+            permission_decision: "ask",
+            permission_decision_reason: Cow::Owned(format_ask_message(
+                command,
+                reason,
+                explanation,
+                pack,
+                pattern,
+            )),
+            allow_once_code: None,
+            allow_once_full_hash: None,
+            approval_id: None,
+            rule_id: build_rule_id(pack, pattern),
+            pack_id: pack.map(String::from),
+            severity,
+            confidence: confidence.map(|gate| gate_value(gate.confidence)),
+            min_confidence_to_deny: confidence.map(|gate| gate_value(gate.threshold)),
+            remediation: None,
+            matches: Vec::new(),
+        },
+    };
+    serde_json::to_writer(&mut *out, &output)?;
+    writeln!(out)
+}
+
+/// Output an ask decision: a short stderr notice and the `ask` hook response.
+#[cold]
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+pub fn output_ask(
+    command: &str,
+    reason: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    explanation: Option<&str>,
+    severity: Option<crate::packs::Severity>,
+    confidence: Option<ConfidenceGate>,
+) {
+    {
+        let stderr = io::stderr();
+        let mut handle = stderr.lock();
+        let _ = writeln!(handle);
+        let _ = writeln!(
+            handle,
+            "{} {}",
+            "dcg APPROVAL NEEDED:".yellow().bold(),
+            reason
+        );
+        if let Some(rule) = build_rule_id(pack, pattern) {
+            let _ = writeln!(handle, "  {} {}", "Rule:".bright_black(), rule);
+        }
+        let _ = writeln!(handle, "  {} {}", "Command:".bright_black(), command);
+    }
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let _ = write_ask_response(
+        &mut handle,
+        command,
+        reason,
+        pack,
+        pattern,
+        explanation,
+        severity,
+        confidence,
+    );
+}
+
+/// One-line summary of a severity aggregation, e.g. `Aggregation: sum (high -> critical)`.
+#[must_use]
+pub fn format_aggregation(trace: &SeverityAggregationTrace) -> String {
+    let label =
+        |severity: Option<crate::packs::Severity>| severity.map_or("unknown", |s| s.label());
+    if trace.base_severity == trace.severity {
+        format!(
+            "Aggregation: {} ({})",
+            trace.policy.label(),
+            label(trace.severity)
+        )
+    } else {
+        format!(
+            "Aggregation: {} ({} -> {})",
+            trace.policy.label(),
+            label(trace.base_severity),
+            label(trace.severity)
+        )
+    }
+}
+
 /// Log a blocked command to a file (if logging is enabled).
 ///
 /// # Errors
@@ -799,10 +1466,13 @@ pub fn output_warning(
 pub fn log_blocked_command(
     log_file: &str,
     command: &str,
+    provenance: crate::provenance::Provenance,
     reason: &str,
     pack: Option<&str>,
+    aggregation: Option<&SeverityAggregationTrace>,
+    confidence: Option<ConfidenceGate>,
 ) -> io::Result<()> {
-    use std::fs::OpenOptions;
+    use std::fmt::Write as _;
 
     // Expand ~ in path
     let path = if log_file.starts_with("~/") {
@@ -814,21 +1484,29 @@ pub fn log_blocked_command(
         std::path::PathBuf::from(log_file)
     };
 
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    // One write per entry so concurrent hooks never interleave lines.
+    let mut entry = String::new();
 
     let timestamp = chrono_lite_timestamp();
     let pack_str = pack.unwrap_or("unknown");
 
-    writeln!(file, "[{timestamp}] [{pack_str}] {reason}")?;
-    writeln!(file, "  Command: {command}")?;
-    writeln!(file)?;
+    let _ = writeln!(entry, "[{timestamp}] [{pack_str}] {reason}");
+    let _ = writeln!(
+        entry,
+        "  Command: {}",
+        crate::logging::redact_secrets(command)
+    );
+    let _ = writeln!(entry, "  Provenance: {provenance}");
+    if let Some(trace) = aggregation {
+        let _ = writeln!(entry, "  {}", format_aggregation(trace));
+        let _ = writeln!(entry, "  Matches: {}", trace.contributing.join(", "));
+    }
+    if let Some(gate) = confidence {
+        let _ = writeln!(entry, "  Confidence: {}", format_confidence_gate(&gate));
+    }
+    let _ = writeln!(entry);
 
-    Ok(())
+    crate::storage::append(&path, &entry)
 }
 
 /// Log a budget skip to a file (if logging is enabled).
@@ -844,7 +1522,7 @@ pub fn log_budget_skip(
     elapsed: Duration,
     budget: Duration,
 ) -> io::Result<()> {
-    use std::fs::OpenOptions;
+    use std::fmt::Write as _;
 
     // Expand ~ in path
     let path = if log_file.starts_with("~/") {
@@ -856,28 +1534,113 @@ pub fn log_budget_skip(
         std::path::PathBuf::from(log_file)
     };
 
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    // One write per entry so concurrent hooks never interleave lines.
+    let mut entry = String::new();
 
     let timestamp = chrono_lite_timestamp();
-    writeln!(
-        file,
+    let _ = writeln!(
+        entry,
         "[{timestamp}] [budget] evaluation skipped due to budget at {stage}"
-    )?;
-    writeln!(
-        file,
+    );
+    let _ = writeln!(
+        entry,
         "  Budget: {}ms, Elapsed: {}ms",
         budget.as_millis(),
         elapsed.as_millis()
-    )?;
-    writeln!(file, "  Command: {command}")?;
-    writeln!(file)?;
+    );
+    let _ = writeln!(
+        entry,
+        "  Command: {}",
+        crate::logging::redact_secrets(command)
+    );
+    let _ = writeln!(entry);
+
+    crate::storage::append(&path, &entry)
+}
+
+/// Log a pattern whose match hit the backtracking limit (if logging is enabled).
+///
+/// # Errors
+///
+/// Returns any I/O errors encountered while creating directories or appending
+/// to the log file.
+pub fn log_slow_pattern(
+    log_file: &str,
+    command: &str,
+    slow: &crate::packs::regex_engine::SlowPattern,
+) -> io::Result<()> {
+    use std::fmt::Write as _;
 
-    Ok(())
+    // Expand ~ in path
+    let path = if log_file.starts_with("~/") {
+        dirs::home_dir().map_or_else(
+            || std::path::PathBuf::from(log_file),
+            |h| h.join(&log_file[2..]),
+        )
+    } else {
+        std::path::PathBuf::from(log_file)
+    };
+
+    let mut entry = String::new();
+
+    let timestamp = chrono_lite_timestamp();
+    let _ = writeln!(
+        entry,
+        "[{timestamp}] [slow-pattern] [{}] {slow}",
+        crate::error_codes::ErrorCode::PatternMatchTimeout
+    );
+    let _ = writeln!(
+        entry,
+        "  Command: {}",
+        crate::logging::redact_secrets(command)
+    );
+    let _ = writeln!(entry);
+
+    crate::storage::append(&path, &entry)
+}
+
+/// Log a hook command with NUL bytes, oversized tokens, deep quoting, or
+/// more bytes than `general.max_command_bytes` (see [`crate::input_guard`]).
+///
+/// # Errors
+///
+/// Returns any I/O error from appending to the log file.
+pub fn log_input_anomaly(
+    log_file: &str,
+    command: &str,
+    anomalies: &[crate::input_guard::InputAnomaly],
+) -> io::Result<()> {
+    use std::fmt::Write as _;
+
+    // Expand ~ in path
+    let path = if log_file.starts_with("~/") {
+        dirs::home_dir().map_or_else(
+            || std::path::PathBuf::from(log_file),
+            |h| h.join(&log_file[2..]),
+        )
+    } else {
+        std::path::PathBuf::from(log_file)
+    };
+
+    let mut entry = String::new();
+
+    let timestamp = chrono_lite_timestamp();
+    let _ = writeln!(
+        entry,
+        "[{timestamp}] [input-anomaly] [{}] command evaluated as a bounded copy",
+        crate::error_codes::ErrorCode::InputAnomaly
+    );
+    for anomaly in anomalies {
+        let _ = writeln!(entry, "  Anomaly: {anomaly}");
+    }
+    let _ = writeln!(
+        entry,
+        "  Command: {}",
+        crate::input_guard::display_command(&crate::logging::redact_secrets(command))
+    );
+    let _ = writeln!(entry);
+
+    crate::storage::append(&path, &entry)
 }
 
 /// Simple timestamp without chrono dependency.
@@ -985,6 +1748,104 @@ mod tests {
         assert_eq!(extract_command(&input), None);
     }
 
+    #[test]
+    fn test_warning_advisory_is_an_allow_with_reason_for_both_protocols() {
+        let mut out = Vec::new();
+        write_warning_advisory(
+            &mut out,
+            HookProtocol::ClaudeCompatible,
+            "git stash drop",
+            "drops a stash",
+            Some("core.git"),
+            Some("stash-drop"),
+            None,
+            Some(crate::packs::Severity::Medium),
+            None,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let output = &json["hookSpecificOutput"];
+        assert_eq!(output["permissionDecision"], "allow");
+        assert_eq!(output["ruleId"], "core.git:stash-drop");
+        assert_eq!(output["severity"], "medium");
+        let reason = output["permissionDecisionReason"].as_str().unwrap();
+        assert!(reason.contains("allowed by policy"));
+        assert!(reason.contains("Reason: drops a stash"));
+        assert!(output.get("allowOnceCode").is_none());
+
+        let mut out = Vec::new();
+        write_warning_advisory(
+            &mut out,
+            HookProtocol::Copilot,
+            "git stash drop",
+            "drops a stash",
+            Some("core.git"),
+            Some("stash-drop"),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["continue"], true);
+        assert_eq!(json["permissionDecision"], "allow");
+        assert!(json.get("stopReason").is_none());
+    }
+
+    #[test]
+    fn test_ask_response_prompts_with_reason() {
+        let mut out = Vec::new();
+        write_ask_response(
+            &mut out,
+            "git clean -fd",
+            "removes untracked files",
+            Some("core.git"),
+            Some("clean-force"),
+            None,
+            Some(crate::packs::Severity::Medium),
+            None,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let output = &json["hookSpecificOutput"];
+        assert_eq!(output["permissionDecision"], "ask");
+        assert_eq!(output["ruleId"], "core.git:clean-force");
+        assert_eq!(output["severity"], "medium");
+        let reason = output["permissionDecisionReason"].as_str().unwrap();
+        assert!(reason.contains("APPROVAL NEEDED"));
+        assert!(reason.contains("Reason: removes untracked files"));
+        assert!(output.get("allowOnceCode").is_none());
+    }
+
+    #[test]
+    fn test_warning_event_is_one_json_line() {
+        let mut out = Vec::new();
+        write_warning_event(
+            &mut out,
+            "git stash drop",
+            "drops a stash",
+            Some("core.git"),
+            Some("stash-drop"),
+            Some(crate::packs::Severity::Medium),
+            Some(ConfidenceGate {
+                confidence: 0.1,
+                threshold: 0.5,
+                downgraded: true,
+            }),
+        )
+        .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["event"], "dcg.warning");
+        assert_eq!(json["decision"], "allow");
+        assert_eq!(json["ruleId"], "core.git:stash-drop");
+        assert_eq!(json["packId"], "core.git");
+        assert_eq!(json["command"], "git stash drop");
+        assert_eq!(json["confidence"], 0.1);
+        assert_eq!(json["minConfidenceToDeny"], 0.5);
+    }
+
     #[test]
     fn test_format_denial_message_includes_explanation_and_rule() {
         let message = format_denial_message(
@@ -993,6 +1854,7 @@ mod tests {
             Some("This is irreversible."),
             Some("core.git"),
             Some("reset-hard"),
+            &[],
         );
 
         assert!(message.contains("Reason: destructive"));
@@ -1001,6 +1863,45 @@ mod tests {
         assert!(message.contains("Tip: dcg explain"));
     }
 
+    #[test]
+    fn test_denial_guidance_uses_template_when_configured() {
+        assert_eq!(
+            denial_guidance(
+                None,
+                "rm -rf /",
+                "root",
+                Some("core.filesystem:rm-rf-root-home"),
+                None
+            ),
+            DEFAULT_DENIAL_GUIDANCE
+        );
+
+        let messages = MessagesConfig {
+            denial_template: Some("Blocked `{command}` ({rule_id}); file {ticket_url}".to_string()),
+            ticket_url: Some("https://tickets.example/new".to_string()),
+            security_contact: None,
+        };
+        assert_eq!(
+            denial_guidance(
+                Some(&messages),
+                "rm -rf /",
+                "root",
+                Some("core.filesystem:rm-rf-root-home"),
+                Some("core.filesystem"),
+            ),
+            "Blocked `rm -rf /` (core.filesystem:rm-rf-root-home); file https://tickets.example/new"
+        );
+
+        let blank = MessagesConfig {
+            denial_template: Some("  ".to_string()),
+            ..MessagesConfig::default()
+        };
+        assert_eq!(
+            denial_guidance(Some(&blank), "rm -rf /", "root", None, None),
+            DEFAULT_DENIAL_GUIDANCE
+        );
+    }
+
     #[test]
     fn test_env_var_guard_restores_value() {
         let _lock = ENV_LOCK.lock().unwrap();
@@ -0,0 +1,254 @@
+//! Who is behind the service account.
+//!
+//! On shared build hosts several people run agents under one account, so the
+//! account's user allowlist would be shared by all of them. dcg resolves a
+//! per-person identity and, when it finds one, keeps that person's user-layer
+//! allowlist in `allowlist.d/<identity>.toml` next to the shared
+//! `allowlist.toml`. The identity is also recorded in audit output
+//! (allowlist `added_by`, allow-once log events, the undo journal).
+//!
+//! Sources, first match wins:
+//! 1. `DCG_USER`
+//! 2. `SUDO_USER` (someone ran `sudo -u svc ...`)
+//! 3. The owner of `SSH_AUTH_SOCK`, when it is not the current account (an
+//!    agent socket forwarded through `sudo -E`)
+//!
+//! Without any of these, nothing is namespaced.
+//!
+//! Allowlist `hosts` and `users` conditions are matched against the machine's
+//! host name and the process's effective account instead, both read from the
+//! system so an agent cannot satisfy them by setting `HOSTNAME` or `USER`.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable naming the identity explicitly.
+pub const ENV_USER: &str = "DCG_USER";
+
+/// Directory (next to the user allowlist) holding per-identity allowlists.
+pub const IDENTITY_ALLOWLIST_DIR: &str = "allowlist.d";
+
+/// Where an identity came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentitySource {
+    /// `DCG_USER`.
+    Env,
+    /// `SUDO_USER`.
+    Sudo,
+    /// Owner of the `SSH_AUTH_SOCK` socket.
+    SshAgent,
+}
+
+impl IdentitySource {
+    /// Label shown by diagnostics.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Env => "DCG_USER",
+            Self::Sudo => "SUDO_USER",
+            Self::SshAgent => "SSH_AUTH_SOCK",
+        }
+    }
+}
+
+/// A resolved per-person identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub name: String,
+    pub source: IdentitySource,
+}
+
+impl Identity {
+    /// Resolve the identity for this process, if any.
+    #[must_use]
+    pub fn resolve() -> Option<Self> {
+        Self::resolve_from(|key| std::env::var(key).ok(), ssh_agent_owner)
+    }
+
+    fn resolve_from(
+        get_env: impl Fn(&str) -> Option<String>,
+        ssh_agent_owner: impl FnOnce(&Path) -> Option<String>,
+    ) -> Option<Self> {
+        if let Some(name) = get_env(ENV_USER).as_deref().and_then(sanitize) {
+            return Some(Self {
+                name,
+                source: IdentitySource::Env,
+            });
+        }
+        if let Some(name) = get_env("SUDO_USER").as_deref().and_then(sanitize) {
+            return Some(Self {
+                name,
+                source: IdentitySource::Sudo,
+            });
+        }
+
+        let sock = get_env("SSH_AUTH_SOCK").filter(|s| !s.is_empty())?;
+        let owner = ssh_agent_owner(Path::new(&sock))?;
+        let account = get_env("USER").or_else(|| get_env("USERNAME"));
+        if account.as_deref() == Some(owner.as_str()) {
+            return None;
+        }
+        sanitize(&owner).map(|name| Self {
+            name,
+            source: IdentitySource::SshAgent,
+        })
+    }
+
+    /// Per-identity allowlist beside `user_allowlist`.
+    #[must_use]
+    pub fn allowlist_path(&self, user_allowlist: &Path) -> PathBuf {
+        user_allowlist
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(IDENTITY_ALLOWLIST_DIR)
+            .join(format!("{}.toml", self.name))
+    }
+}
+
+/// Name recorded in audit output: the resolved identity, else the account.
+#[must_use]
+pub fn audit_user() -> Option<String> {
+    Identity::resolve()
+        .map(|identity| identity.name)
+        .or_else(|| {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok()
+        })
+}
+
+/// This machine's host name, for allowlist `hosts`.
+#[must_use]
+pub fn host_name() -> Option<&'static str> {
+    static HOST: OnceLock<Option<String>> = OnceLock::new();
+    HOST.get_or_init(system_host_name).as_deref()
+}
+
+/// The account this process runs as (its effective uid), for allowlist `users`.
+#[must_use]
+pub fn effective_user() -> Option<&'static str> {
+    static USER: OnceLock<Option<String>> = OnceLock::new();
+    USER.get_or_init(system_effective_user).as_deref()
+}
+
+#[cfg(unix)]
+fn system_host_name() -> Option<String> {
+    nix::unistd::gethostname().ok()?.into_string().ok()
+}
+
+#[cfg(not(unix))]
+fn system_host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(unix)]
+fn system_effective_user() -> Option<String> {
+    nix::unistd::User::from_uid(nix::unistd::geteuid())
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+}
+
+#[cfg(not(unix))]
+fn system_effective_user() -> Option<String> {
+    std::env::var("USERNAME").ok()
+}
+
+/// Identities become file names, so only plain user-name characters pass.
+fn sanitize(name: &str) -> Option<String> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '@'));
+    valid.then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn ssh_agent_owner(sock: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = std::fs::metadata(sock).ok()?.uid();
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    user_name_for_uid(&passwd, uid)
+}
+
+#[cfg(not(unix))]
+fn ssh_agent_owner(_sock: &Path) -> Option<String> {
+    None
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn user_name_for_uid(passwd: &str, uid: u32) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        let entry_uid: u32 = fields.nth(1)?.parse().ok()?;
+        (entry_uid == uid).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn resolve(env: &[(&str, &str)], owner: Option<&str>) -> Option<Identity> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        Identity::resolve_from(|key| env.get(key).cloned(), |_| owner.map(str::to_string))
+    }
+
+    #[test]
+    fn sources_are_checked_in_order() {
+        let all = [
+            ("DCG_USER", "alice"),
+            ("SUDO_USER", "bob"),
+            ("SSH_AUTH_SOCK", "/tmp/ssh-x/agent.1"),
+            ("USER", "svc"),
+        ];
+        let identity = resolve(&all, Some("carol")).unwrap();
+        assert_eq!(identity.name, "alice");
+        assert_eq!(identity.source, IdentitySource::Env);
+
+        let identity = resolve(&all[1..], Some("carol")).unwrap();
+        assert_eq!(identity.source, IdentitySource::Sudo);
+
+        let identity = resolve(&all[2..], Some("carol")).unwrap();
+        assert_eq!(identity.name, "carol");
+        assert_eq!(identity.source, IdentitySource::SshAgent);
+    }
+
+    #[test]
+    fn own_agent_socket_and_unsafe_names_resolve_to_nothing() {
+        assert_eq!(
+            resolve(&[("SSH_AUTH_SOCK", "/tmp/a"), ("USER", "svc")], Some("svc")),
+            None
+        );
+        assert_eq!(resolve(&[("DCG_USER", "../etc/passwd")], None), None);
+        assert_eq!(resolve(&[("DCG_USER", " ")], None), None);
+        assert_eq!(resolve(&[], None), None);
+    }
+
+    #[test]
+    fn allowlist_path_sits_beside_user_allowlist() {
+        let identity = Identity {
+            name: "alice".to_string(),
+            source: IdentitySource::Env,
+        };
+        assert_eq!(
+            identity.allowlist_path(Path::new("/home/svc/.config/dcg/allowlist.toml")),
+            Path::new("/home/svc/.config/dcg/allowlist.d/alice.toml")
+        );
+    }
+
+    #[test]
+    fn passwd_lookup_matches_uid_field() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nalice:x:1001:1001::/home/alice:/bin/sh\n";
+        assert_eq!(user_name_for_uid(passwd, 1001).as_deref(), Some("alice"));
+        assert_eq!(user_name_for_uid(passwd, 7), None);
+    }
+}
@@ -0,0 +1,291 @@
+//! Pathological content in hook commands.
+//!
+//! `read_hook_input` caps how much stdin is read, but a command under that cap
+//! can still carry NUL bytes (which end the string for `execve` but not for
+//! the matchers), a single multi-kilobyte token, or quoting nested so deeply
+//! that no human wrote it. [`inspect`] turns such a command into a bounded
+//! copy that is safe to evaluate and lists every [`InputAnomaly`] it found,
+//! so the hook reports the input instead of allowing it silently.
+//!
+//! The evaluated copy errs toward matching: NULs become spaces (so
+//! `git reset\0--hard` still reads as `git reset --hard`), and only the tail
+//! of an oversized token or command is dropped.
+
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::{self, Write as _};
+
+/// Tokens longer than this are cut to this many bytes before evaluation.
+pub const MAX_TOKEN_BYTES: usize = 4096;
+
+/// Quoting nested deeper than this is reported.
+pub const MAX_QUOTE_DEPTH: usize = 8;
+
+/// Characters of the command shown in anomaly warnings.
+pub const DISPLAY_MAX_CHARS: usize = 200;
+
+/// Something pathological about a hook command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InputAnomaly {
+    /// Embedded NUL bytes, replaced by spaces for evaluation.
+    NulBytes { count: usize },
+    /// Whitespace-free tokens over [`MAX_TOKEN_BYTES`]; `longest` is the
+    /// largest one's size before cutting.
+    LongToken { count: usize, longest: usize },
+    /// Quotes or command substitutions nested `depth` levels deep.
+    DeepQuoting { depth: usize },
+    /// More bytes than `general.max_command_bytes`; only `limit` were evaluated.
+    Oversized { bytes: usize, limit: usize },
+}
+
+impl fmt::Display for InputAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NulBytes { count } => write!(f, "{count} NUL byte(s)"),
+            Self::LongToken { count, longest } => write!(
+                f,
+                "{count} token(s) over {MAX_TOKEN_BYTES} bytes (longest {longest})"
+            ),
+            Self::DeepQuoting { depth } => {
+                write!(
+                    f,
+                    "quoting nested {depth} levels deep (limit {MAX_QUOTE_DEPTH})"
+                )
+            }
+            Self::Oversized { bytes, limit } => write!(
+                f,
+                "{bytes} bytes (limit {limit}); evaluated the first {limit}"
+            ),
+        }
+    }
+}
+
+/// A hook command made safe to evaluate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardedCommand<'a> {
+    /// What to evaluate: the command itself when nothing was wrong with it.
+    pub command: Cow<'a, str>,
+    /// What was wrong, in the order checked.
+    pub anomalies: Vec<InputAnomaly>,
+}
+
+impl GuardedCommand<'_> {
+    /// Whether the command was evaluated unchanged and nothing was reported.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// Check `command` for anomalies and bound it to `max_command_bytes`.
+#[must_use]
+pub fn inspect(command: &str, max_command_bytes: usize) -> GuardedCommand<'_> {
+    let mut anomalies = Vec::new();
+    let mut guarded = Cow::Borrowed(command);
+
+    let nuls = command.bytes().filter(|&b| b == 0).count();
+    if nuls > 0 {
+        anomalies.push(InputAnomaly::NulBytes { count: nuls });
+        guarded = Cow::Owned(command.replace('\0', " "));
+    }
+
+    if let Some((clipped, count, longest)) = clip_long_tokens(&guarded) {
+        anomalies.push(InputAnomaly::LongToken { count, longest });
+        guarded = Cow::Owned(clipped);
+    }
+
+    let depth = quote_depth(&guarded);
+    if depth > MAX_QUOTE_DEPTH {
+        anomalies.push(InputAnomaly::DeepQuoting { depth });
+    }
+
+    if command.len() > max_command_bytes {
+        anomalies.push(InputAnomaly::Oversized {
+            bytes: command.len(),
+            limit: max_command_bytes,
+        });
+    }
+    if guarded.len() > max_command_bytes {
+        let end = floor_char_boundary(&guarded, max_command_bytes);
+        guarded = Cow::Owned(guarded[..end].to_string());
+    }
+
+    GuardedCommand {
+        command: guarded,
+        anomalies,
+    }
+}
+
+/// `command` for a one-line warning: control characters escaped and cut to
+/// [`DISPLAY_MAX_CHARS`] characters.
+#[must_use]
+pub fn display_command(command: &str) -> String {
+    let mut out = String::new();
+    for (shown, (idx, c)) in command.char_indices().enumerate() {
+        if shown == DISPLAY_MAX_CHARS {
+            let _ = write!(out, "… ({} more bytes)", command.len() - idx);
+            break;
+        }
+        if c.is_control() {
+            out.extend(c.escape_default());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Cut whitespace-free runs longer than [`MAX_TOKEN_BYTES`]. Returns `None`
+/// if there are none, else the new command, how many were cut and the
+/// longest one's length.
+fn clip_long_tokens(command: &str) -> Option<(String, usize, usize)> {
+    if !command
+        .split_ascii_whitespace()
+        .any(|token| token.len() > MAX_TOKEN_BYTES)
+    {
+        return None;
+    }
+
+    let mut out = String::with_capacity(command.len().min(MAX_TOKEN_BYTES * 4));
+    let mut count = 0;
+    let mut longest = 0;
+    let mut rest = command;
+    while !rest.is_empty() {
+        let token_end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let token = &rest[..token_end];
+        if token.len() > MAX_TOKEN_BYTES {
+            count += 1;
+            longest = longest.max(token.len());
+            out.push_str(&token[..floor_char_boundary(token, MAX_TOKEN_BYTES)]);
+        } else {
+            out.push_str(token);
+        }
+        rest = &rest[token_end..];
+        let space_end = rest
+            .find(|c: char| !c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        out.push_str(&rest[..space_end]);
+        rest = &rest[space_end..];
+    }
+    Some((out, count, longest))
+}
+
+/// Deepest nesting of quotes and `$(...)` substitutions.
+///
+/// Each quoting level doubles the backslashes in front of an inner quote
+/// (`"`, `\"`, `\\\"`, ...), so a quote behind `n` backslashes sits
+/// `log2(n + 1) + 1` levels deep.
+fn quote_depth(command: &str) -> usize {
+    let mut deepest = 0;
+    let mut substitutions = 0usize;
+    let mut backslashes = 0usize;
+    let mut prev = 0u8;
+    for &b in command.as_bytes() {
+        match b {
+            b'\\' => {
+                backslashes += 1;
+                prev = b;
+                continue;
+            }
+            b'"' | b'\'' | b'`' => {
+                let level = (usize::BITS - (backslashes + 1).leading_zeros()) as usize;
+                deepest = deepest.max(level + substitutions);
+            }
+            b'(' if prev == b'$' => {
+                substitutions += 1;
+                deepest = deepest.max(substitutions);
+            }
+            b')' => substitutions = substitutions.saturating_sub(1),
+            _ => {}
+        }
+        backslashes = 0;
+        prev = b;
+    }
+    deepest
+}
+
+/// Largest char boundary in `s` at or below `index`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    (0..=index)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_command_is_borrowed_unchanged() {
+        let guarded = inspect("git status && ls -la \"my dir\"", 1024);
+        assert!(guarded.is_clean());
+        assert!(matches!(guarded.command, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn nul_bytes_become_spaces() {
+        let guarded = inspect("git reset\0--hard\0", 1024);
+        assert_eq!(guarded.command, "git reset --hard ");
+        assert_eq!(guarded.anomalies, [InputAnomaly::NulBytes { count: 2 }]);
+    }
+
+    #[test]
+    fn long_tokens_are_cut_and_whitespace_kept() {
+        let blob = "é".repeat(MAX_TOKEN_BYTES);
+        let command = format!("echo {blob}\n  rm -rf /");
+        let guarded = inspect(&command, usize::MAX);
+        assert_eq!(
+            guarded.anomalies,
+            [InputAnomaly::LongToken {
+                count: 1,
+                longest: blob.len()
+            }]
+        );
+        let expected = format!("echo {}\n  rm -rf /", "é".repeat(MAX_TOKEN_BYTES / 2));
+        assert_eq!(guarded.command, expected);
+    }
+
+    #[test]
+    fn nesting_depth_counts_escapes_and_substitutions() {
+        assert_eq!(quote_depth("echo hi"), 0);
+        assert_eq!(quote_depth(r#"bash -c "ls""#), 1);
+        assert_eq!(quote_depth(r#"bash -c "bash -c \"ls\"""#), 2);
+        assert_eq!(quote_depth(r#"a "b \"c \\\"d\\\" \" ""#), 3);
+        assert_eq!(quote_depth("echo $(echo $(echo $(date)))"), 3);
+
+        let escapes = "\\".repeat(255);
+        let command = format!("bash -c {escapes}\"rm\"");
+        let guarded = inspect(&command, 1024);
+        assert_eq!(guarded.anomalies, [InputAnomaly::DeepQuoting { depth: 9 }]);
+    }
+
+    #[test]
+    fn oversized_command_keeps_a_prefix_on_a_char_boundary() {
+        let command = format!("rm -rf / # {}", "ü ".repeat(100));
+        let guarded = inspect(&command, 12);
+        assert_eq!(guarded.command, "rm -rf / # ");
+        assert_eq!(
+            guarded.anomalies,
+            [InputAnomaly::Oversized {
+                bytes: command.len(),
+                limit: 12
+            }]
+        );
+    }
+
+    #[test]
+    fn display_escapes_control_characters_and_truncates() {
+        assert_eq!(display_command("a\0b\tc"), "a\\u{0}b\\tc");
+        let long = "x".repeat(DISPLAY_MAX_CHARS + 50);
+        let shown = display_command(&long);
+        assert!(shown.starts_with(&"x".repeat(DISPLAY_MAX_CHARS)));
+        assert!(shown.ends_with("… (50 more bytes)"), "{shown}");
+    }
+}
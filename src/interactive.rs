@@ -166,6 +166,14 @@ pub struct InteractiveConfig {
 
     /// Require this env var to be set to enable interactive mode.
     pub require_env: Option<String>,
+
+    /// Queue Medium-severity warnings for `dcg review` (interactive learning).
+    #[serde(default)]
+    pub learning: bool,
+
+    /// Queue denials from unattended runs for `dcg approvals`.
+    #[serde(default)]
+    pub approval_queue: bool,
 }
 
 impl Default for InteractiveConfig {
@@ -179,6 +187,8 @@ impl Default for InteractiveConfig {
             allow_non_tty_fallback: true,
             disable_in_ci: true,
             require_env: None,
+            learning: false,
+            approval_queue: false,
         }
     }
 }
@@ -275,7 +285,8 @@ pub fn check_interactive_available(config: &InteractiveConfig) -> Result<(), Not
     Ok(())
 }
 
-fn is_ci_environment() -> bool {
+/// Whether a CI system is detected from its environment variables.
+pub(crate) fn is_ci_environment() -> bool {
     ["CI", "GITHUB_ACTIONS", "GITLAB_CI", "JENKINS", "TRAVIS"]
         .iter()
         .any(|var| std::env::var(var).is_ok())
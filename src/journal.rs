@@ -0,0 +1,333 @@
+//! Undo journal for policy-file edits made by dcg itself.
+//!
+//! Commands that rewrite allowlists or config files (`dcg allowlist add`,
+//! `dcg init -o`, `dcg pack new`, ...) record a before/after snapshot here.
+//! `dcg undo` restores the most recent snapshot, so experimenting with policy
+//! never loses the previous state.
+//!
+//! Each change is one JSON file under `~/.local/state/dcg/journal/`, named so
+//! that lexical order is chronological. Only the newest [`MAX_ENTRIES`]
+//! changes are kept.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::resolve_config_path_value;
+use crate::storage;
+
+/// Environment override for the journal directory.
+pub const ENV_JOURNAL_DIR: &str = "DCG_JOURNAL_DIR";
+
+/// Number of changes kept before the oldest are dropped.
+pub const MAX_ENTRIES: usize = 50;
+
+const SCHEMA_VERSION: u32 = 1;
+
+/// One recorded file change.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub schema_version: u32,
+    pub id: String,
+    pub created_at: String,
+    /// The dcg command that made the change (e.g. "allowlist add").
+    pub action: String,
+    pub path: PathBuf,
+    /// File contents before the change; `None` if the command created the file.
+    pub before: Option<String>,
+    pub after: String,
+    /// Person who made the change (see [`crate::identity::audit_user`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// What `undo` did to the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoEffect {
+    /// The previous contents were written back.
+    Restored,
+    /// The command had created the file, so it was removed.
+    Removed,
+}
+
+/// Why an undo could not be applied.
+#[derive(Debug)]
+pub enum UndoError {
+    /// The journal has no entries.
+    Empty,
+    /// The file changed after dcg wrote it; undoing would lose those edits.
+    Modified(Box<JournalEntry>),
+    Io(io::Error),
+}
+
+impl std::fmt::Display for UndoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "nothing to undo"),
+            Self::Modified(entry) => write!(
+                f,
+                "{} was modified after `dcg {}`; use --force to restore it anyway",
+                entry.path.display(),
+                entry.action
+            ),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for UndoError {}
+
+impl From<io::Error> for UndoError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Directory-backed journal.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+impl Journal {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Journal at the default location (env override or `~/.local/state/dcg/journal`).
+    #[must_use]
+    pub fn open_default() -> Self {
+        Self::new(Self::default_dir())
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Resolve the default directory (env override or the dcg state directory).
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_JOURNAL_DIR) {
+            if let Some(path) = resolve_config_path_value(&value, None) {
+                return path;
+            }
+        }
+
+        crate::paths::state_dir().join("journal")
+    }
+
+    /// Record that `action` changed `path` from `before` to its current contents.
+    ///
+    /// Returns `None` (and records nothing) if the file is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while reading `path` or writing the journal.
+    pub fn record_change(
+        &self,
+        action: &str,
+        path: &Path,
+        before: Option<&str>,
+    ) -> io::Result<Option<JournalEntry>> {
+        let after = fs::read_to_string(path)?;
+        if before == Some(after.as_str()) {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let entry = JournalEntry {
+            schema_version: SCHEMA_VERSION,
+            id: format!(
+                "{}-{}",
+                now.format("%Y%m%dT%H%M%S%.9fZ"),
+                std::process::id()
+            ),
+            created_at: now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            action: action.to_string(),
+            path: std::path::absolute(path)?,
+            before: before.map(str::to_string),
+            after,
+            user: crate::identity::audit_user(),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(&entry).map_err(io::Error::other)?;
+        storage::write_atomic(
+            &self.dir.join(format!("{}.json", entry.id)),
+            json.as_bytes(),
+        )?;
+        self.trim(MAX_ENTRIES)?;
+        Ok(Some(entry))
+    }
+
+    /// All entries, oldest first. Unreadable files are skipped.
+    #[must_use]
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entry_files()
+            .iter()
+            .filter_map(|file| {
+                let content = fs::read_to_string(file).ok()?;
+                serde_json::from_str(&content).ok()
+            })
+            .collect()
+    }
+
+    /// The most recent entry, if any.
+    #[must_use]
+    pub fn latest(&self) -> Option<JournalEntry> {
+        self.entries().pop()
+    }
+
+    /// Revert the most recent change and drop it from the journal.
+    ///
+    /// Refuses if the file no longer matches what dcg wrote, unless `force`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UndoError::Empty`] with nothing to undo, [`UndoError::Modified`]
+    /// if the file was edited since, or any I/O error while restoring.
+    pub fn undo_latest(&self, force: bool) -> Result<(JournalEntry, UndoEffect), UndoError> {
+        let entry = self.latest().ok_or(UndoError::Empty)?;
+
+        let current = fs::read_to_string(&entry.path).ok();
+        if !force && current.as_deref() != Some(entry.after.as_str()) {
+            return Err(UndoError::Modified(Box::new(entry)));
+        }
+
+        let effect = match &entry.before {
+            Some(before) => {
+                storage::write_atomic(&entry.path, before.as_bytes())?;
+                UndoEffect::Restored
+            }
+            None => {
+                if current.is_some() {
+                    fs::remove_file(&entry.path)?;
+                }
+                UndoEffect::Removed
+            }
+        };
+
+        fs::remove_file(self.dir.join(format!("{}.json", entry.id)))?;
+        Ok((entry, effect))
+    }
+
+    fn entry_files(&self) -> Vec<PathBuf> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = read_dir
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn trim(&self, keep: usize) -> io::Result<()> {
+        let files = self.entry_files();
+        let excess = files.len().saturating_sub(keep);
+        for file in &files[..excess] {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, Journal, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path().join("journal"));
+        let file = dir.path().join("allowlist.toml");
+        (dir, journal, file)
+    }
+
+    #[test]
+    fn undo_restores_previous_contents_newest_first() {
+        let (_dir, journal, file) = setup();
+
+        fs::write(&file, "v1").unwrap();
+        fs::write(&file, "v2").unwrap();
+        journal
+            .record_change("allowlist add", &file, Some("v1"))
+            .unwrap();
+        fs::write(&file, "v3").unwrap();
+        journal
+            .record_change("allowlist remove", &file, Some("v2"))
+            .unwrap();
+        assert_eq!(journal.entries().len(), 2);
+
+        let (entry, effect) = journal.undo_latest(false).unwrap();
+        assert_eq!(entry.action, "allowlist remove");
+        assert_eq!(effect, UndoEffect::Restored);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v2");
+
+        journal.undo_latest(false).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "v1");
+        assert!(matches!(journal.undo_latest(false), Err(UndoError::Empty)));
+    }
+
+    #[test]
+    fn undo_removes_files_the_command_created() {
+        let (_dir, journal, file) = setup();
+
+        fs::write(&file, "created").unwrap();
+        journal.record_change("init", &file, None).unwrap();
+
+        let (_, effect) = journal.undo_latest(false).unwrap();
+        assert_eq!(effect, UndoEffect::Removed);
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn undo_refuses_when_file_changed_since() {
+        let (_dir, journal, file) = setup();
+
+        fs::write(&file, "dcg wrote this").unwrap();
+        journal
+            .record_change("allowlist add", &file, Some("original"))
+            .unwrap();
+        fs::write(&file, "hand edited").unwrap();
+
+        assert!(matches!(
+            journal.undo_latest(false),
+            Err(UndoError::Modified(_))
+        ));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hand edited");
+
+        journal.undo_latest(true).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+    }
+
+    #[test]
+    fn unchanged_writes_are_not_recorded_and_journal_is_bounded() {
+        let (_dir, journal, file) = setup();
+
+        fs::write(&file, "same").unwrap();
+        assert!(
+            journal
+                .record_change("allowlist add", &file, Some("same"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(journal.entries().is_empty());
+
+        for i in 0..MAX_ENTRIES + 5 {
+            fs::write(&file, format!("v{}", i + 1)).unwrap();
+            journal
+                .record_change("allowlist add", &file, Some(&format!("v{i}")))
+                .unwrap();
+        }
+        let entries = journal.entries();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].before.as_deref(), Some("v5"));
+    }
+}
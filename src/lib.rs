@@ -37,7 +37,8 @@
 //!
 //! # Usage
 //!
-//! The main entry point for command evaluation is the [`evaluator`] module:
+//! To embed dcg in another tool, use the semver-stable [`api::Guard`] facade.
+//! The lower-level entry point for command evaluation is the [`evaluator`] module:
 //!
 //! ```ignore
 //! use destructive_command_guard::config::Config;
@@ -60,37 +61,93 @@
 //! }
 //! ```
 
+#[cfg(feature = "cli")]
+pub mod admin_api;
 pub mod agent;
 pub mod allowlist;
+pub mod allowlist_bootstrap;
+pub mod allowlist_diff;
+pub mod allowlist_exchange;
+pub mod allowlist_usage;
+pub mod allowlist_window;
+pub mod api;
+#[cfg(feature = "cli")]
+pub mod approval_bridge;
+pub mod approvals;
+pub mod arg_context;
 pub mod ast_matcher;
+pub mod canary;
+#[cfg(feature = "cli")]
 pub mod cli;
+#[cfg(feature = "cli")]
+pub mod completion;
 pub mod confidence;
 pub mod config;
 pub mod context;
+pub mod coverage;
 pub mod error_codes;
 pub mod evaluator;
 pub mod exit_codes;
+pub mod fuzz_corpus;
 pub mod git;
 pub mod heredoc;
 pub mod highlight;
 pub mod history;
+#[cfg(feature = "cli")]
 pub mod hook;
+pub mod identity;
+pub mod impact;
+pub mod input_guard;
 pub mod interactive;
+pub mod journal;
 pub mod logging;
+pub mod lsp;
+#[cfg(feature = "cli")]
 pub mod mcp;
+pub mod migrate;
+#[cfg(feature = "napi")]
+pub mod node;
 pub mod normalize;
+pub mod onboard;
+#[cfg(feature = "cli")]
 pub mod output;
 pub mod packs;
+pub mod paths;
 pub mod pending_exceptions;
 pub mod perf;
+pub mod pipeline_trace;
+pub mod policy_snapshot;
+pub mod preview;
+pub mod privacy;
+pub mod provenance;
+#[cfg(feature = "pyo3")]
+pub mod python;
+pub mod remote_include;
+pub mod repeat_denials;
+pub mod review;
+pub mod rule_docs;
 pub mod sarif;
 pub mod scan;
+#[cfg(feature = "cli")]
+pub mod schema;
+pub mod script;
+pub mod self_protection;
+pub mod session_memory;
 pub mod simulate;
 pub mod stats;
+pub mod storage;
+pub mod storage_impact;
 pub mod suggest;
+pub mod suggest_history;
 pub mod suggestions;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
 pub mod trace;
+pub mod trash;
+#[cfg(feature = "cli")]
 pub mod update;
+pub mod warn_once;
+pub mod warnings;
 
 // Re-export commonly used types
 pub use allowlist::{
@@ -100,22 +157,25 @@ pub use allowlist::{
 pub use config::Config;
 pub use error_codes::{DcgError, ErrorCategory, ErrorCode, ErrorResponse};
 pub use evaluator::{
-    ConfidenceResult, DetailedEvaluationResult, EvaluationDecision, EvaluationResult,
-    LegacyDestructivePattern, LegacySafePattern, MatchSource, MatchSpan, PatternMatch,
-    apply_confidence_scoring, evaluate_command, evaluate_command_with_deadline,
-    evaluate_command_with_pack_order, evaluate_command_with_pack_order_at_path,
-    evaluate_command_with_pack_order_deadline, evaluate_command_with_pack_order_deadline_at_path,
-    evaluate_detailed, evaluate_detailed_with_allowlists,
+    ConfidenceGate, ConfidenceResult, DetailedEvaluationResult, EvalContext, EvaluationDecision,
+    EvaluationResult, LegacyDestructivePattern, LegacySafePattern, MatchSource, MatchSpan,
+    NearMiss, NearMissKind, PatternMatch, ResolvedDecision, SeverityAggregationTrace,
+    apply_confidence_scoring, apply_production_target_severity, apply_severity_aggregation,
+    evaluate_command, evaluate_command_with_deadline, evaluate_command_with_pack_order,
+    evaluate_command_with_pack_order_at_path, evaluate_command_with_pack_order_deadline,
+    evaluate_command_with_pack_order_deadline_at_path, evaluate_detailed,
+    evaluate_detailed_with_allowlists, evaluate_in_context, record_near_misses,
 };
 pub use exit_codes::{
     EXIT_CONFIG_ERROR, EXIT_DENIED, EXIT_IO_ERROR, EXIT_PARSE_ERROR, EXIT_SUCCESS, EXIT_WARNING,
     ToExitCode, exit_with, to_exit_code,
 };
+#[cfg(feature = "cli")]
 pub use hook::{HookInput, HookOutput, HookResult, HookSpecificOutput};
 pub use packs::external::{ExternalPack, parse_pack_file, parse_pack_string};
 pub use packs::{Pack, PackId, PackRegistry, PatternSuggestion, Platform};
 pub use pending_exceptions::{
-    AllowOnceEntry, AllowOnceScopeKind, AllowOnceStore, PendingExceptionRecord,
+    AllowOnceEntry, AllowOnceGrant, AllowOnceScopeKind, AllowOnceStore, PendingExceptionRecord,
     PendingExceptionStore,
 };
 
@@ -169,11 +229,12 @@ pub use suggestions::{Suggestion, SuggestionKind, get_suggestion_by_kind, get_su
 // Re-export scan types for `dcg scan`
 pub use scan::{
     ExtractedCommand, ScanDecision, ScanEvalContext, ScanFailOn, ScanFinding, ScanFormat,
-    ScanOptions, ScanReport, ScanSeverity, ScanSummary, extract_docker_compose_from_str,
+    ScanOptions, ScanReport, ScanSeverity, ScanSummary, extract_ansible_from_str,
+    extract_cloud_init_from_str, extract_deno_json_from_str, extract_docker_compose_from_str,
     extract_dockerfile_from_str, extract_github_actions_workflow_from_str,
-    extract_gitlab_ci_from_str, extract_makefile_from_str, extract_package_json_from_str,
-    extract_shell_script_from_str, extract_terraform_from_str, scan_paths, should_fail,
-    sort_findings,
+    extract_gitlab_ci_from_str, extract_justfile_from_str, extract_kubernetes_job_from_str,
+    extract_makefile_from_str, extract_package_json_from_str, extract_shell_script_from_str,
+    extract_terraform_from_str, scan_paths, should_fail, sort_findings,
 };
 
 // Re-export simulate types for `dcg simulate`
@@ -232,6 +293,7 @@ pub use agent::{
 };
 
 // Re-export output types for TUI/CLI visual formatting
+#[cfg(feature = "cli")]
 pub use output::{
     BorderStyle, DenialBox, Severity as OutputSeverity, SeverityColors, Theme, ThemePalette,
     auto_theme, auto_theme_with_config, init as init_output, should_use_rich_output,
@@ -239,6 +301,7 @@ pub use output::{
 };
 
 // Re-export update types for self-update version check
+#[cfg(feature = "cli")]
 pub use update::{
     CACHE_DURATION, VersionCheckError, VersionCheckResult, check_for_update, clear_cache,
     current_version, format_check_result, format_check_result_json,
@@ -0,0 +1,1003 @@
+//! Language server for dcg policy files (`dcg lsp`).
+//!
+//! Speaks the Language Server Protocol over stdio so editors get instant
+//! feedback while editing `allowlist.toml`, `config.toml`, and `.dcg.toml`:
+//!
+//! - **Diagnostics**: TOML syntax errors, invalid allowlist entries (selectors,
+//!   expiry, globs), expired entries, unknown pack and rule IDs, and override
+//!   patterns that don't compile.
+//! - **Completions**: rule IDs from the loaded packs inside `rule = "..."`, and
+//!   pack IDs inside `packs.enabled` / `packs.disabled`.
+//!
+//! Documents are synchronized in full (`TextDocumentSyncKind::Full`). The
+//! server only implements the handful of LSP methods it needs, so it frames
+//! JSON-RPC messages itself instead of pulling in an LSP framework.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::allowlist::{self, AllowSelector};
+use crate::config::Config;
+use crate::packs::{REGISTRY, load_external_packs};
+
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for malformed messages.
+const PARSE_ERROR: i64 = -32700;
+
+/// Which policy format a document holds, decided by its file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyFileKind {
+    /// `allowlist.toml`
+    Allowlist,
+    /// `config.toml` or `.dcg.toml`
+    Config,
+    /// Any other TOML file (syntax checks only)
+    Other,
+}
+
+impl PolicyFileKind {
+    /// Classify a document by the last segment of its URI or path.
+    #[must_use]
+    pub fn from_uri(uri: &str) -> Self {
+        match uri.rsplit(['/', '\\']).next().unwrap_or(uri) {
+            "allowlist.toml" => Self::Allowlist,
+            "config.toml" | ".dcg.toml" => Self::Config,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Pack and rule IDs known to the server.
+#[derive(Debug, Clone, Default)]
+pub struct RuleCatalog {
+    /// Pack ID -> (pattern name, reason) for each named destructive pattern.
+    packs: BTreeMap<String, Vec<(String, String)>>,
+    /// Pack categories accepted in `packs.enabled` (e.g. `containers`).
+    categories: Vec<String>,
+}
+
+impl RuleCatalog {
+    /// Catalog of the built-in packs.
+    #[must_use]
+    pub fn builtin() -> Self {
+        let mut catalog = Self {
+            categories: REGISTRY.all_categories().into_iter().cloned().collect(),
+            ..Self::default()
+        };
+        for pack_id in REGISTRY.all_pack_ids() {
+            if let Some(pack) = REGISTRY.get(pack_id) {
+                catalog.add_pack(pack_id, pack);
+            }
+        }
+        catalog
+    }
+
+    /// Catalog of the built-in packs plus the external packs from `config`.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        let mut catalog = Self::builtin();
+        let store = load_external_packs(&config.packs.expand_custom_paths());
+        for (pack_id, pack) in store.iter_packs() {
+            catalog.add_pack(pack_id, pack);
+        }
+        catalog
+    }
+
+    fn add_pack(&mut self, pack_id: &str, pack: &crate::packs::Pack) {
+        let rules = pack
+            .destructive_patterns
+            .iter()
+            .filter_map(|p| p.name.map(|name| (name.to_string(), p.reason.to_string())))
+            .collect();
+        self.packs.insert(pack_id.to_string(), rules);
+    }
+
+    fn has_pack(&self, pack_id: &str) -> bool {
+        self.packs.contains_key(pack_id)
+    }
+
+    fn has_pack_or_category(&self, id: &str) -> bool {
+        self.has_pack(id) || self.categories.iter().any(|c| c == id)
+    }
+
+    fn has_rule(&self, pack_id: &str, pattern_name: &str) -> bool {
+        self.packs
+            .get(pack_id)
+            .is_some_and(|rules| rules.iter().any(|(name, _)| name == pattern_name))
+    }
+}
+
+/// LSP diagnostic severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+impl Serialize for DiagnosticSeverity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(match self {
+            Self::Error => 1,
+            Self::Warning => 2,
+        })
+    }
+}
+
+/// Zero-based line and UTF-16 column, as LSP expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// Half-open range in a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LspRange {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A problem found in a policy file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub range: LspRange,
+    pub severity: DiagnosticSeverity,
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// A completion candidate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    /// `CompletionItemKind.Value`
+    pub kind: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub text_edit: TextEdit,
+}
+
+/// Replacement applied when a completion is accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub range: LspRange,
+    pub new_text: String,
+}
+
+/// Converts between byte offsets and LSP positions.
+struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self
+            .text
+            .get(start..offset)
+            .map_or(0, |prefix| prefix.encode_utf16().count());
+        Position {
+            line: u32::try_from(line).unwrap_or(u32::MAX),
+            character: u32::try_from(character).unwrap_or(u32::MAX),
+        }
+    }
+
+    fn range(&self, span: &Range<usize>) -> LspRange {
+        LspRange {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+
+    /// Text of the line at `position` up to the cursor.
+    fn line_prefix(&self, position: Position) -> &'a str {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return "";
+        };
+        let line = self.text[start..].split('\n').next().unwrap_or("");
+        let mut units = 0;
+        for (idx, ch) in line.char_indices() {
+            if units >= position.character as usize {
+                return &line[..idx];
+            }
+            units += ch.len_utf16();
+        }
+        line
+    }
+}
+
+struct Diagnostics<'a> {
+    index: LineIndex<'a>,
+    out: Vec<Diagnostic>,
+}
+
+impl Diagnostics<'_> {
+    fn push(&mut self, span: Option<Range<usize>>, severity: DiagnosticSeverity, message: String) {
+        let range = self.index.range(&span.unwrap_or(0..0));
+        self.out.push(Diagnostic {
+            range,
+            severity,
+            source: "dcg",
+            message,
+        });
+    }
+}
+
+/// Check a policy document and return its diagnostics.
+#[must_use]
+pub fn diagnose(catalog: &RuleCatalog, kind: PolicyFileKind, text: &str) -> Vec<Diagnostic> {
+    let mut diags = Diagnostics {
+        index: LineIndex::new(text),
+        out: Vec::new(),
+    };
+    let doc = match toml_edit::ImDocument::parse(text) {
+        Ok(doc) => doc,
+        Err(e) => {
+            diags.push(e.span(), DiagnosticSeverity::Error, e.message().to_string());
+            return diags.out;
+        }
+    };
+    match kind {
+        PolicyFileKind::Allowlist => diagnose_allowlist(catalog, &doc, text, &mut diags),
+        PolicyFileKind::Config => diagnose_config(catalog, &doc, text, &mut diags),
+        PolicyFileKind::Other => {}
+    }
+    diags.out
+}
+
+fn diagnose_allowlist(
+    catalog: &RuleCatalog,
+    doc: &toml_edit::ImDocument<&str>,
+    text: &str,
+    diags: &mut Diagnostics<'_>,
+) {
+    let Some(allow) = doc.get("allow") else {
+        return;
+    };
+    let tables: Vec<(&dyn toml_edit::TableLike, Option<Range<usize>>)> = match allow {
+        toml_edit::Item::ArrayOfTables(array) => array
+            .iter()
+            .map(|t| (t as &dyn toml_edit::TableLike, t.span()))
+            .collect(),
+        toml_edit::Item::Value(toml_edit::Value::Array(array)) => array
+            .iter()
+            .filter_map(|v| {
+                v.as_inline_table()
+                    .map(|t| (t as &dyn toml_edit::TableLike, t.span()))
+            })
+            .collect(),
+        other => {
+            diags.push(
+                other.span(),
+                DiagnosticSeverity::Error,
+                "`allow` must be an array of tables (use [[allow]])".to_string(),
+            );
+            return;
+        }
+    };
+
+    // Entry semantics come from the same parser the hook uses; spans come
+    // from the toml_edit document. Both see the entries in the same order.
+    let values: Vec<toml::value::Table> = toml::from_str::<toml::Value>(text)
+        .ok()
+        .and_then(|v| v.get("allow").and_then(toml::Value::as_array).cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| v.as_table().cloned())
+        .collect();
+
+    for ((table, header), value) in tables.iter().zip(&values) {
+        let field_span = |key: &str| table.get(key).and_then(toml_edit::Item::span);
+
+        if let Some(paths) = table.get("paths").and_then(toml_edit::Item::as_array) {
+            for path in paths {
+                if let Some(pattern) = path.as_str() {
+                    if let Err(e) = allowlist::validate_glob_pattern(pattern) {
+                        diags.push(
+                            path.span(),
+                            DiagnosticSeverity::Error,
+                            format!("invalid path glob: {e}"),
+                        );
+                    }
+                }
+            }
+        }
+
+        let entry = match allowlist::parse_allow_entry(value) {
+            Ok(entry) => entry,
+            Err(message) if message.starts_with("invalid path glob pattern") => continue,
+            Err(message) => {
+                let span = [
+                    "rule",
+                    "expires_at",
+                    "ttl",
+                    "match",
+                    "active_hours",
+                    "active_dates",
+                    "hosts",
+                    "users",
+                    "arg_conditions",
+                    "url_prefix",
+                ]
+                .into_iter()
+                .find(|key| message.contains(key))
+                .and_then(field_span)
+                .or_else(|| header.clone());
+                diags.push(span, DiagnosticSeverity::Error, message);
+                continue;
+            }
+        };
+
+        let renamed_to = match &entry.selector {
+            AllowSelector::Rule(rule) => rule.canonical(),
+            _ => None,
+        };
+        match &entry.selector {
+            AllowSelector::Rule(rule) if rule.pack_id == "*" => diags.push(
+                field_span("rule"),
+                DiagnosticSeverity::Error,
+                "global wildcard rule allows every pack".to_string(),
+            ),
+            AllowSelector::Rule(rule) if !catalog.has_pack(&rule.pack_id) => diags.push(
+                field_span("rule"),
+                DiagnosticSeverity::Warning,
+                format!("unknown pack '{}'", rule.pack_id),
+            ),
+            AllowSelector::Rule(rule) if renamed_to.is_some() => diags.push(
+                field_span("rule"),
+                DiagnosticSeverity::Warning,
+                format!(
+                    "rule '{rule}' was renamed to '{}'; run `dcg allowlist migrate-ids`",
+                    renamed_to
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default()
+                ),
+            ),
+            AllowSelector::Rule(rule)
+                if rule.pattern_name != "*"
+                    && !catalog.has_rule(&rule.pack_id, &rule.pattern_name) =>
+            {
+                diags.push(
+                    field_span("rule"),
+                    DiagnosticSeverity::Warning,
+                    format!(
+                        "unknown rule '{}:{}' (pack '{}' has no such pattern)",
+                        rule.pack_id, rule.pattern_name, rule.pack_id
+                    ),
+                );
+            }
+            AllowSelector::RegexPattern(_) if !entry.risk_acknowledged => diags.push(
+                field_span("pattern"),
+                DiagnosticSeverity::Warning,
+                "regex pattern entries require risk_acknowledged = true".to_string(),
+            ),
+            _ => {}
+        }
+
+        if let Some(context) = entry.context.as_deref() {
+            if context != allowlist::STRING_ARGUMENT_CONTEXT {
+                diags.push(
+                    field_span("context"),
+                    DiagnosticSeverity::Warning,
+                    format!(
+                        "unknown context '{context}'; the entry never matches (expected \"{}\")",
+                        allowlist::STRING_ARGUMENT_CONTEXT
+                    ),
+                );
+            }
+        }
+
+        if allowlist::is_expired(&entry) {
+            let span = field_span("expires_at")
+                .or_else(|| field_span("ttl"))
+                .or_else(|| header.clone());
+            diags.push(
+                span,
+                DiagnosticSeverity::Warning,
+                "entry has expired and no longer applies".to_string(),
+            );
+        }
+    }
+}
+
+fn diagnose_config(
+    catalog: &RuleCatalog,
+    doc: &toml_edit::ImDocument<&str>,
+    text: &str,
+    diags: &mut Diagnostics<'_>,
+) {
+    let config: Config = match toml::from_str(text) {
+        Ok(config) => config,
+        Err(e) => {
+            diags.push(e.span(), DiagnosticSeverity::Error, e.message().to_string());
+            return;
+        }
+    };
+
+    if let Some(packs) = doc.get("packs") {
+        for key in ["enabled", "disabled"] {
+            let Some(ids) = packs.get(key).and_then(toml_edit::Item::as_array) else {
+                continue;
+            };
+            for id in ids {
+                if let Some(entry) = id.as_str() {
+                    let (pack_id, _) = crate::config::split_pack_pin(entry);
+                    if !catalog.has_pack_or_category(pack_id) {
+                        diags.push(
+                            id.span(),
+                            DiagnosticSeverity::Warning,
+                            format!("unknown pack '{pack_id}'"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(rules) = doc
+        .get("policy")
+        .and_then(|p| p.get("rules"))
+        .and_then(toml_edit::Item::as_table_like)
+    {
+        for (rule_id, item) in rules.iter() {
+            let known = allowlist::RuleId::parse(rule_id)
+                .is_some_and(|rule| catalog.has_rule(&rule.pack_id, &rule.pattern_name));
+            if !known {
+                diags.push(
+                    item.span(),
+                    DiagnosticSeverity::Warning,
+                    format!("unknown rule '{rule_id}'"),
+                );
+            }
+        }
+    }
+
+    let compiled = config.overrides.compile();
+    for invalid in &compiled.invalid_patterns {
+        let span = doc
+            .get("overrides")
+            .and_then(|item| find_string(item, &invalid.pattern));
+        diags.push(
+            span,
+            DiagnosticSeverity::Error,
+            format!("invalid override pattern: {}", invalid.error),
+        );
+    }
+}
+
+/// Span of the first string value equal to `needle` inside `item`.
+fn find_string(item: &toml_edit::Item, needle: &str) -> Option<Range<usize>> {
+    match item {
+        toml_edit::Item::Value(value) => find_string_value(value, needle),
+        toml_edit::Item::Table(table) => table.iter().find_map(|(_, i)| find_string(i, needle)),
+        toml_edit::Item::ArrayOfTables(array) => array
+            .iter()
+            .find_map(|t| t.iter().find_map(|(_, i)| find_string(i, needle))),
+        toml_edit::Item::None => None,
+    }
+}
+
+fn find_string_value(value: &toml_edit::Value, needle: &str) -> Option<Range<usize>> {
+    match value {
+        toml_edit::Value::String(s) if s.value() == needle => s.span(),
+        toml_edit::Value::Array(array) => array.iter().find_map(|v| find_string_value(v, needle)),
+        toml_edit::Value::InlineTable(table) => {
+            table.iter().find_map(|(_, v)| find_string_value(v, needle))
+        }
+        _ => None,
+    }
+}
+
+/// Completions at `position`: rule IDs inside `rule = "`, pack IDs inside
+/// `enabled = [` / `disabled = [`.
+#[must_use]
+pub fn complete(
+    catalog: &RuleCatalog,
+    kind: PolicyFileKind,
+    text: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let index = LineIndex::new(text);
+    let prefix = index.line_prefix(position);
+
+    // Only complete inside an open string literal.
+    let Some(quote) = prefix.rfind('"') else {
+        return Vec::new();
+    };
+    if prefix.matches('"').count() % 2 == 0 {
+        return Vec::new();
+    }
+    let typed = &prefix[quote + 1..];
+    let start_character = position
+        .character
+        .saturating_sub(u32::try_from(typed.encode_utf16().count()).unwrap_or(u32::MAX));
+    let edit_range = LspRange {
+        start: Position {
+            line: position.line,
+            character: start_character,
+        },
+        end: position,
+    };
+    let key = prefix[..quote]
+        .trim_start()
+        .split(['=', ' '])
+        .next()
+        .unwrap_or("");
+    let item = |label: String, detail: Option<String>| CompletionItem {
+        kind: 12,
+        detail,
+        text_edit: TextEdit {
+            range: edit_range,
+            new_text: label.clone(),
+        },
+        label,
+    };
+    let item = &item;
+
+    match (kind, key) {
+        (PolicyFileKind::Allowlist, "rule") => catalog
+            .packs
+            .iter()
+            .flat_map(|(pack_id, rules)| {
+                std::iter::once(item(
+                    format!("{pack_id}:*"),
+                    Some(format!("every rule in {pack_id}")),
+                ))
+                .chain(rules.iter().map(move |(name, reason)| {
+                    item(format!("{pack_id}:{name}"), Some(reason.clone()))
+                }))
+            })
+            .collect(),
+        (PolicyFileKind::Config, "enabled" | "disabled") => catalog
+            .categories
+            .iter()
+            .map(|category| item(category.clone(), Some("pack category".to_string())))
+            .chain(catalog.packs.keys().map(|id| item(id.clone(), None)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Open documents and protocol state.
+struct Server {
+    catalog: RuleCatalog,
+    documents: HashMap<String, String>,
+    shutdown_requested: bool,
+}
+
+impl Server {
+    /// Handle one message. Returns `false` once the client sends `exit`.
+    fn handle(&mut self, message: &Value, out: &mut impl Write) -> io::Result<bool> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        match method {
+            "initialize" => respond(
+                out,
+                id,
+                Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "completionProvider": { "triggerCharacters": ["\"", ":", "."] }
+                    },
+                    "serverInfo": { "name": "dcg", "version": env!("CARGO_PKG_VERSION") }
+                })),
+            )?,
+            "textDocument/didOpen" => {
+                let doc = &params["textDocument"];
+                if let (Some(uri), Some(text)) = (doc["uri"].as_str(), doc["text"].as_str()) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish(out, uri)?;
+                }
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str();
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                    self.publish(out, uri)?;
+                }
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+                    notify(
+                        out,
+                        "textDocument/publishDiagnostics",
+                        &json!({ "uri": uri, "diagnostics": [] }),
+                    )?;
+                }
+            }
+            "textDocument/completion" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let position = Position {
+                    line: json_u32(&params["position"]["line"]),
+                    character: json_u32(&params["position"]["character"]),
+                };
+                let items = self.documents.get(uri).map_or_else(Vec::new, |text| {
+                    complete(&self.catalog, PolicyFileKind::from_uri(uri), text, position)
+                });
+                respond(out, id, Ok(json!(items)))?;
+            }
+            "shutdown" => {
+                self.shutdown_requested = true;
+                respond(out, id, Ok(Value::Null))?;
+            }
+            "exit" => return Ok(false),
+            _ if id.is_some() => respond(
+                out,
+                id,
+                Err((METHOD_NOT_FOUND, format!("method not supported: {method}"))),
+            )?,
+            // Unhandled notifications (initialized, didSave, ...) need no reply.
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn publish(&self, out: &mut impl Write, uri: &str) -> io::Result<()> {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        let diagnostics = diagnose(&self.catalog, PolicyFileKind::from_uri(uri), text);
+        notify(
+            out,
+            "textDocument/publishDiagnostics",
+            &json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+}
+
+fn json_u32(value: &Value) -> u32 {
+    value
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .unwrap_or(0)
+}
+
+/// Read one `Content-Length`-framed message. Returns `None` at end of input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_message(out: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(out, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    out.flush()
+}
+
+fn respond(
+    out: &mut impl Write,
+    id: Option<Value>,
+    result: Result<Value, (i64, String)>,
+) -> io::Result<()> {
+    let id = id.unwrap_or(Value::Null);
+    let message = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message }
+        }),
+    };
+    write_message(out, &message)
+}
+
+fn notify(out: &mut impl Write, method: &str, params: &Value) -> io::Result<()> {
+    write_message(
+        out,
+        &json!({ "jsonrpc": "2.0", "method": method, "params": params }),
+    )
+}
+
+/// Serve LSP requests from `reader` until `exit` or end of input.
+///
+/// Returns whether `shutdown` was requested before the client went away.
+///
+/// # Errors
+///
+/// Returns an error if reading from `reader` or writing to `out` fails.
+pub fn serve(
+    catalog: RuleCatalog,
+    reader: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let mut server = Server {
+        catalog,
+        documents: HashMap::new(),
+        shutdown_requested: false,
+    };
+    while let Some(body) = read_message(reader)? {
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(message) => {
+                if !server.handle(&message, out)? {
+                    break;
+                }
+            }
+            Err(e) => respond(out, None, Err((PARSE_ERROR, e.to_string())))?,
+        }
+    }
+    Ok(server.shutdown_requested)
+}
+
+/// Run the language server on stdin/stdout (`dcg lsp`).
+///
+/// # Errors
+///
+/// Returns an error if stdio fails, or if the client exits without first
+/// requesting `shutdown` (per the LSP spec, the exit code is then 1).
+pub fn run_lsp_server() -> Result<(), Box<dyn std::error::Error>> {
+    let catalog = RuleCatalog::from_config(&Config::load());
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let clean = serve(catalog, &mut stdin.lock(), &mut stdout.lock())?;
+    if clean {
+        Ok(())
+    } else {
+        Err("language client exited without shutdown".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(input: &[Value]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for message in input {
+            write_message(&mut out, message).expect("write to vec");
+        }
+        out
+    }
+
+    fn parse_output(output: &[u8]) -> Vec<Value> {
+        let mut reader = io::BufReader::new(output);
+        let mut out = Vec::new();
+        while let Some(body) = read_message(&mut reader).expect("read") {
+            out.push(serde_json::from_slice(&body).expect("json"));
+        }
+        out
+    }
+
+    fn messages_of(diags: &[Diagnostic]) -> Vec<&str> {
+        diags.iter().map(|d| d.message.as_str()).collect()
+    }
+
+    #[test]
+    fn classifies_policy_files_by_name() {
+        assert_eq!(
+            PolicyFileKind::from_uri("file:///w/.dcg/allowlist.toml"),
+            PolicyFileKind::Allowlist
+        );
+        assert_eq!(
+            PolicyFileKind::from_uri("file:///w/.dcg.toml"),
+            PolicyFileKind::Config
+        );
+        assert_eq!(
+            PolicyFileKind::from_uri("file:///home/u/.config/dcg/config.toml"),
+            PolicyFileKind::Config
+        );
+        assert_eq!(
+            PolicyFileKind::from_uri("file:///w/Cargo.toml"),
+            PolicyFileKind::Other
+        );
+    }
+
+    #[test]
+    fn reports_toml_syntax_errors_with_position() {
+        let catalog = RuleCatalog::builtin();
+        let diags = diagnose(&catalog, PolicyFileKind::Other, "a = 1\nb = \n");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(diags[0].range.start.line, 1);
+    }
+
+    #[test]
+    fn allowlist_diagnostics_point_at_the_offending_field() {
+        let catalog = RuleCatalog::builtin();
+        let text = r#"[[allow]]
+rule = "core.git:reset-hard"
+reason = "fine"
+
+[[allow]]
+rule = "core.git:no-such-rule"
+reason = "typo"
+
+[[allow]]
+rule = "nope.pack:thing"
+reason = "unknown pack"
+
+[[allow]]
+rule = "not-a-rule"
+reason = "bad selector"
+
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "stale"
+expires_at = "2000-01-01"
+
+[[allow]]
+exact_command = "make clean"
+reason = "glob"
+paths = ["/w/[", "/ok/*"]
+
+[[allow]]
+command_prefix = "bd create"
+context = "string-arg"
+reason = "typo in context"
+"#;
+        let diags = diagnose(&catalog, PolicyFileKind::Allowlist, text);
+        let messages = messages_of(&diags);
+        assert_eq!(diags.len(), 6, "{messages:?}");
+
+        assert!(messages[0].contains("unknown rule 'core.git:no-such-rule'"));
+        assert_eq!(diags[0].range.start.line, 5);
+        assert_eq!(diags[0].range.start.character, 7);
+
+        assert!(messages[1].contains("unknown pack 'nope.pack'"));
+        assert_eq!(diags[1].range.start.line, 9);
+
+        assert!(messages[2].contains("invalid rule id"));
+        assert_eq!(diags[2].severity, DiagnosticSeverity::Error);
+        assert_eq!(diags[2].range.start.line, 13);
+
+        assert!(messages[3].contains("expired"));
+        assert_eq!(diags[3].range.start.line, 19);
+
+        assert!(messages[4].contains("invalid path glob"));
+        assert_eq!(diags[4].range.start.line, 24);
+        assert_eq!(diags[4].range.start.character, 9);
+
+        assert!(messages[5].contains("unknown context 'string-arg'"));
+        assert_eq!(diags[5].range.start.line, 28);
+    }
+
+    #[test]
+    fn config_diagnostics_flag_unknown_packs_rules_and_bad_overrides() {
+        let catalog = RuleCatalog::builtin();
+        let text = r#"[packs]
+enabled = ["containers", "containers.docker@1", "containers.nope"]
+
+[policy.rules]
+"core.git:reset-hard" = "warn"
+"core.git:bogus" = "log"
+
+[overrides]
+allow = ["(unclosed"]
+"#;
+        let diags = diagnose(&catalog, PolicyFileKind::Config, text);
+        let messages = messages_of(&diags);
+        assert_eq!(diags.len(), 3, "{messages:?}");
+        assert!(messages[0].contains("unknown pack 'containers.nope'"));
+        assert_eq!(diags[0].range.start.line, 1);
+        assert!(messages[1].contains("unknown rule 'core.git:bogus'"));
+        assert_eq!(diags[1].range.start.line, 5);
+        assert!(messages[2].contains("invalid override pattern"));
+        assert_eq!(diags[2].range.start.line, 8);
+    }
+
+    #[test]
+    fn completes_rule_ids_inside_rule_strings() {
+        let catalog = RuleCatalog::builtin();
+        let text = "[[allow]]\nrule = \"core.g\"\n";
+        let position = Position {
+            line: 1,
+            character: 14,
+        };
+        let items = complete(&catalog, PolicyFileKind::Allowlist, text, position);
+        let reset = items
+            .iter()
+            .find(|i| i.label == "core.git:reset-hard")
+            .expect("core.git:reset-hard offered");
+        assert!(reset.detail.is_some());
+        assert_eq!(reset.text_edit.range.start.character, 8);
+        assert_eq!(reset.text_edit.range.end, position);
+        assert!(items.iter().any(|i| i.label == "core.git:*"));
+
+        // Outside a string, or on another key, nothing is offered.
+        let outside = Position {
+            line: 1,
+            character: 4,
+        };
+        assert!(complete(&catalog, PolicyFileKind::Allowlist, text, outside).is_empty());
+        let reason = "[[allow]]\nreason = \"co\"\n";
+        assert!(complete(&catalog, PolicyFileKind::Allowlist, reason, position).is_empty());
+    }
+
+    #[test]
+    fn completes_pack_ids_in_config() {
+        let catalog = RuleCatalog::builtin();
+        let text = "[packs]\nenabled = [\"contai\n";
+        let position = Position {
+            line: 1,
+            character: 18,
+        };
+        let labels: Vec<String> = complete(&catalog, PolicyFileKind::Config, text, position)
+            .into_iter()
+            .map(|i| i.label)
+            .collect();
+        assert!(labels.iter().any(|l| l == "containers"));
+        assert!(labels.iter().any(|l| l == "containers.docker"));
+    }
+
+    #[test]
+    fn serves_a_full_session_over_stdio_framing() {
+        let uri = "file:///w/.dcg/allowlist.toml";
+        let input = messages(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+            json!({"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+                "textDocument": {"uri": uri, "languageId": "toml", "version": 1,
+                    "text": "[[allow]]\nrule = \"core.git:nope\"\nreason = \"x\"\n"}
+            }}),
+            json!({"jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+                "textDocument": {"uri": uri, "version": 2},
+                "contentChanges": [{"text": "[[allow]]\nrule = \"core.git:reset-hard\"\nreason = \"x\"\n"}]
+            }}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ]);
+        let mut output = Vec::new();
+        let clean = serve(
+            RuleCatalog::builtin(),
+            &mut io::BufReader::new(input.as_slice()),
+            &mut output,
+        )
+        .expect("serve");
+        assert!(clean);
+
+        let replies = parse_output(&output);
+        assert_eq!(replies.len(), 5);
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"]["capabilities"]["textDocumentSync"], 1);
+        assert_eq!(replies[1]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(replies[1]["params"]["diagnostics"][0]["severity"], 2);
+        assert_eq!(replies[2]["params"]["diagnostics"], json!([]));
+        assert_eq!(replies[3]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(replies[4]["id"], 3);
+        assert!(replies[4]["result"].is_null());
+    }
+}
@@ -19,11 +19,15 @@
 
 use clap::Parser;
 use colored::Colorize;
+use destructive_command_guard::allowlist_usage;
+use destructive_command_guard::canary;
 use destructive_command_guard::cli::{self, Cli};
 // Exit codes are used by cli.rs for robot mode; main.rs uses them for hook mode errors
 use destructive_command_guard::config::Config;
+use destructive_command_guard::error_codes::{DcgError, ErrorResponse};
 use destructive_command_guard::evaluator::{
-    EvaluationDecision, MatchSource, evaluate_command_with_pack_order_deadline_at_path,
+    EvalContext, EvaluationDecision, apply_severity_aggregation, evaluate_in_context,
+    resolve_decision_for_agent,
 };
 #[allow(unused_imports)]
 use destructive_command_guard::exit_codes::{EXIT_DENIED, EXIT_PARSE_ERROR, EXIT_SUCCESS};
@@ -31,15 +35,25 @@ use destructive_command_guard::history::{
     CommandEntry, ENV_HISTORY_DB_PATH, HistoryDb, HistoryWriter, Outcome as HistoryOutcome,
 };
 use destructive_command_guard::hook;
-use destructive_command_guard::load_default_allowlists;
+use destructive_command_guard::input_guard::{self, InputAnomaly};
+#[cfg(test)]
 use destructive_command_guard::normalize::normalize_command;
 use destructive_command_guard::packs::load_external_packs;
 #[cfg(test)]
 use destructive_command_guard::packs::pack_aware_quick_reject;
-use destructive_command_guard::packs::{DecisionMode, REGISTRY};
-use destructive_command_guard::pending_exceptions::{PendingExceptionStore, log_maintenance};
+use destructive_command_guard::packs::regex_engine::take_slow_patterns;
+use destructive_command_guard::packs::{DecisionMode, REGISTRY, Severity};
+use destructive_command_guard::pending_exceptions::{
+    PendingExceptionStore, log_maintenance, set_session_id,
+};
 use destructive_command_guard::perf::{Deadline, HOOK_EVALUATION_BUDGET};
-use destructive_command_guard::sanitize_for_pattern_matching;
+use destructive_command_guard::pipeline_trace;
+use destructive_command_guard::provenance::Provenance;
+use destructive_command_guard::review;
+use destructive_command_guard::suggestions::{self, Suggestion};
+use destructive_command_guard::{
+    agent, approvals, repeat_denials, session_memory, warn_once, warnings,
+};
 // Import HookInput for parsing stdin JSON in hook mode
 #[cfg(test)]
 use destructive_command_guard::hook::HookInput;
@@ -47,7 +61,7 @@ use destructive_command_guard::hook::HookInput;
 use std::borrow::Cow;
 use std::collections::HashSet;
 use std::io::{self, IsTerminal};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 // Build metadata from vergen (set by build.rs)
@@ -82,9 +96,11 @@ fn history_db_path(config: &destructive_command_guard::config::HistoryConfig) ->
     config.expanded_database_path()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_history_entry(
     command: &str,
     working_dir: &str,
+    provenance: Provenance,
     outcome: HistoryOutcome,
     eval_duration: Duration,
     pack_id: Option<&str>,
@@ -102,17 +118,71 @@ fn build_history_entry(
         pattern_name: pattern_name.map(str::to_string),
         eval_duration_us,
         allowlist_layer: allowlist_layer.map(str::to_string),
+        provenance: Some(provenance.label().to_string()),
         ..Default::default()
     }
 }
 
+/// Suggestions the denied rule's pack builds from `command`, followed by the
+/// safer commands this project has used instead of the rule.
+fn command_suggestions(
+    config: &Config,
+    command: &str,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    cwd: Option<&Path>,
+) -> Vec<Suggestion> {
+    let mut suggestions = pack
+        .zip(pattern)
+        .map(|(pack, pattern)| suggestions::tailored_suggestions(pack, pattern, command))
+        .unwrap_or_default();
+    suggestions.extend(learned_alternatives(config, pack, pattern, cwd));
+    suggestions
+}
+
+/// Safer commands this project has used instead of the denied rule (needs
+/// history; see `suggestions::get_suggestions_for_project`).
+fn learned_alternatives(
+    config: &Config,
+    pack: Option<&str>,
+    pattern: Option<&str>,
+    cwd: Option<&Path>,
+) -> Vec<Suggestion> {
+    let (Some(pack), Some(pattern), Some(cwd)) = (pack, pattern, cwd) else {
+        return Vec::new();
+    };
+    if !config.history.enabled {
+        return Vec::new();
+    }
+    HistoryDb::try_open(history_db_path(&config.history))
+        .map(|db| suggestions::learned_alternatives(&db, &format!("{pack}:{pattern}"), cwd))
+        .unwrap_or_default()
+}
+
+/// Warn about a pathological hook command on stderr and in the log file.
+fn report_input_anomalies(config: &Config, logged_command: &str, anomalies: &[InputAnomaly]) {
+    let code = destructive_command_guard::error_codes::ErrorCode::InputAnomaly;
+    let summary = anomalies
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    eprintln!(
+        "[dcg] Warning: [{code}] input anomaly ({summary}) in command: {}",
+        input_guard::display_command(logged_command)
+    );
+    if let Some(log_file) = config.general.log_file.as_deref() {
+        let _ = hook::log_input_anomaly(log_file, logged_command, anomalies);
+    }
+}
+
 fn install_history_shutdown_handler(
     handle: destructive_command_guard::history::HistoryFlushHandle,
 ) {
     let _ = ctrlc::set_handler(move || {
         eprintln!("[dcg] Flushing history...");
         handle.flush_sync();
-        std::process::exit(130);
+        std::process::exit(destructive_command_guard::exit_codes::EXIT_INTERRUPTED);
     });
 }
 
@@ -201,6 +271,15 @@ fn main() {
 
     // Check for --version flag (useful when run directly, not as hook)
     let args: Vec<String> = std::env::args().collect();
+
+    // `dcg __complete -- <words>...` runs on every TAB from the scripts of
+    // `dcg completions`. Its words may hold any flag, so it bypasses clap.
+    if args.get(1).is_some_and(|a| a == "__complete") {
+        let words = args.get(2..).unwrap_or_default();
+        let words = words.strip_prefix(&["--".to_string()]).unwrap_or(words);
+        std::process::exit(cli::complete_words(&Config::load(), words));
+    }
+
     if args.iter().any(|a| a == "--version" || a == "-V") {
         print_version();
         return;
@@ -231,6 +310,10 @@ fn main() {
     destructive_command_guard::output::init(force_plain_output);
     destructive_command_guard::output::init_console(force_plain_output);
     destructive_command_guard::output::init_suggestions(!cli.no_suggestions && !robot_mode);
+    destructive_command_guard::output::set_width_override(
+        cli.width
+            .or_else(destructive_command_guard::output::env_width),
+    );
 
     // In robot mode, also disable colors completely
     if robot_mode {
@@ -240,7 +323,15 @@ fn main() {
     // If there's a subcommand, handle it and exit.
     if cli.command.is_some() {
         if let Err(e) = cli::run_command(cli) {
-            eprintln!("Error: {e}");
+            let error = DcgError::from_error(e.as_ref());
+            if robot_mode {
+                eprintln!("{}", ErrorResponse::new(error).to_json());
+            } else {
+                eprintln!("Error: {error}");
+                if let Some(code) = error.error_code() {
+                    eprintln!("  See: {}", code.docs_url());
+                }
+            }
             std::process::exit(1);
         }
         return;
@@ -248,6 +339,10 @@ fn main() {
 
     // Load configuration
     let config = Config::load();
+    destructive_command_guard::output::init_hyperlinks(config.output.hyperlinks_enabled());
+    destructive_command_guard::impact::init_budget(config.output.impact_budget());
+    destructive_command_guard::hook::init_messages(config.messages.clone());
+    destructive_command_guard::storage_impact::init(config.output.storage_impact_timeout());
 
     // Check if bypass is requested (escape hatch)
     if Config::is_bypassed() {
@@ -255,18 +350,29 @@ fn main() {
     }
 
     // Compile overrides once (precompiled regexes, no per-command compilation)
-    let compiled_overrides = config.overrides.compile();
+    let compiled_overrides = config.compile_overrides();
+
+    // Profile forced with DCG_PROFILE; applied like the API's `agent_profile`.
+    let forced_profile = config.agents.forced_profile.as_deref();
 
     // Load layered allowlists (project/user/system). Missing/invalid files are treated
     // as empty for hook safety; allowlist decisions are only consulted on matches.
-    let allowlists = load_default_allowlists();
+    let allowlists =
+        if forced_profile.is_some_and(|name| config.agents.allowlist_disabled_for(name)) {
+            destructive_command_guard::LayeredAllowlist::default()
+        } else {
+            config.load_allowlists()
+        };
 
     // Compute effective heredoc settings once (avoid per-command parsing/allocations).
     let heredoc_settings = config.heredoc_settings();
 
     // Get enabled pack IDs early for pack-aware quick reject.
     // This is done before stdin read to minimize latency on the critical path.
-    let mut enabled_packs: HashSet<String> = config.enabled_pack_ids();
+    let mut enabled_packs: HashSet<String> = forced_profile.map_or_else(
+        || config.enabled_pack_ids(),
+        |name| config.enabled_pack_ids_for_profile(name),
+    );
     let mut enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
 
     // Load external packs from custom_paths (glob + tilde expansion).
@@ -274,8 +380,14 @@ fn main() {
     let external_paths = config.packs.expand_custom_paths();
     let external_store = load_external_packs(&external_paths);
 
-    // Log warnings from external pack loading (fail-open: don't block on warnings).
+    // Log warnings from config and external pack loading (fail-open: don't block on warnings).
     if config.general.verbose {
+        for warning in config.load_warnings() {
+            eprintln!("[dcg] Warning: config {warning}");
+        }
+        for warning in config.pack_pin_warnings() {
+            eprintln!("[dcg] Warning: {warning}");
+        }
         for warning in external_store.warnings() {
             eprintln!("[dcg] Warning: {warning}");
         }
@@ -310,11 +422,12 @@ fn main() {
 
     // Read and parse input
     let max_input_bytes = config.general.max_hook_input_bytes();
-    let hook_input = match hook::read_hook_input(max_input_bytes) {
+    let (hook_input, raw_input) = match hook::read_hook_input_with_raw(max_input_bytes) {
         Ok(input) => input,
-        Err(hook::HookReadError::InputTooLarge(len)) => {
+        Err(e @ hook::HookReadError::InputTooLarge(len)) => {
             eprintln!(
-                "[dcg] Warning: stdin input ({len} bytes) exceeds limit ({max_input_bytes} bytes); allowing command (fail-open)"
+                "[dcg] Warning: [{}] stdin input ({len} bytes) exceeds limit ({max_input_bytes} bytes); allowing command (fail-open)",
+                e.code()
             );
             return;
         }
@@ -332,17 +445,22 @@ fn main() {
     let Some((command, hook_protocol)) = hook::extract_command_with_protocol(&hook_input) else {
         return;
     };
-
-    // Check command size limit (fail-open: allow and warn)
-    let max_command_bytes = config.general.max_command_bytes();
-    if command.len() > max_command_bytes {
-        eprintln!(
-            "[dcg] Warning: command ({} bytes) exceeds limit ({} bytes); allowing command (fail-open)",
-            command.len(),
-            max_command_bytes
-        );
-        return;
+    if let Some(session_id) = hook_input.session_id.as_deref() {
+        set_session_id(session_id);
+    }
+    if let Some(transcript_path) = hook_input.transcript_path.as_deref() {
+        session_memory::set_transcript_path(transcript_path);
     }
+    // The hook only ever sees agent commands; `DCG_PROVENANCE` is not honoured here.
+    let provenance = Provenance::Agent;
+
+    // Pathological commands (NULs, huge tokens or commands, deep quoting) are
+    // evaluated as a bounded copy and reported below, never skipped.
+    let input_guard::GuardedCommand {
+        command: guarded_command,
+        anomalies,
+    } = input_guard::inspect(&command, config.general.max_command_bytes());
+    let command = guarded_command.into_owned();
 
     let cwd_path = std::env::current_dir().ok();
     let working_dir = cwd_path.as_ref().map_or_else(
@@ -357,6 +475,13 @@ fn main() {
         None
     };
 
+    // What the log file, history, and webhooks record ([privacy] log_mode).
+    let logged_command = config.privacy.log_command(&command);
+
+    if !anomalies.is_empty() {
+        report_input_anomalies(&config, &logged_command, &anomalies);
+    }
+
     if let Some(writer) = history_writer.as_ref() {
         if let Some(handle) = writer.flush_handle() {
             install_history_shutdown_handler(handle);
@@ -367,7 +492,7 @@ fn main() {
         if let Some(log_file) = config.general.log_file.as_deref() {
             let _ = hook::log_budget_skip(
                 log_file,
-                &command,
+                &logged_command,
                 "pre_evaluation",
                 deadline.elapsed(),
                 HOOK_EVALUATION_BUDGET,
@@ -378,29 +503,77 @@ fn main() {
 
     // Use the shared evaluator for hook mode parity with `dcg test`.
     let eval_start = Instant::now();
-    let result = evaluate_command_with_pack_order_deadline_at_path(
-        &command,
+    let mut eval_context = EvalContext::new(&command)
+        .provenance(provenance)
+        .deadline(&deadline);
+    eval_context.cwd.clone_from(&cwd_path);
+    eval_context.agent = forced_profile.map(str::to_string);
+    let result = evaluate_in_context(
+        &eval_context,
         &enabled_keywords,
         &ordered_packs,
         keyword_index.as_ref(),
         &compiled_overrides,
         &allowlists,
         &heredoc_settings,
-        None, // allow_once_audit
-        None, // project_path
-        Some(&deadline),
     );
+    let result = apply_severity_aggregation(result, config.policy().aggregation());
 
     // NOTE: External packs from custom_paths are now checked in evaluate_command()
     // alongside built-in packs, so no separate fallback check is needed here.
 
     let eval_duration = eval_start.elapsed();
 
+    // Patterns that hit the backtracking limit were resolved by their source; surface them.
+    for slow in take_slow_patterns() {
+        if config.general.verbose {
+            eprintln!("[dcg] Warning: {}", DcgError::from(&slow));
+        }
+        if let Some(log_file) = config.general.log_file.as_deref() {
+            let _ = hook::log_slow_pattern(log_file, &logged_command, &slow);
+        }
+    }
+
+    if cli.trace_file.is_some() || config.general.trace {
+        let input = pipeline_trace::TraceInput {
+            source: "hook",
+            raw_input: &raw_input,
+            command: &command,
+            enabled_keywords: &enabled_keywords,
+            ordered_packs: &ordered_packs,
+            allowlists: &allowlists,
+        };
+        let mode = resolve_decision_for_agent(
+            &config,
+            &command,
+            &result,
+            provenance,
+            severity_modes_agent(&config).as_deref(),
+        )
+        .map(|decision| decision.mode);
+        let trace = pipeline_trace::build(&input, &result, mode, eval_duration);
+        if let Some(path) = cli.trace_file.as_deref() {
+            if let Err(e) = pipeline_trace::write(path, &trace) {
+                eprintln!(
+                    "[dcg] Warning: failed to write trace file {}: {e}",
+                    path.display()
+                );
+            }
+        }
+        // DCG_TRACE=1: the same trace on stderr, for debugging a single run.
+        if config.general.trace {
+            if let Err(e) = pipeline_trace::write_to(&mut std::io::stderr().lock(), &trace) {
+                eprintln!("[dcg] Warning: failed to print trace: {e}");
+            }
+        }
+    }
+
     if result.skipped_due_to_budget {
         if let Some(writer) = history_writer.as_ref() {
             let entry = build_history_entry(
-                &command,
+                &logged_command,
                 &working_dir,
+                provenance,
                 HistoryOutcome::Allow,
                 eval_duration,
                 None,
@@ -412,7 +585,7 @@ fn main() {
         if let Some(log_file) = config.general.log_file.as_deref() {
             let _ = hook::log_budget_skip(
                 log_file,
-                &command,
+                &logged_command,
                 "evaluation",
                 deadline.elapsed(),
                 HOOK_EVALUATION_BUDGET,
@@ -422,6 +595,9 @@ fn main() {
     }
 
     if result.decision != EvaluationDecision::Deny {
+        if let Some(key) = result.allowlist_entry.as_deref() {
+            allowlist_usage::record_use(key, cwd_path.as_deref());
+        }
         if let Some(writer) = history_writer.as_ref() {
             let mut pack_id = None;
             let mut pattern_name = None;
@@ -434,8 +610,9 @@ fn main() {
             }
 
             let entry = build_history_entry(
-                &command,
+                &logged_command,
                 &working_dir,
+                provenance,
                 HistoryOutcome::Allow,
                 eval_duration,
                 pack_id,
@@ -451,8 +628,9 @@ fn main() {
         // Fail open: structurally unexpected, but hook safety wins.
         if let Some(writer) = history_writer.as_ref() {
             let entry = build_history_entry(
-                &command,
+                &logged_command,
                 &working_dir,
+                provenance,
                 HistoryOutcome::Allow,
                 eval_duration,
                 None,
@@ -465,55 +643,46 @@ fn main() {
     };
 
     let pack = info.pack_id.as_deref();
-    let mut mode = match info.source {
-        MatchSource::Pack | MatchSource::HeredocAst => {
-            config
-                .policy()
-                .resolve_mode(pack, info.pattern_name.as_deref(), info.severity)
-        }
-        // Never downgrade explicit blocks.
-        MatchSource::ConfigOverride | MatchSource::LegacyPattern => DecisionMode::Deny,
-    };
-
-    // Apply confidence scoring (if enabled) to potentially downgrade Deny to Warn.
-    // Only applies to pack/heredoc matches, not config overrides.
-    if matches!(info.source, MatchSource::Pack | MatchSource::HeredocAst) {
-        let sanitized = sanitize_for_pattern_matching(&command);
-        let normalized_command = normalize_command(&command);
-        let normalized_sanitized = normalize_command(sanitized.as_ref());
-
-        let mut confidence_command = command.as_str();
-        let mut confidence_sanitized: Option<&str> = None;
-
-        if normalized_command.len() == normalized_sanitized.len() {
-            confidence_command = normalized_command.as_ref();
-            if sanitized.as_ref() != command {
-                confidence_sanitized = Some(normalized_sanitized.as_ref());
-            }
-        }
-
-        let confidence_result = destructive_command_guard::apply_confidence_scoring(
-            confidence_command,
-            confidence_sanitized,
-            &result,
-            mode,
-            &config.confidence,
-        );
-        mode = confidence_result.mode;
+    let is_canary = pack == Some(canary::CANARY_PACK_ID);
+    let resolved = resolve_decision_for_agent(
+        &config,
+        &command,
+        &result,
+        provenance,
+        severity_modes_agent(&config).as_deref(),
+    );
+    let mut mode = resolved.map_or(DecisionMode::Deny, |decision| decision.mode);
+    // Only Claude-compatible hooks can prompt, and only with a human around.
+    if mode == DecisionMode::Ask
+        && (hook_protocol != hook::HookProtocol::ClaudeCompatible || approvals::is_unattended())
+    {
+        mode = DecisionMode::Deny;
     }
+    let confidence = resolved.and_then(|decision| decision.confidence);
 
     let pattern = info.pattern_name.as_deref();
     let explanation = info.explanation.as_deref();
 
+    // `warn_once`: only the first occurrence in this session warns.
+    let repeated_warning = mode == DecisionMode::WarnOnce && {
+        let rule_id = pack.zip(pattern).map_or_else(
+            || info.reason.clone(),
+            |(pack, pattern)| format!("{pack}:{pattern}"),
+        );
+        !warn_once::should_warn(&rule_id, &command, cwd_path.as_deref())
+    };
+
     if let Some(writer) = history_writer.as_ref() {
         let outcome = match mode {
             DecisionMode::Deny => HistoryOutcome::Deny,
-            DecisionMode::Warn => HistoryOutcome::Warn,
+            DecisionMode::WarnOnce if repeated_warning => HistoryOutcome::Allow,
+            DecisionMode::Warn | DecisionMode::WarnOnce | DecisionMode::Ask => HistoryOutcome::Warn,
             DecisionMode::Log => HistoryOutcome::Allow,
         };
         let entry = build_history_entry(
-            &command,
+            &logged_command,
             &working_dir,
+            provenance,
             outcome,
             eval_duration,
             pack,
@@ -523,6 +692,27 @@ fn main() {
         writer.log(entry);
     }
 
+    if is_canary {
+        if let Some(url) = config.canary.webhook_url.as_deref() {
+            let canary_text = info
+                .matched_span
+                .as_ref()
+                .and_then(|span| command.get(span.start..span.end))
+                .unwrap_or_default();
+            let event = canary::CanaryEvent::new(
+                pattern.unwrap_or_default(),
+                canary_text,
+                &logged_command,
+                cwd_path.as_ref().map(|_| working_dir.as_str()),
+            );
+            let outcome = canary::notify_webhook(url, &event);
+            if let Err(e) = &outcome {
+                eprintln!("[dcg] Warning: failed to notify canary webhook: {e}");
+            }
+            canary::record_webhook_outcome(&canary::webhook_failure_path(), &outcome);
+        }
+    }
+
     match mode {
         DecisionMode::Deny => {
             let store_path = PendingExceptionStore::default_path(cwd_path.as_deref());
@@ -534,25 +724,58 @@ fn main() {
                 _ => info.reason.clone(),
             };
 
+            // Canary hits cannot be waved through with `dcg allow-once`, so
+            // they get no pending exception and no allow-once hint.
+            let recorded = if is_canary {
+                None
+            } else {
+                store
+                    .record_block(
+                        &command,
+                        &working_dir,
+                        &reason,
+                        &config.logging.redaction,
+                        false,
+                        Some(format!("{:?}", info.source)),
+                        pack.zip(pattern)
+                            .map(|(pack_id, pattern_name)| format!("{pack_id}:{pattern_name}")),
+                        None,
+                    )
+                    .ok()
+            };
             let mut allow_once_info: Option<hook::AllowOnceInfo> = None;
-            if let Ok((record, maintenance)) = store.record_block(
-                &command,
-                &working_dir,
-                &reason,
-                &config.logging.redaction,
-                false,
-                Some(format!("{:?}", info.source)),
-                None,
-            ) {
+            if let Some((record, maintenance)) = recorded {
+                // Nobody can answer the denial now: queue it for `dcg approvals`.
+                let approval_id = (config.interactive.approval_queue && approvals::is_unattended())
+                    .then(|| approvals::queue_block(&record, cwd_path.as_deref()))
+                    .flatten();
                 allow_once_info = Some(hook::AllowOnceInfo {
                     code: record.short_code,
                     full_hash: record.full_hash,
+                    approval_id,
                 });
                 if let Some(log_file) = config.general.log_file.as_deref() {
                     let _ = log_maintenance(log_file, maintenance, "record_block");
                 }
             }
 
+            // Retries of a command already blocked in this session get a
+            // one-line notice instead of the full box.
+            let repeat_count = if config.output.collapse_repeat_denials() {
+                let rule_id = pack.zip(pattern).map_or_else(
+                    || info.reason.clone(),
+                    |(pack, pattern)| format!("{pack}:{pattern}"),
+                );
+                repeat_denials::record_denial(&rule_id, &command, cwd_path.as_deref())
+            } else {
+                1
+            };
+            let suggestions = if repeat_count > 1 {
+                Vec::new()
+            } else {
+                command_suggestions(&config, &command, pack, pattern, cwd_path.as_deref())
+            };
+
             hook::output_denial_for_protocol(
                 hook_protocol,
                 &command,
@@ -563,27 +786,141 @@ fn main() {
                 allow_once_info.as_ref(),
                 info.matched_span.as_ref(),
                 info.severity,
-                None, // confidence not yet available in PatternMatch
+                confidence,
                 info.suggestions,
+                &suggestions,
+                &result.other_matches,
+                repeat_count,
             );
 
             // Log if configured
             if let Some(log_file) = &config.general.log_file {
-                let _ = hook::log_blocked_command(log_file, &command, &info.reason, pack);
+                let _ = hook::log_blocked_command(
+                    log_file,
+                    &logged_command,
+                    provenance,
+                    &info.reason,
+                    pack,
+                    result.aggregation.as_ref(),
+                    confidence,
+                );
+            }
+
+            #[cfg(feature = "telemetry")]
+            destructive_command_guard::telemetry::on_denial(pack, || ordered_packs.len());
+        }
+        // Already warned about in this session: pass silently.
+        DecisionMode::WarnOnce if repeated_warning => {}
+        DecisionMode::Warn | DecisionMode::WarnOnce => {
+            hook::output_warning_for_channel(
+                config.policy.warn_channel(),
+                hook_protocol,
+                &command,
+                &info.reason,
+                pack,
+                pattern,
+                explanation,
+                info.severity,
+                confidence,
+            );
+
+            // A deny that fell back to warn on low confidence is still audited.
+            if let Some(log_file) = config.general.log_file.as_deref() {
+                if confidence.is_some_and(|gate| gate.downgraded) {
+                    let _ = hook::log_blocked_command(
+                        log_file,
+                        &logged_command,
+                        provenance,
+                        &info.reason,
+                        pack,
+                        result.aggregation.as_ref(),
+                        confidence,
+                    );
+                }
+            }
+
+            // Interactive learning: queue Medium warnings for `dcg review`.
+            if config.interactive.learning && info.severity == Some(Severity::Medium) {
+                if let (Some(pack_id), Some(pattern_name)) = (pack, pattern) {
+                    if !destructive_command_guard::privacy::is_hashed(&logged_command) {
+                        review::record_warning(
+                            &logged_command,
+                            &format!("{pack_id}:{pattern_name}"),
+                            &info.reason,
+                            cwd_path.as_deref(),
+                        );
+                    }
+                }
+            }
+
+            // Queue every warning for `dcg warnings`.
+            if config.policy.warn_queue() {
+                if let (Some(pack_id), Some(pattern_name)) = (pack, pattern) {
+                    if !destructive_command_guard::privacy::is_hashed(&logged_command) {
+                        warnings::record_warning(
+                            &logged_command,
+                            &format!("{pack_id}:{pattern_name}"),
+                            &info.reason,
+                            info.severity.map(|severity| severity.label()),
+                            cwd_path.as_deref(),
+                        );
+                    }
+                }
             }
         }
-        DecisionMode::Warn => {
-            hook::output_warning(&command, &info.reason, pack, pattern, explanation);
+        DecisionMode::Ask => {
+            hook::output_ask(
+                &command,
+                &info.reason,
+                pack,
+                pattern,
+                explanation,
+                info.severity,
+                confidence,
+            );
+
+            if let Some(log_file) = &config.general.log_file {
+                let _ = hook::log_blocked_command(
+                    log_file,
+                    &logged_command,
+                    provenance,
+                    &info.reason,
+                    pack,
+                    result.aggregation.as_ref(),
+                    confidence,
+                );
+            }
         }
         DecisionMode::Log => {
             // Silent allow; optionally log to file for history.
             if let Some(log_file) = &config.general.log_file {
-                let _ = hook::log_blocked_command(log_file, &command, &info.reason, pack);
+                let _ = hook::log_blocked_command(
+                    log_file,
+                    &logged_command,
+                    provenance,
+                    &info.reason,
+                    pack,
+                    result.aggregation.as_ref(),
+                    confidence,
+                );
             }
         }
     }
 }
 
+/// The profile forced with `DCG_PROFILE`, else the calling agent's config key,
+/// detected only when some agent profile sets `severity_modes` (detection may
+/// inspect the parent process).
+fn severity_modes_agent(config: &Config) -> Option<String> {
+    if let Some(profile) = &config.agents.forced_profile {
+        return Some(profile.clone());
+    }
+    config
+        .agents
+        .has_severity_modes()
+        .then(|| agent::detect_agent().config_key().to_string())
+}
+
 /// Print help information.
 #[allow(clippy::too_many_lines)]
 fn print_help() {
@@ -656,6 +993,10 @@ fn print_help() {
         "    {}      Explain why a command would be blocked/allowed",
         "explain".green()
     );
+    eprintln!(
+        "    {}  Trace why a command was allowed",
+        "why-allowed".green()
+    );
     eprintln!(
         "    {}       Check installation and hook registration",
         "doctor".green()
@@ -690,6 +1031,10 @@ fn print_help() {
         "simulate".green()
     );
     eprintln!("    {}       Show current configuration", "config".green());
+    eprintln!(
+        "    {}         Revert the last policy-file change made by dcg",
+        "undo".green()
+    );
     eprintln!(
         "    {}         Generate a sample configuration file",
         "init".green()
@@ -720,13 +1065,17 @@ fn print_help() {
     );
     eprintln!("    {}       Run regression corpus tests", "corpus".green());
     eprintln!(
-        "    {}         Run in explicit hook mode (batch support)",
+        "    {}         Run in explicit hook mode, or install git hooks",
         "hook".green()
     );
     eprintln!(
         "    {}  Generate shell completion scripts",
         "completions".green()
     );
+    eprintln!(
+        "    {}       Print JSON Schemas for dcg output and config",
+        "schema".green()
+    );
     eprintln!(
         "    {}          Developer tools for pack development",
         "dev".green()
@@ -735,6 +1084,10 @@ fn print_help() {
         "    {}   Start MCP server for agent integration",
         "mcp-server".green()
     );
+    eprintln!(
+        "    {}          Run a language server for dcg policy files",
+        "lsp".green()
+    );
     eprintln!();
     eprintln!(
         "    Run {} for detailed help on a command.",
@@ -904,11 +1257,14 @@ mod tests {
                     )),
                     allow_once_code: None,
                     allow_once_full_hash: None,
+                    approval_id: None,
                     rule_id: None,
                     pack_id: None,
                     severity: None,
                     confidence: None,
+                    min_confidence_to_deny: None,
                     remediation: None,
+                    matches: Vec::new(),
                 },
             }
         }
@@ -115,6 +115,7 @@ impl DcgMcpServer {
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 200,
+            jobs: 1,
         }
     }
 
@@ -0,0 +1,700 @@
+//! Versioned schema upgrades for config and allowlist files (`dcg migrate`).
+//!
+//! Both file kinds carry a top-level `config_version`; files written before it
+//! existed are version 1. Each [`Migration`] rewrites a file from one version
+//! to the next through `toml_edit`, so comments and formatting it does not
+//! touch survive. [`plan`] computes the edits without writing anything and
+//! [`apply`] writes the result atomically after copying the original to
+//! `<file>.v<N>.bak`.
+//!
+//! Migrations only ever make implicit behavior explicit: a migrated file must
+//! load to the same effective rules as the original.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+use crate::error_codes::{DcgError, ErrorCode};
+
+/// Schema version written by this build.
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+/// Top-level key holding a file's schema version.
+pub const VERSION_KEY: &str = "config_version";
+
+/// Which schema a file follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    /// `config.toml` / `.dcg.toml` (and files pulled in via `include`).
+    Config,
+    /// `allowlist.toml`.
+    Allowlist,
+}
+
+impl FileKind {
+    /// Guess the kind from a file name: anything named `*allowlist*` is an
+    /// allowlist, everything else a config file.
+    #[must_use]
+    pub fn infer(path: &Path) -> Self {
+        let is_allowlist = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().contains("allowlist"));
+        if is_allowlist {
+            Self::Allowlist
+        } else {
+            Self::Config
+        }
+    }
+
+    /// Human-readable label.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Allowlist => "allowlist",
+        }
+    }
+}
+
+/// One upgrade step from `from` to `from + 1` for one kind of file.
+pub struct Migration {
+    /// Version this step upgrades from.
+    pub from: u32,
+    /// File kind it applies to.
+    pub kind: FileKind,
+    /// One-line description for `dcg migrate --dry-run`.
+    pub summary: &'static str,
+    apply: fn(&mut DocumentMut, &mut Changes),
+}
+
+/// Every migration, in the order they run.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        kind: FileKind::Config,
+        summary: "move legacy `overrides.allowlist` strings into `[[overrides.allowlist_rules]]`",
+        apply: config_v1_simple_allowlist,
+    },
+    Migration {
+        from: 1,
+        kind: FileKind::Config,
+        summary: "resolve `ttl`/`ttl_seconds` + `created_at` on allowlist rules into `expires`",
+        apply: config_v1_rule_ttl,
+    },
+    Migration {
+        from: 1,
+        kind: FileKind::Allowlist,
+        summary: "resolve `ttl` + `added_at` on entries into `expires_at`",
+        apply: allowlist_v1_ttl,
+    },
+];
+
+/// A single change a migration makes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationEdit {
+    /// Version the file has after this edit's migration.
+    pub to_version: u32,
+    /// Dotted key (or entry) the edit touches.
+    pub key: String,
+    /// What changed.
+    pub change: String,
+}
+
+/// Edits and notes collected while a migration runs.
+#[derive(Debug, Default)]
+struct Changes {
+    edits: Vec<(String, String)>,
+    notes: Vec<String>,
+}
+
+impl Changes {
+    fn edit(&mut self, key: impl Into<String>, change: impl Into<String>) {
+        self.edits.push((key.into(), change.into()));
+    }
+
+    fn note(&mut self, note: impl Into<String>) {
+        self.notes.push(note.into());
+    }
+}
+
+/// What migrating one file would do.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationPlan {
+    /// File the plan is for.
+    pub path: PathBuf,
+    /// Schema the file follows.
+    pub kind: FileKind,
+    /// `config_version` before migrating.
+    pub from_version: u32,
+    /// `config_version` after migrating.
+    pub to_version: u32,
+    /// Changes, in the order they are made.
+    pub edits: Vec<MigrationEdit>,
+    /// Things a migration noticed but could not change without altering
+    /// behavior; worth a manual look.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// The migrated file contents.
+    #[serde(skip)]
+    pub migrated: String,
+}
+
+impl MigrationPlan {
+    /// Whether the file is already at [`CURRENT_CONFIG_VERSION`].
+    #[must_use]
+    pub const fn is_current(&self) -> bool {
+        self.from_version >= self.to_version
+    }
+
+    /// Whether migrating changes anything beyond stamping `config_version`.
+    #[must_use]
+    pub fn has_rewrites(&self) -> bool {
+        self.edits.iter().any(|edit| edit.key != VERSION_KEY)
+    }
+}
+
+/// Plan the migration of `content` (read from `path`) to
+/// [`CURRENT_CONFIG_VERSION`].
+///
+/// # Errors
+///
+/// Returns an error if the file is not valid TOML, or its `config_version`
+/// is malformed or newer than this build supports.
+pub fn plan(path: &Path, kind: FileKind, content: &str) -> Result<MigrationPlan, DcgError> {
+    let display = path.display().to_string();
+    let mut doc: DocumentMut = content.parse().map_err(|e: toml_edit::TomlError| {
+        let details = e.to_string();
+        match kind {
+            FileKind::Config => DcgError::config_parse_error(&display, &details),
+            FileKind::Allowlist => DcgError::allowlist_load_error("", &display, &details),
+        }
+    })?;
+
+    let from_version = match doc.get(VERSION_KEY) {
+        None => 1,
+        Some(item) => item
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| {
+                DcgError::new(
+                    ErrorCode::InvalidConfigValue,
+                    format!("{display}: `{VERSION_KEY}` must be a positive integer"),
+                )
+                .add_context("path", display.clone())
+            })?,
+    };
+    if from_version > CURRENT_CONFIG_VERSION {
+        return Err(DcgError::new(
+            ErrorCode::InvalidConfigValue,
+            format!(
+                "{display}: `{VERSION_KEY} = {from_version}` was written by a newer dcg \
+                 (this build supports up to {CURRENT_CONFIG_VERSION}); upgrade dcg"
+            ),
+        )
+        .add_context("path", display));
+    }
+
+    let mut edits = Vec::new();
+    let mut notes = Vec::new();
+    for version in from_version..CURRENT_CONFIG_VERSION {
+        for migration in MIGRATIONS
+            .iter()
+            .filter(|m| m.from == version && m.kind == kind)
+        {
+            let mut changes = Changes::default();
+            (migration.apply)(&mut doc, &mut changes);
+            edits.extend(
+                changes
+                    .edits
+                    .into_iter()
+                    .map(|(key, change)| MigrationEdit {
+                        to_version: version + 1,
+                        key,
+                        change,
+                    }),
+            );
+            notes.extend(changes.notes);
+        }
+    }
+
+    if from_version < CURRENT_CONFIG_VERSION {
+        set_version(&mut doc, CURRENT_CONFIG_VERSION);
+        edits.push(MigrationEdit {
+            to_version: CURRENT_CONFIG_VERSION,
+            key: VERSION_KEY.to_string(),
+            change: format!("set to {CURRENT_CONFIG_VERSION}"),
+        });
+    }
+
+    Ok(MigrationPlan {
+        path: path.to_path_buf(),
+        kind,
+        from_version,
+        to_version: CURRENT_CONFIG_VERSION.max(from_version),
+        edits,
+        notes,
+        migrated: doc.to_string(),
+    })
+}
+
+/// Read `path` and [`plan`] its migration.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or [`plan`] fails.
+pub fn plan_file(path: &Path, kind: FileKind) -> Result<MigrationPlan, DcgError> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        DcgError::io_error("reading file to migrate", &e.to_string())
+            .add_context("path", path.display().to_string())
+    })?;
+    plan(path, kind, &content)
+}
+
+/// Write a planned migration: copy the original to a backup next to it, then
+/// atomically replace the file. Returns the backup path, or `None` when the
+/// file was already current.
+///
+/// # Errors
+///
+/// Returns any I/O error from writing the backup or the migrated file.
+pub fn apply(plan: &MigrationPlan) -> io::Result<Option<PathBuf>> {
+    if plan.is_current() {
+        return Ok(None);
+    }
+    let backup = backup_path(&plan.path, plan.from_version);
+    fs::copy(&plan.path, &backup)?;
+    crate::storage::write_atomic(&plan.path, plan.migrated.as_bytes())?;
+    Ok(Some(backup))
+}
+
+/// `<file>.v<N>.bak`, or `<file>.v<N>.bak.<i>` if earlier backups exist.
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut base = path.as_os_str().to_owned();
+    base.push(format!(".v{version}.bak"));
+    let base = PathBuf::from(base);
+    if !base.exists() {
+        return base;
+    }
+    (1..1000)
+        .map(|i| {
+            let mut candidate = base.as_os_str().to_owned();
+            candidate.push(format!(".{i}"));
+            PathBuf::from(candidate)
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(base)
+}
+
+/// Config and allowlist files `config` was loaded from that exist on disk
+/// (remote includes are skipped).
+#[must_use]
+pub fn discover(config: &crate::config::Config) -> Vec<(FileKind, PathBuf)> {
+    let mut files: Vec<(FileKind, PathBuf)> = Vec::new();
+    let config_files = config
+        .sources
+        .iter()
+        .map(|source| PathBuf::from(&source.location));
+    let allowlist_files = config
+        .load_allowlists()
+        .layers
+        .into_iter()
+        .map(|layer| layer.path);
+    for (kind, path) in config_files
+        .map(|path| (FileKind::Config, path))
+        .chain(allowlist_files.map(|path| (FileKind::Allowlist, path)))
+    {
+        if path.is_file() && !files.iter().any(|(_, seen)| *seen == path) {
+            files.push((kind, path));
+        }
+    }
+    files
+}
+
+/// Stamp `config_version`, placing it first when the key is new.
+fn set_version(doc: &mut DocumentMut, version: u32) {
+    let root = doc.as_table_mut();
+    let existed = root.contains_key(VERSION_KEY);
+    root.insert(VERSION_KEY, toml_edit::value(i64::from(version)));
+    if !existed {
+        root.sort_values_by(|a, _, b, _| (a.get() != VERSION_KEY).cmp(&(b.get() != VERSION_KEY)));
+    }
+}
+
+fn item_str(item: &Item) -> Option<String> {
+    item.as_str()
+        .map(str::to_string)
+        .or_else(|| item.as_datetime().map(ToString::to_string))
+}
+
+fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Visit every table in an array of tables or an inline array of tables.
+fn for_each_table(item: &mut Item, mut f: impl FnMut(usize, &mut dyn TableLike)) {
+    if let Some(tables) = item.as_array_of_tables_mut() {
+        for (idx, table) in tables.iter_mut().enumerate() {
+            f(idx, table);
+        }
+    } else if let Some(array) = item.as_array_mut() {
+        for (idx, value) in array.iter_mut().enumerate() {
+            if let Some(table) = value.as_inline_table_mut() {
+                f(idx, table);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Version 1 -> 2
+// ============================================================================
+
+/// Visit the top-level `overrides` table and each `projects."<path>".overrides`.
+fn for_each_overrides(doc: &mut DocumentMut, mut f: impl FnMut(&str, &mut Item)) {
+    for (key, item) in doc.as_table_mut().iter_mut() {
+        match key.get() {
+            "overrides" => f("overrides", item),
+            "projects" => {
+                let Some(projects) = item.as_table_like_mut() else {
+                    continue;
+                };
+                for (path, project) in projects.iter_mut() {
+                    let overrides = project
+                        .as_table_like_mut()
+                        .and_then(|project| project.get_mut("overrides"));
+                    if let Some(overrides) = overrides {
+                        f(&format!("projects.\"{}\".overrides", path.get()), overrides);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `overrides.allowlist = ["..."]` predates `[[overrides.allowlist_rules]]`;
+/// each string becomes a rule with only `pattern` set, which behaves the same.
+fn config_v1_simple_allowlist(doc: &mut DocumentMut, changes: &mut Changes) {
+    for_each_overrides(doc, |prefix, overrides| {
+        let inline = overrides.is_inline_table();
+        let Some(overrides) = overrides.as_table_like_mut() else {
+            return;
+        };
+        let Some(simple) = overrides.get("allowlist") else {
+            return;
+        };
+        let key = format!("{prefix}.allowlist");
+        let Some(array) = simple.as_array() else {
+            changes.note(format!("{key}: not an array of strings; left unchanged"));
+            return;
+        };
+        let patterns: Vec<String> = array
+            .iter()
+            .filter_map(|v| v.as_str())
+            .filter(|p| !p.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+        if patterns.len() != array.len() {
+            let dropped = array.len() - patterns.len();
+            changes.note(format!(
+                "{key}: dropped {dropped} empty or non-string value(s), which were already \
+                 ignored"
+            ));
+        }
+
+        if overrides.get("allowlist_rules").is_none() {
+            let empty = if inline {
+                Item::Value(toml_edit::Array::new().into())
+            } else {
+                Item::ArrayOfTables(toml_edit::ArrayOfTables::new())
+            };
+            overrides.insert("allowlist_rules", empty);
+        }
+        let Some(rules) = overrides.get_mut("allowlist_rules") else {
+            return;
+        };
+        if let Some(tables) = rules.as_array_of_tables_mut() {
+            for pattern in &patterns {
+                let mut table = Table::new();
+                table.insert("pattern", toml_edit::value(pattern.as_str()));
+                tables.push(table);
+            }
+        } else if let Some(inline_rules) = rules.as_array_mut() {
+            for pattern in &patterns {
+                let mut table = toml_edit::InlineTable::new();
+                table.insert("pattern", pattern.as_str().into());
+                inline_rules.push(table);
+            }
+        } else {
+            changes.note(format!(
+                "{key}: `{prefix}.allowlist_rules` is not an array; left unchanged"
+            ));
+            return;
+        }
+        overrides.remove("allowlist");
+        changes.edit(
+            key,
+            format!(
+                "moved {} pattern(s) to {prefix}.allowlist_rules",
+                patterns.len()
+            ),
+        );
+    });
+}
+
+/// A rule's `ttl`/`ttl_seconds` counts from `created_at`; spell out the
+/// resulting deadline as `expires`. Without `created_at` the TTL never starts,
+/// which is left alone and reported.
+fn config_v1_rule_ttl(doc: &mut DocumentMut, changes: &mut Changes) {
+    for_each_overrides(doc, |prefix, overrides| {
+        let Some(rules) = overrides
+            .as_table_like_mut()
+            .and_then(|o| o.get_mut("allowlist_rules"))
+        else {
+            return;
+        };
+        for_each_table(rules, |idx, rule| {
+            let key = format!("{prefix}.allowlist_rules[{idx}]");
+            resolve_rule_ttl(&key, rule, changes);
+        });
+    });
+}
+
+fn resolve_rule_ttl(key: &str, rule: &mut dyn TableLike, changes: &mut Changes) {
+    let ttl_secs = match (rule.get("ttl"), rule.get("ttl_seconds")) {
+        (Some(ttl), _) => ttl
+            .as_str()
+            .and_then(|ttl| crate::config::parse_ttl_duration(ttl).ok()),
+        (None, Some(secs)) => secs.as_integer().and_then(|s| u64::try_from(s).ok()),
+        (None, None) => return,
+    };
+    let Some(ttl_secs) = ttl_secs.and_then(|s| i64::try_from(s).ok()) else {
+        changes.note(format!("{key}: unparseable TTL; left unchanged"));
+        return;
+    };
+    let created = rule
+        .get("created_at")
+        .and_then(item_str)
+        .and_then(|c| DateTime::parse_from_rfc3339(&c).ok());
+    let Some(created) = created else {
+        changes.note(format!(
+            "{key}: TTL without a valid `created_at` never expires; set `expires` if it should"
+        ));
+        return;
+    };
+    let Some(deadline) = created
+        .with_timezone(&Utc)
+        .checked_add_signed(chrono::TimeDelta::seconds(ttl_secs))
+    else {
+        changes.note(format!("{key}: TTL overflows; left unchanged"));
+        return;
+    };
+    let existing = rule
+        .get("expires")
+        .and_then(item_str)
+        .map(|e| DateTime::parse_from_rfc3339(&e).map(|e| e.with_timezone(&Utc)));
+    let deadline = match existing {
+        Some(Ok(existing)) => existing.min(deadline),
+        Some(Err(_)) => {
+            changes.note(format!("{key}: unparseable `expires`; left unchanged"));
+            return;
+        }
+        None => deadline,
+    };
+    rule.remove("ttl");
+    rule.remove("ttl_seconds");
+    rule.insert("expires", toml_edit::value(rfc3339(deadline)));
+    changes.edit(
+        key,
+        format!("TTL resolved to expires = {}", rfc3339(deadline)),
+    );
+}
+
+/// An entry's `ttl` counts from `added_at`; spell out the deadline as
+/// `expires_at`. Without `added_at` the entry is already treated as expired,
+/// which is left alone and reported.
+fn allowlist_v1_ttl(doc: &mut DocumentMut, changes: &mut Changes) {
+    let Some(entries) = doc.get_mut("allow") else {
+        return;
+    };
+    for_each_table(entries, |idx, entry| {
+        let key = format!("allow[{idx}]");
+        let Some(ttl) = entry
+            .get("ttl")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)
+        else {
+            return;
+        };
+        let Some(added) = entry
+            .get("added_at")
+            .and_then(item_str)
+            .and_then(|a| crate::allowlist::parse_timestamp(&a))
+        else {
+            changes.note(format!(
+                "{key}: `ttl` without a valid `added_at` never matches (treated as expired); \
+                 remove the entry or set `expires_at`"
+            ));
+            return;
+        };
+        let Some(deadline) = crate::allowlist::parse_duration(&ttl)
+            .ok()
+            .and_then(|ttl| added.checked_add_signed(ttl))
+        else {
+            changes.note(format!("{key}: unparseable `ttl`; left unchanged"));
+            return;
+        };
+        entry.remove("ttl");
+        entry.insert("expires_at", toml_edit::value(rfc3339(deadline)));
+        changes.edit(
+            key,
+            format!(
+                "ttl = \"{ttl}\" resolved to expires_at = {}",
+                rfc3339(deadline)
+            ),
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_v1_moves_simple_allowlist_and_resolves_ttl() {
+        let v1 = r#"# project policy
+[general]
+verbose = true
+
+[overrides]
+allowlist = ["npm run build", "", "cargo test"]
+
+[[overrides.allowlist_rules]]
+pattern = "rm -rf target"
+ttl = "1d"
+created_at = "2026-01-01T00:00:00Z"
+
+[[overrides.allowlist_rules]]
+pattern = "make clean"
+ttl = "4h"
+
+[projects."/w"]
+overrides = { allowlist = ["make docs"] }
+"#;
+        let plan = plan(Path::new(".dcg.toml"), FileKind::Config, v1).unwrap();
+        assert_eq!((plan.from_version, plan.to_version), (1, 2));
+        assert!(plan.has_rewrites());
+        assert_eq!(plan.notes.len(), 2, "{:?}", plan.notes);
+
+        let migrated = &plan.migrated;
+        assert!(migrated.starts_with("config_version = 2\n# project policy"));
+        let config: crate::config::Config = toml::from_str(migrated).unwrap();
+        assert!(config.overrides.allowlist.is_none());
+        let rules = config.overrides.allowlist_rules.unwrap();
+        let patterns: Vec<_> = rules.iter().map(|r| r.pattern.as_str()).collect();
+        assert_eq!(
+            patterns,
+            ["rm -rf target", "make clean", "npm run build", "cargo test"]
+        );
+        assert_eq!(rules[0].expires.as_deref(), Some("2026-01-02T00:00:00Z"));
+        assert!(rules[0].ttl.is_none());
+        // No created_at: left alone.
+        assert_eq!(rules[1].ttl.as_deref(), Some("4h"));
+        let project = config.projects["/w"].overrides.as_ref().unwrap();
+        assert!(project.allowlist.is_none());
+        assert_eq!(
+            project.allowlist_rules.as_ref().unwrap()[0].pattern,
+            "make docs"
+        );
+
+        // Migrating again is a no-op.
+        let again = super::plan(Path::new(".dcg.toml"), FileKind::Config, migrated).unwrap();
+        assert!(again.is_current());
+        assert!(again.edits.is_empty());
+        assert_eq!(&again.migrated, migrated);
+    }
+
+    #[test]
+    fn allowlist_v1_resolves_ttl_to_expires_at() {
+        let v1 = r#"[[allow]]
+rule = "core.git:reset-hard"
+reason = "scratch repo"
+added_at = "2026-03-01T12:00:00Z"
+ttl = "2h"
+
+[[allow]]
+exact_command = "rm -rf dist"
+reason = "no added_at"
+ttl = "1d"
+"#;
+        let plan = plan(Path::new("allowlist.toml"), FileKind::Allowlist, v1).unwrap();
+        assert_eq!(plan.notes.len(), 1);
+        let file = crate::allowlist::parse_allowlist_toml(
+            crate::allowlist::AllowlistLayer::Project,
+            Path::new("allowlist.toml"),
+            &plan.migrated,
+        );
+        assert!(file.errors.is_empty(), "{:?}", file.errors);
+        assert_eq!(
+            file.entries[0].expires_at.as_deref(),
+            Some("2026-03-01T14:00:00Z")
+        );
+        assert!(file.entries[0].ttl.is_none());
+        assert_eq!(file.entries[1].ttl.as_deref(), Some("1d"));
+    }
+
+    #[test]
+    fn sample_config_is_current() {
+        let sample = crate::config::Config::generate_sample_config();
+        let plan = plan(Path::new("config.toml"), FileKind::Config, &sample).unwrap();
+        assert!(plan.is_current());
+        assert!(plan.edits.is_empty());
+    }
+
+    #[test]
+    fn rejects_newer_or_malformed_versions() {
+        let newer = plan(
+            Path::new("c.toml"),
+            FileKind::Config,
+            "config_version = 99\n",
+        );
+        assert_eq!(
+            newer.unwrap_err().error_code(),
+            Some(ErrorCode::InvalidConfigValue)
+        );
+        let bad = plan(
+            Path::new("c.toml"),
+            FileKind::Config,
+            "config_version = \"2\"\n",
+        );
+        assert!(bad.is_err());
+        let invalid = plan(Path::new("c.toml"), FileKind::Config, "[general");
+        assert_eq!(
+            invalid.unwrap_err().error_code(),
+            Some(ErrorCode::ConfigParseError)
+        );
+    }
+
+    #[test]
+    fn apply_writes_backup_and_migrated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let original = "[overrides]\nallowlist = [\"ls\"]\n";
+        fs::write(&path, original).unwrap();
+
+        let plan = plan_file(&path, FileKind::infer(&path)).unwrap();
+        let backup = apply(&plan).unwrap().unwrap();
+        assert_eq!(backup, dir.path().join("config.toml.v1.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        assert_eq!(fs::read_to_string(&path).unwrap(), plan.migrated);
+
+        let current = plan_file(&path, FileKind::Config).unwrap();
+        assert_eq!(apply(&current).unwrap(), None);
+    }
+}
@@ -0,0 +1,106 @@
+//! The Node.js addon (`napi` feature).
+//!
+//! Claude Code extensions and many MCP servers are TypeScript; this exposes
+//! the guard to them in-process (package in `npm/`):
+//!
+//! ```js
+//! const dcg = require("destructive-command-guard");
+//!
+//! const result = dcg.evaluate("git reset --hard", { cwd: "/work/repo", profile: "ci-bot" });
+//! if (result.decision === "deny") throw new Error(`${result.rule_id}: ${result.reason}`);
+//! ```
+//!
+//! `evaluate()` returns the `dcg test --format json` document and `explain()`
+//! the `dcg explain --format json` document, built by the same code as the
+//! CLI, so `docs/json-schema/test-output.json` describes both. Policy comes
+//! from the shared [`api`] guards: the config and allowlists `dcg` loads, plus
+//! the `[agents.<profile>]` profile when one is named.
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+use crate::api::{self, Context};
+use crate::cli::{AgentInfo, build_test_output, explain_trace};
+
+/// Options for `evaluate()`.
+#[napi(object)]
+#[derive(Debug, Default)]
+pub struct EvaluateOptions {
+    /// Working directory the command would run in.
+    pub cwd: Option<String>,
+    /// `[agents.<name>]` profile to apply.
+    pub profile: Option<String>,
+}
+
+fn to_napi_error(error: impl std::fmt::Display) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+fn evaluate_json(command: &str, options: &EvaluateOptions) -> Result<serde_json::Value> {
+    let profile = options.profile.as_deref();
+    let guard = api::shared_guard(profile).map_err(to_napi_error)?;
+    let mut context = Context::new();
+    if let Some(cwd) = &options.cwd {
+        context = context.cwd(cwd);
+    }
+    let result = guard.evaluate_result(command, &context);
+    let agent = profile.map(|name| AgentInfo {
+        detected: name.to_string(),
+        trust_level: format!("{:?}", guard.config().agents.trust_level_for(name)).to_lowercase(),
+        detection_method: "explicit".to_string(),
+    });
+    let output = build_test_output(command, context.provenance, &result, agent, false);
+    serde_json::to_value(output).map_err(to_napi_error)
+}
+
+fn explain_json(command: &str) -> Result<serde_json::Value> {
+    let guard = api::shared_guard(None).map_err(to_napi_error)?;
+    let trace = explain_trace(guard.config(), command);
+    serde_json::to_value(trace.to_json_output()).map_err(to_napi_error)
+}
+
+/// Evaluate `command`; returns the `dcg test --format json` document.
+#[napi]
+#[allow(clippy::needless_pass_by_value)] // napi passes owned values
+pub fn evaluate(command: String, options: Option<EvaluateOptions>) -> Result<serde_json::Value> {
+    evaluate_json(&command, &options.unwrap_or_default())
+}
+
+/// Trace the decision for `command`; returns the `dcg explain --format json`
+/// document.
+#[napi]
+#[allow(clippy::needless_pass_by_value)] // napi passes owned values
+pub fn explain(command: String) -> Result<serde_json::Value> {
+    explain_json(&command)
+}
+
+/// Forget cached policy; the next call rereads config and allowlists.
+#[napi]
+pub fn reload() {
+    api::reload_shared_guards();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_returns_the_cli_test_document() {
+        let options = EvaluateOptions {
+            cwd: None,
+            profile: Some("ci-bot".to_string()),
+        };
+        let output = evaluate_json("git reset --hard", &options).unwrap();
+        assert_eq!(output["decision"], "deny");
+        assert_eq!(output["rule_id"], "core.git:reset-hard");
+        assert_eq!(output["schema_version"], 1);
+        assert_eq!(output["agent"]["detection_method"], "explicit");
+
+        let output = evaluate_json("git status", &EvaluateOptions::default()).unwrap();
+        assert_eq!(output["decision"], "allow");
+        assert!(output.get("agent").is_none());
+
+        let trace = explain_json("git reset --hard").unwrap();
+        assert_eq!(trace["decision"], "deny");
+    }
+}
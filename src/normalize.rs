@@ -15,6 +15,21 @@
 //! - `env [-i] [-u name] [NAME=VALUE]... command` - environment modification
 //! - `\git`, `\rm` - bash alias bypass (leading backslash)
 //! - `command [-p] [--] cmd` - but NOT `command -v` or `command -V` (query mode)
+//! - `nohup`, `timeout [-s sig] [-k dur] DURATION`, `nice [-n N]`,
+//!   `stdbuf [-i|-o|-e MODE]`, and `xargs -I REPLACE` - see [`EXEC_WRAPPERS`]
+//!
+//! # Flag Normalization
+//!
+//! Packs can also declare [`CommandFlagSpec`]s for commands whose getopt-style
+//! flags they understand. [`normalize_flags`] rewrites those commands into a
+//! canonical form (`rm -R --force x` becomes `rm -rf x`) that the evaluator
+//! matches in addition to the command as written.
+//!
+//! # Unicode Folding
+//!
+//! [`fold_confusables`] strips zero-width characters and folds NBSPs, Unicode
+//! dashes, and common homoglyphs to ASCII so `r\u{200b}m -rf /` is evaluated
+//! as `rm -rf /`.
 
 use fancy_regex::Regex;
 use smallvec::SmallVec;
@@ -115,6 +130,12 @@ pub fn strip_wrapper_prefixes(command: &str) -> NormalizedCommand<'_> {
             continue;
         }
 
+        if let Some((remaining, wrapper)) = strip_exec_wrapper(&current) {
+            stripped_wrappers.push(wrapper);
+            current = remaining;
+            continue;
+        }
+
         if let Some((remaining, wrapper)) = strip_leading_backslash(&current) {
             stripped_wrappers.push(wrapper);
             current = remaining;
@@ -744,6 +765,187 @@ fn strip_command_wrapper(command: &str) -> Option<(String, StrippedWrapper)> {
     ))
 }
 
+/// A prefix command that runs the command after its own options and operands.
+///
+/// Options are only recognized before the first operand: these tools stop
+/// option parsing there, so `nice git -n 5` runs `git -n 5`. Options a spec
+/// does not list make the wrapper unrecognized, and nothing is stripped.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExecWrapperSpec {
+    /// Command name of the wrapper (basename, so `/usr/bin/nice` matches).
+    pub name: &'static str,
+    /// Short flags without a value.
+    pub short_flags: &'static [char],
+    /// Short flags that consume a value (`-n 19` or `-n19`).
+    pub short_with_value: &'static [char],
+    /// Long flags without a value.
+    pub long_flags: &'static [&'static str],
+    /// Long flags that consume a value (`--signal KILL` or `--signal=KILL`).
+    pub long_with_value: &'static [&'static str],
+    /// Whether a duration operand precedes the command (`timeout 30 cmd`).
+    pub duration_operand: bool,
+    /// Short flag without which the wrapper is not stripped. `xargs` without
+    /// `-I` appends its input to the command, which the filesystem pack parses
+    /// as an `xargs` segment instead.
+    pub required_flag: Option<char>,
+}
+
+impl ExecWrapperSpec {
+    const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            short_flags: &[],
+            short_with_value: &[],
+            long_flags: &[],
+            long_with_value: &[],
+            duration_operand: false,
+            required_flag: None,
+        }
+    }
+
+    /// Parse one option word: the short flag that takes a value (if any) and
+    /// whether the value is the next word. `None` for options the wrapper
+    /// does not accept.
+    pub(crate) fn parse_option(&self, word: &str) -> Option<(Option<char>, bool)> {
+        if let Some(long) = word.strip_prefix("--") {
+            return match long.split_once('=') {
+                Some((name, _)) => self
+                    .long_with_value
+                    .contains(&name)
+                    .then_some((None, false)),
+                None if self.long_with_value.contains(&long) => Some((None, true)),
+                None => self.long_flags.contains(&long).then_some((None, false)),
+            };
+        }
+        for (pos, flag) in word.char_indices().skip(1) {
+            if self.short_with_value.contains(&flag) {
+                return Some((Some(flag), pos + flag.len_utf8() == word.len()));
+            }
+            if !self.short_flags.contains(&flag) {
+                return None;
+            }
+        }
+        Some((None, false))
+    }
+}
+
+/// Prefix commands stripped like `sudo` and `env`, so `nice -n 19 rm -rf /tmp/x`
+/// is evaluated as `rm -rf /tmp/x`.
+pub static EXEC_WRAPPERS: &[ExecWrapperSpec] = &[
+    ExecWrapperSpec::new("nohup"),
+    ExecWrapperSpec {
+        short_flags: &['v'],
+        short_with_value: &['s', 'k'],
+        long_flags: &["preserve-status", "foreground", "verbose"],
+        long_with_value: &["signal", "kill-after"],
+        duration_operand: true,
+        ..ExecWrapperSpec::new("timeout")
+    },
+    ExecWrapperSpec {
+        // `nice -19 cmd` is the historical spelling of `nice -n 19 cmd`.
+        short_flags: &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'],
+        short_with_value: &['n'],
+        long_with_value: &["adjustment"],
+        ..ExecWrapperSpec::new("nice")
+    },
+    ExecWrapperSpec {
+        short_with_value: &['i', 'o', 'e'],
+        long_with_value: &["input", "output", "error"],
+        ..ExecWrapperSpec::new("stdbuf")
+    },
+    ExecWrapperSpec {
+        short_flags: &['0', 'p', 'r', 't', 'x'],
+        short_with_value: &['a', 'd', 'E', 'I', 'L', 'n', 'P', 's'],
+        long_flags: &[
+            "null",
+            "interactive",
+            "no-run-if-empty",
+            "verbose",
+            "exit",
+            "open-tty",
+        ],
+        long_with_value: &[
+            "arg-file",
+            "delimiter",
+            "eof",
+            "max-args",
+            "max-chars",
+            "max-lines",
+            "max-procs",
+            "process-slot-var",
+        ],
+        required_flag: Some('I'),
+        ..ExecWrapperSpec::new("xargs")
+    },
+];
+
+/// `timeout` durations: a number with an optional `s`/`m`/`h`/`d` suffix.
+fn is_duration(word: &str) -> bool {
+    let number = word.strip_suffix(['s', 'm', 'h', 'd']).unwrap_or(word);
+    number.bytes().any(|b| b.is_ascii_digit())
+        && number.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+}
+
+/// Strip one of the [`EXEC_WRAPPERS`] with its options and operands.
+fn strip_exec_wrapper(command: &str) -> Option<(String, StrippedWrapper)> {
+    let trimmed = command.trim_start();
+
+    let first_word_end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+    let first_word = &trimmed[..first_word_end];
+    let basename = first_word.rsplit('/').next().unwrap_or(first_word);
+    let spec = EXEC_WRAPPERS.iter().find(|spec| spec.name == basename)?;
+
+    let rest = &trimmed[first_word_end..];
+    let bytes = rest.as_bytes();
+    let len = bytes.len();
+    let next_word = |idx: usize| -> Option<(usize, usize)> {
+        let start = idx + rest[idx..].len() - rest[idx..].trim_start().len();
+        let end = consume_word_token(bytes, start, len);
+        (end > start).then_some((start, end))
+    };
+
+    let mut idx = 0;
+    let mut saw_required = spec.required_flag.is_none();
+    loop {
+        let (start, end) = next_word(idx)?;
+        let word = &rest[start..end];
+        if word == "--" {
+            idx = end;
+            break;
+        }
+        if !word.starts_with('-') || word == "-" {
+            break;
+        }
+        let (value_flag, takes_next) = spec.parse_option(word)?;
+        saw_required |= value_flag.is_some() && value_flag == spec.required_flag;
+        idx = if takes_next { next_word(end)?.1 } else { end };
+    }
+    if !saw_required {
+        return None;
+    }
+    if spec.duration_operand {
+        let (start, end) = next_word(idx)?;
+        if !is_duration(&rest[start..end]) {
+            return None;
+        }
+        idx = end;
+    }
+
+    let (start, _) = next_word(idx)?;
+    let remaining = &rest[start..];
+    let stripped_text = trimmed[..trimmed.len() - remaining.len()]
+        .trim_end()
+        .to_string();
+
+    Some((
+        remaining.to_string(),
+        StrippedWrapper {
+            wrapper_type: spec.name,
+            stripped_text,
+        },
+    ))
+}
+
 #[must_use]
 pub fn consume_word_token(bytes: &[u8], mut i: usize, len: usize) -> usize {
     while i < len {
@@ -979,10 +1181,25 @@ pub fn is_env_assignment(word: &str) -> bool {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NormalizeWrapper {
     None,
-    Sudo { options_ended: bool, skip_next: u8 },
-    Env { options_ended: bool, skip_next: u8 },
-    Command { options_ended: bool, skip_next: u8 },
+    Sudo {
+        options_ended: bool,
+        skip_next: u8,
+    },
+    Env {
+        options_ended: bool,
+        skip_next: u8,
+    },
+    Command {
+        options_ended: bool,
+        skip_next: u8,
+    },
     CommandQuery,
+    Exec {
+        spec: &'static ExecWrapperSpec,
+        options_ended: bool,
+        skip_next: u8,
+        operands: u8,
+    },
 }
 
 impl NormalizeWrapper {
@@ -1003,7 +1220,15 @@ impl NormalizeWrapper {
                 options_ended: false,
                 skip_next: 0,
             }),
-            _ => None,
+            _ => EXEC_WRAPPERS
+                .iter()
+                .find(|spec| spec.name == base_name)
+                .map(|spec| Self::Exec {
+                    spec,
+                    options_ended: false,
+                    skip_next: 0,
+                    operands: u8::from(spec.duration_operand),
+                }),
         }
     }
 
@@ -1032,6 +1257,16 @@ impl NormalizeWrapper {
                 }
                 !options_ended && word.starts_with('-')
             }
+            Self::Exec {
+                options_ended,
+                skip_next,
+                operands,
+                ..
+            } => {
+                skip_next > 0
+                    || operands > 0
+                    || (!options_ended && word.starts_with('-') && word != "-")
+            }
         }
     }
 
@@ -1143,6 +1378,40 @@ impl NormalizeWrapper {
         }
     }
 
+    #[inline]
+    #[must_use]
+    fn advance_exec(
+        spec: &'static ExecWrapperSpec,
+        mut options_ended: bool,
+        mut skip_next: u8,
+        mut operands: u8,
+        word: &str,
+    ) -> Self {
+        if skip_next > 0 {
+            skip_next -= 1;
+        } else if !options_ended && word == "--" {
+            options_ended = true;
+        } else if !options_ended && word.starts_with('-') && word != "-" {
+            // Unknown options are skipped too: the command word comes later.
+            if spec
+                .parse_option(word)
+                .is_some_and(|(_, takes_next)| takes_next)
+            {
+                skip_next = 1;
+            }
+        } else {
+            // The `timeout` duration.
+            options_ended = true;
+            operands = operands.saturating_sub(1);
+        }
+        Self::Exec {
+            spec,
+            options_ended,
+            skip_next,
+            operands,
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn advance(self, word: &str) -> Self {
@@ -1159,6 +1428,12 @@ impl NormalizeWrapper {
                 options_ended,
                 skip_next,
             } => Self::advance_command(options_ended, skip_next, word),
+            Self::Exec {
+                spec,
+                options_ended,
+                skip_next,
+                operands,
+            } => Self::advance_exec(spec, options_ended, skip_next, operands, word),
             Self::None | Self::CommandQuery => self,
         }
     }
@@ -1588,6 +1863,439 @@ fn strip_leading_backslash(command: &str) -> Option<(String, StrippedWrapper)> {
     ))
 }
 
+/// Getopt-style flag grammar for one command, declared by packs that opt in
+/// to flag normalization.
+///
+/// Packs list the commands whose flags they understand; [`normalize_flags`]
+/// rewrites matching segments into a canonical form so that `rm -rf x`,
+/// `rm -fr x`, `rm -r -f x`, and `rm --recursive --force x` all look alike to
+/// the pack's patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandFlagSpec {
+    /// Leading command words that select this spec (e.g. `["git", "clean"]`).
+    pub command: &'static [&'static str],
+    /// Long flags folded into a short flag (e.g. `("force", 'f')`).
+    pub long_aliases: &'static [(&'static str, char)],
+    /// Short flags folded into another short flag (e.g. `('R', 'r')`).
+    pub short_aliases: &'static [(char, char)],
+    /// Short flags that consume a value (`-e pattern` or `-epattern`).
+    pub short_with_value: &'static [char],
+    /// Long flags that consume a value (`--exclude pattern` or `--exclude=pattern`).
+    pub long_with_value: &'static [&'static str],
+}
+
+impl CommandFlagSpec {
+    /// Create a spec for `command` with no aliases or valued flags.
+    #[must_use]
+    pub const fn new(command: &'static [&'static str]) -> Self {
+        Self {
+            command,
+            long_aliases: &[],
+            short_aliases: &[],
+            short_with_value: &[],
+            long_with_value: &[],
+        }
+    }
+
+    #[must_use]
+    pub const fn long_aliases(mut self, aliases: &'static [(&'static str, char)]) -> Self {
+        self.long_aliases = aliases;
+        self
+    }
+
+    #[must_use]
+    pub const fn short_aliases(mut self, aliases: &'static [(char, char)]) -> Self {
+        self.short_aliases = aliases;
+        self
+    }
+
+    #[must_use]
+    pub const fn short_with_value(mut self, flags: &'static [char]) -> Self {
+        self.short_with_value = flags;
+        self
+    }
+
+    #[must_use]
+    pub const fn long_with_value(mut self, flags: &'static [&'static str]) -> Self {
+        self.long_with_value = flags;
+        self
+    }
+
+    fn short_alias(&self, flag: char) -> char {
+        self.short_aliases
+            .iter()
+            .find(|(from, _)| *from == flag)
+            .map_or(flag, |(_, to)| *to)
+    }
+
+    fn long_alias(&self, name: &str) -> Option<char> {
+        self.long_aliases
+            .iter()
+            .find(|(long, _)| *long == name)
+            .map(|(_, short)| *short)
+    }
+}
+
+/// A command rewritten by [`normalize_flags`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagNormalized {
+    /// The canonical command text.
+    pub canonical: String,
+    /// Rewritten segments as `(original range, canonical range)` pairs, in order.
+    rewrites: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl FlagNormalized {
+    /// Map a byte range in [`Self::canonical`] back onto the original command.
+    ///
+    /// Ranges that touch a rewritten segment widen to cover that whole
+    /// segment in the original, since flags may have moved within it.
+    #[must_use]
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        map_rewritten_range(
+            self.rewrites.iter().map(|(orig, canon)| (orig, canon)),
+            range,
+        )
+    }
+}
+
+/// Rewrite segments of `command` that match one of `specs` into canonical
+/// flag form.
+///
+/// The canonical form of a segment is the command words, then every boolean
+/// short flag bundled into one `-xyz` token (aliases applied, first-seen
+/// order, duplicates dropped), then any other options verbatim, then operands
+/// in their original order. `--` and everything after it are kept as-is.
+/// Bundling matches how most pack patterns are written (`-[a-z]*f`).
+///
+/// Returns `None` when no segment changed.
+#[must_use]
+pub fn normalize_flags(command: &str, specs: &[CommandFlagSpec]) -> Option<FlagNormalized> {
+    if specs.is_empty() {
+        return None;
+    }
+
+    let tokens = tokenize_for_normalization(command);
+    let mut canonical = String::with_capacity(command.len() + 8);
+    let mut rewrites = Vec::new();
+    let mut copied_to = 0;
+
+    for segment in tokens.split(|token| token.kind == NormalizeTokenKind::Separator) {
+        let Some(words) = segment
+            .iter()
+            .map(|token| token.text(command))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        let Some(spec) = specs
+            .iter()
+            .find(|spec| words.len() > spec.command.len() && words.starts_with(spec.command))
+        else {
+            continue;
+        };
+
+        let rewritten = canonicalize_segment(spec, &words);
+        let orig = segment[0].byte_range.start..segment[segment.len() - 1].byte_range.end;
+        if rewritten == command[orig.clone()] {
+            continue;
+        }
+
+        canonical.push_str(&command[copied_to..orig.start]);
+        let start = canonical.len();
+        canonical.push_str(&rewritten);
+        rewrites.push((orig.clone(), start..canonical.len()));
+        copied_to = orig.end;
+    }
+
+    if rewrites.is_empty() {
+        return None;
+    }
+    canonical.push_str(&command[copied_to..]);
+    Some(FlagNormalized {
+        canonical,
+        rewrites,
+    })
+}
+
+fn canonicalize_segment(spec: &CommandFlagSpec, words: &[&str]) -> String {
+    let mut short_flags: SmallVec<[char; 8]> = SmallVec::new();
+    let mut options: Vec<String> = Vec::new();
+    let mut operands: Vec<&str> = Vec::new();
+
+    let mut rest = words[spec.command.len()..].iter();
+    while let Some(&word) = rest.next() {
+        if word == "--" {
+            operands.push(word);
+            operands.extend(rest.by_ref());
+            break;
+        }
+
+        if let Some(long) = word.strip_prefix("--") {
+            let (name, value) = long
+                .split_once('=')
+                .map_or((long, None), |(n, v)| (n, Some(v)));
+            if spec.long_with_value.contains(&name) {
+                match value.map_or_else(|| rest.next(), |_| None) {
+                    Some(next) => options.push(format!("{word} {next}")),
+                    None => options.push(word.to_string()),
+                }
+            } else if let (Some(short), None) = (spec.long_alias(name), value) {
+                if !short_flags.contains(&short) {
+                    short_flags.push(short);
+                }
+            } else {
+                options.push(word.to_string());
+            }
+            continue;
+        }
+
+        let Some(bundle) = word.strip_prefix('-').filter(|b| !b.is_empty()) else {
+            operands.push(word);
+            continue;
+        };
+        for (idx, flag) in bundle.char_indices() {
+            let flag = spec.short_alias(flag);
+            if spec.short_with_value.contains(&flag) {
+                let attached = &bundle[idx + flag.len_utf8()..];
+                if !attached.is_empty() {
+                    options.push(format!("-{flag}{attached}"));
+                } else if let Some(next) = rest.next() {
+                    options.push(format!("-{flag} {next}"));
+                } else {
+                    options.push(format!("-{flag}"));
+                }
+                break;
+            }
+            if !short_flags.contains(&flag) {
+                short_flags.push(flag);
+            }
+        }
+    }
+
+    let mut out = spec.command.join(" ");
+    if !short_flags.is_empty() {
+        out.push_str(" -");
+        out.extend(short_flags);
+    }
+    for part in options.iter().map(String::as_str).chain(operands) {
+        out.push(' ');
+        out.push_str(part);
+    }
+    out
+}
+
+/// What kind of character [`fold_confusables`] rewrote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfusableKind {
+    /// Zero-width or otherwise invisible character (removed).
+    Invisible,
+    /// Non-ASCII space such as NBSP (folded to `' '`).
+    Space,
+    /// Non-ASCII dash or hyphen (folded to `'-'`).
+    Dash,
+    /// Letter or symbol that renders like an ASCII one (Cyrillic `р`, fullwidth `ｒ`).
+    Homoglyph,
+}
+
+/// A command with Unicode look-alikes folded to ASCII.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusableFold {
+    /// The folded command text.
+    pub text: String,
+    /// Folded characters as `(original range, folded range, kind)`, in order.
+    edits: Vec<(Range<usize>, Range<usize>, ConfusableKind)>,
+}
+
+impl ConfusableFold {
+    /// Map a byte range in [`Self::text`] back onto the original command.
+    #[must_use]
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        map_rewritten_range(
+            self.edits.iter().map(|(orig, folded, _)| (orig, folded)),
+            range,
+        )
+    }
+
+    /// Find a keyword occurrence in the folded text that a folded character
+    /// was hiding: a homoglyph inside it, or an invisible character, odd space,
+    /// or dash inside or touching it.
+    ///
+    /// Only occurrences in executed parts of the command count; a keyword
+    /// inside quoted data (`git commit -m "don’t rm—this"`) or a comment is
+    /// not an invocation.
+    ///
+    /// Returns the folded range covering the keyword and the offending
+    /// characters.
+    #[must_use]
+    pub fn obfuscated_keyword(&self, keywords: &[&str]) -> Option<Range<usize>> {
+        let spans = crate::context::classify_command(&self.text);
+        let executed = |range: &Range<usize>| {
+            spans.executable_spans().any(|span| {
+                span.byte_range.start <= range.start && range.end <= span.byte_range.end
+            })
+        };
+        let bytes = self.text.as_bytes();
+        let is_word_byte = |idx: usize| {
+            bytes
+                .get(idx)
+                .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        };
+
+        for keyword in keywords.iter().filter(|k| !k.is_empty()) {
+            for (start, _) in self.text.match_indices(keyword) {
+                let end = start + keyword.len();
+                if (start > 0 && is_word_byte(start - 1))
+                    || is_word_byte(end)
+                    || !executed(&(start..end))
+                {
+                    continue;
+                }
+                let mut hit: Option<Range<usize>> = None;
+                for (_, folded, kind) in &self.edits {
+                    let hides = match kind {
+                        ConfusableKind::Homoglyph => folded.start >= start && folded.end <= end,
+                        _ => folded.end >= start && folded.start <= end,
+                    };
+                    if hides {
+                        let range = hit.get_or_insert(start..end);
+                        range.start = range.start.min(folded.start);
+                        range.end = range.end.max(folded.end);
+                    }
+                }
+                if hit.is_some() {
+                    return hit;
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Fold zero-width characters, non-ASCII spaces and dashes, and common
+/// homoglyphs to their ASCII look-alikes.
+///
+/// `r\u{200b}m -rf /` folds to `rm -rf /` and `rm\u{2011}rf` to `rm-rf`, so
+/// patterns see what the user sees. Returns `None` when nothing was folded
+/// (always the case for ASCII input).
+#[must_use]
+pub fn fold_confusables(command: &str) -> Option<ConfusableFold> {
+    if command.is_ascii() {
+        return None;
+    }
+
+    let mut text = String::with_capacity(command.len());
+    let mut edits = Vec::new();
+    for (idx, c) in command.char_indices() {
+        let Some((folded, kind)) = fold_confusable_char(c) else {
+            text.push(c);
+            continue;
+        };
+        let start = text.len();
+        if let Some(folded) = folded {
+            text.push(folded);
+        }
+        edits.push((idx..idx + c.len_utf8(), start..text.len(), kind));
+    }
+
+    (!edits.is_empty()).then_some(ConfusableFold { text, edits })
+}
+
+fn fold_confusable_char(c: char) -> Option<(Option<char>, ConfusableKind)> {
+    let folded = match c {
+        '\u{00AD}' | '\u{180E}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' => {
+            return Some((None, ConfusableKind::Invisible));
+        }
+        '\u{00A0}'
+        | '\u{1680}'
+        | '\u{2000}'..='\u{200A}'
+        | '\u{202F}'
+        | '\u{205F}'
+        | '\u{3000}' => return Some((Some(' '), ConfusableKind::Space)),
+        '\u{2010}'..='\u{2015}' | '\u{2212}' | '\u{FE58}' | '\u{FE63}' | '\u{FF0D}' => {
+            return Some((Some('-'), ConfusableKind::Dash));
+        }
+        // Fullwidth ASCII (ｒｍ) maps onto ASCII by a fixed offset.
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(u32::from(c) - 0xFEE0)?,
+        // Cyrillic
+        'а' => 'a',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' => 'e',
+        'һ' => 'h',
+        'і' => 'i',
+        'ј' => 'j',
+        'о' => 'o',
+        'р' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'ԝ' => 'w',
+        'х' => 'x',
+        'у' => 'y',
+        'А' => 'A',
+        'В' => 'B',
+        'С' => 'C',
+        'Е' => 'E',
+        'Н' => 'H',
+        'І' => 'I',
+        'Ј' => 'J',
+        'К' => 'K',
+        'М' => 'M',
+        'О' => 'O',
+        'Р' => 'P',
+        'Ѕ' => 'S',
+        'Т' => 'T',
+        'Х' => 'X',
+        'Ү' => 'Y',
+        // Greek
+        'ο' => 'o',
+        'ρ' => 'p',
+        'ν' => 'v',
+        'ι' => 'i',
+        'Α' => 'A',
+        'Β' => 'B',
+        'Ε' => 'E',
+        'Ζ' => 'Z',
+        'Η' => 'H',
+        'Ι' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' => 'O',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Υ' => 'Y',
+        'Χ' => 'X',
+        _ => return None,
+    };
+    Some((Some(folded), ConfusableKind::Homoglyph))
+}
+
+/// Map `range` in rewritten text back onto the text it was rewritten from,
+/// given the rewritten spans as `(original, rewritten)` pairs in order.
+///
+/// Offsets inside a rewritten span widen to cover the whole original span;
+/// offsets past one keep their distance from its end.
+fn map_rewritten_range<'a>(
+    rewrites: impl Iterator<Item = (&'a Range<usize>, &'a Range<usize>)> + Clone,
+    range: Range<usize>,
+) -> Range<usize> {
+    let map_offset = |offset: usize, is_end: bool| {
+        let (mut orig_end, mut rewritten_end) = (0, 0);
+        for (orig, rewritten) in rewrites.clone() {
+            if offset < rewritten.start || (is_end && offset == rewritten.start) {
+                break;
+            }
+            if offset < rewritten.end || (is_end && offset == rewritten.end) {
+                return if is_end { orig.end } else { orig.start };
+            }
+            (orig_end, rewritten_end) = (orig.end, rewritten.end);
+        }
+        offset - rewritten_end + orig_end
+    };
+    map_offset(range.start, false)..map_offset(range.end, true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1747,6 +2455,83 @@ mod tests {
         assert_eq!(result.stripped_wrappers.len(), 2);
     }
 
+    #[test]
+    fn test_exec_wrappers_skip_their_arguments() {
+        for (command, wrapper) in [
+            ("nohup git push --force", "nohup"),
+            ("timeout 60 git push --force", "timeout"),
+            ("timeout -s KILL -k 5 1.5m git push --force", "timeout"),
+            (
+                "timeout --signal=TERM --preserve-status 10s git push --force",
+                "timeout",
+            ),
+            ("nice -n 19 git push --force", "nice"),
+            ("nice -n19 git push --force", "nice"),
+            ("nice -19 git push --force", "nice"),
+            ("/usr/bin/nice --adjustment 5 git push --force", "nice"),
+            ("stdbuf -o0 git push --force", "stdbuf"),
+            ("stdbuf -oL -e 0 git push --force", "stdbuf"),
+            ("xargs -I{} git push --force", "xargs"),
+            ("xargs -0 -I {} -P 4 git push --force", "xargs"),
+        ] {
+            let result = strip_wrapper_prefixes(command);
+            assert_eq!(result.normalized, "git push --force", "{command}");
+            assert_eq!(result.stripped_wrappers[0].wrapper_type, wrapper);
+        }
+    }
+
+    #[test]
+    fn test_exec_wrapper_chain() {
+        let result =
+            strip_wrapper_prefixes("env FOO=1 timeout 60 nice -n 5 nohup git push --force");
+        assert_eq!(result.normalized, "git push --force");
+        let wrappers: Vec<_> = result
+            .stripped_wrappers
+            .iter()
+            .map(|w| w.wrapper_type)
+            .collect();
+        assert_eq!(wrappers, ["env", "timeout", "nice", "nohup"]);
+        assert_eq!(result.stripped_wrappers[1].stripped_text, "timeout 60");
+    }
+
+    #[test]
+    fn test_exec_wrappers_not_stripped_when_ambiguous() {
+        for command in [
+            "nohup",
+            "nice",
+            "timeout 60",
+            "timeout git push --force",
+            "timeout --help",
+            "nice -x git push --force",
+            "stdbuf -o",
+            "xargs git push --force",
+            "xargs rm -rf",
+            "nohup | tee log",
+            "nicely git push --force",
+        ] {
+            assert!(
+                !strip_wrapper_prefixes(command).was_normalized(),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dequote_after_exec_wrappers() {
+        assert_eq!(
+            dequote_segment_command_words("ls && timeout -s KILL 60 g\"i\"t reset --hard"),
+            "ls && timeout -s KILL 60 git reset --hard"
+        );
+        assert_eq!(
+            dequote_segment_command_words("ls | xargs -I {} 'git' reset --hard {}"),
+            "ls | xargs -I {} git reset --hard {}"
+        );
+        assert_eq!(
+            dequote_segment_command_words("nice -n 19 \"rm\" -rf \"/tmp/x\""),
+            "nice -n 19 rm -rf \"/tmp/x\""
+        );
+    }
+
     #[test]
     fn test_env_split_string_handling() {
         // env -S treats the argument as a script/command line.
@@ -2021,3 +2806,147 @@ fn test_mixed_quoting_normalization() {
         "g'i't command should normalize"
     );
 }
+
+#[cfg(test)]
+mod flag_normalization_tests {
+    use super::*;
+
+    const RM: CommandFlagSpec = CommandFlagSpec::new(&["rm"])
+        .long_aliases(&[("recursive", 'r'), ("force", 'f')])
+        .short_aliases(&[('R', 'r')]);
+    const GIT_CLEAN: CommandFlagSpec = CommandFlagSpec::new(&["git", "clean"])
+        .long_aliases(&[("force", 'f')])
+        .short_with_value(&['e'])
+        .long_with_value(&["exclude"]);
+
+    fn canonical(command: &str, specs: &[CommandFlagSpec]) -> String {
+        normalize_flags(command, specs).map_or_else(|| command.to_string(), |n| n.canonical)
+    }
+
+    #[test]
+    fn rm_flag_spellings_collapse_into_one_bundle() {
+        for command in [
+            "rm -rf /etc",
+            "rm -r -f /etc",
+            "rm --recursive --force /etc",
+            "rm -R --force /etc",
+            "rm -rf -r /etc",
+        ] {
+            assert_eq!(canonical(command, &[RM]), "rm -rf /etc", "{command}");
+        }
+        assert_eq!(canonical("rm -f --recursive /etc", &[RM]), "rm -fr /etc");
+    }
+
+    #[test]
+    fn operands_keep_order_and_follow_flags() {
+        assert_eq!(canonical("rm a -r b -f", &[RM]), "rm -rf a b");
+        assert_eq!(canonical("rm -r -- -f x", &[RM]), "rm -r -- -f x");
+    }
+
+    #[test]
+    fn valued_and_unknown_options_are_kept_verbatim() {
+        assert_eq!(
+            canonical("git clean -fe build --exclude=tmp -d", &[GIT_CLEAN]),
+            "git clean -fd -e build --exclude=tmp"
+        );
+        assert_eq!(
+            canonical("git clean --exclude tmp --force", &[GIT_CLEAN]),
+            "git clean -f --exclude tmp"
+        );
+    }
+
+    #[test]
+    fn only_matching_segments_are_rewritten() {
+        let normalized = normalize_flags("echo -rf && rm -f -r x; ls -la", &[RM]).unwrap();
+        assert_eq!(normalized.canonical, "echo -rf && rm -fr x; ls -la");
+        assert!(normalize_flags("git clean -n", &[RM]).is_none());
+        assert!(normalize_flags("rm -rf x", &[RM]).is_none());
+        assert!(normalize_flags("rm -r -f x", &[]).is_none());
+    }
+
+    #[test]
+    fn canonical_ranges_map_back_to_original() {
+        let command = "echo hi && rm --recursive --force x; echo bye";
+        let normalized = normalize_flags(command, &[RM]).unwrap();
+        assert_eq!(normalized.canonical, "echo hi && rm -rf x; echo bye");
+
+        let canon = &normalized.canonical;
+        let rm_start = canon.find("rm").unwrap();
+        let rm_end = canon.find(';').unwrap();
+        let mapped = normalized.original_range(rm_start..rm_end);
+        assert_eq!(&command[mapped], "rm --recursive --force x");
+
+        let bye = canon.find("bye").unwrap();
+        let mapped = normalized.original_range(bye..bye + 3);
+        assert_eq!(&command[mapped], "bye");
+
+        assert_eq!(normalized.original_range(0..4), 0..4);
+    }
+}
+
+#[cfg(test)]
+mod confusable_fold_tests {
+    use super::*;
+
+    #[test]
+    fn ascii_commands_are_not_folded() {
+        assert!(fold_confusables("rm -rf /").is_none());
+        assert!(fold_confusables("echo café").is_none());
+    }
+
+    #[test]
+    fn folds_invisible_space_dash_and_homoglyphs() {
+        let cases = [
+            ("r\u{200b}m -rf /", "rm -rf /"),
+            ("rm\u{00a0}-rf /", "rm -rf /"),
+            ("rm \u{2011}rf /", "rm -rf /"),
+            ("g\u{0456}t reset --hard", "git reset --hard"),
+            ("\u{ff47}\u{ff49}\u{ff54} push --force", "git push --force"),
+            ("\u{feff}rm -rf /", "rm -rf /"),
+        ];
+        for (command, expected) in cases {
+            let folded = fold_confusables(command).expect("should fold");
+            assert_eq!(folded.text, expected, "{command:?}");
+        }
+    }
+
+    #[test]
+    fn folded_ranges_map_back_to_original() {
+        let command = "echo hi; r\u{200b}m -rf /";
+        let folded = fold_confusables(command).unwrap();
+        let start = folded.text.find("rm").unwrap();
+        let mapped = folded.original_range(start..folded.text.len());
+        assert_eq!(&command[mapped], "r\u{200b}m -rf /");
+
+        let flags = folded.text.find("-rf").unwrap();
+        let mapped = folded.original_range(flags..flags + 3);
+        assert_eq!(&command[mapped], "-rf");
+    }
+
+    #[test]
+    fn obfuscated_keyword_requires_fold_touching_a_keyword() {
+        let keywords = ["rm", "git"];
+        let hit = |command: &str| {
+            let folded = fold_confusables(command)?;
+            folded
+                .obfuscated_keyword(&keywords)
+                .map(|range| command[folded.original_range(range)].to_string())
+        };
+
+        assert_eq!(hit("r\u{200b}m -rf /").as_deref(), Some("r\u{200b}m"));
+        assert_eq!(hit("rm\u{2011}rf /").as_deref(), Some("rm\u{2011}"));
+        assert_eq!(hit("g\u{0456}t status").as_deref(), Some("g\u{0456}t"));
+        // Folded characters away from any keyword, or inside a longer word.
+        assert_eq!(hit("echo \u{0440}\u{0435}\u{0441}\u{0442} && ls"), None);
+        assert_eq!(hit("git commit -m 'caf\u{00e9}\u{00a0}ok'"), None);
+        assert_eq!(hit("terraform\u{00a0}plan"), None);
+        // Keywords inside quoted data or comments are not invocations.
+        assert_eq!(hit("git commit -m \"don\u{2019}t rm\u{2014}this\""), None);
+        assert_eq!(hit("ls # r\u{200b}m later"), None);
+        // ...but inline code is executed.
+        assert_eq!(
+            hit("bash -c \"r\u{200b}m -rf /tmp/x\"").as_deref(),
+            Some("r\u{200b}m")
+        );
+    }
+}
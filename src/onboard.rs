@@ -0,0 +1,617 @@
+//! Project onboarding analysis for `dcg onboard --analyze`.
+//!
+//! Before dcg is enabled in an existing project, this finds the commands the
+//! project already runs that would be blocked: recipes in Makefiles and
+//! justfiles, `package.json` scripts, CI configs and shell scripts (via the
+//! `dcg scan` extractors), plus the user's shell history. Findings are grouped
+//! by rule into a summary, and rules the project clearly depends on are
+//! proposed as a starter allowlist.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::packs::{REGISTRY, Severity};
+use crate::scan::{
+    ExtractedCommand, ScanDecision, ScanEvalContext, ScanFinding, ScanFormat, ScanOptions,
+    ScanRedactMode, ScanReport, collect_files, evaluate_extracted_command, in_dependency_dir,
+    scan_paths,
+};
+
+/// Report schema version for `--format json`.
+pub const ONBOARD_SCHEMA_VERSION: u32 = 1;
+
+/// Most locations and example commands kept per rule.
+const MAX_SAMPLES: usize = 3;
+
+/// Shell history file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl HistoryShell {
+    /// Guess the format from a history file name.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if name.contains("zsh") {
+            Self::Zsh
+        } else if name.contains("fish") {
+            Self::Fish
+        } else {
+            Self::Bash
+        }
+    }
+
+    const fn extractor_id(self) -> &'static str {
+        match self {
+            Self::Bash => "history.bash",
+            Self::Zsh => "history.zsh",
+            Self::Fish => "history.fish",
+        }
+    }
+}
+
+/// History files of the current user that exist: `$HISTFILE`, then the
+/// bash, zsh and fish defaults.
+#[must_use]
+pub fn default_history_files() -> Vec<(HistoryShell, PathBuf)> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(histfile) = std::env::var_os("HISTFILE").filter(|v| !v.is_empty()) {
+        candidates.push(PathBuf::from(histfile));
+    }
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join(".bash_history"));
+        let zdotdir = std::env::var_os("ZDOTDIR").map_or_else(|| home.clone(), PathBuf::from);
+        candidates.push(zdotdir.join(".zsh_history"));
+    }
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")));
+    if let Some(data_dir) = data_dir {
+        candidates.push(data_dir.join("fish").join("fish_history"));
+    }
+
+    let mut seen = BTreeSet::new();
+    candidates
+        .into_iter()
+        .filter(|path| path.is_file() && seen.insert(path.clone()))
+        .map(|path| (HistoryShell::from_path(&path), path))
+        .collect()
+}
+
+/// Parse a history file into `(line, command)` pairs, oldest first.
+///
+/// Bash timestamp comments (`#1700000000`), zsh extended-history prefixes
+/// (`: 1700000000:0;`) and fish's YAML-like `- cmd:` records are understood.
+#[must_use]
+pub fn parse_history(shell: HistoryShell, content: &str) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    match shell {
+        HistoryShell::Bash => {
+            for (idx, line) in content.lines().enumerate() {
+                let line = line.trim();
+                let is_timestamp = line.len() > 1 && line[1..].bytes().all(|b| b.is_ascii_digit());
+                if line.is_empty() || (line.starts_with('#') && is_timestamp) {
+                    continue;
+                }
+                out.push((idx + 1, line.to_string()));
+            }
+        }
+        HistoryShell::Zsh => {
+            let mut pending: Option<(usize, String)> = None;
+            for (idx, line) in content.lines().enumerate() {
+                if let Some((start, mut command)) = pending.take() {
+                    command.push('\n');
+                    command.push_str(line);
+                    push_zsh_entry(&mut out, &mut pending, start, command);
+                    continue;
+                }
+                let command = match line.strip_prefix(": ") {
+                    Some(rest) => rest.split_once(';').map_or(line, |(_, cmd)| cmd),
+                    None => line,
+                };
+                push_zsh_entry(&mut out, &mut pending, idx + 1, command.to_string());
+            }
+            if let Some((start, command)) = pending {
+                out.push((start, command));
+            }
+        }
+        HistoryShell::Fish => {
+            for (idx, line) in content.lines().enumerate() {
+                if let Some(command) = line.strip_prefix("- cmd: ") {
+                    let command = unescape_fish(command);
+                    if !command.trim().is_empty() {
+                        out.push((idx + 1, command));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// zsh stores multi-line commands with a trailing backslash per line.
+fn push_zsh_entry(
+    out: &mut Vec<(usize, String)>,
+    pending: &mut Option<(usize, String)>,
+    start: usize,
+    mut command: String,
+) {
+    if command.ends_with('\\') {
+        command.pop();
+        *pending = Some((start, command));
+    } else if !command.trim().is_empty() {
+        out.push((start, command));
+    }
+}
+
+fn unescape_fish(command: &str) -> String {
+    let mut out = String::with_capacity(command.len());
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// The newest `limit` commands of a history file as extracted commands.
+#[must_use]
+pub fn history_commands(
+    shell: HistoryShell,
+    path: &Path,
+    content: &str,
+    limit: usize,
+) -> Vec<ExtractedCommand> {
+    let entries = parse_history(shell, content);
+    let skip = entries.len().saturating_sub(limit);
+    let file = path.display().to_string();
+    entries
+        .into_iter()
+        .skip(skip)
+        .map(|(line, command)| ExtractedCommand {
+            file: file.clone(),
+            line,
+            col: None,
+            extractor_id: shell.extractor_id().to_string(),
+            command,
+            metadata: None,
+        })
+        .collect()
+}
+
+/// Scan options used for onboarding: everything is reported, nothing redacted.
+#[must_use]
+pub fn scan_options() -> ScanOptions {
+    ScanOptions {
+        format: ScanFormat::Json,
+        fail_on: crate::scan::ScanFailOn::None,
+        max_file_size_bytes: 1024 * 1024,
+        max_findings: usize::MAX,
+        redact: ScanRedactMode::None,
+        truncate: 0,
+        jobs: 0,
+    }
+}
+
+/// Scan the project at `root` with the `dcg scan` extractors, skipping
+/// dependency and build directories. Finding paths are relative to `root`.
+///
+/// # Errors
+///
+/// Returns an error if the scan fails.
+pub fn scan_project(
+    root: &Path,
+    config: &Config,
+    ctx: &ScanEvalContext,
+) -> Result<ScanReport, String> {
+    let root = std::fs::canonicalize(root)
+        .map_err(|e| format!("Cannot analyze {}: {e}", root.display()))?;
+    let files: Vec<PathBuf> = collect_files(std::slice::from_ref(&root))
+        .into_iter()
+        .filter(|file| !in_dependency_dir(file, &root))
+        .collect();
+    let mut report = scan_paths(&files, &scan_options(), config, ctx, &[], &[], Some(&root))?;
+    for finding in &mut report.findings {
+        if let Ok(relative) = Path::new(&finding.file).strip_prefix(&root) {
+            finding.file = relative.display().to_string();
+        }
+    }
+    Ok(report)
+}
+
+/// Evaluate history commands, keeping the ones that would not be allowed.
+#[must_use]
+pub fn evaluate_history(
+    commands: &[ExtractedCommand],
+    config: &Config,
+    ctx: &ScanEvalContext,
+) -> Vec<ScanFinding> {
+    let options = scan_options();
+    commands
+        .iter()
+        .filter_map(|command| evaluate_extracted_command(command, &options, config, ctx))
+        .filter(|finding| finding.decision != ScanDecision::Allow)
+        .collect()
+}
+
+/// How often one rule fired during the analysis.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleUsage {
+    pub rule_id: String,
+    pub decision: ScanDecision,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Matches in project files.
+    pub project_hits: usize,
+    /// Matches in shell history.
+    pub history_hits: usize,
+    /// A few `file:line` locations in the project.
+    pub locations: Vec<String>,
+    /// A few distinct matched commands.
+    pub examples: Vec<String>,
+    /// The rule is critical; it is proposed commented out.
+    pub critical: bool,
+    /// Included in the starter allowlist.
+    pub proposed: bool,
+}
+
+/// Result of `dcg onboard --analyze`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardReport {
+    pub schema_version: u32,
+    pub root: String,
+    pub files_scanned: usize,
+    pub history_files: Vec<String>,
+    pub history_commands: usize,
+    /// Rules that fired, most frequent first.
+    pub rules: Vec<RuleUsage>,
+    /// Blocked commands without a rule ID; these cannot be allowlisted by rule.
+    pub unattributed: usize,
+}
+
+/// Inputs to [`summarize`].
+#[derive(Debug, Default)]
+pub struct OnboardInputs<'a> {
+    pub root: &'a str,
+    pub project: &'a [ScanFinding],
+    pub history: &'a [ScanFinding],
+    pub files_scanned: usize,
+    pub history_files: Vec<String>,
+    pub history_commands: usize,
+    /// History matches needed before a rule seen only in history is proposed.
+    pub min_history: usize,
+}
+
+/// Group findings by rule and decide which rules to propose.
+///
+/// A denied rule is proposed when a project file uses it, or when it shows up
+/// at least `min_history` times in shell history. Warn-only rules never block
+/// and are reported without a proposal.
+#[must_use]
+pub fn summarize(inputs: &OnboardInputs<'_>) -> OnboardReport {
+    let mut rules: BTreeMap<String, RuleUsage> = BTreeMap::new();
+    let mut unattributed = 0usize;
+
+    let tagged = inputs
+        .project
+        .iter()
+        .map(|finding| (true, finding))
+        .chain(inputs.history.iter().map(|finding| (false, finding)));
+    for (in_project, finding) in tagged {
+        let Some(rule_id) = finding.rule_id.as_deref() else {
+            unattributed += 1;
+            continue;
+        };
+        let usage = rules
+            .entry(rule_id.to_string())
+            .or_insert_with(|| RuleUsage {
+                rule_id: rule_id.to_string(),
+                decision: finding.decision,
+                reason: finding.reason.clone(),
+                project_hits: 0,
+                history_hits: 0,
+                locations: Vec::new(),
+                examples: Vec::new(),
+                critical: is_critical(rule_id),
+                proposed: false,
+            });
+        if finding.decision == ScanDecision::Deny {
+            usage.decision = ScanDecision::Deny;
+        }
+        if in_project {
+            usage.project_hits += 1;
+            if usage.locations.len() < MAX_SAMPLES {
+                let location = format!("{}:{}", finding.file, finding.line);
+                usage.locations.push(match &finding.script {
+                    Some(script) => format!("{location} ({script})"),
+                    None => location,
+                });
+            }
+        } else {
+            usage.history_hits += 1;
+        }
+        if usage.examples.len() < MAX_SAMPLES
+            && !usage.examples.contains(&finding.extracted_command)
+        {
+            usage.examples.push(finding.extracted_command.clone());
+        }
+    }
+
+    let mut rules: Vec<RuleUsage> = rules
+        .into_values()
+        .map(|mut usage| {
+            usage.proposed = usage.decision == ScanDecision::Deny
+                && (usage.project_hits > 0 || usage.history_hits >= inputs.min_history.max(1));
+            usage
+        })
+        .collect();
+    rules.sort_by(|a, b| {
+        (b.project_hits + b.history_hits)
+            .cmp(&(a.project_hits + a.history_hits))
+            .then_with(|| a.rule_id.cmp(&b.rule_id))
+    });
+
+    OnboardReport {
+        schema_version: ONBOARD_SCHEMA_VERSION,
+        root: inputs.root.to_string(),
+        files_scanned: inputs.files_scanned,
+        history_files: inputs.history_files.clone(),
+        history_commands: inputs.history_commands,
+        rules,
+        unattributed,
+    }
+}
+
+fn is_critical(rule_id: &str) -> bool {
+    let Some((pack_id, name)) = rule_id.split_once(':') else {
+        return false;
+    };
+    REGISTRY.get(pack_id).is_some_and(|pack| {
+        pack.destructive_patterns
+            .iter()
+            .any(|pattern| pattern.name == Some(name) && pattern.severity == Severity::Critical)
+    })
+}
+
+/// Render the proposed rules as an allowlist file that `dcg allowlist import`
+/// accepts. Critical rules are included commented out so they get a human
+/// decision rather than a blanket allow.
+#[must_use]
+pub fn starter_allowlist(report: &OnboardReport) -> String {
+    let mut out = String::from(
+        "# Starter allowlist proposed by `dcg onboard --analyze`.\n\
+         # Review every entry, then: dcg allowlist import <this file> --layer project\n",
+    );
+    for usage in report.rules.iter().filter(|usage| usage.proposed) {
+        let reason = proposal_reason(usage);
+        let entry = format!(
+            "[[allow]]\nrule = {}\nreason = {}\n",
+            toml_string(&usage.rule_id),
+            toml_string(&reason)
+        );
+        out.push('\n');
+        if usage.critical {
+            out.push_str("# Critical rule: prefer an exact_command or path-scoped entry.\n");
+            for line in entry.lines() {
+                let _ = writeln!(out, "# {line}");
+            }
+        } else {
+            out.push_str(&entry);
+        }
+    }
+    out
+}
+
+fn proposal_reason(usage: &RuleUsage) -> String {
+    let mut parts = Vec::new();
+    if let Some(location) = usage.locations.first() {
+        let more = usage.project_hits - 1;
+        parts.push(if more > 0 {
+            format!("used in {location} (+{more} more)")
+        } else {
+            format!("used in {location}")
+        });
+    }
+    if usage.history_hits > 0 {
+        parts.push(format!("{}x in shell history", usage.history_hits));
+    }
+    format!("onboarding: {}", parts.join(", "))
+}
+
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::ScanSeverity;
+
+    fn finding(file: &str, line: usize, command: &str, rule_id: Option<&str>) -> ScanFinding {
+        ScanFinding {
+            file: file.to_string(),
+            line,
+            col: None,
+            extractor_id: "test".to_string(),
+            extracted_command: command.to_string(),
+            decision: ScanDecision::Deny,
+            severity: ScanSeverity::Error,
+            rule_id: rule_id.map(ToString::to_string),
+            reason: Some("test reason".to_string()),
+            suggestion: None,
+            script: None,
+            yaml_path: None,
+        }
+    }
+
+    #[test]
+    fn parses_bash_zsh_and_fish_history() {
+        let bash = "#1700000000\ngit status\n\n#1700000001\ngit reset --hard\n";
+        assert_eq!(
+            parse_history(HistoryShell::Bash, bash),
+            vec![
+                (2, "git status".to_string()),
+                (5, "git reset --hard".to_string())
+            ]
+        );
+
+        let zsh = ": 1700000000:0;git status\n: 1700000001:0;echo a \\\nb\nls\n";
+        assert_eq!(
+            parse_history(HistoryShell::Zsh, zsh),
+            vec![
+                (1, "git status".to_string()),
+                (2, "echo a \nb".to_string()),
+                (4, "ls".to_string())
+            ]
+        );
+
+        let fish = "- cmd: git clean -fd\n  when: 1700000000\n- cmd: echo a\\nb\n";
+        assert_eq!(
+            parse_history(HistoryShell::Fish, fish),
+            vec![
+                (1, "git clean -fd".to_string()),
+                (3, "echo a\nb".to_string())
+            ]
+        );
+
+        assert_eq!(
+            HistoryShell::from_path(Path::new("/home/u/.zsh_history")),
+            HistoryShell::Zsh
+        );
+    }
+
+    #[test]
+    fn history_commands_keeps_newest_entries() {
+        let commands = history_commands(
+            HistoryShell::Bash,
+            Path::new(".bash_history"),
+            "one\ntwo\nthree\n",
+            2,
+        );
+        let texts: Vec<&str> = commands.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(texts, ["two", "three"]);
+        assert_eq!(commands[0].line, 2);
+        assert_eq!(commands[0].extractor_id, "history.bash");
+    }
+
+    #[test]
+    fn proposes_project_rules_and_frequent_history_rules() {
+        let project = [
+            ScanFinding {
+                script: Some("distclean".to_string()),
+                ..finding(
+                    "Makefile",
+                    4,
+                    "git clean -fdx",
+                    Some("core.git:clean-force"),
+                )
+            },
+            finding(
+                "justfile",
+                9,
+                "git clean -fdx",
+                Some("core.git:clean-force"),
+            ),
+        ];
+        let history = [
+            finding(
+                ".bash_history",
+                1,
+                "git stash clear",
+                Some("core.git:stash-clear"),
+            ),
+            finding(
+                ".bash_history",
+                7,
+                "docker system prune",
+                Some("containers.docker:system-prune"),
+            ),
+            finding(
+                ".bash_history",
+                8,
+                "docker system prune",
+                Some("containers.docker:system-prune"),
+            ),
+            finding(".bash_history", 9, "weird", None),
+        ];
+        let report = summarize(&OnboardInputs {
+            root: ".",
+            project: &project,
+            history: &history,
+            files_scanned: 2,
+            min_history: 2,
+            ..OnboardInputs::default()
+        });
+
+        assert_eq!(report.unattributed, 1);
+        let ids: Vec<&str> = report.rules.iter().map(|r| r.rule_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "containers.docker:system-prune",
+                "core.git:clean-force",
+                "core.git:stash-clear"
+            ]
+        );
+        let clean = &report.rules[1];
+        assert_eq!(clean.project_hits, 2);
+        assert_eq!(clean.locations, ["Makefile:4 (distclean)", "justfile:9"]);
+        assert_eq!(clean.examples, ["git clean -fdx"]);
+        assert!(clean.proposed);
+        assert!(report.rules[0].proposed);
+        assert!(!report.rules[2].proposed);
+
+        let toml = starter_allowlist(&report);
+        assert!(toml.contains("rule = \"core.git:clean-force\""));
+        assert!(toml.contains("reason = \"onboarding: used in Makefile:4 (distclean) (+1 more)\""));
+        assert!(!toml.contains("core.git:stash-clear"));
+        let imported = crate::allowlist_exchange::parse_import(
+            &toml,
+            crate::allowlist_exchange::ExchangeFormat::Toml,
+        )
+        .expect("starter allowlist is importable");
+        assert!(!imported.is_empty());
+    }
+
+    #[test]
+    fn critical_rules_are_commented_out() {
+        let project = [finding(
+            "deploy.sh",
+            3,
+            "git reset --hard",
+            Some("core.git:reset-hard"),
+        )];
+        let report = summarize(&OnboardInputs {
+            root: ".",
+            project: &project,
+            min_history: 3,
+            ..OnboardInputs::default()
+        });
+        assert!(report.rules[0].critical);
+        assert!(report.rules[0].proposed);
+
+        let toml = starter_allowlist(&report);
+        assert!(toml.contains("# rule = \"core.git:reset-hard\""));
+        assert!(!toml.contains("\nrule = "));
+    }
+}
@@ -11,9 +11,18 @@
 
 use super::theme::{BorderStyle, Severity, Theme};
 use crate::highlight::{HighlightSpan, format_highlighted_command};
+#[cfg(not(feature = "rich-output"))]
+use crate::output::box_fits;
+#[cfg(not(feature = "rich-output"))]
+use crate::output::hyperlink::hyperlink;
 #[cfg(feature = "rich-output")]
 use crate::output::rich_theme::{RichThemeExt, color_to_markup};
 use crate::output::terminal_width;
+use crate::output::text::display_width;
+#[cfg(not(feature = "rich-output"))]
+use crate::output::text::padding_to;
+#[cfg(not(feature = "rich-output"))]
+use crate::sarif::rule_help_uri;
 #[cfg(not(feature = "rich-output"))]
 use ratatui::style::Color;
 #[cfg(feature = "rich-output")]
@@ -38,6 +47,10 @@ pub struct DenialBox {
     pub alternatives: Vec<String>,
     /// Optional allow-once code.
     pub allow_once_code: Option<String>,
+    /// Additional lower-ranked matches (pattern id, severity).
+    pub other_matches: Vec<(String, Severity)>,
+    /// What the command would delete (e.g. "would delete 120 files ...").
+    pub impact: Option<String>,
 }
 
 impl DenialBox {
@@ -57,6 +70,8 @@ impl DenialBox {
             explanation: None,
             alternatives: Vec::new(),
             allow_once_code: None,
+            other_matches: Vec::new(),
+            impact: None,
         }
     }
 
@@ -89,6 +104,93 @@ impl DenialBox {
         self
     }
 
+    /// Add the other patterns that also matched the command.
+    #[must_use]
+    pub fn with_other_matches(mut self, other_matches: Vec<(String, Severity)>) -> Self {
+        self.other_matches = other_matches;
+        self
+    }
+
+    /// Add an estimate of what the command would delete.
+    #[must_use]
+    pub fn with_impact(mut self, impact: impl Into<String>) -> Self {
+        self.impact = Some(impact.into());
+        self
+    }
+
+    /// The impact estimate as a labelled line.
+    #[cfg(not(feature = "rich-output"))]
+    fn impact_line(&self) -> Option<String> {
+        self.impact
+            .as_ref()
+            .map(|impact| format!("Impact: {impact}"))
+    }
+
+    /// Pattern/pack lines followed by one line per additional match.
+    fn pattern_lines(&self, severity_label: &str) -> Vec<String> {
+        let mut lines = format_pattern_lines(&self.pattern_id, severity_label);
+        lines.extend(self.other_matches.iter().map(|(pattern_id, severity)| {
+            format!(
+                "Also matched: {pattern_id} ({})",
+                format!("{severity:?}").to_lowercase()
+            )
+        }));
+        lines
+    }
+
+    /// Pattern lines for the terminal layouts, the first linked to the rule's
+    /// documentation when hyperlinks are enabled.
+    #[cfg(not(feature = "rich-output"))]
+    fn linked_pattern_lines(&self, theme: &Theme) -> Vec<String> {
+        let mut lines = self.pattern_lines(theme.severity_label(self.severity));
+        if theme.colors_enabled {
+            if let Some(first) = lines.first_mut() {
+                *first = hyperlink(first, &rule_help_uri(&self.pattern_id));
+            }
+        }
+        lines
+    }
+
+    /// Widest line the bordered layouts cannot wrap or window: the header,
+    /// the pattern lines, the bulleted alternatives, and the longest word of
+    /// the explanation. The command is windowed to fit, so it only needs room
+    /// for a useful excerpt.
+    #[cfg(not(feature = "rich-output"))]
+    fn content_min_width(&self, theme: &Theme) -> usize {
+        const MIN_COMMAND_COLUMNS: usize = 20;
+        let header = display_width(" \u{26d4}  BLOCKED: Destructive Command Detected ");
+        let pattern = self
+            .pattern_lines(theme.severity_label(self.severity))
+            .iter()
+            .map(|line| display_width(line))
+            .max()
+            .unwrap_or(0);
+        let alternatives = self
+            .alternatives
+            .iter()
+            .map(|alt| display_width(alt) + 4)
+            .max()
+            .unwrap_or(0);
+        let explanation = self
+            .explanation
+            .iter()
+            .chain(&self.impact)
+            .flat_map(|text| text.split_whitespace())
+            .map(display_width)
+            .max()
+            .unwrap_or(0);
+        [
+            MIN_COMMAND_COLUMNS,
+            header,
+            pattern,
+            alternatives,
+            explanation,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+    }
+
     /// Render the denial box with the given theme.
     ///
     /// Uses rich_rust when the feature is enabled, otherwise falls back to
@@ -101,23 +203,18 @@ impl DenialBox {
             self.render_rich(theme)
         }
         #[cfg(not(feature = "rich-output"))]
-        match theme.border_style {
-            BorderStyle::Unicode => {
-                let output = self.render_unicode(theme);
-                if theme.colors_enabled {
-                    output
-                } else {
-                    strip_ansi_codes(&output)
-                }
-            }
-            BorderStyle::Ascii => self.render_ascii(theme),
-            BorderStyle::None => {
-                let output = self.render_minimal(theme);
-                if theme.colors_enabled {
-                    output
-                } else {
-                    strip_ansi_codes(&output)
-                }
+        {
+            // Too narrow for the box: drop the borders rather than overflow them.
+            let fits = box_fits(self.content_min_width(theme), terminal_width());
+            let output = match theme.border_style {
+                BorderStyle::Unicode if fits => self.render_unicode(theme),
+                BorderStyle::Ascii if fits => return self.render_ascii(theme),
+                _ => self.render_minimal(theme),
+            };
+            if theme.colors_enabled {
+                output
+            } else {
+                strip_ansi_codes(&output)
             }
         }
     }
@@ -128,8 +225,7 @@ impl DenialBox {
         use rich_rust::r#box::{ASCII, DOUBLE, HEAVY, MINIMAL, ROUNDED};
         use rich_rust::prelude::*;
 
-        let pattern_lines =
-            format_pattern_lines(&self.pattern_id, theme.severity_label(self.severity));
+        let pattern_lines = self.pattern_lines(theme.severity_label(self.severity));
         let width = terminal_width().saturating_sub(8).max(40) as usize;
 
         // Build content as a Vec of lines
@@ -143,6 +239,9 @@ impl DenialBox {
         // 2. Command with highlighting
         // Note: We use manual highlighting for now, but rich_rust Syntax could be used later
         lines.push(format!("[dim]Command:[/]  [bold]{}[/]", self.command));
+        if let Some(impact) = &self.impact {
+            lines.push(format!("[yellow]Impact:[/]   {impact}"));
+        }
 
         // 3. Explanation
         if let Some(explanation) = &self.explanation {
@@ -207,7 +306,7 @@ impl DenialBox {
         let mut output = String::new();
         let width = terminal_width().saturating_sub(4).max(40) as usize;
         let severity_label = format!("{:?}", self.severity).to_uppercase();
-        let pattern_lines = format_pattern_lines(&self.pattern_id, &severity_label);
+        let pattern_lines = self.pattern_lines(&severity_label);
 
         // Header
         let _ = writeln!(output, "BLOCKED: Destructive Command Detected");
@@ -221,6 +320,9 @@ impl DenialBox {
         if let Some(label) = &highlighted.label_line {
             let _ = writeln!(output, "           {label}");
         }
+        if let Some(impact) = &self.impact {
+            let _ = writeln!(output, "  Impact:  {impact}");
+        }
         let _ = writeln!(output);
 
         // Explanation
@@ -254,17 +356,16 @@ impl DenialBox {
     #[cfg(not(feature = "rich-output"))]
     #[allow(clippy::too_many_lines)]
     fn render_unicode(&self, theme: &Theme) -> String {
-        let width = terminal_width().saturating_sub(4).max(40) as usize;
+        let width = terminal_width().saturating_sub(4) as usize;
         let mut output = String::new();
         let severity_code = severity_color_code(theme, self.severity);
         let success_code = ansi_color_code(theme.success_color);
-        let pattern_lines =
-            format_pattern_lines(&self.pattern_id, theme.severity_label(self.severity));
+        let pattern_lines = self.linked_pattern_lines(theme);
         let explanation_label = format!("\x1b[1;{}mExplanation:\x1b[0m", &severity_code);
 
         // Top border with header
         let header = " \u{26d4}  BLOCKED: Destructive Command Detected ";
-        let header_len = header.chars().count();
+        let header_len = display_width(header);
         let top_pad = width.saturating_sub(header_len);
 
         let _ = writeln!(
@@ -323,6 +424,20 @@ impl DenialBox {
                 &severity_code
             );
         }
+        if let Some(impact) = self.impact_line() {
+            let warning_code = ansi_color_code(theme.warning_color);
+            for line in wrap_text(&impact, width.saturating_sub(4)) {
+                let _ = writeln!(
+                    output,
+                    "\x1b[{}m\u{2502}\x1b[0m  \x1b[{}m{}\x1b[0m{}  \x1b[{}m\u{2502}\x1b[0m",
+                    severity_code,
+                    warning_code,
+                    line,
+                    padding_for(&line, width.saturating_sub(4)),
+                    severity_code
+                );
+            }
+        }
 
         // Empty line
         let _ = writeln!(
@@ -433,14 +548,13 @@ impl DenialBox {
     /// Render with ASCII box-drawing characters.
     #[cfg(not(feature = "rich-output"))]
     fn render_ascii(&self, theme: &Theme) -> String {
-        let width = terminal_width().saturating_sub(4).max(40) as usize;
+        let width = terminal_width().saturating_sub(4) as usize;
         let mut output = String::new();
-        let pattern_lines =
-            format_pattern_lines(&self.pattern_id, theme.severity_label(self.severity));
+        let pattern_lines = self.linked_pattern_lines(theme);
 
         // Top border with header
         let header = " !  BLOCKED: Destructive Command Detected ";
-        let header_len = header.chars().count();
+        let header_len = display_width(header);
         let top_pad = width.saturating_sub(header_len);
 
         let _ = writeln!(output, "+{}+", "-".repeat(width));
@@ -475,6 +589,16 @@ impl DenialBox {
                 padding_for(label, width.saturating_sub(4))
             );
         }
+        if let Some(impact) = self.impact_line() {
+            for line in wrap_text(&impact, width.saturating_sub(4)) {
+                let _ = writeln!(
+                    output,
+                    "|  {}{}  |",
+                    line,
+                    padding_for(&line, width.saturating_sub(4))
+                );
+            }
+        }
 
         // Empty line
         let _ = writeln!(output, "|{}  |", " ".repeat(width.saturating_sub(2)));
@@ -543,8 +667,7 @@ impl DenialBox {
         let mut output = String::new();
         let severity_code = severity_color_code(theme, self.severity);
         let success_code = ansi_color_code(theme.success_color);
-        let pattern_lines =
-            format_pattern_lines(&self.pattern_id, theme.severity_label(self.severity));
+        let pattern_lines = self.linked_pattern_lines(theme);
 
         // Header with color
         let _ = writeln!(
@@ -555,7 +678,7 @@ impl DenialBox {
         let _ = writeln!(output);
 
         // Command with highlighting
-        let width = terminal_width().saturating_sub(4).max(40);
+        let width = terminal_width().saturating_sub(4).max(20);
         let highlighted = format_highlighted_command(
             &self.command,
             &self.span,
@@ -568,13 +691,17 @@ impl DenialBox {
         if let Some(label) = &highlighted.label_line {
             let _ = writeln!(output, "  {label}");
         }
+        if let Some(impact) = self.impact_line() {
+            let warning_code = ansi_color_code(theme.warning_color);
+            let _ = writeln!(output, "  \x1b[{warning_code}m{impact}\x1b[0m");
+        }
         let _ = writeln!(output);
 
         // Explanation
         if let Some(explanation) = &self.explanation {
             let _ = writeln!(output);
             let explanation_label = format!("\x1b[1;{}mExplanation:\x1b[0m", &severity_code);
-            let width = terminal_width().saturating_sub(4).max(40) as usize;
+            let width = terminal_width().saturating_sub(4).max(20) as usize;
             let _ = writeln!(output, "  {explanation_label}");
             for line in wrap_text(explanation, width.saturating_sub(2)) {
                 let _ = writeln!(output, "  {line}");
@@ -632,12 +759,11 @@ fn severity_color_code(theme: &Theme, severity: Severity) -> String {
     ansi_color_code(theme.color_for_severity(severity))
 }
 
-/// Calculate padding needed to fill width, accounting for ANSI codes.
+/// Calculate padding needed to fill width, accounting for ANSI codes and
+/// double-width characters.
 #[cfg(not(feature = "rich-output"))]
 fn padding_for(text: &str, width: usize) -> String {
-    let visible_len = strip_ansi_codes(text).chars().count();
-    let padding = width.saturating_sub(visible_len);
-    " ".repeat(padding)
+    padding_to(text, width)
 }
 
 /// Strip ANSI escape codes from a string to get visible length.
@@ -663,8 +789,8 @@ fn strip_ansi_codes(s: &str) -> String {
     result
 }
 
-/// Wrap text to fit within the specified width (character count, not bytes).
-fn wrap_text(text: &str, width: usize) -> Vec<String> {
+/// Wrap text to fit within the specified width (display columns, not bytes).
+pub(super) fn wrap_text(text: &str, width: usize) -> Vec<String> {
     if text.is_empty() || width == 0 {
         return vec![];
     }
@@ -677,9 +803,10 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
             continue;
         }
 
-        let prefix_len = raw_line.chars().take_while(|c| c.is_whitespace()).count();
-        let prefix: String = raw_line.chars().take(prefix_len).collect();
-        let content = raw_line[prefix_len..].trim_end();
+        let content = raw_line.trim_start();
+        let prefix = &raw_line[..raw_line.len() - content.len()];
+        let prefix_len = display_width(prefix);
+        let content = content.trim_end();
 
         if content.is_empty() {
             lines.push(String::new());
@@ -687,21 +814,21 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
         }
 
         let mut current_line = String::new();
-        let mut current_char_count = 0;
+        let mut current_width = 0;
 
         for word in content.split_whitespace() {
-            let word_char_count = word.chars().count();
+            let word_width = display_width(word);
             if current_line.is_empty() {
                 current_line = format!("{prefix}{word}");
-                current_char_count = prefix_len + word_char_count;
-            } else if current_char_count + 1 + word_char_count <= width {
+                current_width = prefix_len + word_width;
+            } else if current_width + 1 + word_width <= width {
                 current_line.push(' ');
                 current_line.push_str(word);
-                current_char_count += 1 + word_char_count;
+                current_width += 1 + word_width;
             } else {
                 lines.push(current_line);
                 current_line = format!("{prefix}{word}");
-                current_char_count = prefix_len + word_char_count;
+                current_width = prefix_len + word_width;
             }
         }
 
@@ -769,6 +896,47 @@ mod tests {
         assert!(output.contains("CRITICAL"));
     }
 
+    #[test]
+    fn test_denial_box_lists_other_matches() {
+        let span = HighlightSpan::new(0, 16);
+        let denial = DenialBox::new(
+            "git reset --hard && rm -rf node_modules",
+            span,
+            "core.git.reset_hard",
+            Severity::Critical,
+        )
+        .with_other_matches(vec![(
+            "core.filesystem.rm-rf-general".to_string(),
+            Severity::High,
+        )]);
+
+        let output = denial.render_plain();
+
+        assert!(output.contains("Pattern: reset_hard"));
+        assert!(output.contains("Also matched: core.filesystem.rm-rf-general (high)"));
+    }
+
+    #[test]
+    fn test_denial_box_shows_impact() {
+        let denial = DenialBox::new(
+            "rm -rf project",
+            HighlightSpan::new(0, 14),
+            "core.filesystem.rm-rf-general",
+            Severity::High,
+        )
+        .with_impact("would delete 3 files in 2 directories, 20 bytes, includes .git");
+
+        let plain = denial.render_plain();
+        assert!(plain.contains("Impact:  would delete 3 files"));
+
+        let theme = Theme {
+            border_style: BorderStyle::Ascii,
+            ..Default::default()
+        };
+        let boxed = strip_ansi_codes(&denial.render(&theme));
+        assert!(boxed.contains("includes .git"));
+    }
+
     #[test]
     fn test_denial_box_with_explanation() {
         let span = HighlightSpan::new(0, 10);
@@ -942,6 +1110,32 @@ mod tests {
         assert!(output.contains("中文"), "CJK characters must be preserved");
     }
 
+    #[test]
+    #[cfg(not(feature = "rich-output"))]
+    fn test_denial_box_borders_align_with_wide_characters() {
+        let cmd = "rm -rf ./数据/备份/🎉 && echo 完成";
+        let denial = DenialBox::new(
+            cmd,
+            HighlightSpan::new(0, 6),
+            "core.filesystem:rm-rf-general",
+            Severity::Critical,
+        )
+        .with_explanation("删除 备份 目录 会 丢失 所有 数据 🎉 不可 恢复");
+
+        for border_style in [BorderStyle::Unicode, BorderStyle::Ascii] {
+            let theme = Theme {
+                border_style,
+                ..Default::default()
+            };
+            let output = strip_ansi_codes(&denial.render(&theme));
+            let widths: Vec<usize> = output.lines().map(display_width).collect();
+            assert!(
+                widths.iter().all(|w| *w == widths[0]),
+                "{border_style:?} box lines differ in width {widths:?}:\n{output}"
+            );
+        }
+    }
+
     #[test]
     fn test_denial_box_all_severity_levels() {
         // Verify all severity levels render correctly
@@ -992,6 +1186,25 @@ mod tests {
         assert!(clean_output.contains("Pack: core.git"));
     }
 
+    #[test]
+    #[cfg(not(feature = "rich-output"))]
+    fn test_denial_box_content_min_width_tracks_unbreakable_lines() {
+        let theme = Theme::default();
+        let denial = DenialBox::new(
+            "git push --force",
+            HighlightSpan::new(0, 16),
+            "core.git:force-push",
+            Severity::High,
+        );
+        let header = display_width(" \u{26d4}  BLOCKED: Destructive Command Detected ");
+        assert_eq!(denial.content_min_width(&theme), header);
+
+        let long_word = "x".repeat(90);
+        let denial = denial.with_explanation(format!("see {long_word} for details"));
+        assert_eq!(denial.content_min_width(&theme), 90);
+        assert!(!crate::output::box_fits(90, 80));
+    }
+
     #[test]
     fn test_wrap_text_empty_input() {
         let wrapped = wrap_text("", 30);
@@ -0,0 +1,360 @@
+//! Canonical render cases for golden-file snapshot tests.
+//!
+//! Renders the denial box, the `dcg test` result box, and the tables for a
+//! fixed set of cases under every theme palette and border style, one golden
+//! file per theme variant. `tests/render_snapshots.rs` compares them against
+//! `tests/golden/render/`; the hidden `dcg render-fixtures` command writes or
+//! checks the same files by hand.
+//!
+//! Output is pinned to [`FIXTURE_WIDTH`] columns and colors are forced on or
+//! off per theme, so goldens do not depend on the terminal running the tests.
+//! The `narrow` golden renders the boxes at [`NARROW_WIDTH`] to cover the
+//! borderless fallback and wrapped rows.
+//! Tables go through comfy-table, which only styles TTY output, so their ANSI
+//! codes are stripped.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::tables::{
+    PackListTable, PackRow, ScanResultRow, ScanResultsTable, StatsRow, StatsTable,
+};
+use super::test::{AllowedReason, TestOutcome, TestResultBox};
+use super::theme::{BorderStyle, Severity, Theme};
+use super::{DenialBox, set_width_override};
+use crate::highlight::HighlightSpan;
+
+/// Width every fixture is rendered at.
+pub const FIXTURE_WIDTH: u16 = 80;
+
+/// Width of the `narrow` fixture.
+pub const NARROW_WIDTH: u16 = 40;
+
+/// One golden file: every case rendered under one theme variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedFixture {
+    /// File stem, e.g. `colorblind-ascii`.
+    pub name: String,
+    pub contents: String,
+}
+
+/// Outcome of [`check_snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotStatus {
+    Matched,
+    /// The golden file was missing or stale and has been (re)written.
+    Written,
+}
+
+/// Every palette under every border style, with colors, then without.
+#[must_use]
+pub fn theme_variants() -> Vec<(String, Theme)> {
+    let palettes = [
+        ("default", Theme::default()),
+        ("colorblind", Theme::colorblind_safe()),
+        ("high-contrast", Theme::high_contrast()),
+        ("no-color", Theme::default().without_colors()),
+    ];
+    let borders = [
+        ("unicode", BorderStyle::Unicode),
+        ("ascii", BorderStyle::Ascii),
+        ("none", BorderStyle::None),
+    ];
+
+    let mut variants = Vec::new();
+    for (palette, theme) in &palettes {
+        for (border, style) in borders {
+            let mut theme = theme.clone();
+            theme.border_style = style;
+            variants.push((format!("{palette}-{border}"), theme));
+        }
+    }
+    variants
+}
+
+/// Render every case under every theme variant, plus the plain (non-TTY)
+/// renderers as `plain` and the colorless Unicode boxes at [`NARROW_WIDTH`]
+/// as `narrow`.
+#[must_use]
+pub fn render_fixtures() -> Vec<RenderedFixture> {
+    let previous_width = set_width_override(Some(FIXTURE_WIDTH));
+
+    let mut fixtures: Vec<RenderedFixture> = theme_variants()
+        .into_iter()
+        .map(|(name, theme)| {
+            colored::control::set_override(theme.colors_enabled);
+            let mut contents = String::new();
+            for (case, denial) in denial_cases() {
+                section(&mut contents, case, &denial.render(&theme));
+            }
+            for (case, result) in test_cases() {
+                section(&mut contents, case, &result.render(&theme));
+            }
+            for (case, table) in table_cases(&theme) {
+                section(&mut contents, case, &::console::strip_ansi_codes(&table));
+            }
+            RenderedFixture { name, contents }
+        })
+        .collect();
+
+    colored::control::set_override(false);
+    let mut contents = String::new();
+    for (case, denial) in denial_cases() {
+        section(&mut contents, case, &denial.render_plain());
+    }
+    for (case, result) in test_cases() {
+        section(&mut contents, case, &result.render_plain());
+    }
+    fixtures.push(RenderedFixture {
+        name: "plain".to_string(),
+        contents,
+    });
+
+    set_width_override(Some(NARROW_WIDTH));
+    let theme = Theme::default().without_colors();
+    let mut contents = String::new();
+    for (case, denial) in denial_cases() {
+        section(&mut contents, case, &denial.render(&theme));
+    }
+    for (case, result) in test_cases() {
+        section(&mut contents, case, &result.render(&theme));
+    }
+    fixtures.push(RenderedFixture {
+        name: "narrow".to_string(),
+        contents,
+    });
+
+    colored::control::unset_override();
+    set_width_override(previous_width);
+    fixtures
+}
+
+/// Compare `fixture` with `<dir>/<name>.txt`, or (re)write the file when
+/// `update` is set.
+///
+/// # Errors
+///
+/// Returns the first differing line when the golden file is stale or missing,
+/// or an I/O error message.
+pub fn check_snapshot(
+    dir: &Path,
+    fixture: &RenderedFixture,
+    update: bool,
+) -> Result<SnapshotStatus, String> {
+    let path = dir.join(format!("{}.txt", fixture.name));
+    let write = || {
+        std::fs::create_dir_all(dir)
+            .and_then(|()| std::fs::write(&path, &fixture.contents))
+            .map(|()| SnapshotStatus::Written)
+            .map_err(|e| format!("{}: {e}", path.display()))
+    };
+
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && update => return write(),
+        Err(e) => return Err(format!("{}: {e}", path.display())),
+    };
+    if expected == fixture.contents {
+        return Ok(SnapshotStatus::Matched);
+    }
+    if update {
+        return write();
+    }
+
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = fixture.contents.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(want), Some(got)) if want == got => line += 1,
+            (want, got) => {
+                return Err(format!(
+                    "{} differs at line {line}:\n  expected: {want:?}\n    actual: {got:?}",
+                    path.display()
+                ));
+            }
+        }
+    }
+}
+
+fn section(out: &mut String, case: &str, rendered: &str) {
+    let _ = writeln!(out, "=== {case} ===");
+    out.push_str(rendered);
+    if !rendered.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+fn denial_cases() -> Vec<(&'static str, DenialBox)> {
+    let critical = "git reset --hard HEAD~1";
+    let long = "cd /srv/app && kubectl --context production-eu-west-1 delete namespace \
+                payments-staging --wait=false && echo done";
+    let long_start = long.find("kubectl").unwrap_or(0);
+    let long_end = long.find(" --wait").unwrap_or(long.len());
+
+    vec![
+        (
+            "denial/critical",
+            DenialBox::new(
+                critical,
+                HighlightSpan::with_label(0, 16, "Matched: core.git:reset-hard"),
+                "core.git:reset-hard",
+                Severity::Critical,
+            )
+            .with_explanation(
+                "git reset --hard discards all uncommitted changes in the working tree and \
+                 the index. Changes that were never committed cannot be recovered.",
+            )
+            .with_alternatives(vec![
+                "git stash".to_string(),
+                "git reset --soft HEAD~1".to_string(),
+            ])
+            .with_allow_once_code("a1b2c"),
+        ),
+        (
+            "denial/high-long-command",
+            DenialBox::new(
+                long,
+                HighlightSpan::new(long_start, long_end),
+                "kubernetes.kubectl:delete-namespace",
+                Severity::High,
+            )
+            .with_other_matches(vec![(
+                "kubernetes.kubectl:production-context".to_string(),
+                Severity::Medium,
+            )]),
+        ),
+        (
+            "denial/wide-characters",
+            DenialBox::new(
+                "rm -rf ./数据/备份/🎉",
+                HighlightSpan::new(0, 6),
+                "core.filesystem:rm-rf-general",
+                Severity::High,
+            )
+            .with_explanation("删除 备份 目录 会 丢失 所有 数据，且 不可 恢复。"),
+        ),
+        (
+            "denial/medium-minimal",
+            DenialBox::new(
+                "docker system prune",
+                HighlightSpan::new(0, 19),
+                "containers.docker:system-prune",
+                Severity::Medium,
+            ),
+        ),
+    ]
+}
+
+fn test_cases() -> Vec<(&'static str, TestResultBox)> {
+    vec![
+        (
+            "test/blocked",
+            TestResultBox::blocked(
+                "rm -rf /var/lib/postgres",
+                Some("core.filesystem:rm-rf-root-home".to_string()),
+                Some("core.filesystem".to_string()),
+                Some(crate::packs::Severity::Critical),
+                "rm -rf outside temp directories is destructive",
+                Some(0.92),
+            ),
+        ),
+        (
+            "test/wide-characters",
+            TestResultBox::allowed_by_allowlist(
+                "git commit -m \"修复 🐛\"",
+                "提交 说明",
+                "project",
+            ),
+        ),
+        (
+            "test/allowed",
+            TestResultBox::allowed_no_match("git status"),
+        ),
+        (
+            "test/allowlisted",
+            TestResultBox::allowed_by_allowlist(
+                "git reset --hard",
+                "core.git:reset-hard",
+                "project",
+            ),
+        ),
+        (
+            "test/budget-exhausted",
+            TestResultBox {
+                command: "bash -c \"$(cat script.sh)\"".to_string(),
+                result: TestOutcome::Allowed {
+                    reason: AllowedReason::BudgetExhausted,
+                },
+            },
+        ),
+    ]
+}
+
+fn table_cases(theme: &Theme) -> Vec<(&'static str, String)> {
+    let scan = ScanResultsTable::new(vec![
+        ScanResultRow {
+            file: "scripts/deploy.sh".to_string(),
+            line: 42,
+            severity: Severity::Critical,
+            pattern_id: "core.filesystem:rm-rf-root-home".to_string(),
+            command_preview: Some("rm -rf \"$DEPLOY_DIR\"/*".to_string()),
+        },
+        ScanResultRow {
+            file: ".github/workflows/release.yml".to_string(),
+            line: 7,
+            severity: Severity::Medium,
+            pattern_id: "core.git:push-force".to_string(),
+            command_preview: Some("git push --force origin main".to_string()),
+        },
+    ])
+    .with_theme(theme)
+    .with_max_width(FIXTURE_WIDTH)
+    .with_command_preview();
+
+    let stats = StatsTable::new(vec![
+        StatsRow {
+            name: "core.git:reset-hard".to_string(),
+            hits: 120,
+            allowed: 30,
+            denied: 90,
+            noise_pct: Some(25.0),
+        },
+        StatsRow {
+            name: "core.filesystem:rm-rf-general".to_string(),
+            hits: 4,
+            allowed: 0,
+            denied: 4,
+            noise_pct: None,
+        },
+    ])
+    .with_theme(theme)
+    .with_max_width(FIXTURE_WIDTH)
+    .with_title("Top rules");
+
+    let packs = PackListTable::new(vec![
+        PackRow {
+            id: "core.git".to_string(),
+            name: "Git".to_string(),
+            destructive_count: 12,
+            safe_count: 9,
+            enabled: true,
+        },
+        PackRow {
+            id: "kubernetes.helm".to_string(),
+            name: "Helm".to_string(),
+            destructive_count: 4,
+            safe_count: 2,
+            enabled: false,
+        },
+    ])
+    .with_theme(theme)
+    .with_max_width(FIXTURE_WIDTH);
+
+    vec![
+        ("table/scan", scan.render()),
+        ("table/stats", stats.render()),
+        ("table/packs", packs.render()),
+    ]
+}
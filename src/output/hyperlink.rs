@@ -0,0 +1,105 @@
+//! OSC 8 terminal hyperlinks.
+//!
+//! Terminals that understand OSC 8 render `ESC ] 8 ; ; url ESC \ text
+//! ESC ] 8 ; ; ESC \` as `text` that opens `url` when clicked; others may
+//! print the escape bytes verbatim, so links are only emitted when the
+//! terminal is known to support them and `[output] hyperlinks` is not turned
+//! off. Everywhere else the text is returned unchanged.
+
+use std::sync::OnceLock;
+
+/// Where false-positive reports go.
+pub const ISSUE_URL: &str = "https://github.com/Dicklesworthstone/destructive_command_guard/issues/new?template=false_positive.yml";
+
+/// Global hyperlink setting (set from `[output] hyperlinks`).
+static HYPERLINKS_ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// `TERM_PROGRAM` values of terminals with OSC 8 support.
+const HYPERLINK_TERMINALS: &[&str] = &[
+    "iTerm.app",
+    "WezTerm",
+    "vscode",
+    "ghostty",
+    "Hyper",
+    "Tabby",
+    "rio",
+];
+
+/// First VTE release (0.50) with OSC 8 support, as reported by `VTE_VERSION`.
+const MIN_VTE_VERSION: u32 = 5000;
+
+/// Initialize the hyperlink setting from configuration.
+///
+/// Until this is called, no hyperlinks are emitted.
+pub fn init_hyperlinks(enabled: bool) {
+    let _ = HYPERLINKS_ENABLED.set(enabled && terminal_supports_hyperlinks());
+}
+
+/// Whether [`hyperlink`] emits OSC 8 sequences.
+#[must_use]
+pub fn hyperlinks_enabled() -> bool {
+    HYPERLINKS_ENABLED.get().copied().unwrap_or(false)
+}
+
+/// Whether the terminal is known to support OSC 8, judging by
+/// `TERM_PROGRAM` and `VTE_VERSION`.
+#[must_use]
+pub fn terminal_supports_hyperlinks() -> bool {
+    supports_hyperlinks(
+        std::env::var("TERM_PROGRAM").ok().as_deref(),
+        std::env::var("VTE_VERSION").ok().as_deref(),
+    )
+}
+
+fn supports_hyperlinks(term_program: Option<&str>, vte_version: Option<&str>) -> bool {
+    let vte = vte_version
+        .and_then(|version| version.trim().parse::<u32>().ok())
+        .is_some_and(|version| version >= MIN_VTE_VERSION);
+    vte || term_program.is_some_and(|program| HYPERLINK_TERMINALS.contains(&program))
+}
+
+/// `text` linked to `url` when hyperlinks are enabled, otherwise `text`.
+#[must_use]
+pub fn hyperlink(text: &str, url: &str) -> String {
+    if hyperlinks_enabled() {
+        osc8(text, url)
+    } else {
+        text.to_string()
+    }
+}
+
+fn osc8(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_supporting_terminals() {
+        assert!(supports_hyperlinks(Some("iTerm.app"), None));
+        assert!(supports_hyperlinks(Some("WezTerm"), None));
+        assert!(supports_hyperlinks(None, Some("7600")));
+        assert!(!supports_hyperlinks(None, Some("4800")));
+        assert!(!supports_hyperlinks(Some("Apple_Terminal"), None));
+        assert!(!supports_hyperlinks(None, Some("not-a-number")));
+        assert!(!supports_hyperlinks(None, None));
+    }
+
+    #[test]
+    fn osc8_wraps_text_without_changing_its_width() {
+        let link = osc8("core.git:reset-hard", "https://example.com/r");
+        assert_eq!(
+            link,
+            "\x1b]8;;https://example.com/r\x1b\\core.git:reset-hard\x1b]8;;\x1b\\"
+        );
+        assert_eq!(super::super::text::display_width(&link), 19);
+    }
+
+    #[test]
+    fn hyperlink_is_plain_text_until_enabled() {
+        // Nothing in the test binary calls `init_hyperlinks`.
+        assert_eq!(hyperlink("docs", "https://example.com"), "docs");
+    }
+}
@@ -6,7 +6,9 @@
 //!
 //! - `theme` - Color schemes and border style definitions
 //! - `denial` - Denial message box renderer
+//! - `fixtures` - Canonical render cases for golden-file snapshot tests
 //! - `test` - Test result box renderer
+//! - `preview` - Dry-run preview panel for `dcg preview`
 //! - `progress` - Progress indicators using indicatif (with rich_rust support)
 //! - `console` - Console abstraction for stderr output
 //! - `rich_theme` - rich_rust theme integration
@@ -22,15 +24,21 @@
 
 pub mod console;
 pub mod denial;
+pub mod fixtures;
+pub mod hyperlink;
+pub mod preview;
 pub mod progress;
 pub mod rich_theme;
 pub mod tables;
 pub mod test;
+pub mod text;
 pub mod theme;
 pub mod tree;
 
 pub use console::{DcgConsole, console, init_console};
 pub use denial::DenialBox;
+pub use hyperlink::{hyperlink, hyperlinks_enabled, init_hyperlinks};
+pub use preview::PreviewPanel;
 pub use progress::{
     MaybeProgress, NoopProgress, SCAN_PROGRESS_THRESHOLD, ScanProgress, ScanProgressStyle, spinner,
     spinner_if_tty,
@@ -45,6 +53,7 @@ pub use tree::{DcgTree, DcgTreeGuides, ExplainTreeBuilder, TreeNode};
 
 use crate::config::Config;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU16, Ordering};
 
 /// Global flag to force plain output (set by --no-color or similar).
 static FORCE_PLAIN: OnceLock<bool> = OnceLock::new();
@@ -211,12 +220,63 @@ pub fn supports_256_colors() -> bool {
     true
 }
 
+/// Width renderers use instead of the live terminal (0 = not set).
+static WIDTH_OVERRIDE: AtomicU16 = AtomicU16::new(0);
+
+/// Pin the width returned by [`terminal_width`], or go back to detecting it
+/// with `None`. Returns the previous override.
+pub fn set_width_override(width: Option<u16>) -> Option<u16> {
+    let previous = WIDTH_OVERRIDE.swap(width.unwrap_or(0), Ordering::Relaxed);
+    (previous != 0).then_some(previous)
+}
+
+/// Narrowest width `--width` and `DCG_TERM_WIDTH` accept.
+pub const MIN_TERM_WIDTH: u16 = 20;
+
+/// Width requested through `DCG_TERM_WIDTH`. Unparseable or too-small values
+/// are ignored rather than reported, so a stray variable cannot break hook
+/// mode.
+#[must_use]
+pub fn env_width() -> Option<u16> {
+    std::env::var("DCG_TERM_WIDTH")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|width| *width >= MIN_TERM_WIDTH)
+}
+
+/// The width pinned with [`set_width_override`] (`--width` or
+/// `DCG_TERM_WIDTH`), if any.
+#[must_use]
+pub fn width_override() -> Option<u16> {
+    match WIDTH_OVERRIDE.load(Ordering::Relaxed) {
+        0 => None,
+        width => Some(width),
+    }
+}
+
+/// Columns a bordered box spends outside its content: the margin to the
+/// terminal edge, the border characters, and their inner padding.
+pub(crate) const BOX_CHROME: usize = 8;
+
+/// Whether a bordered box whose widest unbreakable line is `content_min`
+/// columns fits a terminal `width` wide. Renderers draw the borderless
+/// layout instead when it does not, so narrow panes wrap rather than break
+/// the border.
+#[must_use]
+pub(crate) fn box_fits(content_min: usize, width: u16) -> bool {
+    usize::from(width) >= content_min + BOX_CHROME
+}
+
 /// Returns the terminal width, or a default if not detectable.
 #[must_use]
 pub fn terminal_width() -> u16 {
-    ::console::Term::stdout()
-        .size_checked()
-        .map_or(80, |(_, w)| w)
+    width_override().unwrap_or_else(|| {
+        ::console::Term::stdout()
+            .size_checked()
+            .map_or(80, |(_, w)| w)
+    })
 }
 
 /// Returns the terminal height, or a default if not detectable.
@@ -271,6 +331,13 @@ mod tests {
         assert!(height > 0);
     }
 
+    #[test]
+    fn test_box_fits_accounts_for_chrome() {
+        assert!(box_fits(72, 80));
+        assert!(!box_fits(73, 80));
+        assert!(!box_fits(42, 40));
+    }
+
     #[test]
     fn test_supports_256_colors_does_not_panic() {
         // Just verify it doesn't panic in test environment
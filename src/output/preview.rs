@@ -0,0 +1,270 @@
+//! Panel renderer for `dcg preview`.
+//!
+//! Shows the original command, the dry-run variant that was executed, where
+//! it ran and how it exited, followed by the captured output. Long output
+//! lines are cut at the panel edge rather than wrapped, since dry-run output
+//! is usually one path or resource per line.
+
+use super::text::{padding_to, truncate_to_width};
+use super::theme::{BorderStyle, Theme};
+use super::{box_fits, terminal_width};
+use crate::preview::{PreviewOutput, PreviewPlan};
+use ratatui::style::Color;
+use std::fmt::Write;
+
+/// Width of the row labels.
+const LABEL_WIDTH: usize = 11;
+
+/// Output lines shown before the rest is summarized.
+pub const MAX_PREVIEW_LINES: usize = 200;
+
+/// The result of a `dcg preview` run, ready to render.
+#[derive(Debug, Clone)]
+pub struct PreviewPanel<'a> {
+    /// The command as the user wrote it.
+    pub command: &'a str,
+    /// The dry-run variant that was executed.
+    pub plan: &'a PreviewPlan,
+    /// Directory the preview ran in.
+    pub cwd: &'a str,
+    /// Captured result.
+    pub output: &'a PreviewOutput,
+}
+
+impl PreviewPanel<'_> {
+    /// Render the panel with the given theme.
+    #[must_use]
+    pub fn render(&self, theme: &Theme) -> String {
+        let fits = box_fits(LABEL_WIDTH + 20, terminal_width());
+        let (top, side, mid, bottom) = match theme.border_style {
+            BorderStyle::Unicode if fits => (
+                ("\u{256d}", "\u{2500}", "\u{256e}"),
+                "\u{2502}",
+                ("\u{251c}", "\u{2524}"),
+                ("\u{2570}", "\u{256f}"),
+            ),
+            BorderStyle::Ascii if fits => (("+", "-", "+"), "|", ("+", "+"), ("+", "+")),
+            _ => return self.render_minimal(theme),
+        };
+        let width = usize::from(terminal_width()).saturating_sub(4);
+        let inner = width.saturating_sub(4);
+        let color = self.status_color(theme);
+        let border = |text: &str| paint(theme, color, false, text);
+        let rule = top.1.repeat(width);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", border(&format!("{}{rule}{}", top.0, top.2)));
+        let header = truncate_to_width(&self.header(), width);
+        let _ = writeln!(
+            out,
+            "{}{}{}{}",
+            border(side),
+            paint(theme, color, true, &header),
+            padding_to(&header, width),
+            border(side)
+        );
+        let _ = writeln!(out, "{}", border(&format!("{}{rule}{}", mid.0, mid.1)));
+        for line in self
+            .rows(theme, inner)
+            .into_iter()
+            .chain(std::iter::once(String::new()))
+            .chain(self.body_lines(theme, inner))
+        {
+            let _ = writeln!(
+                out,
+                "{}  {line}{}  {}",
+                border(side),
+                padding_to(&line, inner),
+                border(side)
+            );
+        }
+        let _ = write!(
+            out,
+            "{}",
+            border(&format!("{}{rule}{}", bottom.0, bottom.1))
+        );
+        out
+    }
+
+    /// Render without borders, for narrow terminals and `BorderStyle::None`.
+    fn render_minimal(&self, theme: &Theme) -> String {
+        let color = self.status_color(theme);
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", paint(theme, color, true, self.header().trim()));
+        let width = usize::from(terminal_width()).saturating_sub(2);
+        for line in self.rows(theme, width) {
+            let _ = writeln!(out, "  {line}");
+        }
+        let _ = writeln!(out);
+        for line in self.body_lines(theme, width) {
+            let _ = writeln!(out, "  {line}");
+        }
+        out.trim_end().to_string()
+    }
+
+    fn header(&self) -> String {
+        format!(" DRY-RUN PREVIEW: {} ", self.plan.tool)
+    }
+
+    const fn status_color(&self, theme: &Theme) -> Color {
+        if self.output.succeeded() {
+            theme.accent_color
+        } else {
+            theme.warning_color
+        }
+    }
+
+    fn rows(&self, theme: &Theme, width: usize) -> Vec<String> {
+        let status = if self.output.timed_out {
+            format!(
+                "timed out after {:.1}s (killed)",
+                self.output.duration.as_secs_f64()
+            )
+        } else {
+            let code = self.output.exit_code.map_or_else(
+                || "terminated by signal".to_string(),
+                |code| format!("exit {code}"),
+            );
+            format!("{code} in {:.1}s", self.output.duration.as_secs_f64())
+        };
+        [
+            ("Command:", self.command.to_string()),
+            ("Preview:", self.plan.command_line()),
+            ("Directory:", self.cwd.to_string()),
+            ("Result:", status),
+        ]
+        .into_iter()
+        .map(|(label, value)| {
+            let label = format!("{label:<LABEL_WIDTH$}");
+            let value = truncate_to_width(&value, width.saturating_sub(LABEL_WIDTH));
+            format!("{}{value}", paint(theme, theme.muted_color, false, &label))
+        })
+        .collect()
+    }
+
+    /// Captured stdout, then stderr, then notes about anything left out.
+    /// Lines are cut to `width` before they are colored.
+    fn body_lines(&self, theme: &Theme, width: usize) -> Vec<String> {
+        let stdout = self
+            .output
+            .stdout
+            .lines()
+            .map(|line| (line.to_string(), None));
+        let stderr = self
+            .output
+            .stderr
+            .lines()
+            .map(|line| (line.to_string(), Some(theme.warning_color)));
+        let mut lines: Vec<(String, Option<Color>)> = stdout.chain(stderr).collect();
+        if lines.is_empty() {
+            lines.push(("(no output)".to_string(), Some(theme.muted_color)));
+        }
+        let hidden = lines.len().saturating_sub(MAX_PREVIEW_LINES);
+        lines.truncate(MAX_PREVIEW_LINES);
+        if hidden > 0 {
+            let note = format!("... {hidden} more lines (use --format json for all)");
+            lines.push((note, Some(theme.muted_color)));
+        }
+        if self.output.truncated {
+            let note = format!(
+                "output cut off at {} KiB per stream",
+                crate::preview::MAX_OUTPUT_BYTES / 1024
+            );
+            lines.push((note, Some(theme.muted_color)));
+        }
+        lines
+            .into_iter()
+            .map(|(line, color)| {
+                let line = truncate_to_width(&line, width);
+                color.map_or_else(|| line.clone(), |color| paint(theme, color, false, &line))
+            })
+            .collect()
+    }
+}
+
+/// Wrap `text` in the SGR sequence for `color` when colors are enabled.
+fn paint(theme: &Theme, color: Color, bold: bool, text: &str) -> String {
+    if !theme.colors_enabled || text.is_empty() {
+        return text.to_string();
+    }
+    let code = match color {
+        Color::Red => "31",
+        Color::Green => "32",
+        Color::Yellow => "33",
+        Color::Blue => "34",
+        Color::Magenta => "35",
+        Color::Cyan => "36",
+        Color::Gray => "37",
+        Color::DarkGray => "90",
+        Color::LightRed => "91",
+        Color::LightGreen => "92",
+        Color::LightYellow => "93",
+        Color::LightBlue => "94",
+        Color::LightMagenta => "95",
+        Color::LightCyan => "96",
+        Color::White => "97",
+        _ => "0",
+    };
+    let weight = if bold { "1;" } else { "" };
+    format!("\x1b[{weight}{code}m{text}\x1b[0m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn plan() -> PreviewPlan {
+        crate::preview::plan_preview("git clean -fd").unwrap()
+    }
+
+    fn output(stdout: &str) -> PreviewOutput {
+        PreviewOutput {
+            exit_code: Some(0),
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            timed_out: false,
+            truncated: false,
+            duration: Duration::from_millis(120),
+        }
+    }
+
+    #[test]
+    fn panel_shows_preview_command_and_output() {
+        let plan = plan();
+        let output = output("Would remove build/\nWould remove tmp.txt\n");
+        let panel = PreviewPanel {
+            command: "git clean -fd",
+            plan: &plan,
+            cwd: "/work/repo",
+            output: &output,
+        };
+        let theme = Theme::no_color();
+        let rendered = panel.render(&theme);
+        assert!(rendered.contains("DRY-RUN PREVIEW: git clean"));
+        assert!(rendered.contains("git clean --dry-run -d"));
+        assert!(rendered.contains("/work/repo"));
+        assert!(rendered.contains("exit 0"));
+        assert!(rendered.contains("Would remove tmp.txt"));
+        assert!(!rendered.contains('\x1b'));
+    }
+
+    #[test]
+    fn panel_caps_long_output() {
+        let plan = plan();
+        let stdout = (0..MAX_PREVIEW_LINES + 5)
+            .map(|i| format!("Would remove f{i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let output = output(&stdout);
+        let panel = PreviewPanel {
+            command: "git clean -fd",
+            plan: &plan,
+            cwd: ".",
+            output: &output,
+        };
+        let rendered = panel.render(&Theme::no_color());
+        assert!(rendered.contains("5 more lines"));
+        assert!(!rendered.contains(&format!("f{}", MAX_PREVIEW_LINES + 1)));
+    }
+}
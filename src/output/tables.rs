@@ -30,6 +30,7 @@ use ratatui::style::Color as RatColor;
 #[cfg(feature = "rich-output")]
 use super::rich_theme::RichThemeExt;
 
+use super::text::truncate_to_width;
 use super::theme::{BorderStyle, Severity, Theme};
 
 /// Convert rich_rust segments to a plain text string.
@@ -250,7 +251,7 @@ impl ScanResultsTable {
         self.style.apply_preset(&mut table);
         table.set_content_arrangement(ContentArrangement::Dynamic);
 
-        if let Some(width) = self.max_width {
+        if let Some(width) = self.max_width.or_else(crate::output::width_override) {
             table.set_width(width);
         }
 
@@ -273,7 +274,7 @@ impl ScanResultsTable {
 
             if self.show_command {
                 let cmd = row.command_preview.as_deref().unwrap_or("-");
-                let truncated = truncate_with_ellipsis(cmd, 40);
+                let truncated = truncate_to_width(cmd, 40);
                 cells.push(Cell::new(truncated));
             }
 
@@ -315,7 +316,7 @@ impl ScanResultsTable {
 
             if self.show_command {
                 let cmd = row.command_preview.as_deref().unwrap_or("-");
-                let truncated = truncate_with_ellipsis(cmd, 40);
+                let truncated = truncate_to_width(cmd, 40);
                 cells.push(RichCell::new(truncated));
             }
 
@@ -504,7 +505,7 @@ impl StatsTable {
         self.style.apply_preset(&mut table);
         table.set_content_arrangement(ContentArrangement::Dynamic);
 
-        if let Some(width) = self.max_width {
+        if let Some(width) = self.max_width.or_else(crate::output::width_override) {
             table.set_width(width);
         }
 
@@ -764,7 +765,7 @@ impl PackListTable {
         self.style.apply_preset(&mut table);
         table.set_content_arrangement(ContentArrangement::Dynamic);
 
-        if let Some(width) = self.max_width {
+        if let Some(width) = self.max_width.or_else(crate::output::width_override) {
             table.set_width(width);
         }
 
@@ -914,22 +915,6 @@ pub fn format_summary(total: usize, categories: &[(&str, usize)]) -> String {
     }
 }
 
-fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
-    let text_len = text.chars().count();
-    if text_len <= max_chars {
-        return text.to_string();
-    }
-
-    if max_chars <= 3 {
-        return text.chars().take(max_chars).collect();
-    }
-
-    let keep = max_chars.saturating_sub(3);
-    let mut truncated: String = text.chars().take(keep).collect();
-    truncated.push_str("...");
-    truncated
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -8,10 +8,14 @@
 //! Uses the same theme system as denial.rs for consistent visual presentation.
 
 #[cfg(not(feature = "rich-output"))]
-use super::terminal_width;
+use super::denial::wrap_text;
+#[cfg(not(feature = "rich-output"))]
+use super::text::{display_width, padding_to};
 #[cfg(not(feature = "rich-output"))]
 use super::theme::BorderStyle;
 use super::theme::Theme;
+#[cfg(not(feature = "rich-output"))]
+use super::{box_fits, terminal_width};
 use crate::evaluator::{EvaluationDecision, EvaluationResult, PatternMatch};
 #[cfg(feature = "rich-output")]
 use crate::output::rich_theme::RichThemeExt;
@@ -174,6 +178,47 @@ impl TestResultBox {
         matches!(self.result, TestOutcome::Blocked { .. })
     }
 
+    /// Widest line the bordered layouts cannot wrap: the header, or the
+    /// label column plus the longest word of any row value.
+    #[cfg(not(feature = "rich-output"))]
+    fn content_min_width(&self) -> usize {
+        let mut values = vec![self.command.as_str()];
+        match &self.result {
+            TestOutcome::Blocked {
+                pattern_id,
+                pack_id,
+                reason,
+                explanation,
+                ..
+            } => {
+                values.extend(pattern_id.as_deref());
+                values.extend(pack_id.as_deref());
+                values.push(reason);
+                values.extend(explanation.as_deref());
+            }
+            TestOutcome::Allowed {
+                reason: AllowedReason::AllowlistMatch { layer, .. },
+            } => values.push(layer),
+            TestOutcome::Allowed { .. } => {}
+        }
+        // The entry is shown quoted.
+        let entry = match &self.result {
+            TestOutcome::Allowed {
+                reason: AllowedReason::AllowlistMatch { entry, .. },
+            } => Some(format!("\"{entry}\"")),
+            _ => None,
+        };
+        let longest_word = values
+            .iter()
+            .copied()
+            .chain(entry.as_deref())
+            .flat_map(|value| value.split_whitespace())
+            .map(display_width)
+            .max()
+            .unwrap_or(0);
+        (LABEL_WIDTH + longest_word).max(" WOULD BE BLOCKED ".len())
+    }
+
     /// Render the test result box with the given theme.
     #[must_use]
     pub fn render(&self, theme: &Theme) -> String {
@@ -182,23 +227,18 @@ impl TestResultBox {
             self.render_rich(theme)
         }
         #[cfg(not(feature = "rich-output"))]
-        match theme.border_style {
-            BorderStyle::Unicode => {
-                let output = self.render_unicode(theme);
-                if theme.colors_enabled {
-                    output
-                } else {
-                    strip_ansi_codes(&output)
-                }
-            }
-            BorderStyle::Ascii => self.render_ascii(theme),
-            BorderStyle::None => {
-                let output = self.render_minimal(theme);
-                if theme.colors_enabled {
-                    output
-                } else {
-                    strip_ansi_codes(&output)
-                }
+        {
+            // Too narrow for the box: drop the borders rather than overflow them.
+            let fits = box_fits(self.content_min_width(), terminal_width());
+            let output = match theme.border_style {
+                BorderStyle::Unicode if fits => self.render_unicode(theme),
+                BorderStyle::Ascii if fits => return self.render_ascii(theme),
+                _ => self.render_minimal(theme),
+            };
+            if theme.colors_enabled {
+                output
+            } else {
+                strip_ansi_codes(&output)
             }
         }
     }
@@ -368,7 +408,7 @@ impl TestResultBox {
     #[cfg(not(feature = "rich-output"))]
     #[allow(clippy::too_many_lines)]
     fn render_unicode(&self, theme: &Theme) -> String {
-        let width = terminal_width().saturating_sub(4).max(40) as usize;
+        let width = terminal_width().saturating_sub(4) as usize;
         let mut output = String::new();
 
         let (header, header_color) = match &self.result {
@@ -377,7 +417,7 @@ impl TestResultBox {
         };
 
         let color_code = ansi_color_code(header_color);
-        let header_len = header.chars().count();
+        let header_len = display_width(header);
         let top_pad = width.saturating_sub(header_len);
 
         // Top border
@@ -508,22 +548,19 @@ impl TestResultBox {
         width: usize,
         color_code: &str,
     ) {
-        let label_width = 12; // Fixed label column width
-        let content = format!("{label:<label_width$}{value}");
-        let content_len = content.chars().count();
-        let padding = width.saturating_sub(content_len + 4);
-
-        let _ = writeln!(
-            output,
-            "\x1b[{color_code}m\u{2502}\x1b[0m  {content}{}\x1b[{color_code}m\u{2502}\x1b[0m",
-            " ".repeat(padding),
-        );
+        for content in row_lines(label, value, width.saturating_sub(LABEL_WIDTH + 4)) {
+            let _ = writeln!(
+                output,
+                "\x1b[{color_code}m\u{2502}\x1b[0m  {content}{}  \x1b[{color_code}m\u{2502}\x1b[0m",
+                padding_to(&content, width.saturating_sub(4)),
+            );
+        }
     }
 
     /// Render with ASCII box-drawing characters.
     #[cfg(not(feature = "rich-output"))]
     fn render_ascii(&self, _theme: &Theme) -> String {
-        let width = terminal_width().saturating_sub(4).max(40) as usize;
+        let width = terminal_width().saturating_sub(4) as usize;
         let mut output = String::new();
 
         let header = match &self.result {
@@ -531,7 +568,7 @@ impl TestResultBox {
             TestOutcome::Allowed { .. } => " WOULD BE ALLOWED ",
         };
 
-        let header_len = header.chars().count();
+        let header_len = display_width(header);
         let top_pad = width.saturating_sub(header_len);
 
         // Top border
@@ -617,12 +654,13 @@ impl TestResultBox {
     /// Helper to render a labeled row in ASCII box style.
     #[cfg(not(feature = "rich-output"))]
     fn render_ascii_row(&self, output: &mut String, label: &str, value: &str, width: usize) {
-        let label_width = 12; // Fixed label column width
-        let content = format!("{label:<label_width$}{value}");
-        let content_len = content.chars().count();
-        let padding = width.saturating_sub(content_len + 4);
-
-        let _ = writeln!(output, "|  {content}{}|", " ".repeat(padding));
+        for content in row_lines(label, value, width.saturating_sub(LABEL_WIDTH + 4)) {
+            let _ = writeln!(
+                output,
+                "|  {content}{}  |",
+                padding_to(&content, width.saturating_sub(4))
+            );
+        }
     }
 
     /// Render with no borders (minimal style).
@@ -641,7 +679,13 @@ impl TestResultBox {
         let _ = writeln!(output, "\x1b[1;{color_code}m{header}\x1b[0m");
         let _ = writeln!(output);
 
-        // Content based on result type
+        // Content based on result type, wrapped to the terminal
+        let value_width = usize::from(terminal_width()).saturating_sub(LABEL_WIDTH + 2);
+        let mut row = |label: &str, value: &str| {
+            for line in row_lines(label, value, value_width) {
+                let _ = writeln!(output, "  {line}");
+            }
+        };
         match &self.result {
             TestOutcome::Blocked {
                 pattern_id,
@@ -651,39 +695,34 @@ impl TestResultBox {
                 confidence,
                 explanation,
             } => {
-                let _ = writeln!(output, "  Command:    {}", self.command);
+                row("Command:", &self.command);
                 if let Some(pattern) = pattern_id {
-                    let _ = writeln!(output, "  Pattern:    {pattern}");
+                    row("Pattern:", pattern);
                 }
                 if let Some(pack) = pack_id {
                     let severity_str = severity
                         .map(|s| format!(" (severity: {})", severity_label(s)))
                         .unwrap_or_default();
-                    let _ = writeln!(output, "  Pack:       {pack}{severity_str}");
+                    row("Pack:", &format!("{pack}{severity_str}"));
                 }
                 if let Some(conf) = confidence {
-                    let _ = writeln!(output, "  Confidence: {conf:.2}");
+                    row("Confidence:", &format!("{conf:.2}"));
                 }
-                let _ = writeln!(output, "  Reason:     {reason}");
+                row("Reason:", reason);
                 if let Some(text) = explanation {
-                    let _ = writeln!(output, "  Explanation: {text}");
+                    row("Explanation:", text);
                 }
             }
             TestOutcome::Allowed { reason } => {
-                let _ = writeln!(output, "  Command:    {}", self.command);
+                row("Command:", &self.command);
                 match reason {
-                    AllowedReason::NoPatternMatch => {
-                        let _ = writeln!(output, "  Reason:     No pattern matches");
-                    }
+                    AllowedReason::NoPatternMatch => row("Reason:", "No pattern matches"),
                     AllowedReason::AllowlistMatch { entry, layer } => {
-                        let _ = writeln!(output, "  Reason:     Allowlist match: \"{entry}\"");
-                        let _ = writeln!(output, "  Layer:      {layer}");
+                        row("Reason:", &format!("Allowlist match: \"{entry}\""));
+                        row("Layer:", layer);
                     }
                     AllowedReason::BudgetExhausted => {
-                        let _ = writeln!(
-                            output,
-                            "  Reason:     Evaluation budget exhausted (fail-open)"
-                        );
+                        row("Reason:", "Evaluation budget exhausted (fail-open)");
                     }
                 }
             }
@@ -693,6 +732,31 @@ impl TestResultBox {
     }
 }
 
+/// Fixed label column width of the boxed rows.
+#[cfg(not(feature = "rich-output"))]
+const LABEL_WIDTH: usize = 12;
+
+/// A labeled row whose value column is `value_width` wide. Values too long
+/// for one line are wrapped, continuation lines indented under the value
+/// column.
+#[cfg(not(feature = "rich-output"))]
+fn row_lines(label: &str, value: &str, value_width: usize) -> Vec<String> {
+    // Labels that fill the column still need a space before the value.
+    let label = if label.len() < LABEL_WIDTH {
+        format!("{label:<LABEL_WIDTH$}")
+    } else {
+        format!("{label} ")
+    };
+    if display_width(value) <= value_width {
+        return vec![format!("{label}{value}")];
+    }
+    let mut lines = wrap_text(value, value_width).into_iter();
+    let first = lines.next().unwrap_or_default();
+    std::iter::once(format!("{label}{first}"))
+        .chain(lines.map(|line| format!("{:LABEL_WIDTH$}{line}", "")))
+        .collect()
+}
+
 /// Convert a ratatui color to an ANSI foreground color code sequence.
 #[cfg(not(feature = "rich-output"))]
 fn ansi_color_code(color: Color) -> String {
@@ -867,6 +931,32 @@ mod tests {
         assert!(output.contains("git reset --hard"));
     }
 
+    #[test]
+    #[cfg(not(feature = "rich-output"))]
+    fn test_box_borders_align_with_wide_characters() {
+        let result = TestResultBox::blocked(
+            "rm -rf ./数据/🎉 && echo 完成",
+            Some("core.filesystem:rm-rf-general".to_string()),
+            Some("core.filesystem".to_string()),
+            Some(Severity::High),
+            "删除 目录 会 丢失 数据",
+            None,
+        );
+
+        for border_style in [BorderStyle::Unicode, BorderStyle::Ascii] {
+            let theme = Theme {
+                border_style,
+                ..Default::default()
+            };
+            let output = strip_ansi_codes(&result.render(&theme));
+            let widths: Vec<usize> = output.lines().map(display_width).collect();
+            assert!(
+                widths.iter().all(|w| *w == widths[0]),
+                "{border_style:?} box lines differ in width {widths:?}:\n{output}"
+            );
+        }
+    }
+
     #[test]
     #[cfg(not(feature = "rich-output"))]
     fn test_unicode_render_allowed() {
@@ -960,7 +1050,12 @@ mod tests {
                 matched_text_preview: None,
                 explanation: None,
                 suggestions: &[],
+                line_number: None,
             }),
+            other_matches: Vec::new(),
+            aggregation: None,
+            near_misses: Vec::new(),
+            allowlist_entry: None,
             allowlist_override: None,
             effective_mode: Some(crate::packs::DecisionMode::Deny),
             skipped_due_to_budget: false,
@@ -1028,6 +1123,7 @@ mod tests {
             matched_text_preview: None,
             explanation: None,
             suggestions: &[],
+            line_number: None,
         };
 
         assert_eq!(confidence_from_severity(&pattern), Some(0.95));
@@ -1045,6 +1141,43 @@ mod tests {
         assert_eq!(confidence_from_severity(&pattern_none), None);
     }
 
+    #[test]
+    #[cfg(not(feature = "rich-output"))]
+    fn test_row_lines_wrap_long_values_under_value_column() {
+        assert_eq!(
+            row_lines("Reason:", "short", 24),
+            vec!["Reason:     short".to_string()]
+        );
+
+        let lines = row_lines("Reason:", "one two three four five six", 14);
+        assert_eq!(
+            lines,
+            vec![
+                "Reason:     one two three".to_string(),
+                "            four five six".to_string(),
+            ]
+        );
+        assert!(
+            lines
+                .iter()
+                .all(|line| display_width(line) <= LABEL_WIDTH + 14)
+        );
+        assert_eq!(
+            row_lines("Explanation:", "text", 24),
+            vec!["Explanation: text".to_string()]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "rich-output"))]
+    fn test_content_min_width_uses_longest_word() {
+        let result = TestResultBox::allowed_no_match("cat /a/very/long/path/to/some/file.txt");
+        assert_eq!(
+            result.content_min_width(),
+            LABEL_WIDTH + "/a/very/long/path/to/some/file.txt".len()
+        );
+    }
+
     #[test]
     fn test_unicode_command_preservation() {
         let result = TestResultBox::blocked(
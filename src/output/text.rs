@@ -0,0 +1,110 @@
+//! Display-width helpers for box and table layouts.
+//!
+//! Terminals give CJK ideographs and most emoji two columns, so counting
+//! `chars()` misaligns borders as soon as a command contains them. These
+//! helpers measure with `unicode-width`, skip ANSI color and hyperlink
+//! sequences, and cut on grapheme boundaries so a truncated emoji or
+//! combining sequence is never split in half.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marker appended by [`truncate_to_width`].
+pub const ELLIPSIS: &str = "...";
+
+/// Columns `text` occupies on a terminal, ignoring ANSI color codes.
+#[must_use]
+pub fn display_width(text: &str) -> usize {
+    if !text.contains('\x1b') {
+        return text.width();
+    }
+    visible_segments(text).map(UnicodeWidthStr::width).sum()
+}
+
+/// Spaces needed after `text` to fill `width` columns.
+#[must_use]
+pub fn padding_to(text: &str, width: usize) -> String {
+    " ".repeat(width.saturating_sub(display_width(text)))
+}
+
+/// Cut `text` to at most `max_width` columns, ending in [`ELLIPSIS`] when
+/// anything was removed. Cuts fall between grapheme clusters; a wide
+/// character that would straddle the limit is dropped whole.
+#[must_use]
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width <= ELLIPSIS.len() {
+        return take_width(text, max_width);
+    }
+    let mut truncated = take_width(text, max_width - ELLIPSIS.len());
+    truncated.push_str(ELLIPSIS);
+    truncated
+}
+
+/// The longest grapheme-aligned prefix of `text` at most `max_width` wide.
+fn take_width(text: &str, max_width: usize) -> String {
+    let mut taken = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        let width = grapheme.width();
+        if used + width > max_width {
+            break;
+        }
+        taken.push_str(grapheme);
+        used += width;
+    }
+    taken
+}
+
+/// The runs of `text` between ANSI SGR sequences (`ESC [ ... m`) and OSC 8
+/// hyperlink markers (`ESC ] 8 ; ; url ESC \`).
+fn visible_segments(text: &str) -> impl Iterator<Item = &str> {
+    text.split('\x1b').enumerate().map(|(i, part)| {
+        if i == 0 {
+            return part;
+        }
+        match part.as_bytes().first() {
+            Some(b'[') => part.find('m').map_or("", |end| &part[end + 1..]),
+            // OSC payload runs until the string terminator or BEL.
+            Some(b']') => part.find('\x07').map_or("", |end| &part[end + 1..]),
+            Some(b'\\') => &part[1..],
+            _ => part,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(display_width("rm -rf"), 6);
+        assert_eq!(display_width("删除"), 4);
+        assert_eq!(display_width("🎉"), 2);
+        assert_eq!(display_width("\u{26d4}"), 2);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn ansi_codes_have_no_width() {
+        assert_eq!(display_width("\x1b[1;31m删除\x1b[0m ok"), 7);
+        assert_eq!(padding_to("\x1b[2mab\x1b[0m", 5), "   ");
+        let link = "\x1b]8;;https://example.com/rules/a\x1b\\Pattern: 删除\x1b]8;;\x1b\\";
+        assert_eq!(display_width(link), 13);
+    }
+
+    #[test]
+    fn truncation_respects_columns_and_graphemes() {
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("rm -rf /var/lib", 10), "rm -rf ...");
+        // "删除" is 4 columns; only one ideograph fits before the ellipsis.
+        assert_eq!(truncate_to_width("删除文件", 6), "删...");
+        assert_eq!(truncate_to_width("ab🎉🎉", 5), "ab...");
+        // Combining accent stays attached to its base letter.
+        assert_eq!(truncate_to_width("cafe\u{301} noir", 7), "cafe\u{301}...");
+        assert_eq!(truncate_to_width("删除", 3), "删");
+    }
+}
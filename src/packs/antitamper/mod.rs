@@ -0,0 +1,217 @@
+//! Anti-tamper pack - protections against erasing the record of what ran.
+//!
+//! This pack covers commands that hide an agent's tracks or loosen dcg itself:
+//! - clearing or disabling shell history (`history -c`, `unset HISTFILE`,
+//!   deleting `~/.bash_history`)
+//! - truncating or deleting files under `/var/log`
+//! - vacuuming the systemd journal down to nothing
+//! - writing to dcg's own config, allowlist, policy, or state files
+//!
+//! The last group is Critical: an agent that can rewrite the rules it runs
+//! under is no longer guarded by them.
+
+use crate::destructive_pattern;
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+
+/// Create the Anti-Tamper pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "antitamper".to_string(),
+        name: "Anti-Tamper",
+        description: "Protects against clearing shell history, truncating /var/log, vacuuming \
+                      the journal, and editing dcg's own config, allowlist, and audit files",
+        keywords: &[
+            "history",
+            "bash_history",
+            "zsh_history",
+            "sh_history",
+            "zhistory",
+            "fish_history",
+            "python_history",
+            "mysql_history",
+            "psql_history",
+            "node_repl_history",
+            "HISTFILE",
+            "HISTSIZE",
+            "/var/log",
+            "journalctl",
+            "dcg",
+            "DCG_CONFIG",
+            "DCG_STATE_DIR",
+        ],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+const fn create_safe_patterns() -> Vec<SafePattern> {
+    // Reading history, logs, and dcg's config is fine; every destructive
+    // pattern below requires a write.
+    Vec::new()
+}
+
+#[allow(clippy::too_many_lines)]
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        // --- dcg's own files ---
+        destructive_pattern!(
+            "dcg-config-edit",
+            r#"(?:(?:>>?\s*|\btee\s+(?:-\S+\s+)*|\b(?:sed|perl)\s+(?:[^\s;&|]+\s+)*?-[A-Za-z]*i\S*\s+(?:[^\s;&|]+\s+)*?|\b(?:rm|mv|truncate|shred|unlink|ln)\b[^;&|]*?\s)["']?\S*(?:\.dcg\.toml|\.dcg/|\.config/dcg/|/etc/dcg/|\.local/state/dcg/|\$\{?DCG_(?:CONFIG|STATE_DIR)\b)|\b(?:cp|install)\b[^;&|]*\s["']?\S*(?:\.dcg\.toml|\.dcg/|\.config/dcg/|/etc/dcg/|\.local/state/dcg/|\$\{?DCG_(?:CONFIG|STATE_DIR)\b)\S*\s*(?:$|[;&|)]))"#,
+            "Editing dcg's config, allowlist, or audit files from an agent session is not allowed. Ask the user to make this change.",
+            Critical,
+            "dcg's config, allowlists, policy files, and audit logs decide which commands \
+             this session may run and record what it ran. A command that writes to them \
+             from inside the guarded session could allow itself anything or erase the \
+             evidence, so dcg treats it as tampering and never lets it through, \
+             whatever the other rules say.\n\n\
+             If a rule is wrong, ask the user to change it. They can review and apply it \
+             with:\n  \
+             dcg allowlist add <rule-id> --reason \"...\"\n  \
+             dcg config\n\n\
+             Reading these files (cat, dcg config, dcg allowlist list) is allowed."
+        ),
+        // --- shell history ---
+        destructive_pattern!(
+            "history-clear",
+            r"\bhistory\s+-[A-Za-z]*c",
+            "history -c clears the shell's command history.",
+            High,
+            "history -c empties the in-memory history of the current shell; combined \
+             with -w it also overwrites the history file. The record of what was run \
+             in this session is lost.\n\n\
+             To remove a single entry:\n  \
+             history -d <offset>"
+        ),
+        destructive_pattern!(
+            "shell-history-wipe",
+            r"(?:\b(?:rm|shred|truncate|unlink)\b[^;&|]*?|(?:^|[^>0-9&])>\s*|\bln\s+-\S*s\S*\s+/dev/null\s+|\bcp\s+/dev/null\s+)\S*(?:\.(?:bash|zsh|sh|python|mysql|psql|node_repl)_history|\.zhistory|fish_history|\$\{?HISTFILE\b)",
+            "Deleting or emptying a shell history file erases the record of past commands.",
+            High,
+            "Shell history files are often the only record of which commands ran on a \
+             host. Deleting, truncating, or linking them to /dev/null removes it for \
+             every past session, not just this one.\n\n\
+             To remove a single entry, edit the file instead:\n  \
+             history -d <offset> && history -w"
+        ),
+        destructive_pattern!(
+            "history-disable",
+            r"(?:\bunset\s+(?:-v\s+)?(?:\w+\s+)*HISTFILE\b|\b(?:export\s+)?HISTFILE=/dev/null\b|\b(?:export\s+)?HISTSIZE=0\b|\bset\s+\+o\s+history\b)",
+            "Disabling shell history hides the commands that follow.",
+            High,
+            "Unsetting HISTFILE, pointing it at /dev/null, setting HISTSIZE=0, or \
+             `set +o history` stops the shell from recording commands. Nothing run \
+             afterwards in this shell can be reviewed later."
+        ),
+        // --- system logs ---
+        destructive_pattern!(
+            "log-truncate",
+            r"(?:\b(?:truncate|rm|shred|unlink)\b[^;&|]*?\s|(?:^|[;&|(]\s*)(?::|true|echo(?:\s+-n)?(?:\s+(?:''|\x22\x22))?|cat\s+/dev/null)?\s*>\s*|\bcp\s+/dev/null\s+)/var/log/",
+            "Truncating or deleting files under /var/log destroys system logs.",
+            High,
+            "Files under /var/log are the audit trail for logins, sudo, services, and \
+             the kernel. Emptying or deleting them removes the evidence needed to \
+             investigate an incident and cannot be undone.\n\n\
+             To free space, rotate instead:\n  \
+             logrotate -f /etc/logrotate.conf\n\n\
+             Check sizes first:\n  \
+             du -sh /var/log/*"
+        ),
+        destructive_pattern!(
+            "journal-vacuum",
+            r"\bjournalctl\s+(?:[^\s;&|]+\s+)*?--vacuum-(?:time(?:=|\s+)\d+(?:s|sec|m|min|h|hour)?|size(?:=|\s+)\d+[BK]?|files(?:=|\s+)[01])\b",
+            "journalctl --vacuum with a tiny limit deletes (almost) the whole systemd journal.",
+            High,
+            "Vacuuming the journal to a few seconds, a few kilobytes, or a single file \
+             deletes nearly every archived log entry, including authentication and \
+             service logs.\n\n\
+             Keep a realistic retention instead:\n  \
+             journalctl --vacuum-time=2weeks\n\n\
+             Check disk usage first:\n  \
+             journalctl --disk-usage"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "antitamper");
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn dcg_files_are_protected() {
+        let pack = create_pack();
+        for command in [
+            "echo '[[allow]]' >> ~/.config/dcg/allowlist.toml",
+            "echo 'enabled = []' > .dcg.toml",
+            "sed -i 's/deny/allow/' /etc/dcg/config.toml",
+            "rm -f .dcg/allowlist.toml",
+            "cp /tmp/permissive.toml ~/.config/dcg/config.toml",
+            "tee -a .dcg/policy.toml",
+            "truncate -s 0 ~/.local/state/dcg/history.db",
+        ] {
+            assert_blocks_with_pattern(&pack, command, "dcg-config-edit");
+            assert_blocks_with_severity(&pack, command, Severity::Critical);
+        }
+        assert_allows(&pack, "cat ~/.config/dcg/config.toml");
+        assert_allows(&pack, "dcg config");
+        assert_allows(&pack, "cp .dcg.toml /tmp/dcg-backup.toml");
+    }
+
+    #[test]
+    fn shell_history() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "history -c", "history-clear");
+        assert_blocks_with_pattern(&pack, "history -cw", "history-clear");
+        assert_blocks_with_pattern(&pack, "rm ~/.bash_history", "shell-history-wipe");
+        assert_blocks_with_pattern(
+            &pack,
+            "cat /dev/null > ~/.zsh_history",
+            "shell-history-wipe",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "ln -sf /dev/null ~/.bash_history",
+            "shell-history-wipe",
+        );
+        assert_blocks_with_pattern(&pack, "unset HISTFILE", "history-disable");
+        assert_blocks_with_pattern(&pack, "export HISTFILE=/dev/null", "history-disable");
+        assert_blocks_with_pattern(&pack, "set +o history", "history-disable");
+        assert_allows(&pack, "history | tail -20");
+        assert_allows(&pack, "grep ssh ~/.bash_history");
+        assert_allows(&pack, "history -d 42");
+    }
+
+    #[test]
+    fn system_logs() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "truncate -s 0 /var/log/auth.log", "log-truncate");
+        assert_blocks_with_pattern(&pack, "> /var/log/syslog", "log-truncate");
+        assert_blocks_with_pattern(&pack, "cat /dev/null > /var/log/secure", "log-truncate");
+        assert_blocks_with_pattern(&pack, "sudo rm /var/log/wtmp", "log-truncate");
+        assert_blocks_with_pattern(&pack, "journalctl --vacuum-time=1s", "journal-vacuum");
+        assert_blocks_with_pattern(
+            &pack,
+            "journalctl --rotate --vacuum-size=1K",
+            "journal-vacuum",
+        );
+        assert_allows(&pack, "tail -f /var/log/syslog");
+        assert_allows(&pack, "journalctl --vacuum-time=2weeks");
+        assert_allows(&pack, "journalctl -u nginx --since today");
+        assert_allows(&pack, "./server 2>/var/log/server.err");
+    }
+}
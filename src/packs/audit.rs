@@ -0,0 +1,431 @@
+//! Pattern audits for `dcg pack audit`.
+//!
+//! Pack patterns are regexes over the raw command line, so nothing stops one
+//! from matching text that the shell treats as data. The audit takes each
+//! rule's blocked examples from the regression corpus and checks that they
+//! still match. With `--semantic` it also embeds every example in commands
+//! where it is only an argument (an `echo` string, a commit message, a `grep`
+//! needle) or glues it to a longer program name, parses each probe with
+//! tree-sitter-bash, and reports rules whose regex matches inside a quoted
+//! word or in the middle of a token.
+//!
+//! A probe that the full pipeline still denies with the same rule is a false
+//! positive users will hit. One that only the bare regex matches is shielded
+//! by context sanitization today and reported as a note.
+
+use std::collections::HashSet;
+
+use ast_grep_core::AstGrep;
+use ast_grep_language::SupportLang;
+use serde::Serialize;
+
+use super::{DestructivePattern, Pack, REGISTRY};
+use crate::config::Config;
+use crate::evaluator::{EvaluationDecision, evaluate_command_with_pack_order};
+use crate::rule_docs::RuleExamples;
+
+/// How an example is embedded in a probe command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeKind {
+    /// `echo "this describes <example>"`
+    EchoString,
+    /// `git commit -m "docs: explain <example>"`
+    CommitMessage,
+    /// `grep -rn '<example>' docs/`
+    GrepNeedle,
+    /// The example's first word with a prefix glued on (`xrm -rf /`).
+    PrefixedWord,
+}
+
+impl ProbeKind {
+    pub const ALL: [Self; 4] = [
+        Self::EchoString,
+        Self::CommitMessage,
+        Self::GrepNeedle,
+        Self::PrefixedWord,
+    ];
+
+    /// The probe command for `example`, or `None` if the example cannot be
+    /// embedded without changing how the shell reads it.
+    #[must_use]
+    pub fn probe(self, example: &str) -> Option<String> {
+        let double_quotable = !example.contains(['"', '$', '`', '\\', '!']);
+        match self {
+            Self::EchoString => {
+                double_quotable.then(|| format!("echo \"this describes {example}\""))
+            }
+            Self::CommitMessage => {
+                double_quotable.then(|| format!("git commit -m \"docs: explain {example}\""))
+            }
+            Self::GrepNeedle => {
+                (!example.contains('\'')).then(|| format!("grep -rn '{example}' docs/"))
+            }
+            // `x/usr/bin/git` is still a path to git.
+            Self::PrefixedWord => {
+                let example = example.trim_start();
+                let first_word = example.split_whitespace().next().unwrap_or_default();
+                (!first_word.contains('/')).then(|| format!("x{example}"))
+            }
+        }
+    }
+
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::EchoString => "echo string",
+            Self::CommitMessage => "commit message",
+            Self::GrepNeedle => "grep pattern",
+            Self::PrefixedWord => "prefixed program name",
+        }
+    }
+}
+
+/// Where in the parsed probe the regex matched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MatchPlacement {
+    /// Inside a quoted word (`string`, `raw_string`, ...) the shell never runs.
+    Quoted { node: String },
+    /// Starting in the middle of a word.
+    MidWord { word: String },
+}
+
+impl std::fmt::Display for MatchPlacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Quoted { node } => write!(f, "inside a quoted argument ({node})"),
+            Self::MidWord { word } => write!(f, "in the middle of the word `{word}`"),
+        }
+    }
+}
+
+/// A probe whose match does not line up with the shell's reading of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticFinding {
+    pub rule_id: String,
+    pub example: String,
+    pub probe_kind: ProbeKind,
+    pub probe: String,
+    pub matched: String,
+    pub placement: MatchPlacement,
+    /// Whether the full pipeline still denies the probe with this rule.
+    pub blocked: bool,
+}
+
+/// A corpus example the rule no longer matches.
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleExample {
+    pub rule_id: String,
+    pub example: String,
+}
+
+/// Audit results for one pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackAudit {
+    pub pack_id: String,
+    pub rules: usize,
+    /// Rules without a corpus example; nothing was probed for them.
+    pub unexampled: Vec<String>,
+    pub stale_examples: Vec<StaleExample>,
+    /// Empty unless the audit was semantic.
+    pub findings: Vec<SemanticFinding>,
+}
+
+impl PackAudit {
+    /// Findings the full pipeline still blocks.
+    pub fn false_positives(&self) -> impl Iterator<Item = &SemanticFinding> {
+        self.findings.iter().filter(|finding| finding.blocked)
+    }
+
+    /// Whether the audit found anything worth failing on.
+    #[must_use]
+    pub fn has_problems(&self) -> bool {
+        !self.stale_examples.is_empty() || self.false_positives().next().is_some()
+    }
+}
+
+/// Audit `pack` against its corpus `examples`; `semantic` adds the shell
+/// probes.
+#[must_use]
+pub fn audit_pack(pack: &Pack, examples: &RuleExamples, semantic: bool) -> PackAudit {
+    let evaluator = PackEvaluator::new(&pack.id);
+    let mut audit = PackAudit {
+        pack_id: pack.id.clone(),
+        rules: 0,
+        unexampled: Vec::new(),
+        stale_examples: Vec::new(),
+        findings: Vec::new(),
+    };
+
+    for pattern in &pack.destructive_patterns {
+        let Some(name) = pattern.name else {
+            continue;
+        };
+        audit.rules += 1;
+        let rule_id = format!("{}:{name}", pack.id);
+        let Some(rule_examples) = examples.get(&rule_id) else {
+            audit.unexampled.push(rule_id);
+            continue;
+        };
+        for example in rule_examples {
+            // Normalization (`/usr/bin/git`, `sudo`) can be what makes an
+            // example match, so only the full pipeline can call it stale.
+            if !pattern.regex.is_match(example) && !evaluator.denies_with(example, &pack.id, name) {
+                audit.stale_examples.push(StaleExample {
+                    rule_id: rule_id.clone(),
+                    example: example.clone(),
+                });
+                continue;
+            }
+            if semantic {
+                audit.findings.extend(probe_example(
+                    pattern, &rule_id, name, &pack.id, example, &evaluator,
+                ));
+            }
+        }
+    }
+    audit
+}
+
+fn probe_example(
+    pattern: &DestructivePattern,
+    rule_id: &str,
+    name: &str,
+    pack_id: &str,
+    example: &str,
+    evaluator: &PackEvaluator,
+) -> Vec<SemanticFinding> {
+    let mut findings = Vec::new();
+    for kind in ProbeKind::ALL {
+        let Some(probe) = kind.probe(example) else {
+            continue;
+        };
+        let Some((start, end)) = pattern.regex.find(&probe) else {
+            continue;
+        };
+        // Quoting inside the example itself (`bash -c '...'`) is not the
+        // probe's doing, so each probe only reports what it introduced.
+        let placement = match (kind, classify_match(&probe, start, end)) {
+            (ProbeKind::PrefixedWord, Some(placement @ MatchPlacement::MidWord { .. })) => {
+                placement
+            }
+            (ProbeKind::PrefixedWord, _) | (_, None | Some(MatchPlacement::MidWord { .. })) => {
+                continue;
+            }
+            (_, Some(placement)) => placement,
+        };
+        findings.push(SemanticFinding {
+            rule_id: rule_id.to_string(),
+            example: example.to_string(),
+            probe_kind: kind,
+            matched: probe[start..end].to_string(),
+            placement,
+            blocked: evaluator.denies_with(&probe, pack_id, name),
+            probe,
+        });
+    }
+    findings
+}
+
+/// Where the match at `start..end` of `source` sits in the bash parse tree.
+///
+/// `None` means the shell would run it as a command. Otherwise the match is
+/// inside a quoted word (and not in a command substitution within it), or
+/// starts in the middle of a word.
+#[must_use]
+pub fn classify_match(source: &str, start: usize, end: usize) -> Option<MatchPlacement> {
+    // Regexes often consume the separator in front of the program name.
+    let start = start
+        + source[start..end]
+            .find(|c: char| !c.is_whitespace() && !matches!(c, ';' | '&' | '|' | '('))?;
+
+    let ast = AstGrep::new(source, SupportLang::Bash);
+    let mut path = Vec::new();
+    let mut node = ast.root();
+    loop {
+        path.push((
+            node.kind().to_string(),
+            node.range(),
+            node.text().to_string(),
+        ));
+        let Some(child) = node.children().find(|child| {
+            let range = child.range();
+            range.start <= start && start < range.end
+        }) else {
+            break;
+        };
+        node = child;
+    }
+
+    for (kind, range, _) in path.iter().rev() {
+        match kind.as_str() {
+            "command_substitution" | "process_substitution" => break,
+            "string" | "raw_string" | "ansi_c_string" | "translated_string"
+                if range.start <= start && end <= range.end =>
+            {
+                return Some(MatchPlacement::Quoted { node: kind.clone() });
+            }
+            _ => {}
+        }
+    }
+
+    let (kind, range, text) = path.last()?;
+    (kind == "word" && range.start < start).then(|| MatchPlacement::MidWord { word: text.clone() })
+}
+
+/// Full-pipeline evaluation with a single pack enabled.
+struct PackEvaluator {
+    keywords: Vec<&'static str>,
+    ordered_packs: Vec<String>,
+    compiled_overrides: crate::config::CompiledOverrides,
+    heredoc_settings: crate::config::HeredocSettings,
+    allowlists: crate::allowlist::LayeredAllowlist,
+}
+
+impl PackEvaluator {
+    fn new(pack_id: &str) -> Self {
+        let config = Config::default();
+        let enabled: HashSet<String> = std::iter::once(pack_id.to_string()).collect();
+        Self {
+            keywords: REGISTRY.collect_enabled_keywords(&enabled),
+            ordered_packs: REGISTRY.expand_enabled_ordered(&enabled),
+            compiled_overrides: config.compile_overrides(),
+            heredoc_settings: config.heredoc_settings(),
+            allowlists: crate::allowlist::LayeredAllowlist::default(),
+        }
+    }
+
+    fn denies_with(&self, command: &str, pack_id: &str, name: &str) -> bool {
+        let result = evaluate_command_with_pack_order(
+            command,
+            &self.keywords,
+            &self.ordered_packs,
+            None,
+            &self.compiled_overrides,
+            &self.allowlists,
+            &self.heredoc_settings,
+        );
+        result.decision == EvaluationDecision::Deny
+            && result.pattern_info.as_ref().is_some_and(|info| {
+                info.pack_id.as_deref() == Some(pack_id)
+                    && info.pattern_name.as_deref() == Some(name)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::regex_engine::LazyCompiledRegex;
+    use crate::packs::{PackId, Severity};
+    use crate::safe_pattern;
+
+    fn placement(source: &str, needle: &str) -> Option<MatchPlacement> {
+        let start = source.find(needle).unwrap();
+        classify_match(source, start, start + needle.len())
+    }
+
+    #[test]
+    fn quoted_arguments_are_data() {
+        assert_eq!(
+            placement("echo \"this describes rm -rf /\"", "rm -rf /"),
+            Some(MatchPlacement::Quoted {
+                node: "string".to_string()
+            })
+        );
+        assert_eq!(
+            placement("grep -rn 'git reset --hard' docs/", "git reset --hard"),
+            Some(MatchPlacement::Quoted {
+                node: "raw_string".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn executed_commands_are_not_data() {
+        assert_eq!(placement("rm -rf /", "rm -rf /"), None);
+        assert_eq!(placement("cd /tmp && git reset --hard", " git reset"), None);
+        assert_eq!(placement("echo \"$(rm -rf /)\"", "rm -rf /"), None);
+    }
+
+    #[test]
+    fn matches_inside_a_word_are_flagged() {
+        assert_eq!(
+            placement("xrm -rf /", "rm"),
+            Some(MatchPlacement::MidWord {
+                word: "xrm".to_string()
+            })
+        );
+        assert_eq!(placement("rm -rf /", "rm"), None);
+    }
+
+    #[test]
+    fn probes_skip_examples_they_would_change() {
+        assert_eq!(
+            ProbeKind::EchoString.probe("rm -rf /"),
+            Some("echo \"this describes rm -rf /\"".to_string())
+        );
+        assert_eq!(ProbeKind::EchoString.probe("rm -rf \"$HOME\""), None);
+        assert_eq!(ProbeKind::GrepNeedle.probe("git commit -m 'x'"), None);
+        assert_eq!(
+            ProbeKind::PrefixedWord.probe("git reset --hard"),
+            Some("xgit reset --hard".to_string())
+        );
+    }
+
+    fn test_pack(regex: &'static str) -> Pack {
+        Pack::new(
+            PackId::from("test.audit"),
+            "Audit test",
+            "Pack for audit tests",
+            &["wipe"],
+            vec![safe_pattern!("wipe-dry-run", r"wipe\s+--dry-run")],
+            vec![DestructivePattern {
+                regex: LazyCompiledRegex::new(regex),
+                reason: "wipes everything",
+                name: Some("wipe-all"),
+                severity: Severity::Critical,
+                explanation: None,
+                suggestions: &[],
+            }],
+        )
+    }
+
+    fn examples(rule_examples: &[&str]) -> RuleExamples {
+        std::iter::once((
+            "test.audit:wipe-all".to_string(),
+            rule_examples.iter().map(ToString::to_string).collect(),
+        ))
+        .collect()
+    }
+
+    #[test]
+    fn audit_reports_stale_and_missing_examples() {
+        let pack = test_pack(r"\bwipe\s+--all\b");
+        let audit = audit_pack(&pack, &examples(&["wipe --everything"]), false);
+        assert_eq!(audit.rules, 1);
+        assert_eq!(audit.stale_examples.len(), 1);
+        assert!(audit.has_problems());
+
+        let audit = audit_pack(&pack, &RuleExamples::new(), false);
+        assert_eq!(audit.unexampled, ["test.audit:wipe-all"]);
+        assert!(!audit.has_problems());
+    }
+
+    #[test]
+    fn semantic_audit_flags_unanchored_patterns() {
+        let pack = test_pack(r"wipe\s+--all");
+        let audit = audit_pack(&pack, &examples(&["wipe --all"]), true);
+        let kinds: Vec<_> = audit.findings.iter().map(|f| f.probe_kind).collect();
+        assert!(kinds.contains(&ProbeKind::EchoString), "{kinds:?}");
+        assert!(kinds.contains(&ProbeKind::PrefixedWord), "{kinds:?}");
+
+        let audit = audit_pack(
+            &test_pack(r"^wipe\s+--all"),
+            &examples(&["wipe --all"]),
+            true,
+        );
+        assert!(audit.findings.is_empty(), "{:?}", audit.findings);
+    }
+}
@@ -0,0 +1,288 @@
+//! Block Device pack - protections against overwriting disks and partitions.
+//!
+//! This pack covers tools that write to a block device directly:
+//! - dd of=/dev/...
+//! - mkfs.* / mke2fs / mkswap
+//! - wipefs --all / --offset
+//! - blkdiscard
+//! - parted rm / mklabel
+//! - fdisk and sfdisk driven by a script on stdin
+//! - shred on device nodes
+//!
+//! Every rule looks at the target the tool writes to. A real disk or
+//! partition is Critical, a loop device (usually backed by an image file) is
+//! Medium, and a plain image file is Low. Pseudo devices such as `/dev/null`
+//! are never matched.
+
+use crate::destructive_pattern;
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+
+/// Create the Block Device pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "blockdevice".to_string(),
+        name: "Block Devices",
+        description: "Protects against writing to disks and partitions with dd, mkfs, wipefs, \
+                      blkdiscard, parted, scripted fdisk/sfdisk, and shred, treating loop \
+                      devices and image files as less severe than real disks",
+        keywords: &[
+            "dd",
+            "mkfs",
+            "mke2fs",
+            "mkswap",
+            "wipefs",
+            "blkdiscard",
+            "parted",
+            "fdisk",
+            "shred",
+        ],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+const fn create_safe_patterns() -> Vec<SafePattern> {
+    // Read-only and pseudo-device targets are excluded by the destructive
+    // patterns themselves, so a safe pattern here could only hide a later
+    // destructive segment of the same command.
+    Vec::new()
+}
+
+#[allow(clippy::too_many_lines)]
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    // Targets: a disk is any /dev node that is not a loop device or a pseudo
+    // device (/dev/null, /dev/zero, /dev/stdout, ...); loop devices are
+    // /dev/loopN; anything outside /dev is an image file.
+    vec![
+        // --- dd ---
+        destructive_pattern!(
+            "dd-disk",
+            r#"\bdd\s+(?:[^|;&\s]+\s+)*?of=["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S"#,
+            "dd to a disk or partition OVERWRITES the device. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "dd writes raw bytes to the device named by of=, starting at the first sector. \
+             Partition tables, filesystems, and boot loaders are overwritten without any \
+             confirmation, and a wrong device letter (sdb vs sdc) destroys the wrong disk.\n\n\
+             Before writing, confirm the target:\n  \
+             lsblk -o NAME,SIZE,MODEL,MOUNTPOINT\n\n\
+             To experiment, write to an image file or a loop device instead."
+        ),
+        destructive_pattern!(
+            "dd-loop",
+            r#"\bdd\s+(?:[^|;&\s]+\s+)*?of=["']?/dev/loop\d"#,
+            "dd to a loop device overwrites the image file attached to it. Check losetup -a first.",
+            Medium
+        ),
+        // --- mkfs ---
+        destructive_pattern!(
+            "mkfs-disk",
+            r#"\b(?:mkfs(?:\.\w+)?|mke2fs|mkswap)\s+(?:[^|;&\s]+\s+)*?["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S"#,
+            "mkfs on a disk or partition ERASES the existing filesystem. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "Creating a filesystem (or swap area) writes fresh metadata over the target, \
+             making every file on the previous filesystem unreachable. Recovery requires \
+             specialist tools and often fails.\n\n\
+             Confirm the partition is the intended, unmounted one:\n  \
+             lsblk -f"
+        ),
+        destructive_pattern!(
+            "mkfs-loop",
+            r#"\b(?:mkfs(?:\.\w+)?|mke2fs|mkswap)\s+(?:[^|;&\s]+\s+)*?["']?/dev/loop\d"#,
+            "mkfs on a loop device erases the filesystem in the attached image file.",
+            Medium
+        ),
+        destructive_pattern!(
+            "mkfs-image",
+            r"\b(?:mkfs(?:\.\w+)?|mke2fs|mkswap)\s+(?![^|;&]*/dev/)(?:[^|;&\s]+\s+)*?[^-|;&\s]\S*",
+            "mkfs on an image file replaces its contents with an empty filesystem.",
+            Low
+        ),
+        // --- wipefs (only --all / --offset erase; --no-act is a dry run) ---
+        destructive_pattern!(
+            "wipefs-disk",
+            r#"\bwipefs\s+(?=(?:[^|;&]*\s)?(?:-[a-zA-Z]*[ao][a-zA-Z]*|--all|--offset)(?:\s|=|$))(?!(?:[^|;&]*\s)?(?:-[a-zA-Z]*n[a-zA-Z]*|--no-act)(?:\s|$))(?:[^|;&\s]+\s+)*?["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S"#,
+            "wipefs on a disk or partition erases its filesystem and partition-table signatures. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "wipefs --all removes the magic strings that identify filesystems, RAID members, \
+             and partition tables. The data blocks remain, but the system no longer \
+             recognizes the device and will happily reformat it.\n\n\
+             Preview what would be erased:\n  \
+             wipefs --no-act /dev/sdX"
+        ),
+        destructive_pattern!(
+            "wipefs-loop",
+            r#"\bwipefs\s+(?=(?:[^|;&]*\s)?(?:-[a-zA-Z]*[ao][a-zA-Z]*|--all|--offset)(?:\s|=|$))(?!(?:[^|;&]*\s)?(?:-[a-zA-Z]*n[a-zA-Z]*|--no-act)(?:\s|$))(?:[^|;&\s]+\s+)*?["']?/dev/loop\d"#,
+            "wipefs on a loop device erases signatures in the attached image file.",
+            Medium
+        ),
+        destructive_pattern!(
+            "wipefs-image",
+            r"\bwipefs\s+(?=(?:[^|;&]*\s)?(?:-[a-zA-Z]*[ao][a-zA-Z]*|--all|--offset)(?:\s|=|$))(?!(?:[^|;&]*\s)?(?:-[a-zA-Z]*n[a-zA-Z]*|--no-act)(?:\s|$))(?![^|;&]*/dev/)(?:[^|;&\s]+\s+)*?[^-|;&\s]\S*",
+            "wipefs on an image file erases its filesystem signatures.",
+            Low
+        ),
+        // --- blkdiscard ---
+        destructive_pattern!(
+            "blkdiscard-disk",
+            r#"\bblkdiscard\s+(?:[^|;&\s]+\s+)*?["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S"#,
+            "blkdiscard discards every block on the device. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "blkdiscard tells an SSD or thin-provisioned volume that all its blocks are \
+             unused. The device is free to erase them immediately, so unlike an overwrite \
+             there is usually nothing left to recover.\n\n\
+             Confirm the target first:\n  \
+             lsblk -o NAME,SIZE,MODEL,MOUNTPOINT"
+        ),
+        destructive_pattern!(
+            "blkdiscard-loop",
+            r#"\bblkdiscard\s+(?:[^|;&\s]+\s+)*?["']?/dev/loop\d"#,
+            "blkdiscard on a loop device punches holes through the attached image file.",
+            Medium
+        ),
+        // --- parted (rm / mklabel / mktable) ---
+        destructive_pattern!(
+            "parted-destroy-disk",
+            r#"\bparted\s+(?:-\S+\s+)*["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S*\s+(?:[^|;&]*\s)?(?:rm|mklabel|mktable)\b"#,
+            "parted rm/mklabel deletes partitions on a disk. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "parted applies changes immediately, with no write step to cancel. rm deletes \
+             a partition and mklabel replaces the whole partition table, orphaning every \
+             partition on the disk.\n\n\
+             Review the current layout first:\n  \
+             parted /dev/sdX print"
+        ),
+        destructive_pattern!(
+            "parted-destroy-loop",
+            r#"\bparted\s+(?:-\S+\s+)*["']?/dev/loop\d\S*\s+(?:[^|;&]*\s)?(?:rm|mklabel|mktable)\b"#,
+            "parted rm/mklabel on a loop device rewrites the partition table of the attached image file.",
+            Medium
+        ),
+        // --- fdisk / sfdisk fed a script ---
+        destructive_pattern!(
+            "fdisk-scripted-disk",
+            r#"(?:\|\s*fdisk\s+(?:-\S+\s+)*["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S|\bfdisk\s+(?:-\S+\s+)*["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S*\s*<|\bsfdisk\s+(?!(?:[^|;&]*\s)?(?:-[lLdJsgVF]|--list|--dump|--json|--show-size|--show-geometry|--verify|--list-free)(?:\s|$))(?:[^|;&\s]+\s+)*?["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S)"#,
+            "Scripted fdisk/sfdisk rewrites the partition table of a disk with no prompt. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "When fdisk reads its commands from a pipe or a heredoc, or sfdisk reads a \
+             layout from stdin, every answer is given in advance: partitions are deleted \
+             and the table is written without a chance to review it.\n\n\
+             Back up the current table first:\n  \
+             sfdisk --dump /dev/sdX > table.bak"
+        ),
+        destructive_pattern!(
+            "fdisk-scripted-loop",
+            r#"(?:\|\s*fdisk\s+(?:-\S+\s+)*["']?/dev/loop\d|\bfdisk\s+(?:-\S+\s+)*["']?/dev/loop\d\S*\s*<|\bsfdisk\s+(?!(?:[^|;&]*\s)?(?:-[lLdJsgVF]|--list|--dump|--json|--show-size|--show-geometry|--verify|--list-free)(?:\s|$))(?:[^|;&\s]+\s+)*?["']?/dev/loop\d)"#,
+            "Scripted fdisk/sfdisk on a loop device rewrites the partition table of the attached image file.",
+            Medium
+        ),
+        // --- shred on device nodes ---
+        destructive_pattern!(
+            "shred-disk",
+            r#"\bshred\s+(?:[^|;&\s]+\s+)*?["']?/dev/(?!loop\d|null\b|zero\b|full\b|u?random\b|std(?:in|out|err)\b|fd/|tty|pts/|shm/)\S"#,
+            "shred on a disk or partition overwrites the whole device. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "shred repeatedly overwrites its target with random data. Pointed at a device \
+             node it wipes the entire disk or partition, by design beyond recovery."
+        ),
+        destructive_pattern!(
+            "shred-loop",
+            r#"\bshred\s+(?:[^|;&\s]+\s+)*?["']?/dev/loop\d"#,
+            "shred on a loop device overwrites the attached image file.",
+            Medium
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "blockdevice");
+        assert!(pack.keywords.contains(&"blkdiscard"));
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn test_dd_target_classification() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "dd if=image.iso of=/dev/sdb bs=4M", "dd-disk");
+        assert_blocks_with_severity(&pack, "dd if=/dev/zero of=/dev/nvme0n1", Severity::Critical);
+        assert_blocks_with_pattern(&pack, "dd if=/dev/zero of=/dev/mapper/vg-root", "dd-disk");
+        assert_blocks_with_pattern(&pack, "dd if=disk.img of=/dev/loop0", "dd-loop");
+        assert_blocks_with_severity(&pack, "dd if=disk.img of=/dev/loop0", Severity::Medium);
+        assert_allows(&pack, "dd if=/dev/sda of=backup.img bs=4M");
+        assert_allows(&pack, "dd if=/dev/zero of=/dev/null count=1");
+        assert_allows(&pack, "dd if=/dev/urandom of=/dev/stdout count=1");
+    }
+
+    #[test]
+    fn test_mkfs_and_wipefs_target_classification() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "mkfs.ext4 -L data /dev/sdb1", "mkfs-disk");
+        assert_blocks_with_pattern(&pack, "mkswap /dev/vda2", "mkfs-disk");
+        assert_blocks_with_pattern(&pack, "mkfs.xfs /dev/loop3", "mkfs-loop");
+        assert_blocks_with_pattern(&pack, "mkfs.ext4 -F disk.img", "mkfs-image");
+        assert_blocks_with_severity(&pack, "mkfs.ext4 -F disk.img", Severity::Low);
+        assert_allows(&pack, "mkfs.ext4 -V");
+
+        assert_blocks_with_pattern(&pack, "wipefs -a /dev/sdc", "wipefs-disk");
+        assert_blocks_with_pattern(&pack, "wipefs --all --force /dev/sdc1", "wipefs-disk");
+        assert_blocks_with_pattern(&pack, "wipefs -a /dev/loop1", "wipefs-loop");
+        assert_blocks_with_pattern(&pack, "wipefs --all disk.img", "wipefs-image");
+        assert_allows(&pack, "wipefs /dev/sdc");
+        assert_allows(&pack, "wipefs --no-act --all /dev/sdc");
+    }
+
+    #[test]
+    fn test_discard_partition_and_shred_tools() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "blkdiscard -f /dev/nvme1n1", "blkdiscard-disk");
+        assert_blocks_with_pattern(&pack, "blkdiscard /dev/loop0", "blkdiscard-loop");
+
+        assert_blocks_with_pattern(&pack, "parted -s /dev/sda rm 2", "parted-destroy-disk");
+        assert_blocks_with_pattern(
+            &pack,
+            "parted --script /dev/sdb mklabel gpt",
+            "parted-destroy-disk",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "parted -s /dev/loop0 mklabel msdos",
+            "parted-destroy-loop",
+        );
+        assert_allows(&pack, "parted /dev/sda print");
+
+        assert_blocks_with_pattern(
+            &pack,
+            "printf 'o\\nw\\n' | fdisk /dev/sdb",
+            "fdisk-scripted-disk",
+        );
+        assert_blocks_with_pattern(&pack, "fdisk /dev/sdb <<EOF", "fdisk-scripted-disk");
+        assert_blocks_with_pattern(&pack, "sfdisk /dev/sdb < layout.txt", "fdisk-scripted-disk");
+        assert_blocks_with_pattern(&pack, "sfdisk --delete /dev/sda 3", "fdisk-scripted-disk");
+        assert_blocks_with_pattern(
+            &pack,
+            "sfdisk /dev/loop2 < layout.txt",
+            "fdisk-scripted-loop",
+        );
+        assert_allows(&pack, "sfdisk --dump /dev/sda");
+        assert_allows(&pack, "sfdisk -l /dev/sda");
+        assert_allows(&pack, "fdisk -l /dev/sda");
+
+        assert_blocks_with_pattern(&pack, "shred -n 1 -z /dev/sdd", "shred-disk");
+        assert_blocks_with_pattern(&pack, "shred /dev/loop0", "shred-loop");
+        assert_allows(&pack, "shred -u secrets.txt");
+    }
+}
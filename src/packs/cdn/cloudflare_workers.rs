@@ -6,6 +6,7 @@
 //! - KV operations (namespace/key/bulk delete)
 //! - R2 operations (bucket/object delete)
 //! - D1 database deletion
+//! - Zone-wide cache purges (`purge_everything`)
 
 use crate::packs::{DestructivePattern, Pack, SafePattern};
 use crate::{destructive_pattern, safe_pattern};
@@ -17,10 +18,11 @@ pub fn create_pack() -> Pack {
         id: "cdn.cloudflare_workers".to_string(),
         name: "Cloudflare Workers",
         description: "Protects against destructive Cloudflare Workers, KV, R2, and D1 operations \
-                      via the Wrangler CLI.",
-        keywords: &["wrangler"],
+                      via the Wrangler CLI, plus zone-wide cache purges.",
+        keywords: &["wrangler", "purge_everything"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -32,11 +34,11 @@ fn create_safe_patterns() -> Vec<SafePattern> {
         // Account/auth info
         safe_pattern!("wrangler-whoami", r"wrangler\s+whoami\b"),
         // KV read operations
-        safe_pattern!("wrangler-kv-get", r"wrangler\s+kv:key\s+get\b"),
-        safe_pattern!("wrangler-kv-list", r"wrangler\s+kv:key\s+list\b"),
+        safe_pattern!("wrangler-kv-get", r"wrangler\s+kv(?::|\s+)key\s+get\b"),
+        safe_pattern!("wrangler-kv-list", r"wrangler\s+kv(?::|\s+)key\s+list\b"),
         safe_pattern!(
             "wrangler-kv-namespace-list",
-            r"wrangler\s+kv:namespace\s+list\b"
+            r"wrangler\s+kv(?::|\s+)namespace\s+list\b"
         ),
         // R2 read operations
         safe_pattern!("wrangler-r2-object-get", r"wrangler\s+r2\s+object\s+get\b"),
@@ -89,7 +91,7 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
         // KV destructive operations
         destructive_pattern!(
             "wrangler-kv-key-delete",
-            r"wrangler\s+kv:key\s+delete\b",
+            r"wrangler\s+kv(?::|\s+)key\s+delete\b",
             "wrangler kv:key delete removes a key from KV storage.",
             Medium,
             "Deleting a KV key immediately removes the data at all edge locations. \
@@ -102,7 +104,7 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
         ),
         destructive_pattern!(
             "wrangler-kv-namespace-delete",
-            r"wrangler\s+kv:namespace\s+delete\b",
+            r"wrangler\s+kv(?::|\s+)namespace\s+delete\b",
             "wrangler kv:namespace delete removes an entire KV namespace.",
             Critical,
             "Deleting a KV namespace permanently removes ALL keys and values within it. \
@@ -115,7 +117,7 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
         ),
         destructive_pattern!(
             "wrangler-kv-bulk-delete",
-            r"wrangler\s+kv:bulk\s+delete\b",
+            r"wrangler\s+kv(?::|\s+)bulk\s+delete\b",
             "wrangler kv:bulk delete removes multiple keys from KV storage.",
             High,
             "Bulk delete removes many KV keys at once based on a JSON file. This is \
@@ -167,6 +169,20 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              - wrangler d1 info: Review database details\n\
              - Remove Worker bindings before deletion"
         ),
+        // Zone-wide cache purge
+        destructive_pattern!(
+            "cloudflare-purge-everything",
+            r"\bpurge_everything\b[\s\\'\x22]*:\s*true\b",
+            "purge_everything purges the entire Cloudflare cache for a zone.",
+            High,
+            "Purging everything evicts every cached asset for the zone at once. All \
+             subsequent requests go to the origin until the cache refills, which can \
+             overload origin servers and cause slowdowns or outages.\n\n\
+             Safer alternatives:\n\
+             - Purge specific URLs with the \"files\" parameter\n\
+             - Purge by cache tag or prefix to limit the blast radius\n\
+             - Schedule full purges during low-traffic windows"
+        ),
     ]
 }
 
@@ -197,6 +213,8 @@ mod tests {
         assert_safe_pattern_matches(&pack, "wrangler kv:key get --namespace-id=abc KEY");
         assert_safe_pattern_matches(&pack, "wrangler kv:key list --namespace-id=abc");
         assert_safe_pattern_matches(&pack, "wrangler kv:namespace list");
+        assert_safe_pattern_matches(&pack, "wrangler kv namespace list");
+        assert_safe_pattern_matches(&pack, "wrangler kv key get --namespace-id=abc KEY");
         // R2 read
         assert_safe_pattern_matches(&pack, "wrangler r2 object get my-bucket/path/to/obj");
         assert_safe_pattern_matches(&pack, "wrangler r2 bucket list");
@@ -254,4 +272,43 @@ mod tests {
         // D1
         assert_blocks_with_pattern(&pack, "wrangler d1 delete my-db", "wrangler-d1-delete");
     }
+
+    #[test]
+    fn blocks_wrangler_v3_kv_syntax() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "wrangler kv namespace delete --namespace-id=abc",
+            "wrangler-kv-namespace-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "wrangler kv key delete --binding=CACHE KEY",
+            "wrangler-kv-key-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "wrangler kv bulk delete --binding=CACHE keys.json",
+            "wrangler-kv-bulk-delete",
+        );
+    }
+
+    #[test]
+    fn blocks_zone_purge_everything() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            r#"curl -X POST https://api.cloudflare.com/client/v4/zones/abc/purge_cache --data '{"purge_everything":true}'"#,
+            "cloudflare-purge-everything",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            r#"curl -X POST https://api.cloudflare.com/client/v4/zones/abc/purge_cache -d '{"purge_everything": true}'"#,
+            "cloudflare-purge-everything",
+        );
+        assert_no_match(
+            &pack,
+            r#"curl -X POST https://api.cloudflare.com/client/v4/zones/abc/purge_cache -d '{"files":["https://example.com/a.css"]}'"#,
+        );
+    }
 }
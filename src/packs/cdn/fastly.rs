@@ -5,6 +5,7 @@
 //! - Domain deletion (`fastly domain delete`)
 //! - Backend deletion (`fastly backend delete`)
 //! - VCL deletion (`fastly vcl delete`)
+//! - Full cache purges (`fastly purge --all`)
 
 use crate::packs::{DestructivePattern, Pack, SafePattern};
 use crate::{destructive_pattern, safe_pattern};
@@ -16,10 +17,11 @@ pub fn create_pack() -> Pack {
         id: "cdn.fastly".to_string(),
         name: "Fastly CDN",
         description: "Protects against destructive Fastly CLI operations like service, domain, \
-                      backend, and VCL deletion.",
+                      backend, and VCL deletion, plus full cache purges.",
         keywords: &["fastly"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -208,6 +210,20 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              - Deploy replacement package before deleting\n\
              - Use fastly compute describe to review current state"
         ),
+        // Full cache purge
+        destructive_pattern!(
+            "fastly-purge-all",
+            r"fastly\s+purge\b.*\s--all\b",
+            "fastly purge --all purges the entire cache for a service.",
+            High,
+            "Purging all content evicts every cached object for the service. Every \
+             request is sent to the origin until the cache warms up again, which can \
+             overload backends and cause widespread latency or errors.\n\n\
+             Safer alternatives:\n\
+             - fastly purge --url: Purge a single URL\n\
+             - fastly purge --key: Purge by surrogate key\n\
+             - Use --soft to mark content stale instead of evicting it"
+        ),
     ]
 }
 
@@ -358,4 +374,15 @@ mod tests {
         let pack = create_pack();
         assert_blocks_with_pattern(&pack, "fastly compute delete", "fastly-compute-delete");
     }
+
+    #[test]
+    fn blocks_purge_all() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "fastly purge --all --service-id abc",
+            "fastly-purge-all",
+        );
+        assert_no_match(&pack, "fastly purge --key product-123 --service-id abc");
+    }
 }
@@ -3,10 +3,25 @@
 //! This includes patterns for:
 //! - rm -rf outside temp directories (blocked)
 //! - rm -rf in /tmp, /var/tmp, $TMPDIR (allowed)
+//! - find -delete / find -exec rm -rf outside temp directories (blocked)
+//! - xargs rm -rf as the consumer of a pipeline (blocked)
 
+use crate::normalize::CommandFlagSpec;
 use crate::packs::{DestructivePattern, Pack, PatternSuggestion, Platform, SafePattern, Severity};
 use crate::{destructive_pattern, safe_pattern};
 
+/// `rm` flags are normalized before matching, so `rm -R --force` and
+/// `rm -r -f` are evaluated like `rm -rf`.
+pub const FLAG_SPECS: &[CommandFlagSpec] = &[CommandFlagSpec::new(&["rm"])
+    .long_aliases(&[
+        ("recursive", 'r'),
+        ("force", 'f'),
+        ("dir", 'd'),
+        ("verbose", 'v'),
+        ("interactive", 'i'),
+    ])
+    .short_aliases(&[('R', 'r')])];
+
 // ============================================================================
 // Suggestion constants (must be 'static for the pattern struct)
 // ============================================================================
@@ -96,6 +111,33 @@ const RM_RECURSIVE_FORCE_SUGGESTIONS: &[PatternSuggestion] = &[
         "Safe temp directory deletion (allowed without confirmation)",
     ),
 ];
+/// Suggestions for `find -delete` / `find -exec rm -rf` patterns.
+const FIND_DELETE_SUGGESTIONS: &[PatternSuggestion] = &[
+    PatternSuggestion::new(
+        "find {path} -name '{pattern}' -print | head -20",
+        "Preview what the expression matches before deleting",
+    ),
+    PatternSuggestion::new(
+        "find {path} -name '{pattern}' -ok rm {} \\;",
+        "Confirm each deletion interactively with -ok",
+    ),
+    PatternSuggestion::new(
+        "find /tmp/{subdir} -delete",
+        "Deletion under a temp directory is allowed without confirmation",
+    ),
+];
+
+/// Suggestions for `xargs rm -rf` pattern.
+const XARGS_RM_SUGGESTIONS: &[PatternSuggestion] = &[
+    PatternSuggestion::new(
+        "{producer} | head -20",
+        "Preview the paths the pipeline would pass to rm",
+    ),
+    PatternSuggestion::new(
+        "{producer} | xargs -p rm -r",
+        "Prompt before each rm invocation with xargs -p",
+    ),
+];
 use crate::{normalize::NormalizeTokenKind, normalize::tokenize_for_normalization};
 use std::ops::Range;
 
@@ -109,6 +151,35 @@ const RM_R_F_SEPARATE_REASON: &str =
 const RM_RECURSIVE_FORCE_NAME: &str = "rm-recursive-force-long";
 const RM_RECURSIVE_FORCE_REASON: &str =
     "rm --recursive --force is destructive and requires human approval.";
+const FIND_DELETE_ROOT_HOME_NAME: &str = "find-delete-root-home";
+const FIND_DELETE_ROOT_HOME_REASON: &str = "find -delete (or -exec rm -rf) starting from root or home paths is EXTREMELY DANGEROUS. This command will NOT be executed. Ask the user to run it manually if truly needed.";
+const FIND_DELETE_NAME: &str = "find-delete";
+const FIND_DELETE_REASON: &str = "find -delete (or -exec rm -rf) deletes every match and requires human approval. Preview the matches with find first, then ask the user to run the deletion manually.";
+const XARGS_RM_NAME: &str = "xargs-rm";
+const XARGS_RM_REASON: &str = "xargs rm -rf deletes every path fed to it and requires human approval. Preview the input first, then ask the user to run the deletion manually.";
+
+/// `find` options that come before the starting points.
+const FIND_LEADING_FLAGS: &[&str] = &["-H", "-L", "-P"];
+/// Words ending a `find -exec` command.
+const FIND_EXEC_TERMINATORS: &[&str] = &[";", "\\;", "';'", "\";\"", "+"];
+/// `xargs` options that take the following word as their value.
+const XARGS_VALUE_FLAGS: &[&str] = &[
+    "-a",
+    "-d",
+    "-E",
+    "-I",
+    "-L",
+    "-n",
+    "-P",
+    "-s",
+    "--arg-file",
+    "--delimiter",
+    "--max-args",
+    "--max-chars",
+    "--max-lines",
+    "--max-procs",
+    "--process-slot-var",
+];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum QuoteKind {
@@ -198,12 +269,19 @@ impl RmFlagTracker {
     }
 }
 
+/// Parse every `rm`, `find`, and `xargs` segment of a compound command.
+///
+/// Any denied segment denies the command, with the span on that segment, so
+/// `find . | xargs rm -rf` is attributed to the `xargs` consumer rather than
+/// the harmless producer. The command is allowed only when no segment is
+/// denied and at least one was recognized as a safe temp-directory deletion.
 pub(crate) fn parse_rm_command(command: &str) -> RmParseDecision {
     let tokens = tokenize_for_normalization(command);
     if tokens.is_empty() {
         return RmParseDecision::NoMatch;
     }
 
+    let mut decision = RmParseDecision::NoMatch;
     let mut i = 0;
     while i < tokens.len() {
         let current = &tokens[i];
@@ -217,8 +295,16 @@ pub(crate) fn parse_rm_command(command: &str) -> RmParseDecision {
             continue;
         };
 
-        if text == "rm" {
-            return parse_rm_segment(command, &tokens, i + 1);
+        let segment = match text {
+            "rm" => parse_rm_segment(command, &tokens, i + 1),
+            "find" => parse_find_segment(command, &tokens, i + 1),
+            "xargs" => parse_xargs_segment(command, &tokens, i),
+            _ => RmParseDecision::NoMatch,
+        };
+        match segment {
+            RmParseDecision::Deny(hit) => return RmParseDecision::Deny(hit),
+            RmParseDecision::Allow => decision = RmParseDecision::Allow,
+            RmParseDecision::NoMatch => {}
         }
 
         // Skip to the next separator before scanning for another command word.
@@ -228,7 +314,7 @@ pub(crate) fn parse_rm_command(command: &str) -> RmParseDecision {
         }
     }
 
-    RmParseDecision::NoMatch
+    decision
 }
 
 #[allow(clippy::too_many_lines)]
@@ -357,6 +443,191 @@ fn parse_rm_segment(
     })
 }
 
+/// Parse a `find` invocation for `-delete` or `-exec rm` with `-r`/`-f`.
+///
+/// Like `rm -rf`, the deletion is allowed when every starting point is inside
+/// a temp directory and critical when one is a root or home path.
+fn parse_find_segment(
+    command: &str,
+    tokens: &[crate::normalize::NormalizeToken],
+    start_idx: usize,
+) -> RmParseDecision {
+    let mut starting_points: Vec<PathToken<'_>> = Vec::new();
+    let mut in_expression = false;
+    let mut action_span: Option<Range<usize>> = None;
+
+    let mut idx = start_idx;
+    while idx < tokens.len() {
+        let token = &tokens[idx];
+        if token.kind == NormalizeTokenKind::Separator {
+            break;
+        }
+        let Some(text) = token.text(command) else {
+            idx += 1;
+            continue;
+        };
+
+        if !in_expression {
+            if FIND_LEADING_FLAGS.contains(&text) || text.starts_with("-O") {
+                idx += 1;
+                continue;
+            }
+            if text == "-D" {
+                idx += 2;
+                continue;
+            }
+            if !(text.starts_with('-') || text.starts_with('!') || text.starts_with("\\(")) {
+                let (quote, unquoted) = strip_outer_quotes(text);
+                starting_points.push(PathToken {
+                    unquoted,
+                    quote,
+                    range: token.byte_range.clone(),
+                });
+                idx += 1;
+                continue;
+            }
+            in_expression = true;
+        }
+
+        if text == "-delete" {
+            action_span.get_or_insert_with(|| token.byte_range.clone());
+        } else if text == "-exec" || text == "-execdir" {
+            let end = find_exec_end(command, tokens, idx + 1);
+            if let Some(rm_end) = exec_rm_flags_end(command, &tokens[idx + 1..end]) {
+                action_span.get_or_insert(token.byte_range.start..rm_end);
+            }
+            idx = end;
+        }
+        idx += 1;
+    }
+
+    let Some(span) = action_span else {
+        return RmParseDecision::NoMatch;
+    };
+
+    // With no starting point, find searches the current directory.
+    if !starting_points.is_empty()
+        && starting_points
+            .iter()
+            .all(|path| path_is_safe_for_style(path, RmFlagStyle::Combined))
+    {
+        return RmParseDecision::Allow;
+    }
+
+    let (pattern_name, reason, severity) = if starting_points.iter().any(path_is_root_home) {
+        (
+            FIND_DELETE_ROOT_HOME_NAME,
+            FIND_DELETE_ROOT_HOME_REASON,
+            Severity::Critical,
+        )
+    } else {
+        (FIND_DELETE_NAME, FIND_DELETE_REASON, Severity::High)
+    };
+
+    RmParseDecision::Deny(RmParseMatch {
+        pattern_name,
+        reason,
+        severity,
+        span: Some(span),
+    })
+}
+
+/// Index of the word ending a `find -exec` command (or of the segment end).
+fn find_exec_end(
+    command: &str,
+    tokens: &[crate::normalize::NormalizeToken],
+    start_idx: usize,
+) -> usize {
+    let mut idx = start_idx;
+    while idx < tokens.len() && tokens[idx].kind != NormalizeTokenKind::Separator {
+        if tokens[idx]
+            .text(command)
+            .is_some_and(|text| FIND_EXEC_TERMINATORS.contains(&text))
+        {
+            break;
+        }
+        idx += 1;
+    }
+    idx
+}
+
+/// If `words` run `rm` with a recursive or force flag, the end of the last
+/// such flag.
+fn exec_rm_flags_end(command: &str, words: &[crate::normalize::NormalizeToken]) -> Option<usize> {
+    let (first, args) = words.split_first()?;
+    if first.text(command)? != "rm" {
+        return None;
+    }
+
+    let mut end = None;
+    for token in args {
+        let Some(text) = token.text(command) else {
+            continue;
+        };
+        if token.kind == NormalizeTokenKind::Separator || text == "--" {
+            break;
+        }
+        if is_recursive_or_force_flag(text) {
+            end = Some(token.byte_range.end);
+        }
+    }
+    end
+}
+
+fn is_recursive_or_force_flag(text: &str) -> bool {
+    if let Some(long) = text.strip_prefix("--") {
+        return long.starts_with("recursive") || long.starts_with("force");
+    }
+    text.strip_prefix('-')
+        .is_some_and(|short| short.chars().any(|c| matches!(c, 'r' | 'R' | 'f')))
+}
+
+/// Parse `xargs [options] rm ...`, where the paths arrive on stdin and so can
+/// never be shown to be temp paths. The span covers the `xargs` stage.
+fn parse_xargs_segment(
+    command: &str,
+    tokens: &[crate::normalize::NormalizeToken],
+    xargs_idx: usize,
+) -> RmParseDecision {
+    let mut idx = xargs_idx + 1;
+    while let Some(text) = tokens
+        .get(idx)
+        .filter(|token| token.kind == NormalizeTokenKind::Word)
+        .and_then(|token| token.text(command))
+    {
+        if text == "--" {
+            idx += 1;
+            break;
+        }
+        if !text.starts_with('-') || text == "-" {
+            break;
+        }
+        idx += if XARGS_VALUE_FLAGS.contains(&text) {
+            2
+        } else {
+            1
+        };
+    }
+
+    let end = tokens[idx.min(tokens.len())..]
+        .iter()
+        .position(|token| token.kind == NormalizeTokenKind::Separator)
+        .map_or(tokens.len(), |offset| idx + offset);
+    if idx >= end {
+        return RmParseDecision::NoMatch;
+    }
+    let Some(rm_end) = exec_rm_flags_end(command, &tokens[idx..end]) else {
+        return RmParseDecision::NoMatch;
+    };
+
+    RmParseDecision::Deny(RmParseMatch {
+        pattern_name: XARGS_RM_NAME,
+        reason: XARGS_RM_REASON,
+        severity: Severity::High,
+        span: Some(tokens[xargs_idx].byte_range.start..rm_end),
+    })
+}
+
 fn strip_outer_quotes(token: &str) -> (QuoteKind, &str) {
     if token.len() >= 2 {
         if token.starts_with('"') && token.ends_with('"') {
@@ -449,16 +720,102 @@ fn path_is_root_home(path: &PathToken<'_>) -> bool {
     false
 }
 
+/// Where a path argument points, for rules whose danger depends on the target.
+///
+/// Ordered from least to most dangerous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PathTarget {
+    /// Inside `/tmp`, `/var/tmp`, or `$TMPDIR`, as `rm -rf` allows.
+    Temp,
+    /// Relative to the working directory, without `..`.
+    Project,
+    /// Anything else: other absolute paths, `..`, variables, home subdirectories.
+    Other,
+    /// A home directory itself (`~`, `$HOME`, `/home/<user>`, `/Users/<user>`).
+    Home,
+    /// `/`, a top-level system directory or anything under it, or `/home` itself.
+    System,
+}
+
+/// Top-level directories whose trees belong to the system.
+const SYSTEM_DIRS: &[&str] = &[
+    "bin", "boot", "dev", "etc", "lib", "lib64", "opt", "proc", "root", "run", "sbin", "srv",
+    "sys", "usr", "var", "Library", "System",
+];
+
+/// Classify one path argument as written on the command line (quotes included).
+///
+/// Temp paths follow the `rm -rf` rules, and `~` only counts when unquoted.
+#[must_use]
+pub fn classify_path_target(word: &str) -> PathTarget {
+    let (quote, unquoted) = strip_outer_quotes(word);
+    let token = PathToken {
+        unquoted,
+        quote,
+        range: 0..0,
+    };
+    if path_is_safe_for_style(&token, RmFlagStyle::Combined) {
+        return PathTarget::Temp;
+    }
+
+    let home_rest = if quote == QuoteKind::None {
+        unquoted.strip_prefix('~')
+    } else {
+        None
+    }
+    .or_else(|| {
+        (quote != QuoteKind::Single)
+            .then(|| {
+                unquoted
+                    .strip_prefix("${HOME}")
+                    .or_else(|| unquoted.strip_prefix("$HOME"))
+            })
+            .flatten()
+    });
+    if let Some(rest) = home_rest {
+        return if rest.trim_matches(['/', '*']).is_empty() {
+            PathTarget::Home
+        } else {
+            PathTarget::Other
+        };
+    }
+
+    if let Some(absolute) = unquoted.strip_prefix('/') {
+        let mut parts = absolute
+            .trim_end_matches(['/', '*'])
+            .split('/')
+            .filter(|part| !part.is_empty() && *part != ".");
+        return match (parts.next(), parts.next(), parts.next()) {
+            (None, ..) => PathTarget::System,
+            (Some("home" | "Users"), None, _) => PathTarget::System,
+            (Some("home" | "Users"), Some(_), None) => PathTarget::Home,
+            (Some(top), ..) if SYSTEM_DIRS.contains(&top) => PathTarget::System,
+            _ => PathTarget::Other,
+        };
+    }
+
+    if unquoted.is_empty() || unquoted.starts_with(['$', '~', '`']) || has_dotdot_segment(unquoted)
+    {
+        PathTarget::Other
+    } else {
+        PathTarget::Project
+    }
+}
+
+/// Former pattern names, kept so allowlist entries using them still match.
+pub const RULE_ALIASES: &[(&str, &str)] = &[("rm-rf-root", "rm-rf-root-home")];
+
 /// Create the core filesystem pack.
 #[must_use]
 pub fn create_pack() -> Pack {
     Pack {
         id: "core.filesystem".to_string(),
         name: "Core Filesystem",
-        description: "Protects against dangerous rm -rf commands outside temp directories",
-        keywords: &["rm"],
+        description: "Protects against dangerous rm -rf, find -delete, and xargs rm commands outside temp directories",
+        keywords: &["rm", "find"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -594,6 +951,11 @@ fn create_safe_patterns() -> Vec<SafePattern> {
             "rm-force-recursive-tmpdir-brace",
             r"^rm\s+.*--force.*--recursive\s+(?:\$\{TMPDIR\}/(?!\.\.(?:/|\s|$)|[^\s]*/\.\.(?:/|\s|$))\S*(?:\s+|$))+$"
         ),
+        // find -delete whose starting points are all temp directories
+        safe_pattern!(
+            "find-delete-tmp",
+            r"^find\s+(?:(?:/tmp|/var/tmp|\$TMPDIR|\$\{TMPDIR\})/(?!\.\.(?:/|\s|$)|[^\s]*/\.\.(?:/|\s|$))\S*\s+)+-[^|;&]*$"
+        ),
     ]
 }
 
@@ -605,6 +967,54 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
     // - Low: Log only
 
     vec![
+        // find -delete / -exec rm -rf from root or home (CRITICAL)
+        destructive_pattern!(
+            "find-delete-root-home",
+            r#"\bfind\s+(?:-[HLP]\s+)*["']?[/~][^|;&]*\s(?:-delete\b|-exec(?:dir)?\s+rm\s+(?:\S+\s+)*?(?:-[a-zA-Z]*[rRf]|--recursive|--force))"#,
+            "find -delete (or -exec rm -rf) starting from root or home paths is EXTREMELY DANGEROUS. This command will NOT be executed. Ask the user to run it manually if truly needed.",
+            Critical,
+            "find walks the whole tree below its starting points, and -delete (or -exec \
+             rm -rf) removes everything the expression matches. Starting from / or ~, an \
+             overly broad expression such as -name '*' deletes the operating system or \
+             the user's home directory:\n\n\
+             - There is no confirmation and no trash\n\
+             - Expression mistakes (a missing -name, a wrong -type) widen the match silently\n\
+             - -delete implies -depth, so directories are emptied before they are removed\n\n\
+             Preview the matches first:\n  \
+             find /path -name 'pattern' -print | head -20",
+            FIND_DELETE_SUGGESTIONS
+        ),
+        // find -delete / -exec rm -rf elsewhere
+        destructive_pattern!(
+            "find-delete",
+            r"\bfind\b[^|;&]*\s(?:-delete\b|-exec(?:dir)?\s+rm\s+(?:\S+\s+)*?(?:-[a-zA-Z]*[rRf]|--recursive|--force))",
+            "find -delete (or -exec rm -rf) deletes every match and requires human approval. Preview the matches with find first, then ask the user to run the deletion manually.",
+            High,
+            "find -delete and find -exec rm -rf delete every file the expression matches, \
+             recursively and without confirmation. The set of matches is easy to get wrong: \
+             predicates are order-sensitive and a forgotten filter deletes the whole tree.\n\n\
+             Safer approach:\n\
+             - Run the same find with -print and review the output\n\
+             - Use -ok rm {} \\; to confirm each deletion\n\
+             - find under /tmp, /var/tmp, $TMPDIR: Allowed (safe temp directories)",
+            FIND_DELETE_SUGGESTIONS
+        ),
+        // xargs rm -rf consuming a pipeline
+        destructive_pattern!(
+            "xargs-rm",
+            r"\bxargs\s+(?:[^|;&\s]+\s+)*?rm\s+(?:[^|;&\s]+\s+)*?(?:-[a-zA-Z]*[rRf]|--recursive|--force)",
+            "xargs rm -rf deletes every path fed to it and requires human approval. Preview the input first, then ask the user to run the deletion manually.",
+            High,
+            "xargs rm -rf deletes whatever paths arrive on its input, typically from an \
+             earlier stage such as find, ls, or grep -l. The paths are never visible in the \
+             command itself, so a producer that matches more than intended (or emits \
+             names with spaces that xargs splits) deletes the wrong files.\n\n\
+             Safer approach:\n\
+             - Run the producer alone and review its output\n\
+             - Use xargs -p to confirm each rm invocation\n\
+             - Use find -print0 | xargs -0 so names are not split",
+            XARGS_RM_SUGGESTIONS
+        ),
         // rm -rf on root or home paths (CRITICAL - catastrophic, never allow)
         destructive_pattern!(
             "rm-rf-root-home",
@@ -697,6 +1107,30 @@ mod tests {
     use crate::packs::Severity;
     use crate::packs::test_helpers::*;
 
+    #[test]
+    fn path_targets_are_classified() {
+        for (word, expected) in [
+            ("/tmp/build", PathTarget::Temp),
+            ("$TMPDIR/out", PathTarget::Temp),
+            ("./target", PathTarget::Project),
+            ("build/dist", PathTarget::Project),
+            ("../shared", PathTarget::Other),
+            ("/mnt/data", PathTarget::Other),
+            ("~/.ssh", PathTarget::Other),
+            ("'~'", PathTarget::Other),
+            ("~", PathTarget::Home),
+            ("\"$HOME/\"", PathTarget::Home),
+            ("/home/alice", PathTarget::Home),
+            ("/home", PathTarget::System),
+            ("/", PathTarget::System),
+            ("/*", PathTarget::System),
+            ("/etc/nginx", PathTarget::System),
+            ("'/usr'", PathTarget::System),
+        ] {
+            assert_eq!(classify_path_target(word), expected, "{word}");
+        }
+    }
+
     #[test]
     fn test_pack_creation() {
         let pack = create_pack();
@@ -862,4 +1296,87 @@ mod tests {
     fn test_rm_parser_option_terminator() {
         assert_rm_parser_no_match("rm -- -rf /tmp/safe");
     }
+
+    #[test]
+    fn test_find_delete_and_xargs_rm_patterns() {
+        let pack = create_pack();
+        assert!(pack.keywords.contains(&"find"));
+        assert_blocks_with_pattern(&pack, "find / -name '*' -delete", "find-delete-root-home");
+        assert_blocks_with_severity(&pack, "find / -name '*' -delete", Severity::Critical);
+        assert_blocks_with_pattern(&pack, "find . -type f -delete", "find-delete");
+        assert_blocks_with_pattern(&pack, "find . -exec rm -rf {} +", "find-delete");
+        assert_blocks_with_pattern(&pack, "ls | xargs -0 rm -rf", "xargs-rm");
+        assert_safe_pattern_matches(&pack, "find /tmp/cache -type f -delete");
+        assert_allows(&pack, "find . -name '*.txt' -exec rm {} \\;");
+        assert_allows(&pack, "find . -name '*.rs' | xargs grep todo");
+    }
+
+    #[test]
+    fn test_rm_parser_find_delete() {
+        assert_rm_parser_denies(
+            "find / -name '*' -delete",
+            FIND_DELETE_ROOT_HOME_NAME,
+            Severity::Critical,
+        );
+        assert_rm_parser_denies(
+            "find -L ~ -type f -delete",
+            FIND_DELETE_ROOT_HOME_NAME,
+            Severity::Critical,
+        );
+        assert_rm_parser_denies("find -delete", FIND_DELETE_NAME, Severity::High);
+        assert_rm_parser_denies(
+            "find src \\( -name a -o -name b \\) -execdir rm -f {} \\;",
+            FIND_DELETE_NAME,
+            Severity::High,
+        );
+        assert_rm_parser_denies(
+            "find /tmp/a /etc -delete",
+            FIND_DELETE_ROOT_HOME_NAME,
+            Severity::Critical,
+        );
+        assert_rm_parser_allows("find /tmp/cache -delete");
+        assert_rm_parser_allows(r#"find "$TMPDIR/cache" -exec rm -rf {} +"#);
+        assert_rm_parser_denies(
+            "find /tmp/../etc -delete",
+            FIND_DELETE_ROOT_HOME_NAME,
+            Severity::Critical,
+        );
+        assert_rm_parser_no_match("find . -name '*.txt' -exec rm {} \\;");
+        assert_rm_parser_no_match("find . -name '*.rs' -print");
+    }
+
+    #[test]
+    fn test_rm_parser_attributes_pipeline_stage() {
+        let command = "find . -name '*.o' | xargs -n 10 rm -rf";
+        let RmParseDecision::Deny(hit) = parse_rm_command(command) else {
+            unreachable!("expected xargs rm -rf to be denied");
+        };
+        assert_eq!(hit.pattern_name, XARGS_RM_NAME);
+        let span = hit.span.expect("span");
+        assert_eq!(&command[span], "xargs -n 10 rm -rf");
+
+        let command = "find . -name '*.o' -exec rm -rf {} + && make";
+        let RmParseDecision::Deny(hit) = parse_rm_command(command) else {
+            unreachable!("expected find -exec rm -rf to be denied");
+        };
+        assert_eq!(&command[hit.span.expect("span")], "-exec rm -rf");
+
+        assert_rm_parser_no_match("find . -name '*.orig' | xargs rm");
+        assert_rm_parser_no_match("echo a | xargs -I {} echo rm -rf {}");
+    }
+
+    #[test]
+    fn test_rm_parser_checks_every_segment() {
+        assert_rm_parser_denies(
+            "rm -rf /tmp/scratch; rm -rf /",
+            RM_RF_ROOT_HOME_NAME,
+            Severity::Critical,
+        );
+        assert_rm_parser_denies(
+            "rm -rf /tmp/scratch && find . -delete",
+            FIND_DELETE_NAME,
+            Severity::High,
+        );
+        assert_rm_parser_allows("rm -rf /tmp/a && rm -rf /tmp/b");
+    }
 }
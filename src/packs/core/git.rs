@@ -5,9 +5,167 @@
 //! - History rewriting (push --force, branch -D)
 //! - Stash destruction (stash drop, stash clear)
 
+use crate::normalize::CommandFlagSpec;
 use crate::packs::{DestructivePattern, Pack, PatternSuggestion, SafePattern};
+use crate::suggestions::{
+    ParsedCommand, Suggestion, SuggestionKind, SuggestionProvider, shell_quote,
+};
 use crate::{destructive_pattern, safe_pattern};
 
+/// Git subcommands whose flags are normalized before matching, so that
+/// `git clean -d -f` and `git clean --force -d` hit the same rule as
+/// `git clean -fd`.
+///
+/// `git push --force` is not folded into `-f`: `push-force-long` and
+/// `push-force-short` are both Critical, and allowlists name them.
+pub const FLAG_SPECS: &[CommandFlagSpec] = &[
+    CommandFlagSpec::new(&["git", "clean"])
+        .long_aliases(&[("force", 'f'), ("dry-run", 'n'), ("quiet", 'q')])
+        .short_with_value(&['e'])
+        .long_with_value(&["exclude"]),
+    CommandFlagSpec::new(&["git", "push"])
+        .long_aliases(&[("set-upstream", 'u'), ("verbose", 'v')])
+        .short_with_value(&['o'])
+        .long_with_value(&["push-option", "repo", "receive-pack", "exec"]),
+    CommandFlagSpec::new(&["git", "branch"])
+        .long_aliases(&[("delete", 'd'), ("force", 'f')])
+        .short_with_value(&['u'])
+        .long_with_value(&["set-upstream-to"]),
+];
+
+/// Former pattern names, kept so allowlist entries using them still match.
+pub const RULE_ALIASES: &[(&str, &str)] = &[("restore-discard", "restore-worktree")];
+
+/// Patterns whose suggestions are built from the blocked command.
+pub const SUGGESTION_PROVIDERS: &[(&str, SuggestionProvider)] = &[
+    ("push-force-long", force_with_lease_suggestions),
+    ("push-force-short", force_with_lease_suggestions),
+];
+
+/// Options before the `git` subcommand that take a separate value.
+const GLOBAL_OPTIONS_WITH_VALUE: &[&str] = &["-C", "-c", "--git-dir", "--work-tree", "--namespace"];
+
+/// Long `git push` options that take a separate value.
+const PUSH_OPTIONS_WITH_VALUE: &[&str] = &["--push-option", "--repo", "--receive-pack", "--exec"];
+
+/// The blocked force push rewritten to use `--force-with-lease`, keeping its
+/// remote, refspecs and other options, plus a look at the remote commits it
+/// would overwrite when the branch is known.
+fn force_with_lease_suggestions(command: &ParsedCommand<'_>) -> Vec<Suggestion> {
+    let Some(args) = command.args_of("git") else {
+        return Vec::new();
+    };
+    let mut args = args.into_iter();
+
+    let mut global = Vec::new();
+    loop {
+        let Some(arg) = args.next() else {
+            return Vec::new();
+        };
+        if arg == "push" {
+            break;
+        }
+        if !arg.starts_with('-') {
+            return Vec::new();
+        }
+        let takes_value = GLOBAL_OPTIONS_WITH_VALUE.contains(&arg.as_str());
+        global.push(arg);
+        if takes_value {
+            global.extend(args.next());
+        }
+    }
+
+    let mut options = Vec::new();
+    let mut positionals = Vec::new();
+    let mut only_positionals = false;
+    while let Some(arg) = args.next() {
+        if only_positionals || !arg.starts_with('-') || arg == "-" {
+            positionals.push(arg);
+        } else if arg == "--" {
+            only_positionals = true;
+        } else if arg == "--force"
+            || arg == "--force-if-includes"
+            || arg.starts_with("--force-with-lease")
+        {
+        } else if arg.starts_with("--") {
+            let takes_value = PUSH_OPTIONS_WITH_VALUE.contains(&arg.as_str());
+            options.push(arg);
+            if takes_value {
+                options.extend(args.next());
+            }
+        } else {
+            // A short-flag cluster such as `-uf` or `-fopush.opt`: drop the
+            // `f`s before any `-o` and its attached value.
+            let cluster = &arg[1..];
+            let (flags, value) = cluster.split_at(cluster.find('o').unwrap_or(cluster.len()));
+            let kept: String = flags.chars().filter(|&c| c != 'f').collect();
+            if !kept.is_empty() || !value.is_empty() {
+                options.push(format!("-{kept}{value}"));
+            }
+            if value == "o" {
+                options.extend(args.next());
+            }
+        }
+    }
+
+    let Some((remote, refspecs)) = positionals.split_first() else {
+        return Vec::new();
+    };
+    // A leading `+` forces that refspec; the lease replaces it.
+    let refspecs: Vec<&str> = refspecs
+        .iter()
+        .map(|refspec| refspec.strip_prefix('+').unwrap_or(refspec))
+        .collect();
+
+    let git = std::iter::once("git")
+        .chain(global.iter().map(String::as_str))
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let push = std::iter::once("--force-with-lease")
+        .chain(options.iter().map(String::as_str))
+        .chain(std::iter::once(remote.as_str()))
+        .chain(refspecs.iter().copied())
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // With a single `src:dst` (or `branch`) refspec we know the remote branch.
+    let target = match refspecs.as_slice() {
+        [refspec] => {
+            let (src, dst) = refspec.split_once(':').unwrap_or((refspec, refspec));
+            let dst = dst.strip_prefix("refs/heads/").unwrap_or(dst);
+            (!src.is_empty() && !dst.is_empty() && dst != "HEAD").then_some((src, dst))
+        }
+        _ => None,
+    };
+
+    let lease_text = target.map_or_else(
+        || format!("Force only if the {remote} branches have not moved since your last fetch"),
+        |(_, dst)| format!("Force only if {remote}/{dst} has not moved since your last fetch"),
+    );
+    let mut suggestions = vec![
+        Suggestion::new(SuggestionKind::SaferAlternative, lease_text)
+            .with_command(format!("{git} push {push}")),
+    ];
+    if let Some((src, dst)) = target {
+        let remote_ref = format!("{remote}/{dst}");
+        suggestions.push(
+            Suggestion::new(
+                SuggestionKind::PreviewFirst,
+                format!("List the commits on {remote_ref} that the push would discard"),
+            )
+            .with_command(format!(
+                "{git} fetch {} && {git} log --oneline {}..{}",
+                shell_quote(remote),
+                shell_quote(src),
+                shell_quote(&remote_ref)
+            )),
+        );
+    }
+    suggestions
+}
+
 /// Create the core git pack.
 #[must_use]
 pub fn create_pack() -> Pack {
@@ -19,6 +177,7 @@ pub fn create_pack() -> Pack {
         keywords: &["git"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -312,7 +471,7 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
         ),
         destructive_pattern!(
             "push-force-short",
-            r"git\s+(?:\S+\s+)*push\s+.*-f\b",
+            r"git\s+(?:\S+\s+)*push\s+(?:.*-f\b|(?:[^|;&\n]*\s)?-[a-zA-Z]*f[a-zA-Z]*\b)",
             "Force push (-f) can destroy remote history. Use --force-with-lease if necessary.",
             Critical,
             "git push -f (short for --force) overwrites remote history with your local history. \
@@ -345,7 +504,7 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
         // branch -D/-f force deletes or overwrites without checks (Medium: recoverable via reflog)
         destructive_pattern!(
             "branch-force-delete",
-            r"git\s+(?:\S+\s+)*branch\s+.*(?:-D\b|--force\b|-f\b)",
+            r"git\s+(?:\S+\s+)*branch\s+(?:.*(?:-D\b|--force\b|-f\b)|(?:[^|;&\n]*\s)?-[a-zA-Z]*[Df][a-zA-Z]*\b)",
             "git branch -D/--force deletes branches without checks. Recoverable via 'git reflog'.",
             Medium,
             "git branch -D force-deletes a branch without checking if it has been merged. \
@@ -522,6 +681,8 @@ mod tests {
             "git push --force origin main",
             "destroy remote history",
         );
+        assert_blocks_with_pattern(&pack, "git push -uf origin main", "push-force-short");
+        assert_allows(&pack, "git push origin main && grep -vf skip.txt log");
     }
 
     #[test]
@@ -566,6 +727,8 @@ mod tests {
         assert_blocks_with_pattern(&pack, "git branch -D feature", "branch-force-delete");
         assert_blocks_with_pattern(&pack, "git branch --force feature", "branch-force-delete");
         assert_blocks_with_pattern(&pack, "git branch -f feature", "branch-force-delete");
+        assert_blocks_with_pattern(&pack, "git branch -fd feature", "branch-force-delete");
+        assert_allows(&pack, "git branch -a | grep -vf skip.txt");
     }
 
     #[test]
@@ -664,6 +827,72 @@ mod tests {
         assert_no_match(&pack, "echo digit");
     }
 
+    // =========================================================================
+    // Suggestion Provider Tests
+    // =========================================================================
+
+    fn provided(command: &str) -> Vec<(SuggestionKind, String)> {
+        force_with_lease_suggestions(&ParsedCommand::parse(command))
+            .into_iter()
+            .map(|s| (s.kind, s.command.unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn test_force_push_suggests_lease_for_the_pushed_branch() {
+        assert_eq!(
+            provided("git push --force origin feature/login"),
+            [
+                (
+                    SuggestionKind::SaferAlternative,
+                    "git push --force-with-lease origin feature/login".to_string()
+                ),
+                (
+                    SuggestionKind::PreviewFirst,
+                    "git fetch origin && git log --oneline feature/login..origin/feature/login"
+                        .to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            provided("cd repo && git -C app push -uf upstream +HEAD:refs/heads/main")[0].1,
+            "git -C app push --force-with-lease -u upstream HEAD:refs/heads/main"
+        );
+        assert_eq!(
+            provided("git push -fo ci.skip origin 'my branch'")[0].1,
+            "git push --force-with-lease -o ci.skip origin 'my branch'"
+        );
+    }
+
+    #[test]
+    fn test_force_push_without_branch_or_with_expansion() {
+        assert_eq!(
+            provided("git push -f origin"),
+            [(
+                SuggestionKind::SaferAlternative,
+                "git push --force-with-lease origin".to_string()
+            )]
+        );
+        assert!(provided("git push -f").is_empty());
+        assert!(provided("git push -f origin \"$BRANCH\"").is_empty());
+    }
+
+    #[test]
+    fn test_force_push_providers_are_registered() {
+        let registry = &crate::packs::REGISTRY;
+        for pattern in ["push-force-long", "push-force-short"] {
+            assert!(registry.suggestion_provider("core.git", pattern).is_some());
+        }
+        let suggestions = crate::suggestions::suggestions_for_command(
+            "core.git:push-force-short",
+            "git push -f origin main",
+        );
+        assert_eq!(
+            suggestions[0].command.as_deref(),
+            Some("git push --force-with-lease origin main")
+        );
+    }
+
     // =========================================================================
     // Performance Tests
     // =========================================================================
@@ -2,6 +2,7 @@
 //!
 //! Covers destructive CLI/API operations:
 //! - Wrangler DNS record deletion
+//! - flarectl and cfcli DNS record deletion
 //! - Cloudflare API deletes for DNS records and zones
 //! - Terraform destroy targeting Cloudflare DNS resources
 
@@ -20,9 +21,12 @@ pub fn create_pack() -> Pack {
             "cloudflare",
             "api.cloudflare.com",
             "dns-records",
+            "flarectl",
+            "cfcli",
         ],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -39,6 +43,11 @@ fn create_safe_patterns() -> Vec<SafePattern> {
             "cloudflare-wrangler-whoami",
             r"wrangler(?:\s+--?\S+(?:\s+\S+)?)*\s+whoami\b"
         ),
+        safe_pattern!(
+            "cloudflare-flarectl-dns-list",
+            r"flarectl\s+dns\s+(?:list|l)\b"
+        ),
+        safe_pattern!("cloudflare-cfcli-ls", r"cfcli\s+(?:ls|list)\b"),
         safe_pattern!(
             "cloudflare-api-get",
             r"curl\b.*\s-X\s*GET\b.*\bapi\.cloudflare\.com\b"
@@ -61,6 +70,31 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              - Export zone file as backup before deletion\n\
              - Use Cloudflare dashboard for confirmation prompts"
         ),
+        destructive_pattern!(
+            "cloudflare-flarectl-dns-delete",
+            r"flarectl\s+dns\s+(?:delete|d)\b",
+            "flarectl dns delete removes a Cloudflare DNS record.",
+            High,
+            "flarectl deletes the record through the Cloudflare API without a confirmation \
+             prompt. Connectivity to the affected hostname breaks as soon as the change \
+             propagates.\n\n\
+             Safer alternatives:\n\
+             - flarectl dns list --zone <zone> to confirm the record ID\n\
+             - Export the zone file as a backup before deletion\n\
+             - Manage records with Terraform for reviewable changes"
+        ),
+        destructive_pattern!(
+            "cloudflare-cfcli-rm",
+            r"cfcli\s+(?:(?:-\S+|--\S+)(?:\s+\S+)?\s+)*(?:rm|remove|delete)\b",
+            "cfcli rm removes Cloudflare DNS records.",
+            High,
+            "cfcli rm deletes every record matching the given name (and optional content), \
+             so a loose filter can wipe multiple records at once.\n\n\
+             Safer alternatives:\n\
+             - cfcli ls to see which records match first\n\
+             - Pass the record content to narrow the match\n\
+             - Export the zone file as a backup before deletion"
+        ),
         destructive_pattern!(
             "cloudflare-api-delete-dns-record",
             r"curl\b.*-X\s*DELETE\b.*\bapi\.cloudflare\.com\b[^\s]*?/dns_records/[^\s]+",
@@ -124,6 +158,8 @@ mod tests {
         let pack = create_pack();
         assert_safe_pattern_matches(&pack, "wrangler dns-records list --zone-id abc");
         assert_safe_pattern_matches(&pack, "wrangler whoami");
+        assert_safe_pattern_matches(&pack, "flarectl dns list --zone example.com");
+        assert_safe_pattern_matches(&pack, "cfcli ls -d example.com");
         assert_safe_pattern_matches(
             &pack,
             "curl -X GET https://api.cloudflare.com/client/v4/zones",
@@ -154,4 +190,20 @@ mod tests {
             "cloudflare-terraform-destroy-record",
         );
     }
+
+    #[test]
+    fn blocks_cli_record_wipes() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "flarectl dns delete --zone example.com --id abc123",
+            "cloudflare-flarectl-dns-delete",
+        );
+        assert_blocks_with_pattern(&pack, "cfcli rm www -d example.com", "cloudflare-cfcli-rm");
+        assert_blocks_with_pattern(
+            &pack,
+            "cfcli -d example.com remove www",
+            "cloudflare-cfcli-rm",
+        );
+    }
 }
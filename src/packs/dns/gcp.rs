@@ -0,0 +1,174 @@
+//! Google Cloud DNS pack - protections for destructive Cloud DNS operations.
+//!
+//! Covers destructive CLI operations:
+//! - Record set deletion (`gcloud dns record-sets delete`)
+//! - Record removal via transactions (`gcloud dns record-sets transaction remove`)
+//! - Record set imports that replace existing records (`--delete-all-existing`)
+//! - Managed zone deletion
+//! - DNS policy deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Google Cloud DNS pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "dns.gcp".to_string(),
+        name: "Google Cloud DNS",
+        description: "Protects against destructive Google Cloud DNS operations like record set \
+                      deletion, managed zone deletion, and destructive zone imports.",
+        keywords: &["gcloud"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "gcloud-dns-record-sets-list",
+            r"gcloud\s+dns\s+record-sets\s+(?:list|describe)\b"
+        ),
+        safe_pattern!(
+            "gcloud-dns-managed-zones-list",
+            r"gcloud\s+dns\s+managed-zones\s+(?:list|describe)\b"
+        ),
+        safe_pattern!(
+            "gcloud-dns-record-sets-export",
+            r"gcloud\s+dns\s+record-sets\s+export\b"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "gcloud-dns-record-sets-delete",
+            r"gcloud\s+dns\s+record-sets\s+delete\b",
+            "gcloud dns record-sets delete removes a Cloud DNS record set.",
+            High,
+            "Deleting a record set removes every record for that name and type. Clients \
+             lose resolution as soon as cached answers expire, which for low TTLs can be \
+             within seconds.\n\n\
+             Safer alternatives:\n\
+             - gcloud dns record-sets list to review records first\n\
+             - gcloud dns record-sets export to back up the zone\n\
+             - Use record-sets update to change records instead of deleting them"
+        ),
+        destructive_pattern!(
+            "gcloud-dns-transaction-remove",
+            r"gcloud\s+dns\s+record-sets\s+transaction\s+remove\b",
+            "gcloud dns record-sets transaction remove stages deletion of DNS records.",
+            High,
+            "Transaction remove stages record deletions that are applied when the \
+             transaction is executed. A mistaken name or rrdata removes live records.\n\n\
+             Safer alternatives:\n\
+             - Review the transaction file before running transaction execute\n\
+             - gcloud dns record-sets transaction abort to discard staged changes\n\
+             - Export the zone before applying changes"
+        ),
+        destructive_pattern!(
+            "gcloud-dns-import-delete-all-existing",
+            r"gcloud\s+dns\s+record-sets\s+import\b.*--delete-all-existing\b",
+            "gcloud dns record-sets import --delete-all-existing replaces all records in a zone.",
+            Critical,
+            "Importing with --delete-all-existing deletes every record in the zone (except \
+             NS and SOA) before importing. An incomplete zone file wipes the missing records.\n\n\
+             Safer alternatives:\n\
+             - Import without --delete-all-existing to add records only\n\
+             - gcloud dns record-sets export to back up the zone first\n\
+             - Diff the zone file against the current records before importing"
+        ),
+        destructive_pattern!(
+            "gcloud-dns-managed-zones-delete",
+            r"gcloud\s+dns\s+managed-zones\s+delete\b",
+            "gcloud dns managed-zones delete permanently deletes a Cloud DNS zone.",
+            Critical,
+            "Deleting a managed zone removes the zone and its name server delegation. \
+             Every service under the domain stops resolving once resolvers notice.\n\n\
+             Safer alternatives:\n\
+             - gcloud dns record-sets export to back up the zone\n\
+             - Delete individual record sets instead of the whole zone\n\
+             - Verify the zone name with gcloud dns managed-zones describe"
+        ),
+        destructive_pattern!(
+            "gcloud-dns-policies-delete",
+            r"gcloud\s+dns\s+policies\s+delete\b",
+            "gcloud dns policies delete removes a Cloud DNS server policy.",
+            High,
+            "DNS policies control inbound/outbound forwarding and logging for VPC \
+             networks. Deleting one can break private name resolution across networks.\n\n\
+             Safer alternatives:\n\
+             - gcloud dns policies describe to review the policy\n\
+             - Detach networks from the policy before deleting it"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "dns.gcp");
+        assert_eq!(pack.name, "Google Cloud DNS");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"gcloud"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "gcloud dns record-sets list --zone=prod");
+        assert_safe_pattern_matches(&pack, "gcloud dns managed-zones describe prod");
+        assert_safe_pattern_matches(&pack, "gcloud dns record-sets export zone.yaml --zone=prod");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud dns record-sets delete www.example.com. --type=A --zone=prod",
+            "gcloud-dns-record-sets-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud dns record-sets transaction remove --zone=prod --name=www.example.com. --type=A --ttl=300 1.2.3.4",
+            "gcloud-dns-transaction-remove",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud dns record-sets import zone.yaml --zone=prod --delete-all-existing",
+            "gcloud-dns-import-delete-all-existing",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud dns managed-zones delete prod",
+            "gcloud-dns-managed-zones-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud dns policies delete corp-policy",
+            "gcloud-dns-policies-delete",
+        );
+    }
+
+    #[test]
+    fn import_without_delete_all_is_not_blocked() {
+        let pack = create_pack();
+        assert_no_match(&pack, "gcloud dns record-sets import zone.yaml --zone=prod");
+    }
+}
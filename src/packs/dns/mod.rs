@@ -1,5 +1,6 @@
 //! DNS packs - protections for DNS management tooling.
 
 pub mod cloudflare;
+pub mod gcp;
 pub mod generic;
 pub mod route53;
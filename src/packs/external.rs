@@ -41,14 +41,15 @@ use std::fmt;
 use std::io;
 use std::path::{Path, PathBuf};
 
-use super::regex_engine::LazyCompiledRegex;
+use super::regex_engine::{CompiledRegex, LazyCompiledRegex};
 use super::{DestructivePattern, Pack, REGISTRY, SafePattern, Severity};
+use crate::error_codes::ErrorCode;
 
 /// Current schema version for external pack files.
 pub const CURRENT_SCHEMA_VERSION: u32 = 1;
 
 /// ID format regex pattern.
-const ID_PATTERN: &str = r"^[a-z][a-z0-9_]*\.[a-z][a-z0-9_]*$";
+pub(crate) const ID_PATTERN: &str = r"^[a-z][a-z0-9_]*\.[a-z][a-z0-9_]*$";
 
 /// Version format regex pattern (semantic versioning).
 const VERSION_PATTERN: &str = r"^\d+\.\d+\.\d+$";
@@ -195,24 +196,30 @@ impl From<ExternalSeverity> for Severity {
 }
 
 /// Errors that can occur when parsing a pack file.
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum PackParseError {
     /// IO error reading the file.
-    Io(io::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
 
     /// YAML parsing error.
-    Yaml(serde_yaml::Error),
+    #[error("YAML parse error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
 
     /// Invalid pack ID format.
+    #[error("Invalid pack ID '{id}': {reason}")]
     InvalidId { id: String, reason: String },
 
     /// Invalid version format.
+    #[error("Invalid version '{version}': {reason}")]
     InvalidVersion { version: String, reason: String },
 
     /// Schema version not supported.
+    #[error("Schema version {found} is not supported (max: {max_supported})")]
     UnsupportedSchemaVersion { found: u32, max_supported: u32 },
 
     /// Invalid regex pattern.
+    #[error("Invalid pattern '{name}' ({pattern}): {error}")]
     InvalidPattern {
         name: String,
         pattern: String,
@@ -220,82 +227,42 @@ pub enum PackParseError {
     },
 
     /// Duplicate pattern name within a pack.
+    #[error("Duplicate pattern name: {name}")]
     DuplicatePattern { name: String },
 
     /// Empty pack (no patterns defined).
+    #[error("Pack has no patterns defined")]
     EmptyPack,
 
     /// Pack ID collides with a built-in pack.
     ///
     /// External packs cannot override built-in security packs to prevent
     /// accidental or malicious security bypasses.
+    #[error(
+        "Pack ID '{id}' collides with built-in pack '{builtin_name}'. \
+         External packs cannot override built-in security packs."
+    )]
     IdCollision { id: String, builtin_name: String },
 }
 
-impl fmt::Display for PackParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Io(e) => write!(f, "IO error: {e}"),
-            Self::Yaml(e) => write!(f, "YAML parse error: {e}"),
-            Self::InvalidId { id, reason } => {
-                write!(f, "Invalid pack ID '{id}': {reason}")
-            }
-            Self::InvalidVersion { version, reason } => {
-                write!(f, "Invalid version '{version}': {reason}")
-            }
-            Self::UnsupportedSchemaVersion {
-                found,
-                max_supported,
-            } => {
-                write!(
-                    f,
-                    "Schema version {found} is not supported (max: {max_supported})"
-                )
-            }
-            Self::InvalidPattern {
-                name,
-                pattern,
-                error,
-            } => {
-                write!(f, "Invalid pattern '{name}' ({pattern}): {error}")
-            }
-            Self::DuplicatePattern { name } => {
-                write!(f, "Duplicate pattern name: {name}")
-            }
-            Self::EmptyPack => write!(f, "Pack has no patterns defined"),
-            Self::IdCollision { id, builtin_name } => {
-                write!(
-                    f,
-                    "Pack ID '{id}' collides with built-in pack '{builtin_name}'. \
-                     External packs cannot override built-in security packs."
-                )
-            }
-        }
-    }
-}
-
-impl std::error::Error for PackParseError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl PackParseError {
+    /// Stable error code for this error (see `docs/error-codes.md`).
+    #[must_use]
+    pub const fn code(&self) -> ErrorCode {
         match self {
-            Self::Io(e) => Some(e),
-            Self::Yaml(e) => Some(e),
-            _ => None,
+            Self::Io(_) => ErrorCode::ExternalPackLoadFailed,
+            Self::InvalidPattern { .. } => ErrorCode::InvalidPatternSyntax,
+            Self::DuplicatePattern { .. } => ErrorCode::DuplicateRuleId,
+            Self::IdCollision { .. } => ErrorCode::PackConfigError,
+            Self::Yaml(_)
+            | Self::InvalidId { .. }
+            | Self::InvalidVersion { .. }
+            | Self::UnsupportedSchemaVersion { .. }
+            | Self::EmptyPack => ErrorCode::ExternalPackParseError,
         }
     }
 }
 
-impl From<io::Error> for PackParseError {
-    fn from(e: io::Error) -> Self {
-        Self::Io(e)
-    }
-}
-
-impl From<serde_yaml::Error> for PackParseError {
-    fn from(e: serde_yaml::Error) -> Self {
-        Self::Yaml(e)
-    }
-}
-
 /// Parse an external pack from a YAML file.
 ///
 /// This function reads the file, parses the YAML, and validates the pack structure.
@@ -377,12 +344,12 @@ fn validate_pack(pack: &ExternalPack) -> Result<(), PackParseError> {
             });
         }
 
-        // Validate regex compiles
-        if let Err(e) = fancy_regex::Regex::new(&pattern.pattern) {
+        // Validate regex compiles and cannot backtrack catastrophically
+        if let Err(error) = CompiledRegex::new_user(&pattern.pattern) {
             return Err(PackParseError::InvalidPattern {
                 name: pattern.name.clone(),
                 pattern: pattern.pattern.clone(),
-                error: e.to_string(),
+                error,
             });
         }
     }
@@ -396,12 +363,12 @@ fn validate_pack(pack: &ExternalPack) -> Result<(), PackParseError> {
             });
         }
 
-        // Validate regex compiles
-        if let Err(e) = fancy_regex::Regex::new(&pattern.pattern) {
+        // Validate regex compiles and cannot backtrack catastrophically
+        if let Err(error) = CompiledRegex::new_user(&pattern.pattern) {
             return Err(PackParseError::InvalidPattern {
                 name: pattern.name.clone(),
                 pattern: pattern.pattern.clone(),
-                error: e.to_string(),
+                error,
             });
         }
     }
@@ -522,7 +489,7 @@ impl ExternalPack {
             .map(|p| {
                 let name: &'static str = Box::leak(p.name.into_boxed_str());
                 SafePattern {
-                    regex: LazyCompiledRegex::new(Box::leak(p.pattern.into_boxed_str())),
+                    regex: LazyCompiledRegex::new_user(p.pattern),
                     name,
                 }
             })
@@ -560,7 +527,7 @@ impl ExternalPack {
                 };
 
                 DestructivePattern {
-                    regex: LazyCompiledRegex::new(Box::leak(p.pattern.into_boxed_str())),
+                    regex: LazyCompiledRegex::new_user(p.pattern),
                     reason,
                     name: Some(name),
                     severity: p.severity.into(),
@@ -949,6 +916,24 @@ destructive_patterns:
         assert!(matches!(result, Err(PackParseError::InvalidPattern { .. })));
     }
 
+    #[test]
+    fn test_nested_quantifier_pattern_rejected() {
+        let yaml = r#"
+id: test.pack
+name: Test
+version: 1.0.0
+destructive_patterns:
+  - name: redos
+    pattern: "(?<!--)(\\w+\\s?)*\\bdrop"
+"#;
+        let result = parse_pack_string(yaml);
+        assert!(matches!(
+            result,
+            Err(PackParseError::InvalidPattern { ref name, ref error, .. })
+                if name == "redos" && error.contains("nests an unbounded quantifier")
+        ));
+    }
+
     #[test]
     fn test_duplicate_pattern_name() {
         let yaml = r#"
@@ -0,0 +1,129 @@
+//! Auth0 pack - protections for destructive `auth0` CLI and Management API operations.
+//!
+//! Covers destructive operations:
+//! - User, role, organization, application, and API deletion via the `auth0` CLI
+//! - Removing roles from users
+//! - Management API user deletes
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Auth0 pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "iam.auth0".to_string(),
+        name: "Auth0",
+        description: "Protects against destructive Auth0 operations like deleting users, roles, \
+                      organizations, applications, and APIs.",
+        keywords: &["auth0"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "auth0-read",
+            r"auth0\s+(?:users|roles|orgs|apps|apis)\s+(?:list|ls|show|search)\b"
+        ),
+        safe_pattern!("auth0-tenants-list", r"auth0\s+tenants\s+(?:list|ls)\b"),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "auth0-users-delete",
+            r"auth0\s+users\s+(?:delete|rm)\b",
+            "auth0 users delete permanently deletes Auth0 users.",
+            High,
+            "Deleted users lose their profile, metadata, and linked identities. Auth0 has \
+             no undelete; users must sign up again.\n\n\
+             Safer alternatives:\n\
+             - auth0 users update to block the user instead\n\
+             - auth0 users show to export the profile first"
+        ),
+        destructive_pattern!(
+            "auth0-users-roles-remove",
+            r"auth0\s+users\s+roles\s+(?:remove|rm)\b",
+            "auth0 users roles remove revokes roles from a user.",
+            Medium,
+            "Removing roles revokes the permissions they grant on the user's next token.\n\n\
+             Safer alternatives:\n\
+             - auth0 users roles show to review current roles first"
+        ),
+        destructive_pattern!(
+            "auth0-resource-delete",
+            r"auth0\s+(?:roles|orgs|apps|apis)\s+(?:delete|rm)\b",
+            "auth0 ... delete removes an Auth0 role, organization, application, or API.",
+            High,
+            "Deleting a role strips its permissions from every user; deleting an app or API \
+             breaks sign-in and token issuance for every client using it. Deleting an \
+             organization removes all of its memberships.\n\n\
+             Safer alternatives:\n\
+             - Export tenant configuration with auth0 tf generate or the Deploy CLI\n\
+             - Review dependents with the corresponding list/show command"
+        ),
+        destructive_pattern!(
+            "auth0-api-delete-user",
+            r"curl\b.*-X\s*DELETE\b.*\.auth0\.com/api/v2/users/\S+",
+            "DELETE /api/v2/users/{id} permanently deletes an Auth0 user.",
+            High,
+            "Management API deletes are immediate and unrecoverable.\n\n\
+             Safer alternatives:\n\
+             - PATCH the user with {\"blocked\": true} instead\n\
+             - GET the user first to confirm the ID"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "iam.auth0");
+        assert_eq!(pack.name, "Auth0");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"auth0"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "auth0 users search --query email:a@example.com");
+        assert_safe_pattern_matches(&pack, "auth0 apps list");
+        assert_safe_pattern_matches(&pack, "auth0 tenants list");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "auth0 users delete auth0|123", "auth0-users-delete");
+        assert_blocks_with_pattern(
+            &pack,
+            "auth0 users roles remove auth0|123 --roles rol_1",
+            "auth0-users-roles-remove",
+        );
+        assert_blocks_with_pattern(&pack, "auth0 apps delete abc", "auth0-resource-delete");
+        assert_blocks_with_pattern(&pack, "auth0 orgs rm org_1", "auth0-resource-delete");
+        assert_blocks_with_pattern(
+            &pack,
+            "curl -X DELETE https://acme.us.auth0.com/api/v2/users/auth0%7C123",
+            "auth0-api-delete-user",
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! AWS IAM pack - protections for destructive `aws iam` operations.
+//!
+//! Covers destructive CLI operations:
+//! - User, role, group, and managed policy deletion
+//! - Inline or managed policies that grant `"Action": "*"`
+//! - Access key and login profile deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the AWS IAM pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "iam.aws".to_string(),
+        name: "AWS IAM",
+        description: "Protects against destructive AWS IAM operations like deleting users, roles, \
+                      and policies, and attaching policies that allow every action.",
+        keywords: &["iam"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!("aws-iam-list", r"aws\s+iam\s+list-\S+"),
+        safe_pattern!("aws-iam-get", r"aws\s+iam\s+get-\S+"),
+        safe_pattern!("aws-iam-simulate", r"aws\s+iam\s+simulate-\S+"),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        // Wildcard grants are checked first so they win over the generic put/create rules.
+        destructive_pattern!(
+            "aws-iam-wildcard-action-policy",
+            r#"aws\s+iam\s+(?:put-(?:user|role|group)-policy|create-policy(?:-version)?)\b.*\\?"Action\\?"\s*:\s*\[?\s*\\?"\*\\?""#,
+            "IAM policy document grants \"Action\": \"*\" (full administrator access).",
+            Critical,
+            "A policy with Action \"*\" lets the principal perform every AWS API call, \
+             including deleting other users, disabling CloudTrail, and exfiltrating data. \
+             A compromised or misused identity with this grant can destroy the account.\n\n\
+             Safer alternatives:\n\
+             - Grant only the specific actions the principal needs\n\
+             - Use AWS managed job-function policies instead of wildcards\n\
+             - Validate the document with aws accessanalyzer validate-policy"
+        ),
+        destructive_pattern!(
+            "aws-iam-delete-user",
+            r"aws\s+iam\s+delete-user(?:\s|$)",
+            "aws iam delete-user permanently deletes an IAM user.",
+            High,
+            "Deleting an IAM user removes its credentials immediately. Any automation or \
+             person relying on the user's access keys or console login loses access, and \
+             the user's ARN cannot be restored.\n\n\
+             Safer alternatives:\n\
+             - aws iam update-access-key --status Inactive to disable keys first\n\
+             - aws iam delete-login-profile to remove console access only\n\
+             - Review usage with aws iam get-access-key-last-used"
+        ),
+        destructive_pattern!(
+            "aws-iam-delete-role",
+            r"aws\s+iam\s+delete-role(?:\s|$)",
+            "aws iam delete-role permanently deletes an IAM role.",
+            High,
+            "Deleting a role breaks every service, instance profile, and cross-account \
+             trust that assumes it. Recreating a role with the same name does not restore \
+             trust relationships that reference the old role ID.\n\n\
+             Safer alternatives:\n\
+             - aws iam get-role to check RoleLastUsed first\n\
+             - Detach policies and watch for failures before deleting\n\
+             - Update the trust policy to deny assumption instead"
+        ),
+        destructive_pattern!(
+            "aws-iam-delete-group",
+            r"aws\s+iam\s+delete-group(?:\s|$)",
+            "aws iam delete-group deletes an IAM group.",
+            High,
+            "Deleting a group removes the permissions it granted to every member.\n\n\
+             Safer alternatives:\n\
+             - aws iam get-group to review members first\n\
+             - Detach group policies individually"
+        ),
+        destructive_pattern!(
+            "aws-iam-delete-policy",
+            r"aws\s+iam\s+delete-(?:user-|role-|group-)?policy(?:\s|$)",
+            "aws iam delete-policy removes an IAM policy and the permissions it grants.",
+            High,
+            "Deleting a policy revokes its permissions from every attached identity at once, \
+             which can break running workloads.\n\n\
+             Safer alternatives:\n\
+             - aws iam list-entities-for-policy to see who depends on it\n\
+             - aws iam get-policy-version to back up the document first"
+        ),
+        destructive_pattern!(
+            "aws-iam-delete-credentials",
+            r"aws\s+iam\s+delete-(?:access-key|login-profile|ssh-public-key|service-specific-credential|signing-certificate)\b",
+            "aws iam delete-* removes IAM user credentials.",
+            Medium,
+            "Deleting credentials immediately breaks any process still using them.\n\n\
+             Safer alternatives:\n\
+             - Deactivate the credential first and confirm nothing breaks\n\
+             - aws iam get-access-key-last-used to check recent usage"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "iam.aws");
+        assert_eq!(pack.name, "AWS IAM");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"iam"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "aws iam list-users");
+        assert_safe_pattern_matches(&pack, "aws iam get-role --role-name app");
+        assert_safe_pattern_matches(
+            &pack,
+            "aws iam simulate-principal-policy --policy-source-arn arn:aws:iam::1:user/a",
+        );
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "aws iam delete-user --user-name alice",
+            "aws-iam-delete-user",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws iam delete-role --role-name app",
+            "aws-iam-delete-role",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws iam delete-group --group-name admins",
+            "aws-iam-delete-group",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws iam delete-policy --policy-arn arn:aws:iam::1:policy/p",
+            "aws-iam-delete-policy",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws iam delete-role-policy --role-name app --policy-name inline",
+            "aws-iam-delete-policy",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws iam delete-access-key --access-key-id AKIA123",
+            "aws-iam-delete-credentials",
+        );
+    }
+
+    #[test]
+    fn blocks_wildcard_action_policies() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            r#"aws iam put-user-policy --user-name bob --policy-name all --policy-document '{"Statement":[{"Effect":"Allow","Action":"*","Resource":"*"}]}'"#,
+            "aws-iam-wildcard-action-policy",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            r#"aws iam create-policy --policy-name all --policy-document "{\"Statement\":[{\"Action\": [\"*\"]}]}""#,
+            "aws-iam-wildcard-action-policy",
+        );
+    }
+
+    #[test]
+    fn allows_scoped_inline_policy() {
+        let pack = create_pack();
+        assert_no_match(
+            &pack,
+            r#"aws iam put-user-policy --user-name bob --policy-name s3 --policy-document '{"Statement":[{"Action":"s3:GetObject","Resource":"*"}]}'"#,
+        );
+        assert_no_match(
+            &pack,
+            "aws iam delete-policy-version --policy-arn arn:aws:iam::1:policy/p --version-id v1",
+        );
+        assert_no_match(
+            &pack,
+            "aws iam delete-role-permissions-boundary --role-name app",
+        );
+    }
+}
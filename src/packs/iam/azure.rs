@@ -0,0 +1,130 @@
+//! Azure identity pack - protections for destructive `az role` / `az ad` operations.
+//!
+//! Covers destructive CLI operations:
+//! - Role assignment and role definition deletion
+//! - Entra ID (Azure AD) user, group, app, and service principal deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Azure identity pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "iam.azure".to_string(),
+        name: "Azure RBAC and Entra ID",
+        description: "Protects against destructive Azure identity operations like deleting role \
+                      assignments, role definitions, users, groups, and service principals.",
+        keywords: &["az"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "az-role-list",
+            r"az\s+role\s+(?:assignment|definition)\s+list\b"
+        ),
+        safe_pattern!(
+            "az-ad-show",
+            r"az\s+ad\s+(?:user|group|app|sp)\s+(?:list|show)\b"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "az-role-assignment-delete",
+            r"az\s+role\s+assignment\s+delete\b",
+            "az role assignment delete removes Azure RBAC role assignments.",
+            High,
+            "Without filters, az role assignment delete can remove many assignments in the \
+             scope at once. Removing Owner or User Access Administrator assignments can \
+             leave a subscription with nobody able to grant access.\n\n\
+             Safer alternatives:\n\
+             - az role assignment list with the same filters to preview what matches\n\
+             - Pass --ids to delete one specific assignment\n\
+             - Confirm another Owner exists on the scope first"
+        ),
+        destructive_pattern!(
+            "az-role-definition-delete",
+            r"az\s+role\s+definition\s+delete\b",
+            "az role definition delete removes a custom Azure role.",
+            High,
+            "Assignments that reference a deleted custom role stop granting permissions.\n\n\
+             Safer alternatives:\n\
+             - az role definition list --custom-role-only true to back up the definition\n\
+             - az role assignment list --role <name> to find dependents"
+        ),
+        destructive_pattern!(
+            "az-ad-principal-delete",
+            r"az\s+ad\s+(?:user|group|app|sp)\s+delete\b",
+            "az ad ... delete removes an Entra ID user, group, app, or service principal.",
+            High,
+            "Deleting a directory object revokes every role assignment and app permission \
+             it held. Soft-deleted users and apps can be restored for 30 days, but \
+             assignments are not restored with them.\n\n\
+             Safer alternatives:\n\
+             - az ad user update --account-enabled false to disable instead\n\
+             - az role assignment list --assignee <id> to review its access first"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "iam.azure");
+        assert_eq!(pack.name, "Azure RBAC and Entra ID");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"az"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "az role assignment list --assignee a@example.com");
+        assert_safe_pattern_matches(&pack, "az ad user show --id a@example.com");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "az role assignment delete --assignee a@example.com --role Owner",
+            "az-role-assignment-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "az role definition delete --name 'Custom Reader'",
+            "az-role-definition-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "az ad user delete --id a@example.com",
+            "az-ad-principal-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "az ad sp delete --id 00000000-0000-0000-0000-000000000000",
+            "az-ad-principal-delete",
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Google Cloud IAM pack - protections for destructive gcloud IAM operations.
+//!
+//! Covers destructive CLI operations:
+//! - Removing owner/admin role bindings (`remove-iam-policy-binding`)
+//! - Replacing a resource's whole IAM policy (`set-iam-policy`)
+//! - Service account and custom role deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Google Cloud IAM pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "iam.gcp".to_string(),
+        name: "Google Cloud IAM",
+        description: "Protects against destructive Google Cloud IAM operations like removing owner \
+                      bindings, overwriting IAM policies, and deleting service accounts.",
+        keywords: &["gcloud"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "gcloud-get-iam-policy",
+            r"gcloud\s+(?:\S+\s+)+get-iam-policy\b"
+        ),
+        safe_pattern!(
+            "gcloud-iam-list",
+            r"gcloud\s+iam\s+(?:service-accounts|roles)\s+(?:list|describe)\b"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "gcloud-remove-owner-binding",
+            r#"gcloud\s+(?:\S+\s+)+remove-iam-policy-binding\b.*--role[=\s]+['"]?roles/(?:owner|editor|resourcemanager\.organizationAdmin|resourcemanager\.projectIamAdmin|iam\.securityAdmin)\b"#,
+            "remove-iam-policy-binding drops an owner/admin role binding.",
+            Critical,
+            "Removing owner or IAM admin bindings can leave a project or organization with \
+             nobody able to manage it. Recovering access may require a support case with \
+             Google Cloud.\n\n\
+             Safer alternatives:\n\
+             - gcloud projects get-iam-policy to confirm other owners remain\n\
+             - Add the replacement binding before removing the old one\n\
+             - Use IAM conditions to time-limit access instead of removing it"
+        ),
+        destructive_pattern!(
+            "gcloud-remove-iam-policy-binding",
+            r"gcloud\s+(?:\S+\s+)+remove-iam-policy-binding\b",
+            "remove-iam-policy-binding revokes a role from a member.",
+            Medium,
+            "Removing a binding revokes access immediately. Workloads running as the member \
+             start failing with permission errors.\n\n\
+             Safer alternatives:\n\
+             - gcloud ... get-iam-policy to back up the current policy\n\
+             - Check IAM recommender insights for unused permissions first"
+        ),
+        destructive_pattern!(
+            "gcloud-set-iam-policy",
+            r"gcloud\s+(?:\S+\s+)+set-iam-policy\b",
+            "set-iam-policy replaces the entire IAM policy on a resource.",
+            High,
+            "set-iam-policy overwrites every binding on the resource with the contents of \
+             the file. A stale or partial file silently removes owners and service accounts.\n\n\
+             Safer alternatives:\n\
+             - Use add-iam-policy-binding / remove-iam-policy-binding for targeted changes\n\
+             - get-iam-policy immediately before editing so the etag is current"
+        ),
+        destructive_pattern!(
+            "gcloud-iam-service-accounts-delete",
+            r"gcloud\s+iam\s+service-accounts\s+delete\b",
+            "gcloud iam service-accounts delete deletes a service account.",
+            High,
+            "Deleting a service account breaks every workload that runs as it. A new \
+             account with the same email gets a different unique ID, so existing bindings \
+             do not carry over.\n\n\
+             Safer alternatives:\n\
+             - gcloud iam service-accounts disable to test the impact first\n\
+             - Undelete is only possible for 30 days via gcloud iam service-accounts undelete"
+        ),
+        destructive_pattern!(
+            "gcloud-iam-roles-delete",
+            r"gcloud\s+iam\s+roles\s+delete\b",
+            "gcloud iam roles delete deletes a custom IAM role.",
+            Medium,
+            "Bindings that reference a deleted custom role stop granting permissions.\n\n\
+             Safer alternatives:\n\
+             - gcloud iam roles describe to review the role first\n\
+             - gcloud iam roles undelete can restore it within 7 days"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "iam.gcp");
+        assert_eq!(pack.name, "Google Cloud IAM");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"gcloud"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "gcloud projects get-iam-policy my-proj");
+        assert_safe_pattern_matches(&pack, "gcloud iam service-accounts list");
+        assert_safe_pattern_matches(&pack, "gcloud iam roles describe roles/viewer");
+    }
+
+    #[test]
+    fn blocks_owner_binding_removal() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud projects remove-iam-policy-binding my-proj --member=user:a@example.com --role=roles/owner",
+            "gcloud-remove-owner-binding",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud organizations remove-iam-policy-binding 123 --member user:a@example.com --role 'roles/resourcemanager.organizationAdmin'",
+            "gcloud-remove-owner-binding",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud projects remove-iam-policy-binding my-proj --member=user:a@example.com --role=roles/viewer",
+            "gcloud-remove-iam-policy-binding",
+        );
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud projects set-iam-policy my-proj policy.json",
+            "gcloud-set-iam-policy",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud iam service-accounts delete sa@my-proj.iam.gserviceaccount.com",
+            "gcloud-iam-service-accounts-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud iam roles delete customRole --project my-proj",
+            "gcloud-iam-roles-delete",
+        );
+    }
+
+    #[test]
+    fn add_binding_is_not_blocked() {
+        let pack = create_pack();
+        assert_no_match(
+            &pack,
+            "gcloud projects add-iam-policy-binding my-proj --member=user:a@example.com --role=roles/viewer",
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Identity and access management packs - protections for IAM tooling.
+//!
+//! Identity changes (deleting users, dropping role bindings, granting
+//! wildcard permissions) can lock teams out or open up an account just as
+//! badly as data loss, so these packs treat them as destructive.
+
+pub mod auth0;
+pub mod aws;
+pub mod azure;
+pub mod gcp;
+pub mod okta;
@@ -0,0 +1,131 @@
+//! Okta pack - protections for destructive Okta user and app operations.
+//!
+//! Covers destructive operations:
+//! - Okta Management API deletes for users, groups, and apps
+//! - User deactivation via the lifecycle API
+//! - `okta` CLI app deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Okta pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "iam.okta".to_string(),
+        name: "Okta",
+        description: "Protects against destructive Okta operations like deleting or deactivating \
+                      users, groups, and applications.",
+        keywords: &["okta"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "okta-api-get",
+            r"curl\b.*\s-X\s*GET\b.*\.okta(?:preview)?\.com/api/"
+        ),
+        safe_pattern!("okta-cli-apps-list", r"okta\s+apps\s+(?:list|config)\b"),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "okta-api-delete-user",
+            r"curl\b.*-X\s*DELETE\b.*\.okta(?:preview)?\.com/api/v1/users/\S+",
+            "DELETE /api/v1/users/{id} deactivates or permanently deletes an Okta user.",
+            High,
+            "The first DELETE deactivates the user; a second DELETE permanently removes \
+             them and all of their app assignments. Permanent deletion cannot be undone.\n\n\
+             Safer alternatives:\n\
+             - POST /api/v1/users/{id}/lifecycle/suspend to suspend instead\n\
+             - GET the user first and confirm the ID and status"
+        ),
+        destructive_pattern!(
+            "okta-api-deactivate-user",
+            r"curl\b.*-X\s*POST\b.*\.okta(?:preview)?\.com/api/v1/users/\S+/lifecycle/deactivate\b",
+            "POST .../lifecycle/deactivate deactivates an Okta user.",
+            Medium,
+            "Deactivation signs the user out everywhere and deprovisions their apps.\n\n\
+             Safer alternatives:\n\
+             - Use lifecycle/suspend for temporary lockouts\n\
+             - Confirm the user ID with a GET request first"
+        ),
+        destructive_pattern!(
+            "okta-api-delete-group-or-app",
+            r"curl\b.*-X\s*DELETE\b.*\.okta(?:preview)?\.com/api/v1/(?:groups|apps)/\S+",
+            "DELETE /api/v1/groups or /apps removes an Okta group or application.",
+            High,
+            "Deleting a group removes every member's group-based app assignments; deleting \
+             an app removes it for every assigned user and breaks SSO.\n\n\
+             Safer alternatives:\n\
+             - Deactivate the app instead of deleting it\n\
+             - Export group membership before deletion"
+        ),
+        destructive_pattern!(
+            "okta-cli-apps-delete",
+            r"okta\s+apps\s+delete\b",
+            "okta apps delete removes an Okta application.",
+            High,
+            "Deleting an application breaks SSO for every user assigned to it.\n\n\
+             Safer alternatives:\n\
+             - okta apps config to review the app first\n\
+             - Deactivate the app in the admin console instead"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "iam.okta");
+        assert_eq!(pack.name, "Okta");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"okta"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "curl -X GET https://acme.okta.com/api/v1/users/00u1");
+        assert_safe_pattern_matches(&pack, "okta apps list");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "curl -X DELETE https://acme.okta.com/api/v1/users/00u1 -H 'Authorization: SSWS x'",
+            "okta-api-delete-user",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "curl -X POST https://acme.okta.com/api/v1/users/00u1/lifecycle/deactivate",
+            "okta-api-deactivate-user",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "curl -X DELETE https://acme.oktapreview.com/api/v1/apps/0oa1",
+            "okta-api-delete-group-or-app",
+        );
+        assert_blocks_with_pattern(&pack, "okta apps delete 0oa1", "okta-cli-apps-delete");
+    }
+}
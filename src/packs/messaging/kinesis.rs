@@ -0,0 +1,130 @@
+//! `AWS` Kinesis pack - protections for destructive stream operations.
+//!
+//! Covers destructive CLI operations:
+//! - Kinesis Data Streams deletion
+//! - Stream consumer deregistration
+//! - Firehose delivery stream deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the `AWS` Kinesis pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "messaging.kinesis".to_string(),
+        name: "AWS Kinesis",
+        description: "Protects against destructive AWS Kinesis operations like deleting data \
+                      streams, deregistering consumers, and deleting Firehose delivery streams.",
+        keywords: &["kinesis", "firehose"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "aws-kinesis-describe",
+            r"aws(?:\s+--?\S+(?:\s+\S+)?)*\s+(?:kinesis|firehose)\s+(?:list|describe)-\S+"
+        ),
+        safe_pattern!(
+            "aws-kinesis-get-records",
+            r"aws(?:\s+--?\S+(?:\s+\S+)?)*\s+kinesis\s+get-(?:records|shard-iterator)\b"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "aws-kinesis-delete-stream",
+            r"aws(?:\s+--?\S+(?:\s+\S+)?)*\s+kinesis\s+delete-stream\b",
+            "aws kinesis delete-stream deletes a Kinesis data stream and all its records.",
+            High,
+            "Deleting a stream discards every unconsumed record in all shards. Producers \
+             start failing immediately and consumers lose their checkpoints.\n\n\
+             Safer alternatives:\n\
+             - aws kinesis describe-stream-summary to check consumers first\n\
+             - Lower the retention period and drain consumers before deleting\n\
+             - Confirm no producers are still writing to the stream"
+        ),
+        destructive_pattern!(
+            "aws-kinesis-deregister-consumer",
+            r"aws(?:\s+--?\S+(?:\s+\S+)?)*\s+kinesis\s+deregister-stream-consumer\b",
+            "aws kinesis deregister-stream-consumer removes an enhanced fan-out consumer.",
+            Medium,
+            "Deregistering a consumer stops its dedicated throughput; the application \
+             reading through it stops receiving records.\n\n\
+             Safer alternatives:\n\
+             - aws kinesis list-stream-consumers to confirm the consumer ARN"
+        ),
+        destructive_pattern!(
+            "aws-firehose-delete-delivery-stream",
+            r"aws(?:\s+--?\S+(?:\s+\S+)?)*\s+firehose\s+delete-delivery-stream\b",
+            "aws firehose delete-delivery-stream deletes a Firehose delivery stream.",
+            High,
+            "Deleting a delivery stream drops buffered records that have not been \
+             delivered yet and breaks every producer writing to it.\n\n\
+             Safer alternatives:\n\
+             - aws firehose describe-delivery-stream to review the destination\n\
+             - Stop producers and let buffers flush before deleting"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "messaging.kinesis");
+        assert_eq!(pack.name, "AWS Kinesis");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"kinesis"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "aws kinesis list-streams");
+        assert_safe_pattern_matches(&pack, "aws kinesis describe-stream --stream-name clicks");
+        assert_safe_pattern_matches(&pack, "aws firehose list-delivery-streams");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "aws kinesis delete-stream --stream-name clicks",
+            "aws-kinesis-delete-stream",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws --region us-east-1 kinesis delete-stream --stream-name clicks --enforce-consumer-deletion",
+            "aws-kinesis-delete-stream",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws kinesis deregister-stream-consumer --consumer-arn arn:aws:kinesis:1",
+            "aws-kinesis-deregister-consumer",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws firehose delete-delivery-stream --delivery-stream-name logs",
+            "aws-firehose-delete-delivery-stream",
+        );
+    }
+}
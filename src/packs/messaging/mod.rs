@@ -3,5 +3,160 @@
 pub mod kafka;
 pub mod rabbitmq;
 
+pub mod kinesis;
 pub mod nats;
+pub mod pubsub;
 pub mod sqs_sns;
+
+/// Where a messaging rule names the resource it destroys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetArg {
+    /// The value of a flag: `--topic orders` or `--topic=orders`.
+    Flag(&'static str),
+    /// Positional arguments after a subcommand word: `stream rm orders`.
+    After(&'static str),
+}
+
+/// Flags that take no value, so the token after them is still positional.
+const BOOLEAN_FLAGS: &[&str] = &[
+    "-f",
+    "--force",
+    "-y",
+    "--yes",
+    "-q",
+    "--quiet",
+    "--all",
+    "--if-exists",
+    "--async",
+];
+
+/// Target arguments for a messaging rule, by pattern name.
+///
+/// Only these arguments are checked against `[messaging]
+/// production_name_patterns`; connection settings such as
+/// `--bootstrap-server` or `--region` are never targets.
+#[must_use]
+pub fn target_args(rule: &str) -> &'static [TargetArg] {
+    use TargetArg::{After, Flag};
+    match rule {
+        "kafka-topics-delete" => &[Flag("--topic")],
+        "kafka-consumer-groups-delete" => &[Flag("--group")],
+        "kafka-consumer-groups-reset-offsets" => &[Flag("--group"), Flag("--topic")],
+        "kafka-configs-delete-config" => &[Flag("--entity-name")],
+        "kafka-acls-remove" => &[Flag("--topic"), Flag("--group")],
+        "rpk-topic-delete" => &[After("delete")],
+        "aws-kinesis-delete-stream" => &[Flag("--stream-name"), Flag("--stream-arn")],
+        "aws-kinesis-deregister-consumer" => &[
+            Flag("--consumer-name"),
+            Flag("--consumer-arn"),
+            Flag("--stream-arn"),
+        ],
+        "aws-firehose-delete-delivery-stream" => &[Flag("--delivery-stream-name")],
+        "nats-stream-delete" | "nats-consumer-delete" => &[After("delete"), After("rm")],
+        "nats-stream-purge" => &[After("purge")],
+        "nats-kv-delete" => &[After("del"), After("rm")],
+        "nats-object-delete" | "nats-account-delete" => &[After("delete")],
+        "gcloud-pubsub-subscriptions-delete"
+        | "gcloud-pubsub-topics-delete"
+        | "gcloud-pubsub-snapshots-delete" => &[After("delete")],
+        "gcloud-pubsub-subscriptions-seek" => &[After("seek")],
+        "rabbitmqadmin-delete-queue" | "rabbitmqadmin-purge-queue" => {
+            &[After("queue"), Flag("--vhost"), Flag("-V")]
+        }
+        "rabbitmqadmin-delete-exchange" => &[After("exchange"), Flag("--vhost"), Flag("-V")],
+        "rabbitmqctl-purge-queue" => &[After("purge_queue"), Flag("--vhost"), Flag("-p")],
+        "rabbitmqctl-delete-vhost" => &[After("delete_vhost")],
+        "rabbitmqctl-forget-cluster-node" => {
+            &[After("forget_cluster_node"), Flag("--node"), Flag("-n")]
+        }
+        "rabbitmqctl-reset" | "rabbitmqctl-force-reset" => &[Flag("--node"), Flag("-n")],
+        "aws-sqs-delete-queue"
+        | "aws-sqs-purge-queue"
+        | "aws-sqs-delete-message-batch"
+        | "aws-sqs-delete-message" => &[Flag("--queue-url")],
+        "aws-sns-delete-topic" | "aws-sns-remove-permission" => &[Flag("--topic-arn")],
+        "aws-sns-unsubscribe" => &[Flag("--subscription-arn")],
+        "aws-sns-delete-platform-application" => &[Flag("--platform-application-arn")],
+        _ => &[],
+    }
+}
+
+/// Extract the target values a rule names from one command segment.
+#[must_use]
+pub fn target_values<'a>(segment: &'a str, targets: &[TargetArg]) -> Vec<&'a str> {
+    let tokens: Vec<&str> = segment.split_whitespace().collect();
+    let mut values = Vec::new();
+    for target in targets {
+        match *target {
+            TargetArg::Flag(flag) => {
+                for (idx, token) in tokens.iter().enumerate() {
+                    if *token == flag {
+                        if let Some(next) = tokens.get(idx + 1) {
+                            values.push(*next);
+                        }
+                    } else if let Some(value) = token
+                        .strip_prefix(flag)
+                        .and_then(|rest| rest.strip_prefix('='))
+                    {
+                        values.push(value);
+                    }
+                }
+            }
+            TargetArg::After(word) => {
+                let Some(pos) = tokens.iter().position(|token| *token == word) else {
+                    continue;
+                };
+                let mut skip_value = false;
+                for token in &tokens[pos + 1..] {
+                    if skip_value {
+                        skip_value = false;
+                    } else if token.starts_with('-') {
+                        skip_value = !token.contains('=') && !BOOLEAN_FLAGS.contains(token);
+                    } else {
+                        values.push(*token);
+                    }
+                }
+            }
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_targets_ignore_connection_flags() {
+        let segment = "kafka-topics --bootstrap-server prod-kafka:9092 --delete --topic orders-dev";
+        assert_eq!(
+            target_values(segment, target_args("kafka-topics-delete")),
+            vec!["orders-dev"]
+        );
+        let segment = "aws kinesis delete-stream --region=prod-eu --stream-name=clicks";
+        assert_eq!(
+            target_values(segment, target_args("aws-kinesis-delete-stream")),
+            vec!["clicks"]
+        );
+    }
+
+    #[test]
+    fn positional_targets_skip_flag_values() {
+        let segment = "nats stream rm --server prod-nats orders --force";
+        assert_eq!(
+            target_values(segment, target_args("nats-stream-delete")),
+            vec!["orders"]
+        );
+        let segment = "nats stream rm -f products";
+        assert_eq!(
+            target_values(segment, target_args("nats-stream-delete")),
+            vec!["products"]
+        );
+    }
+
+    #[test]
+    fn unknown_rules_have_no_targets() {
+        assert!(target_args("kafka-delete-records").is_empty());
+        assert!(target_values("anything prod", target_args("nope")).is_empty());
+    }
+}
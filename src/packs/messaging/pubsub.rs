@@ -0,0 +1,140 @@
+//! Google Cloud Pub/Sub pack - protections for destructive `gcloud pubsub` operations.
+//!
+//! Covers destructive CLI operations:
+//! - Topic and subscription deletion
+//! - Subscription seeks that drop or replay messages
+//! - Snapshot and schema deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Google Cloud Pub/Sub pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "messaging.pubsub".to_string(),
+        name: "Google Cloud Pub/Sub",
+        description: "Protects against destructive Google Cloud Pub/Sub operations like deleting \
+                      topics and subscriptions and seeking subscriptions past unacked messages.",
+        keywords: &["pubsub"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "gcloud-pubsub-read",
+            r"gcloud\s+pubsub\s+(?:topics|subscriptions|snapshots|schemas)\s+(?:list|describe|list-subscriptions)\b"
+        ),
+        safe_pattern!(
+            "gcloud-pubsub-pull",
+            r"gcloud\s+pubsub\s+subscriptions\s+pull\b"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "gcloud-pubsub-subscriptions-delete",
+            r"gcloud\s+pubsub\s+subscriptions\s+delete\b",
+            "gcloud pubsub subscriptions delete deletes a subscription and its backlog.",
+            High,
+            "Deleting a subscription discards every unacknowledged message in its backlog. \
+             Recreating a subscription with the same name starts empty.\n\n\
+             Safer alternatives:\n\
+             - gcloud pubsub subscriptions describe to check the backlog first\n\
+             - gcloud pubsub snapshots create to keep the backlog recoverable\n\
+             - Detach the subscription instead of deleting it"
+        ),
+        destructive_pattern!(
+            "gcloud-pubsub-topics-delete",
+            r"gcloud\s+pubsub\s+topics\s+delete\b",
+            "gcloud pubsub topics delete deletes a Pub/Sub topic.",
+            High,
+            "Deleting a topic makes every publisher fail and detaches all subscriptions \
+             from it.\n\n\
+             Safer alternatives:\n\
+             - gcloud pubsub topics list-subscriptions to find dependents\n\
+             - Confirm no publishers still use the topic"
+        ),
+        destructive_pattern!(
+            "gcloud-pubsub-subscriptions-seek",
+            r"gcloud\s+pubsub\s+subscriptions\s+seek\b",
+            "gcloud pubsub subscriptions seek can acknowledge or replay messages in bulk.",
+            Medium,
+            "Seeking to a later time or snapshot marks older messages as acknowledged, \
+             effectively dropping them.\n\n\
+             Safer alternatives:\n\
+             - Create a snapshot first so you can seek back"
+        ),
+        destructive_pattern!(
+            "gcloud-pubsub-snapshots-delete",
+            r"gcloud\s+pubsub\s+(?:snapshots|schemas)\s+delete\b",
+            "gcloud pubsub snapshots/schemas delete removes Pub/Sub snapshots or schemas.",
+            Medium,
+            "Deleting a snapshot removes the ability to replay from it; deleting a schema \
+             breaks topics that validate against it.\n\n\
+             Safer alternatives:\n\
+             - gcloud pubsub snapshots describe to check what it protects"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "messaging.pubsub");
+        assert_eq!(pack.name, "Google Cloud Pub/Sub");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"pubsub"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "gcloud pubsub topics list");
+        assert_safe_pattern_matches(&pack, "gcloud pubsub subscriptions describe orders-sub");
+        assert_safe_pattern_matches(&pack, "gcloud pubsub subscriptions pull orders-sub");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud pubsub subscriptions delete orders-sub",
+            "gcloud-pubsub-subscriptions-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud pubsub topics delete orders",
+            "gcloud-pubsub-topics-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud pubsub subscriptions seek orders-sub --time=2024-01-01T00:00:00Z",
+            "gcloud-pubsub-subscriptions-seek",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "gcloud pubsub snapshots delete snap-1",
+            "gcloud-pubsub-snapshots-delete",
+        );
+    }
+}
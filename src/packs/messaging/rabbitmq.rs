@@ -20,6 +20,7 @@ pub fn create_pack() -> Pack {
         keywords: &["rabbitmqadmin", "rabbitmqctl"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -95,6 +96,18 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              - Consume and process messages instead of purging\n\
              - Set message TTL for automatic expiration"
         ),
+        destructive_pattern!(
+            "rabbitmqctl-purge-queue",
+            r"rabbitmqctl(?:\s+--?\S+(?:\s+\S+)?)*\s+purge_queue\b",
+            "rabbitmqctl purge_queue deletes ALL messages in the queue.",
+            High,
+            "Purging a queue deletes all ready messages immediately. Messages that were \
+             waiting to be consumed are permanently lost. This cannot be undone.\n\n\
+             Safer alternatives:\n\
+             - rabbitmqctl list_queues name messages: Check message count first\n\
+             - Consume and process messages instead of purging\n\
+             - Set message TTL for automatic expiration"
+        ),
         destructive_pattern!(
             "rabbitmqctl-delete-vhost",
             r"rabbitmqctl(?:\s+--?\S+(?:\s+\S+)?)*\s+delete_vhost\b",
@@ -197,6 +210,11 @@ mod tests {
             "rabbitmqadmin purge queue name=jobs",
             "rabbitmqadmin-purge-queue",
         );
+        assert_blocks_with_pattern(
+            &pack,
+            "rabbitmqctl purge_queue jobs",
+            "rabbitmqctl-purge-queue",
+        );
         assert_blocks_with_pattern(
             &pack,
             "rabbitmqctl delete_vhost /prod",
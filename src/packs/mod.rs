@@ -13,8 +13,11 @@
 //! Enabling a category enables all its sub-packs. Sub-packs can be individually
 //! disabled even if their parent category is enabled.
 
+pub mod antitamper;
 pub mod apigateway;
+pub mod audit;
 pub mod backup;
+pub mod blockdevice;
 pub mod cdn;
 pub mod cicd;
 pub mod cloud;
@@ -25,29 +28,37 @@ pub mod dns;
 pub mod email;
 pub mod external;
 pub mod featureflags;
+pub mod iam;
 pub mod infrastructure;
 pub mod kubernetes;
 pub mod loadbalancer;
 pub mod messaging;
 pub mod monitoring;
+pub mod network;
 pub mod package_managers;
 pub mod payment;
 pub mod platform;
 pub mod regex_engine;
 pub mod remote;
+pub mod remote_exec;
 pub mod safe;
+pub mod scaffold;
 pub mod search;
 pub mod secrets;
 pub mod storage;
 pub mod strict_git;
 pub mod system;
+pub mod unicode;
+pub mod virtualization;
 
 // Testing infrastructure
 pub mod test_helpers;
 #[cfg(test)]
 mod test_template;
 
+use crate::normalize::CommandFlagSpec;
 pub use crate::normalize::normalize_command;
+use crate::suggestions::SuggestionProvider;
 use memchr::memmem;
 use regex_engine::LazyCompiledRegex;
 use serde::Serialize;
@@ -97,6 +108,28 @@ impl Severity {
         }
     }
 
+    /// Ordering rank: higher is more severe (`Critical` = 3, `Low` = 0).
+    #[must_use]
+    pub const fn rank(&self) -> u8 {
+        match self {
+            Self::Critical => 3,
+            Self::High => 2,
+            Self::Medium => 1,
+            Self::Low => 0,
+        }
+    }
+
+    /// Inverse of [`Self::rank`]; ranks above 3 saturate at `Critical`.
+    #[must_use]
+    pub const fn from_rank(rank: u8) -> Self {
+        match rank {
+            0 => Self::Low,
+            1 => Self::Medium,
+            2 => Self::High,
+            _ => Self::Critical,
+        }
+    }
+
     /// Returns true if this severity level blocks by default.
     #[must_use]
     pub const fn blocks_by_default(&self) -> bool {
@@ -125,15 +158,25 @@ pub enum DecisionMode {
     /// Warn but allow (print warning to stderr, no JSON deny).
     Warn,
 
+    /// Warn on the first occurrence of a command per rule and session, then
+    /// allow identical repeats silently (see [`crate::warn_once`]).
+    WarnOnce,
+
     /// Log only (silent allow, record for history).
     Log,
+
+    /// Let a human decide: the hook answers `permissionDecision: "ask"` so
+    /// the agent prompts the user. Where nobody can be asked, it denies.
+    Ask,
 }
 
 impl DecisionMode {
     /// Returns true if this mode blocks command execution.
+    ///
+    /// `Ask` counts as blocking: the command only runs once a human approves.
     #[must_use]
     pub const fn blocks(&self) -> bool {
-        matches!(self, Self::Deny)
+        matches!(self, Self::Deny | Self::Ask)
     }
 
     /// Get a human-readable label for this mode.
@@ -142,7 +185,9 @@ impl DecisionMode {
         match self {
             Self::Deny => "deny",
             Self::Warn => "warn",
+            Self::WarnOnce => "warn_once",
             Self::Log => "log",
+            Self::Ask => "ask",
         }
     }
 }
@@ -325,7 +370,7 @@ macro_rules! destructive_pattern {
     // Unnamed pattern, default severity (High)
     ($re:literal, $reason:literal) => {
         $crate::packs::DestructivePattern {
-            regex: $crate::packs::regex_engine::LazyCompiledRegex::new($re),
+            regex: $crate::packs::regex_engine::LazyCompiledRegex::new_destructive($re),
             reason: $reason,
             name: None,
             severity: $crate::packs::Severity::High,
@@ -336,7 +381,7 @@ macro_rules! destructive_pattern {
     // Named pattern, default severity (High)
     ($name:literal, $re:literal, $reason:literal) => {
         $crate::packs::DestructivePattern {
-            regex: $crate::packs::regex_engine::LazyCompiledRegex::new($re),
+            regex: $crate::packs::regex_engine::LazyCompiledRegex::new_destructive($re),
             reason: $reason,
             name: Some($name),
             severity: $crate::packs::Severity::High,
@@ -347,7 +392,7 @@ macro_rules! destructive_pattern {
     // Named pattern with explicit severity
     ($name:literal, $re:literal, $reason:literal, $severity:ident) => {
         $crate::packs::DestructivePattern {
-            regex: $crate::packs::regex_engine::LazyCompiledRegex::new($re),
+            regex: $crate::packs::regex_engine::LazyCompiledRegex::new_destructive($re),
             reason: $reason,
             name: Some($name),
             severity: $crate::packs::Severity::$severity,
@@ -358,7 +403,7 @@ macro_rules! destructive_pattern {
     // Named pattern with explicit severity and explanation
     ($name:literal, $re:literal, $reason:literal, $severity:ident, $explanation:literal) => {
         $crate::packs::DestructivePattern {
-            regex: $crate::packs::regex_engine::LazyCompiledRegex::new($re),
+            regex: $crate::packs::regex_engine::LazyCompiledRegex::new_destructive($re),
             reason: $reason,
             name: Some($name),
             severity: $crate::packs::Severity::$severity,
@@ -369,7 +414,7 @@ macro_rules! destructive_pattern {
     // Named pattern with explicit severity, explanation, and suggestions
     ($name:literal, $re:literal, $reason:literal, $severity:ident, $explanation:literal, $suggestions:expr) => {
         $crate::packs::DestructivePattern {
-            regex: $crate::packs::regex_engine::LazyCompiledRegex::new($re),
+            regex: $crate::packs::regex_engine::LazyCompiledRegex::new_destructive($re),
             reason: $reason,
             name: Some($name),
             severity: $crate::packs::Severity::$severity,
@@ -401,6 +446,13 @@ pub struct Pack {
     /// Destructive patterns (blacklist) - checked if no safe pattern matches.
     pub destructive_patterns: Vec<DestructivePattern>,
 
+    /// Match against the command as written instead of the sanitized form.
+    ///
+    /// For packs that look inside the quoted data the sanitizer masks, such as
+    /// credentials in `curl -H 'Authorization: Bearer ...'`. Spans then need no
+    /// mapping back onto the original command.
+    pub match_raw: bool,
+
     /// Pre-built Aho-Corasick automaton for O(n) keyword matching.
     /// Built by `PackRegistry::register_pack()` from keywords. Set to `None` in pack
     /// constructors; the registry initializes this during registration.
@@ -439,6 +491,7 @@ impl Pack {
             keywords,
             safe_patterns,
             destructive_patterns,
+            match_raw: false,
             keyword_matcher: None,
             safe_regex_set: None,
             safe_regex_set_is_complete: false,
@@ -626,6 +679,17 @@ pub struct PackEntry {
     pub id: &'static str,
     /// Keywords for quick-reject filtering.
     pub keywords: &'static [&'static str],
+    /// Commands whose getopt-style flags this pack understands.
+    ///
+    /// The evaluator also matches the pack against the canonical flag form of
+    /// these commands (see [`crate::normalize::normalize_flags`]).
+    pub flag_specs: &'static [CommandFlagSpec],
+    /// Pack version, matched against `id@N` pins in `packs.enabled`.
+    pub version: u32,
+    /// Renamed patterns as `(old_name, new_name)` pairs within this pack.
+    pub rule_aliases: &'static [(&'static str, &'static str)],
+    /// Command-aware suggestion builders as `(pattern_name, provider)` pairs.
+    pub suggestion_providers: &'static [(&'static str, SuggestionProvider)],
     /// Function to build the full pack (called lazily).
     builder: fn() -> Pack,
     /// Cached pack instance (built on first access).
@@ -642,11 +706,74 @@ impl PackEntry {
         Self {
             id,
             keywords,
+            flag_specs: &[],
+            version: 1,
+            rule_aliases: &[],
+            suggestion_providers: &[],
             builder,
             instance: OnceLock::new(),
         }
     }
 
+    /// Opt this pack into flag normalization for the given commands.
+    #[must_use]
+    pub const fn with_flag_specs(mut self, flag_specs: &'static [CommandFlagSpec]) -> Self {
+        self.flag_specs = flag_specs;
+        self
+    }
+
+    /// Bump the pack version after a breaking change to its patterns.
+    #[must_use]
+    pub const fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Record patterns renamed since an earlier release.
+    ///
+    /// Allowlist entries naming `pack:old_name` keep matching `pack:new_name`
+    /// and are reported as deprecated until `dcg allowlist migrate-ids`
+    /// rewrites them.
+    #[must_use]
+    pub const fn with_rule_aliases(
+        mut self,
+        rule_aliases: &'static [(&'static str, &'static str)],
+    ) -> Self {
+        self.rule_aliases = rule_aliases;
+        self
+    }
+
+    /// Build suggestions from the blocked command for these patterns.
+    ///
+    /// Their output comes before the pattern's static suggestions in denials
+    /// and `dcg explain`.
+    #[must_use]
+    pub const fn with_suggestion_providers(
+        mut self,
+        suggestion_providers: &'static [(&'static str, SuggestionProvider)],
+    ) -> Self {
+        self.suggestion_providers = suggestion_providers;
+        self
+    }
+
+    /// Current name of a renamed pattern, following chained renames.
+    ///
+    /// Returns `None` if `pattern_name` is not a former name.
+    #[must_use]
+    pub fn resolve_alias(&self, pattern_name: &str) -> Option<&'static str> {
+        let mut resolved = None;
+        let mut name = pattern_name;
+        // Bounded by the table size so a cyclic table cannot loop forever.
+        for _ in 0..self.rule_aliases.len() {
+            let Some(&(_, new)) = self.rule_aliases.iter().find(|(old, _)| *old == name) else {
+                break;
+            };
+            resolved = Some(new);
+            name = new;
+        }
+        resolved
+    }
+
     /// Get or build the pack instance.
     ///
     /// # Panics
@@ -795,12 +922,22 @@ impl EnabledKeywordIndex {
 
 /// Static pack entries - metadata is available without instantiating packs.
 /// Packs are built lazily on first access.
-static PACK_ENTRIES: [PackEntry; 82] = [
-    PackEntry::new("core.git", &["git"], core::git::create_pack),
+static PACK_ENTRIES: [PackEntry; 103] = [
+    PackEntry::new("core.git", &["git"], core::git::create_pack)
+        .with_flag_specs(core::git::FLAG_SPECS)
+        .with_rule_aliases(core::git::RULE_ALIASES)
+        .with_suggestion_providers(core::git::SUGGESTION_PROVIDERS),
     PackEntry::new(
         "core.filesystem",
-        &["rm", "/rm"],
+        &["rm", "/rm", "find"],
         core::filesystem::create_pack,
+    )
+    .with_flag_specs(core::filesystem::FLAG_SPECS)
+    .with_rule_aliases(core::filesystem::RULE_ALIASES),
+    PackEntry::new(
+        unicode::obfuscation::PACK_ID,
+        &["\u{200b}"],
+        unicode::obfuscation::create_pack,
     ),
     PackEntry::new("storage.s3", &["s3", "s3api"], storage::s3::create_pack),
     PackEntry::new(
@@ -821,6 +958,41 @@ static PACK_ENTRIES: [PackEntry; 82] = [
         remote::ssh::create_pack,
     ),
     PackEntry::new("remote.scp", &["scp"], remote::scp::create_pack),
+    PackEntry::new(
+        "remote_exec",
+        &[
+            "curl",
+            "wget",
+            "iex",
+            "IEX",
+            "Invoke-Expression",
+            "invoke-expression",
+        ],
+        remote_exec::create_pack,
+    ),
+    PackEntry::new(
+        "antitamper",
+        &[
+            "history",
+            "bash_history",
+            "zsh_history",
+            "sh_history",
+            "zhistory",
+            "fish_history",
+            "python_history",
+            "mysql_history",
+            "psql_history",
+            "node_repl_history",
+            "HISTFILE",
+            "HISTSIZE",
+            "/var/log",
+            "journalctl",
+            "dcg",
+            "DCG_CONFIG",
+            "DCG_STATE_DIR",
+        ],
+        antitamper::create_pack,
+    ),
     PackEntry::new(
         "cicd.github_actions",
         &["gh"],
@@ -853,6 +1025,30 @@ static PACK_ENTRIES: [PackEntry; 82] = [
         &["doppler"],
         secrets::doppler::create_pack,
     ),
+    PackEntry::new(
+        "secrets.exposure",
+        &[
+            "AWS_ACCESS_KEY_ID",
+            "AWS_SECRET_ACCESS_KEY",
+            "aws",
+            "PGPASSWORD",
+            "MYSQL_PWD",
+            "--password",
+            "mysql",
+            "mysqldump",
+            "mysqladmin",
+            "mariadb",
+            "curl",
+            "wget",
+            "http",
+            "Bearer",
+            "git",
+            "gh",
+            "GH_TOKEN",
+            "GITHUB_TOKEN",
+        ],
+        secrets::exposure::create_pack,
+    ),
     PackEntry::new("platform.github", &["gh"], platform::github::create_pack),
     PackEntry::new(
         "platform.gitlab",
@@ -866,9 +1062,12 @@ static PACK_ENTRIES: [PackEntry; 82] = [
             "cloudflare",
             "api.cloudflare.com",
             "dns-records",
+            "flarectl",
+            "cfcli",
         ],
         dns::cloudflare::create_pack,
     ),
+    PackEntry::new("dns.gcp", &["gcloud"], dns::gcp::create_pack),
     PackEntry::new(
         "dns.route53",
         &["aws", "route53"],
@@ -1027,6 +1226,16 @@ static PACK_ENTRIES: [PackEntry; 82] = [
         &["aws", "sqs", "sns"],
         messaging::sqs_sns::create_pack,
     ),
+    PackEntry::new(
+        "messaging.kinesis",
+        &["kinesis", "firehose"],
+        messaging::kinesis::create_pack,
+    ),
+    PackEntry::new(
+        "messaging.pubsub",
+        &["pubsub"],
+        messaging::pubsub::create_pack,
+    ),
     PackEntry::new(
         "search.elasticsearch",
         &[
@@ -1143,9 +1352,14 @@ static PACK_ENTRIES: [PackEntry; 82] = [
         cloud::gcp::create_pack,
     ),
     PackEntry::new("cloud.azure", &["az"], cloud::azure::create_pack),
+    PackEntry::new("iam.aws", &["iam"], iam::aws::create_pack),
+    PackEntry::new("iam.gcp", &["gcloud"], iam::gcp::create_pack),
+    PackEntry::new("iam.azure", &["az"], iam::azure::create_pack),
+    PackEntry::new("iam.okta", &["okta"], iam::okta::create_pack),
+    PackEntry::new("iam.auth0", &["auth0"], iam::auth0::create_pack),
     PackEntry::new(
         "cdn.cloudflare_workers",
-        &["wrangler"],
+        &["wrangler", "purge_everything"],
         cdn::cloudflare_workers::create_pack,
     ),
     PackEntry::new("cdn.fastly", &["fastly"], cdn::fastly::create_pack),
@@ -1184,6 +1398,11 @@ static PACK_ENTRIES: [PackEntry; 82] = [
         &["pulumi"],
         infrastructure::pulumi::create_pack,
     ),
+    PackEntry::new(
+        "system.archive",
+        &["tar", "bsdtar", "unzip"],
+        system::archive::create_pack,
+    ),
     PackEntry::new(
         "system.disk",
         &[
@@ -1209,19 +1428,103 @@ static PACK_ENTRIES: [PackEntry; 82] = [
     ),
     PackEntry::new(
         "system.permissions",
-        &["chmod", "chown", "setfacl"],
+        &["chmod", "chown", "chgrp", "setfacl"],
         system::permissions::create_pack,
     ),
+    PackEntry::new(
+        "system.scheduler",
+        &[
+            "crontab",
+            "atrm",
+            "atq",
+            "at -",
+            "schtasks",
+            "SCHTASKS",
+            "launchctl",
+        ],
+        system::scheduler::create_pack,
+    ),
     PackEntry::new(
         "system.services",
-        &["systemctl", "service"],
+        &[
+            "systemctl",
+            "service",
+            "init",
+            "upstart",
+            "shutdown",
+            "reboot",
+            "halt",
+            "poweroff",
+            "kill",
+            "pkill",
+        ],
         system::services::create_pack,
     ),
+    PackEntry::new(
+        "blockdevice",
+        &[
+            "dd",
+            "mkfs",
+            "mke2fs",
+            "mkswap",
+            "wipefs",
+            "blkdiscard",
+            "parted",
+            "fdisk",
+            "shred",
+        ],
+        blockdevice::create_pack,
+    ),
+    PackEntry::new(
+        "network",
+        &[
+            "iptables",
+            "ip6tables",
+            "nft",
+            "ufw",
+            "ip link",
+            "ip addr",
+            "ip address",
+            "ip a flush",
+            "ip route",
+            "ip r flush",
+            "ip -",
+            "networksetup",
+            "revoke-security-group",
+        ],
+        network::create_pack,
+    ),
+    PackEntry::new(
+        "virtualization.libvirt",
+        &["virsh"],
+        virtualization::libvirt::create_pack,
+    ),
+    PackEntry::new(
+        "virtualization.virtualbox",
+        &["VBoxManage", "vboxmanage"],
+        virtualization::virtualbox::create_pack,
+    ),
+    PackEntry::new(
+        "virtualization.vagrant",
+        &["vagrant"],
+        virtualization::vagrant::create_pack,
+    ),
+    PackEntry::new(
+        "virtualization.multipass",
+        &["multipass"],
+        virtualization::multipass::create_pack,
+    ),
+    PackEntry::new(
+        "virtualization.proxmox",
+        &["qm", "pct", "pvesm"],
+        virtualization::proxmox::create_pack,
+    ),
     PackEntry::new("strict_git", &["git"], strict_git::create_pack),
     PackEntry::new(
         "package_managers",
         &[
-            "npm", "yarn", "pnpm", "pip", "cargo", "gem", "composer", "go",
+            "npm", "yarn", "pnpm", "pip", "pip3", "apt", "yum", "dnf", "pacman", "cargo", "gem",
+            "brew", "poetry", "mvn", "mvnw", "gradle", "gradlew", "publish",
         ],
         package_managers::create_pack,
     ),
@@ -1341,10 +1644,10 @@ impl PackRegistry {
     /// multiple packs could match the same command. The ordering is:
     ///
     /// 0. **Tier 0 (safe)**: `safe.*` packs - safe patterns checked first to whitelist
-    /// 1. **Tier 1 (core/storage/remote)**: `core.*`, `storage.*`, `remote.*` packs - most fundamental protections
-    /// 2. **Tier 2 (system)**: `system.*` - disk, permissions, services
+    /// 1. **Tier 1 (core/storage/remote/remote_exec/antitamper/unicode)**: `core.*`, `storage.*`, `remote.*`, `remote_exec`, `antitamper`, `unicode.*` packs - most fundamental protections
+    /// 2. **Tier 2 (system/blockdevice/network/virtualization)**: `system.*`, `blockdevice`, `network`, `virtualization.*` - disk, permissions, services, block devices, firewall and interfaces, VMs
     /// 3. **Tier 3 (infrastructure)**: `infrastructure.*` - terraform, ansible, pulumi
-    /// 4. **Tier 4 (apigateway/cloud/dns/platform/cdn/loadbalancer)**: `apigateway.*`, `cloud.*`, `dns.*`, `platform.*`, `cdn.*`, `loadbalancer.*`
+    /// 4. **Tier 4 (apigateway/cloud/dns/iam/platform/cdn/loadbalancer)**: `apigateway.*`, `cloud.*`, `dns.*`, `iam.*`, `platform.*`, `cdn.*`, `loadbalancer.*`
     /// 5. **Tier 5 (kubernetes)**: `kubernetes.*` - kubectl, helm, kustomize
     /// 6. **Tier 6 (containers)**: `containers.*` - docker, compose, podman
     /// 7. **Tier 7 (database/search/messaging/backup)**: `database.*`, `search.*`, `messaging.*`, `backup.*`
@@ -1377,14 +1680,14 @@ impl PackRegistry {
     ///
     /// Safe packs (tier 0) are evaluated first so their safe patterns can
     /// whitelist commands before other packs' destructive patterns match.
-    fn pack_tier(pack_id: &str) -> u8 {
+    pub(crate) fn pack_tier(pack_id: &str) -> u8 {
         let category = pack_id.split('.').next().unwrap_or(pack_id);
         match category {
             "safe" => 0,
-            "core" | "storage" | "remote" => 1,
-            "system" => 2,
+            "core" | "storage" | "remote" | "remote_exec" | "antitamper" | "unicode" => 1,
+            "system" | "blockdevice" | "network" | "virtualization" => 2,
             "infrastructure" => 3,
-            "apigateway" | "cdn" | "cloud" | "dns" | "loadbalancer" | "platform" => 4,
+            "apigateway" | "cdn" | "cloud" | "dns" | "iam" | "loadbalancer" | "platform" => 4,
             "kubernetes" => 5,
             "containers" => 6,
             "backup" | "database" | "messaging" | "search" => 7,
@@ -1478,6 +1781,7 @@ impl PackRegistry {
                     id: pack.id.clone(),
                     name: pack.name,
                     description: pack.description,
+                    version: entry.version,
                     enabled: expanded.contains(&pack.id),
                     safe_pattern_count: pack.safe_patterns.len(),
                     destructive_pattern_count: pack.destructive_patterns.len(),
@@ -1496,6 +1800,39 @@ impl PackRegistry {
         self.index.get(id).map(|&idx| self.entries[idx])
     }
 
+    /// Current name of a renamed pattern in a built-in pack (see
+    /// [`PackEntry::with_rule_aliases`]), or `None` if the name is current or
+    /// unknown.
+    #[must_use]
+    pub fn canonical_pattern_name(
+        &self,
+        pack_id: &str,
+        pattern_name: &str,
+    ) -> Option<&'static str> {
+        self.get_entry(pack_id)?.resolve_alias(pattern_name)
+    }
+
+    /// Suggestion provider registered for `pack_id:pattern_name` (see
+    /// [`PackEntry::with_suggestion_providers`]).
+    #[must_use]
+    pub fn suggestion_provider(
+        &self,
+        pack_id: &str,
+        pattern_name: &str,
+    ) -> Option<SuggestionProvider> {
+        self.get_entry(pack_id)?
+            .suggestion_providers
+            .iter()
+            .find(|(name, _)| *name == pattern_name)
+            .map(|&(_, provider)| provider)
+    }
+
+    /// Flag normalization specs declared by a pack (empty if it has none).
+    #[must_use]
+    pub fn flag_specs(&self, id: &str) -> &'static [CommandFlagSpec] {
+        self.get_entry(id).map_or(&[], |entry| entry.flag_specs)
+    }
+
     /// Build an [`EnabledKeywordIndex`] for a precomputed ordered pack list.
     ///
     /// This is intended to run once per config load; callers reuse the returned
@@ -1604,6 +1941,8 @@ pub struct PackInfo {
     pub name: &'static str,
     /// Description.
     pub description: &'static str,
+    /// Pack version.
+    pub version: u32,
     /// Whether the pack is enabled.
     pub enabled: bool,
     /// Number of safe patterns.
@@ -1626,6 +1965,8 @@ pub static REGISTRY: LazyLock<PackRegistry> = LazyLock::new(PackRegistry::new);
 pub struct ExternalPackStore {
     /// Loaded packs keyed by pack ID.
     packs: HashMap<String, Pack>,
+    /// Major version of each loaded pack, keyed by pack ID.
+    versions: HashMap<String, u32>,
     /// Keywords from all external packs (for quick rejection).
     keywords: Vec<&'static str>,
     /// Warnings from pack loading (for diagnostics).
@@ -1637,6 +1978,7 @@ impl ExternalPackStore {
     fn new() -> Self {
         Self {
             packs: HashMap::new(),
+            versions: HashMap::new(),
             keywords: Vec::new(),
             warnings: Vec::new(),
         }
@@ -1648,6 +1990,12 @@ impl ExternalPackStore {
         self.packs.get(id)
     }
 
+    /// Major version of a loaded pack (from its semver `version` field).
+    #[must_use]
+    pub fn version(&self, id: &str) -> Option<u32> {
+        self.versions.get(id).copied()
+    }
+
     /// Get all pack IDs.
     pub fn pack_ids(&self) -> impl Iterator<Item = &String> {
         self.packs.keys()
@@ -1815,7 +2163,8 @@ pub fn load_external_packs(paths: &[String]) -> &'static ExternalPackStore {
         // Collect warnings
         for warning in result.warnings {
             store.warnings.push(format!(
-                "Failed to load external pack from {}: {}",
+                "[{}] Failed to load external pack from {}: {}",
+                warning.error.code(),
                 warning.path.display(),
                 warning.error
             ));
@@ -1824,6 +2173,13 @@ pub fn load_external_packs(paths: &[String]) -> &'static ExternalPackStore {
         // Convert and store loaded packs
         for loaded in result.packs {
             let id = loaded.id.clone();
+            let version = loaded
+                .pack
+                .version
+                .split('.')
+                .next()
+                .and_then(|major| major.parse().ok())
+                .unwrap_or(1);
             let pack = loaded.pack.into_pack();
 
             // Collect keywords
@@ -1833,6 +2189,7 @@ pub fn load_external_packs(paths: &[String]) -> &'static ExternalPackStore {
                 }
             }
 
+            store.versions.insert(id.clone(), version);
             store.packs.insert(id, pack);
         }
 
@@ -1846,6 +2203,15 @@ pub fn get_external_packs() -> Option<&'static ExternalPackStore> {
     EXTERNAL_PACKS.get()
 }
 
+/// Installed version of a built-in or loaded external pack.
+#[must_use]
+pub fn installed_pack_version(id: &str) -> Option<u32> {
+    REGISTRY
+        .get_entry(id)
+        .map(|entry| entry.version)
+        .or_else(|| get_external_packs().and_then(|store| store.version(id)))
+}
+
 /// Pre-compiled finders for core quick rejection (git/rm).
 #[allow(dead_code)]
 static GIT_FINDER: LazyLock<memmem::Finder<'static>> = LazyLock::new(|| memmem::Finder::new("git"));
@@ -2117,6 +2483,39 @@ pub fn pack_aware_quick_reject_with_normalized<'a>(
 mod tests {
     use super::*;
 
+    #[test]
+    fn rule_aliases_follow_chained_renames() {
+        static ENTRY: PackEntry = PackEntry::new("core.git", &["git"], core::git::create_pack)
+            .with_rule_aliases(&[
+                ("hard-reset", "reset-hard-v1"),
+                ("reset-hard-v1", "reset-hard"),
+                ("loop-a", "loop-b"),
+                ("loop-b", "loop-a"),
+            ]);
+        assert_eq!(ENTRY.resolve_alias("hard-reset"), Some("reset-hard"));
+        assert_eq!(ENTRY.resolve_alias("reset-hard-v1"), Some("reset-hard"));
+        assert_eq!(ENTRY.resolve_alias("reset-hard"), None);
+        assert!(ENTRY.resolve_alias("loop-a").is_some());
+    }
+
+    #[test]
+    fn rule_aliases_point_at_existing_patterns() {
+        for id in REGISTRY.all_pack_ids() {
+            let entry = REGISTRY.get_entry(id).unwrap();
+            let pack = entry.get_pack();
+            let names: HashSet<&str> = pack
+                .destructive_patterns
+                .iter()
+                .filter_map(|p| p.name)
+                .collect();
+            for (old, _) in entry.rule_aliases {
+                let new = entry.resolve_alias(old).unwrap();
+                assert!(names.contains(new), "{id}: alias {old} -> missing {new}");
+                assert!(!names.contains(old), "{id}: alias {old} shadows a pattern");
+            }
+        }
+    }
+
     #[test]
     fn pack_aware_quick_reject_empty_keywords_is_conservative() {
         assert!(
@@ -2291,10 +2690,17 @@ mod tests {
         assert_eq!(PackRegistry::pack_tier("core.filesystem"), 1);
         assert_eq!(PackRegistry::pack_tier("storage.s3"), 1);
         assert_eq!(PackRegistry::pack_tier("remote.rsync"), 1);
+        assert_eq!(PackRegistry::pack_tier("antitamper"), 1);
+        assert_eq!(PackRegistry::pack_tier("remote_exec"), 1);
 
         // System should be tier 2
+        assert_eq!(PackRegistry::pack_tier("system.archive"), 2);
         assert_eq!(PackRegistry::pack_tier("system.disk"), 2);
         assert_eq!(PackRegistry::pack_tier("system.permissions"), 2);
+        assert_eq!(PackRegistry::pack_tier("system.scheduler"), 2);
+        assert_eq!(PackRegistry::pack_tier("virtualization.libvirt"), 2);
+        assert_eq!(PackRegistry::pack_tier("blockdevice"), 2);
+        assert_eq!(PackRegistry::pack_tier("network"), 2);
 
         // Infrastructure should be tier 3
         assert_eq!(PackRegistry::pack_tier("infrastructure.terraform"), 3);
@@ -2304,7 +2710,10 @@ mod tests {
         assert_eq!(PackRegistry::pack_tier("apigateway.aws"), 4);
         assert_eq!(PackRegistry::pack_tier("dns.cloudflare"), 4);
         assert_eq!(PackRegistry::pack_tier("dns.route53"), 4);
+        assert_eq!(PackRegistry::pack_tier("dns.gcp"), 4);
         assert_eq!(PackRegistry::pack_tier("dns.generic"), 4);
+        assert_eq!(PackRegistry::pack_tier("iam.aws"), 4);
+        assert_eq!(PackRegistry::pack_tier("iam.gcp"), 4);
         assert_eq!(PackRegistry::pack_tier("platform.github"), 4);
         assert_eq!(PackRegistry::pack_tier("cdn.cloudflare_workers"), 4);
         assert_eq!(PackRegistry::pack_tier("loadbalancer.nginx"), 4);
@@ -2649,6 +3058,10 @@ mod tests {
         assert!(DecisionMode::Deny.blocks(), "Deny should block");
         assert!(!DecisionMode::Warn.blocks(), "Warn should not block");
         assert!(!DecisionMode::Log.blocks(), "Log should not block");
+        assert!(
+            DecisionMode::Ask.blocks(),
+            "Ask should block until approved"
+        );
     }
 
     /// Test severity labels.
@@ -2666,6 +3079,7 @@ mod tests {
         assert_eq!(DecisionMode::Deny.label(), "deny");
         assert_eq!(DecisionMode::Warn.label(), "warn");
         assert_eq!(DecisionMode::Log.label(), "log");
+        assert_eq!(DecisionMode::Ask.label(), "ask");
     }
 
     /// Test that `CheckResult` includes severity and `decision_mode`.
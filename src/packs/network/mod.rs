@@ -0,0 +1,290 @@
+//! Network pack - protections against cutting a host off the network.
+//!
+//! An agent usually reaches its host over the network, so a firewall lockout
+//! or a flushed interface is as bad as a powered-off machine. This pack covers:
+//! - iptables/ip6tables flushes and DROP default policies
+//! - nft flush ruleset and table deletion
+//! - ufw disable / reset
+//! - ip link delete / set down, ip addr flush, ip route flush
+//! - macOS `networksetup` turning Wi-Fi or a network service off
+//! - AWS security-group rule revocation, including revoking every rule at once
+
+use crate::destructive_pattern;
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+
+/// Create the Network pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "network".to_string(),
+        name: "Network",
+        description: "Protects against firewall flushes and lockouts (iptables, nft, ufw), \
+                      deleting or flushing interfaces and routes, turning macOS network \
+                      services off, and revoking cloud security-group rules",
+        keywords: &[
+            "iptables",
+            "ip6tables",
+            "nft",
+            "ufw",
+            "ip link",
+            "ip addr",
+            "ip address",
+            "ip a flush",
+            "ip route",
+            "ip r flush",
+            "ip -",
+            "networksetup",
+            "revoke-security-group",
+        ],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+const fn create_safe_patterns() -> Vec<SafePattern> {
+    // Listing commands (iptables -L, nft list ruleset, ip addr show) never
+    // reach the destructive patterns below.
+    Vec::new()
+}
+
+#[allow(clippy::too_many_lines)]
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        // --- iptables / ip6tables ---
+        destructive_pattern!(
+            "iptables-policy-drop",
+            r"\bip6?tables(?:-legacy|-nft)?\s+(?:[^\s;&|]+\s+)*?(?:-P|--policy)\s+(?:INPUT|OUTPUT)\s+(?:DROP|REJECT)\b",
+            "Setting the INPUT/OUTPUT policy to DROP cuts every connection without an explicit ACCEPT rule, including this session.",
+            Critical,
+            "A DROP default policy on INPUT or OUTPUT discards every packet that no \
+             rule accepts. Unless an ACCEPT rule for your SSH connection is already in \
+             place, the host stops answering immediately and can only be recovered from \
+             the console.\n\n\
+             Review the current rules first:\n  \
+             iptables -S\n\n\
+             Add the ACCEPT rules before changing the policy, and schedule a rollback:\n  \
+             iptables-save > /tmp/rules.v4 && echo 'iptables-restore < /tmp/rules.v4' | at now + 5 minutes"
+        ),
+        destructive_pattern!(
+            "iptables-flush",
+            r"\bip6?tables(?:-legacy|-nft)?\s+(?:[^\s;&|]+\s+)*?(?:-F|--flush)(?:[\s;&|)]|$)",
+            "iptables -F deletes every firewall rule; with a DROP policy it locks out remote access.",
+            High,
+            "Flushing removes all rules from the chain (or every chain). With a DROP \
+             default policy the host then rejects all traffic, including SSH; with an \
+             ACCEPT policy every port is suddenly open.\n\n\
+             Save the rules first so they can be restored:\n  \
+             iptables-save > /tmp/rules.v4\n\n\
+             Delete a single rule instead:\n  \
+             iptables -D <chain> <rule-number>"
+        ),
+        // --- nftables ---
+        destructive_pattern!(
+            "nft-flush-ruleset",
+            r"\bnft\s+(?:-\S+\s+)*flush\s+ruleset\b",
+            "nft flush ruleset removes every nftables table, chain, and rule.",
+            High,
+            "flush ruleset empties the whole nftables configuration in one step: NAT, \
+             filtering, and any rules other tools (docker, libvirt, firewalld) installed. \
+             Containers and VMs lose connectivity and the host is left unfiltered.\n\n\
+             Save the ruleset first:\n  \
+             nft list ruleset > /tmp/ruleset.nft\n\n\
+             Restore it with:\n  \
+             nft -f /tmp/ruleset.nft"
+        ),
+        destructive_pattern!(
+            "nft-delete-table",
+            r"\bnft\s+(?:-\S+\s+)*(?:delete|flush|destroy)\s+table\b",
+            "nft delete/flush table removes an nftables table's chains and rules.",
+            Medium
+        ),
+        // --- ufw ---
+        destructive_pattern!(
+            "ufw-disable",
+            r"\bufw\s+(?:--?\S+\s+)*(?:disable|reset)\b",
+            "ufw disable/reset turns the host firewall off and (for reset) deletes its rules.",
+            High,
+            "ufw disable stops filtering and leaves every listening port reachable. \
+             ufw reset also disables the firewall and deletes all user rules, so they \
+             have to be recreated by hand.\n\n\
+             Check the current rules:\n  \
+             ufw status numbered\n\n\
+             Remove a single rule instead:\n  \
+             ufw delete <number>"
+        ),
+        // --- iproute2 ---
+        destructive_pattern!(
+            "ip-addr-flush",
+            r"\bip\s+(?:-\S+\s+)*(?:a|addr|address)\s+flush\b",
+            "ip addr flush removes the interface's addresses and drops every connection over it.",
+            Critical,
+            "Flushing addresses removes the IPs from the interface at once. A remote \
+             session over that interface is cut off, and nothing restores the addresses \
+             until the network service is restarted from the console.\n\n\
+             Inspect the addresses first:\n  \
+             ip addr show <dev>\n\n\
+             Delete one address instead:\n  \
+             ip addr del <address>/<prefix> dev <dev>"
+        ),
+        destructive_pattern!(
+            "ip-route-flush",
+            r"\bip\s+(?:-\S+\s+)*(?:r|route)\s+flush\b",
+            "ip route flush removes routes, including the default route this host is reached over.",
+            Critical,
+            "Flushing the routing table (or a matching subset) removes the default route \
+             and any route back to the client, so remote sessions stop working.\n\n\
+             Inspect the routes first:\n  \
+             ip route show\n\n\
+             Delete one route instead:\n  \
+             ip route del <prefix>"
+        ),
+        destructive_pattern!(
+            "ip-link-delete",
+            r"\bip\s+(?:-\S+\s+)*link\s+(?:delete|del)\b",
+            "ip link delete removes a network interface.",
+            High,
+            "Deleting a link removes the interface and every address and route on it. \
+             Removing a bridge, bond, or VLAN the host is reached through cuts off \
+             remote access; virtual interfaces created by docker or libvirt break their \
+             containers and VMs.\n\n\
+             Inspect the interface first:\n  \
+             ip -d link show <dev>"
+        ),
+        destructive_pattern!(
+            "ip-link-down",
+            r"\bip\s+(?:-\S+\s+)*link\s+set\s+(?:dev\s+)?\S+\s+(?:[^\s;&|]+\s+)*?down\b",
+            "ip link set down disables a network interface.",
+            High,
+            "Setting an interface down drops its carrier immediately. If the agent's \
+             connection runs over that interface, the command cuts it off before any \
+             follow-up command (such as setting it up again) can run.\n\n\
+             Check which interface carries the default route:\n  \
+             ip route get 1.1.1.1"
+        ),
+        // --- macOS networksetup ---
+        destructive_pattern!(
+            "networksetup-off",
+            r#"\bnetworksetup\s+(?:-setairportpower\s+\S+|-setnetworkserviceenabled\s+(?:"[^"]*"|'[^']*'|\S+))\s+off\b"#,
+            "networksetup ... off turns Wi-Fi or a network service off on macOS.",
+            High,
+            "Turning off Wi-Fi or disabling a network service takes the Mac off that \
+             network at once. On a remote or headless Mac there is no way to turn it \
+             back on without physical access or another network path.\n\n\
+             List network services and their state:\n  \
+             networksetup -listallnetworkservices"
+        ),
+        // --- cloud security groups ---
+        destructive_pattern!(
+            "aws-sg-revoke-all",
+            r#"\baws\s+(?:--?\S+\s+)*ec2\s+revoke-security-group-(?:ingress|egress)\b[^;&|]*?(?:--ip-permissions|--security-group-rule-ids)[=\s]+["']?(?:\$\(|`|file://)"#,
+            "Revoking security-group rules from a generated list can remove every rule at once.",
+            Critical,
+            "Feeding revoke-security-group-ingress/egress the output of \
+             describe-security-groups (or a saved JSON file) revokes the group's whole \
+             rule set, including the rule that lets you reach the instances behind it.\n\n\
+             Review the rules first:\n  \
+             aws ec2 describe-security-group-rules --filters Name=group-id,Values=<sg-id>\n\n\
+             Revoke single rules by ID:\n  \
+             aws ec2 revoke-security-group-ingress --group-id <sg-id> --security-group-rule-ids <sgr-id>"
+        ),
+        destructive_pattern!(
+            "aws-sg-revoke",
+            r"\baws\s+(?:--?\S+\s+)*ec2\s+revoke-security-group-(?:ingress|egress)\b",
+            "aws ec2 revoke-security-group-ingress/egress removes firewall rules from a security group.",
+            Medium
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "network");
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn firewall_wipes() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "iptables -F", "iptables-flush");
+        assert_blocks_with_pattern(&pack, "sudo iptables -t nat --flush", "iptables-flush");
+        assert_blocks_with_pattern(&pack, "ip6tables -F INPUT", "iptables-flush");
+        assert_blocks_with_pattern(&pack, "iptables -P INPUT DROP", "iptables-policy-drop");
+        assert_blocks_with_pattern(&pack, "nft flush ruleset", "nft-flush-ruleset");
+        assert_blocks_with_pattern(&pack, "nft delete table inet filter", "nft-delete-table");
+        assert_blocks_with_pattern(&pack, "ufw disable", "ufw-disable");
+        assert_blocks_with_pattern(&pack, "ufw --force reset", "ufw-disable");
+
+        assert_allows(&pack, "iptables -L -n");
+        assert_allows(&pack, "iptables -S");
+        assert_allows(&pack, "iptables -P FORWARD DROP");
+        assert_allows(&pack, "iptables -A INPUT -p tcp --dport 22 -j ACCEPT");
+        assert_allows(&pack, "nft list ruleset");
+        assert_allows(&pack, "ufw status verbose");
+        assert_allows(&pack, "ufw enable");
+    }
+
+    #[test]
+    fn interface_and_route_changes() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "ip addr flush dev eth0", "ip-addr-flush");
+        assert_blocks_with_pattern(&pack, "ip -4 a flush eth0", "ip-addr-flush");
+        assert_blocks_with_pattern(&pack, "ip route flush all", "ip-route-flush");
+        assert_blocks_with_pattern(&pack, "ip link delete br0", "ip-link-delete");
+        assert_blocks_with_pattern(&pack, "ip link set dev eth0 down", "ip-link-down");
+        assert_blocks_with_pattern(&pack, "ip link set eth0 mtu 1400 down", "ip-link-down");
+
+        assert_allows(&pack, "ip addr show");
+        assert_allows(&pack, "ip route get 1.1.1.1");
+        assert_allows(&pack, "ip link set eth0 up");
+        assert_allows(&pack, "ip -br link");
+    }
+
+    #[test]
+    fn macos_networksetup() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "networksetup -setairportpower en0 off",
+            "networksetup-off",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "networksetup -setnetworkserviceenabled \"Wi-Fi\" off",
+            "networksetup-off",
+        );
+        assert_allows(&pack, "networksetup -setairportpower en0 on");
+        assert_allows(&pack, "networksetup -listallnetworkservices");
+    }
+
+    #[test]
+    fn security_group_revocation() {
+        let pack = create_pack();
+        let all = "aws ec2 revoke-security-group-ingress --group-id sg-1 --ip-permissions \"$(aws ec2 describe-security-groups --group-ids sg-1 --query SecurityGroups[0].IpPermissions)\"";
+        assert_blocks_with_pattern(&pack, all, "aws-sg-revoke-all");
+        assert_blocks_with_severity(&pack, all, Severity::Critical);
+        assert_blocks_with_pattern(
+            &pack,
+            "aws ec2 revoke-security-group-egress --group-id sg-1 --ip-permissions file://rules.json",
+            "aws-sg-revoke-all",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "aws ec2 revoke-security-group-ingress --group-id sg-1 --security-group-rule-ids sgr-1",
+            "aws-sg-revoke",
+        );
+        assert_allows(&pack, "aws ec2 describe-security-groups --group-ids sg-1");
+    }
+}
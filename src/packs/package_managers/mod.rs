@@ -4,6 +4,10 @@
 //! - npm/yarn/pnpm publish without verification
 //! - pip install from untrusted sources
 //! - apt/yum remove critical packages
+//! - broad removals: `apt autoremove --purge -y`, `pacman -Rns` with globs,
+//!   `pip uninstall` fed by `pip freeze`
+//! - removing packages the system or the agent itself runs on (essential apt
+//!   packages, core brew formulae, global npm tooling)
 //! - cargo publish
 
 use crate::packs::{DestructivePattern, Pack, SafePattern};
@@ -18,11 +22,12 @@ pub fn create_pack() -> Pack {
         description: "Protects against dangerous package manager operations like publishing \
                       packages and removing critical system packages",
         keywords: &[
-            "npm", "yarn", "pnpm", "pip", "apt", "yum", "dnf", "cargo", "gem", "brew", "poetry",
-            "mvn", "mvnw", "gradle", "gradlew", "publish",
+            "npm", "yarn", "pnpm", "pip", "pip3", "apt", "yum", "dnf", "pacman", "cargo", "gem",
+            "brew", "poetry", "mvn", "mvnw", "gradle", "gradlew", "publish",
         ],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -31,8 +36,8 @@ pub fn create_pack() -> Pack {
 
 fn create_safe_patterns() -> Vec<SafePattern> {
     vec![
-        // npm/yarn/pnpm install are generally safe
-        safe_pattern!("npm-install", r"npm\s+(?:install|i|ci)\b"),
+        // npm/yarn/pnpm install are generally safe (npm: unless chained with more)
+        safe_pattern!("npm-install", r"npm\s+(?:install|i|ci)\b[^|;&]*$"),
         safe_pattern!("yarn-add", r"yarn\s+(?:add|install)\b"),
         safe_pattern!("pnpm-install", r"pnpm\s+(?:add|install|i)\b"),
         // list/info commands are safe
@@ -42,7 +47,8 @@ fn create_safe_patterns() -> Vec<SafePattern> {
         safe_pattern!("npm-audit", r"npm\s+audit"),
         safe_pattern!("yarn-audit", r"yarn\s+audit"),
         // pip list/show are safe
-        safe_pattern!("pip-list", r"pip\s+(?:list|show|freeze)\b"),
+        // (not when its output feeds another command, e.g. `pip uninstall`)
+        safe_pattern!("pip-list", r"pip3?\s+(?:list|show|freeze)\b[^|;&)\x60]*$"),
         // poetry show/info are safe
         safe_pattern!("poetry-show", r"poetry\s+show\b"),
         safe_pattern!("poetry-env-list", r"poetry\s+env\s+list\b"),
@@ -84,6 +90,20 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
             r"npm\s+unpublish\b",
             "npm unpublish removes a published package. This can break dependent projects."
         ),
+        // pip uninstall of everything pip freeze lists
+        destructive_pattern!(
+            "pip-uninstall-all",
+            r"(?:\bpip3?\s+(?:freeze|list)\b[^;&]*\|\s*(?:[^;&|]+\|\s*)*xargs\s+(?:-\S+\s+)*(?:python3?\s+-m\s+)?pip3?\s+uninstall\b|\bpip3?\s+uninstall\b[^;&|]*(?:\$\(|`|<\()\s*pip3?\s+(?:freeze|list)\b|\bfor\s+\w+\s+in\s+(?:\$\(|`)\s*pip3?\s+(?:freeze|list)\b[^;]*;\s*do\s+(?:python3?\s+-m\s+)?pip3?\s+uninstall\b)",
+            "Uninstalling every package pip freeze lists empties the Python environment.",
+            High,
+            "Feeding pip freeze into pip uninstall removes every installed package, \
+             including pip's own dependencies and, outside a virtualenv, packages the \
+             system's Python tools need.\n\n\
+             Recreate the virtualenv instead:\n  \
+             rm -rf .venv && python -m venv .venv\n\n\
+             Save the current set first:\n  \
+             pip freeze > requirements.lock"
+        ),
         // pip uninstall
         destructive_pattern!(
             "pip-uninstall",
@@ -102,6 +122,33 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
             r"pip\s+install\s+.*--(?:system|target\s*/usr)",
             "pip install to system directories requires careful review."
         ),
+        // apt remove/purge of packages the system cannot run without
+        destructive_pattern!(
+            "apt-remove-essential",
+            r"\bapt(?:-get)?\s+(?:-\S+\s+)*(?:remove|purge|autoremove)\b[^;&|]*?\s(?:libc6|libc-bin|coreutils|bash|dash|systemd|systemd-sysv|init|sudo|apt|dpkg|login|passwd|util-linux|base-files|base-passwd|openssh-server|python3|grub[\w.-]*|linux-image[\w.-]*)(?:[:=*]\S*)?(?:\s|$|[;&|)])",
+            "Removing an essential package (libc6, systemd, bash, dpkg, ...) can leave the system unbootable.",
+            Critical,
+            "Essential packages are what the system boots, logs in, and installs \
+             packages with. Removing one with apt also removes everything that depends \
+             on it, which for libc6, systemd, or python3 is most of the system; removing \
+             dpkg or apt leaves no way to put them back.\n\n\
+             Preview what would be removed:\n  \
+             apt-get remove --simulate <package>"
+        ),
+        // broad non-interactive autoremove --purge
+        destructive_pattern!(
+            "apt-autoremove-purge",
+            r"\bapt(?:-get)?\s+(?=(?:[^;&|]*\s)?(?:-[A-Za-z]*y[A-Za-z]*|--yes|--assume-yes)(?:\s|$|[;&|)]))(?=[^;&|]*(?:--purge\b|\bautopurge\b))[^;&|]*\bauto(?:remove|purge)\b",
+            "apt autoremove --purge -y removes every package apt considers unused, with their config, without asking.",
+            High,
+            "autoremove removes every automatically installed package nothing depends on \
+             any more. That set often includes packages installed by hand but marked \
+             auto, old kernels still in use, or tools other users need; --purge also \
+             deletes their configuration and -y skips the list you would otherwise \
+             review.\n\n\
+             Review the list first:\n  \
+             apt-get autoremove --purge --simulate"
+        ),
         // apt remove/purge
         destructive_pattern!(
             "apt-remove",
@@ -114,6 +161,31 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
             r"(?:yum|dnf)\s+(?:remove|erase|autoremove)\b",
             "yum/dnf remove removes packages. Verify no critical packages are affected."
         ),
+        // pacman -R with a glob removes every matching package
+        destructive_pattern!(
+            "pacman-remove-glob",
+            r#"\bpacman\s+(?:-\S+\s+)*-R[A-Za-z]*\s+(?:[^;&|]*\s)?["']?[^\s;&|'"]*[*?\[]"#,
+            "pacman -R with a glob removes every installed package the pattern matches.",
+            High,
+            "pacman does not expand globs itself; the shell matches them against files \
+             in the current directory, or pacman receives the raw pattern from a \
+             wrapper. Either way the set removed is not the one written, and with -ns \
+             the dependencies and config files go too.\n\n\
+             List the matching packages first:\n  \
+             pacman -Qq | grep '<pattern>'"
+        ),
+        // npm uninstall -g of the agent's own tooling
+        destructive_pattern!(
+            "npm-uninstall-global-tooling",
+            r"\bnpm\s+(?:uninstall|un|remove|rm|r|unlink)\b(?=[^;&|]*\s(?:-g|--global|--location[=\s]global)(?:\s|$|[;&|)]))[^;&|]*\s(?:@anthropic-ai/claude-code|@openai/codex|@google/gemini-cli|@github/copilot|npm|corepack|pnpm|yarn|npx)(?:@\S*)?(?:\s|$|[;&|)])",
+            "npm uninstall -g of npm, corepack, or an agent CLI removes tooling this session depends on.",
+            High,
+            "The agent CLI, npm, and corepack are installed globally and this session \
+             runs on them. Uninstalling one globally ends the session or leaves no way \
+             to install packages again.\n\n\
+             To switch versions instead:\n  \
+             npm install -g <package>@<version>"
+        ),
         // cargo publish
         destructive_pattern!(
             "cargo-publish",
@@ -132,6 +204,19 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
             r"gem\s+push\b",
             "gem push releases a gem to rubygems.org. Verify before publishing."
         ),
+        // brew uninstall --force of formulae most others depend on
+        destructive_pattern!(
+            "brew-uninstall-core",
+            r"\bbrew\s+(?:uninstall|remove|rm)\b(?=[^;&|]*\s(?:--force|-f|--ignore-dependencies)(?:\s|$|[;&|)]))[^;&|]*\s(?:openssl(?:@[\d.]+)?|python(?:@[\d.]+)?|git|curl|ca-certificates|readline|sqlite|xz|zlib|gettext|ncurses|libffi|pkgconf|pkg-config|gnupg|node|bash|coreutils)(?:\s|$|[;&|)])",
+            "brew uninstall --force of a core formula (openssl, python, git, ...) breaks every formula that links against it.",
+            High,
+            "--force removes all installed versions and --ignore-dependencies skips the \
+             check that other formulae still need it. Formulae such as openssl, python, \
+             readline, and ca-certificates are linked by most of the rest, which stop \
+             working until it is reinstalled.\n\n\
+             See what depends on it first:\n  \
+             brew uses --installed <formula>"
+        ),
         // brew uninstall
         destructive_pattern!(
             "brew-uninstall",
@@ -172,7 +257,10 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::packs::test_helpers::assert_blocks;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::{
+        assert_allows, assert_blocks, assert_blocks_with_pattern, assert_blocks_with_severity,
+    };
 
     #[test]
     fn brew_uninstall_is_reachable_via_keywords() {
@@ -200,6 +288,55 @@ mod tests {
         assert_blocks(&pack, "pip3 uninstall requests", "pip uninstall");
     }
 
+    #[test]
+    fn system_destruction_patterns() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "sudo apt-get remove --purge -y libc6",
+            "apt-remove-essential",
+        );
+        assert_blocks_with_severity(&pack, "apt purge systemd", Severity::Critical);
+        assert_blocks_with_pattern(
+            &pack,
+            "apt-get autoremove --purge -y",
+            "apt-autoremove-purge",
+        );
+        assert_blocks_with_pattern(&pack, "apt -y autopurge", "apt-autoremove-purge");
+        assert_blocks_with_pattern(&pack, "pacman -Rns 'python-*'", "pacman-remove-glob");
+        assert_blocks_with_pattern(
+            &pack,
+            "brew uninstall --force openssl@3",
+            "brew-uninstall-core",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "pip freeze | xargs pip uninstall -y",
+            "pip-uninstall-all",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "pip uninstall -y -r <(pip freeze)",
+            "pip-uninstall-all",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "npm uninstall -g @anthropic-ai/claude-code",
+            "npm-uninstall-global-tooling",
+        );
+        assert_blocks_with_pattern(&pack, "apt-get remove --purge -y nginx", "apt-remove");
+        assert_blocks_with_pattern(&pack, "brew uninstall --force wget", "brew-uninstall");
+        assert_blocks_with_pattern(&pack, "apt-get remove bash-completion -y", "apt-remove");
+        assert_allows(&pack, "pacman -Rns $(pacman -Qdtq)");
+        assert_allows(&pack, "pip freeze > requirements.txt");
+        assert_blocks_with_pattern(
+            &pack,
+            "npm ci && npm rm --global corepack",
+            "npm-uninstall-global-tooling",
+        );
+        assert_allows(&pack, "npm uninstall lodash");
+    }
+
     #[test]
     fn keyword_absent_skips_pack() {
         let pack = create_pack();
@@ -23,6 +23,7 @@ pub fn create_pack() -> Pack {
         keywords: &["gh"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -10,9 +10,133 @@
 //!
 //! The lazy variant avoids regex compilation during pack registry initialization,
 //! improving startup latency for the common allow-path case.
+//!
+//! # ReDoS protection
+//!
+//! User-supplied patterns (overrides, allowlists, external packs) go through
+//! [`check_pattern_complexity`] at validation time, and every pattern is
+//! compiled with size limits ([`REGEX_SIZE_LIMIT`]) and, on the backtracking
+//! engine, a step limit ([`BACKTRACK_LIMIT`]). A backtracking match that hits
+//! the step limit is recorded (see [`take_slow_patterns`]) and resolved by the
+//! pattern's [`PatternSource`]: a user pattern counts as "no match" and is
+//! skipped for the rest of the process instead of stalling the hook, while a
+//! built-in destructive pattern counts as a match, so the command is denied.
 
 use std::borrow::Cow;
-use std::sync::OnceLock;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Maximum compiled program size for a single pattern (bytes).
+pub const REGEX_SIZE_LIMIT: usize = 2 * (1 << 20);
+
+/// Maximum lazy DFA cache size for a single pattern (bytes).
+pub const REGEX_DFA_SIZE_LIMIT: usize = 2 * (1 << 20);
+
+/// Backtracking steps a `fancy_regex` match may take before it gives up.
+pub const BACKTRACK_LIMIT: usize = 1_000_000;
+
+/// Where a pattern comes from, which decides what a match that cannot finish
+/// within [`BACKTRACK_LIMIT`] means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternSource {
+    /// Built-in pattern that is not destructive (safe patterns, helpers): an
+    /// unfinished match does not match.
+    BuiltIn,
+    /// Built-in destructive pattern: an unfinished match counts as a match.
+    BuiltInDestructive,
+    /// User-supplied pattern (override, allowlist, external pack): an
+    /// unfinished match does not match, and the pattern is skipped for the
+    /// rest of the process.
+    User,
+}
+
+/// A pattern whose match hit [`BACKTRACK_LIMIT`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowPattern {
+    /// The offending pattern.
+    pub pattern: String,
+    /// How long the match ran before giving up.
+    pub elapsed: Duration,
+    /// Where the pattern came from, which decided the outcome.
+    pub source: PatternSource,
+}
+
+impl std::fmt::Display for SlowPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = match self.source {
+            PatternSource::BuiltIn => "was treated as no match",
+            PatternSource::BuiltInDestructive => "was treated as a match",
+            PatternSource::User => "was skipped",
+        };
+        write!(
+            f,
+            "pattern `{}` hit the backtracking limit after {}ms and {outcome}",
+            self.pattern,
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+/// Set once any pattern is recorded, so the common path never takes the lock.
+static ANY_RECORDED: AtomicBool = AtomicBool::new(false);
+
+/// Recorded patterns, and those not yet handed to [`take_slow_patterns`].
+static SLOW_PATTERNS: Mutex<(BTreeSet<String>, Vec<SlowPattern>)> =
+    Mutex::new((BTreeSet::new(), Vec::new()));
+
+/// Whether a user pattern was skipped after hitting the backtracking limit.
+fn is_skipped(pattern: &str, source: PatternSource) -> bool {
+    source == PatternSource::User
+        && ANY_RECORDED.load(Ordering::Relaxed)
+        && SLOW_PATTERNS
+            .lock()
+            .is_ok_and(|guard| guard.0.contains(pattern))
+}
+
+/// Record a match that hit the backtracking limit (once per pattern) and
+/// return whether it counts as a match.
+fn record_unfinished_match(pattern: &str, source: PatternSource, started: Instant) -> bool {
+    let elapsed = started.elapsed();
+    if let Ok(mut guard) = SLOW_PATTERNS.lock() {
+        if guard.0.insert(pattern.to_string()) {
+            guard.1.push(SlowPattern {
+                pattern: pattern.to_string(),
+                elapsed,
+                source,
+            });
+        }
+        ANY_RECORDED.store(true, Ordering::Relaxed);
+    }
+    source == PatternSource::BuiltInDestructive
+}
+
+/// Patterns recorded since the last call (each reported once per process).
+#[must_use]
+pub fn take_slow_patterns() -> Vec<SlowPattern> {
+    SLOW_PATTERNS
+        .lock()
+        .map(|mut guard| std::mem::take(&mut guard.1))
+        .unwrap_or_default()
+}
+
+fn build_linear(pattern: &str) -> Result<regex::Regex, String> {
+    regex::RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("regex compile error: {e}"))
+}
+
+fn build_backtracking(pattern: &str) -> Result<fancy_regex::Regex, String> {
+    fancy_regex::RegexBuilder::new(pattern)
+        .backtrack_limit(BACKTRACK_LIMIT)
+        .delegate_size_limit(REGEX_SIZE_LIMIT)
+        .delegate_dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("fancy_regex compile error: {e}"))
+}
 
 /// A compiled regex that auto-selects between linear-time and backtracking engines.
 ///
@@ -37,8 +161,9 @@ use std::sync::OnceLock;
 pub enum CompiledRegex {
     /// Linear-time regex (O(n) guaranteed, no backtracking).
     Linear(regex::Regex),
-    /// Backtracking regex (supports lookahead/lookbehind).
-    Backtracking(fancy_regex::Regex),
+    /// Backtracking regex (supports lookahead/lookbehind), with the source
+    /// that decides what a match hitting [`BACKTRACK_LIMIT`] means.
+    Backtracking(fancy_regex::Regex, PatternSource),
 }
 
 impl CompiledRegex {
@@ -53,14 +178,34 @@ impl CompiledRegex {
     /// # Errors
     /// Returns an error if the pattern fails to compile.
     pub fn new(pattern: &str) -> Result<Self, String> {
+        Self::with_source(pattern, PatternSource::BuiltIn)
+    }
+
+    /// Compile a built-in destructive pattern, like [`CompiledRegex::new`]
+    /// but a match that cannot finish counts as a match.
+    ///
+    /// # Errors
+    /// Returns an error if the pattern fails to compile.
+    pub fn new_destructive(pattern: &str) -> Result<Self, String> {
+        Self::with_source(pattern, PatternSource::BuiltInDestructive)
+    }
+
+    /// Compile a user-supplied pattern: [`check_pattern_complexity`] first,
+    /// then [`CompiledRegex::new`].
+    ///
+    /// # Errors
+    /// Returns an error if the pattern risks catastrophic backtracking or
+    /// fails to compile.
+    pub fn new_user(pattern: &str) -> Result<Self, String> {
+        check_pattern_complexity(pattern)?;
+        Self::with_source(pattern, PatternSource::User)
+    }
+
+    fn with_source(pattern: &str, source: PatternSource) -> Result<Self, String> {
         if needs_backtracking_engine(pattern) {
-            fancy_regex::Regex::new(pattern)
-                .map(Self::Backtracking)
-                .map_err(|e| format!("fancy_regex compile error: {e}"))
+            build_backtracking(pattern).map(|re| Self::Backtracking(re, source))
         } else {
-            regex::Regex::new(pattern)
-                .map(Self::Linear)
-                .map_err(|e| format!("regex compile error: {e}"))
+            build_linear(pattern).map(Self::Linear)
         }
     }
 
@@ -70,9 +215,7 @@ impl CompiledRegex {
     /// Returns an error if the pattern uses features not supported by the
     /// linear-time engine (lookahead, lookbehind, backreferences).
     pub fn new_linear(pattern: &str) -> Result<Self, String> {
-        regex::Regex::new(pattern)
-            .map(Self::Linear)
-            .map_err(|e| format!("regex compile error: {e}"))
+        build_linear(pattern).map(Self::Linear)
     }
 
     /// Compile a pattern using the backtracking engine.
@@ -80,30 +223,48 @@ impl CompiledRegex {
     /// # Errors
     /// Returns an error if the pattern fails to compile.
     pub fn new_backtracking(pattern: &str) -> Result<Self, String> {
-        fancy_regex::Regex::new(pattern)
-            .map(Self::Backtracking)
-            .map_err(|e| format!("fancy_regex compile error: {e}"))
+        build_backtracking(pattern).map(|re| Self::Backtracking(re, PatternSource::BuiltIn))
     }
 
     /// Check if the pattern matches the text.
     ///
-    /// For backtracking engine, returns `false` on regex execution errors.
+    /// For backtracking engine, a match that cannot finish (see
+    /// [`PatternSource`]) returns `true` for built-in destructive patterns and
+    /// `false` otherwise; skipped user patterns return `false`.
     #[must_use]
     pub fn is_match(&self, text: &str) -> bool {
         match self {
             Self::Linear(re) => re.is_match(text),
-            Self::Backtracking(re) => re.is_match(text).unwrap_or(false),
+            Self::Backtracking(re, source) => {
+                if is_skipped(re.as_str(), *source) {
+                    return false;
+                }
+                let started = Instant::now();
+                re.is_match(text)
+                    .unwrap_or_else(|_| record_unfinished_match(re.as_str(), *source, started))
+            }
         }
     }
 
     /// Find the first match in the text.
     ///
-    /// Returns the start and end byte offsets of the match.
+    /// Returns the start and end byte offsets of the match. A built-in
+    /// destructive pattern that cannot finish matches the whole text.
     #[must_use]
     pub fn find(&self, text: &str) -> Option<(usize, usize)> {
         match self {
             Self::Linear(re) => re.find(text).map(|m| (m.start(), m.end())),
-            Self::Backtracking(re) => re.find(text).ok().flatten().map(|m| (m.start(), m.end())),
+            Self::Backtracking(re, source) => {
+                if is_skipped(re.as_str(), *source) {
+                    return None;
+                }
+                let started = Instant::now();
+                match re.find(text) {
+                    Ok(found) => found.map(|m| (m.start(), m.end())),
+                    Err(_) => record_unfinished_match(re.as_str(), *source, started)
+                        .then_some((0, text.len())),
+                }
+            }
         }
     }
 
@@ -112,28 +273,36 @@ impl CompiledRegex {
     pub fn as_str(&self) -> &str {
         match self {
             Self::Linear(re) => re.as_str(),
-            Self::Backtracking(re) => re.as_str(),
+            Self::Backtracking(re, _) => re.as_str(),
         }
     }
 
     /// Check if this regex uses the backtracking engine.
     #[must_use]
     pub const fn uses_backtracking(&self) -> bool {
-        matches!(self, Self::Backtracking(_))
+        matches!(self, Self::Backtracking(..))
     }
 
     /// Replace up to `limit` matches with the replacement string.
     ///
     /// Returns a `Cow::Borrowed` if no replacements were made.
-    /// For backtracking engine, falls back to original text on execution errors.
+    /// For backtracking engine, falls back to original text on execution errors
+    /// and for skipped user patterns.
     #[must_use]
     pub fn replacen<'t>(&self, text: &'t str, limit: usize, rep: &str) -> Cow<'t, str> {
         match self {
             Self::Linear(re) => re.replacen(text, limit, rep),
             // Use try_replacen to handle errors gracefully (returns Result)
-            Self::Backtracking(re) => re
-                .try_replacen(text, limit, rep)
-                .unwrap_or(Cow::Borrowed(text)),
+            Self::Backtracking(re, source) => {
+                if is_skipped(re.as_str(), *source) {
+                    return Cow::Borrowed(text);
+                }
+                let started = Instant::now();
+                re.try_replacen(text, limit, rep).unwrap_or_else(|_| {
+                    record_unfinished_match(re.as_str(), *source, started);
+                    Cow::Borrowed(text)
+                })
+            }
         }
     }
 }
@@ -190,6 +359,74 @@ pub fn needs_backtracking_engine(pattern: &str) -> bool {
     false
 }
 
+/// Reject patterns prone to catastrophic backtracking.
+///
+/// Flags an unbounded repetition nested inside another (`(a+)+`, `(\w*\s?)*`,
+/// `(?:.*,)+`): on the backtracking engine these explore exponentially many
+/// ways to split the input before failing. Patterns that compile to the
+/// linear-time engine cannot backtrack, so only patterns that need the
+/// backtracking engine (lookaround, backreferences) are checked.
+///
+/// # Errors
+/// Returns a description of the offending construct.
+pub fn check_pattern_complexity(pattern: &str) -> Result<(), String> {
+    if !needs_backtracking_engine(pattern) {
+        return Ok(());
+    }
+    let Ok(tree) = fancy_regex::Expr::parse_tree(pattern) else {
+        // Compile errors are reported by the caller's compile step.
+        return Ok(());
+    };
+    if has_nested_quantifier(&tree.expr, false) {
+        return Err(format!(
+            "pattern `{pattern}` nests an unbounded quantifier inside another (e.g. `(a+)+`), \
+             which can backtrack catastrophically; rewrite it without the nesting or drop \
+             the lookaround so it runs on the linear-time engine"
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `expr` contains an unbounded repeat, inside another unbounded
+/// repeat when `inside_repeat` is set.
+fn has_nested_quantifier(expr: &fancy_regex::Expr, inside_repeat: bool) -> bool {
+    use fancy_regex::Expr;
+
+    match expr {
+        Expr::Repeat { child, hi, .. } => {
+            let unbounded = *hi == usize::MAX;
+            if unbounded && inside_repeat && can_repeat(child) {
+                return true;
+            }
+            has_nested_quantifier(child, inside_repeat || unbounded)
+        }
+        Expr::Concat(children) | Expr::Alt(children) => children
+            .iter()
+            .any(|child| has_nested_quantifier(child, inside_repeat)),
+        Expr::Group(child) | Expr::LookAround(child, _) | Expr::AtomicGroup(child) => {
+            has_nested_quantifier(child, inside_repeat)
+        }
+        Expr::Conditional {
+            condition,
+            true_branch,
+            false_branch,
+        } => [condition, true_branch, false_branch]
+            .iter()
+            .any(|child| has_nested_quantifier(child, inside_repeat)),
+        _ => false,
+    }
+}
+
+/// Whether repeating `expr` can consume input (ignores `()*`-style no-ops).
+fn can_repeat(expr: &fancy_regex::Expr) -> bool {
+    !matches!(
+        expr,
+        fancy_regex::Expr::Empty
+            | fancy_regex::Expr::Assertion(_)
+            | fancy_regex::Expr::LookAround(..)
+    )
+}
+
 // ============================================================================
 // Lazy Regex Primitive
 // ============================================================================
@@ -226,6 +463,7 @@ pub fn needs_backtracking_engine(pattern: &str) -> bool {
 #[derive(Debug)]
 pub struct LazyCompiledRegex {
     pattern: PatternText,
+    source: PatternSource,
     compiled: OnceLock<Result<CompiledRegex, String>>,
 }
 
@@ -253,6 +491,18 @@ impl LazyCompiledRegex {
     pub const fn new(pattern: &'static str) -> Self {
         Self {
             pattern: PatternText::Static(pattern),
+            source: PatternSource::BuiltIn,
+            compiled: OnceLock::new(),
+        }
+    }
+
+    /// Create a lazy built-in destructive pattern (see
+    /// [`CompiledRegex::new_destructive`]).
+    #[must_use]
+    pub const fn new_destructive(pattern: &'static str) -> Self {
+        Self {
+            pattern: PatternText::Static(pattern),
+            source: PatternSource::BuiltInDestructive,
             compiled: OnceLock::new(),
         }
     }
@@ -263,6 +513,19 @@ impl LazyCompiledRegex {
     pub fn new_owned(pattern: String) -> Self {
         Self {
             pattern: PatternText::Owned(pattern),
+            source: PatternSource::BuiltIn,
+            compiled: OnceLock::new(),
+        }
+    }
+
+    /// Create a lazy user-supplied pattern (external packs), already checked
+    /// with [`check_pattern_complexity`] at load time.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new_user(pattern: String) -> Self {
+        Self {
+            pattern: PatternText::Owned(pattern),
+            source: PatternSource::User,
             compiled: OnceLock::new(),
         }
     }
@@ -272,7 +535,7 @@ impl LazyCompiledRegex {
     /// Returns `None` if compilation fails (fail-open).
     fn get_compiled(&self) -> Option<&CompiledRegex> {
         self.compiled
-            .get_or_init(|| CompiledRegex::new(self.pattern.as_str()))
+            .get_or_init(|| CompiledRegex::with_source(self.pattern.as_str(), self.source))
             .as_ref()
             .ok()
     }
@@ -659,6 +922,88 @@ mod tests {
         assert!(!PATTERN.is_match("hello"));
     }
 
+    #[test]
+    fn test_check_pattern_complexity_rejects_nested_quantifiers() {
+        for pattern in [r"(?=x)(a+)+b", r"(\w+\s?)*\1", r"(?<!-)(?:(?:ab)*c)+$"] {
+            let err = check_pattern_complexity(pattern).unwrap_err();
+            assert!(err.contains("nests an unbounded quantifier"), "{err}");
+            assert!(CompiledRegex::new_user(pattern).is_err());
+        }
+    }
+
+    #[test]
+    fn test_check_pattern_complexity_allows_safe_patterns() {
+        for pattern in [
+            // Linear engine: nesting is harmless
+            r"git\s+(?:\S+\s+)*push",
+            r"(a+)+$",
+            // Backtracking engine, but no nested unbounded repeat
+            r"rm\s+-rf(?!\s+--dry-run)",
+            r"(?=x)(a{1,3})+b",
+            r"(\w+)\s+\1",
+        ] {
+            assert!(check_pattern_complexity(pattern).is_ok(), "{pattern}");
+            assert!(CompiledRegex::new_user(pattern).is_ok(), "{pattern}");
+        }
+    }
+
+    /// Slow patterns recorded for `pattern` since the last call.
+    fn take_slow(pattern: &str) -> Vec<SlowPattern> {
+        take_slow_patterns()
+            .into_iter()
+            .filter(|slow| slow.pattern == pattern)
+            .collect()
+    }
+
+    #[test]
+    fn test_backtrack_limit_skips_and_reports_user_pattern() {
+        // Compiled lazily without the validation-time check, as an external
+        // pack pattern is after loading.
+        let pattern = r"^(\w+\s?)*\1!$";
+        let re = LazyCompiledRegex::new_user(pattern.to_string());
+
+        let input = "ab ".repeat(40);
+        assert!(!re.is_match(&input));
+        let slow = take_slow(pattern);
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].source, PatternSource::User);
+        assert!(slow[0].to_string().contains("was skipped"));
+
+        // Skipped from now on, even for input it would match, and not re-reported.
+        assert!(!re.is_match("abab!"));
+        assert_eq!(re.find("abab!"), None);
+        assert!(take_slow(pattern).is_empty());
+    }
+
+    #[test]
+    fn test_backtrack_limit_never_skips_builtin_destructive_pattern() {
+        let pattern = r"^(\w+\s?)*\1;$";
+        let re = LazyCompiledRegex::new_destructive(pattern);
+        let input = "ab ".repeat(40);
+
+        // An unfinished match denies rather than letting the command through.
+        assert!(re.is_match(&input));
+        assert_eq!(re.find(&input), Some((0, input.len())));
+        let slow = take_slow(pattern);
+        assert_eq!(slow.len(), 1);
+        assert!(slow[0].to_string().contains("was treated as a match"));
+
+        // Still evaluated afterwards.
+        assert!(re.is_match("abab;"));
+        assert!(!re.is_match("abcd;"));
+    }
+
+    #[test]
+    fn test_backtrack_limit_builtin_pattern_does_not_match() {
+        let pattern = r"^(\w+\s?)*\1#$";
+        let re = CompiledRegex::new(pattern).unwrap();
+        let input = "ab ".repeat(40);
+
+        assert!(!re.is_match(&input));
+        assert_eq!(take_slow(pattern).len(), 1);
+        assert!(re.is_match("abab#"));
+    }
+
     #[test]
     fn test_lazy_regex_empty_pattern() {
         let lazy = LazyCompiledRegex::new("");
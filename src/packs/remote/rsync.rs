@@ -2,6 +2,8 @@
 //!
 //! Covers destructive CLI operations:
 //! - delete flags (including --del and --delete-* variants)
+//! - delete flags whose destination is `/`, a system directory, or the root
+//!   of `/home` (local or `host:` remote), which are Critical
 
 use crate::packs::{DestructivePattern, Pack, SafePattern};
 use crate::{destructive_pattern, safe_pattern};
@@ -16,6 +18,7 @@ pub fn create_pack() -> Pack {
         keywords: &["rsync"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -32,6 +35,20 @@ fn create_safe_patterns() -> Vec<SafePattern> {
 
 fn create_destructive_patterns() -> Vec<DestructivePattern> {
     vec![
+        destructive_pattern!(
+            "rsync-delete-system",
+            r#"rsync\b[^;&|]*\s--del(?:ete(?:-[a-z-]+)?)?\b[^;&|]*\s["']?(?:[\w.@-]+:)?/(?:(?:bin|boot|dev|etc|lib|lib64|opt|root|sbin|srv|usr|var|Library|System)\b[^\s;&|]*|(?:home|Users)/?)?["']?\s*(?:$|[;&|)])"#,
+            "rsync --delete into / or a system directory deletes system files missing from the source.",
+            Critical,
+            "With --delete, rsync removes every file in the destination that is not in \
+             the source. When the destination is /, /etc, /usr, or /home, a source that \
+             is incomplete, empty, or mistyped deletes system files or every user's home \
+             directory as a side effect of the sync.\n\n\
+             Preview the deletions first:\n  \
+             rsync --dry-run --delete <src>/ <dest>/\n\n\
+             Sync into a staging directory instead:\n  \
+             rsync -a --delete <src>/ /tmp/staging/"
+        ),
         destructive_pattern!(
             "rsync-delete",
             r"rsync\b.*\s--delete(?:-[a-z-]+)?\b",
@@ -92,6 +109,28 @@ mod tests {
     fn blocks_destructive_commands() {
         let pack = create_pack();
         assert_blocks_with_pattern(&pack, "rsync --delete src/ dest/", "rsync-delete");
+        assert_blocks_with_pattern(
+            &pack,
+            "rsync -a --delete /mnt/backup/ /",
+            "rsync-delete-system",
+        );
+        assert_blocks_with_pattern(&pack, "rsync -av --del conf/ /etc/", "rsync-delete-system");
+        assert_blocks_with_pattern(
+            &pack,
+            "rsync -az --delete-after ./usr/ root@host:/usr/local",
+            "rsync-delete-system",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "rsync -a --delete empty/ /home/",
+            "rsync-delete-system",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "rsync -a --delete src/ /home/alice/src/",
+            "rsync-delete",
+        );
+        assert_blocks_with_pattern(&pack, "rsync -a --delete /etc/ ./etc-copy/", "rsync-delete");
         assert_blocks_with_pattern(&pack, "rsync --delete-before src/ dest/", "rsync-delete");
         assert_blocks_with_pattern(&pack, "rsync --delete-excluded src/ dest/", "rsync-delete");
         assert_blocks_with_pattern(&pack, "rsync --del src/ dest/", "rsync-del-short");
@@ -0,0 +1,813 @@
+//! Pack scaffolding for `dcg pack new`.
+//!
+//! A [`PackScaffold`] describes a pack to be created: its ID, keywords, and
+//! patterns with example commands. From it we render either a built-in Rust
+//! pack module (with unit tests) or an external YAML pack, plus a fixture
+//! corpus in the `[[case]]` format written by `dcg dev generate-fixtures`.
+//!
+//! Rendering is pure; the CLI is responsible for prompting and writing files.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use super::external::ID_PATTERN;
+use super::regex_engine::CompiledRegex;
+use super::{REGISTRY, Severity};
+
+/// Maximum width of a wrapped string literal line in generated Rust code.
+const WRAP_WIDTH: usize = 76;
+
+/// A safer alternative shown when a destructive pattern matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldSuggestion {
+    /// The safer command.
+    pub command: String,
+    /// Why the command is safer.
+    pub description: String,
+}
+
+/// A destructive pattern to scaffold.
+#[derive(Debug, Clone)]
+pub struct ScaffoldDestructivePattern {
+    /// Pattern name, unique within the pack (kebab-case).
+    pub name: String,
+    /// Regex (fancy-regex syntax).
+    pub regex: String,
+    /// Example command that must match `regex`; used for tests and fixtures.
+    pub example: String,
+    /// Severity of a match.
+    pub severity: Severity,
+    /// Short reason shown on denial.
+    pub reason: String,
+    /// Optional longer explanation.
+    pub explanation: Option<String>,
+    /// Safer alternatives.
+    pub suggestions: Vec<ScaffoldSuggestion>,
+}
+
+/// A safe pattern to scaffold.
+#[derive(Debug, Clone)]
+pub struct ScaffoldSafePattern {
+    /// Pattern name, unique within the pack (kebab-case).
+    pub name: String,
+    /// Regex (fancy-regex syntax).
+    pub regex: String,
+    /// Example command that must match `regex`; used for tests and fixtures.
+    pub example: String,
+}
+
+/// Description of a pack to generate.
+#[derive(Debug, Clone)]
+pub struct PackScaffold {
+    /// Pack ID in `category.name` form.
+    pub id: String,
+    /// Human-readable pack name.
+    pub name: String,
+    /// What the pack protects against.
+    pub description: String,
+    /// Quick-reject keywords.
+    pub keywords: Vec<String>,
+    /// Destructive patterns (at least one).
+    pub destructive_patterns: Vec<ScaffoldDestructivePattern>,
+    /// Safe patterns.
+    pub safe_patterns: Vec<ScaffoldSafePattern>,
+}
+
+impl PackScaffold {
+    /// Build a scaffold with a placeholder `<tool> delete` rule for `id`.
+    ///
+    /// The tool name is taken from the part of the ID after the dot, so
+    /// `mycompany.deploy` gets a `deploy delete` rule and a `deploy list`
+    /// safe pattern. The result is meant to be edited by hand.
+    #[must_use]
+    pub fn placeholder(id: &str) -> Self {
+        let tool = id.split_once('.').map_or(id, |(_, name)| name);
+        let name = default_display_name(id);
+        Self {
+            id: id.to_string(),
+            name: name.clone(),
+            description: format!("Protects against destructive {name} operations."),
+            keywords: vec![tool.to_string()],
+            destructive_patterns: vec![ScaffoldDestructivePattern {
+                name: format!("{}-delete", tool.replace('_', "-")),
+                regex: format!(r"\b{tool}\s+delete\b"),
+                example: format!("{tool} delete example"),
+                severity: Severity::High,
+                reason: format!("{tool} delete permanently removes a resource."),
+                explanation: Some(format!(
+                    "Describe what {tool} delete removes and why it cannot be undone."
+                )),
+                suggestions: vec![ScaffoldSuggestion {
+                    command: format!("{tool} list"),
+                    description: "Review what exists before deleting".to_string(),
+                }],
+            }],
+            safe_patterns: vec![ScaffoldSafePattern {
+                name: format!("{}-list", tool.replace('_', "-")),
+                regex: format!(r"\b{tool}\s+list\b"),
+                example: format!("{tool} list"),
+            }],
+        }
+    }
+
+    /// The category part of the pack ID (before the dot).
+    #[must_use]
+    pub fn category(&self) -> &str {
+        self.id
+            .split_once('.')
+            .map_or(&self.id, |(category, _)| category)
+    }
+
+    /// The module part of the pack ID (after the dot).
+    #[must_use]
+    pub fn module_name(&self) -> &str {
+        self.id.split_once('.').map_or(&self.id, |(_, name)| name)
+    }
+
+    /// Check the scaffold before anything is written.
+    ///
+    /// Verifies the ID format, that the ID is not already a built-in pack,
+    /// that every regex compiles, and that every example command contains a
+    /// keyword and is classified the way its pattern intends.
+    ///
+    /// # Errors
+    ///
+    /// Returns every problem found, one message per problem.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        let id_regex = regex::Regex::new(ID_PATTERN).expect("ID regex should compile");
+        if !id_regex.is_match(&self.id) {
+            errors.push(format!(
+                "Invalid pack ID '{}': must match {ID_PATTERN} (e.g., 'mycompany.deploy')",
+                self.id
+            ));
+        }
+        if REGISTRY.get(&self.id).is_some() {
+            errors.push(format!("Pack ID '{}' is already a built-in pack", self.id));
+        }
+        if self.name.trim().is_empty() {
+            errors.push("Pack name must not be empty".to_string());
+        }
+        if self.description.trim().is_empty() {
+            errors.push("Pack description must not be empty".to_string());
+        }
+        if self.keywords.iter().all(|k| k.trim().is_empty()) {
+            errors.push("Pack must have at least one keyword".to_string());
+        }
+        if self.destructive_patterns.is_empty() {
+            errors.push("Pack must have at least one destructive pattern".to_string());
+        }
+
+        let mut seen = HashSet::new();
+        let names = self
+            .destructive_patterns
+            .iter()
+            .map(|p| p.name.as_str())
+            .chain(self.safe_patterns.iter().map(|p| p.name.as_str()));
+        for name in names {
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                errors.push(format!(
+                    "Invalid pattern name '{name}': use kebab-case without spaces"
+                ));
+            }
+            if !seen.insert(name) {
+                errors.push(format!("Duplicate pattern name: {name}"));
+            }
+        }
+
+        let safe_regexes: Vec<(&str, Option<CompiledRegex>)> = self
+            .safe_patterns
+            .iter()
+            .map(|p| {
+                let compiled = compile_checked(&p.name, &p.regex, &mut errors);
+                (p.name.as_str(), compiled)
+            })
+            .collect();
+
+        for pattern in &self.destructive_patterns {
+            if pattern.reason.trim().is_empty() {
+                errors.push(format!("Pattern '{}' has an empty reason", pattern.name));
+            }
+            self.check_example_keyword(&pattern.name, &pattern.example, &mut errors);
+            let Some(regex) = compile_checked(&pattern.name, &pattern.regex, &mut errors) else {
+                continue;
+            };
+            if !regex.is_match(&pattern.example) {
+                errors.push(format!(
+                    "Example for '{}' does not match its regex: {}",
+                    pattern.name, pattern.example
+                ));
+            }
+            for (safe_name, safe_regex) in &safe_regexes {
+                if safe_regex
+                    .as_ref()
+                    .is_some_and(|safe| safe.is_match(&pattern.example))
+                {
+                    errors.push(format!(
+                        "Example for '{}' is allowed by safe pattern '{safe_name}': {}",
+                        pattern.name, pattern.example
+                    ));
+                }
+            }
+        }
+
+        for (pattern, (_, regex)) in self.safe_patterns.iter().zip(&safe_regexes) {
+            self.check_example_keyword(&pattern.name, &pattern.example, &mut errors);
+            if regex
+                .as_ref()
+                .is_some_and(|regex| !regex.is_match(&pattern.example))
+            {
+                errors.push(format!(
+                    "Example for '{}' does not match its regex: {}",
+                    pattern.name, pattern.example
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn check_example_keyword(&self, name: &str, example: &str, errors: &mut Vec<String>) {
+        if !self
+            .keywords
+            .iter()
+            .any(|k| !k.is_empty() && example.contains(k.as_str()))
+        {
+            errors.push(format!(
+                "Example for '{name}' contains none of the pack keywords, so it would never be evaluated: {example}"
+            ));
+        }
+    }
+
+    /// Render a built-in pack module (`src/packs/<category>/<name>.rs`).
+    #[must_use]
+    pub fn render_module(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "//! {} pack - protections for destructive `{}` operations.",
+            self.name,
+            self.keywords
+                .first()
+                .map_or_else(|| self.module_name(), String::as_str)
+        );
+        out.push_str("//!\n//! Covers destructive operations:\n");
+        for pattern in &self.destructive_patterns {
+            let _ = writeln!(out, "//! - {}", pattern.reason.trim_end_matches('.'));
+        }
+        out.push('\n');
+        out.push_str("use crate::packs::{DestructivePattern, Pack, SafePattern};\n");
+        out.push_str("use crate::{destructive_pattern, safe_pattern};\n\n");
+
+        let _ = writeln!(out, "/// Create the {} pack.", self.name);
+        out.push_str("#[must_use]\npub fn create_pack() -> Pack {\n    Pack {\n");
+        let _ = writeln!(out, "        id: \"{}\".to_string(),", self.id);
+        let _ = writeln!(out, "        name: \"{}\",", escape_str(&self.name));
+        let _ = writeln!(
+            out,
+            "        description: {},",
+            multiline_literal(&self.description, "                     ")
+        );
+        let keywords: Vec<String> = self
+            .keywords
+            .iter()
+            .map(|k| format!("\"{}\"", escape_str(k)))
+            .collect();
+        let _ = writeln!(out, "        keywords: &[{}],", keywords.join(", "));
+        out.push_str(
+            "        safe_patterns: create_safe_patterns(),\n        \
+             destructive_patterns: create_destructive_patterns(),\n        \
+             match_raw: false,\n        \
+             keyword_matcher: None,\n        \
+             safe_regex_set: None,\n        \
+             safe_regex_set_is_complete: false,\n    }\n}\n\n",
+        );
+
+        out.push_str("fn create_safe_patterns() -> Vec<SafePattern> {\n    vec![\n");
+        for pattern in &self.safe_patterns {
+            let _ = writeln!(
+                out,
+                "        safe_pattern!(\"{}\", {}),",
+                escape_str(&pattern.name),
+                raw_literal(&pattern.regex)
+            );
+        }
+        out.push_str("    ]\n}\n\n");
+
+        out.push_str("fn create_destructive_patterns() -> Vec<DestructivePattern> {\n    vec![\n");
+        for pattern in &self.destructive_patterns {
+            out.push_str("        destructive_pattern!(\n");
+            let _ = writeln!(out, "            \"{}\",", escape_str(&pattern.name));
+            let _ = writeln!(out, "            {},", raw_literal(&pattern.regex));
+            let _ = writeln!(out, "            \"{}\",", escape_str(&pattern.reason));
+            let _ = write!(out, "            {:?}", pattern.severity);
+            let explanation = explanation_text(pattern);
+            if explanation.is_empty() {
+                out.push('\n');
+            } else {
+                let _ = writeln!(
+                    out,
+                    ",\n            {}",
+                    multiline_literal(&explanation, "            ")
+                );
+            }
+            out.push_str("        ),\n");
+        }
+        out.push_str("    ]\n}\n\n");
+
+        self.render_module_tests(&mut out);
+        out
+    }
+
+    fn render_module_tests(&self, out: &mut String) {
+        out.push_str("#[cfg(test)]\nmod tests {\n    use super::*;\n");
+        out.push_str("    use crate::packs::test_helpers::*;\n\n");
+        out.push_str("    #[test]\n    fn test_pack_creation() {\n");
+        out.push_str("        let pack = create_pack();\n");
+        let _ = writeln!(out, "        assert_eq!(pack.id, \"{}\");", self.id);
+        out.push_str("        validate_pack(&pack);\n    }\n");
+
+        if !self.safe_patterns.is_empty() {
+            out.push_str("\n    #[test]\n    fn allows_safe_commands() {\n");
+            out.push_str("        let pack = create_pack();\n");
+            for pattern in &self.safe_patterns {
+                let _ = writeln!(
+                    out,
+                    "        assert_safe_pattern_matches(&pack, \"{}\");",
+                    escape_str(&pattern.example)
+                );
+            }
+            out.push_str("    }\n");
+        }
+
+        out.push_str("\n    #[test]\n    fn blocks_destructive_commands() {\n");
+        out.push_str("        let pack = create_pack();\n");
+        for pattern in &self.destructive_patterns {
+            let _ = writeln!(
+                out,
+                "        assert_blocks_with_pattern(&pack, \"{}\", \"{}\");",
+                escape_str(&pattern.example),
+                escape_str(&pattern.name)
+            );
+        }
+        out.push_str("    }\n}\n");
+    }
+
+    /// Render an external pack YAML file (see `docs/pack.schema.yaml`).
+    #[must_use]
+    pub fn render_yaml(&self) -> String {
+        use serde_yaml::{Mapping, Value};
+
+        fn map(entries: Vec<(&str, Value)>) -> Value {
+            let mut mapping = Mapping::new();
+            for (key, value) in entries {
+                mapping.insert(Value::from(key), value);
+            }
+            Value::Mapping(mapping)
+        }
+
+        let destructive: Vec<Value> = self
+            .destructive_patterns
+            .iter()
+            .map(|p| {
+                let mut entries = vec![
+                    ("name", Value::from(p.name.as_str())),
+                    ("pattern", Value::from(p.regex.as_str())),
+                    ("severity", Value::from(p.severity.label())),
+                    ("description", Value::from(p.reason.as_str())),
+                ];
+                if let Some(explanation) = p.explanation.as_deref().filter(|e| !e.is_empty()) {
+                    entries.push(("explanation", Value::from(explanation)));
+                }
+                if !p.suggestions.is_empty() {
+                    let suggestions = p
+                        .suggestions
+                        .iter()
+                        .map(|s| {
+                            map(vec![
+                                ("command", Value::from(s.command.as_str())),
+                                ("description", Value::from(s.description.as_str())),
+                            ])
+                        })
+                        .collect();
+                    entries.push(("suggestions", Value::Sequence(suggestions)));
+                }
+                map(entries)
+            })
+            .collect();
+
+        let mut entries = vec![
+            (
+                "schema_version",
+                Value::from(super::external::CURRENT_SCHEMA_VERSION),
+            ),
+            ("id", Value::from(self.id.as_str())),
+            ("name", Value::from(self.name.as_str())),
+            ("version", Value::from("1.0.0")),
+            ("description", Value::from(self.description.as_str())),
+            (
+                "keywords",
+                Value::Sequence(
+                    self.keywords
+                        .iter()
+                        .map(|k| Value::from(k.as_str()))
+                        .collect(),
+                ),
+            ),
+            ("destructive_patterns", Value::Sequence(destructive)),
+        ];
+        if !self.safe_patterns.is_empty() {
+            let safe = self
+                .safe_patterns
+                .iter()
+                .map(|p| {
+                    map(vec![
+                        ("name", Value::from(p.name.as_str())),
+                        ("pattern", Value::from(p.regex.as_str())),
+                    ])
+                })
+                .collect();
+            entries.push(("safe_patterns", Value::Sequence(safe)));
+        }
+
+        let body = serde_yaml::to_string(&map(entries)).unwrap_or_default();
+        format!(
+            "# {} external pack, generated by `dcg pack new`.\n\
+             # Validate changes with: dcg pack validate <this file>\n{body}",
+            self.id
+        )
+    }
+
+    /// Render the destructive fixture corpus (`<id>_destructive.toml`).
+    #[must_use]
+    pub fn render_destructive_fixtures(&self) -> String {
+        let mut out = String::from("# Destructive pattern test fixtures\n");
+        let _ = write!(out, "# Generated for pack: {}\n\n", self.id);
+        for pattern in &self.destructive_patterns {
+            let _ = write!(
+                out,
+                "[[case]]\npattern = \"{}\"\ndescription = \"{}\"\ncommand = \"{}\"\nreason = \"{}\"\nexpected = \"deny\"\nrule_id = \"{}:{}\"\n\n",
+                escape_str(&pattern.name),
+                escape_str(&pattern.reason),
+                escape_str(&pattern.example),
+                escape_str(&pattern.reason),
+                self.id,
+                escape_str(&pattern.name)
+            );
+        }
+        out
+    }
+
+    /// Render the safe fixture corpus (`<id>_safe.toml`).
+    #[must_use]
+    pub fn render_safe_fixtures(&self) -> String {
+        let mut out = String::from("# Safe pattern test fixtures\n");
+        let _ = write!(out, "# Generated for pack: {}\n\n", self.id);
+        for pattern in &self.safe_patterns {
+            let _ = write!(
+                out,
+                "[[case]]\npattern = \"{}\"\ndescription = \"{}\"\ncommand = \"{}\"\nexpected = \"allow\"\n\n",
+                escape_str(&pattern.name),
+                escape_str(&pattern.name),
+                escape_str(&pattern.example)
+            );
+        }
+        out
+    }
+
+    /// Base file name for fixture files (`<category>_<name>`).
+    #[must_use]
+    pub fn fixture_stem(&self) -> String {
+        self.id.replace('.', "_")
+    }
+
+    /// Render the `PACK_ENTRIES` line that registers a built-in pack.
+    #[must_use]
+    pub fn render_pack_entry(&self) -> String {
+        let keywords: Vec<String> = self
+            .keywords
+            .iter()
+            .map(|k| format!("\"{}\"", escape_str(k)))
+            .collect();
+        format!(
+            "PackEntry::new(\"{}\", &[{}], {}::{}::create_pack),",
+            self.id,
+            keywords.join(", "),
+            self.category(),
+            self.module_name()
+        )
+    }
+}
+
+/// Render a new category `mod.rs` declaring a single pack module.
+#[must_use]
+pub fn render_category_mod(category: &str, module: &str) -> String {
+    format!(
+        "//! {} packs - protections for {category} tooling.\n\npub mod {module};\n",
+        capitalize(category)
+    )
+}
+
+/// Insert `pub mod <module>;` into a `mod.rs` source, keeping declarations sorted.
+///
+/// Only the first contiguous run of `pub mod` lines is considered, which is
+/// how category and top-level pack `mod.rs` files are laid out. Returns
+/// `None` if the module is already declared.
+#[must_use]
+pub fn insert_mod_declaration(source: &str, module: &str) -> Option<String> {
+    let declaration = format!("pub mod {module};");
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.iter().any(|line| line.trim() == declaration) {
+        return None;
+    }
+
+    let first = lines.iter().position(|line| line.starts_with("pub mod "));
+    let insert_at = first.map_or(lines.len(), |start| {
+        let mut index = start;
+        while index < lines.len()
+            && lines[index].starts_with("pub mod ")
+            && lines[index] < declaration.as_str()
+        {
+            index += 1;
+        }
+        index
+    });
+
+    let mut out: Vec<&str> = lines[..insert_at].to_vec();
+    if first.is_none() && out.last().is_some_and(|line| !line.is_empty()) {
+        out.push("");
+    }
+    out.push(&declaration);
+    out.extend_from_slice(&lines[insert_at..]);
+    let mut rendered = out.join("\n");
+    rendered.push('\n');
+    Some(rendered)
+}
+
+/// Derive a display name from a pack ID (`mycompany.deploy_tool` -> `Deploy Tool`).
+#[must_use]
+pub fn default_display_name(id: &str) -> String {
+    let module = id.split_once('.').map_or(id, |(_, name)| name);
+    module
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(capitalize)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+fn compile_checked(name: &str, regex: &str, errors: &mut Vec<String>) -> Option<CompiledRegex> {
+    match CompiledRegex::new(regex) {
+        Ok(compiled) => Some(compiled),
+        Err(e) => {
+            errors.push(format!("Invalid regex in pattern '{name}': {e}"));
+            None
+        }
+    }
+}
+
+/// Explanation text for a built-in pattern, with suggestions folded into a
+/// "Safer alternatives" list the way hand-written packs do it.
+fn explanation_text(pattern: &ScaffoldDestructivePattern) -> String {
+    let mut text = pattern
+        .explanation
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if !pattern.suggestions.is_empty() {
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str("Safer alternatives:");
+        for suggestion in &pattern.suggestions {
+            if suggestion.description.is_empty() {
+                let _ = write!(text, "\n- {}", suggestion.command);
+            } else {
+                let _ = write!(
+                    text,
+                    "\n- {}: {}",
+                    suggestion.command, suggestion.description
+                );
+            }
+        }
+    }
+    text
+}
+
+/// Escape a string for a Rust (or TOML basic) string literal.
+fn escape_str(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// Render a raw string literal with enough `#`s to hold `s`.
+fn raw_literal(s: &str) -> String {
+    let mut hashes = 0;
+    while s.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    let fence = "#".repeat(hashes);
+    format!("r{fence}\"{s}\"{fence}")
+}
+
+/// Render a string literal wrapped across lines with `\` continuations.
+///
+/// `indent` is the indentation of the line holding the opening quote;
+/// continuation lines are indented one column further so they line up
+/// after the quote.
+fn multiline_literal(text: &str, indent: &str) -> String {
+    let logical: Vec<&str> = text.split('\n').collect();
+    let mut physical: Vec<String> = Vec::new();
+
+    for (index, line) in logical.iter().enumerate() {
+        let mut current = String::new();
+        for word in line.split(' ').filter(|w| !w.is_empty()) {
+            let word = escape_str(word);
+            if !current.is_empty() && current.len() + word.len() + 1 > WRAP_WIDTH {
+                current.push(' ');
+                physical.push(std::mem::take(&mut current));
+            } else if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&word);
+        }
+        if !current.is_empty() {
+            physical.push(current);
+        }
+        if index + 1 < logical.len() {
+            match physical.last_mut() {
+                Some(last) => last.push_str("\\n"),
+                None => physical.push("\\n".to_string()),
+            }
+        }
+    }
+
+    let continuation = format!("\\\n{indent} ");
+    format!("\"{}\"", physical.join(&continuation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::external::parse_pack_string;
+
+    fn sample() -> PackScaffold {
+        PackScaffold {
+            id: "mycompany.deploy".to_string(),
+            name: "Deploy".to_string(),
+            description: "Protects against destructive deploy operations.".to_string(),
+            keywords: vec!["deploy".to_string()],
+            destructive_patterns: vec![ScaffoldDestructivePattern {
+                name: "prod-direct".to_string(),
+                regex: r#"deploy\s+--env\s*=?\s*"?prod"#.to_string(),
+                example: "deploy --env prod".to_string(),
+                severity: Severity::Critical,
+                reason: "Direct production deployment.".to_string(),
+                explanation: Some(
+                    "Production deployments must go through the release pipeline. Direct \
+                     deploys bypass approval workflows and audit logging."
+                        .to_string(),
+                ),
+                suggestions: vec![ScaffoldSuggestion {
+                    command: "deploy --env staging".to_string(),
+                    description: "Verify in staging first".to_string(),
+                }],
+            }],
+            safe_patterns: vec![ScaffoldSafePattern {
+                name: "staging-deploy".to_string(),
+                regex: r"deploy\s+--env\s*=?\s*(?:staging|dev)".to_string(),
+                example: "deploy --env staging".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn placeholder_is_valid() {
+        let scaffold = PackScaffold::placeholder("mycompany.deploy_tool");
+        assert_eq!(scaffold.name, "Deploy Tool");
+        assert_eq!(scaffold.category(), "mycompany");
+        assert_eq!(scaffold.module_name(), "deploy_tool");
+        assert_eq!(scaffold.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_bad_examples_and_ids() {
+        let mut scaffold = sample();
+        scaffold.id = "core.git".to_string();
+        scaffold.destructive_patterns[0].example = "deploy --env staging".to_string();
+        scaffold.safe_patterns[0].example = "rollout".to_string();
+        let errors = scaffold.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("already a built-in pack")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("does not match its regex"))
+        );
+        assert!(errors.iter().any(|e| e.contains("allowed by safe pattern")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("none of the pack keywords"))
+        );
+
+        let mut scaffold = sample();
+        scaffold.destructive_patterns[0].regex = "deploy(".to_string();
+        let errors = scaffold.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("Invalid regex")));
+    }
+
+    #[test]
+    fn yaml_round_trips_through_external_parser() {
+        let scaffold = sample();
+        let yaml = scaffold.render_yaml();
+        let pack = parse_pack_string(&yaml).expect("generated YAML should parse");
+        assert_eq!(pack.id, "mycompany.deploy");
+        assert_eq!(
+            pack.destructive_patterns[0].pattern,
+            scaffold.destructive_patterns[0].regex
+        );
+        assert_eq!(pack.destructive_patterns[0].suggestions.len(), 1);
+        assert_eq!(pack.safe_patterns.len(), 1);
+
+        let built = pack.into_pack();
+        assert!(built.matches_destructive("deploy --env prod").is_some());
+    }
+
+    #[test]
+    fn module_renders_macros_and_tests() {
+        let source = sample().render_module();
+        assert!(source.contains("id: \"mycompany.deploy\".to_string(),"));
+        assert!(source.contains(r##"r#"deploy\s+--env\s*=?\s*"?prod"#,"##));
+        assert!(source.contains("            Critical,\n"));
+        assert!(source.contains("Safer alternatives:\\n\\\n"));
+        assert!(source.contains(
+            "assert_blocks_with_pattern(&pack, \"deploy --env prod\", \"prod-direct\");"
+        ));
+        assert!(source.contains("assert_safe_pattern_matches(&pack, \"deploy --env staging\");"));
+    }
+
+    #[test]
+    fn multiline_literal_preserves_text() {
+        let text = "First paragraph that is long enough to need wrapping across more than one line of output.\n\nSafer alternatives:\n- a \"quoted\" command";
+        let literal = multiline_literal(text, "    ");
+        // Undo the continuation the way rustc does: drop `\` + newline + leading whitespace.
+        let mut unwrapped = String::new();
+        for (index, line) in literal.split("\\\n").enumerate() {
+            unwrapped.push_str(if index == 0 { line } else { line.trim_start() });
+        }
+        let inner = &unwrapped[1..unwrapped.len() - 1];
+        assert_eq!(inner, escape_str(text));
+        assert!(literal.lines().all(|line| line.len() <= WRAP_WIDTH + 8));
+    }
+
+    #[test]
+    fn raw_literal_picks_enough_hashes() {
+        assert_eq!(raw_literal(r"a\s+b"), r#"r"a\s+b""#);
+        assert_eq!(raw_literal(r#"a"b"#), r##"r#"a"b"#"##);
+        assert_eq!(raw_literal(r##"a"#b"##), r###"r##"a"#b"##"###);
+    }
+
+    #[test]
+    fn fixtures_use_case_format() {
+        let scaffold = sample();
+        let destructive = scaffold.render_destructive_fixtures();
+        let parsed: toml::Value = toml::from_str(&destructive).expect("valid TOML");
+        let case = &parsed["case"][0];
+        assert_eq!(case["command"].as_str(), Some("deploy --env prod"));
+        assert_eq!(
+            case["rule_id"].as_str(),
+            Some("mycompany.deploy:prod-direct")
+        );
+        let safe: toml::Value = toml::from_str(&scaffold.render_safe_fixtures()).unwrap();
+        assert_eq!(safe["case"][0]["expected"].as_str(), Some("allow"));
+    }
+
+    #[test]
+    fn insert_mod_declaration_keeps_order() {
+        let source = "//! Docs.\n\npub mod alpha;\npub mod gamma;\n\n// Other\npub mod zeta;\n";
+        let updated = insert_mod_declaration(source, "beta").unwrap();
+        assert_eq!(
+            updated,
+            "//! Docs.\n\npub mod alpha;\npub mod beta;\npub mod gamma;\n\n// Other\npub mod zeta;\n"
+        );
+        assert!(insert_mod_declaration(&updated, "beta").is_none());
+        let appended = insert_mod_declaration("//! Docs.\n", "alpha").unwrap();
+        assert_eq!(appended, "//! Docs.\n\npub mod alpha;\n");
+    }
+}
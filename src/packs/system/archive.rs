@@ -0,0 +1,125 @@
+//! Archive extraction patterns - protections against unpacking over system trees.
+//!
+//! This includes patterns for:
+//! - `tar -x` with `-C /` or `-C` a system directory
+//! - `unzip -o` with `-d` a system directory
+//!
+//! Extraction overwrites every file the archive contains, without a prompt and
+//! without a backup, so an archive unpacked at `/` or into `/etc` replaces
+//! system files as a side effect. `rsync --delete` onto system paths is
+//! covered by the `remote.rsync` pack.
+
+use crate::destructive_pattern;
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+
+/// Create the Archive Extraction pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "system.archive".to_string(),
+        name: "Archive Extraction",
+        description: "Protects against extracting archives over system trees: tar -x -C / \
+                      and unzip -o into /etc, /usr, and other system directories",
+        keywords: &["tar", "bsdtar", "unzip"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+const fn create_safe_patterns() -> Vec<SafePattern> {
+    // Listing and creating archives never match: every destructive pattern
+    // below requires an extract mode and a system target directory.
+    Vec::new()
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        // tar -x into / or a system directory, with -C before or after the mode
+        destructive_pattern!(
+            "tar-extract-system",
+            r#"\b(?:bsd)?tar\s+(?:(?:[A-Za-z]*x[A-Za-z]*\s|(?:[^;&|]*\s)?(?:-[A-Za-z]*x[A-Za-z]*|--extract|--get)\s)(?:[^;&|]*\s)?(?:-C\s*|--directory[=\s]\s*)["']?/(?:(?:bin|boot|dev|etc|lib|lib64|opt|root|sbin|srv|usr|var|Library|System)\b[^\s;&|]*|(?:home|Users)/?)?["']?(?:\s|$)|(?:[^;&|]*\s)?(?:-C\s*|--directory[=\s]\s*)["']?/(?:(?:bin|boot|dev|etc|lib|lib64|opt|root|sbin|srv|usr|var|Library|System)\b[^\s;&|]*|(?:home|Users)/?)?["']?\s(?:[^;&|]*\s)?(?:-[A-Za-z]*x[A-Za-z]*|--extract|--get)(?:\s|$))"#,
+            "Extracting a tar archive into / or a system directory overwrites system files.",
+            Critical,
+            "tar -x replaces every file the archive contains, silently and without a \
+             backup. With -C / or -C /etc an archive built elsewhere overwrites binaries, \
+             libraries, or configuration, and files it does not contain are left in a \
+             mixed state that no package manager knows about.\n\n\
+             List the archive first:\n  \
+             tar -tf <archive>\n\n\
+             Extract into a scratch directory and copy what is needed:\n  \
+             mkdir -p /tmp/unpack && tar -xf <archive> -C /tmp/unpack"
+        ),
+        // unzip -o into / or a system directory, with -d before or after -o
+        destructive_pattern!(
+            "unzip-overwrite-system",
+            r#"\bunzip\s+(?:(?:[^;&|]*\s)?-[A-Za-z]*o[A-Za-z]*\s(?:[^;&|]*\s)?-d\s*["']?/(?:(?:bin|boot|dev|etc|lib|lib64|opt|root|sbin|srv|usr|var|Library|System)\b[^\s;&|]*|(?:home|Users)/?)?["']?(?:\s|$)|(?:[^;&|]*\s)?-d\s*["']?/(?:(?:bin|boot|dev|etc|lib|lib64|opt|root|sbin|srv|usr|var|Library|System)\b[^\s;&|]*|(?:home|Users)/?)?["']?\s(?:[^;&|]*\s)?-[A-Za-z]*o[A-Za-z]*(?:\s|$))"#,
+            "unzip -o into / or a system directory overwrites system files without asking.",
+            Critical,
+            "-o tells unzip to overwrite existing files without prompting, so extracting \
+             with -d /etc or -d /usr replaces system configuration and binaries with \
+             whatever the archive holds.\n\n\
+             List the archive first:\n  \
+             unzip -l <archive>\n\n\
+             Extract into a scratch directory and copy what is needed:\n  \
+             unzip <archive> -d /tmp/unpack"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "system.archive");
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn tar_extraction_into_system_paths() {
+        let pack = create_pack();
+        for command in [
+            "tar -xf backup.tar -C /",
+            "sudo tar xzf rootfs.tar.gz -C /",
+            "tar -C / -xpf system.tar",
+            "tar --extract --file=etc.tar --directory=/etc",
+            "bsdtar -xf payload.tar -C /usr/local",
+            "tar -xvf homes.tar -C /home/",
+        ] {
+            assert_blocks_with_pattern(&pack, command, "tar-extract-system");
+        }
+        assert_allows(&pack, "tar -xf backup.tar -C /tmp/restore");
+        assert_allows(&pack, "tar -xzf release.tar.gz -C ./vendor");
+        assert_allows(&pack, "tar -tf backup.tar");
+        assert_allows(&pack, "tar -czf etc.tar.gz -C / etc");
+        assert_allows(&pack, "tar -xf alice.tar -C /home/alice");
+    }
+
+    #[test]
+    fn unzip_overwrite_into_system_paths() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "unzip -o config.zip -d /etc",
+            "unzip-overwrite-system",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "unzip -oq bundle.zip -d /usr/lib/app",
+            "unzip-overwrite-system",
+        );
+        assert_blocks_with_pattern(&pack, "unzip -d / -o rootfs.zip", "unzip-overwrite-system");
+        assert_allows(&pack, "unzip config.zip -d /etc");
+        assert_allows(&pack, "unzip -o release.zip -d ./dist");
+        assert_allows(&pack, "unzip -l bundle.zip");
+    }
+}
@@ -1,10 +1,14 @@
 //! System pack - protections for system administration commands.
 //!
 //! This pack provides protection against destructive system operations:
+//! - Archive extraction over system trees (tar -C /, unzip -o)
 //! - Disk operations (dd, fdisk, mkfs)
 //! - Permission changes (chmod, chown with dangerous patterns)
 //! - Service management (systemctl, service)
+//! - Scheduled jobs (crontab, at, schtasks, launchctl)
 
+pub mod archive;
 pub mod disk;
 pub mod permissions;
+pub mod scheduler;
 pub mod services;
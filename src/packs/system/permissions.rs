@@ -5,6 +5,9 @@
 //! - chmod -R on system directories
 //! - chown -R on system directories
 //! - setfacl with dangerous patterns
+//! - any other recursive chmod/chown/chgrp and `setfacl -b -R`, re-graded by
+//!   target after matching (see [`recursive_targets`]): inside the project is
+//!   Low, a home directory High, a system path Critical
 
 use crate::packs::{DestructivePattern, Pack, PatternSuggestion, SafePattern};
 use crate::{destructive_pattern, safe_pattern};
@@ -46,6 +49,7 @@ pub fn create_pack() -> Pack {
         keywords: &["chmod", "chown", "chgrp", "setfacl"],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -72,21 +76,10 @@ fn create_safe_patterns() -> Vec<SafePattern> {
 
 fn create_destructive_patterns() -> Vec<DestructivePattern> {
     vec![
-        // chmod 777 (world writable)
-        destructive_pattern!(
-            "chmod-777",
-            r#"chmod\s+(?:.*\s+)?["'=]?0*777(?:[\s"']|$)"#,
-            "chmod 777 makes files world-writable. This is a security risk.",
-            High,
-            "chmod 777 grants read/write/execute to everyone. This can expose sensitive \
-             files and allow unauthorized modification. Prefer least-privilege permissions \
-             that only grant the specific access needed.",
-            CHMOD_777_SUGGESTIONS
-        ),
         // chmod -R on root or system directories
         destructive_pattern!(
             "chmod-recursive-root",
-            r"chmod\s+(?:.*(?:-[rR]|--recursive)).*\s+/(?:$|bin|boot|dev|etc|lib|lib64|opt|proc|root|run|sbin|srv|sys|usr|var)\b",
+            r"chmod\s+(?:.*(?:-[rR]|--recursive)).*\s+/(?:(?:bin|boot|dev|etc|lib|lib64|opt|proc|root|run|sbin|srv|sys|usr|var)\b|(?:home|Users)/?(?:\s|$)|\*?(?:\s|$))",
             "chmod -R on system directories can break system permissions.",
             Critical,
             "Recursively changing permissions on system directories can render the system \
@@ -99,7 +92,7 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
         // chown -R on root or system directories
         destructive_pattern!(
             "chown-recursive-root",
-            r"chown\s+(?:.*(?:-[rR]|--recursive)).*\s+/(?:$|bin|boot|dev|etc|lib|lib64|opt|proc|root|run|sbin|srv|sys|usr|var)\b",
+            r"chown\s+(?:.*(?:-[rR]|--recursive)).*\s+/(?:(?:bin|boot|dev|etc|lib|lib64|opt|proc|root|run|sbin|srv|sys|usr|var)\b|(?:home|Users)/?(?:\s|$)|\*?(?:\s|$))",
             "chown -R on system directories can break system ownership.",
             High,
             "Recursive ownership changes on system directories can disrupt services, \
@@ -107,6 +100,32 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              path or a shallow find before applying broader changes.",
             CHOWN_RECURSIVE_SUGGESTIONS
         ),
+        // setfacl with dangerous patterns
+        destructive_pattern!(
+            "setfacl-all",
+            r"setfacl\s+.*-[rR].*\s+/(?:(?:bin|boot|dev|etc|lib|lib64|opt|proc|root|run|sbin|srv|sys|usr|var)\b|(?:home|Users)/?(?:\s|$)|\*?(?:\s|$))",
+            "setfacl -R on system directories can modify access control across the filesystem.",
+            Critical,
+            "Recursively modifying ACLs on system directories changes fine-grained access \
+             control for every file in the tree. This can override traditional Unix \
+             permissions and grant or revoke access in unexpected ways, breaking services \
+             and security boundaries.\n\n\
+             Review current ACLs first:\n  \
+             getfacl <path>\n\n\
+             Apply to a specific file instead of recursively:\n  \
+             setfacl -m u:<user>:rwx <specific-file>"
+        ),
+        // chmod 777 (world writable)
+        destructive_pattern!(
+            "chmod-777",
+            r#"chmod\s+(?:.*\s+)?["'=]?0*777(?:[\s"']|$)"#,
+            "chmod 777 makes files world-writable. This is a security risk.",
+            High,
+            "chmod 777 grants read/write/execute to everyone. This can expose sensitive \
+             files and allow unauthorized modification. Prefer least-privilege permissions \
+             that only grant the specific access needed.",
+            CHMOD_777_SUGGESTIONS
+        ),
         // chmod u+s (setuid)
         destructive_pattern!(
             "chmod-setuid",
@@ -149,20 +168,170 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              Consider using group ownership instead:\n  \
              chgrp <group> <path>"
         ),
-        // setfacl with dangerous patterns
+        // any other recursive change; the evaluator re-grades these by target
         destructive_pattern!(
-            "setfacl-all",
-            r"setfacl\s+.*-[rR].*\s+/(?:$|bin|boot|dev|etc|lib|lib64|opt|proc|root|run|sbin|srv|sys|usr|var)\b",
-            "setfacl -R on system directories can modify access control across the filesystem.",
-            Critical,
-            "Recursively modifying ACLs on system directories changes fine-grained access \
-             control for every file in the tree. This can override traditional Unix \
-             permissions and grant or revoke access in unexpected ways, breaking services \
-             and security boundaries.\n\n\
-             Review current ACLs first:\n  \
-             getfacl <path>\n\n\
-             Apply to a specific file instead of recursively:\n  \
-             setfacl -m u:<user>:rwx <specific-file>"
+            "chmod-recursive",
+            r"\bchmod\s+(?:[^;&|]*\s)?(?:-[cfvR]*R[cfvR]*|--recursive)(?:\s|$)",
+            "chmod -R changes the permissions of every file below the target.",
+            Medium,
+            "A recursive chmod applies one mode to files and directories alike: \
+             executables can lose their execute bit, or keys and configs become readable \
+             by others. The previous modes are not recorded anywhere.\n\n\
+             Inside the project this is only logged; on a home directory it is denied, \
+             and on a system path it is critical.\n\n\
+             Change only what needs it:\n  \
+             find <path> -type d -exec chmod 755 {} +"
+        ),
+        destructive_pattern!(
+            "chown-recursive",
+            r"\bch(?:own|grp)\s+(?:[^;&|]*\s)?(?:-[a-zA-Z]*R[a-zA-Z]*|--recursive)(?:\s|$)",
+            "chown/chgrp -R changes the owner of every file below the target.",
+            Medium,
+            "A recursive chown hands every file below the target to one owner. Services \
+             and users that owned files there lose access, and the previous owners are \
+             not recorded anywhere.\n\n\
+             Inside the project this is only logged; on a home directory it is denied, \
+             and on a system path it is critical.",
+            CHOWN_RECURSIVE_SUGGESTIONS
+        ),
+        destructive_pattern!(
+            "setfacl-strip-recursive",
+            r"\bsetfacl\s+(?:[^;&|]*\s)?(?:-[A-Za-z]*(?:b[A-Za-z]*R|R[A-Za-z]*b)[A-Za-z]*|(?:-[A-Za-z]*b[A-Za-z]*|--remove-all)\s+(?:[^;&|]*\s)?(?:-[A-Za-z]*R[A-Za-z]*|--recursive)|(?:-[A-Za-z]*R[A-Za-z]*|--recursive)\s+(?:[^;&|]*\s)?(?:-[A-Za-z]*b[A-Za-z]*|--remove-all))(?:\s|$)",
+            "setfacl -b -R removes every extended ACL below the target.",
+            Medium,
+            "setfacl -b strips all extended ACL entries and -R does it for the whole \
+             tree. Access granted through ACLs (shared directories, service accounts) is \
+             lost and has to be rebuilt by hand.\n\n\
+             Back the ACLs up first:\n  \
+             getfacl -R <path> > acls.txt\n\n\
+             Restore them with:\n  \
+             setfacl --restore=acls.txt"
         ),
     ]
 }
+
+/// Rules whose severity the evaluator sets from [`recursive_targets`].
+pub const TARGET_GRADED_RULES: &[&str] = &[
+    "chmod-recursive",
+    "chown-recursive",
+    "setfacl-strip-recursive",
+];
+
+/// The paths a `chmod`, `chown`, `chgrp`, or `setfacl` segment operates on, as
+/// written (quotes kept for
+/// [`classify_path_target`](crate::packs::core::filesystem::classify_path_target)).
+///
+/// Flags, the mode or owner operand, and setfacl's ACL specs are skipped.
+#[must_use]
+pub fn recursive_targets(segment: &str) -> Vec<&str> {
+    let mut words = segment
+        .split_whitespace()
+        .skip_while(|word| !matches!(*word, "chmod" | "chown" | "chgrp" | "setfacl"));
+    let Some(command) = words.next() else {
+        return Vec::new();
+    };
+
+    // chmod takes a mode and chown/chgrp an owner first, unless --reference
+    // supplies it; setfacl's specs are option values.
+    let mut needs_operand = command != "setfacl";
+    let mut skip_value = false;
+    let mut targets = Vec::new();
+    for word in words {
+        if skip_value {
+            skip_value = false;
+            continue;
+        }
+        if word.starts_with("--reference") {
+            needs_operand = false;
+            continue;
+        }
+        let is_symbolic_mode =
+            command == "chmod" && word.len() > 1 && word[1..].chars().all(|c| "rwxXst".contains(c));
+        if word.starts_with('-') && !is_symbolic_mode {
+            skip_value = command == "setfacl"
+                && matches!(
+                    word,
+                    "-m" | "-M"
+                        | "-x"
+                        | "-X"
+                        | "--set"
+                        | "--set-file"
+                        | "--modify"
+                        | "--modify-file"
+                        | "--remove"
+                        | "--remove-file"
+                );
+            continue;
+        }
+        if needs_operand {
+            needs_operand = false;
+            continue;
+        }
+        targets.push(word);
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "system.permissions");
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn recursive_changes_on_system_paths() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "chmod -R 000 /", "chmod-recursive-root");
+        assert_blocks_with_pattern(&pack, "chmod -R 777 /etc", "chmod-recursive-root");
+        assert_blocks_with_severity(&pack, "chmod -R 777 /home", Severity::Critical);
+        assert_blocks_with_pattern(&pack, "chown -R nobody /usr/lib", "chown-recursive-root");
+        assert_blocks_with_pattern(&pack, "setfacl -b -R /etc", "setfacl-all");
+    }
+
+    #[test]
+    fn other_recursive_changes() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "chmod -R u+w build", "chmod-recursive");
+        assert_blocks_with_pattern(&pack, "chmod -R 755 /home/alice", "chmod-recursive");
+        assert_blocks_with_pattern(&pack, "chown -R me:me ~/", "chown-recursive");
+        assert_blocks_with_pattern(&pack, "chgrp -R staff ./shared", "chown-recursive");
+        assert_blocks_with_pattern(&pack, "setfacl -bR ./data", "setfacl-strip-recursive");
+        assert_blocks_with_pattern(&pack, "setfacl -R -b ~/shared", "setfacl-strip-recursive");
+        assert_allows(&pack, "chmod 644 README.md");
+        assert_allows(&pack, "setfacl -m u:alice:rwx ./data");
+    }
+
+    #[test]
+    fn recursive_targets_skip_modes_owners_and_specs() {
+        assert_eq!(
+            recursive_targets("chmod -R 755 build dist"),
+            ["build", "dist"]
+        );
+        assert_eq!(
+            recursive_targets("sudo chmod -R -x '/srv/app'"),
+            ["'/srv/app'"]
+        );
+        assert_eq!(
+            recursive_targets("chown -R www-data:www-data /var/www"),
+            ["/var/www"]
+        );
+        assert_eq!(
+            recursive_targets("chown -R --reference=. ~/project"),
+            ["~/project"]
+        );
+        assert_eq!(
+            recursive_targets("setfacl -R -m u:bob:rx -b /data"),
+            ["/data"]
+        );
+        assert!(recursive_targets("ls -R").is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+//! Scheduler patterns - protections against wiping scheduled jobs.
+//!
+//! This includes patterns for:
+//! - crontab -r and replacing a crontab with an empty one
+//! - bulk at/atrm removals driven by atq
+//! - schtasks /delete on Windows
+//! - launchctl bootout/disable of the macOS system domain
+//!
+//! Backups, log rotation, and certificate renewal usually run from these
+//! schedulers, and removing them fails silently: nothing breaks until the
+//! job that no longer runs is needed.
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Scheduler pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "system.scheduler".to_string(),
+        name: "Scheduler",
+        description: "Protects against wiping scheduled jobs: crontab -r, bulk at job \
+                      removal, schtasks /delete, and launchctl bootout of system services",
+        keywords: &[
+            "crontab",
+            "atrm",
+            "atq",
+            "at -",
+            "schtasks",
+            "SCHTASKS",
+            "launchctl",
+        ],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        // listing is safe
+        safe_pattern!("crontab-list", r"^\s*crontab\s+(?:-u\s+\S+\s+)?-l\s*$"),
+        safe_pattern!("atq", r"^\s*atq(?:\s+-q\s+\S+)?\s*$"),
+        safe_pattern!(
+            "schtasks-query",
+            r"(?i)^\s*schtasks(?:\.exe)?\s+/query\b[^;&|]*$"
+        ),
+        safe_pattern!(
+            "launchctl-list",
+            r"^\s*launchctl\s+(?:list|print)\b[^;&|]*$"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        // crontab -r removes the whole crontab
+        destructive_pattern!(
+            "crontab-remove",
+            r"\bcrontab\s+(?:-u\s+\S+\s+)?-i?ri?(?:\s+-u\s+\S+)?(?:[\s;&|)]|$)",
+            "crontab -r deletes every cron job of the user without a backup.",
+            High,
+            "crontab -r removes the user's entire crontab, not one entry, and keeps no \
+             copy. Backups, cleanup, and renewal jobs stop running silently. The flag sits \
+             next to -e on the keyboard, which makes it a common accident.\n\n\
+             Save the crontab first:\n  \
+             crontab -l > ~/crontab.bak\n\n\
+             Remove a single job by editing instead:\n  \
+             crontab -e"
+        ),
+        // installing /dev/null as the crontab empties it
+        destructive_pattern!(
+            "crontab-replace-empty",
+            r"\bcrontab\s+(?:-u\s+\S+\s+)?/dev/null\b",
+            "Installing /dev/null as the crontab deletes every cron job.",
+            High,
+            "crontab <file> replaces the whole crontab with the file's contents, so \
+             /dev/null leaves the user with no cron jobs at all.\n\n\
+             Save the crontab first:\n  \
+             crontab -l > ~/crontab.bak"
+        ),
+        // atrm fed the whole queue
+        destructive_pattern!(
+            "at-remove-all",
+            r"(?:\b(?:atrm|at\s+-[rd])\s+(?:[^\s;&|]+\s+)*?(?:\$\(|`)\s*atq\b|\batq\b[^;&]*\|\s*xargs\s+(?:-\S+\s+)*(?:atrm|at\s+-[rd])\b)",
+            "Removing every queued at job at once deletes jobs other users and tools scheduled.",
+            High,
+            "Feeding atq's output to atrm removes the whole at queue, including jobs \
+             scheduled by other tools (and by other users when run as root).\n\n\
+             Review the queue first:\n  \
+             atq\n\n\
+             Remove the job you mean:\n  \
+             atrm <job-number>"
+        ),
+        // schtasks /delete /tn * removes every task
+        destructive_pattern!(
+            "schtasks-delete-all",
+            r#"(?i)\bschtasks(?:\.exe)?\b[^;&|]*?/delete\b[^;&|]*?/tn\s+["']?\*["']?(?:\s|$)"#,
+            "schtasks /delete /tn * deletes every scheduled task on the machine.",
+            Critical,
+            "With /tn * schtasks deletes all scheduled tasks, including Windows \
+             maintenance, update, and backup tasks. There is no undo; tasks have to be \
+             recreated or re-imported from XML.\n\n\
+             Export the tasks first:\n  \
+             schtasks /query /xml > tasks.xml\n\n\
+             Delete a single task by name:\n  \
+             schtasks /delete /tn \"<TaskName>\""
+        ),
+        // schtasks /delete /f skips the confirmation
+        destructive_pattern!(
+            "schtasks-delete",
+            r"(?i)\bschtasks(?:\.exe)?\b[^;&|]*?/delete\b[^;&|]*?/f\b",
+            "schtasks /delete /f deletes a scheduled task without confirmation.",
+            High,
+            "/f suppresses the confirmation prompt, so the task is gone as soon as the \
+             command runs. Export it first so it can be re-imported:\n  \
+             schtasks /query /tn \"<TaskName>\" /xml > task.xml"
+        ),
+        // launchctl bootout/disable of the system domain
+        destructive_pattern!(
+            "launchctl-bootout-system",
+            r"\blaunchctl\s+(?:-\S+\s+)*(?:bootout|disable)\s+system(?:/|\s|$)",
+            "launchctl bootout/disable of the system domain stops system daemons.",
+            High,
+            "bootout removes a daemon (or, given the bare system domain, every daemon) \
+             from launchd, and disable keeps it from starting again after reboot. \
+             Scheduled maintenance, backups (Time Machine), and security agents stop \
+             silently.\n\n\
+             Inspect the service first:\n  \
+             launchctl print system/<label>\n\n\
+             To stop a user agent instead, use the gui/<uid> domain."
+        ),
+        // legacy launchctl unload of a system LaunchDaemon
+        destructive_pattern!(
+            "launchctl-unload-daemon",
+            r"\blaunchctl\s+unload\s+(?:-\S+\s+)*\S*/Library/LaunchDaemons/",
+            "launchctl unload of a LaunchDaemon stops a system daemon.",
+            High,
+            "Unloading a plist from /Library/LaunchDaemons or \
+             /System/Library/LaunchDaemons stops a system-wide daemon; with -w it also \
+             stays disabled after reboot.\n\n\
+             Inspect the service first:\n  \
+             launchctl print system/<label>"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "system.scheduler");
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn crontab_wipes() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "crontab -r", "crontab-remove");
+        assert_blocks_with_pattern(&pack, "crontab -u root -r", "crontab-remove");
+        assert_blocks_with_pattern(&pack, "crontab -ri", "crontab-remove");
+        assert_blocks_with_pattern(&pack, "crontab /dev/null", "crontab-replace-empty");
+        assert_allows(&pack, "crontab -l");
+        assert_allows(&pack, "crontab -e");
+        assert_allows(&pack, "crontab -l > ~/crontab.bak");
+        assert_allows(&pack, "crontab mycron.txt");
+    }
+
+    #[test]
+    fn bulk_at_removal() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "atrm $(atq | cut -f1)", "at-remove-all");
+        assert_blocks_with_pattern(&pack, "at -r `atq | awk '{print $1}'`", "at-remove-all");
+        assert_blocks_with_pattern(&pack, "atq | cut -f1 | xargs atrm", "at-remove-all");
+        assert_allows(&pack, "atrm 12");
+        assert_allows(&pack, "atq");
+    }
+
+    #[test]
+    fn schtasks_delete() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "schtasks /delete /tn * /f", "schtasks-delete-all");
+        assert_blocks_with_severity(&pack, "SCHTASKS /Delete /TN \"*\" /F", Severity::Critical);
+        assert_blocks_with_pattern(
+            &pack,
+            "schtasks.exe /delete /tn \"Nightly Backup\" /f",
+            "schtasks-delete",
+        );
+        assert_allows(&pack, "schtasks /query /fo LIST");
+        assert_allows(
+            &pack,
+            "schtasks /create /tn Backup /tr backup.cmd /sc daily",
+        );
+    }
+
+    #[test]
+    fn launchctl_system_domain() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "sudo launchctl bootout system/com.apple.backupd-helper",
+            "launchctl-bootout-system",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "launchctl bootout system /Library/LaunchDaemons/com.example.agent.plist",
+            "launchctl-bootout-system",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "launchctl unload -w /Library/LaunchDaemons/com.example.backup.plist",
+            "launchctl-unload-daemon",
+        );
+        assert_allows(&pack, "launchctl bootout gui/501/com.example.agent");
+        assert_allows(&pack, "launchctl print system/com.apple.backupd-helper");
+    }
+}
@@ -1,9 +1,16 @@
 //! Services patterns - protections against dangerous service operations.
 //!
 //! This includes patterns for:
-//! - systemctl stop/disable on critical services
+//! - systemctl stop/disable/mask on critical units (`[services] critical_units`)
 //! - service stop on critical services
-//! - init system modifications
+//! - dropping to rescue/emergency mode and other init system changes
+//! - killing PID 1 and broad `pkill -f` patterns
+//! - shutdown, reboot, halt, and poweroff
+//!
+//! The `systemctl-stop-critical` regex covers the default critical units; the
+//! evaluator re-checks the stopped units against the configured list (see
+//! [`stopped_units`]) and moves the match between the critical and plain
+//! rules accordingly.
 
 use crate::packs::{DestructivePattern, Pack, SafePattern};
 use crate::{destructive_pattern, safe_pattern};
@@ -15,7 +22,7 @@ pub fn create_pack() -> Pack {
         id: "system.services".to_string(),
         name: "Services",
         description: "Protects against dangerous service operations like stopping critical \
-                      services and modifying init configuration",
+                      services, killing init, and shutting down the system",
         keywords: &[
             "systemctl",
             "service",
@@ -23,9 +30,14 @@ pub fn create_pack() -> Pack {
             "upstart",
             "shutdown",
             "reboot",
+            "halt",
+            "poweroff",
+            "kill",
+            "pkill",
         ],
         safe_patterns: create_safe_patterns(),
         destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
         keyword_matcher: None,
         safe_regex_set: None,
         safe_regex_set_is_complete: false,
@@ -57,12 +69,13 @@ fn create_safe_patterns() -> Vec<SafePattern> {
 
 fn create_destructive_patterns() -> Vec<DestructivePattern> {
     vec![
-        // systemctl stop/disable critical services
+        // systemctl stop/disable critical services (the default critical units;
+        // the evaluator applies the configured list)
         destructive_pattern!(
             "systemctl-stop-critical",
-            r"systemctl\s+(?:stop|disable|mask)\s+(?:ssh|sshd|network|networking|firewalld|ufw|docker|containerd)",
+            r"systemctl\s+(?:-\S+\s+)*(?:stop|disable|mask)\s+(?:[^\s;&|]+\s+)*?(?:ssh|sshd|systemd-networkd|NetworkManager|network|networking|systemd-resolved|firewalld|ufw|docker|containerd|dbus)(?:\.service)?(?:[\s;&|)]|$)",
             "Stopping/disabling critical services can cause system access loss or outage.",
-            High,
+            Critical,
             "Stopping, disabling, or masking a critical system service can lock you out \
              of the machine or cause cascading failures. For example, stopping sshd severs \
              remote access, stopping networking drops all connections, and stopping docker \
@@ -70,7 +83,8 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              Check current state first:\n  \
              systemctl status <service>\n\n\
              If you need to restart rather than stop:\n  \
-             systemctl restart <service>"
+             systemctl restart <service>\n\n\
+             The list of critical units is set by `[services] critical_units`."
         ),
         // systemctl stop/disable any service
         destructive_pattern!(
@@ -100,6 +114,20 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              Prefer systemctl on systemd systems:\n  \
              systemctl status <name>"
         ),
+        // systemctl isolate rescue/emergency (single-user mode)
+        destructive_pattern!(
+            "systemctl-isolate-rescue",
+            r"systemctl\s+(?:-\S+\s+)*isolate\s+(?:rescue|emergency)(?:\.target)?(?:[\s;&|)]|$)",
+            "systemctl isolate rescue/emergency drops the system to single-user mode.",
+            Critical,
+            "Isolating rescue.target or emergency.target stops every service outside that \
+             target, including sshd and networking. A remote session is cut off and the \
+             machine needs console access to recover.\n\n\
+             Check current target:\n  \
+             systemctl get-default\n\n\
+             Restart the one failing service instead:\n  \
+             systemctl restart <service>"
+        ),
         // systemctl isolate (changes runlevel)
         destructive_pattern!(
             "systemctl-isolate",
@@ -158,6 +186,49 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
              For a delayed reboot with notice:\n  \
              shutdown -r +5 \"Rebooting in 5 minutes\""
         ),
+        // halt/poweroff commands
+        destructive_pattern!(
+            "halt",
+            r"\b(?:halt|poweroff)\b",
+            "halt/poweroff will stop the system.",
+            Critical,
+            "halt stops the CPU and poweroff switches the machine off, both immediately. \
+             All processes are killed, remote users lose access, and a powered-off server \
+             needs out-of-band access to come back.\n\n\
+             Check who is logged in:\n  \
+             who\n\n\
+             Schedule a graceful shutdown instead:\n  \
+             shutdown -h +5 \"Shutting down for maintenance\""
+        ),
+        // kill PID 1 (init) or -1 (every process)
+        destructive_pattern!(
+            "kill-init",
+            r"\bkill\s+(?:(?:-s|-n|--signal)\s+\S+\s+|-\S+\s+)*(?:\d+\s+)*(?:--\s+)?-?1(?:[\s;&|)]|$)",
+            "kill targeting PID 1 (init) or -1 (every process) takes the system down.",
+            Critical,
+            "PID 1 is init (systemd on most systems); killing it panics the kernel or \
+             halts the machine. PID -1 signals every process you are allowed to signal, \
+             which as root is the whole system.\n\n\
+             Find the process you actually mean:\n  \
+             pgrep -a <name>\n\n\
+             Then signal it by PID:\n  \
+             kill <pid>"
+        ),
+        // pkill -f with a pattern that matches far more than intended
+        destructive_pattern!(
+            "pkill-broad",
+            r#"\bpkill\s+(?:-\S+\s+)*?(?:-[A-Za-z]*f[A-Za-z]*|--full)\s+(?:-\S+\s+)*(?:['"](?:\.\*?|\.\+|\^|\$|[A-Za-z0-9_.-]{1,2}|python[0-9.]*|node|java|ruby|perl|php|bash|sh|zsh|ssh|sshd|systemd|init|root|usr|bin)['"]|(?:\.\*?|\.\+|\^|\$|[A-Za-z0-9_.-]{1,2}|python[0-9.]*|node|java|ruby|perl|php|bash|sh|zsh|ssh|sshd|systemd|init|root|usr|bin))(?:[\s;&|)]|$)"#,
+            "pkill -f with a broad pattern kills unrelated processes.",
+            High,
+            "pkill -f matches the pattern against every process's full command line, not \
+             just its name. A short or generic pattern such as '.', 'python', or 'ssh' also \
+             matches shells, editors, agents, and system daemons, including the session \
+             running this command.\n\n\
+             Preview what would be killed:\n  \
+             pgrep -af <pattern>\n\n\
+             Use a specific pattern or kill by PID:\n  \
+             pkill -f 'python manage.py runserver'"
+        ),
         // init 0/6 (shutdown/reboot)
         destructive_pattern!(
             "init-level",
@@ -176,6 +247,25 @@ fn create_destructive_patterns() -> Vec<DestructivePattern> {
     ]
 }
 
+/// The units a `systemctl stop|disable|mask` segment names, without quotes
+/// and without a `.service` suffix. Flags are skipped.
+#[must_use]
+pub fn stopped_units(segment: &str) -> Vec<&str> {
+    let mut tokens = segment.split_whitespace();
+    if !tokens
+        .by_ref()
+        .any(|token| matches!(token, "stop" | "disable" | "mask"))
+    {
+        return Vec::new();
+    }
+    tokens
+        .filter(|token| !token.starts_with('-'))
+        .map(|token| token.trim_matches(|c| c == '\'' || c == '"'))
+        .map(|unit| unit.strip_suffix(".service").unwrap_or(unit))
+        .filter(|unit| !unit.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,4 +302,80 @@ mod tests {
         assert!(!pack.might_match("echo hello"));
         assert!(pack.check("echo hello").is_none());
     }
+
+    #[test]
+    fn default_critical_units_match_critical_rule() {
+        let pack = create_pack();
+        for unit in crate::config::ServicesConfig::default().critical_units {
+            for verb in ["stop", "disable", "mask"] {
+                let command = format!("systemctl {verb} {unit}.service");
+                let matched = pack.check(&command).expect("should be blocked");
+                assert_eq!(matched.name, Some("systemctl-stop-critical"), "{command}");
+            }
+        }
+        let matched = pack.check("systemctl --now disable sshd").unwrap();
+        assert_eq!(matched.name, Some("systemctl-stop-critical"));
+        let matched = pack.check("systemctl stop sshd-keygen").unwrap();
+        assert_eq!(matched.name, Some("systemctl-stop"));
+    }
+
+    #[test]
+    fn stopped_units_skips_flags_and_suffix() {
+        assert_eq!(
+            stopped_units("systemctl --now disable -q sshd.service 'docker'"),
+            vec!["sshd", "docker"]
+        );
+        assert!(stopped_units("systemctl restart sshd").is_empty());
+    }
+
+    #[test]
+    fn isolate_rescue_is_critical() {
+        let pack = create_pack();
+        for command in [
+            "systemctl isolate rescue.target",
+            "systemctl isolate emergency",
+        ] {
+            let matched = pack.check(command).expect("should be blocked");
+            assert_eq!(matched.name, Some("systemctl-isolate-rescue"), "{command}");
+        }
+        let matched = pack.check("systemctl isolate multi-user.target").unwrap();
+        assert_eq!(matched.name, Some("systemctl-isolate"));
+    }
+
+    #[test]
+    fn kill_init_and_broad_pkill() {
+        let pack = create_pack();
+        for command in ["kill -9 1", "kill -KILL 1", "kill -s KILL -- -1", "kill 1"] {
+            let matched = pack.check(command).expect("should be blocked");
+            assert_eq!(matched.name, Some("kill-init"), "{command}");
+        }
+        for command in ["kill -9 12345", "kill -9 1234 10", "kill %1"] {
+            assert!(pack.check(command).is_none(), "{command}");
+        }
+        for command in [
+            "pkill -f python",
+            "pkill -9 -f .",
+            "pkill -f '.*'",
+            "pkill --full ssh",
+        ] {
+            let matched = pack.check(command).expect("should be blocked");
+            assert_eq!(matched.name, Some("pkill-broad"), "{command}");
+        }
+        for command in [
+            "pkill -f 'python manage.py runserver'",
+            "pkill -f gunicorn",
+            "pkill python",
+        ] {
+            assert!(pack.check(command).is_none(), "{command}");
+        }
+    }
+
+    #[test]
+    fn halt_and_poweroff_are_blocked() {
+        let pack = create_pack();
+        for command in ["halt", "sudo poweroff", "halt -p"] {
+            let matched = pack.check(command).expect("should be blocked");
+            assert_eq!(matched.name, Some("halt"), "{command}");
+        }
+    }
 }
@@ -791,7 +791,9 @@ impl EvalSnapshot {
         let effective_mode = result.effective_mode.map(|m| match m {
             DecisionMode::Deny => "deny".to_string(),
             DecisionMode::Warn => "warn".to_string(),
+            DecisionMode::WarnOnce => "warn_once".to_string(),
             DecisionMode::Log => "log".to_string(),
+            DecisionMode::Ask => "ask".to_string(),
         });
 
         let (pack_id, pattern_name, rule_id, match_source, reason_preview, matched_text_preview) =
@@ -836,6 +838,7 @@ impl EvalSnapshot {
             AllowlistLayer::Project => "project".to_string(),
             AllowlistLayer::User => "user".to_string(),
             AllowlistLayer::System => "system".to_string(),
+            AllowlistLayer::Generated => "generated".to_string(),
         });
 
         Self {
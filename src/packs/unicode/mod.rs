@@ -0,0 +1,5 @@
+//! Unicode packs - protections against look-alike and invisible characters.
+//!
+//! Enabled by default; disable with `packs.disabled = ["unicode"]`.
+
+pub mod obfuscation;
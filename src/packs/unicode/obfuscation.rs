@@ -0,0 +1,65 @@
+//! Unicode obfuscation pack - blocks guarded command names hidden behind
+//! look-alike or invisible characters.
+//!
+//! The evaluator folds zero-width characters, non-ASCII spaces and dashes,
+//! and homoglyphs before matching any pack (see
+//! [`crate::normalize::fold_confusables`]). This pack's rule fires from that
+//! step when a folded character sits inside or next to an enabled pack's
+//! keyword in an executed part of the command; quoted data such as a commit
+//! message is left alone. Its regex only documents the invisible characters
+//! involved, since packs see the folded text.
+
+use crate::packs::regex_engine::LazyCompiledRegex;
+use crate::packs::{DestructivePattern, Pack, SafePattern, Severity};
+
+/// Pack ID reported for obfuscated invocations.
+pub const PACK_ID: &str = "unicode.obfuscation";
+
+/// Pattern name reported for obfuscated invocations.
+pub const PATTERN_NAME: &str = "obfuscated-invocation";
+
+/// Reason reported for obfuscated invocations.
+pub const REASON: &str = "Command hides a known command name with zero-width, look-alike, or non-ASCII space characters.";
+
+/// Explanation reported for obfuscated invocations.
+pub const EXPLANATION: &str = "Invisible characters, non-breaking spaces, Unicode dashes, \
+     and homoglyphs (e.g. Cyrillic 'р' for 'p') make a command look different to a pattern \
+     matcher than it does to a reader. dcg folds them before matching, and blocks any command \
+     where they sit next to a guarded command name, since there is no legitimate reason to \
+     type them there.\n\n\
+     Retype the command using plain ASCII characters.";
+
+/// Create the Unicode obfuscation pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: PACK_ID.to_string(),
+        name: "Unicode Obfuscation",
+        description: "Blocks command names disguised with zero-width characters, non-breaking \
+                      spaces, Unicode dashes, or homoglyphs.",
+        // Folding removes zero-width spaces, so pack matching never sees this
+        // keyword and the regex below never runs on its own.
+        keywords: &["\u{200b}"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+const fn create_safe_patterns() -> Vec<SafePattern> {
+    Vec::new()
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![DestructivePattern {
+        regex: LazyCompiledRegex::new_destructive(r"[\x{200B}-\x{200F}\x{2060}\x{FEFF}]"),
+        reason: REASON,
+        name: Some(PATTERN_NAME),
+        severity: Severity::High,
+        explanation: Some(EXPLANATION),
+        suggestions: &[],
+    }]
+}
@@ -0,0 +1,167 @@
+//! libvirt pack - protections for destructive `virsh` operations.
+//!
+//! Covers destructive CLI operations:
+//! - Undefining domains together with their storage volumes
+//! - Volume and storage pool deletion
+//! - Forced power-off and snapshot deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the libvirt pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "virtualization.libvirt".to_string(),
+        name: "libvirt",
+        description: "Protects against destructive libvirt operations like undefining domains \
+                      with --remove-all-storage, deleting volumes and pools, and forced power-off.",
+        keywords: &["virsh"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "virsh-read",
+            r"virsh\b.*\s(?:list|dominfo|domstate|dumpxml|domblklist|vol-list|vol-info|pool-list|pool-info|snapshot-list|snapshot-info)\b"
+        ),
+        safe_pattern!(
+            "virsh-start",
+            r"virsh\b.*\s(?:start|shutdown|reboot)(?:\s|$)"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        // Storage removal is checked first so it wins over the plain undefine rule.
+        destructive_pattern!(
+            "virsh-undefine-remove-storage",
+            r"virsh\b.*\sundefine\b.*\s--(?:remove-all-storage|storage|wipe-storage)\b",
+            "virsh undefine with --remove-all-storage deletes the domain and its disk images.",
+            Critical,
+            "Undefining with storage removal deletes every disk image attached to the \
+             domain. The VM definition and all of its data are gone; there is no trash or \
+             undo.\n\n\
+             Safer alternatives:\n\
+             - virsh domblklist <domain> to see which volumes would be removed\n\
+             - virsh undefine <domain> without storage flags to keep the disks\n\
+             - Back up the images with virsh vol-download first"
+        ),
+        destructive_pattern!(
+            "virsh-undefine",
+            r"virsh\b.*\sundefine\b",
+            "virsh undefine removes a domain definition.",
+            Medium,
+            "Undefining removes the domain's XML configuration, NVRAM, and snapshot \
+             metadata. The disks remain but the VM must be recreated by hand.\n\n\
+             Safer alternatives:\n\
+             - virsh dumpxml <domain> > domain.xml to keep the definition"
+        ),
+        destructive_pattern!(
+            "virsh-vol-delete",
+            r"virsh\b.*\svol-(?:delete|wipe)\b",
+            "virsh vol-delete/vol-wipe destroys a storage volume's contents.",
+            High,
+            "Deleting or wiping a volume destroys the disk image permanently, even if a \
+             domain still references it.\n\n\
+             Safer alternatives:\n\
+             - virsh vol-info to confirm the volume first\n\
+             - virsh vol-download to back it up"
+        ),
+        destructive_pattern!(
+            "virsh-pool-delete",
+            r"virsh\b.*\spool-delete\b",
+            "virsh pool-delete deletes the storage pool's underlying resources.",
+            High,
+            "pool-delete removes the pool's backing directory or device contents, which \
+             takes every volume in the pool with it.\n\n\
+             Safer alternatives:\n\
+             - virsh pool-destroy to stop the pool without deleting data\n\
+             - virsh vol-list <pool> to see what would be lost"
+        ),
+        destructive_pattern!(
+            "virsh-destroy",
+            r"virsh\b.*\sdestroy\b",
+            "virsh destroy forcibly powers off a running domain.",
+            Medium,
+            "destroy is an immediate power cut. Guests lose unflushed writes and \
+             filesystems may need repair on the next boot.\n\n\
+             Safer alternatives:\n\
+             - virsh shutdown <domain> for a clean ACPI shutdown"
+        ),
+        destructive_pattern!(
+            "virsh-snapshot-delete",
+            r"virsh\b.*\ssnapshot-delete\b",
+            "virsh snapshot-delete removes a domain snapshot.",
+            Medium,
+            "Deleting a snapshot removes the restore point it provides.\n\n\
+             Safer alternatives:\n\
+             - virsh snapshot-list <domain> to confirm the snapshot name"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "virtualization.libvirt");
+        assert_eq!(pack.name, "libvirt");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"virsh"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "virsh list --all");
+        assert_safe_pattern_matches(&pack, "virsh -c qemu:///system dominfo web1");
+        assert_safe_pattern_matches(&pack, "virsh shutdown web1");
+        assert_safe_pattern_matches(&pack, "virsh start web1");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "virsh undefine web1 --remove-all-storage",
+            "virsh-undefine-remove-storage",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "virsh -c qemu:///system undefine web1 --nvram --storage vda",
+            "virsh-undefine-remove-storage",
+        );
+        assert_blocks_with_pattern(&pack, "virsh undefine web1", "virsh-undefine");
+        assert_blocks_with_pattern(
+            &pack,
+            "virsh vol-delete web1.qcow2 --pool default",
+            "virsh-vol-delete",
+        );
+        assert_blocks_with_pattern(&pack, "virsh pool-delete default", "virsh-pool-delete");
+        assert_blocks_with_pattern(&pack, "virsh destroy web1", "virsh-destroy");
+        assert_blocks_with_severity(
+            &pack,
+            "virsh undefine web1 --remove-all-storage",
+            Severity::Critical,
+        );
+    }
+}
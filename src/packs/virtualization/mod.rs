@@ -0,0 +1,11 @@
+//! Virtualization packs - protections for VM and bare-metal hypervisor tooling.
+//!
+//! Agents managing local dev VMs or hypervisor hosts can wipe whole disks
+//! with a single command, so these packs guard VM deletion, storage removal,
+//! and forced power-off.
+
+pub mod libvirt;
+pub mod multipass;
+pub mod proxmox;
+pub mod vagrant;
+pub mod virtualbox;
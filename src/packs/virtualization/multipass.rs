@@ -0,0 +1,108 @@
+//! Multipass pack - protections for destructive `multipass` operations.
+//!
+//! Covers destructive CLI operations:
+//! - `multipass delete --purge` (permanent instance deletion)
+//! - `multipass purge` (permanently removes all deleted instances)
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Multipass pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "virtualization.multipass".to_string(),
+        name: "Multipass",
+        description: "Protects against destructive Multipass operations like delete --purge and \
+                      purge, which permanently remove instances and their disks.",
+        keywords: &["multipass"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "multipass-read",
+            r"multipass\s+(?:list|ls|info|version|find|networks)\b"
+        ),
+        safe_pattern!("multipass-recover", r"multipass\s+recover\b"),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "multipass-delete-purge",
+            r"multipass\s+delete\b.*\s(?:--purge\b|-[a-zA-Z]*p[a-zA-Z]*\b)",
+            "multipass delete --purge permanently deletes instances and their disks.",
+            High,
+            "Without --purge, deleted instances can be brought back with multipass \
+             recover. With --purge the instance image is removed immediately.\n\n\
+             Safer alternatives:\n\
+             - multipass delete <name> without --purge so it can be recovered\n\
+             - multipass stop <name> to stop it instead"
+        ),
+        destructive_pattern!(
+            "multipass-purge",
+            r"multipass\s+purge\b",
+            "multipass purge permanently removes all deleted instances.",
+            High,
+            "purge destroys every instance that is in the Deleted state; none of them can \
+             be recovered afterwards.\n\n\
+             Safer alternatives:\n\
+             - multipass list to see which instances are marked Deleted\n\
+             - multipass recover <name> for anything still needed"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "virtualization.multipass");
+        assert_eq!(pack.name, "Multipass");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"multipass"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "multipass list");
+        assert_safe_pattern_matches(&pack, "multipass info dev");
+        assert_safe_pattern_matches(&pack, "multipass recover dev");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "multipass delete dev --purge",
+            "multipass-delete-purge",
+        );
+        assert_blocks_with_pattern(&pack, "multipass delete --all -p", "multipass-delete-purge");
+        assert_blocks_with_pattern(&pack, "multipass purge", "multipass-purge");
+    }
+
+    #[test]
+    fn allows_recoverable_delete() {
+        let pack = create_pack();
+        assert_no_match(&pack, "multipass delete dev");
+    }
+}
@@ -0,0 +1,126 @@
+//! Proxmox VE pack - protections for destructive `qm`, `pct`, and `pvesm` operations.
+//!
+//! Covers destructive CLI operations:
+//! - VM and container destruction (`qm destroy`, `pct destroy`)
+//! - Storage volume deletion (`pvesm free`)
+//! - Forced stop and snapshot deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Proxmox VE pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "virtualization.proxmox".to_string(),
+        name: "Proxmox VE",
+        description: "Protects against destructive Proxmox VE operations like qm destroy, pct \
+                      destroy, pvesm free, and snapshot deletion.",
+        keywords: &["qm", "pct", "pvesm"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "proxmox-read",
+            r"\b(?:qm|pct)\s+(?:list|status|config|pending|listsnapshot)\b"
+        ),
+        safe_pattern!("pvesm-read", r"\bpvesm\s+(?:list|status)\b"),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "proxmox-destroy",
+            r"\b(?:qm|pct)\s+destroy\b",
+            "qm/pct destroy deletes a Proxmox VM or container and its disks.",
+            Critical,
+            "destroy removes the guest configuration and every disk owned by it. With \
+             --purge it is also removed from backup jobs, replication, and HA. Nothing \
+             is kept unless a backup exists.\n\n\
+             Safer alternatives:\n\
+             - vzdump <vmid> to take a backup first\n\
+             - qm stop / pct stop to stop the guest instead\n\
+             - qm config <vmid> to confirm the VMID"
+        ),
+        destructive_pattern!(
+            "pvesm-free",
+            r"\bpvesm\s+free\b",
+            "pvesm free deletes a storage volume.",
+            High,
+            "pvesm free permanently deletes the volume, even if a guest still lists it as \
+             an unused disk.\n\n\
+             Safer alternatives:\n\
+             - pvesm list <storage> to confirm the volume ID"
+        ),
+        destructive_pattern!(
+            "proxmox-delsnapshot",
+            r"\b(?:qm|pct)\s+delsnapshot\b",
+            "qm/pct delsnapshot removes a guest snapshot.",
+            Medium,
+            "Deleting a snapshot removes the restore point it provides.\n\n\
+             Safer alternatives:\n\
+             - qm listsnapshot <vmid> to confirm the snapshot name"
+        ),
+        destructive_pattern!(
+            "proxmox-stop",
+            r"\b(?:qm|pct)\s+stop\b",
+            "qm/pct stop immediately stops a guest without a clean shutdown.",
+            Medium,
+            "stop is a hard power-off; unflushed writes inside the guest are lost.\n\n\
+             Safer alternatives:\n\
+             - qm shutdown / pct shutdown for a clean shutdown"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::Severity;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "virtualization.proxmox");
+        assert_eq!(pack.name, "Proxmox VE");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"qm"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "qm list");
+        assert_safe_pattern_matches(&pack, "pct config 101");
+        assert_safe_pattern_matches(&pack, "pvesm status");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "qm destroy 100 --purge", "proxmox-destroy");
+        assert_blocks_with_pattern(&pack, "pct destroy 101", "proxmox-destroy");
+        assert_blocks_with_pattern(&pack, "pvesm free local-lvm:vm-100-disk-0", "pvesm-free");
+        assert_blocks_with_pattern(
+            &pack,
+            "qm delsnapshot 100 pre-upgrade",
+            "proxmox-delsnapshot",
+        );
+        assert_blocks_with_pattern(&pack, "qm stop 100", "proxmox-stop");
+        assert_blocks_with_severity(&pack, "qm destroy 100", Severity::Critical);
+    }
+}
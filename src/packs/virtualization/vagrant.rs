@@ -0,0 +1,129 @@
+//! Vagrant pack - protections for destructive `vagrant` operations.
+//!
+//! Covers destructive CLI operations:
+//! - Forced `vagrant destroy` (skips the confirmation prompt)
+//! - Box removal and pruning
+//! - Snapshot deletion
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the Vagrant pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "virtualization.vagrant".to_string(),
+        name: "Vagrant",
+        description: "Protects against destructive Vagrant operations like vagrant destroy -f, \
+                      box removal, and snapshot deletion.",
+        keywords: &["vagrant"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "vagrant-read",
+            r"vagrant\s+(?:status|global-status|ssh-config|validate|version)\b"
+        ),
+        safe_pattern!("vagrant-box-list", r"vagrant\s+(?:box|snapshot)\s+list\b"),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "vagrant-destroy-force",
+            r"vagrant\s+destroy\b.*\s(?:--force\b|-[a-zA-Z]*f[a-zA-Z]*\b)",
+            "vagrant destroy -f deletes the VM and its disks without confirmation.",
+            High,
+            "destroy removes the machine and every disk Vagrant created for it. With -f \
+             there is no prompt, and anything not in a synced folder is lost.\n\n\
+             Safer alternatives:\n\
+             - vagrant halt to stop the machine instead\n\
+             - vagrant snapshot save before tearing down\n\
+             - Run vagrant destroy without -f to get a confirmation prompt"
+        ),
+        destructive_pattern!(
+            "vagrant-box-remove",
+            r"vagrant\s+box\s+(?:remove|prune)\b",
+            "vagrant box remove/prune deletes downloaded base boxes.",
+            Medium,
+            "Removing boxes forces a re-download and can remove the only copy of a \
+             locally built box.\n\n\
+             Safer alternatives:\n\
+             - vagrant box list to confirm which boxes are installed\n\
+             - vagrant box prune --dry-run to preview"
+        ),
+        destructive_pattern!(
+            "vagrant-snapshot-delete",
+            r"vagrant\s+snapshot\s+(?:delete|pop)\b",
+            "vagrant snapshot delete/pop removes a VM snapshot.",
+            Medium,
+            "delete removes the snapshot; pop restores and then deletes it.\n\n\
+             Safer alternatives:\n\
+             - vagrant snapshot restore to keep the snapshot after restoring"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "virtualization.vagrant");
+        assert_eq!(pack.name, "Vagrant");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"vagrant"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "vagrant status");
+        assert_safe_pattern_matches(&pack, "vagrant global-status --prune");
+        assert_safe_pattern_matches(&pack, "vagrant box list");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(&pack, "vagrant destroy -f", "vagrant-destroy-force");
+        assert_blocks_with_pattern(
+            &pack,
+            "vagrant destroy web --force",
+            "vagrant-destroy-force",
+        );
+        assert_blocks_with_pattern(&pack, "vagrant destroy -gf", "vagrant-destroy-force");
+        assert_blocks_with_pattern(
+            &pack,
+            "vagrant box remove ubuntu/jammy64 --all",
+            "vagrant-box-remove",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "vagrant snapshot delete clean",
+            "vagrant-snapshot-delete",
+        );
+    }
+
+    #[test]
+    fn allows_prompting_destroy() {
+        let pack = create_pack();
+        assert_no_match(&pack, "vagrant destroy web");
+    }
+}
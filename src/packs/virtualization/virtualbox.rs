@@ -0,0 +1,151 @@
+//! `VirtualBox` pack - protections for destructive `VBoxManage` operations.
+//!
+//! Covers destructive CLI operations:
+//! - Unregistering VMs with `--delete`
+//! - Closing disk media with `--delete`
+//! - Snapshot deletion and forced power-off
+
+use crate::packs::{DestructivePattern, Pack, SafePattern};
+use crate::{destructive_pattern, safe_pattern};
+
+/// Create the `VirtualBox` pack.
+#[must_use]
+pub fn create_pack() -> Pack {
+    Pack {
+        id: "virtualization.virtualbox".to_string(),
+        name: "VirtualBox",
+        description: "Protects against destructive VirtualBox operations like unregistervm \
+                      --delete, closemedium --delete, and snapshot deletion.",
+        keywords: &["VBoxManage", "vboxmanage"],
+        safe_patterns: create_safe_patterns(),
+        destructive_patterns: create_destructive_patterns(),
+        match_raw: false,
+        keyword_matcher: None,
+        safe_regex_set: None,
+        safe_regex_set_is_complete: false,
+    }
+}
+
+fn create_safe_patterns() -> Vec<SafePattern> {
+    vec![
+        safe_pattern!(
+            "vboxmanage-read",
+            r"(?i:vboxmanage)\s+(?:list|showvminfo|showmediuminfo|guestproperty\s+enumerate)\b"
+        ),
+        safe_pattern!(
+            "vboxmanage-snapshot-list",
+            r"(?i:vboxmanage)\s+snapshot\s+\S+\s+(?:list|showvminfo)\b"
+        ),
+    ]
+}
+
+fn create_destructive_patterns() -> Vec<DestructivePattern> {
+    vec![
+        destructive_pattern!(
+            "vboxmanage-unregistervm-delete",
+            r"(?i:vboxmanage)\s+unregistervm\b.*\s--delete(?:-all)?\b",
+            "VBoxManage unregistervm --delete removes the VM and deletes its disk images.",
+            Critical,
+            "--delete removes the VM's settings, logs, saved states, and every attached \
+             hard disk image. The files are deleted directly, not moved to the trash.\n\n\
+             Safer alternatives:\n\
+             - VBoxManage unregistervm <vm> without --delete to keep the files\n\
+             - VBoxManage export <vm> -o backup.ova first"
+        ),
+        destructive_pattern!(
+            "vboxmanage-closemedium-delete",
+            r"(?i:vboxmanage)\s+closemedium\b.*\s--delete\b",
+            "VBoxManage closemedium --delete deletes a disk image file.",
+            High,
+            "closemedium --delete removes the medium from the registry and deletes the \
+             image file from disk.\n\n\
+             Safer alternatives:\n\
+             - VBoxManage closemedium without --delete to only unregister it\n\
+             - VBoxManage showmediuminfo to check which VMs use it"
+        ),
+        destructive_pattern!(
+            "vboxmanage-snapshot-delete",
+            r"(?i:vboxmanage)\s+snapshot\s+\S+\s+delete\b",
+            "VBoxManage snapshot delete removes a VM snapshot.",
+            Medium,
+            "Deleting a snapshot merges its differencing image and removes the restore \
+             point.\n\n\
+             Safer alternatives:\n\
+             - VBoxManage snapshot <vm> list to confirm the snapshot name"
+        ),
+        destructive_pattern!(
+            "vboxmanage-poweroff",
+            r"(?i:vboxmanage)\s+controlvm\s+\S+\s+poweroff\b",
+            "VBoxManage controlvm poweroff pulls the power on a running VM.",
+            Medium,
+            "poweroff is equivalent to unplugging the machine; unflushed guest writes are \
+             lost.\n\n\
+             Safer alternatives:\n\
+             - VBoxManage controlvm <vm> acpipowerbutton for a clean shutdown\n\
+             - VBoxManage controlvm <vm> savestate to preserve state"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::test_helpers::*;
+
+    #[test]
+    fn test_pack_creation() {
+        let pack = create_pack();
+        assert_eq!(pack.id, "virtualization.virtualbox");
+        assert_eq!(pack.name, "VirtualBox");
+        assert!(!pack.description.is_empty());
+        assert!(pack.keywords.contains(&"VBoxManage"));
+
+        assert_patterns_compile(&pack);
+        assert_all_patterns_have_reasons(&pack);
+        assert_unique_pattern_names(&pack);
+    }
+
+    #[test]
+    fn allows_safe_commands() {
+        let pack = create_pack();
+        assert_safe_pattern_matches(&pack, "VBoxManage list vms");
+        assert_safe_pattern_matches(&pack, "vboxmanage showvminfo dev");
+        assert_safe_pattern_matches(&pack, "VBoxManage snapshot dev list");
+    }
+
+    #[test]
+    fn blocks_destructive_commands() {
+        let pack = create_pack();
+        assert_blocks_with_pattern(
+            &pack,
+            "VBoxManage unregistervm dev --delete",
+            "vboxmanage-unregistervm-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "vboxmanage unregistervm dev --delete-all",
+            "vboxmanage-unregistervm-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "VBoxManage closemedium disk dev.vdi --delete",
+            "vboxmanage-closemedium-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "VBoxManage snapshot dev delete clean",
+            "vboxmanage-snapshot-delete",
+        );
+        assert_blocks_with_pattern(
+            &pack,
+            "VBoxManage controlvm dev poweroff",
+            "vboxmanage-poweroff",
+        );
+    }
+
+    #[test]
+    fn allows_unregister_without_delete() {
+        let pack = create_pack();
+        assert_no_match(&pack, "VBoxManage unregistervm dev");
+    }
+}
@@ -0,0 +1,347 @@
+//! Where dcg keeps the files it writes at runtime.
+//!
+//! Configuration and allowlists stay in the config directory (see
+//! [`crate::config`]). Everything else follows the XDG base directory spec:
+//!
+//! - **State** (allow-once codes and grants, allowlist usage counters, the
+//!   review queue, suggestion history, the undo journal, the privacy salt): `$DCG_STATE_DIR`, else `$XDG_STATE_HOME/dcg`,
+//!   else the platform state directory (`~/.local/state/dcg`).
+//! - **Cache** (learned pack order, update checks, remote includes):
+//!   `$DCG_CACHE_DIR`, else `$XDG_CACHE_HOME/dcg`, else the platform cache
+//!   directory (`~/.cache/dcg`, `~/Library/Caches/dcg`).
+//!
+//! State files written by older releases under the config directory keep being
+//! used until the new location exists, so upgrading does not drop pending
+//! allow-once codes.
+
+use serde::Serialize;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use crate::config::resolve_config_path_value;
+
+/// Environment override for the state directory.
+pub const ENV_STATE_DIR: &str = "DCG_STATE_DIR";
+/// Environment override for the cache directory.
+pub const ENV_CACHE_DIR: &str = "DCG_CACHE_DIR";
+
+/// The state directory (`$DCG_STATE_DIR`, `$XDG_STATE_HOME/dcg`, or the platform default).
+#[must_use]
+pub fn state_dir() -> PathBuf {
+    state_dir_from(|key| env::var(key).ok())
+}
+
+/// The cache directory (`$DCG_CACHE_DIR`, `$XDG_CACHE_HOME/dcg`, or the platform default).
+#[must_use]
+pub fn cache_dir() -> PathBuf {
+    cache_dir_from(|key| env::var(key).ok())
+}
+
+/// Path of the state file `name`.
+///
+/// Falls back to a file of the same name in the config directory, where
+/// releases before the state directory kept it, while the state-directory
+/// copy does not exist yet. `DCG_STATE_DIR` disables the fallback.
+#[must_use]
+pub fn state_file(name: &str) -> PathBuf {
+    let path = state_dir().join(name);
+    if path.exists() || env_dir(&|key| env::var(key).ok(), ENV_STATE_DIR).is_some() {
+        return path;
+    }
+    legacy_config_file(name).unwrap_or(path)
+}
+
+fn state_dir_from(get_env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    if let Some(dir) = env_dir(&get_env, ENV_STATE_DIR) {
+        return dir;
+    }
+    if let Some(dir) = env_dir(&get_env, "XDG_STATE_HOME") {
+        return dir.join("dcg");
+    }
+    dirs::state_dir()
+        .unwrap_or_else(|| home_dir().join(".local").join("state"))
+        .join("dcg")
+}
+
+fn cache_dir_from(get_env: impl Fn(&str) -> Option<String>) -> PathBuf {
+    if let Some(dir) = env_dir(&get_env, ENV_CACHE_DIR) {
+        return dir;
+    }
+    if let Some(dir) = env_dir(&get_env, "XDG_CACHE_HOME") {
+        return dir.join("dcg");
+    }
+    dirs::cache_dir()
+        .unwrap_or_else(|| home_dir().join(".cache"))
+        .join("dcg")
+}
+
+/// An absolute directory from `key`. Relative XDG values are ignored, as the
+/// spec requires.
+fn env_dir(get_env: &impl Fn(&str) -> Option<String>, key: &str) -> Option<PathBuf> {
+    let value = get_env(key)?;
+    resolve_config_path_value(&value, None).filter(|path| path.is_absolute())
+}
+
+fn legacy_config_file(name: &str) -> Option<PathBuf> {
+    let xdg = dirs::home_dir().map(|home| home.join(".config").join("dcg").join(name));
+    let native = dirs::config_dir().map(|dir| dir.join("dcg").join(name));
+    [xdg, native]
+        .into_iter()
+        .flatten()
+        .find(|path| path.exists())
+}
+
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_default()
+}
+
+/// What a path listed by `dcg paths` is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathRole {
+    Config,
+    Allowlist,
+    State,
+    Cache,
+    Log,
+}
+
+impl PathRole {
+    /// Section heading used in text output.
+    #[must_use]
+    pub const fn heading(self) -> &'static str {
+        match self {
+            Self::Config => "Config",
+            Self::Allowlist => "Allowlists",
+            Self::State => "State",
+            Self::Cache => "Cache",
+            Self::Log => "Logs",
+        }
+    }
+}
+
+/// A file or directory dcg reads or writes.
+#[derive(Debug, Clone, Serialize)]
+pub struct DcgPath {
+    /// Stable identifier (e.g. `allow_once`).
+    pub id: &'static str,
+    pub role: PathRole,
+    pub description: &'static str,
+    pub path: PathBuf,
+    /// Whether dcg writes here (as opposed to only reading it).
+    pub writes: bool,
+    pub is_dir: bool,
+}
+
+impl DcgPath {
+    #[must_use]
+    pub fn file(
+        id: &'static str,
+        role: PathRole,
+        description: &'static str,
+        path: PathBuf,
+        writes: bool,
+    ) -> Self {
+        Self {
+            id,
+            role,
+            description,
+            path,
+            writes,
+            is_dir: false,
+        }
+    }
+
+    #[must_use]
+    pub fn dir(id: &'static str, role: PathRole, description: &'static str, path: PathBuf) -> Self {
+        Self {
+            id,
+            role,
+            description,
+            path,
+            writes: true,
+            is_dir: true,
+        }
+    }
+
+    /// Probe the filesystem for this path.
+    #[must_use]
+    pub fn status(&self) -> PathStatus {
+        PathStatus::probe(&self.path, self.is_dir)
+    }
+
+    /// Whether dcg will fail to use this path: unreadable, or not writable
+    /// (or creatable) when dcg writes to it.
+    #[must_use]
+    pub fn has_problem(&self, status: &PathStatus) -> bool {
+        if status.exists {
+            !status.readable || (self.writes && !status.writable)
+        } else {
+            self.writes && !status.writable
+        }
+    }
+}
+
+/// Existence and permission status of a path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathStatus {
+    pub exists: bool,
+    pub readable: bool,
+    /// For a missing path: whether it could be created.
+    pub writable: bool,
+    /// Unix permission bits (e.g. `0644`), when the path exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+impl PathStatus {
+    /// Check `path` without modifying it.
+    #[must_use]
+    pub fn probe(path: &Path, is_dir: bool) -> Self {
+        let Ok(meta) = fs::metadata(path) else {
+            let writable = path
+                .ancestors()
+                .skip(1)
+                .find(|dir| dir.exists())
+                .is_some_and(|dir| fs::metadata(dir).is_ok_and(|m| m.is_dir() && dir_writable(&m)));
+            return Self {
+                exists: false,
+                readable: false,
+                writable,
+                mode: None,
+            };
+        };
+        let (readable, writable) = if is_dir || meta.is_dir() {
+            (fs::read_dir(path).is_ok(), dir_writable(&meta))
+        } else {
+            (
+                fs::File::open(path).is_ok(),
+                OpenOptions::new().append(true).open(path).is_ok(),
+            )
+        };
+        Self {
+            exists: true,
+            readable,
+            writable,
+            #[cfg(unix)]
+            mode: Some(mode_string(&meta)),
+            #[cfg(not(unix))]
+            mode: None,
+        }
+    }
+
+    /// Short summary for text output (`ok`, `missing`, `not writable`, ...).
+    #[must_use]
+    pub fn label(&self) -> &'static str {
+        match (self.exists, self.readable, self.writable) {
+            (true, true, true) => "ok",
+            (true, true, false) => "read-only",
+            (true, false, _) => "not readable",
+            (false, _, true) => "missing",
+            (false, _, false) => "missing, cannot create",
+        }
+    }
+}
+
+#[cfg(unix)]
+fn dir_writable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let euid = nix::unistd::geteuid().as_raw();
+    if euid == 0 {
+        return true;
+    }
+    let mode = meta.mode();
+    if meta.uid() == euid {
+        mode & 0o300 == 0o300
+    } else if meta.gid() == nix::unistd::getegid().as_raw() {
+        mode & 0o030 == 0o030
+    } else {
+        mode & 0o003 == 0o003
+    }
+}
+
+#[cfg(not(unix))]
+fn dir_writable(meta: &fs::Metadata) -> bool {
+    !meta.permissions().readonly()
+}
+
+#[cfg(unix)]
+fn mode_string(meta: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    format!("{:04o}", meta.permissions().mode() & 0o7777)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_of<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| (*v).to_string())
+        }
+    }
+
+    #[test]
+    fn dcg_override_beats_xdg_which_beats_platform_default() {
+        let both = [
+            ("DCG_STATE_DIR", "/tmp/dcg-state"),
+            ("XDG_STATE_HOME", "/tmp/xdg-state"),
+            ("DCG_CACHE_DIR", "/tmp/dcg-cache"),
+            ("XDG_CACHE_HOME", "/tmp/xdg-cache"),
+        ];
+        assert_eq!(
+            state_dir_from(env_of(&both)),
+            PathBuf::from("/tmp/dcg-state")
+        );
+        assert_eq!(
+            cache_dir_from(env_of(&both)),
+            PathBuf::from("/tmp/dcg-cache")
+        );
+
+        let xdg = [
+            ("XDG_STATE_HOME", "/tmp/xdg-state"),
+            ("XDG_CACHE_HOME", "/tmp/xdg-cache"),
+        ];
+        assert_eq!(
+            state_dir_from(env_of(&xdg)),
+            PathBuf::from("/tmp/xdg-state/dcg")
+        );
+        assert_eq!(
+            cache_dir_from(env_of(&xdg)),
+            PathBuf::from("/tmp/xdg-cache/dcg")
+        );
+    }
+
+    #[test]
+    fn relative_xdg_values_are_ignored() {
+        let relative = [("XDG_STATE_HOME", "state"), ("XDG_CACHE_HOME", "")];
+        assert!(state_dir_from(env_of(&relative)).ends_with("dcg"));
+        assert_ne!(
+            state_dir_from(env_of(&relative)),
+            PathBuf::from("state/dcg")
+        );
+        assert!(cache_dir_from(env_of(&relative)).is_absolute());
+    }
+
+    #[test]
+    fn probe_reports_missing_paths_as_creatable_under_writable_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("nested").join("allow_once.jsonl");
+        let status = PathStatus::probe(&missing, false);
+        assert!(!status.exists);
+        assert!(status.writable);
+        assert_eq!(status.label(), "missing");
+
+        let file = dir.path().join("pending_exceptions.jsonl");
+        fs::write(&file, "").unwrap();
+        let status = PathStatus::probe(&file, false);
+        assert!(status.exists && status.readable && status.writable);
+        assert_eq!(status.label(), "ok");
+        #[cfg(unix)]
+        assert!(status.mode.is_some());
+    }
+}
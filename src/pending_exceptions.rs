@@ -3,21 +3,25 @@
 //! This module provides a small JSONL-backed record store that is:
 //! - Append-friendly for concurrent hooks
 //! - Deterministic in serialization
-//! - Fail-open on parse errors (corrupt lines are skipped)
+//! - Fail-open on parse errors (corrupt lines are skipped, and the damaged
+//!   file is quarantined on the next rewrite)
+//! - Rewritten atomically under a lock shared by parallel hooks (see
+//!   [`crate::storage`])
 
 use chrono::{DateTime, Duration, Utc};
-use fs2::FileExt;
 use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::env;
 use std::fmt::Write as FmtWrite;
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use crate::config::resolve_config_path_value;
 use crate::logging::{RedactionConfig, redact_command};
+use crate::storage::{self, StateLock};
 
 /// Environment override for pending exceptions file path.
 pub const ENV_PENDING_EXCEPTIONS_PATH: &str = "DCG_PENDING_EXCEPTIONS_PATH";
@@ -26,6 +30,9 @@ pub const ENV_ALLOW_ONCE_PATH: &str = "DCG_ALLOW_ONCE_PATH";
 /// Optional HMAC secret for short-code hardening.
 /// When set, codes cannot be forged without knowing the secret.
 pub const ENV_ALLOW_ONCE_SECRET: &str = "DCG_ALLOW_ONCE_SECRET";
+/// Session identifier used for session-scoped allow-once entries when the
+/// hook input does not carry one.
+pub const ENV_SESSION_ID: &str = "DCG_SESSION_ID";
 
 const PENDING_EXCEPTIONS_FILE: &str = "pending_exceptions.jsonl";
 const ALLOW_ONCE_FILE: &str = "allow_once.jsonl";
@@ -40,6 +47,55 @@ pub enum AllowOnceScopeKind {
     Project,
 }
 
+/// What an allow-once entry grants.
+///
+/// `Exact` is the historical behaviour: the same command string, for 24h.
+/// `Rule` allows anything the originating rule would block, and `Session`
+/// limits an exact-command grant to the agent session that was blocked.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowOnceGrant {
+    #[default]
+    Exact,
+    Rule,
+    Session,
+}
+
+impl AllowOnceGrant {
+    /// Stable label used in CLI output and logs.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Rule => "rule",
+            Self::Session => "session",
+        }
+    }
+}
+
+static SESSION_ID: OnceLock<String> = OnceLock::new();
+
+/// Record the agent session id reported by the hook input.
+///
+/// Only the first call has an effect; a hook process serves one session.
+pub fn set_session_id(id: &str) {
+    let id = id.trim();
+    if !id.is_empty() {
+        let _ = SESSION_ID.set(id.to_string());
+    }
+}
+
+/// The current agent session id, from the hook input or `DCG_SESSION_ID`.
+#[must_use]
+pub fn current_session_id() -> Option<String> {
+    SESSION_ID.get().cloned().or_else(|| {
+        env::var(ENV_SESSION_ID)
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+    })
+}
+
 /// A stored pending exception record (JSONL line).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PendingExceptionRecord {
@@ -56,6 +112,15 @@ pub struct PendingExceptionRecord {
     pub consumed_at: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// Rule that blocked the command (`pack:pattern`), when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    /// Agent session that was blocked, when the hook input carried one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Detected agent (config key), used to pick the default grant scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
 }
 
 /// A stored allow-once entry (JSONL line).
@@ -76,6 +141,12 @@ pub struct AllowOnceEntry {
     pub consumed_at: Option<String>,
     #[serde(default)]
     pub force_allow_config: bool,
+    #[serde(default)]
+    pub grant: AllowOnceGrant,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
 }
 
 impl AllowOnceEntry {
@@ -89,8 +160,8 @@ impl AllowOnceEntry {
         force_allow_config: bool,
         redaction: &RedactionConfig,
     ) -> Self {
-        let created_at = format_timestamp(now);
-        let expires_at = format_timestamp(now + Duration::hours(EXPIRY_HOURS));
+        let created_at = storage::format_timestamp(now);
+        let expires_at = storage::format_timestamp(now + Duration::hours(EXPIRY_HOURS));
 
         Self {
             schema_version: SCHEMA_VERSION,
@@ -106,9 +177,19 @@ impl AllowOnceEntry {
             single_use,
             consumed_at: None,
             force_allow_config,
+            grant: AllowOnceGrant::Exact,
+            rule_id: pending.rule_id.clone(),
+            session_id: pending.session_id.clone(),
         }
     }
 
+    /// Set what this entry grants (exact command, rule, or session).
+    #[must_use]
+    pub const fn with_grant(mut self, grant: AllowOnceGrant) -> Self {
+        self.grant = grant;
+        self
+    }
+
     #[must_use]
     pub const fn is_consumed(&self) -> bool {
         self.consumed_at.is_some()
@@ -122,6 +203,24 @@ impl AllowOnceEntry {
             AllowOnceScopeKind::Project => cwd.starts_with(scope_path),
         }
     }
+
+    /// Whether this entry allows `command` on its own (exact and session grants).
+    ///
+    /// Rule grants are checked against the blocking rule instead; see
+    /// [`AllowOnceStore::match_rule`].
+    #[must_use]
+    pub fn matches_command(&self, command: &str, session_id: Option<&str>) -> bool {
+        if self.command_raw != command {
+            return false;
+        }
+        match self.grant {
+            AllowOnceGrant::Exact => true,
+            AllowOnceGrant::Session => {
+                self.session_id.is_some() && self.session_id.as_deref() == session_id
+            }
+            AllowOnceGrant::Rule => false,
+        }
+    }
 }
 
 impl PendingExceptionRecord {
@@ -135,8 +234,8 @@ impl PendingExceptionRecord {
         single_use: bool,
         source: Option<String>,
     ) -> Self {
-        let created_at = format_timestamp(timestamp);
-        let expires_at = format_timestamp(timestamp + Duration::hours(EXPIRY_HOURS));
+        let created_at = storage::format_timestamp(timestamp);
+        let expires_at = storage::format_timestamp(timestamp + Duration::hours(EXPIRY_HOURS));
         let full_hash = compute_full_hash(&created_at, cwd, command_raw);
         let short_code = short_code_from_hash(&full_hash);
         let command_redacted = redact_for_pending(command_raw, redaction);
@@ -154,6 +253,9 @@ impl PendingExceptionRecord {
             single_use,
             consumed_at: None,
             source,
+            rule_id: None,
+            session_id: None,
+            agent: None,
         }
     }
 
@@ -196,7 +298,7 @@ impl PendingExceptionStore {
         &self.path
     }
 
-    /// Resolve the default path (env override or ~/.config/dcg/..).
+    /// Resolve the default path (env override, else the dcg state directory).
     #[must_use]
     pub fn default_path(cwd: Option<&Path>) -> PathBuf {
         if let Ok(value) = env::var(ENV_PENDING_EXCEPTIONS_PATH) {
@@ -205,26 +307,7 @@ impl PendingExceptionStore {
             }
         }
 
-        // Check XDG-style path first (~/.config/dcg/), then platform-native
-        let xdg_base = dirs::home_dir().map(|h| h.join(".config"));
-        let xdg_path = xdg_base
-            .as_ref()
-            .map(|b| b.join("dcg").join(PENDING_EXCEPTIONS_FILE));
-        if let Some(ref path) = xdg_path {
-            if path.exists()
-                || xdg_base
-                    .as_ref()
-                    .map(|b| b.join("dcg").exists())
-                    .unwrap_or(false)
-            {
-                return path.clone();
-            }
-        }
-
-        // Fall back to platform-native
-        let base = dirs::config_dir()
-            .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"));
-        base.join("dcg").join(PENDING_EXCEPTIONS_FILE)
+        crate::paths::state_file(PENDING_EXCEPTIONS_FILE)
     }
 
     /// Record a blocked command in the pending exceptions store.
@@ -243,20 +326,24 @@ impl PendingExceptionStore {
         redaction: &RedactionConfig,
         single_use: bool,
         source: Option<String>,
+        rule_id: Option<String>,
         allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
     ) -> io::Result<(PendingExceptionRecord, PendingMaintenance)> {
         let now = Utc::now();
-        let record =
+        let mut record =
             PendingExceptionRecord::new(now, cwd, command, reason, redaction, single_use, source);
+        record.rule_id = rule_id;
+        record.session_id = current_session_id();
+        record.agent = Some(crate::agent::detect_agent().config_key().to_string());
 
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_active_from_file(&mut file, now, allow_once_audit);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_active_from_file(&self.path, now, allow_once_audit)?;
 
-        if maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_records(&mut file, &active)?;
+        if !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
-        append_record(&mut file, &record)?;
+        append_record(&self.path, &record)?;
 
         if let Some(audit) = allow_once_audit {
             let _ = log_code_issued(audit.log_file, &record, audit.redaction, audit.format);
@@ -274,11 +361,11 @@ impl PendingExceptionStore {
         &self,
         now: DateTime<Utc>,
     ) -> io::Result<(Vec<PendingExceptionRecord>, PendingMaintenance)> {
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_active_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_active_from_file(&self.path, now, None)?;
 
-        if maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_records(&mut file, &active)?;
+        if !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
         Ok((active, maintenance))
@@ -296,8 +383,8 @@ impl PendingExceptionStore {
         &self,
         now: DateTime<Utc>,
     ) -> io::Result<(Vec<PendingExceptionRecord>, PendingMaintenance)> {
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_active_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_active_from_file(&self.path, now, None)?;
         Ok((active, maintenance))
     }
 
@@ -307,10 +394,10 @@ impl PendingExceptionStore {
     ///
     /// Returns any I/O errors encountered while opening, locking, or writing the store file.
     pub fn clear_all(&self, now: DateTime<Utc>) -> io::Result<(usize, PendingMaintenance)> {
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_active_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_active_from_file(&self.path, now, None)?;
         let removed = active.len();
-        rewrite_records(&mut file, &[])?;
+        rewrite_records::<PendingExceptionRecord>(&self.path, &[], maintenance)?;
         Ok((removed, maintenance))
     }
 
@@ -324,14 +411,14 @@ impl PendingExceptionStore {
         full_hash: &str,
         now: DateTime<Utc>,
     ) -> io::Result<(usize, PendingMaintenance)> {
-        let mut file = open_locked(&self.path)?;
-        let (mut active, maintenance) = load_active_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (mut active, maintenance) = load_active_from_file(&self.path, now, None)?;
         let before = active.len();
         active.retain(|record| record.full_hash != full_hash);
         let removed = before - active.len();
 
-        if removed > 0 || maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_records(&mut file, &active)?;
+        if removed > 0 || !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
         Ok((removed, maintenance))
@@ -374,7 +461,7 @@ impl AllowOnceStore {
         &self.path
     }
 
-    /// Resolve the default path (env override or ~/.config/dcg/..).
+    /// Resolve the default path (env override, else the dcg state directory).
     #[must_use]
     pub fn default_path(cwd: Option<&Path>) -> PathBuf {
         if let Ok(value) = env::var(ENV_ALLOW_ONCE_PATH) {
@@ -383,26 +470,7 @@ impl AllowOnceStore {
             }
         }
 
-        // Check XDG-style path first (~/.config/dcg/), then platform-native
-        let xdg_base = dirs::home_dir().map(|h| h.join(".config"));
-        let xdg_path = xdg_base
-            .as_ref()
-            .map(|b| b.join("dcg").join(ALLOW_ONCE_FILE));
-        if let Some(ref path) = xdg_path {
-            if path.exists()
-                || xdg_base
-                    .as_ref()
-                    .map(|b| b.join("dcg").exists())
-                    .unwrap_or(false)
-            {
-                return path.clone();
-            }
-        }
-
-        // Fall back to platform-native
-        let base = dirs::config_dir()
-            .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"));
-        base.join("dcg").join(ALLOW_ONCE_FILE)
+        crate::paths::state_file(ALLOW_ONCE_FILE)
     }
 
     /// Append a new allow-once entry and prune expired/consumed entries.
@@ -415,14 +483,14 @@ impl AllowOnceStore {
         entry: &AllowOnceEntry,
         now: DateTime<Utc>,
     ) -> io::Result<PendingMaintenance> {
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_allow_once_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_allow_once_from_file(&self.path, now, None)?;
 
-        if maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_allow_once_records(&mut file, &active)?;
+        if !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
-        append_allow_once_record(&mut file, entry)?;
+        append_record(&self.path, entry)?;
         Ok(maintenance)
     }
 
@@ -439,11 +507,11 @@ impl AllowOnceStore {
             return Ok((Vec::new(), PendingMaintenance::default()));
         }
 
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_allow_once_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_allow_once_from_file(&self.path, now, None)?;
 
-        if maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_allow_once_records(&mut file, &active)?;
+        if !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
         Ok((active, maintenance))
@@ -462,8 +530,8 @@ impl AllowOnceStore {
             return Ok((Vec::new(), PendingMaintenance::default()));
         }
 
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_allow_once_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_allow_once_from_file(&self.path, now, None)?;
         Ok((active, maintenance))
     }
 
@@ -477,10 +545,10 @@ impl AllowOnceStore {
             return Ok((0, PendingMaintenance::default()));
         }
 
-        let mut file = open_locked(&self.path)?;
-        let (active, maintenance) = load_allow_once_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (active, maintenance) = load_allow_once_from_file(&self.path, now, None)?;
         let removed = active.len();
-        rewrite_allow_once_records(&mut file, &[])?;
+        rewrite_records::<AllowOnceEntry>(&self.path, &[], maintenance)?;
         Ok((removed, maintenance))
     }
 
@@ -498,14 +566,14 @@ impl AllowOnceStore {
             return Ok((0, PendingMaintenance::default()));
         }
 
-        let mut file = open_locked(&self.path)?;
-        let (mut active, maintenance) = load_allow_once_from_file(&mut file, now, None);
+        let _lock = open_locked(&self.path)?;
+        let (mut active, maintenance) = load_allow_once_from_file(&self.path, now, None)?;
         let before = active.len();
         active.retain(|entry| entry.source_full_hash != full_hash);
         let removed = before - active.len();
 
-        if removed > 0 || maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_allow_once_records(&mut file, &active)?;
+        if removed > 0 || !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
         Ok((removed, maintenance))
@@ -513,7 +581,9 @@ impl AllowOnceStore {
 
     /// Match a command against active allow-once entries.
     ///
-    /// If a single-use entry matches, it is consumed immediately.
+    /// Exact grants match the command string; session grants additionally
+    /// require `session_id` to be the grant's session. If a single-use entry
+    /// matches, it is consumed immediately.
     ///
     /// # Errors
     ///
@@ -521,58 +591,14 @@ impl AllowOnceStore {
     pub fn match_command(
         &self,
         command: &str,
+        session_id: Option<&str>,
         cwd: &Path,
         now: DateTime<Utc>,
         allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
     ) -> io::Result<Option<AllowOnceEntry>> {
-        if !self.path.exists() {
-            return Ok(None);
-        }
-
-        let mut file = open_locked(&self.path)?;
-        let (mut active, maintenance) = load_allow_once_from_file(&mut file, now, allow_once_audit);
-
-        if maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_allow_once_records(&mut file, &active)?;
-        }
-
-        let idx = active
-            .iter()
-            .position(|entry| entry.command_raw == command && entry.matches_scope(cwd));
-
-        let Some(idx) = idx else {
-            return Ok(None);
-        };
-
-        let mut selected = active[idx].clone();
-        if active[idx].single_use {
-            selected.consumed_at = Some(format_timestamp(now));
-            active.remove(idx);
-            rewrite_allow_once_records(&mut file, &active)?;
-        }
-
-        if let Some(audit) = allow_once_audit {
-            let cwd_str = cwd.to_string_lossy();
-            let _ = log_allow_granted(
-                audit.log_file,
-                &selected,
-                audit.redaction,
-                "allow_once",
-                audit.format,
-                cwd_str.as_ref(),
-            );
-            if selected.consumed_at.is_some() {
-                let _ = log_entry_consumed(
-                    audit.log_file,
-                    &selected,
-                    audit.redaction,
-                    audit.format,
-                    cwd_str.as_ref(),
-                );
-            }
-        }
-
-        Ok(Some(selected))
+        self.match_entry(cwd, now, allow_once_audit, |entry| {
+            entry.matches_command(command, session_id)
+        })
     }
 
     /// Match a command against allow-once entries, but only grant if `force_allow_config` is set.
@@ -588,38 +614,67 @@ impl AllowOnceStore {
     pub fn match_command_force_config(
         &self,
         command: &str,
+        session_id: Option<&str>,
+        cwd: &Path,
+        now: DateTime<Utc>,
+        allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
+    ) -> io::Result<Option<AllowOnceEntry>> {
+        self.match_entry(cwd, now, allow_once_audit, |entry| {
+            entry.force_allow_config && entry.matches_command(command, session_id)
+        })
+    }
+
+    /// Match the rule that blocked a command (`pack:pattern`) against rule-scoped entries.
+    ///
+    /// If a single-use entry matches, it is consumed immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the store file.
+    pub fn match_rule(
+        &self,
+        rule_id: &str,
         cwd: &Path,
         now: DateTime<Utc>,
         allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
+    ) -> io::Result<Option<AllowOnceEntry>> {
+        self.match_entry(cwd, now, allow_once_audit, |entry| {
+            entry.grant == AllowOnceGrant::Rule && entry.rule_id.as_deref() == Some(rule_id)
+        })
+    }
+
+    fn match_entry(
+        &self,
+        cwd: &Path,
+        now: DateTime<Utc>,
+        allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
+        matches: impl Fn(&AllowOnceEntry) -> bool,
     ) -> io::Result<Option<AllowOnceEntry>> {
         if !self.path.exists() {
             return Ok(None);
         }
 
-        let mut file = open_locked(&self.path)?;
-        let (mut active, maintenance) = load_allow_once_from_file(&mut file, now, allow_once_audit);
+        let _lock = open_locked(&self.path)?;
+        let (mut active, maintenance) =
+            load_allow_once_from_file(&self.path, now, allow_once_audit)?;
 
-        if maintenance.pruned_expired > 0 || maintenance.pruned_consumed > 0 {
-            rewrite_allow_once_records(&mut file, &active)?;
+        if !maintenance.is_empty() {
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
         let idx = active
             .iter()
-            .position(|entry| entry.command_raw == command && entry.matches_scope(cwd));
+            .position(|entry| entry.matches_scope(cwd) && matches(entry));
 
         let Some(idx) = idx else {
             return Ok(None);
         };
 
-        if !active[idx].force_allow_config {
-            return Ok(None);
-        }
-
         let mut selected = active[idx].clone();
         if active[idx].single_use {
-            selected.consumed_at = Some(format_timestamp(now));
+            selected.consumed_at = Some(storage::format_timestamp(now));
             active.remove(idx);
-            rewrite_allow_once_records(&mut file, &active)?;
+            rewrite_records(&self.path, &active, maintenance)?;
         }
 
         if let Some(audit) = allow_once_audit {
@@ -670,18 +725,14 @@ pub fn log_maintenance(
         PathBuf::from(log_file)
     };
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-    let timestamp = format_timestamp(Utc::now());
-    writeln!(
-        file,
-        "[{timestamp}] [pending-exceptions] {context}: pruned_expired={}, pruned_consumed={}, parse_errors={}",
-        maintenance.pruned_expired, maintenance.pruned_consumed, maintenance.parse_errors
-    )?;
-    Ok(())
+    let timestamp = storage::format_timestamp(Utc::now());
+    storage::append(
+        &path,
+        &format!(
+            "[{timestamp}] [pending-exceptions] {context}: pruned_expired={}, pruned_consumed={}, parse_errors={}\n",
+            maintenance.pruned_expired, maintenance.pruned_consumed, maintenance.parse_errors
+        ),
+    )
 }
 
 /// Log an allow-once management action (best-effort).
@@ -701,20 +752,21 @@ pub fn log_allow_once_action(log_file: &str, action: &str, details: &str) -> io:
         PathBuf::from(log_file)
     };
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-    let timestamp = format_timestamp(Utc::now());
-    writeln!(file, "[{timestamp}] [allow-once] {action}: {details}")?;
-    Ok(())
+    let timestamp = storage::format_timestamp(Utc::now());
+    storage::append(
+        &path,
+        &format!("[{timestamp}] [allow-once] {action}: {details}\n"),
+    )
 }
 
 // ============================================================================
 // Structured Allow-Once Logging
 // ============================================================================
 
+fn current_identity() -> Option<String> {
+    crate::identity::Identity::resolve().map(|identity| identity.name)
+}
+
 /// Runtime configuration for allow-once audit logging.
 ///
 /// This is passed down from hook/CLI code so pending/allow-once store maintenance can emit
@@ -786,6 +838,9 @@ pub struct AllowOnceLogEntry {
     /// Scope kind (cwd or project).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope_kind: Option<String>,
+    /// What the entry grants (exact, rule, or session).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grant: Option<String>,
     /// Whether this was a single-use exception.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub single_use: Option<bool>,
@@ -795,6 +850,9 @@ pub struct AllowOnceLogEntry {
     /// Match source (pack, `config_override`, etc.).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// Person behind a shared service account (see [`crate::identity`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
 }
 
 impl AllowOnceLogEntry {
@@ -802,7 +860,7 @@ impl AllowOnceLogEntry {
     #[must_use]
     pub fn code_issued(record: &PendingExceptionRecord, redaction: &RedactionConfig) -> Self {
         Self {
-            timestamp: format_timestamp(Utc::now()),
+            timestamp: storage::format_timestamp(Utc::now()),
             event: AllowOnceEventKind::CodeIssued.label().to_string(),
             short_code: record.short_code.clone(),
             full_hash: record.full_hash.clone(),
@@ -812,9 +870,11 @@ impl AllowOnceLogEntry {
             reason: Some(record.reason.clone()),
             allowlist_layer: None,
             scope_kind: None,
+            grant: None,
             single_use: Some(record.single_use),
             force_allow_config: None,
             source: record.source.clone(),
+            identity: current_identity(),
         }
     }
 
@@ -822,7 +882,7 @@ impl AllowOnceLogEntry {
     #[must_use]
     pub fn code_resolved(entry: &AllowOnceEntry, redaction: &RedactionConfig) -> Self {
         Self {
-            timestamp: format_timestamp(Utc::now()),
+            timestamp: storage::format_timestamp(Utc::now()),
             event: AllowOnceEventKind::CodeResolved.label().to_string(),
             short_code: entry.source_short_code.clone(),
             full_hash: entry.source_full_hash.clone(),
@@ -832,9 +892,11 @@ impl AllowOnceLogEntry {
             reason: Some(entry.reason.clone()),
             allowlist_layer: None,
             scope_kind: Some(format!("{:?}", entry.scope_kind).to_lowercase()),
+            grant: Some(entry.grant.label().to_string()),
             single_use: Some(entry.single_use),
             force_allow_config: Some(entry.force_allow_config),
             source: None,
+            identity: current_identity(),
         }
     }
 
@@ -847,7 +909,7 @@ impl AllowOnceLogEntry {
         cwd: &str,
     ) -> Self {
         Self {
-            timestamp: format_timestamp(Utc::now()),
+            timestamp: storage::format_timestamp(Utc::now()),
             event: AllowOnceEventKind::AllowGranted.label().to_string(),
             short_code: entry.source_short_code.clone(),
             full_hash: entry.source_full_hash.clone(),
@@ -857,9 +919,11 @@ impl AllowOnceLogEntry {
             reason: None,
             allowlist_layer: Some(layer.to_string()),
             scope_kind: Some(format!("{:?}", entry.scope_kind).to_lowercase()),
+            grant: Some(entry.grant.label().to_string()),
             single_use: Some(entry.single_use),
             force_allow_config: Some(entry.force_allow_config),
             source: None,
+            identity: current_identity(),
         }
     }
 
@@ -867,7 +931,7 @@ impl AllowOnceLogEntry {
     #[must_use]
     pub fn entry_consumed(entry: &AllowOnceEntry, redaction: &RedactionConfig, cwd: &str) -> Self {
         Self {
-            timestamp: format_timestamp(Utc::now()),
+            timestamp: storage::format_timestamp(Utc::now()),
             event: AllowOnceEventKind::EntryConsumed.label().to_string(),
             short_code: entry.source_short_code.clone(),
             full_hash: entry.source_full_hash.clone(),
@@ -877,9 +941,11 @@ impl AllowOnceLogEntry {
             reason: None,
             allowlist_layer: None,
             scope_kind: Some(format!("{:?}", entry.scope_kind).to_lowercase()),
+            grant: Some(entry.grant.label().to_string()),
             single_use: Some(true),
             force_allow_config: None,
             source: None,
+            identity: current_identity(),
         }
     }
 
@@ -894,7 +960,7 @@ impl AllowOnceLogEntry {
         redaction: &RedactionConfig,
     ) -> Self {
         Self {
-            timestamp: format_timestamp(Utc::now()),
+            timestamp: storage::format_timestamp(Utc::now()),
             event: AllowOnceEventKind::EntryExpired.label().to_string(),
             short_code: short_code.to_string(),
             full_hash: full_hash.to_string(),
@@ -904,9 +970,11 @@ impl AllowOnceLogEntry {
             reason: None,
             allowlist_layer: None,
             scope_kind: None,
+            grant: None,
             single_use: None,
             force_allow_config: None,
             source: None,
+            identity: current_identity(),
         }
     }
 
@@ -929,6 +997,12 @@ impl AllowOnceLogEntry {
         if let Some(ref layer) = self.allowlist_layer {
             parts.push(format!("layer={layer}"));
         }
+        if let Some(ref grant) = self.grant {
+            parts.push(format!("grant={grant}"));
+        }
+        if let Some(ref identity) = self.identity {
+            parts.push(format!("identity={identity}"));
+        }
         if let Some(single) = self.single_use {
             if single {
                 parts.push("single_use=true".to_string());
@@ -975,19 +1049,13 @@ pub fn log_allow_once_event(
 ) -> io::Result<()> {
     let path = expand_log_path(log_file);
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-
-    let line = match format {
+    let mut line = match format {
         AllowOnceLogFormat::Text => entry.format_text(),
         AllowOnceLogFormat::Json => entry.format_json(),
     };
+    line.push('\n');
 
-    writeln!(file, "{line}")?;
-    Ok(())
+    storage::append(&path, &line)
 }
 
 /// Log a code issuance event (when a command is blocked and a short code is generated).
@@ -1065,42 +1133,23 @@ fn expand_log_path(log_file: &str) -> PathBuf {
 }
 
 fn redact_for_log(command: &str, redaction: &RedactionConfig) -> String {
-    if !redaction.enabled {
-        return command.to_string();
-    }
     redact_command(command, redaction)
 }
 
-fn open_locked(path: &Path) -> io::Result<File> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .truncate(false)
-        .open(path)?;
-    file.lock_exclusive()?;
-    Ok(file)
+fn open_locked(path: &Path) -> io::Result<StateLock> {
+    StateLock::acquire(path)
 }
 
-fn load_active_from_file(
-    file: &mut File,
-    now: DateTime<Utc>,
-    allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
-) -> (Vec<PendingExceptionRecord>, PendingMaintenance) {
-    let mut maintenance = PendingMaintenance::default();
-    let mut active: Vec<PendingExceptionRecord> = Vec::new();
-
-    if file.seek(SeekFrom::Start(0)).is_err() {
-        maintenance.parse_errors += 1;
-        return (active, maintenance);
-    }
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let Ok(line) = line else {
+/// Parse each line of the JSONL store at `path`, counting lines that are not
+/// valid records.
+fn read_jsonl<T: DeserializeOwned>(
+    path: &Path,
+    maintenance: &mut PendingMaintenance,
+) -> io::Result<Vec<T>> {
+    let bytes = storage::read(path)?;
+    let mut records = Vec::new();
+    for line in bytes.split(|byte| *byte == b'\n') {
+        let Ok(line) = std::str::from_utf8(line) else {
             maintenance.parse_errors += 1;
             continue;
         };
@@ -1108,12 +1157,23 @@ fn load_active_from_file(
         if trimmed.is_empty() {
             continue;
         }
+        match serde_json::from_str::<T>(trimmed) {
+            Ok(record) => records.push(record),
+            Err(_) => maintenance.parse_errors += 1,
+        }
+    }
+    Ok(records)
+}
 
-        let Ok(record) = serde_json::from_str::<PendingExceptionRecord>(trimmed) else {
-            maintenance.parse_errors += 1;
-            continue;
-        };
+fn load_active_from_file(
+    path: &Path,
+    now: DateTime<Utc>,
+    allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
+) -> io::Result<(Vec<PendingExceptionRecord>, PendingMaintenance)> {
+    let mut maintenance = PendingMaintenance::default();
+    let mut active: Vec<PendingExceptionRecord> = Vec::new();
 
+    for record in read_jsonl::<PendingExceptionRecord>(path, &mut maintenance)? {
         if record.is_consumed() {
             maintenance.pruned_consumed += 1;
             continue;
@@ -1138,38 +1198,18 @@ fn load_active_from_file(
         active.push(record);
     }
 
-    (active, maintenance)
+    Ok((active, maintenance))
 }
 
 fn load_allow_once_from_file(
-    file: &mut File,
+    path: &Path,
     now: DateTime<Utc>,
     allow_once_audit: Option<&AllowOnceAuditConfig<'_>>,
-) -> (Vec<AllowOnceEntry>, PendingMaintenance) {
+) -> io::Result<(Vec<AllowOnceEntry>, PendingMaintenance)> {
     let mut maintenance = PendingMaintenance::default();
     let mut active: Vec<AllowOnceEntry> = Vec::new();
 
-    if file.seek(SeekFrom::Start(0)).is_err() {
-        maintenance.parse_errors += 1;
-        return (active, maintenance);
-    }
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let Ok(line) = line else {
-            maintenance.parse_errors += 1;
-            continue;
-        };
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        let Ok(record) = serde_json::from_str::<AllowOnceEntry>(trimmed) else {
-            maintenance.parse_errors += 1;
-            continue;
-        };
-
+    for record in read_jsonl::<AllowOnceEntry>(path, &mut maintenance)? {
         if record.is_consumed() {
             maintenance.pruned_consumed += 1;
             continue;
@@ -1194,49 +1234,31 @@ fn load_allow_once_from_file(
         active.push(record);
     }
 
-    (active, maintenance)
+    Ok((active, maintenance))
 }
 
-fn rewrite_records(file: &mut File, records: &[PendingExceptionRecord]) -> io::Result<()> {
-    file.set_len(0)?;
-    file.seek(SeekFrom::Start(0))?;
-    for record in records {
-        let line = serde_json::to_string(record).map_err(io::Error::other)?;
-        file.write_all(line.as_bytes())?;
-        file.write_all(b"\n")?;
+/// Atomically replace the store with `records`. A store with unparseable
+/// lines is quarantined first so the damaged original can be inspected.
+fn rewrite_records<T: Serialize>(
+    path: &Path,
+    records: &[T],
+    maintenance: PendingMaintenance,
+) -> io::Result<()> {
+    if maintenance.parse_errors > 0 && path.exists() {
+        storage::quarantine(path)?;
     }
-    file.sync_data()?;
-    Ok(())
-}
-
-fn rewrite_allow_once_records(file: &mut File, records: &[AllowOnceEntry]) -> io::Result<()> {
-    file.set_len(0)?;
-    file.seek(SeekFrom::Start(0))?;
+    let mut contents = String::new();
     for record in records {
-        let line = serde_json::to_string(record).map_err(io::Error::other)?;
-        file.write_all(line.as_bytes())?;
-        file.write_all(b"\n")?;
+        contents.push_str(&serde_json::to_string(record).map_err(io::Error::other)?);
+        contents.push('\n');
     }
-    file.sync_data()?;
-    Ok(())
-}
-
-fn append_record(file: &mut File, record: &PendingExceptionRecord) -> io::Result<()> {
-    file.seek(SeekFrom::End(0))?;
-    let line = serde_json::to_string(record).map_err(io::Error::other)?;
-    file.write_all(line.as_bytes())?;
-    file.write_all(b"\n")?;
-    file.sync_data()?;
-    Ok(())
+    storage::write_atomic(path, contents.as_bytes())
 }
 
-fn append_allow_once_record(file: &mut File, record: &AllowOnceEntry) -> io::Result<()> {
-    file.seek(SeekFrom::End(0))?;
-    let line = serde_json::to_string(record).map_err(io::Error::other)?;
-    file.write_all(line.as_bytes())?;
-    file.write_all(b"\n")?;
-    file.sync_data()?;
-    Ok(())
+fn append_record<T: Serialize>(path: &Path, record: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+    line.push('\n');
+    storage::append(path, &line)
 }
 
 fn is_expired(expires_at: &str, now: DateTime<Utc>) -> bool {
@@ -1248,10 +1270,6 @@ fn is_expired(expires_at: &str, now: DateTime<Utc>) -> bool {
     true
 }
 
-fn format_timestamp(timestamp: DateTime<Utc>) -> String {
-    timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string()
-}
-
 /// Type alias for HMAC-SHA256.
 type HmacSha256 = Hmac<Sha256>;
 
@@ -1383,7 +1401,7 @@ mod tests {
 
         let mut active =
             PendingExceptionRecord::new(now, "/repo", "git status", "ok", &redaction, false, None);
-        active.expires_at = format_timestamp(now + Duration::hours(1));
+        active.expires_at = storage::format_timestamp(now + Duration::hours(1));
 
         let mut expired = PendingExceptionRecord::new(
             now - Duration::hours(30),
@@ -1394,7 +1412,7 @@ mod tests {
             false,
             None,
         );
-        expired.expires_at = format_timestamp(now - Duration::hours(1));
+        expired.expires_at = storage::format_timestamp(now - Duration::hours(1));
 
         let mut consumed = PendingExceptionRecord::new(
             now,
@@ -1405,7 +1423,7 @@ mod tests {
             true,
             None,
         );
-        consumed.consumed_at = Some(format_timestamp(now));
+        consumed.consumed_at = Some(storage::format_timestamp(now));
 
         let contents = format!(
             "{}\n{}\n{}\n",
@@ -1426,7 +1444,7 @@ mod tests {
 
     #[test]
     fn test_skips_corrupt_lines() {
-        let (store, _dir) = make_store();
+        let (store, dir) = make_store();
         let now = DateTime::parse_from_rfc3339("2026-01-10T06:30:00Z")
             .unwrap()
             .with_timezone(&Utc);
@@ -1446,6 +1464,54 @@ mod tests {
         let (records, maintenance) = store.load_active(now).unwrap();
         assert_eq!(records.len(), 1);
         assert_eq!(maintenance.parse_errors, 1);
+
+        // The damaged file is kept aside and the store recreated without it.
+        let rewritten = std::fs::read_to_string(store.path()).unwrap();
+        assert_eq!(rewritten.lines().count(), 1);
+        let quarantined: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(storage::QUARANTINE_INFIX))
+            .collect();
+        assert_eq!(quarantined.len(), 1);
+        assert!(
+            std::fs::read_to_string(&quarantined[0])
+                .unwrap()
+                .starts_with("not-json\n")
+        );
+    }
+
+    #[test]
+    fn test_parallel_record_block_keeps_every_record() {
+        let (store, _dir) = make_store();
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for j in 0..5 {
+                        store
+                            .record_block(
+                                &format!("rm -rf /tmp/{i}-{j}"),
+                                "/repo",
+                                "blocked",
+                                &redaction_config(),
+                                false,
+                                None,
+                                None,
+                                None,
+                            )
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let (records, maintenance) = store.preview_active(Utc::now()).unwrap();
+        assert_eq!(records.len(), 40);
+        assert_eq!(maintenance.parse_errors, 0);
     }
 
     #[test]
@@ -1506,10 +1572,14 @@ mod tests {
         store.add_entry(&entry, now).unwrap();
 
         let cwd = Path::new("/repo");
-        let first = store.match_command("git status", cwd, now, None).unwrap();
+        let first = store
+            .match_command("git status", None, cwd, now, None)
+            .unwrap();
         assert!(first.is_some());
 
-        let second = store.match_command("git status", cwd, now, None).unwrap();
+        let second = store
+            .match_command("git status", None, cwd, now, None)
+            .unwrap();
         assert!(second.is_none());
     }
 
@@ -1539,7 +1609,9 @@ mod tests {
         store.add_entry(&entry, now).unwrap();
 
         let cwd = Path::new("/repo/subdir");
-        let matched = store.match_command("git status", cwd, now, None).unwrap();
+        let matched = store
+            .match_command("git status", None, cwd, now, None)
+            .unwrap();
         assert!(matched.is_some());
     }
 
@@ -1585,13 +1657,13 @@ mod tests {
         let cwd = Path::new("/repo");
         assert!(
             store
-                .match_command("git status", cwd, now, None)
+                .match_command("git status", None, cwd, now, None)
                 .unwrap()
                 .is_some()
         );
         assert!(
             store
-                .match_command("git status", cwd, now, None)
+                .match_command("git status", None, cwd, now, None)
                 .unwrap()
                 .is_some()
         );
@@ -1626,12 +1698,102 @@ mod tests {
         let cwd = Path::new("/different");
         assert!(
             store
-                .match_command("git status", cwd, now, None)
+                .match_command("git status", None, cwd, now, None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_allow_once_rule_grant_matches_rule_not_command() {
+        let dir = TempDir::new().expect("tempdir");
+        let store = AllowOnceStore::new(dir.path().join("allow_once.jsonl"));
+        let now = DateTime::parse_from_rfc3339("2026-01-10T06:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let redaction = redaction_config();
+
+        let mut pending = PendingExceptionRecord::new(
+            now,
+            "/repo",
+            "git reset --hard",
+            "core.git:reset-hard - destroys work",
+            &redaction,
+            false,
+            None,
+        );
+        pending.rule_id = Some("core.git:reset-hard".to_string());
+        let entry = AllowOnceEntry::from_pending(
+            &pending,
+            now,
+            AllowOnceScopeKind::Project,
+            "/repo",
+            false,
+            false,
+            &redaction,
+        )
+        .with_grant(AllowOnceGrant::Rule);
+        store.add_entry(&entry, now).unwrap();
+
+        let cwd = Path::new("/repo/src");
+        assert!(
+            store
+                .match_rule("core.git:reset-hard", cwd, now, None)
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            store
+                .match_rule("core.git:clean-force", cwd, now, None)
+                .unwrap()
+                .is_none()
+        );
+        // The grant is decided by the blocking rule, never by the command text.
+        assert!(
+            store
+                .match_command("git reset --hard", None, cwd, now, None)
                 .unwrap()
                 .is_none()
         );
     }
 
+    #[test]
+    fn test_allow_once_session_grant_requires_same_session() {
+        let now = DateTime::parse_from_rfc3339("2026-01-10T06:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let redaction = redaction_config();
+
+        let mut pending =
+            PendingExceptionRecord::new(now, "/repo", "git status", "ok", &redaction, false, None);
+        pending.session_id = Some("sess-1".to_string());
+        let entry = AllowOnceEntry::from_pending(
+            &pending,
+            now,
+            AllowOnceScopeKind::Cwd,
+            "/repo",
+            false,
+            false,
+            &redaction,
+        );
+
+        assert!(entry.matches_command("git status", None));
+        let entry = entry.with_grant(AllowOnceGrant::Session);
+        assert!(entry.matches_command("git status", Some("sess-1")));
+        assert!(!entry.matches_command("git status", Some("sess-2")));
+        assert!(!entry.matches_command("git status", None));
+        assert!(!entry.matches_command("git log", Some("sess-1")));
+    }
+
+    #[test]
+    fn test_allow_once_entry_without_grant_defaults_to_exact() {
+        let line = r#"{"schema_version":1,"source_short_code":"12345","source_full_hash":"ab","created_at":"2026-01-10T06:30:00Z","expires_at":"2026-01-11T06:30:00Z","scope_kind":"cwd","scope_path":"/repo","command_raw":"git status","command_redacted":"git status","reason":"ok","consumed_at":null}"#;
+        let entry: AllowOnceEntry = serde_json::from_str(line).unwrap();
+        assert_eq!(entry.grant, AllowOnceGrant::Exact);
+        assert!(entry.rule_id.is_none());
+        assert!(entry.matches_command("git status", None));
+    }
+
     #[test]
     fn test_allow_once_load_active_prunes_expired_and_consumed() {
         let dir = TempDir::new().expect("tempdir");
@@ -1664,7 +1826,7 @@ mod tests {
             false,
             &redaction,
         );
-        expired.expires_at = format_timestamp(now - Duration::hours(1));
+        expired.expires_at = storage::format_timestamp(now - Duration::hours(1));
 
         let mut consumed = AllowOnceEntry::from_pending(
             &pending,
@@ -1675,7 +1837,7 @@ mod tests {
             false,
             &redaction,
         );
-        consumed.consumed_at = Some(format_timestamp(now));
+        consumed.consumed_at = Some(storage::format_timestamp(now));
 
         let contents = format!(
             "{}\n{}\n{}\n",
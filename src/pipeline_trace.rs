@@ -0,0 +1,377 @@
+//! Full pipeline trace written by `--trace-file`.
+//!
+//! Where `dcg explain` summarizes a decision, this dumps every stage a bug
+//! reporter's command went through so a maintainer can reproduce the decision
+//! from one artifact: the raw input, each normalization, the pack order, keyword
+//! gating and per-pattern timing for every pack, the allowlist lookups, and the
+//! final decision.
+//!
+//! The decision is the one the evaluator actually made; the stages are replayed
+//! next to it. Pattern timings come from that replay and only cover the pack
+//! regexes (structural checks such as `rm` argument parsing are not itemized).
+
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::allowlist::{AllowlistHit, LayeredAllowlist};
+use crate::evaluator::{EvaluationDecision, EvaluationResult};
+use crate::packs::{DecisionMode, Pack, REGISTRY};
+
+/// JSON schema version of the trace file.
+pub const TRACE_FILE_SCHEMA_VERSION: u32 = 1;
+
+/// What was evaluated and with which configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceInput<'a> {
+    /// `"hook"` or `"test"`.
+    pub source: &'static str,
+    /// Input as received (hook JSON, or the command for `dcg test`).
+    pub raw_input: &'a str,
+    /// Command extracted from the input.
+    pub command: &'a str,
+    pub enabled_keywords: &'a [&'a str],
+    pub ordered_packs: &'a [String],
+    pub allowlists: &'a LayeredAllowlist,
+}
+
+/// The trace file contents.
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineTrace {
+    pub schema_version: u32,
+    pub dcg_version: &'static str,
+    pub generated_at: String,
+    pub source: &'static str,
+    pub raw_input: String,
+    pub command: String,
+    pub normalizations: Vec<NormalizationStep>,
+    /// Whether no enabled keyword occurs in the command, so packs are skipped.
+    pub quick_rejected: bool,
+    /// Enabled packs in evaluation order.
+    pub pack_order: Vec<String>,
+    pub packs: Vec<PackTrace>,
+    pub allowlist_checks: Vec<AllowlistCheck>,
+    pub decision: DecisionTrace,
+    /// Wall time of the real evaluation, in microseconds.
+    pub evaluation_us: u64,
+}
+
+/// One normalization stage and its output.
+#[derive(Debug, Clone, Serialize)]
+pub struct NormalizationStep {
+    pub stage: &'static str,
+    pub changed: bool,
+    pub output: String,
+}
+
+/// Keyword gating and pattern results for one pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackTrace {
+    pub id: String,
+    pub external: bool,
+    /// Whether one of the pack's keywords occurs in the command.
+    pub keyword_match: bool,
+    pub keyword_ns: u64,
+    pub safe_patterns: Vec<PatternTrace>,
+    /// Empty when keyword gating or a safe pattern skipped the pack.
+    pub destructive_patterns: Vec<PatternTrace>,
+}
+
+/// One pattern and how long matching it took.
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternTrace {
+    pub name: String,
+    pub matched: bool,
+    pub duration_ns: u64,
+}
+
+/// One allowlist lookup.
+#[derive(Debug, Clone, Serialize)]
+pub struct AllowlistCheck {
+    /// `"exact_command"`, `"command_prefix"`, or `"rule"`.
+    pub kind: &'static str,
+    /// Command or rule ID looked up.
+    pub subject: String,
+    pub hit: Option<AllowlistCheckHit>,
+}
+
+/// The entry an allowlist lookup found.
+#[derive(Debug, Clone, Serialize)]
+pub struct AllowlistCheckHit {
+    pub layer: &'static str,
+    pub path: String,
+    pub reason: String,
+}
+
+/// The decision the evaluator made.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecisionTrace {
+    /// `"allow"` or `"deny"`.
+    pub decision: &'static str,
+    /// How a deny is handled (`"deny"`, `"warn"`, `"log"`).
+    pub mode: Option<&'static str>,
+    pub rule_id: Option<String>,
+    pub reason: Option<String>,
+    pub source: Option<String>,
+    pub other_rules: Vec<String>,
+    pub allowlist_layer: Option<&'static str>,
+    pub allowlist_entry: Option<String>,
+    pub skipped_due_to_budget: bool,
+}
+
+/// Replay the pipeline for `input` next to the evaluator's `result`.
+#[must_use]
+pub fn build(
+    input: &TraceInput<'_>,
+    result: &EvaluationResult,
+    mode: Option<DecisionMode>,
+    evaluation: Duration,
+) -> PipelineTrace {
+    let command = input.command;
+    let mut normalizations = Vec::new();
+    let mut step = |stage, before: &str, output: &str| {
+        normalizations.push(NormalizationStep {
+            stage,
+            changed: before != output,
+            output: output.to_string(),
+        });
+    };
+
+    let folded = crate::normalize::fold_confusables(command).map(|folded| folded.text);
+    let folded = folded.as_deref().unwrap_or(command);
+    step("fold_confusables", command, folded);
+    let sanitized = crate::context::sanitize_for_pattern_matching(folded);
+    step("sanitize", folded, &sanitized);
+    let (quick_rejected, _) =
+        crate::packs::pack_aware_quick_reject_with_normalized(&sanitized, input.enabled_keywords);
+    let normalized = crate::normalize::normalize_command(&sanitized);
+    step("normalize", &sanitized, &normalized);
+    let masked = crate::heredoc::mask_non_executing_heredocs(&normalized);
+    step("mask_heredocs", &normalized, &masked);
+
+    let mut allowlist_checks = vec![
+        AllowlistCheck {
+            kind: "exact_command",
+            subject: normalized.to_string(),
+            hit: input
+                .allowlists
+                .match_exact_command_at_path(&normalized, None)
+                .map(|hit| check_hit(&hit)),
+        },
+        AllowlistCheck {
+            kind: "command_prefix",
+            subject: normalized.to_string(),
+            hit: input
+                .allowlists
+                .match_command_prefix_at_path(&normalized, None)
+                .map(|hit| check_hit(&hit)),
+        },
+    ];
+
+    let mut packs = Vec::new();
+    for pack_id in input.ordered_packs {
+        let (pack, external) = match REGISTRY.get_entry(pack_id) {
+            Some(entry) => (entry.get_pack(), false),
+            None => match crate::packs::get_external_packs().and_then(|store| store.get(pack_id)) {
+                Some(pack) => (pack, true),
+                None => continue,
+            },
+        };
+        let haystack = if pack.match_raw { folded } else { &masked };
+        let trace = trace_pack(pack_id, pack, external, haystack);
+        for pattern in trace.destructive_patterns.iter().filter(|p| p.matched) {
+            allowlist_checks.push(AllowlistCheck {
+                kind: "rule",
+                subject: format!("{pack_id}:{}", pattern.name),
+                hit: input
+                    .allowlists
+                    .match_rule_for_command(pack_id, &pattern.name, Some(&normalized), None)
+                    .map(|hit| check_hit(&hit)),
+            });
+        }
+        packs.push(trace);
+    }
+
+    PipelineTrace {
+        schema_version: TRACE_FILE_SCHEMA_VERSION,
+        dcg_version: env!("CARGO_PKG_VERSION"),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        source: input.source,
+        raw_input: input.raw_input.to_string(),
+        command: command.to_string(),
+        normalizations,
+        quick_rejected,
+        pack_order: input.ordered_packs.to_vec(),
+        packs,
+        allowlist_checks,
+        decision: decision_trace(result, mode),
+        evaluation_us: u64::try_from(evaluation.as_micros()).unwrap_or(u64::MAX),
+    }
+}
+
+/// Write `trace` to `path` as pretty-printed JSON.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write(path: &Path, trace: &PipelineTrace) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(trace).map_err(io::Error::other)?;
+    std::fs::write(path, json + "\n")
+}
+
+/// Write `trace` to `out` (stderr for `DCG_TRACE`) as pretty-printed JSON.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_to(out: &mut impl io::Write, trace: &PipelineTrace) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(trace).map_err(io::Error::other)?;
+    writeln!(out, "{json}")
+}
+
+fn trace_pack(pack_id: &str, pack: &Pack, external: bool, haystack: &str) -> PackTrace {
+    let start = Instant::now();
+    let keyword_match = pack.might_match(haystack);
+    let keyword_ns = elapsed_ns(start);
+
+    let mut trace = PackTrace {
+        id: pack_id.to_string(),
+        external,
+        keyword_match,
+        keyword_ns,
+        safe_patterns: Vec::new(),
+        destructive_patterns: Vec::new(),
+    };
+    if !keyword_match {
+        return trace;
+    }
+
+    for pattern in &pack.safe_patterns {
+        let start = Instant::now();
+        let matched = pattern.regex.is_match(haystack);
+        trace.safe_patterns.push(PatternTrace {
+            name: pattern.name.to_string(),
+            matched,
+            duration_ns: elapsed_ns(start),
+        });
+    }
+    if trace.safe_patterns.iter().any(|p| p.matched) {
+        return trace;
+    }
+
+    for pattern in &pack.destructive_patterns {
+        let start = Instant::now();
+        let matched = pattern.regex.is_match(haystack);
+        trace.destructive_patterns.push(PatternTrace {
+            name: pattern.name.unwrap_or("unnamed").to_string(),
+            matched,
+            duration_ns: elapsed_ns(start),
+        });
+    }
+    trace
+}
+
+fn check_hit(hit: &AllowlistHit<'_>) -> AllowlistCheckHit {
+    AllowlistCheckHit {
+        layer: hit.layer.label(),
+        path: hit.path.display().to_string(),
+        reason: hit.entry.reason.clone(),
+    }
+}
+
+fn decision_trace(result: &EvaluationResult, mode: Option<DecisionMode>) -> DecisionTrace {
+    let rule_id = |info: &crate::evaluator::PatternMatch| match (&info.pack_id, &info.pattern_name)
+    {
+        (Some(pack), Some(name)) => Some(format!("{pack}:{name}")),
+        (Some(pack), None) => Some(pack.clone()),
+        _ => None,
+    };
+    let info = result.pattern_info.as_ref();
+    DecisionTrace {
+        decision: match result.decision {
+            EvaluationDecision::Allow => "allow",
+            EvaluationDecision::Deny => "deny",
+        },
+        mode: mode.map(|mode| mode.label()),
+        rule_id: info.and_then(rule_id),
+        reason: info.map(|info| info.reason.clone()),
+        source: info.map(|info| format!("{:?}", info.source)),
+        other_rules: result.other_matches.iter().filter_map(rule_id).collect(),
+        allowlist_layer: result
+            .allowlist_override
+            .as_ref()
+            .map(|allowed| allowed.layer.label()),
+        allowlist_entry: result.allowlist_entry.clone(),
+        skipped_due_to_budget: result.skipped_due_to_budget,
+    }
+}
+
+fn elapsed_ns(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn trace_records_stages_patterns_and_decision() {
+        let config = Config::default();
+        let enabled = config.enabled_pack_ids();
+        let keywords = REGISTRY.collect_enabled_keywords(&enabled);
+        let ordered = REGISTRY.expand_enabled_ordered(&enabled);
+        let allowlists = LayeredAllowlist::default();
+        let command = "sudo git reset --hard";
+        let result = crate::evaluator::evaluate_command_with_pack_order(
+            command,
+            &keywords,
+            &ordered,
+            None,
+            &config.compile_overrides(),
+            &allowlists,
+            &config.heredoc_settings(),
+        );
+
+        let input = TraceInput {
+            source: "test",
+            raw_input: command,
+            command,
+            enabled_keywords: &keywords,
+            ordered_packs: &ordered,
+            allowlists: &allowlists,
+        };
+        let trace = build(&input, &result, Some(DecisionMode::Deny), Duration::ZERO);
+
+        assert_eq!(trace.decision.decision, "deny");
+        assert_eq!(
+            trace.decision.rule_id.as_deref(),
+            Some("core.git:reset-hard")
+        );
+        let normalize = trace
+            .normalizations
+            .iter()
+            .find(|step| step.stage == "normalize")
+            .unwrap();
+        assert!(normalize.changed);
+        assert_eq!(normalize.output, "git reset --hard");
+        let git = trace.packs.iter().find(|p| p.id == "core.git").unwrap();
+        assert!(git.keyword_match);
+        assert!(
+            git.destructive_patterns
+                .iter()
+                .any(|p| p.name == "reset-hard" && p.matched)
+        );
+        assert!(
+            trace
+                .allowlist_checks
+                .iter()
+                .any(|check| check.kind == "rule" && check.subject == "core.git:reset-hard")
+        );
+
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["schema_version"], TRACE_FILE_SCHEMA_VERSION);
+    }
+}
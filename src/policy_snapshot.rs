@@ -0,0 +1,446 @@
+//! Canonical snapshots of the effective policy, and the drift between two.
+//!
+//! `dcg policy export` captures what a host enforces: enabled packs and their
+//! versions, `[policy]` modes, `[overrides]`, canary and protected-branch
+//! settings, and every allowlist layer. It is written as JSON with sorted keys
+//! and no order-dependent arrays, so two hosts with the same policy produce
+//! the same `policy` object byte for byte. `dcg policy diff a.json b.json`
+//! compares two snapshots leaf by leaf, which lets ops check a fleet for
+//! drift by diffing each host's export against a reference.
+//!
+//! Arrays are canonicalized as sets: arrays of scalars are sorted and
+//! deduplicated, and arrays that hold tables (such as `[[overrides.block]]`)
+//! become objects keyed by each table's `pattern` (or its compact JSON).
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::allowlist::LayeredAllowlist;
+use crate::config::Config;
+
+/// Version of the snapshot format; bumped when the `policy` layout changes.
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// Allowlist entry fields that record who added an entry and when, not what
+/// it allows; left out of snapshots.
+const ALLOWLIST_AUDIT_FIELDS: &[&str] = &["added_by", "added_at"];
+
+/// Where and when a snapshot was taken. Not compared by [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSource {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub dcg_version: String,
+    pub generated_at: String,
+    /// Config files that contributed, in merge order.
+    #[serde(default)]
+    pub config_files: Vec<String>,
+}
+
+/// The effective policy of one host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicySnapshot {
+    pub schema_version: u32,
+    pub source: SnapshotSource,
+    /// Canonical policy tree; see the module docs.
+    pub policy: Value,
+}
+
+impl PolicySnapshot {
+    /// Capture the policy `config` and `allowlists` enforce.
+    #[must_use]
+    pub fn capture(config: &Config, allowlists: &LayeredAllowlist) -> Self {
+        crate::packs::load_external_packs(&config.packs.expand_custom_paths());
+        let packs: Map<String, Value> = config
+            .enabled_pack_ids()
+            .into_iter()
+            .map(|id| {
+                let version = crate::packs::installed_pack_version(&id);
+                (id, version.map_or(Value::Null, Value::from))
+            })
+            .collect();
+
+        let mut policy = Map::new();
+        policy.insert("packs".to_string(), Value::Object(packs));
+        policy.insert("modes".to_string(), to_json(&config.policy));
+        policy.insert("overrides".to_string(), to_json(&config.overrides));
+        policy.insert(
+            "canary".to_string(),
+            serde_json::json!({
+                "paths": config.canary.paths,
+                "names": config.canary.names,
+            }),
+        );
+        policy.insert("git_awareness".to_string(), to_json(&config.git_awareness));
+        policy.insert("allowlists".to_string(), allowlist_layers(allowlists));
+
+        Self {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            source: SnapshotSource {
+                host: crate::identity::host_name().map(str::to_string),
+                dcg_version: env!("CARGO_PKG_VERSION").to_string(),
+                generated_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                config_files: config
+                    .sources
+                    .iter()
+                    .filter(|source| source.applied)
+                    .map(|source| source.location.clone())
+                    .collect(),
+            },
+            policy: canonicalize(Value::Object(policy)),
+        }
+    }
+
+    /// Parse an exported snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `content` is not a snapshot or was written by a
+    /// newer dcg.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let snapshot: Self =
+            serde_json::from_str(content).map_err(|e| format!("not a policy snapshot: {e}"))?;
+        if snapshot.schema_version > SNAPSHOT_SCHEMA_VERSION {
+            return Err(format!(
+                "snapshot schema_version {} is newer than this dcg supports ({SNAPSHOT_SCHEMA_VERSION})",
+                snapshot.schema_version
+            ));
+        }
+        Ok(Self {
+            policy: canonicalize(snapshot.policy),
+            ..snapshot
+        })
+    }
+
+    /// The snapshot as pretty JSON with a trailing newline.
+    #[must_use]
+    pub fn to_json_string(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+}
+
+/// One leaf that differs between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyChange {
+    /// Path of the leaf, e.g. `modes.rules["core.git:reset-hard"]`.
+    pub path: String,
+    /// Value in the first snapshot (`None`: not set there).
+    pub before: Option<Value>,
+    /// Value in the second snapshot (`None`: not set there).
+    pub after: Option<Value>,
+}
+
+impl PolicyChange {
+    /// `+` for added, `-` for removed, `~` for changed.
+    #[must_use]
+    pub const fn marker(&self) -> char {
+        match (&self.before, &self.after) {
+            (None, _) => '+',
+            (_, None) => '-',
+            _ => '~',
+        }
+    }
+}
+
+/// Every leaf set in only one of the snapshots or set differently in each,
+/// sorted by path.
+#[must_use]
+pub fn diff(before: &PolicySnapshot, after: &PolicySnapshot) -> Vec<PolicyChange> {
+    let mut before_leaves = BTreeMap::new();
+    flatten(&before.policy, String::new(), &mut before_leaves);
+    let mut after_leaves = BTreeMap::new();
+    flatten(&after.policy, String::new(), &mut after_leaves);
+
+    let mut changes = Vec::new();
+    for (path, old) in &before_leaves {
+        match after_leaves.get(path) {
+            Some(new) if new == old => {}
+            new => changes.push(PolicyChange {
+                path: path.clone(),
+                before: Some(old.clone()),
+                after: new.cloned(),
+            }),
+        }
+    }
+    for (path, new) in after_leaves {
+        if !before_leaves.contains_key(&path) {
+            changes.push(PolicyChange {
+                path,
+                before: None,
+                after: Some(new),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Allowlist entries by layer, keyed by `kind:value` selector.
+fn allowlist_layers(allowlists: &LayeredAllowlist) -> Value {
+    let mut layers = Map::new();
+    for loaded in &allowlists.layers {
+        let Some(entries) = allowlist_entries(&loaded.path) else {
+            continue;
+        };
+        let Value::Object(layer) = layers
+            .entry(loaded.layer.label())
+            .or_insert_with(|| Value::Object(Map::new()))
+        else {
+            continue;
+        };
+        for (key, entry) in entries {
+            let mut unique = key.clone();
+            let mut n = 1;
+            while layer.contains_key(&unique) {
+                n += 1;
+                unique = format!("{key} #{n}");
+            }
+            layer.insert(unique, entry);
+        }
+    }
+    Value::Object(layers)
+}
+
+/// The valid entries of the allowlist file at `path`, without their selector
+/// and audit fields; `None` if it cannot be read.
+fn allowlist_entries(path: &Path) -> Option<Vec<(String, Value)>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let (tables, _skipped) = crate::allowlist_exchange::allow_tables(&content).ok()?;
+    let entries = tables
+        .iter()
+        .filter_map(|table| {
+            let entry = crate::allowlist::parse_allow_entry(table).ok()?;
+            let kind = entry.selector.kind_label();
+            let Value::Object(mut fields) =
+                crate::allowlist_exchange::toml_to_json(&toml::Value::Table(table.clone()))
+            else {
+                return None;
+            };
+            fields.remove(kind);
+            for audit in ALLOWLIST_AUDIT_FIELDS {
+                fields.remove(*audit);
+            }
+            Some((
+                format!("{kind}:{}", entry.selector.value()),
+                Value::Object(fields),
+            ))
+        })
+        .collect();
+    Some(entries)
+}
+
+/// Sort scalar arrays and turn arrays holding tables into keyed objects, so
+/// the result does not depend on the order anything was written in.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, canonicalize(value)))
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let items: Vec<Value> = items.into_iter().map(canonicalize).collect();
+            if items
+                .iter()
+                .all(|item| !item.is_object() && !item.is_array())
+            {
+                let mut items = items;
+                items.sort_by_key(scalar_text);
+                items.dedup();
+                Value::Array(items)
+            } else {
+                Value::Object(items.into_iter().map(keyed_item).collect())
+            }
+        }
+        scalar => scalar,
+    }
+}
+
+/// Key for one member of an array that holds tables.
+fn keyed_item(item: Value) -> (String, Value) {
+    match item {
+        Value::Object(mut table) => match table.remove("pattern") {
+            Some(Value::String(pattern)) => (pattern, Value::Object(table)),
+            Some(other) => {
+                table.insert("pattern".to_string(), other);
+                (
+                    Value::Object(table.clone()).to_string(),
+                    Value::Object(table),
+                )
+            }
+            None => (
+                Value::Object(table.clone()).to_string(),
+                Value::Object(table),
+            ),
+        },
+        Value::String(text) => (text, Value::Object(Map::new())),
+        other => (other.to_string(), Value::Object(Map::new())),
+    }
+}
+
+/// A scalar as shown in paths: strings unquoted, everything else as JSON.
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Collect the leaves of a canonical tree by path. Empty objects and arrays
+/// contribute nothing, so "empty" and "absent" compare equal.
+fn flatten(value: &Value, path: String, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(value, child_path(&path, key), out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                out.insert(child_path(&path, &scalar_text(item)), Value::Bool(true));
+            }
+        }
+        Value::Null => {}
+        leaf => {
+            out.insert(path, leaf.clone());
+        }
+    }
+}
+
+/// `parent.key`, or `parent["key"]` when `key` is not a plain identifier.
+fn child_path(parent: &str, key: &str) -> String {
+    let plain = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match (parent.is_empty(), plain) {
+        (true, true) => key.to_string(),
+        (false, true) => format!("{parent}.{key}"),
+        (_, false) => format!("{parent}[{}]", Value::String(key.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(policy: Value) -> PolicySnapshot {
+        PolicySnapshot {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            source: SnapshotSource {
+                host: None,
+                dcg_version: "0.0.0".to_string(),
+                generated_at: "2026-01-01T00:00:00Z".to_string(),
+                config_files: Vec::new(),
+            },
+            policy: canonicalize(policy),
+        }
+    }
+
+    #[test]
+    fn canonical_form_ignores_write_order() {
+        let a = snapshot(json!({
+            "canary": { "paths": ["/b", "/a", "/a"] },
+            "overrides": { "block": [
+                { "pattern": "rm -rf /srv", "reason": "srv" },
+                { "pattern": "dropdb", "reason": "db" },
+            ]},
+        }));
+        let b = snapshot(json!({
+            "overrides": { "block": [
+                { "reason": "db", "pattern": "dropdb" },
+                { "reason": "srv", "pattern": "rm -rf /srv" },
+            ]},
+            "canary": { "paths": ["/a", "/b"] },
+        }));
+        assert_eq!(a.policy, b.policy);
+        assert!(diff(&a, &b).is_empty());
+        assert_eq!(
+            a.policy["overrides"]["block"]["dropdb"],
+            json!({ "reason": "db" })
+        );
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_leaves() {
+        let a = snapshot(json!({
+            "packs": { "core.git": 1, "database.postgresql": 1 },
+            "modes": { "default_mode": "deny", "rules": {} },
+            "canary": { "paths": ["/srv/keep"] },
+        }));
+        let b = snapshot(json!({
+            "packs": { "core.git": 2 },
+            "modes": { "default_mode": "warn", "rules": { "core.git:reset-hard": "warn" } },
+            "canary": { "paths": [] },
+        }));
+
+        let changes: Vec<(char, String)> = diff(&a, &b)
+            .into_iter()
+            .map(|change| (change.marker(), change.path))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ('-', r#"canary.paths["/srv/keep"]"#.to_string()),
+                ('~', "modes.default_mode".to_string()),
+                ('+', r#"modes.rules["core.git:reset-hard"]"#.to_string()),
+                ('~', r#"packs["core.git"]"#.to_string()),
+                ('-', r#"packs["database.postgresql"]"#.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_newer_schema_and_recanonicalizes() {
+        let mut newer = snapshot(json!({}));
+        newer.schema_version = SNAPSHOT_SCHEMA_VERSION + 1;
+        assert!(PolicySnapshot::parse(&newer.to_json_string()).is_err());
+
+        let mut edited = snapshot(json!({}));
+        edited.policy = json!({ "canary": { "names": ["b", "a"] } });
+        let parsed = PolicySnapshot::parse(&edited.to_json_string()).unwrap();
+        assert_eq!(parsed.policy, json!({ "canary": { "names": ["a", "b"] } }));
+    }
+
+    #[test]
+    fn allowlist_entries_drop_selector_and_audit_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("allowlist.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "release tooling"
+added_by = "alice"
+added_at = "2026-01-01T00:00:00Z"
+
+[[allow]]
+exact_command = "rm -rf build"
+reason = "build dir"
+"#,
+        )
+        .unwrap();
+        let entries = allowlist_entries(&path).unwrap();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "rule:core.git:reset-hard".to_string(),
+                    json!({ "reason": "release tooling" })
+                ),
+                (
+                    "exact_command:rm -rf build".to_string(),
+                    json!({ "reason": "build dir" })
+                ),
+            ]
+        );
+    }
+}
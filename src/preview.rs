@@ -0,0 +1,568 @@
+//! Dry-run previews of destructive commands (`dcg preview`).
+//!
+//! Many destructive tools can report what they would do without doing it:
+//! `git clean -n`, `kubectl delete --dry-run=client`, `terraform plan
+//! -destroy`. [`plan_preview`] rewrites a command into that variant and
+//! [`run_preview`] executes it in the caller's directory, so the human
+//! deciding on a block sees the files, resources or plan it would touch
+//! instead of only a rule name.
+//!
+//! Only a single simple command is accepted. Anything that needs a shell
+//! (pipes, `;`, redirections, substitutions, variables, globs) is rejected,
+//! because the preview runs exactly the arguments constructed here, without
+//! a shell.
+
+use std::fmt;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Commands `dcg preview` can turn into a dry run, with the variant it runs.
+pub const SUPPORTED_PREVIEWS: &[(&str, &str)] = &[
+    ("git clean", "git clean --dry-run (without -f/-i)"),
+    ("git rm", "git rm --dry-run"),
+    ("kubectl delete", "kubectl delete --dry-run=client -o name"),
+    ("kubectl drain", "kubectl drain --dry-run=client"),
+    ("helm uninstall", "helm uninstall --dry-run"),
+    ("terraform destroy", "terraform plan -destroy"),
+    ("terraform apply", "terraform plan"),
+    ("tofu destroy", "tofu plan -destroy"),
+    ("tofu apply", "tofu plan"),
+    ("rsync", "rsync --dry-run --itemize-changes"),
+    ("ansible-playbook", "ansible-playbook --check"),
+];
+
+/// Captured output is cut off after this many bytes per stream.
+pub const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// The dry-run variant of a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewPlan {
+    /// The tool and subcommand being previewed (e.g. `git clean`).
+    pub tool: String,
+    /// Program to execute, as given in the original command.
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl PreviewPlan {
+    /// The preview as a shell-quoted command line.
+    #[must_use]
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|word| quote(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Why a command cannot be previewed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewError {
+    /// The command is empty.
+    Empty,
+    /// The command uses shell syntax that cannot be run without a shell.
+    ShellSyntax(String),
+    /// The command is not one with a known dry-run variant.
+    Unsupported(String),
+}
+
+impl fmt::Display for PreviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no command given"),
+            Self::ShellSyntax(what) => write!(
+                f,
+                "the command uses {what}; dcg preview only runs a single simple command"
+            ),
+            Self::Unsupported(what) => {
+                let tools: Vec<&str> = SUPPORTED_PREVIEWS.iter().map(|(tool, _)| *tool).collect();
+                write!(
+                    f,
+                    "{what} has no dry-run preview (supported: {})",
+                    tools.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreviewError {}
+
+/// Result of running a preview.
+#[derive(Debug, Clone)]
+pub struct PreviewOutput {
+    /// Exit code, if the process exited normally.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// The process was killed after exceeding the timeout.
+    pub timed_out: bool,
+    /// Output was cut off at [`MAX_OUTPUT_BYTES`].
+    pub truncated: bool,
+    pub duration: Duration,
+}
+
+impl PreviewOutput {
+    #[must_use]
+    pub const fn succeeded(&self) -> bool {
+        !self.timed_out && matches!(self.exit_code, Some(0))
+    }
+}
+
+/// Build the dry-run variant of `command`.
+///
+/// # Errors
+///
+/// Returns [`PreviewError`] if the command is empty, needs a shell, or has no
+/// known dry-run variant.
+pub fn plan_preview(command: &str) -> Result<PreviewPlan, PreviewError> {
+    let words = split_words(command)?;
+    let Some((program, args)) = words.split_first() else {
+        return Err(PreviewError::Empty);
+    };
+    if program.contains('=') {
+        return Err(PreviewError::ShellSyntax(
+            "environment variable assignments".to_string(),
+        ));
+    }
+    let name = Path::new(program).file_name().map_or_else(
+        || program.clone(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let plan = |tool: String, args: Vec<String>| PreviewPlan {
+        tool,
+        program: program.clone(),
+        args,
+    };
+
+    match name.as_str() {
+        "git" => {
+            let (global, sub, rest) = split_subcommand(args, &["-C", "-c"]);
+            let tool = tool_name(&name, sub);
+            let rest = match sub {
+                "clean" => rest
+                    .iter()
+                    .filter_map(|arg| {
+                        strip_short_flags(arg, &['f', 'i'], &["--force", "--interactive"])
+                    })
+                    .collect(),
+                "rm" => rest.to_vec(),
+                _ => return Err(PreviewError::Unsupported(tool)),
+            };
+            Ok(plan(
+                tool,
+                join([global, &[sub.to_string(), "--dry-run".to_string()], &rest]),
+            ))
+        }
+        "kubectl" => {
+            let (global, sub, rest) =
+                split_subcommand(args, &["-n", "--namespace", "--context", "--kubeconfig"]);
+            let tool = tool_name(&name, sub);
+            let mut rest = drop_options(rest, &["--dry-run"], &["-o", "--output"]);
+            match sub {
+                "delete" => {
+                    rest.retain(|arg| {
+                        arg != "-i"
+                            && !arg.starts_with("--interactive")
+                            && !arg.starts_with("--wait")
+                    });
+                    rest.extend(["--dry-run=client", "-o", "name"].map(String::from));
+                }
+                "drain" => rest.push("--dry-run=client".to_string()),
+                _ => return Err(PreviewError::Unsupported(tool)),
+            }
+            Ok(plan(tool, join([global, &[sub.to_string()], &rest])))
+        }
+        "helm" => {
+            let (global, sub, rest) = split_subcommand(
+                args,
+                &["-n", "--namespace", "--kube-context", "--kubeconfig"],
+            );
+            if !matches!(sub, "uninstall" | "delete" | "del" | "un") {
+                return Err(PreviewError::Unsupported(tool_name(&name, sub)));
+            }
+            let mut rest = drop_options(rest, &["--dry-run"], &[]);
+            rest.push("--dry-run".to_string());
+            Ok(plan(
+                "helm uninstall".to_string(),
+                join([global, &[sub.to_string()], &rest]),
+            ))
+        }
+        "terraform" | "tofu" => {
+            let (global, sub, rest) = split_subcommand(args, &[]);
+            let tool = tool_name(&name, sub);
+            let rest = drop_options(rest, &["-auto-approve", "--auto-approve"], &[]);
+            let mut plan_args = vec!["plan".to_string()];
+            match sub {
+                "destroy" => plan_args.push("-destroy".to_string()),
+                // `apply <planfile>` applies a saved plan; `terraform show` previews that.
+                "apply"
+                    if rest
+                        .iter()
+                        .any(|arg| !arg.starts_with('-') && !arg.contains('=')) =>
+                {
+                    return Err(PreviewError::Unsupported(format!(
+                        "{tool} with a saved plan"
+                    )));
+                }
+                "apply" => {}
+                _ => return Err(PreviewError::Unsupported(tool)),
+            }
+            Ok(plan(tool, join([global, &plan_args, &rest])))
+        }
+        "rsync" => {
+            let mut preview = vec!["--dry-run".to_string()];
+            if !args
+                .iter()
+                .any(|arg| arg == "--itemize-changes" || arg == "-i")
+            {
+                preview.push("--itemize-changes".to_string());
+            }
+            Ok(plan("rsync".to_string(), join([&preview, args])))
+        }
+        "ansible-playbook" => {
+            let mut rest = args.to_vec();
+            if !rest.iter().any(|arg| arg == "--check" || arg == "-C") {
+                rest.push("--check".to_string());
+            }
+            Ok(plan("ansible-playbook".to_string(), rest))
+        }
+        _ => Err(PreviewError::Unsupported(name)),
+    }
+}
+
+/// Execute `plan` in `cwd` without a shell, killing it after `timeout`.
+///
+/// # Errors
+///
+/// Returns an error if the program cannot be started.
+pub fn run_preview(plan: &PreviewPlan, cwd: &Path, timeout: Duration) -> io::Result<PreviewOutput> {
+    let started = Instant::now();
+    let mut child = Command::new(&plan.program)
+        .args(&plan.args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child.stdout.take().map(capture);
+    let stderr = child.stderr.take().map(capture);
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            timed_out = true;
+            break child.wait().ok();
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    let (stdout, stdout_truncated) = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+    Ok(PreviewOutput {
+        exit_code: status.and_then(|status| status.code()),
+        stdout,
+        stderr,
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+        duration: started.elapsed(),
+    })
+}
+
+/// Read a stream to the end on a thread, keeping the first
+/// [`MAX_OUTPUT_BYTES`]. Draining the rest keeps the child from blocking on a
+/// full pipe.
+fn capture(mut stream: impl Read + Send + 'static) -> std::thread::JoinHandle<(String, bool)> {
+    std::thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut truncated = false;
+        let mut buf = [0u8; 8192];
+        while let Ok(n) = stream.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let room = MAX_OUTPUT_BYTES.saturating_sub(kept.len());
+            kept.extend_from_slice(&buf[..n.min(room)]);
+            truncated |= n > room;
+        }
+        (String::from_utf8_lossy(&kept).into_owned(), truncated)
+    })
+}
+
+/// Split `args` into global options, the subcommand, and its arguments.
+/// `valued` lists global options that take a separate value.
+fn split_subcommand<'a>(
+    args: &'a [String],
+    valued: &[&str],
+) -> (&'a [String], &'a str, &'a [String]) {
+    let mut i = 0;
+    while i < args.len() && args[i].starts_with('-') {
+        i += if valued.contains(&args[i].as_str()) {
+            2
+        } else {
+            1
+        };
+    }
+    match args.get(i) {
+        Some(sub) => (&args[..i], sub.as_str(), &args[i + 1..]),
+        None => (args, "", &[]),
+    }
+}
+
+/// Remove `flags` (also in `--flag=value` form) and `valued` options
+/// together with their value.
+fn drop_options(args: &[String], flags: &[&str], valued: &[&str]) -> Vec<String> {
+    let mut kept = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+        if flags.contains(&name) || valued.contains(&name) {
+            if valued.contains(&arg.as_str()) {
+                iter.next();
+            }
+            continue;
+        }
+        if valued
+            .iter()
+            .any(|opt| opt.len() == 2 && arg.starts_with(opt) && arg.len() > 2)
+        {
+            // Attached short value, e.g. `-oyaml`.
+            continue;
+        }
+        kept.push(arg.clone());
+    }
+    kept
+}
+
+/// Drop `long` flags and the `short` letters from a bundle like `-fdx`.
+/// Returns `None` when nothing of the argument is left.
+fn strip_short_flags(arg: &str, short: &[char], long: &[&str]) -> Option<String> {
+    if long.contains(&arg) {
+        return None;
+    }
+    match arg.strip_prefix('-') {
+        Some(letters) if !letters.starts_with('-') && !letters.is_empty() => {
+            let kept: String = letters.chars().filter(|c| !short.contains(c)).collect();
+            (!kept.is_empty()).then(|| format!("-{kept}"))
+        }
+        _ => Some(arg.to_string()),
+    }
+}
+
+fn tool_name(program: &str, subcommand: &str) -> String {
+    if subcommand.is_empty() {
+        program.to_string()
+    } else {
+        format!("{program} {subcommand}")
+    }
+}
+
+fn join<const N: usize>(parts: [&[String]; N]) -> Vec<String> {
+    parts.concat()
+}
+
+/// Split a command into words the way a POSIX shell would, rejecting
+/// anything that would need the shell to expand or connect it.
+pub(crate) fn split_words(command: &str) -> Result<Vec<String>, PreviewError> {
+    let shell = |what: &str| Err(PreviewError::ShellSyntax(what.to_string()));
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.trim().chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if c == '\n' {
+                    return shell("multiple lines");
+                }
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return shell("an unterminated quote"),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return shell("an unterminated quote"),
+                        },
+                        Some('$' | '`') => return shell("variable or command substitution"),
+                        Some(c) => word.push(c),
+                        None => return shell("an unterminated quote"),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => word.push(c),
+                    None => return shell("a trailing backslash"),
+                }
+            }
+            ';' | '&' | '|' => return shell("command separators or pipes"),
+            '<' | '>' => return shell("redirections"),
+            '$' | '`' => return shell("variable or command substitution"),
+            '(' | ')' | '{' | '}' => return shell("subshells or brace expansion"),
+            '*' | '?' | '[' => return shell("glob patterns"),
+            '#' | '~' if !in_word => return shell("comments or `~` expansion"),
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Quote `word` for display if a shell would split or expand it.
+pub(crate) fn quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(command: &str) -> String {
+        plan_preview(command).unwrap().command_line()
+    }
+
+    #[test]
+    fn git_clean_drops_force_and_interactive() {
+        assert_eq!(preview("git clean -fdx"), "git clean --dry-run -dx");
+        assert_eq!(
+            preview("git -C repo clean --force -d build"),
+            "git -C repo clean --dry-run -d build"
+        );
+        assert_eq!(preview("git clean -f -i"), "git clean --dry-run");
+        assert_eq!(
+            preview("git rm -r --cached src"),
+            "git rm --dry-run -r --cached src"
+        );
+    }
+
+    #[test]
+    fn kubernetes_and_terraform_use_native_dry_runs() {
+        assert_eq!(
+            preview("kubectl -n prod delete deployment api -o yaml --wait=false"),
+            "kubectl -n prod delete deployment api --dry-run=client -o name"
+        );
+        assert_eq!(
+            preview("helm uninstall web -n prod"),
+            "helm uninstall web -n prod --dry-run"
+        );
+        assert_eq!(
+            preview("terraform -chdir=infra destroy -auto-approve -target=aws_s3_bucket.logs"),
+            "terraform -chdir=infra plan -destroy -target=aws_s3_bucket.logs"
+        );
+        assert_eq!(preview("tofu apply -auto-approve"), "tofu plan");
+        assert!(matches!(
+            plan_preview("terraform apply saved.tfplan"),
+            Err(PreviewError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn rsync_and_ansible_add_check_flags() {
+        assert_eq!(
+            preview("rsync -a --delete 'my dir/' host:/srv/"),
+            "rsync --dry-run --itemize-changes -a --delete 'my dir/' host:/srv/"
+        );
+        assert_eq!(
+            preview("ansible-playbook -i hosts site.yml"),
+            "ansible-playbook -i hosts site.yml --check"
+        );
+    }
+
+    #[test]
+    fn shell_syntax_and_unknown_tools_are_rejected() {
+        for command in [
+            "git clean -fd && rm -rf build",
+            "git clean -fd | tee log",
+            "git clean -fd $DIR",
+            "git clean -fd \"$(pwd)\"",
+            "git rm *.log",
+            "FOO=1 git clean -fd",
+            "git clean -fd > out",
+        ] {
+            assert!(
+                matches!(plan_preview(command), Err(PreviewError::ShellSyntax(_))),
+                "{command}"
+            );
+        }
+        assert_eq!(
+            plan_preview("rm -rf build"),
+            Err(PreviewError::Unsupported("rm".to_string()))
+        );
+        assert!(matches!(
+            plan_preview("git reset --hard"),
+            Err(PreviewError::Unsupported(tool)) if tool == "git reset"
+        ));
+        assert_eq!(plan_preview("   "), Err(PreviewError::Empty));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_preview_captures_output_in_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("marker"), "").unwrap();
+        let plan = PreviewPlan {
+            tool: "ls".to_string(),
+            program: "ls".to_string(),
+            args: vec![],
+        };
+        let output = run_preview(&plan, dir.path(), Duration::from_secs(10)).unwrap();
+        assert!(output.succeeded());
+        assert_eq!(output.stdout.trim(), "marker");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_preview_kills_slow_commands() {
+        let plan = PreviewPlan {
+            tool: "sleep".to_string(),
+            program: "sleep".to_string(),
+            args: vec!["5".to_string()],
+        };
+        let output = run_preview(&plan, Path::new("."), Duration::from_millis(100)).unwrap();
+        assert!(output.timed_out);
+        assert!(!output.succeeded());
+    }
+}
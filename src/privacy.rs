@@ -0,0 +1,491 @@
+//! Privacy mode for commands dcg persists.
+//!
+//! `[privacy] log_mode` decides what the log file, the history database (and
+//! the stats and suggestions built from it), and canary webhook payloads record
+//! for each evaluated command:
+//!
+//! - `full`: the command as typed (inline credentials are still masked).
+//! - `hash`: only `sha256:<hex>`, an HMAC of the command keyed by a
+//!   per-installation salt, so repeats can be counted but not read.
+//! - `redacted`: every argument replaced by a placeholder (`rm -rf <path>`,
+//!   `git <arg> origin` becomes `git <arg> <arg>`). Only command words, flags,
+//!   and subcommands named in a pack keyword (`gcloud storage`, `s3api`) stay;
+//!   attached short-flag values (`-pSECRET`) are split off and redacted too.
+//!   `dcg suggest-allowlist` turns the placeholders back into regex classes.
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment override for the hash salt (share it to compare hashes across machines).
+pub const ENV_PRIVACY_SALT: &str = "DCG_PRIVACY_SALT";
+
+/// Prefix of hashed commands.
+pub const HASH_PREFIX: &str = "sha256:";
+
+/// Placeholder for filesystem paths.
+pub const PATH_PLACEHOLDER: &str = "<path>";
+/// Placeholder for quoted strings and assignment values.
+pub const STRING_PLACEHOLDER: &str = "<str>";
+/// Placeholder for numbers.
+pub const NUMBER_PLACEHOLDER: &str = "<num>";
+/// Placeholder for URLs.
+pub const URL_PLACEHOLDER: &str = "<url>";
+/// Placeholder for any other argument.
+pub const ARG_PLACEHOLDER: &str = "<arg>";
+
+/// Placeholders, the regex each stands for, and a sample value it matches.
+const PLACEHOLDERS: [(&str, &str, &str); 5] = [
+    (PATH_PLACEHOLDER, r"[^\s;&|]+", "/p"),
+    (
+        STRING_PLACEHOLDER,
+        r#"(?:'[^']*'|"[^"]*"|[^\s;&|]+)"#,
+        "'s'",
+    ),
+    (NUMBER_PLACEHOLDER, r"\d+", "0"),
+    (
+        URL_PLACEHOLDER,
+        r"[A-Za-z][\w+.-]*://[^\s;&|]+",
+        "https://h",
+    ),
+    (ARG_PLACEHOLDER, r"[^\s;&|]+", "a"),
+];
+
+/// Pack keywords split into words; arguments that spell one out are kept.
+static KEYWORD_WORDS: std::sync::LazyLock<Vec<Vec<&'static str>>> =
+    std::sync::LazyLock::new(|| {
+        let registry = &crate::packs::REGISTRY;
+        let all: std::collections::HashSet<String> = registry
+            .all_pack_ids()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        registry
+            .collect_enabled_keywords(&all)
+            .into_iter()
+            .map(|keyword| keyword.split_whitespace().collect::<Vec<_>>())
+            .filter(|words| !words.is_empty())
+            .collect()
+    });
+
+/// What persisted logs record for each command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyLogMode {
+    /// The command as typed.
+    #[default]
+    Full,
+    /// A salted hash only.
+    Hash,
+    /// Paths and literals replaced by placeholders.
+    Redacted,
+}
+
+impl PrivacyLogMode {
+    /// Config label (`full`, `hash`, `redacted`).
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Hash => "hash",
+            Self::Redacted => "redacted",
+        }
+    }
+
+    /// Parse a label, case-insensitively.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "full" => Some(Self::Full),
+            "hash" => Some(Self::Hash),
+            "redacted" => Some(Self::Redacted),
+            _ => None,
+        }
+    }
+
+    /// The form of `command` to persist under this mode.
+    ///
+    /// Hashing falls back to the redacted form if no salt is available, so
+    /// the raw command is never written when the user asked for less.
+    #[must_use]
+    pub fn apply(self, command: &str) -> Cow<'_, str> {
+        match self {
+            Self::Full => Cow::Borrowed(command),
+            Self::Hash => load_salt().map_or_else(
+                |_| Cow::Owned(redact_literals(command)),
+                |salt| Cow::Owned(hash_command(command, &salt)),
+            ),
+            Self::Redacted => Cow::Owned(redact_literals(command)),
+        }
+    }
+}
+
+/// `sha256:<hex>` HMAC of `command` keyed by `salt`.
+#[must_use]
+pub fn hash_command(command: &str, salt: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(command.as_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let mut hashed = String::with_capacity(HASH_PREFIX.len() + digest.len() * 2);
+    hashed.push_str(HASH_PREFIX);
+    for byte in digest {
+        let _ = write!(hashed, "{byte:02x}");
+    }
+    hashed
+}
+
+/// `true` if `command` is a hash produced by [`hash_command`].
+#[must_use]
+pub fn is_hashed(command: &str) -> bool {
+    command
+        .strip_prefix(HASH_PREFIX)
+        .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Default location of the per-installation salt.
+#[must_use]
+pub fn default_salt_path() -> PathBuf {
+    crate::paths::state_dir().join("privacy-salt")
+}
+
+/// The hash salt: `DCG_PRIVACY_SALT`, else the per-installation salt file
+/// (created on first use).
+///
+/// # Errors
+///
+/// Returns an error if the salt file cannot be read or created.
+pub fn load_salt() -> io::Result<String> {
+    if let Ok(salt) = std::env::var(ENV_PRIVACY_SALT) {
+        if !salt.is_empty() {
+            return Ok(salt);
+        }
+    }
+    load_or_create_salt(&default_salt_path())
+}
+
+fn load_or_create_salt(path: &Path) -> io::Result<String> {
+    if let Some(existing) = read_salt(path) {
+        return Ok(existing);
+    }
+    // Parallel hooks may race to create the salt; the loser must use the
+    // winner's instead of overwriting it.
+    let _lock = crate::storage::StateLock::acquire(path)?;
+    if let Some(existing) = read_salt(path) {
+        return Ok(existing);
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill(&mut bytes);
+    let mut salt = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(salt, "{byte:02x}");
+    }
+
+    crate::storage::write_atomic(path, salt.as_bytes())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(salt)
+}
+
+fn read_salt(path: &Path) -> Option<String> {
+    let existing = fs::read_to_string(path).ok()?;
+    let existing = existing.trim();
+    (!existing.is_empty()).then(|| existing.to_string())
+}
+
+/// Replace every argument with a placeholder, keeping command words, flags,
+/// operators, and subcommands spelled by a pack keyword.
+#[must_use]
+pub fn redact_literals(command: &str) -> String {
+    let words = split_words(command);
+    let keep = keyword_positions(&words);
+    let mut out: Vec<Cow<'_, str>> = Vec::new();
+    let mut expect_command_word = true;
+
+    for (idx, token) in words.into_iter().enumerate() {
+        if matches!(token, "|" | "||" | "&&" | ";" | "&") {
+            out.push(Cow::Borrowed(token));
+            expect_command_word = true;
+            continue;
+        }
+
+        if let Some((name, value)) = split_assignment(token) {
+            let redacted = if value.is_empty() {
+                Cow::Borrowed(token)
+            } else {
+                Cow::Owned(format!("{name}={STRING_PLACEHOLDER}"))
+            };
+            out.push(redacted);
+            continue;
+        }
+
+        if expect_command_word && !token.starts_with(['\'', '"']) {
+            out.push(Cow::Borrowed(token));
+            expect_command_word = false;
+            continue;
+        }
+        expect_command_word = false;
+
+        if token.starts_with('-') {
+            out.push(redact_flag(token));
+            continue;
+        }
+
+        out.push(if keep.contains(&idx) {
+            Cow::Borrowed(token)
+        } else {
+            Cow::Borrowed(classify_argument(token))
+        });
+    }
+
+    out.join(" ")
+}
+
+/// Indices of words that spell out a pack keyword (`gcloud storage`, `s3api`).
+fn keyword_positions(words: &[&str]) -> std::collections::HashSet<usize> {
+    let mut keep = std::collections::HashSet::new();
+    for keyword in KEYWORD_WORDS.iter() {
+        for (start, window) in words.windows(keyword.len()).enumerate() {
+            if window == keyword.as_slice() {
+                keep.extend(start..start + keyword.len());
+            }
+        }
+    }
+    keep
+}
+
+/// A flag with its value redacted: `--name=<arg>`, `-p<arg>`.
+///
+/// A single-dash token is a cluster of boolean flags (`-rf`, `-fdx`) only if it
+/// is a few letters long; anything else is a flag with an attached value.
+fn redact_flag(token: &str) -> Cow<'_, str> {
+    if let Some((flag, value)) = token.split_once('=') {
+        if !value.is_empty() {
+            return Cow::Owned(format!("{flag}={}", classify_argument(value)));
+        }
+        return Cow::Borrowed(token);
+    }
+    if token.starts_with("--") {
+        return Cow::Borrowed(token);
+    }
+    let Some(flag) = token.get(..2) else {
+        return Cow::Borrowed(token);
+    };
+    let value = &token[2..];
+    let is_cluster = value.len() <= 3 && value.bytes().all(|b| b.is_ascii_alphabetic());
+    if value.is_empty() || is_cluster {
+        Cow::Borrowed(token)
+    } else {
+        Cow::Owned(format!("{flag}{}", classify_argument(value)))
+    }
+}
+
+/// Placeholder for an argument.
+fn classify_argument(arg: &str) -> &'static str {
+    if arg.contains(['\'', '"']) {
+        STRING_PLACEHOLDER
+    } else if arg.contains("://") {
+        URL_PLACEHOLDER
+    } else if arg.bytes().all(|b| b.is_ascii_digit()) {
+        NUMBER_PLACEHOLDER
+    } else if arg.contains('/') || arg.starts_with(['~', '.']) {
+        PATH_PLACEHOLDER
+    } else {
+        ARG_PLACEHOLDER
+    }
+}
+
+/// `NAME=value` with a shell variable name, split at the `=`.
+fn split_assignment(token: &str) -> Option<(&str, &str)> {
+    let (name, value) = token.split_once('=')?;
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    ((first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_'))
+    .then_some((name, value))
+}
+
+/// Split on unquoted whitespace, keeping quotes in the words.
+fn split_words(command: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (idx, ch) in command.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, ch) {
+            (_, '\\') if quote != Some('\'') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, c) if c.is_whitespace() => {
+                if let Some(begin) = start.take() {
+                    words.push(&command[begin..idx]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if start.is_none() {
+            start = Some(idx);
+        }
+    }
+    if let Some(begin) = start {
+        words.push(&command[begin..]);
+    }
+    words
+}
+
+/// Regex for one whitespace-free token of a redacted command: literal text is
+/// escaped and placeholders become the class of values they replaced.
+#[must_use]
+pub fn token_regex(token: &str) -> String {
+    let mut regex = String::new();
+    let mut rest = token;
+    while let Some((idx, placeholder, pattern)) = next_placeholder(rest) {
+        regex.push_str(&regex::escape(&rest[..idx]));
+        regex.push_str(pattern);
+        rest = &rest[idx + placeholder.len()..];
+    }
+    regex.push_str(&regex::escape(rest));
+    regex
+}
+
+/// `true` if `command` contains any placeholder.
+#[must_use]
+pub fn has_placeholders(command: &str) -> bool {
+    next_placeholder(command).is_some()
+}
+
+/// `command` with each placeholder swapped for a sample value it stands for,
+/// so regexes built by [`token_regex`] can be checked against it.
+#[must_use]
+pub fn materialize_placeholders(command: &str) -> Cow<'_, str> {
+    if !has_placeholders(command) {
+        return Cow::Borrowed(command);
+    }
+    let mut out = command.to_string();
+    for (placeholder, _, sample) in PLACEHOLDERS {
+        out = out.replace(placeholder, sample);
+    }
+    Cow::Owned(out)
+}
+
+fn next_placeholder(text: &str) -> Option<(usize, &'static str, &'static str)> {
+    PLACEHOLDERS
+        .iter()
+        .filter_map(|(placeholder, pattern, _)| {
+            text.find(placeholder)
+                .map(|idx| (idx, *placeholder, *pattern))
+        })
+        .min_by_key(|(idx, _, _)| *idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted_mode_keeps_only_commands_flags_and_keyword_subcommands() {
+        let cases = [
+            ("rm -rf /home/me/project/build", "rm -rf <path>"),
+            (
+                "git commit -m \"fix the thing\" && git push origin main",
+                "git <arg> -m <str> && git <arg> <arg> <arg>",
+            ),
+            (
+                "DATABASE_URL=postgres://x psql --port=5432 -f ./drop.sql",
+                "DATABASE_URL=<str> psql --port=<num> -f <path>",
+            ),
+            (
+                "curl -X DELETE https://api.example.com/v1/items/42",
+                "curl -X DELETE <url>",
+            ),
+            (
+                "kubectl delete pod web-7d9 -n prod",
+                "kubectl <arg> <arg> <arg> -n <arg>",
+            ),
+            (
+                "gcloud storage rm --name=orders gs://bucket/a",
+                "gcloud storage rm --name=<arg> <url>",
+            ),
+            (
+                "aws s3api delete-bucket --bucket acme",
+                "aws s3api <arg> --bucket <arg>",
+            ),
+        ];
+        for (command, expected) in cases {
+            assert_eq!(redact_literals(command), expected, "{command}");
+        }
+    }
+
+    #[test]
+    fn attached_short_flag_values_are_redacted() {
+        assert_eq!(
+            redact_literals("mysql -uroot -pS3cretPass db"),
+            "mysql -u<arg> -p<arg> <arg>"
+        );
+        assert_eq!(redact_literals("psql -p5432"), "psql -p<num>");
+        assert_eq!(redact_literals("git clean -fdx"), "git <arg> -fdx");
+    }
+
+    #[test]
+    fn hashes_are_salted_stable_and_recognizable() {
+        let a = hash_command("rm -rf /", "salt-a");
+        assert_eq!(a, hash_command("rm -rf /", "salt-a"));
+        assert_ne!(a, hash_command("rm -rf /", "salt-b"));
+        assert!(is_hashed(&a));
+        assert!(!is_hashed("sha256:not-hex"));
+        assert!(!is_hashed("rm -rf /"));
+    }
+
+    #[test]
+    fn salt_file_is_created_once_and_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("privacy-salt");
+        let first = load_or_create_salt(&path).unwrap();
+        assert_eq!(first.len(), 64);
+        assert_eq!(load_or_create_salt(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn placeholder_tokens_become_regex_classes() {
+        let regex = regex::Regex::new(&format!(
+            "^{}$",
+            ["rm", "-rf", "<path>"].map(token_regex).join(r"\s+")
+        ))
+        .unwrap();
+        assert!(regex.is_match("rm -rf /tmp/build"));
+        assert!(regex.is_match(&materialize_placeholders("rm -rf <path>")));
+        assert!(!regex.is_match("rm -rf /tmp/a; reboot"));
+
+        assert_eq!(token_regex("--port=<num>"), r"\-\-port=\d+");
+        assert_eq!(token_regex("a.b"), r"a\.b");
+    }
+
+    #[test]
+    fn labels_round_trip() {
+        for mode in [
+            PrivacyLogMode::Full,
+            PrivacyLogMode::Hash,
+            PrivacyLogMode::Redacted,
+        ] {
+            assert_eq!(PrivacyLogMode::parse(mode.label()), Some(mode));
+        }
+        assert_eq!(PrivacyLogMode::parse("raw"), None);
+    }
+}
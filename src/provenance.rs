@@ -0,0 +1,89 @@
+//! Who authored the command being evaluated.
+//!
+//! The hook and MCP server see commands an AI agent wants to run, while a
+//! shell integration that calls `dcg test` sees commands a person typed. Each
+//! entry point tags its evaluation with a [`Provenance`] so `[policy.provenance.*]`
+//! can treat the two differently (e.g. humans may force-push, agents may not),
+//! and the tag is recorded in history and the log file.
+
+use serde::{Deserialize, Serialize};
+
+/// Environment override for the provenance of `dcg test`.
+///
+/// Shell integrations that route human-typed commands through dcg set it per
+/// command (`DCG_PROVENANCE=human dcg test ...`). The hook and MCP server
+/// ignore it: whatever an agent exports, its commands are evaluated as agent.
+pub const ENV_PROVENANCE: &str = "DCG_PROVENANCE";
+
+/// Author of an evaluated command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Provenance {
+    /// Proposed by an AI coding agent (hook, MCP server).
+    Agent,
+    /// Typed by a person (shell integrations).
+    Human,
+}
+
+impl Provenance {
+    /// Config/log label (`agent`, `human`).
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Agent => "agent",
+            Self::Human => "human",
+        }
+    }
+
+    /// Parse a label, case-insensitively.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "agent" => Some(Self::Agent),
+            "human" => Some(Self::Human),
+            _ => None,
+        }
+    }
+
+    /// Provenance for a human-facing entry point whose natural default is
+    /// `default`.
+    ///
+    /// `DCG_PROVENANCE` wins when it holds a known value. Agent entry points
+    /// must not call this.
+    #[must_use]
+    pub fn resolve(default: Self) -> Self {
+        Self::resolve_from(default, std::env::var(ENV_PROVENANCE).ok().as_deref())
+    }
+
+    fn resolve_from(default: Self, env_value: Option<&str>) -> Self {
+        env_value.and_then(Self::parse).unwrap_or(default)
+    }
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_value_overrides_entry_point_default() {
+        assert_eq!(
+            Provenance::resolve_from(Provenance::Agent, None),
+            Provenance::Agent
+        );
+        assert_eq!(
+            Provenance::resolve_from(Provenance::Agent, Some(" Human ")),
+            Provenance::Human
+        );
+        assert_eq!(
+            Provenance::resolve_from(Provenance::Human, Some("robot")),
+            Provenance::Human
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! The `dcg` Python module (`pyo3` feature).
+//!
+//! Agent orchestrators written in Python (`LangChain`, `AutoGen`, custom tool
+//! wrappers) can check a shell command in-process instead of spawning the
+//! hook:
+//!
+//! ```python
+//! import dcg
+//!
+//! decision = dcg.evaluate("git reset --hard", cwd="/work/repo", profile="ci-bot")
+//! if decision.denied:
+//!     raise PermissionError(f"{decision.rule_id}: {decision.reason}")
+//! ```
+//!
+//! Each call goes through an [`api::Guard`](crate::api::Guard) built from the
+//! same config and allowlists `dcg` itself loads, plus the `[agents.<profile>]`
+//! profile when one is named. Guards are built on first use per profile and
+//! reused; `dcg.reload()` drops them so the next call rereads the files.
+//! Evaluation releases the GIL.
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::api::{self, Action, Context};
+
+/// Result of `dcg.evaluate`.
+#[pyclass(frozen, get_all, module = "dcg")]
+#[derive(Debug, Clone)]
+pub struct Decision {
+    /// `"allow"`, `"log"`, `"warn"`, or `"deny"`.
+    action: &'static str,
+    /// `pack:pattern` of the deciding rule.
+    rule_id: Option<String>,
+    pack_id: Option<String>,
+    severity: Option<&'static str>,
+    reason: Option<String>,
+    explanation: Option<String>,
+    /// `"project"`, `"user"`, or `"system"` when an allowlist let it through.
+    allowlisted_by: Option<&'static str>,
+    timed_out: bool,
+}
+
+#[pymethods]
+impl Decision {
+    /// Whether the command should be blocked.
+    #[getter]
+    fn denied(&self) -> bool {
+        self.action == "deny"
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.rule_id {
+            Some(rule_id) => format!("Decision(action={:?}, rule_id={rule_id:?})", self.action),
+            None => format!("Decision(action={:?})", self.action),
+        }
+    }
+}
+
+impl From<api::Decision> for Decision {
+    fn from(decision: api::Decision) -> Self {
+        let action = match decision.action {
+            Action::Allow => "allow",
+            Action::Log => "log",
+            Action::Warn => "warn",
+            Action::Deny => "deny",
+        };
+        let rule = decision.rule;
+        Self {
+            action,
+            rule_id: rule.as_ref().and_then(|r| r.rule_id.clone()),
+            pack_id: rule.as_ref().and_then(|r| r.pack_id.clone()),
+            severity: rule.as_ref().and_then(|r| r.severity).map(|s| s.label()),
+            reason: rule.as_ref().map(|r| r.reason.clone()),
+            explanation: rule.and_then(|r| r.explanation),
+            allowlisted_by: decision.allowlisted_by.map(|layer| layer.label()),
+            timed_out: decision.timed_out,
+        }
+    }
+}
+
+fn evaluate_with(
+    command: &str,
+    cwd: Option<PathBuf>,
+    profile: Option<String>,
+) -> Result<Decision, api::GuardError> {
+    let guard = api::shared_guard(profile.as_deref())?;
+    let mut context = Context::new();
+    if let Some(cwd) = cwd {
+        context = context.cwd(cwd);
+    }
+    Ok(guard.evaluate(command, &context).into())
+}
+
+/// Evaluate `command` as if run in `cwd` by the agent `profile`.
+#[pyfunction]
+#[pyo3(signature = (command, cwd=None, profile=None))]
+fn evaluate(
+    py: Python<'_>,
+    command: &str,
+    cwd: Option<PathBuf>,
+    profile: Option<String>,
+) -> PyResult<Decision> {
+    py.allow_threads(|| evaluate_with(command, cwd, profile))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Forget cached guards; the next `evaluate` rereads config and allowlists.
+#[pyfunction]
+fn reload() {
+    api::reload_shared_guards();
+}
+
+#[pymodule]
+fn dcg(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<Decision>()?;
+    m.add_function(wrap_pyfunction!(evaluate, m)?)?;
+    m.add_function(wrap_pyfunction!(reload, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decisions_convert_to_python_fields() {
+        let decision = evaluate_with("git reset --hard", None, None).unwrap();
+        assert!(decision.denied());
+        assert_eq!(decision.rule_id.as_deref(), Some("core.git:reset-hard"));
+        assert_eq!(decision.pack_id.as_deref(), Some("core.git"));
+        assert!(decision.reason.is_some());
+
+        let decision = evaluate_with("git status", None, None).unwrap();
+        assert_eq!(decision.action, "allow");
+        assert_eq!(decision.rule_id, None);
+    }
+}
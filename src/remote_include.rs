@@ -0,0 +1,410 @@
+//! Fetching and caching `https://` config includes.
+//!
+//! A config file may include shared policy by URL:
+//!
+//! ```toml
+//! include = [
+//!     "https://policy.example.com/dcg/org.toml",
+//!     { url = "https://policy.example.com/dcg/strict.toml", sha256 = "9f86d0…" },
+//!     { url = "https://policy.example.com/dcg/team.toml", etag = "\"v42\"" },
+//! ]
+//! ```
+//!
+//! Fetched files are cached under the user cache directory and reused for
+//! [`REFRESH_AFTER`]; after that they are revalidated with `If-None-Match`.
+//! A `sha256` pin only accepts content with that hash (and never needs a
+//! refetch once cached); an `etag` pin only accepts the response carrying that
+//! ETag. When a refresh fails, the last cached copy that satisfies the pin is
+//! used, and the reason is recorded so `dcg doctor` can report it.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::{content_hash, resolve_config_path_value};
+use crate::storage;
+
+/// Environment override for the remote include cache directory.
+pub const ENV_INCLUDE_CACHE_DIR: &str = "DCG_INCLUDE_CACHE_DIR";
+
+/// Cached copies younger than this are used without contacting the server.
+pub const REFRESH_AFTER: Duration = Duration::hours(1);
+
+/// Budget for one fetch, so an unreachable server cannot stall the hook.
+#[cfg(feature = "cli")]
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Remote includes larger than this are rejected.
+#[cfg(feature = "cli")]
+const MAX_INCLUDE_BYTES: u64 = 1024 * 1024;
+
+/// What a remote include must match to be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncludePin<'a> {
+    /// Any content the server returns.
+    None,
+    /// Content whose SHA-256 is this lowercase hex digest.
+    Sha256(&'a str),
+    /// The response carrying this ETag.
+    Etag(&'a str),
+}
+
+/// A remote include ready to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchedInclude {
+    /// The TOML text.
+    pub content: String,
+    /// Set when a stale cached copy stood in for a failed refresh.
+    pub warning: Option<String>,
+}
+
+/// Metadata stored next to each cached body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheMeta {
+    url: String,
+    etag: Option<String>,
+    sha256: String,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Result of asking the server for a fresh copy.
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+enum Fetch {
+    NotModified,
+    Body {
+        content: String,
+        etag: Option<String>,
+    },
+}
+
+/// On-disk cache of fetched includes, one body and one metadata file per URL.
+#[derive(Debug, Clone)]
+pub struct IncludeCache {
+    dir: PathBuf,
+}
+
+impl IncludeCache {
+    #[must_use]
+    pub const fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `DCG_INCLUDE_CACHE_DIR`, else `<cache dir>/dcg/includes`.
+    #[must_use]
+    pub fn default_dir() -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_INCLUDE_CACHE_DIR) {
+            if let Some(path) = resolve_config_path_value(&value, None) {
+                return path;
+            }
+        }
+        crate::paths::cache_dir().join("includes")
+    }
+
+    /// Load `url`, from the cache when it is fresh enough and from the
+    /// server otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the include cannot be applied: a non-HTTPS URL, a pin
+    /// mismatch, or a failed fetch with no usable cached copy.
+    pub fn load(
+        &self,
+        url: &str,
+        pin: IncludePin<'_>,
+        now: DateTime<Utc>,
+    ) -> Result<FetchedInclude, String> {
+        if !is_allowed_url(url) {
+            return Err("remote includes must use https".to_string());
+        }
+
+        let cached = self.read(url).filter(|(meta, _)| satisfies(pin, meta));
+        if let Some((meta, content)) = &cached {
+            let fresh = now - meta.fetched_at < REFRESH_AFTER;
+            if fresh || matches!(pin, IncludePin::Sha256(_)) {
+                return Ok(FetchedInclude {
+                    content: content.clone(),
+                    warning: None,
+                });
+            }
+        }
+
+        let etag = cached.as_ref().and_then(|(meta, _)| meta.etag.clone());
+        let refreshed = fetch(url, etag.as_deref()).and_then(|fetch| match fetch {
+            Fetch::NotModified => cached
+                .clone()
+                .ok_or_else(|| "server answered 304 without a cached copy".to_string()),
+            Fetch::Body { content, etag } => {
+                let meta = CacheMeta {
+                    url: url.to_string(),
+                    etag,
+                    sha256: content_hash(&content),
+                    fetched_at: now,
+                };
+                if satisfies(pin, &meta) {
+                    Ok((meta, content))
+                } else {
+                    Err(pin_mismatch(pin, &meta))
+                }
+            }
+        });
+
+        match (refreshed, cached) {
+            (Ok((mut meta, content)), _) => {
+                meta.fetched_at = now;
+                // A cache that cannot be written only costs a refetch.
+                let _ = self.write(&meta, &content);
+                Ok(FetchedInclude {
+                    content,
+                    warning: None,
+                })
+            }
+            (Err(e), Some((meta, content))) => Ok(FetchedInclude {
+                content,
+                warning: Some(format!(
+                    "using copy cached {}; refresh failed: {e}",
+                    meta.fetched_at.format("%Y-%m-%d %H:%M UTC")
+                )),
+            }),
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    fn paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = &content_hash(url)[..32];
+        (
+            self.dir.join(format!("{key}.toml")),
+            self.dir.join(format!("{key}.json")),
+        )
+    }
+
+    fn read(&self, url: &str) -> Option<(CacheMeta, String)> {
+        let (body_path, meta_path) = self.paths(url);
+        let meta: CacheMeta = serde_json::from_str(&fs::read_to_string(meta_path).ok()?).ok()?;
+        let content = fs::read_to_string(body_path).ok()?;
+        // A body that no longer matches its metadata was tampered with or torn.
+        (meta.url == url && meta.sha256 == content_hash(&content)).then_some((meta, content))
+    }
+
+    fn write(&self, meta: &CacheMeta, content: &str) -> io::Result<()> {
+        let (body_path, meta_path) = self.paths(&meta.url);
+        storage::write_atomic(&body_path, content.as_bytes())?;
+        storage::write_atomic(&meta_path, serde_json::to_string_pretty(meta)?.as_bytes())
+    }
+}
+
+/// HTTPS anywhere; plain HTTP only to the loopback interface.
+fn is_allowed_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    if lower.starts_with("https://") {
+        return true;
+    }
+    lower.strip_prefix("http://").is_some_and(|rest| {
+        let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+        let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+        let host = host
+            .strip_prefix('[')
+            .and_then(|h| h.split_once(']'))
+            .map_or_else(|| host.split(':').next().unwrap_or(""), |(h, _)| h);
+        host == "localhost"
+            || host
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    })
+}
+
+fn satisfies(pin: IncludePin<'_>, meta: &CacheMeta) -> bool {
+    match pin {
+        IncludePin::None => true,
+        IncludePin::Sha256(hash) => meta.sha256.eq_ignore_ascii_case(hash.trim()),
+        IncludePin::Etag(etag) => meta.etag.as_deref() == Some(etag),
+    }
+}
+
+fn pin_mismatch(pin: IncludePin<'_>, meta: &CacheMeta) -> String {
+    match pin {
+        IncludePin::Sha256(hash) => {
+            format!(
+                "content sha256 {} does not match pinned {hash}",
+                meta.sha256
+            )
+        }
+        IncludePin::Etag(etag) => format!(
+            "ETag {} does not match pinned {etag}",
+            meta.etag.as_deref().unwrap_or("(none)")
+        ),
+        IncludePin::None => String::new(),
+    }
+}
+
+/// Fetch on a fresh thread: the blocking client runs its own runtime, which
+/// must not be nested inside the MCP or admin servers' runtimes.
+#[cfg(feature = "cli")]
+fn fetch(url: &str, etag: Option<&str>) -> Result<Fetch, String> {
+    let url = url.to_string();
+    let etag = etag.map(str::to_string);
+    std::thread::spawn(move || fetch_blocking(&url, etag.as_deref()))
+        .join()
+        .unwrap_or_else(|_| Err("fetch panicked".to_string()))
+}
+
+#[cfg(feature = "cli")]
+fn fetch_blocking(url: &str, etag: Option<&str>) -> Result<Fetch, String> {
+    use std::io::Read as _;
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("dcg/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    let response = request.send().map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(Fetch::NotModified);
+    }
+    if !status.is_success() {
+        return Err(format!("server answered {status}"));
+    }
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let mut content = String::new();
+    response
+        .take(MAX_INCLUDE_BYTES + 1)
+        .read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    if content.len() as u64 > MAX_INCLUDE_BYTES {
+        return Err(format!("larger than {MAX_INCLUDE_BYTES} bytes"));
+    }
+    Ok(Fetch::Body { content, etag })
+}
+
+#[cfg(not(feature = "cli"))]
+fn fetch(_url: &str, _etag: Option<&str>) -> Result<Fetch, String> {
+    Err("fetching remote includes requires the `cli` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const POLICY: &str = "[packs]\nenabled = [\"kubernetes\"]\n";
+
+    /// Serve `POLICY` with ETag `"v1"` on loopback, answering 304 to a
+    /// matching `If-None-Match`. Returns the base URL and a request counter.
+    fn serve(requests: usize) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/org.toml", listener.local_addr().unwrap());
+        let count = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&count);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut revalidate = false;
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    revalidate |= line.eq_ignore_ascii_case("if-none-match: \"v1\"");
+                }
+                seen.fetch_add(1, Ordering::SeqCst);
+                let response = if revalidate {
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{POLICY}",
+                        POLICY.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, count)
+    }
+
+    #[test]
+    fn only_https_and_loopback_http_are_fetched() {
+        assert!(is_allowed_url("https://policy.example.com/org.toml"));
+        assert!(is_allowed_url("http://127.0.0.1:8080/org.toml"));
+        assert!(is_allowed_url("http://localhost/org.toml"));
+        assert!(is_allowed_url("http://[::1]:9000/org.toml"));
+        assert!(!is_allowed_url("http://policy.example.com/org.toml"));
+        assert!(!is_allowed_url("http://127.0.0.1.example.com/org.toml"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = IncludeCache::new(dir.path().to_path_buf());
+        let err = cache
+            .load(
+                "http://policy.example.com/org.toml",
+                IncludePin::None,
+                Utc::now(),
+            )
+            .unwrap_err();
+        assert!(err.contains("https"));
+    }
+
+    #[test]
+    fn fetches_caches_and_revalidates_with_etag() {
+        let (url, requests) = serve(2);
+        let dir = tempfile::tempdir().unwrap();
+        let cache = IncludeCache::new(dir.path().to_path_buf());
+        let now = Utc::now();
+
+        let first = cache.load(&url, IncludePin::None, now).unwrap();
+        assert_eq!(first.content, POLICY);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Fresh copies are served from the cache.
+        cache.load(&url, IncludePin::Etag("\"v1\""), now).unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Stale copies are revalidated; a 304 keeps the cached body.
+        let later = now + REFRESH_AFTER + Duration::minutes(1);
+        let second = cache.load(&url, IncludePin::None, later).unwrap();
+        assert_eq!(second.content, POLICY);
+        assert_eq!(second.warning, None);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn pins_reject_other_content_and_fall_back_to_the_cache() {
+        let (url, _) = serve(2);
+        let dir = tempfile::tempdir().unwrap();
+        let cache = IncludeCache::new(dir.path().to_path_buf());
+        let now = Utc::now();
+
+        let err = cache
+            .load(&url, IncludePin::Sha256(&"0".repeat(64)), now)
+            .unwrap_err();
+        assert!(err.contains("does not match pinned"), "{err}");
+
+        let hash = content_hash(POLICY);
+        let pinned = cache.load(&url, IncludePin::Sha256(&hash), now).unwrap();
+        assert_eq!(pinned.content, POLICY);
+
+        // A hash-pinned copy never needs the server again, even when stale.
+        let much_later = now + Duration::days(30);
+        let offline = cache
+            .load(&url, IncludePin::Sha256(&hash), much_later)
+            .unwrap();
+        assert_eq!(offline.warning, None);
+
+        // An unpinned refresh that fails serves the stale copy with a warning.
+        let stale = cache.load(&url, IncludePin::None, much_later).unwrap();
+        assert_eq!(stale.content, POLICY);
+        assert!(stale.warning.unwrap().contains("refresh failed"));
+    }
+}
@@ -0,0 +1,38 @@
+//! Session memory of repeated denials.
+//!
+//! Agents often retry a blocked command verbatim. The first denial in a
+//! session gets the full box on stderr; identical repeats in that session get
+//! a one-line "previously blocked (xN)" instead, while the hook still answers
+//! with the full JSON deny. That keeps transcripts readable and skips the
+//! rendering (and any impact estimate) on retries. Denials are counted per
+//! (rule, command) in a [`SessionMemoryStore`], per [`current_session`].
+//!
+//! Recording is best-effort: when the memory cannot be read or written, the
+//! full box is shown.
+
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+use crate::session_memory::{SessionMemoryStore, current_session};
+
+/// Environment override for the repeat-denial memory path.
+pub const ENV_REPEAT_DENIALS_PATH: &str = "DCG_REPEAT_DENIALS_PATH";
+
+const REPEAT_DENIALS_FILE: &str = "repeat_denials.json";
+
+/// Resolve the repeat-denial memory path (env override or the dcg state directory).
+#[must_use]
+pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+    SessionMemoryStore::default_path(ENV_REPEAT_DENIALS_PATH, REPEAT_DENIALS_FILE, cwd)
+}
+
+/// Count a denial in the default store under the current session and return
+/// the session's running total for it. Fails open to 1 (show the full box)
+/// when the memory cannot be used.
+#[must_use]
+pub fn record_denial(rule_id: &str, command: &str, cwd: Option<&Path>) -> u32 {
+    let store = SessionMemoryStore::new(default_path(cwd));
+    store
+        .record(current_session().as_deref(), rule_id, command, Utc::now())
+        .unwrap_or(1)
+}
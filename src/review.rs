@@ -0,0 +1,321 @@
+//! Review queue for interactive learning.
+//!
+//! With `[interactive] learning = true`, every Medium-severity command the
+//! hook lets through with a warning is queued in a small JSON sidecar (next to
+//! the pending exception store by default). `dcg review` walks the queue one
+//! rule at a time and, using the suggest module's pattern generation, either
+//! promotes the commands to a `[overrides] block` rule or records them as
+//! safe in the allowlist.
+//!
+//! Recording is best-effort: a missing, locked, or corrupt sidecar never
+//! affects the hook decision.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::resolve_config_path_value;
+use crate::storage::{self, StateLock};
+use crate::suggest::{GeneratedPattern, generate_pattern_from_cluster};
+
+/// Environment override for the review queue path.
+pub const ENV_REVIEW_QUEUE_PATH: &str = "DCG_REVIEW_QUEUE_PATH";
+
+const REVIEW_QUEUE_FILE: &str = "review_queue.json";
+const SCHEMA_VERSION: u32 = 1;
+
+/// Oldest entries are dropped beyond this many distinct commands.
+pub const MAX_QUEUED_COMMANDS: usize = 500;
+
+/// One queued command.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QueuedCommand {
+    /// The command as the log file records it (see `[privacy] log_mode`).
+    pub command: String,
+    /// Rule that warned (`pack:pattern`).
+    pub rule_id: String,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    pub count: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// Contents of the review queue sidecar.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReviewQueue {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub commands: Vec<QueuedCommand>,
+}
+
+impl ReviewQueue {
+    /// Queued commands grouped by rule, most frequent rule first.
+    #[must_use]
+    pub fn groups(&self) -> Vec<ReviewGroup> {
+        let mut grouped: Vec<(&QueuedCommand, u64, Vec<String>)> = Vec::new();
+        for queued in &self.commands {
+            match grouped
+                .iter_mut()
+                .find(|(first, _, _)| first.rule_id == queued.rule_id)
+            {
+                Some((_, count, commands)) => {
+                    *count += queued.count;
+                    commands.push(queued.command.clone());
+                }
+                None => grouped.push((queued, queued.count, vec![queued.command.clone()])),
+            }
+        }
+
+        let mut groups: Vec<ReviewGroup> = grouped
+            .into_iter()
+            .map(|(first, count, commands)| ReviewGroup {
+                rule_id: first.rule_id.clone(),
+                reason: first.reason.clone(),
+                count,
+                pattern: generate_pattern_from_cluster(&commands),
+                commands,
+            })
+            .collect();
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then(a.rule_id.cmp(&b.rule_id)));
+        groups
+    }
+}
+
+/// Queued commands that tripped the same rule, with a generated pattern.
+#[derive(Debug, Clone)]
+pub struct ReviewGroup {
+    pub rule_id: String,
+    pub reason: String,
+    /// Total warnings across the group's commands.
+    pub count: u64,
+    /// Distinct commands, oldest first.
+    pub commands: Vec<String>,
+    /// Pattern matching every command in the group.
+    pub pattern: GeneratedPattern,
+}
+
+/// JSON-backed review queue store.
+#[derive(Debug, Clone)]
+pub struct ReviewQueueStore {
+    path: PathBuf,
+}
+
+impl ReviewQueueStore {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resolve the default path (env override or the dcg state directory).
+    #[must_use]
+    pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_REVIEW_QUEUE_PATH) {
+            if let Some(path) = resolve_config_path_value(&value, cwd) {
+                return path;
+            }
+        }
+
+        crate::paths::state_file(REVIEW_QUEUE_FILE)
+    }
+
+    /// Load the queue. A missing or unreadable sidecar yields an empty queue.
+    #[must_use]
+    pub fn load(&self) -> ReviewQueue {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Queue one warning for `command` under `rule_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn record(
+        &self,
+        command: &str,
+        rule_id: &str,
+        reason: &str,
+        working_dir: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> io::Result<()> {
+        self.update(|queue| {
+            let stamp = storage::format_timestamp(now);
+            if let Some(queued) = queue
+                .commands
+                .iter_mut()
+                .find(|q| q.command == command && q.rule_id == rule_id)
+            {
+                queued.count += 1;
+                queued.last_seen = stamp;
+                return;
+            }
+            queue.commands.push(QueuedCommand {
+                command: command.to_string(),
+                rule_id: rule_id.to_string(),
+                reason: reason.to_string(),
+                working_dir: working_dir.map(str::to_string),
+                count: 1,
+                first_seen: stamp.clone(),
+                last_seen: stamp,
+            });
+            if queue.commands.len() > MAX_QUEUED_COMMANDS {
+                let excess = queue.commands.len() - MAX_QUEUED_COMMANDS;
+                queue.commands.drain(..excess);
+            }
+        })
+    }
+
+    /// Remove every queued command for `rule_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn resolve(&self, rule_id: &str) -> io::Result<()> {
+        self.update(|queue| queue.commands.retain(|q| q.rule_id != rule_id))
+    }
+
+    /// Empty the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn clear(&self) -> io::Result<()> {
+        self.update(|queue| queue.commands.clear())
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut ReviewQueue)) -> io::Result<()> {
+        let _lock = StateLock::acquire(&self.path)?;
+        let mut queue: ReviewQueue = storage::read_json(&self.path)?.unwrap_or_default();
+        queue.schema_version = SCHEMA_VERSION;
+        apply(&mut queue);
+
+        storage::write_json(&self.path, &queue)
+    }
+}
+
+/// Queue a warning in the default store, ignoring failures (hook paths must
+/// not fail on bookkeeping).
+pub fn record_warning(command: &str, rule_id: &str, reason: &str, cwd: Option<&Path>) {
+    let store = ReviewQueueStore::new(ReviewQueueStore::default_path(cwd));
+    let working_dir = cwd.map(|path| path.to_string_lossy());
+    let _ = store.record(command, rule_id, reason, working_dir.as_deref(), Utc::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn record_counts_repeats_and_resolve_drops_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReviewQueueStore::new(dir.path().join("queue.json"));
+        let rule = "core.git:stash-drop";
+
+        store
+            .record(
+                "git stash drop",
+                rule,
+                "drops",
+                Some("/w"),
+                at("2030-01-01T00:00:00Z"),
+            )
+            .unwrap();
+        store
+            .record(
+                "git stash drop",
+                rule,
+                "drops",
+                Some("/w"),
+                at("2030-01-02T00:00:00Z"),
+            )
+            .unwrap();
+        store
+            .record(
+                "docker system prune",
+                "containers.docker:system-prune",
+                "prunes",
+                None,
+                at("2030-01-03T00:00:00Z"),
+            )
+            .unwrap();
+
+        let queue = store.load();
+        assert_eq!(queue.schema_version, SCHEMA_VERSION);
+        assert_eq!(queue.commands.len(), 2);
+        assert_eq!(queue.commands[0].count, 2);
+        assert_eq!(queue.commands[0].first_seen, "2030-01-01T00:00:00Z");
+        assert_eq!(queue.commands[0].last_seen, "2030-01-02T00:00:00Z");
+
+        store.resolve(rule).unwrap();
+        let queue = store.load();
+        assert_eq!(queue.commands.len(), 1);
+        assert_eq!(queue.commands[0].rule_id, "containers.docker:system-prune");
+
+        store.clear().unwrap();
+        assert!(store.load().commands.is_empty());
+    }
+
+    #[test]
+    fn groups_share_a_generated_pattern_per_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReviewQueueStore::new(dir.path().join("queue.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        let rule = "core.git:stash-drop";
+
+        store
+            .record("git stash drop stash@{0}", rule, "drops", None, now)
+            .unwrap();
+        store
+            .record("git stash drop stash@{1}", rule, "drops", None, now)
+            .unwrap();
+        store
+            .record("git stash drop stash@{1}", rule, "drops", None, now)
+            .unwrap();
+        store
+            .record("rm -rf <path>", "core.filesystem:rm-rf", "rm", None, now)
+            .unwrap();
+
+        let groups = store.load().groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].rule_id, rule);
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].commands.len(), 2);
+        assert!(groups[0].pattern.matches_all);
+
+        let regex = regex::Regex::new(&groups[0].pattern.regex).unwrap();
+        assert!(regex.is_match("git stash drop stash@{0}"));
+        assert!(!regex.is_match("git stash drop stash@{2}"));
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReviewQueueStore::new(dir.path().join("queue.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        for i in 0..=MAX_QUEUED_COMMANDS {
+            store
+                .record(&format!("cmd {i}"), "p:r", "r", None, now)
+                .unwrap();
+        }
+        let queue = store.load();
+        assert_eq!(queue.commands.len(), MAX_QUEUED_COMMANDS);
+        assert_eq!(queue.commands[0].command, "cmd 1");
+    }
+}
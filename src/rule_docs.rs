@@ -0,0 +1,485 @@
+//! Per-rule documentation pages for `dcg docs generate`, and the same facts
+//! for a single rule for `dcg explain --rule`.
+//!
+//! Every named destructive pattern gets one Markdown page at
+//! [`rule_doc_path`], which is also where the SARIF `helpUri` and the
+//! terminal hyperlinks point. A `README.md` index lists the rules by pack.
+//! Examples come from the deny cases of the regression corpus, when one is
+//! given.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::packs::test_helpers::{CorpusCategory, load_corpus_dir};
+use crate::packs::{DestructivePattern, Pack, PatternSuggestion, Platform, REGISTRY};
+
+/// Most corpus examples shown per rule.
+const MAX_EXAMPLES: usize = 5;
+
+/// One generated file, relative to the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocPage {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Blocked example commands per rule ID.
+pub type RuleExamples = BTreeMap<String, Vec<String>>;
+
+/// Page of `rule_id` relative to the docs root, e.g. `core.git:reset-hard`
+/// lives at `core/git/reset-hard.md`.
+#[must_use]
+pub fn rule_doc_path(rule_id: &str) -> String {
+    format!("{}.md", rule_id.replace([':', '.'], "/"))
+}
+
+/// Collect blocked example commands per rule from the true-positive and
+/// bypass cases of the corpus at `dir`.
+///
+/// # Errors
+///
+/// Returns an error if a corpus file cannot be read or parsed.
+pub fn load_examples(dir: &Path) -> Result<RuleExamples, String> {
+    let mut examples = RuleExamples::new();
+    for (category, _, case) in load_corpus_dir(dir)? {
+        if !matches!(
+            category,
+            CorpusCategory::TruePositives | CorpusCategory::BypassAttempts
+        ) || case.expected != "deny"
+        {
+            continue;
+        }
+        let rule_id = case
+            .rule_id
+            .or_else(|| case.log.and_then(|log| log.rule_id));
+        let Some(rule_id) = rule_id else {
+            continue;
+        };
+        let commands = examples.entry(rule_id).or_default();
+        if commands.len() < MAX_EXAMPLES && !commands.contains(&case.command) {
+            commands.push(case.command);
+        }
+    }
+    Ok(examples)
+}
+
+/// Render a page per named destructive pattern of `packs`, plus the index.
+///
+/// Packs are listed in the order given; patterns without a name have no
+/// rule ID and are skipped.
+#[must_use]
+pub fn generate(packs: &[&Pack], examples: &RuleExamples) -> Vec<DocPage> {
+    let mut pages = Vec::new();
+    let mut index = String::from(
+        "# Rule reference\n\n\
+         One page per destructive rule: what it matches, why it is blocked, \
+         safer alternatives, and how to allowlist it.\n\n\
+         Generated by `dcg docs generate`; do not edit by hand.\n",
+    );
+
+    for pack in packs {
+        let rules: Vec<(&str, &DestructivePattern)> = pack
+            .destructive_patterns
+            .iter()
+            .filter_map(|pattern| pattern.name.map(|name| (name, pattern)))
+            .collect();
+        if rules.is_empty() {
+            continue;
+        }
+
+        let _ = write!(index, "\n## {} (`{}`)\n\n", pack.name, pack.id);
+        for (name, pattern) in rules {
+            let rule_id = format!("{}:{name}", pack.id);
+            let path = rule_doc_path(&rule_id);
+            let _ = writeln!(
+                index,
+                "- [`{rule_id}`]({path}) ({}): {}",
+                pattern.severity.label(),
+                pattern.reason
+            );
+            let contents = rule_page(pack, &rule_id, &path, pattern, examples.get(&rule_id));
+            pages.push(DocPage {
+                path: PathBuf::from(path),
+                contents,
+            });
+        }
+    }
+
+    pages.push(DocPage {
+        path: PathBuf::from("README.md"),
+        contents: index,
+    });
+    pages
+}
+
+fn rule_page(
+    pack: &Pack,
+    rule_id: &str,
+    path: &str,
+    pattern: &DestructivePattern,
+    examples: Option<&Vec<String>>,
+) -> String {
+    let index = format!("{}README.md", "../".repeat(path.matches('/').count()));
+    let mut out = String::new();
+    let _ = writeln!(out, "# `{rule_id}`\n");
+    let _ = writeln!(
+        out,
+        "**Pack:** {} (`{}`)  \n**Severity:** {}  \n**Default decision:** {}\n",
+        pack.name,
+        pack.id,
+        pattern.severity.label(),
+        pattern.severity.default_mode().label()
+    );
+    let _ = writeln!(out, "{}\n", pattern.reason);
+
+    if let Some(explanation) = pattern.explanation {
+        let _ = writeln!(out, "## Why it is blocked\n\n{}\n", explanation.trim_end());
+    }
+
+    let _ = writeln!(
+        out,
+        "## Pattern\n\n```text\n{}\n```\n",
+        pattern.regex.as_str()
+    );
+
+    if let Some(examples) = examples.filter(|examples| !examples.is_empty()) {
+        out.push_str("## Examples\n\nBlocked:\n\n```bash\n");
+        for example in examples {
+            let _ = writeln!(out, "{example}");
+        }
+        out.push_str("```\n\n");
+    }
+
+    if !pattern.suggestions.is_empty() {
+        out.push_str("## Safe alternatives\n\n");
+        for suggestion in pattern.suggestions {
+            let platform = match suggestion.platform {
+                Platform::All => String::new(),
+                platform => format!(" ({})", platform_label(platform)),
+            };
+            let _ = writeln!(
+                out,
+                "- `{}`{platform}: {}",
+                suggestion.command, suggestion.description
+            );
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(
+        out,
+        "## Allowlisting\n\n\
+         If this command is expected in your project, allowlist the rule with a reason:\n\n\
+         ```toml\n{}```\n\n\
+         or run `dcg allowlist add {rule_id} --project`. \
+         `dcg explain \"<command>\"` shows how a specific command is evaluated.\n\n\
+         [All rules]({index})",
+        allowlist_stanza(rule_id)
+    );
+    out
+}
+
+/// The `allowlist.toml` entry that permits `rule_id`.
+#[must_use]
+pub fn allowlist_stanza(rule_id: &str) -> String {
+    format!("[[allow]]\nrule = \"{rule_id}\"\nreason = \"<why this is safe here>\"\n")
+}
+
+/// What `dcg explain --rule` reports about one rule, all from pack metadata
+/// except the examples.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleInfo {
+    pub rule_id: String,
+    /// The ID as given, when it is a former name of the rule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    pub pack_id: String,
+    pub pack_name: String,
+    pub severity: &'static str,
+    pub default_decision: &'static str,
+    pub reason: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<&'static str>,
+    /// The match pattern with regex syntax spelled out (see [`readable_pattern`]).
+    pub pattern: String,
+    pub regex: String,
+    /// Blocked commands from the regression corpus.
+    pub examples: Vec<String>,
+    pub safe_alternatives: &'static [PatternSuggestion],
+    pub allowlist_stanza: String,
+    /// Rule page relative to the docs root.
+    pub doc_path: String,
+}
+
+/// Look up `rule_id` (`pack:pattern`, or a former name of it) in `packs`.
+/// Examples are taken from `examples` when given.
+///
+/// # Errors
+///
+/// Returns a message naming the unknown pack or rule.
+pub fn rule_info(
+    rule_id: &str,
+    packs: &[&Pack],
+    examples: Option<&RuleExamples>,
+) -> Result<RuleInfo, String> {
+    let Some((pack_id, name)) = rule_id.trim().split_once(':') else {
+        return Err(format!(
+            "'{rule_id}' is not a rule ID; expected <pack>:<pattern>, e.g. core.git:reset-hard"
+        ));
+    };
+    let pack = packs
+        .iter()
+        .find(|pack| pack.id == pack_id)
+        .ok_or_else(|| format!("unknown pack '{pack_id}' (see `dcg packs`)"))?;
+    let given = name;
+    let current = REGISTRY.canonical_pattern_name(pack_id, given);
+    let name = current.unwrap_or(given);
+    let pattern = pack
+        .destructive_patterns
+        .iter()
+        .find(|pattern| pattern.name == Some(name))
+        .ok_or_else(|| {
+            format!("pack '{pack_id}' has no rule '{name}' (see `dcg pack info {pack_id}`)")
+        })?;
+
+    let rule_id = format!("{pack_id}:{name}");
+    Ok(RuleInfo {
+        renamed_from: current.map(|_| format!("{pack_id}:{given}")),
+        pack_id: pack.id.clone(),
+        pack_name: pack.name.to_string(),
+        severity: pattern.severity.label(),
+        default_decision: pattern.severity.default_mode().label(),
+        reason: pattern.reason,
+        explanation: pattern.explanation,
+        pattern: readable_pattern(pattern.regex.as_str()),
+        regex: pattern.regex.as_str().to_string(),
+        examples: examples
+            .and_then(|examples| examples.get(&rule_id))
+            .cloned()
+            .unwrap_or_default(),
+        safe_alternatives: pattern.suggestions,
+        allowlist_stanza: allowlist_stanza(&rule_id),
+        doc_path: rule_doc_path(&rule_id),
+        rule_id,
+    })
+}
+
+/// Spell out the regex syntax of a rule pattern for people reading it.
+///
+/// Whitespace classes become spaces, `\S+` becomes `<arg>`, wildcards become
+/// `…`, and anchors, word boundaries, and flags are dropped. Character
+/// classes and alternations are kept as written.
+#[must_use]
+pub fn readable_pattern(regex: &str) -> String {
+    let mut out = String::new();
+    let mut chars = regex.chars().peekable();
+    let skip_quantifier = |chars: &mut std::iter::Peekable<std::str::Chars<'_>>| {
+        if matches!(chars.peek(), Some('+' | '*' | '?')) {
+            chars.next();
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('s') => {
+                    out.push(' ');
+                    skip_quantifier(&mut chars);
+                }
+                Some('S') => {
+                    out.push_str("<arg>");
+                    skip_quantifier(&mut chars);
+                }
+                Some('d') => {
+                    out.push_str("<digits>");
+                    skip_quantifier(&mut chars);
+                }
+                Some('w') => {
+                    out.push_str("<word>");
+                    skip_quantifier(&mut chars);
+                }
+                Some('b' | 'B' | 'A' | 'z' | 'Z') | None => {}
+                Some(other) => out.push(other),
+            },
+            '[' => {
+                out.push(c);
+                while let Some(inner) = chars.next() {
+                    out.push(inner);
+                    if inner == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            out.push(escaped);
+                        }
+                    } else if inner == ']' {
+                        break;
+                    }
+                }
+            }
+            '(' if chars.peek() == Some(&'?') => {
+                chars.next();
+                match chars.next() {
+                    Some(':' | '=') => out.push('('),
+                    Some('!') => out.push_str("(not "),
+                    Some('<') if matches!(chars.peek(), Some('=' | '!')) => {
+                        if chars.next() == Some('!') {
+                            out.push_str("(not ");
+                        } else {
+                            out.push('(');
+                        }
+                    }
+                    // Named groups: `(?P<name>` and `(?<name>`.
+                    Some('P' | '<') => {
+                        for name in chars.by_ref() {
+                            if name == '>' {
+                                break;
+                            }
+                        }
+                        out.push('(');
+                    }
+                    // Inline flags such as `(?i)`.
+                    _ => {
+                        for flag in chars.by_ref() {
+                            if flag == ')' {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            '.' => {
+                out.push('…');
+                skip_quantifier(&mut chars);
+            }
+            '^' | '$' => {}
+            _ => out.push(c),
+        }
+    }
+    let out = out.replace("(<arg> )*", " [<args>] ");
+    out.split(' ')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) const fn platform_label(platform: Platform) -> &'static str {
+    match platform {
+        Platform::All => "all platforms",
+        Platform::Linux => "Linux",
+        Platform::MacOS => "macOS",
+        Platform::Windows => "Windows",
+        Platform::Bsd => "BSD",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::REGISTRY;
+
+    #[test]
+    fn rule_pages_match_help_uri_paths() {
+        assert_eq!(
+            rule_doc_path("core.git:reset-hard"),
+            "core/git/reset-hard.md"
+        );
+        assert!(
+            crate::sarif::rule_help_uri("core.git:reset-hard").ends_with(&format!(
+                "docs/rules/{}",
+                rule_doc_path("core.git:reset-hard")
+            ))
+        );
+    }
+
+    #[test]
+    fn generates_page_per_named_rule_with_examples() {
+        let pack = REGISTRY.get("core.git").expect("core.git pack");
+        let examples = RuleExamples::from([(
+            "core.git:reset-hard".to_string(),
+            vec!["git reset --hard HEAD~1".to_string()],
+        )]);
+        let pages = generate(&[pack], &examples);
+
+        let named = pack
+            .destructive_patterns
+            .iter()
+            .filter(|p| p.name.is_some())
+            .count();
+        assert_eq!(pages.len(), named + 1);
+
+        let page = pages
+            .iter()
+            .find(|page| page.path == Path::new("core/git/reset-hard.md"))
+            .expect("reset-hard page");
+        assert!(page.contents.starts_with("# `core.git:reset-hard`"));
+        assert!(page.contents.contains("git reset --hard HEAD~1"));
+        assert!(page.contents.contains("rule = \"core.git:reset-hard\""));
+        assert!(page.contents.contains("[All rules](../../README.md)"));
+
+        let index = pages.last().expect("index");
+        assert_eq!(index.path, Path::new("README.md"));
+        assert!(
+            index
+                .contents
+                .contains("[`core.git:reset-hard`](core/git/reset-hard.md)")
+        );
+    }
+
+    #[test]
+    fn rule_info_resolves_ids_and_former_names() {
+        let packs = [REGISTRY.get("core.git").expect("core.git pack")];
+        let examples = RuleExamples::from([(
+            "core.git:reset-hard".to_string(),
+            vec!["git reset --hard HEAD~1".to_string()],
+        )]);
+        let info = rule_info("core.git:reset-hard", &packs, Some(&examples)).expect("known rule");
+        assert_eq!(info.pack_id, "core.git");
+        assert_eq!(info.severity, "critical");
+        assert_eq!(info.pattern, "git [<args>] reset --hard");
+        assert_eq!(info.examples, ["git reset --hard HEAD~1"]);
+        assert!(
+            info.allowlist_stanza
+                .contains("rule = \"core.git:reset-hard\"")
+        );
+        assert_eq!(info.doc_path, "core/git/reset-hard.md");
+        assert!(info.renamed_from.is_none());
+
+        let renamed = rule_info("core.git:restore-discard", &packs, None).expect("former name");
+        assert_eq!(renamed.rule_id, "core.git:restore-worktree");
+        assert_eq!(
+            renamed.renamed_from.as_deref(),
+            Some("core.git:restore-discard")
+        );
+
+        assert!(rule_info("core.git", &packs, None).is_err());
+        assert!(rule_info("nope.pack:rule", &packs, None).is_err());
+        assert!(rule_info("core.git:nope", &packs, None).is_err());
+    }
+
+    #[test]
+    fn readable_pattern_spells_out_regex_syntax() {
+        assert_eq!(
+            readable_pattern(r"git\s+(?:\S+\s+)*clean\s+(?:-[a-z]*f|--force\b)"),
+            "git [<args>] clean (-[a-z]*f|--force)"
+        );
+        assert_eq!(
+            readable_pattern(r"(?i)^DROP\s+TABLE\s+(?P<name>\w+)$"),
+            "DROP TABLE (<word>)"
+        );
+        assert_eq!(
+            readable_pattern(r"kubectl\s+delete\b(?!.*--dry-run)"),
+            "kubectl delete(not …--dry-run)"
+        );
+        assert_eq!(readable_pattern(r"rm\s+[^\s]*\.bak"), "rm [^\\s]*.bak");
+    }
+
+    #[test]
+    fn loads_examples_from_repo_corpus() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+        let examples = load_examples(&dir).expect("corpus loads");
+        let reset = examples
+            .get("core.git:reset-hard")
+            .expect("reset-hard cases");
+        assert!(reset.contains(&"git reset --hard".to_string()));
+        assert!(reset.len() <= MAX_EXAMPLES);
+    }
+}
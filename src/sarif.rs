@@ -397,21 +397,20 @@ impl SarifReport {
                 .unwrap_or_else(|| finding.extractor_id.clone());
 
             let rule_key = rule_id.clone();
-            rules_map.entry(rule_key).or_insert_with(|| SarifReportingDescriptor {
-                id: rule_id.clone(),
-                name: Some(humanize_rule_id(&rule_id)),
-                short_description: finding.reason.as_ref().map(SarifMessage::text),
-                full_description: None,
-                help_uri: Some(format!(
-                    "https://github.com/Dicklesworthstone/destructive_command_guard/blob/master/docs/rules/{}.md",
-                    rule_id.replace([':', '.'], "/")
-                )),
-                default_configuration: Some(SarifReportingConfiguration {
-                    level: Some(finding.severity.into()),
-                    enabled: Some(true),
-                }),
-                properties: None,
-            });
+            rules_map
+                .entry(rule_key)
+                .or_insert_with(|| SarifReportingDescriptor {
+                    id: rule_id.clone(),
+                    name: Some(humanize_rule_id(&rule_id)),
+                    short_description: finding.reason.as_ref().map(SarifMessage::text),
+                    full_description: None,
+                    help_uri: Some(rule_help_uri(&rule_id)),
+                    default_configuration: Some(SarifReportingConfiguration {
+                        level: Some(finding.severity.into()),
+                        enabled: Some(true),
+                    }),
+                    properties: None,
+                });
         }
 
         let rules: Vec<_> = rules_map.into_values().collect();
@@ -439,7 +438,7 @@ impl SarifReport {
                 },
                 results,
                 invocations: Some(vec![SarifInvocation {
-                    execution_successful: true,
+                    execution_successful: report.summary.interrupted.is_none(),
                     working_directory: std::env::current_dir().ok().map(|p| {
                         SarifArtifactLocation {
                             uri: p.display().to_string(),
@@ -518,6 +517,16 @@ fn finding_to_result(finding: &ScanFinding) -> SarifResult {
     }
 }
 
+/// Documentation page for a rule ID, e.g. `core.git:reset-hard` maps to
+/// `docs/rules/core/git/reset-hard.md` (see `dcg docs generate`).
+#[must_use]
+pub fn rule_help_uri(rule_id: &str) -> String {
+    format!(
+        "https://github.com/Dicklesworthstone/destructive_command_guard/blob/master/docs/rules/{}",
+        crate::rule_docs::rule_doc_path(rule_id)
+    )
+}
+
 /// Convert a rule ID like "git.force-push" to "Git Force Push".
 fn humanize_rule_id(rule_id: &str) -> String {
     rule_id
@@ -550,6 +559,8 @@ mod tests {
             rule_id: Some("core.filesystem:recursive-delete-root".to_string()),
             reason: Some("Recursively deletes the entire filesystem".to_string()),
             suggestion: Some("Use a specific path instead of root".to_string()),
+            script: None,
+            yaml_path: None,
         }
     }
 
@@ -565,6 +576,7 @@ mod tests {
                 severities: crate::scan::ScanSeverityCounts::default(),
                 max_findings_reached: false,
                 elapsed_ms: None,
+                interrupted: None,
             },
             findings: vec![
                 mock_finding(ScanDecision::Deny, ScanSeverity::Error),
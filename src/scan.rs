@@ -33,11 +33,13 @@ use crate::evaluator::{
 };
 use crate::packs::{DecisionMode, REGISTRY, Severity};
 use crate::suggestions::{SuggestionKind, get_suggestion_by_kind};
-use clap::ValueEnum;
 use memchr::memmem;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 pub const SCAN_SCHEMA_VERSION: u32 = 1;
 
@@ -127,11 +129,12 @@ fn warn_unknown_hooks_toml_keys(value: &toml::Value, path: &str, warnings: &mut
 }
 
 /// Scan output format.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 #[serde(rename_all = "snake_case")]
 pub enum ScanFormat {
     /// Human-readable output
-    #[value(alias = "text")]
+    #[cfg_attr(feature = "cli", value(alias = "text"))]
     Pretty,
     /// Structured JSON output
     Json,
@@ -142,7 +145,8 @@ pub enum ScanFormat {
 }
 
 /// Controls scan failure behavior (CI integration).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 #[serde(rename_all = "snake_case")]
 pub enum ScanFailOn {
     None,
@@ -162,7 +166,8 @@ impl ScanFailOn {
 }
 
 /// Redaction mode for scan output.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
 #[serde(rename_all = "snake_case")]
 pub enum ScanRedactMode {
     /// No redaction.
@@ -233,6 +238,14 @@ pub struct ScanFinding {
     pub reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggestion: Option<String>,
+    /// Makefile target, justfile recipe, or package.json/deno.json script
+    /// that runs the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Path of the YAML node holding the command, e.g. `[0].tasks[2].shell`
+    /// (Ansible, cloud-init and Kubernetes manifests).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yaml_path: Option<String>,
 }
 
 /// Counts of findings by decision.
@@ -263,6 +276,9 @@ pub struct ScanSummary {
     pub max_findings_reached: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub elapsed_ms: Option<u64>,
+    /// Set when the scan was cancelled or timed out; findings are partial.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interrupted: Option<ScanInterruptReason>,
 }
 
 /// Complete scan output (stable JSON schema).
@@ -283,6 +299,10 @@ pub struct ScanOptions {
     pub redact: ScanRedactMode,
     /// Truncate extracted commands in output (chars). 0 disables truncation.
     pub truncate: usize,
+    /// Worker threads for parallel scanning. 0 uses one per CPU; 1 scans
+    /// sequentially. Library builds without the `rayon` feature (enabled by
+    /// `cli`) always scan sequentially.
+    pub jobs: usize,
 }
 
 /// Precomputed evaluator context for scanning.
@@ -303,8 +323,8 @@ impl ScanEvalContext {
         let enabled_keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
         let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
         let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
-        let compiled_overrides = config.overrides.compile();
-        let allowlists = crate::load_default_allowlists();
+        let compiled_overrides = config.compile_overrides();
+        let allowlists = config.load_allowlists();
         let heredoc_settings = config.heredoc_settings();
 
         Self {
@@ -318,6 +338,84 @@ impl ScanEvalContext {
     }
 }
 
+/// Why a scan stopped before visiting every file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanInterruptReason {
+    /// Cancelled by the user (Ctrl-C).
+    Cancelled,
+    /// The `--timeout` budget ran out.
+    Timeout,
+}
+
+impl ScanInterruptReason {
+    /// Process exit code for a scan that stopped for this reason.
+    #[must_use]
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::Cancelled => crate::exit_codes::EXIT_INTERRUPTED,
+            Self::Timeout => crate::exit_codes::EXIT_TIMEOUT,
+        }
+    }
+
+    /// Short human-readable description ("cancelled", "timed out").
+    #[must_use]
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::Cancelled => "cancelled",
+            Self::Timeout => "timed out",
+        }
+    }
+}
+
+/// Cooperative stop signal for a running scan.
+///
+/// The scan checks it before each file. Files that were already scanned are
+/// still reported, and the report's `summary.interrupted` records why it stopped.
+#[derive(Debug, Clone, Default)]
+pub struct ScanInterrupt {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl ScanInterrupt {
+    /// A signal that never fires until [`cancel`](Self::cancel) is called.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also stop once `timeout` has elapsed from now.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Instant::now().checked_add(timeout);
+        self
+    }
+
+    /// Request cancellation. Safe to call from a signal handler thread.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns the reason to stop, if any. Cancellation wins over the timeout.
+    #[must_use]
+    pub fn check(&self) -> Option<ScanInterruptReason> {
+        if self.is_cancelled() {
+            return Some(ScanInterruptReason::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(ScanInterruptReason::Timeout),
+            _ => None,
+        }
+    }
+}
+
 #[must_use]
 pub fn should_fail(report: &ScanReport, fail_on: ScanFailOn) -> bool {
     report.findings.iter().any(|f| fail_on.blocks(f.severity))
@@ -354,6 +452,16 @@ pub fn evaluate_extracted_command(
     config: &Config,
     ctx: &ScanEvalContext,
 ) -> Option<ScanFinding> {
+    let metadata_str = |key: &str| {
+        extracted
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(key))
+            .and_then(serde_json::Value::as_str)
+            .map(ToString::to_string)
+    };
+    let script = metadata_str("script_name");
+    let yaml_path = metadata_str("yaml_path");
     let project_path = {
         let candidate = std::path::Path::new(&extracted.file);
         if candidate.is_absolute() {
@@ -389,14 +497,17 @@ pub fn evaluate_extracted_command(
             rule_id: None,
             reason: Some("Blocked (missing match metadata)".to_string()),
             suggestion: None,
+            script,
+            yaml_path,
         });
     };
 
     let (rule_id, severity, decision_mode) = resolve_severity_and_rule_id(config, &pattern);
 
     let scan_decision = match decision_mode {
-        Some(DecisionMode::Deny) | None => ScanDecision::Deny,
-        Some(DecisionMode::Warn) => ScanDecision::Warn,
+        // Nobody is prompted during a scan, so ask counts as deny.
+        Some(DecisionMode::Deny | DecisionMode::Ask) | None => ScanDecision::Deny,
+        Some(DecisionMode::Warn | DecisionMode::WarnOnce) => ScanDecision::Warn,
         Some(DecisionMode::Log) => ScanDecision::Allow,
     };
 
@@ -424,6 +535,8 @@ pub fn evaluate_extracted_command(
         rule_id,
         reason: Some(pattern.reason),
         suggestion,
+        script,
+        yaml_path,
     })
 }
 
@@ -607,7 +720,12 @@ pub type ScanProgressCallback<'a> = &'a mut dyn FnMut(usize, usize, &str);
 /// - GitHub Actions workflow extractor (`.github/workflows/*.yml|*.yaml`)
 /// - GitLab CI extractor (`.gitlab-ci.yml`, `*.gitlab-ci.yml`)
 /// - Makefile extractor (`Makefile`)
+/// - justfile extractor (`justfile`, `.justfile` - recipe bodies)
 /// - package.json extractor (`package.json` - scripts only)
+/// - deno.json extractor (`deno.json`, `deno.jsonc` - tasks only)
+/// - Ansible extractor (`shell`/`command`/`raw` tasks in playbooks and roles)
+/// - cloud-init extractor (`#cloud-config`, `user-data` - `runcmd`/`bootcmd`)
+/// - Kubernetes extractor (Job/CronJob manifests - container `command`/`args`)
 /// - Terraform extractor (`*.tf` - provisioner blocks)
 /// - docker-compose extractor (`docker-compose.yml`, `compose.yml` - command/entrypoint)
 #[allow(clippy::missing_errors_doc)]
@@ -653,7 +771,6 @@ pub fn scan_paths(
 /// )?;
 /// ```
 #[allow(clippy::missing_errors_doc)]
-#[allow(clippy::too_many_lines)]
 pub fn scan_paths_with_progress(
     paths: &[PathBuf],
     options: &ScanOptions,
@@ -664,16 +781,39 @@ pub fn scan_paths_with_progress(
     repo_root: Option<&Path>,
     progress: Option<ScanProgressCallback<'_>>,
 ) -> Result<ScanReport, String> {
-    let started = std::time::Instant::now();
+    scan_paths_interruptible(
+        paths,
+        options,
+        config,
+        ctx,
+        include,
+        exclude,
+        repo_root,
+        progress,
+        &ScanInterrupt::new(),
+    )
+}
 
-    let mut files: Vec<PathBuf> = Vec::new();
-    let mut visited: HashSet<PathBuf> = HashSet::new();
-    for path in paths {
-        collect_files_recursively(path, &mut files, &mut visited);
-    }
+/// Scan file paths with progress reporting and a stop signal.
+///
+/// Same as [`scan_paths_with_progress`], but stops before the next file once
+/// `interrupt` fires (Ctrl-C or timeout). The returned report covers the
+/// files scanned so far and sets `summary.interrupted`.
+#[allow(clippy::missing_errors_doc)]
+pub fn scan_paths_interruptible(
+    paths: &[PathBuf],
+    options: &ScanOptions,
+    config: &Config,
+    ctx: &ScanEvalContext,
+    include: &[String],
+    exclude: &[String],
+    repo_root: Option<&Path>,
+    progress: Option<ScanProgressCallback<'_>>,
+    interrupt: &ScanInterrupt,
+) -> Result<ScanReport, String> {
+    let started = Instant::now();
 
-    files.sort();
-    files.dedup();
+    let mut files = collect_files(paths);
 
     if !include.is_empty() || !exclude.is_empty() {
         files = filter_paths(&files, include, exclude, repo_root);
@@ -687,184 +827,504 @@ pub fn scan_paths_with_progress(
         cb(0, total_files, "");
     }
 
-    let mut files_scanned = 0usize;
-    let mut files_skipped = 0usize;
-    let mut commands_extracted = 0usize;
-    let mut findings: Vec<ScanFinding> = Vec::new();
-    let mut max_findings_reached = false;
+    let mut tally = ScanTally::new(options.max_findings);
+    let jobs = resolve_scan_jobs(options.jobs);
+
+    #[cfg(feature = "rayon")]
+    if jobs > 1 && files.len() > 1 {
+        scan_files_parallel(
+            &files,
+            jobs,
+            options,
+            config,
+            ctx,
+            &mut progress,
+            interrupt,
+            &mut tally,
+        )?;
+        return Ok(tally.into_report(started));
+    }
+    #[cfg(not(feature = "rayon"))]
+    let _ = jobs;
 
     for (file_idx, file) in files.iter().enumerate() {
         // Report progress
         if let Some(ref mut cb) = progress {
             cb(file_idx + 1, total_files, &file.to_string_lossy());
         }
-        if findings.len() >= options.max_findings {
-            max_findings_reached = true;
+        if !tally.begin_file(interrupt) {
             break;
         }
 
-        let Ok(meta) = std::fs::metadata(file) else {
-            files_skipped += 1;
-            continue;
-        };
-
-        if !meta.is_file() {
-            files_skipped += 1;
-            continue;
+        let extraction = extract_file_commands(file, options, ctx);
+        if !tally.record(extraction, |cmd| {
+            evaluate_extracted_command(cmd, options, config, ctx)
+        }) {
+            break;
         }
+    }
 
-        if meta.len() > options.max_file_size_bytes {
-            files_skipped += 1;
-            continue;
-        }
+    Ok(tally.into_report(started))
+}
 
-        // Determine which extractor(s) to use
-        let is_shell = is_shell_script_path(file);
-        let is_docker = is_dockerfile_path(file);
-        let is_actions = is_github_actions_workflow_path(file);
-        let is_gitlab = is_gitlab_ci_path(file);
-        let is_azure = is_azure_pipelines_path(file);
-        let is_circleci = is_circleci_path(file);
-        let is_makefile = is_makefile_path(file);
-        let is_package_json = is_package_json_path(file);
-        let is_terraform = is_terraform_path(file);
-        let is_compose = is_docker_compose_path(file);
-
-        if !is_shell
-            && !is_docker
-            && !is_actions
-            && !is_gitlab
-            && !is_azure
-            && !is_circleci
-            && !is_makefile
-            && !is_package_json
-            && !is_terraform
-            && !is_compose
-        {
-            files_skipped += 1;
-            continue;
-        }
+/// Resolve `--jobs`: `0` means one worker per available CPU.
+#[must_use]
+pub fn resolve_scan_jobs(jobs: usize) -> usize {
+    if jobs == 0 {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    } else {
+        jobs
+    }
+}
 
-        let Ok(bytes) = std::fs::read(file) else {
-            files_skipped += 1;
-            continue;
-        };
+// Parallel scans share one `ScanEvalContext` and `Config` across worker
+// threads. Compiled pack regexes are process-wide statics, and the `regex`
+// crate keeps a per-thread scratch cache for each of them, so no per-worker
+// evaluator copy is needed.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ScanEvalContext>();
+    assert_send_sync::<Config>();
+};
 
-        let content = String::from_utf8_lossy(&bytes);
-        let file_label = file.to_string_lossy();
-        files_scanned += 1;
+/// Files handed to each worker per parallel batch.
+///
+/// Batches are folded in file order before the next one starts, so
+/// `--max-findings` stops a parallel scan at the same point as a sequential one.
+#[cfg(feature = "rayon")]
+const PARALLEL_FILES_PER_JOB: usize = 8;
 
-        // Extract commands using appropriate extractor(s)
-        let mut extracted: Vec<ExtractedCommand> = Vec::new();
+/// Scan `files` on a rayon pool of `jobs` threads.
+///
+/// Files are extracted and evaluated in parallel, then folded into `tally` in
+/// file order, so the report matches a sequential scan exactly.
+#[cfg(feature = "rayon")]
+fn scan_files_parallel(
+    files: &[PathBuf],
+    jobs: usize,
+    options: &ScanOptions,
+    config: &Config,
+    ctx: &ScanEvalContext,
+    progress: &mut Option<ScanProgressCallback<'_>>,
+    interrupt: &ScanInterrupt,
+    tally: &mut ScanTally,
+) -> Result<(), String> {
+    use rayon::prelude::*;
 
-        if is_shell {
-            extracted.extend(extract_shell_script_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
-        }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(|e| format!("Failed to start scan worker pool: {e}"))?;
 
-        if is_docker {
-            extracted.extend(extract_dockerfile_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
-        }
+    let total_files = files.len();
+    let batch_size = jobs.saturating_mul(PARALLEL_FILES_PER_JOB);
+    for (batch_idx, batch) in files.chunks(batch_size).enumerate() {
+        // Don't evaluate a whole batch just to discover the scan must stop.
+        if tally.limit_reached() || interrupt.check().is_some() {
+            if let Some(cb) = progress {
+                cb(
+                    batch_idx * batch_size + 1,
+                    total_files,
+                    &batch[0].to_string_lossy(),
+                );
+            }
+            tally.begin_file(interrupt);
+            return Ok(());
+        }
+
+        let results: Vec<(FileExtraction, Vec<Option<ScanFinding>>)> = pool.install(|| {
+            batch
+                .par_iter()
+                .map(|file| {
+                    let extraction = extract_file_commands(file, options, ctx);
+                    let evaluated = match &extraction {
+                        FileExtraction::Skipped => Vec::new(),
+                        FileExtraction::Scanned(commands) => commands
+                            .iter()
+                            .map(|cmd| evaluate_extracted_command(cmd, options, config, ctx))
+                            .collect(),
+                    };
+                    (extraction, evaluated)
+                })
+                .collect()
+        });
 
-        if is_actions {
-            extracted.extend(extract_github_actions_workflow_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
+        for (offset, (extraction, evaluated)) in results.into_iter().enumerate() {
+            let file_idx = batch_idx * batch_size + offset;
+            if let Some(cb) = progress {
+                cb(
+                    file_idx + 1,
+                    total_files,
+                    &files[file_idx].to_string_lossy(),
+                );
+            }
+            if !tally.begin_file(interrupt) {
+                return Ok(());
+            }
+            let mut evaluated = evaluated.into_iter();
+            if !tally.record(extraction, |_| evaluated.next().flatten()) {
+                return Ok(());
+            }
         }
+    }
 
-        if is_gitlab {
-            extracted.extend(extract_gitlab_ci_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
-        }
+    Ok(())
+}
 
-        if is_azure {
-            extracted.extend(extract_azure_pipelines_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
+/// Result of reading one file and running its extractors.
+enum FileExtraction {
+    /// Unreadable, too large, or not a recognized file type.
+    Skipped,
+    Scanned(Vec<ExtractedCommand>),
+}
+
+/// Running scan totals, folded in file order.
+struct ScanTally {
+    max_findings: usize,
+    files_scanned: usize,
+    files_skipped: usize,
+    commands_extracted: usize,
+    findings: Vec<ScanFinding>,
+    max_findings_reached: bool,
+    interrupted: Option<ScanInterruptReason>,
+}
+
+impl ScanTally {
+    const fn new(max_findings: usize) -> Self {
+        Self {
+            max_findings,
+            files_scanned: 0,
+            files_skipped: 0,
+            commands_extracted: 0,
+            findings: Vec::new(),
+            max_findings_reached: false,
+            interrupted: None,
         }
+    }
 
-        if is_circleci {
-            extracted.extend(extract_circleci_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
+    fn limit_reached(&self) -> bool {
+        self.findings.len() >= self.max_findings
+    }
+
+    /// Returns `false` (and marks the report truncated or interrupted) once
+    /// the scan must stop before the next file.
+    fn begin_file(&mut self, interrupt: &ScanInterrupt) -> bool {
+        if let Some(reason) = interrupt.check() {
+            self.interrupted = Some(reason);
+            return false;
+        }
+        if self.limit_reached() {
+            self.max_findings_reached = true;
+            return false;
         }
+        true
+    }
 
-        if is_makefile {
-            extracted.extend(extract_makefile_from_str(
-                &file_label,
-                &content,
-                &ctx.enabled_keywords,
-            ));
+    /// Fold one file, calling `evaluate` per extracted command until the
+    /// findings limit is hit. Returns `false` when scanning should stop.
+    fn record(
+        &mut self,
+        extraction: FileExtraction,
+        mut evaluate: impl FnMut(&ExtractedCommand) -> Option<ScanFinding>,
+    ) -> bool {
+        let commands = match extraction {
+            FileExtraction::Skipped => {
+                self.files_skipped += 1;
+                return true;
+            }
+            FileExtraction::Scanned(commands) => commands,
+        };
+
+        self.files_scanned += 1;
+        self.commands_extracted += commands.len();
+
+        for cmd in &commands {
+            if self.limit_reached() {
+                self.max_findings_reached = true;
+                return false;
+            }
+            if let Some(finding) = evaluate(cmd) {
+                self.findings.push(finding);
+            }
         }
+        true
+    }
+
+    fn into_report(self, started: Instant) -> ScanReport {
+        let elapsed_ms = u64::try_from(started.elapsed().as_millis()).ok();
+        let mut report = build_report(
+            self.findings,
+            self.files_scanned,
+            self.files_skipped,
+            self.commands_extracted,
+            self.max_findings_reached,
+            elapsed_ms,
+        );
+        report.summary.interrupted = self.interrupted;
+        report
+    }
+}
+
+/// Read `file` and run every extractor that applies to its path.
+fn extract_file_commands(
+    file: &Path,
+    options: &ScanOptions,
+    ctx: &ScanEvalContext,
+) -> FileExtraction {
+    let Ok(meta) = std::fs::metadata(file) else {
+        return FileExtraction::Skipped;
+    };
+
+    if !meta.is_file() {
+        return FileExtraction::Skipped;
+    }
+
+    if meta.len() > options.max_file_size_bytes {
+        return FileExtraction::Skipped;
+    }
+
+    // Determine which extractor(s) to use
+    let is_shell = is_shell_script_path(file);
+    let is_docker = is_dockerfile_path(file);
+    let is_actions = is_github_actions_workflow_path(file);
+    let is_gitlab = is_gitlab_ci_path(file);
+    let is_azure = is_azure_pipelines_path(file);
+    let is_circleci = is_circleci_path(file);
+    let is_makefile = is_makefile_path(file);
+    let is_justfile = is_justfile_path(file);
+    let is_package_json = is_package_json_path(file);
+    let is_deno_json = is_deno_json_path(file);
+    let is_terraform = is_terraform_path(file);
+    let is_compose = is_docker_compose_path(file);
+    let is_cloud_init = is_cloud_init_path(file);
+    // Other YAML is read and sniffed for Ansible, cloud-init and Kubernetes.
+    let is_other_yaml =
+        is_yaml_path(file) && !is_actions && !is_gitlab && !is_azure && !is_circleci && !is_compose;
+
+    if !is_shell
+        && !is_docker
+        && !is_actions
+        && !is_gitlab
+        && !is_azure
+        && !is_circleci
+        && !is_makefile
+        && !is_justfile
+        && !is_package_json
+        && !is_deno_json
+        && !is_terraform
+        && !is_compose
+        && !is_cloud_init
+        && !is_other_yaml
+    {
+        return FileExtraction::Skipped;
+    }
+
+    let Ok(bytes) = std::fs::read(file) else {
+        return FileExtraction::Skipped;
+    };
+
+    let content = String::from_utf8_lossy(&bytes);
+    let file_label = file.to_string_lossy();
+
+    // Extract commands using appropriate extractor(s)
+    let mut extracted: Vec<ExtractedCommand> = Vec::new();
+
+    if is_shell {
+        extracted.extend(extract_shell_script_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_docker {
+        extracted.extend(extract_dockerfile_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_actions {
+        extracted.extend(extract_github_actions_workflow_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_gitlab {
+        extracted.extend(extract_gitlab_ci_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_azure {
+        extracted.extend(extract_azure_pipelines_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_circleci {
+        extracted.extend(extract_circleci_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_makefile {
+        extracted.extend(extract_makefile_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_justfile {
+        extracted.extend(extract_justfile_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_package_json {
+        extracted.extend(extract_package_json_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_deno_json {
+        extracted.extend(extract_deno_json_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_terraform {
+        extracted.extend(extract_terraform_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
 
-        if is_package_json {
-            extracted.extend(extract_package_json_from_str(
+    if is_compose {
+        extracted.extend(extract_docker_compose_from_str(
+            &file_label,
+            &content,
+            &ctx.enabled_keywords,
+        ));
+    }
+
+    if is_cloud_init || is_other_yaml {
+        let is_ansible = is_other_yaml && looks_like_ansible(file, &content);
+        let is_cloud_init = is_cloud_init || looks_like_cloud_init(&content);
+        let is_kubernetes = is_other_yaml && looks_like_kubernetes_job(&content);
+        if !is_ansible && !is_cloud_init && !is_kubernetes && extracted.is_empty() {
+            return FileExtraction::Skipped;
+        }
+        if is_ansible {
+            extracted.extend(extract_ansible_from_str(
                 &file_label,
                 &content,
                 &ctx.enabled_keywords,
             ));
         }
-
-        if is_terraform {
-            extracted.extend(extract_terraform_from_str(
+        if is_cloud_init {
+            extracted.extend(extract_cloud_init_from_str(
                 &file_label,
                 &content,
                 &ctx.enabled_keywords,
             ));
         }
-
-        if is_compose {
-            extracted.extend(extract_docker_compose_from_str(
+        if is_kubernetes {
+            extracted.extend(extract_kubernetes_job_from_str(
                 &file_label,
                 &content,
                 &ctx.enabled_keywords,
             ));
         }
+    }
 
-        commands_extracted += extracted.len();
+    FileExtraction::Scanned(extracted)
+}
 
-        for cmd in extracted {
-            if findings.len() >= options.max_findings {
-                max_findings_reached = true;
-                break;
-            }
+/// Files under `paths` (directories expanded recursively), sorted and deduped.
+#[must_use]
+pub fn collect_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    for path in paths {
+        collect_files_recursively(path, &mut files, &mut visited);
+    }
 
-            if let Some(finding) = evaluate_extracted_command(&cmd, options, config, ctx) {
-                findings.push(finding);
-            }
-        }
+    files.sort();
+    files.dedup();
+    files
+}
 
-        if max_findings_reached {
-            break;
-        }
-    }
+/// Directories holding dependencies or build output rather than a project's
+/// own scripts.
+pub const DEPENDENCY_DIRS: &[&str] = &[".git", "node_modules", "target", "vendor", ".venv", "dist"];
 
-    let elapsed_ms = u64::try_from(started.elapsed().as_millis()).ok();
-    Ok(build_report(
-        findings,
-        files_scanned,
-        files_skipped,
-        commands_extracted,
-        max_findings_reached,
-        elapsed_ms,
-    ))
+/// Whether `path` lies in one of [`DEPENDENCY_DIRS`] below `root`.
+#[must_use]
+pub fn in_dependency_dir(path: &Path, root: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|component| {
+            DEPENDENCY_DIRS
+                .iter()
+                .any(|dir| component.as_os_str() == std::ffi::OsStr::new(dir))
+        })
+}
+
+/// Scan only script-runner files (Makefiles, justfiles, `package.json`, `deno.json`).
+///
+/// Directories are expanded recursively, skipping [`DEPENDENCY_DIRS`];
+/// findings name the script that runs the command.
+///
+/// # Errors
+///
+/// Returns an error if the scan fails.
+pub fn scan_scripts(
+    paths: &[PathBuf],
+    options: &ScanOptions,
+    config: &Config,
+    ctx: &ScanEvalContext,
+    exclude: &[String],
+    repo_root: Option<&Path>,
+) -> Result<ScanReport, String> {
+    let files: Vec<PathBuf> = paths
+        .iter()
+        .flat_map(|path| {
+            collect_files(std::slice::from_ref(path))
+                .into_iter()
+                .filter(move |file| is_script_runner_path(file) && !in_dependency_dir(file, path))
+        })
+        .collect();
+    scan_paths(&files, options, config, ctx, &[], exclude, repo_root)
+}
+
+/// Whether `path` is a Makefile, justfile, `package.json` or `deno.json`.
+#[must_use]
+pub fn is_script_runner_path(path: &Path) -> bool {
+    is_makefile_path(path)
+        || is_justfile_path(path)
+        || is_package_json_path(path)
+        || is_deno_json_path(path)
 }
+
 fn collect_files_recursively(
     path: &PathBuf,
     out: &mut Vec<PathBuf>,
@@ -2696,10 +3156,14 @@ pub fn extract_makefile_from_str(
     let lines: Vec<&str> = content.lines().collect();
     let mut out = Vec::new();
     let mut idx = 0usize;
+    let mut target: Option<&str> = None;
 
     while idx < lines.len() {
         let raw_line = lines[idx];
         if !raw_line.starts_with('\t') {
+            if let Some(name) = makefile_target_name(raw_line) {
+                target = Some(name);
+            }
             idx += 1;
             continue;
         }
@@ -2725,13 +3189,137 @@ pub fn extract_makefile_from_str(
             idx += 1;
         }
 
-        out.extend(extract_shell_script_with_offset_and_id(
-            file,
-            start_line,
-            &block,
-            enabled_keywords,
-            EXTRACTOR_ID,
-        ));
+        out.extend(with_script_name(
+            extract_shell_script_with_offset_and_id(
+                file,
+                start_line,
+                &block,
+                enabled_keywords,
+                EXTRACTOR_ID,
+            ),
+            target,
+        ));
+    }
+
+    out
+}
+
+/// Name of the first target of a `target: prerequisites` rule line.
+fn makefile_target_name(line: &str) -> Option<&str> {
+    if line.starts_with(['#', ' ', '.']) {
+        return None;
+    }
+    let (targets, rest) = line.split_once(':')?;
+    if rest.starts_with('=') || targets.contains('=') || targets.contains('$') {
+        return None;
+    }
+    targets.split_whitespace().next()
+}
+
+/// Tag commands with the Makefile target, justfile recipe or task that runs
+/// them.
+fn with_script_name(
+    mut commands: Vec<ExtractedCommand>,
+    name: Option<&str>,
+) -> Vec<ExtractedCommand> {
+    if let Some(name) = name {
+        for command in &mut commands {
+            command.metadata = Some(serde_json::json!({ "script_name": name }));
+        }
+    }
+    commands
+}
+
+// ============================================================================
+// Justfile extractor (justfile)
+// ============================================================================
+
+fn is_justfile_path(path: &Path) -> bool {
+    let file_name = path.file_name().and_then(std::ffi::OsStr::to_str);
+    file_name.is_some_and(|name| name.eq_ignore_ascii_case("justfile") || name == ".justfile")
+}
+
+/// Whether a top-level justfile line starts a recipe (`name args: deps`).
+fn is_justfile_recipe_header(line: &str) -> bool {
+    let trimmed = line.trim_end();
+    if trimmed.is_empty() || trimmed.starts_with(['#', '[']) {
+        return false;
+    }
+    let first_word = trimmed.split_whitespace().next().unwrap_or_default();
+    if matches!(
+        first_word,
+        "set" | "export" | "alias" | "import" | "mod" | "import?" | "mod?"
+    ) {
+        return false;
+    }
+    match trimmed.find(':') {
+        Some(pos) => !trimmed[pos..].starts_with(":="),
+        None => false,
+    }
+}
+
+/// Extract commands from justfile recipe bodies.
+///
+/// Recipe bodies are the indented lines following a recipe header. Shebang
+/// recipes are only extracted when the interpreter is a shell.
+#[must_use]
+pub fn extract_justfile_from_str(
+    file: &str,
+    content: &str,
+    enabled_keywords: &[&'static str],
+) -> Vec<ExtractedCommand> {
+    const EXTRACTOR_ID: &str = "justfile.recipe";
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = Vec::new();
+    let mut idx = 0usize;
+
+    while idx < lines.len() {
+        if !is_justfile_recipe_header(lines[idx]) {
+            idx += 1;
+            continue;
+        }
+        let recipe = lines[idx]
+            .trim_start_matches('@')
+            .split([' ', ':'])
+            .next()
+            .unwrap_or_default();
+        idx += 1;
+
+        let start_line = idx + 1;
+        let mut block = String::new();
+        while idx < lines.len() {
+            let line = lines[idx];
+            if !line.is_empty() && !line.starts_with([' ', '\t']) {
+                break;
+            }
+            if !block.is_empty() {
+                block.push('\n');
+            }
+            block.push_str(line.trim_start());
+            idx += 1;
+        }
+
+        if let Some(shebang) = block.strip_prefix("#!") {
+            let interpreter = shebang.lines().next().unwrap_or_default();
+            let is_shell = interpreter
+                .split(['/', ' '])
+                .any(|part| matches!(part, "sh" | "bash" | "zsh" | "dash"));
+            if !is_shell {
+                continue;
+            }
+        }
+
+        out.extend(with_script_name(
+            extract_shell_script_with_offset_and_id(
+                file,
+                start_line,
+                &block,
+                enabled_keywords,
+                EXTRACTOR_ID,
+            ),
+            Some(recipe),
+        ));
     }
 
     out
@@ -2849,6 +3437,110 @@ fn find_json_key_line(lines: &[&str], key: &str, parent: &str) -> usize {
     1 // Default to line 1 if not found
 }
 
+// ============================================================================
+// deno.json extractor
+// ============================================================================
+
+fn is_deno_json_path(path: &Path) -> bool {
+    let file_name = path.file_name().and_then(std::ffi::OsStr::to_str);
+    file_name.is_some_and(|name| name == "deno.json" || name == "deno.jsonc")
+}
+
+/// Extract tasks from deno.json / deno.jsonc.
+///
+/// A task is either a command string or an object with a `command` field.
+/// Comments are stripped so `deno.jsonc` parses.
+#[must_use]
+pub fn extract_deno_json_from_str(
+    file: &str,
+    content: &str,
+    enabled_keywords: &[&'static str],
+) -> Vec<ExtractedCommand> {
+    const EXTRACTOR_ID: &str = "deno_json.task";
+
+    let mut out = Vec::new();
+    let content = strip_json_comments(content);
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return out;
+    };
+    let Some(tasks) = json.get("tasks").and_then(|t| t.as_object()) else {
+        return out;
+    };
+
+    let line_map = build_json_line_map(&content);
+    for (task_name, task_value) in tasks {
+        let task_cmd = task_value
+            .as_str()
+            .or_else(|| task_value.get("command").and_then(|c| c.as_str()));
+        let Some(task_cmd) = task_cmd else {
+            continue;
+        };
+        if !enabled_keywords.is_empty() && !contains_any_keyword(task_cmd, enabled_keywords) {
+            continue;
+        }
+        out.push(ExtractedCommand {
+            file: file.to_string(),
+            line: find_json_key_line(&line_map, task_name, "tasks"),
+            col: None,
+            extractor_id: EXTRACTOR_ID.to_string(),
+            command: task_cmd.to_string(),
+            metadata: Some(serde_json::json!({ "script_name": task_name })),
+        });
+    }
+
+    out
+}
+
+/// Blank out `//` and `/* */` comments outside strings, keeping line breaks
+/// so line numbers still match the original.
+fn strip_json_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        out.push('\n');
+                    }
+                    if prev == '*' && skipped == '/' {
+                        break;
+                    }
+                    prev = skipped;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 // ============================================================================
 // Terraform extractor (*.tf) - provisioner blocks
 // ============================================================================
@@ -3428,6 +4120,556 @@ fn extract_docker_compose_command(
     out
 }
 
+// ============================================================================
+// YAML path tracking (Ansible, cloud-init, Kubernetes)
+// ============================================================================
+
+/// A mapping key found by [`yaml_entries`].
+#[derive(Debug)]
+struct YamlEntry<'a> {
+    idx: usize,
+    indent: usize,
+    key: String,
+    path: String,
+    value: &'a str,
+}
+
+/// Walk YAML lines and return every mapping key with its path, e.g.
+/// `[0].tasks[2].shell`. Block scalar bodies are skipped and `---` starts a
+/// new document.
+fn yaml_entries<'a>(lines: &[&'a str]) -> Vec<YamlEntry<'a>> {
+    enum Segment {
+        Key(String),
+        Index(usize),
+    }
+
+    fn render(stack: &[(usize, Segment)]) -> String {
+        use std::fmt::Write as _;
+
+        let mut path = String::new();
+        for (_, segment) in stack {
+            match segment {
+                Segment::Key(key) => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                }
+                Segment::Index(n) => {
+                    let _ = write!(path, "[{n}]");
+                }
+            }
+        }
+        path
+    }
+
+    let mut stack: Vec<(usize, Segment)> = Vec::new();
+    let mut out = Vec::new();
+    let mut block_indent: Option<usize> = None;
+
+    for (idx, raw) in lines.iter().enumerate() {
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = raw.len() - trimmed.len();
+        if let Some(parent) = block_indent {
+            if indent > parent {
+                continue;
+            }
+            block_indent = None;
+        }
+        if indent == 0 && (trimmed == "---" || trimmed.starts_with("--- ")) {
+            stack.clear();
+            continue;
+        }
+
+        let mut col = indent;
+        let mut rest = trimmed;
+        loop {
+            stack.retain(|(top, _)| *top <= col);
+            let item = rest
+                .strip_prefix("- ")
+                .or_else(|| (rest == "-").then_some(""));
+            if let Some(item) = item {
+                match stack.last_mut() {
+                    Some((top, Segment::Index(n))) if *top == col => *n += 1,
+                    _ => stack.push((col, Segment::Index(0))),
+                }
+                let item_trimmed = item.trim_start();
+                col += 2 + (item.len() - item_trimmed.len());
+                rest = item_trimmed;
+                if is_yaml_block_indicator(rest) {
+                    block_indent = Some(indent);
+                }
+                continue;
+            }
+
+            let Some((key, value)) = split_yaml_mapping(rest) else {
+                break;
+            };
+            stack.retain(|(top, _)| *top < col);
+            stack.push((col, Segment::Key(key.clone())));
+            if is_yaml_block_indicator(value) {
+                block_indent = Some(col);
+            }
+            out.push(YamlEntry {
+                idx,
+                indent: col,
+                key,
+                path: render(&stack),
+                value,
+            });
+            break;
+        }
+    }
+
+    out
+}
+
+fn is_yaml_block_indicator(value: &str) -> bool {
+    value.starts_with('|') || value.starts_with('>')
+}
+
+/// Split `key: value` (or `key:`) into the unquoted key and the raw value.
+fn split_yaml_mapping(line: &str) -> Option<(String, &str)> {
+    if line.starts_with(['[', '{', '|', '>', '&', '*', '!']) {
+        return None;
+    }
+    let (key, value) =
+        if let Some(quote) = line.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            let end = line[1..].find(quote)? + 1;
+            let value = line[end + 1..].trim_start().strip_prefix(':')?;
+            (line[1..end].to_string(), value)
+        } else {
+            let pos = line.match_indices(':').map(|(pos, _)| pos).find(|pos| {
+                line[pos + 1..].is_empty() || line[pos + 1..].starts_with([' ', '\t'])
+            })?;
+            let key = line[..pos].trim_end();
+            if key.is_empty() || key.contains(" #") {
+                return None;
+            }
+            (key.to_string(), &line[pos + 1..])
+        };
+    Some((key, value.trim()))
+}
+
+/// A YAML value read starting at a key or sequence item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum YamlValue {
+    /// Plain or quoted scalar and its 1-based line.
+    Scalar(usize, String),
+    /// Literal or folded block and the 1-based line of its first line.
+    Block(usize, String),
+    Seq(Vec<YamlValue>),
+    /// Anything else (a nested mapping, an empty value).
+    Other,
+}
+
+/// Read the value of the key at `lines[idx]`, whose raw value is `value`.
+fn read_yaml_value(lines: &[&str], idx: usize, indent: usize, value: &str) -> YamlValue {
+    if is_yaml_block_indicator(value) {
+        let (block, start_line, _) = parse_yaml_block(lines, idx + 1, indent);
+        return YamlValue::Block(start_line, block);
+    }
+    if let Some(items) = parse_inline_yaml_sequence(value) {
+        return YamlValue::Seq(
+            items
+                .into_iter()
+                .map(|item| YamlValue::Scalar(idx + 1, item))
+                .collect(),
+        );
+    }
+    if value.is_empty() || value.starts_with('#') {
+        return read_yaml_block_sequence(lines, idx + 1, indent);
+    }
+    YamlValue::Scalar(idx + 1, unquote_yaml_scalar(strip_yaml_comment(value)))
+}
+
+/// Read a `- item` sequence starting at or after `lines[start]`. Compact
+/// sequences at the parent key's own indent are accepted.
+fn read_yaml_block_sequence(lines: &[&str], start: usize, parent_indent: usize) -> YamlValue {
+    let mut items = Vec::new();
+    let mut seq_indent: Option<usize> = None;
+    let mut idx = start;
+
+    while idx < lines.len() {
+        let raw = lines[idx];
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            idx += 1;
+            continue;
+        }
+        let indent = raw.len() - trimmed.len();
+        let is_item = trimmed.starts_with("- ") || trimmed == "-";
+        let expected = *seq_indent.get_or_insert(indent);
+        if indent < expected || indent < parent_indent || (indent == expected && !is_item) {
+            break;
+        }
+        if indent > expected {
+            idx += 1;
+            continue;
+        }
+
+        let item = trimmed.strip_prefix('-').unwrap_or_default().trim();
+        if is_yaml_block_indicator(item) {
+            let (block, start_line, next) = parse_yaml_block(lines, idx + 1, indent);
+            items.push(YamlValue::Block(start_line, block));
+            idx = next;
+            continue;
+        }
+        if let Some(inner) = parse_inline_yaml_sequence(item) {
+            items.push(YamlValue::Seq(
+                inner
+                    .into_iter()
+                    .map(|value| YamlValue::Scalar(idx + 1, value))
+                    .collect(),
+            ));
+        } else if item.is_empty() || split_yaml_mapping(item).is_some() {
+            items.push(YamlValue::Other);
+        } else {
+            items.push(YamlValue::Scalar(
+                idx + 1,
+                unquote_yaml_scalar(strip_yaml_comment(item)),
+            ));
+        }
+        idx += 1;
+    }
+
+    if items.is_empty() {
+        YamlValue::Other
+    } else {
+        YamlValue::Seq(items)
+    }
+}
+
+fn strip_yaml_comment(value: &str) -> &str {
+    if value.starts_with(['"', '\'']) {
+        return value;
+    }
+    value
+        .find(" #")
+        .map_or(value, |pos| value[..pos].trim_end())
+}
+
+/// Turn an argv-style list into commands. `sh -c <script>` (and an `args`
+/// list starting with `-c`) yields the script; anything else is joined.
+fn yaml_argv_commands(
+    file: &str,
+    argv: &[YamlValue],
+    line: usize,
+    enabled_keywords: &[&'static str],
+    extractor_id: &'static str,
+) -> Vec<ExtractedCommand> {
+    let text = |value: &YamlValue| match value {
+        YamlValue::Scalar(_, s) | YamlValue::Block(_, s) => Some(s.clone()),
+        _ => None,
+    };
+    let words: Vec<String> = argv.iter().filter_map(text).collect();
+
+    let script_at = words.iter().position(|word| word == "-c").filter(|&pos| {
+        pos == 0
+            || words[..pos].iter().any(|word| {
+                matches!(
+                    word.rsplit('/').next(),
+                    Some("sh" | "bash" | "dash" | "zsh" | "ash")
+                )
+            })
+    });
+    if let Some(pos) = script_at {
+        let Some(script) = argv.iter().filter(|v| text(v).is_some()).nth(pos + 1) else {
+            return Vec::new();
+        };
+        let (start, script) = match script {
+            YamlValue::Block(start, s) => (*start, s.as_str()),
+            YamlValue::Scalar(start, s) => (*start, s.as_str()),
+            _ => return Vec::new(),
+        };
+        return extract_shell_script_with_offset_and_id(
+            file,
+            start,
+            script,
+            enabled_keywords,
+            extractor_id,
+        );
+    }
+
+    let command = words.join(" ");
+    if command.trim().is_empty()
+        || (!enabled_keywords.is_empty() && !contains_any_keyword(&command, enabled_keywords))
+    {
+        return Vec::new();
+    }
+    vec![ExtractedCommand {
+        file: file.to_string(),
+        line,
+        col: None,
+        extractor_id: extractor_id.to_string(),
+        command,
+        metadata: None,
+    }]
+}
+
+/// Commands held by a single YAML value: a scalar is one command line, a
+/// block is a shell script, a sequence is an argv list.
+fn yaml_value_commands(
+    file: &str,
+    value: &YamlValue,
+    line: usize,
+    enabled_keywords: &[&'static str],
+    extractor_id: &'static str,
+) -> Vec<ExtractedCommand> {
+    match value {
+        YamlValue::Scalar(line, script) | YamlValue::Block(line, script) => {
+            extract_shell_script_with_offset_and_id(
+                file,
+                *line,
+                script,
+                enabled_keywords,
+                extractor_id,
+            )
+        }
+        YamlValue::Seq(items) => {
+            let line = match items.first() {
+                Some(YamlValue::Scalar(line, _) | YamlValue::Block(line, _)) => *line,
+                _ => line,
+            };
+            yaml_argv_commands(file, items, line, enabled_keywords, extractor_id)
+        }
+        YamlValue::Other => Vec::new(),
+    }
+}
+
+/// Tag commands with the YAML path they came from.
+fn with_yaml_path(mut commands: Vec<ExtractedCommand>, path: &str) -> Vec<ExtractedCommand> {
+    for command in &mut commands {
+        command.metadata = Some(serde_json::json!({ "yaml_path": path }));
+    }
+    commands
+}
+
+fn is_yaml_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yml") || ext.eq_ignore_ascii_case("yaml"))
+}
+
+// ============================================================================
+// Ansible extractor (playbooks, roles)
+// ============================================================================
+
+/// Modules whose value is a command line.
+const ANSIBLE_COMMAND_MODULES: &[&str] = &[
+    "shell",
+    "command",
+    "raw",
+    "ansible.builtin.shell",
+    "ansible.builtin.command",
+    "ansible.builtin.raw",
+    "ansible.legacy.shell",
+    "ansible.legacy.command",
+    "ansible.legacy.raw",
+];
+
+/// Whether a YAML file looks like an Ansible playbook or task list: it lives
+/// under a `tasks/`, `handlers/` or `playbooks/` directory, uses a
+/// fully-qualified `ansible.builtin.` module, or has a `- hosts:` play.
+fn looks_like_ansible(path: &Path, content: &str) -> bool {
+    let in_ansible_dir = path.parent().is_some_and(|dir| {
+        dir.components().any(|c| {
+            matches!(
+                c.as_os_str().to_str(),
+                Some("tasks" | "handlers" | "playbooks")
+            )
+        })
+    });
+    in_ansible_dir
+        || content.contains("ansible.builtin.")
+        || content
+            .lines()
+            .any(|line| line.trim_start().starts_with("- hosts:"))
+}
+
+/// Extract commands from Ansible `shell`/`command`/`raw` tasks.
+///
+/// Handles the free-form string, block scalar, `cmd:` and `argv:` forms.
+/// Each command records the YAML path of its module key.
+#[must_use]
+pub fn extract_ansible_from_str(
+    file: &str,
+    content: &str,
+    enabled_keywords: &[&'static str],
+) -> Vec<ExtractedCommand> {
+    const EXTRACTOR_ID: &str = "ansible.task";
+
+    let lines: Vec<&str> = content.lines().collect();
+    let entries = yaml_entries(&lines);
+    let mut out = Vec::new();
+
+    for (pos, entry) in entries.iter().enumerate() {
+        if !ANSIBLE_COMMAND_MODULES.contains(&entry.key.as_str()) {
+            continue;
+        }
+        let value = read_yaml_value(&lines, entry.idx, entry.indent, entry.value);
+        let commands = if value == YamlValue::Other {
+            // Mapping form: `shell: { cmd: ... }` or `command: { argv: [...] }`.
+            let prefix = format!("{}.", entry.path);
+            entries[pos + 1..]
+                .iter()
+                .take_while(|child| child.path.starts_with(&prefix))
+                .filter(|child| child.key == "cmd" || child.key == "argv")
+                .flat_map(|child| {
+                    let value = read_yaml_value(&lines, child.idx, child.indent, child.value);
+                    yaml_value_commands(file, &value, child.idx + 1, enabled_keywords, EXTRACTOR_ID)
+                })
+                .collect()
+        } else {
+            yaml_value_commands(file, &value, entry.idx + 1, enabled_keywords, EXTRACTOR_ID)
+        };
+        out.extend(with_yaml_path(commands, &entry.path));
+    }
+
+    out
+}
+
+// ============================================================================
+// cloud-init extractor (#cloud-config)
+// ============================================================================
+
+fn is_cloud_init_path(path: &Path) -> bool {
+    let Some(file_name) = path.file_name().and_then(std::ffi::OsStr::to_str) else {
+        return false;
+    };
+    let lower = file_name.to_ascii_lowercase();
+    lower == "user-data"
+        || ((lower.starts_with("user-data")
+            || lower.starts_with("cloud-init")
+            || lower.starts_with("cloud-config"))
+            && is_yaml_path(path))
+}
+
+fn looks_like_cloud_init(content: &str) -> bool {
+    content
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.trim_end() == "#cloud-config")
+}
+
+/// Extract commands from cloud-init `runcmd` and `bootcmd` lists.
+///
+/// String items run through the shell; list items are argv lists.
+#[must_use]
+pub fn extract_cloud_init_from_str(
+    file: &str,
+    content: &str,
+    enabled_keywords: &[&'static str],
+) -> Vec<ExtractedCommand> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = Vec::new();
+
+    for entry in yaml_entries(&lines) {
+        let extractor_id = match entry.path.as_str() {
+            "runcmd" => "cloud_init.runcmd",
+            "bootcmd" => "cloud_init.bootcmd",
+            _ => continue,
+        };
+        let YamlValue::Seq(items) = read_yaml_value(&lines, entry.idx, entry.indent, entry.value)
+        else {
+            continue;
+        };
+        for (n, item) in items.iter().enumerate() {
+            let commands =
+                yaml_value_commands(file, item, entry.idx + 1, enabled_keywords, extractor_id);
+            out.extend(with_yaml_path(commands, &format!("{}[{n}]", entry.path)));
+        }
+    }
+
+    out
+}
+
+// ============================================================================
+// Kubernetes Job/CronJob extractor
+// ============================================================================
+
+fn looks_like_kubernetes_job(content: &str) -> bool {
+    content.lines().any(|line| {
+        matches!(
+            yaml_key_value(line, "kind").map(|kind| strip_yaml_comment(kind.trim())),
+            Some("Job" | "CronJob")
+        ) && !line.starts_with([' ', '\t'])
+    })
+}
+
+/// Extract container `command`/`args` from Kubernetes Job and CronJob
+/// manifests.
+///
+/// `command` and `args` of a container are combined into one argv; a shell
+/// `-c` script is extracted as a script. Each command records the YAML path
+/// of its container.
+#[must_use]
+pub fn extract_kubernetes_job_from_str(
+    file: &str,
+    content: &str,
+    enabled_keywords: &[&'static str],
+) -> Vec<ExtractedCommand> {
+    const EXTRACTOR_ID: &str = "kubernetes.container";
+
+    let lines: Vec<&str> = content.lines().collect();
+    let entries = yaml_entries(&lines);
+    let mut out = Vec::new();
+    let mut idx = 0usize;
+
+    while idx < entries.len() {
+        let entry = &entries[idx];
+        idx += 1;
+        if entry.key != "command" && entry.key != "args" {
+            continue;
+        }
+        let Some(container) = entry.path.strip_suffix(&format!(".{}", entry.key)) else {
+            continue;
+        };
+        if !container.ends_with(']') || !container.contains("ontainers[") {
+            continue;
+        }
+
+        // Gather `command` then `args` of this container.
+        let mut command = Vec::new();
+        let mut args = Vec::new();
+        let line = entry.idx + 1;
+        for sibling in std::iter::once(entry).chain(
+            entries[idx..]
+                .iter()
+                .take_while(|e| e.path.starts_with(container)),
+        ) {
+            if sibling.path != format!("{container}.{}", sibling.key) {
+                continue;
+            }
+            let target = match sibling.key.as_str() {
+                "command" => &mut command,
+                "args" => &mut args,
+                _ => continue,
+            };
+            match read_yaml_value(&lines, sibling.idx, sibling.indent, sibling.value) {
+                YamlValue::Seq(items) => target.extend(items),
+                value @ (YamlValue::Scalar(..) | YamlValue::Block(..)) => target.push(value),
+                YamlValue::Other => {}
+            }
+        }
+        while idx < entries.len()
+            && entries[idx].path.starts_with(container)
+            && entries[idx].path != container
+        {
+            idx += 1;
+        }
+
+        command.extend(args);
+        let commands = yaml_argv_commands(file, &command, line, enabled_keywords, EXTRACTOR_ID);
+        out.extend(with_yaml_path(commands, container));
+    }
+
+    out
+}
+
 #[must_use]
 pub fn build_report(
     mut findings: Vec<ScanFinding>,
@@ -3467,6 +4709,7 @@ pub fn build_report(
             severities,
             max_findings_reached,
             elapsed_ms,
+            interrupted: None,
         },
         findings,
     }
@@ -3659,6 +4902,8 @@ fail_on = "nope"
                     rule_id: Some("core.filesystem:rm-rf-general".to_string()),
                     reason: Some("blocked".to_string()),
                     suggestion: None,
+                    script: None,
+                    yaml_path: None,
                 },
                 ScanFinding {
                     file: "b".to_string(),
@@ -3671,6 +4916,8 @@ fail_on = "nope"
                     rule_id: None,
                     reason: Some("warn".to_string()),
                     suggestion: None,
+                    script: None,
+                    yaml_path: None,
                 },
             ],
             2,
@@ -3699,6 +4946,8 @@ fail_on = "nope"
                 rule_id: Some("pack:rule".to_string()),
                 reason: None,
                 suggestion: None,
+                script: None,
+                yaml_path: None,
             },
             ScanFinding {
                 file: "a".to_string(),
@@ -3711,6 +4960,8 @@ fail_on = "nope"
                 rule_id: Some("pack:rule".to_string()),
                 reason: None,
                 suggestion: None,
+                script: None,
+                yaml_path: None,
             },
         ];
 
@@ -3730,6 +4981,7 @@ fail_on = "nope"
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 0,
+            jobs: 1,
         };
         let extracted = ExtractedCommand {
             file: "test".to_string(),
@@ -3760,6 +5012,7 @@ fail_on = "nope"
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 0,
+            jobs: 1,
         };
 
         // This is what docker-compose extractor produces for: command: sh -c "git reset --hard && ./start.sh"
@@ -3830,6 +5083,7 @@ fail_on = "nope"
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 0,
+            jobs: 1,
         };
 
         // Step 1: Extract
@@ -4062,6 +5316,8 @@ resource "null_resource" "test" {
                 rule_id: Some("core.filesystem:rm-rf-root-home".to_string()),
                 reason: Some("dangerous".to_string()),
                 suggestion: Some("use safer rm".to_string()),
+                script: None,
+                yaml_path: None,
             }],
             1,
             0,
@@ -4132,6 +5388,8 @@ resource "null_resource" "test" {
             rule_id: None,
             reason: None,
             suggestion: None,
+            script: None,
+            yaml_path: None,
         }
     }
 
@@ -4306,6 +5564,7 @@ resource "null_resource" "test" {
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 0,
+            jobs: 1,
         };
 
         let safe_commands = [
@@ -4351,6 +5610,7 @@ resource "null_resource" "test" {
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 0,
+            jobs: 1,
         };
 
         let dangerous_commands = [
@@ -4402,6 +5662,7 @@ resource "null_resource" "test" {
             max_findings: 100,
             redact: ScanRedactMode::None,
             truncate: 0,
+            jobs: 1,
         };
 
         let extracted = ExtractedCommand {
@@ -4428,6 +5689,149 @@ resource "null_resource" "test" {
         );
     }
 
+    #[test]
+    fn scan_scripts_names_scripts_and_skips_dependencies() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("Makefile"), "reset:\n\tgit reset --hard\n").expect("write");
+        std::fs::write(dir.path().join("deploy.sh"), "rm -rf /\n").expect("write");
+        let dep = dir.path().join("web").join("node_modules").join("pkg");
+        std::fs::create_dir_all(&dep).expect("mkdir");
+        std::fs::write(
+            dep.join("package.json"),
+            r#"{"scripts":{"postinstall":"rm -rf /"}}"#,
+        )
+        .expect("write");
+
+        let config = default_config();
+        let ctx = ScanEvalContext::from_config(&config);
+        let options = ScanOptions {
+            format: ScanFormat::Json,
+            fail_on: ScanFailOn::Error,
+            max_file_size_bytes: 1024 * 1024,
+            max_findings: 100,
+            redact: ScanRedactMode::None,
+            truncate: 0,
+            jobs: 1,
+        };
+        let report = scan_scripts(
+            &[dir.path().to_path_buf()],
+            &options,
+            &config,
+            &ctx,
+            &[],
+            None,
+        )
+        .expect("scan");
+
+        assert_eq!(report.summary.files_scanned, 1);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].script.as_deref(), Some("reset"));
+        assert_eq!(
+            report.findings[0].rule_id.as_deref(),
+            Some("core.git:reset-hard")
+        );
+    }
+
+    #[test]
+    fn parallel_scan_report_matches_sequential() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        for i in 0..40 {
+            let body = match i % 3 {
+                0 => "git status\ngit reset --hard HEAD\nrm -rf /\n",
+                1 => "echo ok\n",
+                _ => "git push --force origin main\ngit log\n",
+            };
+            std::fs::write(dir.path().join(format!("s{i:02}.sh")), body).expect("write");
+        }
+        std::fs::write(dir.path().join("notes.txt"), "rm -rf /").expect("write");
+
+        let config = default_config();
+        let ctx = ScanEvalContext::from_config(&config);
+        let paths = vec![dir.path().to_path_buf()];
+        let report_for = |jobs: usize, max_findings: usize| {
+            let options = ScanOptions {
+                format: ScanFormat::Json,
+                fail_on: ScanFailOn::Error,
+                max_file_size_bytes: 1024 * 1024,
+                max_findings,
+                redact: ScanRedactMode::None,
+                truncate: 0,
+                jobs,
+            };
+            let mut report =
+                scan_paths(&paths, &options, &config, &ctx, &[], &[], None).expect("scan");
+            report.summary.elapsed_ms = None;
+            serde_json::to_value(&report).expect("serialize")
+        };
+
+        for max_findings in [1000, 7] {
+            let sequential = report_for(1, max_findings);
+            assert_eq!(report_for(4, max_findings), sequential);
+            assert_eq!(report_for(0, max_findings), sequential);
+        }
+        assert_eq!(report_for(1, 7)["summary"]["max_findings_reached"], true);
+    }
+
+    #[test]
+    fn interrupted_scan_reports_partial_results() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.sh"), "git reset --hard HEAD\n").expect("write");
+
+        let config = default_config();
+        let ctx = ScanEvalContext::from_config(&config);
+        let options = ScanOptions {
+            format: ScanFormat::Json,
+            fail_on: ScanFailOn::Error,
+            max_file_size_bytes: 1024 * 1024,
+            max_findings: 100,
+            redact: ScanRedactMode::None,
+            truncate: 0,
+            jobs: 1,
+        };
+        let paths = vec![dir.path().to_path_buf()];
+        let scan = |interrupt: &ScanInterrupt| {
+            scan_paths_interruptible(
+                &paths,
+                &options,
+                &config,
+                &ctx,
+                &[],
+                &[],
+                None,
+                None,
+                interrupt,
+            )
+            .expect("scan")
+        };
+
+        let complete = scan(&ScanInterrupt::new());
+        assert_eq!(complete.summary.interrupted, None);
+        assert_eq!(complete.findings.len(), 1);
+
+        let cancelled = ScanInterrupt::new();
+        cancelled.cancel();
+        let report = scan(&cancelled);
+        assert_eq!(
+            report.summary.interrupted,
+            Some(ScanInterruptReason::Cancelled)
+        );
+        assert_eq!(report.summary.files_scanned, 0);
+        assert!(!report.summary.max_findings_reached);
+
+        let expired = ScanInterrupt::new().with_timeout(Duration::ZERO);
+        let report = scan(&expired);
+        assert_eq!(
+            report.summary.interrupted,
+            Some(ScanInterruptReason::Timeout)
+        );
+        let json = serde_json::to_value(&report).expect("serialize");
+        assert_eq!(json["summary"]["interrupted"], "timeout");
+        assert_eq!(
+            ScanInterruptReason::Timeout.exit_code(),
+            crate::exit_codes::EXIT_TIMEOUT
+        );
+    }
+
     // ========================================================================
     // Shell extractor tests (git_safety_guard-scan.3.1)
     // ========================================================================
@@ -5373,6 +6777,293 @@ all:\n\
         assert_eq!(extracted[0].command, "git log --oneline");
     }
 
+    #[test]
+    fn justfile_path_detection() {
+        use std::path::Path;
+        assert!(is_justfile_path(Path::new("justfile")));
+        assert!(is_justfile_path(Path::new("Justfile")));
+        assert!(is_justfile_path(Path::new(".justfile")));
+        assert!(!is_justfile_path(Path::new("justfile.bak")));
+    }
+
+    #[test]
+    fn justfile_extractor_extracts_recipe_bodies_only() {
+        let content = [
+            "set shell := [\"bash\", \"-c\"]",
+            "clean_cmd := \"rm -rf /\"",
+            "",
+            "# Remove build output",
+            "clean:",
+            "    rm -rf ./target",
+            "",
+            "reset target=\"main\": clean",
+            "  @git reset --hard {{target}}",
+            "",
+            "report:",
+            "    #!/usr/bin/env python3",
+            "    import shutil; shutil.rmtree(\"/tmp/x\")",
+        ]
+        .join("\n");
+
+        let extracted = extract_justfile_from_str("justfile", &content, &["git", "rm"]);
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(extracted[0].line, 6);
+        assert_eq!(extracted[0].extractor_id, "justfile.recipe");
+        assert_eq!(extracted[0].command, "rm -rf ./target");
+        assert_eq!(extracted[1].line, 9);
+        assert_eq!(extracted[1].command, "@git reset --hard {{target}}");
+    }
+
+    #[test]
+    fn makefile_and_justfile_commands_carry_script_names() {
+        let makefile = ".PHONY: clean\nOUT := build\nclean distclean: deps\n\trm -rf $(OUT)\n";
+        let extracted = extract_makefile_from_str("Makefile", makefile, &["rm"]);
+        assert_eq!(extracted.len(), 1);
+        assert_eq!(
+            extracted[0].metadata,
+            Some(serde_json::json!({ "script_name": "clean" }))
+        );
+
+        let justfile = "@wipe dir=\"out\":\n    rm -rf {{dir}}\n";
+        let extracted = extract_justfile_from_str("justfile", justfile, &["rm"]);
+        assert_eq!(
+            extracted[0].metadata,
+            Some(serde_json::json!({ "script_name": "wipe" }))
+        );
+    }
+
+    #[test]
+    fn deno_json_extracts_string_and_object_tasks() {
+        let content = r#"{
+  // comments are allowed in deno.jsonc
+  "tasks": {
+    "wipe": "git clean -fdx", /* trailing */
+    "dev": { "command": "git reset --hard", "description": "// not a comment" },
+    "fmt": "deno fmt"
+  }
+}"#;
+        assert!(is_deno_json_path(std::path::Path::new("deno.jsonc")));
+        let extracted = extract_deno_json_from_str("deno.jsonc", content, &["git"]);
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(extracted[0].extractor_id, "deno_json.task");
+        let by_name: Vec<(&str, usize)> = extracted
+            .iter()
+            .map(|c| {
+                (
+                    c.metadata.as_ref().unwrap()["script_name"]
+                        .as_str()
+                        .unwrap(),
+                    c.line,
+                )
+            })
+            .collect();
+        assert!(by_name.contains(&("wipe", 4)));
+        assert!(by_name.contains(&("dev", 5)));
+    }
+
+    fn yaml_paths(commands: &[ExtractedCommand]) -> Vec<(&str, &str, usize)> {
+        commands
+            .iter()
+            .map(|c| {
+                (
+                    c.metadata.as_ref().unwrap()["yaml_path"].as_str().unwrap(),
+                    c.command.as_str(),
+                    c.line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn yaml_entries_track_sequence_and_mapping_paths() {
+        let content = [
+            "- hosts: all",
+            "  tasks:",
+            "    - name: one",
+            "      shell: |",
+            "        key: not a key",
+            "    - name: two",
+            "      command: ls",
+            "---",
+            "kind: Job",
+        ]
+        .join("\n");
+        let lines: Vec<&str> = content.lines().collect();
+        let paths: Vec<String> = yaml_entries(&lines).into_iter().map(|e| e.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "[0].hosts",
+                "[0].tasks",
+                "[0].tasks[0].name",
+                "[0].tasks[0].shell",
+                "[0].tasks[1].name",
+                "[0].tasks[1].command",
+                "kind",
+            ]
+        );
+    }
+
+    #[test]
+    fn ansible_extractor_handles_module_forms() {
+        let content = [
+            "- hosts: web",
+            "  tasks:",
+            "    - name: free form",
+            "      ansible.builtin.shell: rm -rf /var/www/old",
+            "    - name: block",
+            "      shell: |",
+            "        cd /srv",
+            "        git reset --hard",
+            "    - name: cmd form",
+            "      command:",
+            "        cmd: git clean -fdx",
+            "    - name: argv form",
+            "      command:",
+            "        argv:",
+            "          - rm",
+            "          - -rf",
+            "          - /tmp/cache",
+            "    - name: unrelated",
+            "      copy: src=a dest=b",
+        ]
+        .join("\n");
+        let path = Path::new("site.yml");
+        assert!(looks_like_ansible(path, &content));
+        let extracted = extract_ansible_from_str("site.yml", &content, &["rm", "git"]);
+        assert!(extracted.iter().all(|c| c.extractor_id == "ansible.task"));
+        assert_eq!(
+            yaml_paths(&extracted),
+            [
+                (
+                    "[0].tasks[0].ansible.builtin.shell",
+                    "rm -rf /var/www/old",
+                    4
+                ),
+                ("[0].tasks[1].shell", "git reset --hard", 8),
+                ("[0].tasks[2].command", "git clean -fdx", 11),
+                ("[0].tasks[3].command", "rm -rf /tmp/cache", 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn cloud_init_extractor_reads_runcmd_items() {
+        let content = [
+            "#cloud-config",
+            "packages: [git]",
+            "runcmd:",
+            "  - echo hello",
+            "  - rm -rf /opt/app",
+            "  - [ sh, -c, \"git reset --hard\" ]",
+            "bootcmd:",
+            "  - [rm, -rf, /var/tmp/boot]",
+        ]
+        .join("\n");
+        assert!(looks_like_cloud_init(&content));
+        assert!(is_cloud_init_path(Path::new("user-data")));
+        assert!(is_cloud_init_path(Path::new("cloud-init.yaml")));
+        assert!(!is_cloud_init_path(Path::new("values.yaml")));
+
+        let extracted = extract_cloud_init_from_str("user-data", &content, &["rm", "git"]);
+        assert_eq!(
+            yaml_paths(&extracted),
+            [
+                ("runcmd[1]", "rm -rf /opt/app", 5),
+                ("runcmd[2]", "git reset --hard", 6),
+                ("bootcmd[0]", "rm -rf /var/tmp/boot", 8),
+            ]
+        );
+        assert_eq!(extracted[2].extractor_id, "cloud_init.bootcmd");
+    }
+
+    #[test]
+    fn kubernetes_extractor_combines_command_and_args() {
+        let content = [
+            "apiVersion: batch/v1",
+            "kind: CronJob",
+            "spec:",
+            "  jobTemplate:",
+            "    spec:",
+            "      template:",
+            "        spec:",
+            "          initContainers:",
+            "            - name: init",
+            "              command: [\"rm\", \"-rf\", \"/data/cache\"]",
+            "          containers:",
+            "            - name: reset",
+            "              image: alpine/git",
+            "              command: [\"/bin/sh\", \"-c\"]",
+            "              args:",
+            "                - |",
+            "                  cd /repo",
+            "                  git reset --hard origin/main",
+            "            - name: safe",
+            "              command: [\"echo\", \"rm\"]",
+        ]
+        .join("\n");
+        assert!(looks_like_kubernetes_job(&content));
+        assert!(!looks_like_kubernetes_job("kind: Deployment\n"));
+
+        let extracted = extract_kubernetes_job_from_str("cron.yaml", &content, &["rm", "git"]);
+        assert!(
+            extracted
+                .iter()
+                .all(|c| c.extractor_id == "kubernetes.container")
+        );
+        let paths = yaml_paths(&extracted);
+        assert!(paths.contains(&(
+            "spec.jobTemplate.spec.template.spec.initContainers[0]",
+            "rm -rf /data/cache",
+            10
+        )));
+        assert!(paths.contains(&(
+            "spec.jobTemplate.spec.template.spec.containers[0]",
+            "git reset --hard origin/main",
+            18
+        )));
+    }
+
+    #[test]
+    fn yaml_findings_carry_yaml_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks = dir.path().join("roles").join("web").join("tasks");
+        std::fs::create_dir_all(&tasks).unwrap();
+        std::fs::write(
+            tasks.join("main.yml"),
+            "- name: wipe\n  shell: git reset --hard\n",
+        )
+        .unwrap();
+
+        let config = default_config();
+        let ctx = ScanEvalContext::from_config(&config);
+        let options = ScanOptions {
+            format: ScanFormat::Json,
+            fail_on: ScanFailOn::Error,
+            max_file_size_bytes: 1024 * 1024,
+            max_findings: 100,
+            redact: ScanRedactMode::None,
+            truncate: 0,
+            jobs: 1,
+        };
+        let report = scan_paths(
+            &[dir.path().to_path_buf()],
+            &options,
+            &config,
+            &ctx,
+            &[],
+            &[],
+            None,
+        )
+        .unwrap();
+        let finding = report
+            .findings
+            .iter()
+            .find(|f| f.extractor_id == "ansible.task")
+            .expect("ansible finding");
+        assert_eq!(finding.yaml_path.as_deref(), Some("[0].shell"));
+    }
+
     // =========================================================================
     // package.json extractor tests
     // =========================================================================
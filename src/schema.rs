@@ -0,0 +1,735 @@
+//! Published JSON Schemas for dcg's machine-readable formats.
+//!
+//! The schema documents live in `docs/json-schema/` and are embedded in the
+//! binary so `dcg schema <kind>` works without a source checkout. TOML formats
+//! (config, allowlist) are described as the JSON equivalent of their TOML, which
+//! is what TOML language servers such as Taplo expect.
+//!
+//! The tests in this module check each schema against the Rust types it
+//! describes, in both directions: a fully populated value must only produce
+//! documented keys with the documented types and values, must carry every
+//! required key, and must exercise every property the schema documents.
+
+use clap::ValueEnum;
+
+/// A published schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaKind {
+    /// Hook denial output (`hookSpecificOutput`)
+    HookOutput,
+    /// `dcg test --format json` / robot-mode envelope
+    TestOutput,
+    /// `dcg scan --format json` report
+    ScanResults,
+    /// `dcg stats --format json` report
+    StatsOutput,
+    /// JSON error responses
+    Error,
+    /// Config TOML (`config.toml`, `.dcg.toml`)
+    Config,
+    /// Allowlist TOML (`allowlist.toml`)
+    Allowlist,
+}
+
+impl SchemaKind {
+    /// Every published schema, in `dcg schema` help order.
+    pub const ALL: [Self; 7] = [
+        Self::HookOutput,
+        Self::TestOutput,
+        Self::ScanResults,
+        Self::StatsOutput,
+        Self::Error,
+        Self::Config,
+        Self::Allowlist,
+    ];
+
+    /// File name under `docs/json-schema/`.
+    #[must_use]
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::HookOutput => "hook-output.json",
+            Self::TestOutput => "test-output.json",
+            Self::ScanResults => "scan-results.json",
+            Self::StatsOutput => "stats-output.json",
+            Self::Error => "error.json",
+            Self::Config => "config.json",
+            Self::Allowlist => "allowlist.json",
+        }
+    }
+
+    /// The schema document (JSON Schema draft 2020-12).
+    #[must_use]
+    pub const fn document(self) -> &'static str {
+        match self {
+            Self::HookOutput => include_str!("../docs/json-schema/hook-output.json"),
+            Self::TestOutput => include_str!("../docs/json-schema/test-output.json"),
+            Self::ScanResults => include_str!("../docs/json-schema/scan-results.json"),
+            Self::StatsOutput => include_str!("../docs/json-schema/stats-output.json"),
+            Self::Error => include_str!("../docs/json-schema/error.json"),
+            Self::Config => include_str!("../docs/json-schema/config.json"),
+            Self::Allowlist => include_str!("../docs/json-schema/allowlist.json"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+
+    fn schema(kind: SchemaKind) -> Value {
+        serde_json::from_str(kind.document())
+            .unwrap_or_else(|e| panic!("{} is not valid JSON: {e}", kind.file_name()))
+    }
+
+    /// How serialized values differ from a schema.
+    #[derive(Default)]
+    struct Drift {
+        /// Value paths with an undocumented key, a missing required key, or
+        /// a type or value the schema does not allow.
+        errors: Vec<String>,
+        /// Pointers of the documented properties the values exercised.
+        covered: BTreeSet<String>,
+    }
+
+    /// Follow a local `$ref` (`#/$defs/name`), tracking the schema pointer.
+    fn resolve<'a>(root: &'a Value, ptr: String, node: &'a Value) -> (String, &'a Value) {
+        match node.get("$ref").and_then(Value::as_str) {
+            Some(reference) => {
+                let name = reference
+                    .strip_prefix("#/$defs/")
+                    .unwrap_or_else(|| panic!("unsupported $ref {reference}"));
+                resolve(root, format!("#/$defs/{name}"), &root["$defs"][name])
+            }
+            None => (ptr, node),
+        }
+    }
+
+    fn type_matches(expected: &str, value: &Value) -> bool {
+        match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "null" => value.is_null(),
+            other => panic!("unsupported type {other}"),
+        }
+    }
+
+    /// Schema for `key` inside object schema `node`, looking through
+    /// `properties`, `oneOf` branches, and `additionalProperties`.
+    fn property<'a>(
+        root: &'a Value,
+        ptr: &str,
+        node: &'a Value,
+        key: &str,
+    ) -> Option<(String, &'a Value)> {
+        let (ptr, node) = resolve(root, ptr.to_string(), node);
+        if let Some(prop) = node.get("properties").and_then(|p| p.get(key)) {
+            return Some((format!("{ptr}/properties/{key}"), prop));
+        }
+        if let Some(branches) = node.get("oneOf").and_then(Value::as_array) {
+            let found = branches
+                .iter()
+                .enumerate()
+                .find_map(|(idx, b)| property(root, &format!("{ptr}/oneOf/{idx}"), b, key));
+            if found.is_some() {
+                return found;
+            }
+        }
+        node.get("additionalProperties")
+            .filter(|v| v.is_object())
+            .map(|v| (format!("{ptr}/additionalProperties"), v))
+    }
+
+    /// Check `value` against `node`. A `oneOf` must have at least one
+    /// matching branch; keys a branch does not declare are left to the
+    /// enclosing schema, which looks through all branches.
+    fn check(
+        root: &Value,
+        ptr: String,
+        node: &Value,
+        value: &Value,
+        path: &str,
+        in_branch: bool,
+        drift: &mut Drift,
+    ) {
+        let (ptr, node) = resolve(root, ptr, node);
+        let type_ok = match node.get("type") {
+            Some(Value::String(expected)) => type_matches(expected, value),
+            Some(Value::Array(expected)) => expected
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|t| type_matches(t, value)),
+            _ => true,
+        };
+        if !type_ok {
+            drift
+                .errors
+                .push(format!("{path}: {value} is not of type {}", node["type"]));
+            return;
+        }
+        if node.get("const").is_some_and(|constant| constant != value) {
+            drift
+                .errors
+                .push(format!("{path}: {value} is not {}", node["const"]));
+        }
+        if let Some(allowed) = node.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                drift
+                    .errors
+                    .push(format!("{path}: {value} is not one of {allowed:?}"));
+            }
+        }
+        if let Some(branches) = node.get("oneOf").and_then(Value::as_array) {
+            let matched = branches.iter().enumerate().find_map(|(idx, branch)| {
+                let mut scratch = Drift::default();
+                let branch_ptr = format!("{ptr}/oneOf/{idx}");
+                check(root, branch_ptr, branch, value, path, true, &mut scratch);
+                scratch.errors.is_empty().then_some(scratch.covered)
+            });
+            match matched {
+                Some(covered) => drift.covered.extend(covered),
+                None => drift
+                    .errors
+                    .push(format!("{path}: matches no oneOf branch")),
+            }
+        }
+
+        match value {
+            Value::Object(map) => {
+                let required = node.get("required").and_then(Value::as_array);
+                for key in required.into_iter().flatten().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        drift.errors.push(format!("{path}: missing required {key}"));
+                    }
+                }
+                for (key, child) in map {
+                    let child_path = format!("{path}.{key}");
+                    let child_node = if in_branch {
+                        node.get("properties")
+                            .and_then(|p| p.get(key))
+                            .map(|p| (format!("{ptr}/properties/{key}"), p))
+                    } else {
+                        property(root, &ptr, node, key)
+                    };
+                    match child_node {
+                        Some((child_ptr, child_node)) => {
+                            drift.covered.insert(child_ptr.clone());
+                            check(
+                                root,
+                                child_ptr,
+                                child_node,
+                                child,
+                                &child_path,
+                                false,
+                                drift,
+                            );
+                        }
+                        None if !in_branch => {
+                            drift.errors.push(format!("{child_path}: not documented"));
+                        }
+                        None => {}
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_node) = node.get("items") {
+                    for (idx, item) in items.iter().enumerate() {
+                        let item_path = format!("{path}[{idx}]");
+                        let item_ptr = format!("{ptr}/items");
+                        check(root, item_ptr, item_node, item, &item_path, false, drift);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Pointers of every property documented at or below `node`.
+    fn documented(ptr: &str, node: &Value, out: &mut BTreeSet<String>) {
+        let Some(node) = node.as_object() else {
+            return;
+        };
+        for (key, prop) in node
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            let prop_ptr = format!("{ptr}/properties/{key}");
+            documented(&prop_ptr, prop, out);
+            out.insert(prop_ptr);
+        }
+        for (name, def) in node
+            .get("$defs")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            documented(&format!("{ptr}/$defs/{name}"), def, out);
+        }
+        for keyword in ["items", "additionalProperties", "not"] {
+            if let Some(child) = node.get(keyword) {
+                documented(&format!("{ptr}/{keyword}"), child, out);
+            }
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            let branches = node.get(keyword).and_then(Value::as_array);
+            for (idx, branch) in branches.into_iter().flatten().enumerate() {
+                documented(&format!("{ptr}/{keyword}/{idx}"), branch, out);
+            }
+        }
+    }
+
+    /// TOML has no null: a `None` serialized to JSON is an absent key.
+    fn strip_nulls(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.retain(|_, child| !child.is_null());
+                map.values_mut().for_each(strip_nulls);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+            _ => {}
+        }
+    }
+
+    fn drift(kind: SchemaKind, values: &[Value]) -> (Value, Drift) {
+        let root = schema(kind);
+        let mut drift = Drift::default();
+        for value in values {
+            let mut value = value.clone();
+            if matches!(kind, SchemaKind::Config | SchemaKind::Allowlist) {
+                strip_nulls(&mut value);
+            }
+            check(
+                &root,
+                "#".to_string(),
+                &root,
+                &value,
+                "$",
+                false,
+                &mut drift,
+            );
+        }
+        assert!(
+            drift.errors.is_empty(),
+            "{} drifted from the serialized values: {:#?}",
+            kind.file_name(),
+            drift.errors
+        );
+        (root, drift)
+    }
+
+    /// Check `values` against the `kind` schema in both directions: every
+    /// key they contain is documented with an allowed type and value, every
+    /// required key is present, and together they exercise every documented
+    /// property except those under the `exempt` pointers.
+    fn assert_schema_matches(kind: SchemaKind, values: &[Value], exempt: &[&str]) {
+        let (root, drift) = drift(kind, values);
+        let mut all = BTreeSet::new();
+        documented("#", &root, &mut all);
+        let unused: Vec<_> = all
+            .difference(&drift.covered)
+            .filter(|ptr| !exempt.iter().any(|prefix| ptr.starts_with(prefix)))
+            .collect();
+        assert!(
+            unused.is_empty(),
+            "{} documents properties no serialized value has: {unused:#?}",
+            kind.file_name()
+        );
+    }
+
+    fn toml_to_json(input: &str) -> Value {
+        let value: toml::Value = toml::from_str(input).expect("valid TOML");
+        serde_json::to_value(value).expect("TOML converts to JSON")
+    }
+
+    #[test]
+    fn every_schema_is_draft_2020_12() {
+        for kind in SchemaKind::ALL {
+            let root = schema(kind);
+            assert_eq!(
+                root["$schema"],
+                "https://json-schema.org/draft/2020-12/schema",
+                "{}",
+                kind.file_name()
+            );
+            let id = root["$id"].as_str().unwrap_or_default();
+            assert!(
+                id.ends_with(kind.file_name()),
+                "{} has $id {id}",
+                kind.file_name()
+            );
+        }
+    }
+
+    #[test]
+    fn config_schema_documents_every_config_field() {
+        let sample = crate::Config::generate_sample_config();
+        let populated = r#"
+include = ["team.toml"]
+
+[general]
+environment = "staging"
+log_file = "~/.local/state/dcg/dcg.log"
+hook_timeout_ms = 250
+max_command_bytes = 65536
+max_hook_input_bytes = 1048576
+max_findings_per_command = 8
+
+[policy]
+default_mode = "warn"
+observe_until = "2030-01-01"
+aggregation = "max"
+packs = { "core.git" = "deny" }
+rules = { "core.git:reset-hard" = "log" }
+severity = { medium = "ask" }
+min_confidence_to_deny = 0.5
+warn_channel = "advisory"
+warn_queue = false
+
+[policy.provenance.human]
+default_mode = "warn"
+packs = { "core.git" = "log" }
+rules = { "core.git:push-force-long" = "warn" }
+
+[canary]
+paths = ["/srv/DO_NOT_DELETE"]
+names = ["prod-canary"]
+webhook_url = "https://hooks.example.com/dcg"
+
+[privacy]
+log_mode = "redacted"
+
+[packs]
+pinned = true
+
+[approvals]
+callback_url = "https://approvals.example.com/dcg"
+slack_webhook_url = "https://hooks.slack.com/services/T/B/X"
+matrix_homeserver = "https://matrix.example.com"
+matrix_room_id = "!room:example.com"
+
+[heredoc]
+languages = ["bash", "python"]
+
+[history]
+database_path = "/var/lib/dcg/history.db"
+
+[interactive]
+require_env = "DCG_INTERACTIVE"
+
+[logging]
+file = "/var/log/dcg.log"
+
+[messages]
+denial_template = "{reason}"
+ticket_url = "https://tickets.example.com/new"
+security_contact = "security@example.com"
+
+[output]
+collapse_repeat_denials = false
+explanations_enabled = false
+high_contrast = true
+highlight_enabled = false
+hyperlinks = false
+impact_budget_ms = 100
+storage_impact = false
+storage_impact_timeout_ms = 500
+
+[self_protection]
+binary_paths = ["/usr/local/bin/dcg"]
+
+[theme]
+palette = "solarized"
+use_color = false
+use_unicode = false
+
+[trash]
+backend = "dcg"
+dir = "~/.local/share/dcg/trash"
+
+[overrides]
+allow = [
+  "^make clean$",
+  { pattern = "^npm run deploy$", when = "CI=true" },
+  { pattern = "^kubectl delete pod", args = { namespace = "dev", context = "kind-.*" } },
+]
+block = [
+  { pattern = "shutdown", reason = "no shutdowns" },
+  { pattern = "^gcloud", reason = "prod", args = { project = "prod", region = "eu-.*" } },
+  { pattern = "^aws", reason = "prod", args = { profile = "prod" } },
+]
+allowlist = ["cargo test"]
+allowlist_rules = [
+  { pattern = "rm -rf node_modules", paths = ["/w/*"], ttl = "4h" },
+  { pattern = "rm -rf dist", comment = "build output", created_at = "2030-01-01T00:00:00Z", expires = "2030-02-01T00:00:00Z", ttl_seconds = 3600 },
+  { pattern = "rm -rf out", session = true },
+]
+
+[heredoc.allowlist]
+commands = ["./scripts/ok.sh"]
+patterns = [{ language = "python", pattern = "print", reason = "safe" }]
+content_hashes = [{ hash = "00", reason = "known" }]
+projects = [{ path = "/w", patterns = [], content_hashes = [] }]
+
+[agents.claude-code]
+trust_level = "high"
+extra_packs = ["containers.docker"]
+allow_once_scope = "rule"
+severity_modes = { high = "warn" }
+
+[projects."/w"]
+packs = { enabled = ["kubernetes"] }
+overrides = { block = [] }
+"#;
+        drift(
+            SchemaKind::Config,
+            &[toml_to_json(&sample), toml_to_json(populated)],
+        );
+
+        // Coverage comes from what `Config` keeps after merging the files,
+        // so a documented key the config types silently drop fails the test.
+        let parsed = |input: &str| {
+            let config = crate::Config::with_layer_toml(input).expect("valid config");
+            serde_json::to_value(&config).expect("serialize")
+        };
+        assert_schema_matches(
+            SchemaKind::Config,
+            &[
+                serde_json::to_value(crate::Config::default()).expect("serialize"),
+                parsed(&sample),
+                parsed(populated),
+            ],
+            // Read before the files are merged, only from the system config,
+            // or folded into the allowlist by `dcg migrate`.
+            &[
+                "#/properties/include",
+                "#/properties/config_version",
+                "#/properties/self_protection/properties/binary_paths",
+                "#/$defs/overrides/properties/allowlist",
+            ],
+        );
+    }
+
+    #[test]
+    fn allowlist_schema_documents_every_entry_field() {
+        let input = r#"
+config_version = 2
+
+[[allow]]
+rule = "core.git:reset-hard"
+reason = "release tooling"
+added_by = "ops"
+added_at = "2030-01-01T00:00:00Z"
+expires_at = "2031-01-01"
+context = "string-argument"
+conditions = { CI = "true" }
+environments = ["ci"]
+paths = ["/workspace/*"]
+
+[[allow]]
+pattern = "^rm -rf build$"
+risk_acknowledged = true
+ttl = "4h"
+reason = "build cleanup"
+
+[[allow]]
+exact_command = "git clean -fdx"
+session = true
+reason = "session only"
+
+[[allow]]
+command_prefix = "echo"
+reason = "docs"
+
+[[allow]]
+command_prefix = "kubectl delete pod"
+arg_conditions = { namespace = "dev", context = "kind-.*" }
+reason = "dev cluster"
+
+[[allow]]
+pattern = "^gcloud compute instances delete"
+risk_acknowledged = true
+arg_conditions = { project = "sandbox", region = "us-.*" }
+reason = "sandbox project"
+
+[[allow]]
+command_prefix = "aws s3 rm"
+arg_conditions = { profile = "dev" }
+active_hours = "Mon-Fri 09:00-18:00"
+active_dates = ["2030-01-01..2030-01-31"]
+hosts = ["build-*"]
+users = ["deploy"]
+paths = ["/workspace/*"]
+match = "targets"
+reason = "office hours cleanup"
+
+[[allow]]
+url_prefix = "https://sh.rustup.rs"
+reason = "rustup installer"
+"#;
+        let parsed = crate::allowlist::parse_allowlist_toml(
+            crate::AllowlistLayer::Project,
+            std::path::Path::new("allowlist.toml"),
+            input,
+        );
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        assert_eq!(parsed.entries.len(), 8);
+        assert_schema_matches(SchemaKind::Allowlist, &[toml_to_json(input)], &[]);
+    }
+
+    #[test]
+    fn test_output_schema_documents_every_field() {
+        let info = crate::cli::TestMatchInfo {
+            rule_id: Some("core.git:reset-hard".to_string()),
+            pack_id: Some("core.git".to_string()),
+            pattern_name: Some("reset-hard".to_string()),
+            reason: "destroys changes".to_string(),
+            severity: Some("critical".to_string()),
+            matched_span: Some((0, 14)),
+            line: Some(1),
+        };
+        let output = crate::cli::TestOutput {
+            schema_version: 1,
+            dcg_version: "0.0.0".to_string(),
+            robot_mode: true,
+            command: "git reset --hard".to_string(),
+            provenance: crate::provenance::Provenance::Human,
+            decision: "deny".to_string(),
+            rule_id: info.rule_id.clone(),
+            pack_id: info.pack_id.clone(),
+            pattern_name: info.pattern_name.clone(),
+            reason: Some(info.reason.clone()),
+            explanation: Some("explanation".to_string()),
+            source: Some("pack".to_string()),
+            matched_span: info.matched_span,
+            line: info.line,
+            severity: info.severity.clone(),
+            allowlist: Some(crate::cli::AllowlistOverrideInfo {
+                layer: "project".to_string(),
+                reason: "ok".to_string(),
+            }),
+            confidence: Some(0.9),
+            min_confidence_to_deny: Some(0.5),
+            agent: Some(crate::cli::AgentInfo {
+                detected: "claude-code".to_string(),
+                trust_level: "medium".to_string(),
+                detection_method: "environment_variable".to_string(),
+            }),
+            matches: vec![info],
+            arguments: [
+                "kubectl --context kind-dev -n dev delete pods --all",
+                "aws --profile dev --region us-east-1 s3 rm s3://b --recursive",
+                "gcloud --project sandbox compute instances delete vm",
+            ]
+            .iter()
+            .flat_map(|command| crate::arg_context::arg_contexts(command))
+            .collect(),
+        };
+        assert_schema_matches(
+            SchemaKind::TestOutput,
+            &[serde_json::to_value(&output).expect("serialize")],
+            &[],
+        );
+    }
+
+    #[test]
+    fn hook_output_schema_documents_every_field() {
+        let output = crate::HookOutput {
+            hook_specific_output: crate::HookSpecificOutput {
+                hook_event_name: "PreToolUse",
+                permission_decision: "deny",
+                permission_decision_reason: "blocked".into(),
+                allow_once_code: Some("a1b2c3".to_string()),
+                allow_once_full_hash: Some(format!("sha256:{}", "0".repeat(64))),
+                approval_id: Some(3),
+                rule_id: Some("core.git:reset-hard".to_string()),
+                pack_id: Some("core.git".to_string()),
+                severity: Some(crate::packs::Severity::Critical),
+                confidence: Some(0.9),
+                min_confidence_to_deny: Some(0.5),
+                remediation: Some(crate::hook::Remediation {
+                    safe_alternative: Some("git stash".to_string()),
+                    explanation: "stash first".to_string(),
+                    allow_once_command: "dcg allow-once a1b2c3".to_string(),
+                }),
+                matches: vec![crate::hook::MatchEntry {
+                    rule_id: Some("core.git:reset-hard".to_string()),
+                    pack_id: Some("core.git".to_string()),
+                    severity: Some(crate::packs::Severity::Critical),
+                    reason: "destroys changes".to_string(),
+                    matched_span: Some((0, 14)),
+                }],
+            },
+        };
+        assert_schema_matches(
+            SchemaKind::HookOutput,
+            &[serde_json::to_value(&output).expect("serialize")],
+            &[],
+        );
+    }
+
+    #[test]
+    fn scan_results_schema_documents_every_field() {
+        let finding = crate::scan::ScanFinding {
+            file: "a.sh".to_string(),
+            line: 1,
+            col: Some(1),
+            extractor_id: "shell.script".to_string(),
+            extracted_command: "rm -rf /".to_string(),
+            decision: crate::scan::ScanDecision::Deny,
+            severity: crate::scan::ScanSeverity::Error,
+            rule_id: Some("core.filesystem:rm-rf-root".to_string()),
+            reason: Some("root".to_string()),
+            suggestion: Some("don't".to_string()),
+            script: Some("clean".to_string()),
+            yaml_path: Some("tasks[0].shell".to_string()),
+        };
+        let mut report = crate::scan::build_report(vec![finding], 1, 0, 1, false, Some(3));
+        report.summary.interrupted = Some(crate::scan::ScanInterruptReason::Timeout);
+        assert_schema_matches(
+            SchemaKind::ScanResults,
+            &[serde_json::to_value(&report).expect("serialize")],
+            &[],
+        );
+    }
+
+    #[test]
+    fn stats_output_schema_documents_every_field() {
+        let stats = crate::stats::AggregatedStats {
+            period_start: 1_700_000_000,
+            period_end: 1_700_086_400,
+            total_entries: 3,
+            total_blocks: 1,
+            total_allows: 1,
+            total_bypasses: 0,
+            total_warns: 1,
+            by_pack: vec![crate::stats::PackStats {
+                pack_id: "core.git".to_string(),
+                blocks: 1,
+                allows: 1,
+                bypasses: 0,
+                warns: 1,
+            }],
+        };
+        assert_schema_matches(
+            SchemaKind::StatsOutput,
+            &[serde_json::to_value(&stats).expect("serialize")],
+            &[],
+        );
+    }
+
+    #[test]
+    fn schema_examples_match_their_schema() {
+        for kind in SchemaKind::ALL {
+            let root = schema(kind);
+            let examples = root.get("examples").and_then(Value::as_array);
+            drift(kind, examples.map_or(&[][..], Vec::as_slice));
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Splitting multi-line commands into the lines the shell runs.
+//!
+//! Agents often send a whole script in one Bash call: several commands on
+//! separate lines, backslash continuations, and heredocs such as
+//! `cat <<'EOF' > notes.md ... EOF`. [`split`] walks such a payload the way
+//! the shell reads it and returns one [`ScriptUnit`] per logical line, so the
+//! evaluator can judge each command on its own:
+//!
+//! - A newline ends a command unless it is quoted or escaped with `\`.
+//! - Comments (`# ...`) are dropped.
+//! - A heredoc body fed to a command that only reads data (`cat`, `tee`,
+//!   `grep`, ...) is skipped. Unless the delimiter is quoted, the shell still
+//!   expands `$(...)` and backticks in it, so those become units of their own.
+//! - A heredoc body fed to anything else (`bash <<EOF`, `cat <<EOF | sh`) is
+//!   code; it stays part of its command's unit for the heredoc analysis.
+//!
+//! Offsets are byte offsets into the original command, so spans found in a
+//! unit map straight back onto what the agent sent.
+
+use std::ops::Range;
+
+use crate::arg_context::program;
+use crate::heredoc::is_non_executing_heredoc_command;
+
+/// One command line of a script, as a byte range of the original command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptUnit {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ScriptUnit {
+    /// The unit's text within `command`.
+    #[must_use]
+    pub fn text<'a>(&self, command: &'a str) -> &'a str {
+        &command[self.start..self.end]
+    }
+}
+
+/// A heredoc body found while splitting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeredocBody {
+    /// Byte range of the body, without the terminator line.
+    pub body: Range<usize>,
+    pub delimiter: String,
+    /// Whether the shell expands `$(...)`, backticks and variables in it
+    /// (the delimiter was not quoted).
+    pub expands: bool,
+    /// Whether the receiving command only reads it as data.
+    pub is_data: bool,
+}
+
+/// A multi-line command split into units.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    pub units: Vec<ScriptUnit>,
+    pub heredocs: Vec<HeredocBody>,
+}
+
+/// 1-based line of byte `offset` in `command`.
+#[must_use]
+pub fn line_number(command: &str, offset: usize) -> usize {
+    let offset = offset.min(command.len());
+    command.as_bytes()[..offset].split(|&b| b == b'\n').count()
+}
+
+/// Split `command` into the commands the shell would run.
+///
+/// Returns `None` when there is nothing to split: the command is a single
+/// line without heredocs, or it reads as one unit anyway (e.g. `bash <<EOF`
+/// with its body).
+#[must_use]
+pub fn split(command: &str) -> Option<Script> {
+    if !command.contains('\n') {
+        return None;
+    }
+    let script = Scanner::new(command).run();
+    let whole = trim_range(command, 0, command.len());
+    match script.units.as_slice() {
+        [only] if script.heredocs.iter().all(|h| !h.is_data) && whole == Some(*only) => None,
+        [] if script.heredocs.is_empty() => None,
+        _ => Some(script),
+    }
+}
+
+/// A heredoc whose body starts after the current line.
+struct PendingHeredoc {
+    delimiter: String,
+    strip_tabs: bool,
+    expands: bool,
+    /// Text from the start of the simple command up to `<<`.
+    prefix: Range<usize>,
+    segment: usize,
+    piped: bool,
+}
+
+struct Scanner<'a> {
+    command: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    unit_start: usize,
+    /// Where a comment cut the current line short.
+    comment_start: Option<usize>,
+    segment_start: usize,
+    segment: usize,
+    pending: Vec<PendingHeredoc>,
+    script: Script,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(command: &'a str) -> Self {
+        Self {
+            command,
+            bytes: command.as_bytes(),
+            pos: 0,
+            unit_start: 0,
+            comment_start: None,
+            segment_start: 0,
+            segment: 0,
+            pending: Vec::new(),
+            script: Script::default(),
+        }
+    }
+
+    fn run(mut self) -> Script {
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                // Escapes, including line continuations.
+                b'\\' => self.pos += 2,
+                b'\'' => self.skip_single_quoted(),
+                b'"' => self.skip_double_quoted(),
+                b'#' if self.at_word_start() => self.skip_comment(),
+                b'<' if self.bytes.get(self.pos + 1) == Some(&b'<') => self.heredoc_operator(),
+                b'|' if self.bytes.get(self.pos + 1) == Some(&b'|') => {
+                    self.pos += 2;
+                    self.new_segment();
+                }
+                b'|' => {
+                    for pending in &mut self.pending {
+                        if pending.segment == self.segment {
+                            pending.piped = true;
+                        }
+                    }
+                    self.pos += 1;
+                    self.new_segment();
+                }
+                b';' | b'&' | b'(' | b')' => {
+                    self.pos += 1;
+                    self.new_segment();
+                }
+                b'\n' => self.end_line(),
+                _ => self.pos += 1,
+            }
+        }
+        let end = self.comment_start.take().unwrap_or(self.bytes.len());
+        self.push_unit(end);
+        self.script
+    }
+
+    fn new_segment(&mut self) {
+        self.segment += 1;
+        self.segment_start = self.pos;
+    }
+
+    fn at_word_start(&self) -> bool {
+        self.pos == 0
+            || matches!(
+                self.bytes[self.pos - 1],
+                b' ' | b'\t' | b'\n' | b';' | b'&' | b'|' | b'(' | b')'
+            )
+    }
+
+    fn skip_single_quoted(&mut self) {
+        self.pos += 1;
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\'' {
+            self.pos += 1;
+        }
+        self.pos += 1;
+    }
+
+    fn skip_double_quoted(&mut self) {
+        self.pos += 1;
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+    }
+
+    fn skip_comment(&mut self) {
+        self.comment_start.get_or_insert(self.pos);
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+            self.pos += 1;
+        }
+    }
+
+    /// Parse `<<[-]DELIM` at `pos` (a `<<<` here-string is left alone).
+    fn heredoc_operator(&mut self) {
+        let operator = self.pos;
+        self.pos += 2;
+        if self.bytes.get(self.pos) == Some(&b'<') {
+            self.pos += 1;
+            return;
+        }
+        let strip_tabs = self.bytes.get(self.pos) == Some(&b'-');
+        if strip_tabs {
+            self.pos += 1;
+        }
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+
+        let mut delimiter = String::new();
+        let mut expands = true;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            match b {
+                b'\'' | b'"' => {
+                    expands = false;
+                    let close = self.command[self.pos + 1..]
+                        .find(b as char)
+                        .map_or(self.bytes.len(), |i| self.pos + 1 + i);
+                    delimiter.push_str(&self.command[self.pos + 1..close]);
+                    self.pos = close + 1;
+                }
+                b'\\' => {
+                    expands = false;
+                    self.pos += 1;
+                }
+                b' ' | b'\t' | b'\n' | b';' | b'&' | b'|' | b'<' | b'>' | b'(' | b')' => break,
+                _ => {
+                    let ch = self.command[self.pos..].chars().next().unwrap_or_default();
+                    delimiter.push(ch);
+                    self.pos += ch.len_utf8();
+                }
+            }
+        }
+        // `<< ""` is valid and ends at the first empty line; a bare `<<` is not.
+        if delimiter.is_empty() && expands {
+            return;
+        }
+        self.pending.push(PendingHeredoc {
+            delimiter,
+            strip_tabs,
+            expands,
+            prefix: self.segment_start..operator,
+            segment: self.segment,
+            piped: false,
+        });
+    }
+
+    /// Finish the current line at the newline at `pos`, then consume the
+    /// bodies of heredocs opened on it.
+    fn end_line(&mut self) {
+        let line_end = self.comment_start.take().unwrap_or(self.pos);
+        let line_unit = self.script.units.len();
+        self.push_unit(line_end);
+        self.pos += 1;
+
+        let mut code_end = None;
+        for pending in std::mem::take(&mut self.pending) {
+            let body_start = self.pos;
+            let (body_end, after) = self.find_terminator(&pending);
+            self.pos = after;
+            let is_data = !pending.piped && self.reads_data(&pending.prefix);
+            if !is_data {
+                code_end = Some(after);
+            } else if pending.expands {
+                self.push_substitutions(body_start, body_end);
+            }
+            self.script.heredocs.push(HeredocBody {
+                body: body_start..body_end,
+                delimiter: pending.delimiter,
+                expands: pending.expands,
+                is_data,
+            });
+        }
+        // Code bodies stay with the command that runs them.
+        if let (Some(end), Some(unit)) = (code_end, self.script.units.get_mut(line_unit)) {
+            unit.end = self.command[..end].trim_end().len();
+        }
+        self.unit_start = self.pos;
+        self.segment_start = self.pos;
+        self.segment += 1;
+    }
+
+    /// End of the body and the position after its terminator line.
+    fn find_terminator(&self, pending: &PendingHeredoc) -> (usize, usize) {
+        let mut line_start = self.pos;
+        while line_start < self.bytes.len() {
+            let line_end = self.command[line_start..]
+                .find('\n')
+                .map_or(self.bytes.len(), |i| line_start + i);
+            let mut line = self.command[line_start..line_end].trim_end_matches('\r');
+            if pending.strip_tabs {
+                line = line.trim_start_matches('\t');
+            }
+            if line == pending.delimiter {
+                return (line_start, (line_end + 1).min(self.bytes.len()));
+            }
+            line_start = line_end + 1;
+        }
+        // Unterminated: the shell reads to the end of input.
+        (self.bytes.len(), self.bytes.len())
+    }
+
+    fn reads_data(&self, prefix: &Range<usize>) -> bool {
+        let words: Vec<&str> = self.command[prefix.clone()]
+            .split_whitespace()
+            .filter(|word| !word.starts_with(['<', '>']) && !word.ends_with(['<', '>']))
+            .collect();
+        program(&words).is_some_and(|(tool, _)| is_non_executing_heredoc_command(tool))
+    }
+
+    /// Units for the `$(...)` and backtick substitutions the shell runs while
+    /// expanding a data body.
+    fn push_substitutions(&mut self, start: usize, end: usize) {
+        let body = &self.command[start..end];
+        let mut i = 0;
+        while i < body.len() {
+            let rest = &body[i..];
+            if rest.starts_with("$((") {
+                i += 3;
+            } else if rest.starts_with("$(") {
+                let inner = i + 2;
+                let mut depth = 1;
+                let close = body[inner..]
+                    .char_indices()
+                    .find_map(|(offset, ch)| {
+                        match ch {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        (depth == 0).then_some(inner + offset)
+                    })
+                    .unwrap_or(body.len());
+                self.push_range(start + inner, start + close);
+                i = close + 1;
+            } else if rest.starts_with('`') {
+                let inner = i + 1;
+                let close = body[inner..].find('`').map_or(body.len(), |k| inner + k);
+                self.push_range(start + inner, start + close);
+                i = close + 1;
+            } else if rest.starts_with('\\') {
+                i += 2;
+            } else {
+                i += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+
+    fn push_unit(&mut self, end: usize) {
+        let start = self.unit_start;
+        self.push_range(start, end);
+    }
+
+    fn push_range(&mut self, start: usize, end: usize) {
+        if let Some(unit) = trim_range(self.command, start, end) {
+            self.script.units.push(unit);
+        }
+    }
+}
+
+/// `start..end` without surrounding whitespace, or `None` if nothing is left.
+fn trim_range(command: &str, start: usize, end: usize) -> Option<ScriptUnit> {
+    let end = end.min(command.len());
+    let start = start.min(end);
+    let text = &command[start..end];
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let leading = text.len() - text.trim_start().len();
+    Some(ScriptUnit {
+        start: start + leading,
+        end: start + leading + trimmed.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(command: &str) -> Vec<&str> {
+        split(command).map_or_else(
+            || vec![command],
+            |script| script.units.iter().map(|unit| unit.text(command)).collect(),
+        )
+    }
+
+    #[test]
+    fn single_lines_are_not_split() {
+        assert!(split("git reset --hard").is_none());
+        assert!(split("git commit -m \"one\ntwo\"").is_none());
+    }
+
+    #[test]
+    fn lines_and_continuations() {
+        assert_eq!(
+            units("echo hi\ngit \\\n  reset --hard\n\nls"),
+            ["echo hi", "git \\\n  reset --hard", "ls"]
+        );
+        assert_eq!(units("# rm -rf /\nls # git reset --hard"), ["ls"]);
+    }
+
+    #[test]
+    fn data_heredocs_are_skipped() {
+        let command = "cat <<'EOF' > notes.md\ngit reset --hard\nEOF\necho done";
+        assert_eq!(units(command), ["cat <<'EOF' > notes.md", "echo done"]);
+        let script = split(command).unwrap();
+        assert_eq!(script.heredocs.len(), 1);
+        assert!(script.heredocs[0].is_data);
+        assert!(!script.heredocs[0].expands);
+        assert_eq!(
+            &command[script.heredocs[0].body.clone()],
+            "git reset --hard\n"
+        );
+
+        assert_eq!(
+            units("tee x <<-EOF\n\trm -rf /\n\tEOF\nls"),
+            ["tee x <<-EOF", "ls"]
+        );
+        assert_eq!(
+            units("cat <<A <<B\nrm -rf /\nA\ngit reset --hard\nB"),
+            ["cat <<A <<B"]
+        );
+    }
+
+    #[test]
+    fn expanding_data_bodies_keep_substitutions() {
+        assert_eq!(
+            units("cat <<EOF > f\nhello $(rm -rf /) and `git clean -fd`\nEOF"),
+            ["cat <<EOF > f", "rm -rf /", "git clean -fd"]
+        );
+        assert_eq!(
+            units("cat <<'EOF' > f\n$(rm -rf /)\nEOF"),
+            ["cat <<'EOF' > f"]
+        );
+    }
+
+    #[test]
+    fn code_heredocs_stay_with_their_command() {
+        let command = "python3 << \"\"\nimport shutil\nshutil.rmtree('/tmp/test')\n\necho done";
+        assert_eq!(
+            units(command),
+            [
+                "python3 << \"\"\nimport shutil\nshutil.rmtree('/tmp/test')",
+                "echo done"
+            ]
+        );
+
+        assert!(split("bash <<EOF\nrm -rf /\nEOF").is_none());
+        assert_eq!(
+            units("cat <<EOF | sh\nrm -rf /\nEOF\nls"),
+            ["cat <<EOF | sh\nrm -rf /\nEOF", "ls"]
+        );
+        assert_eq!(
+            units("ls\npython3 <<EOF\nimport os\nEOF"),
+            ["ls", "python3 <<EOF\nimport os\nEOF"]
+        );
+    }
+
+    #[test]
+    fn line_numbers() {
+        let command = "ls\n\ngit reset --hard";
+        assert_eq!(line_number(command, 0), 1);
+        assert_eq!(line_number(command, command.find("git").unwrap()), 3);
+    }
+}
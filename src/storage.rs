@@ -0,0 +1,335 @@
+//! Locked, atomic writes for dcg's state files.
+//!
+//! Parallel hook invocations share the allow-once stores, usage counters and
+//! audit logs, so every state writer goes through this module:
+//!
+//! - Writers serialize on an advisory lock held on a `<file>.lock` sidecar.
+//!   The data file itself is replaced on every write, so it cannot carry the
+//!   lock.
+//! - Rewrites go to a temp file in the same directory, are fsynced, and are
+//!   renamed over the target, so readers see the old or the new contents and
+//!   never a torn write.
+//! - Log lines are appended with a single write under a lock on the log, so
+//!   entries from concurrent hooks never interleave.
+//! - A state file that no longer parses is moved aside as
+//!   `<file>.corrupt-<timestamp>` for inspection and recreated, instead of
+//!   failing every later hook.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use fs2::FileExt;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Suffix of the lock sidecar next to each state file.
+pub const LOCK_SUFFIX: &str = ".lock";
+/// Infix of quarantined copies of damaged state files.
+pub const QUARANTINE_INFIX: &str = ".corrupt-";
+
+/// Exclusive advisory lock on a state file, released on drop.
+#[derive(Debug)]
+pub struct StateLock {
+    _file: File,
+}
+
+impl StateLock {
+    /// Block until the lock for `path` is held.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while creating or locking the sidecar.
+    pub fn acquire(path: &Path) -> io::Result<Self> {
+        let file = open_lock_file(path)?;
+        file.lock_exclusive()?;
+        Ok(Self { _file: file })
+    }
+
+    /// Take the lock for `path`, or return `None` if another process holds it.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while creating or locking the sidecar.
+    pub fn try_acquire(path: &Path) -> io::Result<Option<Self>> {
+        let file = open_lock_file(path)?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(err) if err.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// The lock sidecar for `path`.
+#[must_use]
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(LOCK_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn open_lock_file(path: &Path) -> io::Result<File> {
+    let lock = lock_path(path);
+    if let Some(parent) = lock.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(lock)
+}
+
+/// Contents of `path`; a missing file reads as empty.
+///
+/// # Errors
+///
+/// Returns any I/O error other than the file not existing.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(bytes),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Replace `path` with `contents`: write a temp file in the same directory,
+/// fsync it, and rename it over `path`.
+///
+/// # Errors
+///
+/// Returns any I/O errors encountered while writing, syncing or renaming.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let parent = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)?;
+    let name = path
+        .file_name()
+        .map_or_else(|| "state".into(), |name| name.to_string_lossy());
+    let temp_path = parent.join(format!(
+        ".{name}.{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = (|| {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        // Keep restrictive permissions (e.g. the 0600 privacy salt) across rewrites.
+        if let Ok(meta) = fs::metadata(path) {
+            fs::set_permissions(&temp_path, meta.permissions())?;
+        }
+        fs::rename(&temp_path, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        return written;
+    }
+    sync_dir(parent);
+    Ok(())
+}
+
+/// Make a rename in `dir` durable. Best-effort: not every platform can open
+/// a directory for syncing.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+/// Append `text` to the file at `path` in one write, under an exclusive lock
+/// on the file itself, and flush it to disk.
+///
+/// # Errors
+///
+/// Returns any I/O errors encountered while opening, locking, or writing the log.
+pub fn append(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock_exclusive()?;
+    let written = file
+        .write_all(text.as_bytes())
+        .and_then(|()| file.sync_data());
+    let _ = FileExt::unlock(&file);
+    written
+}
+
+/// Move a damaged state file aside as `<file>.corrupt-<timestamp>` and
+/// return the new location.
+///
+/// # Errors
+///
+/// Returns any I/O errors encountered while renaming the file.
+pub fn quarantine(path: &Path) -> io::Result<PathBuf> {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut target = path.as_os_str().to_owned();
+    target.push(format!("{QUARANTINE_INFIX}{stamp}"));
+    let mut target = PathBuf::from(target);
+    if target.exists() {
+        let mut unique = target.into_os_string();
+        unique.push(format!("-{}", std::process::id()));
+        target = PathBuf::from(unique);
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Parse the JSON document at `path`. Call with the file's [`StateLock`] held.
+///
+/// A missing or empty file yields `None`. A file that does not parse is
+/// [quarantined](quarantine) and also yields `None`, so the caller starts
+/// over from its default state.
+///
+/// # Errors
+///
+/// Returns any I/O errors encountered while reading or quarantining the file.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    let bytes = read(path)?;
+    if bytes.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    match serde_json::from_slice(&bytes) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => {
+            quarantine(path)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Atomically replace `path` with `value` as pretty-printed JSON.
+///
+/// # Errors
+///
+/// Returns any serialization or I/O errors.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let mut serialized = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+    serialized.push(b'\n');
+    write_atomic(path, &serialized)
+}
+
+/// Format a timestamp the way every state file stores it (`2024-01-15T10:30:00Z`).
+#[must_use]
+pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Parse a state-file timestamp: RFC 3339, or a bare `YYYY-MM-DD` date taken
+/// as midnight UTC.
+#[must_use]
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::Barrier;
+
+    fn quarantined(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().contains(QUARANTINE_INFIX))
+            .collect()
+    }
+
+    #[test]
+    fn write_atomic_replaces_contents_and_leaves_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("state.json")]);
+    }
+
+    #[test]
+    fn timestamps_round_trip_and_accept_bare_dates() {
+        let stamp = "2024-01-15T10:30:00Z";
+        assert_eq!(format_timestamp(parse_timestamp(stamp).unwrap()), stamp);
+        assert_eq!(
+            format_timestamp(parse_timestamp("2024-01-15").unwrap()),
+            "2024-01-15T00:00:00Z"
+        );
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+
+    #[test]
+    fn read_json_quarantines_damaged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage.json");
+        fs::write(&path, "{\"truncated\":").unwrap();
+
+        let value: Option<serde_json::Value> = read_json(&path).unwrap();
+        assert!(value.is_none());
+        assert!(!path.exists());
+        let moved = quarantined(dir.path());
+        assert_eq!(moved.len(), 1);
+        assert_eq!(fs::read_to_string(&moved[0]).unwrap(), "{\"truncated\":");
+    }
+
+    #[test]
+    fn try_acquire_reports_a_held_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("allow_once.jsonl");
+        let held = StateLock::acquire(&path).unwrap();
+        assert!(StateLock::try_acquire(&path).unwrap().is_none());
+        drop(held);
+        assert!(StateLock::try_acquire(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn concurrent_locked_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Arc::new(dir.path().join("counter.json"));
+        let threads = 8;
+        let barrier = Arc::new(Barrier::new(threads));
+
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let path = Arc::clone(&path);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    for _ in 0..10 {
+                        let _lock = StateLock::acquire(&path).unwrap();
+                        let count: u64 = read_json(&path).unwrap().unwrap_or(0);
+                        write_json(&path, &(count + 1)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let count: u64 = read_json(&path).unwrap().unwrap();
+        assert_eq!(count, 80);
+        assert!(quarantined(dir.path()).is_empty());
+    }
+}
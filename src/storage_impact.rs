@@ -0,0 +1,705 @@
+//! Object-count estimates for blocked bucket deletions.
+//!
+//! `aws s3 rb --force s3://logs` reads the same whether the bucket is empty
+//! or holds years of versioned audit logs. When enabled
+//! (`[output] storage_impact = true`), dcg asks the provider's own CLI, with
+//! whatever credentials are already in the environment, for a bounded object
+//! listing and the bucket's versioning and retention settings. The result is
+//! shown next to the denial: "would delete more than 1,000 objects in
+//! s3://logs, versioning enabled, object lock enabled".
+//!
+//! Lookups make network requests, so they are off by default and each one is
+//! killed after a short timeout. A lookup that fails (CLI not installed, no
+//! credentials, access denied) is left out of the report.
+
+use crate::preview::{PreviewOutput, PreviewPlan};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Objects listed before the count is reported as "more than".
+pub const LIST_LIMIT: u64 = 1000;
+
+/// Default timeout for each lookup (`[output] storage_impact_timeout_ms`).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Global lookup timeout (set from `[output]`); `None` means off.
+static TIMEOUT: OnceLock<Option<Duration>> = OnceLock::new();
+
+/// Initialize lookups from configuration. `None` turns them off.
+pub fn init(timeout: Option<Duration>) {
+    let _ = TIMEOUT.set(timeout);
+}
+
+/// The configured lookup timeout, or `None` when lookups are off.
+#[must_use]
+pub fn timeout() -> Option<Duration> {
+    TIMEOUT.get().copied().flatten()
+}
+
+/// Cloud storage service a command deletes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageProvider {
+    S3,
+    Gcs,
+    Azure,
+}
+
+impl StorageProvider {
+    /// What the provider calls the setting that protects objects from
+    /// deletion.
+    #[must_use]
+    pub const fn retention_name(self) -> &'static str {
+        match self {
+            Self::S3 => "object lock",
+            Self::Gcs => "retention policy",
+            Self::Azure => "soft delete",
+        }
+    }
+}
+
+/// The bucket (or Azure container) and prefix a command deletes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageTarget {
+    pub provider: StorageProvider,
+    /// Bucket name, or the container for Azure.
+    pub bucket: String,
+    /// Object prefix; empty for the whole bucket.
+    pub prefix: String,
+    /// Options from the original command that select the account, profile
+    /// or region, passed on to the lookups.
+    pub options: Vec<(String, String)>,
+}
+
+impl StorageTarget {
+    /// `s3://bucket/prefix`-style location for messages.
+    #[must_use]
+    pub fn location(&self) -> String {
+        let scheme = match self.provider {
+            StorageProvider::S3 => "s3",
+            StorageProvider::Gcs => "gs",
+            StorageProvider::Azure => "container",
+        };
+        if self.prefix.is_empty() {
+            format!("{scheme}://{}", self.bucket)
+        } else {
+            format!("{scheme}://{}/{}", self.bucket, self.prefix)
+        }
+    }
+
+    /// `--option value` words for the carried options named in `names`.
+    fn carried(&self, names: &[&str]) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|(name, _)| names.contains(&name.as_str()))
+            .flat_map(|(name, value)| [name.clone(), value.clone()])
+            .collect()
+    }
+}
+
+/// What a bucket deletion would remove, as far as the lookups could tell.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageImpact {
+    pub target: StorageTarget,
+    /// Objects found under the prefix, up to [`LIST_LIMIT`].
+    pub objects: Option<u64>,
+    /// Whether the listing stopped at [`LIST_LIMIT`].
+    pub more: bool,
+    /// Whether object versioning is enabled on the bucket.
+    pub versioning: Option<bool>,
+    /// Whether the provider's retention setting
+    /// ([`StorageProvider::retention_name`]) is enabled.
+    pub retention: Option<bool>,
+}
+
+impl StorageImpact {
+    /// One-line summary for the denial box.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let location = self.target.location();
+        let mut parts = vec![match self.objects {
+            Some(count) if self.more => format!(
+                "would delete more than {} objects in {location}",
+                crate::impact::group_digits(count)
+            ),
+            Some(count) => format!(
+                "would delete {} {} in {location}",
+                crate::impact::group_digits(count),
+                if count == 1 { "object" } else { "objects" }
+            ),
+            None => format!("{location} (object count unavailable)"),
+        }];
+        match self.versioning {
+            Some(true) => parts.push("versioning enabled".to_string()),
+            Some(false) => parts.push("versioning off".to_string()),
+            None => {}
+        }
+        let retention = self.target.provider.retention_name();
+        match self.retention {
+            Some(true) => parts.push(format!("{retention} enabled")),
+            Some(false) => parts.push(format!("no {retention}")),
+            None => {}
+        }
+        parts.join(", ")
+    }
+}
+
+/// What a lookup asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lookup {
+    Count,
+    Versioning,
+    Retention,
+    /// Versioning and retention from a single bucket description.
+    Settings,
+}
+
+/// The bucket `command` deletes from, if it is a bucket deletion.
+///
+/// Recognized: `aws s3 rb`, `aws s3 rm --recursive`, `gsutil rm -r`,
+/// `gsutil rb`, `gcloud storage rm --recursive`, `gcloud storage buckets
+/// delete`, `az storage container delete` and `az storage blob
+/// delete-batch`, optionally run through `sudo`.
+#[must_use]
+pub fn storage_target(command: &str) -> Option<StorageTarget> {
+    let words = crate::preview::split_words(command).ok()?;
+    let mut words = words.as_slice();
+    if words.first().is_some_and(|word| word == "sudo") {
+        words = &words[1..];
+    }
+    let (program, args) = words.split_first()?;
+    match Path::new(program).file_name()?.to_str()? {
+        "aws" => aws_target(args),
+        "gsutil" => gsutil_target(args),
+        "gcloud" => gcloud_target(args),
+        "az" => az_target(args),
+        _ => None,
+    }
+}
+
+/// Look up what `command` would delete, giving each lookup `timeout`.
+///
+/// Returns `None` if the command is not a bucket deletion dcg understands
+/// or every lookup failed.
+#[must_use]
+pub fn estimate_storage(command: &str, timeout: Duration) -> Option<StorageImpact> {
+    let target = storage_target(command)?;
+    let lookups = lookups(&target);
+    let outputs: Vec<(Lookup, PreviewOutput)> = std::thread::scope(|scope| {
+        // Spawn every lookup before joining any, so they run in parallel.
+        #[allow(clippy::needless_collect)]
+        let handles: Vec<_> = lookups
+            .iter()
+            .map(|(lookup, plan)| {
+                scope.spawn(move || {
+                    crate::preview::run_preview(plan, Path::new("."), timeout)
+                        .ok()
+                        .map(|output| (*lookup, output))
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok().flatten())
+            .collect()
+    });
+
+    let mut impact = StorageImpact {
+        target,
+        objects: None,
+        more: false,
+        versioning: None,
+        retention: None,
+    };
+    for (lookup, output) in &outputs {
+        apply(&mut impact, *lookup, output);
+    }
+    (impact.objects.is_some() || impact.versioning.is_some() || impact.retention.is_some())
+        .then_some(impact)
+}
+
+/// The provider CLI calls that describe `target`.
+fn lookups(target: &StorageTarget) -> Vec<(Lookup, PreviewPlan)> {
+    let plan = |program: &str, args: Vec<String>| PreviewPlan {
+        tool: format!("{program} lookup"),
+        program: program.to_string(),
+        args,
+    };
+    let words =
+        |words: &[&str]| -> Vec<String> { words.iter().map(|word| (*word).to_string()).collect() };
+    let bucket = target.bucket.clone();
+    let limit = (LIST_LIMIT + 1).to_string();
+    match target.provider {
+        StorageProvider::S3 => {
+            let global = target.carried(&["--profile", "--region"]);
+            let s3api = |args: Vec<String>| {
+                let mut all: Vec<String> = words(&["s3api"]);
+                all.extend(args);
+                all.extend(words(&["--bucket", bucket.as_str(), "--output", "json"]));
+                all.extend(global.iter().cloned());
+                plan("aws", all)
+            };
+            let mut list = words(&[
+                "list-objects-v2",
+                "--max-items",
+                limit.as_str(),
+                "--query",
+                "length(Contents || `[]`)",
+            ]);
+            if !target.prefix.is_empty() {
+                list.extend(words(&["--prefix", target.prefix.as_str()]));
+            }
+            vec![
+                (Lookup::Count, s3api(list)),
+                (Lookup::Versioning, s3api(words(&["get-bucket-versioning"]))),
+                (
+                    Lookup::Retention,
+                    s3api(words(&["get-object-lock-configuration"])),
+                ),
+            ]
+        }
+        StorageProvider::Gcs => {
+            let global = target.carried(&["--project", "--account", "--configuration"]);
+            let url = format!("gs://{bucket}");
+            let mut list = words(&[
+                "storage",
+                "objects",
+                "list",
+                format!("{url}/{}**", target.prefix).as_str(),
+                "--limit",
+                limit.as_str(),
+                "--format=value(name)",
+            ]);
+            list.extend(global.iter().cloned());
+            let mut describe = words(&[
+                "storage",
+                "buckets",
+                "describe",
+                url.as_str(),
+                "--format=json",
+            ]);
+            describe.extend(global);
+            vec![
+                (Lookup::Count, plan("gcloud", list)),
+                (Lookup::Settings, plan("gcloud", describe)),
+            ]
+        }
+        StorageProvider::Azure => {
+            let mut list = words(&[
+                "storage",
+                "blob",
+                "list",
+                "--container-name",
+                bucket.as_str(),
+                "--num-results",
+                limit.as_str(),
+                "--query",
+                "length(@)",
+                "--output",
+                "tsv",
+                "--only-show-errors",
+            ]);
+            list.extend(target.carried(&[
+                "--account-name",
+                "--account-key",
+                "--connection-string",
+                "--sas-token",
+                "--auth-mode",
+                "--subscription",
+            ]));
+            let mut show = words(&[
+                "storage",
+                "account",
+                "blob-service-properties",
+                "show",
+                "--query",
+                "{versioning: isVersioningEnabled, softDelete: deleteRetentionPolicy.enabled}",
+                "--output",
+                "json",
+                "--only-show-errors",
+            ]);
+            show.extend(target.carried(&["--account-name", "--subscription"]));
+            vec![
+                (Lookup::Count, plan("az", list)),
+                (Lookup::Settings, plan("az", show)),
+            ]
+        }
+    }
+}
+
+/// Fold the result of one lookup into `impact`. Failed lookups leave it
+/// unchanged.
+fn apply(impact: &mut StorageImpact, lookup: Lookup, output: &PreviewOutput) {
+    let provider = impact.target.provider;
+    if lookup == Lookup::Retention && provider == StorageProvider::S3 && !output.succeeded() {
+        // Buckets without object lock answer with an error, not `Disabled`.
+        if output
+            .stderr
+            .contains("ObjectLockConfigurationNotFoundError")
+        {
+            impact.retention = Some(false);
+        }
+        return;
+    }
+    if !output.succeeded() {
+        return;
+    }
+    let stdout = output.stdout.trim();
+    match lookup {
+        Lookup::Count => {
+            let count = if provider == StorageProvider::Gcs {
+                Some(
+                    stdout
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .count() as u64,
+                )
+            } else {
+                stdout.parse::<u64>().ok()
+            };
+            if let Some(count) = count {
+                impact.objects = Some(count.min(LIST_LIMIT));
+                impact.more = count > LIST_LIMIT;
+            }
+        }
+        Lookup::Versioning => {
+            // An empty answer means versioning was never turned on.
+            let json: serde_json::Value = serde_json::from_str(stdout).unwrap_or_default();
+            impact.versioning = Some(json["Status"] == "Enabled");
+        }
+        Lookup::Retention => {
+            let json: serde_json::Value = serde_json::from_str(stdout).unwrap_or_default();
+            impact.retention =
+                Some(json["ObjectLockConfiguration"]["ObjectLockEnabled"] == "Enabled");
+        }
+        Lookup::Settings => {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(stdout) else {
+                return;
+            };
+            if provider == StorageProvider::Gcs {
+                impact.versioning = Some(json["versioning_enabled"] == true);
+                impact.retention = Some(!json["retention_policy"].is_null());
+            } else {
+                impact.versioning = Some(json["versioning"] == true);
+                impact.retention = Some(json["softDelete"] == true);
+            }
+        }
+    }
+}
+
+/// Positional words, flags and valued options of a command line.
+#[derive(Debug, Default)]
+struct Words {
+    positional: Vec<String>,
+    flags: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Words {
+    /// Split `args`; `valued` lists options that take a separate value.
+    fn parse(args: &[String], valued: &[&str]) -> Self {
+        let mut words = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some((name, value)) = arg
+                .split_once('=')
+                .filter(|(name, _)| name.starts_with("--"))
+            {
+                words.options.push((name.to_string(), value.to_string()));
+            } else if valued.contains(&arg.as_str()) {
+                if let Some(value) = args.next() {
+                    words.options.push((arg.clone(), value.clone()));
+                }
+            } else if arg.len() > 1 && arg.starts_with('-') {
+                words.flags.push(arg.clone());
+            } else {
+                words.positional.push(arg.clone());
+            }
+        }
+        words
+    }
+
+    fn positional(&self, prefix: &[&str]) -> bool {
+        self.positional.len() >= prefix.len()
+            && prefix.iter().zip(&self.positional).all(|(a, b)| a == b)
+    }
+
+    fn option(&self, names: &[&str]) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(name, _)| names.contains(&name.as_str()))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn has_flag(&self, names: &[&str]) -> bool {
+        self.flags.iter().any(|flag| names.contains(&flag.as_str()))
+    }
+
+    /// Whether a short flag group (`-rf`) or long flag enables recursion.
+    fn recursive(&self) -> bool {
+        self.flags.iter().any(|flag| {
+            flag == "--recursive" || (!flag.starts_with("--") && flag.contains(['r', 'R']))
+        })
+    }
+
+    /// The options among `names`, to pass on to lookups.
+    fn carried(&self, names: &[&str]) -> Vec<(String, String)> {
+        self.options
+            .iter()
+            .filter(|(name, _)| names.contains(&name.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Split `url` (`s3://bucket/prefix`) into bucket and prefix.
+fn split_url(url: &str, scheme: &str) -> Option<(String, String)> {
+    let rest = url.strip_prefix(scheme)?.strip_prefix("://")?;
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    // Wildcards select objects the listing cannot reproduce.
+    if bucket.is_empty() || rest.contains(['*', '?', '[']) {
+        return None;
+    }
+    Some((bucket.to_string(), prefix.to_string()))
+}
+
+fn aws_target(args: &[String]) -> Option<StorageTarget> {
+    const CARRY: &[&str] = &["--profile", "--region"];
+    let words = Words::parse(
+        args,
+        &[
+            "--profile",
+            "--region",
+            "--endpoint-url",
+            "--output",
+            "--query",
+            "--include",
+            "--exclude",
+            "--color",
+            "--ca-bundle",
+            "--cli-read-timeout",
+            "--cli-connect-timeout",
+        ],
+    );
+    let url = if words.positional(&["s3", "rb"])
+        || (words.positional(&["s3", "rm"]) && words.recursive())
+    {
+        words.positional.get(2)?
+    } else {
+        return None;
+    };
+    let (bucket, prefix) = split_url(url, "s3")?;
+    Some(StorageTarget {
+        provider: StorageProvider::S3,
+        bucket,
+        prefix,
+        options: words.carried(CARRY),
+    })
+}
+
+fn gsutil_target(args: &[String]) -> Option<StorageTarget> {
+    let words = Words::parse(args, &["-o", "-h", "-u"]);
+    let deletes = words.positional(&["rb"]) || (words.positional(&["rm"]) && words.recursive());
+    if !deletes {
+        return None;
+    }
+    let (bucket, prefix) = split_url(words.positional.get(1)?, "gs")?;
+    Some(StorageTarget {
+        provider: StorageProvider::Gcs,
+        bucket,
+        prefix,
+        options: Vec::new(),
+    })
+}
+
+fn gcloud_target(args: &[String]) -> Option<StorageTarget> {
+    const CARRY: &[&str] = &["--project", "--account", "--configuration"];
+    let words = Words::parse(
+        args,
+        &[
+            "--project",
+            "--account",
+            "--configuration",
+            "--billing-project",
+            "--format",
+            "--verbosity",
+        ],
+    );
+    let url = if words.positional(&["storage", "buckets", "delete"]) {
+        words.positional.get(3)?
+    } else if words.positional(&["storage", "rm"]) && words.recursive() {
+        words.positional.get(2)?
+    } else {
+        return None;
+    };
+    let (bucket, prefix) = split_url(url, "gs")?;
+    Some(StorageTarget {
+        provider: StorageProvider::Gcs,
+        bucket,
+        prefix,
+        options: words.carried(CARRY),
+    })
+}
+
+fn az_target(args: &[String]) -> Option<StorageTarget> {
+    const CARRY: &[&str] = &[
+        "--account-name",
+        "--account-key",
+        "--connection-string",
+        "--sas-token",
+        "--auth-mode",
+        "--subscription",
+    ];
+    let mut valued = CARRY.to_vec();
+    valued.extend([
+        "--name",
+        "-n",
+        "--source",
+        "-s",
+        "--pattern",
+        "--output",
+        "-o",
+    ]);
+    let words = Words::parse(args, &valued);
+    let container = if words.positional(&["storage", "container", "delete"]) {
+        words.option(&["--name", "-n"])?
+    } else if words.positional(&["storage", "blob", "delete-batch"])
+        && words.option(&["--pattern"]).is_none()
+        && !words.has_flag(&["--dryrun"])
+    {
+        words.option(&["--source", "-s"])?
+    } else {
+        return None;
+    };
+    Some(StorageTarget {
+        provider: StorageProvider::Azure,
+        bucket: container.to_string(),
+        prefix: String::new(),
+        options: words.carried(CARRY),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(exit_code: i32, stdout: &str, stderr: &str) -> PreviewOutput {
+        PreviewOutput {
+            exit_code: Some(exit_code),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            timed_out: false,
+            truncated: false,
+            duration: Duration::from_millis(300),
+        }
+    }
+
+    #[test]
+    fn targets_cover_bucket_deletions() {
+        let s3 = storage_target("aws --profile prod s3 rb s3://logs --force").unwrap();
+        assert_eq!(s3.provider, StorageProvider::S3);
+        assert_eq!(s3.location(), "s3://logs");
+        assert_eq!(s3.options, vec![("--profile".into(), "prod".into())]);
+        let endpoint = storage_target(
+            "aws --region eu-west-1 --endpoint-url https://s3.example.com s3 rb s3://logs",
+        )
+        .unwrap();
+        assert_eq!(
+            endpoint.options,
+            vec![("--region".into(), "eu-west-1".into())]
+        );
+
+        let prefix = storage_target("aws s3 rm s3://logs/2024/ --recursive").unwrap();
+        assert_eq!(prefix.prefix, "2024/");
+
+        let gcs = storage_target("gsutil -m rm -r gs://assets/img").unwrap();
+        assert_eq!(gcs.location(), "gs://assets/img");
+        let gcloud =
+            storage_target("gcloud storage buckets delete gs://assets --project=web").unwrap();
+        assert_eq!(gcloud.options, vec![("--project".into(), "web".into())]);
+
+        let az = storage_target("az storage container delete --name backups --account-name acct")
+            .unwrap();
+        assert_eq!(az.provider, StorageProvider::Azure);
+        assert_eq!(az.bucket, "backups");
+    }
+
+    #[test]
+    fn targets_skip_single_objects_and_wildcards() {
+        assert!(storage_target("aws s3 rm s3://logs/app.log").is_none());
+        assert!(storage_target("aws s3 ls s3://logs").is_none());
+        assert!(storage_target("gsutil rm -r gs://assets/*.png").is_none());
+        assert!(storage_target("az storage blob delete-batch -s logs --pattern '*.tmp'").is_none());
+        assert!(storage_target("aws s3 rb s3://logs && echo done").is_none());
+    }
+
+    #[test]
+    fn s3_lookups_reuse_profile_and_cap_the_listing() {
+        let target = storage_target("aws --profile prod s3 rb s3://logs --force").unwrap();
+        let lookups = lookups(&target);
+        let list = lookups[0].1.command_line();
+        assert!(list.starts_with("aws s3api list-objects-v2 --max-items 1001"));
+        assert!(list.ends_with("--bucket logs --output json --profile prod"));
+        assert_eq!(lookups.len(), 3);
+    }
+
+    #[test]
+    fn lookups_fill_in_counts_and_settings() {
+        let target = storage_target("aws s3 rb s3://logs --force").unwrap();
+        let mut impact = StorageImpact {
+            target,
+            objects: None,
+            more: false,
+            versioning: None,
+            retention: None,
+        };
+        apply(&mut impact, Lookup::Count, &output(0, "1001\n", ""));
+        apply(
+            &mut impact,
+            Lookup::Versioning,
+            &output(0, r#"{"Status": "Enabled"}"#, ""),
+        );
+        apply(
+            &mut impact,
+            Lookup::Retention,
+            &output(
+                254,
+                "",
+                "An error occurred (ObjectLockConfigurationNotFoundError)",
+            ),
+        );
+        assert_eq!(impact.objects, Some(LIST_LIMIT));
+        assert!(impact.more);
+        assert_eq!(
+            impact.summary(),
+            "would delete more than 1,000 objects in s3://logs, versioning enabled, \
+             no object lock"
+        );
+
+        // Failed lookups (e.g. missing credentials) report nothing.
+        let target = storage_target("gsutil rb gs://assets").unwrap();
+        let mut impact = StorageImpact {
+            target,
+            objects: None,
+            more: false,
+            versioning: None,
+            retention: None,
+        };
+        apply(&mut impact, Lookup::Count, &output(1, "", "ERROR: auth"));
+        apply(
+            &mut impact,
+            Lookup::Settings,
+            &output(
+                0,
+                r#"{"versioning_enabled": true, "retention_policy": {"retentionPeriod": "86400"}}"#,
+                "",
+            ),
+        );
+        assert_eq!(
+            impact.summary(),
+            "gs://assets (object count unavailable), versioning enabled, retention policy enabled"
+        );
+    }
+}
@@ -17,17 +17,40 @@
 //!
 //! Each suggestion includes:
 //! - **Confidence tier**: Based on frequency, consistency, and path clustering
-//! - **Risk level**: Based on command type and potential for misuse
+//! - **Risk level**: Based on the severity and decision mode of the pack rules
+//!   the commands trip
 //! - **Path patterns**: Common directories where the command was blocked
-
+//!
+//! # Suggestion Identity
+//!
+//! Each suggestion carries a `suggestion_id` derived from its cluster's
+//! centroid (the tokens every variant shares). With a
+//! [`ClusterHistory`](crate::suggest_history::ClusterHistory), fresh clusters
+//! that resemble a remembered centroid reuse its id and are merged, and
+//! accepted or dismissed suggestions are dropped.
+
+use crate::api::{Action, Context, Guard, GuardBuilder, Severity};
+use crate::config::Config;
 use crate::normalize::strip_wrapper_prefixes;
+use crate::packs::REGISTRY;
+use crate::privacy::{has_placeholders, materialize_placeholders, token_regex};
+use crate::suggest_history::ClusterHistory;
+use chrono::{DateTime, Utc};
 use regex::{Regex, escape as regex_escape};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as FmtWrite;
+use std::sync::LazyLock;
 
 /// Default similarity threshold for clustering (Jaccard over token sets).
 const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.30;
 
+/// Similarity a fresh cluster's centroid needs with a remembered one to be
+/// treated as the same suggestion. Stricter than clustering, since a match
+/// can hide the cluster (when the remembered one was dismissed).
+const HISTORY_MERGE_THRESHOLD: f32 = 0.5;
+
 /// Maximum number of alternations before using character class patterns.
 const MAX_ALTERNATION_COUNT: usize = 10;
 
@@ -92,7 +115,7 @@ impl std::fmt::Display for ConfidenceTier {
 ///
 /// Indicates how dangerous it would be to allow this pattern.
 /// Higher risk means the pattern could potentially match destructive commands.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum RiskLevel {
     /// Low risk: safe command types like read-only operations.
@@ -189,6 +212,9 @@ pub struct PathPattern {
 /// Enhanced allowlist suggestion with confidence, risk, and path information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AllowlistSuggestion {
+    /// Stable identifier, shared with the same cluster on later runs.
+    #[serde(default)]
+    pub suggestion_id: String,
     /// The cluster of similar commands this suggestion is based on.
     pub cluster: CommandCluster,
     /// Confidence tier for this suggestion.
@@ -213,12 +239,20 @@ impl AllowlistSuggestion {
     /// Create a new suggestion from a cluster with basic analysis.
     #[must_use]
     pub fn from_cluster(cluster: CommandCluster) -> Self {
+        Self::from_cluster_with(cluster, &RISK_GUARD)
+    }
+
+    /// Create a new suggestion, assessing risk with `risk_guard` (see
+    /// [`risk_guard`]).
+    #[must_use]
+    pub fn from_cluster_with(cluster: CommandCluster, risk_guard: &Guard) -> Self {
         let confidence = calculate_confidence_tier(cluster.frequency, cluster.unique_count);
-        let risk = assess_risk_level(&cluster.commands);
+        let risk = assess_risk_level_with(risk_guard, &cluster.commands);
         let reason = determine_primary_reason(cluster.frequency, false, &[]);
         let score = calculate_suggestion_score(confidence, risk);
 
         Self {
+            suggestion_id: cluster_suggestion_id(&cluster),
             cluster,
             confidence,
             risk,
@@ -301,43 +335,68 @@ pub fn calculate_confidence_tier(frequency: usize, unique_variants: usize) -> Co
     ConfidenceTier::Low
 }
 
-/// Assess risk level based on command content.
+/// Guard that assesses suggestion risk under the default policy.
+static RISK_GUARD: LazyLock<Guard> = LazyLock::new(|| risk_guard(&Config::default()));
+
+/// Build the guard that assesses suggestion risk under `config`.
+///
+/// Every built-in pack is enabled alongside the config's external packs,
+/// `overrides.block` entries, and `[policy]` modes. Allowlists, allow-once
+/// entries, and `overrides.allow` are left out: they are what a suggestion
+/// would add to, not evidence that a command is safe.
+#[must_use]
+pub fn risk_guard(config: &Config) -> Guard {
+    let mut config = config.clone();
+    config.overrides.allow.clear();
+    config.packs.disabled.clear();
+    REGISTRY
+        .all_pack_ids()
+        .into_iter()
+        .fold(Guard::builder().config(config), GuardBuilder::enable_pack)
+        .ignore_allow_once()
+        .build()
+        .expect("built-in pack IDs are known")
+}
+
+/// Assess risk level by evaluating the commands against every built-in pack
+/// under the default policy.
+///
+/// See [`assess_risk_level_with`].
 #[must_use]
 pub fn assess_risk_level(commands: &[String]) -> RiskLevel {
-    // Check for high-risk indicators in any command
-    for cmd in commands {
-        let lower = cmd.to_lowercase();
-
-        // Critical risk patterns - these should rarely be auto-allowlisted
-        if lower.contains("rm -rf")
-            || lower.contains("rmdir")
-            || lower.contains("drop ")
-            || lower.contains("truncate ")
-            || lower.contains("delete ")
-            || lower.contains("--force")
-            || lower.contains("-f ")
-            || lower.contains("reset --hard")
-            || lower.contains("clean -f")
-        {
-            return RiskLevel::High;
-        }
+    assess_risk_level_with(&RISK_GUARD, commands)
+}
 
-        // Medium risk patterns
-        if lower.contains("rm ")
-            || lower.contains("git reset")
-            || lower.contains("git checkout")
-            || lower.contains("git restore")
-            || lower.contains("docker rm")
-            || lower.contains("docker rmi")
-            || lower.contains("kubectl delete")
-            || lower.starts_with("sudo ")
-        {
-            return RiskLevel::Medium;
-        }
-    }
+/// Assess the risk of denied commands with a guard from [`risk_guard`].
+///
+/// A command's risk follows the rule that decides it: denied by a critical
+/// or high severity rule is high, any other denial or a warning is medium,
+/// and log-only is low. The commands come from denial history, so one that
+/// no rule matches was blocked by something this guard cannot see (a pack
+/// or override since removed) and is high. A cluster is as risky as its
+/// riskiest command.
+#[must_use]
+pub fn assess_risk_level_with(guard: &Guard, commands: &[String]) -> RiskLevel {
+    commands
+        .iter()
+        .map(|command| command_risk(guard, command))
+        .max()
+        .unwrap_or(RiskLevel::Low)
+}
 
-    // Low risk - read-only or safe operations
-    RiskLevel::Low
+fn command_risk(guard: &Guard, command: &str) -> RiskLevel {
+    // Redacted history entries are evaluated with sample values.
+    let decision = guard.evaluate(&materialize_placeholders(command), &Context::new());
+    let severity = decision.rule.as_ref().and_then(|rule| rule.severity);
+    match decision.action {
+        Action::Deny => match severity {
+            Some(Severity::Medium | Severity::Low) => RiskLevel::Medium,
+            Some(Severity::Critical | Severity::High) | None => RiskLevel::High,
+        },
+        Action::Warn => RiskLevel::Medium,
+        Action::Log => RiskLevel::Low,
+        Action::Allow => RiskLevel::High,
+    }
 }
 
 /// Determine the primary reason for suggesting allowlisting.
@@ -525,6 +584,32 @@ pub struct CommandEntryInfo {
 pub fn generate_enhanced_suggestions(
     entries: &[CommandEntryInfo],
     min_frequency: usize,
+) -> Vec<AllowlistSuggestion> {
+    build_enhanced_suggestions(entries, min_frequency, &RISK_GUARD, None)
+}
+
+/// Generate enhanced suggestions, merging clusters into those remembered in
+/// `history` and skipping accepted or dismissed ones.
+///
+/// New clusters are added to `history` as pending; remembered ones get their
+/// last-seen time (and, while pending, their pattern) refreshed. Risk is
+/// assessed with `risk_guard` (see [`risk_guard`]).
+#[must_use]
+pub fn generate_enhanced_suggestions_with_history(
+    entries: &[CommandEntryInfo],
+    min_frequency: usize,
+    risk_guard: &Guard,
+    history: &mut ClusterHistory,
+    now: DateTime<Utc>,
+) -> Vec<AllowlistSuggestion> {
+    build_enhanced_suggestions(entries, min_frequency, risk_guard, Some((history, now)))
+}
+
+fn build_enhanced_suggestions(
+    entries: &[CommandEntryInfo],
+    min_frequency: usize,
+    risk_guard: &Guard,
+    history: Option<(&mut ClusterHistory, DateTime<Utc>)>,
 ) -> Vec<AllowlistSuggestion> {
     if entries.is_empty() {
         return Vec::new();
@@ -556,11 +641,21 @@ pub fn generate_enhanced_suggestions(
 
     // Generate clusters
     let clusters = cluster_denied_commands(&commands, 1);
+    let clusters: Vec<(CommandCluster, String)> = match history {
+        Some((history, now)) => merge_with_history(clusters, history, now),
+        None => clusters
+            .into_iter()
+            .map(|cluster| {
+                let id = cluster_suggestion_id(&cluster);
+                (cluster, id)
+            })
+            .collect(),
+    };
 
     // Enhance clusters with path and bypass information
     let mut suggestions: Vec<AllowlistSuggestion> = clusters
         .into_iter()
-        .map(|cluster| {
+        .map(|(cluster, suggestion_id)| {
             // Collect working dirs for all commands in this cluster
             let working_dirs: Vec<String> = cluster
                 .commands
@@ -577,9 +672,11 @@ pub fn generate_enhanced_suggestions(
                 .map(|(_, _, bypasses)| *bypasses)
                 .sum();
 
-            AllowlistSuggestion::from_cluster(cluster)
+            let mut suggestion = AllowlistSuggestion::from_cluster_with(cluster, risk_guard)
                 .with_path_analysis(&working_dirs)
-                .with_bypass_count(bypass_count)
+                .with_bypass_count(bypass_count);
+            suggestion.suggestion_id = suggestion_id;
+            suggestion
         })
         .collect();
 
@@ -593,6 +690,112 @@ pub fn generate_enhanced_suggestions(
     suggestions
 }
 
+/// Assign each cluster a suggestion id, reusing the id of the most similar
+/// remembered centroid, merge clusters that land on the same id, and drop
+/// the ones already decided.
+fn merge_with_history(
+    clusters: Vec<CommandCluster>,
+    history: &mut ClusterHistory,
+    now: DateTime<Utc>,
+) -> Vec<(CommandCluster, String)> {
+    // Only centroids from earlier runs take part in matching, so a single
+    // batch clusters exactly as it would without history.
+    let known = history.clusters.len();
+    let mut merged: Vec<(CommandCluster, String)> = Vec::new();
+    let mut centroids: Vec<(String, Vec<String>)> = Vec::new();
+
+    for cluster in clusters {
+        let program = cluster_program(&cluster);
+        let tokens = cluster_centroid(&cluster);
+        let remembered = history.clusters[..known]
+            .iter()
+            .filter(|c| c.program == program)
+            .map(|c| (c, jaccard_similarity(&c.tokens, &tokens)))
+            .filter(|(_, similarity)| *similarity >= HISTORY_MERGE_THRESHOLD)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(c, _)| c.suggestion_id.clone());
+        let id = remembered.unwrap_or_else(|| suggestion_id_for(&program, &tokens));
+
+        match merged.iter_mut().find(|(_, existing)| *existing == id) {
+            Some((existing, _)) => merge_clusters(existing, cluster),
+            None => {
+                merged.push((cluster, id));
+                centroids.push((program, tokens));
+            }
+        }
+    }
+
+    for ((cluster, id), (program, tokens)) in merged.iter().zip(&centroids) {
+        history.observe(id, program, tokens, &cluster.proposed_pattern, now);
+    }
+    merged.retain(|(_, id)| history.get(id).is_none_or(|c| c.status.is_pending()));
+    merged
+}
+
+fn merge_clusters(into: &mut CommandCluster, other: CommandCluster) {
+    for command in other.commands {
+        if !into.commands.contains(&command) {
+            into.commands.push(command);
+        }
+    }
+    for normalized in other.normalized {
+        if !into.normalized.contains(&normalized) {
+            into.normalized.push(normalized);
+        }
+    }
+    into.frequency = into.frequency.saturating_add(other.frequency);
+    into.unique_count = into.normalized.len();
+    into.proposed_pattern = build_proposed_pattern(&into.normalized);
+}
+
+/// First token of the cluster's commands.
+fn cluster_program(cluster: &CommandCluster) -> String {
+    cluster
+        .normalized
+        .first()
+        .and_then(|normalized| tokenize_for_similarity(normalized).into_iter().next())
+        .unwrap_or_default()
+}
+
+/// Sorted tokens shared by every command in the cluster.
+fn cluster_centroid(cluster: &CommandCluster) -> Vec<String> {
+    let mut token_sets = cluster.normalized.iter().map(|normalized| {
+        tokenize_for_similarity(normalized)
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+    });
+    let Some(first) = token_sets.next() else {
+        return Vec::new();
+    };
+    token_sets
+        .fold(first, |shared, tokens| {
+            shared.intersection(&tokens).cloned().collect()
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Suggestion id a cluster gets when no remembered centroid matches it.
+#[must_use]
+pub fn cluster_suggestion_id(cluster: &CommandCluster) -> String {
+    suggestion_id_for(&cluster_program(cluster), &cluster_centroid(cluster))
+}
+
+fn suggestion_id_for(program: &str, tokens: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(program.as_bytes());
+    for token in tokens {
+        hasher.update([0]);
+        hasher.update(token.as_bytes());
+    }
+    let digest = hasher.finalize();
+    let mut id = String::with_capacity(16);
+    for byte in &digest[..8] {
+        let _ = write!(id, "{byte:02x}");
+    }
+    id
+}
+
 /// Filter suggestions by confidence tier.
 #[must_use]
 pub fn filter_by_confidence(
@@ -723,7 +926,7 @@ pub fn generate_pattern_from_cluster(commands: &[String]) -> GeneratedPattern {
 
     // Single command: return exact match pattern
     if unique_commands.len() == 1 {
-        let regex = format!("^{}$", regex_escape(&unique_commands[0]));
+        let regex = format!("^{}$", command_regex(&unique_commands[0]));
         return GeneratedPattern {
             regex,
             specificity_score: 1.0,
@@ -760,6 +963,21 @@ pub fn generate_pattern_from_cluster(commands: &[String]) -> GeneratedPattern {
     }
 }
 
+/// Regex matching `command` exactly.
+///
+/// Commands logged with `privacy.log_mode = "redacted"` carry placeholders
+/// (`<path>`, `<str>`, ...), which become the class of values they replaced.
+fn command_regex(command: &str) -> String {
+    if !has_placeholders(command) {
+        return regex_escape(command);
+    }
+    command
+        .split_whitespace()
+        .map(token_regex)
+        .collect::<Vec<_>>()
+        .join(r"\s+")
+}
+
 /// Deduplicate commands while preserving order.
 fn deduplicate_commands(commands: &[String]) -> Vec<String> {
     let mut seen = HashSet::new();
@@ -832,7 +1050,7 @@ fn build_segmented_pattern(
     if prefix_len > 0 {
         let prefix_tokens: Vec<&str> = tokenized[0][..prefix_len].to_vec();
         for token in prefix_tokens {
-            parts.push(regex_escape(token));
+            parts.push(token_regex(token));
         }
     }
 
@@ -864,7 +1082,7 @@ fn build_segmented_pattern(
                 // Single variant - use exact match
                 let escaped: Vec<String> = middle_variants[0]
                     .split_whitespace()
-                    .map(regex_escape)
+                    .map(token_regex)
                     .collect();
                 parts.extend(escaped);
             } else if middle_variants.len() <= MAX_ALTERNATION_COUNT {
@@ -873,7 +1091,7 @@ fn build_segmented_pattern(
                     .iter()
                     .map(|v| {
                         v.split_whitespace()
-                            .map(regex_escape)
+                            .map(token_regex)
                             .collect::<Vec<_>>()
                             .join(r"\s+")
                     })
@@ -898,7 +1116,7 @@ fn build_segmented_pattern(
         let suffix_start = first_len - suffix_len;
         let suffix_tokens: Vec<&str> = first[suffix_start..].to_vec();
         for token in suffix_tokens {
-            parts.push(regex_escape(token));
+            parts.push(token_regex(token));
         }
     }
 
@@ -955,7 +1173,7 @@ fn validate_pattern_against_commands(pattern: &str, commands: &[String]) -> (boo
     let mut example_matches = Vec::new();
 
     for cmd in commands {
-        if regex.is_match(cmd) {
+        if regex.is_match(&materialize_placeholders(cmd)) {
             if example_matches.len() < 3 {
                 example_matches.push(cmd.clone());
             }
@@ -1229,12 +1447,12 @@ fn build_proposed_pattern(commands: &[String]) -> String {
     }
 
     if unique.len() == 1 {
-        return format!("^{}$", regex_escape(&unique[0]));
+        return format!("^{}$", command_regex(&unique[0]));
     }
 
     let mut parts = Vec::with_capacity(unique.len());
     for cmd in unique {
-        parts.push(regex_escape(&cmd));
+        parts.push(command_regex(&cmd));
     }
 
     format!("^(?:{})$", parts.join("|"))
@@ -1619,6 +1837,25 @@ mod tests {
         assert!(pattern.specificity_score < 1.0);
     }
 
+    #[test]
+    fn generate_pattern_from_redacted_commands() {
+        let single = generate_pattern_from_cluster(&["rm -rf <path>".to_string()]);
+        assert!(single.matches_all);
+        let regex = Regex::new(&single.regex).unwrap();
+        assert!(regex.is_match("rm -rf /tmp/build"));
+        assert!(!regex.is_match("rm -rf /tmp/build; reboot"));
+
+        let commands = vec![
+            "kubectl delete pod web -n <str>".to_string(),
+            "kubectl delete pod api -n <str>".to_string(),
+        ];
+        let pattern = generate_pattern_from_cluster(&commands);
+        assert!(pattern.matches_all);
+        let regex = Regex::new(&pattern.regex).unwrap();
+        assert!(regex.is_match("kubectl delete pod web -n staging"));
+        assert!(!regex.is_match("kubectl delete pod db -n staging"));
+    }
+
     #[test]
     fn common_prefix_length_calculation() {
         let tokenized = vec![
@@ -0,0 +1,302 @@
+//! Cluster memory for `dcg suggest-allowlist`.
+//!
+//! Each run clusters the denied commands in its `--since` window from
+//! scratch. To keep repeated runs from proposing near-duplicates of earlier
+//! suggestions, every cluster's centroid (the tokens all of its variants
+//! share) and proposed pattern are remembered in a small JSON sidecar (next to
+//! the pending exception store by default) under a stable `suggestion_id`.
+//! Later runs fold fresh clusters into the remembered ones they resemble, and
+//! suggestions that were accepted or dismissed are not proposed again.
+//!
+//! The sidecar is bookkeeping only: a missing, locked, or corrupt file just
+//! means every cluster looks new.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::resolve_config_path_value;
+use crate::storage::{self, StateLock};
+
+/// Environment override for the suggestion history path.
+pub const ENV_SUGGESTION_HISTORY_PATH: &str = "DCG_SUGGESTION_HISTORY_PATH";
+
+const SUGGESTION_HISTORY_FILE: &str = "suggestion_history.json";
+const SCHEMA_VERSION: u32 = 1;
+
+/// Least recently seen clusters are dropped beyond this many.
+pub const MAX_REMEMBERED_CLUSTERS: usize = 1000;
+
+/// What the user decided about a suggestion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionStatus {
+    /// Proposed but not yet acted on; shown again on later runs.
+    #[default]
+    Pending,
+    /// Added to the allowlist.
+    Accepted,
+    /// Skipped or dismissed; never proposed again.
+    Dismissed,
+}
+
+impl SuggestionStatus {
+    /// Whether suggestions with this status are still proposed.
+    #[must_use]
+    pub const fn is_pending(self) -> bool {
+        matches!(self, Self::Pending)
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Dismissed => "dismissed",
+        }
+    }
+}
+
+/// A cluster remembered from an earlier run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClusterCentroid {
+    pub suggestion_id: String,
+    /// First token of the cluster's commands (clusters never span programs).
+    pub program: String,
+    /// Sorted tokens shared by every command in the cluster when first seen.
+    pub tokens: Vec<String>,
+    /// Most recent proposed pattern (frozen once accepted or dismissed).
+    pub proposed_pattern: String,
+    #[serde(default)]
+    pub status: SuggestionStatus,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// Contents of the suggestion history sidecar.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClusterHistory {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub clusters: Vec<ClusterCentroid>,
+}
+
+impl ClusterHistory {
+    /// The remembered cluster with this id.
+    #[must_use]
+    pub fn get(&self, suggestion_id: &str) -> Option<&ClusterCentroid> {
+        self.clusters
+            .iter()
+            .find(|c| c.suggestion_id == suggestion_id)
+    }
+
+    /// Remember a new cluster as pending, or refresh one already known.
+    ///
+    /// Pending clusters take the latest pattern; decided ones keep the
+    /// pattern the user saw.
+    pub fn observe(
+        &mut self,
+        suggestion_id: &str,
+        program: &str,
+        tokens: &[String],
+        proposed_pattern: &str,
+        now: DateTime<Utc>,
+    ) {
+        let stamp = storage::format_timestamp(now);
+        if let Some(cluster) = self
+            .clusters
+            .iter_mut()
+            .find(|c| c.suggestion_id == suggestion_id)
+        {
+            if cluster.status.is_pending() {
+                cluster.proposed_pattern = proposed_pattern.to_string();
+            }
+            cluster.last_seen = stamp;
+            return;
+        }
+        self.clusters.push(ClusterCentroid {
+            suggestion_id: suggestion_id.to_string(),
+            program: program.to_string(),
+            tokens: tokens.to_vec(),
+            proposed_pattern: proposed_pattern.to_string(),
+            status: SuggestionStatus::Pending,
+            first_seen: stamp.clone(),
+            last_seen: stamp,
+        });
+    }
+
+    /// Record a decision. Returns `false` when the id is unknown.
+    pub fn set_status(&mut self, suggestion_id: &str, status: SuggestionStatus) -> bool {
+        match self
+            .clusters
+            .iter_mut()
+            .find(|c| c.suggestion_id == suggestion_id)
+        {
+            Some(cluster) => {
+                cluster.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn prune(&mut self) {
+        if self.clusters.len() > MAX_REMEMBERED_CLUSTERS {
+            self.clusters.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+            self.clusters.truncate(MAX_REMEMBERED_CLUSTERS);
+        }
+    }
+}
+
+/// JSON-backed suggestion history store.
+#[derive(Debug, Clone)]
+pub struct SuggestionHistoryStore {
+    path: PathBuf,
+}
+
+impl SuggestionHistoryStore {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resolve the default path (env override or the dcg state directory).
+    #[must_use]
+    pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_SUGGESTION_HISTORY_PATH) {
+            if let Some(path) = resolve_config_path_value(&value, cwd) {
+                return path;
+            }
+        }
+
+        crate::paths::state_file(SUGGESTION_HISTORY_FILE)
+    }
+
+    /// Load the history. A missing or unreadable sidecar yields an empty one.
+    #[must_use]
+    pub fn load(&self) -> ClusterHistory {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record a decision for `suggestion_id`. Returns `false` when the id is
+    /// unknown.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn set_status(&self, suggestion_id: &str, status: SuggestionStatus) -> io::Result<bool> {
+        self.update(|history| history.set_status(suggestion_id, status))
+    }
+
+    /// Apply `apply` to the history under an exclusive lock and save it.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the sidecar.
+    pub fn update<T>(&self, apply: impl FnOnce(&mut ClusterHistory) -> T) -> io::Result<T> {
+        let _lock = StateLock::acquire(&self.path)?;
+        let mut history: ClusterHistory = storage::read_json(&self.path)?.unwrap_or_default();
+        history.schema_version = SCHEMA_VERSION;
+        let result = apply(&mut history);
+        history.prune();
+
+        storage::write_json(&self.path, &history)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn observe_refreshes_pending_and_freezes_decided_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SuggestionHistoryStore::new(dir.path().join("suggestion_history.json"));
+        let tokens = vec!["npm".to_string(), "run".to_string()];
+
+        store
+            .update(|history| {
+                history.observe(
+                    "abc",
+                    "npm",
+                    &tokens,
+                    "^npm run a$",
+                    at("2026-01-01T00:00:00Z"),
+                );
+            })
+            .unwrap();
+        store
+            .update(|history| {
+                history.observe(
+                    "abc",
+                    "npm",
+                    &tokens,
+                    "^npm run b$",
+                    at("2026-01-02T00:00:00Z"),
+                );
+            })
+            .unwrap();
+
+        let history = store.load();
+        assert_eq!(history.schema_version, SCHEMA_VERSION);
+        let cluster = history.get("abc").unwrap();
+        assert_eq!(cluster.proposed_pattern, "^npm run b$");
+        assert_eq!(cluster.first_seen, "2026-01-01T00:00:00Z");
+        assert_eq!(cluster.last_seen, "2026-01-02T00:00:00Z");
+        assert_eq!(cluster.status, SuggestionStatus::Pending);
+
+        assert!(
+            store
+                .set_status("abc", SuggestionStatus::Dismissed)
+                .unwrap()
+        );
+        assert!(
+            !store
+                .set_status("missing", SuggestionStatus::Accepted)
+                .unwrap()
+        );
+        store
+            .update(|history| {
+                history.observe(
+                    "abc",
+                    "npm",
+                    &tokens,
+                    "^npm run c$",
+                    at("2026-01-03T00:00:00Z"),
+                );
+            })
+            .unwrap();
+
+        let history = store.load();
+        let cluster = history.get("abc").unwrap();
+        assert_eq!(cluster.status, SuggestionStatus::Dismissed);
+        assert_eq!(cluster.proposed_pattern, "^npm run b$");
+        assert_eq!(cluster.last_seen, "2026-01-03T00:00:00Z");
+    }
+
+    #[test]
+    fn corrupt_sidecar_loads_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("suggestion_history.json");
+        std::fs::write(&path, "not json").unwrap();
+        let store = SuggestionHistoryStore::new(path);
+        assert!(store.load().clusters.is_empty());
+    }
+}
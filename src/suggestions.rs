@@ -10,11 +10,20 @@
 //! - [`Suggestion`] struct with actionable guidance
 //! - [`SUGGESTION_REGISTRY`] static registry keyed by `rule_id`
 //! - [`get_suggestions`] lookup function
+//! - [`get_suggestions_for_project`], which puts safer commands learned from
+//!   the project's command history ahead of the registered ones
+//! - [`SuggestionProvider`] functions that packs register per pattern (see
+//!   [`crate::packs::PackEntry::with_suggestion_providers`]) to build
+//!   suggestions from the blocked command itself, and
+//!   [`suggestions_for_command`], which puts those first
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::LazyLock;
 
+use crate::history::{HistoryAnalyzer, HistoryDb};
+
 /// Type of suggestion to help the user.
 ///
 /// Each kind represents a different strategy for helping users
@@ -140,6 +149,147 @@ pub fn get_suggestion_by_kind(rule_id: &str, kind: SuggestionKind) -> Option<&'s
     get_suggestions(rule_id).and_then(|suggestions| suggestions.iter().find(|s| s.kind == kind))
 }
 
+// ============================================================================
+// Suggestion Providers (Command-Aware)
+// ============================================================================
+
+/// Builds suggestions from the blocked command, e.g. the exact
+/// `git push --force-with-lease origin main` for `git push -f origin main`.
+///
+/// Providers return an empty list when the command lacks what they need.
+pub type SuggestionProvider = fn(&ParsedCommand<'_>) -> Vec<Suggestion>;
+
+/// A blocked command split into simple commands for a [`SuggestionProvider`].
+#[derive(Debug, Clone)]
+pub struct ParsedCommand<'a> {
+    /// The command as evaluated.
+    pub raw: &'a str,
+    segments: Vec<Vec<&'a str>>,
+}
+
+impl<'a> ParsedCommand<'a> {
+    /// Split `raw` at `;`, `&&`, `||`, `|` and newlines.
+    #[must_use]
+    pub fn parse(raw: &'a str) -> Self {
+        let mut segments = Vec::new();
+        let mut words = Vec::new();
+        for token in &crate::normalize::tokenize_for_normalization(raw) {
+            if token.kind == crate::normalize::NormalizeTokenKind::Separator {
+                if !words.is_empty() {
+                    segments.push(std::mem::take(&mut words));
+                }
+            } else {
+                words.extend(token.text(raw));
+            }
+        }
+        if !words.is_empty() {
+            segments.push(words);
+        }
+        Self { raw, segments }
+    }
+
+    /// Arguments of the first simple command that runs `program` (after
+    /// environment assignments and `sudo`), with shell quoting removed.
+    ///
+    /// Returns `None` if no simple command runs `program` or one of its
+    /// arguments depends on shell expansion (`$BRANCH`), since a suggestion
+    /// built from it could name the wrong target.
+    #[must_use]
+    pub fn args_of(&self, program: &str) -> Option<Vec<String>> {
+        let args = self.segments.iter().find_map(|words| {
+            let (tool, args) = crate::arg_context::program(words)?;
+            (tool == program).then_some(args)
+        })?;
+        args.into_iter().map(crate::arg_context::unquote).collect()
+    }
+}
+
+/// Quote `word` for a POSIX shell if it needs it.
+#[must_use]
+pub fn shell_quote(word: &str) -> std::borrow::Cow<'_, str> {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@+,%^".contains(c));
+    if plain {
+        std::borrow::Cow::Borrowed(word)
+    } else {
+        std::borrow::Cow::Owned(format!("'{}'", word.replace('\'', "'\\''")))
+    }
+}
+
+/// Suggestions a pack's provider builds for `command`, denied by
+/// `pack_id:pattern_name`. Empty when the pattern has no provider.
+#[must_use]
+pub fn tailored_suggestions(pack_id: &str, pattern_name: &str, command: &str) -> Vec<Suggestion> {
+    crate::packs::REGISTRY
+        .suggestion_provider(pack_id, pattern_name)
+        .map(|provider| provider(&ParsedCommand::parse(command)))
+        .unwrap_or_default()
+}
+
+/// Suggestions for `command` denied by `rule_id`: the pack provider's
+/// tailored ones first, then the registered ones.
+#[must_use]
+pub fn suggestions_for_command(rule_id: &str, command: &str) -> Vec<Suggestion> {
+    let mut suggestions = rule_id
+        .split_once(':')
+        .map(|(pack_id, pattern_name)| tailored_suggestions(pack_id, pattern_name, command))
+        .unwrap_or_default();
+    suggestions.extend(get_suggestions(rule_id).into_iter().flatten().cloned());
+    suggestions
+}
+
+// ============================================================================
+// Learned Alternatives (History-Driven)
+// ============================================================================
+
+/// Most learned alternatives surfaced per rule.
+const MAX_LEARNED_ALTERNATIVES: usize = 2;
+
+/// Look up suggestions for a rule denied in `cwd`.
+///
+/// Safer commands that replaced earlier denials of the rule in the same
+/// project (the git repository containing `cwd`, else `cwd` itself) come
+/// first, most used first; see [`HistoryAnalyzer::get_safer_alternatives`]
+/// for what counts. The registered suggestions follow. Without a history
+/// database only the registered suggestions are returned.
+#[must_use]
+pub fn get_suggestions_for_project(
+    rule_id: &str,
+    cwd: &Path,
+    history: Option<&HistoryDb>,
+) -> Vec<Suggestion> {
+    let mut suggestions = history
+        .map(|db| learned_alternatives(db, rule_id, cwd))
+        .unwrap_or_default();
+    suggestions.extend(get_suggestions(rule_id).into_iter().flatten().cloned());
+    suggestions
+}
+
+/// Safer commands learned from history for `rule_id` in the project of `cwd`.
+///
+/// History errors yield no suggestions.
+#[must_use]
+pub fn learned_alternatives(db: &HistoryDb, rule_id: &str, cwd: &Path) -> Vec<Suggestion> {
+    let project = crate::config::find_repo_root(cwd, crate::config::REPO_ROOT_SEARCH_MAX_HOPS)
+        .unwrap_or_else(|| cwd.to_path_buf());
+    HistoryAnalyzer::new(db)
+        .get_safer_alternatives(rule_id, &project.to_string_lossy())
+        .unwrap_or_default()
+        .into_iter()
+        .take(MAX_LEARNED_ALTERNATIVES)
+        .map(|alt| {
+            let times = if alt.count == 1 { "time" } else { "times" };
+            Suggestion::new(
+                SuggestionKind::SaferAlternative,
+                format!("Used instead in this project ({} {times})", alt.count),
+            )
+            .with_command(alt.command)
+        })
+        .collect()
+}
+
 // ============================================================================
 // Explanation Fallback System
 // ============================================================================
@@ -450,6 +600,38 @@ fn register_core_filesystem_suggestions(m: &mut HashMap<&'static str, Vec<Sugges
     m.insert("core.filesystem:rm-rf-general", rm_rf_suggestions.clone());
     m.insert("core.filesystem:rm-r-f-separate", rm_rf_suggestions.clone());
     m.insert("core.filesystem:rm-recursive-force-long", rm_rf_suggestions);
+
+    let find_delete_suggestions = vec![
+        Suggestion::new(
+            SuggestionKind::PreviewFirst,
+            "Run the same find with `-print` instead of `-delete` to review the matches",
+        )
+        .with_command("find <path> -name '<pattern>' -print | head -20"),
+        Suggestion::new(
+            SuggestionKind::SaferAlternative,
+            "Use `-ok rm {} \\;` to confirm each deletion",
+        )
+        .with_command("find <path> -name '<pattern>' -ok rm {} \\;"),
+    ];
+    m.insert(
+        "core.filesystem:find-delete-root-home",
+        find_delete_suggestions.clone(),
+    );
+    m.insert("core.filesystem:find-delete", find_delete_suggestions);
+    m.insert(
+        "core.filesystem:xargs-rm",
+        vec![
+            Suggestion::new(
+                SuggestionKind::PreviewFirst,
+                "Run the producing command alone to review the paths xargs would pass to rm",
+            ),
+            Suggestion::new(
+                SuggestionKind::SaferAlternative,
+                "Use `xargs -p` to confirm each rm invocation",
+            )
+            .with_command("<producer> | xargs -p rm -r"),
+        ],
+    );
 }
 
 /// Register suggestions for heredoc pattern rules.
@@ -1945,4 +2127,43 @@ mod tests {
             "Fallback should be concise: {word_count} words"
         );
     }
+
+    #[test]
+    fn learned_alternatives_come_before_registered_suggestions() {
+        use crate::history::{CommandEntry, Outcome};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path();
+        let db = HistoryDb::open_in_memory().unwrap();
+        let log = |command: &str, outcome, rule: Option<&str>, secs| {
+            let entry = CommandEntry {
+                timestamp: chrono::Utc::now() + chrono::Duration::seconds(secs),
+                agent_type: "claude_code".to_string(),
+                working_dir: cwd.to_string_lossy().into_owned(),
+                command: command.to_string(),
+                outcome,
+                pack_id: rule.map(|_| "core.git".to_string()),
+                pattern_name: rule.map(str::to_string),
+                ..Default::default()
+            };
+            db.log_command(&entry).unwrap();
+        };
+        log("git reset --hard", Outcome::Deny, Some("reset-hard"), 0);
+        log("git stash", Outcome::Allow, None, 1);
+
+        let suggestions = get_suggestions_for_project("core.git:reset-hard", cwd, Some(&db));
+        assert_eq!(suggestions[0].kind, SuggestionKind::SaferAlternative);
+        assert_eq!(suggestions[0].command.as_deref(), Some("git stash"));
+        assert_eq!(suggestions[0].text, "Used instead in this project (1 time)");
+        assert_eq!(
+            &suggestions[1..],
+            get_suggestions("core.git:reset-hard").unwrap()
+        );
+
+        let without_history = get_suggestions_for_project("core.git:reset-hard", cwd, None);
+        assert_eq!(
+            without_history,
+            get_suggestions("core.git:reset-hard").unwrap()
+        );
+    }
 }
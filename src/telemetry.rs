@@ -0,0 +1,348 @@
+//! Opt-in anonymous usage ping.
+//!
+//! Off until `dcg telemetry enable`. Once enabled, the hook counts denials per
+//! pack in a small state file and, at most once every [`SEND_INTERVAL`], posts
+//! a [`Ping`] with the dcg version, OS, architecture, number of enabled packs,
+//! and those per-pack denial counts. Commands, paths, rule names, hostnames,
+//! and identifiers of any kind are never collected; `dcg telemetry status`
+//! prints the exact payload that would be sent next.
+//!
+//! `DO_NOT_TRACK=1` or `DCG_NO_TELEMETRY=1` disables the ping regardless of
+//! the stored setting. Distributions can compile this module out entirely by
+//! building without the `telemetry` feature.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Where pings are posted unless `DCG_TELEMETRY_URL` says otherwise.
+pub const DEFAULT_ENDPOINT: &str = "https://telemetry.dcg.dev/v1/ping";
+
+/// Environment override for the ping endpoint.
+pub const ENV_ENDPOINT: &str = "DCG_TELEMETRY_URL";
+
+/// Minimum time between two pings.
+pub const SEND_INTERVAL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How long a ping may hold up the hook before it is abandoned.
+const SEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Payload format version, bumped whenever a field is added or removed.
+pub const PING_SCHEMA: u32 = 1;
+
+/// Stored telemetry setting and the counts accumulated since the last ping.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryState {
+    pub enabled: bool,
+    /// When telemetry was last enabled (RFC 3339).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_at: Option<String>,
+    /// When a ping was last attempted (RFC 3339), successful or not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_ping: Option<String>,
+    /// Denials per pack since the last successful ping.
+    #[serde(default)]
+    pub denials: BTreeMap<String, u64>,
+}
+
+/// Everything a ping sends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ping {
+    pub schema: u32,
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub pack_count: usize,
+    pub total_denials: u64,
+    pub denials: BTreeMap<String, u64>,
+}
+
+/// Default location of the telemetry state file.
+#[must_use]
+pub fn default_path() -> PathBuf {
+    crate::paths::state_dir().join("telemetry.json")
+}
+
+/// The endpoint pings are posted to.
+#[must_use]
+pub fn endpoint() -> String {
+    std::env::var(ENV_ENDPOINT)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string())
+}
+
+/// The environment variable that turns telemetry off for this process, if any.
+#[must_use]
+pub fn disabled_by_env() -> Option<&'static str> {
+    disabled_by(|key| std::env::var(key).ok())
+}
+
+fn disabled_by(get_env: impl Fn(&str) -> Option<String>) -> Option<&'static str> {
+    ["DO_NOT_TRACK", "DCG_NO_TELEMETRY"]
+        .into_iter()
+        .find(|key| {
+            get_env(key).is_some_and(|value| {
+                let value = value.trim();
+                !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+            })
+        })
+}
+
+impl TelemetryState {
+    /// Load the state at `path`; a missing or unreadable file means disabled.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        crate::storage::write_atomic(path, json.as_bytes())
+    }
+
+    /// Turn telemetry on. Counting starts from zero.
+    pub fn enable(&mut self, now: DateTime<Utc>) {
+        if !self.enabled {
+            self.enabled = true;
+            self.enabled_at = Some(format_time(now));
+            self.denials.clear();
+        }
+    }
+
+    /// Turn telemetry off and drop anything counted so far.
+    pub fn disable(&mut self) {
+        self.enabled = false;
+        self.enabled_at = None;
+        self.denials.clear();
+    }
+
+    /// Count one denial from `pack_id` (`None` for denials outside any pack).
+    pub fn record_denial(&mut self, pack_id: Option<&str>) {
+        *self
+            .denials
+            .entry(pack_id.unwrap_or("other").to_string())
+            .or_default() += 1;
+    }
+
+    /// When the next ping is due: [`SEND_INTERVAL`] after the last attempt,
+    /// or after enabling if none was made yet.
+    #[must_use]
+    pub fn next_ping(&self) -> Option<DateTime<Utc>> {
+        if !self.enabled {
+            return None;
+        }
+        let since = self.last_ping.as_deref().or(self.enabled_at.as_deref());
+        let base = since.and_then(parse_time).unwrap_or(DateTime::UNIX_EPOCH);
+        Some(base + chrono::Duration::from_std(SEND_INTERVAL).unwrap_or_default())
+    }
+
+    /// Whether a ping should be sent at `now`.
+    #[must_use]
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_ping().is_some_and(|due| now >= due)
+    }
+
+    /// The ping the current counts would produce.
+    #[must_use]
+    pub fn ping(&self, pack_count: usize) -> Ping {
+        Ping {
+            schema: PING_SCHEMA,
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            pack_count,
+            total_denials: self.denials.values().sum(),
+            denials: self.denials.clone(),
+        }
+    }
+}
+
+/// Hook entry point after a denial: count it and, when a ping is due, send
+/// one. `pack_count` is only called when a ping is actually built.
+///
+/// Does nothing unless telemetry is enabled and not disabled by the
+/// environment. Failures are silent; a lost ping is retried after the next
+/// interval with the counts kept.
+pub fn on_denial(pack_id: Option<&str>, pack_count: impl FnOnce() -> usize) {
+    if disabled_by_env().is_some() {
+        return;
+    }
+    let path = default_path();
+    let mut state = TelemetryState::load(&path);
+    if !state.enabled {
+        return;
+    }
+    state.record_denial(pack_id);
+
+    let now = Utc::now();
+    if state.is_due(now) {
+        state.last_ping = Some(format_time(now));
+        if send(&endpoint(), &state.ping(pack_count())).is_ok() {
+            state.denials.clear();
+        }
+    }
+    let _ = state.save(&path);
+}
+
+/// POST `ping` to `url` as JSON.
+///
+/// # Errors
+///
+/// Returns an error if the request fails, times out, or is answered with a
+/// non-success status.
+pub fn send(url: &str, ping: &Ping) -> Result<(), String> {
+    let payload = serde_json::to_vec(ping).map_err(|e| e.to_string())?;
+    let url = url.to_string();
+    // The blocking client runs its own runtime; keep it off the caller's.
+    std::thread::spawn(move || {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .user_agent(concat!("dcg/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload)
+            .send()
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("telemetry endpoint answered HTTP {status}"))
+        }
+    })
+    .join()
+    .unwrap_or_else(|_| Err("telemetry request panicked".to_string()))
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_time(value).unwrap()
+    }
+
+    #[test]
+    fn missing_state_is_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = TelemetryState::load(&dir.path().join("telemetry.json"));
+        assert!(!state.enabled);
+        assert_eq!(state.next_ping(), None);
+        assert!(!state.is_due(Utc::now()));
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telemetry.json");
+        let mut state = TelemetryState::default();
+        state.enable(at("2026-01-01T00:00:00Z"));
+        state.record_denial(Some("core.git"));
+        state.record_denial(Some("core.git"));
+        state.record_denial(None);
+        state.save(&path).unwrap();
+
+        let loaded = TelemetryState::load(&path);
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.denials["core.git"], 2);
+        assert_eq!(loaded.denials["other"], 1);
+    }
+
+    #[test]
+    fn first_ping_waits_one_interval_after_enabling() {
+        let mut state = TelemetryState::default();
+        state.enable(at("2026-01-01T00:00:00Z"));
+        assert!(!state.is_due(at("2026-01-07T23:59:59Z")));
+        assert!(state.is_due(at("2026-01-08T00:00:00Z")));
+
+        state.last_ping = Some("2026-01-08T00:00:00Z".to_string());
+        assert!(!state.is_due(at("2026-01-10T00:00:00Z")));
+        assert!(state.is_due(at("2026-01-15T00:00:00Z")));
+    }
+
+    #[test]
+    fn disable_forgets_counts() {
+        let mut state = TelemetryState::default();
+        state.enable(Utc::now());
+        state.record_denial(Some("core.filesystem"));
+        state.disable();
+        assert!(!state.enabled);
+        assert!(state.denials.is_empty());
+        assert_eq!(state.enabled_at, None);
+    }
+
+    #[test]
+    fn ping_carries_only_aggregates() {
+        let mut state = TelemetryState::default();
+        state.enable(Utc::now());
+        state.record_denial(Some("core.git"));
+        state.record_denial(Some("database.postgresql"));
+        state.record_denial(Some("core.git"));
+
+        let ping = state.ping(12);
+        assert_eq!(ping.pack_count, 12);
+        assert_eq!(ping.total_denials, 3);
+        let json = serde_json::to_value(&ping).unwrap();
+        let mut keys: Vec<_> = json.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "arch",
+                "denials",
+                "os",
+                "pack_count",
+                "schema",
+                "total_denials",
+                "version"
+            ]
+        );
+    }
+
+    #[test]
+    fn do_not_track_disables_telemetry() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(name, _)| *name == key)
+                    .map(|(_, value)| (*value).to_string())
+            }
+        };
+        assert_eq!(disabled_by(env(&[])), None);
+        assert_eq!(disabled_by(env(&[("DO_NOT_TRACK", "0")])), None);
+        assert_eq!(disabled_by(env(&[("DO_NOT_TRACK", "")])), None);
+        assert_eq!(
+            disabled_by(env(&[("DO_NOT_TRACK", "1")])),
+            Some("DO_NOT_TRACK")
+        );
+        assert_eq!(
+            disabled_by(env(&[("DCG_NO_TELEMETRY", "true")])),
+            Some("DCG_NO_TELEMETRY")
+        );
+    }
+}
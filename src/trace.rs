@@ -487,6 +487,10 @@ impl ExplainTrace {
                 out.push_str(&format!(
                     "{cyan}Rule ID:{reset}    {yellow}{rule_id}{reset}\n"
                 ));
+                out.push_str(&format!(
+                    "{cyan}Docs:{reset}       {}\n",
+                    crate::sarif::rule_help_uri(rule_id)
+                ));
             }
 
             if let Some(ref pack_id) = info.pack_id {
@@ -598,28 +602,29 @@ impl ExplainTrace {
         // ═══════════════════════════════════════════════════════════════════
         // SUGGESTIONS
         // ═══════════════════════════════════════════════════════════════════
-        if let Some(ref info) = self.match_info {
-            if let Some(rule_id) = info.rule_id.as_deref() {
-                if let Some(suggestions) = crate::suggestions::get_suggestions(rule_id) {
-                    if !suggestions.is_empty() {
-                        out.push_str(&format!("{bold}─── Suggestions ───────────────────────────────────────────────────{reset}\n"));
-
-                        for s in suggestions {
-                            out.push_str(&format!(
-                                "{yellow}• {}{reset}: {}\n",
-                                s.kind.label(),
-                                s.text
-                            ));
-                            if let Some(ref cmd) = s.command {
-                                out.push_str(&format!("  {dim}${reset} {green}{cmd}{reset}\n"));
-                            }
-                            if let Some(ref url) = s.url {
-                                out.push_str(&format!("  {dim}→ {url}{reset}\n"));
-                            }
-                        }
-                        out.push('\n');
+        if let Some(rule_id) = self
+            .match_info
+            .as_ref()
+            .and_then(|info| info.rule_id.as_deref())
+        {
+            let suggestions = crate::suggestions::suggestions_for_command(rule_id, &self.command);
+            if !suggestions.is_empty() {
+                out.push_str(&format!("{bold}─── Suggestions ───────────────────────────────────────────────────{reset}\n"));
+
+                for s in &suggestions {
+                    out.push_str(&format!(
+                        "{yellow}• {}{reset}: {}\n",
+                        s.kind.label(),
+                        s.text
+                    ));
+                    if let Some(ref cmd) = s.command {
+                        out.push_str(&format!("  {dim}${reset} {green}{cmd}{reset}\n"));
+                    }
+                    if let Some(ref url) = s.url {
+                        out.push_str(&format!("  {dim}→ {url}{reset}\n"));
                     }
                 }
+                out.push('\n');
             }
         }
 
@@ -658,9 +663,8 @@ impl ExplainTrace {
             .match_info
             .as_ref()
             .and_then(|m| m.rule_id.as_deref())
-            .and_then(crate::suggestions::get_suggestions)
-            .map(|slist| {
-                slist
+            .map(|rule_id| {
+                crate::suggestions::suggestions_for_command(rule_id, &self.command)
                     .iter()
                     .map(|s| JsonSuggestion {
                         kind: s.kind.label().to_string(),
@@ -1680,6 +1684,7 @@ mod tests {
         assert!(pretty.contains("─── Match"));
         assert!(pretty.contains("Rule ID:"));
         assert!(pretty.contains("core.git:reset-hard"));
+        assert!(pretty.contains("docs/rules/core/git/reset-hard.md"));
         assert!(pretty.contains("Pack:"));
         assert!(pretty.contains("core.git"));
         assert!(pretty.contains("Pattern:"));
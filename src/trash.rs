@@ -0,0 +1,440 @@
+//! A restorable replacement for `rm -rf` (`dcg trash`).
+//!
+//! Agents are blocked from recursive deletes, but cleaning up build output
+//! is often the right move. `dcg trash put <paths>` gives them a deletion
+//! they can actually run: each call moves its targets into a new
+//! `<trash dir>/<timestamp>/` entry alongside a manifest of where they came
+//! from, and `dcg trash restore` puts them back.
+//!
+//! With `[trash] backend = "system"` the targets go to the desktop trash or
+//! recycle bin instead, and are restored from there.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Manifest file inside each trash entry.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Where trashed files go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashBackend {
+    /// dcg's own trash directory, restorable with `dcg trash restore`.
+    #[default]
+    Dcg,
+    /// The desktop trash / recycle bin.
+    System,
+}
+
+/// Default trash directory: `<state dir>/trash`.
+#[must_use]
+pub fn default_dir() -> PathBuf {
+    crate::paths::state_dir().join("trash")
+}
+
+/// One `dcg trash put` call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Entry directory name, sortable by time (e.g. `20261017T101500Z`).
+    pub id: String,
+    pub trashed_at: DateTime<Utc>,
+    /// Working directory of the `put` call.
+    pub cwd: PathBuf,
+    pub items: Vec<TrashedItem>,
+}
+
+/// A file or directory inside a trash entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashedItem {
+    /// Absolute path it was moved from.
+    pub original: PathBuf,
+    /// Name inside the entry directory.
+    pub stored: String,
+    pub is_dir: bool,
+}
+
+/// Move `targets` (relative to `cwd`) into a new entry under `root`.
+///
+/// Every target is checked before anything moves, so a typo in one path
+/// leaves all of them in place.
+///
+/// # Errors
+///
+/// Returns an error if a target does not exist, is the trash directory or
+/// contains it, or cannot be moved.
+pub fn put(root: &Path, cwd: &Path, targets: &[PathBuf]) -> io::Result<TrashEntry> {
+    if targets.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no paths to trash",
+        ));
+    }
+    let root_abs = absolute(root, cwd);
+    let mut items = Vec::with_capacity(targets.len());
+    for (index, target) in targets.iter().enumerate() {
+        let original = absolute(target, cwd);
+        let meta = fs::symlink_metadata(&original)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", target.display())))?;
+        if root_abs.starts_with(&original) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} contains the trash directory", target.display()),
+            ));
+        }
+        let name = original
+            .file_name()
+            .map_or_else(|| "root".into(), |name| name.to_string_lossy());
+        items.push(TrashedItem {
+            stored: format!("{index}-{name}"),
+            original,
+            is_dir: meta.is_dir(),
+        });
+    }
+
+    let (id, dir) = create_entry_dir(root)?;
+    let entry = TrashEntry {
+        id,
+        trashed_at: Utc::now(),
+        cwd: cwd.to_path_buf(),
+        items,
+    };
+    // The manifest goes first so a half-finished put can still be restored.
+    crate::storage::write_json(&dir.join(MANIFEST_FILE), &entry)?;
+    for item in &entry.items {
+        move_path(&item.original, &dir.join(&item.stored))?;
+    }
+    Ok(entry)
+}
+
+/// Move `targets` (relative to `cwd`) to the desktop trash.
+///
+/// # Errors
+///
+/// Returns an error if a target does not exist or the platform trash fails.
+#[cfg(feature = "cli")]
+pub fn put_system(cwd: &Path, targets: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let paths: Vec<PathBuf> = targets.iter().map(|target| absolute(target, cwd)).collect();
+    for (path, target) in paths.iter().zip(targets) {
+        fs::symlink_metadata(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", target.display())))?;
+    }
+    trash::delete_all(&paths).map_err(io::Error::other)?;
+    Ok(paths)
+}
+
+/// Entries under `root`, oldest first. Directories without a readable
+/// manifest are skipped.
+///
+/// # Errors
+///
+/// Returns an error if `root` exists but cannot be read.
+pub fn list(root: &Path) -> io::Result<Vec<TrashEntry>> {
+    let dirs = match fs::read_dir(root) {
+        Ok(dirs) => dirs,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut entries: Vec<TrashEntry> = dirs
+        .filter_map(Result::ok)
+        .filter_map(|dir| read_manifest(&dir.path()))
+        .collect();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(entries)
+}
+
+/// Move the items of entry `id` (the newest entry when `None`) back to
+/// where they came from, then delete the entry.
+///
+/// # Errors
+///
+/// Returns an error if the entry does not exist, or an original path is
+/// occupied and `force` is not set. With `force`, occupied paths are
+/// replaced.
+pub fn restore(root: &Path, id: Option<&str>, force: bool) -> io::Result<TrashEntry> {
+    let entries = list(root)?;
+    let entry = match id {
+        Some(id) => entries.into_iter().find(|entry| entry.id == id),
+        None => entries.into_iter().last(),
+    }
+    .ok_or_else(|| {
+        let what = id.map_or_else(
+            || "trash is empty".to_string(),
+            |id| format!("no trash entry {id}"),
+        );
+        io::Error::new(io::ErrorKind::NotFound, what)
+    })?;
+    let dir = root.join(&entry.id);
+
+    let present: Vec<&TrashedItem> = entry
+        .items
+        .iter()
+        .filter(|item| fs::symlink_metadata(dir.join(&item.stored)).is_ok())
+        .collect();
+    if !force {
+        if let Some(item) = present
+            .iter()
+            .find(|item| fs::symlink_metadata(&item.original).is_ok())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists (use --force to replace it)",
+                    item.original.display()
+                ),
+            ));
+        }
+    }
+    for item in present {
+        if force {
+            remove_path(&item.original)?;
+        }
+        if let Some(parent) = item.original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        move_path(&dir.join(&item.stored), &item.original)?;
+    }
+    fs::remove_dir_all(&dir)?;
+    Ok(entry)
+}
+
+/// Permanently delete entries trashed before `cutoff` (all entries when
+/// `None`). Returns the deleted entries.
+///
+/// # Errors
+///
+/// Returns an error if an entry cannot be removed.
+pub fn empty(root: &Path, cutoff: Option<DateTime<Utc>>) -> io::Result<Vec<TrashEntry>> {
+    let mut removed = Vec::new();
+    for entry in list(root)? {
+        if cutoff.is_none_or(|cutoff| entry.trashed_at < cutoff) {
+            fs::remove_dir_all(root.join(&entry.id))?;
+            removed.push(entry);
+        }
+    }
+    Ok(removed)
+}
+
+/// `dcg trash put` for the targets of a blocked recursive `rm`.
+///
+/// Only offered when every target is a relative path inside the working
+/// directory: trashing `/etc` or `..` is restorable but still breaks things.
+#[must_use]
+pub fn suggestion_for(command: &str) -> Option<String> {
+    let targets = crate::impact::rm_targets(command)?;
+    let inside_cwd = |target: &String| {
+        let path = Path::new(target);
+        path.is_relative()
+            && path
+                .components()
+                .all(|part| matches!(part, std::path::Component::Normal(_)))
+    };
+    targets
+        .iter()
+        .all(inside_cwd)
+        .then(|| put_command(&targets))
+}
+
+/// The `dcg trash put` command for `targets`.
+#[must_use]
+pub fn put_command(targets: &[String]) -> String {
+    std::iter::once("dcg trash put".to_string())
+        .chain(targets.iter().map(|target| crate::preview::quote(target)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn read_manifest(dir: &Path) -> Option<TrashEntry> {
+    let bytes = fs::read(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// A new, unique entry directory named after the current time.
+fn create_entry_dir(root: &Path) -> io::Result<(String, PathBuf)> {
+    fs::create_dir_all(root)?;
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    for attempt in 0u32.. {
+        let id = if attempt == 0 {
+            stamp.clone()
+        } else {
+            format!("{stamp}-{attempt}")
+        };
+        let dir = root.join(&id);
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok((id, dir)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!("u32 attempts exhausted")
+}
+
+fn absolute(path: &Path, cwd: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    }
+}
+
+/// Rename `from` to `to`, copying and deleting when they are on different
+/// filesystems.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            copy_path(from, to)?;
+            remove_path(from)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn copy_path(from: &Path, to: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, meta.permissions())
+    } else if meta.file_type().is_symlink() {
+        copy_symlink(from, to)
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        fs::create_dir_all(work.join("build/out")).unwrap();
+        fs::write(work.join("build/out/app.o"), "obj").unwrap();
+        fs::write(work.join("notes.txt"), "keep me").unwrap();
+        let root = dir.path().join("trash");
+        (dir, work, root)
+    }
+
+    #[test]
+    fn put_and_restore_round_trip() {
+        let (_dir, work, root) = setup();
+        let entry = put(
+            &root,
+            &work,
+            &[PathBuf::from("build"), PathBuf::from("notes.txt")],
+        )
+        .unwrap();
+        assert!(!work.join("build").exists());
+        assert!(!work.join("notes.txt").exists());
+        assert_eq!(entry.items.len(), 2);
+        assert!(entry.items[0].is_dir);
+        assert_eq!(list(&root).unwrap(), vec![entry.clone()]);
+
+        let restored = restore(&root, None, false).unwrap();
+        assert_eq!(restored.id, entry.id);
+        assert_eq!(
+            fs::read_to_string(work.join("build/out/app.o")).unwrap(),
+            "obj"
+        );
+        assert_eq!(
+            fs::read_to_string(work.join("notes.txt")).unwrap(),
+            "keep me"
+        );
+        assert!(list(&root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn put_moves_nothing_when_a_target_is_missing() {
+        let (_dir, work, root) = setup();
+        let err = put(
+            &root,
+            &work,
+            &[PathBuf::from("build"), PathBuf::from("nope")],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(work.join("build").exists());
+        assert!(list(&root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn put_refuses_to_trash_the_trash() {
+        let (dir, work, root) = setup();
+        let err = put(&root, &work, &[dir.path().to_path_buf()]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn restore_does_not_overwrite_without_force() {
+        let (_dir, work, root) = setup();
+        let entry = put(&root, &work, &[PathBuf::from("notes.txt")]).unwrap();
+        fs::write(work.join("notes.txt"), "new").unwrap();
+
+        let err = restore(&root, Some(&entry.id), false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(work.join("notes.txt")).unwrap(), "new");
+
+        restore(&root, Some(&entry.id), true).unwrap();
+        assert_eq!(
+            fs::read_to_string(work.join("notes.txt")).unwrap(),
+            "keep me"
+        );
+    }
+
+    #[test]
+    fn empty_respects_cutoff() {
+        let (_dir, work, root) = setup();
+        put(&root, &work, &[PathBuf::from("notes.txt")]).unwrap();
+        let old = Utc::now() - chrono::Duration::days(1);
+        assert!(empty(&root, Some(old)).unwrap().is_empty());
+        assert_eq!(empty(&root, None).unwrap().len(), 1);
+        assert!(list(&root).unwrap().is_empty());
+    }
+
+    #[test]
+    fn suggestion_only_for_paths_inside_cwd() {
+        assert_eq!(
+            suggestion_for("rm -rf build node_modules/.cache").as_deref(),
+            Some("dcg trash put build node_modules/.cache")
+        );
+        assert_eq!(suggestion_for("rm -rf /"), None);
+        assert_eq!(suggestion_for("rm -rf /etc"), None);
+        assert_eq!(suggestion_for("rm -rf ../sibling"), None);
+        assert_eq!(suggestion_for("rm -rf ."), None);
+        assert_eq!(suggestion_for("rm notes.txt"), None);
+    }
+
+    #[test]
+    fn put_command_quotes_targets() {
+        assert_eq!(
+            put_command(&["build".to_string(), "my dir".to_string()]),
+            "dcg trash put build 'my dir'"
+        );
+    }
+}
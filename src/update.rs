@@ -407,13 +407,13 @@ pub fn format_backup_list(backups: &[BackupEntry], use_color: bool) -> String {
 }
 
 /// Get the path to the version check cache file.
-fn cache_path() -> Option<PathBuf> {
-    dirs::cache_dir().map(|d| d.join("dcg").join("version_check.json"))
+pub(crate) fn cache_path() -> PathBuf {
+    crate::paths::cache_dir().join("version_check.json")
 }
 
 /// Read cached version check if it exists and is still valid.
 fn read_cache() -> Option<VersionCheckResult> {
-    let path = cache_path()?;
+    let path = cache_path();
     let content = fs::read_to_string(&path).ok()?;
     let cached: CachedCheck = serde_json::from_str(&content).ok()?;
 
@@ -454,16 +454,7 @@ pub fn spawn_update_check_if_needed() {
 
 /// Write version check result to cache.
 fn write_cache(result: &VersionCheckResult) -> Result<(), VersionCheckError> {
-    let path = cache_path().ok_or_else(|| {
-        VersionCheckError::CacheError("Could not determine cache directory".to_string())
-    })?;
-
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| {
-            VersionCheckError::CacheError(format!("Failed to create cache directory: {e}"))
-        })?;
-    }
+    let path = cache_path();
 
     let now = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -478,7 +469,7 @@ fn write_cache(result: &VersionCheckResult) -> Result<(), VersionCheckError> {
     let content = serde_json::to_string_pretty(&cached)
         .map_err(|e| VersionCheckError::CacheError(format!("Failed to serialize cache: {e}")))?;
 
-    fs::write(&path, content)
+    crate::storage::write_atomic(&path, content.as_bytes())
         .map_err(|e| VersionCheckError::CacheError(format!("Failed to write cache: {e}")))?;
 
     Ok(())
@@ -636,12 +627,10 @@ fn truncate_release_notes(body: &str, max_chars: usize) -> String {
 ///
 /// Returns `VersionCheckError::CacheError` if the cache file exists but cannot be removed.
 pub fn clear_cache() -> Result<(), VersionCheckError> {
-    if let Some(path) = cache_path() {
-        if path.exists() {
-            fs::remove_file(&path).map_err(|e| {
-                VersionCheckError::CacheError(format!("Failed to remove cache: {e}"))
-            })?;
-        }
+    let path = cache_path();
+    if path.exists() {
+        fs::remove_file(&path)
+            .map_err(|e| VersionCheckError::CacheError(format!("Failed to remove cache: {e}")))?;
     }
     Ok(())
 }
@@ -0,0 +1,38 @@
+//! Session memory for `warn_once` rules.
+//!
+//! A rule in `warn_once` mode warns like `warn` the first time a command
+//! matches it in an agent session; identical repeats later in that session
+//! pass silently, so rules a team is still tuning stay visible without
+//! repeating the same notice on every retry. Which (rule, command) pairs have
+//! already warned is kept in a [`SessionMemoryStore`], per
+//! [`current_session`].
+//!
+//! Recording is best-effort: when the memory cannot be read or written, the
+//! rule simply warns again.
+
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+use crate::session_memory::{SessionMemoryStore, current_session};
+
+/// Environment override for the warn-once memory path.
+pub const ENV_WARN_ONCE_PATH: &str = "DCG_WARN_ONCE_PATH";
+
+const WARN_ONCE_FILE: &str = "warn_once.json";
+
+/// Resolve the warn-once memory path (env override or the dcg state directory).
+#[must_use]
+pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+    SessionMemoryStore::default_path(ENV_WARN_ONCE_PATH, WARN_ONCE_FILE, cwd)
+}
+
+/// Whether a `warn_once` match should warn, recording it in the default
+/// store under the current session. Fails open to `true` (warn) when the
+/// memory cannot be used.
+#[must_use]
+pub fn should_warn(rule_id: &str, command: &str, cwd: Option<&Path>) -> bool {
+    let store = SessionMemoryStore::new(default_path(cwd));
+    store
+        .record(current_session().as_deref(), rule_id, command, Utc::now())
+        .map_or(true, |count| count == 1)
+}
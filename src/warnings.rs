@@ -0,0 +1,377 @@
+//! Queue of warn-mode events for `dcg warnings`.
+//!
+//! A rule in `warn` mode lets the command run and prints a notice that often
+//! scrolls past unread. The hook also records each warning here (see
+//! `[policy] warn_queue`), so a human can later list what agents did under
+//! warn rules, acknowledge what is fine, and convert recurring commands into
+//! `[overrides] block` rules or allowlist entries in bulk.
+//!
+//! Recording is best-effort: a missing, locked, or corrupt queue never
+//! affects the hook decision.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::resolve_config_path_value;
+use crate::storage::{self, StateLock};
+use crate::suggest::{GeneratedPattern, generate_pattern_from_cluster};
+
+/// Environment override for the warning queue path.
+pub const ENV_WARNINGS_PATH: &str = "DCG_WARNINGS_PATH";
+
+const WARNINGS_FILE: &str = "warnings.json";
+const SCHEMA_VERSION: u32 = 1;
+
+/// Oldest entries are dropped beyond this many distinct commands.
+pub const MAX_QUEUED_WARNINGS: usize = 1000;
+
+/// One warned command (repeats of the same command and rule are counted).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WarningEvent {
+    /// Stable ID for `dcg warnings ack` / `convert`.
+    pub id: u64,
+    /// The command as the log file records it (see `[privacy] log_mode`).
+    pub command: String,
+    /// Rule that warned (`pack:pattern`).
+    pub rule_id: String,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    pub count: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// Contents of the warning queue file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WarningQueue {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub events: Vec<WarningEvent>,
+}
+
+/// Which queued warnings a `dcg warnings` action applies to.
+#[derive(Debug, Clone, Default)]
+pub struct WarningSelection {
+    /// Event IDs (empty = any).
+    pub ids: Vec<u64>,
+    /// Only events from these rules (empty = any).
+    pub rules: Vec<String>,
+    /// Only rules warned at least this many times in total.
+    pub min_count: u64,
+}
+
+impl WarningSelection {
+    /// `true` if nothing narrows the selection.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty() && self.rules.is_empty() && self.min_count <= 1
+    }
+
+    fn selects(&self, event: &WarningEvent) -> bool {
+        (self.ids.is_empty() || self.ids.contains(&event.id))
+            && (self.rules.is_empty() || self.rules.contains(&event.rule_id))
+    }
+}
+
+impl WarningQueue {
+    /// Selected events grouped by rule, most frequent rule first. Groups
+    /// below `selection.min_count` are left out.
+    #[must_use]
+    pub fn groups(&self, selection: &WarningSelection) -> Vec<WarningGroup> {
+        let mut groups: Vec<WarningGroup> = Vec::new();
+        for event in self.events.iter().filter(|event| selection.selects(event)) {
+            match groups
+                .iter_mut()
+                .find(|group| group.rule_id == event.rule_id)
+            {
+                Some(group) => {
+                    group.count += event.count;
+                    group.events.push(event.clone());
+                }
+                None => groups.push(WarningGroup {
+                    rule_id: event.rule_id.clone(),
+                    reason: event.reason.clone(),
+                    count: event.count,
+                    events: vec![event.clone()],
+                }),
+            }
+        }
+        groups.retain(|group| group.count >= selection.min_count);
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then(a.rule_id.cmp(&b.rule_id)));
+        groups
+    }
+}
+
+/// Queued warnings from the same rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct WarningGroup {
+    pub rule_id: String,
+    pub reason: String,
+    /// Total warnings across the group's events.
+    pub count: u64,
+    /// Distinct commands, oldest first.
+    pub events: Vec<WarningEvent>,
+}
+
+impl WarningGroup {
+    /// IDs of the group's events.
+    #[must_use]
+    pub fn ids(&self) -> Vec<u64> {
+        self.events.iter().map(|event| event.id).collect()
+    }
+
+    /// Pattern matching every command in the group.
+    #[must_use]
+    pub fn pattern(&self) -> GeneratedPattern {
+        let commands: Vec<String> = self
+            .events
+            .iter()
+            .map(|event| event.command.clone())
+            .collect();
+        generate_pattern_from_cluster(&commands)
+    }
+}
+
+/// JSON-backed warning queue store.
+#[derive(Debug, Clone)]
+pub struct WarningQueueStore {
+    path: PathBuf,
+}
+
+impl WarningQueueStore {
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Resolve the default path (env override or the dcg state directory).
+    #[must_use]
+    pub fn default_path(cwd: Option<&Path>) -> PathBuf {
+        if let Ok(value) = std::env::var(ENV_WARNINGS_PATH) {
+            if let Some(path) = resolve_config_path_value(&value, cwd) {
+                return path;
+            }
+        }
+
+        crate::paths::state_file(WARNINGS_FILE)
+    }
+
+    /// Load the queue. A missing or unreadable file yields an empty queue.
+    #[must_use]
+    pub fn load(&self) -> WarningQueue {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record one warning for `command` under `rule_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the queue.
+    pub fn record(
+        &self,
+        command: &str,
+        rule_id: &str,
+        reason: &str,
+        severity: Option<&str>,
+        working_dir: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> io::Result<()> {
+        self.update(|queue| {
+            let stamp = storage::format_timestamp(now);
+            if let Some(event) = queue
+                .events
+                .iter_mut()
+                .find(|e| e.command == command && e.rule_id == rule_id)
+            {
+                event.count += 1;
+                event.last_seen = stamp;
+                return;
+            }
+            queue.next_id += 1;
+            queue.events.push(WarningEvent {
+                id: queue.next_id,
+                command: command.to_string(),
+                rule_id: rule_id.to_string(),
+                reason: reason.to_string(),
+                severity: severity.map(str::to_string),
+                working_dir: working_dir.map(str::to_string),
+                count: 1,
+                first_seen: stamp.clone(),
+                last_seen: stamp,
+            });
+            if queue.events.len() > MAX_QUEUED_WARNINGS {
+                let excess = queue.events.len() - MAX_QUEUED_WARNINGS;
+                queue.events.drain(..excess);
+            }
+        })
+    }
+
+    /// Remove the events with the given IDs; returns how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O errors encountered while opening, locking, or writing the queue.
+    pub fn remove(&self, ids: &[u64]) -> io::Result<usize> {
+        let mut removed = 0;
+        self.update(|queue| {
+            let before = queue.events.len();
+            queue.events.retain(|event| !ids.contains(&event.id));
+            removed = before - queue.events.len();
+        })?;
+        Ok(removed)
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut WarningQueue)) -> io::Result<()> {
+        let _lock = StateLock::acquire(&self.path)?;
+        let mut queue: WarningQueue = storage::read_json(&self.path)?.unwrap_or_default();
+        queue.schema_version = SCHEMA_VERSION;
+        apply(&mut queue);
+
+        storage::write_json(&self.path, &queue)
+    }
+}
+
+/// Record a warning in the default store, ignoring failures (hook paths must
+/// not fail on bookkeeping).
+pub fn record_warning(
+    command: &str,
+    rule_id: &str,
+    reason: &str,
+    severity: Option<&str>,
+    cwd: Option<&Path>,
+) {
+    let store = WarningQueueStore::new(WarningQueueStore::default_path(cwd));
+    let working_dir = cwd.map(|path| path.to_string_lossy());
+    let _ = store.record(
+        command,
+        rule_id,
+        reason,
+        severity,
+        working_dir.as_deref(),
+        Utc::now(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn store_with_events() -> (tempfile::TempDir, WarningQueueStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = WarningQueueStore::new(dir.path().join("warnings.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        let stash = "core.git:stash-drop";
+        for command in [
+            "git stash drop stash@{0}",
+            "git stash drop stash@{1}",
+            "git stash drop stash@{1}",
+        ] {
+            store
+                .record(command, stash, "drops", Some("medium"), Some("/w"), now)
+                .unwrap();
+        }
+        store
+            .record(
+                "docker system prune",
+                "containers.docker:system-prune",
+                "prunes",
+                None,
+                None,
+                at("2030-01-02T00:00:00Z"),
+            )
+            .unwrap();
+        (dir, store)
+    }
+
+    #[test]
+    fn record_assigns_ids_and_counts_repeats() {
+        let (_dir, store) = store_with_events();
+        let queue = store.load();
+        assert_eq!(queue.schema_version, SCHEMA_VERSION);
+        let ids: Vec<u64> = queue.events.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(queue.events[1].count, 2);
+        assert_eq!(queue.events[2].first_seen, "2030-01-02T00:00:00Z");
+
+        assert_eq!(store.remove(&[1, 3, 9]).unwrap(), 2);
+        let queue = store.load();
+        assert_eq!(queue.events.len(), 1);
+        store
+            .record("rm -rf x", "p:r", "r", None, None, Utc::now())
+            .unwrap();
+        assert_eq!(store.load().events[1].id, 4, "IDs are never reused");
+    }
+
+    #[test]
+    fn groups_filter_by_selection_and_min_count() {
+        let (_dir, store) = store_with_events();
+        let queue = store.load();
+
+        let groups = queue.groups(&WarningSelection::default());
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].rule_id, "core.git:stash-drop");
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].ids(), vec![1, 2]);
+        let pattern = groups[0].pattern();
+        assert!(pattern.matches_all);
+        let regex = regex::Regex::new(&pattern.regex).unwrap();
+        assert!(regex.is_match("git stash drop stash@{0}"));
+
+        let recurring = WarningSelection {
+            min_count: 2,
+            ..WarningSelection::default()
+        };
+        assert_eq!(queue.groups(&recurring).len(), 1);
+
+        let by_id = WarningSelection {
+            ids: vec![2, 3],
+            ..WarningSelection::default()
+        };
+        let groups = queue.groups(&by_id);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].ids(), vec![2]);
+
+        let by_rule = WarningSelection {
+            rules: vec!["containers.docker:system-prune".to_string()],
+            ..WarningSelection::default()
+        };
+        assert_eq!(queue.groups(&by_rule)[0].ids(), vec![3]);
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = WarningQueueStore::new(dir.path().join("warnings.json"));
+        let now = at("2030-01-01T00:00:00Z");
+        for i in 0..=MAX_QUEUED_WARNINGS {
+            store
+                .record(&format!("cmd {i}"), "p:r", "r", None, None, now)
+                .unwrap();
+        }
+        let queue = store.load();
+        assert_eq!(queue.events.len(), MAX_QUEUED_WARNINGS);
+        assert_eq!(queue.events[0].command, "cmd 1");
+    }
+}
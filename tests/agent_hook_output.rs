@@ -364,3 +364,35 @@ fn test_hook_output_remediation_safe_alternative() {
         }
     }
 }
+
+#[test]
+fn test_hook_output_lists_every_match() {
+    let (stdout, _stderr, _) = run_hook_mode("rm -rf node_modules && git reset --hard");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout).expect("hook output should be valid JSON");
+
+    let hook_output = &json["hookSpecificOutput"];
+    assert_eq!(hook_output["permissionDecision"], "deny");
+
+    let matches = hook_output["matches"]
+        .as_array()
+        .expect("matches array should be present for denied commands");
+    assert_eq!(
+        matches.len(),
+        2,
+        "both rules should be reported: {matches:?}"
+    );
+
+    // The primary (highest-severity) match comes first and mirrors ruleId.
+    assert_eq!(matches[0]["ruleId"], hook_output["ruleId"]);
+    assert_eq!(matches[0]["severity"], "critical");
+    assert_eq!(matches[1]["packId"], "core.filesystem");
+    assert!(matches[1]["matchedSpan"].is_array());
+
+    let reason = hook_output["permissionDecisionReason"].as_str().unwrap();
+    assert!(
+        reason.contains("Also matched: core.filesystem:"),
+        "reason should list secondary matches: {reason}"
+    );
+}
@@ -159,6 +159,52 @@ mod explain_tests {
         assert!(json["steps"].is_array(), "should have steps array");
     }
 
+    #[test]
+    fn explain_rule_describes_rule_from_pack_metadata() {
+        let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/corpus");
+        let output = run_dcg(&[
+            "explain",
+            "--rule",
+            "core.git:reset-hard",
+            "--corpus",
+            corpus,
+            "--format",
+            "json",
+        ]);
+        assert!(output.status.success(), "explain --rule should succeed");
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("explain --rule JSON");
+        assert_eq!(json["rule_id"], "core.git:reset-hard");
+        assert_eq!(json["severity"], "critical");
+        assert_eq!(json["pattern"], "git [<args>] reset --hard");
+        assert!(
+            json["examples"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|example| example == "git reset --hard"),
+            "examples come from the corpus"
+        );
+        assert!(!json["safe_alternatives"].as_array().unwrap().is_empty());
+        assert!(
+            json["allowlist_stanza"]
+                .as_str()
+                .unwrap()
+                .contains("rule = \"core.git:reset-hard\"")
+        );
+
+        let pretty = run_dcg(&["explain", "--rule", "core.git:reset-hard"]);
+        let stdout = String::from_utf8_lossy(&pretty.stdout);
+        assert!(
+            stdout.contains("[[allow]]"),
+            "pretty output shows the stanza"
+        );
+
+        let unknown = run_dcg(&["explain", "--rule", "core.git:no-such-rule"]);
+        assert!(!unknown.status.success(), "unknown rules fail");
+    }
+
     #[test]
     fn explain_json_includes_suggestions_for_blocked_commands() {
         // Use git command since core.git is always enabled
@@ -630,6 +676,31 @@ mod allow_once_flow_tests {
         assert_is_allowed(&result3);
     }
 
+    #[test]
+    fn allow_once_rule_grant_allows_other_commands_from_same_rule() {
+        let env = FlowTestEnv::new();
+
+        let result1 = env.run_hook("git reset --hard");
+        let stdout1 = assert_is_denial(&result1);
+        let code = extract_code_from_denial(&stdout1).expect("should emit code");
+
+        let allow_output = env.run_cli(&["allow-once", &code, "--yes", "--rule"]);
+        assert!(
+            allow_output.status.success(),
+            "allow-once --rule should succeed\nstdout: {}\nstderr: {}",
+            String::from_utf8_lossy(&allow_output.stdout),
+            String::from_utf8_lossy(&allow_output.stderr)
+        );
+
+        // A different command blocked by the same rule is now allowed.
+        let result2 = env.run_hook("git reset --hard HEAD~1");
+        assert_is_allowed(&result2);
+
+        // Commands blocked by other rules are still denied.
+        let result3 = env.run_hook("git clean -fd");
+        assert_is_denial(&result3);
+    }
+
     #[test]
     fn block_emits_full_hash_in_hook_output() {
         let env = FlowTestEnv::new();
@@ -1716,6 +1787,81 @@ block = [
         assert_hook_denies_output(&result, "explicit config block");
     }
 
+    #[test]
+    fn hook_mode_collapses_repeated_denials_on_stderr() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let deny = || {
+            let result = run_dcg_hook_in_dir_with_env(temp.path(), "git reset --hard", &[]);
+            let json: serde_json::Value =
+                serde_json::from_str(result.stdout_str().trim()).expect("hook JSON");
+            assert_eq!(json["hookSpecificOutput"]["permissionDecision"], "deny");
+            assert!(json["hookSpecificOutput"]["allowOnceCode"].is_string());
+            result.stderr_str()
+        };
+
+        let first = deny();
+        assert!(!first.contains("previously blocked"), "stderr:\n{first}");
+        let second = deny();
+        assert!(
+            second.contains("previously blocked (x2)") && second.contains("core.git:reset-hard"),
+            "stderr:\n{second}"
+        );
+        assert_eq!(second.trim().lines().count(), 1, "stderr:\n{second}");
+    }
+
+    #[test]
+    fn hook_mode_ignores_provenance_env() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let config_path = temp.path().join("dcg.toml");
+        std::fs::write(
+            &config_path,
+            "[policy.provenance.human]\nrules = { \"core.git:push-force-long\" = \"warn\" }\n",
+        )
+        .expect("write dcg config");
+
+        let result = run_dcg_hook_in_dir_with_env(
+            temp.path(),
+            "git push --force origin main",
+            &[
+                ("DCG_CONFIG", config_path.as_os_str()),
+                ("DCG_PROVENANCE", std::ffi::OsStr::new("human")),
+            ],
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(result.stdout_str().trim()).expect("hook JSON");
+        assert_eq!(json["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert_eq!(
+            json["hookSpecificOutput"]["ruleId"],
+            "core.git:push-force-long"
+        );
+    }
+
+    #[test]
+    fn hook_mode_severity_ask_prompts_unless_unattended() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let config_path = temp.path().join("dcg.toml");
+        std::fs::write(&config_path, "[policy.severity]\nhigh = \"ask\"\n")
+            .expect("write dcg config");
+
+        let decision = |extra_env: &[(&str, &std::ffi::OsStr)]| {
+            let mut env = vec![("DCG_CONFIG", config_path.as_os_str())];
+            env.extend_from_slice(extra_env);
+            let result =
+                run_dcg_hook_in_dir_with_env(temp.path(), "git checkout -- file.txt", &env);
+            let json: serde_json::Value =
+                serde_json::from_str(result.stdout_str().trim()).expect("hook JSON");
+            json["hookSpecificOutput"].clone()
+        };
+
+        let output = decision(&[]);
+        assert_eq!(output["permissionDecision"], "ask");
+        assert_eq!(output["ruleId"], "core.git:checkout-discard");
+        assert!(output.get("allowOnceCode").is_none());
+
+        let unattended = decision(&[("DCG_NON_INTERACTIVE", std::ffi::OsStr::new("1"))]);
+        assert_eq!(unattended["permissionDecision"], "deny");
+    }
+
     #[test]
     fn hook_mode_allow_once_can_override_config_block_with_force_flag() {
         let temp = tempfile::tempdir().expect("tempdir");
@@ -1778,6 +1924,89 @@ block = [
         );
     }
 
+    #[test]
+    fn hook_mode_invocation_env_overrides() {
+        let packs = std::ffi::OsStr::new("core.git,containers.docker");
+        let command = "docker system prune -af --volumes";
+        let result = run_dcg_hook_with_env(command, &[("DCG_PACKS", packs)]);
+        assert!(result.stdout_str().contains("deny"));
+
+        // DCG_DISABLE_PACKS excludes packs for this invocation only.
+        let disable = std::ffi::OsStr::new("containers.*");
+        let result = run_dcg_hook_with_env(
+            command,
+            &[("DCG_PACKS", packs), ("DCG_DISABLE_PACKS", disable)],
+        );
+        assert!(
+            result.stdout_str().trim().is_empty(),
+            "expected allow with DCG_DISABLE_PACKS\nstdout:\n{}\nstderr:\n{}",
+            result.stdout_str(),
+            result.stderr_str()
+        );
+
+        // DCG_PROFILE applies that profile's pack adjustments.
+        let temp = tempfile::tempdir().expect("tempdir");
+        let config = temp.path().join("config.toml");
+        std::fs::write(
+            &config,
+            "[agents.ci]\ndisabled_packs = [\"containers.docker\"]\n",
+        )
+        .expect("write config");
+        let env = [
+            ("DCG_PACKS", packs),
+            ("DCG_CONFIG", config.as_os_str()),
+            ("DCG_PROFILE", std::ffi::OsStr::new("ci")),
+        ];
+        let result = run_dcg_hook_with_env(command, &env);
+        assert!(
+            result.stdout_str().trim().is_empty(),
+            "expected allow with DCG_PROFILE=ci\nstdout:\n{}\nstderr:\n{}",
+            result.stdout_str(),
+            result.stderr_str()
+        );
+
+        // DCG_TRACE prints the decision trace to stderr.
+        let result = run_dcg_hook_with_env(
+            "git reset --hard",
+            &[("DCG_TRACE", std::ffi::OsStr::new("1"))],
+        );
+        assert!(result.stdout_str().contains("deny"));
+        assert!(
+            result.stderr_str().contains("\"schema_version\""),
+            "expected trace on stderr\nstderr:\n{}",
+            result.stderr_str()
+        );
+    }
+
+    #[test]
+    fn hook_mode_anomalous_input_is_evaluated_and_reported() {
+        // NULs no longer hide the flag from the matcher.
+        let result = run_dcg_hook("git reset\0--hard");
+        assert!(
+            result.stdout_str().contains("deny"),
+            "NUL-separated command should be denied\nstdout:\n{}\nstderr:\n{}",
+            result.stdout_str(),
+            result.stderr_str()
+        );
+        assert!(result.stderr_str().contains("DCG-3012"));
+
+        // Oversized commands are evaluated by their prefix instead of allowed.
+        let oversized = format!("git reset --hard # {}", "x ".repeat(40_000));
+        let result = run_dcg_hook(&oversized);
+        assert!(
+            result.stdout_str().contains("deny"),
+            "oversized command should be denied by its prefix\nstderr:\n{}",
+            result.stderr_str()
+        );
+        assert!(result.stderr_str().contains("input anomaly"));
+
+        // A harmless command with a huge token is allowed, but not silently.
+        let blob = "A".repeat(10_000);
+        let result = run_dcg_hook(&format!("echo {blob}"));
+        assert!(result.stdout_str().trim().is_empty());
+        assert!(result.stderr_str().contains("DCG-3012"));
+    }
+
     #[test]
     fn hook_mode_path_normalization_and_wrappers_matrix() {
         // Deny cases: absolute paths, quoted command words, wrappers, env assignments.
@@ -1896,6 +2125,46 @@ mod simulate_tests {
     // Basic functionality tests
     // -------------------------------------------------------------------------
 
+    #[test]
+    fn coverage_reports_fired_rules_and_untouched_commands() {
+        let content = concat!(
+            "git reset --hard\n",
+            "git reset --hard\n",
+            "git status\n",
+            "{\"tool_name\":\"Bash\",\"tool_input\":{\"command\":\"ls -la\"}}\n",
+        );
+        let file = create_temp_log_file(content);
+
+        let output = run_dcg(&[
+            "coverage",
+            file.path().to_str().unwrap(),
+            "--format",
+            "json",
+        ]);
+        assert!(
+            output.status.success(),
+            "coverage should succeed\nstderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("coverage JSON");
+        assert_eq!(json["total_commands"], 4);
+        assert_eq!(json["unique_commands"], 3);
+        assert_eq!(json["matched_commands"], 2);
+        assert_eq!(json["untouched"][0]["command"], "ls -la");
+
+        let git = json["packs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|pack| pack["pack_id"] == "core.git")
+            .expect("core.git in report");
+        assert_eq!(git["touched"], 3);
+        assert_eq!(git["fired"][0]["rule_id"], "core.git:reset-hard");
+        assert_eq!(git["fired"][0]["hits"], 2);
+    }
+
     #[test]
     fn simulate_plain_commands_file() {
         let content = "git status\necho hello\nls -la\n";
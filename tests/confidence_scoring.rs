@@ -27,7 +27,12 @@ fn mock_deny_result(
             matched_text_preview: Some("rm -rf".to_string()),
             explanation: None,
             suggestions: &[],
+            line_number: None,
         }),
+        other_matches: Vec::new(),
+        aggregation: None,
+        near_misses: Vec::new(),
+        allowlist_entry: None,
         allowlist_override: None,
         effective_mode: Some(DecisionMode::Deny),
         skipped_due_to_budget: false,
@@ -340,3 +345,40 @@ fn test_non_deny_mode_passes_through() {
     assert!(confidence_result.score.is_none());
     assert!(!confidence_result.downgraded);
 }
+
+#[test]
+fn test_policy_min_confidence_to_deny_gates_low_confidence_matches() {
+    use destructive_command_guard::config::Config;
+    use destructive_command_guard::evaluator::resolve_decision;
+    use destructive_command_guard::provenance::Provenance;
+
+    let command = "git commit -m 'Fix rm -rf detection'";
+    let result = mock_deny_result(
+        Severity::High,
+        MatchSource::Pack,
+        Some(MatchSpan { start: 18, end: 24 }),
+    );
+
+    // Unset: confidence scoring stays off and the match is denied.
+    let mut config = Config::default();
+    let decision = resolve_decision(&config, command, &result, Provenance::Agent).unwrap();
+    assert_eq!(decision.mode, DecisionMode::Deny);
+    assert!(decision.confidence.is_none());
+
+    // Set: the quoted match scores low and falls back to warn.
+    config.policy.min_confidence_to_deny = Some(0.5);
+    let decision = resolve_decision(&config, command, &result, Provenance::Agent).unwrap();
+    assert_eq!(decision.mode, DecisionMode::Warn);
+    let gate = decision.confidence.expect("confidence reported");
+    assert!(gate.downgraded);
+    assert!(gate.confidence < 0.5);
+    assert!((gate.threshold - 0.5).abs() < f32::EPSILON);
+
+    // A zero threshold still reports the score but never downgrades.
+    config.policy.min_confidence_to_deny = Some(0.0);
+    let decision = resolve_decision(&config, command, &result, Provenance::Agent).unwrap();
+    assert_eq!(decision.mode, DecisionMode::Deny);
+    let gate = decision.confidence.expect("confidence reported");
+    assert!(!gate.downgraded);
+    assert!(gate.threshold.abs() < f32::EPSILON);
+}
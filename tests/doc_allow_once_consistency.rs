@@ -34,6 +34,9 @@ fn allow_once_doc_contains_key_commands() -> std::io::Result<()> {
         "--dry-run",
         "--pick",
         "--hash",
+        "--exact",
+        "--rule",
+        "--session",
         "allow-once list",
         "allow-once revoke",
         "allow-once clear",
@@ -17,6 +17,7 @@ mod tests {
             keywords: &["lazy_trigger"],
             safe_patterns: vec![],
             destructive_patterns: vec![],
+            match_raw: false,
             keyword_matcher: None,
             safe_regex_set: None,
             safe_regex_set_is_complete: false,
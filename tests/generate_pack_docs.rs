@@ -110,6 +110,7 @@ fn generate_category_doc(category: &str, packs: &[&Pack]) -> String {
         "dns" => "DNS Packs",
         "email" => "Email Packs",
         "featureflags" => "Feature Flags Packs",
+        "iam" => "Identity and Access Packs",
         "loadbalancer" => "Load Balancer Packs",
         "monitoring" => "Monitoring Packs",
         "payment" => "Payment Packs",
@@ -124,6 +125,11 @@ fn generate_category_doc(category: &str, packs: &[&Pack]) -> String {
         "apigateway" => "API Gateway Packs",
         "infrastructure" => "Infrastructure as Code Packs",
         "system" => "System Packs",
+        "antitamper" => "Anti-Tamper Packs",
+        "remote_exec" => "Remote Code Execution Packs",
+        "blockdevice" => "Block Device Packs",
+        "network" => "Network Packs",
+        "virtualization" => "Virtualization Packs",
         "safe" => "Safe Packs",
         "strict_git" => "Strict Git Packs",
         "package_managers" => "Package Manager Packs",
@@ -262,8 +262,8 @@ fn golden_rm_variant_parity() {
         ("rm -rf /tmp/../etc", "core.filesystem:rm-rf-root-home"),
         ("rm -rf ~/stuff", "core.filesystem:rm-rf-root-home"),
         ("rm -rf ./build", "core.filesystem:rm-rf-general"),
-        ("rm -r -f ./build", "core.filesystem:rm-r-f-separate"),
-        ("rm -f -r ./build", "core.filesystem:rm-r-f-separate"),
+        ("rm -r -f ./build", "core.filesystem:rm-rf-general"),
+        ("rm -f -r ./build", "core.filesystem:rm-rf-general"),
         (
             r#"rm -r -f "$TMPDIR/foo""#,
             "core.filesystem:rm-r-f-separate",
@@ -274,7 +274,7 @@ fn golden_rm_variant_parity() {
         ),
         (
             "rm --recursive --force ./build",
-            "core.filesystem:rm-recursive-force-long",
+            "core.filesystem:rm-rf-general",
         ),
         (
             r#"rm --recursive --force "$TMPDIR/foo""#,
@@ -286,7 +286,7 @@ fn golden_rm_variant_parity() {
         ),
         (
             "rm --force --recursive ./build",
-            "core.filesystem:rm-recursive-force-long",
+            "core.filesystem:rm-rf-general",
         ),
         (
             r#"rm --force --recursive "$TMPDIR/foo""#,
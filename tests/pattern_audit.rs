@@ -39,6 +39,23 @@ fn test_audit_backtracking_requirements() {
                 "rm-force-recursive-tmpdir",
                 "rm-recursive-force-tmpdir-brace",
                 "rm-force-recursive-tmpdir-brace",
+                "find-delete-tmp",
+            ]),
+        ),
+        (
+            "blockdevice",
+            HashSet::from([
+                "dd-disk",
+                "mkfs-disk",
+                "mkfs-image",
+                "wipefs-disk",
+                "wipefs-loop",
+                "wipefs-image",
+                "blkdiscard-disk",
+                "parted-destroy-disk",
+                "fdisk-scripted-disk",
+                "fdisk-scripted-loop",
+                "shred-disk",
             ]),
         ),
         (
@@ -98,6 +115,9 @@ fn test_audit_backtracking_requirements() {
             "package_managers",
             HashSet::from([
                 "apt-get-list",
+                "apt-autoremove-purge",
+                "brew-uninstall-core",
+                "npm-uninstall-global-tooling",
                 "npm-publish",
                 "yarn-publish",
                 "pnpm-publish",
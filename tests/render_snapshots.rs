@@ -0,0 +1,40 @@
+//! Golden-file snapshot tests for the terminal renderers.
+//!
+//! Every denial box, `dcg test` result box, and table fixture is rendered under
+//! every theme and border style (see `output::fixtures`) and compared with
+//! `tests/golden/render/<theme>-<border>.txt`, so changes to box layout,
+//! wrapping, or ANSI handling show up as a diff.
+//!
+//! # Updating Golden Files
+//!
+//! After an intentional rendering change:
+//! ```bash
+//! UPDATE_GOLDEN=1 cargo test --test render_snapshots
+//! # or
+//! dcg render-fixtures
+//! ```
+//!
+//! The goldens are for the default renderers; `rich-output` builds render
+//! through `rich_rust` and skip this test.
+
+#![cfg(not(feature = "rich-output"))]
+
+use destructive_command_guard::output::fixtures::{check_snapshot, render_fixtures};
+use std::path::Path;
+
+#[test]
+fn renderers_match_golden_files() {
+    let update = std::env::var("UPDATE_GOLDEN").is_ok();
+    let dir = Path::new("tests/golden/render");
+
+    let failures: Vec<String> = render_fixtures()
+        .iter()
+        .filter_map(|fixture| check_snapshot(dir, fixture, update).err())
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "render snapshots out of date (rerun with UPDATE_GOLDEN=1 if intended):\n\n{}",
+        failures.join("\n\n")
+    );
+}
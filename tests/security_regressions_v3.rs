@@ -26,8 +26,9 @@ mod tests {
         // This uses backslash escaping for the delimiter, which is valid in bash
         // and behaves like a quoted delimiter (no expansion).
         // If the heredoc extractor regex doesn't support this, it will be missed.
+        // The body goes to a shell, so it is code rather than data.
         let cmd = r"
-cat <<\EOF
+bash <<\EOF
 rm -rf /
 EOF
 ";
@@ -8,10 +8,15 @@
 //!
 //! Part of git_safety_guard-x1l7: [E3-T9] Comprehensive testing for Command Rewriting Suggestions
 
+use chrono::Utc;
+use destructive_command_guard::api::Guard;
+use destructive_command_guard::config::Config;
 use destructive_command_guard::suggest::{
     AllowlistSuggestion, CommandEntryInfo, ConfidenceTier, RiskLevel, SuggestionReason,
     filter_by_confidence, filter_by_risk, generate_enhanced_suggestions,
+    generate_enhanced_suggestions_with_history, risk_guard,
 };
+use destructive_command_guard::suggest_history::{ClusterHistory, SuggestionStatus};
 
 // ============================================================================
 // Enhanced Suggestions Generation Tests
@@ -160,10 +165,10 @@ fn generate_enhanced_suggestions_scattered_paths() {
 fn generate_enhanced_suggestions_multiple_commands_sorted_by_score() {
     let mut entries = Vec::new();
 
-    // High-frequency, low-risk command with bypasses
+    // High-frequency, medium-risk command with bypasses
     for _ in 0..20 {
         entries.push(CommandEntryInfo {
-            command: "npm run build".to_string(),
+            command: "git branch -D feature".to_string(),
             working_dir: "/data/projects/app".to_string(),
             was_bypassed: true,
         });
@@ -185,7 +190,7 @@ fn generate_enhanced_suggestions_multiple_commands_sorted_by_score() {
         "Should generate suggestions for both commands"
     );
     // Suggestions should be sorted by score (higher first)
-    // High-confidence, low-risk "npm run build" should rank higher
+    // High-confidence, medium-risk "git branch -D" should rank higher
     // than medium-confidence, high-risk "rm -rf"
     for i in 1..suggestions.len() {
         assert!(
@@ -219,13 +224,29 @@ fn create_suggestion_with_confidence(confidence: ConfidenceTier) -> AllowlistSug
     suggestion
 }
 
+fn default_risk_guard() -> Guard {
+    risk_guard(&Config::default())
+}
+
+/// Risk guard for a policy that only logs `core.git` matches.
+fn logging_git_risk_guard() -> Guard {
+    let config: Config = toml::from_str(
+        r#"
+[policy.packs]
+"core.git" = "log"
+"#,
+    )
+    .unwrap();
+    risk_guard(&config)
+}
+
 fn create_suggestion_with_risk(risk: RiskLevel) -> AllowlistSuggestion {
     use destructive_command_guard::suggest::CommandCluster;
 
-    let cmd = match risk {
-        RiskLevel::High => "rm -rf /tmp",
-        RiskLevel::Medium => "git checkout -- file",
-        RiskLevel::Low => "npm run build",
+    let (cmd, guard) = match risk {
+        RiskLevel::High => ("rm -rf ./build", default_risk_guard()),
+        RiskLevel::Medium => ("git branch -D feature", default_risk_guard()),
+        RiskLevel::Low => ("git branch -D feature", logging_git_risk_guard()),
     };
 
     let cluster = CommandCluster {
@@ -236,7 +257,7 @@ fn create_suggestion_with_risk(risk: RiskLevel) -> AllowlistSuggestion {
         unique_count: 1,
     };
 
-    AllowlistSuggestion::from_cluster(cluster)
+    AllowlistSuggestion::from_cluster_with(cluster, &guard)
 }
 
 #[test]
@@ -443,6 +464,117 @@ fn generate_enhanced_suggestions_preserves_command_order_in_cluster() {
     );
 }
 
+// ============================================================================
+// Cross-Run Suggestion History Tests
+// ============================================================================
+
+#[test]
+fn suggestion_ids_are_stable_without_history() {
+    let entries = create_test_entries(
+        &[
+            ("npm run build", "/data/projects/app", false),
+            ("npm run test", "/data/projects/app", false),
+        ],
+        5,
+    );
+
+    let first = generate_enhanced_suggestions(&entries, 3);
+    let mut reversed = entries.clone();
+    reversed.reverse();
+    let second = generate_enhanced_suggestions(&reversed, 3);
+
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].suggestion_id.len(), 16);
+    assert_eq!(first[0].suggestion_id, second[0].suggestion_id);
+}
+
+#[test]
+fn later_runs_merge_into_remembered_clusters() {
+    let mut history = ClusterHistory::default();
+    let first_run = create_test_entries(
+        &[
+            ("npm run build", "/data/projects/app", false),
+            ("npm run test", "/data/projects/app", false),
+        ],
+        5,
+    );
+    let first = generate_enhanced_suggestions_with_history(
+        &first_run,
+        3,
+        &default_risk_guard(),
+        &mut history,
+        Utc::now(),
+    );
+    assert_eq!(first.len(), 1);
+    assert_eq!(history.clusters.len(), 1);
+
+    // A new variant on its own would cluster separately; history folds it in.
+    let second_run = create_test_entries(&[("npm run lint", "/data/projects/app", false)], 5);
+    let second = generate_enhanced_suggestions_with_history(
+        &second_run,
+        3,
+        &default_risk_guard(),
+        &mut history,
+        Utc::now(),
+    );
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].suggestion_id, first[0].suggestion_id);
+    assert_eq!(history.clusters.len(), 1);
+    assert_eq!(
+        history.clusters[0].proposed_pattern,
+        second[0].cluster.proposed_pattern
+    );
+}
+
+#[test]
+fn decided_suggestions_are_not_proposed_again() {
+    let mut history = ClusterHistory::default();
+    let entries = create_test_entries(
+        &[
+            ("npm run build", "/data/projects/app", false),
+            ("cargo build --release", "/data/projects/app", false),
+        ],
+        5,
+    );
+    let first = generate_enhanced_suggestions_with_history(
+        &entries,
+        3,
+        &default_risk_guard(),
+        &mut history,
+        Utc::now(),
+    );
+    assert_eq!(first.len(), 2);
+
+    let npm = first
+        .iter()
+        .find(|s| s.cluster.commands[0].starts_with("npm"))
+        .unwrap();
+    let dismissed_pattern = npm.cluster.proposed_pattern.clone();
+    assert!(history.set_status(&npm.suggestion_id, SuggestionStatus::Dismissed));
+
+    let again = create_test_entries(
+        &[
+            ("npm run build", "/data/projects/app", false),
+            ("npm run build --watch", "/data/projects/app", false),
+            ("cargo build --release", "/data/projects/app", false),
+        ],
+        5,
+    );
+    let second = generate_enhanced_suggestions_with_history(
+        &again,
+        3,
+        &default_risk_guard(),
+        &mut history,
+        Utc::now(),
+    );
+    assert_eq!(second.len(), 1);
+    assert!(second[0].cluster.commands[0].starts_with("cargo"));
+
+    let dismissed = history.get(&npm.suggestion_id).unwrap();
+    assert_eq!(dismissed.status, SuggestionStatus::Dismissed);
+    assert_eq!(dismissed.proposed_pattern, dismissed_pattern);
+}
+
 // ============================================================================
 // Real-World Scenario Tests
 // ============================================================================
@@ -479,7 +611,8 @@ fn scenario_ci_build_commands() {
             .iter()
             .all(|s| s.confidence == ConfidenceTier::High)
     );
-    assert!(suggestions.iter().all(|s| s.risk == RiskLevel::Low));
+    // No rule explains why they were blocked, so they are not assumed safe
+    assert!(suggestions.iter().all(|s| s.risk == RiskLevel::High));
 }
 
 #[test]
@@ -514,10 +647,10 @@ fn scenario_developer_workflow() {
 fn scenario_mixed_risk_commands() {
     let mut entries = Vec::new();
 
-    // Low risk: npm commands
+    // Low risk: git commands, which this policy only logs
     for _ in 0..10 {
         entries.push(CommandEntryInfo {
-            command: "npm run lint".to_string(),
+            command: "git stash drop".to_string(),
             working_dir: "/data/projects/app".to_string(),
             was_bypassed: false,
         });
@@ -526,20 +659,26 @@ fn scenario_mixed_risk_commands() {
     // High risk: rm commands
     for _ in 0..10 {
         entries.push(CommandEntryInfo {
-            command: "rm -rf /tmp/build".to_string(),
+            command: "rm -rf ./build".to_string(),
             working_dir: "/data/projects/app".to_string(),
             was_bypassed: false,
         });
     }
 
-    let all_suggestions = generate_enhanced_suggestions(&entries, 3);
+    let all_suggestions = generate_enhanced_suggestions_with_history(
+        &entries,
+        3,
+        &logging_git_risk_guard(),
+        &mut ClusterHistory::default(),
+        Utc::now(),
+    );
 
     // Filter to only low-risk suggestions
     let safe_suggestions = filter_by_risk(all_suggestions.clone(), RiskLevel::Low);
 
-    // Should only include the npm command
+    // Should only include the git command
     assert_eq!(safe_suggestions.len(), 1);
-    assert!(safe_suggestions[0].cluster.commands[0].contains("npm"));
+    assert!(safe_suggestions[0].cluster.commands[0].contains("git"));
 
     // Filter to high confidence only
     let confident_suggestions = filter_by_confidence(all_suggestions, ConfidenceTier::High);
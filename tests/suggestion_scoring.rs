@@ -10,10 +10,11 @@
 //!
 //! Part of git_safety_guard-x1l7: [E3-T9] Comprehensive testing for Command Rewriting Suggestions
 
+use destructive_command_guard::config::Config;
 use destructive_command_guard::suggest::{
     AllowlistSuggestion, CommandCluster, ConfidenceTier, PathPattern, RiskLevel, SuggestionReason,
-    analyze_path_patterns, assess_risk_level, calculate_confidence_tier,
-    calculate_suggestion_score, determine_primary_reason,
+    analyze_path_patterns, assess_risk_level, assess_risk_level_with, calculate_confidence_tier,
+    calculate_suggestion_score, determine_primary_reason, risk_guard,
 };
 
 // ============================================================================
@@ -234,10 +235,60 @@ fn confidence_tier_boundary_conditions() {
 
 #[test]
 fn risk_level_high_for_rm_rf() {
-    let commands = vec!["rm -rf /tmp/test".to_string()];
+    let commands = vec!["rm -rf ./build".to_string()];
     assert_eq!(assess_risk_level(&commands), RiskLevel::High);
 }
 
+#[test]
+fn risk_level_high_when_no_rule_explains_the_denial() {
+    // These were denied, but no rule matches them now (core.filesystem treats
+    // /tmp cleanup as safe), so something unseen blocked them
+    for command in [
+        "rm -rf /tmp/test",
+        "git reset HEAD~1",
+        "sudo npm install",
+        "git status",
+        "npm run build",
+    ] {
+        assert_eq!(
+            assess_risk_level(&[command.to_string()]),
+            RiskLevel::High,
+            "{command}"
+        );
+    }
+}
+
+#[test]
+fn risk_level_low_for_rules_the_policy_only_logs() {
+    let config: Config = toml::from_str(
+        r#"
+[policy.rules]
+"core.git:reset-hard" = "log"
+"#,
+    )
+    .unwrap();
+    let guard = risk_guard(&config);
+    let commands = vec!["git reset --hard HEAD".to_string()];
+    assert_eq!(assess_risk_level_with(&guard, &commands), RiskLevel::Low);
+}
+
+#[test]
+fn risk_level_ignores_allow_overrides_and_disabled_packs() {
+    let config: Config = toml::from_str(
+        r#"
+[packs]
+disabled = ["core.git"]
+
+[overrides]
+allow = ["git reset --hard"]
+"#,
+    )
+    .unwrap();
+    let guard = risk_guard(&config);
+    let commands = vec!["git reset --hard HEAD".to_string()];
+    assert_eq!(assess_risk_level_with(&guard, &commands), RiskLevel::High);
+}
+
 #[test]
 fn risk_level_high_for_force_flag() {
     let commands = vec!["git push --force origin main".to_string()];
@@ -263,61 +314,38 @@ fn risk_level_high_for_drop() {
 }
 
 #[test]
-fn risk_level_high_for_truncate() {
-    let commands = vec!["truncate -s 0 /var/log/syslog".to_string()];
+fn risk_level_uses_packs_outside_the_default_set() {
+    // Risk is assessed against every built-in pack, not just enabled ones
+    let commands = vec!["terraform destroy".to_string()];
     assert_eq!(assess_risk_level(&commands), RiskLevel::High);
-}
-
-#[test]
-fn risk_level_medium_for_rm_without_rf() {
-    let commands = vec!["rm test.txt".to_string()];
-    assert_eq!(assess_risk_level(&commands), RiskLevel::Medium);
-}
 
-#[test]
-fn risk_level_medium_for_git_reset() {
-    let commands = vec!["git reset HEAD~1".to_string()];
-    assert_eq!(assess_risk_level(&commands), RiskLevel::Medium);
+    let commands = vec!["kubectl delete namespace prod".to_string()];
+    assert_eq!(assess_risk_level(&commands), RiskLevel::High);
 }
 
 #[test]
-fn risk_level_medium_for_sudo() {
-    let commands = vec!["sudo npm install".to_string()];
+fn risk_level_medium_for_warn_severity_rules() {
+    // Medium-severity rules warn by default
+    let commands = vec!["git branch -D feature".to_string()];
     assert_eq!(assess_risk_level(&commands), RiskLevel::Medium);
-}
 
-#[test]
-fn risk_level_medium_for_docker_rm() {
-    let commands = vec!["docker rm container_name".to_string()];
+    let commands = vec!["git stash drop".to_string()];
     assert_eq!(assess_risk_level(&commands), RiskLevel::Medium);
 }
 
 #[test]
-fn risk_level_high_for_kubectl_delete() {
-    // kubectl delete contains "delete " which is a high-risk pattern
-    let commands = vec!["kubectl delete pod my-pod".to_string()];
+fn risk_level_sees_through_sudo() {
+    let commands = vec!["sudo git reset --hard".to_string()];
     assert_eq!(assess_risk_level(&commands), RiskLevel::High);
 }
 
-#[test]
-fn risk_level_low_for_safe_commands() {
-    let commands = vec!["git status".to_string()];
-    assert_eq!(assess_risk_level(&commands), RiskLevel::Low);
-}
-
-#[test]
-fn risk_level_low_for_npm_run() {
-    let commands = vec!["npm run build".to_string()];
-    assert_eq!(assess_risk_level(&commands), RiskLevel::Low);
-}
-
 #[test]
 fn risk_level_high_takes_precedence_in_cluster() {
     // If any command in cluster is high risk, whole cluster is high risk
     let commands = vec![
         "npm run build".to_string(),
         "git status".to_string(),
-        "rm -rf /tmp/cache".to_string(), // High risk
+        "rm -rf ./cache".to_string(), // High risk
     ];
     assert_eq!(assess_risk_level(&commands), RiskLevel::High);
 }
@@ -329,9 +357,9 @@ fn risk_level_empty_commands() {
 }
 
 #[test]
-fn risk_level_case_insensitive() {
-    // Risk detection should be case insensitive
-    let commands = vec!["RM -RF /tmp".to_string()];
+fn risk_level_for_redacted_commands() {
+    // Placeholders from privacy.log_mode = "redacted" are evaluated with sample values
+    let commands = vec!["rm -rf <path>".to_string()];
     assert_eq!(assess_risk_level(&commands), RiskLevel::High);
 }
 
@@ -565,8 +593,8 @@ fn allowlist_suggestion_from_cluster_low_frequency() {
 
     // Low frequency = Low confidence
     assert_eq!(suggestion.confidence, ConfidenceTier::Low);
-    // npm run build is low risk
-    assert_eq!(suggestion.risk, RiskLevel::Low);
+    // No rule explains why npm run build was denied
+    assert_eq!(suggestion.risk, RiskLevel::High);
 }
 
 #[test]
@@ -727,6 +755,6 @@ fn full_suggestion_flow_low_frequency_scattered() {
     assert_eq!(suggestion.confidence, ConfidenceTier::Low);
     // Scattered paths = no path-specific suggestion
     assert!(!suggestion.suggest_path_specific);
-    // Low risk command
-    assert_eq!(suggestion.risk, RiskLevel::Low);
+    // No rule explains the denial, so it is not assumed safe
+    assert_eq!(suggestion.risk, RiskLevel::High);
 }
@@ -124,8 +124,10 @@ mod duration_parsing {
 // ============================================================================
 
 mod timestamp_parsing {
-    use destructive_command_guard::allowlist::{AllowEntry, AllowSelector, RuleId, is_expired};
-    use std::collections::HashMap;
+    use destructive_command_guard::allowlist::{
+        AllowEntry, AllowSelector, PathMatch, RuleId, is_expired,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     fn make_test_entry() -> AllowEntry {
         AllowEntry {
@@ -139,7 +141,13 @@ mod timestamp_parsing {
             context: None,
             conditions: HashMap::new(),
             environments: Vec::new(),
+            active_hours: None,
+            active_dates: Vec::new(),
+            hosts: Vec::new(),
+            users: Vec::new(),
+            arg_conditions: BTreeMap::new(),
             paths: None,
+            path_match: PathMatch::Cwd,
             risk_acknowledged: false,
         }
     }
@@ -242,8 +250,10 @@ mod timestamp_parsing {
 
 mod ttl_expiration {
     use chrono::{Duration, Utc};
-    use destructive_command_guard::allowlist::{AllowEntry, AllowSelector, RuleId, is_expired};
-    use std::collections::HashMap;
+    use destructive_command_guard::allowlist::{
+        AllowEntry, AllowSelector, PathMatch, RuleId, is_expired,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     fn make_test_entry() -> AllowEntry {
         AllowEntry {
@@ -257,7 +267,13 @@ mod ttl_expiration {
             context: None,
             conditions: HashMap::new(),
             environments: Vec::new(),
+            active_hours: None,
+            active_dates: Vec::new(),
+            hosts: Vec::new(),
+            users: Vec::new(),
+            arg_conditions: BTreeMap::new(),
             paths: None,
+            path_match: PathMatch::Cwd,
             risk_acknowledged: false,
         }
     }
@@ -334,8 +350,10 @@ mod ttl_expiration {
 // ============================================================================
 
 mod session_entries {
-    use destructive_command_guard::allowlist::{AllowEntry, AllowSelector, RuleId, is_expired};
-    use std::collections::HashMap;
+    use destructive_command_guard::allowlist::{
+        AllowEntry, AllowSelector, PathMatch, RuleId, is_expired,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     fn make_test_entry() -> AllowEntry {
         AllowEntry {
@@ -349,7 +367,13 @@ mod session_entries {
             context: None,
             conditions: HashMap::new(),
             environments: Vec::new(),
+            active_hours: None,
+            active_dates: Vec::new(),
+            hosts: Vec::new(),
+            users: Vec::new(),
+            arg_conditions: BTreeMap::new(),
             paths: None,
+            path_match: PathMatch::Cwd,
             risk_acknowledged: false,
         }
     }
@@ -599,8 +623,10 @@ fn e2e_validate_checks_expired_entries() {
 
 #[test]
 fn integration_entry_validity_check() {
-    use destructive_command_guard::allowlist::{AllowEntry, AllowSelector, RuleId, is_entry_valid};
-    use std::collections::HashMap;
+    use destructive_command_guard::allowlist::{
+        AllowEntry, AllowSelector, PathMatch, RuleId, is_entry_valid,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     // Valid entry (no expiration)
     let valid = AllowEntry {
@@ -614,11 +640,17 @@ fn integration_entry_validity_check() {
         context: None,
         conditions: HashMap::new(),
         environments: Vec::new(),
+        active_hours: None,
+        active_dates: Vec::new(),
+        hosts: Vec::new(),
+        users: Vec::new(),
+        arg_conditions: BTreeMap::new(),
         paths: None,
+        path_match: PathMatch::Cwd,
         risk_acknowledged: false,
     };
     assert!(
-        is_entry_valid(&valid),
+        is_entry_valid(&valid, "local"),
         "Entry without expiration should be valid"
     );
 
@@ -634,11 +666,17 @@ fn integration_entry_validity_check() {
         context: None,
         conditions: HashMap::new(),
         environments: Vec::new(),
+        active_hours: None,
+        active_dates: Vec::new(),
+        hosts: Vec::new(),
+        users: Vec::new(),
+        arg_conditions: BTreeMap::new(),
         paths: None,
+        path_match: PathMatch::Cwd,
         risk_acknowledged: false,
     };
     assert!(
-        !is_entry_valid(&expired),
+        !is_entry_valid(&expired, "local"),
         "Expired entry should not be valid"
     );
 }
@@ -649,8 +687,10 @@ fn integration_entry_validity_check() {
 
 #[test]
 fn regression_permanent_entries_never_expire() {
-    use destructive_command_guard::allowlist::{AllowEntry, AllowSelector, RuleId, is_expired};
-    use std::collections::HashMap;
+    use destructive_command_guard::allowlist::{
+        AllowEntry, AllowSelector, PathMatch, RuleId, is_expired,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     let permanent = AllowEntry {
         selector: AllowSelector::Rule(RuleId::parse("core.git:*").expect("valid rule id")),
@@ -663,7 +703,13 @@ fn regression_permanent_entries_never_expire() {
         context: None,
         conditions: HashMap::new(),
         environments: Vec::new(),
+        active_hours: None,
+        active_dates: Vec::new(),
+        hosts: Vec::new(),
+        users: Vec::new(),
+        arg_conditions: BTreeMap::new(),
         paths: None,
+        path_match: PathMatch::Cwd,
         risk_acknowledged: false,
     };
 
@@ -675,8 +721,10 @@ fn regression_permanent_entries_never_expire() {
 
 #[test]
 fn regression_far_future_dates_not_expired() {
-    use destructive_command_guard::allowlist::{AllowEntry, AllowSelector, RuleId, is_expired};
-    use std::collections::HashMap;
+    use destructive_command_guard::allowlist::{
+        AllowEntry, AllowSelector, PathMatch, RuleId, is_expired,
+    };
+    use std::collections::{BTreeMap, HashMap};
 
     let far_future = AllowEntry {
         selector: AllowSelector::Rule(RuleId::parse("core.git:*").expect("valid rule id")),
@@ -689,7 +737,13 @@ fn regression_far_future_dates_not_expired() {
         context: None,
         conditions: HashMap::new(),
         environments: Vec::new(),
+        active_hours: None,
+        active_dates: Vec::new(),
+        hosts: Vec::new(),
+        users: Vec::new(),
+        arg_conditions: BTreeMap::new(),
         paths: None,
+        path_match: PathMatch::Cwd,
         risk_acknowledged: false,
     };
 